@@ -19,39 +19,101 @@ pub enum TaskType {
     Custom(String),
 }
 
+/// A task type with its English and Korean keywords.
+type LexiconEntry = (fn() -> TaskType, &'static [&'static str], &'static [&'static str]);
+
+/// Keywords per task type, English then Korean. Korean entries are stems
+/// without particles or endings ("요약" matches 요약해줘, 요약하기, 요약본).
+const LEXICON: &[LexiconEntry] = &[
+    (
+        || TaskType::Rag,
+        &["rag", "retrieval", "qa", "question answering"],
+        &["검색", "질문", "질의응답", "질의 응답", "문서 기반", "찾아서 답"],
+    ),
+    (
+        || TaskType::Summarize,
+        &["summarize", "summary", "tl;dr"],
+        &["요약", "축약", "핵심만", "간추려", "줄여서"],
+    ),
+    (
+        || TaskType::Review,
+        &["review", "audit", "evaluate"],
+        &["리뷰", "평가", "감사", "검토", "점검"],
+    ),
+    (
+        || TaskType::DataAnalysis,
+        &["analysis", "analyze", "statistics"],
+        &["분석", "통계", "집계", "추이", "추세"],
+    ),
+    (
+        || TaskType::ReportGeneration,
+        &["report"],
+        &["보고서", "리포트", "레포트"],
+    ),
+    (
+        || TaskType::Translation,
+        &["translate", "translation"],
+        &["번역", "영문으로", "한글로", "영어로", "한국어로"],
+    ),
+    (
+        || TaskType::CodeReview,
+        &["code review", "pull request"],
+        &["코드 리뷰", "코드리뷰", "코드 검토", "코드검토"],
+    ),
+    (
+        || TaskType::QaExtraction,
+        &["faq", "qa extraction", "question generation"],
+        &["질문 추출", "문답 생성", "예상 질문", "질문 생성"],
+    ),
+    (
+        || TaskType::SentimentAnalysis,
+        &["sentiment"],
+        &["감성", "감정 분석", "감정분석", "긍정", "부정", "여론"],
+    ),
+    (
+        || TaskType::KnowledgeBaseBuild,
+        &["knowledge base", "kb"],
+        &["지식 베이스", "지식베이스", "지식 저장소", "색인", "인덱싱"],
+    ),
+];
+
 /// Classify a user prompt into a task type using rule-based heuristics.
+///
+/// Every task type is scored by the characters of its keywords found in the
+/// prompt, so a specific phrase ("코드 리뷰", "감정 분석") beats a generic
+/// word it contains ("리뷰", "분석"). Ties go to the earlier task type.
 pub fn classify(prompt: &str) -> Result<TaskType, CompilerError> {
     let lower = prompt.to_lowercase();
 
-    let task_type = if contains_any(&lower, &["rag", "검색", "retrieval", "qa", "질문"]) {
-        TaskType::Rag
-    } else if contains_any(&lower, &["요약", "summarize", "summary", "축약"]) {
-        TaskType::Summarize
-    } else if contains_any(&lower, &["리뷰", "review", "평가", "감사", "audit"]) {
-        TaskType::Review
-    } else if contains_any(&lower, &["분석", "analysis", "통계", "statistics"]) {
-        TaskType::DataAnalysis
-    } else if contains_any(&lower, &["보고서", "report", "리포트"]) {
-        TaskType::ReportGeneration
-    } else if contains_any(&lower, &["번역", "translate", "translation"]) {
-        TaskType::Translation
-    } else if contains_any(&lower, &["코드 리뷰", "code review", "코드리뷰"]) {
-        TaskType::CodeReview
-    } else if contains_any(&lower, &["faq", "질문 추출", "qa extraction"]) {
-        TaskType::QaExtraction
-    } else if contains_any(&lower, &["감성", "sentiment", "감정 분석"]) {
-        TaskType::SentimentAnalysis
-    } else if contains_any(&lower, &["knowledge base", "kb", "지식 베이스"]) {
-        TaskType::KnowledgeBaseBuild
-    } else {
-        TaskType::Custom("unknown".into())
-    };
+    let mut best: Option<(usize, TaskType)> = None;
+    for (task_type, english, korean) in LEXICON {
+        let score: usize = english
+            .iter()
+            .filter(|kw| contains_word(&lower, kw))
+            .chain(korean.iter().filter(|kw| lower.contains(*kw)))
+            .map(|kw| kw.chars().count())
+            .sum();
+        if score > 0 && best.as_ref().is_none_or(|(top, _)| score > *top) {
+            best = Some((score, task_type()));
+        }
+    }
 
-    Ok(task_type)
+    Ok(best.map_or_else(|| TaskType::Custom("unknown".into()), |(_, t)| t))
 }
 
-fn contains_any(text: &str, keywords: &[&str]) -> bool {
-    keywords.iter().any(|kw| text.contains(kw))
+/// Whether `text` contains `word` starting a word, so "review" doesn't match
+/// "preview". Short keywords are acronyms and must end one too, so "kb"
+/// doesn't match "kbps"; longer ones may be inflected ("summarized").
+/// Hangul around a keyword ("RAG를") counts as a boundary.
+fn contains_word(text: &str, word: &str) -> bool {
+    let acronym = word.chars().count() < 4;
+    text.match_indices(word).any(|(at, _)| {
+        let before = text[..at].chars().next_back();
+        let after = text[at + word.len()..].chars().next();
+        let starts = !before.is_some_and(|c| c.is_ascii_alphanumeric());
+        let ends = !after.is_some_and(|c| c.is_ascii_alphanumeric());
+        starts && (ends || !acronym)
+    })
 }
 
 #[cfg(test)]
@@ -71,6 +133,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn specific_korean_phrases_win() {
+        assert_eq!(classify("이 PR 코드 리뷰 해줘").unwrap(), TaskType::CodeReview);
+        assert_eq!(
+            classify("고객 댓글 감정 분석 부탁해요").unwrap(),
+            TaskType::SentimentAnalysis
+        );
+        assert_eq!(
+            classify("매뉴얼에서 예상 질문 추출하기").unwrap(),
+            TaskType::QaExtraction
+        );
+        assert_eq!(classify("계약서를 영어로 옮겨줘").unwrap(), TaskType::Translation);
+        assert_eq!(classify("Translate the memo").unwrap(), TaskType::Translation);
+        assert_eq!(classify("Get it summarized").unwrap(), TaskType::Summarize);
+        assert!(matches!(
+            classify("Measure kbps on the link").unwrap(),
+            TaskType::Custom(_)
+        ));
+    }
+
     #[test]
     fn classify_unknown() {
         assert!(matches!(
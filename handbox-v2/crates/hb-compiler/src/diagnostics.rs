@@ -0,0 +1,758 @@
+//! Live graph diagnostics — per-node validation that can be re-run for just
+//! the part of a workflow touched by an edit.
+//!
+//! Diagnostics are owned by a node: node-level findings by the node itself,
+//! edge findings by the edge's target node. Re-validating a set of changed
+//! nodes therefore only needs those nodes and their direct neighbours.
+//! Cycles and types inferred through `any` ports (see [`crate::inference`])
+//! depend on the whole graph, so they are recomputed on every pass and the
+//! nodes whose cycle errors or inferred warnings changed are reported as
+//! revalidated too.
+
+use crate::inference::{infer_types, transform_type};
+use crate::type_checker::types_compatible;
+use hb_core::graph::{EdgeKind, EdgeSpec, NodeEntry, PortSpec, WorkflowSpec};
+use hb_core::tool::{ConfigField, ConfigFieldType};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+pub use hb_core::graph::Severity;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Stable machine-readable code, e.g. "type_mismatch".
+    pub code: &'static str,
+    pub message: String,
+    /// Owning node.
+    pub node_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edge_id: Option<String>,
+}
+
+impl Diagnostic {
+    pub(crate) fn new(severity: Severity, code: &'static str, node_id: &str, message: String) -> Self {
+        Self {
+            severity,
+            code,
+            message,
+            node_id: node_id.to_string(),
+            edge_id: None,
+        }
+    }
+
+    pub(crate) fn on_edge(mut self, edge: &EdgeSpec) -> Self {
+        self.edge_id = Some(edge.id.clone());
+        self
+    }
+}
+
+/// Declared ports of a node, and the config fields of its tool.
+#[derive(Debug, Clone, Default)]
+pub struct NodePorts {
+    pub inputs: Vec<PortSpec>,
+    pub outputs: Vec<PortSpec>,
+    pub config: Vec<ConfigField>,
+}
+
+/// Resolves a primitive node's `tool_ref` to its ports; `None` means unknown tool.
+pub type PortResolver<'a> = dyn Fn(&str) -> Option<NodePorts> + 'a;
+
+/// Result of (re-)validating part of a workflow.
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationUpdate {
+    /// Nodes whose diagnostics were recomputed; their previous diagnostics
+    /// are replaced by the ones in `diagnostics`.
+    pub revalidated: Vec<String>,
+    pub diagnostics: Vec<Diagnostic>,
+    /// Whole-workflow totals after this update.
+    pub error_count: usize,
+    pub warning_count: usize,
+}
+
+/// Ports of `node`, or `None` when the node accepts any port (conditionals,
+/// loops) or its tool is unknown.
+pub(crate) fn node_ports(node: &NodeEntry, resolve: &PortResolver) -> Option<NodePorts> {
+    match node {
+        NodeEntry::Primitive(n) => resolve(&n.tool_ref),
+        NodeEntry::Composite(n) => Some(NodePorts {
+            inputs: n.input_ports.clone(),
+            outputs: n.output_ports.clone(),
+            config: Vec::new(),
+        }),
+        NodeEntry::WorkflowRef(n) => Some(NodePorts {
+            inputs: n.input_ports.clone(),
+            outputs: n.output_ports.clone(),
+            config: Vec::new(),
+        }),
+        NodeEntry::Conditional(_) | NodeEntry::Loop(_) | NodeEntry::MapReduce(_) => None,
+    }
+}
+
+/// Graph view shared by the per-node checks of one validation pass.
+struct Graph<'s> {
+    nodes: HashMap<&'s str, &'s NodeEntry>,
+    id_counts: HashMap<&'s str, usize>,
+    incoming: HashMap<&'s str, Vec<&'s EdgeSpec>>,
+    outgoing: HashMap<&'s str, Vec<&'s EdgeSpec>>,
+    groups: HashSet<&'s str>,
+}
+
+impl<'s> Graph<'s> {
+    fn new(spec: &'s WorkflowSpec) -> Self {
+        let mut id_counts: HashMap<&str, usize> = HashMap::new();
+        for n in &spec.nodes {
+            *id_counts.entry(n.id()).or_default() += 1;
+        }
+        let mut incoming: HashMap<&str, Vec<&EdgeSpec>> = HashMap::new();
+        let mut outgoing: HashMap<&str, Vec<&EdgeSpec>> = HashMap::new();
+        for e in &spec.edges {
+            incoming.entry(e.target_node.as_str()).or_default().push(e);
+            outgoing.entry(e.source_node.as_str()).or_default().push(e);
+        }
+        Self {
+            nodes: spec.nodes.iter().map(|n| (n.id(), n)).collect(),
+            id_counts,
+            incoming,
+            outgoing,
+            groups: spec.groups.iter().map(|g| g.id.as_str()).collect(),
+        }
+    }
+
+    fn reachable_from(&self, from: &'s str) -> HashSet<&'s str> {
+        let mut stack = vec![from];
+        let mut seen = HashSet::new();
+        while let Some(id) = stack.pop() {
+            if !seen.insert(id) {
+                continue;
+            }
+            for e in self.outgoing.get(id).into_iter().flatten() {
+                stack.push(e.target_node.as_str());
+            }
+        }
+        seen
+    }
+
+    /// Cycle errors of the whole workflow by owning node: every edge between
+    /// existing nodes whose target reaches its source.
+    fn check_cycles(&self) -> HashMap<String, Vec<Diagnostic>> {
+        let mut out = HashMap::new();
+        for (&owner, edges) in &self.incoming {
+            if !self.nodes.contains_key(owner) {
+                continue;
+            }
+            let reachable = self.reachable_from(owner);
+            let found: Vec<Diagnostic> = edges
+                .iter()
+                .filter(|e| {
+                    self.nodes.contains_key(e.source_node.as_str())
+                        && reachable.contains(e.source_node.as_str())
+                })
+                .map(|edge| {
+                    Diagnostic::new(
+                        Severity::Error,
+                        "cycle",
+                        owner,
+                        format!("Edge from '{}' creates a cycle", edge.source_node),
+                    )
+                    .on_edge(edge)
+                })
+                .collect();
+            if !found.is_empty() {
+                out.insert(owner.to_string(), found);
+            }
+        }
+        out
+    }
+
+    /// Diagnostics owned by `node_id`: the node itself and its incoming edges,
+    /// plus outgoing edges whose target does not exist.
+    fn check_node(&self, node_id: &str, resolve: &PortResolver) -> Vec<Diagnostic> {
+        let mut out = Vec::new();
+        let Some(node) = self.nodes.get(node_id) else {
+            return out;
+        };
+        if self.id_counts.get(node_id).copied().unwrap_or(0) > 1 {
+            out.push(Diagnostic::new(
+                Severity::Error,
+                "duplicate_node_id",
+                node_id,
+                format!("Node ID '{node_id}' is used more than once"),
+            ));
+        }
+        if let Some(group) = node.presentation().and_then(|p| p.group.as_deref()) {
+            if !self.groups.contains(group) {
+                out.push(Diagnostic::new(
+                    Severity::Warning,
+                    "unknown_group",
+                    node_id,
+                    format!("Group '{group}' does not exist"),
+                ));
+            }
+        }
+        for invalid in hb_core::expr::check_nodes(std::slice::from_ref(node), |_| None) {
+            out.push(Diagnostic::new(Severity::Error, "invalid_expression", node_id, invalid.to_string()));
+        }
+        let ports = node_ports(node, resolve);
+        if let NodeEntry::Primitive(n) = node {
+            if ports.is_none() {
+                out.push(Diagnostic::new(
+                    Severity::Error,
+                    "unknown_tool",
+                    node_id,
+                    format!("Unknown tool '{}'", n.tool_ref),
+                ));
+            }
+            let fields = ports.iter().flat_map(|p| &p.config);
+            for field in fields {
+                let Some(text) = n.config.get(&field.name).and_then(|v| v.as_str()) else {
+                    continue;
+                };
+                let (code, problem) = match field.field_type {
+                    ConfigFieldType::Regex => match regex::Regex::new(text) {
+                        Ok(_) => continue,
+                        Err(e) => ("invalid_regex", format!("a valid regular expression: {e}")),
+                    },
+                    ConfigFieldType::Expression if !text.trim().is_empty() => {
+                        match hb_core::expr::Expr::parse(text) {
+                            Ok(_) => continue,
+                            Err(e) => ("invalid_expression", format!("a valid expression: {e}")),
+                        }
+                    }
+                    _ => continue,
+                };
+                out.push(Diagnostic::new(
+                    Severity::Error,
+                    code,
+                    node_id,
+                    format!("'{}' is not {problem}", field.name),
+                ));
+            }
+        }
+
+        if let NodeEntry::Composite(n) = node {
+            if let Err(e) = n.resolved_subgraph() {
+                out.push(Diagnostic::new(Severity::Error, "invalid_config_mapping", node_id, e));
+            }
+            for key in n.config.keys() {
+                if !n.exposed_config.iter().any(|f| &f.name == key) {
+                    out.push(Diagnostic::new(
+                        Severity::Warning,
+                        "unknown_config_field",
+                        node_id,
+                        format!("'{key}' is not an exposed config field"),
+                    ));
+                }
+            }
+        }
+
+        let incoming = self.incoming.get(node_id).map(Vec::as_slice).unwrap_or_default();
+        let mut per_port: HashMap<&str, usize> = HashMap::new();
+        for edge in incoming {
+            *per_port.entry(edge.target_port.as_str()).or_default() += 1;
+            out.extend(self.check_edge(edge, node_id, ports.as_ref(), resolve));
+        }
+        for (port, count) in per_port {
+            if count > 1 {
+                out.push(Diagnostic::new(
+                    Severity::Error,
+                    "multiple_inputs",
+                    node_id,
+                    format!("Input '{port}' has {count} incoming edges"),
+                ));
+            }
+        }
+
+        if let Some(ports) = &ports {
+            let config = match node {
+                NodeEntry::Primitive(n) => Some(&n.config),
+                _ => None,
+            };
+            for port in ports.inputs.iter().filter(|p| p.required && p.default_value.is_none()) {
+                let connected = incoming.iter().any(|e| port.accepts(&e.target_port));
+                let configured = config.is_some_and(|c| c.contains_key(&port.name));
+                if !connected && !configured {
+                    out.push(Diagnostic::new(
+                        Severity::Warning,
+                        "missing_required_input",
+                        node_id,
+                        format!("Required input '{}' is not connected", port.name),
+                    ));
+                }
+            }
+        }
+
+        for edge in self.outgoing.get(node_id).into_iter().flatten() {
+            if !self.nodes.contains_key(edge.target_node.as_str()) {
+                out.push(
+                    Diagnostic::new(
+                        Severity::Error,
+                        "missing_node",
+                        node_id,
+                        format!("Edge targets missing node '{}'", edge.target_node),
+                    )
+                    .on_edge(edge),
+                );
+            }
+        }
+        out
+    }
+
+    fn check_edge(
+        &self,
+        edge: &EdgeSpec,
+        owner: &str,
+        target_ports: Option<&NodePorts>,
+        resolve: &PortResolver,
+    ) -> Vec<Diagnostic> {
+        let mut out = Vec::new();
+        let Some(source) = self.nodes.get(edge.source_node.as_str()) else {
+            out.push(
+                Diagnostic::new(
+                    Severity::Error,
+                    "missing_node",
+                    owner,
+                    format!("Edge comes from missing node '{}'", edge.source_node),
+                )
+                .on_edge(edge),
+            );
+            return out;
+        };
+
+        // Error edges carry the node's error, not one of its outputs
+        let source_ports = match edge.kind {
+            EdgeKind::Error => None,
+            _ => node_ports(source, resolve),
+        };
+        let source_port = source_ports.map(|p| {
+            p.outputs
+                .into_iter()
+                .find(|port| port.accepts(&edge.source_port))
+                .ok_or(())
+        });
+        let target_port = target_ports.map(|p| {
+            p.inputs
+                .iter()
+                .find(|port| port.accepts(&edge.target_port))
+                .ok_or(())
+        });
+        if let Some(Err(())) = source_port {
+            out.push(
+                Diagnostic::new(
+                    Severity::Error,
+                    "unknown_port",
+                    owner,
+                    format!("'{}' has no output '{}'", edge.source_node, edge.source_port),
+                )
+                .on_edge(edge),
+            );
+        }
+        if let Some(Err(())) = target_port {
+            out.push(
+                Diagnostic::new(
+                    Severity::Error,
+                    "unknown_port",
+                    owner,
+                    format!("'{owner}' has no input '{}'", edge.target_port),
+                )
+                .on_edge(edge),
+            );
+        }
+        if let (Some(Ok(s)), Some(Ok(t))) = (&source_port, &target_port) {
+            let carried = match &edge.transform {
+                Some(transform) => transform_type(transform, &s.port_type),
+                None => s.port_type.clone(),
+            };
+            if !types_compatible(&carried, &t.port_type) {
+                out.push(
+                    Diagnostic::new(
+                        Severity::Error,
+                        "type_mismatch",
+                        owner,
+                        format!(
+                            "{}.{} ({:?}) is not compatible with {owner}.{} ({:?})",
+                            edge.source_node, edge.source_port, carried, edge.target_port, t.port_type
+                        ),
+                    )
+                    .on_edge(edge),
+                );
+            }
+        }
+        out
+    }
+}
+
+/// Keeps the current diagnostics of one workflow and updates them as edits
+/// arrive.
+#[derive(Debug, Default)]
+pub struct IncrementalValidator {
+    by_node: HashMap<String, Vec<Diagnostic>>,
+    /// Cycle errors by owning node, from the last pass.
+    cycles: HashMap<String, Vec<Diagnostic>>,
+    /// Inferred type warnings by owning node, from the last pass.
+    inferred: HashMap<String, Vec<Diagnostic>>,
+}
+
+impl IncrementalValidator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Validate every node, replacing all stored diagnostics.
+    pub fn validate_all(&mut self, spec: &WorkflowSpec, resolve: &PortResolver) -> ValidationUpdate {
+        self.by_node.clear();
+        self.cycles.clear();
+        self.inferred.clear();
+        let ids: Vec<String> = spec.nodes.iter().map(|n| n.id().to_string()).collect();
+        self.revalidate_exact(spec, ids, resolve)
+    }
+
+    /// Re-validate only `changed` nodes and their direct neighbours. Nodes
+    /// that no longer exist have their diagnostics dropped.
+    pub fn revalidate(
+        &mut self,
+        spec: &WorkflowSpec,
+        changed: &[String],
+        resolve: &PortResolver,
+    ) -> ValidationUpdate {
+        let mut scope: HashSet<String> = changed.iter().cloned().collect();
+        for e in &spec.edges {
+            if scope.contains(&e.source_node) || scope.contains(&e.target_node) {
+                scope.insert(e.source_node.clone());
+                scope.insert(e.target_node.clone());
+            }
+        }
+        let mut ids: Vec<String> = scope.into_iter().collect();
+        ids.sort();
+        self.revalidate_exact(spec, ids, resolve)
+    }
+
+    fn revalidate_exact(
+        &mut self,
+        spec: &WorkflowSpec,
+        ids: Vec<String>,
+        resolve: &PortResolver,
+    ) -> ValidationUpdate {
+        let graph = Graph::new(spec);
+        for id in &ids {
+            let found = graph.check_node(id, resolve);
+            if found.is_empty() {
+                self.by_node.remove(id);
+            } else {
+                self.by_node.insert(id.clone(), found);
+            }
+        }
+
+        let mut inferred: HashMap<String, Vec<Diagnostic>> = HashMap::new();
+        for d in infer_types(spec, resolve).diagnostics {
+            inferred.entry(d.node_id.clone()).or_default().push(d);
+        }
+        let cycles = graph.check_cycles();
+        let mut ids = ids;
+        let mut changed: Vec<String> = changed_owners(&self.cycles, &cycles)
+            .chain(changed_owners(&self.inferred, &inferred))
+            .filter(|id| !ids.contains(id))
+            .cloned()
+            .collect();
+        changed.sort();
+        changed.dedup();
+        ids.extend(changed);
+        self.cycles = cycles;
+        self.inferred = inferred;
+
+        let diagnostics = ids
+            .iter()
+            .flat_map(|id| {
+                self.by_node
+                    .get(id)
+                    .into_iter()
+                    .chain(self.cycles.get(id))
+                    .chain(self.inferred.get(id))
+            })
+            .flatten()
+            .cloned()
+            .collect();
+        let (error_count, warning_count) = self.counts();
+        ValidationUpdate {
+            revalidated: ids,
+            diagnostics,
+            error_count,
+            warning_count,
+        }
+    }
+
+    /// All current diagnostics.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.by_node
+            .values()
+            .chain(self.cycles.values())
+            .chain(self.inferred.values())
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
+    fn counts(&self) -> (usize, usize) {
+        self.by_node
+            .values()
+            .chain(self.cycles.values())
+            .chain(self.inferred.values())
+            .flatten()
+            .fold((0, 0), |(e, w), d| match d.severity {
+                Severity::Error => (e + 1, w),
+                Severity::Warning => (e, w + 1),
+            })
+    }
+}
+
+/// Owners whose diagnostics differ between two whole-workflow passes.
+fn changed_owners<'a>(
+    old: &'a HashMap<String, Vec<Diagnostic>>,
+    new: &'a HashMap<String, Vec<Diagnostic>>,
+) -> impl Iterator<Item = &'a String> {
+    old.keys()
+        .chain(new.keys())
+        .filter(move |id| old.get(*id) != new.get(*id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hb_core::graph::{NodeSpec, PortType};
+
+    fn port(name: &str, port_type: PortType) -> PortSpec {
+        PortSpec {
+            name: name.into(),
+            port_type,
+            description: None,
+            required: true,
+            default_value: None,
+            variadic: false,
+            same_type_as: None,
+        }
+    }
+
+    fn resolver(tool_ref: &str) -> Option<NodePorts> {
+        match tool_ref {
+            "t/num@1" => Some(NodePorts {
+                inputs: vec![port("in", PortType::Number)],
+                outputs: vec![port("out", PortType::Number)],
+                ..Default::default()
+            }),
+            "t/text@1" => Some(NodePorts {
+                inputs: vec![port("in", PortType::String)],
+                outputs: vec![port("out", PortType::String)],
+                ..Default::default()
+            }),
+            _ => None,
+        }
+    }
+
+    fn node(id: &str, tool_ref: &str) -> NodeEntry {
+        NodeEntry::Primitive(NodeSpec {
+            id: id.into(),
+            tool_ref: tool_ref.into(),
+            ..Default::default()
+        })
+    }
+
+    fn edge(from: &str, to: &str) -> EdgeSpec {
+        EdgeSpec {
+            id: format!("{from}-{to}"),
+            source_node: from.into(),
+            source_port: "out".into(),
+            target_node: to.into(),
+            target_port: "in".into(),
+            ..Default::default()
+        }
+    }
+
+    fn codes(diags: &[Diagnostic]) -> Vec<&'static str> {
+        let mut c: Vec<_> = diags.iter().map(|d| d.code).collect();
+        c.sort();
+        c
+    }
+
+    #[test]
+    fn reports_type_mismatch_and_unconnected_inputs() {
+        let spec = WorkflowSpec {
+            nodes: vec![node("a", "t/num@1"), node("b", "t/text@1"), node("c", "x/y@1")],
+            edges: vec![edge("a", "b")],
+            ..Default::default()
+        };
+        let mut v = IncrementalValidator::new();
+        let update = v.validate_all(&spec, &resolver);
+        assert_eq!(
+            codes(&update.diagnostics),
+            vec!["missing_required_input", "type_mismatch", "unknown_tool"]
+        );
+        assert_eq!((update.error_count, update.warning_count), (2, 1));
+    }
+
+    #[test]
+    fn presentation_is_checked_against_the_workflow_groups() {
+        let grouped = |id: &str, group: &str| {
+            let mut n = node(id, "t/num@1");
+            if let NodeEntry::Primitive(p) = &mut n {
+                p.presentation.group = Some(group.into());
+            }
+            n
+        };
+        let json = serde_json::json!({
+            "version": "0.1.0",
+            "meta": { "name": "grouped", "description": "" },
+            "groups": [{ "id": "ingest", "label": "Ingest", "color": "teal" }],
+            "nodes": [],
+            "edges": []
+        });
+        let mut spec: WorkflowSpec = serde_json::from_value(json).unwrap();
+        spec.nodes = vec![grouped("a", "ingest"), grouped("b", "gone")];
+        let mut v = IncrementalValidator::new();
+        let update = v.validate_all(&spec, &resolver);
+        let unknown: Vec<_> = update.diagnostics.iter().filter(|d| d.code == "unknown_group").collect();
+        assert_eq!(unknown.len(), 1);
+        assert_eq!(unknown[0].node_id, "b");
+
+        // Notes, colors and groups sit flat on the node in JSON
+        let value = serde_json::to_value(&spec.nodes[0]).unwrap();
+        assert_eq!(value["group"], "ingest");
+        assert!(value.get("note").is_none());
+        let back: NodeEntry = serde_json::from_value(value).unwrap();
+        assert_eq!(back.presentation().and_then(|p| p.group.as_deref()), Some("ingest"));
+    }
+
+    #[test]
+    fn variadic_ports_accept_keyed_edges() {
+        let merge = |tool_ref: &str| {
+            if tool_ref == "t/merge@1" {
+                let mut inputs = port("inputs", PortType::Any);
+                inputs.variadic = true;
+                Some(NodePorts {
+                    inputs: vec![inputs],
+                    outputs: vec![port("merged", PortType::Any)],
+                    ..Default::default()
+                })
+            } else {
+                resolver(tool_ref)
+            }
+        };
+        let keyed = |from: &str, key: &str| EdgeSpec {
+            target_port: format!("inputs.{key}"),
+            ..edge(from, "m")
+        };
+        let mut spec = WorkflowSpec {
+            nodes: vec![node("a", "t/num@1"), node("b", "t/text@1"), node("m", "t/merge@1")],
+            edges: vec![keyed("a", "0"), keyed("b", "1")],
+            ..Default::default()
+        };
+        let mut v = IncrementalValidator::new();
+        let update = v.validate_all(&spec, &merge);
+        assert!(update.diagnostics.iter().all(|d| d.node_id != "m"));
+
+        // A dotted port on a non-variadic input is still unknown.
+        spec.edges.push(EdgeSpec {
+            target_port: "in.x".into(),
+            ..edge("a", "b")
+        });
+        let update = v.revalidate(&spec, &["b".into()], &merge);
+        assert!(update.diagnostics.iter().any(|d| d.code == "unknown_port"));
+    }
+
+    #[test]
+    fn revalidates_only_the_edited_neighbourhood() {
+        let mut spec = WorkflowSpec {
+            nodes: vec![
+                node("a", "t/num@1"),
+                node("b", "t/num@1"),
+                node("far", "unknown/tool@1"),
+            ],
+            edges: vec![],
+            ..Default::default()
+        };
+        let mut v = IncrementalValidator::new();
+        v.validate_all(&spec, &resolver);
+        assert_eq!(v.diagnostics().len(), 3);
+
+        // Connect a → b: only a and b are re-checked, "far" keeps its error.
+        spec.edges.push(edge("a", "b"));
+        let update = v.revalidate(&spec, &["a".into(), "b".into()], &resolver);
+        assert_eq!(update.revalidated, vec!["a", "b"]);
+        assert_eq!(codes(&update.diagnostics), vec!["missing_required_input"]);
+        assert_eq!((update.error_count, update.warning_count), (1, 1));
+
+        // Closing a cycle is reported on the edge's target.
+        spec.edges.push(edge("b", "a"));
+        let update = v.revalidate(&spec, &["a".into(), "b".into()], &resolver);
+        assert_eq!(codes(&update.diagnostics), vec!["cycle", "cycle"]);
+    }
+
+    #[test]
+    fn cycle_errors_clear_when_a_distant_edge_breaks_the_cycle() {
+        let ids = ["a", "b", "c", "d", "e"];
+        let mut spec = WorkflowSpec {
+            nodes: ids.iter().map(|id| node(id, "t/num@1")).collect(),
+            edges: ids
+                .iter()
+                .zip(ids.iter().cycle().skip(1))
+                .map(|(from, to)| edge(from, to))
+                .collect(),
+            ..Default::default()
+        };
+        let mut v = IncrementalValidator::new();
+        let update = v.validate_all(&spec, &resolver);
+        assert_eq!(update.error_count, 5);
+
+        // Removing c → d only re-checks b, c, d and e, but "a" is off the cycle too.
+        spec.edges.retain(|e| !(e.source_node == "c" && e.target_node == "d"));
+        let update = v.revalidate(&spec, &["c".into(), "d".into()], &resolver);
+        assert!(update.revalidated.contains(&"a".to_string()));
+        assert!(!v.diagnostics().iter().any(|d| d.code == "cycle"));
+    }
+
+    #[test]
+    fn reports_regex_and_expression_config_that_does_not_parse() {
+        let field = |name: &str, field_type| ConfigField {
+            name: name.into(),
+            field_type,
+            label: String::new(),
+            description: None,
+            default_value: None,
+            required: false,
+            options: vec![],
+            options_source: None,
+            visible_when: None,
+            min: None,
+            max: None,
+            pattern: None,
+        };
+        let regex = |tool_ref: &str| {
+            (tool_ref == "t/regex@1").then(|| NodePorts {
+                config: vec![
+                    field("pattern", ConfigFieldType::Regex),
+                    field("when", ConfigFieldType::Expression),
+                ],
+                ..Default::default()
+            })
+        };
+        let with_config = |id: &str, key: &str, value: &str| {
+            let mut entry = node(id, "t/regex@1");
+            if let NodeEntry::Primitive(n) = &mut entry {
+                n.config.insert(key.into(), serde_json::json!(value));
+            }
+            entry
+        };
+        let spec = WorkflowSpec {
+            nodes: vec![
+                with_config("ok", "pattern", r"(?P<code>[A-Z]{2}-\d+)"),
+                with_config("bad", "pattern", "(?P<code>[A-Z"),
+                with_config("guard", "when", "len(rows) > 0 && status == 'ok'"),
+                with_config("typo", "when", "score >= "),
+            ],
+            ..Default::default()
+        };
+        let update = IncrementalValidator::new().validate_all(&spec, &regex);
+        assert_eq!(codes(&update.diagnostics), vec!["invalid_expression", "invalid_regex"]);
+        let nodes: HashSet<_> = update.diagnostics.iter().map(|d| (d.code, d.node_id.as_str())).collect();
+        assert_eq!(nodes, HashSet::from([("invalid_regex", "bad"), ("invalid_expression", "typo")]));
+    }
+}
@@ -0,0 +1,419 @@
+//! Type inference — concrete types for `any` ports, worked out from what is
+//! wired into them.
+//!
+//! The port check lets anything through an `any` port, which hides wiring
+//! bugs such as a count reaching an input that expects a list two nodes
+//! later. Inference follows Data edges: an `any` input takes the type of the
+//! value wired into it, an `any` output declared
+//! [`same_type_as`](hb_core::graph::PortSpec::same_type_as) an input passes
+//! that type on, and an edge transform maps the type it receives (see
+//! [`transform_type`]). Where an inferred type reaches an input declaring a
+//! different one, [`infer_types`] reports an `inferred_type_mismatch`
+//! warning. Mismatches between two declared types are the live diagnostics'
+//! `type_mismatch` errors and are not repeated here.
+
+use crate::diagnostics::{node_ports, Diagnostic, NodePorts, PortResolver};
+use crate::type_checker::types_compatible;
+use hb_core::graph::{split_variadic_port, EdgeKind, EdgeSpec, PortType, Severity, WorkflowSpec};
+use serde_json::Value;
+use std::collections::hash_map::{Entry, HashMap};
+
+/// Port types of one workflow, declared or inferred.
+#[derive(Debug, Default)]
+pub struct TypeInference {
+    /// Concrete output types by (node, port).
+    outputs: HashMap<(String, String), PortType>,
+    /// Concrete types reaching `any` inputs by (node, port).
+    inputs: HashMap<(String, String), PortType>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl TypeInference {
+    /// Type of what `node` puts on `port`; `Any` when it can't be told.
+    pub fn output_type(&self, node: &str, port: &str) -> PortType {
+        lookup(&self.outputs, node, port)
+    }
+
+    /// Type of what reaches the `any` input `port` of `node`.
+    pub fn input_type(&self, node: &str, port: &str) -> PortType {
+        lookup(&self.inputs, node, port)
+    }
+
+    /// Type of the value `edge` delivers, after its transform.
+    fn carried(&self, edge: &EdgeSpec) -> PortType {
+        let source = self.output_type(&edge.source_node, &edge.source_port);
+        match &edge.transform {
+            Some(transform) => transform_type(transform, &source),
+            None => source,
+        }
+    }
+}
+
+fn lookup(types: &HashMap<(String, String), PortType>, node: &str, port: &str) -> PortType {
+    let (base, _) = split_variadic_port(port);
+    types
+        .get(&(node.to_string(), base.to_string()))
+        .cloned()
+        .unwrap_or(PortType::Any)
+}
+
+/// Infer the types flowing through `spec`'s top-level Data edges.
+pub fn infer_types(spec: &WorkflowSpec, resolve: &PortResolver) -> TypeInference {
+    let ports: HashMap<&str, NodePorts> = spec
+        .nodes
+        .iter()
+        .filter_map(|n| Some((n.id(), node_ports(n, resolve)?)))
+        .collect();
+    let edges: Vec<&EdgeSpec> = spec
+        .edges
+        .iter()
+        .filter(|e| e.kind == EdgeKind::Data)
+        .collect();
+
+    let mut inference = TypeInference::default();
+    for (id, p) in &ports {
+        for out in p.outputs.iter().filter(|o| o.port_type != PortType::Any) {
+            inference
+                .outputs
+                .insert((id.to_string(), out.name.clone()), out.port_type.clone());
+        }
+    }
+
+    // Each round carries types at least one edge further, and no chain of
+    // edges is longer than the node count
+    for _ in 0..=spec.nodes.len() {
+        let mut changed = false;
+        for edge in &edges {
+            let carried = inference.carried(edge);
+            let declared = ports
+                .get(edge.target_node.as_str())
+                .and_then(|p| p.inputs.iter().find(|i| i.accepts(&edge.target_port)));
+            if carried == PortType::Any || declared.is_some_and(|i| i.port_type != PortType::Any) {
+                continue;
+            }
+            let (base, _) = split_variadic_port(&edge.target_port);
+            let key = (edge.target_node.clone(), base.to_string());
+            if let Entry::Vacant(slot) = inference.inputs.entry(key) {
+                slot.insert(carried);
+                changed = true;
+            }
+        }
+        for (id, p) in &ports {
+            for out in p.outputs.iter().filter(|o| o.port_type == PortType::Any) {
+                let Some(from) = &out.same_type_as else {
+                    continue;
+                };
+                let passed = inference.input_type(id, from);
+                let key = (id.to_string(), out.name.clone());
+                if passed != PortType::Any && !inference.outputs.contains_key(&key) {
+                    inference.outputs.insert(key, passed);
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    for edge in &edges {
+        let declared_source = ports
+            .get(edge.source_node.as_str())
+            .and_then(|p| p.outputs.iter().find(|o| o.accepts(&edge.source_port)));
+        if declared_source.is_some_and(|o| o.port_type != PortType::Any) {
+            continue;
+        }
+        let Some(expected) = ports
+            .get(edge.target_node.as_str())
+            .and_then(|p| p.inputs.iter().find(|i| i.accepts(&edge.target_port)))
+            .map(|i| &i.port_type)
+        else {
+            continue;
+        };
+        let carried = inference.carried(edge);
+        if carried != PortType::Any && !types_compatible(&carried, expected) {
+            inference.diagnostics.push(
+                Diagnostic::new(
+                    Severity::Warning,
+                    "inferred_type_mismatch",
+                    &edge.target_node,
+                    format!(
+                        "{}.{} carries {carried:?} here, but {}.{} expects {expected:?}",
+                        edge.source_node, edge.source_port, edge.target_node, edge.target_port
+                    ),
+                )
+                .on_edge(edge),
+            );
+        }
+    }
+    inference
+}
+
+/// Type of what the JMESPath edge transform `expr` yields from a value of
+/// type `input`, judged by its outermost form: a pipe, comparison, function
+/// call, literal, multiselect or projection. `Any` when the form doesn't
+/// settle it, e.g. a field lookup or an index.
+pub fn transform_type(expr: &str, input: &PortType) -> PortType {
+    let expr = expr.trim();
+    let top = top_level(expr);
+    let at = |pattern: &'static str| {
+        top.iter()
+            .copied()
+            .filter(move |&i| expr[i..].starts_with(pattern))
+    };
+
+    // Pipes are left-associative: the last one splits off the final step
+    if let Some(i) = at("|").rfind(|&i| !is_or(expr, i)) {
+        return transform_type(&expr[i + 1..], &transform_type(&expr[..i], input));
+    }
+    if at("||").next().is_some() || at("&&").next().is_some() {
+        return PortType::Any;
+    }
+    if ["==", "!=", "<", ">"]
+        .iter()
+        .any(|op| at(op).next().is_some())
+        || expr.starts_with('!')
+    {
+        return PortType::Boolean;
+    }
+    if expr == "@" {
+        return input.clone();
+    }
+
+    let encloses = |open: char| expr.starts_with(open) && top == [0, expr.len() - 1];
+    if encloses('`') {
+        return match serde_json::from_str(&expr[1..expr.len() - 1]) {
+            Ok(value) => value_type(&value),
+            Err(_) => PortType::Any,
+        };
+    }
+    if encloses('\'') {
+        return PortType::String;
+    }
+    if encloses('{') {
+        return PortType::Json;
+    }
+
+    let name_len = expr
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(expr.len());
+    let call = expr[name_len..].starts_with('(') && top[name_len..] == [name_len, expr.len() - 1];
+    if name_len > 0 && call {
+        return function_type(&expr[..name_len]);
+    }
+
+    // `[*]`, `[]`, `[?filter]`, `[a:b]`, `*` and multiselect lists yield a
+    // list whatever follows them; only `[n]` picks one element
+    for (open, close) in at("[").zip(at("]")) {
+        if expr[open + 1..close].trim().parse::<i64>().is_err() {
+            return PortType::Array;
+        }
+    }
+    if at("*").next().is_some() {
+        return PortType::Array;
+    }
+    PortType::Any
+}
+
+/// Whether the `|` at `i` is half of `||`.
+fn is_or(expr: &str, i: usize) -> bool {
+    expr[i + 1..].starts_with('|') || expr[..i].ends_with('|')
+}
+
+/// Byte offsets of the characters of `expr` outside brackets and quotes,
+/// including the brackets and quotes that open and close at the top.
+fn top_level(expr: &str) -> Vec<usize> {
+    let mut top = Vec::new();
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for (i, c) in expr.char_indices() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+                if depth == 0 {
+                    top.push(i);
+                }
+            }
+            continue;
+        }
+        match c {
+            '\'' | '"' | '`' => {
+                quote = Some(c);
+                if depth == 0 {
+                    top.push(i);
+                }
+            }
+            '(' | '[' | '{' => {
+                if depth == 0 {
+                    top.push(i);
+                }
+                depth += 1;
+            }
+            ')' | ']' | '}' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    top.push(i);
+                }
+            }
+            c if c.is_whitespace() => {}
+            _ if depth == 0 => top.push(i),
+            _ => {}
+        }
+    }
+    top
+}
+
+fn function_type(name: &str) -> PortType {
+    match name {
+        "length" | "sum" | "avg" | "abs" | "ceil" | "floor" | "to_number" => PortType::Number,
+        "to_string" | "join" | "type" => PortType::String,
+        "keys" | "values" | "sort" | "sort_by" | "map" | "to_array" => PortType::Array,
+        "contains" | "starts_with" | "ends_with" => PortType::Boolean,
+        "merge" => PortType::Json,
+        _ => PortType::Any,
+    }
+}
+
+fn value_type(value: &Value) -> PortType {
+    match value {
+        Value::Null => PortType::Any,
+        Value::Bool(_) => PortType::Boolean,
+        Value::Number(_) => PortType::Number,
+        Value::String(_) => PortType::String,
+        Value::Array(_) => PortType::Array,
+        Value::Object(_) => PortType::Json,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hb_core::graph::{NodeEntry, NodeSpec, PortSpec};
+
+    fn port(name: &str, port_type: PortType) -> PortSpec {
+        PortSpec {
+            name: name.into(),
+            port_type,
+            description: None,
+            required: false,
+            default_value: None,
+            variadic: false,
+            same_type_as: None,
+        }
+    }
+
+    fn resolver(tool_ref: &str) -> Option<NodePorts> {
+        let ports = |inputs, outputs| {
+            Some(NodePorts {
+                inputs,
+                outputs,
+                ..Default::default()
+            })
+        };
+        match tool_ref {
+            "t/count" => ports(vec![], vec![port("count", PortType::Number)]),
+            "t/rows" => ports(vec![], vec![port("rows", PortType::Array)]),
+            "t/delay" => {
+                let mut output = port("output", PortType::Any);
+                output.same_type_as = Some("input".into());
+                ports(vec![port("input", PortType::Any)], vec![output])
+            }
+            "t/opaque" => ports(
+                vec![port("input", PortType::Any)],
+                vec![port("output", PortType::Any)],
+            ),
+            "t/list" => ports(vec![port("items", PortType::Array)], vec![]),
+            _ => None,
+        }
+    }
+
+    fn node(id: &str, tool_ref: &str) -> NodeEntry {
+        NodeEntry::Primitive(NodeSpec {
+            id: id.into(),
+            tool_ref: tool_ref.into(),
+            ..Default::default()
+        })
+    }
+
+    fn edge(from: &str, out: &str, to: &str, input: &str, transform: Option<&str>) -> EdgeSpec {
+        EdgeSpec {
+            id: format!("{from}-{to}"),
+            source_node: from.into(),
+            source_port: out.into(),
+            target_node: to.into(),
+            target_port: input.into(),
+            transform: transform.map(Into::into),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn concrete_types_flow_through_any_ports_and_transforms() {
+        // count (Number) → wait → hold → sink expecting Array
+        let spec = WorkflowSpec {
+            nodes: vec![
+                node("count", "t/count"),
+                node("wait", "t/delay"),
+                node("hold", "t/delay"),
+                node("sink", "t/list"),
+                node("rows", "t/rows"),
+                node("size", "t/delay"),
+                node("blind", "t/opaque"),
+                node("fine", "t/list"),
+            ],
+            edges: vec![
+                edge("count", "count", "wait", "input", None),
+                edge("wait", "output", "hold", "input", None),
+                edge("hold", "output", "sink", "items", None),
+                edge("rows", "rows", "size", "input", Some("length(@)")),
+                edge("size", "output", "fine", "items", Some("to_array(@)")),
+                edge("count", "count", "blind", "input", None),
+            ],
+            ..Default::default()
+        };
+        let inference = infer_types(&spec, &resolver);
+        assert_eq!(inference.output_type("hold", "output"), PortType::Number);
+        assert_eq!(inference.input_type("size", "input"), PortType::Number);
+        // Outputs not declared to pass their input on stay unknown
+        assert_eq!(inference.output_type("blind", "output"), PortType::Any);
+
+        let [warning] = &inference.diagnostics[..] else {
+            panic!("{:?}", inference.diagnostics);
+        };
+        assert_eq!(warning.code, "inferred_type_mismatch");
+        assert_eq!(warning.node_id, "sink");
+        assert_eq!(
+            warning.message,
+            "hold.output carries Number here, but sink.items expects Array"
+        );
+        // Strict compiles refuse what is otherwise a warning
+        assert!(crate::type_checker::check_inferred(&spec, &resolver, false).is_ok());
+        let err = crate::type_checker::check_inferred(&spec, &resolver, true).unwrap_err();
+        assert!(
+            err.to_string().contains("sink: hold.output carries Number"),
+            "{err}"
+        );
+
+        for (expr, input, expected) in [
+            ("@", PortType::Json, PortType::Json),
+            ("items[*].name", PortType::Json, PortType::Array),
+            ("items[0]", PortType::Json, PortType::Any),
+            ("[?score > `3`]", PortType::Array, PortType::Array),
+            ("items | length(@)", PortType::Json, PortType::Number),
+            ("items[*] | [0]", PortType::Json, PortType::Any),
+            ("status == 'done'", PortType::Json, PortType::Boolean),
+            ("{ n: name }", PortType::Json, PortType::Json),
+            ("join(', ', names)", PortType::Json, PortType::String),
+            ("`[1, 2]`", PortType::Any, PortType::Array),
+            ("a || b", PortType::Json, PortType::Any),
+            ("name", PortType::Json, PortType::Any),
+        ] {
+            assert_eq!(transform_type(expr, &input), expected, "{expr}");
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! Language Detector — tells Korean prompts from English ones, so the
+//! classifier and slot filler can use Korean lexicons and the LLM fallback
+//! can answer in the user's language.
+
+use serde::{Deserialize, Serialize};
+
+/// Share of letters that must be Hangul for a prompt to count as Korean.
+/// Korean prompts routinely carry English terms (RAG, file and model names),
+/// so a majority is not required.
+const KOREAN_THRESHOLD: f64 = 0.3;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Language {
+    Korean,
+    English,
+}
+
+impl Language {
+    /// ISO 639-1 code.
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::Korean => "ko",
+            Self::English => "en",
+        }
+    }
+}
+
+/// Detect the language a prompt is written in. Prompts without letters are
+/// treated as English.
+pub fn detect(prompt: &str) -> Language {
+    let (mut hangul, mut letters) = (0usize, 0usize);
+    for c in prompt.chars() {
+        if is_hangul(c) {
+            hangul += 1;
+            letters += 1;
+        } else if c.is_alphabetic() {
+            letters += 1;
+        }
+    }
+    if letters > 0 && hangul as f64 / letters as f64 >= KOREAN_THRESHOLD {
+        Language::Korean
+    } else {
+        Language::English
+    }
+}
+
+fn is_hangul(c: char) -> bool {
+    matches!(c, '\u{AC00}'..='\u{D7A3}' | '\u{1100}'..='\u{11FF}' | '\u{3130}'..='\u{318F}')
+}
+
+/// Instructions appended to the LLM fallback's system prompt for prompts in
+/// `language`.
+pub fn fallback_instructions(language: Language) -> &'static str {
+    match language {
+        Language::Korean => KOREAN_INSTRUCTIONS,
+        Language::English => "",
+    }
+}
+
+const KOREAN_INSTRUCTIONS: &str = r#"
+## Language
+
+The instruction is written in Korean. Read it as a Korean speaker would: particles
+(을/를, 에서, 으로) mark objects, sources and formats, and polite or casual endings
+(해줘, 해 주세요, 하기) all mean "do this".
+
+- Write meta.name, meta.description and every node "label" in Korean.
+- Keep tool_ref, node ids, port names and config keys in English, exactly as listed above.
+- Korean numbers and dates go into config as plain values: "세 문장" → 3, "1만 건" → 10000,
+  "2024년 3월 5일" → "2024-03-05".
+
+## Examples
+
+Instruction: 보고서.pdf를 읽고 세 문장으로 요약해서 보여줘
+{"version":"0.1.0","meta":{"name":"보고서 요약","description":"보고서를 세 문장으로 요약합니다"},"nodes":[{"kind":"primitive","id":"read_report","tool_ref":"io/file-read","config":{"path":"보고서.pdf"},"position":{"x":0,"y":0},"label":"보고서 읽기"},{"kind":"primitive","id":"summarize_report","tool_ref":"ai/llm-summarize","config":{},"position":{"x":300,"y":0},"label":"세 문장 요약"},{"kind":"primitive","id":"show_summary","tool_ref":"io/display-output","config":{},"position":{"x":600,"y":0},"label":"요약 보기"}],"edges":[{"source_node":"read_report","source_port":"content","target_node":"summarize_report","target_port":"text","kind":"data"},{"source_node":"summarize_report","source_port":"summary","target_node":"show_summary","target_port":"data","kind":"data"}]}
+
+Instruction: sales.csv에서 금액이 100만 원 넘는 거래만 골라 엑셀로 저장해 주세요
+{"version":"0.1.0","meta":{"name":"고액 거래 추출","description":"100만 원을 넘는 거래를 엑셀로 저장합니다"},"nodes":[{"kind":"primitive","id":"read_sales","tool_ref":"data/csv-read","config":{"path":"sales.csv"},"position":{"x":0,"y":0},"label":"매출 읽기"},{"kind":"primitive","id":"filter_large","tool_ref":"data/data-filter","config":{"condition":"amount > 1000000"},"position":{"x":300,"y":0},"label":"고액 거래 필터"},{"kind":"primitive","id":"export_excel","tool_ref":"export/to-excel","config":{},"position":{"x":600,"y":0},"label":"엑셀 저장"}],"edges":[{"source_node":"read_sales","source_port":"rows","target_node":"filter_large","target_port":"items","kind":"data"},{"source_node":"filter_large","source_port":"filtered","target_node":"export_excel","target_port":"data","kind":"data"}]}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_korean_with_embedded_english() {
+        assert_eq!(detect("RAG 파이프라인 만들어줘"), Language::Korean);
+        assert_eq!(
+            detect("'report.pdf' 파일을 gpt-4o로 요약"),
+            Language::Korean
+        );
+        assert_eq!(
+            detect("Summarize report.pdf with gpt-4o"),
+            Language::English
+        );
+        assert_eq!(detect("123 !!"), Language::English);
+        assert!(fallback_instructions(Language::Korean).contains("label"));
+    }
+}
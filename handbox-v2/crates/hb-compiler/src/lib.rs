@@ -4,8 +4,15 @@
 //! Falls back to LLM-based graph generation when no template matches.
 
 pub mod classifier;
+pub mod diagnostics;
+pub mod inference;
+pub mod language;
 pub mod llm_fallback;
+pub mod migrate;
+pub mod optimize;
+pub mod publish;
 pub mod slot_filler;
+pub mod suggest;
 pub mod template;
 pub mod type_checker;
 pub mod validator_inserter;
@@ -29,6 +36,25 @@ pub enum CompilerError {
     Validation(String),
 }
 
+impl From<CompilerError> for hb_core::error::AppError {
+    fn from(e: CompilerError) -> Self {
+        use hb_core::error::{AppError, ErrorCode};
+        let code = match e {
+            CompilerError::NoTemplateMatch => ErrorCode::NotFound,
+            CompilerError::TypeCheckFailed(_) | CompilerError::Validation(_) => ErrorCode::InvalidInput,
+            CompilerError::LlmFallback(ref msg) => return AppError::from_message(format!("LLM fallback error: {msg}")),
+            _ => ErrorCode::Internal,
+        };
+        let err = AppError::new(code, e.to_string());
+        match e {
+            CompilerError::NoTemplateMatch => {
+                err.with_hint("Rephrase the request or enable the LLM fallback.")
+            }
+            _ => err,
+        }
+    }
+}
+
 /// Compile a natural-language prompt into an executable WorkflowSpec.
 pub async fn compile(prompt: &str) -> Result<WorkflowSpec, CompilerError> {
     // Try template-based compilation first
@@ -42,7 +68,12 @@ pub async fn compile(prompt: &str) -> Result<WorkflowSpec, CompilerError> {
     }
 
     // No template match — use LLM fallback to generate workflow
-    tracing::info!("No template match for prompt, using LLM fallback: {}", &prompt[..prompt.len().min(100)]);
+    // Truncate by characters: a byte index can split a Korean character
+    let preview: String = prompt.chars().take(100).collect();
+    tracing::info!(
+        "No template match for {} prompt, using LLM fallback: {preview}",
+        slots.get_or_default("language", "en")
+    );
     let spec = llm_fallback::generate_with_llm(prompt).await?;
 
     // Run type checking and validation on LLM-generated spec
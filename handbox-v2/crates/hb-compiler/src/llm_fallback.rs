@@ -3,11 +3,12 @@
 //! This module provides the core intelligence of Handbox v2: using an LLM to
 //! plan and generate workflow graphs from natural language prompts.
 
+use crate::language;
 use crate::CompilerError;
 use chrono::Utc;
 use hmac::{Hmac, Mac};
 use hb_core::graph::{
-    EdgeKind, EdgeSpec, NodeEntry, NodeSpec, Position, WorkflowMeta, WorkflowSpec,
+    EdgeSpec, NodeEntry, NodeSpec, Position, WorkflowMeta, WorkflowSpec,
 };
 use sha2::{Digest, Sha256};
 use std::collections::BTreeMap;
@@ -109,6 +110,7 @@ const SYSTEM_PROMPT: &str = r#"You are Handbox, an AI workflow generator. Your t
 
 ### Input/Output (io/)
 - io/file-read: Read text from a file. Inputs: path. Outputs: content.
+- io/pdf-table-extract: Extract the tables of a PDF as tables with headers and rows (usable as data-filter items). Inputs: path. Outputs: tables, table, count.
 - io/file-write: Write text to a file. Inputs: path, content. Outputs: success.
 - io/user-input: Get input from user. Outputs: text.
 - io/display-output: Display result to user. Inputs: data.
@@ -116,30 +118,42 @@ const SYSTEM_PROMPT: &str = r#"You are Handbox, an AI workflow generator. Your t
 ### AI/LLM (ai/)
 - ai/llm-chat: Send prompt to LLM. Inputs: prompt, context. Outputs: response.
 - ai/llm-summarize: Summarize text. Inputs: text. Outputs: summary.
+- ai/summarize-long: Summarize a document too long for one LLM call (chunk summaries merged level by level). Inputs: text, path. Outputs: summary, chunk_summaries.
 - ai/embedding: Generate embeddings. Inputs: text. Outputs: vector.
 
 ### Text Processing (text/)
 - text/text-split: Split text into chunks. Inputs: text. Outputs: chunks.
 - text/text-merge: Merge texts. Inputs: texts. Outputs: merged.
 - text/text-template: Apply template. Inputs: variables. Outputs: result.
-- text/regex-extract: Extract with regex. Inputs: text. Outputs: matches.
+- text/regex-extract: Extract with regex (named groups become fields of captures). Inputs: text. Outputs: matches, captures.
+- text/clause-split: Split a contract or regulation into numbered clauses. Inputs: text, path. Outputs: clauses, count.
+- text/doc-compare: Compare two document versions clause by clause (modified/added/deleted/moved, flags changed numbers and obligations). Inputs: old, new. Outputs: changes, summary, report.
 
 ### Data (data/)
 - data/json-parse: Parse JSON. Inputs: json_string. Outputs: data.
 - data/json-path: Query JSON with path. Inputs: data. Outputs: result.
 - data/csv-read: Read CSV. Inputs: path. Outputs: rows.
-- data/data-filter: Filter array. Inputs: items. Outputs: filtered.
+- data/data-filter: Filter, sort and page an array or table by expression. Inputs: items. Outputs: filtered, count.
+- data/doc-extract-fields: Extract labelled fields (invoice number, dates, amounts...) from a form, invoice or permit as typed JSON checked against a field schema. Inputs: text, path. Outputs: fields, valid, errors, missing.
+- data/public-api-fetch: Fetch records from Korean public data APIs (data.go.kr presets: kma-forecast, apt-trade, building-register, air-quality). Inputs: params. Outputs: items, count, total_count.
 
 ### Control Flow (control/)
-- control/condition: Branch on condition. Inputs: value. Outputs: true, false.
+- control/condition: Branch on an expression, or switch on cases. Inputs: value. Outputs: true_out, false_out, cases.<label>, default.
 - control/loop: Iterate over items. Inputs: items. Outputs: results.
-- control/merge: Merge inputs. Inputs: input_a, input_b. Outputs: merged.
+- control/merge: Merge any number of inputs. Inputs: inputs.<key> (variadic, e.g. inputs.0, inputs.1). Outputs: merged.
 - control/delay: Wait for duration. Inputs: trigger. Outputs: trigger.
 
 ### RAG (rag/)
 - rag/vector-store: Store embeddings. Inputs: chunks, vectors. Outputs: index_id.
 - rag/vector-search: Search vectors. Inputs: query_vector. Outputs: results.
 - rag/reranker: Rerank results. Inputs: query, documents. Outputs: ranked.
+- rag/graph-extract: Extract entity/relation facts from documents into a knowledge graph. Inputs: text, source. Outputs: triples, count, added.
+- rag/graph-query: Find facts by subject/predicate/object pattern or around an entity. Inputs: entity, subject, predicate, object. Outputs: triples, entities, context, count.
+
+### Meeting (meeting/)
+- meeting/audio-transcribe: Transcribe a meeting recording (or read SRT/VTT subtitles) into timed segments. Inputs: path. Outputs: text, transcript, segments.
+- meeting/speaker-diarize: Tell the speakers of a transcript apart. Inputs: segments, text. Outputs: transcript, segments, speakers.
+- meeting/meeting-minutes: Write minutes with decisions and action items (owner, due). Inputs: transcript, segments, path. Outputs: minutes, action_items, decisions, summary.
 
 ### Export (export/)
 - export/to-pdf: Export to PDF. Inputs: content. Outputs: path.
@@ -209,6 +223,7 @@ fn sha256_hash(data: &[u8]) -> String {
     hex::encode(hasher.finalize())
 }
 
+#[allow(clippy::too_many_arguments)]
 fn sign_aws_request(
     method: &str,
     url: &str,
@@ -328,6 +343,7 @@ async fn call_bedrock_bearer(
     Ok(result["content"][0]["text"].as_str().unwrap_or("").to_string())
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn call_bedrock_iam(
     access_key: &str,
     secret_key: &str,
@@ -520,6 +536,8 @@ fn parse_llm_response(response: &str) -> Result<WorkflowSpec, CompilerError> {
         tags: vec!["generated".to_string()],
         created_at: Utc::now(),
         updated_at: Utc::now(),
+        output_contract: Vec::new(),
+        memoize: false,
     };
 
     // Parse nodes
@@ -553,9 +571,7 @@ fn parse_llm_response(response: &str) -> Result<WorkflowSpec, CompilerError> {
                 config,
                 position,
                 label,
-                disabled: false,
-                retry: None,
-                cache: None,
+                ..Default::default()
             }));
         }
     }
@@ -576,8 +592,7 @@ fn parse_llm_response(response: &str) -> Result<WorkflowSpec, CompilerError> {
                     source_port,
                     target_node,
                     target_port,
-                    kind: EdgeKind::Data,
-                    transform: None,
+                    ..Default::default()
                 });
             }
         }
@@ -591,6 +606,8 @@ fn parse_llm_response(response: &str) -> Result<WorkflowSpec, CompilerError> {
         nodes,
         edges,
         required_packs: vec![],
+        tests: vec![],
+        groups: vec![],
     })
 }
 
@@ -598,6 +615,15 @@ fn parse_llm_response(response: &str) -> Result<WorkflowSpec, CompilerError> {
 // Main Function
 // ============================================================
 
+/// The system prompt, with language-specific guidance and examples for the
+/// language `prompt` is written in.
+fn system_prompt_for(prompt: &str) -> String {
+    format!(
+        "{SYSTEM_PROMPT}{}",
+        language::fallback_instructions(language::detect(prompt))
+    )
+}
+
 /// Use an LLM to generate a WorkflowSpec from a natural-language prompt.
 pub async fn generate_with_llm(prompt: &str) -> Result<WorkflowSpec, CompilerError> {
     let config = LLMGeneratorConfig::default();
@@ -611,6 +637,7 @@ pub async fn generate_with_llm_config(
 ) -> Result<WorkflowSpec, CompilerError> {
     let provider = LLMProvider::from_env()
         .ok_or_else(|| CompilerError::LlmFallback("No LLM provider configured".into()))?;
+    let system_prompt = system_prompt_for(prompt);
 
     let response = match provider {
         LLMProvider::BedrockApiKey { api_key, region } => {
@@ -624,12 +651,12 @@ pub async fn generate_with_llm_config(
                 &model_id,
                 &region,
                 prompt,
-                SYSTEM_PROMPT,
+                &system_prompt,
                 config.temperature,
                 config.max_tokens,
             )
             .await
-            .map_err(|e| CompilerError::LlmFallback(e))?
+            .map_err(CompilerError::LlmFallback)?
         }
         LLMProvider::Bedrock {
             access_key,
@@ -647,12 +674,12 @@ pub async fn generate_with_llm_config(
                 &model_id,
                 &region,
                 prompt,
-                SYSTEM_PROMPT,
+                &system_prompt,
                 config.temperature,
                 config.max_tokens,
             )
             .await
-            .map_err(|e| CompilerError::LlmFallback(e))?
+            .map_err(CompilerError::LlmFallback)?
         }
         LLMProvider::OpenAI { api_key } => {
             let model = if config.model_id.starts_with("gpt-") {
@@ -664,24 +691,24 @@ pub async fn generate_with_llm_config(
                 &api_key,
                 &model,
                 prompt,
-                SYSTEM_PROMPT,
+                &system_prompt,
                 config.temperature,
                 config.max_tokens,
             )
             .await
-            .map_err(|e| CompilerError::LlmFallback(e))?
+            .map_err(CompilerError::LlmFallback)?
         }
         LLMProvider::Local { endpoint } => {
             call_local_llm(
                 &endpoint,
                 &config.model_id,
                 prompt,
-                SYSTEM_PROMPT,
+                &system_prompt,
                 config.temperature,
                 config.max_tokens,
             )
             .await
-            .map_err(|e| CompilerError::LlmFallback(e))?
+            .map_err(CompilerError::LlmFallback)?
         }
     };
 
@@ -694,6 +721,19 @@ pub async fn generate_with_llm_config(
 mod tests {
     use super::*;
 
+    #[test]
+    fn korean_prompts_get_korean_examples_that_parse() {
+        assert_eq!(system_prompt_for("Summarize report.pdf"), SYSTEM_PROMPT);
+        let system = system_prompt_for("보고서를 요약해줘");
+        let added = system.strip_prefix(SYSTEM_PROMPT).unwrap();
+        let examples: Vec<_> = added.lines().filter(|l| l.starts_with('{')).collect();
+        assert_eq!(examples.len(), 2);
+        for example in examples {
+            let spec = parse_llm_response(example).expect("example should parse");
+            assert!(!spec.edges.is_empty());
+        }
+    }
+
     #[test]
     fn test_parse_simple_workflow() {
         let json = r#"{
@@ -0,0 +1,654 @@
+//! Workflow migration — carries saved workflows over a pack upgrade that
+//! changed tool schemas.
+//!
+//! For every node of the upgraded pack, the [`ToolMigration`] rules the new
+//! release declares run first. What they leave is checked against the new
+//! tool schemas: a removed tool, port or config field, a changed field type
+//! or a select value no longer offered is flagged for manual attention and
+//! left as it was, so nothing breaks silently. Nodes inside composite, loop,
+//! conditional and map-reduce bodies are migrated too.
+
+use hb_core::graph::{split_variadic_port, EdgeKind, EdgeSpec, NodeEntry, NodeSpec, WorkflowSpec};
+use hb_core::pack::{MigrationRule, ToolMigration};
+use hb_core::tool::{ConfigFieldType, ToolInterface};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// One pack going from the installed release to a newer one.
+#[derive(Debug, Clone)]
+pub struct PackUpgrade {
+    pub pack_id: String,
+    pub from_version: semver::Version,
+    pub to_version: semver::Version,
+    /// Tools of the installed and the new release, keyed by tool ID.
+    pub old_tools: HashMap<String, ToolInterface>,
+    pub new_tools: HashMap<String, ToolInterface>,
+    /// Migrations declared by the new release.
+    pub migrations: Vec<ToolMigration>,
+}
+
+/// A change the migration makes.
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationChange {
+    /// Changed node; `None` for the workflow itself (its pack pin).
+    pub node_id: Option<String>,
+    pub description: String,
+}
+
+/// Something a person has to look at after the upgrade.
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationIssue {
+    pub node_id: String,
+    /// Stable machine-readable code, e.g. "port_removed".
+    pub code: &'static str,
+    pub message: String,
+}
+
+/// What migrating one workflow does.
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkflowMigration {
+    pub workflow_id: String,
+    pub workflow_name: String,
+    pub changes: Vec<MigrationChange>,
+    pub issues: Vec<MigrationIssue>,
+    /// The workflow with every change applied.
+    #[serde(skip)]
+    pub migrated: WorkflowSpec,
+}
+
+impl WorkflowMigration {
+    pub fn needs_attention(&self) -> bool {
+        !self.issues.is_empty()
+    }
+}
+
+/// Migration of every affected workflow.
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationReport {
+    pub pack_id: String,
+    pub from_version: String,
+    pub to_version: String,
+    pub workflows: Vec<WorkflowMigration>,
+}
+
+impl MigrationReport {
+    /// Workflows with nodes needing manual attention.
+    pub fn attention_count(&self) -> usize {
+        self.workflows
+            .iter()
+            .filter(|w| w.needs_attention())
+            .count()
+    }
+}
+
+/// Migrate every workflow in `workflows` that uses the pack.
+pub fn report<'a>(
+    upgrade: &PackUpgrade,
+    workflows: impl IntoIterator<Item = &'a WorkflowSpec>,
+) -> MigrationReport {
+    MigrationReport {
+        pack_id: upgrade.pack_id.clone(),
+        from_version: upgrade.from_version.to_string(),
+        to_version: upgrade.to_version.to_string(),
+        workflows: workflows
+            .into_iter()
+            .filter_map(|spec| plan(upgrade, spec))
+            .collect(),
+    }
+}
+
+/// Migrate one workflow; `None` when it neither uses nor pins the pack.
+pub fn plan(upgrade: &PackUpgrade, spec: &WorkflowSpec) -> Option<WorkflowMigration> {
+    let mut migrated = spec.clone();
+    let mut plan = Planner {
+        upgrade,
+        changes: Vec::new(),
+        issues: Vec::new(),
+        uses_pack: false,
+    };
+    plan.graph(&mut migrated.nodes, &mut migrated.edges);
+
+    let mut pinned = false;
+    for dep in migrated
+        .required_packs
+        .iter_mut()
+        .filter(|d| d.pack_id == upgrade.pack_id)
+    {
+        pinned = true;
+        let excluded = semver::VersionReq::parse(&dep.version_range)
+            .map(|req| !req.matches(&upgrade.to_version))
+            .unwrap_or(true);
+        if excluded {
+            let range = format!("^{}", upgrade.to_version);
+            plan.changes.push(MigrationChange {
+                node_id: None,
+                description: format!(
+                    "Pin of {} changed from {} to {range}",
+                    upgrade.pack_id, dep.version_range
+                ),
+            });
+            dep.version_range = range;
+        }
+    }
+    if !plan.uses_pack && !pinned {
+        return None;
+    }
+    Some(WorkflowMigration {
+        workflow_id: spec.id.to_string(),
+        workflow_name: spec.meta.name.clone(),
+        changes: plan.changes,
+        issues: plan.issues,
+        migrated,
+    })
+}
+
+struct Planner<'a> {
+    upgrade: &'a PackUpgrade,
+    changes: Vec<MigrationChange>,
+    issues: Vec<MigrationIssue>,
+    uses_pack: bool,
+}
+
+impl Planner<'_> {
+    fn graph(&mut self, nodes: &mut [NodeEntry], edges: &mut [EdgeSpec]) {
+        for node in nodes {
+            match node {
+                NodeEntry::Primitive(n) => self.node(n, edges),
+                NodeEntry::Composite(n) => self.graph(&mut n.subgraph.nodes, &mut n.subgraph.edges),
+                NodeEntry::Conditional(n) => {
+                    for body in n
+                        .branches
+                        .iter_mut()
+                        .map(|b| &mut b.body)
+                        .chain(&mut n.default_branch)
+                    {
+                        self.graph(&mut body.nodes, &mut body.edges);
+                    }
+                }
+                NodeEntry::Loop(n) => self.graph(&mut n.body.nodes, &mut n.body.edges),
+                NodeEntry::MapReduce(n) => {
+                    for body in std::iter::once(&mut n.map).chain(&mut n.reduce) {
+                        self.graph(&mut body.nodes, &mut body.edges);
+                    }
+                }
+                // Migrated when the referenced workflow itself is
+                NodeEntry::WorkflowRef(_) => {}
+            }
+        }
+    }
+
+    fn change(&mut self, node: &NodeSpec, description: String) {
+        self.changes.push(MigrationChange {
+            node_id: Some(node.id.clone()),
+            description,
+        });
+    }
+
+    fn issue(&mut self, node: &NodeSpec, code: &'static str, message: String) {
+        self.issues.push(MigrationIssue {
+            node_id: node.id.clone(),
+            code,
+            message,
+        });
+    }
+
+    fn node(&mut self, node: &mut NodeSpec, edges: &mut [EdgeSpec]) {
+        let upgrade = self.upgrade;
+        let (tool_id, version) = match node.tool_ref.split_once('@') {
+            Some((id, version)) => (id.to_string(), Some(version.to_string())),
+            None => (node.tool_ref.clone(), None),
+        };
+        if tool_id.split('/').next() != Some(upgrade.pack_id.as_str()) || !tool_id.contains('/') {
+            return;
+        }
+        self.uses_pack = true;
+        let old_tool = upgrade.old_tools.get(&tool_id);
+
+        let rules: Vec<&MigrationRule> = upgrade
+            .migrations
+            .iter()
+            .filter(|m| {
+                m.tool_id == tool_id
+                    && m.from.as_deref().is_none_or(|range| {
+                        semver::VersionReq::parse(range)
+                            .map(|req| req.matches(&upgrade.from_version))
+                            .unwrap_or(false)
+                    })
+            })
+            .flat_map(|m| &m.rules)
+            .collect();
+        let mut tool_id = tool_id;
+        for rule in rules {
+            self.apply(rule, node, &mut tool_id, edges);
+        }
+
+        let Some(new_tool) = upgrade.new_tools.get(&tool_id) else {
+            self.issue(
+                node,
+                "tool_removed",
+                format!(
+                    "{tool_id} is not part of {}@{}",
+                    upgrade.pack_id, upgrade.to_version
+                ),
+            );
+            return;
+        };
+        self.check_config(node, old_tool, new_tool);
+        self.check_ports(node, old_tool, new_tool, edges);
+
+        let tool_ref = match version {
+            Some(_) => format!("{tool_id}@{}", new_tool.version),
+            None => tool_id,
+        };
+        if tool_ref != node.tool_ref {
+            self.change(
+                node,
+                format!("Tool {} updated to {tool_ref}", node.tool_ref),
+            );
+            node.tool_ref = tool_ref;
+        }
+    }
+
+    fn apply(
+        &mut self,
+        rule: &MigrationRule,
+        node: &mut NodeSpec,
+        tool_id: &mut String,
+        edges: &mut [EdgeSpec],
+    ) {
+        match rule {
+            MigrationRule::RenameConfig { from, to } => {
+                let Some(value) = node.config.remove(from) else {
+                    return;
+                };
+                if node.config.contains_key(to) {
+                    self.issue(
+                        node,
+                        "config_conflict",
+                        format!("'{from}' was renamed to '{to}', which is already set; the old value was dropped"),
+                    );
+                } else {
+                    node.config.insert(to.clone(), value);
+                    self.change(node, format!("Config '{from}' renamed to '{to}'"));
+                }
+            }
+            MigrationRule::RemoveConfig { key } => {
+                if node.config.remove(key).is_some() {
+                    self.change(node, format!("Config '{key}' removed"));
+                }
+            }
+            MigrationRule::SetConfig { key, value } => {
+                if !node.config.contains_key(key) {
+                    node.config.insert(key.clone(), value.clone());
+                    self.change(node, format!("Config '{key}' set to {value}"));
+                }
+            }
+            MigrationRule::MapValue { key, values } => {
+                let Some(current) = node.config.get(key) else {
+                    return;
+                };
+                let text = match current {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                if let Some(value) = values.get(&text) {
+                    self.change(
+                        node,
+                        format!("Config '{key}' changed from {current} to {value}"),
+                    );
+                    node.config.insert(key.clone(), value.clone());
+                }
+            }
+            MigrationRule::RenamePort { from, to } => {
+                let mut renamed = 0;
+                for edge in edges.iter_mut() {
+                    if edge.target_node == node.id {
+                        renamed += rename_port(&mut edge.target_port, from, to) as usize;
+                    }
+                    if edge.source_node == node.id {
+                        renamed += rename_port(&mut edge.source_port, from, to) as usize;
+                    }
+                }
+                if renamed > 0 {
+                    self.change(
+                        node,
+                        format!("Port '{from}' renamed to '{to}' on {renamed} edge(s)"),
+                    );
+                }
+            }
+            MigrationRule::RenameTool { to } => {
+                self.change(node, format!("Tool {tool_id} replaced by {to}"));
+                *tool_id = to.clone();
+            }
+            MigrationRule::Manual { message } => self.issue(node, "manual", message.clone()),
+        }
+    }
+
+    fn check_config(
+        &mut self,
+        node: &NodeSpec,
+        old_tool: Option<&ToolInterface>,
+        new_tool: &ToolInterface,
+    ) {
+        let old_fields = old_tool
+            .map(|t| t.config_schema.as_slice())
+            .unwrap_or_default();
+        for (key, value) in &node.config {
+            let old = old_fields.iter().find(|f| &f.name == key);
+            let Some(new) = new_tool.config_schema.iter().find(|f| &f.name == key) else {
+                // Keys the old schema didn't declare either are the tool's business
+                if old.is_some() {
+                    self.issue(
+                        node,
+                        "config_removed",
+                        format!("Config '{key}' is no longer a setting of the tool"),
+                    );
+                }
+                continue;
+            };
+            if let Some(old) = old.filter(|old| old.field_type != new.field_type) {
+                self.issue(
+                    node,
+                    "config_type_changed",
+                    format!(
+                        "Config '{key}' changed from {:?} to {:?}",
+                        old.field_type, new.field_type
+                    ),
+                );
+            } else if new.field_type == ConfigFieldType::Select
+                && !new.options.is_empty()
+                && !new.options.iter().any(|o| &o.value == value)
+            {
+                self.issue(
+                    node,
+                    "invalid_option",
+                    format!("Config '{key}' value {value} is no longer an option"),
+                );
+            }
+        }
+        for field in new_tool
+            .config_schema
+            .iter()
+            .filter(|f| f.required && f.default_value.is_none())
+        {
+            let was_required = old_fields
+                .iter()
+                .any(|f| f.name == field.name && f.required);
+            if !was_required && !node.config.contains_key(&field.name) {
+                self.issue(
+                    node,
+                    "config_required",
+                    format!("Config '{}' is now required", field.name),
+                );
+            }
+        }
+    }
+
+    fn check_ports(
+        &mut self,
+        node: &NodeSpec,
+        old_tool: Option<&ToolInterface>,
+        new_tool: &ToolInterface,
+        edges: &[EdgeSpec],
+    ) {
+        let data_edges = || edges.iter().filter(|e| e.kind == EdgeKind::Data);
+        for edge in data_edges().filter(|e| e.target_node == node.id) {
+            if !new_tool
+                .input_schema
+                .ports
+                .iter()
+                .any(|p| p.accepts(&edge.target_port))
+            {
+                self.issue(
+                    node,
+                    "port_removed",
+                    format!(
+                        "Input '{}' no longer exists; edge {} is disconnected",
+                        edge.target_port, edge.id
+                    ),
+                );
+            }
+        }
+        for edge in data_edges().filter(|e| e.source_node == node.id) {
+            if !new_tool
+                .output_schema
+                .ports
+                .iter()
+                .any(|p| p.accepts(&edge.source_port))
+            {
+                self.issue(
+                    node,
+                    "port_removed",
+                    format!(
+                        "Output '{}' no longer exists; edge {} is disconnected",
+                        edge.source_port, edge.id
+                    ),
+                );
+            }
+        }
+        let old_inputs = old_tool
+            .map(|t| t.input_schema.ports.as_slice())
+            .unwrap_or_default();
+        for port in new_tool
+            .input_schema
+            .ports
+            .iter()
+            .filter(|p| p.required && p.default_value.is_none())
+        {
+            let was_required = old_inputs.iter().any(|p| p.name == port.name && p.required);
+            let connected =
+                data_edges().any(|e| e.target_node == node.id && port.accepts(&e.target_port));
+            if !was_required && !connected {
+                self.issue(
+                    node,
+                    "input_required",
+                    format!("Input '{}' is now required and not connected", port.name),
+                );
+            }
+        }
+    }
+}
+
+/// Rename `port` if it is `from` or one of its variadic keys.
+fn rename_port(port: &mut String, from: &str, to: &str) -> bool {
+    match split_variadic_port(port) {
+        (base, Some(key)) if base == from => *port = format!("{to}.{key}"),
+        (base, None) if base == from => *port = to.to_string(),
+        _ => return false,
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hb_core::graph::PackDependency;
+    use std::collections::BTreeMap;
+
+    fn tool(id: &str, version: &str, inputs: &[&str], config: Value) -> ToolInterface {
+        serde_json::from_value(serde_json::json!({
+            "tool_id": id,
+            "version": version,
+            "display_name": id,
+            "description": "",
+            "capability_tags": [],
+            "input_schema": { "ports": inputs.iter().map(|p| serde_json::json!({ "name": p, "port_type": "string" })).collect::<Vec<_>>() },
+            "output_schema": { "ports": [{ "name": "chunks", "port_type": "array" }] },
+            "side_effect": "none",
+            "required_permissions": [],
+            "cost_hint": { "time": "fast", "monetary": "free" },
+            "error_model": { "error_types": [], "idempotent": true,
+                             "default_retry": { "max_retries": 0, "backoff_ms": 0, "backoff_multiplier": 1.0 } },
+            "runtime": { "kind": "native" },
+            "config_schema": config,
+        }))
+        .unwrap()
+    }
+
+    fn node(id: &str, tool_ref: &str, config: Value) -> NodeEntry {
+        NodeEntry::Primitive(NodeSpec {
+            id: id.into(),
+            tool_ref: tool_ref.into(),
+            config: config.as_object().unwrap().clone(),
+            ..Default::default()
+        })
+    }
+
+    fn edge(source: &str, source_port: &str, target: &str, target_port: &str) -> EdgeSpec {
+        EdgeSpec {
+            id: format!("{source}-{target}"),
+            source_node: source.into(),
+            source_port: source_port.into(),
+            target_node: target.into(),
+            target_port: target_port.into(),
+            ..Default::default()
+        }
+    }
+
+    fn upgrade() -> PackUpgrade {
+        let old_config = serde_json::json!([
+            { "name": "size", "field_type": "number" },
+            { "name": "mode", "field_type": "select", "options": [{ "label": "Lines", "value": "lines" }, { "label": "Words", "value": "words" }] },
+            { "name": "legacy", "field_type": "boolean" },
+        ]);
+        let new_config = serde_json::json!([
+            { "name": "chunk_size", "field_type": "number" },
+            { "name": "mode", "field_type": "select", "options": [{ "label": "Sentences", "value": "sentences" }, { "label": "Words", "value": "words" }] },
+        ]);
+        PackUpgrade {
+            pack_id: "rag-pack".into(),
+            from_version: semver::Version::new(1, 2, 0),
+            to_version: semver::Version::new(2, 0, 0),
+            old_tools: HashMap::from([
+                (
+                    "rag-pack/chunk".into(),
+                    tool("rag-pack/chunk", "1.2.0", &["text"], old_config),
+                ),
+                (
+                    "rag-pack/old-embed".into(),
+                    tool(
+                        "rag-pack/old-embed",
+                        "1.0.0",
+                        &["text"],
+                        serde_json::json!([]),
+                    ),
+                ),
+            ]),
+            new_tools: HashMap::from([(
+                "rag-pack/chunk".into(),
+                tool("rag-pack/chunk", "2.0.0", &["document"], new_config),
+            )]),
+            migrations: vec![
+                ToolMigration {
+                    tool_id: "rag-pack/chunk".into(),
+                    from: Some("<2.0.0".into()),
+                    rules: vec![
+                        MigrationRule::RenameConfig {
+                            from: "size".into(),
+                            to: "chunk_size".into(),
+                        },
+                        MigrationRule::MapValue {
+                            key: "mode".into(),
+                            values: BTreeMap::from([("lines".into(), "sentences".into())]),
+                        },
+                    ],
+                },
+                // Only for upgrades from 0.x, so not applied
+                ToolMigration {
+                    tool_id: "rag-pack/chunk".into(),
+                    from: Some("<1.0.0".into()),
+                    rules: vec![MigrationRule::Manual {
+                        message: "Re-check chunking".into(),
+                    }],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn declared_rules_apply_and_the_rest_is_flagged() {
+        let mut spec = WorkflowSpec {
+            nodes: vec![
+                node("read", "core-tools/file-read@1.0.0", serde_json::json!({})),
+                node(
+                    "chunk",
+                    "rag-pack/chunk@1.2.0",
+                    serde_json::json!({ "size": 500, "mode": "lines", "legacy": true }),
+                ),
+                node("embed", "rag-pack/old-embed", serde_json::json!({})),
+            ],
+            edges: vec![
+                edge("read", "content", "chunk", "text"),
+                edge("chunk", "chunks", "embed", "text"),
+            ],
+            ..Default::default()
+        };
+        spec.required_packs.push(PackDependency {
+            pack_id: "rag-pack".into(),
+            version_range: "~1.2".into(),
+        });
+
+        let migration = plan(&upgrade(), &spec).unwrap();
+        let NodeEntry::Primitive(chunk) = &migration.migrated.nodes[1] else {
+            panic!()
+        };
+        assert_eq!(chunk.tool_ref, "rag-pack/chunk@2.0.0");
+        assert_eq!(chunk.config["chunk_size"], 500);
+        assert_eq!(chunk.config["mode"], "sentences");
+        assert_eq!(migration.migrated.required_packs[0].version_range, "^2.0.0");
+        assert_eq!(migration.changes.len(), 4);
+
+        let mut issues: Vec<_> = migration
+            .issues
+            .iter()
+            .map(|i| (i.node_id.as_str(), i.code))
+            .collect();
+        issues.sort();
+        assert_eq!(
+            issues,
+            [
+                ("chunk", "config_removed"),
+                ("chunk", "port_removed"),
+                ("embed", "tool_removed")
+            ]
+        );
+        assert!(migration.needs_attention());
+
+        // Workflows that don't use the pack aren't reported
+        let other = WorkflowSpec {
+            nodes: vec![spec.nodes[0].clone()],
+            ..Default::default()
+        };
+        assert_eq!(report(&upgrade(), [&spec, &other]).workflows.len(), 1);
+    }
+
+    #[test]
+    fn renamed_ports_reconnect_their_edges() {
+        let mut upgrade = upgrade();
+        upgrade.migrations[0].rules.push(MigrationRule::RenamePort {
+            from: "text".into(),
+            to: "document".into(),
+        });
+        let spec = WorkflowSpec {
+            nodes: vec![
+                node("read", "core-tools/file-read", serde_json::json!({})),
+                node(
+                    "chunk",
+                    "rag-pack/chunk",
+                    serde_json::json!({ "mode": "words" }),
+                ),
+            ],
+            edges: vec![edge("read", "content", "chunk", "text")],
+            ..Default::default()
+        };
+        let migration = plan(&upgrade, &spec).unwrap();
+        assert_eq!(migration.migrated.edges[0].target_port, "document");
+        // Unversioned references stay unversioned
+        let NodeEntry::Primitive(chunk) = &migration.migrated.nodes[1] else {
+            panic!()
+        };
+        assert_eq!(chunk.tool_ref, "rag-pack/chunk");
+        assert!(!migration.needs_attention(), "{:?}", migration.issues);
+    }
+}
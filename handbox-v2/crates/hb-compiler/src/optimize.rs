@@ -0,0 +1,709 @@
+//! Optimization hints — what recorded runs of a workflow say about where its
+//! time goes.
+//!
+//! Timings are per-node means over the spans of recent executions. The
+//! runner schedules the top-level graph level by level (every node of a
+//! level waits for the slowest node of the previous one), so the estimated
+//! run time is the sum of each level's slowest node, while the critical path
+//! only follows data dependencies. Nodes without history count as instant
+//! and are listed in the report.
+
+use hb_core::graph::{EdgeKind, NodeEntry, SubgraphSpec, WorkflowSpec};
+use hb_core::trace::{ExecutionStatus, NodeSpan};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Runs needed before a cache that never hits is reported.
+const MIN_CACHE_RUNS: u32 = 3;
+
+/// Share of the critical path above which a node is called dominant.
+const DOMINANT_SHARE: f64 = 0.4;
+
+/// Level barrier waits below this are not worth reporting.
+const MIN_SAVING_MS: u64 = 1_000;
+
+/// Recorded timing of one node.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NodeTiming {
+    /// Completed runs, including cache hits.
+    pub runs: u32,
+    pub cache_hits: u32,
+    /// Mean duration of runs that executed; of cache hits when every run
+    /// was one.
+    pub mean_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HintKind {
+    /// Unconnected parts of the graph held back by each other's level
+    /// barriers. Each chain lists its nodes in execution order.
+    ParallelChains {
+        chains: Vec<Vec<String>>,
+        estimated_saving_ms: u64,
+    },
+    /// A node taking a large share of the critical path.
+    CriticalPath { mean_ms: u64, share: f64 },
+    /// A cached node whose cache has never been hit.
+    CacheNeverHits { runs: u32 },
+    /// More concurrent calls to a tool than its rate limit allows.
+    RateLimitedFanOut {
+        tool_id: String,
+        concurrent: u32,
+        /// Sustained rate estimated from history.
+        requests_per_minute: Option<u32>,
+        limit: u32,
+        suggested_concurrency: u32,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OptimizationHint {
+    #[serde(flatten)]
+    pub kind: HintKind,
+    pub node_ids: Vec<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OptimizationReport {
+    /// Executions the timings were taken from.
+    pub executions: usize,
+    pub timings: BTreeMap<String, NodeTiming>,
+    /// Estimated run time under level-by-level scheduling.
+    pub estimated_ms: u64,
+    pub critical_path: Vec<String>,
+    pub critical_path_ms: u64,
+    /// Top-level nodes that have never completed in the sampled runs.
+    pub nodes_without_history: Vec<String>,
+    pub hints: Vec<OptimizationHint>,
+}
+
+/// Aggregate spans into per-node timings.
+pub fn timings(spans: &[NodeSpan]) -> BTreeMap<String, NodeTiming> {
+    #[derive(Default)]
+    struct Acc {
+        runs: u32,
+        cache_hits: u32,
+        ran_ms: Vec<i64>,
+        hit_ms: Vec<i64>,
+    }
+    let mut acc: BTreeMap<&str, Acc> = BTreeMap::new();
+    for span in spans {
+        let hit = span.cache_hit || span.status == ExecutionStatus::CacheHit;
+        if !hit && span.status != ExecutionStatus::Completed {
+            continue;
+        }
+        let a = acc.entry(&span.node_id).or_default();
+        a.runs += 1;
+        if hit {
+            a.cache_hits += 1;
+        }
+        if let Some(ms) = span.duration_ms {
+            if hit { &mut a.hit_ms } else { &mut a.ran_ms }.push(ms.max(0));
+        }
+    }
+    acc.into_iter()
+        .map(|(id, a)| {
+            let samples = if a.ran_ms.is_empty() {
+                &a.hit_ms
+            } else {
+                &a.ran_ms
+            };
+            let mean_ms = (!samples.is_empty())
+                .then(|| samples.iter().sum::<i64>() as u64 / samples.len() as u64);
+            let timing = NodeTiming {
+                runs: a.runs,
+                cache_hits: a.cache_hits,
+                mean_ms,
+            };
+            (id.to_string(), timing)
+        })
+        .collect()
+}
+
+/// Analyze `spec` against the spans of its recent executions. `rate_limits`
+/// maps tool IDs to the requests per minute their backing service accepts.
+pub fn analyze(
+    spec: &WorkflowSpec,
+    spans: &[NodeSpan],
+    rate_limits: &BTreeMap<String, u32>,
+) -> OptimizationReport {
+    let timings = timings(spans);
+    let executions = spans
+        .iter()
+        .map(|s| s.execution_id)
+        .collect::<HashSet<_>>()
+        .len();
+    let duration = |id: &str| timings.get(id).and_then(|t| t.mean_ms).unwrap_or(0);
+    let graph = Graph::new(spec);
+
+    let levels = graph.levels(&graph.ids);
+    let estimated_ms = levels_ms(&levels, &duration);
+    let (critical_path, critical_path_ms) = graph.critical_path(&levels, &duration);
+    let nodes_without_history = graph
+        .ids
+        .iter()
+        .filter(|id| timings.get(**id).and_then(|t| t.mean_ms).is_none())
+        .map(|id| id.to_string())
+        .collect();
+
+    let mut hints = Vec::new();
+    parallel_chains(&graph, estimated_ms, &duration, &mut hints);
+    dominant_nodes(&critical_path, critical_path_ms, &duration, &mut hints);
+    for node in &spec.nodes {
+        unhit_caches(node, &timings, &mut hints);
+    }
+    sibling_fan_outs(spec, &levels, rate_limits, &mut hints);
+    for node in &spec.nodes {
+        map_fan_outs(node, &timings, rate_limits, &mut hints);
+    }
+
+    OptimizationReport {
+        executions,
+        timings,
+        estimated_ms,
+        critical_path,
+        critical_path_ms,
+        nodes_without_history,
+        hints,
+    }
+}
+
+/// The top-level data-flow graph, as the runner schedules it.
+struct Graph<'a> {
+    ids: Vec<&'a str>,
+    successors: HashMap<&'a str, Vec<&'a str>>,
+    predecessors: HashMap<&'a str, Vec<&'a str>>,
+}
+
+impl<'a> Graph<'a> {
+    fn new(spec: &'a WorkflowSpec) -> Self {
+        let ids: Vec<&str> = spec.nodes.iter().map(|n| n.id()).collect();
+        let known: HashSet<&str> = ids.iter().copied().collect();
+        let mut successors: HashMap<&str, Vec<&str>> = HashMap::new();
+        let mut predecessors: HashMap<&str, Vec<&str>> = HashMap::new();
+        for edge in spec.edges.iter().filter(|e| e.kind == EdgeKind::Data) {
+            let (from, to) = (edge.source_node.as_str(), edge.target_node.as_str());
+            if !known.contains(from) || !known.contains(to) {
+                continue;
+            }
+            let next = successors.entry(from).or_default();
+            if !next.contains(&to) {
+                next.push(to);
+                predecessors.entry(to).or_default().push(from);
+            }
+        }
+        Self {
+            ids,
+            successors,
+            predecessors,
+        }
+    }
+
+    /// Kahn levels of the subgraph induced by `nodes`, in their order.
+    fn levels(&self, nodes: &[&'a str]) -> Vec<Vec<&'a str>> {
+        let members: HashSet<&str> = nodes.iter().copied().collect();
+        let mut in_degree: HashMap<&str, usize> = nodes
+            .iter()
+            .map(|id| {
+                let preds = self
+                    .predecessors
+                    .get(id)
+                    .map_or(0, |p| p.iter().filter(|p| members.contains(*p)).count());
+                (*id, preds)
+            })
+            .collect();
+        let mut current: Vec<&str> = nodes
+            .iter()
+            .copied()
+            .filter(|id| in_degree[id] == 0)
+            .collect();
+        let mut levels = Vec::new();
+        while !current.is_empty() {
+            let mut next = Vec::new();
+            for id in &current {
+                for succ in self.successors.get(id).into_iter().flatten() {
+                    if let Some(d) = in_degree.get_mut(succ) {
+                        *d -= 1;
+                        if *d == 0 {
+                            next.push(*succ);
+                        }
+                    }
+                }
+            }
+            levels.push(std::mem::replace(&mut current, next));
+        }
+        levels
+    }
+
+    /// Longest path by duration along data edges.
+    fn critical_path(
+        &self,
+        levels: &[Vec<&'a str>],
+        duration: &impl Fn(&str) -> u64,
+    ) -> (Vec<String>, u64) {
+        let mut finish: HashMap<&str, u64> = HashMap::new();
+        let mut via: HashMap<&str, &str> = HashMap::new();
+        for id in levels.iter().flatten() {
+            let best = self
+                .predecessors
+                .get(id)
+                .into_iter()
+                .flatten()
+                .filter_map(|p| finish.get(p).map(|f| (*p, *f)))
+                .max_by_key(|(_, f)| *f);
+            let start = best.map_or(0, |(_, f)| f);
+            if let Some((p, _)) = best {
+                via.insert(id, p);
+            }
+            finish.insert(id, start + duration(id));
+        }
+        let Some((mut last, total)) = levels
+            .iter()
+            .flatten()
+            .map(|id| (*id, finish[id]))
+            .max_by_key(|(_, f)| *f)
+        else {
+            return (Vec::new(), 0);
+        };
+        let mut path = vec![last.to_string()];
+        while let Some(prev) = via.get(last) {
+            path.push(prev.to_string());
+            last = prev;
+        }
+        path.reverse();
+        (path, total)
+    }
+
+    /// Weakly connected components, each in scheduling order.
+    fn components(&self) -> Vec<Vec<&'a str>> {
+        let mut seen = HashSet::new();
+        let mut components = Vec::new();
+        for id in &self.ids {
+            if !seen.insert(*id) {
+                continue;
+            }
+            let mut members = HashSet::from([*id]);
+            let mut queue = vec![*id];
+            while let Some(n) = queue.pop() {
+                let neighbours = self.successors.get(n).into_iter().flatten();
+                for m in neighbours.chain(self.predecessors.get(n).into_iter().flatten()) {
+                    if seen.insert(*m) {
+                        members.insert(*m);
+                        queue.push(*m);
+                    }
+                }
+            }
+            let ordered: Vec<&str> = self
+                .ids
+                .iter()
+                .copied()
+                .filter(|n| members.contains(n))
+                .collect();
+            components.push(self.levels(&ordered).concat());
+        }
+        components
+    }
+}
+
+fn levels_ms(levels: &[Vec<&str>], duration: &impl Fn(&str) -> u64) -> u64 {
+    levels
+        .iter()
+        .map(|level| level.iter().map(|id| duration(id)).max().unwrap_or(0))
+        .sum()
+}
+
+fn secs(ms: u64) -> String {
+    format!("{:.1}s", ms as f64 / 1000.0)
+}
+
+/// Independent chains run level by level in lockstep; report how much
+/// sooner the workflow would finish if each advanced on its own.
+fn parallel_chains(
+    graph: &Graph,
+    estimated_ms: u64,
+    duration: &impl Fn(&str) -> u64,
+    hints: &mut Vec<OptimizationHint>,
+) {
+    let components = graph.components();
+    if components.len() < 2 {
+        return;
+    }
+    let alone_ms = components
+        .iter()
+        .map(|c| levels_ms(&graph.levels(c), duration))
+        .max()
+        .unwrap_or(0);
+    let saving = estimated_ms.saturating_sub(alone_ms);
+    if saving < MIN_SAVING_MS {
+        return;
+    }
+    let chains: Vec<Vec<String>> = components
+        .iter()
+        .map(|c| c.iter().map(|id| id.to_string()).collect())
+        .collect();
+    hints.push(OptimizationHint {
+        node_ids: chains.concat(),
+        message: format!(
+            "{} independent chains wait for each other at every level; running them \
+             side by side would finish about {} sooner",
+            chains.len(),
+            secs(saving)
+        ),
+        kind: HintKind::ParallelChains {
+            chains,
+            estimated_saving_ms: saving,
+        },
+    });
+}
+
+fn dominant_nodes(
+    critical_path: &[String],
+    critical_path_ms: u64,
+    duration: &impl Fn(&str) -> u64,
+    hints: &mut Vec<OptimizationHint>,
+) {
+    if critical_path.len() < 2 || critical_path_ms == 0 {
+        return;
+    }
+    for id in critical_path {
+        let mean_ms = duration(id);
+        let share = mean_ms as f64 / critical_path_ms as f64;
+        if share < DOMINANT_SHARE {
+            continue;
+        }
+        hints.push(OptimizationHint {
+            kind: HintKind::CriticalPath { mean_ms, share },
+            node_ids: vec![id.clone()],
+            message: format!(
+                "'{id}' takes {} of the critical path ({}); speeding it up shortens every run",
+                format_args!("{:.0}%", share * 100.0),
+                secs(mean_ms)
+            ),
+        });
+    }
+}
+
+fn unhit_caches(
+    node: &NodeEntry,
+    timings: &BTreeMap<String, NodeTiming>,
+    hints: &mut Vec<OptimizationHint>,
+) {
+    match node {
+        NodeEntry::Primitive(n) => {
+            let cached = n.cache.as_ref().is_none_or(|c| c.enabled);
+            let Some(timing) = timings.get(&n.id) else {
+                return;
+            };
+            if n.disabled || !cached || timing.cache_hits > 0 || timing.runs < MIN_CACHE_RUNS {
+                return;
+            }
+            hints.push(OptimizationHint {
+                kind: HintKind::CacheNeverHits { runs: timing.runs },
+                node_ids: vec![n.id.clone()],
+                message: format!(
+                    "'{}' missed the cache in all {} runs; its inputs change every time, so \
+                     disabling its cache saves the lookup and storage",
+                    n.id, timing.runs
+                ),
+            });
+        }
+        _ => {
+            for subgraph in subgraphs(node) {
+                for inner in &subgraph.nodes {
+                    unhit_caches(inner, timings, hints);
+                }
+            }
+        }
+    }
+}
+
+/// Nodes of one level calling the same rate-limited tool all start at once.
+fn sibling_fan_outs(
+    spec: &WorkflowSpec,
+    levels: &[Vec<&str>],
+    rate_limits: &BTreeMap<String, u32>,
+    hints: &mut Vec<OptimizationHint>,
+) {
+    let tools: HashMap<&str, &str> = spec
+        .nodes
+        .iter()
+        .filter_map(|n| match n {
+            NodeEntry::Primitive(p) if !p.disabled => Some((p.id.as_str(), tool_id(&p.tool_ref))),
+            _ => None,
+        })
+        .collect();
+    for level in levels {
+        let mut by_tool: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for id in level {
+            if let Some(tool) = tools.get(id) {
+                by_tool.entry(tool).or_default().push(id.to_string());
+            }
+        }
+        for (tool, node_ids) in by_tool {
+            let Some(&limit) = rate_limits.get(tool) else {
+                continue;
+            };
+            let concurrent = node_ids.len() as u32;
+            if concurrent <= limit {
+                continue;
+            }
+            hints.push(OptimizationHint {
+                message: format!(
+                    "{concurrent} '{tool}' nodes start together, above its limit of {limit} \
+                     requests per minute; chain some of them after the others"
+                ),
+                kind: HintKind::RateLimitedFanOut {
+                    tool_id: tool.to_string(),
+                    concurrent,
+                    requests_per_minute: None,
+                    limit,
+                    suggested_concurrency: limit.max(1),
+                },
+                node_ids,
+            });
+        }
+    }
+}
+
+/// Map-reduce shards call the tools of their `map` body `max_concurrency`
+/// at a time.
+fn map_fan_outs(
+    node: &NodeEntry,
+    timings: &BTreeMap<String, NodeTiming>,
+    rate_limits: &BTreeMap<String, u32>,
+    hints: &mut Vec<OptimizationHint>,
+) {
+    for subgraph in subgraphs(node) {
+        for inner in &subgraph.nodes {
+            map_fan_outs(inner, timings, rate_limits, hints);
+        }
+    }
+    let NodeEntry::MapReduce(mr) = node else {
+        return;
+    };
+    let mut calls: BTreeMap<&str, u32> = BTreeMap::new();
+    for n in &mr.map.nodes {
+        if let NodeEntry::Primitive(p) = n {
+            if !p.disabled {
+                *calls.entry(tool_id(&p.tool_ref)).or_default() += 1;
+            }
+        }
+    }
+    // Shards run their body nodes one after another
+    let shard_ms: Option<u64> = mr
+        .map
+        .nodes
+        .iter()
+        .map(|n| timings.get(n.id()).and_then(|t| t.mean_ms))
+        .sum();
+    let concurrent = mr.max_concurrency.max(1);
+
+    for (tool, per_shard) in calls {
+        let Some(&limit) = rate_limits.get(tool) else {
+            continue;
+        };
+        let (requests_per_minute, suggested) = match shard_ms.filter(|ms| *ms > 0) {
+            Some(ms) => {
+                let per_minute = concurrent as u64 * per_shard as u64 * 60_000 / ms;
+                let fits = limit as u64 * ms / (per_shard as u64 * 60_000);
+                (Some(per_minute.min(u32::MAX as u64) as u32), fits as u32)
+            }
+            None => (None, limit),
+        };
+        let suggested = suggested.clamp(1, concurrent);
+        let exceeds = match requests_per_minute {
+            Some(rate) => rate > limit,
+            None => concurrent > limit,
+        };
+        if !exceeds || suggested >= concurrent {
+            continue;
+        }
+        let rate = match requests_per_minute {
+            Some(rate) => format!("about {rate} requests per minute"),
+            None => format!("{concurrent} requests at once"),
+        };
+        hints.push(OptimizationHint {
+            kind: HintKind::RateLimitedFanOut {
+                tool_id: tool.to_string(),
+                concurrent,
+                requests_per_minute,
+                limit,
+                suggested_concurrency: suggested,
+            },
+            node_ids: vec![mr.id.clone()],
+            message: format!(
+                "'{}' sends {rate} to '{tool}', above its limit of {limit} per minute; \
+                 lower max_concurrency to {suggested}",
+                mr.id
+            ),
+        });
+    }
+}
+
+fn subgraphs(node: &NodeEntry) -> Vec<&SubgraphSpec> {
+    match node {
+        NodeEntry::Primitive(_) | NodeEntry::WorkflowRef(_) => Vec::new(),
+        NodeEntry::Composite(n) => vec![&n.subgraph],
+        NodeEntry::Conditional(n) => n
+            .branches
+            .iter()
+            .map(|b| &b.body)
+            .chain(n.default_branch.as_ref())
+            .collect(),
+        NodeEntry::Loop(n) => vec![&n.body],
+        NodeEntry::MapReduce(n) => std::iter::once(&n.map).chain(n.reduce.as_ref()).collect(),
+    }
+}
+
+fn tool_id(tool_ref: &str) -> &str {
+    tool_ref.split('@').next().unwrap_or(tool_ref)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use hb_core::trace::ExecutionEnvironment;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn span(execution_id: Uuid, node_id: &str, ms: i64, cache_hit: bool) -> NodeSpan {
+        NodeSpan {
+            span_id: Uuid::new_v4(),
+            execution_id,
+            node_id: node_id.into(),
+            tool_ref: String::new(),
+            input_json: json!({}),
+            output_json: None,
+            config_json: json!({}),
+            started_at: Utc::now(),
+            completed_at: None,
+            duration_ms: Some(ms),
+            status: if cache_hit {
+                ExecutionStatus::CacheHit
+            } else {
+                ExecutionStatus::Completed
+            },
+            error: None,
+            cache_hit,
+            environment: ExecutionEnvironment {
+                platform_version: String::new(),
+                os: String::new(),
+                tool_version: String::new(),
+                extra: Default::default(),
+            },
+        }
+    }
+
+    fn node(id: &str, tool: &str) -> serde_json::Value {
+        json!({ "kind": "primitive", "id": id, "tool_ref": format!("{tool}@1.0.0") })
+    }
+
+    fn edge(from: &str, to: &str) -> serde_json::Value {
+        json!({ "source_node": from, "source_port": "out", "target_node": to, "target_port": "in" })
+    }
+
+    #[test]
+    fn reports_chains_bottlenecks_caches_and_fan_outs() {
+        // a1 (5s) → a2 (1s)    b1 (1s) → b2 (5s)    fan (map over llm)
+        let spec: WorkflowSpec = serde_json::from_value(json!({
+            "version": "0.1.0",
+            "meta": { "name": "", "description": "" },
+            "nodes": [
+                node("a1", "t/slow"),
+                node("a2", "t/llm"),
+                node("b1", "t/llm"),
+                node("b2", "t/slow"),
+                { "kind": "map_reduce", "id": "fan", "max_concurrency": 8,
+                  "map": { "nodes": [node("ask", "t/llm")], "edges": [] } },
+            ],
+            "edges": [edge("a1", "a2"), edge("b1", "b2")],
+        }))
+        .unwrap();
+
+        let mut spans = Vec::new();
+        for _ in 0..3 {
+            let ex = Uuid::new_v4();
+            spans.extend([
+                span(ex, "a1", 5_000, false),
+                span(ex, "a2", 1_000, false),
+                span(ex, "b1", 1_000, false),
+                span(ex, "b2", 5_000, false),
+                span(ex, "ask", 2_000, false),
+                span(ex, "fan", 2_000, true),
+            ]);
+        }
+        let limits = BTreeMap::from([("t/llm".to_string(), 60), ("t/slow".to_string(), 10)]);
+        let report = analyze(&spec, &spans, &limits);
+
+        assert_eq!(report.executions, 3);
+        // Level 1 waits for a1, level 2 for b2
+        assert_eq!(report.estimated_ms, 10_000);
+        assert_eq!(report.critical_path_ms, 6_000);
+        assert!(report.nodes_without_history.is_empty());
+
+        let chains = report
+            .hints
+            .iter()
+            .find_map(|h| match &h.kind {
+                HintKind::ParallelChains {
+                    chains,
+                    estimated_saving_ms,
+                } => Some((chains.len(), *estimated_saving_ms)),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(chains, (3, 4_000));
+
+        let dominant: Vec<_> = report
+            .hints
+            .iter()
+            .filter(|h| matches!(h.kind, HintKind::CriticalPath { .. }))
+            .map(|h| h.node_ids[0].as_str())
+            .collect();
+        assert_eq!(dominant.len(), 1);
+
+        let unhit: HashSet<_> = report
+            .hints
+            .iter()
+            .filter(|h| matches!(h.kind, HintKind::CacheNeverHits { .. }))
+            .map(|h| h.node_ids[0].as_str())
+            .collect();
+        assert_eq!(unhit, HashSet::from(["a1", "a2", "b1", "b2", "ask"]));
+
+        // 8 shards of one 2s call each ≈ 240/min against a limit of 60
+        let fan_out = report
+            .hints
+            .iter()
+            .find_map(|h| match &h.kind {
+                HintKind::RateLimitedFanOut {
+                    requests_per_minute,
+                    suggested_concurrency,
+                    ..
+                } => Some((*requests_per_minute, *suggested_concurrency)),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(fan_out, (Some(240), 2));
+    }
+
+    #[test]
+    fn connected_graph_without_history_has_no_chain_hint() {
+        let spec: WorkflowSpec = serde_json::from_value(json!({
+            "version": "0.1.0",
+            "meta": { "name": "", "description": "" },
+            "nodes": [node("a", "t/x"), node("b", "t/x"), node("c", "t/x")],
+            "edges": [edge("a", "b"), edge("a", "c")],
+        }))
+        .unwrap();
+        let limits = BTreeMap::from([("t/x".to_string(), 1)]);
+        let report = analyze(&spec, &[], &limits);
+        assert_eq!(report.nodes_without_history, ["a", "b", "c"]);
+        assert_eq!(report.estimated_ms, 0);
+        // b and c start together against a limit of 1
+        assert_eq!(report.hints.len(), 1);
+        assert_eq!(report.hints[0].node_ids, ["b", "c"]);
+    }
+}
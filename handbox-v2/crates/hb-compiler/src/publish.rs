@@ -0,0 +1,580 @@
+//! Publish checks — what a workflow must pass before it goes to the
+//! marketplace.
+//!
+//! Each check produces findings; an error finding fails its check and
+//! blocks publishing, warnings are reported only. Lint, secret scanning and
+//! the license check are static; validation findings come from
+//! [`crate::diagnostics`] and test outcomes from running the workflow's
+//! [`WorkflowTest`](hb_core::graph::WorkflowTest)s, both done by the caller.
+
+use crate::diagnostics::{Severity, ValidationUpdate};
+use hb_core::graph::{NodeEntry, NodeSpec, WorkflowSpec};
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::OnceLock;
+
+/// Object keys whose string values must not be embedded (matched on the
+/// lowercased key, whole or as a `_`-separated suffix).
+const SECRET_KEYS: &[&str] = &[
+    "api_key",
+    "apikey",
+    "secret",
+    "client_secret",
+    "password",
+    "passwd",
+    "token",
+    "access_token",
+    "accesstoken",
+    "auth_token",
+    "authorization",
+    "private_key",
+    "credentials",
+];
+
+/// Licenses under which a pack can't be redistributed with a workflow.
+const RESTRICTED_LICENSES: &[&str] = &["proprietary", "unlicensed", "commercial", "none"];
+
+/// Copyleft licenses that bind a workflow published under another license.
+const COPYLEFT_PREFIXES: &[&str] = &["gpl", "agpl"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckKind {
+    Lint,
+    Validation,
+    Secrets,
+    Licenses,
+    Tests,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Passed,
+    Warning,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    /// Stable machine-readable code, e.g. "embedded_secret".
+    pub code: &'static str,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_id: Option<String>,
+}
+
+impl Finding {
+    pub fn error(code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            code,
+            message: message.into(),
+            node_id: None,
+        }
+    }
+
+    pub fn warning(code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            ..Self::error(code, message)
+        }
+    }
+
+    pub fn on_node(mut self, node_id: &str) -> Self {
+        self.node_id = Some(node_id.to_string());
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    pub check: CheckKind,
+    pub status: CheckStatus,
+    pub findings: Vec<Finding>,
+    /// Why the check did not run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skipped_reason: Option<String>,
+}
+
+impl CheckResult {
+    pub fn from_findings(check: CheckKind, findings: Vec<Finding>) -> Self {
+        let status = if findings.iter().any(|f| f.severity == Severity::Error) {
+            CheckStatus::Failed
+        } else if findings.is_empty() {
+            CheckStatus::Passed
+        } else {
+            CheckStatus::Warning
+        };
+        Self {
+            check,
+            status,
+            findings,
+            skipped_reason: None,
+        }
+    }
+
+    pub fn skipped(check: CheckKind, reason: impl Into<String>) -> Self {
+        Self {
+            check,
+            status: CheckStatus::Skipped,
+            findings: Vec::new(),
+            skipped_reason: Some(reason.into()),
+        }
+    }
+}
+
+/// Outcome of the publish pipeline.
+#[derive(Debug, Clone, Serialize)]
+pub struct PublishReport {
+    /// False when any check failed; publishing is refused.
+    pub passed: bool,
+    pub checks: Vec<CheckResult>,
+    pub error_count: usize,
+    pub warning_count: usize,
+}
+
+impl PublishReport {
+    pub fn new(checks: Vec<CheckResult>) -> Self {
+        let count = |severity| {
+            checks
+                .iter()
+                .flat_map(|c| &c.findings)
+                .filter(|f| f.severity == severity)
+                .count()
+        };
+        Self {
+            passed: checks.iter().all(|c| c.status != CheckStatus::Failed),
+            error_count: count(Severity::Error),
+            warning_count: count(Severity::Warning),
+            checks,
+        }
+    }
+
+    /// One line per error, for error messages.
+    pub fn summary(&self) -> String {
+        self.checks
+            .iter()
+            .flat_map(|c| &c.findings)
+            .filter(|f| f.severity == Severity::Error)
+            .map(|f| match &f.node_id {
+                Some(node) => format!("{node}: {}", f.message),
+                None => f.message.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+/// Primitive nodes at any depth.
+fn primitives(nodes: &[NodeEntry]) -> Vec<&NodeSpec> {
+    let mut out = Vec::new();
+    for node in nodes {
+        match node {
+            NodeEntry::Primitive(n) => out.push(n),
+            NodeEntry::Composite(c) => out.extend(primitives(&c.subgraph.nodes)),
+            NodeEntry::Loop(l) => out.extend(primitives(&l.body.nodes)),
+            NodeEntry::MapReduce(m) => out.extend(primitives(&m.map.nodes)),
+            NodeEntry::Conditional(c) => {
+                for branch in &c.branches {
+                    out.extend(primitives(&branch.body.nodes));
+                }
+                if let Some(default) = &c.default_branch {
+                    out.extend(primitives(&default.nodes));
+                }
+            }
+            NodeEntry::WorkflowRef(_) => {}
+        }
+    }
+    out
+}
+
+/// Publishing hygiene: metadata, leftovers from editing, unpinned tools and
+/// packs used but not declared.
+pub fn lint(spec: &WorkflowSpec) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    if spec.meta.name.trim().is_empty() {
+        findings.push(Finding::error("missing_name", "The workflow has no name"));
+    }
+    if spec.meta.description.trim().is_empty() {
+        findings.push(Finding::warning(
+            "missing_description",
+            "The workflow has no description",
+        ));
+    }
+    if spec.nodes.is_empty() {
+        findings.push(Finding::error(
+            "empty_workflow",
+            "The workflow has no nodes",
+        ));
+    }
+
+    if spec.nodes.len() > 1 {
+        let connected: HashSet<&str> = spec
+            .edges
+            .iter()
+            .flat_map(|e| [e.source_node.as_str(), e.target_node.as_str()])
+            .collect();
+        for node in &spec.nodes {
+            if !connected.contains(node.id()) {
+                findings.push(
+                    Finding::warning("isolated_node", "Node is not connected to the workflow")
+                        .on_node(node.id()),
+                );
+            }
+        }
+    }
+
+    let declared: HashSet<&str> = spec
+        .required_packs
+        .iter()
+        .map(|p| p.pack_id.as_str())
+        .collect();
+    let mut undeclared = BTreeMap::new();
+    for node in primitives(&spec.nodes) {
+        if node.disabled {
+            findings.push(
+                Finding::warning("disabled_node", "Disabled node left in the workflow")
+                    .on_node(&node.id),
+            );
+        }
+        if !node.tool_ref.contains('@') {
+            findings.push(
+                Finding::warning(
+                    "unpinned_tool",
+                    format!("'{}' has no version; pin it with @version", node.tool_ref),
+                )
+                .on_node(&node.id),
+            );
+        }
+        if let Some((pack, _)) = node.tool_ref.split_once('/') {
+            if !declared.contains(pack) {
+                undeclared.entry(pack).or_insert(node.id.as_str());
+            }
+        }
+    }
+    for (pack, node_id) in undeclared {
+        findings.push(
+            Finding::warning(
+                "undeclared_pack",
+                format!("Pack '{pack}' is used but not listed in required packs"),
+            )
+            .on_node(node_id),
+        );
+    }
+    findings
+}
+
+/// Graph diagnostics as findings.
+pub fn validation(update: &ValidationUpdate) -> Vec<Finding> {
+    update
+        .diagnostics
+        .iter()
+        .map(|d| Finding {
+            severity: d.severity,
+            code: d.code,
+            message: d.message.clone(),
+            node_id: Some(d.node_id.clone()),
+        })
+        .collect()
+}
+
+/// Patterns of API keys and tokens pasted into text, shared with the debug
+/// bundle redactor so both catch the same secrets.
+pub const TOKEN_PATTERNS: &[&str] = &[
+    r"sk-[A-Za-z0-9_-]{16,}",
+    r"AKIA[0-9A-Z]{16}",
+    r"gh[pousr]_[A-Za-z0-9]{20,}",
+    r"xox[abpr]-[A-Za-z0-9-]{10,}",
+    r"-----BEGIN [A-Z ]*PRIVATE KEY-----",
+    r"(?i)bearer\s+[A-Za-z0-9._~+/-]{8,}=*",
+];
+
+fn token_patterns() -> &'static [Regex] {
+    static PATTERNS: OnceLock<Vec<Regex>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        TOKEN_PATTERNS
+            .iter()
+            .map(|p| Regex::new(p).expect("valid pattern"))
+            .collect()
+    })
+}
+
+fn home_path_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"(?i)(?:/home/|/Users/|[a-z]:\\Users\\)[^/\\\s]+").expect("valid pattern")
+    })
+}
+
+fn is_secret_key(key: &str) -> bool {
+    let key = key.to_lowercase().replace('-', "_");
+    SECRET_KEYS
+        .iter()
+        .any(|k| key == *k || key.ends_with(&format!("_{k}")))
+}
+
+/// Values that name a secret rather than contain it.
+fn is_reference(value: &str) -> bool {
+    let value = value.trim();
+    value.is_empty() || value.starts_with("{{") || value.starts_with("${")
+}
+
+fn is_path_key(key: &str) -> bool {
+    let key = key.to_lowercase();
+    ["path", "file", "dir", "folder"]
+        .iter()
+        .any(|k| key.contains(k))
+}
+
+fn is_absolute_path(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    value.starts_with('/')
+        || (bytes.len() > 2 && bytes[0].is_ascii_alphabetic() && &value[1..3] == ":\\")
+}
+
+/// Embedded keys and tokens, and local paths that would not exist (or would
+/// leak a user name) on another machine. `{{env:NAME}}` references are fine.
+pub fn scan_secrets(spec: &WorkflowSpec) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    if let Ok(value) = serde_json::to_value(spec) {
+        scan_value(&value, "", None, &mut findings);
+    }
+    findings
+}
+
+fn scan_value(value: &Value, key: &str, node: Option<&str>, out: &mut Vec<Finding>) {
+    let finding = |f: Finding| match node {
+        Some(node) => f.on_node(node),
+        None => f,
+    };
+    match value {
+        Value::String(s) => {
+            if is_secret_key(key) && !is_reference(s) {
+                out.push(finding(Finding::error(
+                    "embedded_secret",
+                    format!("'{key}' holds a value; use an {{{{env:NAME}}}} reference"),
+                )));
+            } else if token_patterns().iter().any(|p| p.is_match(s)) {
+                out.push(finding(Finding::error(
+                    "embedded_token",
+                    format!("'{key}' contains what looks like an access token"),
+                )));
+            }
+            if home_path_pattern().is_match(s) {
+                out.push(finding(Finding::error(
+                    "local_path",
+                    format!("'{key}' contains a path inside a user's home directory"),
+                )));
+            } else if is_path_key(key) && is_absolute_path(s) {
+                out.push(finding(Finding::warning(
+                    "absolute_path",
+                    format!("'{key}' is an absolute path that may not exist elsewhere"),
+                )));
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                // Entries of a `nodes` list own what is inside them
+                let item_node = (key == "nodes")
+                    .then(|| item.get("id").and_then(Value::as_str))
+                    .flatten()
+                    .or(node);
+                scan_value(item, key, item_node, out);
+            }
+        }
+        Value::Object(map) => {
+            for (k, v) in map {
+                scan_value(v, k, node, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Check the licenses of the workflow and its required packs. `packs` maps
+/// installed packs to the license in their manifest.
+pub fn check_licenses(
+    spec: &WorkflowSpec,
+    license: &str,
+    packs: &HashMap<String, String>,
+) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let license = license.trim();
+    if license.is_empty() {
+        findings.push(Finding::error(
+            "missing_license",
+            "The workflow has no license",
+        ));
+    }
+    let own = license.to_lowercase();
+    for dependency in &spec.required_packs {
+        let pack = &dependency.pack_id;
+        let Some(pack_license) = packs.get(pack).map(|l| l.trim()) else {
+            findings.push(Finding::error(
+                "unknown_pack_license",
+                format!("Pack '{pack}' is not installed; its license can't be checked"),
+            ));
+            continue;
+        };
+        let lower = pack_license.to_lowercase();
+        if lower.is_empty() {
+            findings.push(Finding::error(
+                "missing_pack_license",
+                format!("Pack '{pack}' declares no license"),
+            ));
+        } else if RESTRICTED_LICENSES.contains(&lower.as_str()) {
+            findings.push(Finding::error(
+                "restricted_license",
+                format!("Pack '{pack}' is {pack_license} and can't be redistributed"),
+            ));
+        } else if let Some(family) = COPYLEFT_PREFIXES.iter().find(|p| lower.starts_with(*p)) {
+            if !own.starts_with(family) {
+                findings.push(Finding::warning(
+                    "copyleft_license",
+                    format!("Pack '{pack}' is {pack_license}; the workflow is {license}"),
+                ));
+            }
+        }
+    }
+    findings
+}
+
+/// Whether `actual` has everything in `expected`: objects may have more
+/// fields, other values must be equal.
+pub fn output_matches(expected: &Value, actual: &Value) -> bool {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => expected
+            .iter()
+            .all(|(k, v)| actual.get(k).is_some_and(|a| output_matches(v, a))),
+        _ => expected == actual,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hb_core::graph::{EdgeSpec, PackDependency};
+    use serde_json::json;
+
+    fn spec(nodes: Value) -> WorkflowSpec {
+        serde_json::from_value(json!({
+            "version": "0.1.0",
+            "meta": { "name": "Report", "description": "" },
+            "nodes": nodes,
+            "edges": [],
+        }))
+        .unwrap()
+    }
+
+    fn codes(findings: &[Finding]) -> Vec<(&str, Option<&str>)> {
+        findings
+            .iter()
+            .map(|f| (f.code, f.node_id.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn lint_and_secret_scan() {
+        let mut spec = spec(json!([
+            { "kind": "primitive", "id": "fetch", "tool_ref": "web/http-get@1.0.0",
+              "config": {
+                  "api_key": "{{env:API_KEY}}",
+                  "headers": { "Authorization": "Bearer abcdefgh12345678" },
+                  "max_tokens": 512,
+              } },
+            { "kind": "primitive", "id": "save", "tool_ref": "core-tools/file-write",
+              "disabled": true,
+              "config": { "path": "/home/kim/reports/out.md", "out_dir": "/srv/reports" } },
+            { "kind": "primitive", "id": "extra", "tool_ref": "core-tools/file-read@1.0.0",
+              "config": { "db_password": "hunter2!" } },
+        ]));
+        spec.edges.push(EdgeSpec {
+            id: "e1".into(),
+            source_node: "fetch".into(),
+            source_port: "body".into(),
+            target_node: "save".into(),
+            target_port: "content".into(),
+            ..Default::default()
+        });
+        spec.required_packs.push(PackDependency {
+            pack_id: "core-tools".into(),
+            version_range: "^1".into(),
+        });
+
+        assert_eq!(
+            codes(&lint(&spec)),
+            [
+                ("missing_description", None),
+                ("isolated_node", Some("extra")),
+                ("disabled_node", Some("save")),
+                ("unpinned_tool", Some("save")),
+                ("undeclared_pack", Some("fetch")),
+            ]
+        );
+        assert_eq!(
+            codes(&scan_secrets(&spec)),
+            [
+                ("embedded_secret", Some("fetch")),
+                // Config keys are visited in sorted order
+                ("absolute_path", Some("save")),
+                ("local_path", Some("save")),
+                ("embedded_secret", Some("extra")),
+            ]
+        );
+    }
+
+    #[test]
+    fn license_check_and_report() {
+        let mut spec = spec(json!([]));
+        for pack in ["core-tools", "gis", "vendor", "missing"] {
+            spec.required_packs.push(PackDependency {
+                pack_id: pack.into(),
+                version_range: "*".into(),
+            });
+        }
+        let packs = HashMap::from([
+            ("core-tools".to_string(), "MIT".to_string()),
+            ("gis".to_string(), "GPL-3.0".to_string()),
+            ("vendor".to_string(), "Proprietary".to_string()),
+        ]);
+        let findings = check_licenses(&spec, "MIT", &packs);
+        assert_eq!(
+            codes(&findings),
+            [
+                ("copyleft_license", None),
+                ("restricted_license", None),
+                ("unknown_pack_license", None),
+            ]
+        );
+        assert!(check_licenses(&spec, "GPL-3.0-or-later", &packs)
+            .iter()
+            .all(|f| f.code != "copyleft_license"));
+
+        let report = PublishReport::new(vec![
+            CheckResult::from_findings(CheckKind::Lint, lint(&spec)),
+            CheckResult::from_findings(CheckKind::Licenses, findings),
+            CheckResult::skipped(CheckKind::Tests, "No test cases"),
+        ]);
+        assert!(!report.passed);
+        assert_eq!(report.checks[0].status, CheckStatus::Failed); // no nodes
+        assert_eq!((report.error_count, report.warning_count), (3, 2));
+        assert!(report.summary().contains("Pack 'vendor' is Proprietary"));
+    }
+
+    #[test]
+    fn outputs_match_by_subset() {
+        let actual = json!({ "rows": 3, "meta": { "sheet": "A", "cols": 2 } });
+        assert!(output_matches(
+            &json!({ "meta": { "sheet": "A" } }),
+            &actual
+        ));
+        assert!(!output_matches(&json!({ "rows": 4 }), &actual));
+        assert!(!output_matches(&json!({ "missing": null }), &actual));
+        assert!(output_matches(&json!([1, 2]), &json!([1, 2])));
+    }
+}
@@ -1,10 +1,13 @@
 //! Slot Decomposer — extract entities from a prompt into named slots.
 
 use crate::classifier::TaskType;
+use crate::language;
 use crate::CompilerError;
+use chrono::{Datelike, Duration, Local, NaiveDate};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::LazyLock;
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Slots {
@@ -27,12 +30,28 @@ impl Slots {
 
 /// Extract slots from a prompt based on the classified task type.
 pub fn extract_slots(prompt: &str, task_type: &TaskType) -> Result<Slots, CompilerError> {
+    extract_slots_on(prompt, task_type, Local::now().date_naive())
+}
+
+/// [`extract_slots`], with relative dates ("어제", "last week") taken from
+/// `today`.
+pub fn extract_slots_on(
+    prompt: &str,
+    task_type: &TaskType,
+    today: NaiveDate,
+) -> Result<Slots, CompilerError> {
     let mut slots = Slots::default();
+    slots
+        .values
+        .insert("language".into(), language::detect(prompt).code().into());
 
-    // Extract file/data source references
+    // Extract file/data source references. The extension must be Latin so a
+    // Korean particle after it ("보고서.pdf를") is not taken as part of it.
     if let Some(path) = extract_pattern(prompt, r#"["\']([^"']+\.\w{1,5})["\']"#) {
         slots.values.insert("data_source".into(), path.into());
-    } else if let Some(path) = extract_pattern(prompt, r"(\S+\.\w{1,5})") {
+    } else if let Some(path) =
+        extract_pattern(prompt, r"(\S+?\.[A-Za-z][A-Za-z0-9]{0,4})(?:[^A-Za-z0-9]|$)")
+    {
         slots.values.insert("data_source".into(), path.into());
     }
 
@@ -43,18 +62,49 @@ pub fn extract_slots(prompt: &str, task_type: &TaskType) -> Result<Slots, Compil
         slots.values.insert("llm_model".into(), "claude-sonnet-4-20250514".into());
     }
 
-    // Extract numeric parameters
-    if let Some(n) = extract_number(prompt, r"(?i)top[_\s]?k\s*[:=]?\s*(\d+)") {
-        slots.values.insert("top_k".into(), n.into());
-    }
-    if let Some(n) = extract_number(prompt, r"(?i)chunk[_\s]?size\s*[:=]?\s*(\d+)") {
-        slots.values.insert("chunk_size".into(), n.into());
-    }
-    if let Some(n) = extract_number(prompt, r"(?i)max[_\s]?length\s*[:=]?\s*(\d+)") {
-        slots.values.insert("max_length".into(), n.into());
+    // Extract numeric parameters, in English or Korean phrasing
+    let number_slots: [(&str, &[&str]); 4] = [
+        (
+            "top_k",
+            &[r"(?i)top[_\s]?k\s*[:=]?\s*(NUM)", r"(?i)(?:상위|top)\s*(NUM)\s*(?:개|건)?"],
+        ),
+        (
+            "chunk_size",
+            &[
+                r"(?i)chunk[_\s]?size\s*[:=]?\s*(NUM)",
+                r"청크\s*(?:크기|사이즈)?\s*(?:를|은|는|:|=)?\s*(NUM)",
+            ],
+        ),
+        (
+            "max_length",
+            &[
+                r"(?i)max[_\s]?length\s*[:=]?\s*(NUM)",
+                r"최대\s*(NUM)\s*자",
+                r"(NUM)\s*자\s*(?:이내|이하|내외)",
+            ],
+        ),
+        (
+            "num_questions",
+            &[
+                r"질문\s*(NUM)\s*개",
+                r"(?i)(NUM)\s*(?:개의?\s*질문|questions)",
+            ],
+        ),
+    ];
+    for (slot, patterns) in number_slots {
+        if let Some(n) = patterns.iter().find_map(|p| extract_number(prompt, p)) {
+            slots.values.insert(slot.into(), n.into());
+        }
     }
-    if let Some(n) = extract_number(prompt, r"(?i)(\d+)\s*(?:개|questions|질문)") {
-        slots.values.insert("num_questions".into(), n.into());
+
+    // Extract the period the prompt is about
+    if let Some((from, to)) = extract_date_range(prompt, today) {
+        slots
+            .values
+            .insert("date_from".into(), from.format("%Y-%m-%d").to_string().into());
+        slots
+            .values
+            .insert("date_to".into(), to.format("%Y-%m-%d").to_string().into());
     }
 
     // Task-specific extraction
@@ -97,8 +147,173 @@ fn extract_pattern(text: &str, pattern: &str) -> Option<String> {
     Regex::new(pattern).ok()?.captures(text).map(|c| c[1].to_string())
 }
 
+/// A number as written in a prompt: digits with optional thousands separators
+/// and a Korean unit ("1,000", "1.5만", "3천"), or a native Korean count
+/// ("세", "열다섯", "스무").
+const NUMBER: &str = r"\d[\d,]*(?:\.\d+)?\s*[천만억]?|(?:열|스물|스무|서른|마흔|쉰)(?:하나|한|둘|두|셋|세|넷|네|다섯|여섯|일곱|여덟|아홉)?|하나|한|둘|두|셋|세|넷|네|다섯|여섯|일곱|여덟|아홉";
+
+/// Extract a number with `pattern`, whose `NUM` placeholder stands for
+/// [`NUMBER`].
 fn extract_number(text: &str, pattern: &str) -> Option<u64> {
-    extract_pattern(text, pattern)?.parse().ok()
+    parse_number(&extract_pattern(text, &pattern.replace("NUM", NUMBER))?)
+}
+
+fn parse_number(text: &str) -> Option<u64> {
+    let text = text.trim();
+    if text.starts_with(|c: char| c.is_ascii_digit()) {
+        let (digits, multiplier) = match text.chars().last()? {
+            '천' => (&text[..text.len() - '천'.len_utf8()], 1_000.0),
+            '만' => (&text[..text.len() - '만'.len_utf8()], 10_000.0),
+            '억' => (&text[..text.len() - '억'.len_utf8()], 100_000_000.0),
+            _ => (text, 1.0),
+        };
+        let value: f64 = digits.trim().replace(',', "").parse().ok()?;
+        return Some((value * multiplier).round() as u64);
+    }
+
+    const TENS: [(&str, u64); 6] = [
+        ("열", 10),
+        ("스물", 20),
+        ("스무", 20),
+        ("서른", 30),
+        ("마흔", 40),
+        ("쉰", 50),
+    ];
+    const UNITS: [(&str, u64); 13] = [
+        ("하나", 1),
+        ("한", 1),
+        ("둘", 2),
+        ("두", 2),
+        ("셋", 3),
+        ("세", 3),
+        ("넷", 4),
+        ("네", 4),
+        ("다섯", 5),
+        ("여섯", 6),
+        ("일곱", 7),
+        ("여덟", 8),
+        ("아홉", 9),
+    ];
+    let (tens, rest) = TENS
+        .iter()
+        .find_map(|(word, n)| text.strip_prefix(word).map(|rest| (*n, rest)))
+        .unwrap_or((0, text));
+    let units = match rest {
+        "" => 0,
+        rest => UNITS.iter().find(|(word, _)| *word == rest)?.1,
+    };
+    (tens + units > 0).then_some(tens + units)
+}
+
+fn pattern(pattern: &str) -> Regex {
+    Regex::new(pattern).expect("valid pattern")
+}
+
+static FULL_DATE: LazyLock<Regex> =
+    LazyLock::new(|| pattern(r"(\d{4})\s*(?:년\s*|[./-])\s*(\d{1,2})\s*(?:월\s*|[./-])\s*(\d{1,2})"));
+static YEAR_MONTH: LazyLock<Regex> = LazyLock::new(|| pattern(r"(\d{4})\s*년\s*(\d{1,2})\s*월"));
+static YEAR: LazyLock<Regex> = LazyLock::new(|| pattern(r"(\d{4})\s*년"));
+static RECENT_DAYS: LazyLock<[Regex; 2]> = LazyLock::new(|| {
+    [
+        pattern(&r"(?:최근|지난)\s*(NUM)\s*일".replace("NUM", NUMBER)),
+        pattern(r"(?i)\b(?:last|past)\s+(\d+)\s+days?\b"),
+    ]
+});
+
+/// "Last N days" looks back at most this far.
+const MAX_RECENT_DAYS: u64 = 100 * 366;
+
+#[derive(Clone, Copy)]
+enum Period {
+    Today,
+    Yesterday,
+    LastWeek,
+    ThisWeek,
+    LastMonth,
+    ThisMonth,
+    LastYear,
+    ThisYear,
+}
+
+/// Relative periods, checked in order.
+static PERIODS: LazyLock<[(Regex, Period); 8]> = LazyLock::new(|| {
+    [
+        (pattern(r"오늘|(?i)\btoday\b"), Period::Today),
+        (pattern(r"어제|(?i)\byesterday\b"), Period::Yesterday),
+        (pattern(r"지난\s*주|저번\s*주|(?i)\blast\s+week\b"), Period::LastWeek),
+        (pattern(r"이번\s*주|(?i)\bthis\s+week\b"), Period::ThisWeek),
+        (pattern(r"지난\s*달|저번\s*달|(?i)\blast\s+month\b"), Period::LastMonth),
+        (pattern(r"이번\s*달|(?i)\bthis\s+month\b"), Period::ThisMonth),
+        (pattern(r"작년|지난\s*해|(?i)\blast\s+year\b"), Period::LastYear),
+        (pattern(r"올해|(?i)\bthis\s+year\b"), Period::ThisYear),
+    ]
+});
+
+/// The period a prompt refers to, as an inclusive range of days. Explicit
+/// dates win over relative phrases; two explicit dates form a range.
+fn extract_date_range(prompt: &str, today: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+    let mut dates: Vec<NaiveDate> = FULL_DATE
+        .captures_iter(prompt)
+        .filter_map(|c| NaiveDate::from_ymd_opt(c[1].parse().ok()?, c[2].parse().ok()?, c[3].parse().ok()?))
+        .collect();
+    if !dates.is_empty() {
+        dates.sort();
+        return Some((dates[0], dates[dates.len() - 1]));
+    }
+
+    if let Some(c) = YEAR_MONTH.captures(prompt) {
+        return month_range(c[1].parse().ok()?, c[2].parse().ok()?);
+    }
+    if let Some(c) = YEAR.captures(prompt) {
+        let year = c[1].parse().ok()?;
+        return Some((
+            NaiveDate::from_ymd_opt(year, 1, 1)?,
+            NaiveDate::from_ymd_opt(year, 12, 31)?,
+        ));
+    }
+
+    if let Some(days) = RECENT_DAYS
+        .iter()
+        .find_map(|re| parse_number(&re.captures(prompt)?[1]))
+    {
+        let back = days.clamp(1, MAX_RECENT_DAYS) - 1;
+        return Some((today.checked_sub_signed(Duration::days(back as i64))?, today));
+    }
+
+    let (_, period) = PERIODS.iter().find(|(re, _)| re.is_match(prompt))?;
+    let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    match period {
+        Period::Today => Some((today, today)),
+        Period::Yesterday => {
+            let yesterday = today.pred_opt()?;
+            Some((yesterday, yesterday))
+        }
+        Period::LastWeek => Some((monday - Duration::days(7), monday - Duration::days(1))),
+        Period::ThisWeek => Some((monday, today)),
+        Period::LastMonth => {
+            let last_month = today.with_day(1)?.pred_opt()?;
+            month_range(last_month.year(), last_month.month())
+        }
+        Period::ThisMonth => Some((today.with_day(1)?, today)),
+        Period::LastYear => {
+            let year = today.year() - 1;
+            Some((
+                NaiveDate::from_ymd_opt(year, 1, 1)?,
+                NaiveDate::from_ymd_opt(year, 12, 31)?,
+            ))
+        }
+        Period::ThisYear => Some((NaiveDate::from_ymd_opt(today.year(), 1, 1)?, today)),
+    }
+}
+
+fn month_range(year: i32, month: u32) -> Option<(NaiveDate, NaiveDate)> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+    let next = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)?
+    };
+    Some((first, next - Duration::days(1)))
 }
 
 #[cfg(test)]
@@ -116,5 +331,52 @@ mod tests {
         let slots = extract_slots("gpt-4o 모델로 top_k=10 RAG 해줘", &TaskType::Rag).unwrap();
         assert_eq!(slots.get_str("llm_model"), Some("gpt-4o"));
         assert_eq!(slots.get_u64("top_k"), Some(10));
+        assert_eq!(slots.get_str("language"), Some("ko"));
+    }
+
+    #[test]
+    fn korean_numbers_and_particles() {
+        let slots = extract_slots_on(
+            "보고서.pdf를 읽고 상위 세 개 문단을 최대 1천 자로, 질문 열다섯 개 만들어줘",
+            &TaskType::Rag,
+            NaiveDate::from_ymd_opt(2024, 3, 14).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(slots.get_str("data_source"), Some("보고서.pdf"));
+        assert_eq!(slots.get_u64("top_k"), Some(3));
+        assert_eq!(slots.get_u64("max_length"), Some(1000));
+        assert_eq!(slots.get_u64("num_questions"), Some(15));
+        assert_eq!(parse_number("1.5만"), Some(15_000));
+        assert_eq!(parse_number("1,200"), Some(1_200));
+        assert_eq!(parse_number("스무"), Some(20));
+    }
+
+    #[test]
+    fn korean_and_relative_dates() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 14).unwrap(); // Thursday
+        let range = |prompt| {
+            let slots = extract_slots_on(prompt, &TaskType::Summarize, today).unwrap();
+            (
+                slots.get_or_default("date_from", ""),
+                slots.get_or_default("date_to", ""),
+            )
+        };
+        assert_eq!(range("2024년 2월 매출 요약"), ("2024-02-01".into(), "2024-02-29".into()));
+        assert_eq!(
+            range("2023년 12월 1일부터 2024.01.15까지 로그 요약"),
+            ("2023-12-01".into(), "2024-01-15".into())
+        );
+        assert_eq!(range("지난주 회의록 요약해줘"), ("2024-03-04".into(), "2024-03-10".into()));
+        assert_eq!(range("최근 7일 뉴스 요약"), ("2024-03-08".into(), "2024-03-14".into()));
+        assert_eq!(range("summarize yesterday's tickets"), ("2024-03-13".into(), "2024-03-13".into()));
+        assert_eq!(range("요약해줘"), ("".into(), "".into()));
+
+        // Absurd look-backs are capped instead of overflowing
+        let (from, to) = range("최근 99999999999999999999일 로그");
+        assert_eq!(to, "2024-03-14");
+        assert!(from.as_str() > "1920-01-01", "{from}");
+        assert_eq!(range("past 9999999999999 days").1, "2024-03-14");
+        let early = NaiveDate::MIN + Duration::days(10);
+        assert_eq!(extract_date_range("last 100 days", early), None);
     }
 }
@@ -0,0 +1,428 @@
+//! Connection suggestions — where an edge dragged from an output port could
+//! go: compatible inputs on existing nodes, and tools that could be
+//! inserted to take the value.
+//!
+//! Candidates are ranked by a simple additive score: type fit first, then
+//! port-name similarity, whether the input is still free (and required),
+//! related capability tags, and layout. Inputs that would be incompatible,
+//! create a cycle or duplicate an existing edge are never suggested.
+
+use crate::type_checker::types_compatible;
+use crate::CompilerError;
+use hb_core::graph::{split_variadic_port, NodeEntry, PortSpec, PortType, WorkflowSpec};
+use hb_core::tool::ToolInterface;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+const SCORE_SAME_TYPE: u32 = 40;
+const SCORE_ANY_TYPE: u32 = 15;
+const SCORE_SAME_NAME: u32 = 25;
+const SCORE_SIMILAR_NAME: u32 = 10;
+const SCORE_FREE_INPUT: u32 = 15;
+const SCORE_REQUIRED_INPUT: u32 = 10;
+const SCORE_RELATED_TAGS: u32 = 10;
+const SCORE_DOWNSTREAM: u32 = 5;
+
+/// An input port on an existing node.
+#[derive(Debug, Clone, Serialize)]
+pub struct PortSuggestion {
+    pub node_id: String,
+    /// Port to connect to; for variadic inputs, the next free key
+    /// (e.g. `inputs.2`).
+    pub port: String,
+    pub port_type: PortType,
+    pub score: u32,
+    pub reasons: Vec<String>,
+}
+
+/// A tool to insert and connect to.
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolSuggestion {
+    pub tool_id: String,
+    pub display_name: String,
+    pub port: String,
+    pub port_type: PortType,
+    pub score: u32,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionSuggestions {
+    pub source_node: String,
+    pub source_port: String,
+    /// `Any` when the source node's ports aren't known.
+    pub source_type: PortType,
+    /// Best first.
+    pub ports: Vec<PortSuggestion>,
+    /// Best first.
+    pub tools: Vec<ToolSuggestion>,
+}
+
+/// A candidate's score and why.
+#[derive(Default)]
+struct Score {
+    total: u32,
+    reasons: Vec<String>,
+}
+
+impl Score {
+    fn add(&mut self, points: u32, reason: impl Into<String>) {
+        self.total += points;
+        self.reasons.push(reason.into());
+    }
+}
+
+/// Rank up to `limit` existing inputs and `limit` tools that the output
+/// `source_port` of `source_node` could connect to. `tools` are the
+/// registered tools, used both to resolve nodes and as insert candidates.
+pub fn suggest_connections(
+    spec: &WorkflowSpec,
+    source_node: &str,
+    source_port: &str,
+    tools: &[&ToolInterface],
+    limit: usize,
+) -> Result<ConnectionSuggestions, CompilerError> {
+    let registry: HashMap<&str, &ToolInterface> =
+        tools.iter().map(|t| (t.tool_id.as_str(), *t)).collect();
+    let tool_of = |node: &NodeEntry| match node {
+        NodeEntry::Primitive(n) => {
+            let tool_id = n.tool_ref.split('@').next().unwrap_or(&n.tool_ref);
+            registry.get(tool_id).copied()
+        }
+        _ => None,
+    };
+
+    let source = spec
+        .nodes
+        .iter()
+        .find(|n| n.id() == source_node)
+        .ok_or_else(|| CompilerError::Validation(format!("Node not found: {source_node}")))?;
+    let source_type = match outputs_of(source, &tool_of) {
+        Some(outputs) => outputs
+            .iter()
+            .find(|p| p.name == source_port)
+            .ok_or_else(|| {
+                CompilerError::Validation(format!("'{source_node}' has no output '{source_port}'"))
+            })?
+            .port_type
+            .clone(),
+        None => PortType::Any,
+    };
+    let source_roots = tool_of(source).map(tag_roots).unwrap_or_default();
+    let source_x = position_x(source);
+
+    // Edges into each input, and inputs this port already feeds
+    let mut connected: HashMap<(&str, &str), usize> = HashMap::new();
+    let mut taken: HashSet<(&str, &str)> = HashSet::new();
+    let mut existing: HashSet<(&str, &str)> = HashSet::new();
+    for edge in &spec.edges {
+        let (base, _) = split_variadic_port(&edge.target_port);
+        *connected.entry((&edge.target_node, base)).or_default() += 1;
+        taken.insert((&edge.target_node, &edge.target_port));
+        if edge.source_node == source_node && edge.source_port == source_port {
+            existing.insert((&edge.target_node, base));
+        }
+    }
+
+    let mut ports = Vec::new();
+    for node in &spec.nodes {
+        let node_id = node.id();
+        if node_id == source_node || reaches(spec, node_id, source_node) {
+            continue;
+        }
+        let Some(inputs) = inputs_of(node, &tool_of) else {
+            continue;
+        };
+        let target_roots = tool_of(node).map(tag_roots).unwrap_or_default();
+        for input in &inputs {
+            if existing.contains(&(node_id, input.name.as_str())) {
+                continue;
+            }
+            let uses = connected
+                .get(&(node_id, input.name.as_str()))
+                .copied()
+                .unwrap_or(0);
+            let port = if input.variadic {
+                (uses..)
+                    .map(|key| format!("{}.{key}", input.name))
+                    .find(|port| !taken.contains(&(node_id, port.as_str())))
+                    .unwrap_or_default()
+            } else {
+                input.name.clone()
+            };
+            let Some(mut score) = port_score(&source_type, source_port, input) else {
+                continue;
+            };
+            if uses == 0 || input.variadic {
+                score.add(SCORE_FREE_INPUT, "input not connected yet");
+                if input.required && uses == 0 {
+                    score.add(SCORE_REQUIRED_INPUT, "required input");
+                }
+            }
+            if let Some(root) = source_roots.iter().find(|r| target_roots.contains(*r)) {
+                score.add(SCORE_RELATED_TAGS, format!("related capability ({root})"));
+            }
+            if let (Some(from), Some(to)) = (source_x, position_x(node)) {
+                if to > from {
+                    score.add(SCORE_DOWNSTREAM, "placed after the source");
+                }
+            }
+            ports.push(PortSuggestion {
+                node_id: node_id.to_string(),
+                port,
+                port_type: input.port_type.clone(),
+                score: score.total,
+                reasons: score.reasons,
+            });
+        }
+    }
+    ports.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.node_id.cmp(&b.node_id))
+    });
+    ports.truncate(limit);
+
+    let mut suggested_tools: Vec<ToolSuggestion> = tools
+        .iter()
+        .filter_map(|tool| {
+            let roots = tag_roots(tool);
+            let (input, mut score) = tool
+                .input_schema
+                .ports
+                .iter()
+                .filter_map(|input| {
+                    let mut score = port_score(&source_type, source_port, input)?;
+                    if input.required {
+                        score.add(SCORE_REQUIRED_INPUT, "required input");
+                    }
+                    Some((input, score))
+                })
+                .max_by_key(|(_, score)| score.total)?;
+            if let Some(root) = source_roots.iter().find(|r| roots.contains(*r)) {
+                score.add(SCORE_RELATED_TAGS, format!("related capability ({root})"));
+            }
+            Some(ToolSuggestion {
+                tool_id: tool.tool_id.clone(),
+                display_name: tool.display_name.clone(),
+                port: if input.variadic {
+                    format!("{}.0", input.name)
+                } else {
+                    input.name.clone()
+                },
+                port_type: input.port_type.clone(),
+                score: score.total,
+                reasons: score.reasons,
+            })
+        })
+        .collect();
+    suggested_tools.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.tool_id.cmp(&b.tool_id))
+    });
+    suggested_tools.truncate(limit);
+
+    Ok(ConnectionSuggestions {
+        source_node: source_node.to_string(),
+        source_port: source_port.to_string(),
+        source_type,
+        ports,
+        tools: suggested_tools,
+    })
+}
+
+/// Type and name fit of `input` for a value of `source_type` from the
+/// port named `source_port`; `None` when the types are incompatible.
+fn port_score(source_type: &PortType, source_port: &str, input: &PortSpec) -> Option<Score> {
+    if !types_compatible(source_type, &input.port_type) {
+        return None;
+    }
+    let mut score = Score::default();
+    if *source_type == PortType::Any || input.port_type == PortType::Any {
+        score.add(SCORE_ANY_TYPE, "accepts any type");
+    } else {
+        score.add(
+            SCORE_SAME_TYPE,
+            format!("same type ({:?})", input.port_type),
+        );
+    }
+    if input.name.eq_ignore_ascii_case(source_port) {
+        score.add(SCORE_SAME_NAME, "same port name");
+    } else if name_tokens(&input.name).any(|t| name_tokens(source_port).any(|s| s == t)) {
+        score.add(SCORE_SIMILAR_NAME, "similar port name");
+    }
+    Some(score)
+}
+
+fn name_tokens(name: &str) -> impl Iterator<Item = String> + '_ {
+    name.split(['_', '-', '.', ' '])
+        .filter(|t| !t.is_empty())
+        .map(str::to_ascii_lowercase)
+}
+
+/// Top-level capability tag segments of a tool (e.g. "rag" for "rag.ingest").
+fn tag_roots(tool: &ToolInterface) -> Vec<&str> {
+    let mut roots: Vec<&str> = tool
+        .capability_tags
+        .iter()
+        .filter_map(|tag| tag.0.split('.').next())
+        .filter(|root| !root.is_empty())
+        .collect();
+    roots.dedup();
+    roots
+}
+
+/// Declared ports of a node; `None` when it accepts any port (conditionals,
+/// loops) or its tool is unknown.
+fn inputs_of<'a>(
+    node: &'a NodeEntry,
+    tool_of: &dyn Fn(&NodeEntry) -> Option<&'a ToolInterface>,
+) -> Option<Vec<PortSpec>> {
+    match node {
+        NodeEntry::Composite(n) => Some(n.input_ports.clone()),
+        _ => tool_of(node).map(|t| t.input_schema.ports.clone()),
+    }
+}
+
+fn outputs_of<'a>(
+    node: &'a NodeEntry,
+    tool_of: &dyn Fn(&NodeEntry) -> Option<&'a ToolInterface>,
+) -> Option<Vec<PortSpec>> {
+    match node {
+        NodeEntry::Composite(n) => Some(n.output_ports.clone()),
+        _ => tool_of(node).map(|t| t.output_schema.ports.clone()),
+    }
+}
+
+fn position_x(node: &NodeEntry) -> Option<f64> {
+    match node {
+        NodeEntry::Primitive(n) => n.position.map(|p| p.x),
+        NodeEntry::Composite(n) => n.position.map(|p| p.x),
+        _ => None,
+    }
+}
+
+/// Whether `to` is reachable from `from` along existing edges.
+fn reaches(spec: &WorkflowSpec, from: &str, to: &str) -> bool {
+    let mut seen = HashSet::from([from]);
+    let mut queue = vec![from];
+    while let Some(id) = queue.pop() {
+        for edge in spec.edges.iter().filter(|e| e.source_node == id) {
+            if edge.target_node == to {
+                return true;
+            }
+            if seen.insert(&edge.target_node) {
+                queue.push(&edge.target_node);
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tool(
+        id: &str,
+        tags: &[&str],
+        inputs: serde_json::Value,
+        outputs: serde_json::Value,
+    ) -> ToolInterface {
+        serde_json::from_value(json!({
+            "tool_id": id,
+            "version": "1.0.0",
+            "display_name": id,
+            "description": "",
+            "capability_tags": tags,
+            "input_schema": { "ports": inputs },
+            "output_schema": { "ports": outputs },
+            "side_effect": "none",
+            "required_permissions": [],
+            "cost_hint": { "time": "instant", "monetary": "free" },
+            "error_model": { "error_types": [], "idempotent": true,
+                             "default_retry": { "max_retries": 0, "backoff_ms": 0, "backoff_multiplier": 1.0 } },
+            "runtime": { "kind": "native" },
+        }))
+        .unwrap()
+    }
+
+    fn port(name: &str, port_type: &str, required: bool) -> serde_json::Value {
+        json!({ "name": name, "port_type": port_type, "required": required })
+    }
+
+    #[test]
+    fn ranks_compatible_inputs_and_tools() {
+        let tools = [
+            tool(
+                "t/read",
+                &["file.read"],
+                json!([port("path", "string", true)]),
+                json!([port("content", "string", true)]),
+            ),
+            tool(
+                "t/split",
+                &["text.split"],
+                json!([port("text", "string", true)]),
+                json!([port("chunks", "array", true)]),
+            ),
+            tool(
+                "t/summarize",
+                &["text.summarize"],
+                json!([
+                    port("content", "string", true),
+                    port("max_words", "number", false)
+                ]),
+                json!([port("summary", "string", true)]),
+            ),
+            tool(
+                "t/show",
+                &["io.display"],
+                json!([port("data", "any", true)]),
+                json!([]),
+            ),
+            tool(
+                "t/add",
+                &["math"],
+                json!([port("a", "number", true)]),
+                json!([port("sum", "number", true)]),
+            ),
+        ];
+        let registry: Vec<&ToolInterface> = tools.iter().collect();
+        let spec: WorkflowSpec = serde_json::from_value(json!({
+            "version": "0.1.0",
+            "meta": { "name": "", "description": "" },
+            "nodes": [
+                { "kind": "primitive", "id": "read", "tool_ref": "t/read@1.0.0", "position": { "x": 0, "y": 0 } },
+                { "kind": "primitive", "id": "split", "tool_ref": "t/split@1.0.0", "position": { "x": 200, "y": 0 } },
+                { "kind": "primitive", "id": "sum", "tool_ref": "t/summarize@1.0.0", "position": { "x": 400, "y": 0 } },
+                { "kind": "primitive", "id": "add", "tool_ref": "t/add@1.0.0" },
+                { "kind": "primitive", "id": "show", "tool_ref": "t/show@1.0.0" },
+            ],
+            "edges": [
+                { "source_node": "read", "source_port": "content", "target_node": "split", "target_port": "text" },
+                { "source_node": "sum", "source_port": "summary", "target_node": "read", "target_port": "path" },
+            ],
+        }))
+        .unwrap();
+
+        let s = suggest_connections(&spec, "read", "content", &registry, 10).unwrap();
+        assert_eq!(s.source_type, PortType::String);
+        let targets: Vec<_> = s
+            .ports
+            .iter()
+            .map(|p| (p.node_id.as_str(), p.port.as_str()))
+            .collect();
+        // The existing edge, the cycle back into `sum` and the number input are left out
+        assert_eq!(targets, [("show", "data")]);
+
+        let tools: Vec<_> = s.tools.iter().map(|t| t.tool_id.as_str()).collect();
+        assert_eq!(tools[0], "t/summarize");
+        assert!(!tools.contains(&"t/add"));
+        assert_eq!(s.tools[0].port, "content");
+
+        assert!(suggest_connections(&spec, "read", "nope", &registry, 10).is_err());
+    }
+}
@@ -33,12 +33,9 @@ fn prim(id: &str, tool: &str, label: &str, x: f64, y: f64) -> NodeEntry {
     NodeEntry::Primitive(NodeSpec {
         id: id.into(),
         tool_ref: format!("core-tools/{tool}@1.0.0"),
-        config: Default::default(),
         position: Some(Position { x, y }),
         label: Some(label.into()),
-        disabled: false,
-        retry: None,
-        cache: None,
+        ..Default::default()
     })
 }
 
@@ -49,8 +46,7 @@ fn edge(src: &str, src_port: &str, tgt: &str, tgt_port: &str) -> EdgeSpec {
         source_port: src_port.into(),
         target_node: tgt.into(),
         target_port: tgt_port.into(),
-        kind: EdgeKind::Data,
-        transform: None,
+        ..Default::default()
     }
 }
 
@@ -138,8 +134,8 @@ fn build_multi_agent_review(slots: &Slots) -> Result<WorkflowSpec, CompilerError
             edge("read", "content", "reviewer1", "prompt"),
             edge("read", "content", "reviewer2", "prompt"),
             edge("read", "content", "reviewer3", "prompt"),
-            edge("reviewer1", "response", "merge", "input_a"),
-            edge("reviewer2", "response", "merge", "input_b"),
+            edge("reviewer1", "response", "merge", "inputs.0"),
+            edge("reviewer2", "response", "merge", "inputs.1"),
             edge("merge", "merged", "synthesize", "context"),
             edge("reviewer3", "response", "synthesize", "prompt"),
             edge("synthesize", "response", "out", "data"),
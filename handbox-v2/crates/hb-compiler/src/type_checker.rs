@@ -1,50 +1,51 @@
 //! Type Checker — verify port compatibility across all edges.
 
+use crate::diagnostics::{Diagnostic, PortResolver};
 use crate::CompilerError;
-use hb_core::graph::{NodeEntry, PortType, WorkflowSpec};
-use std::collections::HashMap;
+use hb_core::graph::{NodeEntry, PortType, Severity, WorkflowSpec};
 
-/// Validate that every edge connects compatible port types.
+/// Validate that the graph is structurally sound (see
+/// [`hb_core::graph::validate`]) and that every node expression parses.
 pub fn check(spec: WorkflowSpec) -> Result<WorkflowSpec, CompilerError> {
-    // Build a map of node_id → (input_ports, output_ports)
-    let mut port_map: HashMap<String, (Vec<(String, PortType)>, Vec<(String, PortType)>)> =
-        HashMap::new();
+    // Conditions and loop expressions must parse before the run starts
+    if let Some(invalid) = hb_core::expr::check_nodes(&spec.nodes, |_| None).first() {
+        return Err(CompilerError::TypeCheckFailed(invalid.to_string()));
+    }
 
     for node in &spec.nodes {
-        match node {
-            NodeEntry::Primitive(n) => {
-                // For now, accept all ports as Any since we don't have the tool registry here.
-                // Real type checking will resolve tool_ref → ToolInterface ports.
-                port_map.insert(n.id.clone(), (vec![], vec![]));
-            }
-            NodeEntry::Composite(n) => {
-                let inputs = n.input_ports.iter().map(|p| (p.name.clone(), p.port_type.clone())).collect();
-                let outputs = n.output_ports.iter().map(|p| (p.name.clone(), p.port_type.clone())).collect();
-                port_map.insert(n.id.clone(), (inputs, outputs));
-            }
-            _ => {}
+        if let NodeEntry::Composite(n) = node {
+            n.resolved_subgraph().map_err(|e| {
+                CompilerError::TypeCheckFailed(format!("Composite '{}': {e}", n.id))
+            })?;
         }
     }
 
-    // Verify edges reference existing nodes
-    for edge in &spec.edges {
-        if !port_map.contains_key(&edge.source_node) {
-            return Err(CompilerError::TypeCheckFailed(format!(
-                "Edge {} references non-existent source node '{}'",
-                edge.id, edge.source_node
-            )));
-        }
-        if !port_map.contains_key(&edge.target_node) {
-            return Err(CompilerError::TypeCheckFailed(format!(
-                "Edge {} references non-existent target node '{}'",
-                edge.id, edge.target_node
-            )));
-        }
+    // Dangling edges, duplicate IDs, undeclared ports and Data cycles
+    if let Some(issue) = spec.validate().into_iter().find(|i| i.severity == Severity::Error) {
+        return Err(CompilerError::TypeCheckFailed(issue.to_string()));
     }
 
     Ok(spec)
 }
 
+/// Infer the types flowing through `any` ports and edge transforms (see
+/// [`crate::inference`]) and return the mismatches found as warnings, or
+/// with `strict` fail on the first one.
+pub fn check_inferred(
+    spec: &WorkflowSpec,
+    resolve: &PortResolver,
+    strict: bool,
+) -> Result<Vec<Diagnostic>, CompilerError> {
+    let warnings = crate::inference::infer_types(spec, resolve).diagnostics;
+    match warnings.first() {
+        Some(first) if strict => Err(CompilerError::TypeCheckFailed(format!(
+            "{}: {}",
+            first.node_id, first.message
+        ))),
+        _ => Ok(warnings),
+    }
+}
+
 /// Check if two port types are compatible.
 pub fn types_compatible(source: &PortType, target: &PortType) -> bool {
     if *source == PortType::Any || *target == PortType::Any {
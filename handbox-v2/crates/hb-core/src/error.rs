@@ -0,0 +1,185 @@
+//! AppError — the error payload returned across the UI boundary, with a
+//! machine-readable code and an optional hint the user can act on.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    NotFound,
+    InvalidInput,
+    MissingCredential,
+    PermissionDenied,
+    /// The resource is busy or was changed concurrently.
+    Conflict,
+    Database,
+    Execution,
+    PolicyViolation,
+    Timeout,
+    Cancelled,
+    Network,
+    Internal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppError {
+    pub code: ErrorCode,
+    pub message: String,
+    /// Structured context (e.g. validation errors per field).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+    /// Node the error is attributed to, for highlighting on the canvas.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node_id: Option<String>,
+    /// What the user can do about it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl AppError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            details: None,
+            node_id: None,
+            hint: None,
+        }
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::NotFound, message)
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidInput, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    pub fn with_node(mut self, node_id: impl Into<String>) -> Self {
+        self.node_id = Some(node_id.into());
+        self
+    }
+
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    /// Build an error from a plain message, recognising the common failure
+    /// phrases that bubble up as strings from tools and stores.
+    pub fn from_message(message: impl Into<String>) -> Self {
+        let message = message.into();
+        let lower = message.to_lowercase();
+        let has = |needles: &[&str]| needles.iter().any(|n| lower.contains(n));
+
+        if has(&["api key", "credential", "access key"]) && has(&["not configured", "missing", "no "]) {
+            return Self::new(ErrorCode::MissingCredential, message)
+                .with_hint("Add an API key or local endpoint in Settings → LLM providers.");
+        }
+        let code = if has(&["not found", "no such", "unknown workflow"]) {
+            ErrorCode::NotFound
+        } else if has(&["permission denied", "access denied", "not allowed"]) {
+            ErrorCode::PermissionDenied
+        } else if has(&["is locked by", "already running", "already exists"]) {
+            ErrorCode::Conflict
+        } else if has(&["timed out", "timeout"]) {
+            ErrorCode::Timeout
+        } else if has(&["cancelled", "canceled"]) {
+            ErrorCode::Cancelled
+        } else if has(&["invalid", "failed to parse", "expected", "required"]) {
+            ErrorCode::InvalidInput
+        } else if has(&["database", "sqlite"]) {
+            ErrorCode::Database
+        } else if has(&["connection", "network", "http "]) {
+            ErrorCode::Network
+        } else {
+            ErrorCode::Internal
+        };
+        Self::new(code, message)
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.node_id {
+            Some(node_id) => write!(f, "{} (node {node_id})", self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        Self::from_message(message)
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        Self::from_message(message)
+    }
+}
+
+/// Lets code that still reports errors as strings call commands with `?`.
+impl From<AppError> for String {
+    fn from(e: AppError) -> Self {
+        e.to_string()
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::invalid_input(format!("invalid JSON: {e}"))
+    }
+}
+
+impl From<uuid::Error> for AppError {
+    fn from(e: uuid::Error) -> Self {
+        Self::invalid_input(format!("invalid ID: {e}"))
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        let code = match e.kind() {
+            std::io::ErrorKind::NotFound => ErrorCode::NotFound,
+            std::io::ErrorKind::PermissionDenied => ErrorCode::PermissionDenied,
+            std::io::ErrorKind::TimedOut => ErrorCode::Timeout,
+            _ => ErrorCode::Internal,
+        };
+        Self::new(code, e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_messages_and_serializes_compactly() {
+        let err = AppError::from("OpenAI API key not configured. Set credentials in Settings.");
+        assert_eq!(err.code, ErrorCode::MissingCredential);
+        assert!(err.hint.is_some());
+
+        assert_eq!(AppError::from("Workflow not found: abc").code, ErrorCode::NotFound);
+        assert_eq!(AppError::from("boom").code, ErrorCode::Internal);
+
+        let json = serde_json::to_value(AppError::not_found("missing").with_node("n1")).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "code": "not_found", "message": "missing", "node_id": "n1" })
+        );
+    }
+}
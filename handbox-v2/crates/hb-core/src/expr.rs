@@ -0,0 +1,1078 @@
+//! Expression engine shared by the condition tool, conditional nodes and
+//! loop guards.
+//!
+//! Expressions are evaluated against a JSON value. Names refer to its fields
+//! (`score`, `item.status`, `rows[0]`, `rows.0`), `$` is the value itself,
+//! and `$.a.b` paths are accepted as well. Two JMESPath-style projections
+//! pick values out of arrays: `rows[*].id` maps over the elements and
+//! `rows[?qty > 10]` keeps those matching a filter, in which names (and `@`)
+//! refer to the element. Projections drop `null` results.
+//!
+//! ```text
+//! score >= 0.8 && status == "approved"
+//! len(items) > 0 || "urgent" in tags
+//! lower(category) contains "철근"
+//! length(rows[?status == 'open']) > 0
+//! ```
+//!
+//! Comparisons can also be written with the words `eq`, `ne`, `lt`, `lte`,
+//! `gt` and `gte` (`amount gt 100`).
+//!
+//! This is not full JMESPath: strings are quoted with `'` or `"`, and pipes,
+//! slices, flattening (`[]`), multi-select, backtick literals and `&`
+//! expression references are syntax errors. The functions are `len` (or
+//! `length`), `lower`, `upper`, `trim`, `number`, `string`, `starts_with`,
+//! `ends_with`, `contains`, `keys`, `values` and `sum`.
+//!
+//! Missing fields are `null`. Ordering two values of different types is
+//! false rather than an error, so `missing > 3` simply doesn't hold.
+//! Expressions nest at most [`MAX_DEPTH`] levels deep.
+//!
+//! [`check_nodes`] parses every expression of a workflow's conditionals,
+//! loops and map/reduce nodes, and of the workflows they reference, so
+//! invalid ones are reported before a run.
+
+use crate::graph::{NodeEntry, WorkflowSpec};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt;
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ExprError {
+    #[error("{message} at position {position}")]
+    Syntax { message: String, position: usize },
+    #[error("{0}")]
+    Eval(String),
+}
+
+fn syntax(message: impl Into<String>, position: usize) -> ExprError {
+    ExprError::Syntax {
+        message: message.into(),
+        position,
+    }
+}
+
+/// A parsed expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr(Node);
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Literal(Value),
+    Root,
+    Field(Box<Node>, String),
+    Index(Box<Node>, Box<Node>),
+    /// `base[*] rest` or `base[?filter] rest`: `rest` (and the filter)
+    /// evaluated with each element of `base` as the root.
+    Project {
+        base: Box<Node>,
+        filter: Option<Box<Node>>,
+        rest: Box<Node>,
+    },
+    List(Vec<Node>),
+    Not(Box<Node>),
+    Neg(Box<Node>),
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+    Binary(BinaryOp, Box<Node>, Box<Node>),
+    Call(Func, Vec<Node>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinaryOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+    In,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Func {
+    Len,
+    Lower,
+    Upper,
+    Trim,
+    Number,
+    String,
+    StartsWith,
+    EndsWith,
+    Contains,
+    Keys,
+    Values,
+    Sum,
+}
+
+impl Func {
+    fn lookup(name: &str) -> Option<(Self, usize)> {
+        Some(match name {
+            "len" | "length" => (Self::Len, 1),
+            "lower" => (Self::Lower, 1),
+            "upper" => (Self::Upper, 1),
+            "trim" => (Self::Trim, 1),
+            "number" => (Self::Number, 1),
+            "string" => (Self::String, 1),
+            "starts_with" => (Self::StartsWith, 2),
+            "ends_with" => (Self::EndsWith, 2),
+            "contains" => (Self::Contains, 2),
+            "keys" => (Self::Keys, 1),
+            "values" => (Self::Values, 1),
+            "sum" => (Self::Sum, 1),
+            _ => return None,
+        })
+    }
+}
+
+impl Expr {
+    pub fn parse(text: &str) -> Result<Self, ExprError> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            depth: 0,
+        };
+        let node = parser.or()?;
+        match parser.peek() {
+            Token::End => Ok(Self(node)),
+            _ => Err(syntax("Unexpected token", parser.position())),
+        }
+    }
+
+    pub fn evaluate(&self, root: &Value) -> Result<Value, ExprError> {
+        eval(&self.0, root)
+    }
+
+    /// Evaluate and apply [`truthy`].
+    pub fn test(&self, root: &Value) -> Result<bool, ExprError> {
+        self.evaluate(root).map(|v| truthy(&v))
+    }
+
+    /// Top-level fields of the root the expression reads, in order of
+    /// appearance. Names inside projections refer to the elements and are
+    /// not included.
+    pub fn fields(&self) -> Vec<&str> {
+        fn walk<'a>(node: &'a Node, out: &mut Vec<&'a str>) {
+            match node {
+                Node::Field(base, name) if **base == Node::Root => {
+                    if !out.contains(&name.as_str()) {
+                        out.push(name);
+                    }
+                }
+                Node::Field(base, _)
+                | Node::Not(base)
+                | Node::Neg(base)
+                | Node::Project { base, .. } => walk(base, out),
+                Node::Index(a, b) | Node::And(a, b) | Node::Or(a, b) | Node::Binary(_, a, b) => {
+                    walk(a, out);
+                    walk(b, out);
+                }
+                Node::List(items) | Node::Call(_, items) => {
+                    items.iter().for_each(|item| walk(item, out))
+                }
+                Node::Literal(_) | Node::Root => {}
+            }
+        }
+        let mut out = Vec::new();
+        walk(&self.0, &mut out);
+        out
+    }
+}
+
+/// Parse and evaluate `text` against `root`.
+pub fn evaluate(text: &str, root: &Value) -> Result<Value, ExprError> {
+    Expr::parse(text)?.evaluate(root)
+}
+
+/// `false`, `null`, `0` and `""` are false; everything else is true.
+pub fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Bool(b) => *b,
+        Value::Null => false,
+        Value::Number(n) => n.as_f64().unwrap_or(0.0) != 0.0,
+        Value::String(s) => !s.is_empty(),
+        _ => true,
+    }
+}
+
+/// Equality with numbers compared by value (`1 == 1.0`).
+pub fn equals(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64() == y.as_f64(),
+        (Value::Array(x), Value::Array(y)) => {
+            x.len() == y.len() && x.iter().zip(y).all(|(a, b)| equals(a, b))
+        }
+        _ => a == b,
+    }
+}
+
+/// Total order for sorting values: numbers, then strings, then booleans,
+/// then arrays and objects, with nulls last. Numbers and strings compare
+/// naturally; other values of the same kind are equal.
+pub fn sort_order(a: &Value, b: &Value) -> Ordering {
+    let rank = |v: &Value| match v {
+        Value::Number(_) => 0,
+        Value::String(_) => 1,
+        Value::Bool(_) => 2,
+        Value::Array(_) | Value::Object(_) => 3,
+        Value::Null => 4,
+    };
+    match (a, b) {
+        (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
+        _ => compare(a, b).unwrap_or_else(|| rank(a).cmp(&rank(b))),
+    }
+}
+
+// ---- Tokens ----
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Str(String),
+    Ident(String),
+    Op(&'static str),
+    End,
+}
+
+const OPERATORS: [&str; 21] = [
+    "==", "!=", "<=", ">=", "&&", "||", "<", ">", "!", "+", "-", "*", "/", "%", "(", ")", "[", "]",
+    ",", ".", "?",
+];
+
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, ExprError> {
+    let mut tokens = Vec::new();
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let (at, c) = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            // After a dot the number is a path segment (`rows.0.id`), not a decimal
+            let segment = matches!(tokens.last(), Some((Token::Op("."), _)));
+            while i < chars.len() && (chars[i].1.is_ascii_digit() || (chars[i].1 == '.' && !segment)) {
+                i += 1;
+            }
+            let end = chars.get(i).map_or(text.len(), |(b, _)| *b);
+            let number = text[at..end]
+                .parse()
+                .map_err(|_| syntax("Invalid number", start))?;
+            tokens.push((Token::Number(number), at));
+        } else if c == '"' || c == '\'' {
+            let mut value = String::new();
+            i += 1;
+            loop {
+                let Some(&(_, next)) = chars.get(i) else {
+                    return Err(syntax("Unterminated string", at));
+                };
+                i += 1;
+                match next {
+                    _ if next == c => break,
+                    '\\' => {
+                        let escaped = chars.get(i).map(|(_, e)| *e);
+                        i += 1;
+                        value.push(match escaped {
+                            Some('n') => '\n',
+                            Some('t') => '\t',
+                            Some(other) => other,
+                            None => return Err(syntax("Unterminated string", at)),
+                        });
+                    }
+                    other => value.push(other),
+                }
+            }
+            tokens.push((Token::Str(value), at));
+        } else if c.is_alphabetic() || c == '_' || c == '$' || c == '@' {
+            let start = at;
+            i += 1;
+            if c != '$' && c != '@' {
+                while i < chars.len() && (chars[i].1.is_alphanumeric() || chars[i].1 == '_') {
+                    i += 1;
+                }
+            }
+            let end = chars.get(i).map_or(text.len(), |(b, _)| *b);
+            tokens.push((Token::Ident(text[start..end].to_string()), at));
+        } else {
+            let rest = &text[at..];
+            let op = OPERATORS
+                .iter()
+                .find(|op| rest.starts_with(*op))
+                .ok_or_else(|| syntax(unsupported(c), at))?;
+            tokens.push((Token::Op(op), at));
+            i += op.chars().count();
+        }
+    }
+    tokens.push((Token::End, text.len()));
+    Ok(tokens)
+}
+
+/// Error for a character no token starts with, naming the JMESPath
+/// feature it usually comes from.
+fn unsupported(c: char) -> String {
+    let feature = match c {
+        '|' => "pipes",
+        ':' => "slices",
+        '`' => "backtick literals; quote strings with ' or \"",
+        '{' | '}' => "multi-select hashes",
+        '&' => "expression references",
+        _ => return format!("Unexpected character '{c}'"),
+    };
+    format!("'{c}' is not supported: expressions have no {feature}")
+}
+
+// ---- Parser ----
+
+/// Deepest nesting an expression may have, counting brackets, operators
+/// and path steps, so neither parsing nor evaluation can overflow the stack.
+pub const MAX_DEPTH: usize = 128;
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].0
+    }
+
+    fn position(&self) -> usize {
+        self.tokens[self.pos].1
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.pos].0.clone();
+        if token != Token::End {
+            self.pos += 1;
+        }
+        token
+    }
+
+    /// Consume the operator or keyword `word` if it is next.
+    fn eat(&mut self, word: &str) -> bool {
+        let matches = match self.peek() {
+            Token::Op(op) => *op == word,
+            Token::Ident(name) => name == word,
+            _ => false,
+        };
+        if matches {
+            self.pos += 1;
+        }
+        matches
+    }
+
+    fn expect(&mut self, op: &str) -> Result<(), ExprError> {
+        if self.eat(op) {
+            Ok(())
+        } else {
+            Err(syntax(format!("Expected '{op}'"), self.position()))
+        }
+    }
+
+    /// Go one level deeper, failing past [`MAX_DEPTH`]. The level is given
+    /// back by the enclosing [`Parser::scoped`].
+    fn nest(&mut self) -> Result<(), ExprError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(syntax("Expression is nested too deeply", self.position()));
+        }
+        Ok(())
+    }
+
+    /// Run `parse`, then return to the current depth.
+    fn scoped(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<Node, ExprError>,
+    ) -> Result<Node, ExprError> {
+        let depth = self.depth;
+        let node = parse(self);
+        self.depth = depth;
+        node
+    }
+
+    fn or(&mut self) -> Result<Node, ExprError> {
+        self.scoped(|p| {
+            let mut left = p.and()?;
+            while p.eat("||") || p.eat("or") {
+                p.nest()?;
+                left = Node::Or(Box::new(left), Box::new(p.and()?));
+            }
+            Ok(left)
+        })
+    }
+
+    fn and(&mut self) -> Result<Node, ExprError> {
+        self.scoped(|p| {
+            let mut left = p.not()?;
+            while p.eat("&&") || p.eat("and") {
+                p.nest()?;
+                left = Node::And(Box::new(left), Box::new(p.not()?));
+            }
+            Ok(left)
+        })
+    }
+
+    fn not(&mut self) -> Result<Node, ExprError> {
+        if self.eat("!") || self.eat("not") {
+            return self.scoped(|p| {
+                p.nest()?;
+                Ok(Node::Not(Box::new(p.not()?)))
+            });
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Node, ExprError> {
+        let left = self.sum()?;
+        let ops = [
+            ("==", BinaryOp::Eq),
+            ("!=", BinaryOp::Ne),
+            ("<=", BinaryOp::Le),
+            (">=", BinaryOp::Ge),
+            ("<", BinaryOp::Lt),
+            (">", BinaryOp::Gt),
+            // Word forms of the simple `field op value` filters
+            ("eq", BinaryOp::Eq),
+            ("ne", BinaryOp::Ne),
+            ("lte", BinaryOp::Le),
+            ("gte", BinaryOp::Ge),
+            ("lt", BinaryOp::Lt),
+            ("gt", BinaryOp::Gt),
+            ("contains", BinaryOp::Contains),
+            ("in", BinaryOp::In),
+        ];
+        for (word, op) in ops {
+            if self.eat(word) {
+                return self.scoped(|p| {
+                    p.nest()?;
+                    Ok(Node::Binary(op, Box::new(left), Box::new(p.sum()?)))
+                });
+            }
+        }
+        Ok(left)
+    }
+
+    fn sum(&mut self) -> Result<Node, ExprError> {
+        self.scoped(|p| {
+            let mut left = p.product()?;
+            loop {
+                let op = if p.eat("+") {
+                    BinaryOp::Add
+                } else if p.eat("-") {
+                    BinaryOp::Sub
+                } else {
+                    return Ok(left);
+                };
+                p.nest()?;
+                left = Node::Binary(op, Box::new(left), Box::new(p.product()?));
+            }
+        })
+    }
+
+    fn product(&mut self) -> Result<Node, ExprError> {
+        self.scoped(|p| {
+            let mut left = p.unary()?;
+            loop {
+                let op = if p.eat("*") {
+                    BinaryOp::Mul
+                } else if p.eat("/") {
+                    BinaryOp::Div
+                } else if p.eat("%") {
+                    BinaryOp::Rem
+                } else {
+                    return Ok(left);
+                };
+                p.nest()?;
+                left = Node::Binary(op, Box::new(left), Box::new(p.unary()?));
+            }
+        })
+    }
+
+    fn unary(&mut self) -> Result<Node, ExprError> {
+        if self.eat("-") {
+            return self.scoped(|p| {
+                p.nest()?;
+                Ok(Node::Neg(Box::new(p.unary()?)))
+            });
+        }
+        let node = self.primary()?;
+        self.postfix(node)
+    }
+
+    /// Field access, indexing and projections following `node`.
+    fn postfix(&mut self, node: Node) -> Result<Node, ExprError> {
+        self.scoped(|p| {
+            let mut node = node;
+            loop {
+                if p.eat(".") {
+                    p.nest()?;
+                    let position = p.position();
+                    match p.advance() {
+                        Token::Ident(name) => node = Node::Field(Box::new(node), name),
+                        Token::Number(n) if n.fract() == 0.0 => {
+                            node = Node::Index(Box::new(node), Box::new(Node::Literal(number(n)?)))
+                        }
+                        _ => return Err(syntax("Expected a field name", position)),
+                    }
+                } else if p.eat("[") {
+                    p.nest()?;
+                    if matches!(p.peek(), Token::Op("]")) {
+                        return Err(syntax("'[]' is not supported: expressions have no flattening", p.position()));
+                    }
+                    let filter = if p.eat("*") {
+                        None
+                    } else if p.eat("?") {
+                        Some(Box::new(p.or()?))
+                    } else {
+                        let index = p.or()?;
+                        p.expect("]")?;
+                        node = Node::Index(Box::new(node), Box::new(index));
+                        continue;
+                    };
+                    p.expect("]")?;
+                    // The rest of the path applies to each element
+                    let rest = p.postfix(Node::Root)?;
+                    return Ok(Node::Project {
+                        base: Box::new(node),
+                        filter,
+                        rest: Box::new(rest),
+                    });
+                } else {
+                    return Ok(node);
+                }
+            }
+        })
+    }
+
+    fn primary(&mut self) -> Result<Node, ExprError> {
+        let position = self.position();
+        self.scoped(|p| p.primary_at(position))
+    }
+
+    fn primary_at(&mut self, position: usize) -> Result<Node, ExprError> {
+        match self.advance() {
+            Token::Number(n) => Ok(Node::Literal(number(n)?)),
+            Token::Str(s) => Ok(Node::Literal(Value::String(s))),
+            Token::Ident(name) => match name.as_str() {
+                "$" | "@" => Ok(Node::Root),
+                "true" => Ok(Node::Literal(Value::Bool(true))),
+                "false" => Ok(Node::Literal(Value::Bool(false))),
+                "null" => Ok(Node::Literal(Value::Null)),
+                _ if self.eat("(") => {
+                    self.nest()?;
+                    let (func, arity) = Func::lookup(&name)
+                        .ok_or_else(|| syntax(format!("Unknown function '{name}'"), position))?;
+                    let args = self.list(")")?;
+                    if args.len() != arity {
+                        return Err(syntax(
+                            format!("'{name}' takes {arity} argument(s)"),
+                            position,
+                        ));
+                    }
+                    Ok(Node::Call(func, args))
+                }
+                _ => Ok(Node::Field(Box::new(Node::Root), name)),
+            },
+            Token::Op("(") => {
+                self.nest()?;
+                let node = self.or()?;
+                self.expect(")")?;
+                Ok(node)
+            }
+            Token::Op("[") => {
+                self.nest()?;
+                Ok(Node::List(self.list("]")?))
+            }
+            Token::End => Err(syntax("Unexpected end of expression", position)),
+            Token::Op(op) => Err(syntax(format!("Unexpected '{op}'"), position)),
+        }
+    }
+
+    /// Comma-separated expressions up to `close`.
+    fn list(&mut self, close: &str) -> Result<Vec<Node>, ExprError> {
+        let mut items = Vec::new();
+        if self.eat(close) {
+            return Ok(items);
+        }
+        loop {
+            items.push(self.or()?);
+            if self.eat(close) {
+                return Ok(items);
+            }
+            self.expect(",")?;
+        }
+    }
+}
+
+// ---- Evaluation ----
+
+fn number(n: f64) -> Result<Value, ExprError> {
+    if n.fract() == 0.0 && n.abs() < i64::MAX as f64 {
+        return Ok(Value::from(n as i64));
+    }
+    serde_json::Number::from_f64(n)
+        .map(Value::Number)
+        .ok_or_else(|| ExprError::Eval(format!("{n} is not a finite number")))
+}
+
+fn as_number(value: &Value, context: &str) -> Result<f64, ExprError> {
+    value
+        .as_f64()
+        .ok_or_else(|| ExprError::Eval(format!("{context} needs numbers, got {value}")))
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn compare(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64()?.partial_cmp(&y.as_f64()?),
+        (Value::String(x), Value::String(y)) => Some(x.cmp(y)),
+        _ => None,
+    }
+}
+
+fn contains(haystack: &Value, needle: &Value) -> bool {
+    match (haystack, needle) {
+        (Value::String(h), Value::String(n)) => h.contains(n.as_str()),
+        (Value::Array(items), _) => items.iter().any(|item| equals(item, needle)),
+        (Value::Object(map), Value::String(key)) => map.contains_key(key),
+        _ => false,
+    }
+}
+
+fn eval(node: &Node, root: &Value) -> Result<Value, ExprError> {
+    Ok(match node {
+        Node::Literal(value) => value.clone(),
+        Node::Root => root.clone(),
+        Node::Field(base, name) => match eval(base, root)? {
+            Value::Object(mut map) => map.remove(name).unwrap_or(Value::Null),
+            _ => Value::Null,
+        },
+        Node::Index(base, index) => {
+            let (base, index) = (eval(base, root)?, eval(index, root)?);
+            match (&base, &index) {
+                (Value::Array(items), Value::Number(n)) => {
+                    let i = n.as_i64().unwrap_or(i64::MAX);
+                    let i = if i < 0 { items.len() as i64 + i } else { i };
+                    usize::try_from(i)
+                        .ok()
+                        .and_then(|i| items.get(i))
+                        .cloned()
+                        .unwrap_or(Value::Null)
+                }
+                (Value::Object(map), Value::String(key)) => {
+                    map.get(key).cloned().unwrap_or(Value::Null)
+                }
+                _ => Value::Null,
+            }
+        }
+        Node::Project { base, filter, rest } => {
+            let elements = match eval(base, root)? {
+                Value::Array(items) => items,
+                Value::Object(map) => map.into_iter().map(|(_, v)| v).collect(),
+                _ => return Ok(Value::Null),
+            };
+            let mut out = Vec::new();
+            for element in elements {
+                if let Some(filter) = filter {
+                    if !truthy(&eval(filter, &element)?) {
+                        continue;
+                    }
+                }
+                let value = eval(rest, &element)?;
+                if !value.is_null() {
+                    out.push(value);
+                }
+            }
+            Value::Array(out)
+        }
+        Node::List(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| eval(item, root))
+                .collect::<Result<_, _>>()?,
+        ),
+        Node::Not(inner) => Value::Bool(!truthy(&eval(inner, root)?)),
+        Node::Neg(inner) => number(-as_number(&eval(inner, root)?, "-")?)?,
+        Node::And(a, b) => Value::Bool(truthy(&eval(a, root)?) && truthy(&eval(b, root)?)),
+        Node::Or(a, b) => Value::Bool(truthy(&eval(a, root)?) || truthy(&eval(b, root)?)),
+        Node::Binary(op, a, b) => binary(*op, &eval(a, root)?, &eval(b, root)?)?,
+        Node::Call(func, args) => {
+            let args: Vec<Value> = args
+                .iter()
+                .map(|arg| eval(arg, root))
+                .collect::<Result<_, _>>()?;
+            call(*func, &args)?
+        }
+    })
+}
+
+fn binary(op: BinaryOp, a: &Value, b: &Value) -> Result<Value, ExprError> {
+    let order = |pred: fn(Ordering) -> bool| Value::Bool(compare(a, b).is_some_and(pred));
+    Ok(match op {
+        BinaryOp::Eq => Value::Bool(equals(a, b)),
+        BinaryOp::Ne => Value::Bool(!equals(a, b)),
+        BinaryOp::Lt => order(Ordering::is_lt),
+        BinaryOp::Le => order(Ordering::is_le),
+        BinaryOp::Gt => order(Ordering::is_gt),
+        BinaryOp::Ge => order(Ordering::is_ge),
+        BinaryOp::Contains => Value::Bool(contains(a, b)),
+        BinaryOp::In => Value::Bool(contains(b, a)),
+        BinaryOp::Add if a.is_string() || b.is_string() => Value::String(text(a) + &text(b)),
+        BinaryOp::Add => number(as_number(a, "+")? + as_number(b, "+")?)?,
+        BinaryOp::Sub => number(as_number(a, "-")? - as_number(b, "-")?)?,
+        BinaryOp::Mul => number(as_number(a, "*")? * as_number(b, "*")?)?,
+        BinaryOp::Div | BinaryOp::Rem => {
+            let (x, y) = (as_number(a, "/")?, as_number(b, "/")?);
+            if y == 0.0 {
+                return Err(ExprError::Eval("Division by zero".into()));
+            }
+            number(if op == BinaryOp::Div { x / y } else { x % y })?
+        }
+    })
+}
+
+fn call(func: Func, args: &[Value]) -> Result<Value, ExprError> {
+    let arg = &args[0];
+    Ok(match func {
+        Func::Len => Value::from(match arg {
+            Value::String(s) => s.chars().count(),
+            Value::Array(items) => items.len(),
+            Value::Object(map) => map.len(),
+            Value::Null => 0,
+            other => return Err(ExprError::Eval(format!("len() of {other}"))),
+        }),
+        Func::Lower => Value::String(text(arg).to_lowercase()),
+        Func::Upper => Value::String(text(arg).to_uppercase()),
+        Func::Trim => Value::String(text(arg).trim().to_string()),
+        Func::String => Value::String(text(arg)),
+        Func::Number => match arg {
+            Value::Number(_) => arg.clone(),
+            Value::Bool(b) => Value::from(u8::from(*b)),
+            other => text(other)
+                .trim()
+                .replace(',', "")
+                .parse()
+                .ok()
+                .map(number)
+                .transpose()?
+                .unwrap_or(Value::Null),
+        },
+        Func::StartsWith => Value::Bool(text(arg).starts_with(&text(&args[1]))),
+        Func::EndsWith => Value::Bool(text(arg).ends_with(&text(&args[1]))),
+        Func::Contains => Value::Bool(contains(arg, &args[1])),
+        Func::Keys => match arg {
+            Value::Object(map) => map.keys().cloned().map(Value::String).collect(),
+            _ => Value::Null,
+        },
+        Func::Values => match arg {
+            Value::Object(map) => map.values().cloned().collect(),
+            _ => Value::Null,
+        },
+        Func::Sum => match arg {
+            Value::Array(items) => number(
+                items
+                    .iter()
+                    .map(|item| as_number(item, "sum()"))
+                    .sum::<Result<f64, _>>()?,
+            )?,
+            other => return Err(ExprError::Eval(format!("sum() of {other}"))),
+        },
+    })
+}
+
+// ---- Workflow checks ----
+
+/// An expression of a workflow node that does not parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidExpression {
+    /// Node ID, with the IDs of enclosing nodes joined by `/`.
+    pub node_id: String,
+    /// Field holding the expression, e.g. `condition_expr`.
+    pub field: &'static str,
+    pub error: ExprError,
+}
+
+impl fmt::Display for InvalidExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}' {} is not a valid expression: {}", self.node_id, self.field, self.error)
+    }
+}
+
+/// References are followed at most this deep; the runner rejects cycles
+/// and missing workflows on its own.
+const MAX_REF_DEPTH: usize = 32;
+
+/// Parse every expression in `nodes`, their sub-graphs and the workflows
+/// they reference, and list the ones that fail. `workflows` looks up a
+/// referenced workflow; each one is checked once.
+pub fn check_nodes<'a>(
+    nodes: &[NodeEntry],
+    workflows: impl Fn(Uuid) -> Option<&'a WorkflowSpec>,
+) -> Vec<InvalidExpression> {
+    struct Check<'f, 'a> {
+        workflows: &'f dyn Fn(Uuid) -> Option<&'a WorkflowSpec>,
+        seen: HashSet<Uuid>,
+        out: Vec<InvalidExpression>,
+    }
+
+    fn visit(check: &mut Check, nodes: &[NodeEntry], prefix: &str, refs: usize) {
+        for node in nodes {
+            let node_id = format!("{prefix}{}", node.id());
+            for (field, text) in node.expressions() {
+                // JSON literals predate the expression syntax and still work
+                if serde_json::from_str::<Value>(text).is_ok() {
+                    continue;
+                }
+                if let Err(error) = Expr::parse(text) {
+                    check.out.push(InvalidExpression {
+                        node_id: node_id.clone(),
+                        field,
+                        error,
+                    });
+                }
+            }
+            let prefix = format!("{node_id}/");
+            for subgraph in node.subgraphs() {
+                visit(check, &subgraph.nodes, &prefix, refs);
+            }
+            if let NodeEntry::WorkflowRef(n) = node {
+                if refs < MAX_REF_DEPTH && check.seen.insert(n.workflow_id) {
+                    if let Some(referenced) = (check.workflows)(n.workflow_id) {
+                        visit(check, &referenced.nodes, &prefix, refs + 1);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut check = Check {
+        workflows: &workflows,
+        seen: HashSet::new(),
+        out: Vec::new(),
+    };
+    visit(&mut check, nodes, "", 0);
+    check.out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn eval(text: &str) -> Value {
+        let root = json!({
+            "score": 0.92,
+            "status": "approved",
+            "tags": ["urgent", "rebar"],
+            "item": { "수량": 120, "unit": "ton" },
+            "rows": [{ "id": 1 }, { "id": 2 }],
+            "amount": "1,250",
+        });
+        evaluate(text, &root).unwrap_or_else(|e| panic!("{text}: {e}"))
+    }
+
+    #[test]
+    fn evaluates_conditions() {
+        assert_eq!(eval(r#"score >= 0.8 && status == "approved""#), json!(true));
+        assert_eq!(
+            eval(r#""urgent" in tags and not (score < 0.5)"#),
+            json!(true)
+        );
+        assert_eq!(eval("item.수량 * 2 + 10"), json!(250));
+        assert_eq!(eval("$.item.unit"), json!("ton"));
+        assert_eq!(eval("rows[-1].id"), json!(2));
+        assert_eq!(eval("len(rows) == 2"), json!(true));
+        assert_eq!(eval("number(amount) > 1000"), json!(true));
+        assert_eq!(eval("upper(item.unit) + '!'"), json!("TON!"));
+        assert_eq!(eval("item contains 'unit'"), json!(true));
+        assert_eq!(eval("starts_with(status, 'app')"), json!(true));
+        assert_eq!(eval("[1, 2.0] == [1.0, 2]"), json!(true));
+    }
+
+    #[test]
+    fn projects_and_filters_like_jmespath() {
+        assert_eq!(eval("rows[*].id"), json!([1, 2]));
+        assert_eq!(eval("rows[?id > 1].id"), json!([2]));
+        assert_eq!(eval("rows[?@.id == 1]"), json!([{ "id": 1 }]));
+        assert_eq!(eval("length(rows[?id >= 1]) == 2"), json!(true));
+        assert_eq!(eval("sum(rows[*].id)"), json!(3));
+        assert_eq!(eval("rows[*].missing"), json!([]));
+        assert_eq!(eval("rows.1.id"), json!(2));
+        assert_eq!(eval("contains(tags, 'rebar')"), json!(true));
+        assert_eq!(eval("keys(item)"), json!(["unit", "수량"]));
+        assert_eq!(eval("status[*]"), Value::Null);
+    }
+
+    #[test]
+    fn invalid_workflow_expressions_are_found_in_subgraphs() {
+        use crate::graph::{ConditionalKind, ConditionalSpec, LoopAggregation, LoopKind, LoopSpec, SubgraphSpec};
+        let conditional = |id: &str, expr: &str| {
+            NodeEntry::Conditional(ConditionalSpec {
+                id: id.into(),
+                kind: ConditionalKind::If,
+                condition_expr: expr.into(),
+                branches: Vec::new(),
+                default_branch: None,
+            })
+        };
+        let looped = NodeEntry::Loop(LoopSpec {
+            id: "loop".into(),
+            kind: LoopKind::ForEach,
+            body: SubgraphSpec {
+                nodes: vec![conditional("inner", "score >")],
+                edges: Vec::new(),
+            },
+            max_iterations: 10,
+            condition_expr: None,
+            items_expr: Some("rows[?qty > 1]".into()),
+            collect_expr: Some("(".into()),
+            aggregation: LoopAggregation::default(),
+        });
+        let found = check_nodes(&[conditional("ok", "status == 'open'"), looped.clone()], |_| None);
+        let fields: Vec<_> = found.iter().map(|e| (e.node_id.as_str(), e.field)).collect();
+        assert_eq!(fields, [("loop", "collect_expr"), ("loop/inner", "condition_expr")]);
+        assert_eq!(
+            found[1].to_string(),
+            "'loop/inner' condition_expr is not a valid expression: Unexpected end of expression at position 7"
+        );
+
+        // Referenced workflows are checked too, once each, even when they
+        // reference themselves
+        let mut referenced = WorkflowSpec::default();
+        let reference = |id: &str| {
+            NodeEntry::WorkflowRef(crate::graph::WorkflowRefSpec {
+                id: id.into(),
+                workflow_id: referenced.id,
+                input_ports: Vec::new(),
+                output_ports: Vec::new(),
+                input_mapping: Vec::new(),
+                output_mapping: Vec::new(),
+                position: None,
+                label: None,
+            })
+        };
+        let again = reference("again");
+        referenced.nodes = vec![looped, again];
+        let found = check_nodes(&[reference("sub"), reference("twice")], |id| {
+            (id == referenced.id).then_some(&referenced)
+        });
+        let fields: Vec<_> = found.iter().map(|e| e.node_id.as_str()).collect();
+        assert_eq!(fields, ["sub/loop", "sub/loop/inner"]);
+    }
+
+    #[test]
+    fn sorts_mixed_values_with_nulls_last() {
+        let mut values = vec![
+            json!(null),
+            json!("b"),
+            json!(10),
+            json!(true),
+            json!(2.5),
+            json!("a"),
+        ];
+        values.sort_by(sort_order);
+        assert_eq!(
+            values,
+            vec![
+                json!(2.5),
+                json!(10),
+                json!("a"),
+                json!("b"),
+                json!(true),
+                json!(null)
+            ]
+        );
+    }
+
+    #[test]
+    fn word_comparisons_and_read_fields() {
+        assert_eq!(evaluate("amount gt 100", &json!({ "amount": 150 })).unwrap(), json!(true));
+        assert_eq!(evaluate("status eq 'open'", &json!({ "status": "open" })).unwrap(), json!(true));
+        assert_eq!(evaluate("gt lte 2", &json!({ "gt": 2 })).unwrap(), json!(true));
+
+        let expr = Expr::parse("status == open && len(rows[?qty > 1]) > 0 && $.meta.id").unwrap();
+        assert_eq!(expr.fields(), ["status", "open", "rows", "meta"]);
+    }
+
+    #[test]
+    fn missing_fields_and_mixed_types_are_not_errors() {
+        assert_eq!(eval("missing"), Value::Null);
+        assert_eq!(eval("missing.deeper[3]"), Value::Null);
+        assert_eq!(eval("missing > 3"), json!(false));
+        assert_eq!(eval("status < 3"), json!(false));
+        assert_eq!(eval("missing == null"), json!(true));
+        // Short-circuit guards a comparison that would fail
+        assert_eq!(eval("missing != null && missing / 0 > 1"), json!(false));
+    }
+
+    #[test]
+    fn reports_syntax_and_evaluation_errors() {
+        for (text, position) in [
+            ("score >", 7),
+            ("(score", 6),
+            ("score @ 1", 6),
+            ("nope(score)", 0),
+            ("len(a, b)", 0),
+            ("'open", 0),
+            ("rows | [0]", 5),
+            ("rows[0:2]", 6),
+            ("rows[]", 5),
+            ("`true`", 0),
+            ("{id: id}", 0),
+            ("sort_by(rows, &id)", 14),
+        ] {
+            match Expr::parse(text) {
+                Err(ExprError::Syntax { position: at, .. }) => assert_eq!(at, position, "{text}"),
+                other => panic!("{text}: {other:?}"),
+            }
+        }
+        assert!(matches!(
+            evaluate("1 / 0", &Value::Null),
+            Err(ExprError::Eval(_))
+        ));
+        assert!(matches!(
+            evaluate("status - 1", &json!({ "status": "x" })),
+            Err(ExprError::Eval(_))
+        ));
+    }
+
+    #[test]
+    fn deep_nesting_is_a_syntax_error() {
+        let too_deep = [
+            format!("{}1{}", "(".repeat(10_000), ")".repeat(10_000)),
+            format!("{}x", "!".repeat(10_000)),
+            format!("{}x", "-".repeat(10_000)),
+            vec!["1"; 10_000].join(" + "),
+            format!("x{}", ".a".repeat(10_000)),
+            format!("{}1{}", "[".repeat(10_000), "]".repeat(10_000)),
+        ];
+        for text in too_deep {
+            match Expr::parse(&text) {
+                Err(ExprError::Syntax { message, .. }) => assert!(message.contains("too deeply")),
+                other => panic!("{}…: {other:?}", &text[..20]),
+            }
+        }
+        // Reasonable expressions are nowhere near the limit
+        let nested = format!("{}1{}", "(".repeat(40), ")".repeat(40));
+        assert_eq!(evaluate(&nested, &Value::Null).unwrap(), json!(1));
+        assert_eq!(evaluate(&vec!["1"; 60].join(" + "), &Value::Null).unwrap(), json!(60));
+    }
+}
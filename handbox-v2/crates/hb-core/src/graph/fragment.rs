@@ -0,0 +1,426 @@
+//! Fragments — a selection of nodes and the edges between them, copied out
+//! of one workflow and pasted into another.
+//!
+//! A fragment is plain JSON so it survives the clipboard and chat messages;
+//! [`WorkflowFragment::parse`] also takes it wrapped in a ``` fence. Edges
+//! that crossed the selection boundary travel in `detached` so the editor can
+//! offer to reconnect them. `requires` lists what the nodes depend on — tool
+//! refs, referenced workflows and `{{env:NAME}}` variables — so the receiving
+//! project can tell what is missing before anything runs.
+//!
+//! Pasting renames node and edge IDs that collide with the target workflow
+//! and moves the nodes by an offset so they don't land on the originals.
+
+use super::{EdgeSpec, NodeEntry, Position, WorkflowSpec};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// `format` of every fragment.
+pub const FRAGMENT_FORMAT: &str = "handbox/fragment";
+/// Newest fragment version this build reads and the one it writes.
+pub const FRAGMENT_VERSION: u32 = 1;
+
+const ENV_OPEN: &str = "{{env:";
+const ENV_CLOSE: &str = "}}";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowFragment {
+    pub format: String,
+    pub version: u32,
+    /// Workflow the fragment was copied from.
+    #[serde(default)]
+    pub source: Option<Uuid>,
+    pub nodes: Vec<NodeEntry>,
+    /// Edges between the fragment's nodes.
+    #[serde(default)]
+    pub edges: Vec<EdgeSpec>,
+    /// Edges that had one end outside the selection.
+    #[serde(default)]
+    pub detached: Vec<EdgeSpec>,
+    #[serde(default)]
+    pub requires: FragmentRequirements,
+}
+
+/// What a fragment's nodes need from the workflow or project they land in.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FragmentRequirements {
+    #[serde(default)]
+    pub tools: Vec<String>,
+    #[serde(default)]
+    pub workflows: Vec<Uuid>,
+    #[serde(default)]
+    pub env: Vec<String>,
+}
+
+/// Result of [`WorkflowSpec::paste_fragment`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PastedFragment {
+    /// Fragment ID → ID in the workflow, for the nodes that were renamed.
+    pub id_map: HashMap<String, String>,
+    /// IDs of the pasted nodes, in fragment order.
+    pub nodes: Vec<String>,
+    /// The fragment's detached edges with their pasted end renamed.
+    pub detached: Vec<EdgeSpec>,
+    pub requires: FragmentRequirements,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum FragmentError {
+    #[error("not a workflow fragment: {0}")]
+    Parse(String),
+    #[error("unsupported fragment format '{0}'")]
+    UnsupportedFormat(String),
+    #[error("fragment version {0} is newer than this version of Handbox supports")]
+    NewerVersion(u32),
+    #[error("fragment has no nodes")]
+    Empty,
+    #[error("node '{0}' is not in the workflow")]
+    UnknownNode(String),
+    #[error("node '{0}' appears more than once in the fragment")]
+    DuplicateNode(String),
+    #[error("edge '{edge}' connects node '{node}', which is not in the fragment")]
+    DanglingEdge { edge: String, node: String },
+}
+
+impl WorkflowFragment {
+    /// Pretty JSON, as put on the clipboard.
+    pub fn to_text(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Read a fragment from clipboard or chat text, with or without a
+    /// surrounding ``` fence, and [`validate`](Self::validate) it.
+    pub fn parse(text: &str) -> Result<Self, FragmentError> {
+        let fragment: Self =
+            serde_json::from_str(unfence(text)).map_err(|e| FragmentError::Parse(e.to_string()))?;
+        fragment.validate()?;
+        Ok(fragment)
+    }
+
+    /// Check the format and version, that node IDs are unique and that every
+    /// edge connects nodes of the fragment.
+    pub fn validate(&self) -> Result<(), FragmentError> {
+        if self.format != FRAGMENT_FORMAT {
+            return Err(FragmentError::UnsupportedFormat(self.format.clone()));
+        }
+        if self.version > FRAGMENT_VERSION {
+            return Err(FragmentError::NewerVersion(self.version));
+        }
+        if self.nodes.is_empty() {
+            return Err(FragmentError::Empty);
+        }
+        let mut ids = HashSet::new();
+        for node in &self.nodes {
+            if !ids.insert(node.id()) {
+                return Err(FragmentError::DuplicateNode(node.id().to_string()));
+            }
+        }
+        for edge in &self.edges {
+            for node in [&edge.source_node, &edge.target_node] {
+                if !ids.contains(node.as_str()) {
+                    return Err(FragmentError::DanglingEdge {
+                        edge: edge.id.clone(),
+                        node: node.clone(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl WorkflowSpec {
+    /// The nodes in `node_ids`, in workflow order, with the edges among them.
+    pub fn copy_fragment(&self, node_ids: &[String]) -> Result<WorkflowFragment, FragmentError> {
+        if let Some(missing) = node_ids
+            .iter()
+            .find(|id| !self.nodes.iter().any(|n| n.id() == id.as_str()))
+        {
+            return Err(FragmentError::UnknownNode(missing.clone()));
+        }
+        let selected: HashSet<&str> = node_ids.iter().map(String::as_str).collect();
+        if selected.is_empty() {
+            return Err(FragmentError::Empty);
+        }
+        let nodes: Vec<NodeEntry> = self
+            .nodes
+            .iter()
+            .filter(|n| selected.contains(n.id()))
+            .cloned()
+            .collect();
+        let (edges, detached) = self
+            .edges
+            .iter()
+            .filter(|e| {
+                selected.contains(e.source_node.as_str())
+                    || selected.contains(e.target_node.as_str())
+            })
+            .cloned()
+            .partition(|e| {
+                selected.contains(e.source_node.as_str())
+                    && selected.contains(e.target_node.as_str())
+            });
+        let requires = requirements(&nodes);
+        Ok(WorkflowFragment {
+            format: FRAGMENT_FORMAT.into(),
+            version: FRAGMENT_VERSION,
+            source: Some(self.id),
+            nodes,
+            edges,
+            detached,
+            requires,
+        })
+    }
+
+    /// Add `fragment` to the workflow, renaming node and edge IDs already in
+    /// use to `{id}_2`, `{id}_3`, … and moving positioned nodes by `offset`.
+    pub fn paste_fragment(
+        &mut self,
+        fragment: WorkflowFragment,
+        offset: Position,
+    ) -> Result<PastedFragment, FragmentError> {
+        fragment.validate()?;
+        let mut taken: HashSet<String> = self.nodes.iter().map(|n| n.id().to_string()).collect();
+        let mut id_map = HashMap::new();
+        let mut pasted = Vec::with_capacity(fragment.nodes.len());
+        for mut node in fragment.nodes {
+            let id = unused(node.id(), &taken);
+            if id != node.id() {
+                id_map.insert(node.id().to_string(), id.clone());
+                set_id(&mut node, id.clone());
+            }
+            shift(&mut node, offset);
+            taken.insert(id.clone());
+            pasted.push(id);
+            self.nodes.push(node);
+        }
+
+        let rename = |node: &mut String| {
+            if let Some(new) = id_map.get(node.as_str()) {
+                *node = new.clone();
+            }
+        };
+        let mut edge_ids: HashSet<String> = self.edges.iter().map(|e| e.id.clone()).collect();
+        for mut edge in fragment.edges {
+            rename(&mut edge.source_node);
+            rename(&mut edge.target_node);
+            edge.id = unused(&edge.id, &edge_ids);
+            edge_ids.insert(edge.id.clone());
+            self.edges.push(edge);
+        }
+        let detached = fragment
+            .detached
+            .into_iter()
+            .map(|mut edge| {
+                rename(&mut edge.source_node);
+                rename(&mut edge.target_node);
+                edge
+            })
+            .collect();
+
+        Ok(PastedFragment {
+            id_map,
+            nodes: pasted,
+            detached,
+            requires: fragment.requires,
+        })
+    }
+}
+
+/// The contents of the first ``` fence in `text` (without its language
+/// tag), or all of `text` when there is none.
+fn unfence(text: &str) -> &str {
+    let Some(start) = text.find("```") else {
+        return text.trim();
+    };
+    let inner = &text[start + 3..];
+    let inner = inner.find("```").map_or(inner, |end| &inner[..end]);
+    // Drop the info string, e.g. "json".
+    match inner.find('\n') {
+        Some(newline) if !inner[..newline].trim_start().starts_with(['{', '[']) => {
+            inner[newline + 1..].trim()
+        }
+        _ => inner.trim(),
+    }
+}
+
+/// `id` if it is free, else the first free `{id}_{n}` from n = 2.
+fn unused(id: &str, taken: &HashSet<String>) -> String {
+    if !taken.contains(id) {
+        return id.to_string();
+    }
+    (2..)
+        .map(|n| format!("{id}_{n}"))
+        .find(|candidate| !taken.contains(candidate))
+        .expect("some suffix is free")
+}
+
+fn set_id(node: &mut NodeEntry, id: String) {
+    match node {
+        NodeEntry::Primitive(n) => n.id = id,
+        NodeEntry::Composite(n) => n.id = id,
+        NodeEntry::Conditional(n) => n.id = id,
+        NodeEntry::Loop(n) => n.id = id,
+        NodeEntry::MapReduce(n) => n.id = id,
+        NodeEntry::WorkflowRef(n) => n.id = id,
+    }
+}
+
+fn shift(node: &mut NodeEntry, offset: Position) {
+    let position = match node {
+        NodeEntry::Primitive(n) => &mut n.position,
+        NodeEntry::Composite(n) => &mut n.position,
+        NodeEntry::WorkflowRef(n) => &mut n.position,
+        NodeEntry::Conditional(_) | NodeEntry::Loop(_) | NodeEntry::MapReduce(_) => return,
+    };
+    if let Some(p) = position {
+        p.x += offset.x;
+        p.y += offset.y;
+    }
+}
+
+/// Tool refs, referenced workflows and environment variables used by
+/// `nodes` at any depth, each once, in the order they first appear.
+fn requirements(nodes: &[NodeEntry]) -> FragmentRequirements {
+    fn visit(nodes: &[NodeEntry], out: &mut FragmentRequirements) {
+        for node in nodes {
+            match node {
+                NodeEntry::Primitive(n) => {
+                    if !out.tools.contains(&n.tool_ref) {
+                        out.tools.push(n.tool_ref.clone());
+                    }
+                    n.config.values().for_each(|v| env_refs(v, &mut out.env));
+                }
+                NodeEntry::WorkflowRef(n) if !out.workflows.contains(&n.workflow_id) => {
+                    out.workflows.push(n.workflow_id);
+                }
+                _ => {}
+            }
+            for subgraph in node.subgraphs() {
+                visit(&subgraph.nodes, out);
+            }
+        }
+    }
+    let mut requires = FragmentRequirements::default();
+    visit(nodes, &mut requires);
+    requires
+}
+
+/// Names of the `{{env:NAME}}` references in `value`'s strings.
+fn env_refs(value: &serde_json::Value, out: &mut Vec<String>) {
+    match value {
+        serde_json::Value::String(s) => {
+            let mut rest = s.as_str();
+            while let Some(start) = rest.find(ENV_OPEN) {
+                let after = &rest[start + ENV_OPEN.len()..];
+                let Some(end) = after.find(ENV_CLOSE) else {
+                    break;
+                };
+                let name = after[..end].trim();
+                if !name.is_empty() && !out.iter().any(|n| n == name) {
+                    out.push(name.to_string());
+                }
+                rest = &after[end + ENV_CLOSE.len()..];
+            }
+        }
+        serde_json::Value::Array(items) => items.iter().for_each(|v| env_refs(v, out)),
+        serde_json::Value::Object(map) => map.values().for_each(|v| env_refs(v, out)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{EdgeKind, NodeSpec};
+
+    fn node(id: &str, config: serde_json::Value) -> NodeEntry {
+        NodeEntry::Primitive(NodeSpec {
+            id: id.into(),
+            tool_ref: format!("core/{id}@1.0.0"),
+            config: config.as_object().cloned().unwrap_or_default(),
+            position: Some(Position { x: 10.0, y: 20.0 }),
+            ..Default::default()
+        })
+    }
+
+    fn edge(id: &str, from: &str, to: &str) -> EdgeSpec {
+        EdgeSpec {
+            id: id.into(),
+            source_node: from.into(),
+            source_port: "out".into(),
+            target_node: to.into(),
+            target_port: "in".into(),
+            kind: EdgeKind::default(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn fragments_round_trip_through_text_and_paste_without_collisions() {
+        let source = WorkflowSpec {
+            nodes: vec![
+                node("a", serde_json::json!({})),
+                node("b", serde_json::json!({ "key": "{{env:API_KEY}}" })),
+                node("c", serde_json::json!({})),
+            ],
+            edges: vec![edge("e1", "a", "b"), edge("e2", "b", "c")],
+            ..Default::default()
+        };
+        let fragment = source.copy_fragment(&["b".into(), "a".into()]).unwrap();
+        assert_eq!(fragment.nodes.len(), 2);
+        assert_eq!(fragment.edges.len(), 1);
+        assert_eq!(fragment.detached[0].id, "e2");
+        assert_eq!(fragment.requires.env, ["API_KEY"]);
+        assert_eq!(fragment.requires.tools, ["core/a@1.0.0", "core/b@1.0.0"]);
+        assert_eq!(
+            source.copy_fragment(&["zz".into()]).unwrap_err(),
+            FragmentError::UnknownNode("zz".into())
+        );
+
+        let message = format!("here you go:\n```json\n{}\n```\nenjoy", fragment.to_text());
+        let parsed = WorkflowFragment::parse(&message).unwrap();
+        assert!(matches!(
+            WorkflowFragment::parse("{}"),
+            Err(FragmentError::Parse(_))
+        ));
+
+        let mut target = source.clone();
+        let pasted = target
+            .paste_fragment(parsed, Position { x: 40.0, y: 40.0 })
+            .unwrap();
+        assert_eq!(pasted.nodes, ["a_2", "b_2"]);
+        assert_eq!(pasted.detached[0].source_node, "b_2");
+        assert_eq!(pasted.detached[0].target_node, "c");
+        let pasted_edge = target.edges.last().unwrap();
+        assert_eq!(pasted_edge.id, "e1_2");
+        assert_eq!(
+            (
+                pasted_edge.source_node.as_str(),
+                pasted_edge.target_node.as_str()
+            ),
+            ("a_2", "b_2")
+        );
+        let NodeEntry::Primitive(b) = &target.nodes[4] else {
+            panic!("pasted node is not primitive");
+        };
+        assert_eq!(b.position.map(|p| (p.x, p.y)), Some((50.0, 60.0)));
+
+        let mut broken = fragment.clone();
+        broken.edges.push(edge("e9", "a", "c"));
+        assert_eq!(
+            broken.validate(),
+            Err(FragmentError::DanglingEdge {
+                edge: "e9".into(),
+                node: "c".into()
+            })
+        );
+        broken.format = "other".into();
+        assert!(matches!(
+            broken.validate(),
+            Err(FragmentError::UnsupportedFormat(_))
+        ));
+    }
+}
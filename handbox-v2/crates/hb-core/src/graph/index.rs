@@ -0,0 +1,125 @@
+//! Node and edge lookup by node ID in O(1), for code that walks large
+//! graphs node by node (the scheduler, validation) instead of scanning
+//! `nodes` and `edges` for every node.
+
+use super::{EdgeSpec, NodeEntry, SubgraphSpec, WorkflowSpec};
+use std::collections::HashMap;
+
+/// Borrowed index over a graph's nodes and edges.
+#[derive(Debug, Default)]
+pub struct GraphIndex<'a> {
+    nodes: HashMap<&'a str, &'a NodeEntry>,
+    /// Edges into each node, in declaration order.
+    incoming: HashMap<&'a str, Vec<&'a EdgeSpec>>,
+    /// Edges out of each node, in declaration order.
+    outgoing: HashMap<&'a str, Vec<&'a EdgeSpec>>,
+}
+
+impl<'a> GraphIndex<'a> {
+    pub fn new(nodes: &'a [NodeEntry], edges: &'a [EdgeSpec]) -> Self {
+        let mut index = Self {
+            nodes: nodes.iter().map(|n| (n.id(), n)).collect(),
+            ..Default::default()
+        };
+        for edge in edges {
+            index
+                .incoming
+                .entry(edge.target_node.as_str())
+                .or_default()
+                .push(edge);
+            index
+                .outgoing
+                .entry(edge.source_node.as_str())
+                .or_default()
+                .push(edge);
+        }
+        index
+    }
+
+    pub fn node(&self, id: &str) -> Option<&'a NodeEntry> {
+        self.nodes.get(id).copied()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.nodes.contains_key(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Edges of every kind that end at `id`.
+    pub fn incoming(&self, id: &str) -> impl Iterator<Item = &'a EdgeSpec> + '_ {
+        self.incoming.get(id).into_iter().flatten().copied()
+    }
+
+    /// Edges of every kind that start at `id`.
+    pub fn outgoing(&self, id: &str) -> impl Iterator<Item = &'a EdgeSpec> + '_ {
+        self.outgoing.get(id).into_iter().flatten().copied()
+    }
+}
+
+impl WorkflowSpec {
+    /// Index of the top-level nodes and edges.
+    pub fn index(&self) -> GraphIndex<'_> {
+        GraphIndex::new(&self.nodes, &self.edges)
+    }
+}
+
+impl SubgraphSpec {
+    pub fn index(&self) -> GraphIndex<'_> {
+        GraphIndex::new(&self.nodes, &self.edges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::NodeSpec;
+
+    fn node(id: &str) -> NodeEntry {
+        NodeEntry::Primitive(NodeSpec {
+            id: id.into(),
+            tool_ref: "text-merge".into(),
+            ..Default::default()
+        })
+    }
+
+    fn edge(id: &str, from: &str, to: &str) -> EdgeSpec {
+        EdgeSpec {
+            id: id.into(),
+            source_node: from.into(),
+            source_port: "out".into(),
+            target_node: to.into(),
+            target_port: "in".into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn nodes_and_edges_are_found_by_node_id() {
+        let spec = WorkflowSpec {
+            nodes: vec![node("a"), node("b"), node("c")],
+            edges: vec![
+                edge("e1", "a", "c"),
+                edge("e2", "b", "c"),
+                edge("e3", "a", "b"),
+            ],
+            ..Default::default()
+        };
+        let index = spec.index();
+
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.node("b").map(NodeEntry::id), Some("b"));
+        assert!(index.node("missing").is_none());
+        let ids =
+            |edges: Vec<&EdgeSpec>| edges.into_iter().map(|e| e.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(index.incoming("c").collect()), ["e1", "e2"]);
+        assert_eq!(ids(index.outgoing("a").collect()), ["e1", "e3"]);
+        assert_eq!(index.incoming("a").count(), 0);
+    }
+}
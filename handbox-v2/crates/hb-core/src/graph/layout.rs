@@ -0,0 +1,266 @@
+//! Graph layout — places workflow nodes on a 2D canvas for rendering.
+//!
+//! Stored UI positions are used when every node has one; otherwise nodes are
+//! arranged left-to-right in layers by longest path from the sources.
+
+use super::{NodeEntry, WorkflowSpec};
+use serde::Serialize;
+use std::collections::HashMap;
+
+pub const NODE_WIDTH: f64 = 180.0;
+pub const NODE_HEIGHT: f64 = 60.0;
+const LAYER_GAP: f64 = 80.0;
+const ROW_GAP: f64 = 40.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoxKind {
+    Primitive,
+    Composite,
+    Conditional,
+    Loop,
+    MapReduce,
+    WorkflowRef,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeBox {
+    pub id: String,
+    pub label: String,
+    pub kind: BoxKind,
+    pub disabled: bool,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EdgeLine {
+    pub source: usize,
+    pub target: usize,
+}
+
+/// Laid-out graph; coordinates are normalized so the bounds start at (0, 0).
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphLayout {
+    pub nodes: Vec<NodeBox>,
+    /// Edges as indices into `nodes`; edges to unknown nodes are dropped.
+    pub edges: Vec<EdgeLine>,
+    pub width: f64,
+    pub height: f64,
+}
+
+fn describe(entry: &NodeEntry) -> (String, BoxKind, bool) {
+    match entry {
+        NodeEntry::Primitive(n) => {
+            // "pack/tool@version" → "tool"
+            let fallback = n
+                .tool_ref
+                .rsplit('/')
+                .next()
+                .and_then(|t| t.split('@').next())
+                .unwrap_or(&n.tool_ref);
+            (
+                n.label.clone().unwrap_or_else(|| fallback.to_string()),
+                BoxKind::Primitive,
+                n.disabled,
+            )
+        }
+        NodeEntry::Composite(n) => (
+            n.label.clone().unwrap_or_else(|| n.id.clone()),
+            BoxKind::Composite,
+            false,
+        ),
+        NodeEntry::Conditional(n) => (n.id.clone(), BoxKind::Conditional, false),
+        NodeEntry::Loop(n) => (n.id.clone(), BoxKind::Loop, false),
+        NodeEntry::MapReduce(n) => (n.id.clone(), BoxKind::MapReduce, false),
+        NodeEntry::WorkflowRef(n) => (
+            n.label.clone().unwrap_or_else(|| n.id.clone()),
+            BoxKind::WorkflowRef,
+            false,
+        ),
+    }
+}
+
+fn stored_position(entry: &NodeEntry) -> Option<(f64, f64)> {
+    match entry {
+        NodeEntry::Primitive(n) => n.position.as_ref().map(|p| (p.x, p.y)),
+        NodeEntry::Composite(n) => n.position.as_ref().map(|p| (p.x, p.y)),
+        NodeEntry::WorkflowRef(n) => n.position.as_ref().map(|p| (p.x, p.y)),
+        NodeEntry::Conditional(_) | NodeEntry::Loop(_) | NodeEntry::MapReduce(_) => None,
+    }
+}
+
+/// Layer index per node: longest path from any source. Nodes on cycles are
+/// placed one layer after the deepest acyclic node.
+fn layers(node_count: usize, edges: &[EdgeLine]) -> Vec<usize> {
+    let mut indegree = vec![0usize; node_count];
+    let mut outgoing = vec![Vec::new(); node_count];
+    for e in edges {
+        indegree[e.target] += 1;
+        outgoing[e.source].push(e.target);
+    }
+
+    let mut layer = vec![0usize; node_count];
+    let mut placed = vec![false; node_count];
+    let mut queue: Vec<usize> = (0..node_count).filter(|&i| indegree[i] == 0).collect();
+    while let Some(i) = queue.pop() {
+        placed[i] = true;
+        for &t in &outgoing[i] {
+            layer[t] = layer[t].max(layer[i] + 1);
+            indegree[t] -= 1;
+            if indegree[t] == 0 {
+                queue.push(t);
+            }
+        }
+    }
+
+    let deepest = layer
+        .iter()
+        .zip(&placed)
+        .filter(|(_, p)| **p)
+        .map(|(l, _)| *l)
+        .max()
+        .unwrap_or(0);
+    for (l, p) in layer.iter_mut().zip(&placed) {
+        if !*p {
+            *l = deepest + 1;
+        }
+    }
+    layer
+}
+
+/// Lay out the top-level nodes and edges of a workflow.
+pub fn layout(spec: &WorkflowSpec) -> GraphLayout {
+    let index: HashMap<&str, usize> = spec
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(i, n)| (n.id(), i))
+        .collect();
+    let edges: Vec<EdgeLine> = spec
+        .edges
+        .iter()
+        .filter_map(|e| {
+            Some(EdgeLine {
+                source: *index.get(e.source_node.as_str())?,
+                target: *index.get(e.target_node.as_str())?,
+            })
+        })
+        .collect();
+
+    let stored: Option<Vec<(f64, f64)>> = spec.nodes.iter().map(stored_position).collect();
+    let positions = match stored {
+        Some(p) if !p.is_empty() => p,
+        _ => {
+            let layer = layers(spec.nodes.len(), &edges);
+            let mut rows: HashMap<usize, usize> = HashMap::new();
+            layer
+                .iter()
+                .map(|&l| {
+                    let row = rows.entry(l).or_insert(0);
+                    let pos = (
+                        l as f64 * (NODE_WIDTH + LAYER_GAP),
+                        *row as f64 * (NODE_HEIGHT + ROW_GAP),
+                    );
+                    *row += 1;
+                    pos
+                })
+                .collect()
+        }
+    };
+
+    let min_x = positions.iter().map(|p| p.0).fold(f64::INFINITY, f64::min);
+    let min_y = positions.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+    let (min_x, min_y) = if positions.is_empty() {
+        (0.0, 0.0)
+    } else {
+        (min_x, min_y)
+    };
+
+    let nodes: Vec<NodeBox> = spec
+        .nodes
+        .iter()
+        .zip(&positions)
+        .map(|(entry, (x, y))| {
+            let (label, kind, disabled) = describe(entry);
+            NodeBox {
+                id: entry.id().to_string(),
+                label,
+                kind,
+                disabled,
+                x: x - min_x,
+                y: y - min_y,
+                width: NODE_WIDTH,
+                height: NODE_HEIGHT,
+            }
+        })
+        .collect();
+
+    let width = nodes.iter().map(|n| n.x + n.width).fold(0.0, f64::max);
+    let height = nodes.iter().map(|n| n.y + n.height).fold(0.0, f64::max);
+    GraphLayout {
+        nodes,
+        edges,
+        width,
+        height,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{EdgeSpec, NodeSpec, Position};
+
+    fn node(id: &str, position: Option<Position>) -> NodeEntry {
+        NodeEntry::Primitive(NodeSpec {
+            id: id.into(),
+            tool_ref: format!("core-tools/{id}@1.0.0"),
+            position,
+            ..Default::default()
+        })
+    }
+
+    fn edge(from: &str, to: &str) -> EdgeSpec {
+        EdgeSpec {
+            id: format!("{from}-{to}"),
+            source_node: from.into(),
+            source_port: "out".into(),
+            target_node: to.into(),
+            target_port: "in".into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn auto_layout_layers_by_longest_path() {
+        let spec = WorkflowSpec {
+            nodes: vec![node("c", None), node("a", None), node("b", None)],
+            edges: vec![edge("a", "b"), edge("b", "c"), edge("a", "c")],
+            ..Default::default()
+        };
+        let layout = layout(&spec);
+        let x = |id: &str| layout.nodes.iter().find(|n| n.id == id).unwrap().x;
+        assert_eq!(x("a"), 0.0);
+        assert!(x("b") > x("a"));
+        assert!(x("c") > x("b"));
+        assert_eq!(layout.edges.len(), 3);
+        assert_eq!(layout.nodes[1].label, "a");
+    }
+
+    #[test]
+    fn stored_positions_are_normalized() {
+        let spec = WorkflowSpec {
+            nodes: vec![
+                node("a", Some(Position { x: 100.0, y: 50.0 })),
+                node("b", Some(Position { x: 400.0, y: 150.0 })),
+            ],
+            ..Default::default()
+        };
+        let layout = layout(&spec);
+        assert_eq!((layout.nodes[0].x, layout.nodes[0].y), (0.0, 0.0));
+        assert_eq!((layout.nodes[1].x, layout.nodes[1].y), (300.0, 100.0));
+        assert_eq!(layout.width, 300.0 + NODE_WIDTH);
+    }
+}
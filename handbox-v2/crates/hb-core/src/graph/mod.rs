@@ -1,16 +1,31 @@
 //! Graph DSL v0.1 — ReactFlow-independent executable workflow specification.
 
+use crate::tool::ConfigOption;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+pub mod fragment;
+pub mod index;
+pub mod layout;
+pub mod page;
+pub mod params;
+pub mod upgrade;
+pub mod validate;
+
+pub use fragment::{FragmentError, FragmentRequirements, PastedFragment, WorkflowFragment};
+pub use index::GraphIndex;
+pub use page::{PageError, SpecAssembler, SpecOutline, SpecPage};
+pub use upgrade::{SpecMigrator, UpgradeError, UpgradedSpec, SPEC_VERSION};
+pub use validate::{Severity, SpecIssue};
+
 // ---------------------------------------------------------------------------
 // WorkflowSpec — top-level graph
 // ---------------------------------------------------------------------------
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkflowSpec {
-    /// Schema version (always "0.1.0" for this release).
+    /// Graph DSL version the spec was written for; see [`upgrade`].
     pub version: String,
 
     /// Unique workflow identifier.
@@ -33,18 +48,28 @@ pub struct WorkflowSpec {
     /// Packs required by this workflow.
     #[serde(default)]
     pub required_packs: Vec<PackDependency>,
+
+    /// Test cases run before the workflow is published.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tests: Vec<WorkflowTest>,
+
+    /// Boxes grouping nodes in the editor.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<NodeGroup>,
 }
 
 impl Default for WorkflowSpec {
     fn default() -> Self {
         Self {
-            version: "0.1.0".into(),
+            version: SPEC_VERSION.into(),
             id: Uuid::new_v4(),
             meta: WorkflowMeta::default(),
             variables: Vec::new(),
             nodes: Vec::new(),
             edges: Vec::new(),
             required_packs: Vec::new(),
+            tests: Vec::new(),
+            groups: Vec::new(),
         }
     }
 }
@@ -65,6 +90,14 @@ pub struct WorkflowMeta {
     pub created_at: DateTime<Utc>,
     #[serde(default = "Utc::now")]
     pub updated_at: DateTime<Utc>,
+    /// Outputs the workflow must produce, checked by the runner when an
+    /// execution completes.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub output_contract: Vec<OutputAssertion>,
+    /// Reuse the outputs of chains of pure tools from earlier executions
+    /// (see `hb_runner::memo`).
+    #[serde(default)]
+    pub memoize: bool,
 }
 
 impl Default for WorkflowMeta {
@@ -76,10 +109,29 @@ impl Default for WorkflowMeta {
             tags: Vec::new(),
             created_at: Utc::now(),
             updated_at: Utc::now(),
+            output_contract: Vec::new(),
+            memoize: false,
         }
     }
 }
 
+/// One expected output: a port of a (usually terminal) node that must be
+/// present, and optionally of a type, non-empty, or matching a schema.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OutputAssertion {
+    pub node_id: String,
+    pub port: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port_type: Option<PortType>,
+    /// Reject null, empty strings, arrays and objects.
+    #[serde(default)]
+    pub non_empty: bool,
+    /// JSON Schema the value must match (`type`, `required`, `properties`,
+    /// `items`, `enum`, `minItems`, `minLength`, `minimum`, `maximum`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<serde_json::Value>,
+}
+
 // ---------------------------------------------------------------------------
 // Variables
 // ---------------------------------------------------------------------------
@@ -91,6 +143,32 @@ pub struct VariableSpec {
     pub var_type: PortType,
     pub default_value: Option<serde_json::Value>,
     pub required: bool,
+    /// Display label in parameter forms; defaults to `name`.
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Form section this variable is shown in.
+    #[serde(default)]
+    pub group: Option<String>,
+    /// Allowed values; when non-empty the form renders a select.
+    #[serde(default)]
+    pub options: Vec<ConfigOption>,
+    #[serde(default)]
+    pub constraints: VariableConstraints,
+}
+
+/// Value constraints checked when parameters are submitted.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VariableConstraints {
+    /// Inclusive bounds for `number` variables.
+    #[serde(default)]
+    pub minimum: Option<f64>,
+    #[serde(default)]
+    pub maximum: Option<f64>,
+    /// Length bounds for `string` (characters) and `array` (items) variables.
+    #[serde(default)]
+    pub min_length: Option<usize>,
+    #[serde(default)]
+    pub max_length: Option<usize>,
 }
 
 // ---------------------------------------------------------------------------
@@ -108,6 +186,10 @@ pub enum NodeEntry {
     Conditional(ConditionalSpec),
     #[serde(rename = "loop")]
     Loop(LoopSpec),
+    #[serde(rename = "map_reduce")]
+    MapReduce(MapReduceSpec),
+    #[serde(rename = "workflow_ref")]
+    WorkflowRef(WorkflowRefSpec),
 }
 
 impl NodeEntry {
@@ -117,6 +199,84 @@ impl NodeEntry {
             NodeEntry::Composite(n) => &n.id,
             NodeEntry::Conditional(n) => &n.id,
             NodeEntry::Loop(n) => &n.id,
+            NodeEntry::MapReduce(n) => &n.id,
+            NodeEntry::WorkflowRef(n) => &n.id,
+        }
+    }
+
+    /// Sub-graphs the node embeds: a composite's subgraph, branch, loop and
+    /// map/reduce bodies. Referenced workflows are not embedded.
+    pub fn subgraphs(&self) -> Vec<&SubgraphSpec> {
+        match self {
+            NodeEntry::Primitive(_) | NodeEntry::WorkflowRef(_) => Vec::new(),
+            NodeEntry::Composite(n) => vec![&n.subgraph],
+            NodeEntry::Conditional(n) => n
+                .branches
+                .iter()
+                .map(|b| &b.body)
+                .chain(&n.default_branch)
+                .collect(),
+            NodeEntry::Loop(n) => vec![&n.body],
+            NodeEntry::MapReduce(n) => std::iter::once(&n.map).chain(&n.reduce).collect(),
+        }
+    }
+
+    pub fn subgraphs_mut(&mut self) -> Vec<&mut SubgraphSpec> {
+        match self {
+            NodeEntry::Primitive(_) | NodeEntry::WorkflowRef(_) => Vec::new(),
+            NodeEntry::Composite(n) => vec![&mut n.subgraph],
+            NodeEntry::Conditional(n) => n
+                .branches
+                .iter_mut()
+                .map(|b| &mut b.body)
+                .chain(&mut n.default_branch)
+                .collect(),
+            NodeEntry::Loop(n) => vec![&mut n.body],
+            NodeEntry::MapReduce(n) => std::iter::once(&mut n.map).chain(&mut n.reduce).collect(),
+        }
+    }
+
+    /// Expressions the node evaluates at run time, by field name, for
+    /// [`crate::expr::check_nodes`]. Sub-graphs are not included.
+    pub fn expressions(&self) -> Vec<(&'static str, &str)> {
+        fn optional<'a>(field: &'static str, expr: &'a Option<String>) -> Option<(&'static str, &'a str)> {
+            expr.as_deref().map(|e| (field, e))
+        }
+        match self {
+            NodeEntry::Conditional(n) => vec![("condition_expr", n.condition_expr.as_str())],
+            NodeEntry::Loop(n) => [
+                optional("condition_expr", &n.condition_expr),
+                optional("items_expr", &n.items_expr),
+                optional("collect_expr", &n.collect_expr),
+            ]
+            .into_iter()
+            .flatten()
+            .collect(),
+            NodeEntry::MapReduce(n) => [
+                optional("items_expr", &n.items_expr),
+                optional("collect_expr", &n.collect_expr),
+            ]
+            .into_iter()
+            .flatten()
+            .collect(),
+            NodeEntry::Primitive(_) | NodeEntry::Composite(_) | NodeEntry::WorkflowRef(_) => Vec::new(),
+        }
+    }
+
+    /// Note, color tag and group; only primitive and composite nodes have them.
+    pub fn presentation(&self) -> Option<&Presentation> {
+        match self {
+            NodeEntry::Primitive(n) => Some(&n.presentation),
+            NodeEntry::Composite(n) => Some(&n.presentation),
+            _ => None,
+        }
+    }
+
+    /// Fan-in mode; only primitive nodes configure one, others use the default.
+    pub fn fan_in(&self) -> FanIn {
+        match self {
+            NodeEntry::Primitive(n) => n.fan_in,
+            _ => FanIn::default(),
         }
     }
 }
@@ -125,7 +285,7 @@ impl NodeEntry {
 // Primitive Node
 // ---------------------------------------------------------------------------
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct NodeSpec {
     pub id: String,
 
@@ -155,6 +315,40 @@ pub struct NodeSpec {
     /// Per-node cache policy override.
     #[serde(default)]
     pub cache: Option<CachePolicy>,
+
+    /// How values from several data edges into the same input port combine.
+    #[serde(default)]
+    pub fan_in: FanIn,
+
+    /// Scheduler resource class (e.g. "llm") whose concurrency limit the
+    /// node counts against; derived from the tool when unset.
+    #[serde(default)]
+    pub resource_class: Option<String>,
+
+    /// Note, color tag and group, for the editor only.
+    #[serde(flatten)]
+    pub presentation: Presentation,
+}
+
+/// Fan-in semantics for an input port fed by several data edges.
+///
+/// Incoming edges are always considered in their declaration order in the
+/// workflow's `edges` list, so results never depend on execution timing.
+/// Edges into distinct variadic keys (`inputs.0`, `inputs.1`) are separate
+/// ports and are not affected.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FanIn {
+    /// Collect every upstream value into an array. Failed upstream nodes
+    /// contribute their error output.
+    #[default]
+    MergeAsArray,
+    /// Like `MergeAsArray`, but the node fails without running unless every
+    /// upstream node completed successfully.
+    WaitForAll,
+    /// Take the value of the first upstream node (in edge order) that
+    /// completed successfully.
+    FirstWins,
 }
 
 // ---------------------------------------------------------------------------
@@ -182,11 +376,106 @@ pub struct CompositeNodeSpec {
     #[serde(default)]
     pub output_mapping: Vec<PortMapping>,
 
+    /// Internal config fields exposed as composite-level config.
+    #[serde(default)]
+    pub exposed_config: Vec<ExposedConfigField>,
+
+    /// Values for the exposed config fields, keyed by field name.
+    #[serde(default)]
+    pub config: serde_json::Map<String, serde_json::Value>,
+
     #[serde(default)]
     pub position: Option<Position>,
 
     #[serde(default)]
     pub label: Option<String>,
+
+    /// Note, color tag and group, for the editor only.
+    #[serde(flatten)]
+    pub presentation: Presentation,
+}
+
+impl CompositeNodeSpec {
+    /// The subgraph with exposed config values (or their defaults) written
+    /// into the internal nodes they map to. Nested composites receive the
+    /// value as their own composite-level config.
+    pub fn resolved_subgraph(&self) -> Result<SubgraphSpec, String> {
+        let mut subgraph = self.subgraph.clone();
+        for field in &self.exposed_config {
+            let value = match self.config.get(&field.name).or(field.default_value.as_ref()) {
+                Some(v) => v,
+                None => continue,
+            };
+            for target in &field.targets {
+                let node = node_at_path(&mut subgraph.nodes, &target.node).ok_or_else(|| {
+                    format!(
+                        "config field '{}' maps to missing node '{}'",
+                        field.name, target.node
+                    )
+                })?;
+                match node {
+                    NodeEntry::Primitive(n) => {
+                        n.config.insert(target.key.clone(), value.clone());
+                    }
+                    NodeEntry::Composite(n) => {
+                        n.config.insert(target.key.clone(), value.clone());
+                    }
+                    NodeEntry::Conditional(_)
+                    | NodeEntry::Loop(_)
+                    | NodeEntry::MapReduce(_)
+                    | NodeEntry::WorkflowRef(_) => {
+                        return Err(format!(
+                            "config field '{}' maps to node '{}', which has no config",
+                            field.name, target.node
+                        ))
+                    }
+                }
+            }
+        }
+        Ok(subgraph)
+    }
+}
+
+/// The node at `path`: an ID, or IDs joined by `/` through the sub-graphs
+/// of the nodes on the way (e.g. `batches/request/call`).
+fn node_at_path<'a>(nodes: &'a mut [NodeEntry], path: &str) -> Option<&'a mut NodeEntry> {
+    let (id, rest) = match path.split_once('/') {
+        Some((id, rest)) => (id, Some(rest)),
+        None => (path, None),
+    };
+    let node = nodes.iter_mut().find(|n| n.id() == id)?;
+    match rest {
+        None => Some(node),
+        Some(rest) => node
+            .subgraphs_mut()
+            .into_iter()
+            .find_map(|subgraph| node_at_path(&mut subgraph.nodes, rest)),
+    }
+}
+
+/// A composite-level config field backed by one or more internal config keys.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExposedConfigField {
+    /// Field name in the composite's `config`.
+    pub name: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Used when the composite's `config` does not set the field.
+    #[serde(default)]
+    pub default_value: Option<serde_json::Value>,
+    /// Internal config keys that receive the value.
+    pub targets: Vec<ConfigTarget>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigTarget {
+    /// The internal node ID, or a `/`-separated path to a node inside the
+    /// sub-graphs of internal nodes.
+    pub node: String,
+    /// The config key on that node.
+    pub key: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -213,7 +502,8 @@ pub struct PortMapping {
 pub struct ConditionalSpec {
     pub id: String,
     pub kind: ConditionalKind,
-    /// Expression evaluated to determine the branch (JMESPath or simple comparison).
+    /// Expression evaluated to determine the branch (see [`crate::expr`]).
+    /// Its result is matched against each branch `value`.
     pub condition_expr: String,
     /// Named branches mapping condition results to sub-graphs.
     pub branches: Vec<Branch>,
@@ -249,9 +539,51 @@ pub struct LoopSpec {
     /// For `while` loops: expression that must be truthy to continue.
     #[serde(default)]
     pub condition_expr: Option<String>,
-    /// For `for_each` loops: JMESPath selecting items from an upstream port.
+    /// For `for_each` loops: expression selecting the items from the input
+    /// (see [`crate::expr`]).
     #[serde(default)]
     pub items_expr: Option<String>,
+    /// Path selecting the value collected from each iteration's output
+    /// (an object keyed by body node id). Defaults to the whole output.
+    #[serde(default)]
+    pub collect_expr: Option<String>,
+    /// How collected per-iteration values combine into the loop's `aggregate` output.
+    #[serde(default)]
+    pub aggregation: LoopAggregation,
+}
+
+/// Reserved input ports every node inside a loop body receives for the
+/// current iteration. For `while` loops `total` is `None`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IterationContext {
+    /// Current item (`for_each` only; `null` otherwise).
+    pub item: serde_json::Value,
+    /// Zero-based iteration index.
+    pub index: u32,
+    pub total: Option<u32>,
+    pub is_first: bool,
+    pub is_last: bool,
+}
+
+/// Combination of per-iteration values into a loop's `aggregate` output.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum LoopAggregation {
+    /// Collect values into an array, in iteration order.
+    #[default]
+    Array,
+    /// Join string values with `separator`; array values are flattened.
+    Concat {
+        #[serde(default)]
+        separator: String,
+    },
+    /// Fold values with `expr`, evaluated per iteration against
+    /// `{ "acc", "item", "index" }`; its result becomes the next `acc`.
+    Reduce {
+        expr: String,
+        #[serde(default)]
+        initial: serde_json::Value,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -262,6 +594,102 @@ pub enum LoopKind {
     Repeat,
 }
 
+// ---------------------------------------------------------------------------
+// Map-reduce
+// ---------------------------------------------------------------------------
+
+/// Shards an input array, runs `map` once per shard with bounded
+/// concurrency, then runs `reduce` over the collected shard results.
+///
+/// Each `map` execution receives the [`IterationContext`] ports with `item`
+/// set to the shard (the bare item when `shard_size` is 1). `reduce`
+/// receives `{ "results": [...] }` in shard order. The node outputs
+/// `{ "results", "shards", "reduced" }`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MapReduceSpec {
+    pub id: String,
+    /// Path selecting the array to shard from the node input; defaults to `items`.
+    #[serde(default)]
+    pub items_expr: Option<String>,
+    /// Items per shard.
+    #[serde(default = "default_shard_size")]
+    pub shard_size: u32,
+    /// Maximum number of `map` executions running at once.
+    #[serde(default = "default_max_concurrency")]
+    pub max_concurrency: u32,
+    pub map: SubgraphSpec,
+    /// Path selecting each shard's result from its `map` output (an object
+    /// keyed by body node id). Defaults to the whole output.
+    #[serde(default)]
+    pub collect_expr: Option<String>,
+    /// Without a reduce sub-graph, `reduced` is `null`.
+    #[serde(default)]
+    pub reduce: Option<SubgraphSpec>,
+}
+
+fn default_shard_size() -> u32 {
+    1
+}
+
+fn default_max_concurrency() -> u32 {
+    4
+}
+
+// ---------------------------------------------------------------------------
+// Workflow reference (sub-workflow)
+// ---------------------------------------------------------------------------
+
+/// Runs another saved workflow, by ID, as one node. Unlike a composite the
+/// graph is not copied in, so changes to the referenced workflow reach every
+/// workflow that uses it.
+///
+/// `input_mapping` routes the node's input ports to ports of the referenced
+/// workflow's nodes; `output_mapping` picks the node's outputs from them.
+/// Without output mappings the output is keyed by inner node ID, like a
+/// composite's.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowRefSpec {
+    pub id: String,
+    /// The saved workflow to run.
+    pub workflow_id: Uuid,
+    #[serde(default)]
+    pub input_ports: Vec<PortSpec>,
+    #[serde(default)]
+    pub output_ports: Vec<PortSpec>,
+    /// External input port → inner node.port
+    #[serde(default)]
+    pub input_mapping: Vec<PortMapping>,
+    /// Inner node.port → external output port
+    #[serde(default)]
+    pub output_mapping: Vec<PortMapping>,
+    #[serde(default)]
+    pub position: Option<Position>,
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+impl WorkflowSpec {
+    /// IDs of the workflows referenced by nodes at any depth, each once, in
+    /// the order they first appear.
+    pub fn workflow_refs(&self) -> Vec<Uuid> {
+        fn collect(nodes: &[NodeEntry], out: &mut Vec<Uuid>) {
+            for node in nodes {
+                if let NodeEntry::WorkflowRef(n) = node {
+                    if !out.contains(&n.workflow_id) {
+                        out.push(n.workflow_id);
+                    }
+                }
+                for subgraph in node.subgraphs() {
+                    collect(&subgraph.nodes, out);
+                }
+            }
+        }
+        let mut refs = Vec::new();
+        collect(&self.nodes, &mut refs);
+        refs
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Edge
 // ---------------------------------------------------------------------------
@@ -285,6 +713,21 @@ fn default_edge_id() -> String {
     Uuid::new_v4().to_string()
 }
 
+impl Default for EdgeSpec {
+    /// An empty data edge with a fresh id, for filling in with struct update syntax.
+    fn default() -> Self {
+        Self {
+            id: default_edge_id(),
+            source_node: String::new(),
+            source_port: String::new(),
+            target_node: String::new(),
+            target_port: String::new(),
+            kind: EdgeKind::default(),
+            transform: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum EdgeKind {
@@ -308,6 +751,34 @@ pub struct PortSpec {
     pub required: bool,
     #[serde(default)]
     pub default_value: Option<serde_json::Value>,
+    /// Accepts any number of edges, addressed as `"{name}.{key}"` (e.g.
+    /// `inputs.0`, `inputs.summary`). The tool receives an object keyed by `key`.
+    #[serde(default)]
+    pub variadic: bool,
+    /// Input whose value this output passes on, so it carries the type of
+    /// whatever is wired into that input (e.g. a delay's `output`). Used by
+    /// type inference for `any` ports.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub same_type_as: Option<String>,
+}
+
+impl PortSpec {
+    /// Whether an edge endpoint named `port` connects to this port.
+    pub fn accepts(&self, port: &str) -> bool {
+        match split_variadic_port(port) {
+            (base, Some(_)) => self.variadic && base == self.name,
+            (base, None) => base == self.name,
+        }
+    }
+}
+
+/// Split an edge endpoint port into its base name and variadic key:
+/// `"inputs.2"` → `("inputs", Some("2"))`, `"text"` → `("text", None)`.
+pub fn split_variadic_port(port: &str) -> (&str, Option<&str>) {
+    match port.split_once('.') {
+        Some((base, key)) if !base.is_empty() && !key.is_empty() => (base, Some(key)),
+        _ => (port, None),
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -332,6 +803,67 @@ pub struct Position {
     pub y: f64,
 }
 
+// ---------------------------------------------------------------------------
+// Presentation (UI-only notes, color tags and groups)
+// ---------------------------------------------------------------------------
+
+/// Editor metadata of a node or group. Stored with the spec and ignored by
+/// the runner.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Presentation {
+    /// Free-text note shown with the node.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<ColorTag>,
+
+    /// ID of the [`NodeGroup`] the node (or group) sits in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+}
+
+/// Named colors, so themes decide the actual shade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorTag {
+    Gray,
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Teal,
+    Blue,
+    Purple,
+    Pink,
+}
+
+/// A labelled box around nodes. Nodes join it through their
+/// `presentation.group`; a group in another group nests inside it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeGroup {
+    pub id: String,
+
+    #[serde(default)]
+    pub label: Option<String>,
+
+    /// Top-left corner.
+    #[serde(default)]
+    pub position: Option<Position>,
+
+    #[serde(default)]
+    pub size: Option<Size>,
+
+    #[serde(flatten)]
+    pub presentation: Presentation,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Size {
+    pub width: f64,
+    pub height: f64,
+}
+
 // ---------------------------------------------------------------------------
 // Retry / Cache policies
 // ---------------------------------------------------------------------------
@@ -388,6 +920,21 @@ pub struct PackDependency {
     pub version_range: String,
 }
 
+// ---------------------------------------------------------------------------
+// Workflow test case
+// ---------------------------------------------------------------------------
+
+/// A test case: the workflow is run as is and must complete with outputs
+/// matching the expectations.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkflowTest {
+    pub name: String,
+    /// Expected output per node ID. An expected object matches an output
+    /// with at least its fields; other values must be equal.
+    #[serde(default)]
+    pub expected_outputs: std::collections::BTreeMap<String, serde_json::Value>,
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -410,18 +957,17 @@ mod tests {
             nodes: vec![NodeEntry::Primitive(NodeSpec {
                 id: "n1".into(),
                 tool_ref: "core-tools/file-read@1.0.0".into(),
-                config: Default::default(),
                 position: Some(Position { x: 0.0, y: 0.0 }),
                 label: Some("Read File".into()),
-                disabled: false,
-                retry: None,
-                cache: None,
+                ..Default::default()
             })],
             edges: vec![],
             required_packs: vec![PackDependency {
                 pack_id: "core-tools".into(),
                 version_range: "^1.0.0".into(),
             }],
+            tests: vec![],
+            groups: Vec::new(),
         };
 
         let json = serde_json::to_string_pretty(&spec).unwrap();
@@ -430,17 +976,56 @@ mod tests {
         assert_eq!(deserialized.nodes.len(), 1);
     }
 
+    #[test]
+    fn composite_config_maps_into_subgraph() {
+        let json = serde_json::json!({
+            "kind": "composite",
+            "id": "rag",
+            "subgraph": {
+                "nodes": [
+                    { "kind": "primitive", "id": "search", "tool_ref": "rag/search@1.0.0", "config": { "top_k": 3 } },
+                    { "kind": "primitive", "id": "answer", "tool_ref": "core-tools/llm-chat@1.0.0" }
+                ],
+                "edges": []
+            },
+            "input_ports": [],
+            "output_ports": [],
+            "exposed_config": [
+                { "name": "top_k", "label": "Top K", "targets": [{ "node": "search", "key": "top_k" }] },
+                { "name": "model", "default_value": "claude", "targets": [{ "node": "answer", "key": "model" }] }
+            ],
+            "config": { "top_k": 8 }
+        });
+        let NodeEntry::Composite(composite) = serde_json::from_value(json).unwrap() else {
+            panic!("expected composite");
+        };
+        let subgraph = composite.resolved_subgraph().unwrap();
+        let config = |i: usize| match &subgraph.nodes[i] {
+            NodeEntry::Primitive(n) => n.config.clone(),
+            _ => unreachable!(),
+        };
+        assert_eq!(config(0)["top_k"], 8);
+        assert_eq!(config(1)["model"], "claude");
+    }
+
+    #[test]
+    fn variadic_port_matching() {
+        assert_eq!(split_variadic_port("inputs.2"), ("inputs", Some("2")));
+        assert_eq!(split_variadic_port("text"), ("text", None));
+        let port: PortSpec =
+            serde_json::from_value(serde_json::json!({ "name": "inputs", "port_type": "any", "variadic": true }))
+                .unwrap();
+        assert!(port.accepts("inputs.summary"));
+        assert!(port.accepts("inputs"));
+        assert!(!port.accepts("other.1"));
+    }
+
     #[test]
     fn node_entry_tagged_serde() {
         let node = NodeEntry::Primitive(NodeSpec {
             id: "n1".into(),
             tool_ref: "core-tools/echo@1.0.0".into(),
-            config: Default::default(),
-            position: None,
-            label: None,
-            disabled: false,
-            retry: None,
-            cache: None,
+            ..Default::default()
         });
         let json = serde_json::to_value(&node).unwrap();
         assert_eq!(json["kind"], "primitive");
@@ -0,0 +1,386 @@
+//! Paging — moving large workflows across the Tauri boundary in pieces
+//! instead of one JSON document per round trip.
+//!
+//! A spec travels as its [`SpecOutline`] followed by [`SpecPage`]s of nodes
+//! and edges. Composite subgraphs are deferred: a page carries the composite
+//! with an empty subgraph and lists its path in `deferred`, and the editor
+//! loads the subgraph with [`WorkflowSpec::subgraph_page`] when it is
+//! opened. Pages sent back are put together by [`SpecAssembler`], which
+//! takes the subgraphs the editor never loaded from the stored spec.
+//!
+//! Loop, conditional and map-reduce bodies are always sent inline.
+
+use super::{
+    CompositeNodeSpec, EdgeSpec, NodeEntry, NodeGroup, PackDependency, SubgraphSpec, VariableSpec,
+    WorkflowMeta, WorkflowSpec, WorkflowTest,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Separates the composite IDs of a subgraph path, outermost first.
+pub const PATH_SEPARATOR: char = '/';
+
+/// Everything of a spec but its nodes and edges, with their counts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpecOutline {
+    pub version: String,
+    pub id: Uuid,
+    pub meta: WorkflowMeta,
+    #[serde(default)]
+    pub variables: Vec<VariableSpec>,
+    #[serde(default)]
+    pub required_packs: Vec<PackDependency>,
+    #[serde(default)]
+    pub tests: Vec<WorkflowTest>,
+    #[serde(default)]
+    pub groups: Vec<NodeGroup>,
+    pub node_count: usize,
+    pub edge_count: usize,
+}
+
+/// A run of nodes and edges starting at `offset` in each list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpecPage {
+    pub offset: usize,
+    pub nodes: Vec<NodeEntry>,
+    pub edges: Vec<EdgeSpec>,
+    /// Paths of the composites on the page sent without their subgraph.
+    #[serde(default)]
+    pub deferred: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PageError {
+    #[error(
+        "page at offset {offset} does not follow the {nodes} node(s) and {edges} edge(s) received"
+    )]
+    OutOfOrder {
+        offset: usize,
+        nodes: usize,
+        edges: usize,
+    },
+    #[error("expected {expected_nodes} node(s) and {expected_edges} edge(s), received {nodes} and {edges}")]
+    CountMismatch {
+        expected_nodes: usize,
+        expected_edges: usize,
+        nodes: usize,
+        edges: usize,
+    },
+    #[error("deferred subgraph '{0}' is not in the stored workflow")]
+    MissingSubgraph(String),
+}
+
+impl WorkflowSpec {
+    pub fn outline(&self) -> SpecOutline {
+        SpecOutline {
+            version: self.version.clone(),
+            id: self.id,
+            meta: self.meta.clone(),
+            variables: self.variables.clone(),
+            required_packs: self.required_packs.clone(),
+            tests: self.tests.clone(),
+            groups: self.groups.clone(),
+            node_count: self.nodes.len(),
+            edge_count: self.edges.len(),
+        }
+    }
+
+    /// Up to `limit` nodes and `limit` edges from `offset`, composite
+    /// subgraphs deferred. Past the end of a list the page has none of it.
+    pub fn page(&self, offset: usize, limit: usize) -> SpecPage {
+        let slice = |len: usize| offset.min(len)..offset.saturating_add(limit.max(1)).min(len);
+        let mut deferred = Vec::new();
+        SpecPage {
+            offset,
+            nodes: defer(&self.nodes[slice(self.nodes.len())], "", &mut deferred),
+            edges: self.edges[slice(self.edges.len())].to_vec(),
+            deferred,
+        }
+    }
+
+    /// The whole subgraph of the composite at `path`, nested composites
+    /// deferred. `None` when no composite is there.
+    pub fn subgraph_page(&self, path: &str) -> Option<SpecPage> {
+        let composite = composite_at(&self.nodes, path)?;
+        let mut deferred = Vec::new();
+        Some(SpecPage {
+            offset: 0,
+            nodes: defer(&composite.subgraph.nodes, path, &mut deferred),
+            edges: composite.subgraph.edges.clone(),
+            deferred,
+        })
+    }
+}
+
+/// `nodes` with the subgraph of every non-empty composite left out and its
+/// path added to `deferred`.
+fn defer(nodes: &[NodeEntry], parent: &str, deferred: &mut Vec<String>) -> Vec<NodeEntry> {
+    nodes
+        .iter()
+        .map(|node| match node {
+            NodeEntry::Composite(c)
+                if !c.subgraph.nodes.is_empty() || !c.subgraph.edges.is_empty() =>
+            {
+                deferred.push(child_path(parent, &c.id));
+                NodeEntry::Composite(CompositeNodeSpec {
+                    subgraph: SubgraphSpec {
+                        nodes: Vec::new(),
+                        edges: Vec::new(),
+                    },
+                    ..c.clone()
+                })
+            }
+            node => node.clone(),
+        })
+        .collect()
+}
+
+fn child_path(parent: &str, id: &str) -> String {
+    if parent.is_empty() {
+        id.to_string()
+    } else {
+        format!("{parent}{PATH_SEPARATOR}{id}")
+    }
+}
+
+fn composite_at<'a>(nodes: &'a [NodeEntry], path: &str) -> Option<&'a CompositeNodeSpec> {
+    let mut nodes = nodes;
+    let mut found = None;
+    for id in path.split(PATH_SEPARATOR) {
+        let composite = nodes.iter().find_map(|n| match n {
+            NodeEntry::Composite(c) if c.id == id => Some(c),
+            _ => None,
+        })?;
+        nodes = &composite.subgraph.nodes;
+        found = Some(composite);
+    }
+    found
+}
+
+fn composite_at_mut<'a>(
+    nodes: &'a mut [NodeEntry],
+    path: &str,
+) -> Option<&'a mut CompositeNodeSpec> {
+    let (id, rest) = match path.split_once(PATH_SEPARATOR) {
+        Some((id, rest)) => (id, Some(rest)),
+        None => (path, None),
+    };
+    let composite = nodes.iter_mut().find_map(|n| match n {
+        NodeEntry::Composite(c) if c.id == id => Some(c),
+        _ => None,
+    })?;
+    match rest {
+        Some(rest) => composite_at_mut(&mut composite.subgraph.nodes, rest),
+        None => Some(composite),
+    }
+}
+
+/// Puts a spec back together from pages sent in order.
+#[derive(Debug)]
+pub struct SpecAssembler {
+    outline: SpecOutline,
+    nodes: Vec<NodeEntry>,
+    edges: Vec<EdgeSpec>,
+    deferred: Vec<String>,
+}
+
+impl SpecAssembler {
+    pub fn new(outline: SpecOutline) -> Self {
+        Self {
+            nodes: Vec::with_capacity(outline.node_count),
+            edges: Vec::with_capacity(outline.edge_count),
+            outline,
+            deferred: Vec::new(),
+        }
+    }
+
+    pub fn workflow_id(&self) -> Uuid {
+        self.outline.id
+    }
+
+    /// Add the next page. A page's nodes, and its edges, must start where
+    /// the ones received so far end.
+    pub fn push(&mut self, page: SpecPage) -> Result<(), PageError> {
+        let follows = |received: usize, empty: bool| empty || page.offset == received;
+        if !follows(self.nodes.len(), page.nodes.is_empty())
+            || !follows(self.edges.len(), page.edges.is_empty())
+        {
+            return Err(PageError::OutOfOrder {
+                offset: page.offset,
+                nodes: self.nodes.len(),
+                edges: self.edges.len(),
+            });
+        }
+        self.nodes.extend(page.nodes);
+        self.edges.extend(page.edges);
+        self.deferred.extend(page.deferred);
+        Ok(())
+    }
+
+    /// The complete spec. Deferred subgraphs are taken from `stored`, the
+    /// spec as it was before this upload; deferred composites that were
+    /// deleted in the meantime are ignored.
+    pub fn finish(self, stored: Option<&WorkflowSpec>) -> Result<WorkflowSpec, PageError> {
+        let outline = self.outline;
+        if self.nodes.len() != outline.node_count || self.edges.len() != outline.edge_count {
+            return Err(PageError::CountMismatch {
+                expected_nodes: outline.node_count,
+                expected_edges: outline.edge_count,
+                nodes: self.nodes.len(),
+                edges: self.edges.len(),
+            });
+        }
+        let mut spec = WorkflowSpec {
+            version: outline.version,
+            id: outline.id,
+            meta: outline.meta,
+            variables: outline.variables,
+            nodes: self.nodes,
+            edges: self.edges,
+            required_packs: outline.required_packs,
+            tests: outline.tests,
+            groups: outline.groups,
+        };
+        // Outer subgraphs first, so nested paths resolve inside them
+        let mut deferred = self.deferred;
+        deferred.sort_by_key(|path| path.matches(PATH_SEPARATOR).count());
+        for path in deferred {
+            let Some(target) = composite_at_mut(&mut spec.nodes, &path) else {
+                continue;
+            };
+            let source = stored
+                .and_then(|stored| composite_at(&stored.nodes, &path))
+                .ok_or_else(|| PageError::MissingSubgraph(path.clone()))?;
+            target.subgraph = source.subgraph.clone();
+        }
+        Ok(spec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::NodeSpec;
+
+    fn node(id: &str) -> NodeEntry {
+        NodeEntry::Primitive(NodeSpec {
+            id: id.into(),
+            tool_ref: "text-merge".into(),
+            ..Default::default()
+        })
+    }
+
+    fn composite(id: &str, nodes: Vec<NodeEntry>) -> NodeEntry {
+        NodeEntry::Composite(CompositeNodeSpec {
+            id: id.into(),
+            subgraph: SubgraphSpec {
+                nodes,
+                edges: Vec::new(),
+            },
+            input_ports: Vec::new(),
+            output_ports: Vec::new(),
+            input_mapping: Vec::new(),
+            output_mapping: Vec::new(),
+            exposed_config: Vec::new(),
+            config: Default::default(),
+            position: None,
+            label: None,
+            presentation: Default::default(),
+        })
+    }
+
+    fn edge(from: &str, to: &str) -> EdgeSpec {
+        EdgeSpec {
+            id: format!("{from}-{to}"),
+            source_node: from.into(),
+            source_port: "out".into(),
+            target_node: to.into(),
+            target_port: "in".into(),
+            ..Default::default()
+        }
+    }
+
+    fn spec() -> WorkflowSpec {
+        WorkflowSpec {
+            nodes: vec![
+                node("load"),
+                composite(
+                    "review",
+                    vec![node("check"), composite("inner", vec![node("score")])],
+                ),
+                node("report"),
+            ],
+            edges: vec![edge("load", "review"), edge("review", "report")],
+            ..Default::default()
+        }
+    }
+
+    fn pages(spec: &WorkflowSpec, limit: usize) -> Vec<SpecPage> {
+        let total = spec.nodes.len().max(spec.edges.len());
+        (0..total)
+            .step_by(limit)
+            .map(|offset| spec.page(offset, limit))
+            .collect()
+    }
+
+    #[test]
+    fn pages_defer_subgraphs_until_they_are_opened() {
+        let spec = spec();
+        let outline = spec.outline();
+        assert_eq!((outline.node_count, outline.edge_count), (3, 2));
+
+        let pages = pages(&spec, 2);
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].deferred, ["review"]);
+        let NodeEntry::Composite(review) = &pages[0].nodes[1] else {
+            panic!("expected the composite");
+        };
+        assert!(review.subgraph.nodes.is_empty());
+        assert_eq!((pages[1].nodes.len(), pages[1].edges.len()), (1, 0));
+
+        let review = spec.subgraph_page("review").unwrap();
+        assert_eq!(review.nodes.len(), 2);
+        assert_eq!(review.deferred, ["review/inner"]);
+        let inner = spec.subgraph_page("review/inner").unwrap();
+        assert_eq!(inner.nodes[0].id(), "score");
+        assert!(inner.deferred.is_empty());
+        assert!(spec.subgraph_page("load").is_none());
+    }
+
+    #[test]
+    fn assembled_pages_take_unopened_subgraphs_from_the_stored_spec() {
+        let spec = spec();
+        let mut assembler = SpecAssembler::new(spec.outline());
+        for page in pages(&spec, 2) {
+            assembler.push(page).unwrap();
+        }
+        let assembled = assembler.finish(Some(&spec)).unwrap();
+        assert_eq!(
+            serde_json::to_value(&assembled).unwrap(),
+            serde_json::to_value(&spec).unwrap()
+        );
+
+        let mut assembler = SpecAssembler::new(spec.outline());
+        assembler.push(spec.page(0, 2)).unwrap();
+        assert_eq!(
+            assembler.push(spec.page(0, 2)).unwrap_err(),
+            PageError::OutOfOrder {
+                offset: 0,
+                nodes: 2,
+                edges: 2
+            }
+        );
+        assembler.push(spec.page(2, 2)).unwrap();
+        assert_eq!(
+            assembler.finish(None).unwrap_err(),
+            PageError::MissingSubgraph("review".into())
+        );
+
+        let assembler = SpecAssembler::new(spec.outline());
+        assert!(matches!(
+            assembler.finish(Some(&spec)),
+            Err(PageError::CountMismatch { nodes: 0, .. })
+        ));
+    }
+}
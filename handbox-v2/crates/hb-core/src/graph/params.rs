@@ -0,0 +1,271 @@
+//! Workflow parameters — JSON form schemas generated from a workflow's
+//! [`VariableSpec`]s, and validation of submitted values.
+
+use super::{PortType, VariableSpec};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+/// A problem with one submitted parameter.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParamError {
+    pub name: String,
+    pub message: String,
+}
+
+fn type_schema(var_type: &PortType) -> Map<String, Value> {
+    let schema = match var_type {
+        PortType::String => json!({ "type": "string" }),
+        PortType::Number => json!({ "type": "number" }),
+        PortType::Boolean => json!({ "type": "boolean" }),
+        PortType::Array => json!({ "type": "array" }),
+        PortType::Binary => json!({ "type": "string", "contentEncoding": "base64" }),
+        PortType::Json | PortType::Any => json!({}),
+    };
+    match schema {
+        Value::Object(map) => map,
+        _ => Map::new(),
+    }
+}
+
+fn field_schema(var: &VariableSpec) -> Value {
+    let mut field = type_schema(&var.var_type);
+    field.insert("title".into(), json!(var.label.as_deref().unwrap_or(&var.name)));
+    if let Some(ref description) = var.description {
+        field.insert("description".into(), json!(description));
+    }
+    if let Some(ref default) = var.default_value {
+        field.insert("default".into(), default.clone());
+    }
+    if !var.options.is_empty() {
+        let options: Vec<Value> = var
+            .options
+            .iter()
+            .map(|o| json!({ "const": o.value, "title": o.label }))
+            .collect();
+        field.insert("oneOf".into(), Value::Array(options));
+    }
+    let c = &var.constraints;
+    let (min_len, max_len) = match var.var_type {
+        PortType::Array => ("minItems", "maxItems"),
+        _ => ("minLength", "maxLength"),
+    };
+    for (key, bound) in [
+        ("minimum", c.minimum.map(Value::from)),
+        ("maximum", c.maximum.map(Value::from)),
+        (min_len, c.min_length.map(Value::from)),
+        (max_len, c.max_length.map(Value::from)),
+    ] {
+        if let Some(bound) = bound {
+            field.insert(key.into(), bound);
+        }
+    }
+    if let Some(ref group) = var.group {
+        field.insert("x-group".into(), json!(group));
+    }
+    Value::Object(field)
+}
+
+/// Build a JSON Schema (draft-07 object) describing the parameter form.
+/// `x-groups` lists field names per group in declaration order; ungrouped
+/// fields are collected under a `null` group name.
+pub fn form_schema(variables: &[VariableSpec]) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    let mut groups: Vec<(Option<&str>, Vec<&str>)> = Vec::new();
+    for var in variables {
+        properties.insert(var.name.clone(), field_schema(var));
+        if var.required {
+            required.push(var.name.as_str());
+        }
+        let group = var.group.as_deref();
+        match groups.iter_mut().find(|(g, _)| *g == group) {
+            Some((_, fields)) => fields.push(&var.name),
+            None => groups.push((group, vec![&var.name])),
+        }
+    }
+    let groups: Vec<Value> = groups
+        .into_iter()
+        .map(|(name, fields)| json!({ "name": name, "fields": fields }))
+        .collect();
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+        "x-groups": groups,
+    })
+}
+
+pub(super) fn check_value(var: &VariableSpec, value: &Value) -> Result<(), String> {
+    let type_ok = match var.var_type {
+        PortType::String | PortType::Binary => value.is_string(),
+        PortType::Number => value.is_number(),
+        PortType::Boolean => value.is_boolean(),
+        PortType::Array => value.is_array(),
+        PortType::Json | PortType::Any => true,
+    };
+    if !type_ok {
+        let expected = serde_json::to_value(&var.var_type).unwrap_or_default();
+        return Err(format!("expected {}, got {value}", expected.as_str().unwrap_or("value")));
+    }
+    if !var.options.is_empty() && !var.options.iter().any(|o| o.value == *value) {
+        return Err("not one of the allowed options".into());
+    }
+
+    let c = &var.constraints;
+    if let Some(n) = value.as_f64() {
+        if c.minimum.is_some_and(|min| n < min) || c.maximum.is_some_and(|max| n > max) {
+            return Err(format!(
+                "must be between {} and {}",
+                c.minimum.map_or("-∞".into(), |m| m.to_string()),
+                c.maximum.map_or("∞".into(), |m| m.to_string()),
+            ));
+        }
+    }
+    let len = match value {
+        Value::String(s) if var.var_type == PortType::String => Some(s.chars().count()),
+        Value::Array(items) => Some(items.len()),
+        _ => None,
+    };
+    if let Some(len) = len {
+        if let Some(min) = c.min_length.filter(|&min| len < min) {
+            return Err(format!("must have at least {min} characters or items"));
+        }
+        if let Some(max) = c.max_length.filter(|&max| len > max) {
+            return Err(format!("must have at most {max} characters or items"));
+        }
+    }
+    Ok(())
+}
+
+/// Validate submitted parameter values, filling in defaults for omitted
+/// variables. Returns the complete values or every problem found.
+pub fn validate_values(
+    variables: &[VariableSpec],
+    values: &Map<String, Value>,
+) -> Result<Map<String, Value>, Vec<ParamError>> {
+    let mut errors = Vec::new();
+    let mut resolved = Map::new();
+
+    for name in values.keys() {
+        if !variables.iter().any(|v| v.name == *name) {
+            errors.push(ParamError {
+                name: name.clone(),
+                message: "unknown parameter".into(),
+            });
+        }
+    }
+
+    for var in variables {
+        let value = match values.get(&var.name).filter(|v| !v.is_null()) {
+            Some(v) => v.clone(),
+            None => match var.default_value {
+                Some(ref default) => default.clone(),
+                None if var.required => {
+                    errors.push(ParamError {
+                        name: var.name.clone(),
+                        message: "required".into(),
+                    });
+                    continue;
+                }
+                None => continue,
+            },
+        };
+        match check_value(var, &value) {
+            Ok(()) => {
+                resolved.insert(var.name.clone(), value);
+            }
+            Err(message) => errors.push(ParamError {
+                name: var.name.clone(),
+                message,
+            }),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(resolved)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Values a run without submitted parameters uses: each variable's default.
+pub fn defaults(variables: &[VariableSpec]) -> Map<String, Value> {
+    variables
+        .iter()
+        .filter_map(|var| Some((var.name.clone(), var.default_value.clone()?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::VariableConstraints;
+    use crate::tool::ConfigOption;
+
+    fn var(name: &str, var_type: PortType, required: bool) -> VariableSpec {
+        VariableSpec {
+            name: name.into(),
+            description: None,
+            var_type,
+            default_value: None,
+            required,
+            label: None,
+            group: None,
+            options: vec![],
+            constraints: VariableConstraints::default(),
+        }
+    }
+
+    fn variables() -> Vec<VariableSpec> {
+        let mut count = var("count", PortType::Number, false);
+        count.default_value = Some(json!(3));
+        count.constraints.minimum = Some(1.0);
+        count.constraints.maximum = Some(10.0);
+        count.group = Some("Advanced".into());
+
+        let mut mode = var("mode", PortType::String, true);
+        mode.options = vec![
+            ConfigOption { label: "Fast".into(), value: json!("fast") },
+            ConfigOption { label: "Thorough".into(), value: json!("thorough") },
+        ];
+        vec![var("query", PortType::String, true), count, mode]
+    }
+
+    #[test]
+    fn form_schema_describes_fields_and_groups() {
+        let schema = form_schema(&variables());
+        assert_eq!(schema["required"], json!(["query", "mode"]));
+        assert_eq!(schema["properties"]["count"]["default"], json!(3));
+        assert_eq!(schema["properties"]["count"]["maximum"], json!(10.0));
+        assert_eq!(schema["properties"]["mode"]["oneOf"][1]["const"], json!("thorough"));
+        assert_eq!(
+            schema["x-groups"],
+            json!([
+                { "name": null, "fields": ["query", "mode"] },
+                { "name": "Advanced", "fields": ["count"] },
+            ])
+        );
+    }
+
+    #[test]
+    fn validation_applies_defaults_and_reports_errors() {
+        let vars = variables();
+        let ok = validate_values(
+            &vars,
+            json!({ "query": "rust", "mode": "fast" }).as_object().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(ok["count"], json!(3));
+        assert_eq!(defaults(&vars), *json!({ "count": 3 }).as_object().unwrap());
+
+        let errors = validate_values(
+            &vars,
+            json!({ "count": 20, "mode": "slow", "extra": 1 }).as_object().unwrap(),
+        )
+        .unwrap_err();
+        let names: Vec<&str> = errors.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["extra", "query", "count", "mode"]);
+    }
+}
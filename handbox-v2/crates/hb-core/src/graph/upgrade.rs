@@ -0,0 +1,231 @@
+//! Spec upgrades — bring workflow JSON written for an older Graph DSL
+//! version up to [`SPEC_VERSION`] before it is deserialized, so a breaking
+//! change to the spec doesn't strand saved workflows, shared bundles and
+//! marketplace downloads.
+//!
+//! Each [`SpecMigration`] takes the raw JSON from one version to the next;
+//! [`SpecMigrator::upgrade`] chains them from the document's `version` to
+//! the current one. A breaking spec change bumps [`SPEC_VERSION`] and adds
+//! the step that rewrites older documents to [`BUILTIN`]. A document
+//! without a `version` is taken to be the current version; one newer than
+//! this build is refused rather than half-read.
+
+use super::WorkflowSpec;
+use semver::Version;
+use serde_json::Value;
+use thiserror::Error;
+
+/// Graph DSL version this build reads and writes.
+pub const SPEC_VERSION: &str = "0.1.0";
+
+/// One step of the upgrade chain, on the raw JSON of a workflow.
+#[derive(Debug, Clone, Copy)]
+pub struct SpecMigration {
+    pub from: &'static str,
+    pub to: &'static str,
+    /// What the step changes, for logs and import reports.
+    pub description: &'static str,
+    pub apply: fn(&mut Value) -> Result<(), String>,
+}
+
+/// Steps shipped with this build, oldest first.
+pub const BUILTIN: &[SpecMigration] = &[];
+
+#[derive(Debug, Error)]
+pub enum UpgradeError {
+    #[error("workflow JSON must be an object")]
+    NotAnObject,
+    #[error("unreadable spec version '{0}'")]
+    InvalidVersion(String),
+    #[error("spec version {found} is newer than this app supports ({supported}); update the app to open it")]
+    TooNew { found: String, supported: String },
+    #[error("no migration from spec version {0}")]
+    NoPath(String),
+    #[error("migrating spec {from} → {to} failed: {message}")]
+    Step {
+        from: String,
+        to: String,
+        message: String,
+    },
+    #[error("invalid workflow: {0}")]
+    Invalid(#[from] serde_json::Error),
+}
+
+/// A workflow read through the migrator.
+#[derive(Debug, Clone)]
+pub struct UpgradedSpec {
+    pub spec: WorkflowSpec,
+    /// Version the document was written for.
+    pub from_version: String,
+    /// Descriptions of the steps applied, in order.
+    pub applied: Vec<&'static str>,
+}
+
+/// Registered migrations and the version they lead to.
+#[derive(Debug, Clone)]
+pub struct SpecMigrator {
+    current: Version,
+    steps: Vec<SpecMigration>,
+}
+
+impl Default for SpecMigrator {
+    /// [`SPEC_VERSION`] with the [`BUILTIN`] steps.
+    fn default() -> Self {
+        BUILTIN
+            .iter()
+            .fold(Self::new(SPEC_VERSION), |migrator, step| {
+                migrator.register(*step)
+            })
+    }
+}
+
+impl SpecMigrator {
+    /// A migrator leading to `current`, with no steps yet.
+    ///
+    /// # Panics
+    /// If `current` is not a semantic version.
+    pub fn new(current: &str) -> Self {
+        Self {
+            current: Version::parse(current).expect("spec version must be semver"),
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn register(mut self, step: SpecMigration) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    pub fn current(&self) -> &Version {
+        &self.current
+    }
+
+    /// Rewrite `value` up to the current version. Returns the version it
+    /// was written for and the steps applied.
+    pub fn upgrade(&self, value: &mut Value) -> Result<(String, Vec<&'static str>), UpgradeError> {
+        let object = value.as_object_mut().ok_or(UpgradeError::NotAnObject)?;
+        let found = match object.get("version") {
+            None | Some(Value::Null) => self.current.to_string(),
+            Some(Value::String(v)) => v.clone(),
+            Some(other) => return Err(UpgradeError::InvalidVersion(other.to_string())),
+        };
+        let mut version =
+            Version::parse(&found).map_err(|_| UpgradeError::InvalidVersion(found.clone()))?;
+        if version > self.current {
+            return Err(UpgradeError::TooNew {
+                found,
+                supported: self.current.to_string(),
+            });
+        }
+
+        let mut applied = Vec::new();
+        while version < self.current {
+            let step = self
+                .steps
+                .iter()
+                .find(|s| Version::parse(s.from).is_ok_and(|from| from == version))
+                .ok_or_else(|| UpgradeError::NoPath(version.to_string()))?;
+            (step.apply)(value).map_err(|message| UpgradeError::Step {
+                from: step.from.into(),
+                to: step.to.into(),
+                message,
+            })?;
+            version = Version::parse(step.to)
+                .map_err(|_| UpgradeError::InvalidVersion(step.to.into()))?;
+            applied.push(step.description);
+        }
+        if let Some(object) = value.as_object_mut() {
+            object.insert("version".into(), Value::String(self.current.to_string()));
+        }
+        Ok((found, applied))
+    }
+
+    /// Upgrade `value` and deserialize it.
+    pub fn parse(&self, mut value: Value) -> Result<UpgradedSpec, UpgradeError> {
+        let (from_version, applied) = self.upgrade(&mut value)?;
+        Ok(UpgradedSpec {
+            spec: serde_json::from_value(value)?,
+            from_version,
+            applied,
+        })
+    }
+}
+
+impl WorkflowSpec {
+    /// Read a workflow of any supported spec version, upgraded to the
+    /// current one.
+    pub fn from_json(json: &str) -> Result<UpgradedSpec, UpgradeError> {
+        Self::from_value(serde_json::from_str(json)?)
+    }
+
+    /// [`WorkflowSpec::from_json`] for already-parsed JSON.
+    pub fn from_value(value: Value) -> Result<UpgradedSpec, UpgradeError> {
+        SpecMigrator::default().parse(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// 0.0.9 kept the name at the top level; 0.1.0 moved it into `meta`.
+    fn name_into_meta(value: &mut Value) -> Result<(), String> {
+        let object = value.as_object_mut().ok_or("not an object")?;
+        let name = object.remove("name").ok_or("no name")?;
+        object.insert("meta".into(), json!({ "name": name, "description": "" }));
+        Ok(())
+    }
+
+    #[test]
+    fn old_specs_are_upgraded_step_by_step_and_newer_ones_refused() {
+        let migrator = SpecMigrator::new("0.1.0")
+            .register(SpecMigration {
+                from: "0.0.9",
+                to: "0.1.0",
+                description: "name moves into meta",
+                apply: name_into_meta,
+            })
+            .register(SpecMigration {
+                from: "0.0.8",
+                to: "0.0.9",
+                description: "nothing changes",
+                apply: |_| Ok(()),
+            });
+
+        let old = json!({ "version": "0.0.8", "name": "Legacy", "nodes": [], "edges": [] });
+        let upgraded = migrator.parse(old).unwrap();
+        assert_eq!(upgraded.spec.meta.name, "Legacy");
+        assert_eq!(upgraded.spec.version, "0.1.0");
+        assert_eq!(upgraded.from_version, "0.0.8");
+        assert_eq!(
+            upgraded.applied,
+            ["nothing changes", "name moves into meta"]
+        );
+
+        let current =
+            json!({ "meta": { "name": "New", "description": "" }, "nodes": [], "edges": [] });
+        let upgraded = migrator.parse(current).unwrap();
+        assert!(upgraded.applied.is_empty());
+        assert_eq!(upgraded.spec.version, "0.1.0");
+
+        let newer = json!({ "version": "0.2.0", "nodes": [], "edges": [] });
+        assert!(matches!(
+            migrator.parse(newer),
+            Err(UpgradeError::TooNew { .. })
+        ));
+        let stranded = json!({ "version": "0.0.1", "nodes": [], "edges": [] });
+        assert!(matches!(migrator.parse(stranded), Err(UpgradeError::NoPath(v)) if v == "0.0.1"));
+        let broken = json!({ "version": "0.0.9", "nodes": [], "edges": [] });
+        let err = migrator.parse(broken).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "migrating spec 0.0.9 → 0.1.0 failed: no name"
+        );
+
+        // The built-in chain reads what this build writes
+        let spec = WorkflowSpec::default();
+        let json = serde_json::to_string(&spec).unwrap();
+        assert_eq!(WorkflowSpec::from_json(&json).unwrap().spec.id, spec.id);
+    }
+}
@@ -0,0 +1,537 @@
+//! Spec lint — structural checks of a workflow that need nothing but the
+//! spec: dangling edges, duplicate node IDs, unknown ports, cycles among
+//! Data edges, nodes that can never run and required variables. Findings
+//! are returned as [`SpecIssue`]s pointing at the node, edge or variable at
+//! fault, for the editor and the compiler to surface.
+//!
+//! Sub-graphs are checked too; their nodes are referenced by path
+//! (`"outer/inner"`), as in composite config targets. Ports of primitive
+//! nodes come from their tools, so they are only checked when
+//! [`ValidateOptions::tool_ports`] resolves them.
+
+use super::params;
+use super::{EdgeKind, EdgeSpec, GraphIndex, NodeEntry, PortMapping, PortSpec, WorkflowSpec};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SpecIssue {
+    pub severity: Severity,
+    /// Stable machine-readable code, e.g. "dangling_edge".
+    pub code: &'static str,
+    pub message: String,
+    /// Node at fault; nodes inside sub-graphs by path.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edge_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variable: Option<String>,
+}
+
+impl SpecIssue {
+    fn new(severity: Severity, code: &'static str, message: String) -> Self {
+        Self {
+            severity,
+            code,
+            message,
+            node_id: None,
+            edge_id: None,
+            variable: None,
+        }
+    }
+
+    fn on_node(mut self, path: String) -> Self {
+        self.node_id = Some(path);
+        self
+    }
+
+    fn on_edge(mut self, edge: &EdgeSpec) -> Self {
+        self.edge_id = Some(edge.id.clone());
+        self
+    }
+
+    fn on_variable(mut self, name: &str) -> Self {
+        self.variable = Some(name.to_string());
+        self
+    }
+}
+
+impl fmt::Display for SpecIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.node_id {
+            Some(ref node) => write!(f, "{node}: {}", self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// Input and output ports of a primitive node's tool, by `tool_ref`; `None`
+/// when the tool is unknown.
+pub type ToolPorts<'a> = dyn Fn(&str) -> Option<(Vec<PortSpec>, Vec<PortSpec>)> + 'a;
+
+#[derive(Default, Clone, Copy)]
+pub struct ValidateOptions<'a> {
+    /// Resolves primitive nodes' ports. Without it their ports are not
+    /// checked and unknown tools are not reported.
+    pub tool_ports: Option<&'a ToolPorts<'a>>,
+    /// Run parameters. When given, required variables with neither a value
+    /// nor a default are reported, as are values that don't fit.
+    pub values: Option<&'a Map<String, Value>>,
+}
+
+impl WorkflowSpec {
+    /// Lint the spec on its own; see [`validate`].
+    pub fn validate(&self) -> Vec<SpecIssue> {
+        validate(self, ValidateOptions::default())
+    }
+}
+
+/// Every structural problem in `spec`, in a stable order: variables first,
+/// then each graph level's nodes and edges, outer levels before inner.
+pub fn validate(spec: &WorkflowSpec, options: ValidateOptions) -> Vec<SpecIssue> {
+    let mut out = Vec::new();
+    check_variables(spec, options.values, &mut out);
+    check_graph("", &spec.nodes, &spec.edges, options.tool_ports, &mut out);
+    out
+}
+
+fn check_variables(
+    spec: &WorkflowSpec,
+    values: Option<&Map<String, Value>>,
+    out: &mut Vec<SpecIssue>,
+) {
+    let mut seen = HashSet::new();
+    for var in &spec.variables {
+        if !seen.insert(var.name.as_str()) {
+            out.push(
+                SpecIssue::new(
+                    Severity::Error,
+                    "duplicate_variable",
+                    format!("Variable '{}' is declared more than once", var.name),
+                )
+                .on_variable(&var.name),
+            );
+        }
+        if let Some(ref default) = var.default_value {
+            if let Err(e) = params::check_value(var, default) {
+                out.push(
+                    SpecIssue::new(
+                        Severity::Error,
+                        "invalid_default",
+                        format!("Default of '{}' {e}", var.name),
+                    )
+                    .on_variable(&var.name),
+                );
+            }
+        }
+    }
+
+    let Some(values) = values else {
+        return;
+    };
+    for error in params::validate_values(&spec.variables, values)
+        .err()
+        .unwrap_or_default()
+    {
+        let issue = if error.message == "required" {
+            SpecIssue::new(
+                Severity::Error,
+                "missing_variable",
+                format!("Required variable '{}' has no value", error.name),
+            )
+        } else {
+            SpecIssue::new(
+                Severity::Error,
+                "invalid_variable",
+                format!("'{}': {}", error.name, error.message),
+            )
+        };
+        out.push(issue.on_variable(&error.name));
+    }
+}
+
+/// Ports a node declares, or `None` when it accepts any port (conditionals,
+/// loops) or they can't be resolved.
+fn node_ports(
+    node: &NodeEntry,
+    tool_ports: Option<&ToolPorts>,
+) -> Option<(Vec<PortSpec>, Vec<PortSpec>)> {
+    match node {
+        NodeEntry::Primitive(n) => tool_ports.and_then(|resolve| resolve(&n.tool_ref)),
+        NodeEntry::Composite(n) => Some((n.input_ports.clone(), n.output_ports.clone())),
+        NodeEntry::WorkflowRef(n) => Some((n.input_ports.clone(), n.output_ports.clone())),
+        NodeEntry::Conditional(_) | NodeEntry::Loop(_) | NodeEntry::MapReduce(_) => None,
+    }
+}
+
+fn check_graph(
+    prefix: &str,
+    nodes: &[NodeEntry],
+    edges: &[EdgeSpec],
+    tool_ports: Option<&ToolPorts>,
+    out: &mut Vec<SpecIssue>,
+) {
+    let path = |id: &str| format!("{prefix}{id}");
+    let index = GraphIndex::new(nodes, edges);
+
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for node in nodes {
+        *counts.entry(node.id()).or_default() += 1;
+    }
+    let mut reported = HashSet::new();
+    let mut ports = HashMap::new();
+    for node in nodes {
+        let id = node.id();
+        let count = counts[id];
+        if count > 1 && reported.insert(id) {
+            out.push(
+                SpecIssue::new(
+                    Severity::Error,
+                    "duplicate_node_id",
+                    format!("Node ID '{id}' is used {count} times"),
+                )
+                .on_node(path(id)),
+            );
+        }
+        let declared = node_ports(node, tool_ports);
+        if let (NodeEntry::Primitive(n), Some(_), None) = (node, tool_ports, &declared) {
+            out.push(
+                SpecIssue::new(
+                    Severity::Error,
+                    "unknown_tool",
+                    format!("Unknown tool '{}'", n.tool_ref),
+                )
+                .on_node(path(id)),
+            );
+        }
+        ports.insert(id, declared);
+    }
+
+    for edge in edges {
+        let source = index.node(&edge.source_node);
+        let target = index.node(&edge.target_node);
+        if source.is_none() || target.is_none() {
+            let (missing, owner) = match source {
+                None => (
+                    format!("Edge comes from missing node '{}'", edge.source_node),
+                    &edge.target_node,
+                ),
+                Some(_) => (
+                    format!("Edge targets missing node '{}'", edge.target_node),
+                    &edge.source_node,
+                ),
+            };
+            let mut issue = SpecIssue::new(Severity::Error, "dangling_edge", missing).on_edge(edge);
+            if index.contains(owner) {
+                issue = issue.on_node(path(owner));
+            }
+            out.push(issue);
+            continue;
+        }
+
+        // Error edges carry the node's error, not one of its outputs
+        let outputs = match edge.kind {
+            EdgeKind::Error => None,
+            _ => ports[edge.source_node.as_str()].as_ref().map(|(_, o)| o),
+        };
+        if outputs.is_some_and(|o| !o.iter().any(|p| p.accepts(&edge.source_port))) {
+            out.push(
+                SpecIssue::new(
+                    Severity::Error,
+                    "unknown_port",
+                    format!(
+                        "'{}' has no output '{}'",
+                        edge.source_node, edge.source_port
+                    ),
+                )
+                .on_node(path(&edge.source_node))
+                .on_edge(edge),
+            );
+        }
+        let inputs = ports[edge.target_node.as_str()].as_ref().map(|(i, _)| i);
+        if inputs.is_some_and(|i| !i.iter().any(|p| p.accepts(&edge.target_port))) {
+            out.push(
+                SpecIssue::new(
+                    Severity::Error,
+                    "unknown_port",
+                    format!("'{}' has no input '{}'", edge.target_node, edge.target_port),
+                )
+                .on_node(path(&edge.target_node))
+                .on_edge(edge),
+            );
+        }
+    }
+
+    let ids: Vec<&str> = nodes.iter().map(NodeEntry::id).collect();
+    let connected = |kinds: &dyn Fn(&EdgeKind) -> bool| -> Vec<(&str, &str)> {
+        edges
+            .iter()
+            .filter(|e| {
+                kinds(&e.kind) && index.contains(&e.source_node) && index.contains(&e.target_node)
+            })
+            .map(|e| (e.source_node.as_str(), e.target_node.as_str()))
+            .collect()
+    };
+
+    // Trimming nodes without incoming, then without outgoing, Data edges
+    // leaves the nodes on (or between) Data cycles
+    let data = connected(&|k| *k == EdgeKind::Data);
+    let blocked = unsorted(&ids, &data);
+    let reversed: Vec<_> = data
+        .iter()
+        .filter(|(s, t)| blocked.contains(s) && blocked.contains(t))
+        .map(|&(s, t)| (t, s))
+        .collect();
+    let blocked_ids: Vec<&str> = ids
+        .iter()
+        .copied()
+        .filter(|id| blocked.contains(id))
+        .collect();
+    let on_cycle = unsorted(&blocked_ids, &reversed);
+    for edge in edges.iter().filter(|e| e.kind == EdgeKind::Data) {
+        let (s, t) = (edge.source_node.as_str(), edge.target_node.as_str());
+        if on_cycle.contains(s) && on_cycle.contains(t) {
+            out.push(
+                SpecIssue::new(
+                    Severity::Error,
+                    "cycle",
+                    format!("Edge from '{s}' to '{t}' is part of a cycle"),
+                )
+                .on_node(path(t))
+                .on_edge(edge),
+            );
+        }
+    }
+
+    // Nodes waiting on a cycle of any edge kind never start
+    let all = connected(&|_| true);
+    let never_run = unsorted(&ids, &all);
+    let mut reported = HashSet::new();
+    for id in ids
+        .iter()
+        .filter(|id| never_run.contains(*id) && !on_cycle.contains(*id))
+    {
+        if reported.insert(*id) {
+            out.push(
+                SpecIssue::new(
+                    Severity::Warning,
+                    "unreachable_node",
+                    format!("'{id}' never runs: every path to it goes through a cycle"),
+                )
+                .on_node(path(id)),
+            );
+        }
+    }
+
+    for node in nodes {
+        let id = node.id();
+        match node {
+            NodeEntry::Composite(n) => {
+                let inner: HashSet<&str> = n.subgraph.nodes.iter().map(NodeEntry::id).collect();
+                check_mappings(
+                    &path(id),
+                    &n.input_ports,
+                    &n.input_mapping,
+                    Some(&inner),
+                    out,
+                );
+                check_mappings(
+                    &path(id),
+                    &n.output_ports,
+                    &n.output_mapping,
+                    Some(&inner),
+                    out,
+                );
+            }
+            NodeEntry::WorkflowRef(n) => {
+                check_mappings(&path(id), &n.input_ports, &n.input_mapping, None, out);
+                check_mappings(&path(id), &n.output_ports, &n.output_mapping, None, out);
+            }
+            _ => {}
+        }
+        for subgraph in node.subgraphs() {
+            check_graph(
+                &format!("{}/", path(id)),
+                &subgraph.nodes,
+                &subgraph.edges,
+                tool_ports,
+                out,
+            );
+        }
+    }
+}
+
+/// Port mappings must name a declared port and, where the inner graph is
+/// embedded, a node in it.
+fn check_mappings(
+    node_path: &str,
+    ports: &[PortSpec],
+    mappings: &[PortMapping],
+    inner: Option<&HashSet<&str>>,
+    out: &mut Vec<SpecIssue>,
+) {
+    for mapping in mappings {
+        let problem = if !ports.iter().any(|p| p.accepts(&mapping.external_port)) {
+            format!("Mapping names undeclared port '{}'", mapping.external_port)
+        } else if inner.is_some_and(|inner| !inner.contains(mapping.node.as_str())) {
+            format!(
+                "Port '{}' maps to missing node '{}'",
+                mapping.external_port, mapping.node
+            )
+        } else {
+            continue;
+        };
+        out.push(
+            SpecIssue::new(Severity::Error, "invalid_mapping", problem)
+                .on_node(node_path.to_string()),
+        );
+    }
+}
+
+/// Nodes left after repeatedly removing nodes with no incoming edges
+/// (Kahn's algorithm): those on a cycle or downstream of one.
+fn unsorted<'a>(ids: &[&'a str], edges: &[(&'a str, &'a str)]) -> HashSet<&'a str> {
+    let mut indegree: HashMap<&str, usize> = ids.iter().map(|id| (*id, 0)).collect();
+    let mut outgoing: HashMap<&str, Vec<&str>> = HashMap::new();
+    for &(source, target) in edges {
+        *indegree.entry(target).or_default() += 1;
+        outgoing.entry(source).or_default().push(target);
+    }
+    let mut ready: Vec<&str> = indegree
+        .iter()
+        .filter(|(_, d)| **d == 0)
+        .map(|(id, _)| *id)
+        .collect();
+    while let Some(id) = ready.pop() {
+        indegree.remove(id);
+        for target in outgoing.get(id).into_iter().flatten() {
+            if let Some(d) = indegree.get_mut(target) {
+                *d -= 1;
+                if *d == 0 {
+                    ready.push(target);
+                }
+            }
+        }
+    }
+    indegree.into_keys().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(json: serde_json::Value) -> WorkflowSpec {
+        let mut base = serde_json::json!({
+            "version": "0.1.0",
+            "meta": { "name": "lint", "description": "" },
+            "edges": []
+        });
+        base.as_object_mut()
+            .unwrap()
+            .extend(json.as_object().unwrap().clone());
+        serde_json::from_value(base).unwrap()
+    }
+
+    fn codes(issues: &[SpecIssue]) -> Vec<(&str, Option<&str>)> {
+        issues
+            .iter()
+            .map(|i| (i.code, i.node_id.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn reports_structural_problems_with_their_location() {
+        let edge = |id: &str, from: &str, to: &str| {
+            serde_json::json!({
+                "id": id, "source_node": from, "source_port": "out",
+                "target_node": to, "target_port": "in"
+            })
+        };
+        let prim =
+            |id: &str| serde_json::json!({ "kind": "primitive", "id": id, "tool_ref": "t/echo@1" });
+        let spec = spec(serde_json::json!({
+            "variables": [
+                { "name": "topic", "var_type": "string", "required": true },
+                { "name": "limit", "var_type": "number", "required": false, "default_value": "ten" }
+            ],
+            "nodes": [
+                prim("a"), prim("b"), prim("c"), prim("d"), prim("a"),
+                {
+                    "kind": "composite", "id": "comp",
+                    "input_ports": [{ "name": "data", "port_type": "any" }],
+                    "output_ports": [],
+                    "input_mapping": [{ "external_port": "data", "node": "gone", "port": "in" }],
+                    "subgraph": { "nodes": [prim("x")], "edges": [edge("e9", "x", "y")] }
+                }
+            ],
+            "edges": [
+                edge("e1", "a", "b"), edge("e2", "b", "c"), edge("e3", "c", "b"),
+                edge("e4", "c", "d"), edge("e5", "ghost", "d"), edge("e6", "a", "comp"),
+            ]
+        }));
+
+        let issues = spec.validate();
+        assert_eq!(
+            codes(&issues),
+            vec![
+                ("invalid_default", None),
+                ("duplicate_node_id", Some("a")),
+                ("dangling_edge", Some("d")),
+                ("unknown_port", Some("comp")),
+                ("cycle", Some("c")),
+                ("cycle", Some("b")),
+                ("unreachable_node", Some("d")),
+                ("invalid_mapping", Some("comp")),
+                ("dangling_edge", Some("comp/x")),
+            ]
+        );
+        assert_eq!(issues[4].edge_id.as_deref(), Some("e2"));
+
+        // Run parameters: the required variable is missing
+        let values = Map::new();
+        let options = ValidateOptions {
+            values: Some(&values),
+            ..Default::default()
+        };
+        let missing: Vec<_> = validate(&spec, options)
+            .into_iter()
+            .filter(|i| i.code == "missing_variable")
+            .collect();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].variable.as_deref(), Some("topic"));
+
+        // With the tool's ports known, primitive ports are checked too
+        let echo = |tool_ref: &str| {
+            let port = |name: &str| PortSpec {
+                name: name.into(),
+                port_type: super::super::PortType::Any,
+                description: None,
+                required: false,
+                default_value: None,
+                variadic: false,
+                same_type_as: None,
+            };
+            (tool_ref == "t/echo@1").then(|| (vec![port("text")], vec![port("out")]))
+        };
+        let options = ValidateOptions {
+            tool_ports: Some(&echo),
+            ..Default::default()
+        };
+        let unknown = validate(&spec, options)
+            .into_iter()
+            .filter(|i| i.code == "unknown_port")
+            .count();
+        // e1–e4 into primitives, plus e6 into the composite
+        assert_eq!(unknown, 5);
+    }
+}
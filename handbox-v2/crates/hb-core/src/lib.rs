@@ -3,8 +3,11 @@
 //! This crate has zero internal crate dependencies and defines the
 //! canonical types used across all other hb-* crates.
 
+pub mod error;
+pub mod expr;
 pub mod graph;
 pub mod pack;
+pub mod path;
 pub mod policy;
 pub mod project;
 pub mod tool;
@@ -13,9 +16,10 @@ pub mod trace;
 /// Re-export commonly used types.
 pub mod prelude {
     pub use crate::graph::{
-        CompositeNodeSpec, ConditionalSpec, EdgeKind, EdgeSpec, LoopKind, LoopSpec, NodeSpec,
-        PortSpec, PortType, SubgraphSpec, VariableSpec, WorkflowMeta, WorkflowSpec,
+        CompositeNodeSpec, ConditionalSpec, EdgeKind, EdgeSpec, LoopKind, LoopSpec, MapReduceSpec,
+        NodeSpec, PortSpec, PortType, SubgraphSpec, VariableSpec, WorkflowMeta, WorkflowSpec,
     };
+    pub use crate::error::{AppError, ErrorCode};
     pub use crate::pack::PackManifest;
     pub use crate::policy::{CostLimit, PermissionSet};
     pub use crate::tool::Permission;
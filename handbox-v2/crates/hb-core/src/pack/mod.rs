@@ -1,6 +1,10 @@
-//! Pack manifest v0.1 — extension unit with tools, templates, and composites.
+//! Pack manifest v0.1 — extension unit with tools, templates, composites,
+//! and the prompt-to-workflow content the compiler and evals consume:
+//! compile templates, prompt libraries, and eval suites.
 
+use crate::graph::{CompositeNodeSpec, WorkflowSpec};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 // ---------------------------------------------------------------------------
 // PackManifest
@@ -40,19 +44,199 @@ pub struct PackManifest {
     pub category: PackCategory,
 
     /// Relative paths to tool definition files within the pack.
+    #[serde(default)]
     pub tools: Vec<String>,
 
     /// Relative paths to workflow template files.
     #[serde(default)]
     pub templates: Vec<String>,
 
-    /// Relative paths to composite node definitions.
+    /// Relative paths to composite node definitions ([`CompositeDefinition`]).
     #[serde(default)]
     pub composites: Vec<String>,
 
+    /// Relative paths to compile template files ([`CompileTemplate`]).
+    #[serde(default)]
+    pub compile_templates: Vec<String>,
+
+    /// Relative paths to prompt library files ([`PromptLibrary`]).
+    #[serde(default)]
+    pub prompt_libraries: Vec<String>,
+
+    /// Relative paths to eval suite files ([`EvalSuite`]).
+    #[serde(default)]
+    pub eval_suites: Vec<String>,
+
     /// Runtime requirements the host must satisfy.
     #[serde(default)]
     pub runtime_requirements: Option<RuntimeRequirements>,
+
+    /// How nodes saved against earlier versions carry over to this one.
+    #[serde(default)]
+    pub migrations: Vec<ToolMigration>,
+}
+
+impl PackManifest {
+    /// Files the manifest lists for one kind of content.
+    pub fn files(&self, kind: PackContentKind) -> &[String] {
+        match kind {
+            PackContentKind::Tool => &self.tools,
+            PackContentKind::WorkflowTemplate => &self.templates,
+            PackContentKind::Composite => &self.composites,
+            PackContentKind::CompileTemplate => &self.compile_templates,
+            PackContentKind::PromptLibrary => &self.prompt_libraries,
+            PackContentKind::EvalSuite => &self.eval_suites,
+        }
+    }
+
+    /// Kinds of content the pack ships, in [`PackContentKind::ALL`] order.
+    pub fn content_kinds(&self) -> Vec<PackContentKind> {
+        PackContentKind::ALL
+            .into_iter()
+            .filter(|kind| !self.files(*kind).is_empty())
+            .collect()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Content kinds
+// ---------------------------------------------------------------------------
+
+/// A publishable kind of pack content.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum PackContentKind {
+    Tool,
+    WorkflowTemplate,
+    Composite,
+    CompileTemplate,
+    PromptLibrary,
+    EvalSuite,
+}
+
+impl PackContentKind {
+    pub const ALL: [Self; 6] = [
+        Self::Tool,
+        Self::WorkflowTemplate,
+        Self::Composite,
+        Self::CompileTemplate,
+        Self::PromptLibrary,
+        Self::EvalSuite,
+    ];
+
+    /// Kinds installed outside the pack directory, into their own library.
+    pub const LIBRARY: [Self; 4] = [
+        Self::Composite,
+        Self::CompileTemplate,
+        Self::PromptLibrary,
+        Self::EvalSuite,
+    ];
+
+    /// Directory the kind is installed under, relative to the content library.
+    pub fn install_dir(self) -> &'static str {
+        match self {
+            Self::Tool => "tools",
+            Self::WorkflowTemplate => "templates",
+            Self::Composite => "composites",
+            Self::CompileTemplate => "compile-templates",
+            Self::PromptLibrary => "prompts",
+            Self::EvalSuite => "evals",
+        }
+    }
+}
+
+/// A ready-made composite node users drop into their workflows. `version`
+/// is the composite's own and changes when its behavior does.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompositeDefinition {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub version: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub composite: CompositeNodeSpec,
+}
+
+impl CompositeDefinition {
+    /// A copy of the composite to place in a workflow as `node_id`.
+    pub fn instantiate(&self, node_id: &str) -> CompositeNodeSpec {
+        let mut node = self.composite.clone();
+        node.id = node_id.to_string();
+        node.label.get_or_insert_with(|| self.name.clone());
+        node
+    }
+}
+
+/// A "prompt → workflow" recipe: the compiler picks it when a prompt
+/// matches its keywords and fills `{{slot}}` placeholders in the workflow.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompileTemplate {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Words that make a prompt a match for this template.
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    #[serde(default)]
+    pub slots: Vec<TemplateSlot>,
+    pub workflow: WorkflowSpec,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateSlot {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+/// Reusable prompts with `{{variable}}` placeholders.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptLibrary {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub prompts: Vec<PromptEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptEntry {
+    pub id: String,
+    pub name: String,
+    pub template: String,
+    #[serde(default)]
+    pub variables: Vec<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Input/expectation cases a workflow is checked against.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalSuite {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Compile template or workflow the suite targets, if any.
+    #[serde(default)]
+    pub target: Option<String>,
+    pub cases: Vec<EvalCase>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalCase {
+    pub name: String,
+    /// Workflow variables or the prompt to compile.
+    #[serde(default)]
+    pub inputs: serde_json::Map<String, serde_json::Value>,
+    /// Output values expected, by output name.
+    #[serde(default)]
+    pub expected: serde_json::Map<String, serde_json::Value>,
 }
 
 // ---------------------------------------------------------------------------
@@ -81,7 +265,7 @@ pub enum PackCategory {
     Custom,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RuntimeRequirements {
     #[serde(default)]
     pub python: Option<String>,
@@ -93,6 +277,48 @@ pub struct RuntimeRequirements {
     pub os: Vec<String>,
 }
 
+// ---------------------------------------------------------------------------
+// Migrations
+// ---------------------------------------------------------------------------
+
+/// Rules that carry nodes of one tool over from earlier versions of the
+/// pack, declared by the release that changed the tool.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolMigration {
+    /// Tool whose nodes are migrated, e.g. "rag-pack/chunk".
+    pub tool_id: String,
+    /// Versions of the pack the rules upgrade from, as a semver range such
+    /// as "<2.0.0"; every earlier version when unset.
+    #[serde(default)]
+    pub from: Option<String>,
+    /// Applied in order.
+    pub rules: Vec<MigrationRule>,
+}
+
+/// One change to the nodes of a tool.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum MigrationRule {
+    /// Move a config value to a new key.
+    RenameConfig { from: String, to: String },
+    /// Drop a config value the tool no longer reads.
+    RemoveConfig { key: String },
+    /// Set a config value the node doesn't have yet.
+    SetConfig { key: String, value: serde_json::Value },
+    /// Replace config values, e.g. retired model names. Keys are the old
+    /// values as text.
+    MapValue {
+        key: String,
+        values: BTreeMap<String, serde_json::Value>,
+    },
+    /// Reconnect the edges of a renamed input or output port.
+    RenamePort { from: String, to: String },
+    /// The tool moved to another ID within the pack.
+    RenameTool { to: String },
+    /// A change nobody can make automatically, explained by `message`.
+    Manual { message: String },
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -121,17 +347,58 @@ mod tests {
             tools: vec!["tools/chunk.json".into(), "tools/embed.json".into()],
             templates: vec!["templates/rag-basic.json".into()],
             composites: vec![],
+            compile_templates: vec![],
+            prompt_libraries: vec!["prompts/rag.json".into()],
+            eval_suites: vec![],
             runtime_requirements: Some(RuntimeRequirements {
                 python: Some(">=3.10".into()),
                 docker: None,
                 native_deps: vec![],
                 os: vec![],
             }),
+            migrations: vec![ToolMigration {
+                tool_id: "rag-pack/chunk".into(),
+                from: Some("<2.0.0".into()),
+                rules: vec![MigrationRule::RenameConfig {
+                    from: "size".into(),
+                    to: "chunk_size".into(),
+                }],
+            }],
         };
 
         let json = serde_json::to_string_pretty(&manifest).unwrap();
         let back: PackManifest = serde_json::from_str(&json).unwrap();
         assert_eq!(back.id, "rag-pack");
         assert_eq!(back.category, PackCategory::Rag);
+        assert_eq!(back.migrations[0].rules, manifest.migrations[0].rules);
+        assert!(json.contains(r#""op": "rename_config""#));
+        assert_eq!(
+            back.content_kinds(),
+            vec![
+                PackContentKind::Tool,
+                PackContentKind::WorkflowTemplate,
+                PackContentKind::PromptLibrary
+            ]
+        );
+    }
+
+    #[test]
+    fn content_only_packs_need_no_tools() {
+        let manifest: PackManifest = serde_json::from_value(serde_json::json!({
+            "pack_version": "0.1.0",
+            "id": "evals",
+            "version": "0.2.0",
+            "name": "Evals",
+            "description": "",
+            "author": "",
+            "license": "MIT",
+            "platform_version": "0.1.0",
+            "category": "custom",
+            "eval_suites": ["evals/summaries.json"],
+        }))
+        .unwrap();
+        assert!(manifest.tools.is_empty());
+        assert_eq!(manifest.content_kinds(), vec![PackContentKind::EvalSuite]);
+        assert_eq!(PackContentKind::EvalSuite.install_dir(), "evals");
     }
 }
@@ -0,0 +1,351 @@
+//! Path handling shared by file tools, pack loading and data sources.
+//!
+//! Paths reach the app as text: pasted with quotes, dropped as `file://`
+//! URLs, in the Windows extended-length form, or with Korean names in a
+//! different Unicode normalization than the one on disk (macOS writes
+//! decomposed Hangul, so names copied from a Mac often differ byte-wise from
+//! what the user types). These helpers turn such text into a path that opens,
+//! and compare paths the way the file system would.
+
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use unicode_normalization::UnicodeNormalization;
+
+/// Paths at least this long get the `\\?\` prefix on Windows. `MAX_PATH` is
+/// 260, but directories are limited to 248 so files can still fit in them.
+pub const LONG_PATH_THRESHOLD: usize = 248;
+
+const VERBATIM: &str = r"\\?\";
+const VERBATIM_UNC: &str = r"\\?\UNC\";
+const DEVICE: &str = r"\\.\";
+
+/// Clean up a path typed, pasted or dropped by the user: surrounding
+/// whitespace and quotes, `file://` URLs and the `\\?\` prefix are removed.
+pub fn from_user_input(raw: &str) -> PathBuf {
+    let mut text = raw.trim();
+    for quote in ['"', '\''] {
+        if text.len() >= 2 && text.starts_with(quote) && text.ends_with(quote) {
+            text = text[1..text.len() - 1].trim();
+        }
+    }
+    let text = file_url_path(text).map_or(Cow::Borrowed(text), Cow::Owned);
+    PathBuf::from(strip_verbatim(&text).into_owned())
+}
+
+/// Local path of a `file://` URL, percent-decoded. A host other than
+/// `localhost` becomes a UNC share.
+pub fn file_url_path(text: &str) -> Option<String> {
+    let scheme = text.get(..7)?;
+    if !scheme.eq_ignore_ascii_case("file://") {
+        return None;
+    }
+    let rest = &text[7..];
+    let (host, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    let path = percent_decode(path);
+    if !host.is_empty() && !host.eq_ignore_ascii_case("localhost") {
+        return Some(format!("//{}{path}", percent_decode(host)));
+    }
+    // file:///C:/dir → C:/dir
+    let bytes = path.as_bytes();
+    if bytes.len() >= 3 && bytes[0] == b'/' && bytes[1].is_ascii_alphabetic() && bytes[2] == b':' {
+        return Some(path[1..].to_string());
+    }
+    Some(path)
+}
+
+/// Decode `%XX` escapes. Text that doesn't decode to UTF-8 is returned as is.
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = |b: u8| (b as char).to_digit(16);
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let (Some(hi), Some(lo)) = (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                out.push((hi * 16 + lo) as u8);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8(out).unwrap_or_else(|_| text.to_string())
+}
+
+/// Remove the `\\?\` prefix from a drive or UNC path, for display and for
+/// programs that don't understand it.
+pub fn strip_verbatim(path: &str) -> Cow<'_, str> {
+    if let Some(rest) = strip_prefix_ignore_case(path, VERBATIM_UNC) {
+        return Cow::Owned(format!(r"\\{rest}"));
+    }
+    if let Some(rest) = path.strip_prefix(VERBATIM) {
+        if is_drive_absolute(rest) {
+            return Cow::Borrowed(rest);
+        }
+    }
+    Cow::Borrowed(path)
+}
+
+fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = text.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix)
+        .then(|| &text[prefix.len()..])
+}
+
+fn is_drive_absolute(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    bytes.len() >= 3
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && matches!(bytes[2], b'\\' | b'/')
+}
+
+fn is_unc(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    bytes.len() > 2
+        && matches!(bytes[0], b'\\' | b'/')
+        && matches!(bytes[1], b'\\' | b'/')
+        && !matches!(bytes[2], b'\\' | b'/' | b'?' | b'.')
+}
+
+/// The extended-length form of an absolute Windows path: `\\?\C:\…` or
+/// `\\?\UNC\server\share\…`. Separators are unified and `.`/`..` resolved,
+/// since Windows does neither for these paths. `None` for relative paths and
+/// paths already in a prefixed form.
+pub fn extended_length(path: &str) -> Option<String> {
+    if path.starts_with(VERBATIM) || path.starts_with(DEVICE) {
+        return None;
+    }
+    let (prefix, root, rest) = if is_drive_absolute(path) {
+        (VERBATIM, &path[..2], &path[3..])
+    } else if is_unc(path) {
+        let mut parts = path[2..].splitn(3, ['\\', '/']);
+        let server = parts.next()?;
+        let share = parts.next().filter(|s| !s.is_empty())?;
+        let rest = parts.next().unwrap_or("");
+        let root_len = 2 + server.len() + 1 + share.len();
+        (VERBATIM_UNC, &path[2..root_len], rest)
+    } else {
+        return None;
+    };
+
+    let mut components: Vec<&str> = Vec::new();
+    for component in rest.split(['\\', '/']) {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            // Windows drops trailing dots and spaces from names
+            name => {
+                let name = name.trim_end_matches(['.', ' ']);
+                if !name.is_empty() {
+                    components.push(name);
+                }
+            }
+        }
+    }
+    let root = root.replace('/', "\\");
+    Some(format!("{prefix}{root}\\{}", components.join("\\")))
+}
+
+/// `path` in a form the file system accepts at any length: on Windows, long
+/// absolute paths get the extended-length prefix. Elsewhere unchanged.
+pub fn for_io(path: &Path) -> PathBuf {
+    if cfg!(windows) {
+        let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+        if let Some(text) = absolute.to_str() {
+            if text.len() >= LONG_PATH_THRESHOLD {
+                if let Some(extended) = extended_length(text) {
+                    return PathBuf::from(extended);
+                }
+            }
+        }
+    }
+    path.to_path_buf()
+}
+
+/// Resolve user-supplied path text to a path to open. When the path doesn't
+/// exist as written, the composed and decomposed Unicode spellings of each
+/// component are tried too (folders and files copied at different times can
+/// differ).
+pub fn resolve(raw: &str) -> PathBuf {
+    resolve_in(raw, None)
+}
+
+/// [`from_user_input`], with a relative path taken from `base` (the
+/// workspace root) rather than the process's working directory.
+pub fn from_user_input_in(raw: &str, base: Option<&Path>) -> PathBuf {
+    let path = from_user_input(raw);
+    match base {
+        Some(base) if path.is_relative() && !path.has_root() && !base.as_os_str().is_empty() => {
+            base.join(path)
+        }
+        _ => path,
+    }
+}
+
+/// [`resolve`], with a relative path taken from `base`.
+pub fn resolve_in(raw: &str, base: Option<&Path>) -> PathBuf {
+    let path = from_user_input_in(raw, base);
+    if path.to_string_lossy().is_ascii() || for_io(&path).exists() {
+        return for_io(&path);
+    }
+    find_spelling(&path).unwrap_or_else(|| for_io(&path))
+}
+
+fn find_spelling(path: &Path) -> Option<PathBuf> {
+    let mut found = PathBuf::new();
+    for component in path.components() {
+        let candidate = found.join(component);
+        if for_io(&candidate).exists() {
+            found = candidate;
+            continue;
+        }
+        let name = component.as_os_str().to_str()?;
+        let composed: String = name.nfc().collect();
+        let decomposed: String = name.nfd().collect();
+        found = [composed, decomposed]
+            .into_iter()
+            .filter(|variant| variant != name)
+            .map(|variant| found.join(variant))
+            .find(|candidate| for_io(candidate).exists())?;
+    }
+    Some(for_io(&found))
+}
+
+/// A path as shown to the user, without the `\\?\` prefix.
+pub fn display(path: &Path) -> String {
+    strip_verbatim(&path.to_string_lossy()).into_owned()
+}
+
+/// A key for comparing paths: no `\\?\` prefix, composed Unicode, `/`
+/// separators, no trailing separator, and lowercase for Windows paths.
+pub fn comparable(path: &str) -> String {
+    let path = strip_verbatim(path);
+    let windows = is_drive_absolute(&path) || is_unc(&path);
+    let mut key: String = path.nfc().collect::<String>().replace('\\', "/");
+    while key.len() > 1 && key.ends_with('/') {
+        key.pop();
+    }
+    if key == "/" {
+        key.clear();
+    }
+    if windows {
+        key = key.to_lowercase();
+    }
+    key
+}
+
+/// Whether `path` is `base` or lies under it, compared with [`comparable`].
+pub fn within(path: &str, base: &str) -> bool {
+    let base = comparable(base);
+    let path = comparable(path);
+    !base.is_empty()
+        && path
+            .strip_prefix(&base)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cleans_user_input() {
+        assert_eq!(
+            from_user_input("  \"C:\\현장 자료\\도면.csv\" "),
+            PathBuf::from("C:\\현장 자료\\도면.csv")
+        );
+        assert_eq!(
+            from_user_input("file:///C:/%ED%98%84%EC%9E%A5/a%20b.csv"),
+            PathBuf::from("C:/현장/a b.csv")
+        );
+        assert_eq!(
+            from_user_input("file:///home/kim/%ED%98%84%EC%9E%A5"),
+            PathBuf::from("/home/kim/현장")
+        );
+        assert_eq!(
+            from_user_input("file://nas/projects/a.ifc"),
+            PathBuf::from("//nas/projects/a.ifc")
+        );
+        assert_eq!(
+            from_user_input(r"\\?\UNC\nas\projects\a.ifc"),
+            PathBuf::from(r"\\nas\projects\a.ifc")
+        );
+        assert_eq!(from_user_input(r"\\?\D:\data"), PathBuf::from(r"D:\data"));
+        // Broken escapes are kept rather than mangled
+        assert_eq!(
+            from_user_input("file:///tmp/100%"),
+            PathBuf::from("/tmp/100%")
+        );
+    }
+
+    #[test]
+    fn builds_extended_length_paths() {
+        assert_eq!(
+            extended_length("C:/Users/김/./docs/../현장//a.csv").as_deref(),
+            Some(r"\\?\C:\Users\김\현장\a.csv")
+        );
+        assert_eq!(
+            extended_length(r"\\nas\projects\2024\site. \a.ifc").as_deref(),
+            Some(r"\\?\UNC\nas\projects\2024\site\a.ifc")
+        );
+        assert_eq!(extended_length("relative/path"), None);
+        assert_eq!(extended_length(r"\\?\C:\already"), None);
+        assert_eq!(extended_length(r"\\nas"), None);
+
+        for path in [r"C:\a\b", r"\\nas\share\a"] {
+            let extended = extended_length(path).unwrap();
+            assert_eq!(strip_verbatim(&extended), path);
+        }
+        // Only drive and UNC forms are unwrapped
+        assert_eq!(strip_verbatim(r"\\?\Volume{x}\a"), r"\\?\Volume{x}\a");
+    }
+
+    #[test]
+    fn compares_across_spellings() {
+        let composed = "/data/현장";
+        let decomposed: String = composed.nfd().collect();
+        assert_ne!(composed, decomposed);
+        assert!(within(&format!("{decomposed}/a.csv"), composed));
+        assert!(within(r"\\?\C:\Data\Site\a.csv", "c:/data/site/"));
+        assert!(within(r"\\NAS\Share\x", r"\\?\UNC\nas\share"));
+        assert!(!within("/data/customers-old/a.csv", "/data/customers"));
+        // Unix paths stay case-sensitive
+        assert!(!within("/Data/a", "/data"));
+        assert!(!within("/anything", "/"));
+    }
+
+    #[test]
+    fn resolves_other_unicode_spelling_on_disk() {
+        let dir = std::env::temp_dir().join(format!("hb-path-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let decomposed: String = "현장.csv".nfd().collect();
+        std::fs::write(dir.join(&decomposed), "a,b").unwrap();
+
+        let typed = format!("\"{}\"", dir.join("현장.csv").display());
+        let resolved = resolve(&typed);
+        assert_eq!(std::fs::read_to_string(&resolved).unwrap(), "a,b");
+        // Missing files resolve to the cleaned-up input
+        assert_eq!(
+            resolve(&format!("{}/없음.csv", dir.display())),
+            dir.join("없음.csv")
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn relative_paths_resolve_against_base() {
+        let root = Path::new("/projects/현장");
+        assert_eq!(
+            from_user_input_in("\"data/a.csv\"", Some(root)),
+            root.join("data/a.csv")
+        );
+        assert_eq!(from_user_input_in("/tmp/a.csv", Some(root)), PathBuf::from("/tmp/a.csv"));
+        assert_eq!(from_user_input_in("a.csv", None), PathBuf::from("a.csv"));
+        assert_eq!(from_user_input_in("a.csv", Some(Path::new(""))), PathBuf::from("a.csv"));
+        assert_eq!(resolve_in("out/b.json", Some(root)), root.join("out/b.json"));
+    }
+}
@@ -1,7 +1,10 @@
 //! Policy types — permissions, cost limits, and tool whitelists.
 
+use crate::project::SensitiveCategory;
 use crate::tool::Permission;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
 
 // ---------------------------------------------------------------------------
 // Permission set (attached to a project or workflow)
@@ -76,6 +79,235 @@ impl ToolWhitelist {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Command allowlist
+// ---------------------------------------------------------------------------
+
+/// Programs the shell-exec tool may start without asking. Any other program
+/// needs the user's approval each time it runs.
+///
+/// A bare name ("ogr2ogr") allows that program as found on `PATH`, and only
+/// when the node names it the same way; a path allows exactly that file.
+/// Allowing a shell or interpreter allows whatever it is told to run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CommandPolicy {
+    #[serde(default)]
+    pub allowed_programs: Vec<String>,
+}
+
+impl CommandPolicy {
+    pub fn allows(&self, program: &str) -> bool {
+        let program = program.trim();
+        let is_path = |p: &str| p.contains(['/', '\\']);
+        self.allowed_programs.iter().any(|allowed| {
+            let allowed = allowed.trim();
+            match (is_path(allowed), is_path(program)) {
+                (true, true) => crate::path::comparable(allowed) == crate::path::comparable(program),
+                (false, false) => program_name(allowed) == program_name(program),
+                _ => false,
+            }
+        })
+    }
+}
+
+/// A bare program name without a Windows executable extension, lowercased
+/// on Windows where names are case-insensitive.
+fn program_name(name: &str) -> String {
+    let lower = name.to_ascii_lowercase();
+    let name = [".exe", ".cmd", ".bat", ".com"]
+        .iter()
+        .find(|ext| lower.ends_with(*ext))
+        .map_or(name, |ext| &name[..name.len() - ext.len()]);
+    if cfg!(windows) {
+        name.to_ascii_lowercase()
+    } else {
+        name.to_string()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Edge capture
+// ---------------------------------------------------------------------------
+
+/// Whether the values flowing across edges are persisted for later
+/// inspection. Off by default, since values may be large or sensitive.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EdgeCapturePolicy {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Values whose serialized size exceeds this are stored as a truncated preview.
+    #[serde(default)]
+    pub max_value_bytes: Option<usize>,
+
+    /// If non-empty, only these edge IDs are captured.
+    #[serde(default)]
+    pub edges: Vec<String>,
+}
+
+impl EdgeCapturePolicy {
+    pub fn captures(&self, edge_id: &str) -> bool {
+        self.enabled && (self.edges.is_empty() || self.edges.iter().any(|e| e == edge_id))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Liveness
+// ---------------------------------------------------------------------------
+
+/// How long a running node may go without a heartbeat (progress, process
+/// output) before it counts as stalled or hung. Native tools count as alive
+/// while they run, so this applies to process, Python and Docker tools.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LivenessPolicy {
+    /// Warn after this much silence; 0 never warns.
+    #[serde(default = "default_warn_after_secs")]
+    pub warn_after_secs: u64,
+
+    /// Kill a node silent this long and fail the attempt, so the node's
+    /// retry policy decides whether it runs again. Never when unset.
+    #[serde(default)]
+    pub kill_after_secs: Option<u64>,
+}
+
+fn default_warn_after_secs() -> u64 {
+    60
+}
+
+impl Default for LivenessPolicy {
+    fn default() -> Self {
+        Self {
+            warn_after_secs: default_warn_after_secs(),
+            kill_after_secs: None,
+        }
+    }
+}
+
+impl LivenessPolicy {
+    pub fn warn_after(&self) -> Option<Duration> {
+        (self.warn_after_secs > 0).then(|| Duration::from_secs(self.warn_after_secs))
+    }
+
+    pub fn kill_after(&self) -> Option<Duration> {
+        self.kill_after_secs
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Concurrency
+// ---------------------------------------------------------------------------
+
+/// How many nodes of one execution run at once, overall and per resource
+/// class ("llm", "docker", "cpu", ...; see
+/// [`crate::tool::ToolInterface::resource_class`]). Unset or zero limits
+/// are unlimited.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConcurrencyPolicy {
+    #[serde(default)]
+    pub max_parallel: Option<usize>,
+
+    /// Limit per resource class; classes not listed are only held to
+    /// `max_parallel`.
+    #[serde(default = "default_class_limits")]
+    pub classes: BTreeMap<String, usize>,
+}
+
+/// Keeps a wide level from tripping provider rate limits or starting more
+/// containers than a workstation handles.
+fn default_class_limits() -> BTreeMap<String, usize> {
+    BTreeMap::from([("llm".into(), 4), ("docker".into(), 2), ("cpu".into(), 4)])
+}
+
+impl Default for ConcurrencyPolicy {
+    fn default() -> Self {
+        Self {
+            max_parallel: None,
+            classes: default_class_limits(),
+        }
+    }
+}
+
+impl ConcurrencyPolicy {
+    pub fn max_parallel(&self) -> Option<usize> {
+        self.max_parallel.filter(|n| *n > 0)
+    }
+
+    pub fn class_limit(&self, class: &str) -> Option<usize> {
+        self.classes.get(class).copied().filter(|n| *n > 0)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Sensitive data
+// ---------------------------------------------------------------------------
+
+/// Which tools may consume data sources labelled with sensitive data.
+/// Categories without a rule are unrestricted.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SensitiveDataPolicy {
+    #[serde(default)]
+    pub rules: Vec<SensitiveDataRule>,
+}
+
+/// Sources labelled `category` may only be consumed by `allowed_tools`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SensitiveDataRule {
+    pub category: SensitiveCategory,
+    #[serde(default)]
+    pub allowed_tools: Vec<String>,
+}
+
+impl SensitiveDataPolicy {
+    /// The first of `labels` whose rule doesn't allow `tool_id`.
+    pub fn denied_label(&self, labels: &[SensitiveCategory], tool_id: &str) -> Option<SensitiveCategory> {
+        labels.iter().copied().find(|label| {
+            self.rules
+                .iter()
+                .filter(|rule| rule.category == *label)
+                .any(|rule| !rule.allowed_tools.iter().any(|t| t == tool_id))
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Sharing
+// ---------------------------------------------------------------------------
+
+/// What a shared run (a viewer bundle for people without Handbox) keeps.
+/// Known secrets are always redacted; this decides what else is left out.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SharePolicy {
+    /// Keep node input and output values; without them viewers see
+    /// statuses, timings and errors only.
+    #[serde(default = "default_true")]
+    pub include_values: bool,
+
+    /// Keep node and workflow config.
+    #[serde(default = "default_true")]
+    pub include_config: bool,
+
+    /// Object keys whose values are redacted, on top of the built-in secret
+    /// keys (matched case-insensitively as substrings).
+    #[serde(default)]
+    pub redact_keys: Vec<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for SharePolicy {
+    fn default() -> Self {
+        Self {
+            include_values: true,
+            include_config: true,
+            redact_keys: Vec::new(),
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Composite policy (everything together)
 // ---------------------------------------------------------------------------
@@ -85,6 +317,18 @@ pub struct Policy {
     pub permissions: PermissionSet,
     pub cost_limit: CostLimit,
     pub tool_whitelist: ToolWhitelist,
+    #[serde(default)]
+    pub edge_capture: EdgeCapturePolicy,
+    #[serde(default)]
+    pub sensitive_data: SensitiveDataPolicy,
+    #[serde(default)]
+    pub commands: CommandPolicy,
+    #[serde(default)]
+    pub liveness: LivenessPolicy,
+    #[serde(default)]
+    pub concurrency: ConcurrencyPolicy,
+    #[serde(default)]
+    pub sharing: SharePolicy,
 }
 
 // ---------------------------------------------------------------------------
@@ -119,6 +363,51 @@ mod tests {
         assert!(!wl.is_tool_allowed("core-tools/shell-exec")); // blocked
     }
 
+    #[test]
+    fn command_policy_matches_names_and_paths_separately() {
+        let policy = CommandPolicy {
+            allowed_programs: vec!["ogr2ogr".into(), "gdalinfo.exe".into(), "C:/OSGeo4W/bin/python.exe".into()],
+        };
+        assert!(policy.allows("ogr2ogr"));
+        assert!(policy.allows("ogr2ogr.exe"));
+        assert!(policy.allows("gdalinfo"));
+        assert!(policy.allows(r"c:\osgeo4w\bin\python.exe"));
+        // A file that merely shares an allowed name is not allowed
+        assert!(!policy.allows("/tmp/downloads/ogr2ogr"));
+        assert!(!policy.allows("python"));
+        assert!(!policy.allows("ogr2ogr2"));
+        assert!(!CommandPolicy::default().allows("ls"));
+    }
+
+    #[test]
+    fn edge_capture_filters_edges() {
+        let mut policy = EdgeCapturePolicy::default();
+        assert!(!policy.captures("e1"));
+        policy.enabled = true;
+        assert!(policy.captures("e1"));
+        policy.edges = vec!["e2".into()];
+        assert!(!policy.captures("e1"));
+        assert!(policy.captures("e2"));
+    }
+
+    #[test]
+    fn sensitive_data_rules_restrict_labelled_sources() {
+        let policy = SensitiveDataPolicy {
+            rules: vec![SensitiveDataRule {
+                category: SensitiveCategory::CardNumber,
+                allowed_tools: vec!["core-tools/file-read".into()],
+            }],
+        };
+        let labels = [SensitiveCategory::Email, SensitiveCategory::CardNumber];
+
+        assert_eq!(policy.denied_label(&labels, "core-tools/file-read"), None);
+        assert_eq!(
+            policy.denied_label(&labels, "core-tools/llm-chat"),
+            Some(SensitiveCategory::CardNumber)
+        );
+        assert_eq!(policy.denied_label(&[SensitiveCategory::Email], "core-tools/llm-chat"), None);
+    }
+
     #[test]
     fn empty_whitelist_allows_all() {
         let wl = ToolWhitelist {
@@ -2,8 +2,10 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
+use crate::error::{AppError, ErrorCode};
 use crate::policy::Policy;
 
 // ---------------------------------------------------------------------------
@@ -34,6 +36,176 @@ pub struct WorkspaceConfig {
     /// LLM provider configurations.
     #[serde(default)]
     pub llm_providers: Vec<LlmProviderConfig>,
+
+    /// IANA timezone schedules are evaluated in (e.g. "Asia/Seoul"); UTC when unset.
+    #[serde(default)]
+    pub timezone: Option<String>,
+
+    /// Members of a shared workspace. Roles are enforced once there is at
+    /// least one member.
+    #[serde(default)]
+    pub members: Vec<WorkspaceMember>,
+
+    /// When set, workflows must be approved before they are published or
+    /// scheduled from this workspace.
+    #[serde(default)]
+    pub review_policy: Option<ReviewPolicy>,
+}
+
+impl WorkspaceConfig {
+    /// Whether the workspace is shared, i.e. has members whose roles apply.
+    pub fn is_shared(&self) -> bool {
+        !self.members.is_empty()
+    }
+
+    /// Role of `user` in a shared workspace.
+    pub fn role_of(&self, user: Option<&str>) -> Option<Role> {
+        let user = user?;
+        self.members.iter().find(|m| m.user == user).map(|m| m.role)
+    }
+
+    /// Check that `user` may perform `permission`. Returns the user's role in
+    /// a shared workspace, or `None` for an unshared one, where everything is
+    /// allowed.
+    pub fn authorize(
+        &self,
+        user: Option<&str>,
+        permission: Permission,
+    ) -> Result<Option<Role>, AppError> {
+        if !self.is_shared() {
+            return Ok(None);
+        }
+        match self.role_of(user) {
+            Some(role) if role.allows(permission) => Ok(Some(role)),
+            Some(role) => Err(AppError::new(
+                ErrorCode::PermissionDenied,
+                format!(
+                    "The {} role in workspace '{}' does not allow {}",
+                    role.as_str(),
+                    self.name,
+                    permission.describe()
+                ),
+            )),
+            None => Err(AppError::new(
+                ErrorCode::PermissionDenied,
+                format!(
+                    "{} is not a member of workspace '{}'",
+                    user.unwrap_or("An anonymous user"),
+                    self.name
+                ),
+            )
+            .with_hint("Set your user name in the team sync settings")),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Access control
+// ---------------------------------------------------------------------------
+
+/// Role of a member in a shared workspace, from least to most privileged.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Read-only access.
+    Viewer,
+    /// May run workflows but not change them.
+    Runner,
+    /// May edit and run workflows.
+    Editor,
+    /// Everything, including policies, credentials and membership.
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Viewer => "viewer",
+            Self::Runner => "runner",
+            Self::Editor => "editor",
+            Self::Admin => "admin",
+        }
+    }
+
+    pub fn allows(self, permission: Permission) -> bool {
+        match permission {
+            Permission::View => true,
+            Permission::Execute => self >= Role::Runner,
+            Permission::EditWorkflow | Permission::Approve => self >= Role::Editor,
+            Permission::ChangePolicy
+            | Permission::ManageCredentials
+            | Permission::ManageWorkspace => self == Role::Admin,
+        }
+    }
+}
+
+/// An action gated by workspace roles.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    View,
+    Execute,
+    EditWorkflow,
+    /// Deciding workflow reviews; the review policy may demand a higher role.
+    Approve,
+    ChangePolicy,
+    ManageCredentials,
+    /// Membership, settings and deletion of the workspace itself.
+    ManageWorkspace,
+}
+
+impl Permission {
+    pub fn describe(self) -> &'static str {
+        match self {
+            Self::View => "viewing",
+            Self::Execute => "running workflows",
+            Self::EditWorkflow => "editing workflows",
+            Self::Approve => "approving workflows",
+            Self::ChangePolicy => "changing the policy",
+            Self::ManageCredentials => "managing credentials",
+            Self::ManageWorkspace => "managing the workspace",
+        }
+    }
+}
+
+/// A user's role in a shared workspace.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WorkspaceMember {
+    /// User name, as configured in the team sync settings.
+    pub user: String,
+    pub role: Role,
+}
+
+/// Approval required before a workflow is published or scheduled.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ReviewPolicy {
+    /// Minimum role of an approver in a shared workspace.
+    #[serde(default = "default_approver_role")]
+    pub approver_role: Role,
+    /// Approvals from distinct reviewers needed.
+    #[serde(default = "default_min_approvals")]
+    pub min_approvals: u32,
+    /// Whether whoever submitted a workflow may also approve it.
+    #[serde(default)]
+    pub allow_self_approval: bool,
+}
+
+fn default_approver_role() -> Role {
+    Role::Admin
+}
+
+fn default_min_approvals() -> u32 {
+    1
+}
+
+impl Default for ReviewPolicy {
+    fn default() -> Self {
+        Self {
+            approver_role: default_approver_role(),
+            min_approvals: default_min_approvals(),
+            allow_self_approval: false,
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -48,6 +220,76 @@ pub struct DataSource {
     pub path_or_uri: String,
     #[serde(default)]
     pub metadata: serde_json::Map<String, serde_json::Value>,
+    /// Sensitive data found in the source; `None` until it is classified.
+    #[serde(default)]
+    pub classification: Option<DataClassification>,
+}
+
+impl DataSource {
+    /// Sensitive data labels on the source.
+    pub fn labels(&self) -> &[SensitiveCategory] {
+        self.classification.as_ref().map_or(&[], |c| &c.labels)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Run context
+// ---------------------------------------------------------------------------
+
+/// What tools know about the workspace a run belongs to. The runner passes
+/// it to every native tool as the reserved [`WorkspaceContext::INPUT_KEY`]
+/// input, so relative paths resolve against the project root rather than
+/// wherever the app was started.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WorkspaceContext {
+    pub workspace_id: Uuid,
+    pub root_path: String,
+    #[serde(default)]
+    pub data_sources: Vec<DataSourceRef>,
+}
+
+/// A data source as tools see it: where it is, not how it was classified.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DataSourceRef {
+    pub id: String,
+    pub name: String,
+    pub kind: DataSourceKind,
+    pub path_or_uri: String,
+}
+
+impl WorkspaceContext {
+    /// Input key the context is passed under. Ports can't use it.
+    pub const INPUT_KEY: &'static str = "_context";
+
+    /// The context in a tool's inputs, if the run has a workspace.
+    pub fn from_inputs(inputs: &serde_json::Value) -> Option<Self> {
+        serde_json::from_value(inputs.get(Self::INPUT_KEY)?.clone()).ok()
+    }
+
+    /// A path from node config or inputs, ready to open: relative paths are
+    /// taken from the workspace root.
+    pub fn resolve_path(&self, raw: &str) -> std::path::PathBuf {
+        crate::path::resolve_in(raw, Some(std::path::Path::new(&self.root_path)))
+    }
+}
+
+impl From<&WorkspaceConfig> for WorkspaceContext {
+    fn from(ws: &WorkspaceConfig) -> Self {
+        Self {
+            workspace_id: ws.id,
+            root_path: ws.root_path.clone(),
+            data_sources: ws
+                .data_sources
+                .iter()
+                .map(|ds| DataSourceRef {
+                    id: ds.id.clone(),
+                    name: ds.name.clone(),
+                    kind: ds.kind.clone(),
+                    path_or_uri: ds.path_or_uri.clone(),
+                })
+                .collect(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -61,6 +303,45 @@ pub enum DataSourceKind {
     WebUrl,
 }
 
+/// Category of sensitive data a data source may hold.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum SensitiveCategory {
+    Email,
+    /// Korean resident registration number (주민등록번호).
+    ResidentRegistrationNumber,
+    /// US social security number.
+    SocialSecurityNumber,
+    CardNumber,
+}
+
+impl SensitiveCategory {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Email => "email",
+            Self::ResidentRegistrationNumber => "resident_registration_number",
+            Self::SocialSecurityNumber => "social_security_number",
+            Self::CardNumber => "card_number",
+        }
+    }
+}
+
+/// Sensitive data labels on a data source, from a scan or set by hand.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataClassification {
+    pub labels: Vec<SensitiveCategory>,
+    /// Matches per category in the scanned sample (empty when set by hand).
+    #[serde(default)]
+    pub matches: BTreeMap<SensitiveCategory, usize>,
+    /// Bytes of the source that were scanned.
+    #[serde(default)]
+    pub sampled_bytes: u64,
+    /// Whether the labels were set by hand rather than by a scan.
+    #[serde(default)]
+    pub manual: bool,
+    pub classified_at: DateTime<Utc>,
+}
+
 // ---------------------------------------------------------------------------
 // Index config
 // ---------------------------------------------------------------------------
@@ -105,6 +386,122 @@ pub enum LlmProviderType {
     Custom,
 }
 
+// ---------------------------------------------------------------------------
+// Activity feed
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    WorkflowCreated,
+    WorkflowEdited,
+    WorkflowDeleted,
+    PackInstalled,
+    PackUpdated,
+    ExecutionRun,
+    PolicyChanged,
+    MembersChanged,
+    CredentialsChanged,
+    /// A workflow was submitted for review, approved or rejected.
+    ReviewDecision,
+    /// A data source was classified, by a scan or by hand.
+    DataClassified,
+    /// A command was refused for lack of a role.
+    AccessDenied,
+}
+
+/// One entry in a workspace's activity log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityEntry {
+    pub id: Uuid,
+    /// Workspace the activity belongs to (None for app-wide activity).
+    pub workspace_id: Option<Uuid>,
+    pub kind: ActivityKind,
+    /// ID of the affected object (workflow, pack, execution, ...).
+    pub subject_id: String,
+    /// Human-readable one-liner for the UI.
+    pub summary: String,
+    #[serde(default)]
+    pub actor: Option<String>,
+    /// The actor's role, when the workspace is shared.
+    #[serde(default)]
+    pub role: Option<Role>,
+    #[serde(default)]
+    pub details: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ActivityEntry {
+    pub fn new(kind: ActivityKind, subject_id: impl Into<String>, summary: impl Into<String>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            workspace_id: None,
+            kind,
+            subject_id: subject_id.into(),
+            summary: summary.into(),
+            actor: None,
+            role: None,
+            details: serde_json::Value::Null,
+            created_at: Utc::now(),
+        }
+    }
+
+    pub fn in_workspace(mut self, workspace_id: Option<Uuid>) -> Self {
+        self.workspace_id = workspace_id;
+        self
+    }
+
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = details;
+        self
+    }
+
+    /// Attribute the entry to a user and their role.
+    pub fn by(mut self, actor: Option<String>, role: Option<Role>) -> Self {
+        self.actor = actor;
+        self.role = role;
+        self
+    }
+}
+
+/// A page of activity entries, newest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityPage {
+    pub entries: Vec<ActivityEntry>,
+    pub offset: usize,
+    pub limit: usize,
+    pub has_more: bool,
+}
+
+// ---------------------------------------------------------------------------
+// Environment variables
+// ---------------------------------------------------------------------------
+
+/// Non-secret workspace setting (region, base URL, ...) exposed to process
+/// runtimes and to `{{env:NAME}}` references in node config. Credentials
+/// belong in the provider settings, not here.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnvVar {
+    pub name: String,
+    pub value: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default = "Utc::now")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl EnvVar {
+    /// Whether `name` is a valid variable name: a letter or underscore
+    /// followed by letters, digits or underscores.
+    pub fn is_valid_name(name: &str) -> bool {
+        let mut chars = name.chars();
+        chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -128,9 +525,13 @@ mod tests {
                 kind: DataSourceKind::LocalDirectory,
                 path_or_uri: "/home/user/project/docs".into(),
                 metadata: Default::default(),
+                classification: None,
             }],
             indexes: vec![],
             default_policy: None,
+            timezone: None,
+            members: vec![],
+            review_policy: None,
             llm_providers: vec![LlmProviderConfig {
                 id: "openai".into(),
                 name: "OpenAI".into(),
@@ -146,4 +547,70 @@ mod tests {
         assert_eq!(back.name, "My Project");
         assert_eq!(back.data_sources.len(), 1);
     }
+
+    #[test]
+    fn workspace_context_travels_in_tool_inputs() {
+        let json = serde_json::json!({
+            "id": Uuid::new_v4(),
+            "name": "현장",
+            "root_path": "/home/user/project",
+            "created_at": Utc::now(),
+            "updated_at": Utc::now(),
+            "data_sources": [{
+                "id": "ds1",
+                "name": "도면",
+                "kind": "local_directory",
+                "path_or_uri": "/home/user/project/drawings",
+            }],
+        });
+        let ws: WorkspaceConfig = serde_json::from_value(json).unwrap();
+        let context = WorkspaceContext::from(&ws);
+
+        let inputs = serde_json::json!({
+            "path": "data/a.csv",
+            WorkspaceContext::INPUT_KEY: context,
+        });
+        let back = WorkspaceContext::from_inputs(&inputs).unwrap();
+        assert_eq!(back, context);
+        assert_eq!(back.data_sources[0].kind, DataSourceKind::LocalDirectory);
+        assert_eq!(
+            back.resolve_path("data/a.csv"),
+            std::path::Path::new("/home/user/project/data/a.csv")
+        );
+        assert_eq!(WorkspaceContext::from_inputs(&serde_json::json!({})), None);
+    }
+
+    #[test]
+    fn roles_gate_permissions_in_shared_workspaces() {
+        assert!(Role::Viewer.allows(Permission::View));
+        assert!(!Role::Viewer.allows(Permission::Execute));
+        assert!(Role::Runner.allows(Permission::Execute));
+        assert!(!Role::Runner.allows(Permission::EditWorkflow));
+        assert!(Role::Editor.allows(Permission::EditWorkflow));
+        assert!(!Role::Editor.allows(Permission::ManageCredentials));
+        assert!(Role::Admin.allows(Permission::ChangePolicy));
+
+        let json = serde_json::json!({
+            "id": Uuid::new_v4(),
+            "name": "Shared",
+            "root_path": "",
+            "created_at": Utc::now(),
+            "updated_at": Utc::now(),
+        });
+        let mut ws: WorkspaceConfig = serde_json::from_value(json).unwrap();
+        assert_eq!(ws.authorize(None, Permission::ChangePolicy).unwrap(), None);
+
+        ws.members = vec![WorkspaceMember {
+            user: "kim".into(),
+            role: Role::Runner,
+        }];
+        assert_eq!(
+            ws.authorize(Some("kim"), Permission::Execute).unwrap(),
+            Some(Role::Runner)
+        );
+        let denied = ws.authorize(Some("kim"), Permission::EditWorkflow).unwrap_err();
+        assert_eq!(denied.code, ErrorCode::PermissionDenied);
+        assert!(ws.authorize(Some("lee"), Permission::View).is_err());
+        assert!(ws.authorize(None, Permission::View).is_err());
+    }
 }
@@ -2,6 +2,7 @@
 
 use crate::graph::{PortSpec, RetryPolicy};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 // ---------------------------------------------------------------------------
 // ToolInterface
@@ -21,6 +22,14 @@ pub struct ToolInterface {
     /// What this tool does.
     pub description: String,
 
+    /// Palette category (derived from the first capability tag when absent).
+    #[serde(default)]
+    pub category: Option<String>,
+
+    /// Icon reference for the palette (lucide icon name).
+    #[serde(default)]
+    pub icon: Option<String>,
+
     /// Hierarchical capability tags (e.g. "rag.ingest", "file.parse.pdf").
     pub capability_tags: Vec<CapabilityTag>,
 
@@ -48,6 +57,50 @@ pub struct ToolInterface {
     /// Optional tool-level configuration fields.
     #[serde(default)]
     pub config_schema: Vec<ConfigField>,
+
+    /// Shared resources (indexes, output files) the tool reads or writes;
+    /// executions touching the same resource are serialized.
+    #[serde(default)]
+    pub resources: Vec<ResourceDecl>,
+}
+
+impl ToolInterface {
+    /// Whether a run with unchanged inputs may be skipped: the tool is
+    /// idempotent, doesn't write, start processes or call the network, and
+    /// doesn't ask the user (`ui.prompt`). The outputs of chains of pure
+    /// tools are reused across executions of workflows that opt in.
+    pub fn is_pure(&self) -> bool {
+        self.error_model.idempotent
+            && matches!(self.side_effect, SideEffect::None | SideEffect::Read)
+            && !self.capability_tags.iter().any(|tag| tag.0 == "ui.prompt")
+    }
+
+    /// Whether the tool's output also depends on files it reads, so a
+    /// memoized run is only valid while those files are unchanged.
+    pub fn reads_files(&self) -> bool {
+        self.side_effect == SideEffect::Read
+    }
+
+    /// Scheduler resource class the tool's nodes count against when they
+    /// don't name one: "llm" for tools with token estimates or an `llm://`
+    /// resource, "docker" for container runtimes, and "cpu" for local
+    /// programs and slow tools. Other tools only count toward the overall
+    /// limit.
+    pub fn resource_class(&self) -> Option<&'static str> {
+        let uses_llm = self.cost_hint.estimated_tokens.is_some()
+            || self.resources.iter().any(|r| r.uri.starts_with("llm://"));
+        if uses_llm {
+            return Some("llm");
+        }
+        match self.runtime {
+            RuntimeSpec::Docker { .. } => Some("docker"),
+            RuntimeSpec::Process(_) | RuntimeSpec::Python { .. } | RuntimeSpec::Wasm { .. } => Some("cpu"),
+            RuntimeSpec::Native if matches!(self.cost_hint.time, TimeHint::Slow | TimeHint::VerySlow) => {
+                Some("cpu")
+            }
+            _ => None,
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -93,6 +146,78 @@ pub enum SideEffect {
     Process,
 }
 
+// ---------------------------------------------------------------------------
+// Resources
+// ---------------------------------------------------------------------------
+
+/// A resource a tool touches, identified by a URI template such as
+/// `index://{config.index_name}` or `file://{input.path}`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ResourceDecl {
+    pub uri: String,
+    #[serde(default)]
+    pub access: ResourceAccess,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceAccess {
+    /// Shared with other readers.
+    Read,
+    /// Exclusive.
+    #[default]
+    Write,
+}
+
+impl ResourceDecl {
+    /// Fill `{config.<key>}` and `{input.<port>}` placeholders. Returns `None`
+    /// when a placeholder has no value (or a non-scalar one).
+    pub fn resolve(
+        &self,
+        config: &serde_json::Map<String, serde_json::Value>,
+        input: &serde_json::Value,
+    ) -> Option<String> {
+        fill_placeholders(&self.uri, config, input)
+    }
+}
+
+/// Fill `{config.<key>}` and `{input.<port>}` placeholders in `template`
+/// with a node's config and inputs; other braces are kept as written.
+/// Returns `None` when a placeholder has no value (or a non-scalar one).
+pub fn fill_placeholders(
+    template: &str,
+    config: &serde_json::Map<String, serde_json::Value>,
+    input: &serde_json::Value,
+) -> Option<String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let inner = &rest[open + 1..];
+        let placeholder = inner.find('}').and_then(|close| {
+            let value = match inner[..close].split_once('.')? {
+                ("config", key) => config.get(key),
+                ("input", port) => input.get(port),
+                _ => return None,
+            };
+            Some((value, close))
+        });
+        let Some((value, close)) = placeholder else {
+            out.push('{');
+            rest = inner;
+            continue;
+        };
+        match value? {
+            serde_json::Value::String(s) => out.push_str(s),
+            serde_json::Value::Number(n) => out.push_str(&n.to_string()),
+            _ => return None,
+        }
+        rest = &inner[close + 1..];
+    }
+    out.push_str(rest);
+    Some(out)
+}
+
 // ---------------------------------------------------------------------------
 // Permission
 // ---------------------------------------------------------------------------
@@ -173,9 +298,7 @@ pub struct ErrorType {
 #[serde(tag = "kind", rename_all = "snake_case")]
 pub enum RuntimeSpec {
     Native,
-    Process {
-        command: String,
-    },
+    Process(ProcessSpec),
     Python {
         script: String,
     },
@@ -190,6 +313,38 @@ pub enum RuntimeSpec {
     },
 }
 
+/// How a `process` runtime starts its program. No shell is involved: each
+/// entry of `args` reaches the program as one argument, after placeholders
+/// are filled, so a value containing spaces or `;` can't add commands.
+///
+/// `args`, literal `env` values and `working_dir` may use the
+/// `{config.<key>}` and `{input.<port>}` placeholders of [`ResourceDecl`],
+/// which makes them per-node settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessSpec {
+    /// Program to run. Without `args` it is split into words, so
+    /// `"python tool.py"` works; quotes group words.
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Variables set on the process, over the workspace environment.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, EnvValue>,
+    /// Directory the process starts in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<String>,
+}
+
+/// Value of an environment variable set on a process.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EnvValue {
+    Literal(String),
+    /// `{ "secret": "NAME" }`: looked up in the credential store's service
+    /// keys when the process starts, so the secret never appears in the pack.
+    Secret { secret: String },
+}
+
 // ---------------------------------------------------------------------------
 // Config field (for tool-level settings UI)
 // ---------------------------------------------------------------------------
@@ -208,6 +363,23 @@ pub struct ConfigField {
     pub required: bool,
     #[serde(default)]
     pub options: Vec<ConfigOption>,
+    /// Live list the options are filled from when the config form is built,
+    /// ahead of the static `options`.
+    #[serde(default)]
+    pub options_source: Option<OptionsSource>,
+    /// Expression over the node's config (see [`crate::expr`]) that must be
+    /// truthy for the field to apply, e.g. `mode == 'switch'`. Always shown
+    /// when unset.
+    #[serde(default)]
+    pub visible_when: Option<String>,
+    /// Bounds of a number field.
+    #[serde(default)]
+    pub min: Option<f64>,
+    #[serde(default)]
+    pub max: Option<f64>,
+    /// Regular expression a string value must match.
+    #[serde(default)]
+    pub pattern: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -220,6 +392,11 @@ pub enum ConfigFieldType {
     MultiSelect,
     FilePath,
     Json,
+    /// A regular expression; patterns that don't compile are reported when
+    /// the workflow is validated.
+    Regex,
+    /// A condition expression (see [`crate::expr`]); checked the same way.
+    Expression,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -228,6 +405,18 @@ pub struct ConfigOption {
     pub value: serde_json::Value,
 }
 
+/// Where a field's options come from at runtime.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum OptionsSource {
+    /// Models of the active LLM provider.
+    LlmModels,
+    /// Models installed in the local runtime.
+    LocalModels,
+    /// Names of the stored service keys.
+    ServiceKeys,
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -244,6 +433,8 @@ mod tests {
             version: "1.0.0".into(),
             display_name: "File Read".into(),
             description: "Reads a file from disk".into(),
+            category: None,
+            icon: None,
             capability_tags: vec![CapabilityTag::new("file.read")],
             input_schema: PortSchema {
                 ports: vec![PortSpec {
@@ -252,6 +443,8 @@ mod tests {
                     description: Some("File path".into()),
                     required: true,
                     default_value: None,
+                    variadic: false,
+                    same_type_as: None,
                 }],
             },
             output_schema: PortSchema {
@@ -261,6 +454,8 @@ mod tests {
                     description: Some("File content".into()),
                     required: true,
                     default_value: None,
+                    variadic: false,
+                    same_type_as: None,
                 }],
             },
             side_effect: SideEffect::Read,
@@ -282,12 +477,73 @@ mod tests {
             },
             runtime: RuntimeSpec::Native,
             config_schema: vec![],
+            resources: vec![],
         };
 
         let json = serde_json::to_string_pretty(&tool).unwrap();
         let back: ToolInterface = serde_json::from_str(&json).unwrap();
         assert_eq!(back.tool_id, "core-tools/file-read");
         assert_eq!(back.side_effect, SideEffect::Read);
+
+        // Quick native tools count toward no resource class
+        assert_eq!(back.resource_class(), None);
+        let mut tool = back;
+        tool.cost_hint.time = TimeHint::Slow;
+        assert_eq!(tool.resource_class(), Some("cpu"));
+        tool.resources.push(ResourceDecl {
+            uri: "llm://{config.model}".into(),
+            access: ResourceAccess::Read,
+        });
+        assert_eq!(tool.resource_class(), Some("llm"));
+
+        // File reads are pure but depend on the files; network calls and
+        // prompts for the user are never pure
+        assert!(tool.is_pure() && tool.reads_files());
+        tool.side_effect = SideEffect::Network;
+        assert!(!tool.is_pure());
+        tool.side_effect = SideEffect::None;
+        tool.capability_tags.push(CapabilityTag::new("ui.prompt"));
+        assert!(!tool.is_pure());
+    }
+
+    #[test]
+    fn resource_uri_resolves_placeholders() {
+        let decl = ResourceDecl {
+            uri: "index://{config.index_name}/{input.shard}".into(),
+            access: ResourceAccess::Write,
+        };
+        let config = serde_json::json!({ "index_name": "docs" });
+        let config = config.as_object().unwrap();
+        assert_eq!(
+            decl.resolve(config, &serde_json::json!({ "shard": 2 })).as_deref(),
+            Some("index://docs/2")
+        );
+        assert_eq!(decl.resolve(config, &serde_json::json!({})), None);
+    }
+
+    #[test]
+    fn process_runtime_accepts_bare_command_and_full_spec() {
+        let old: RuntimeSpec =
+            serde_json::from_str(r#"{ "kind": "process", "command": "ogr2ogr" }"#).unwrap();
+        assert!(matches!(old, RuntimeSpec::Process(ref p) if p.command == "ogr2ogr" && p.args.is_empty()));
+
+        let full: RuntimeSpec = serde_json::from_value(serde_json::json!({
+            "kind": "process",
+            "command": "ogr2ogr",
+            "args": ["-f", "GeoJSON", "{config.output}", "{input.path}"],
+            "env": { "GDAL_DATA": "/opt/gdal", "API_TOKEN": { "secret": "VWORLD_KEY" } },
+            "working_dir": "{config.workspace}",
+        }))
+        .unwrap();
+        let RuntimeSpec::Process(spec) = &full else { panic!("{full:?}") };
+        assert_eq!(spec.env["API_TOKEN"], EnvValue::Secret { secret: "VWORLD_KEY".into() });
+        assert_eq!(spec.env["GDAL_DATA"], EnvValue::Literal("/opt/gdal".into()));
+
+        let config = serde_json::json!({ "output": "out; rm -rf ~" });
+        let filled = fill_placeholders(&spec.args[2], config.as_object().unwrap(), &serde_json::json!({}));
+        assert_eq!(filled.as_deref(), Some("out; rm -rf ~"));
+        let json_arg = fill_placeholders(r#"{"id": {input.id}}"#, config.as_object().unwrap(), &serde_json::json!({ "id": 7 }));
+        assert_eq!(json_arg.as_deref(), Some(r#"{"id": 7}"#));
     }
 
     #[test]
@@ -1,5 +1,6 @@
 //! Trace types — evidence records for every node execution.
 
+use crate::graph::WorkflowSpec;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -67,6 +68,11 @@ pub enum ExecutionStatus {
     Skipped,
     CacheHit,
     Cancelled,
+    /// The app shut down (or crashed) while this was still running.
+    Interrupted,
+    /// Every node succeeded, but the outputs broke the workflow's output
+    /// contract.
+    CompletedWithViolations,
 }
 
 // ---------------------------------------------------------------------------
@@ -97,6 +103,234 @@ pub struct ExecutionRecord {
     pub completed_nodes: u32,
     pub failed_nodes: u32,
     pub cache_hits: u32,
+
+    /// Workspace the execution ran in (none for runs outside a workspace,
+    /// and for records written before runs stored it).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace_id: Option<Uuid>,
+
+    /// What the execution produced or touched outside the workflow.
+    #[serde(default)]
+    pub manifest: ExecutionManifest,
+
+    /// Output contract assertions the completed run failed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contract_violations: Vec<ContractViolation>,
+}
+
+/// A broken output contract assertion.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractViolation {
+    pub node_id: String,
+    pub port: String,
+    pub message: String,
+}
+
+// ---------------------------------------------------------------------------
+// ExecutionManifest — files, indexes and external endpoints an execution touched
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    FileWritten,
+    IndexUpdated,
+    ExternalCall,
+}
+
+/// One file, index or endpoint, with the nodes that touched it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArtifactEntry {
+    pub kind: ArtifactKind,
+    /// File path, index name or endpoint URI.
+    pub target: String,
+    pub node_ids: Vec<String>,
+    /// How many times the target was touched.
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExecutionManifest {
+    #[serde(default)]
+    pub artifacts: Vec<ArtifactEntry>,
+    /// The workflow as it ran, which later edits to the saved workflow
+    /// don't change.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spec: Option<WorkflowSpec>,
+}
+
+impl ExecutionManifest {
+    pub fn is_empty(&self) -> bool {
+        self.artifacts.is_empty() && self.spec.is_none()
+    }
+
+    /// Record that `node_id` touched `target`, merging repeat touches.
+    pub fn record(&mut self, kind: ArtifactKind, target: &str, node_id: &str) {
+        match self
+            .artifacts
+            .iter_mut()
+            .find(|a| a.kind == kind && a.target == target)
+        {
+            Some(entry) => {
+                entry.count += 1;
+                if !entry.node_ids.iter().any(|n| n == node_id) {
+                    entry.node_ids.push(node_id.to_string());
+                }
+            }
+            None => self.artifacts.push(ArtifactEntry {
+                kind,
+                target: target.to_string(),
+                node_ids: vec![node_id.to_string()],
+                count: 1,
+            }),
+        }
+    }
+
+    /// Entries of one kind, in the order they were first touched.
+    pub fn of_kind(&self, kind: ArtifactKind) -> impl Iterator<Item = &ArtifactEntry> {
+        self.artifacts.iter().filter(move |a| a.kind == kind)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// EdgeValue — a value that crossed an edge during an execution
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EdgeValue {
+    pub execution_id: Uuid,
+    pub edge_id: String,
+    pub source_node: String,
+    pub source_port: String,
+    pub target_node: String,
+    pub target_port: String,
+
+    /// The value delivered to the target port (a string preview if truncated).
+    pub value: serde_json::Value,
+
+    /// Whether `value` was cut down to the capture policy's size limit.
+    pub truncated: bool,
+
+    /// Serialized size of the original value in bytes.
+    pub size_bytes: u64,
+
+    pub recorded_at: DateTime<Utc>,
+}
+
+// ---------------------------------------------------------------------------
+// TokenUsage — LLM tokens recorded in node spans
+// ---------------------------------------------------------------------------
+
+/// Tokens one execution spent on one model in one day (UTC).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub execution_id: Uuid,
+    pub model: String,
+    /// "YYYY-MM-DD".
+    pub day: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Node runs that called the model.
+    pub calls: u32,
+}
+
+// ---------------------------------------------------------------------------
+// ToolOutcomes — how often a tool's node runs succeed
+// ---------------------------------------------------------------------------
+
+/// Final outcomes of one tool's node runs (after any retries).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ToolOutcomes {
+    /// Tool ID without `@version`.
+    pub tool_id: String,
+    pub runs: u32,
+    pub failures: u32,
+}
+
+impl ToolOutcomes {
+    pub fn success_rate(&self) -> f64 {
+        if self.runs == 0 {
+            return 1.0;
+        }
+        (self.runs - self.failures.min(self.runs)) as f64 / self.runs as f64
+    }
+}
+
+// ---------------------------------------------------------------------------
+// ToolAverages — what a tool's runs usually take
+// ---------------------------------------------------------------------------
+
+/// Duration and token use of one tool's completed runs, cache hits left out.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ToolAverages {
+    /// Tool ID without `@version`.
+    pub tool_id: String,
+    pub runs: u32,
+    pub mean_ms: u64,
+    pub min_ms: u64,
+    pub max_ms: u64,
+    /// Means over all runs; 0 for tools that don't call a model.
+    pub mean_input_tokens: u64,
+    pub mean_output_tokens: u64,
+}
+
+// ---------------------------------------------------------------------------
+// SchedulerMetrics — how an execution's nodes waited and ran
+// ---------------------------------------------------------------------------
+
+/// Scheduler telemetry of one execution, for tuning large DAGs: how long
+/// nodes queued before they ran, how deep the ready queue got and how much
+/// of the available parallelism was used.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SchedulerMetrics {
+    pub execution_id: Uuid,
+    /// Wall time from the first node dispatched to the last one finished.
+    pub wall_ms: u64,
+    /// Ready-queue depth and running nodes after every change.
+    pub samples: Vec<QueueSample>,
+    pub nodes: Vec<NodeTiming>,
+    /// Most nodes running at once.
+    pub peak_running: u32,
+    /// Node run time divided by wall time.
+    pub average_parallelism: f64,
+    /// Most nodes that could have run at once: the overall concurrency
+    /// limit, or the widest level of the DAG.
+    pub capacity: u32,
+    /// `average_parallelism` as a share of `capacity`, 0.0–1.0.
+    pub utilization: f64,
+    /// Loop nodes at any depth, in the order they finished.
+    #[serde(default)]
+    pub loops: Vec<LoopTiming>,
+}
+
+/// Scheduler state at `at_ms` after the execution started.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct QueueSample {
+    pub at_ms: u64,
+    /// Nodes whose inputs are ready but which wait for a slot.
+    pub ready: u32,
+    pub running: u32,
+}
+
+/// When one node was ready, started and finished.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NodeTiming {
+    pub node_id: String,
+    /// DAG level the node ran in, from 0.
+    pub level: u32,
+    /// From ready to started: waiting for a concurrency slot.
+    pub queued_ms: u64,
+    /// From started to finished.
+    pub run_ms: u64,
+}
+
+/// Iterations one run of a loop node made.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LoopTiming {
+    pub node_id: String,
+    pub iterations: u32,
+    pub max_iterations: u32,
+    pub run_ms: u64,
 }
 
 // ---------------------------------------------------------------------------
@@ -107,6 +341,21 @@ pub struct ExecutionRecord {
 mod tests {
     use super::*;
 
+    #[test]
+    fn manifest_merges_repeat_touches() {
+        let mut manifest = ExecutionManifest::default();
+        manifest.record(ArtifactKind::FileWritten, "/out/report.pdf", "n1");
+        manifest.record(ArtifactKind::FileWritten, "/out/report.pdf", "n2");
+        manifest.record(ArtifactKind::ExternalCall, "llm://bedrock", "n1");
+        manifest.record(ArtifactKind::ExternalCall, "llm://bedrock", "n1");
+
+        let files: Vec<_> = manifest.of_kind(ArtifactKind::FileWritten).collect();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].node_ids, ["n1", "n2"]);
+        let calls: Vec<_> = manifest.of_kind(ArtifactKind::ExternalCall).collect();
+        assert_eq!((calls[0].count, calls[0].node_ids.len()), (2, 1));
+    }
+
     #[test]
     fn round_trip_node_span() {
         let span = NodeSpan {
@@ -14,10 +14,11 @@ use tokio::sync::RwLock;
 // ============================================================================
 
 /// Agent execution mode.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum AgentMode {
     /// Fully autonomous — agent decides when to stop.
+    #[default]
     Auto,
     /// Planning only — agent produces a plan but doesn't execute.
     Plan,
@@ -25,12 +26,6 @@ pub enum AgentMode {
     Execute,
 }
 
-impl Default for AgentMode {
-    fn default() -> Self {
-        Self::Auto
-    }
-}
-
 /// Configuration for an agent instance.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentConfig {
@@ -64,7 +59,7 @@ fn default_max_depth() -> usize {
 }
 
 /// Policy governing which tools an agent may call.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ToolPolicy {
     /// Allowlist mode: if non-empty, only these tools are allowed.
     #[serde(default)]
@@ -74,15 +69,6 @@ pub struct ToolPolicy {
     pub denied: HashSet<String>,
 }
 
-impl Default for ToolPolicy {
-    fn default() -> Self {
-        Self {
-            allowed: HashSet::new(),
-            denied: HashSet::new(),
-        }
-    }
-}
-
 impl ToolPolicy {
     /// Check if a tool is permitted under this policy.
     pub fn is_allowed(&self, tool_ref: &str) -> bool {
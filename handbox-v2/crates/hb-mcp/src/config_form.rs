@@ -0,0 +1,242 @@
+//! Config forms — what the property panel renders for a node, generated from
+//! the tool's `config_schema` instead of a hand-written panel per tool.
+//!
+//! A form is the tool's config fields localized like the palette, with
+//! options from live sources (see [`OptionsSource`]) merged ahead of the
+//! static ones, each field's `visible_when` evaluated against the node's
+//! config, and the problems with the values the node has now.
+
+use crate::palette::{localize_tool, PaletteCatalog};
+use hb_core::tool::{ConfigField, ConfigFieldType, ConfigOption, OptionsSource, ToolInterface};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Options probed from live sources by the host before building a form.
+pub type ProbedOptions = HashMap<OptionsSource, Vec<ConfigOption>>;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FormField {
+    #[serde(flatten)]
+    pub field: ConfigField,
+    /// Whether `visible_when` holds for the node's config. Hidden fields
+    /// are not validated.
+    pub visible: bool,
+    /// What is wrong with the node's value for this field.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigForm {
+    pub tool_id: String,
+    pub version: String,
+    pub label: String,
+    pub fields: Vec<FormField>,
+}
+
+impl ConfigForm {
+    /// Whether every visible field holds a valid value.
+    pub fn is_valid(&self) -> bool {
+        self.fields.iter().all(|f| f.error.is_none())
+    }
+}
+
+/// Live sources the tool's fields take options from, for the host to probe.
+pub fn option_sources(tool: &ToolInterface) -> Vec<OptionsSource> {
+    let mut sources = Vec::new();
+    for source in tool.config_schema.iter().filter_map(|f| f.options_source) {
+        if !sources.contains(&source) {
+            sources.push(source);
+        }
+    }
+    sources
+}
+
+/// Build the form for a node of `tool` whose config is `config`.
+pub fn build_form(
+    tool: &ToolInterface,
+    catalog: &PaletteCatalog,
+    locale: &str,
+    config: &Map<String, Value>,
+    probed: &ProbedOptions,
+) -> ConfigForm {
+    let localized = localize_tool(tool, &catalog.chain(locale));
+
+    // Defaults first, so `visible_when` sees what the node will run with
+    let mut effective = Map::new();
+    for field in &tool.config_schema {
+        if let Some(ref default) = field.default_value {
+            effective.insert(field.name.clone(), default.clone());
+        }
+    }
+    effective.extend(config.iter().map(|(k, v)| (k.clone(), v.clone())));
+    let effective = Value::Object(effective);
+
+    let fields = localized
+        .config_fields
+        .into_iter()
+        .map(|mut field| {
+            if let Some(live) = field.options_source.and_then(|s| probed.get(&s)) {
+                let mut options = live.clone();
+                options.extend(
+                    field
+                        .options
+                        .drain(..)
+                        .filter(|o| !live.iter().any(|l| l.value == o.value)),
+                );
+                field.options = options;
+            }
+            let visible = match field.visible_when {
+                Some(ref when) => hb_core::expr::evaluate(when, &effective)
+                    .map(|v| hb_core::expr::truthy(&v))
+                    .unwrap_or(true),
+                None => true,
+            };
+            let error = if visible {
+                check_value(&field, effective.get(&field.name)).err()
+            } else {
+                None
+            };
+            FormField {
+                field,
+                visible,
+                error,
+            }
+        })
+        .collect();
+
+    ConfigForm {
+        tool_id: localized.tool_id,
+        version: localized.version,
+        label: localized.label,
+        fields,
+    }
+}
+
+/// Check `value` against the field's type, bounds, pattern and options.
+pub fn check_value(field: &ConfigField, value: Option<&Value>) -> Result<(), String> {
+    let value = match value {
+        None | Some(Value::Null) => None,
+        Some(Value::String(s)) if s.trim().is_empty() => None,
+        Some(v) => Some(v),
+    };
+    let Some(value) = value else {
+        return if field.required {
+            Err("A value is required".into())
+        } else {
+            Ok(())
+        };
+    };
+    let known = |v: &Value| field.options.is_empty() || field.options.iter().any(|o| o.value == *v);
+
+    match field.field_type {
+        ConfigFieldType::Number => {
+            let n = value
+                .as_f64()
+                .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+                .ok_or("Must be a number")?;
+            if let Some(min) = field.min.filter(|min| n < *min) {
+                return Err(format!("Must be at least {min}"));
+            }
+            if let Some(max) = field.max.filter(|max| n > *max) {
+                return Err(format!("Must be at most {max}"));
+            }
+        }
+        ConfigFieldType::Boolean if !value.is_boolean() => {
+            return Err("Must be true or false".into())
+        }
+        ConfigFieldType::Select if !known(value) => {
+            return Err(format!("{value} is not one of the options"))
+        }
+        ConfigFieldType::MultiSelect => {
+            let items = value.as_array().ok_or("Must be a list of options")?;
+            if let Some(unknown) = items.iter().find(|v| !known(v)) {
+                return Err(format!("{unknown} is not one of the options"));
+            }
+        }
+        ConfigFieldType::Regex => {
+            let text = value.as_str().ok_or("Must be text")?;
+            regex::Regex::new(text).map_err(|e| format!("Invalid regular expression: {e}"))?;
+        }
+        ConfigFieldType::Expression => {
+            let text = value.as_str().ok_or("Must be text")?;
+            hb_core::expr::Expr::parse(text).map_err(|e| format!("Invalid expression: {e}"))?;
+        }
+        _ => {}
+    }
+
+    if let (Some(pattern), Some(text)) = (&field.pattern, value.as_str()) {
+        // A pattern that doesn't compile is the tool's fault; don't block the user
+        if regex::Regex::new(pattern).is_ok_and(|re| !re.is_match(text)) {
+            return Err(format!("Must match {pattern}"));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::{scan_packs, ToolRegistry};
+    use serde_json::json;
+
+    fn core_tool(id: &str) -> ToolInterface {
+        let mut registry = ToolRegistry::new();
+        for tool in scan_packs(&format!("{}/../../packs", env!("CARGO_MANIFEST_DIR"))).unwrap() {
+            registry.register(tool);
+        }
+        registry.get(id).unwrap().clone()
+    }
+
+    fn field<'a>(form: &'a ConfigForm, name: &str) -> &'a FormField {
+        form.fields.iter().find(|f| f.field.name == name).unwrap()
+    }
+
+    #[test]
+    fn forms_follow_the_schema_config_and_live_options() {
+        let catalog = PaletteCatalog::new();
+
+        let llm = core_tool("core-tools/llm-chat");
+        assert_eq!(option_sources(&llm), vec![OptionsSource::LlmModels]);
+        let probed = ProbedOptions::from([(
+            OptionsSource::LlmModels,
+            vec![ConfigOption {
+                label: "GPT-4o".into(),
+                value: json!("gpt-4o"),
+            }],
+        )]);
+        let config = json!({ "temperature": 3 });
+        let form = build_form(&llm, &catalog, "en", config.as_object().unwrap(), &probed);
+        assert_eq!(
+            field(&form, "model").field.options[0].value,
+            json!("gpt-4o")
+        );
+        assert_eq!(
+            field(&form, "temperature").error.as_deref(),
+            Some("Must be at most 2")
+        );
+        assert!(!form.is_valid());
+
+        // `cases` only applies in switch mode
+        let condition = core_tool("core-tools/condition");
+        let config = json!({ "expression": "score >=" });
+        let config = config.as_object().unwrap();
+        let form = build_form(&condition, &catalog, "en", config, &ProbedOptions::new());
+        assert!(!field(&form, "cases").visible);
+        assert!(field(&form, "expression").error.is_some());
+
+        let mut switch = config.clone();
+        switch.insert("mode".into(), json!("switch"));
+        switch.insert("expression".into(), json!("status"));
+        let form = build_form(&condition, &catalog, "en", &switch, &ProbedOptions::new());
+        assert!(field(&form, "cases").visible);
+        assert!(form.is_valid());
+
+        switch.insert("mode".into(), json!("loop"));
+        let form = build_form(&condition, &catalog, "en", &switch, &ProbedOptions::new());
+        assert_eq!(
+            field(&form, "mode").error.as_deref(),
+            Some("\"loop\" is not one of the options")
+        );
+    }
+}
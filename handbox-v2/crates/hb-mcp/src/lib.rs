@@ -2,8 +2,10 @@
 
 pub mod agent_runtime;
 pub mod client;
+pub mod config_form;
 pub mod connector;
 pub mod index;
+pub mod palette;
 pub mod registry;
 pub mod server;
 
@@ -31,3 +33,15 @@ pub enum McpError {
     #[error("registry error: {0}")]
     Registry(String),
 }
+
+impl From<McpError> for hb_core::error::AppError {
+    fn from(e: McpError) -> Self {
+        use hb_core::error::{AppError, ErrorCode};
+        let code = match e {
+            McpError::ToolNotFound(_) => ErrorCode::NotFound,
+            McpError::Server(_) | McpError::Client(_) => ErrorCode::Network,
+            McpError::Registry(_) => ErrorCode::Internal,
+        };
+        AppError::new(code, e.to_string())
+    }
+}
@@ -0,0 +1,378 @@
+//! Node Palette — merges registry tool metadata with localized strings from
+//! pack resource bundles, grouped into categories for the editor palette.
+//!
+//! Packs ship bundles as `locales/<locale>.json`:
+//!
+//! ```json
+//! {
+//!   "categories": { "io": "입력 / 출력" },
+//!   "tools": {
+//!     "core-tools/file-read": {
+//!       "display_name": "파일 읽기",
+//!       "description": "로컬 파일의 내용을 읽습니다",
+//!       "ports": { "path": { "label": "경로" } },
+//!       "config": { "encoding": { "label": "인코딩" } }
+//!     }
+//!   }
+//! }
+//! ```
+
+use crate::registry::ToolRegistry;
+use hb_core::tool::{ConfigField, ToolInterface};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Locale used when neither the requested locale nor its language has strings.
+pub const FALLBACK_LOCALE: &str = "en";
+
+/// Built-in categories: (id, icon, color, default label), in palette order.
+const BUILTIN_CATEGORIES: &[(&str, &str, &str, &str)] = &[
+    ("io", "FileInput", "#3b82f6", "Input / Output"),
+    ("ai", "Brain", "#a855f7", "AI / LLM"),
+    ("text", "Type", "#22c55e", "Text Processing"),
+    ("data", "Database", "#f59e0b", "Data"),
+    ("control", "GitFork", "#ef4444", "Control Flow"),
+    ("rag", "Search", "#06b6d4", "RAG"),
+    ("export", "Download", "#ec4899", "Export"),
+];
+
+const DEFAULT_CATEGORY_ICON: &str = "CircleDot";
+const DEFAULT_CATEGORY_COLOR: &str = "#737373";
+
+// ---------------------------------------------------------------------------
+// Resource bundles
+// ---------------------------------------------------------------------------
+
+/// Localized label/description for a port or config field.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FieldStrings {
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Localized strings for one tool.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolStrings {
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub ports: HashMap<String, FieldStrings>,
+    #[serde(default)]
+    pub config: HashMap<String, FieldStrings>,
+}
+
+/// One locale's strings, as shipped in a pack.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LocaleBundle {
+    #[serde(default)]
+    pub categories: HashMap<String, String>,
+    #[serde(default)]
+    pub tools: HashMap<String, ToolStrings>,
+}
+
+impl LocaleBundle {
+    /// Merge `other` into `self`; entries in `other` win.
+    pub fn merge(&mut self, other: LocaleBundle) {
+        self.categories.extend(other.categories);
+        self.tools.extend(other.tools);
+    }
+}
+
+/// All loaded bundles, keyed by locale (e.g. "ko", "en", "ko-KR").
+#[derive(Debug, Default)]
+pub struct PaletteCatalog {
+    bundles: HashMap<String, LocaleBundle>,
+}
+
+impl PaletteCatalog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_bundle(&mut self, locale: &str, bundle: LocaleBundle) {
+        self.bundles
+            .entry(locale.to_string())
+            .or_default()
+            .merge(bundle);
+    }
+
+    /// Locales with at least one bundle loaded.
+    pub fn locales(&self) -> Vec<String> {
+        let mut locales: Vec<_> = self.bundles.keys().cloned().collect();
+        locales.sort();
+        locales
+    }
+
+    /// Bundles to consult for `locale`, most specific first:
+    /// "ko-KR" → ["ko-KR", "ko", "en"].
+    pub(crate) fn chain(&self, locale: &str) -> Vec<&LocaleBundle> {
+        let mut keys = vec![locale];
+        if let Some((language, _)) = locale.split_once(['-', '_']) {
+            keys.push(language);
+        }
+        keys.push(FALLBACK_LOCALE);
+        keys.dedup();
+        keys.iter().filter_map(|k| self.bundles.get(*k)).collect()
+    }
+}
+
+/// Load every `locales/*.json` bundle from the packs in `packs_dir`.
+pub fn scan_locale_bundles(packs_dir: &str) -> Result<Vec<(String, LocaleBundle)>, String> {
+    let packs_path = Path::new(packs_dir);
+    if !packs_path.exists() {
+        return Err(format!("Packs directory not found: {packs_dir}"));
+    }
+
+    let mut bundles = Vec::new();
+    let entries = std::fs::read_dir(packs_path)
+        .map_err(|e| format!("Failed to read packs dir: {e}"))?;
+
+    for entry in entries.flatten() {
+        let locales_dir = entry.path().join("locales");
+        let Ok(files) = std::fs::read_dir(&locales_dir) else {
+            continue;
+        };
+        for file in files.flatten() {
+            let path = file.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(locale) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+            match serde_json::from_str::<LocaleBundle>(&content) {
+                Ok(bundle) => bundles.push((locale.to_string(), bundle)),
+                Err(e) => tracing::warn!("Failed to parse {}: {e}", path.display()),
+            }
+        }
+    }
+
+    Ok(bundles)
+}
+
+// ---------------------------------------------------------------------------
+// Palette
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PalettePort {
+    pub name: String,
+    pub port_type: hb_core::graph::PortType,
+    pub label: String,
+    pub description: Option<String>,
+    pub required: bool,
+    pub variadic: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PaletteTool {
+    pub tool_id: String,
+    pub version: String,
+    pub label: String,
+    pub description: String,
+    pub category: String,
+    pub icon: Option<String>,
+    pub inputs: Vec<PalettePort>,
+    pub outputs: Vec<PalettePort>,
+    pub config_fields: Vec<ConfigField>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PaletteCategory {
+    pub id: String,
+    pub label: String,
+    pub icon: String,
+    pub color: String,
+    pub tools: Vec<PaletteTool>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Palette {
+    pub locale: String,
+    pub categories: Vec<PaletteCategory>,
+}
+
+/// Palette category of a tool: its explicit category, else the first
+/// segment of its first capability tag.
+pub fn tool_category(tool: &ToolInterface) -> String {
+    if let Some(ref category) = tool.category {
+        return category.clone();
+    }
+    match tool
+        .capability_tags
+        .first()
+        .and_then(|t| t.0.split('.').next())
+    {
+        Some("file" | "io" | "ui") => "io".into(),
+        Some("llm" | "ai") => "ai".into(),
+        Some(segment) if !segment.is_empty() => segment.into(),
+        _ => "other".into(),
+    }
+}
+
+fn first<'a, T>(chain: &[&'a LocaleBundle], f: impl Fn(&'a LocaleBundle) -> Option<T>) -> Option<T> {
+    chain.iter().find_map(|b| f(b))
+}
+
+fn localize_ports(
+    ports: &[hb_core::graph::PortSpec],
+    tool_id: &str,
+    chain: &[&LocaleBundle],
+) -> Vec<PalettePort> {
+    ports
+        .iter()
+        .map(|p| {
+            let strings = |b: &LocaleBundle| b.tools.get(tool_id)?.ports.get(&p.name).cloned();
+            PalettePort {
+                name: p.name.clone(),
+                port_type: p.port_type.clone(),
+                label: first(chain, |b| strings(b)?.label).unwrap_or_else(|| p.name.clone()),
+                description: first(chain, |b| strings(b)?.description)
+                    .or_else(|| p.description.clone()),
+                required: p.required,
+                variadic: p.variadic,
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn localize_tool(tool: &ToolInterface, chain: &[&LocaleBundle]) -> PaletteTool {
+    let id = tool.tool_id.as_str();
+    let config_fields = tool
+        .config_schema
+        .iter()
+        .map(|f| {
+            let strings = |b: &LocaleBundle| b.tools.get(id)?.config.get(&f.name).cloned();
+            let mut field = f.clone();
+            if let Some(label) = first(chain, |b| strings(b)?.label) {
+                field.label = label;
+            } else if field.label.is_empty() {
+                field.label = f.name.clone();
+            }
+            if let Some(description) = first(chain, |b| strings(b)?.description) {
+                field.description = Some(description);
+            }
+            field
+        })
+        .collect();
+
+    PaletteTool {
+        tool_id: tool.tool_id.clone(),
+        version: tool.version.clone(),
+        label: first(chain, |b| b.tools.get(id)?.display_name.clone())
+            .unwrap_or_else(|| tool.display_name.clone()),
+        description: first(chain, |b| b.tools.get(id)?.description.clone())
+            .unwrap_or_else(|| tool.description.clone()),
+        category: tool_category(tool),
+        icon: tool.icon.clone(),
+        inputs: localize_ports(&tool.input_schema.ports, id, chain),
+        outputs: localize_ports(&tool.output_schema.ports, id, chain),
+        config_fields,
+    }
+}
+
+/// Build the palette for `locale` from every registered tool.
+pub fn build_palette(registry: &ToolRegistry, catalog: &PaletteCatalog, locale: &str) -> Palette {
+    let chain = catalog.chain(locale);
+
+    let mut by_category: HashMap<String, Vec<PaletteTool>> = HashMap::new();
+    for tool in registry.list() {
+        let entry = localize_tool(tool, &chain);
+        by_category.entry(entry.category.clone()).or_default().push(entry);
+    }
+
+    // Built-in categories first, in their fixed order, then the rest by ID.
+    let mut ids: Vec<String> = BUILTIN_CATEGORIES
+        .iter()
+        .map(|(id, ..)| id.to_string())
+        .filter(|id| by_category.contains_key(id))
+        .collect();
+    let mut extra: Vec<String> = by_category
+        .keys()
+        .filter(|id| !BUILTIN_CATEGORIES.iter().any(|(b, ..)| b == id))
+        .cloned()
+        .collect();
+    extra.sort();
+    ids.extend(extra);
+
+    let categories = ids
+        .into_iter()
+        .map(|id| {
+            let builtin = BUILTIN_CATEGORIES.iter().find(|(b, ..)| *b == id);
+            let mut tools = by_category.remove(&id).unwrap_or_default();
+            tools.sort_by(|a, b| a.label.cmp(&b.label));
+            PaletteCategory {
+                label: first(&chain, |b| b.categories.get(&id).cloned())
+                    .or_else(|| builtin.map(|(.., label)| label.to_string()))
+                    .unwrap_or_else(|| id.clone()),
+                icon: builtin.map_or(DEFAULT_CATEGORY_ICON, |(_, icon, ..)| *icon).into(),
+                color: builtin.map_or(DEFAULT_CATEGORY_COLOR, |(_, _, color, _)| *color).into(),
+                id,
+                tools,
+            }
+        })
+        .collect();
+
+    Palette {
+        locale: locale.to_string(),
+        categories,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn core_packs_dir() -> String {
+        format!("{}/../../packs", env!("CARGO_MANIFEST_DIR"))
+    }
+
+    fn load(locale: &str) -> Palette {
+        let mut registry = ToolRegistry::new();
+        for tool in crate::registry::scan_packs(&core_packs_dir()).unwrap() {
+            registry.register(tool);
+        }
+        let mut catalog = PaletteCatalog::new();
+        for (locale, bundle) in scan_locale_bundles(&core_packs_dir()).unwrap() {
+            catalog.add_bundle(&locale, bundle);
+        }
+        build_palette(&registry, &catalog, locale)
+    }
+
+    #[test]
+    fn korean_palette_uses_pack_bundle() {
+        let palette = load("ko-KR");
+        let io = palette.categories.iter().find(|c| c.id == "io").unwrap();
+        assert_eq!(io.label, "입력 / 출력");
+        let file_read = io
+            .tools
+            .iter()
+            .find(|t| t.tool_id == "core-tools/file-read")
+            .unwrap();
+        assert_eq!(file_read.label, "파일 읽기");
+        assert_eq!(file_read.icon.as_deref(), Some("FileText"));
+    }
+
+    #[test]
+    fn unknown_locale_falls_back_to_english() {
+        let palette = load("fr");
+        assert_eq!(palette.categories[0].id, "io");
+        assert_eq!(palette.categories[0].label, "Input / Output");
+        assert!(palette
+            .categories
+            .iter()
+            .flat_map(|c| &c.tools)
+            .any(|t| t.label == "File Read"));
+    }
+}
@@ -159,6 +159,12 @@ impl McpServer {
             inputs: arguments,
             config: serde_json::json!({}),
             llm_provider: None,
+            env: Default::default(),
+            progress: Default::default(),
+            stream: Default::default(),
+            commands: Default::default(),
+            secrets: Default::default(),
+            scratch_dir: None,
         };
 
         // Execute with timeout
@@ -278,6 +284,8 @@ mod tests {
             version: "1.0.0".into(),
             display_name: id.into(),
             description: format!("Test tool {id}"),
+            category: None,
+            icon: None,
             capability_tags: vec![],
             input_schema: PortSchema {
                 ports: required_inputs
@@ -288,6 +296,8 @@ mod tests {
                         description: None,
                         required: true,
                         default_value: None,
+                        variadic: false,
+                        same_type_as: None,
                     })
                     .collect(),
             },
@@ -298,6 +308,8 @@ mod tests {
                     description: None,
                     required: true,
                     default_value: None,
+                    variadic: false,
+                    same_type_as: None,
                 }],
             },
             side_effect: SideEffect::None,
@@ -315,6 +327,7 @@ mod tests {
             },
             runtime: RuntimeSpec::Native,
             config_schema: vec![],
+            resources: vec![],
         }
     }
 
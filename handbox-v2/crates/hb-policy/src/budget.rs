@@ -1,5 +1,6 @@
 //! Cost/time budget tracking.
 
+use crate::estimate::{CostEstimate, Range};
 use crate::PolicyError;
 use hb_core::policy::CostLimit;
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -48,3 +49,43 @@ impl BudgetTracker {
         Ok(())
     }
 }
+
+/// Check an estimate against `limit` before running. A limit the estimate's
+/// low end already exceeds is an error; one only its high end exceeds is
+/// returned as a warning.
+pub fn preflight(limit: &CostLimit, estimate: &CostEstimate) -> Result<Vec<String>, PolicyError> {
+    let mut checks: Vec<(String, Range, f64, &str)> = Vec::new();
+    if let Some(max) = limit.max_cost_usd {
+        checks.push(("cost".into(), estimate.cost_usd, max, "USD"));
+    }
+    if let Some(max) = limit.max_tokens {
+        checks.push(("token usage".into(), estimate.tokens, max as f64, "tokens"));
+    }
+    if let Some(max) = limit.max_execution_time_secs {
+        let secs = Range::new(estimate.duration_ms.min / 1000.0, estimate.duration_ms.max / 1000.0);
+        checks.push(("execution time".into(), secs, max as f64, "s"));
+    }
+    if let Some(max) = limit.max_node_time_secs {
+        for node in &estimate.nodes {
+            let secs = Range::new(node.duration_ms.min / 1000.0, node.duration_ms.max / 1000.0);
+            checks.push((format!("time of node '{}'", node.node_id), secs, max as f64, "s"));
+        }
+    }
+
+    let mut warnings = Vec::new();
+    for (what, estimated, max, unit) in checks {
+        if estimated.min > max {
+            return Err(PolicyError::BudgetExceeded(format!(
+                "estimated {what} of at least {:.2} {unit} exceeds the limit of {max} {unit}",
+                estimated.min
+            )));
+        }
+        if estimated.max > max {
+            warnings.push(format!(
+                "Estimated {what} may reach {:.2} {unit}, above the limit of {max} {unit}",
+                estimated.max
+            ));
+        }
+    }
+    Ok(warnings)
+}
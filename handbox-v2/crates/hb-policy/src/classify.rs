@@ -0,0 +1,148 @@
+//! Sensitive data classification — local pattern scan of sampled content.
+//!
+//! Matches are checked beyond the pattern where the format allows it (Luhn
+//! for card numbers, date and area ranges for RRNs and SSNs) to keep false
+//! positives down. Nothing leaves the machine.
+
+use hb_core::project::SensitiveCategory;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+type Validator = fn(&str) -> bool;
+
+fn detectors() -> &'static [(SensitiveCategory, Regex, Validator)] {
+    static DETECTORS: OnceLock<Vec<(SensitiveCategory, Regex, Validator)>> = OnceLock::new();
+    DETECTORS.get_or_init(|| {
+        let rule = |category, pattern: &str, validate: Validator| {
+            (
+                category,
+                Regex::new(pattern).expect("valid pattern"),
+                validate,
+            )
+        };
+        vec![
+            rule(
+                SensitiveCategory::Email,
+                r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}\b",
+                |_| true,
+            ),
+            rule(
+                SensitiveCategory::ResidentRegistrationNumber,
+                r"\b[0-9]{6}-[1-8][0-9]{6}\b",
+                valid_rrn,
+            ),
+            rule(
+                SensitiveCategory::SocialSecurityNumber,
+                r"\b[0-9]{3}-[0-9]{2}-[0-9]{4}\b",
+                valid_ssn,
+            ),
+            rule(
+                SensitiveCategory::CardNumber,
+                r"\b(?:[0-9]{4}[ -]?){3}[0-9]{1,4}\b|\b3[47][0-9]{2}[ -]?[0-9]{6}[ -]?[0-9]{5}\b",
+                valid_card,
+            ),
+        ]
+    })
+}
+
+/// Count matches per category in `text`. Categories without matches are
+/// left out.
+pub fn scan(text: &str) -> BTreeMap<SensitiveCategory, usize> {
+    let mut counts = BTreeMap::new();
+    for (category, pattern, validate) in detectors() {
+        let found = pattern
+            .find_iter(text)
+            .filter(|m| validate(m.as_str()))
+            .count();
+        if found > 0 {
+            counts.insert(*category, found);
+        }
+    }
+    counts
+}
+
+/// Add the matches in `text` to running totals.
+pub fn scan_into(totals: &mut BTreeMap<SensitiveCategory, usize>, text: &str) {
+    for (category, count) in scan(text) {
+        *totals.entry(category).or_default() += count;
+    }
+}
+
+/// ASCII digits only, matching the `[0-9]` in the patterns; `\d` would
+/// also match full-width and other Unicode digits.
+fn digits(s: &str) -> Vec<u32> {
+    s.bytes()
+        .filter(u8::is_ascii_digit)
+        .map(|b| u32::from(b - b'0'))
+        .collect()
+}
+
+/// YYMMDD must be a plausible date.
+fn valid_rrn(s: &str) -> bool {
+    let d = digits(s);
+    let month = d[2] * 10 + d[3];
+    let day = d[4] * 10 + d[5];
+    (1..=12).contains(&month) && (1..=31).contains(&day)
+}
+
+/// Area 000, 666 and 900–999, group 00 and serial 0000 are never issued.
+fn valid_ssn(s: &str) -> bool {
+    let d = digits(s);
+    let area = d[0] * 100 + d[1] * 10 + d[2];
+    let group = d[3] * 10 + d[4];
+    let serial = d[5..].iter().fold(0, |n, d| n * 10 + d);
+    area != 0 && area != 666 && area < 900 && group != 0 && serial != 0
+}
+
+fn valid_card(s: &str) -> bool {
+    let d = digits(s);
+    if !(13..=19).contains(&d.len()) {
+        return false;
+    }
+    let sum: u32 = d
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &n)| match (i % 2, n * 2) {
+            (0, _) => n,
+            (_, doubled) if doubled > 9 => doubled - 9,
+            (_, doubled) => doubled,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_and_validates_each_category() {
+        let text = "\
+            Contact: kim.minsu@example.co.kr, lee@example.com\n\
+            주민번호 900101-1234567, invalid 901301-1234567\n\
+            SSN 123-45-6789, never issued 666-12-3456\n\
+            Card 4111 1111 1111 1111, fails Luhn 4111 1111 1111 1112\n\
+            Order 2024-01-15 total 12,345";
+        let counts = scan(text);
+        assert_eq!(counts.get(&SensitiveCategory::Email), Some(&2));
+        assert_eq!(
+            counts.get(&SensitiveCategory::ResidentRegistrationNumber),
+            Some(&1)
+        );
+        assert_eq!(
+            counts.get(&SensitiveCategory::SocialSecurityNumber),
+            Some(&1)
+        );
+        assert_eq!(counts.get(&SensitiveCategory::CardNumber), Some(&1));
+
+        assert!(scan("nothing to see here, version 1.2.3").is_empty());
+    }
+
+    #[test]
+    fn ignores_non_ascii_digits() {
+        assert!(scan("９００１０１-１２３４５６７ １２３-４５-６７８９").is_empty());
+        assert!(scan("٩٠٠١٠١-١٢٣٤٥٦٧").is_empty());
+    }
+}
@@ -1,8 +1,13 @@
 //! Policy evaluation engine.
 
 use crate::PolicyError;
-use hb_core::policy::Policy;
+use hb_core::graph::{NodeEntry, NodeSpec, SubgraphSpec, WorkflowSpec};
+use hb_core::path;
+use hb_core::policy::{Policy, SensitiveDataPolicy};
+use hb_core::project::DataSource;
 use hb_core::tool::ToolInterface;
+use serde_json::Value;
+use std::collections::HashSet;
 
 /// Check whether a tool is allowed under the given policy.
 pub fn check_tool_allowed(policy: &Policy, tool: &ToolInterface) -> Result<(), PolicyError> {
@@ -23,3 +28,182 @@ pub fn check_tool_allowed(policy: &Policy, tool: &ToolInterface) -> Result<(), P
 
     Ok(())
 }
+
+/// Check that no tool in `spec` consumes a labelled data source its policy
+/// rules out.
+///
+/// A node reads a source when its config names the source's ID or a path
+/// within it; every node downstream of a reader consumes the source too.
+/// Container nodes (composites, loops, ...) consume through every tool
+/// they run.
+pub fn check_data_sources(
+    policy: &SensitiveDataPolicy,
+    spec: &WorkflowSpec,
+    sources: &[DataSource],
+) -> Result<(), PolicyError> {
+    if policy.rules.is_empty() {
+        return Ok(());
+    }
+    for source in sources.iter().filter(|s| !s.labels().is_empty()) {
+        let readers = spec
+            .nodes
+            .iter()
+            .filter(|node| {
+                node_configs(node)
+                    .iter()
+                    .any(|config| references(config, source))
+            })
+            .map(|node| node.id());
+        let consumers = downstream(spec, readers);
+
+        for node in spec.nodes.iter().filter(|n| consumers.contains(n.id())) {
+            for primitive in primitives(node) {
+                let tool_id = primitive
+                    .tool_ref
+                    .split('@')
+                    .next()
+                    .unwrap_or(&primitive.tool_ref);
+                if let Some(label) = policy.denied_label(source.labels(), tool_id) {
+                    return Err(PolicyError::PermissionDenied(format!(
+                        "node '{}' ({tool_id}) may not consume data source '{}', which holds {} data",
+                        node.id(),
+                        source.name,
+                        label.as_str()
+                    )));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// IDs of `from` and every node reachable from them.
+fn downstream<'a>(spec: &'a WorkflowSpec, from: impl Iterator<Item = &'a str>) -> HashSet<&'a str> {
+    let mut seen: HashSet<&str> = from.collect();
+    let mut queue: Vec<&str> = seen.iter().copied().collect();
+    while let Some(id) = queue.pop() {
+        for edge in spec.edges.iter().filter(|e| e.source_node == id) {
+            if seen.insert(&edge.target_node) {
+                queue.push(&edge.target_node);
+            }
+        }
+    }
+    seen
+}
+
+/// Primitive nodes a node runs, including those in nested subgraphs.
+fn primitives(node: &NodeEntry) -> Vec<&NodeSpec> {
+    fn subgraph(graph: &SubgraphSpec) -> impl Iterator<Item = &NodeSpec> {
+        graph.nodes.iter().flat_map(primitives)
+    }
+    match node {
+        NodeEntry::Primitive(n) => vec![n],
+        NodeEntry::Composite(n) => subgraph(&n.subgraph).collect(),
+        NodeEntry::Conditional(n) => n
+            .branches
+            .iter()
+            .map(|b| &b.body)
+            .chain(&n.default_branch)
+            .flat_map(subgraph)
+            .collect(),
+        NodeEntry::Loop(n) => subgraph(&n.body).collect(),
+        NodeEntry::MapReduce(n) => std::iter::once(&n.map)
+            .chain(&n.reduce)
+            .flat_map(subgraph)
+            .collect(),
+        // Checked only once expanded into the workflow it references
+        NodeEntry::WorkflowRef(_) => Vec::new(),
+    }
+}
+
+fn node_configs(node: &NodeEntry) -> Vec<&serde_json::Map<String, Value>> {
+    let mut configs: Vec<_> = primitives(node).into_iter().map(|n| &n.config).collect();
+    if let NodeEntry::Composite(n) = node {
+        configs.push(&n.config);
+    }
+    configs
+}
+
+fn references(config: &serde_json::Map<String, Value>, source: &DataSource) -> bool {
+    fn value_references(value: &Value, source: &DataSource) -> bool {
+        match value {
+            Value::String(s) => s == &source.id || path::within(s, &source.path_or_uri),
+            Value::Array(items) => items.iter().any(|v| value_references(v, source)),
+            Value::Object(map) => map.values().any(|v| value_references(v, source)),
+            _ => false,
+        }
+    }
+    config.values().any(|v| value_references(v, source))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hb_core::policy::SensitiveDataRule;
+    use hb_core::project::{DataClassification, DataSourceKind, SensitiveCategory};
+    use serde_json::json;
+
+    fn workflow() -> WorkflowSpec {
+        serde_json::from_value(json!({
+            "version": "0.1.0",
+            "meta": { "name": "Summarize", "description": "" },
+            "nodes": [
+                { "kind": "primitive", "id": "read", "tool_ref": "core-tools/file-read@1.0.0",
+                  "config": { "path": "/data/customers/list.csv" } },
+                { "kind": "primitive", "id": "chat", "tool_ref": "core-tools/llm-chat@1.0.0" },
+                { "kind": "primitive", "id": "other", "tool_ref": "core-tools/llm-chat@1.0.0" },
+            ],
+            "edges": [
+                { "source_node": "read", "source_port": "content", "target_node": "chat", "target_port": "prompt" },
+            ],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn labelled_sources_only_reach_allowed_tools() {
+        let mut source = DataSource {
+            id: "customers".into(),
+            name: "Customers".into(),
+            kind: DataSourceKind::LocalDirectory,
+            path_or_uri: "/data/customers/".into(),
+            metadata: Default::default(),
+            classification: None,
+        };
+        let mut policy = SensitiveDataPolicy {
+            rules: vec![SensitiveDataRule {
+                category: SensitiveCategory::Email,
+                allowed_tools: vec!["core-tools/file-read".into()],
+            }],
+        };
+        let spec = workflow();
+
+        // Unclassified sources are unrestricted
+        check_data_sources(&policy, &spec, std::slice::from_ref(&source)).unwrap();
+
+        source.classification = Some(DataClassification {
+            labels: vec![SensitiveCategory::Email],
+            matches: Default::default(),
+            sampled_bytes: 0,
+            manual: true,
+            classified_at: chrono::Utc::now(),
+        });
+        let err = check_data_sources(&policy, &spec, std::slice::from_ref(&source)).unwrap_err();
+        assert!(err.to_string().contains("'chat'"), "{err}");
+
+        policy.rules[0]
+            .allowed_tools
+            .push("core-tools/llm-chat".into());
+        check_data_sources(&policy, &spec, std::slice::from_ref(&source)).unwrap();
+    }
+
+    #[test]
+    fn paths_match_within_the_source_only() {
+        assert!(path::within("/data/customers/list.csv", "/data/customers/"));
+        assert!(path::within("/data/customers", "/data/customers"));
+        assert!(!path::within("/data/customers-old/list.csv", "/data/customers"));
+        assert!(!path::within("anything", ""));
+        assert!(path::within(r"D:\고객\명단.csv", "d:/고객"));
+    }
+}
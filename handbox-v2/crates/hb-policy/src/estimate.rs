@@ -0,0 +1,554 @@
+//! Cost estimation — what a workflow will likely cost and take before it runs.
+//!
+//! Each tool run is estimated from the tool's recorded history when there is
+//! any, and from its declared [`CostHint`] otherwise. Node runs are multiplied
+//! by how often their container runs them: loops by their iteration count,
+//! map-reduce by its shard count. Counts come from the run's variables when an
+//! items expression names one, and fall back to the node's bounds. Durations
+//! follow the runner's level-by-level scheduling: each level takes as long as
+//! its slowest node.
+//!
+//! Every figure is a min/max range. Conditional branches contribute the
+//! cheapest branch to the low end and the most expensive one to the high end.
+
+use hb_core::graph::{EdgeKind, LoopKind, NodeEntry, NodeSpec, SubgraphSpec, WorkflowSpec};
+use hb_core::tool::{CostHint, MonetaryHint, TimeHint};
+use hb_core::trace::ToolAverages;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+
+/// Items assumed for a map-reduce node whose item count is unknown.
+const UNKNOWN_ITEMS: f64 = 100.0;
+
+/// Price of a model, in USD per million tokens. `model` matches model IDs
+/// it is a prefix of.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenPrice {
+    pub model: String,
+    pub input_per_million_usd: f64,
+    pub output_per_million_usd: f64,
+}
+
+/// What is known about tools ahead of a run.
+#[derive(Debug, Clone, Default)]
+pub struct CostCatalog {
+    /// Declared hints by tool ID (without `@version`).
+    pub hints: HashMap<String, CostHint>,
+    /// Recorded averages by tool ID.
+    pub history: HashMap<String, ToolAverages>,
+    pub prices: Vec<TokenPrice>,
+}
+
+/// Low and high end of an estimate.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Range {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Range {
+    pub fn new(min: f64, max: f64) -> Self {
+        Self { min, max }
+    }
+
+    pub fn exact(value: f64) -> Self {
+        Self::new(value, value)
+    }
+
+    fn plus(self, other: Range) -> Self {
+        Self::new(self.min + other.min, self.max + other.max)
+    }
+
+    fn times(self, count: Range) -> Self {
+        Self::new(self.min * count.min, self.max * count.max)
+    }
+
+    /// Ends of the larger of two ranges, end by end.
+    fn widest(self, other: Range) -> Self {
+        Self::new(self.min.max(other.min), self.max.max(other.max))
+    }
+}
+
+/// Estimate for one tool node, nested nodes included (their IDs are joined
+/// with `/`).
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeEstimate {
+    pub node_id: String,
+    pub tool_id: String,
+    /// How often the node runs.
+    pub runs: Range,
+    /// Duration of one run.
+    pub duration_ms: Range,
+    /// Tokens and cost over all runs.
+    pub tokens: Range,
+    pub cost_usd: Range,
+    /// Whether the figures come from recorded runs rather than hints.
+    pub from_history: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CostEstimate {
+    pub cost_usd: Range,
+    pub duration_ms: Range,
+    pub tokens: Range,
+    pub nodes: Vec<NodeEstimate>,
+    /// Assumptions behind the figures, e.g. loop counts that were guessed.
+    pub notes: Vec<String>,
+}
+
+/// Totals of a graph or node.
+#[derive(Debug, Clone, Copy, Default)]
+struct Totals {
+    duration_ms: Range,
+    tokens: Range,
+    cost_usd: Range,
+}
+
+impl Totals {
+    fn times(self, count: Range) -> Self {
+        Self {
+            duration_ms: self.duration_ms.times(count),
+            tokens: self.tokens.times(count),
+            cost_usd: self.cost_usd.times(count),
+        }
+    }
+}
+
+struct Estimator<'a> {
+    catalog: &'a CostCatalog,
+    variables: &'a Map<String, Value>,
+    nodes: Vec<NodeEstimate>,
+    notes: Vec<String>,
+}
+
+/// Estimate the cost and duration of running `spec` with run parameters
+/// `variables`.
+pub fn estimate_cost(
+    spec: &WorkflowSpec,
+    variables: &Map<String, Value>,
+    catalog: &CostCatalog,
+) -> CostEstimate {
+    let mut estimator = Estimator {
+        catalog,
+        variables,
+        nodes: Vec::new(),
+        notes: Vec::new(),
+    };
+    let totals = estimator.graph(&spec.nodes, &spec.edges, "", Range::exact(1.0));
+    CostEstimate {
+        cost_usd: totals.cost_usd,
+        duration_ms: totals.duration_ms,
+        tokens: totals.tokens,
+        nodes: estimator.nodes,
+        notes: estimator.notes,
+    }
+}
+
+impl Estimator<'_> {
+    /// Totals of one pass over a graph whose passes run `runs` times in all.
+    fn graph(
+        &mut self,
+        nodes: &[NodeEntry],
+        edges: &[hb_core::graph::EdgeSpec],
+        prefix: &str,
+        runs: Range,
+    ) -> Totals {
+        let mut levels: BTreeMap<usize, Range> = BTreeMap::new();
+        let mut totals = Totals::default();
+        for node in nodes {
+            let node_totals = self.node(node, prefix, runs);
+            let level = depth(node.id(), edges, nodes.len());
+            let slowest = levels.entry(level).or_default();
+            *slowest = slowest.widest(node_totals.duration_ms);
+            totals.tokens = totals.tokens.plus(node_totals.tokens);
+            totals.cost_usd = totals.cost_usd.plus(node_totals.cost_usd);
+        }
+        totals.duration_ms = levels.into_values().fold(Range::default(), Range::plus);
+        totals
+    }
+
+    /// Totals of one run of `node`.
+    fn node(&mut self, node: &NodeEntry, prefix: &str, runs: Range) -> Totals {
+        let id = format!("{prefix}{}", node.id());
+        let inner = format!("{id}/");
+        match node {
+            NodeEntry::Primitive(n) => self.primitive(n, id, runs),
+            NodeEntry::Composite(n) => self.subgraph(&n.subgraph, &inner, runs),
+            NodeEntry::WorkflowRef(n) => {
+                self.notes.push(format!(
+                    "'{id}' runs workflow {}, which is not included",
+                    n.workflow_id
+                ));
+                Totals::default()
+            }
+            NodeEntry::Conditional(n) => {
+                let branches: Vec<Totals> = n
+                    .branches
+                    .iter()
+                    .map(|b| &b.body)
+                    .chain(&n.default_branch)
+                    .map(|graph| self.subgraph(graph, &inner, runs))
+                    .collect();
+                let mut totals = branches.iter().fold(Totals::default(), |acc, b| Totals {
+                    duration_ms: acc.duration_ms.widest(b.duration_ms),
+                    tokens: acc.tokens.widest(b.tokens),
+                    cost_usd: acc.cost_usd.widest(b.cost_usd),
+                });
+                // Without a default branch, possibly nothing runs
+                let cheapest = |f: fn(&Totals) -> f64| {
+                    let min = branches.iter().map(f).fold(f64::INFINITY, f64::min);
+                    if n.default_branch.is_none() || !min.is_finite() {
+                        0.0
+                    } else {
+                        min
+                    }
+                };
+                totals.duration_ms.min = cheapest(|t| t.duration_ms.min);
+                totals.tokens.min = cheapest(|t| t.tokens.min);
+                totals.cost_usd.min = cheapest(|t| t.cost_usd.min);
+                totals
+            }
+            NodeEntry::Loop(n) => {
+                let max = f64::from(n.max_iterations);
+                let iterations = match n.kind {
+                    LoopKind::Repeat => Range::exact(max),
+                    LoopKind::ForEach => match self.item_count(n.items_expr.as_deref()) {
+                        Some(count) => Range::exact(count.min(max)),
+                        None => {
+                            self.notes.push(format!(
+                                "'{id}' runs between 1 and {} times (item count unknown)",
+                                n.max_iterations
+                            ));
+                            Range::new(1.0, max)
+                        }
+                    },
+                    LoopKind::While => {
+                        self.notes.push(format!(
+                            "'{id}' runs between 1 and {} times (while loop)",
+                            n.max_iterations
+                        ));
+                        Range::new(1.0, max)
+                    }
+                };
+                // Iterations run one after another
+                self.subgraph(&n.body, &inner, runs.times(iterations))
+                    .times(iterations)
+            }
+            NodeEntry::MapReduce(n) => {
+                let items = match self.item_count(n.items_expr.as_deref().or(Some("items"))) {
+                    Some(count) => Range::exact(count),
+                    None => {
+                        self.notes.push(format!(
+                            "'{id}' assumed to map up to {UNKNOWN_ITEMS} items (item count unknown)"
+                        ));
+                        Range::new(1.0, UNKNOWN_ITEMS)
+                    }
+                };
+                let shard_size = f64::from(n.shard_size.max(1));
+                let shards = Range::new(
+                    (items.min / shard_size).ceil(),
+                    (items.max / shard_size).ceil(),
+                );
+                let concurrency = f64::from(n.max_concurrency.max(1));
+                let waves = Range::new(
+                    (shards.min / concurrency).ceil(),
+                    (shards.max / concurrency).ceil(),
+                );
+
+                let map = self.subgraph(&n.map, &format!("{inner}map/"), runs.times(shards));
+                let mut totals = Totals {
+                    duration_ms: map.duration_ms.times(waves),
+                    tokens: map.tokens.times(shards),
+                    cost_usd: map.cost_usd.times(shards),
+                };
+                if let Some(reduce) = &n.reduce {
+                    let reduce = self.subgraph(reduce, &format!("{inner}reduce/"), runs);
+                    totals.duration_ms = totals.duration_ms.plus(reduce.duration_ms);
+                    totals.tokens = totals.tokens.plus(reduce.tokens);
+                    totals.cost_usd = totals.cost_usd.plus(reduce.cost_usd);
+                }
+                totals
+            }
+        }
+    }
+
+    fn subgraph(&mut self, graph: &SubgraphSpec, prefix: &str, runs: Range) -> Totals {
+        self.graph(&graph.nodes, &graph.edges, prefix, runs)
+    }
+
+    /// Totals of one run of a tool node; records the node's estimate.
+    fn primitive(&mut self, node: &NodeSpec, id: String, runs: Range) -> Totals {
+        if node.disabled {
+            return Totals::default();
+        }
+        let tool_id = node.tool_ref.split('@').next().unwrap_or(&node.tool_ref);
+        let hint = self.catalog.hints.get(tool_id);
+        let history = self.catalog.history.get(tool_id).filter(|h| h.runs > 0);
+
+        let (duration_ms, input, output) = match (history, hint) {
+            (Some(h), _) => (
+                Range::new(h.min_ms as f64, h.max_ms as f64),
+                Range::exact(h.mean_input_tokens as f64),
+                Range::exact(h.mean_output_tokens as f64),
+            ),
+            (None, Some(hint)) => {
+                let spread = if hint.scales_with_input {
+                    Range::new(0.5, 2.0)
+                } else {
+                    Range::exact(1.0)
+                };
+                let (input, output) = hint
+                    .estimated_tokens
+                    .map(|t| (t.input as f64, t.output as f64))
+                    .unwrap_or_default();
+                (
+                    time_range(&hint.time),
+                    Range::exact(input).times(spread),
+                    Range::exact(output).times(spread),
+                )
+            }
+            (None, None) => {
+                self.notes
+                    .push(format!("No cost information for '{id}' ({tool_id})"));
+                (Range::default(), Range::default(), Range::default())
+            }
+        };
+        let tokens = input.plus(output);
+        let model = node.config.get("model").and_then(Value::as_str);
+        let cost_usd = match self.token_cost(model, input, output) {
+            Some(cost) if tokens.max > 0.0 => cost,
+            _ => hint
+                .map(|h| monetary_range(&h.monetary))
+                .unwrap_or_default(),
+        };
+
+        self.nodes.push(NodeEstimate {
+            node_id: id,
+            tool_id: tool_id.to_string(),
+            runs,
+            duration_ms,
+            tokens: tokens.times(runs),
+            cost_usd: cost_usd.times(runs),
+            from_history: history.is_some(),
+        });
+        Totals {
+            duration_ms,
+            tokens,
+            cost_usd,
+        }
+    }
+
+    /// Cost of the tokens at the price of `model`, or between the cheapest
+    /// and dearest price when the model is unknown. `None` without prices.
+    fn token_cost(&self, model: Option<&str>, input: Range, output: Range) -> Option<Range> {
+        let cost = |price: &TokenPrice, input: f64, output: f64| {
+            (input * price.input_per_million_usd + output * price.output_per_million_usd)
+                / 1_000_000.0
+        };
+        let matched = model.and_then(|model| {
+            self.catalog
+                .prices
+                .iter()
+                .filter(|p| !p.model.is_empty() && model.starts_with(&p.model))
+                .max_by_key(|p| p.model.len())
+        });
+        if let Some(price) = matched {
+            return Some(Range::new(
+                cost(price, input.min, output.min),
+                cost(price, input.max, output.max),
+            ));
+        }
+        let lows = self
+            .catalog
+            .prices
+            .iter()
+            .map(|p| cost(p, input.min, output.min));
+        let highs = self
+            .catalog
+            .prices
+            .iter()
+            .map(|p| cost(p, input.max, output.max));
+        Some(Range::new(lows.reduce(f64::min)?, highs.reduce(f64::max)?))
+    }
+
+    /// Length of the array variable an items expression starts with.
+    fn item_count(&self, items_expr: Option<&str>) -> Option<f64> {
+        let expr = items_expr?.trim_start_matches("$.");
+        let name = expr.split(['.', '[']).next()?;
+        match self.variables.get(name)? {
+            Value::Array(items) => Some(items.len() as f64),
+            _ => None,
+        }
+    }
+}
+
+/// Level of `node_id` under level-by-level scheduling: the longest chain of
+/// data edges leading to it.
+fn depth(node_id: &str, edges: &[hb_core::graph::EdgeSpec], limit: usize) -> usize {
+    if limit == 0 {
+        return 0;
+    }
+    edges
+        .iter()
+        .filter(|e| e.target_node == node_id && e.kind == EdgeKind::Data)
+        .map(|e| depth(&e.source_node, edges, limit - 1) + 1)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Duration of one run for each time hint, in milliseconds.
+fn time_range(hint: &TimeHint) -> Range {
+    match hint {
+        TimeHint::Instant => Range::new(0.0, 100.0),
+        TimeHint::Fast => Range::new(100.0, 1_000.0),
+        TimeHint::Medium => Range::new(1_000.0, 10_000.0),
+        TimeHint::Slow => Range::new(10_000.0, 60_000.0),
+        TimeHint::VerySlow => Range::new(60_000.0, 600_000.0),
+    }
+}
+
+/// Cost of one run for each monetary hint, in USD.
+fn monetary_range(hint: &MonetaryHint) -> Range {
+    match hint {
+        MonetaryHint::Free => Range::default(),
+        MonetaryHint::Cheap => Range::new(0.0, 0.01),
+        MonetaryHint::Moderate => Range::new(0.01, 0.1),
+        MonetaryHint::Expensive => Range::new(0.1, 1.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hb_core::graph::{EdgeSpec, LoopAggregation, LoopSpec};
+    use hb_core::tool::TokenEstimate;
+    use serde_json::json;
+
+    fn node(id: &str, tool: &str, config: Value) -> NodeEntry {
+        NodeEntry::Primitive(NodeSpec {
+            id: id.into(),
+            tool_ref: format!("{tool}@1.0.0"),
+            config: config.as_object().cloned().unwrap_or_default(),
+            ..Default::default()
+        })
+    }
+
+    fn edge(source: &str, target: &str) -> EdgeSpec {
+        EdgeSpec {
+            id: format!("{source}-{target}"),
+            source_node: source.into(),
+            source_port: "out".into(),
+            target_node: target.into(),
+            target_port: "in".into(),
+            ..Default::default()
+        }
+    }
+
+    fn catalog() -> CostCatalog {
+        let hint = |time, monetary, tokens: Option<(u64, u64)>| CostHint {
+            time,
+            monetary,
+            scales_with_input: false,
+            estimated_tokens: tokens.map(|(input, output)| TokenEstimate { input, output }),
+        };
+        CostCatalog {
+            hints: HashMap::from([
+                (
+                    "file-read".into(),
+                    hint(TimeHint::Fast, MonetaryHint::Free, None),
+                ),
+                (
+                    "llm-chat".into(),
+                    hint(TimeHint::Slow, MonetaryHint::Moderate, Some((1_000, 500))),
+                ),
+            ]),
+            history: HashMap::from([(
+                "file-read".into(),
+                ToolAverages {
+                    tool_id: "file-read".into(),
+                    runs: 4,
+                    mean_ms: 30,
+                    min_ms: 10,
+                    max_ms: 50,
+                    ..Default::default()
+                },
+            )]),
+            prices: vec![
+                TokenPrice {
+                    model: "cheap".into(),
+                    input_per_million_usd: 1.0,
+                    output_per_million_usd: 2.0,
+                },
+                TokenPrice {
+                    model: "dear".into(),
+                    input_per_million_usd: 10.0,
+                    output_per_million_usd: 20.0,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn loops_multiply_body_runs_by_the_item_count() {
+        let spec = WorkflowSpec {
+            nodes: vec![
+                node("read", "file-read", json!({})),
+                NodeEntry::Loop(LoopSpec {
+                    id: "each".into(),
+                    kind: LoopKind::ForEach,
+                    body: SubgraphSpec {
+                        nodes: vec![node("ask", "llm-chat", json!({ "model": "cheap-v2" }))],
+                        edges: vec![],
+                    },
+                    max_iterations: 50,
+                    condition_expr: None,
+                    items_expr: Some("documents[*]".into()),
+                    collect_expr: None,
+                    aggregation: LoopAggregation::default(),
+                }),
+            ],
+            edges: vec![edge("read", "each")],
+            ..Default::default()
+        };
+        let variables = json!({ "documents": ["a", "b", "c"] });
+        let estimate = estimate_cost(&spec, variables.as_object().unwrap(), &catalog());
+
+        // 3 × (1000 input + 500 output) tokens at the cheap price
+        assert_eq!(estimate.tokens, Range::exact(4_500.0));
+        let cost = 3.0 * (1_000.0 * 1.0 + 500.0 * 2.0) / 1_000_000.0;
+        assert!(
+            (estimate.cost_usd.min - cost).abs() < 1e-12
+                && estimate.cost_usd.max == estimate.cost_usd.min
+        );
+        // read (history), then three sequential slow calls
+        assert_eq!(
+            estimate.duration_ms,
+            Range::new(10.0 + 30_000.0, 50.0 + 180_000.0)
+        );
+        let ask = estimate
+            .nodes
+            .iter()
+            .find(|n| n.node_id == "each/ask")
+            .unwrap();
+        assert_eq!(ask.runs, Range::exact(3.0));
+        assert!(estimate.nodes[0].from_history && !ask.from_history);
+        assert!(estimate.notes.is_empty());
+
+        // Unknown items: between one iteration and the loop's bound
+        let estimate = estimate_cost(&spec, &Map::new(), &catalog());
+        assert_eq!(estimate.tokens, Range::new(1_500.0, 75_000.0));
+        assert!((estimate.cost_usd.max / estimate.cost_usd.min - 50.0).abs() < 1e-9);
+        assert_eq!(estimate.notes.len(), 1);
+
+        // Unknown model: between the cheapest and dearest price
+        let mut unpriced = spec.clone();
+        if let NodeEntry::Loop(l) = &mut unpriced.nodes[1] {
+            if let NodeEntry::Primitive(n) = &mut l.body.nodes[0] {
+                n.config.clear();
+            }
+        }
+        let estimate = estimate_cost(&unpriced, variables.as_object().unwrap(), &catalog());
+        assert!((estimate.cost_usd.max / estimate.cost_usd.min - 10.0).abs() < 1e-9);
+    }
+}
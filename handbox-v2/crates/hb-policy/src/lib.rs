@@ -1,7 +1,9 @@
 //! hb-policy: Policy engine — tool whitelists, cost budgets, permission checks.
 
 pub mod budget;
+pub mod classify;
 pub mod engine;
+pub mod estimate;
 pub mod whitelist;
 
 use thiserror::Error;
@@ -15,3 +17,14 @@ pub enum PolicyError {
     #[error("tool not allowed: {0}")]
     ToolNotAllowed(String),
 }
+
+impl From<PolicyError> for hb_core::error::AppError {
+    fn from(e: PolicyError) -> Self {
+        use hb_core::error::{AppError, ErrorCode};
+        let code = match e {
+            PolicyError::PermissionDenied(_) => ErrorCode::PermissionDenied,
+            PolicyError::BudgetExceeded(_) | PolicyError::ToolNotAllowed(_) => ErrorCode::PolicyViolation,
+        };
+        AppError::new(code, e.to_string()).with_hint("Review the workspace policy in project settings.")
+    }
+}
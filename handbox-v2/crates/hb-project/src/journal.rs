@@ -0,0 +1,312 @@
+//! Edit-command journal — granular workflow edits with undo/redo.
+//!
+//! Each [`EditOp`] applied to a workflow is stored together with its inverse,
+//! so undo is just applying the inverse. The ordered journal is also the
+//! change feed for versioning and collaboration.
+
+use crate::ProjectError;
+use chrono::{DateTime, Utc};
+use hb_core::graph::{EdgeSpec, NodeEntry, Position, WorkflowSpec};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A single granular edit to a workflow graph.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum EditOp {
+    /// Add a node at `index` in the node list, or at the end.
+    AddNode {
+        node: NodeEntry,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        index: Option<usize>,
+    },
+    /// Remove a node together with every edge attached to it.
+    RemoveNode {
+        node_id: String,
+    },
+    MoveNode {
+        node_id: String,
+        position: Option<Position>,
+    },
+    Connect {
+        edge: EdgeSpec,
+    },
+    Disconnect {
+        edge_id: String,
+    },
+    /// Set (or with `value = None`, clear) a config field of a primitive node,
+    /// or an exposed config field of a composite.
+    SetConfig {
+        node_id: String,
+        key: String,
+        value: Option<serde_json::Value>,
+    },
+    SetLabel {
+        node_id: String,
+        label: Option<String>,
+    },
+    /// Several edits applied and undone as one step.
+    Batch {
+        ops: Vec<EditOp>,
+    },
+}
+
+fn node_mut<'a>(spec: &'a mut WorkflowSpec, node_id: &str) -> Result<&'a mut NodeEntry, ProjectError> {
+    spec.nodes
+        .iter_mut()
+        .find(|n| n.id() == node_id)
+        .ok_or_else(|| ProjectError::Validation(format!("node not found: {node_id}")))
+}
+
+impl EditOp {
+    /// Apply this edit to `spec`, returning the edit that reverts it.
+    /// On error `spec` is left unchanged.
+    pub fn apply(&self, spec: &mut WorkflowSpec) -> Result<EditOp, ProjectError> {
+        match self {
+            Self::AddNode { node, index } => {
+                if spec.nodes.iter().any(|n| n.id() == node.id()) {
+                    return Err(ProjectError::Validation(format!(
+                        "node already exists: {}",
+                        node.id()
+                    )));
+                }
+                let index = index.map_or(spec.nodes.len(), |i| i.min(spec.nodes.len()));
+                spec.nodes.insert(index, node.clone());
+                Ok(Self::RemoveNode {
+                    node_id: node.id().to_string(),
+                })
+            }
+            Self::RemoveNode { node_id } => {
+                let idx = spec
+                    .nodes
+                    .iter()
+                    .position(|n| n.id() == node_id)
+                    .ok_or_else(|| ProjectError::Validation(format!("node not found: {node_id}")))?;
+                let node = spec.nodes.remove(idx);
+                let (removed, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut spec.edges)
+                    .into_iter()
+                    .partition(|e| e.source_node == *node_id || e.target_node == *node_id);
+                spec.edges = kept;
+
+                let mut restore = vec![Self::AddNode { node, index: Some(idx) }];
+                restore.extend(removed.into_iter().map(|edge| Self::Connect { edge }));
+                Ok(Self::Batch { ops: restore })
+            }
+            Self::MoveNode { node_id, position } => {
+                let slot = match node_mut(spec, node_id)? {
+                    NodeEntry::Primitive(n) => &mut n.position,
+                    NodeEntry::Composite(n) => &mut n.position,
+                    NodeEntry::WorkflowRef(n) => &mut n.position,
+                    NodeEntry::Conditional(_) | NodeEntry::Loop(_) | NodeEntry::MapReduce(_) => {
+                        return Err(ProjectError::Validation(format!(
+                            "node has no position: {node_id}"
+                        )))
+                    }
+                };
+                let previous = std::mem::replace(slot, *position);
+                Ok(Self::MoveNode {
+                    node_id: node_id.clone(),
+                    position: previous,
+                })
+            }
+            Self::Connect { edge } => {
+                for endpoint in [&edge.source_node, &edge.target_node] {
+                    if !spec.nodes.iter().any(|n| n.id() == endpoint) {
+                        return Err(ProjectError::Validation(format!(
+                            "edge endpoint not found: {endpoint}"
+                        )));
+                    }
+                }
+                if spec.edges.iter().any(|e| e.id == edge.id) {
+                    return Err(ProjectError::Validation(format!(
+                        "edge already exists: {}",
+                        edge.id
+                    )));
+                }
+                spec.edges.push(edge.clone());
+                Ok(Self::Disconnect {
+                    edge_id: edge.id.clone(),
+                })
+            }
+            Self::Disconnect { edge_id } => {
+                let idx = spec
+                    .edges
+                    .iter()
+                    .position(|e| e.id == *edge_id)
+                    .ok_or_else(|| ProjectError::Validation(format!("edge not found: {edge_id}")))?;
+                Ok(Self::Connect {
+                    edge: spec.edges.remove(idx),
+                })
+            }
+            Self::SetConfig {
+                node_id,
+                key,
+                value,
+            } => {
+                let config = match node_mut(spec, node_id)? {
+                    NodeEntry::Primitive(n) => &mut n.config,
+                    NodeEntry::Composite(n) => &mut n.config,
+                    NodeEntry::Conditional(_)
+                    | NodeEntry::Loop(_)
+                    | NodeEntry::MapReduce(_)
+                    | NodeEntry::WorkflowRef(_) => {
+                        return Err(ProjectError::Validation(format!(
+                            "node has no config: {node_id}"
+                        )))
+                    }
+                };
+                let previous = match value {
+                    Some(v) => config.insert(key.clone(), v.clone()),
+                    None => config.remove(key),
+                };
+                Ok(Self::SetConfig {
+                    node_id: node_id.clone(),
+                    key: key.clone(),
+                    value: previous,
+                })
+            }
+            Self::SetLabel { node_id, label } => {
+                let slot = match node_mut(spec, node_id)? {
+                    NodeEntry::Primitive(n) => &mut n.label,
+                    NodeEntry::Composite(n) => &mut n.label,
+                    NodeEntry::WorkflowRef(n) => &mut n.label,
+                    NodeEntry::Conditional(_) | NodeEntry::Loop(_) | NodeEntry::MapReduce(_) => {
+                        return Err(ProjectError::Validation(format!(
+                            "node has no label: {node_id}"
+                        )))
+                    }
+                };
+                let previous = std::mem::replace(slot, label.clone());
+                Ok(Self::SetLabel {
+                    node_id: node_id.clone(),
+                    label: previous,
+                })
+            }
+            Self::Batch { ops } => {
+                // Apply to a scratch copy so a failing op leaves `spec` untouched.
+                let mut scratch = spec.clone();
+                let mut inverses = Vec::with_capacity(ops.len());
+                for op in ops {
+                    inverses.push(op.apply(&mut scratch)?);
+                }
+                *spec = scratch;
+                inverses.reverse();
+                Ok(Self::Batch { ops: inverses })
+            }
+        }
+    }
+
+    /// IDs of the nodes this edit touches (edge edits report their endpoints
+    /// when known).
+    pub fn affected_nodes(&self) -> Vec<String> {
+        match self {
+            Self::AddNode { node, .. } => vec![node.id().to_string()],
+            Self::RemoveNode { node_id }
+            | Self::MoveNode { node_id, .. }
+            | Self::SetConfig { node_id, .. }
+            | Self::SetLabel { node_id, .. } => vec![node_id.clone()],
+            Self::Connect { edge } => vec![edge.source_node.clone(), edge.target_node.clone()],
+            Self::Disconnect { .. } => vec![],
+            Self::Batch { ops } => {
+                let mut ids: Vec<String> = ops.iter().flat_map(Self::affected_nodes).collect();
+                ids.sort();
+                ids.dedup();
+                ids
+            }
+        }
+    }
+}
+
+/// One journaled edit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub workflow_id: Uuid,
+    /// Monotonic per-workflow sequence number.
+    pub seq: i64,
+    pub op: EditOp,
+    pub inverse: EditOp,
+    #[serde(default)]
+    pub actor: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// True while the edit is undone (and available for redo).
+    pub undone: bool,
+}
+
+impl JournalEntry {
+    /// Nodes touched by this edit, from both the edit and its inverse (the
+    /// inverse names the endpoints of removed edges).
+    pub fn affected_nodes(&self) -> Vec<String> {
+        let mut ids = self.op.affected_nodes();
+        ids.extend(self.inverse.affected_nodes());
+        ids.sort();
+        ids.dedup();
+        ids
+    }
+}
+
+/// Whether undo/redo is currently possible for a workflow.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct UndoState {
+    pub can_undo: bool,
+    pub can_redo: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hb_core::graph::NodeSpec;
+
+    fn node(id: &str) -> NodeEntry {
+        NodeEntry::Primitive(NodeSpec {
+            id: id.into(),
+            tool_ref: "core-tools/echo@1.0.0".into(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn remove_node_inverse_restores_edges() {
+        let mut spec = WorkflowSpec::default();
+        for id in ["a", "b", "c"] {
+            EditOp::AddNode { node: node(id), index: None }.apply(&mut spec).unwrap();
+        }
+        EditOp::Connect {
+            edge: EdgeSpec {
+                id: "e1".into(),
+                source_node: "a".into(),
+                source_port: "out".into(),
+                target_node: "b".into(),
+                target_port: "in".into(),
+                ..Default::default()
+            },
+        }
+        .apply(&mut spec)
+        .unwrap();
+
+        let inverse = EditOp::RemoveNode { node_id: "b".into() }.apply(&mut spec).unwrap();
+        assert_eq!(spec.nodes.len(), 2);
+        assert!(spec.edges.is_empty());
+
+        inverse.apply(&mut spec).unwrap();
+        let ids: Vec<_> = spec.nodes.iter().map(NodeEntry::id).collect();
+        assert_eq!(ids, ["a", "b", "c"]);
+        assert_eq!(spec.edges[0].id, "e1");
+    }
+
+    #[test]
+    fn failing_batch_leaves_spec_unchanged() {
+        let mut spec = WorkflowSpec::default();
+        let batch = EditOp::Batch {
+            ops: vec![
+                EditOp::AddNode { node: node("a"), index: None },
+                EditOp::SetConfig {
+                    node_id: "missing".into(),
+                    key: "k".into(),
+                    value: Some(1.into()),
+                },
+            ],
+        };
+        assert!(batch.apply(&mut spec).is_err());
+        assert!(spec.nodes.is_empty());
+    }
+}
@@ -1,7 +1,16 @@
-//! hb-project: Project/Workspace management with SQLite persistence.
+//! hb-project: Project/Workspace management behind a pluggable store
+//! (SQLite by default).
 
-use hb_core::project::WorkspaceConfig;
+pub mod journal;
+pub mod review;
+pub mod thumbnail;
+
+use hb_core::graph::WorkflowSpec;
+use hb_core::project::{ActivityEntry, ActivityKind, ActivityPage, EnvVar, WorkspaceConfig};
+use journal::{EditOp, JournalEntry, UndoState};
+use review::WorkflowReview;
 use rusqlite::Connection;
+use std::collections::BTreeMap;
 use std::path::Path;
 use std::sync::Mutex;
 use thiserror::Error;
@@ -15,9 +24,117 @@ pub enum ProjectError {
     NotFound(String),
     #[error("validation error: {0}")]
     Validation(String),
+    #[error("permission denied: {0}")]
+    PermissionDenied(String),
+}
+
+impl From<ProjectError> for hb_core::error::AppError {
+    fn from(e: ProjectError) -> Self {
+        use hb_core::error::{AppError, ErrorCode};
+        let code = match e {
+            ProjectError::Database(_) => ErrorCode::Database,
+            ProjectError::NotFound(_) => ErrorCode::NotFound,
+            ProjectError::Validation(_) => ErrorCode::InvalidInput,
+            ProjectError::PermissionDenied(_) => ErrorCode::PermissionDenied,
+        };
+        AppError::new(code, e.to_string())
+    }
+}
+
+/// Persistence backend for workspaces, activity, environment variables,
+/// workflow reviews and the edit journal. Thumbnails are a rendering cache
+/// kept apart, in [`thumbnail::ThumbnailCache`].
+///
+/// [`ProjectManager`] (SQLite) is the default; the app holds a
+/// `Box<dyn ProjectStore>` so other backends can be swapped in.
+pub trait ProjectStore: Send + Sync {
+    /// Whether the store persists anything (rather than the placeholder
+    /// used when opening a database failed).
+    fn is_persistent(&self) -> bool;
+
+    /// Release the underlying connection. Further calls behave like an
+    /// unopened store.
+    fn close(&mut self) -> Result<(), ProjectError>;
+
+    /// Store a new workspace under `config.id`, which is returned.
+    fn create_workspace(&self, config: &WorkspaceConfig) -> Result<Uuid, ProjectError>;
+
+    fn get_workspace(&self, id: Uuid) -> Result<WorkspaceConfig, ProjectError>;
+
+    fn list_workspaces(&self) -> Result<Vec<WorkspaceConfig>, ProjectError>;
+
+    fn delete_workspace(&self, id: Uuid) -> Result<(), ProjectError>;
+
+    /// Update a workspace's stored config.
+    fn update_workspace(&self, id: Uuid, config: &WorkspaceConfig) -> Result<(), ProjectError>;
+
+    /// Append an entry to the activity log. No-op without a database.
+    fn record_activity(&self, entry: &ActivityEntry) -> Result<(), ProjectError>;
+
+    /// Query the activity log, newest first. `workspace_id = None` returns
+    /// activity across all workspaces; `kinds` filters when non-empty.
+    fn query_activity(
+        &self,
+        workspace_id: Option<Uuid>,
+        kinds: &[ActivityKind],
+        offset: usize,
+        limit: usize,
+    ) -> Result<ActivityPage, ProjectError>;
+
+    /// Environment variables of a workspace, sorted by name.
+    fn list_env(&self, workspace_id: Uuid) -> Result<Vec<EnvVar>, ProjectError>;
+
+    /// Name → value map handed to runtimes and `{{env:NAME}}` references.
+    fn env_map(&self, workspace_id: Uuid) -> Result<BTreeMap<String, String>, ProjectError> {
+        Ok(self
+            .list_env(workspace_id)?
+            .into_iter()
+            .map(|var| (var.name, var.value))
+            .collect())
+    }
+
+    /// Create or replace a variable.
+    fn set_env(&self, workspace_id: Uuid, var: &EnvVar) -> Result<(), ProjectError>;
+
+    /// Remove a variable. Returns whether it existed.
+    fn delete_env(&self, workspace_id: Uuid, name: &str) -> Result<bool, ProjectError>;
+
+    /// Review status of a workflow; `None` if it was never submitted.
+    fn get_review(&self, workflow_id: Uuid) -> Result<Option<WorkflowReview>, ProjectError>;
+
+    fn save_review(&self, review: &WorkflowReview) -> Result<(), ProjectError>;
+
+    /// Apply an edit to `spec` and append it to the workflow's journal.
+    /// Any undone edits are discarded, as with a linear undo stack.
+    fn apply_edit(
+        &self,
+        spec: &mut WorkflowSpec,
+        op: EditOp,
+        actor: Option<String>,
+    ) -> Result<JournalEntry, ProjectError>;
+
+    /// Revert the most recent edit that has not been undone.
+    fn undo_edit(&self, spec: &mut WorkflowSpec) -> Result<Option<JournalEntry>, ProjectError>;
+
+    /// Re-apply the earliest undone edit.
+    fn redo_edit(&self, spec: &mut WorkflowSpec) -> Result<Option<JournalEntry>, ProjectError>;
+
+    fn undo_state(&self, workflow_id: Uuid) -> Result<UndoState, ProjectError>;
+
+    /// Journal entries after `after_seq`, oldest first — the change feed for
+    /// versioning and collaboration.
+    fn journal_since(
+        &self,
+        workflow_id: Uuid,
+        after_seq: i64,
+    ) -> Result<Vec<JournalEntry>, ProjectError>;
+
+    /// Drop a workflow's journal, as when the whole graph is replaced and
+    /// its edits no longer apply.
+    fn clear_journal(&self, workflow_id: Uuid) -> Result<(), ProjectError>;
 }
 
-/// Project manager — CRUD for workspaces, backed by SQLite.
+/// Project manager — the SQLite [`ProjectStore`].
 pub struct ProjectManager {
     conn: Option<Mutex<Connection>>,
 }
@@ -37,6 +154,18 @@ impl ProjectManager {
     pub fn open(path: &Path) -> Result<Self, ProjectError> {
         let conn =
             Connection::open(path).map_err(|e| ProjectError::Database(e.to_string()))?;
+        Self::with_connection(conn)
+    }
+
+    /// Create a database-backed manager that lives in memory (useful for
+    /// testing).
+    pub fn in_memory() -> Result<Self, ProjectError> {
+        let conn =
+            Connection::open_in_memory().map_err(|e| ProjectError::Database(e.to_string()))?;
+        Self::with_connection(conn)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self, ProjectError> {
         conn.execute_batch(
             "CREATE TABLE IF NOT EXISTS projects (
                 id TEXT PRIMARY KEY,
@@ -45,17 +174,129 @@ impl ProjectManager {
                 config_json TEXT NOT NULL,
                 created_at TEXT NOT NULL,
                 updated_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS activity (
+                id TEXT PRIMARY KEY,
+                workspace_id TEXT,
+                kind TEXT NOT NULL,
+                subject_id TEXT NOT NULL,
+                summary TEXT NOT NULL,
+                actor TEXT,
+                role TEXT,
+                details_json TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_activity_workspace
+                ON activity(workspace_id, created_at);
+
+            CREATE TABLE IF NOT EXISTS workflow_reviews (
+                workflow_id TEXT PRIMARY KEY,
+                review_json TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS edit_journal (
+                workflow_id TEXT NOT NULL,
+                seq INTEGER NOT NULL,
+                op_json TEXT NOT NULL,
+                inverse_json TEXT NOT NULL,
+                actor TEXT,
+                created_at TEXT NOT NULL,
+                undone INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (workflow_id, seq)
+            );
+
+            CREATE TABLE IF NOT EXISTS workspace_env (
+                workspace_id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                value TEXT NOT NULL,
+                description TEXT,
+                updated_at TEXT NOT NULL,
+                PRIMARY KEY (workspace_id, name)
             );",
         )
         .map_err(|e| ProjectError::Database(e.to_string()))?;
 
+        // Databases created before workspace roles lack the activity role column
+        let has_role: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('activity') WHERE name = 'role'",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(|e| ProjectError::Database(e.to_string()))?;
+        if !has_role {
+            conn.execute("ALTER TABLE activity ADD COLUMN role TEXT", [])
+                .map_err(|e| ProjectError::Database(e.to_string()))?;
+        }
+
         Ok(Self {
             conn: Some(Mutex::new(conn)),
         })
     }
 
-    pub fn create_workspace(&self, config: &WorkspaceConfig) -> Result<Uuid, ProjectError> {
-        let id = Uuid::new_v4();
+    fn step_edit(
+        &self,
+        spec: &mut WorkflowSpec,
+        undo: bool,
+    ) -> Result<Option<JournalEntry>, ProjectError> {
+        let conn = match &self.conn {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+        let conn = conn.lock().map_err(|e| ProjectError::Database(e.to_string()))?;
+
+        let sql = if undo {
+            "SELECT seq, op_json, inverse_json, actor, created_at, undone FROM edit_journal
+             WHERE workflow_id = ?1 AND seq > ?2 AND undone = 0 ORDER BY seq DESC LIMIT 1"
+        } else {
+            "SELECT seq, op_json, inverse_json, actor, created_at, undone FROM edit_journal
+             WHERE workflow_id = ?1 AND seq > ?2 AND undone = 1 ORDER BY seq ASC LIMIT 1"
+        };
+        let mut entries = query_journal(&conn, sql, spec.id, 0)?;
+        let Some(mut entry) = entries.pop() else {
+            return Ok(None);
+        };
+
+        let mut edited = spec.clone();
+        if undo {
+            entry.inverse.apply(&mut edited)?;
+        } else {
+            entry.op.apply(&mut edited)?;
+        }
+        conn.execute(
+            "UPDATE edit_journal SET undone = ?3 WHERE workflow_id = ?1 AND seq = ?2",
+            rusqlite::params![spec.id.to_string(), entry.seq, undo],
+        )
+        .map_err(|e| ProjectError::Database(e.to_string()))?;
+
+        entry.undone = undo;
+        edited.meta.updated_at = chrono::Utc::now();
+        *spec = edited;
+        Ok(Some(entry))
+    }
+}
+
+impl ProjectStore for ProjectManager {
+    fn is_persistent(&self) -> bool {
+        self.conn.is_some()
+    }
+
+    fn close(&mut self) -> Result<(), ProjectError> {
+        if let Some(conn) = self.conn.take() {
+            let conn = conn
+                .into_inner()
+                .map_err(|e| ProjectError::Database(e.to_string()))?;
+            conn.close()
+                .map_err(|(_, e)| ProjectError::Database(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    fn create_workspace(&self, config: &WorkspaceConfig) -> Result<Uuid, ProjectError> {
+        let id = config.id;
         let now = chrono::Utc::now().to_rfc3339();
 
         if let Some(conn) = &self.conn {
@@ -80,7 +321,7 @@ impl ProjectManager {
         Ok(id)
     }
 
-    pub fn get_workspace(&self, id: Uuid) -> Result<WorkspaceConfig, ProjectError> {
+    fn get_workspace(&self, id: Uuid) -> Result<WorkspaceConfig, ProjectError> {
         let conn = self
             .conn
             .as_ref()
@@ -98,7 +339,7 @@ impl ProjectManager {
         serde_json::from_str(&config_json).map_err(|e| ProjectError::Database(e.to_string()))
     }
 
-    pub fn list_workspaces(&self) -> Result<Vec<WorkspaceConfig>, ProjectError> {
+    fn list_workspaces(&self) -> Result<Vec<WorkspaceConfig>, ProjectError> {
         let conn = match &self.conn {
             Some(c) => c,
             None => return Ok(vec![]),
@@ -122,7 +363,7 @@ impl ProjectManager {
         Ok(configs)
     }
 
-    pub fn delete_workspace(&self, id: Uuid) -> Result<(), ProjectError> {
+    fn delete_workspace(&self, id: Uuid) -> Result<(), ProjectError> {
         let conn = self
             .conn
             .as_ref()
@@ -134,7 +375,741 @@ impl ProjectManager {
             rusqlite::params![id.to_string()],
         )
         .map_err(|e| ProjectError::Database(e.to_string()))?;
+        conn.execute(
+            "DELETE FROM workspace_env WHERE workspace_id = ?1",
+            rusqlite::params![id.to_string()],
+        )
+        .map_err(|e| ProjectError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    fn update_workspace(&self, id: Uuid, config: &WorkspaceConfig) -> Result<(), ProjectError> {
+        let conn = self
+            .conn
+            .as_ref()
+            .ok_or_else(|| ProjectError::Database("No database".into()))?;
+        let conn = conn.lock().map_err(|e| ProjectError::Database(e.to_string()))?;
+        let config_json =
+            serde_json::to_string(config).map_err(|e| ProjectError::Database(e.to_string()))?;
+
+        let updated = conn
+            .execute(
+                "UPDATE projects SET name = ?2, root_path = ?3, config_json = ?4, updated_at = ?5
+                 WHERE id = ?1",
+                rusqlite::params![
+                    id.to_string(),
+                    config.name,
+                    config.root_path,
+                    config_json,
+                    chrono::Utc::now().to_rfc3339(),
+                ],
+            )
+            .map_err(|e| ProjectError::Database(e.to_string()))?;
+        if updated == 0 {
+            return Err(ProjectError::NotFound(id.to_string()));
+        }
+        Ok(())
+    }
+
+    fn record_activity(&self, entry: &ActivityEntry) -> Result<(), ProjectError> {
+        let conn = match &self.conn {
+            Some(c) => c,
+            None => return Ok(()),
+        };
+        let conn = conn.lock().map_err(|e| ProjectError::Database(e.to_string()))?;
 
+        conn.execute(
+            "INSERT INTO activity (id, workspace_id, kind, subject_id, summary, actor, role, details_json, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            rusqlite::params![
+                entry.id.to_string(),
+                entry.workspace_id.map(|id| id.to_string()),
+                serde_json::to_string(&entry.kind).unwrap_or_default(),
+                entry.subject_id,
+                entry.summary,
+                entry.actor,
+                entry.role.map(|r| r.as_str()),
+                entry.details.to_string(),
+                entry.created_at.to_rfc3339(),
+            ],
+        )
+        .map_err(|e| ProjectError::Database(e.to_string()))?;
         Ok(())
     }
+
+    fn query_activity(
+        &self,
+        workspace_id: Option<Uuid>,
+        kinds: &[ActivityKind],
+        offset: usize,
+        limit: usize,
+    ) -> Result<ActivityPage, ProjectError> {
+        let empty = ActivityPage {
+            entries: vec![],
+            offset,
+            limit,
+            has_more: false,
+        };
+        let conn = match &self.conn {
+            Some(c) => c,
+            None => return Ok(empty),
+        };
+        let conn = conn.lock().map_err(|e| ProjectError::Database(e.to_string()))?;
+
+        let mut sql = String::from(
+            "SELECT id, workspace_id, kind, subject_id, summary, actor, role, details_json, created_at
+             FROM activity WHERE (?1 IS NULL OR workspace_id = ?1)",
+        );
+        let kind_values: Vec<String> = kinds
+            .iter()
+            .map(|k| serde_json::to_string(k).unwrap_or_default())
+            .collect();
+        if !kind_values.is_empty() {
+            let placeholders: Vec<String> =
+                (0..kind_values.len()).map(|i| format!("?{}", i + 4)).collect();
+            sql.push_str(&format!(" AND kind IN ({})", placeholders.join(", ")));
+        }
+        // Fetch one extra row to know whether another page exists.
+        sql.push_str(" ORDER BY created_at DESC, id DESC LIMIT ?2 OFFSET ?3");
+
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![
+            Box::new(workspace_id.map(|id| id.to_string())),
+            Box::new(limit as i64 + 1),
+            Box::new(offset as i64),
+        ];
+        params.extend(kind_values.into_iter().map(|k| Box::new(k) as Box<dyn rusqlite::ToSql>));
+
+        let mut stmt = conn
+            .prepare(&sql)
+            .map_err(|e| ProjectError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(params.iter()), |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, Option<String>>(5)?,
+                    row.get::<_, Option<String>>(6)?,
+                    row.get::<_, String>(7)?,
+                    row.get::<_, String>(8)?,
+                ))
+            })
+            .map_err(|e| ProjectError::Database(e.to_string()))?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            let (id, ws, kind, subject_id, summary, actor, role, details, created_at) =
+                row.map_err(|e| ProjectError::Database(e.to_string()))?;
+            entries.push(ActivityEntry {
+                id: id.parse().map_err(|e: uuid::Error| ProjectError::Database(e.to_string()))?,
+                workspace_id: ws.and_then(|s| s.parse().ok()),
+                kind: serde_json::from_str(&kind)
+                    .map_err(|e| ProjectError::Database(e.to_string()))?,
+                subject_id,
+                summary,
+                actor,
+                role: role.and_then(|r| serde_json::from_value(serde_json::Value::String(r)).ok()),
+                details: serde_json::from_str(&details).unwrap_or_default(),
+                created_at: chrono::DateTime::parse_from_rfc3339(&created_at)
+                    .map_err(|e| ProjectError::Database(e.to_string()))?
+                    .with_timezone(&chrono::Utc),
+            });
+        }
+
+        let has_more = entries.len() > limit;
+        entries.truncate(limit);
+        Ok(ActivityPage {
+            entries,
+            offset,
+            limit,
+            has_more,
+        })
+    }
+
+    fn list_env(&self, workspace_id: Uuid) -> Result<Vec<EnvVar>, ProjectError> {
+        let conn = match &self.conn {
+            Some(c) => c,
+            None => return Ok(vec![]),
+        };
+        let conn = conn.lock().map_err(|e| ProjectError::Database(e.to_string()))?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT name, value, description, updated_at FROM workspace_env
+                 WHERE workspace_id = ?1 ORDER BY name",
+            )
+            .map_err(|e| ProjectError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map([workspace_id.to_string()], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            })
+            .map_err(|e| ProjectError::Database(e.to_string()))?;
+
+        let mut vars = Vec::new();
+        for row in rows {
+            let (name, value, description, updated_at) =
+                row.map_err(|e| ProjectError::Database(e.to_string()))?;
+            vars.push(EnvVar {
+                name,
+                value,
+                description,
+                updated_at: chrono::DateTime::parse_from_rfc3339(&updated_at)
+                    .map_err(|e| ProjectError::Database(e.to_string()))?
+                    .with_timezone(&chrono::Utc),
+            });
+        }
+        Ok(vars)
+    }
+
+    fn set_env(&self, workspace_id: Uuid, var: &EnvVar) -> Result<(), ProjectError> {
+        if !EnvVar::is_valid_name(&var.name) {
+            return Err(ProjectError::Validation(format!(
+                "invalid variable name '{}': use letters, digits and underscores",
+                var.name
+            )));
+        }
+        let conn = match &self.conn {
+            Some(c) => c,
+            None => return Ok(()),
+        };
+        let conn = conn.lock().map_err(|e| ProjectError::Database(e.to_string()))?;
+        conn.execute(
+            "INSERT OR REPLACE INTO workspace_env (workspace_id, name, value, description, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![
+                workspace_id.to_string(),
+                var.name,
+                var.value,
+                var.description,
+                var.updated_at.to_rfc3339(),
+            ],
+        )
+        .map_err(|e| ProjectError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn delete_env(&self, workspace_id: Uuid, name: &str) -> Result<bool, ProjectError> {
+        let conn = match &self.conn {
+            Some(c) => c,
+            None => return Ok(false),
+        };
+        let conn = conn.lock().map_err(|e| ProjectError::Database(e.to_string()))?;
+        let removed = conn
+            .execute(
+                "DELETE FROM workspace_env WHERE workspace_id = ?1 AND name = ?2",
+                rusqlite::params![workspace_id.to_string(), name],
+            )
+            .map_err(|e| ProjectError::Database(e.to_string()))?;
+        Ok(removed > 0)
+    }
+
+    fn get_review(&self, workflow_id: Uuid) -> Result<Option<WorkflowReview>, ProjectError> {
+        let conn = match &self.conn {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+        let conn = conn.lock().map_err(|e| ProjectError::Database(e.to_string()))?;
+        let json = conn.query_row(
+            "SELECT review_json FROM workflow_reviews WHERE workflow_id = ?1",
+            rusqlite::params![workflow_id.to_string()],
+            |row| row.get::<_, String>(0),
+        );
+        match json {
+            Ok(json) => serde_json::from_str(&json)
+                .map(Some)
+                .map_err(|e| ProjectError::Database(e.to_string())),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(ProjectError::Database(e.to_string())),
+        }
+    }
+
+    fn save_review(&self, review: &WorkflowReview) -> Result<(), ProjectError> {
+        let conn = match &self.conn {
+            Some(c) => c,
+            None => return Ok(()),
+        };
+        let conn = conn.lock().map_err(|e| ProjectError::Database(e.to_string()))?;
+        let json =
+            serde_json::to_string(review).map_err(|e| ProjectError::Database(e.to_string()))?;
+        conn.execute(
+            "INSERT OR REPLACE INTO workflow_reviews (workflow_id, review_json, updated_at)
+             VALUES (?1, ?2, ?3)",
+            rusqlite::params![
+                review.workflow_id.to_string(),
+                json,
+                review.updated_at.to_rfc3339(),
+            ],
+        )
+        .map_err(|e| ProjectError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn apply_edit(
+        &self,
+        spec: &mut WorkflowSpec,
+        op: EditOp,
+        actor: Option<String>,
+    ) -> Result<JournalEntry, ProjectError> {
+        let mut edited = spec.clone();
+        let inverse = op.apply(&mut edited)?;
+        let mut entry = JournalEntry {
+            workflow_id: spec.id,
+            seq: 0,
+            op,
+            inverse,
+            actor,
+            created_at: chrono::Utc::now(),
+            undone: false,
+        };
+
+        if let Some(conn) = &self.conn {
+            let mut conn = conn.lock().map_err(|e| ProjectError::Database(e.to_string()))?;
+            let tx = conn
+                .transaction()
+                .map_err(|e| ProjectError::Database(e.to_string()))?;
+            let workflow_id = spec.id.to_string();
+            tx.execute(
+                "DELETE FROM edit_journal WHERE workflow_id = ?1 AND undone = 1",
+                rusqlite::params![workflow_id],
+            )
+            .map_err(|e| ProjectError::Database(e.to_string()))?;
+            entry.seq = tx
+                .query_row(
+                    "SELECT COALESCE(MAX(seq), 0) + 1 FROM edit_journal WHERE workflow_id = ?1",
+                    rusqlite::params![workflow_id],
+                    |row| row.get(0),
+                )
+                .map_err(|e| ProjectError::Database(e.to_string()))?;
+            tx.execute(
+                "INSERT INTO edit_journal (workflow_id, seq, op_json, inverse_json, actor, created_at, undone)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0)",
+                rusqlite::params![
+                    workflow_id,
+                    entry.seq,
+                    serde_json::to_string(&entry.op)
+                        .map_err(|e| ProjectError::Database(e.to_string()))?,
+                    serde_json::to_string(&entry.inverse)
+                        .map_err(|e| ProjectError::Database(e.to_string()))?,
+                    entry.actor,
+                    entry.created_at.to_rfc3339(),
+                ],
+            )
+            .map_err(|e| ProjectError::Database(e.to_string()))?;
+            tx.commit()
+                .map_err(|e| ProjectError::Database(e.to_string()))?;
+        }
+
+        edited.meta.updated_at = entry.created_at;
+        *spec = edited;
+        Ok(entry)
+    }
+
+    fn undo_edit(&self, spec: &mut WorkflowSpec) -> Result<Option<JournalEntry>, ProjectError> {
+        self.step_edit(spec, true)
+    }
+
+    fn redo_edit(&self, spec: &mut WorkflowSpec) -> Result<Option<JournalEntry>, ProjectError> {
+        self.step_edit(spec, false)
+    }
+
+    fn undo_state(&self, workflow_id: Uuid) -> Result<UndoState, ProjectError> {
+        let conn = match &self.conn {
+            Some(c) => c,
+            None => return Ok(UndoState::default()),
+        };
+        let conn = conn.lock().map_err(|e| ProjectError::Database(e.to_string()))?;
+        let (done, undone): (i64, i64) = conn
+            .query_row(
+                "SELECT COALESCE(SUM(undone = 0), 0), COALESCE(SUM(undone = 1), 0)
+                 FROM edit_journal WHERE workflow_id = ?1",
+                rusqlite::params![workflow_id.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|e| ProjectError::Database(e.to_string()))?;
+        Ok(UndoState {
+            can_undo: done > 0,
+            can_redo: undone > 0,
+        })
+    }
+
+    fn journal_since(
+        &self,
+        workflow_id: Uuid,
+        after_seq: i64,
+    ) -> Result<Vec<JournalEntry>, ProjectError> {
+        let conn = match &self.conn {
+            Some(c) => c,
+            None => return Ok(vec![]),
+        };
+        let conn = conn.lock().map_err(|e| ProjectError::Database(e.to_string()))?;
+        query_journal(
+            &conn,
+            "SELECT seq, op_json, inverse_json, actor, created_at, undone FROM edit_journal
+             WHERE workflow_id = ?1 AND seq > ?2 ORDER BY seq ASC",
+            workflow_id,
+            after_seq,
+        )
+    }
+
+    fn clear_journal(&self, workflow_id: Uuid) -> Result<(), ProjectError> {
+        let conn = match &self.conn {
+            Some(c) => c,
+            None => return Ok(()),
+        };
+        let conn = conn.lock().map_err(|e| ProjectError::Database(e.to_string()))?;
+        conn.execute(
+            "DELETE FROM edit_journal WHERE workflow_id = ?1",
+            rusqlite::params![workflow_id.to_string()],
+        )
+        .map_err(|e| ProjectError::Database(e.to_string()))?;
+        Ok(())
+    }
+}
+
+/// Run a journal query binding `?1 = workflow_id` and `?2 = after_seq`.
+fn query_journal(
+    conn: &Connection,
+    sql: &str,
+    workflow_id: Uuid,
+    after_seq: i64,
+) -> Result<Vec<JournalEntry>, ProjectError> {
+    let mut stmt = conn
+        .prepare(sql)
+        .map_err(|e| ProjectError::Database(e.to_string()))?;
+    let rows = stmt
+        .query_map(rusqlite::params![workflow_id.to_string(), after_seq], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, bool>(5)?,
+            ))
+        })
+        .map_err(|e| ProjectError::Database(e.to_string()))?;
+
+    let mut entries = Vec::new();
+    for row in rows {
+        let (seq, op, inverse, actor, created_at, undone) =
+            row.map_err(|e| ProjectError::Database(e.to_string()))?;
+        entries.push(JournalEntry {
+            workflow_id,
+            seq,
+            op: serde_json::from_str(&op).map_err(|e| ProjectError::Database(e.to_string()))?,
+            inverse: serde_json::from_str(&inverse)
+                .map_err(|e| ProjectError::Database(e.to_string()))?,
+            actor,
+            created_at: chrono::DateTime::parse_from_rfc3339(&created_at)
+                .map_err(|e| ProjectError::Database(e.to_string()))?
+                .with_timezone(&chrono::Utc),
+            undone,
+        });
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hb_core::project::Role;
+
+
+    /// Minimal map-backed store, to exercise the trait apart from SQLite.
+    /// Only what the round trip below uses is kept.
+    #[derive(Default)]
+    struct MemoryProjectStore {
+        workspaces: Mutex<BTreeMap<Uuid, WorkspaceConfig>>,
+        activity: Mutex<Vec<ActivityEntry>>,
+        env: Mutex<BTreeMap<(Uuid, String), EnvVar>>,
+    }
+
+    impl ProjectStore for MemoryProjectStore {
+        fn is_persistent(&self) -> bool {
+            true
+        }
+        fn close(&mut self) -> Result<(), ProjectError> {
+            Ok(())
+        }
+        fn create_workspace(&self, config: &WorkspaceConfig) -> Result<Uuid, ProjectError> {
+            self.workspaces.lock().unwrap().insert(config.id, config.clone());
+            Ok(config.id)
+        }
+        fn get_workspace(&self, id: Uuid) -> Result<WorkspaceConfig, ProjectError> {
+            let workspaces = self.workspaces.lock().unwrap();
+            workspaces.get(&id).cloned().ok_or_else(|| ProjectError::NotFound(id.to_string()))
+        }
+        fn list_workspaces(&self) -> Result<Vec<WorkspaceConfig>, ProjectError> {
+            Ok(self.workspaces.lock().unwrap().values().cloned().collect())
+        }
+        fn delete_workspace(&self, id: Uuid) -> Result<(), ProjectError> {
+            self.workspaces.lock().unwrap().remove(&id);
+            Ok(())
+        }
+        fn update_workspace(&self, id: Uuid, config: &WorkspaceConfig) -> Result<(), ProjectError> {
+            let mut workspaces = self.workspaces.lock().unwrap();
+            let stored = workspaces.get_mut(&id).ok_or_else(|| ProjectError::NotFound(id.to_string()))?;
+            *stored = config.clone();
+            Ok(())
+        }
+        fn record_activity(&self, entry: &ActivityEntry) -> Result<(), ProjectError> {
+            self.activity.lock().unwrap().push(entry.clone());
+            Ok(())
+        }
+        fn query_activity(
+            &self,
+            workspace_id: Option<Uuid>,
+            kinds: &[ActivityKind],
+            offset: usize,
+            limit: usize,
+        ) -> Result<ActivityPage, ProjectError> {
+            let mut entries: Vec<ActivityEntry> = self
+                .activity
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|e| workspace_id.is_none() || e.workspace_id == workspace_id)
+                .filter(|e| kinds.is_empty() || kinds.contains(&e.kind))
+                .cloned()
+                .collect();
+            entries.sort_by_key(|e| std::cmp::Reverse(e.created_at));
+            let has_more = entries.len() > offset + limit;
+            let entries = entries.into_iter().skip(offset).take(limit).collect();
+            Ok(ActivityPage {
+                entries,
+                offset,
+                limit,
+                has_more,
+            })
+        }
+        fn list_env(&self, workspace_id: Uuid) -> Result<Vec<EnvVar>, ProjectError> {
+            let env = self.env.lock().unwrap();
+            Ok(env
+                .iter()
+                .filter(|((ws, _), _)| *ws == workspace_id)
+                .map(|(_, var)| var.clone())
+                .collect())
+        }
+        fn set_env(&self, workspace_id: Uuid, var: &EnvVar) -> Result<(), ProjectError> {
+            self.env.lock().unwrap().insert((workspace_id, var.name.clone()), var.clone());
+            Ok(())
+        }
+        fn delete_env(&self, workspace_id: Uuid, name: &str) -> Result<bool, ProjectError> {
+            Ok(self.env.lock().unwrap().remove(&(workspace_id, name.to_string())).is_some())
+        }
+        fn get_review(&self, _: Uuid) -> Result<Option<WorkflowReview>, ProjectError> {
+            Ok(None)
+        }
+        fn save_review(&self, _: &WorkflowReview) -> Result<(), ProjectError> {
+            Ok(())
+        }
+        fn apply_edit(&self, _: &mut WorkflowSpec, _: EditOp, _: Option<String>) -> Result<JournalEntry, ProjectError> {
+            Err(ProjectError::Validation("not journaled".into()))
+        }
+        fn undo_edit(&self, _: &mut WorkflowSpec) -> Result<Option<JournalEntry>, ProjectError> {
+            Ok(None)
+        }
+        fn redo_edit(&self, _: &mut WorkflowSpec) -> Result<Option<JournalEntry>, ProjectError> {
+            Ok(None)
+        }
+        fn undo_state(&self, _: Uuid) -> Result<UndoState, ProjectError> {
+            Ok(UndoState::default())
+        }
+        fn journal_since(&self, _: Uuid, _: i64) -> Result<Vec<JournalEntry>, ProjectError> {
+            Ok(Vec::new())
+        }
+        fn clear_journal(&self, _: Uuid) -> Result<(), ProjectError> {
+            Ok(())
+        }
+    }
+
+    /// Store a workspace with its env and activity and read them back,
+    /// only through the trait.
+    fn round_trip(store: &dyn ProjectStore) {
+        let now = chrono::Utc::now();
+        let mut config = WorkspaceConfig {
+            id: Uuid::new_v4(),
+            name: "현장 A".into(),
+            description: Some("기초 공사".into()),
+            root_path: "/projects/site-a".into(),
+            created_at: now,
+            updated_at: now,
+            data_sources: vec![],
+            indexes: vec![],
+            default_policy: None,
+            llm_providers: vec![],
+            timezone: Some("Asia/Seoul".into()),
+            members: vec![hb_core::project::WorkspaceMember {
+                user: "kim".into(),
+                role: Role::Editor,
+            }],
+            review_policy: None,
+        };
+        let id = store.create_workspace(&config).unwrap();
+        let stored = store.get_workspace(id).unwrap();
+        assert_eq!((id, stored.id), (config.id, config.id));
+        assert_eq!((stored.name.as_str(), stored.timezone.as_deref()), ("현장 A", Some("Asia/Seoul")));
+        assert_eq!(stored.role_of(Some("kim")), Some(Role::Editor));
+        config.name = "현장 B".into();
+        store.update_workspace(id, &config).unwrap();
+        assert_eq!(store.list_workspaces().unwrap().iter().map(|w| w.name.as_str()).collect::<Vec<_>>(), ["현장 B"]);
+
+        let var = EnvVar {
+            name: "AWS_REGION".into(),
+            value: "ap-northeast-2".into(),
+            description: None,
+            updated_at: now,
+        };
+        store.set_env(id, &var).unwrap();
+        assert_eq!(store.env_map(id).unwrap()["AWS_REGION"], "ap-northeast-2");
+        assert!(store.delete_env(id, "AWS_REGION").unwrap());
+        assert!(store.list_env(id).unwrap().is_empty());
+
+        for i in 0..3 {
+            let mut entry = ActivityEntry::new(ActivityKind::WorkflowEdited, format!("wf-{i}"), "Edited")
+                .in_workspace(Some(id))
+                .by(Some("kim".into()), Some(Role::Editor));
+            entry.created_at += chrono::Duration::seconds(i);
+            store.record_activity(&entry).unwrap();
+        }
+        let page = store.query_activity(Some(id), &[ActivityKind::WorkflowEdited], 0, 2).unwrap();
+        assert!(page.has_more);
+        assert_eq!(page.entries[0].subject_id, "wf-2");
+        assert_eq!(page.entries[0].actor.as_deref(), Some("kim"));
+
+        store.delete_workspace(id).unwrap();
+        assert!(matches!(store.get_workspace(id), Err(ProjectError::NotFound(_))));
+    }
+
+    #[test]
+    fn backends_round_trip_through_the_trait() {
+        round_trip(&MemoryProjectStore::default());
+
+        let dir = std::env::temp_dir().join(format!("hb-project-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut store: Box<dyn ProjectStore> = Box::new(ProjectManager::open(&dir.join("projects.db")).unwrap());
+        round_trip(store.as_ref());
+        store.close().unwrap();
+        let _ = std::fs::remove_dir_all(dir);
+    }
+    #[test]
+    fn env_vars_are_scoped_to_a_workspace() {
+        let dir = std::env::temp_dir().join(format!("hb-project-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pm = ProjectManager::open(&dir.join("projects.db")).unwrap();
+        let (ws, other) = (Uuid::new_v4(), Uuid::new_v4());
+        let var = |name: &str, value: &str| EnvVar {
+            name: name.into(),
+            value: value.into(),
+            description: None,
+            updated_at: chrono::Utc::now(),
+        };
+
+        pm.set_env(ws, &var("AWS_REGION", "us-east-1")).unwrap();
+        pm.set_env(ws, &var("AWS_REGION", "ap-northeast-2")).unwrap();
+        pm.set_env(ws, &var("BASE_URL", "http://localhost:8080")).unwrap();
+        pm.set_env(other, &var("BASE_URL", "https://example.com")).unwrap();
+        assert!(matches!(
+            pm.set_env(ws, &var("1BAD-NAME", "x")),
+            Err(ProjectError::Validation(_))
+        ));
+
+        let env = pm.env_map(ws).unwrap();
+        assert_eq!(env.len(), 2);
+        assert_eq!(env["AWS_REGION"], "ap-northeast-2");
+
+        assert!(pm.delete_env(ws, "BASE_URL").unwrap());
+        assert!(!pm.delete_env(ws, "BASE_URL").unwrap());
+        assert_eq!(pm.list_env(other).unwrap().len(), 1);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn activity_is_paginated_per_workspace() {
+        let dir = std::env::temp_dir().join(format!("hb-project-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pm = ProjectManager::open(&dir.join("projects.db")).unwrap();
+
+        let ws = Uuid::new_v4();
+        for i in 0..5 {
+            let mut entry = ActivityEntry::new(ActivityKind::WorkflowEdited, format!("wf-{i}"), "Edited")
+                .in_workspace(Some(ws));
+            entry.created_at += chrono::Duration::seconds(i);
+            pm.record_activity(&entry).unwrap();
+        }
+        pm.record_activity(
+            &ActivityEntry::new(ActivityKind::PackInstalled, "rag-pack", "Installed")
+                .by(Some("kim".into()), Some(Role::Admin)),
+        )
+        .unwrap();
+
+        let first = pm.query_activity(Some(ws), &[], 0, 3).unwrap();
+        assert_eq!(first.entries.len(), 3);
+        assert!(first.has_more);
+        assert_eq!(first.entries[0].subject_id, "wf-4");
+
+        let second = pm.query_activity(Some(ws), &[], 3, 3).unwrap();
+        assert_eq!(second.entries.len(), 2);
+        assert!(!second.has_more);
+
+        let packs = pm.query_activity(None, &[ActivityKind::PackInstalled], 0, 10).unwrap();
+        assert_eq!(packs.entries.len(), 1);
+        assert_eq!(packs.entries[0].actor.as_deref(), Some("kim"));
+        assert_eq!(packs.entries[0].role, Some(Role::Admin));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn edits_can_be_undone_and_redone() {
+        let dir = std::env::temp_dir().join(format!("hb-project-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pm = ProjectManager::open(&dir.join("projects.db")).unwrap();
+
+        let mut spec = WorkflowSpec::default();
+        let node = hb_core::graph::NodeEntry::Primitive(hb_core::graph::NodeSpec {
+            id: "n1".into(),
+            tool_ref: "core-tools/echo@1.0.0".into(),
+            ..Default::default()
+        });
+        pm.apply_edit(&mut spec, EditOp::AddNode { node, index: None }, None).unwrap();
+        let set = EditOp::SetConfig {
+            node_id: "n1".into(),
+            key: "text".into(),
+            value: Some("hi".into()),
+        };
+        assert_eq!(pm.apply_edit(&mut spec, set, Some("alice".into())).unwrap().seq, 2);
+
+        pm.undo_edit(&mut spec).unwrap().unwrap();
+        let hb_core::graph::NodeEntry::Primitive(ref n) = spec.nodes[0] else { unreachable!() };
+        assert!(n.config.is_empty());
+        let state = pm.undo_state(spec.id).unwrap();
+        assert!(state.can_undo && state.can_redo);
+
+        pm.redo_edit(&mut spec).unwrap().unwrap();
+        let hb_core::graph::NodeEntry::Primitive(ref n) = spec.nodes[0] else { unreachable!() };
+        assert_eq!(n.config["text"], "hi");
+        assert!(pm.redo_edit(&mut spec).unwrap().is_none());
+
+        // A new edit after undo discards the redo branch.
+        pm.undo_edit(&mut spec).unwrap();
+        pm.apply_edit(&mut spec, EditOp::RemoveNode { node_id: "n1".into() }, None)
+            .unwrap();
+        assert!(!pm.undo_state(spec.id).unwrap().can_redo);
+        let feed = pm.journal_since(spec.id, 0).unwrap();
+        assert_eq!(feed.len(), 2);
+        assert!(matches!(feed[1].op, EditOp::RemoveNode { .. }));
+
+        // Replacing the graph drops the journal, so undo can't replay onto it.
+        pm.clear_journal(spec.id).unwrap();
+        assert!(!pm.undo_state(spec.id).unwrap().can_undo);
+        assert!(pm.undo_edit(&mut spec).unwrap().is_none());
+        let _ = std::fs::remove_dir_all(dir);
+    }
 }
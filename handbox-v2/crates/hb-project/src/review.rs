@@ -0,0 +1,280 @@
+//! Workflow review — draft → in review → approved, before a workflow may be
+//! published or scheduled in a workspace with a [`ReviewPolicy`].
+//!
+//! A review covers the workflow as it was when submitted: the content hash
+//! taken at submission must still match when reviewers decide, and an
+//! approval lapses (back to draft) as soon as the workflow changes.
+
+use crate::ProjectError;
+use chrono::{DateTime, Utc};
+use hb_core::graph::WorkflowSpec;
+use hb_core::project::{ReviewPolicy, Role};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewState {
+    Draft,
+    InReview,
+    Approved,
+}
+
+/// A reviewer's approval or rejection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewDecision {
+    pub reviewer: Option<String>,
+    /// The reviewer's role, when roles apply.
+    pub role: Option<Role>,
+    pub approved: bool,
+    pub comment: Option<String>,
+    pub decided_at: DateTime<Utc>,
+}
+
+/// Review status of one workflow.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowReview {
+    pub workflow_id: Uuid,
+    /// Workspace the workflow was submitted in; its review policy applies
+    /// wherever the workflow is later published or scheduled from.
+    #[serde(default)]
+    pub workspace_id: Option<Uuid>,
+    pub state: ReviewState,
+    pub submitted_by: Option<String>,
+    pub submitted_at: Option<DateTime<Utc>>,
+    /// Content hash of the workflow as submitted.
+    pub hash: Option<String>,
+    /// Decisions on the current submission.
+    pub decisions: Vec<ReviewDecision>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Content hash of a workflow, ignoring its modification time.
+pub fn fingerprint(spec: &WorkflowSpec) -> String {
+    let mut spec = spec.clone();
+    spec.meta.updated_at = spec.meta.created_at;
+    let mut hasher = Sha256::new();
+    hasher.update(serde_json::to_vec(&spec).unwrap_or_default());
+    hex::encode(hasher.finalize())
+}
+
+impl WorkflowReview {
+    pub fn new(workflow_id: Uuid) -> Self {
+        Self {
+            workflow_id,
+            workspace_id: None,
+            state: ReviewState::Draft,
+            submitted_by: None,
+            submitted_at: None,
+            hash: None,
+            decisions: Vec::new(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// State of the review for the workflow as it is now: a review of an
+    /// earlier version counts as a draft.
+    pub fn effective_state(&self, spec: &WorkflowSpec) -> ReviewState {
+        match self.state {
+            ReviewState::Draft => ReviewState::Draft,
+            _ if self.hash.as_deref() != Some(fingerprint(spec).as_str()) => ReviewState::Draft,
+            state => state,
+        }
+    }
+
+    /// Submit the workflow as it is now for review.
+    pub fn submit(&mut self, spec: &WorkflowSpec, by: Option<String>) -> Result<(), ProjectError> {
+        match self.effective_state(spec) {
+            ReviewState::InReview => {
+                return Err(ProjectError::Validation(
+                    "workflow is already in review".into(),
+                ))
+            }
+            ReviewState::Approved => {
+                return Err(ProjectError::Validation(
+                    "workflow is already approved".into(),
+                ))
+            }
+            ReviewState::Draft => {}
+        }
+        let now = Utc::now();
+        self.state = ReviewState::InReview;
+        self.submitted_by = by;
+        self.submitted_at = Some(now);
+        self.hash = Some(fingerprint(spec));
+        self.decisions.clear();
+        self.updated_at = now;
+        Ok(())
+    }
+
+    /// Record a reviewer's decision. A rejection returns the workflow to
+    /// draft; enough approvals from distinct reviewers approve it. `role` is
+    /// `None` when workspace roles don't apply, but the reviewer must be
+    /// known so approvals can't come from the author or count twice.
+    pub fn decide(
+        &mut self,
+        spec: &WorkflowSpec,
+        policy: &ReviewPolicy,
+        decision: ReviewDecision,
+    ) -> Result<ReviewState, ProjectError> {
+        if self.state != ReviewState::InReview {
+            return Err(ProjectError::Validation("workflow is not in review".into()));
+        }
+        if self.effective_state(spec) != ReviewState::InReview {
+            return Err(ProjectError::Validation(
+                "workflow changed since it was submitted; submit it again".into(),
+            ));
+        }
+        if decision
+            .role
+            .is_some_and(|role| role < policy.approver_role)
+        {
+            return Err(ProjectError::PermissionDenied(format!(
+                "reviews require the {} role",
+                policy.approver_role.as_str()
+            )));
+        }
+        let Some(reviewer) = decision.reviewer.as_deref() else {
+            return Err(ProjectError::PermissionDenied(
+                "reviews need a known reviewer; turn on team sync".into(),
+            ));
+        };
+        if decision.approved
+            && !policy.allow_self_approval
+            && self.submitted_by.as_deref().is_none_or(|author| author == reviewer)
+        {
+            return Err(ProjectError::PermissionDenied(
+                "workflows cannot be approved by whoever submitted them".into(),
+            ));
+        }
+
+        let approved = decision.approved;
+        self.decisions.retain(|d| d.reviewer != decision.reviewer);
+        self.decisions.push(decision);
+        self.updated_at = Utc::now();
+        if !approved {
+            self.state = ReviewState::Draft;
+        } else if self.decisions.iter().filter(|d| d.approved).count()
+            >= policy.min_approvals.max(1) as usize
+        {
+            self.state = ReviewState::Approved;
+        }
+        Ok(self.state)
+    }
+
+    /// Fail unless the workflow as it is now has been approved.
+    pub fn require_approved(&self, spec: &WorkflowSpec) -> Result<(), ProjectError> {
+        match self.effective_state(spec) {
+            ReviewState::Approved => Ok(()),
+            ReviewState::InReview => Err(ProjectError::PermissionDenied(format!(
+                "workflow '{}' is still in review",
+                spec.meta.name
+            ))),
+            ReviewState::Draft => Err(ProjectError::PermissionDenied(format!(
+                "workflow '{}' has not been approved",
+                spec.meta.name
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decision(reviewer: &str, role: Role, approved: bool) -> ReviewDecision {
+        ReviewDecision {
+            reviewer: Some(reviewer.into()),
+            role: Some(role),
+            approved,
+            comment: None,
+            decided_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn approval_follows_policy_and_lapses_on_edit() {
+        let mut spec = WorkflowSpec::default();
+        let policy = ReviewPolicy {
+            approver_role: Role::Editor,
+            min_approvals: 2,
+            allow_self_approval: false,
+        };
+        let mut review = WorkflowReview::new(spec.id);
+        assert!(review.require_approved(&spec).is_err());
+
+        review.submit(&spec, Some("kim".into())).unwrap();
+        assert!(review.submit(&spec, None).is_err());
+        assert!(review
+            .decide(&spec, &policy, decision("kim", Role::Admin, true))
+            .is_err());
+        assert!(review
+            .decide(&spec, &policy, decision("lee", Role::Runner, true))
+            .is_err());
+
+        assert_eq!(
+            review
+                .decide(&spec, &policy, decision("lee", Role::Editor, true))
+                .unwrap(),
+            ReviewState::InReview
+        );
+        // A repeated approval from the same reviewer counts once
+        assert_eq!(
+            review
+                .decide(&spec, &policy, decision("lee", Role::Editor, true))
+                .unwrap(),
+            ReviewState::InReview
+        );
+        assert_eq!(
+            review
+                .decide(&spec, &policy, decision("park", Role::Admin, true))
+                .unwrap(),
+            ReviewState::Approved
+        );
+        review.require_approved(&spec).unwrap();
+
+        spec.meta.description = "changed".into();
+        assert_eq!(review.effective_state(&spec), ReviewState::Draft);
+        assert!(review.require_approved(&spec).is_err());
+    }
+
+    #[test]
+    fn rejection_returns_to_draft() {
+        let spec = WorkflowSpec::default();
+        let policy = ReviewPolicy::default();
+        let mut review = WorkflowReview::new(spec.id);
+        review.submit(&spec, Some("kim".into())).unwrap();
+        assert_eq!(
+            review
+                .decide(&spec, &policy, decision("lee", Role::Admin, false))
+                .unwrap(),
+            ReviewState::Draft
+        );
+        review.submit(&spec, Some("kim".into())).unwrap();
+        assert!(review.decisions.is_empty());
+    }
+
+    #[test]
+    fn approvals_need_a_reviewer_other_than_the_author() {
+        let spec = WorkflowSpec::default();
+        let policy = ReviewPolicy::default();
+        let mut review = WorkflowReview::new(spec.id);
+        review.submit(&spec, Some("kim".into())).unwrap();
+        let anonymous = ReviewDecision {
+            reviewer: None,
+            ..decision("lee", Role::Admin, true)
+        };
+        assert!(review.decide(&spec, &policy, anonymous).is_err());
+        assert!(review
+            .decide(&spec, &policy, decision("kim", Role::Admin, true))
+            .is_err());
+
+        // Without a known author nobody can tell self-approval apart
+        let mut unattributed = WorkflowReview::new(spec.id);
+        unattributed.submit(&spec, None).unwrap();
+        assert!(unattributed
+            .decide(&spec, &policy, decision("lee", Role::Admin, true))
+            .is_err());
+    }
+}
@@ -0,0 +1,321 @@
+//! Workflow thumbnails — SVG/PNG previews of a workflow graph for workflow
+//! lists and marketplace cards.
+
+use crate::ProjectError;
+use chrono::{DateTime, Utc};
+use hb_core::graph::layout::{self, BoxKind, GraphLayout};
+use hb_core::graph::WorkflowSpec;
+use resvg::{tiny_skia, usvg};
+use rusqlite::Connection;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use uuid::Uuid;
+
+/// Rendered width of thumbnails in pixels; height follows the graph's aspect ratio.
+pub const THUMBNAIL_WIDTH: u32 = 320;
+
+const PADDING: f64 = 24.0;
+/// Cap on height relative to width so very tall graphs stay card-shaped.
+const MAX_ASPECT: f64 = 1.0;
+const MAX_LABEL_CHARS: usize = 22;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Thumbnail {
+    pub workflow_id: Uuid,
+    /// Hash of the graph content the thumbnail was rendered from.
+    pub spec_hash: String,
+    pub svg: String,
+    #[serde(skip)]
+    pub png: Vec<u8>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Hash of the parts of a workflow that affect its thumbnail.
+pub fn spec_hash(spec: &WorkflowSpec) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(spec.meta.name.as_bytes());
+    hasher.update(serde_json::to_vec(&spec.nodes).unwrap_or_default());
+    hasher.update(serde_json::to_vec(&spec.edges).unwrap_or_default());
+    hex::encode(hasher.finalize())
+}
+
+fn colors(kind: BoxKind) -> (&'static str, &'static str) {
+    match kind {
+        BoxKind::Primitive => ("#1e293b", "#3b82f6"),
+        BoxKind::Composite => ("#1e1b4b", "#8b5cf6"),
+        BoxKind::Conditional => ("#422006", "#f59e0b"),
+        BoxKind::Loop => ("#052e16", "#10b981"),
+        BoxKind::MapReduce => ("#083344", "#06b6d4"),
+        BoxKind::WorkflowRef => ("#4a044e", "#d946ef"),
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn truncate(label: &str) -> String {
+    if label.chars().count() <= MAX_LABEL_CHARS {
+        label.to_string()
+    } else {
+        let mut s: String = label.chars().take(MAX_LABEL_CHARS - 1).collect();
+        s.push('…');
+        s
+    }
+}
+
+/// Render a laid-out graph as an SVG document `width` pixels wide.
+pub fn render_svg(graph: &GraphLayout, width: u32) -> String {
+    let view_w = graph.width.max(layout::NODE_WIDTH) + PADDING * 2.0;
+    let view_h = (graph.height.max(layout::NODE_HEIGHT) + PADDING * 2.0).min(view_w * MAX_ASPECT);
+    let height = (width as f64 * view_h / view_w).round().max(1.0) as u32;
+
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="{} {} {view_w} {view_h}" preserveAspectRatio="xMidYMin meet">"##,
+        -PADDING, -PADDING,
+    );
+    let _ = write!(
+        svg,
+        r##"<rect x="{}" y="{}" width="{view_w}" height="{view_h}" fill="#0f172a"/>"##,
+        -PADDING, -PADDING,
+    );
+
+    for edge in &graph.edges {
+        let (s, t) = (&graph.nodes[edge.source], &graph.nodes[edge.target]);
+        let (x1, y1) = (s.x + s.width, s.y + s.height / 2.0);
+        let (x2, y2) = (t.x, t.y + t.height / 2.0);
+        let dx = ((x2 - x1).abs() / 2.0).max(30.0);
+        let _ = write!(
+            svg,
+            r##"<path d="M{x1} {y1} C{} {y1} {} {y2} {x2} {y2}" fill="none" stroke="#64748b" stroke-width="2"/>"##,
+            x1 + dx,
+            x2 - dx,
+        );
+    }
+
+    for node in &graph.nodes {
+        let (fill, stroke) = colors(node.kind);
+        let opacity = if node.disabled { "0.4" } else { "1" };
+        let _ = write!(
+            svg,
+            r##"<g opacity="{opacity}"><rect x="{}" y="{}" width="{}" height="{}" rx="8" fill="{fill}" stroke="{stroke}" stroke-width="2"/>"##,
+            node.x, node.y, node.width, node.height,
+        );
+        let _ = write!(
+            svg,
+            r##"<text x="{}" y="{}" font-family="sans-serif" font-size="16" fill="#e2e8f0" text-anchor="middle" dominant-baseline="middle">{}</text></g>"##,
+            node.x + node.width / 2.0,
+            node.y + node.height / 2.0,
+            escape(&truncate(&node.label)),
+        );
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+fn usvg_options() -> &'static usvg::Options<'static> {
+    static OPTIONS: OnceLock<usvg::Options<'static>> = OnceLock::new();
+    OPTIONS.get_or_init(|| {
+        let mut options = usvg::Options::default();
+        options.fontdb_mut().load_system_fonts();
+        options
+    })
+}
+
+/// Rasterize an SVG document to PNG at its declared size.
+pub fn render_png(svg: &str) -> Result<Vec<u8>, ProjectError> {
+    let tree = usvg::Tree::from_str(svg, usvg_options())
+        .map_err(|e| ProjectError::Validation(format!("invalid thumbnail SVG: {e}")))?;
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| ProjectError::Validation("empty thumbnail".into()))?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    pixmap
+        .encode_png()
+        .map_err(|e| ProjectError::Validation(e.to_string()))
+}
+
+/// Lay out and render a workflow thumbnail in both formats.
+pub fn render(spec: &WorkflowSpec) -> Result<Thumbnail, ProjectError> {
+    let svg = render_svg(&layout::layout(spec), THUMBNAIL_WIDTH);
+    let png = render_png(&svg)?;
+    Ok(Thumbnail {
+        workflow_id: spec.id,
+        spec_hash: spec_hash(spec),
+        svg,
+        png,
+        updated_at: Utc::now(),
+    })
+}
+
+/// Rendered thumbnails keyed by workflow, in their own table of the project
+/// database. Without a database nothing is cached.
+#[derive(Default)]
+pub struct ThumbnailCache {
+    conn: Option<Mutex<Connection>>,
+}
+
+impl ThumbnailCache {
+    pub fn new() -> Self {
+        Self { conn: None }
+    }
+
+    /// Open or create the cache in the database at `path`.
+    pub fn open(path: &Path) -> Result<Self, ProjectError> {
+        let conn =
+            Connection::open(path).map_err(|e| ProjectError::Database(e.to_string()))?;
+        Self::with_connection(conn)
+    }
+
+    /// A cache that lives in memory (useful for testing).
+    pub fn in_memory() -> Result<Self, ProjectError> {
+        let conn =
+            Connection::open_in_memory().map_err(|e| ProjectError::Database(e.to_string()))?;
+        Self::with_connection(conn)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self, ProjectError> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS thumbnails (
+                workflow_id TEXT PRIMARY KEY,
+                spec_hash TEXT NOT NULL,
+                svg TEXT NOT NULL,
+                png BLOB NOT NULL,
+                updated_at TEXT NOT NULL
+            );",
+        )
+        .map_err(|e| ProjectError::Database(e.to_string()))?;
+        Ok(Self {
+            conn: Some(Mutex::new(conn)),
+        })
+    }
+
+    /// Cached thumbnail for a workflow, if one has been rendered.
+    pub fn get(&self, workflow_id: Uuid) -> Result<Option<Thumbnail>, ProjectError> {
+        let conn = match &self.conn {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+        let conn = conn.lock().map_err(|e| ProjectError::Database(e.to_string()))?;
+
+        let row = conn.query_row(
+            "SELECT spec_hash, svg, png, updated_at FROM thumbnails WHERE workflow_id = ?1",
+            rusqlite::params![workflow_id.to_string()],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Vec<u8>>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            },
+        );
+        let (spec_hash, svg, png, updated_at) = match row {
+            Ok(r) => r,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+            Err(e) => return Err(ProjectError::Database(e.to_string())),
+        };
+        Ok(Some(Thumbnail {
+            workflow_id,
+            spec_hash,
+            svg,
+            png,
+            updated_at: chrono::DateTime::parse_from_rfc3339(&updated_at)
+                .map_err(|e| ProjectError::Database(e.to_string()))?
+                .with_timezone(&chrono::Utc),
+        }))
+    }
+
+    /// Cache a rendered thumbnail, replacing the workflow's previous one.
+    pub fn save(&self, thumb: &Thumbnail) -> Result<(), ProjectError> {
+        if let Some(conn) = &self.conn {
+            let conn = conn.lock().map_err(|e| ProjectError::Database(e.to_string()))?;
+            conn.execute(
+                "INSERT OR REPLACE INTO thumbnails (workflow_id, spec_hash, svg, png, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![
+                    thumb.workflow_id.to_string(),
+                    thumb.spec_hash,
+                    thumb.svg,
+                    thumb.png,
+                    thumb.updated_at.to_rfc3339(),
+                ],
+            )
+            .map_err(|e| ProjectError::Database(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    pub fn delete(&self, workflow_id: Uuid) -> Result<(), ProjectError> {
+        let conn = match &self.conn {
+            Some(c) => c,
+            None => return Ok(()),
+        };
+        let conn = conn.lock().map_err(|e| ProjectError::Database(e.to_string()))?;
+        conn.execute(
+            "DELETE FROM thumbnails WHERE workflow_id = ?1",
+            rusqlite::params![workflow_id.to_string()],
+        )
+        .map_err(|e| ProjectError::Database(e.to_string()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hb_core::graph::{NodeEntry, NodeSpec};
+
+    #[test]
+    fn renders_svg_and_png() {
+        let spec = WorkflowSpec {
+            nodes: vec![NodeEntry::Primitive(NodeSpec {
+                id: "n1".into(),
+                tool_ref: "core-tools/file-read@1.0.0".into(),
+                label: Some("Read <input> & parse".into()),
+                ..Default::default()
+            })],
+            ..Default::default()
+        };
+        let thumb = render(&spec).unwrap();
+        assert!(thumb.svg.contains("Read &lt;input&gt; &amp; parse"));
+        assert!(thumb.png.starts_with(b"\x89PNG"));
+        assert_eq!(thumb.spec_hash, spec_hash(&spec));
+    }
+
+    #[test]
+    fn cached_thumbnail_is_replaced_when_saved_again() {
+        let cache = ThumbnailCache::in_memory().unwrap();
+
+        let mut spec = WorkflowSpec::default();
+        let first = render(&spec).unwrap();
+        cache.save(&first).unwrap();
+        assert_eq!(cache.get(spec.id).unwrap().unwrap().spec_hash, spec_hash(&spec));
+
+        spec.meta.name = "renamed".into();
+        let updated = render(&spec).unwrap();
+        assert_ne!(updated.spec_hash, first.spec_hash);
+        cache.save(&updated).unwrap();
+        assert_eq!(cache.get(spec.id).unwrap().unwrap().spec_hash, updated.spec_hash);
+
+        cache.delete(spec.id).unwrap();
+        assert!(cache.get(spec.id).unwrap().is_none());
+    }
+}
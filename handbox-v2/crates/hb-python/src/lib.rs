@@ -0,0 +1,352 @@
+//! hb-python: the `handbox` Python module, built on [`hb_sdk`].
+//!
+//! Workflows, records, spans and issues cross the boundary as plain Python
+//! dicts and lists (the same JSON the app and the trace store use), so they
+//! work with `json`, pandas and the like without wrapper classes. Anything
+//! that takes a workflow accepts either a dict or a JSON string, and IDs may
+//! be `str` or `uuid.UUID`.
+//!
+//! ```python
+//! import handbox
+//!
+//! engine = handbox.Engine("./handbox-data")
+//! spec = engine.load_workflow("summarize.json")
+//! record = engine.execute(spec, on_status=lambda e: print(e["node_id"], e["status"]))
+//! for span in engine.spans(record["execution_id"]):
+//!     print(span["node_id"], span["status"])
+//! ```
+//!
+//! Runs and compiles release the GIL while they wait, so other Python
+//! threads keep going; status callbacks take it back for each event.
+
+use hb_sdk::{RunOptions, SdkError, Severity, WatchdogLimits, WorkflowSpec};
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyString;
+use serde::Serialize;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+create_exception!(
+    handbox,
+    HandboxError,
+    PyException,
+    "An engine operation failed."
+);
+create_exception!(
+    handbox,
+    InvalidWorkflowError,
+    HandboxError,
+    "The workflow has validation errors; `args[1]` holds every issue found."
+);
+create_exception!(
+    handbox,
+    NotFoundError,
+    HandboxError,
+    "No execution, workspace or tool with that ID."
+);
+
+fn to_py_err(py: Python<'_>, e: SdkError) -> PyErr {
+    match e {
+        SdkError::Invalid(issues) => {
+            let message = SdkError::Invalid(issues.clone()).to_string();
+            match to_py(py, &issues) {
+                Ok(issues) => InvalidWorkflowError::new_err((message, issues)),
+                Err(e) => e,
+            }
+        }
+        SdkError::NotFound(_) => NotFoundError::new_err(e.to_string()),
+        e => HandboxError::new_err(e.to_string()),
+    }
+}
+
+/// Convert through JSON, so Python sees exactly what the app serializes.
+fn to_py<T: Serialize + ?Sized>(py: Python<'_>, value: &T) -> PyResult<PyObject> {
+    let json = serde_json::to_string(value).map_err(|e| HandboxError::new_err(e.to_string()))?;
+    Ok(py.import("json")?.call_method1("loads", (json,))?.unbind())
+}
+
+/// A JSON string as-is, anything else through `json.dumps`.
+fn to_json(value: &Bound<'_, PyAny>) -> PyResult<String> {
+    if let Ok(s) = value.downcast::<PyString>() {
+        return Ok(s.to_str()?.to_string());
+    }
+    value
+        .py()
+        .import("json")?
+        .call_method1("dumps", (value,))?
+        .extract()
+}
+
+fn to_uuid(value: &Bound<'_, PyAny>) -> PyResult<Uuid> {
+    let text = value.str()?;
+    Uuid::parse_str(text.to_str()?).map_err(|e| PyValueError::new_err(format!("{text}: {e}")))
+}
+
+/// The Handbox engine. `Engine()` keeps everything in memory;
+/// `Engine(data_dir)` opens (or creates) a data directory laid out like the
+/// app's, loading tool packs from its `packs/` folder.
+#[pyclass(module = "handbox")]
+struct Engine {
+    inner: hb_sdk::Engine,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl Engine {
+    fn spec(&self, py: Python<'_>, spec: &Bound<'_, PyAny>) -> PyResult<WorkflowSpec> {
+        self.inner
+            .parse_workflow(&to_json(spec)?)
+            .map_err(|e| to_py_err(py, e))
+    }
+}
+
+#[pymethods]
+impl Engine {
+    #[new]
+    #[pyo3(signature = (data_dir=None))]
+    fn new(py: Python<'_>, data_dir: Option<PathBuf>) -> PyResult<Self> {
+        let inner = match data_dir {
+            Some(dir) => hb_sdk::Engine::open(dir),
+            None => hb_sdk::Engine::in_memory(),
+        }
+        .map_err(|e| to_py_err(py, e))?;
+        let runtime = tokio::runtime::Runtime::new()
+            .map_err(|e| HandboxError::new_err(format!("tokio runtime: {e}")))?;
+        Ok(Self { inner, runtime })
+    }
+
+    #[getter]
+    fn data_dir(&self) -> Option<PathBuf> {
+        self.inner.data_dir().map(|d| d.to_path_buf())
+    }
+
+    /// Register the tools of every pack under `packs_dir`; returns how many
+    /// were loaded.
+    fn load_packs(&mut self, py: Python<'_>, packs_dir: PathBuf) -> PyResult<usize> {
+        self.inner
+            .load_packs(packs_dir)
+            .map_err(|e| to_py_err(py, e))
+    }
+
+    fn tools(&self, py: Python<'_>) -> PyResult<PyObject> {
+        to_py(py, &self.inner.tools().list())
+    }
+
+    /// A tool by ID, with or without an `@version` suffix, or `None`.
+    fn tool(&self, py: Python<'_>, tool_ref: &str) -> PyResult<Option<PyObject>> {
+        self.inner.tool(tool_ref).map(|t| to_py(py, t)).transpose()
+    }
+
+    fn workspaces(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let workspaces = self.inner.workspaces().map_err(|e| to_py_err(py, e))?;
+        to_py(py, &workspaces)
+    }
+
+    /// Read a workflow file of any supported spec version, upgraded to the
+    /// current one.
+    fn load_workflow(&self, py: Python<'_>, path: PathBuf) -> PyResult<PyObject> {
+        let spec = self
+            .inner
+            .load_workflow(path)
+            .map_err(|e| to_py_err(py, e))?;
+        to_py(py, &spec)
+    }
+
+    /// `load_workflow` for a dict or JSON string already in memory.
+    fn parse_workflow(&self, py: Python<'_>, spec: &Bound<'_, PyAny>) -> PyResult<PyObject> {
+        to_py(py, &self.spec(py, spec)?)
+    }
+
+    /// Make `spec` available to workflow reference nodes of later runs.
+    fn add_workflow(&mut self, py: Python<'_>, spec: &Bound<'_, PyAny>) -> PyResult<()> {
+        let spec = self.spec(py, spec)?;
+        self.inner.add_workflow(spec);
+        Ok(())
+    }
+
+    /// Compile a natural-language prompt into a workflow.
+    fn compile(&self, py: Python<'_>, prompt: &str) -> PyResult<PyObject> {
+        let spec = py
+            .allow_threads(|| self.runtime.block_on(self.inner.compile(prompt)))
+            .map_err(|e| to_py_err(py, e))?;
+        to_py(py, &spec)
+    }
+
+    /// Lint `spec` against the registered tools; returns every issue found.
+    fn validate(&self, py: Python<'_>, spec: &Bound<'_, PyAny>) -> PyResult<PyObject> {
+        to_py(py, &self.inner.validate(&self.spec(py, spec)?))
+    }
+
+    /// Whether `spec` has no validation errors (warnings are allowed).
+    fn is_valid(&self, py: Python<'_>, spec: &Bound<'_, PyAny>) -> PyResult<bool> {
+        let issues = self.inner.validate(&self.spec(py, spec)?);
+        Ok(issues.iter().all(|i| i.severity != Severity::Error))
+    }
+
+    /// Run `spec` to completion and return its execution record. Raises
+    /// `InvalidWorkflowError` before anything runs if the spec has errors.
+    /// `on_status` is called with each node status event as a dict.
+    #[pyo3(signature = (spec, workspace_id=None, on_status=None, max_node_executions=None, max_workflow_depth=None))]
+    fn execute(
+        &self,
+        py: Python<'_>,
+        spec: &Bound<'_, PyAny>,
+        workspace_id: Option<&Bound<'_, PyAny>>,
+        on_status: Option<PyObject>,
+        max_node_executions: Option<u64>,
+        max_workflow_depth: Option<usize>,
+    ) -> PyResult<PyObject> {
+        let spec = self.spec(py, spec)?;
+        let mut options = RunOptions::default();
+        if let Some(id) = workspace_id {
+            options = options.in_workspace(to_uuid(id)?);
+        }
+        if let Some(callback) = on_status {
+            options = options.on_status(move |event| {
+                Python::with_gil(|py| {
+                    let called = to_py(py, &event).and_then(|e| callback.call1(py, (e,)));
+                    if let Err(e) = called {
+                        e.write_unraisable(py, Some(callback.bind(py)));
+                    }
+                })
+            });
+        }
+        if max_node_executions.is_some() || max_workflow_depth.is_some() {
+            let defaults = WatchdogLimits::default();
+            options = options.with_watchdog(WatchdogLimits {
+                max_node_executions: max_node_executions.unwrap_or(defaults.max_node_executions),
+                max_workflow_depth: max_workflow_depth.unwrap_or(defaults.max_workflow_depth),
+            });
+        }
+        let record = py
+            .allow_threads(|| self.runtime.block_on(self.inner.execute(&spec, options)))
+            .map_err(|e| to_py_err(py, e))?;
+        self.inner
+            .traces()
+            .flush()
+            .map_err(|e| to_py_err(py, e.into()))?;
+        to_py(py, &record)
+    }
+
+    fn execution(&self, py: Python<'_>, execution_id: &Bound<'_, PyAny>) -> PyResult<PyObject> {
+        let record = self
+            .inner
+            .execution(to_uuid(execution_id)?)
+            .map_err(|e| to_py_err(py, e))?;
+        to_py(py, &record)
+    }
+
+    /// The most recent executions of a workflow, newest first.
+    #[pyo3(signature = (workflow_id, limit=20))]
+    fn executions(
+        &self,
+        py: Python<'_>,
+        workflow_id: &Bound<'_, PyAny>,
+        limit: usize,
+    ) -> PyResult<PyObject> {
+        let records = self
+            .inner
+            .executions(to_uuid(workflow_id)?, limit)
+            .map_err(|e| to_py_err(py, e))?;
+        to_py(py, &records)
+    }
+
+    /// The node spans of an execution.
+    fn spans(&self, py: Python<'_>, execution_id: &Bound<'_, PyAny>) -> PyResult<PyObject> {
+        let spans = self
+            .inner
+            .spans(to_uuid(execution_id)?)
+            .map_err(|e| to_py_err(py, e))?;
+        to_py(py, &spans)
+    }
+
+    fn __repr__(&self) -> String {
+        match self.inner.data_dir() {
+            Some(dir) => format!("Engine({:?})", dir.display().to_string()),
+            None => "Engine()".into(),
+        }
+    }
+}
+
+#[pymodule]
+fn handbox(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    m.add("SPEC_VERSION", hb_sdk::SPEC_VERSION)?;
+    m.add("HandboxError", py.get_type::<HandboxError>())?;
+    m.add(
+        "InvalidWorkflowError",
+        py.get_type::<InvalidWorkflowError>(),
+    )?;
+    m.add("NotFoundError", py.get_type::<NotFoundError>())?;
+    m.add_class::<Engine>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::PyDict;
+    use std::ffi::CString;
+
+    #[test]
+    fn python_drives_a_workflow_end_to_end() {
+        Python::with_gil(|py| {
+            let module = PyModule::new(py, "handbox").unwrap();
+            handbox(&module).unwrap();
+            let globals = PyDict::new(py);
+            globals.set_item("handbox", module).unwrap();
+            let packs = concat!(env!("CARGO_MANIFEST_DIR"), "/../../packs");
+            globals.set_item("packs", packs).unwrap();
+
+            let script = CString::new(
+                r#"
+import uuid
+
+engine = handbox.Engine()
+assert engine.load_packs(packs) > 0
+assert engine.tool("core-tools/llm-chat@1.0.0")["tool_id"] == "core-tools/llm-chat"
+
+spec = {
+    "version": handbox.SPEC_VERSION,
+    "id": str(uuid.uuid4()),
+    "meta": {"name": "show", "description": ""},
+    "nodes": [{"kind": "primitive", "id": "show", "tool_ref": "core-tools/display-output"}],
+    "edges": [],
+}
+spec = engine.parse_workflow(spec)
+assert engine.is_valid(spec)
+
+events = []
+record = engine.execute(spec, on_status=events.append)
+assert record["status"] == "completed", record
+assert any(e["node_id"] == "show" for e in events)
+
+assert engine.execution(uuid.UUID(record["execution_id"]))["workflow_id"] == spec["id"]
+assert len(engine.executions(spec["id"])) == 1
+assert [s["node_id"] for s in engine.spans(record["execution_id"])] == ["show"]
+
+try:
+    engine.execution(str(uuid.uuid4()))
+    raise AssertionError("expected NotFoundError")
+except handbox.NotFoundError:
+    pass
+
+broken = dict(spec, nodes=spec["nodes"] * 2)
+try:
+    engine.execute(broken)
+    raise AssertionError("expected InvalidWorkflowError")
+except handbox.InvalidWorkflowError as e:
+    assert isinstance(e, handbox.HandboxError)
+    assert any(i["severity"] == "error" for i in e.args[1])
+"#,
+            )
+            .unwrap();
+            if let Err(e) = py.run(&script, Some(&globals), None) {
+                e.print(py);
+                panic!("python script failed");
+            }
+        });
+    }
+}
@@ -0,0 +1,889 @@
+//! HTTP layer: the `/api/v1` routes described in the crate docs.
+
+use crate::archive;
+use crate::model::{
+    IndexEntry, PackIndex, PackQuery, PackRelease, PublishRequest, RegistryStats, ReviewRequest, SearchQuery,
+    SearchResult, SortBy, SortOrder, WorkflowAuthor, WorkflowListing, WorkflowReview, CATEGORIES,
+};
+use crate::store::RegistryStore;
+use crate::RegistryError;
+use axum::body::Bytes;
+use axum::extract::{DefaultBodyLimit, Path, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::Utc;
+use hb_core::error::AppError;
+use hb_core::graph::{NodeEntry, WorkflowSpec};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+
+/// Default page size of workflow searches.
+const DEFAULT_PAGE_SIZE: usize = 20;
+const MAX_PAGE_SIZE: usize = 100;
+
+/// Shared state of the routes.
+pub struct Registry {
+    store: Arc<dyn RegistryStore>,
+    /// Upload token → publisher name.
+    publishers: HashMap<String, String>,
+}
+
+impl Registry {
+    pub fn new(store: Arc<dyn RegistryStore>, publishers: HashMap<String, String>) -> Self {
+        Self { store, publishers }
+    }
+
+    /// The publisher whose token the request carries.
+    fn publisher(&self, headers: &HeaderMap) -> Result<String, RegistryError> {
+        headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .and_then(|token| self.publishers.get(token.trim()))
+            .cloned()
+            .ok_or(RegistryError::Unauthorized)
+    }
+
+    /// Run a store call on the blocking thread pool.
+    async fn blocking<T, F>(&self, f: F) -> Result<T, RegistryError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&dyn RegistryStore) -> Result<T, RegistryError> + Send + 'static,
+    {
+        let store = self.store.clone();
+        tokio::task::spawn_blocking(move || f(store.as_ref()))
+            .await
+            .map_err(|e| RegistryError::Database(e.to_string()))?
+    }
+}
+
+impl IntoResponse for RegistryError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            RegistryError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            RegistryError::NotFound(_) => StatusCode::NOT_FOUND,
+            RegistryError::Conflict(_) => StatusCode::CONFLICT,
+            RegistryError::Invalid(_) => StatusCode::BAD_REQUEST,
+            RegistryError::Unauthorized => StatusCode::UNAUTHORIZED,
+            RegistryError::Forbidden(_) => StatusCode::FORBIDDEN,
+        };
+        if status.is_server_error() {
+            tracing::error!("{self}");
+        }
+        (status, Json(AppError::from(self))).into_response()
+    }
+}
+
+type ApiResult<T> = Result<T, RegistryError>;
+
+/// Build the router; uploads larger than `max_upload_bytes` are refused.
+pub fn router(registry: Arc<Registry>, max_upload_bytes: usize) -> Router {
+    let api = Router::new()
+        .route("/index.json", get(pack_index))
+        .route("/packs", get(list_packs).post(upload_pack))
+        .route("/packs/{id}/{file}", get(download_pack))
+        .route("/workflows", get(search_workflows).post(publish_workflow))
+        .route("/workflows/{id}", get(get_workflow).put(update_workflow))
+        .route("/workflows/{id}/download", get(download_workflow))
+        .route(
+            "/workflows/{id}/reviews",
+            get(list_reviews).post(post_review),
+        )
+        .route("/stats", get(stats));
+    Router::new()
+        .nest("/api/v1", api)
+        .layer(DefaultBodyLimit::max(max_upload_bytes))
+        .with_state(registry)
+}
+
+// ---------------------------------------------------------------------------
+// Packs
+// ---------------------------------------------------------------------------
+
+async fn pack_index(
+    State(registry): State<Arc<Registry>>,
+    Query(query): Query<PackQuery>,
+) -> ApiResult<Json<PackIndex>> {
+    let packs = registry.blocking(|store| store.list_packs()).await?;
+    let mut index = latest_releases(packs);
+    if let Some(kind) = query.kind {
+        index.packs.retain(|entry| entry.contents.contains(&kind));
+    }
+    Ok(Json(index))
+}
+
+/// The highest version of each pack, pointing at its download route.
+fn latest_releases(packs: Vec<PackRelease>) -> PackIndex {
+    let mut latest: BTreeMap<String, (semver::Version, PackRelease)> = BTreeMap::new();
+    for release in packs {
+        let Ok(version) = semver::Version::parse(&release.version) else {
+            continue;
+        };
+        if latest
+            .get(&release.id)
+            .is_none_or(|(current, _)| version > *current)
+        {
+            latest.insert(release.id.clone(), (version, release));
+        }
+    }
+    PackIndex {
+        packs: latest
+            .into_values()
+            .map(|(_, r)| IndexEntry {
+                location: format!("packs/{}/{}.tar.gz", r.id, r.version),
+                id: r.id,
+                version: r.version,
+                changelog: r.changelog,
+                contents: r.contents,
+            })
+            .collect(),
+    }
+}
+
+async fn list_packs(
+    State(registry): State<Arc<Registry>>,
+    Query(query): Query<PackQuery>,
+) -> ApiResult<Json<Vec<PackRelease>>> {
+    let mut packs = registry.blocking(|store| store.list_packs()).await?;
+    if let Some(kind) = query.kind {
+        packs.retain(|release| release.contents.contains(&kind));
+    }
+    Ok(Json(packs))
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct UploadQuery {
+    changelog: Option<String>,
+}
+
+async fn upload_pack(
+    State(registry): State<Arc<Registry>>,
+    headers: HeaderMap,
+    Query(query): Query<UploadQuery>,
+    body: Bytes,
+) -> ApiResult<(StatusCode, Json<PackRelease>)> {
+    let publisher = registry.publisher(&headers)?;
+    let inspected = archive::inspect(&body)?;
+    let manifest = inspected.manifest;
+    let contents = manifest.content_kinds();
+    let release = PackRelease {
+        id: manifest.id,
+        version: manifest.version,
+        name: manifest.name,
+        description: manifest.description,
+        author: manifest.author,
+        changelog: query.changelog.unwrap_or_default(),
+        sha256: inspected.sha256,
+        size: inspected.size,
+        publisher,
+        downloads: 0,
+        published_at: Utc::now(),
+        contents,
+    };
+
+    let stored = release.clone();
+    registry
+        .blocking(move |store| {
+            let owner = store
+                .list_packs()?
+                .into_iter()
+                .find(|r| r.id == stored.id)
+                .map(|r| r.publisher);
+            if owner.is_some_and(|owner| owner != stored.publisher) {
+                return Err(RegistryError::Forbidden(format!(
+                    "pack '{}' belongs to another publisher",
+                    stored.id
+                )));
+            }
+            store.insert_pack(&stored, &body)
+        })
+        .await?;
+    tracing::info!(pack = %release.id, version = %release.version, publisher = %release.publisher, "pack published");
+    Ok((StatusCode::CREATED, Json(release)))
+}
+
+async fn download_pack(
+    State(registry): State<Arc<Registry>>,
+    Path((id, file)): Path<(String, String)>,
+) -> ApiResult<impl IntoResponse> {
+    let not_found = || RegistryError::NotFound(format!("{id}/{file}"));
+    let version = file
+        .strip_suffix(".tar.gz")
+        .ok_or_else(not_found)?
+        .to_string();
+    let pack_id = id.clone();
+    let archive = registry
+        .blocking(move |store| store.download_pack(&pack_id, &version))
+        .await?
+        .ok_or_else(not_found)?;
+    Ok(([(header::CONTENT_TYPE, "application/gzip")], archive))
+}
+
+// ---------------------------------------------------------------------------
+// Workflows
+// ---------------------------------------------------------------------------
+
+async fn search_workflows(
+    State(registry): State<Arc<Registry>>,
+    Query(query): Query<SearchQuery>,
+) -> ApiResult<Json<SearchResult>> {
+    let mut workflows = registry.blocking(|store| store.list_workflows()).await?;
+    fill_author_stats(&mut workflows);
+    Ok(Json(search(workflows, &query)))
+}
+
+/// Filter, sort and page `workflows`.
+pub fn search(workflows: Vec<WorkflowListing>, query: &SearchQuery) -> SearchResult {
+    let text = query.query.as_deref().map(str::to_lowercase);
+    let mut matches: Vec<WorkflowListing> = workflows
+        .into_iter()
+        .filter(|w| {
+            text.as_deref().is_none_or(|text| {
+                w.name.to_lowercase().contains(text)
+                    || w.description.to_lowercase().contains(text)
+                    || w.tags.iter().any(|t| t.to_lowercase().contains(text))
+            })
+        })
+        .filter(|w| query.category.as_ref().is_none_or(|c| w.category == *c))
+        .filter(|w| query.tag.as_ref().is_none_or(|t| w.tags.contains(t)))
+        .filter(|w| query.author_id.as_ref().is_none_or(|a| w.author.id == *a))
+        .filter(|w| query.min_rating.is_none_or(|r| w.rating >= r))
+        .collect();
+
+    matches.sort_by(|a, b| {
+        let by_rating = || {
+            b.rating
+                .total_cmp(&a.rating)
+                .then(b.rating_count.cmp(&a.rating_count))
+        };
+        let ordering = match query.sort_by {
+            SortBy::Popular | SortBy::Trending | SortBy::Downloads => {
+                b.downloads.cmp(&a.downloads).then_with(by_rating)
+            }
+            SortBy::Rating => by_rating(),
+            SortBy::Recent => b.published_at.cmp(&a.published_at),
+        };
+        match query.sort_order {
+            SortOrder::Desc => ordering,
+            SortOrder::Asc => ordering.reverse(),
+        }
+    });
+
+    let page = query.page.unwrap_or(1).max(1);
+    let page_size = query
+        .page_size
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    let total = matches.len();
+    let workflows: Vec<_> = matches
+        .into_iter()
+        .skip((page - 1) * page_size)
+        .take(page_size)
+        .collect();
+    SearchResult {
+        has_more: page * page_size < total,
+        workflows,
+        total,
+        page,
+        page_size,
+    }
+}
+
+/// Fill in each author's published workflow count and total downloads.
+fn fill_author_stats(workflows: &mut [WorkflowListing]) {
+    let mut stats: HashMap<String, (usize, u64)> = HashMap::new();
+    for w in workflows.iter() {
+        let entry = stats.entry(w.author.id.clone()).or_default();
+        entry.0 += 1;
+        entry.1 += w.downloads;
+    }
+    for w in workflows.iter_mut() {
+        (w.author.published_count, w.author.total_downloads) = stats[&w.author.id];
+    }
+}
+
+/// Load one workflow (with its data if `download`) and its author's stats.
+async fn load_workflow(
+    registry: &Registry,
+    id: String,
+    download: bool,
+) -> ApiResult<WorkflowListing> {
+    registry
+        .blocking(move |store| {
+            let found = if download {
+                store.download_workflow(&id)?
+            } else {
+                store.get_workflow(&id)?
+            };
+            let mut workflow =
+                found.ok_or_else(|| RegistryError::NotFound(format!("workflow {id}")))?;
+            let mut all = store.list_workflows()?;
+            fill_author_stats(&mut all);
+            if let Some(listed) = all.into_iter().find(|w| w.id == id) {
+                workflow.author = listed.author;
+            }
+            Ok(workflow)
+        })
+        .await
+}
+
+async fn get_workflow(
+    State(registry): State<Arc<Registry>>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<WorkflowListing>> {
+    Ok(Json(load_workflow(&registry, id, false).await?))
+}
+
+async fn download_workflow(
+    State(registry): State<Arc<Registry>>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<WorkflowListing>> {
+    Ok(Json(load_workflow(&registry, id, true).await?))
+}
+
+/// Validate a publish request into a listing for `publisher`.
+fn listing(
+    id: String,
+    publisher: &str,
+    request: PublishRequest,
+) -> Result<WorkflowListing, RegistryError> {
+    if request.name.trim().is_empty() {
+        return Err(RegistryError::Invalid("name must not be empty".into()));
+    }
+    if !CATEGORIES.contains(&request.category.as_str()) {
+        return Err(RegistryError::Invalid(format!(
+            "unknown category '{}'",
+            request.category
+        )));
+    }
+    let version = request.version.unwrap_or_else(|| "1.0.0".into());
+    semver::Version::parse(&version)
+        .map_err(|e| RegistryError::Invalid(format!("invalid version '{version}': {e}")))?;
+    let data: serde_json::Value = serde_json::from_str(&request.workflow_data)
+        .map_err(|e| RegistryError::Invalid(format!("workflow_data is not JSON: {e}")))?;
+    // Listings show what a workflow needs when it is a workflow spec; other
+    // payloads are stored as they are.
+    let (node_count, required_tools) = match WorkflowSpec::from_value(data) {
+        Ok(upgraded) => (upgraded.spec.nodes.len(), tool_refs(&upgraded.spec.nodes)),
+        Err(_) => (0, Vec::new()),
+    };
+
+    let now = Utc::now();
+    Ok(WorkflowListing {
+        id,
+        name: request.name,
+        description: request.description,
+        author: WorkflowAuthor::new(publisher),
+        version,
+        category: request.category,
+        tags: request.tags,
+        downloads: 0,
+        likes: 0,
+        rating: 0.0,
+        rating_count: 0,
+        node_count,
+        estimated_runtime: "unknown".into(),
+        required_tools,
+        required_providers: Vec::new(),
+        published_at: now,
+        updated_at: now,
+        workflow_data: request.workflow_data,
+        is_verified: false,
+        is_featured: false,
+        is_official: false,
+        license: request.license,
+        preview_images: request.preview_images,
+        readme: request.readme,
+    })
+}
+
+/// Tool IDs (without `@version`) used by primitive nodes, at any depth.
+fn tool_refs(nodes: &[NodeEntry]) -> Vec<String> {
+    fn collect(nodes: &[NodeEntry], out: &mut BTreeSet<String>) {
+        for node in nodes {
+            match node {
+                NodeEntry::Primitive(n) => {
+                    let id = n.tool_ref.split('@').next().unwrap_or(&n.tool_ref);
+                    out.insert(id.to_string());
+                }
+                NodeEntry::Composite(c) => collect(&c.subgraph.nodes, out),
+                NodeEntry::Loop(l) => collect(&l.body.nodes, out),
+                NodeEntry::MapReduce(m) => collect(&m.map.nodes, out),
+                NodeEntry::Conditional(c) => {
+                    for branch in &c.branches {
+                        collect(&branch.body.nodes, out);
+                    }
+                    if let Some(default) = &c.default_branch {
+                        collect(&default.nodes, out);
+                    }
+                }
+                // Referenced workflows are not part of the listing
+                NodeEntry::WorkflowRef(_) => {}
+            }
+        }
+    }
+    let mut out = BTreeSet::new();
+    collect(nodes, &mut out);
+    out.into_iter().collect()
+}
+
+async fn publish_workflow(
+    State(registry): State<Arc<Registry>>,
+    headers: HeaderMap,
+    Json(request): Json<PublishRequest>,
+) -> ApiResult<(StatusCode, Json<WorkflowListing>)> {
+    let publisher = registry.publisher(&headers)?;
+    let workflow = listing(uuid::Uuid::new_v4().to_string(), &publisher, request)?;
+    let stored = workflow.clone();
+    registry
+        .blocking(move |store| store.upsert_workflow(&stored))
+        .await?;
+    tracing::info!(workflow = %workflow.id, publisher = %publisher, "workflow published");
+    Ok((
+        StatusCode::CREATED,
+        Json(WorkflowListing {
+            workflow_data: String::new(),
+            ..workflow
+        }),
+    ))
+}
+
+async fn update_workflow(
+    State(registry): State<Arc<Registry>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(request): Json<PublishRequest>,
+) -> ApiResult<Json<WorkflowListing>> {
+    let publisher = registry.publisher(&headers)?;
+    if request.version.is_none() {
+        return Err(RegistryError::Invalid(
+            "updates must name their version".into(),
+        ));
+    }
+    let mut workflow = listing(id.clone(), &publisher, request)?;
+    let updated = registry
+        .blocking(move |store| {
+            let current = store
+                .get_workflow(&id)?
+                .ok_or_else(|| RegistryError::NotFound(format!("workflow {id}")))?;
+            if current.author.id != workflow.author.id {
+                return Err(RegistryError::Forbidden(format!(
+                    "workflow {id} belongs to another publisher"
+                )));
+            }
+            let newer = match (
+                semver::Version::parse(&workflow.version),
+                semver::Version::parse(&current.version),
+            ) {
+                (Ok(new), Ok(old)) => new > old,
+                (Ok(_), Err(_)) => true,
+                _ => false,
+            };
+            if !newer {
+                return Err(RegistryError::Conflict(format!(
+                    "version {} is not newer than {}",
+                    workflow.version, current.version
+                )));
+            }
+            workflow.published_at = current.published_at;
+            store.upsert_workflow(&workflow)?;
+            store.get_workflow(&id)
+        })
+        .await?;
+    updated
+        .map(Json)
+        .ok_or_else(|| RegistryError::Database("workflow vanished during update".into()))
+}
+
+// ---------------------------------------------------------------------------
+// Reviews and stats
+// ---------------------------------------------------------------------------
+
+async fn list_reviews(
+    State(registry): State<Arc<Registry>>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<Vec<WorkflowReview>>> {
+    let reviews = registry
+        .blocking(move |store| {
+            if store.get_workflow(&id)?.is_none() {
+                return Err(RegistryError::NotFound(format!("workflow {id}")));
+            }
+            store.list_reviews(&id)
+        })
+        .await?;
+    Ok(Json(reviews))
+}
+
+async fn post_review(
+    State(registry): State<Arc<Registry>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(request): Json<ReviewRequest>,
+) -> ApiResult<(StatusCode, Json<WorkflowReview>)> {
+    let publisher = registry.publisher(&headers)?;
+    if !(1..=5).contains(&request.rating) {
+        return Err(RegistryError::Invalid("rating must be 1 to 5".into()));
+    }
+    let now = Utc::now();
+    let review = WorkflowReview {
+        id: uuid::Uuid::new_v4().to_string(),
+        workflow_id: id.clone(),
+        author: WorkflowAuthor::new(&publisher),
+        rating: request.rating,
+        title: request.title,
+        content: request.content,
+        created_at: now,
+        updated_at: now,
+        helpful_count: 0,
+        is_verified_purchase: false,
+    };
+    let stored = registry
+        .blocking(move |store| {
+            if store.get_workflow(&id)?.is_none() {
+                return Err(RegistryError::NotFound(format!("workflow {id}")));
+            }
+            store.upsert_review(&review)?;
+            // Return the stored row: a replaced review keeps its ID
+            Ok(store
+                .list_reviews(&id)?
+                .into_iter()
+                .find(|r| r.author.id == review.author.id)
+                .unwrap_or(review))
+        })
+        .await?;
+    Ok((StatusCode::CREATED, Json(stored)))
+}
+
+async fn stats(State(registry): State<Arc<Registry>>) -> ApiResult<Json<RegistryStats>> {
+    let (packs, workflows) = registry
+        .blocking(|store| Ok((store.list_packs()?, store.list_workflows()?)))
+        .await?;
+    let publishers: BTreeSet<&str> = packs
+        .iter()
+        .map(|p| p.publisher.as_str())
+        .chain(workflows.iter().map(|w| w.author.id.as_str()))
+        .collect();
+    Ok(Json(RegistryStats {
+        packs: packs.iter().map(|p| &p.id).collect::<BTreeSet<_>>().len(),
+        pack_releases: packs.len(),
+        pack_downloads: packs.iter().map(|p| p.downloads).sum(),
+        workflows: workflows.len(),
+        workflow_downloads: workflows.iter().map(|w| w.downloads).sum(),
+        reviews: workflows.iter().map(|w| w.rating_count).sum(),
+        publishers: publishers.len(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::{build_archive, manifest_json};
+    use crate::store::SqliteRegistryStore;
+    use axum::body::Body;
+    use axum::http::Request;
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        let store = Arc::new(SqliteRegistryStore::in_memory().unwrap());
+        let publishers = HashMap::from([
+            ("alice-token".to_string(), "alice".to_string()),
+            ("bob-token".to_string(), "bob".to_string()),
+        ]);
+        router(Arc::new(Registry::new(store, publishers)), 1024 * 1024)
+    }
+
+    async fn send(
+        app: &Router,
+        method: &str,
+        uri: &str,
+        token: Option<&str>,
+        body: Body,
+    ) -> (StatusCode, Vec<u8>) {
+        let mut request = Request::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        if method != "GET" {
+            request = request.header(header::CONTENT_TYPE, "application/json");
+        }
+        let response = app
+            .clone()
+            .oneshot(request.body(body).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, bytes.to_vec())
+    }
+
+    async fn send_json(
+        app: &Router,
+        method: &str,
+        uri: &str,
+        token: Option<&str>,
+        body: Value,
+    ) -> (StatusCode, Value) {
+        let (status, bytes) = send(app, method, uri, token, Body::from(body.to_string())).await;
+        (
+            status,
+            serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+        )
+    }
+
+    #[tokio::test]
+    async fn packs_upload_index_and_download() {
+        let app = app();
+        let archive = build_archive(&manifest_json("rag-pack", "1.0.0"), None);
+
+        let (status, _) = send(&app, "POST", "/api/v1/packs", None, archive.clone().into()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, body) = send(
+            &app,
+            "POST",
+            "/api/v1/packs?changelog=First",
+            Some("alice-token"),
+            archive.clone().into(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        let release: PackRelease = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            (release.publisher.as_str(), release.changelog.as_str()),
+            ("alice", "First")
+        );
+
+        let (status, _) = send(
+            &app,
+            "POST",
+            "/api/v1/packs",
+            Some("alice-token"),
+            archive.clone().into(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let newer = build_archive(&manifest_json("rag-pack", "1.1.0"), Some("rag-pack"));
+        let (status, _) = send(
+            &app,
+            "POST",
+            "/api/v1/packs",
+            Some("bob-token"),
+            newer.clone().into(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, _) = send(
+            &app,
+            "POST",
+            "/api/v1/packs",
+            Some("alice-token"),
+            newer.into(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let (_, index) = send_json(&app, "GET", "/api/v1/index.json", None, Value::Null).await;
+        assert_eq!(
+            index,
+            json!({ "packs": [{ "id": "rag-pack", "version": "1.1.0", "changelog": "",
+                                "location": "packs/rag-pack/1.1.0.tar.gz" }] })
+        );
+
+        let (status, body) = send(
+            &app,
+            "GET",
+            "/api/v1/packs/rag-pack/1.0.0.tar.gz",
+            None,
+            Body::empty(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, archive);
+        let (status, _) = send(
+            &app,
+            "GET",
+            "/api/v1/packs/rag-pack/2.0.0.tar.gz",
+            None,
+            Body::empty(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (_, stats) = send_json(&app, "GET", "/api/v1/stats", None, Value::Null).await;
+        assert_eq!(stats["packs"], 1);
+        assert_eq!(stats["pack_releases"], 2);
+        assert_eq!(stats["pack_downloads"], 1);
+    }
+
+    #[tokio::test]
+    async fn packs_are_indexed_by_content_kind() {
+        let app = app();
+        let mut evals = manifest_json("summary-evals", "0.1.0");
+        evals["eval_suites"] = json!(["evals/summaries.json"]);
+        let mut recipes = manifest_json("recipes", "1.0.0");
+        recipes["compile_templates"] = json!(["compile/rag.json"]);
+        recipes["prompt_libraries"] = json!(["prompts/rag.json"]);
+        for manifest in [evals, recipes, manifest_json("rag-pack", "1.0.0")] {
+            let (status, _) = send(
+                &app,
+                "POST",
+                "/api/v1/packs",
+                Some("alice-token"),
+                build_archive(&manifest, None).into(),
+            )
+            .await;
+            assert_eq!(status, StatusCode::CREATED);
+        }
+
+        let (_, index) = send_json(
+            &app,
+            "GET",
+            "/api/v1/index.json?kind=prompt_library",
+            None,
+            Value::Null,
+        )
+        .await;
+        assert_eq!(
+            index,
+            json!({ "packs": [{ "id": "recipes", "version": "1.0.0", "changelog": "",
+                                "location": "packs/recipes/1.0.0.tar.gz",
+                                "contents": ["compile_template", "prompt_library"] }] })
+        );
+        let (_, packs) =
+            send_json(&app, "GET", "/api/v1/packs?kind=eval_suite", None, Value::Null).await;
+        assert_eq!(packs.as_array().unwrap().len(), 1);
+        assert_eq!(packs[0]["id"], "summary-evals");
+        let (_, packs) = send_json(&app, "GET", "/api/v1/packs", None, Value::Null).await;
+        assert_eq!(packs.as_array().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn workflows_publish_search_review_and_update() {
+        let app = app();
+        let spec = json!({
+            "version": "0.1.0",
+            "meta": { "name": "Summarize", "description": "" },
+            "nodes": [{ "kind": "primitive", "id": "read", "tool_ref": "core-tools/file-read@1.0.0" }],
+            "edges": [],
+        });
+        let publish = |name: &str, category: &str| {
+            json!({
+                "name": name, "description": "Summarize PDFs", "category": category,
+                "tags": ["pdf"], "workflow_data": spec.to_string(), "license": "MIT",
+            })
+        };
+
+        let (status, _) = send_json(
+            &app,
+            "POST",
+            "/api/v1/workflows",
+            Some("alice-token"),
+            publish("Bad", "nonsense"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, created) = send_json(
+            &app,
+            "POST",
+            "/api/v1/workflows",
+            Some("alice-token"),
+            publish("PDF summary", "document"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        let id = created["id"].as_str().unwrap().to_string();
+        assert_eq!(created["version"], "1.0.0");
+        assert_eq!(created["required_tools"], json!(["core-tools/file-read"]));
+        send_json(
+            &app,
+            "POST",
+            "/api/v1/workflows",
+            Some("bob-token"),
+            publish("CSV cleanup", "data-processing"),
+        )
+        .await;
+
+        let (_, found) = send_json(
+            &app,
+            "GET",
+            "/api/v1/workflows?query=pdf%20sum",
+            None,
+            Value::Null,
+        )
+        .await;
+        assert_eq!(found["total"], 1);
+        assert_eq!(found["workflows"][0]["workflow_data"], "");
+        let (_, page) = send_json(
+            &app,
+            "GET",
+            "/api/v1/workflows?sort_by=recent&page=2&page_size=1",
+            None,
+            Value::Null,
+        )
+        .await;
+        assert_eq!(
+            (page["total"].clone(), page["has_more"].clone()),
+            (json!(2), json!(false))
+        );
+        assert_eq!(page["workflows"][0]["name"], "PDF summary");
+
+        let (_, downloaded) = send_json(
+            &app,
+            "GET",
+            &format!("/api/v1/workflows/{id}/download"),
+            None,
+            Value::Null,
+        )
+        .await;
+        assert_eq!(downloaded["workflow_data"], spec.to_string());
+        assert_eq!(downloaded["author"]["total_downloads"], 1);
+
+        let reviews = format!("/api/v1/workflows/{id}/reviews");
+        let (status, _) = send_json(
+            &app,
+            "POST",
+            &reviews,
+            Some("bob-token"),
+            json!({ "rating": 6 }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = send_json(
+            &app,
+            "POST",
+            &reviews,
+            Some("bob-token"),
+            json!({ "rating": 4, "title": "Useful" }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        let (_, listed) = send_json(&app, "GET", &reviews, None, Value::Null).await;
+        assert_eq!(listed.as_array().unwrap().len(), 1);
+
+        let uri = format!("/api/v1/workflows/{id}");
+        let mut update = publish("PDF summary", "document");
+        update["version"] = json!("1.0.0");
+        let (status, _) = send_json(&app, "PUT", &uri, Some("alice-token"), update.clone()).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        update["version"] = json!("1.1.0");
+        let (status, _) = send_json(&app, "PUT", &uri, Some("bob-token"), update.clone()).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, updated) = send_json(&app, "PUT", &uri, Some("alice-token"), update).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(updated["version"], "1.1.0");
+        assert_eq!(
+            (updated["downloads"].clone(), updated["rating"].clone()),
+            (json!(1), json!(4.0))
+        );
+
+        let (status, error) =
+            send_json(&app, "GET", "/api/v1/workflows/missing", None, Value::Null).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(error["code"], "not_found");
+    }
+}
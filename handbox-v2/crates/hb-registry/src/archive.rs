@@ -0,0 +1,158 @@
+//! Pack archive inspection: find and validate the manifest of an uploaded
+//! `.tar.gz` before it is stored.
+
+use crate::RegistryError;
+use hb_core::pack::PackManifest;
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::{Component, Path};
+
+/// Manifests larger than this are rejected.
+const MAX_MANIFEST_BYTES: u64 = 1024 * 1024;
+
+/// What the registry needs to know about an uploaded archive.
+#[derive(Debug, Clone)]
+pub struct PackArchive {
+    pub manifest: PackManifest,
+    pub sha256: String,
+    pub size: u64,
+}
+
+/// Read `manifest.json` from the archive root or from a single top-level
+/// directory (the layouts the app's installer accepts) and validate it.
+pub fn inspect(bytes: &[u8]) -> Result<PackArchive, RegistryError> {
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(bytes));
+    let entries = archive
+        .entries()
+        .map_err(|e| RegistryError::Invalid(format!("not a .tar.gz archive: {e}")))?;
+
+    let mut manifest = None;
+    for entry in entries {
+        let mut entry =
+            entry.map_err(|e| RegistryError::Invalid(format!("corrupt archive: {e}")))?;
+        let path = entry
+            .path()
+            .map_err(|e| RegistryError::Invalid(format!("corrupt archive: {e}")))?
+            .into_owned();
+        if !is_manifest_path(&path) {
+            continue;
+        }
+        if entry.size() > MAX_MANIFEST_BYTES {
+            return Err(RegistryError::Invalid("manifest.json is too large".into()));
+        }
+        let mut content = String::new();
+        entry
+            .read_to_string(&mut content)
+            .map_err(|e| RegistryError::Invalid(format!("unreadable manifest.json: {e}")))?;
+        manifest = Some(content);
+        break;
+    }
+
+    let content =
+        manifest.ok_or_else(|| RegistryError::Invalid("archive has no manifest.json".into()))?;
+    let manifest: PackManifest = serde_json::from_str(&content)
+        .map_err(|e| RegistryError::Invalid(format!("invalid manifest.json: {e}")))?;
+    validate_id(&manifest.id)?;
+    semver::Version::parse(&manifest.version).map_err(|e| {
+        RegistryError::Invalid(format!("invalid version '{}': {e}", manifest.version))
+    })?;
+
+    Ok(PackArchive {
+        manifest,
+        sha256: hex::encode(Sha256::digest(bytes)),
+        size: bytes.len() as u64,
+    })
+}
+
+fn is_manifest_path(path: &Path) -> bool {
+    let parts: Vec<_> = path
+        .components()
+        .filter(|c| !matches!(c, Component::CurDir))
+        .collect();
+    let named = |c: &Component| matches!(c, Component::Normal(_));
+    match parts.as_slice() {
+        [file] => named(file) && file.as_os_str() == "manifest.json",
+        [dir, file] => named(dir) && named(file) && file.as_os_str() == "manifest.json",
+        _ => false,
+    }
+}
+
+/// Pack IDs end up in URLs and directory names.
+pub fn validate_id(id: &str) -> Result<(), RegistryError> {
+    let valid = !id.is_empty()
+        && id.len() <= 128
+        && !id.starts_with('.')
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(RegistryError::Invalid(format!("invalid pack id '{id}'")))
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn build_archive(manifest: &serde_json::Value, wrapper: Option<&str>) -> Vec<u8> {
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+        Vec::new(),
+        flate2::Compression::default(),
+    ));
+    let content = serde_json::to_vec(manifest).unwrap();
+    let path = match wrapper {
+        Some(dir) => format!("{dir}/manifest.json"),
+        None => "manifest.json".to_string(),
+    };
+    let mut header = tar::Header::new_gnu();
+    header.set_size(content.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, path, content.as_slice())
+        .unwrap();
+    builder.into_inner().unwrap().finish().unwrap()
+}
+
+#[cfg(test)]
+pub(crate) fn manifest_json(id: &str, version: &str) -> serde_json::Value {
+    serde_json::json!({
+        "pack_version": "0.1.0",
+        "id": id,
+        "version": version,
+        "name": "Test Pack",
+        "description": "For tests",
+        "author": "Tests",
+        "license": "MIT",
+        "platform_version": "2.0.0",
+        "category": "custom",
+        "tools": [],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_manifest_at_root_or_in_one_directory() {
+        let root = build_archive(&manifest_json("rag-pack", "1.2.0"), None);
+        let inspected = inspect(&root).unwrap();
+        assert_eq!(inspected.manifest.id, "rag-pack");
+        assert_eq!(inspected.size, root.len() as u64);
+        assert_eq!(inspected.sha256.len(), 64);
+
+        let wrapped = build_archive(&manifest_json("rag-pack", "1.2.0"), Some("rag-pack"));
+        assert_eq!(inspect(&wrapped).unwrap().manifest.version, "1.2.0");
+
+        let nested = build_archive(&manifest_json("rag-pack", "1.2.0"), Some("a/b"));
+        assert!(matches!(inspect(&nested), Err(RegistryError::Invalid(_))));
+        let bad_version = build_archive(&manifest_json("rag-pack", "latest"), None);
+        assert!(matches!(
+            inspect(&bad_version),
+            Err(RegistryError::Invalid(_))
+        ));
+        let bad_id = build_archive(&manifest_json("../etc", "1.0.0"), None);
+        assert!(matches!(inspect(&bad_id), Err(RegistryError::Invalid(_))));
+        assert!(inspect(b"not gzip").is_err());
+    }
+}
@@ -0,0 +1,80 @@
+//! hb-registry: a self-hosted marketplace registry, so organizations can
+//! share packs and workflows behind their own firewall.
+//!
+//! The server speaks JSON over HTTP under `/api/v1`:
+//!
+//! - `GET index.json` → the latest release of every pack, in the format
+//!   pack sources serve, so `{server}/api/v1` can be added as a pack source
+//! - `GET packs` → every pack release
+//! - `?kind=` on either of the above (`tool`, `compile_template`,
+//!   `prompt_library`, `eval_suite`, ...) → only packs shipping that kind of
+//!   content, so a client can browse a library of recipes or evals
+//! - `POST packs` with a pack `.tar.gz` as body (and an optional
+//!   `?changelog=`) → the stored release; `409 Conflict` if that version
+//!   exists
+//! - `GET packs/{id}/{version}.tar.gz` → the archive (counted as a download)
+//! - `GET workflows?query=&category=&tag=&author_id=&min_rating=&sort_by=&sort_order=&page=&page_size=`
+//!   → a page of listings, without workflow data
+//! - `POST workflows` / `PUT workflows/{id}` with a publish request → the
+//!   stored listing; an update needs a higher `version` than the current one
+//! - `GET workflows/{id}` → the listing; `GET workflows/{id}/download` →
+//!   the listing with its workflow data (counted as a download)
+//! - `GET`/`POST workflows/{id}/reviews` → reviews; one per author, posting
+//!   again replaces it
+//! - `GET stats` → totals and download counts
+//!
+//! Reads are open. Writes carry `Authorization: Bearer <token>`; each token
+//! is issued to a named publisher, and only the publisher of a pack or
+//! workflow can release new versions of it. Errors are returned as
+//! [`hb_core::error::AppError`] JSON.
+
+pub mod api;
+pub mod archive;
+pub mod model;
+pub mod store;
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum RegistryError {
+    #[error("database error: {0}")]
+    Database(String),
+    #[error("not found: {0}")]
+    NotFound(String),
+    #[error("conflict: {0}")]
+    Conflict(String),
+    #[error("invalid request: {0}")]
+    Invalid(String),
+    #[error("missing or unknown token")]
+    Unauthorized,
+    #[error("forbidden: {0}")]
+    Forbidden(String),
+}
+
+impl From<rusqlite::Error> for RegistryError {
+    fn from(e: rusqlite::Error) -> Self {
+        Self::Database(e.to_string())
+    }
+}
+
+impl From<postgres::Error> for RegistryError {
+    fn from(e: postgres::Error) -> Self {
+        Self::Database(e.to_string())
+    }
+}
+
+impl From<RegistryError> for hb_core::error::AppError {
+    fn from(e: RegistryError) -> Self {
+        use hb_core::error::{AppError, ErrorCode};
+        let code = match e {
+            RegistryError::Database(_) => ErrorCode::Database,
+            RegistryError::NotFound(_) => ErrorCode::NotFound,
+            RegistryError::Conflict(_) => ErrorCode::Conflict,
+            RegistryError::Invalid(_) => ErrorCode::InvalidInput,
+            RegistryError::Unauthorized | RegistryError::Forbidden(_) => {
+                ErrorCode::PermissionDenied
+            }
+        };
+        AppError::new(code, e.to_string())
+    }
+}
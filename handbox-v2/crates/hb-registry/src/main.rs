@@ -0,0 +1,105 @@
+//! `hb-registry` — run a self-hosted marketplace registry.
+//!
+//! ```text
+//! hb-registry [--listen 0.0.0.0:8787] [--database registry.db]
+//!             [--token NAME=TOKEN]... [--max-upload-mb 64]
+//! ```
+//!
+//! `--database` takes a SQLite file path or a `postgres://` URL. Publisher
+//! tokens can also be given as `HB_REGISTRY_TOKENS=alice=t1,bob=t2`, which
+//! keeps them out of the process list.
+
+use hb_registry::api::{self, Registry};
+use hb_registry::store;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const USAGE: &str = "usage: hb-registry [--listen ADDR] [--database PATH|URL] \
+                     [--token NAME=TOKEN]... [--max-upload-mb N]";
+
+struct Options {
+    listen: String,
+    database: String,
+    /// Token → publisher name.
+    publishers: HashMap<String, String>,
+    max_upload_bytes: usize,
+}
+
+fn parse_token(spec: &str, publishers: &mut HashMap<String, String>) -> Result<(), String> {
+    let (name, token) = spec
+        .split_once('=')
+        .filter(|(name, token)| !name.is_empty() && !token.is_empty())
+        .ok_or_else(|| format!("invalid token '{spec}', expected NAME=TOKEN"))?;
+    publishers.insert(token.to_string(), name.to_string());
+    Ok(())
+}
+
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        listen: "0.0.0.0:8787".into(),
+        database: "registry.db".into(),
+        publishers: HashMap::new(),
+        max_upload_bytes: 64 * 1024 * 1024,
+    };
+    if let Ok(tokens) = std::env::var("HB_REGISTRY_TOKENS") {
+        for spec in tokens.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            parse_token(spec, &mut options.publishers)?;
+        }
+    }
+
+    let mut args = args;
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{arg} needs a value"));
+        match arg.as_str() {
+            "--listen" => options.listen = value()?,
+            "--database" => options.database = value()?,
+            "--token" => parse_token(&value()?, &mut options.publishers)?,
+            "--max-upload-mb" => {
+                let mb: usize = value()?
+                    .parse()
+                    .map_err(|e| format!("invalid --max-upload-mb: {e}"))?;
+                options.max_upload_bytes = mb * 1024 * 1024;
+            }
+            "-h" | "--help" => return Err(USAGE.into()),
+            other => return Err(format!("unknown argument '{other}'\n{USAGE}")),
+        }
+    }
+    Ok(options)
+}
+
+fn main() {
+    tracing_subscriber::fmt::init();
+
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{message}");
+            std::process::exit(2);
+        }
+    };
+    if options.publishers.is_empty() {
+        tracing::warn!("no publisher tokens configured; the registry is read-only");
+    }
+
+    // Opened before the runtime starts: the Postgres client runs its own
+    let store = match store::open(&options.database) {
+        Ok(store) => Arc::from(store),
+        Err(e) => {
+            eprintln!("failed to open {}: {e}", options.database);
+            std::process::exit(1);
+        }
+    };
+    let registry = Arc::new(Registry::new(store, options.publishers));
+    let app = api::router(registry, options.max_upload_bytes);
+
+    let runtime = tokio::runtime::Runtime::new().expect("failed to start the runtime");
+    let served = runtime.block_on(async {
+        let listener = tokio::net::TcpListener::bind(&options.listen).await?;
+        tracing::info!("registry listening on http://{}/api/v1", options.listen);
+        axum::serve(listener, app).await
+    });
+    if let Err(e) = served {
+        eprintln!("registry server failed: {e}");
+        std::process::exit(1);
+    }
+}
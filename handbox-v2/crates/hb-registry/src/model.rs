@@ -0,0 +1,221 @@
+//! Wire types of the registry protocol.
+//!
+//! Workflow listings and reviews serialize like the app's marketplace types,
+//! so a client can use registry responses as they are.
+
+use chrono::{DateTime, Utc};
+use hb_core::pack::PackContentKind;
+use serde::{Deserialize, Serialize};
+
+/// Workflow categories, as the app names them.
+pub const CATEGORIES: &[&str] = &[
+    "ai-assistant",
+    "data-processing",
+    "document",
+    "automation",
+    "integration",
+    "analysis",
+    "creative",
+    "developer",
+    "other",
+];
+
+/// One stored version of a pack.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackRelease {
+    pub id: String,
+    pub version: String,
+    pub name: String,
+    pub description: String,
+    /// Author named in the pack manifest.
+    pub author: String,
+    pub changelog: String,
+    /// SHA-256 of the archive, hex encoded.
+    pub sha256: String,
+    pub size: u64,
+    /// Publisher whose token uploaded the release.
+    pub publisher: String,
+    pub downloads: u64,
+    pub published_at: DateTime<Utc>,
+    /// Kinds of content the manifest lists.
+    #[serde(default)]
+    pub contents: Vec<PackContentKind>,
+}
+
+/// A release as listed in `index.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub id: String,
+    pub version: String,
+    pub changelog: String,
+    /// Relative to `{server}/api/v1`.
+    pub location: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contents: Vec<PackContentKind>,
+}
+
+/// Query parameters of `GET index.json` and `GET packs`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PackQuery {
+    /// Only packs shipping this kind of content.
+    pub kind: Option<PackContentKind>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PackIndex {
+    pub packs: Vec<IndexEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkflowAuthor {
+    pub id: String,
+    pub name: String,
+    pub is_verified: bool,
+    pub published_count: usize,
+    pub total_downloads: u64,
+    pub avatar_url: Option<String>,
+}
+
+impl WorkflowAuthor {
+    pub fn new(name: &str) -> Self {
+        Self {
+            id: name.to_string(),
+            name: name.to_string(),
+            is_verified: false,
+            published_count: 0,
+            total_downloads: 0,
+            avatar_url: None,
+        }
+    }
+}
+
+/// A published workflow.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkflowListing {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub author: WorkflowAuthor,
+    pub version: String,
+    pub category: String,
+    pub tags: Vec<String>,
+    pub downloads: u64,
+    pub likes: u64,
+    pub rating: f64,
+    pub rating_count: u64,
+    pub node_count: usize,
+    pub estimated_runtime: String,
+    pub required_tools: Vec<String>,
+    pub required_providers: Vec<String>,
+    pub published_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Empty except in downloads.
+    pub workflow_data: String,
+    pub is_verified: bool,
+    pub is_featured: bool,
+    pub is_official: bool,
+    pub license: String,
+    pub preview_images: Vec<String>,
+    pub readme: Option<String>,
+}
+
+/// Body of `POST workflows` and `PUT workflows/{id}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishRequest {
+    pub name: String,
+    pub description: String,
+    pub category: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub workflow_data: String,
+    pub license: String,
+    #[serde(default)]
+    pub readme: Option<String>,
+    #[serde(default)]
+    pub preview_images: Vec<String>,
+    /// Defaults to 1.0.0 for new workflows; required for updates.
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkflowReview {
+    pub id: String,
+    pub workflow_id: String,
+    pub author: WorkflowAuthor,
+    pub rating: u8,
+    pub title: String,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub helpful_count: u64,
+    pub is_verified_purchase: bool,
+}
+
+/// Body of `POST workflows/{id}/reviews`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewRequest {
+    /// 1 to 5.
+    pub rating: u8,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortBy {
+    #[default]
+    Popular,
+    Downloads,
+    Rating,
+    Recent,
+    Trending,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Query parameters of `GET workflows`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchQuery {
+    pub query: Option<String>,
+    pub category: Option<String>,
+    pub tag: Option<String>,
+    pub author_id: Option<String>,
+    pub min_rating: Option<f64>,
+    pub sort_by: SortBy,
+    pub sort_order: SortOrder,
+    /// 1-based; defaults to 1.
+    pub page: Option<usize>,
+    /// Defaults to 20, at most 100.
+    pub page_size: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub workflows: Vec<WorkflowListing>,
+    pub total: usize,
+    pub page: usize,
+    pub page_size: usize,
+    pub has_more: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RegistryStats {
+    pub packs: usize,
+    pub pack_releases: usize,
+    pub pack_downloads: u64,
+    pub workflows: usize,
+    pub workflow_downloads: u64,
+    pub reviews: u64,
+    pub publishers: usize,
+}
@@ -0,0 +1,880 @@
+//! Registry storage: the [`RegistryStore`] backend trait with SQLite and
+//! Postgres implementations.
+//!
+//! Both backends keep archives and workflow data in the database itself, so
+//! backing up the registry is backing up one database.
+
+use crate::model::{PackRelease, WorkflowAuthor, WorkflowListing, WorkflowReview};
+use crate::RegistryError;
+use hb_core::pack::PackContentKind;
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+/// Persistence backend of the registry. Calls block; the HTTP layer runs
+/// them on the blocking thread pool.
+pub trait RegistryStore: Send + Sync {
+    /// Store a pack release and its archive. Fails with
+    /// [`RegistryError::Conflict`] if the version already exists.
+    fn insert_pack(&self, release: &PackRelease, archive: &[u8]) -> Result<(), RegistryError>;
+
+    /// All pack releases, by pack ID then publication time.
+    fn list_packs(&self) -> Result<Vec<PackRelease>, RegistryError>;
+
+    /// The archive of a release, counting the download.
+    fn download_pack(&self, id: &str, version: &str) -> Result<Option<Vec<u8>>, RegistryError>;
+
+    /// Insert a workflow, or replace everything but its downloads and
+    /// publication time. Ratings are derived from reviews and ignored here.
+    fn upsert_workflow(&self, workflow: &WorkflowListing) -> Result<(), RegistryError>;
+
+    /// All workflows, without workflow data.
+    fn list_workflows(&self) -> Result<Vec<WorkflowListing>, RegistryError>;
+
+    /// One workflow, without workflow data.
+    fn get_workflow(&self, id: &str) -> Result<Option<WorkflowListing>, RegistryError>;
+
+    /// One workflow with its data, counting the download.
+    fn download_workflow(&self, id: &str) -> Result<Option<WorkflowListing>, RegistryError>;
+
+    /// Insert a review, replacing the author's previous review of the same
+    /// workflow.
+    fn upsert_review(&self, review: &WorkflowReview) -> Result<(), RegistryError>;
+
+    /// Reviews of a workflow, newest first.
+    fn list_reviews(&self, workflow_id: &str) -> Result<Vec<WorkflowReview>, RegistryError>;
+}
+
+/// Open the store named by `database`: a `postgres://` or `postgresql://`
+/// connection string, otherwise a SQLite file path.
+pub fn open(database: &str) -> Result<Box<dyn RegistryStore>, RegistryError> {
+    if database.starts_with("postgres://") || database.starts_with("postgresql://") {
+        Ok(Box::new(PostgresRegistryStore::connect(database)?))
+    } else {
+        Ok(Box::new(SqliteRegistryStore::open(Path::new(database))?))
+    }
+}
+
+const PACK_COLUMNS: &str = "id, version, name, description, author, changelog, sha256, size, \
+     publisher, downloads, published_at, contents";
+
+const WORKFLOW_COLUMNS: &str = "w.id, w.name, w.description, w.author, w.version, w.category, \
+     w.tags, w.license, w.readme, w.preview_images, w.node_count, w.required_tools, w.downloads, \
+     w.published_at, w.updated_at";
+
+const RATING_COLUMNS: &str = "COALESCE((SELECT CAST(AVG(r.rating) AS DOUBLE PRECISION) \
+     FROM reviews r WHERE r.workflow_id = w.id), 0.0), \
+     (SELECT COUNT(*) FROM reviews r WHERE r.workflow_id = w.id)";
+
+const REVIEW_COLUMNS: &str =
+    "id, workflow_id, author, rating, title, content, created_at, updated_at";
+
+fn workflow_query(with_data: bool, filter: &str) -> String {
+    let data = if with_data { "w.workflow_data" } else { "''" };
+    format!("SELECT {WORKFLOW_COLUMNS}, {data}, {RATING_COLUMNS} FROM workflows w {filter}")
+}
+
+fn parse_time(value: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_default()
+}
+
+fn parse_list(value: &str) -> Vec<String> {
+    serde_json::from_str(value).unwrap_or_default()
+}
+
+fn parse_contents(value: &str) -> Vec<PackContentKind> {
+    serde_json::from_str(value).unwrap_or_default()
+}
+
+fn contents_json(contents: &[PackContentKind]) -> String {
+    serde_json::to_string(contents).unwrap_or_else(|_| "[]".into())
+}
+
+fn to_json(list: &[String]) -> String {
+    serde_json::to_string(list).unwrap_or_else(|_| "[]".into())
+}
+
+/// Columns of a workflow row, in [`workflow_query`] order, shared by both
+/// backends' row readers.
+struct WorkflowRow {
+    id: String,
+    name: String,
+    description: String,
+    author: String,
+    version: String,
+    category: String,
+    tags: String,
+    license: String,
+    readme: Option<String>,
+    preview_images: String,
+    node_count: i64,
+    required_tools: String,
+    downloads: i64,
+    published_at: String,
+    updated_at: String,
+    workflow_data: String,
+    rating: f64,
+    rating_count: i64,
+}
+
+impl From<WorkflowRow> for WorkflowListing {
+    fn from(row: WorkflowRow) -> Self {
+        Self {
+            id: row.id,
+            name: row.name,
+            description: row.description,
+            author: WorkflowAuthor::new(&row.author),
+            version: row.version,
+            category: row.category,
+            tags: parse_list(&row.tags),
+            downloads: row.downloads.max(0) as u64,
+            likes: 0,
+            rating: row.rating,
+            rating_count: row.rating_count.max(0) as u64,
+            node_count: row.node_count.max(0) as usize,
+            estimated_runtime: "unknown".into(),
+            required_tools: parse_list(&row.required_tools),
+            required_providers: Vec::new(),
+            published_at: parse_time(&row.published_at),
+            updated_at: parse_time(&row.updated_at),
+            workflow_data: row.workflow_data,
+            is_verified: false,
+            is_featured: false,
+            is_official: false,
+            license: row.license,
+            preview_images: parse_list(&row.preview_images),
+            readme: row.readme,
+        }
+    }
+}
+
+fn review(
+    id: String,
+    workflow_id: String,
+    author: String,
+    rating: i64,
+    (title, content): (String, String),
+    (created_at, updated_at): (String, String),
+) -> WorkflowReview {
+    WorkflowReview {
+        id,
+        workflow_id,
+        author: WorkflowAuthor::new(&author),
+        rating: rating.clamp(0, 5) as u8,
+        title,
+        content,
+        created_at: parse_time(&created_at),
+        updated_at: parse_time(&updated_at),
+        helpful_count: 0,
+        is_verified_purchase: false,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// SQLite
+// ---------------------------------------------------------------------------
+
+/// SQLite-backed registry store.
+///
+/// Uses Mutex<Connection> for thread safety (rusqlite::Connection is !Sync).
+pub struct SqliteRegistryStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteRegistryStore {
+    /// Open (or create) the registry database at the given path.
+    pub fn open(path: &Path) -> Result<Self, RegistryError> {
+        let store = Self {
+            conn: Mutex::new(Connection::open(path)?),
+        };
+        store.initialize_schema()?;
+        Ok(store)
+    }
+
+    /// Create an in-memory registry store (useful for testing).
+    pub fn in_memory() -> Result<Self, RegistryError> {
+        let store = Self {
+            conn: Mutex::new(Connection::open_in_memory()?),
+        };
+        store.initialize_schema()?;
+        Ok(store)
+    }
+
+    fn conn(&self) -> Result<MutexGuard<'_, Connection>, RegistryError> {
+        self.conn
+            .lock()
+            .map_err(|e| RegistryError::Database(e.to_string()))
+    }
+
+    fn initialize_schema(&self) -> Result<(), RegistryError> {
+        self.conn()?.execute_batch(
+            "CREATE TABLE IF NOT EXISTS packs (
+                id TEXT NOT NULL,
+                version TEXT NOT NULL,
+                name TEXT NOT NULL,
+                description TEXT NOT NULL,
+                author TEXT NOT NULL,
+                changelog TEXT NOT NULL,
+                sha256 TEXT NOT NULL,
+                size INTEGER NOT NULL,
+                publisher TEXT NOT NULL,
+                downloads INTEGER NOT NULL DEFAULT 0,
+                published_at TEXT NOT NULL,
+                archive BLOB NOT NULL,
+                contents TEXT NOT NULL DEFAULT '[]',
+                PRIMARY KEY (id, version)
+            );
+            CREATE TABLE IF NOT EXISTS workflows (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                description TEXT NOT NULL,
+                author TEXT NOT NULL,
+                version TEXT NOT NULL,
+                category TEXT NOT NULL,
+                tags TEXT NOT NULL,
+                license TEXT NOT NULL,
+                readme TEXT,
+                preview_images TEXT NOT NULL,
+                node_count INTEGER NOT NULL,
+                required_tools TEXT NOT NULL,
+                downloads INTEGER NOT NULL DEFAULT 0,
+                published_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                workflow_data TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS reviews (
+                id TEXT PRIMARY KEY,
+                workflow_id TEXT NOT NULL REFERENCES workflows(id),
+                author TEXT NOT NULL,
+                rating INTEGER NOT NULL,
+                title TEXT NOT NULL,
+                content TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                UNIQUE (workflow_id, author)
+            );",
+        )?;
+        // Registries created before packs listed their content kinds.
+        let conn = self.conn()?;
+        let has_contents: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('packs') WHERE name = 'contents'",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_contents {
+            conn.execute(
+                "ALTER TABLE packs ADD COLUMN contents TEXT NOT NULL DEFAULT '[]'",
+                [],
+            )?;
+        }
+        Ok(())
+    }
+
+    fn pack_from_row(row: &rusqlite::Row) -> rusqlite::Result<PackRelease> {
+        Ok(PackRelease {
+            id: row.get(0)?,
+            version: row.get(1)?,
+            name: row.get(2)?,
+            description: row.get(3)?,
+            author: row.get(4)?,
+            changelog: row.get(5)?,
+            sha256: row.get(6)?,
+            size: row.get::<_, i64>(7)?.max(0) as u64,
+            publisher: row.get(8)?,
+            downloads: row.get::<_, i64>(9)?.max(0) as u64,
+            published_at: parse_time(&row.get::<_, String>(10)?),
+            contents: parse_contents(&row.get::<_, String>(11)?),
+        })
+    }
+
+    fn workflow_from_row(row: &rusqlite::Row) -> rusqlite::Result<WorkflowListing> {
+        Ok(WorkflowRow {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            description: row.get(2)?,
+            author: row.get(3)?,
+            version: row.get(4)?,
+            category: row.get(5)?,
+            tags: row.get(6)?,
+            license: row.get(7)?,
+            readme: row.get(8)?,
+            preview_images: row.get(9)?,
+            node_count: row.get(10)?,
+            required_tools: row.get(11)?,
+            downloads: row.get(12)?,
+            published_at: row.get(13)?,
+            updated_at: row.get(14)?,
+            workflow_data: row.get(15)?,
+            rating: row.get(16)?,
+            rating_count: row.get(17)?,
+        }
+        .into())
+    }
+
+    fn query_workflow(
+        &self,
+        id: &str,
+        with_data: bool,
+    ) -> Result<Option<WorkflowListing>, RegistryError> {
+        let conn = self.conn()?;
+        let workflow = conn
+            .query_row(
+                &workflow_query(with_data, "WHERE w.id = ?1"),
+                [id],
+                Self::workflow_from_row,
+            )
+            .optional()?;
+        Ok(workflow)
+    }
+}
+
+impl RegistryStore for SqliteRegistryStore {
+    fn insert_pack(&self, release: &PackRelease, archive: &[u8]) -> Result<(), RegistryError> {
+        let inserted = self.conn()?.execute(
+            "INSERT INTO packs (id, version, name, description, author, changelog, sha256, size,
+                 publisher, downloads, published_at, archive, contents)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 0, ?10, ?11, ?12)
+             ON CONFLICT (id, version) DO NOTHING",
+            rusqlite::params![
+                release.id,
+                release.version,
+                release.name,
+                release.description,
+                release.author,
+                release.changelog,
+                release.sha256,
+                release.size as i64,
+                release.publisher,
+                release.published_at.to_rfc3339(),
+                archive,
+                contents_json(&release.contents),
+            ],
+        )?;
+        if inserted == 0 {
+            return Err(RegistryError::Conflict(format!(
+                "{}@{} already exists",
+                release.id, release.version
+            )));
+        }
+        Ok(())
+    }
+
+    fn list_packs(&self) -> Result<Vec<PackRelease>, RegistryError> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {PACK_COLUMNS} FROM packs ORDER BY id, published_at"
+        ))?;
+        let packs = stmt
+            .query_map([], Self::pack_from_row)?
+            .collect::<Result<_, _>>()?;
+        Ok(packs)
+    }
+
+    fn download_pack(&self, id: &str, version: &str) -> Result<Option<Vec<u8>>, RegistryError> {
+        let conn = self.conn()?;
+        let archive = conn
+            .query_row(
+                "UPDATE packs SET downloads = downloads + 1 WHERE id = ?1 AND version = ?2
+                 RETURNING archive",
+                [id, version],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(archive)
+    }
+
+    fn upsert_workflow(&self, w: &WorkflowListing) -> Result<(), RegistryError> {
+        self.conn()?.execute(
+            "INSERT INTO workflows (id, name, description, author, version, category, tags,
+                 license, readme, preview_images, node_count, required_tools, downloads,
+                 published_at, updated_at, workflow_data)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, 0, ?13, ?14, ?15)
+             ON CONFLICT (id) DO UPDATE SET
+                 name = excluded.name, description = excluded.description,
+                 author = excluded.author, version = excluded.version,
+                 category = excluded.category, tags = excluded.tags,
+                 license = excluded.license, readme = excluded.readme,
+                 preview_images = excluded.preview_images, node_count = excluded.node_count,
+                 required_tools = excluded.required_tools, updated_at = excluded.updated_at,
+                 workflow_data = excluded.workflow_data",
+            rusqlite::params![
+                w.id,
+                w.name,
+                w.description,
+                w.author.name,
+                w.version,
+                w.category,
+                to_json(&w.tags),
+                w.license,
+                w.readme,
+                to_json(&w.preview_images),
+                w.node_count as i64,
+                to_json(&w.required_tools),
+                w.published_at.to_rfc3339(),
+                w.updated_at.to_rfc3339(),
+                w.workflow_data,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn list_workflows(&self) -> Result<Vec<WorkflowListing>, RegistryError> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(&workflow_query(false, "ORDER BY w.published_at"))?;
+        let workflows = stmt
+            .query_map([], Self::workflow_from_row)?
+            .collect::<Result<_, _>>()?;
+        Ok(workflows)
+    }
+
+    fn get_workflow(&self, id: &str) -> Result<Option<WorkflowListing>, RegistryError> {
+        self.query_workflow(id, false)
+    }
+
+    fn download_workflow(&self, id: &str) -> Result<Option<WorkflowListing>, RegistryError> {
+        self.conn()?.execute(
+            "UPDATE workflows SET downloads = downloads + 1 WHERE id = ?1",
+            [id],
+        )?;
+        self.query_workflow(id, true)
+    }
+
+    fn upsert_review(&self, r: &WorkflowReview) -> Result<(), RegistryError> {
+        self.conn()?.execute(
+            "INSERT INTO reviews (id, workflow_id, author, rating, title, content, created_at,
+                 updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT (workflow_id, author) DO UPDATE SET
+                 rating = excluded.rating, title = excluded.title,
+                 content = excluded.content, updated_at = excluded.updated_at",
+            rusqlite::params![
+                r.id,
+                r.workflow_id,
+                r.author.name,
+                r.rating,
+                r.title,
+                r.content,
+                r.created_at.to_rfc3339(),
+                r.updated_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn list_reviews(&self, workflow_id: &str) -> Result<Vec<WorkflowReview>, RegistryError> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {REVIEW_COLUMNS} FROM reviews WHERE workflow_id = ?1
+             ORDER BY updated_at DESC"
+        ))?;
+        let reviews = stmt
+            .query_map([workflow_id], |row| {
+                Ok(review(
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    (row.get(4)?, row.get(5)?),
+                    (row.get(6)?, row.get(7)?),
+                ))
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(reviews)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Postgres
+// ---------------------------------------------------------------------------
+
+/// Postgres-backed registry store, for registries shared by several server
+/// instances or kept on an existing database server.
+pub struct PostgresRegistryStore {
+    client: Mutex<postgres::Client>,
+}
+
+impl PostgresRegistryStore {
+    /// Connect and create the tables if needed. Must not be called from
+    /// inside an async runtime; the client runs its own.
+    pub fn connect(url: &str) -> Result<Self, RegistryError> {
+        let store = Self {
+            client: Mutex::new(postgres::Client::connect(url, postgres::NoTls)?),
+        };
+        store.initialize_schema()?;
+        Ok(store)
+    }
+
+    fn client(&self) -> Result<MutexGuard<'_, postgres::Client>, RegistryError> {
+        self.client
+            .lock()
+            .map_err(|e| RegistryError::Database(e.to_string()))
+    }
+
+    fn initialize_schema(&self) -> Result<(), RegistryError> {
+        self.client()?.batch_execute(
+            "CREATE TABLE IF NOT EXISTS packs (
+                id TEXT NOT NULL,
+                version TEXT NOT NULL,
+                name TEXT NOT NULL,
+                description TEXT NOT NULL,
+                author TEXT NOT NULL,
+                changelog TEXT NOT NULL,
+                sha256 TEXT NOT NULL,
+                size BIGINT NOT NULL,
+                publisher TEXT NOT NULL,
+                downloads BIGINT NOT NULL DEFAULT 0,
+                published_at TEXT NOT NULL,
+                archive BYTEA NOT NULL,
+                contents TEXT NOT NULL DEFAULT '[]',
+                PRIMARY KEY (id, version)
+            );
+            CREATE TABLE IF NOT EXISTS workflows (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                description TEXT NOT NULL,
+                author TEXT NOT NULL,
+                version TEXT NOT NULL,
+                category TEXT NOT NULL,
+                tags TEXT NOT NULL,
+                license TEXT NOT NULL,
+                readme TEXT,
+                preview_images TEXT NOT NULL,
+                node_count BIGINT NOT NULL,
+                required_tools TEXT NOT NULL,
+                downloads BIGINT NOT NULL DEFAULT 0,
+                published_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                workflow_data TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS reviews (
+                id TEXT PRIMARY KEY,
+                workflow_id TEXT NOT NULL REFERENCES workflows(id),
+                author TEXT NOT NULL,
+                rating BIGINT NOT NULL,
+                title TEXT NOT NULL,
+                content TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                UNIQUE (workflow_id, author)
+            );
+            ALTER TABLE packs ADD COLUMN IF NOT EXISTS contents TEXT NOT NULL DEFAULT '[]';",
+        )?;
+        Ok(())
+    }
+
+    fn pack_from_row(row: &postgres::Row) -> PackRelease {
+        PackRelease {
+            id: row.get(0),
+            version: row.get(1),
+            name: row.get(2),
+            description: row.get(3),
+            author: row.get(4),
+            changelog: row.get(5),
+            sha256: row.get(6),
+            size: row.get::<_, i64>(7).max(0) as u64,
+            publisher: row.get(8),
+            downloads: row.get::<_, i64>(9).max(0) as u64,
+            published_at: parse_time(row.get(10)),
+            contents: parse_contents(row.get(11)),
+        }
+    }
+
+    fn workflow_from_row(row: &postgres::Row) -> WorkflowListing {
+        WorkflowRow {
+            id: row.get(0),
+            name: row.get(1),
+            description: row.get(2),
+            author: row.get(3),
+            version: row.get(4),
+            category: row.get(5),
+            tags: row.get(6),
+            license: row.get(7),
+            readme: row.get(8),
+            preview_images: row.get(9),
+            node_count: row.get(10),
+            required_tools: row.get(11),
+            downloads: row.get(12),
+            published_at: row.get(13),
+            updated_at: row.get(14),
+            workflow_data: row.get(15),
+            rating: row.get(16),
+            rating_count: row.get(17),
+        }
+        .into()
+    }
+
+    fn query_workflow(
+        &self,
+        id: &str,
+        with_data: bool,
+    ) -> Result<Option<WorkflowListing>, RegistryError> {
+        let row = self
+            .client()?
+            .query_opt(&workflow_query(with_data, "WHERE w.id = $1"), &[&id])?;
+        Ok(row.as_ref().map(Self::workflow_from_row))
+    }
+}
+
+impl RegistryStore for PostgresRegistryStore {
+    fn insert_pack(&self, release: &PackRelease, archive: &[u8]) -> Result<(), RegistryError> {
+        let inserted = self.client()?.execute(
+            "INSERT INTO packs (id, version, name, description, author, changelog, sha256, size,
+                 publisher, downloads, published_at, archive, contents)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, 0, $10, $11, $12)
+             ON CONFLICT (id, version) DO NOTHING",
+            &[
+                &release.id,
+                &release.version,
+                &release.name,
+                &release.description,
+                &release.author,
+                &release.changelog,
+                &release.sha256,
+                &(release.size as i64),
+                &release.publisher,
+                &release.published_at.to_rfc3339(),
+                &archive,
+                &contents_json(&release.contents),
+            ],
+        )?;
+        if inserted == 0 {
+            return Err(RegistryError::Conflict(format!(
+                "{}@{} already exists",
+                release.id, release.version
+            )));
+        }
+        Ok(())
+    }
+
+    fn list_packs(&self) -> Result<Vec<PackRelease>, RegistryError> {
+        let rows = self.client()?.query(
+            &format!("SELECT {PACK_COLUMNS} FROM packs ORDER BY id, published_at"),
+            &[],
+        )?;
+        Ok(rows.iter().map(Self::pack_from_row).collect())
+    }
+
+    fn download_pack(&self, id: &str, version: &str) -> Result<Option<Vec<u8>>, RegistryError> {
+        let row = self.client()?.query_opt(
+            "UPDATE packs SET downloads = downloads + 1 WHERE id = $1 AND version = $2
+             RETURNING archive",
+            &[&id, &version],
+        )?;
+        Ok(row.map(|row| row.get(0)))
+    }
+
+    fn upsert_workflow(&self, w: &WorkflowListing) -> Result<(), RegistryError> {
+        self.client()?.execute(
+            "INSERT INTO workflows (id, name, description, author, version, category, tags,
+                 license, readme, preview_images, node_count, required_tools, downloads,
+                 published_at, updated_at, workflow_data)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, 0, $13, $14, $15)
+             ON CONFLICT (id) DO UPDATE SET
+                 name = excluded.name, description = excluded.description,
+                 author = excluded.author, version = excluded.version,
+                 category = excluded.category, tags = excluded.tags,
+                 license = excluded.license, readme = excluded.readme,
+                 preview_images = excluded.preview_images, node_count = excluded.node_count,
+                 required_tools = excluded.required_tools, updated_at = excluded.updated_at,
+                 workflow_data = excluded.workflow_data",
+            &[
+                &w.id,
+                &w.name,
+                &w.description,
+                &w.author.name,
+                &w.version,
+                &w.category,
+                &to_json(&w.tags),
+                &w.license,
+                &w.readme,
+                &to_json(&w.preview_images),
+                &(w.node_count as i64),
+                &to_json(&w.required_tools),
+                &w.published_at.to_rfc3339(),
+                &w.updated_at.to_rfc3339(),
+                &w.workflow_data,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn list_workflows(&self) -> Result<Vec<WorkflowListing>, RegistryError> {
+        let rows = self
+            .client()?
+            .query(&workflow_query(false, "ORDER BY w.published_at"), &[])?;
+        Ok(rows.iter().map(Self::workflow_from_row).collect())
+    }
+
+    fn get_workflow(&self, id: &str) -> Result<Option<WorkflowListing>, RegistryError> {
+        self.query_workflow(id, false)
+    }
+
+    fn download_workflow(&self, id: &str) -> Result<Option<WorkflowListing>, RegistryError> {
+        self.client()?.execute(
+            "UPDATE workflows SET downloads = downloads + 1 WHERE id = $1",
+            &[&id],
+        )?;
+        self.query_workflow(id, true)
+    }
+
+    fn upsert_review(&self, r: &WorkflowReview) -> Result<(), RegistryError> {
+        self.client()?.execute(
+            "INSERT INTO reviews (id, workflow_id, author, rating, title, content, created_at,
+                 updated_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+             ON CONFLICT (workflow_id, author) DO UPDATE SET
+                 rating = excluded.rating, title = excluded.title,
+                 content = excluded.content, updated_at = excluded.updated_at",
+            &[
+                &r.id,
+                &r.workflow_id,
+                &r.author.name,
+                &i64::from(r.rating),
+                &r.title,
+                &r.content,
+                &r.created_at.to_rfc3339(),
+                &r.updated_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn list_reviews(&self, workflow_id: &str) -> Result<Vec<WorkflowReview>, RegistryError> {
+        let rows = self.client()?.query(
+            &format!(
+                "SELECT {REVIEW_COLUMNS} FROM reviews WHERE workflow_id = $1
+                 ORDER BY updated_at DESC"
+            ),
+            &[&workflow_id],
+        )?;
+        Ok(rows
+            .iter()
+            .map(|row| {
+                review(
+                    row.get(0),
+                    row.get(1),
+                    row.get(2),
+                    row.get(3),
+                    (row.get(4), row.get(5)),
+                    (row.get(6), row.get(7)),
+                )
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(version: &str) -> PackRelease {
+        PackRelease {
+            id: "rag-pack".into(),
+            version: version.into(),
+            name: "RAG".into(),
+            description: String::new(),
+            author: "Tests".into(),
+            changelog: String::new(),
+            sha256: "00".into(),
+            size: 3,
+            publisher: "alice".into(),
+            downloads: 0,
+            published_at: Utc::now(),
+            contents: vec![PackContentKind::Tool],
+        }
+    }
+
+    fn workflow(id: &str) -> WorkflowListing {
+        let now = Utc::now();
+        WorkflowListing {
+            id: id.into(),
+            name: "Summarize".into(),
+            description: "Summarize documents".into(),
+            author: WorkflowAuthor::new("alice"),
+            version: "1.0.0".into(),
+            category: "document".into(),
+            tags: vec!["pdf".into()],
+            downloads: 0,
+            likes: 0,
+            rating: 0.0,
+            rating_count: 0,
+            node_count: 2,
+            estimated_runtime: "unknown".into(),
+            required_tools: vec!["core-tools/file-read".into()],
+            required_providers: vec![],
+            published_at: now,
+            updated_at: now,
+            workflow_data: "{}".into(),
+            is_verified: false,
+            is_featured: false,
+            is_official: false,
+            license: "MIT".into(),
+            preview_images: vec![],
+            readme: None,
+        }
+    }
+
+    fn review_by(author: &str, rating: u8) -> WorkflowReview {
+        let now = Utc::now();
+        review(
+            uuid::Uuid::new_v4().to_string(),
+            "wf-1".into(),
+            author.into(),
+            i64::from(rating),
+            ("Title".into(), "Content".into()),
+            (now.to_rfc3339(), now.to_rfc3339()),
+        )
+    }
+
+    #[test]
+    fn packs_are_immutable_and_count_downloads() {
+        let store = SqliteRegistryStore::in_memory().unwrap();
+        store.insert_pack(&release("1.0.0"), b"abc").unwrap();
+        assert!(matches!(
+            store.insert_pack(&release("1.0.0"), b"xyz"),
+            Err(RegistryError::Conflict(_))
+        ));
+        store.insert_pack(&release("1.1.0"), b"def").unwrap();
+
+        assert_eq!(
+            store.download_pack("rag-pack", "1.0.0").unwrap().unwrap(),
+            b"abc"
+        );
+        assert!(store.download_pack("rag-pack", "9.9.9").unwrap().is_none());
+        let packs = store.list_packs().unwrap();
+        assert_eq!(packs.len(), 2);
+        assert_eq!(packs[0].downloads, 1);
+        assert_eq!(packs[1].downloads, 0);
+    }
+
+    #[test]
+    fn workflows_keep_downloads_and_rate_from_reviews() {
+        let store = SqliteRegistryStore::in_memory().unwrap();
+        store.upsert_workflow(&workflow("wf-1")).unwrap();
+        let downloaded = store.download_workflow("wf-1").unwrap().unwrap();
+        assert_eq!(downloaded.workflow_data, "{}");
+        assert_eq!(downloaded.downloads, 1);
+
+        let mut update = workflow("wf-1");
+        update.version = "1.1.0".into();
+        store.upsert_workflow(&update).unwrap();
+
+        store.upsert_review(&review_by("bob", 2)).unwrap();
+        store.upsert_review(&review_by("carol", 4)).unwrap();
+        // A second review by the same author replaces the first
+        store.upsert_review(&review_by("bob", 5)).unwrap();
+
+        let listed = store.get_workflow("wf-1").unwrap().unwrap();
+        assert_eq!(listed.version, "1.1.0");
+        assert_eq!(listed.downloads, 1);
+        assert!(listed.workflow_data.is_empty());
+        assert_eq!((listed.rating, listed.rating_count), (4.5, 2));
+        assert_eq!(listed.required_tools, ["core-tools/file-read"]);
+        assert_eq!(store.list_reviews("wf-1").unwrap().len(), 2);
+        assert_eq!(store.list_workflows().unwrap().len(), 1);
+        assert!(store.get_workflow("missing").unwrap().is_none());
+    }
+}
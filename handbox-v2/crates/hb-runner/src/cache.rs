@@ -1,16 +1,202 @@
 //! Cache — input-hash-based caching backed by SQLite.
 //!
 //! cache_key = hash(tool_ref + tool_version, sorted(input_values), config, data_source_version?)
+//!
+//! Two tiers: a byte-bounded in-memory LRU in front of the persistent SQLite
+//! table. Lookups try memory first and promote persistent hits into it.
+//! [`ExecutionCache::offer`] applies the admission rules of [`CacheConfig`]:
+//! outputs that were cheap to compute or are too large are not cached, and
+//! large ones skip the memory tier.
 
 use crate::RunnerError;
 use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Memory budget and admission rules.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// Serialized output bytes the memory tier may hold before evicting the
+    /// least recently used entries.
+    pub memory_budget_bytes: u64,
+    /// Outputs larger than this stay out of the memory tier (persistent only).
+    pub max_memory_entry_bytes: u64,
+    /// Outputs larger than this are not cached at all.
+    pub max_entry_bytes: u64,
+    /// Outputs computed in less time than this are cheaper to recompute than
+    /// to keep.
+    pub min_compute_ms: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            memory_budget_bytes: 64 * 1024 * 1024,
+            max_memory_entry_bytes: 4 * 1024 * 1024,
+            max_entry_bytes: 32 * 1024 * 1024,
+            min_compute_ms: 20,
+        }
+    }
+}
+
+/// Where [`ExecutionCache::offer`] put an output, or why it did not.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Admission {
+    /// Memory and persistent tiers.
+    Memory,
+    /// Persistent tier only — too large for memory.
+    Persistent,
+    TooLarge,
+    TooCheap,
+}
+
+impl Admission {
+    pub fn is_admitted(self) -> bool {
+        matches!(self, Admission::Memory | Admission::Persistent)
+    }
+}
+
+/// Counters since the cache was opened, plus the memory tier's occupancy.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CacheStats {
+    pub memory_hits: u64,
+    pub persistent_hits: u64,
+    pub misses: u64,
+    pub stores: u64,
+    pub rejected_large: u64,
+    pub rejected_cheap: u64,
+    /// Entries dropped from the memory tier to stay within budget.
+    pub evictions: u64,
+    pub memory_entries: u64,
+    pub memory_bytes: u64,
+    pub memory_budget_bytes: u64,
+    /// Hits over lookups; 0 before the first lookup.
+    pub hit_rate: f64,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    memory_hits: AtomicU64,
+    persistent_hits: AtomicU64,
+    misses: AtomicU64,
+    stores: AtomicU64,
+    rejected_large: AtomicU64,
+    rejected_cheap: AtomicU64,
+    evictions: AtomicU64,
+}
+
+fn bump(counter: &AtomicU64, by: u64) {
+    counter.fetch_add(by, Ordering::Relaxed);
+}
+
+struct MemoryEntry {
+    output: serde_json::Value,
+    bytes: u64,
+    expires_at: Option<Instant>,
+    /// Position in `MemoryTier::recency`.
+    tick: u64,
+}
+
+/// LRU map bounded by the serialized size of its values.
+#[derive(Default)]
+struct MemoryTier {
+    entries: HashMap<String, MemoryEntry>,
+    /// Last use → key, oldest first.
+    recency: BTreeMap<u64, String>,
+    tick: u64,
+    bytes: u64,
+}
+
+impl MemoryTier {
+    fn get(&mut self, key: &str) -> Option<serde_json::Value> {
+        let entry = self.entries.get(key)?;
+        if entry.expires_at.is_some_and(|at| at <= Instant::now()) {
+            self.remove(key);
+            return None;
+        }
+        self.tick += 1;
+        let tick = self.tick;
+        let entry = self.entries.get_mut(key)?;
+        self.recency.remove(&entry.tick);
+        self.recency.insert(tick, key.to_string());
+        entry.tick = tick;
+        Some(entry.output.clone())
+    }
+
+    /// Insert, then evict least recently used entries until within `budget`.
+    /// Returns the number evicted.
+    fn insert(
+        &mut self,
+        key: &str,
+        output: serde_json::Value,
+        bytes: u64,
+        expires_at: Option<Instant>,
+        budget: u64,
+    ) -> u64 {
+        self.remove(key);
+        self.tick += 1;
+        self.recency.insert(self.tick, key.to_string());
+        self.entries.insert(
+            key.to_string(),
+            MemoryEntry {
+                output,
+                bytes,
+                expires_at,
+                tick: self.tick,
+            },
+        );
+        self.bytes += bytes;
+
+        let mut evicted = 0;
+        while self.bytes > budget {
+            let Some((_, oldest)) = self.recency.pop_first() else {
+                break;
+            };
+            if let Some(entry) = self.entries.remove(&oldest) {
+                self.bytes -= entry.bytes;
+                evicted += 1;
+            }
+        }
+        evicted
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.recency.remove(&entry.tick);
+            self.bytes -= entry.bytes;
+        }
+    }
+
+    fn remove_expired(&mut self) {
+        let now = Instant::now();
+        let expired: Vec<String> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.expires_at.is_some_and(|at| at <= now))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in expired {
+            self.remove(&key);
+        }
+    }
 
-/// Thread-safe cache backed by SQLite.
+    fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Thread-safe two-tier cache: bounded memory over SQLite.
 pub struct ExecutionCache {
     conn: Mutex<Connection>,
+    memory: Mutex<MemoryTier>,
+    config: CacheConfig,
+    counters: Counters,
 }
 
 impl ExecutionCache {
@@ -18,7 +204,17 @@ impl ExecutionCache {
     pub fn new(db_path: &Path) -> Result<Self, RunnerError> {
         let conn = Connection::open(db_path)
             .map_err(|e| RunnerError::Cache(format!("Failed to open cache DB: {e}")))?;
+        Self::with_connection(conn)
+    }
+
+    /// Create an in-memory cache (for testing).
+    pub fn in_memory() -> Result<Self, RunnerError> {
+        let conn = Connection::open_in_memory()
+            .map_err(|e| RunnerError::Cache(format!("Failed to open in-memory DB: {e}")))?;
+        Self::with_connection(conn)
+    }
 
+    fn with_connection(conn: Connection) -> Result<Self, RunnerError> {
         // Create cache table if not exists
         conn.execute(
             "CREATE TABLE IF NOT EXISTS execution_cache (
@@ -41,78 +237,122 @@ impl ExecutionCache {
 
         Ok(Self {
             conn: Mutex::new(conn),
+            memory: Mutex::new(MemoryTier::default()),
+            config: CacheConfig::default(),
+            counters: Counters::default(),
         })
     }
 
-    /// Create an in-memory cache (for testing).
-    pub fn in_memory() -> Result<Self, RunnerError> {
-        let conn = Connection::open_in_memory()
-            .map_err(|e| RunnerError::Cache(format!("Failed to open in-memory DB: {e}")))?;
+    /// Replace the memory budget and admission rules.
+    pub fn with_config(mut self, config: CacheConfig) -> Self {
+        self.config = config;
+        self
+    }
 
-        conn.execute(
-            "CREATE TABLE execution_cache (
-                cache_key TEXT PRIMARY KEY,
-                output_json TEXT NOT NULL,
-                created_at TEXT DEFAULT CURRENT_TIMESTAMP,
-                expires_at TEXT,
-                hit_count INTEGER DEFAULT 0
-            )",
-            [],
-        )
-        .map_err(|e| RunnerError::Cache(format!("Failed to create cache table: {e}")))?;
+    pub fn config(&self) -> &CacheConfig {
+        &self.config
+    }
 
-        Ok(Self {
-            conn: Mutex::new(conn),
-        })
+    fn memory(&self) -> std::sync::MutexGuard<'_, MemoryTier> {
+        self.memory.lock().unwrap_or_else(|e| e.into_inner())
     }
 
-    /// Look up a cached result.
+    /// Look up a cached result, memory tier first.
     pub fn lookup(&self, cache_key: &str) -> Result<Option<serde_json::Value>, RunnerError> {
+        if let Some(output) = self.memory().get(cache_key) {
+            bump(&self.counters.memory_hits, 1);
+            return Ok(Some(output));
+        }
+
         let conn = self.conn.lock().map_err(|_| RunnerError::Cache("Lock poisoned".into()))?;
 
         // Check if entry exists and is not expired
-        let result: Result<(String, i64), _> = conn.query_row(
-            "SELECT output_json, hit_count FROM execution_cache
+        let result: Result<(String, i64, Option<String>), _> = conn.query_row(
+            "SELECT output_json, hit_count, expires_at FROM execution_cache
              WHERE cache_key = ?1
              AND (expires_at IS NULL OR expires_at > datetime('now'))",
             params![cache_key],
-            |row| Ok((row.get(0)?, row.get(1)?)),
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
         );
 
         match result {
-            Ok((output_json, hit_count)) => {
+            Ok((output_json, hit_count, expires_at)) => {
                 // Update hit count
                 let _ = conn.execute(
                     "UPDATE execution_cache SET hit_count = ?1 WHERE cache_key = ?2",
                     params![hit_count + 1, cache_key],
                 );
+                drop(conn);
 
                 let output: serde_json::Value = serde_json::from_str(&output_json)
                     .map_err(|e| RunnerError::Cache(format!("Invalid cached JSON: {e}")))?;
+                bump(&self.counters.persistent_hits, 1);
+
+                let bytes = output_json.len() as u64;
+                if bytes <= self.config.max_memory_entry_bytes {
+                    let expires_at = expires_at.as_deref().and_then(instant_from_rfc3339);
+                    self.remember(cache_key, output.clone(), bytes, expires_at);
+                }
                 Ok(Some(output))
             }
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                bump(&self.counters.misses, 1);
+                Ok(None)
+            }
             Err(e) => Err(RunnerError::Cache(format!("Cache lookup failed: {e}"))),
         }
     }
 
-    /// Store a result in the cache with optional TTL.
+    /// Store a result in the cache with optional TTL, bypassing admission.
+    /// Outputs too large for the memory tier go to the persistent tier only.
     pub fn store(
         &self,
         cache_key: &str,
         output: &serde_json::Value,
         ttl_secs: Option<u64>,
     ) -> Result<(), RunnerError> {
-        let conn = self.conn.lock().map_err(|_| RunnerError::Cache("Lock poisoned".into()))?;
+        let output_json = serde_json::to_string(output)
+            .map_err(|e| RunnerError::Cache(format!("Failed to serialize output: {e}")))?;
+        self.store_json(cache_key, output, &output_json, ttl_secs)?;
+        Ok(())
+    }
 
+    /// Store a result that took `compute` to produce, if the admission rules
+    /// let it in.
+    pub fn offer(
+        &self,
+        cache_key: &str,
+        output: &serde_json::Value,
+        ttl_secs: Option<u64>,
+        compute: Duration,
+    ) -> Result<Admission, RunnerError> {
+        if compute < Duration::from_millis(self.config.min_compute_ms) {
+            bump(&self.counters.rejected_cheap, 1);
+            return Ok(Admission::TooCheap);
+        }
         let output_json = serde_json::to_string(output)
             .map_err(|e| RunnerError::Cache(format!("Failed to serialize output: {e}")))?;
+        if output_json.len() as u64 > self.config.max_entry_bytes {
+            bump(&self.counters.rejected_large, 1);
+            return Ok(Admission::TooLarge);
+        }
+        self.store_json(cache_key, output, &output_json, ttl_secs)
+    }
+
+    fn store_json(
+        &self,
+        cache_key: &str,
+        output: &serde_json::Value,
+        output_json: &str,
+        ttl_secs: Option<u64>,
+    ) -> Result<Admission, RunnerError> {
+        let conn = self.conn.lock().map_err(|_| RunnerError::Cache("Lock poisoned".into()))?;
 
-        let expires_at = ttl_secs.map(|ttl| {
+        let expires_at = ttl_secs.and_then(|ttl| {
             chrono::Utc::now()
                 .checked_add_signed(chrono::Duration::seconds(ttl as i64))
                 .map(|t| t.to_rfc3339())
-        }).flatten();
+        });
 
         conn.execute(
             "INSERT OR REPLACE INTO execution_cache (cache_key, output_json, expires_at, hit_count)
@@ -120,12 +360,70 @@ impl ExecutionCache {
             params![cache_key, output_json, expires_at],
         )
         .map_err(|e| RunnerError::Cache(format!("Failed to store cache: {e}")))?;
+        drop(conn);
+        bump(&self.counters.stores, 1);
+
+        let bytes = output_json.len() as u64;
+        if bytes > self.config.max_memory_entry_bytes {
+            // Don't serve a stale value from memory after replacing it
+            self.memory().remove(cache_key);
+            return Ok(Admission::Persistent);
+        }
+        let expires_at = ttl_secs.map(|ttl| Instant::now() + Duration::from_secs(ttl));
+        self.remember(cache_key, output.clone(), bytes, expires_at);
+        Ok(Admission::Memory)
+    }
 
-        Ok(())
+    fn remember(
+        &self,
+        cache_key: &str,
+        output: serde_json::Value,
+        bytes: u64,
+        expires_at: Option<Instant>,
+    ) {
+        let evicted = self.memory().insert(
+            cache_key,
+            output,
+            bytes,
+            expires_at,
+            self.config.memory_budget_bytes,
+        );
+        bump(&self.counters.evictions, evicted);
+    }
+
+    /// Hit, miss, admission and eviction counts since the cache was opened.
+    pub fn stats(&self) -> CacheStats {
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        let memory_hits = load(&self.counters.memory_hits);
+        let persistent_hits = load(&self.counters.persistent_hits);
+        let misses = load(&self.counters.misses);
+        let lookups = memory_hits + persistent_hits + misses;
+        let (memory_entries, memory_bytes) = {
+            let memory = self.memory();
+            (memory.entries.len() as u64, memory.bytes)
+        };
+        CacheStats {
+            memory_hits,
+            persistent_hits,
+            misses,
+            stores: load(&self.counters.stores),
+            rejected_large: load(&self.counters.rejected_large),
+            rejected_cheap: load(&self.counters.rejected_cheap),
+            evictions: load(&self.counters.evictions),
+            memory_entries,
+            memory_bytes,
+            memory_budget_bytes: self.config.memory_budget_bytes,
+            hit_rate: if lookups == 0 {
+                0.0
+            } else {
+                (memory_hits + persistent_hits) as f64 / lookups as f64
+            },
+        }
     }
 
     /// Clean up expired entries.
     pub fn cleanup(&self) -> Result<usize, RunnerError> {
+        self.memory().remove_expired();
         let conn = self.conn.lock().map_err(|_| RunnerError::Cache("Lock poisoned".into()))?;
 
         let deleted = conn
@@ -138,8 +436,28 @@ impl ExecutionCache {
         Ok(deleted)
     }
 
+    /// Size of the cache database in bytes.
+    pub fn database_size(&self) -> Result<u64, RunnerError> {
+        let conn = self.conn.lock().map_err(|_| RunnerError::Cache("Lock poisoned".into()))?;
+        conn.query_row(
+            "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+            [],
+            |row| row.get::<_, i64>(0),
+        )
+        .map(|size| size.max(0) as u64)
+        .map_err(|e| RunnerError::Cache(format!("Size query failed: {e}")))
+    }
+
+    /// Rebuild the cache database to return space freed by deletions.
+    pub fn vacuum(&self) -> Result<(), RunnerError> {
+        let conn = self.conn.lock().map_err(|_| RunnerError::Cache("Lock poisoned".into()))?;
+        conn.execute_batch("VACUUM;")
+            .map_err(|e| RunnerError::Cache(format!("Vacuum failed: {e}")))
+    }
+
     /// Clear all cache entries.
     pub fn clear(&self) -> Result<(), RunnerError> {
+        self.memory().clear();
         let conn = self.conn.lock().map_err(|_| RunnerError::Cache("Lock poisoned".into()))?;
         conn.execute("DELETE FROM execution_cache", [])
             .map_err(|e| RunnerError::Cache(format!("Clear failed: {e}")))?;
@@ -147,6 +465,15 @@ impl ExecutionCache {
     }
 }
 
+/// The `Instant` an RFC 3339 expiry falls on; now if it has passed.
+fn instant_from_rfc3339(expires_at: &str) -> Option<Instant> {
+    let expires_at = chrono::DateTime::parse_from_rfc3339(expires_at).ok()?;
+    let left = (expires_at.with_timezone(&chrono::Utc) - chrono::Utc::now())
+        .to_std()
+        .unwrap_or_default();
+    Some(Instant::now() + left)
+}
+
 /// Compute a cache key from tool reference, inputs, and config.
 pub fn compute_cache_key(
     tool_ref: &str,
@@ -252,4 +579,62 @@ mod tests {
         cache.store(key, &output, Some(3600)).unwrap();
         assert!(cache.lookup(key).unwrap().is_some());
     }
+
+    #[test]
+    fn memory_tier_evicts_least_recently_used() {
+        // Each output serializes to 10 bytes; room for two
+        let cache = ExecutionCache::in_memory().unwrap().with_config(CacheConfig {
+            memory_budget_bytes: 25,
+            ..CacheConfig::default()
+        });
+        let output = |n: u32| serde_json::json!({ "n": 1000 + n });
+        for n in 0..2 {
+            cache.store(&format!("k{n}"), &output(n), None).unwrap();
+        }
+        assert_eq!(cache.lookup("k0").unwrap(), Some(output(0)));
+        cache.store("k2", &output(2), None).unwrap();
+
+        // k1 was least recently used: evicted from memory, still on disk
+        let stats = cache.stats();
+        assert_eq!((stats.evictions, stats.memory_entries, stats.memory_bytes), (1, 2, 20));
+        assert_eq!(cache.lookup("k1").unwrap(), Some(output(1)));
+        assert_eq!(cache.lookup("k2").unwrap(), Some(output(2)));
+        assert_eq!(cache.lookup("k9").unwrap(), None);
+
+        let stats = cache.stats();
+        assert_eq!((stats.memory_hits, stats.persistent_hits, stats.misses), (2, 1, 1));
+        assert_eq!(stats.hit_rate, 0.75);
+        // Promoting k1 pushed out k0
+        assert_eq!(stats.evictions, 2);
+    }
+
+    #[test]
+    fn admission_skips_cheap_and_oversized_outputs() {
+        let cache = ExecutionCache::in_memory().unwrap().with_config(CacheConfig {
+            memory_budget_bytes: 1_000,
+            max_memory_entry_bytes: 100,
+            max_entry_bytes: 1_000,
+            min_compute_ms: 50,
+        });
+        let ms = Duration::from_millis;
+        let small = serde_json::json!({ "text": "x" });
+        let medium = serde_json::json!({ "text": "x".repeat(200) });
+        let huge = serde_json::json!({ "text": "x".repeat(2_000) });
+
+        assert_eq!(cache.offer("cheap", &small, None, ms(5)).unwrap(), Admission::TooCheap);
+        assert_eq!(cache.offer("huge", &huge, None, ms(500)).unwrap(), Admission::TooLarge);
+        assert_eq!(cache.offer("medium", &medium, None, ms(500)).unwrap(), Admission::Persistent);
+        assert_eq!(cache.offer("small", &small, None, ms(500)).unwrap(), Admission::Memory);
+
+        assert!(cache.lookup("cheap").unwrap().is_none());
+        assert!(cache.lookup("huge").unwrap().is_none());
+        assert_eq!(cache.lookup("medium").unwrap(), Some(medium));
+        assert_eq!(cache.lookup("small").unwrap(), Some(small));
+
+        let stats = cache.stats();
+        assert_eq!((stats.rejected_cheap, stats.rejected_large, stats.stores), (1, 1, 2));
+        // The medium output is served from disk every time
+        assert_eq!((stats.memory_hits, stats.persistent_hits), (1, 1));
+        assert_eq!(stats.memory_entries, 1);
+    }
 }
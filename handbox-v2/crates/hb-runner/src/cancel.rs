@@ -0,0 +1,154 @@
+//! Per-node cancellation — stops one node and everything downstream of it
+//! while the rest of the execution carries on.
+//!
+//! A running node is dropped as soon as it is cancelled, which kills any
+//! process it started. Nodes that have not started are skipped when their
+//! level comes up, as are their dependents, and are recorded as cancelled.
+//!
+//! Nodes inside composites, loops and branches are cancelled by their own
+//! ID, and nodes of a referenced workflow by `{ref_node}/{inner_id}`, the
+//! IDs their status events carry.
+
+use crate::partial::compute_dirty_set;
+use hb_core::graph::{NodeEntry, WorkflowSpec};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+#[derive(Debug, Default)]
+pub struct NodeCancellation {
+    nodes: Mutex<HashSet<String>>,
+    /// Every node ID of the running workflow, once [`Self::track`] is called.
+    known: Mutex<Option<HashSet<String>>>,
+    changed: Notify,
+}
+
+impl NodeCancellation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take the node IDs of `spec`, nested and referenced ones included, as
+    /// the ones [`Self::cancel`] accepts.
+    pub fn track(&self, spec: &WorkflowSpec, workflows: &HashMap<Uuid, WorkflowSpec>) {
+        let mut ids = HashSet::new();
+        collect_ids(&spec.nodes, "", workflows, &mut vec![spec.id], &mut ids);
+        *self.known.lock().unwrap() = Some(ids);
+    }
+
+    /// Cancel `node_id`; false if it already was. An ID the tracked
+    /// workflow does not have is an error.
+    pub fn cancel(&self, node_id: &str) -> Result<bool, String> {
+        if let Some(known) = &*self.known.lock().unwrap() {
+            if !known.contains(node_id) {
+                return Err(format!("The execution has no node '{node_id}'"));
+            }
+        }
+        let added = self.nodes.lock().unwrap().insert(node_id.to_string());
+        self.changed.notify_waiters();
+        Ok(added)
+    }
+
+    pub fn is_cancelled(&self, node_id: &str) -> bool {
+        self.nodes.lock().unwrap().contains(node_id)
+    }
+
+    /// The cancelled nodes of `spec` and all their downstream dependents,
+    /// by their IDs within `spec`. `prefix` is what the IDs of `spec`'s
+    /// nodes are reported under, e.g. `{ref_node}/` in a referenced workflow.
+    pub fn affected(&self, spec: &WorkflowSpec, prefix: &str) -> HashSet<String> {
+        let nodes: Vec<String> = self
+            .nodes
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|id| id.strip_prefix(prefix))
+            .map(str::to_string)
+            .collect();
+        if nodes.is_empty() {
+            return HashSet::new();
+        }
+        compute_dirty_set(spec, &nodes)
+    }
+
+    /// Wait until `node_id` is cancelled.
+    pub async fn cancelled(&self, node_id: &str) {
+        loop {
+            let changed = self.changed.notified();
+            tokio::pin!(changed);
+            // Registered before the check, so a cancel in between is not missed
+            changed.as_mut().enable();
+            if self.is_cancelled(node_id) {
+                return;
+            }
+            changed.await;
+        }
+    }
+}
+
+/// The IDs of `nodes` under `prefix`, descending into sub-graphs and
+/// referenced workflows; `chain` guards against reference cycles.
+fn collect_ids(
+    nodes: &[NodeEntry],
+    prefix: &str,
+    workflows: &HashMap<Uuid, WorkflowSpec>,
+    chain: &mut Vec<Uuid>,
+    ids: &mut HashSet<String>,
+) {
+    for node in nodes {
+        ids.insert(format!("{prefix}{}", node.id()));
+        for subgraph in node.subgraphs() {
+            // Sub-graph nodes report under their own IDs
+            collect_ids(&subgraph.nodes, "", workflows, chain, ids);
+        }
+        if let NodeEntry::WorkflowRef(r) = node {
+            if let Some(spec) = workflows.get(&r.workflow_id).filter(|_| !chain.contains(&r.workflow_id)) {
+                chain.push(r.workflow_id);
+                collect_ids(&spec.nodes, &format!("{prefix}{}/", r.id), workflows, chain, ids);
+                chain.pop();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hb_core::graph::EdgeSpec;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn cancelling_a_node_reaches_its_dependents_and_waiters() {
+        let edge = |source: &str, target: &str| EdgeSpec {
+            id: format!("{source}-{target}"),
+            source_node: source.into(),
+            source_port: "out".into(),
+            target_node: target.into(),
+            target_port: "in".into(),
+            ..Default::default()
+        };
+        // a → b → c, d
+        let spec = WorkflowSpec {
+            edges: vec![edge("a", "b"), edge("b", "c")],
+            ..Default::default()
+        };
+        let cancellation = Arc::new(NodeCancellation::new());
+        assert!(cancellation.affected(&spec, "").is_empty());
+
+        let waiter = {
+            let cancellation = cancellation.clone();
+            tokio::spawn(async move { cancellation.cancelled("b").await })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiter.is_finished());
+        assert!(cancellation.cancel("b").unwrap());
+        assert!(!cancellation.cancel("b").unwrap());
+        tokio::time::timeout(Duration::from_secs(1), waiter).await.unwrap().unwrap();
+
+        let mut affected: Vec<_> = cancellation.affected(&spec, "").into_iter().collect();
+        affected.sort();
+        assert_eq!(affected, ["b", "c"]);
+    }
+}
@@ -0,0 +1,95 @@
+//! Edge capture — snapshots of the values crossing edges, recorded per the
+//! workflow's [`EdgeCapturePolicy`] for time-travel inspection of a run.
+
+use crate::fan_in::edge_value;
+use chrono::Utc;
+use hb_core::graph::{EdgeKind, EdgeSpec};
+use hb_core::policy::EdgeCapturePolicy;
+use hb_core::trace::EdgeValue;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Characters kept in the preview of a value over the size limit.
+const PREVIEW_CHARS: usize = 1024;
+
+/// Snapshot the values delivered to `node_id` over its incoming data edges.
+/// Edges from nodes that produced no output are skipped.
+pub fn capture_inputs<'a>(
+    policy: &EdgeCapturePolicy,
+    execution_id: Uuid,
+    node_id: &str,
+    edges: impl IntoIterator<Item = &'a EdgeSpec>,
+    outputs: &HashMap<String, serde_json::Value>,
+) -> Vec<EdgeValue> {
+    edges
+        .into_iter()
+        .filter(|e| e.target_node == node_id && e.kind == EdgeKind::Data && policy.captures(&e.id))
+        .filter_map(|edge| {
+            let value = edge_value(edge, outputs.get(&edge.source_node)?);
+            let serialized = value.to_string();
+            let size_bytes = serialized.len();
+            let truncated = policy.max_value_bytes.is_some_and(|max| size_bytes > max);
+            let value = if truncated {
+                let limit = policy.max_value_bytes.unwrap_or(0).min(PREVIEW_CHARS);
+                serde_json::Value::String(serialized.chars().take(limit).collect())
+            } else {
+                value
+            };
+            Some(EdgeValue {
+                execution_id,
+                edge_id: edge.id.clone(),
+                source_node: edge.source_node.clone(),
+                source_port: edge.source_port.clone(),
+                target_node: edge.target_node.clone(),
+                target_port: edge.target_port.clone(),
+                value,
+                truncated,
+                size_bytes: size_bytes as u64,
+                recorded_at: Utc::now(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn edge(id: &str, from: &str) -> EdgeSpec {
+        EdgeSpec {
+            id: id.into(),
+            source_node: from.into(),
+            source_port: "out".into(),
+            target_node: "join".into(),
+            target_port: "in".into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn captures_per_policy_and_truncates() {
+        let edges = vec![edge("e1", "a"), edge("e2", "b"), edge("e3", "missing")];
+        let outputs = HashMap::from([
+            ("a".to_string(), json!({ "out": 1 })),
+            ("b".to_string(), json!({ "out": "x".repeat(100) })),
+        ]);
+        let mut policy = EdgeCapturePolicy {
+            enabled: true,
+            max_value_bytes: Some(10),
+            edges: vec![],
+        };
+
+        let values = capture_inputs(&policy, Uuid::new_v4(), "join", &edges, &outputs);
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[0].value, json!(1));
+        assert!(!values[0].truncated);
+        assert!(values[1].truncated);
+        assert_eq!(values[1].size_bytes, 102);
+        assert_eq!(values[1].value.as_str().unwrap().len(), 10);
+
+        policy.edges = vec!["e1".into()];
+        let values = capture_inputs(&policy, Uuid::new_v4(), "join", &edges, &outputs);
+        assert_eq!(values.len(), 1);
+    }
+}
@@ -0,0 +1,274 @@
+//! Checkpoints — node outputs an earlier run of an execution left in the
+//! trace store, restored so a resumed run starts from its failure frontier.
+//!
+//! Every finished node already has its output persisted as a span, so the
+//! checkpoint is rebuilt from those spans rather than written separately. A
+//! node is only restored if it succeeded, still has the tool and config it
+//! ran with, and nothing upstream of it has to run again. Structured nodes
+//! carry a [`fingerprint`] of their whole definition, referenced workflows
+//! included, in their span.
+
+use crate::env::resolve_env_refs;
+use crate::partial::compute_dirty_set;
+use crate::RunnerError;
+use hb_core::graph::{NodeEntry, WorkflowSpec};
+use hb_core::trace::{ExecutionRecord, ExecutionStatus, NodeSpan};
+use hb_trace::store::TraceStore;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// Key of the fingerprint in a structured node's span environment.
+pub(crate) const FINGERPRINT_KEY: &str = "node_fingerprint";
+
+/// Outputs of the nodes a resumed run does not execute again.
+#[derive(Debug, Clone, Default)]
+pub struct Checkpoint {
+    pub outputs: BTreeMap<String, serde_json::Value>,
+}
+
+impl Checkpoint {
+    /// Load the record of `execution_id` and the outputs its spans restore
+    /// for `spec`, with `workflows` holding the workflows it references.
+    /// Only failed, cancelled or interrupted executions of the same workflow
+    /// can be resumed.
+    pub fn load(
+        store: &dyn TraceStore,
+        execution_id: Uuid,
+        spec: &WorkflowSpec,
+        workflows: &HashMap<Uuid, WorkflowSpec>,
+        env: &BTreeMap<String, String>,
+    ) -> Result<(ExecutionRecord, Self), RunnerError> {
+        let trace_err = |e: hb_trace::TraceError| RunnerError::Resume(e.to_string());
+        store.flush().map_err(trace_err)?;
+        let record = store
+            .query_execution(execution_id)
+            .map_err(trace_err)?
+            .ok_or_else(|| RunnerError::Resume(format!("execution {execution_id} not found")))?;
+        if record.workflow_id != spec.id {
+            return Err(RunnerError::Resume(format!(
+                "execution {execution_id} ran workflow {}, not {}",
+                record.workflow_id, spec.id
+            )));
+        }
+        if !matches!(
+            record.status,
+            ExecutionStatus::Failed | ExecutionStatus::Cancelled | ExecutionStatus::Interrupted
+        ) {
+            return Err(RunnerError::Resume(format!(
+                "execution {execution_id} is {:?}; only failed, cancelled or interrupted runs can be resumed",
+                record.status
+            )));
+        }
+
+        let spans = store.query_spans_by_execution(execution_id).map_err(trace_err)?;
+        Ok((record, Self::from_spans(spec, &spans, workflows, env)))
+    }
+
+    /// The outputs `spans` restore for `spec`. A node's latest span counts.
+    pub fn from_spans(
+        spec: &WorkflowSpec,
+        spans: &[NodeSpan],
+        workflows: &HashMap<Uuid, WorkflowSpec>,
+        env: &BTreeMap<String, String>,
+    ) -> Self {
+        let mut latest: HashMap<&str, &NodeSpan> = HashMap::new();
+        for span in spans {
+            match latest.get(span.node_id.as_str()) {
+                Some(seen) if seen.started_at > span.started_at => {}
+                _ => {
+                    latest.insert(&span.node_id, span);
+                }
+            }
+        }
+
+        let mut outputs = BTreeMap::new();
+        let mut stale = Vec::new();
+        for node in &spec.nodes {
+            let output = latest
+                .get(node.id())
+                .filter(|span| still_valid(node, span, workflows, env))
+                .and_then(|span| span.output_json.clone());
+            match output {
+                Some(output) => {
+                    outputs.insert(node.id().to_string(), output);
+                }
+                None => stale.push(node.id().to_string()),
+            }
+        }
+
+        // Whatever runs again may change what its dependents receive
+        for node_id in compute_dirty_set(spec, &stale) {
+            outputs.remove(&node_id);
+        }
+        Self { outputs }
+    }
+
+    pub fn contains(&self, node_id: &str) -> bool {
+        self.outputs.contains_key(node_id)
+    }
+}
+
+/// Fingerprint of a structured node as it runs: the whole entry, with the
+/// workflows it references inlined and `{{env:…}}` references resolved.
+pub fn fingerprint(
+    node: &NodeEntry,
+    workflows: &HashMap<Uuid, WorkflowSpec>,
+    env: &BTreeMap<String, String>,
+) -> String {
+    let single = WorkflowSpec {
+        nodes: vec![node.clone()],
+        ..Default::default()
+    };
+    let expanded = crate::workflow_ref::expand(&single, workflows);
+    let value = serde_json::to_value(&expanded.nodes[0]).unwrap_or_default();
+    let (resolved, _) = resolve_env_refs(&value, env);
+    hex::encode(Sha256::digest(resolved.to_string().as_bytes()))
+}
+
+/// Whether `span` succeeded with the node as it is now specified.
+fn still_valid(
+    node: &NodeEntry,
+    span: &NodeSpan,
+    workflows: &HashMap<Uuid, WorkflowSpec>,
+    env: &BTreeMap<String, String>,
+) -> bool {
+    if !matches!(span.status, ExecutionStatus::Completed | ExecutionStatus::CacheHit) {
+        return false;
+    }
+    let tool_ref = match node {
+        NodeEntry::Primitive(n) => {
+            // Memoized spans keep the config as written, others as resolved
+            let config = serde_json::Value::Object(n.config.clone());
+            let (resolved, _) = resolve_env_refs(&config, env);
+            return span.tool_ref == n.tool_ref
+                && (span.config_json == resolved || span.config_json == config);
+        }
+        NodeEntry::Composite(_) => "composite",
+        NodeEntry::Conditional(_) => "conditional",
+        NodeEntry::Loop(_) => "loop",
+        NodeEntry::MapReduce(_) => "map_reduce",
+        NodeEntry::WorkflowRef(_) => "workflow_ref",
+    };
+    span.tool_ref == tool_ref
+        && span.environment.extra.get(FINGERPRINT_KEY).and_then(|v| v.as_str())
+            == Some(fingerprint(node, workflows, env).as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hb_core::graph::{EdgeSpec, NodeSpec};
+    use hb_core::trace::ExecutionEnvironment;
+
+    fn node(id: &str, config: serde_json::Value) -> NodeEntry {
+        NodeEntry::Primitive(NodeSpec {
+            id: id.into(),
+            tool_ref: "t".into(),
+            config: config.as_object().unwrap().clone(),
+            ..Default::default()
+        })
+    }
+
+    fn span(node_id: &str, config: serde_json::Value, status: ExecutionStatus) -> NodeSpan {
+        NodeSpan {
+            span_id: Uuid::new_v4(),
+            execution_id: Uuid::nil(),
+            node_id: node_id.into(),
+            tool_ref: "t".into(),
+            input_json: serde_json::json!({}),
+            output_json: Some(serde_json::json!({ "out": node_id })),
+            config_json: config,
+            started_at: chrono::Utc::now(),
+            completed_at: Some(chrono::Utc::now()),
+            duration_ms: Some(1),
+            status,
+            error: None,
+            cache_hit: false,
+            environment: ExecutionEnvironment {
+                platform_version: "0.1.0".into(),
+                os: "test".into(),
+                tool_version: "1.0.0".into(),
+                extra: Default::default(),
+            },
+        }
+    }
+
+    #[test]
+    fn changed_or_failed_nodes_invalidate_their_dependents() {
+        let edge = |source: &str, target: &str| EdgeSpec {
+            id: format!("{source}-{target}"),
+            source_node: source.into(),
+            source_port: "out".into(),
+            target_node: target.into(),
+            target_port: "in".into(),
+            ..Default::default()
+        };
+        // a → b → c, d → e
+        let spec = WorkflowSpec {
+            nodes: ["a", "b", "c", "d", "e"]
+                .map(|id| node(id, serde_json::json!({ "path": "{{env:DIR}}/x" })))
+                .into(),
+            edges: vec![edge("a", "b"), edge("b", "c"), edge("d", "e")],
+            ..Default::default()
+        };
+        let env = BTreeMap::from([("DIR".to_string(), "/data".to_string())]);
+        let resolved = serde_json::json!({ "path": "/data/x" });
+        let spans = vec![
+            span("a", resolved.clone(), ExecutionStatus::Completed),
+            // Ran with a different config, so b and c run again
+            span("b", serde_json::json!({ "path": "/old/x" }), ExecutionStatus::Completed),
+            span("c", resolved.clone(), ExecutionStatus::Completed),
+            // Memoized spans keep the config as written
+            span("d", serde_json::json!({ "path": "{{env:DIR}}/x" }), ExecutionStatus::CacheHit),
+            span("e", resolved, ExecutionStatus::Failed),
+        ];
+
+        let checkpoint = Checkpoint::from_spans(&spec, &spans, &HashMap::new(), &env);
+        assert_eq!(checkpoint.outputs.keys().collect::<Vec<_>>(), ["a", "d"]);
+    }
+
+    #[test]
+    fn referenced_workflows_are_part_of_the_fingerprint() {
+        let referenced = WorkflowSpec {
+            id: Uuid::new_v4(),
+            nodes: vec![node("inner", serde_json::json!({ "path": "{{env:DIR}}/x" }))],
+            ..Default::default()
+        };
+        let spec = WorkflowSpec {
+            nodes: vec![NodeEntry::WorkflowRef(hb_core::graph::WorkflowRefSpec {
+                id: "r".into(),
+                workflow_id: referenced.id,
+                input_ports: Vec::new(),
+                output_ports: Vec::new(),
+                input_mapping: Vec::new(),
+                output_mapping: Vec::new(),
+                position: None,
+                label: None,
+            })],
+            ..Default::default()
+        };
+        let env = BTreeMap::from([("DIR".to_string(), "/data".to_string())]);
+        let workflows = HashMap::from([(referenced.id, referenced.clone())]);
+        let mut ran = span("r", serde_json::json!({}), ExecutionStatus::Completed);
+        ran.tool_ref = "workflow_ref".into();
+        ran.environment.extra.insert(
+            FINGERPRINT_KEY.into(),
+            fingerprint(&spec.nodes[0], &workflows, &env).into(),
+        );
+        let spans = [ran];
+
+        let restored = Checkpoint::from_spans(&spec, &spans, &workflows, &env);
+        assert!(restored.contains("r"));
+
+        // An environment variable the referenced workflow resolves changed
+        let moved = BTreeMap::from([("DIR".to_string(), "/other".to_string())]);
+        assert!(!Checkpoint::from_spans(&spec, &spans, &workflows, &moved).contains("r"));
+
+        // The same node, but the workflow it runs was edited
+        let mut edited = referenced;
+        edited.nodes = vec![node("inner", serde_json::json!({ "path": "/elsewhere" }))];
+        let workflows = HashMap::from([(edited.id, edited)]);
+        assert!(!Checkpoint::from_spans(&spec, &spans, &workflows, &env).contains("r"));
+    }
+}
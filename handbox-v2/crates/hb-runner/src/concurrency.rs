@@ -0,0 +1,158 @@
+//! Concurrency limits — caps on how many nodes of one execution run at once,
+//! overall and per resource class ("llm", "docker", "cpu", ...), so a wide
+//! level doesn't fire dozens of LLM calls together and trip rate limits.
+//!
+//! Only tool nodes take a slot. Composite, loop and map-reduce nodes don't,
+//! so their bodies never wait on their own parent. A node waits for its
+//! class before taking an overall slot, so nodes queued behind a busy class
+//! don't hold back nodes of other classes. A node waiting to retry gives
+//! its slots up until the retry.
+
+use hb_core::graph::NodeSpec;
+use hb_core::policy::ConcurrencyPolicy;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Class of agent-task nodes, which run an LLM agent loop.
+const AGENT_TASK_CLASS: &str = "llm";
+
+pub struct ConcurrencyLimits {
+    overall: Option<Arc<Semaphore>>,
+    max_parallel: Option<usize>,
+    classes: HashMap<String, Arc<Semaphore>>,
+    /// Resource class of each tool, keyed by tool ID (without `@version`).
+    tools: HashMap<String, String>,
+}
+
+/// Slots held by a running node; released when dropped.
+pub struct Slot {
+    _class: Option<OwnedSemaphorePermit>,
+    _overall: Option<OwnedSemaphorePermit>,
+}
+
+impl ConcurrencyLimits {
+    /// Limits per `policy`, with `tools` giving each tool's resource class
+    /// (see [`hb_core::tool::ToolInterface::resource_class`]).
+    pub fn new(policy: &ConcurrencyPolicy, tools: HashMap<String, String>) -> Self {
+        let classes = policy
+            .classes
+            .keys()
+            .filter_map(|class| {
+                let limit = policy.class_limit(class)?;
+                Some((class.clone(), Arc::new(Semaphore::new(limit))))
+            })
+            .collect();
+        let max_parallel = policy.max_parallel();
+        Self {
+            overall: max_parallel.map(|n| Arc::new(Semaphore::new(n))),
+            max_parallel,
+            classes,
+            tools,
+        }
+    }
+
+    /// How many tool nodes may run at once overall (unlimited when none).
+    pub fn max_parallel(&self) -> Option<usize> {
+        self.max_parallel
+    }
+
+    /// The class `node` counts against: the one it names, else its tool's.
+    pub fn class_of<'a>(&'a self, node: &'a NodeSpec) -> Option<&'a str> {
+        if let Some(class) = node.resource_class.as_deref().filter(|c| !c.is_empty()) {
+            return Some(class);
+        }
+        if node.tool_ref == "agent-task" {
+            return Some(AGENT_TASK_CLASS);
+        }
+        let tool_id = node.tool_ref.split('@').next().unwrap_or(&node.tool_ref);
+        self.tools.get(tool_id).map(String::as_str)
+    }
+
+    /// Wait for a slot for a node of `class`.
+    pub async fn acquire(&self, class: Option<&str>) -> Slot {
+        // The semaphores are never closed, so acquiring can't fail
+        let class = match class.and_then(|c| self.classes.get(c)) {
+            Some(semaphore) => semaphore.clone().acquire_owned().await.ok(),
+            None => None,
+        };
+        let overall = match &self.overall {
+            Some(semaphore) => semaphore.clone().acquire_owned().await.ok(),
+            None => None,
+        };
+        Slot {
+            _class: class,
+            _overall: overall,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn node(tool_ref: &str, resource_class: Option<&str>) -> NodeSpec {
+        NodeSpec {
+            id: "n".into(),
+            tool_ref: tool_ref.into(),
+            resource_class: resource_class.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn nodes_name_their_class_or_take_their_tools() {
+        let tools = HashMap::from([("core-tools/llm-chat".to_string(), "llm".to_string())]);
+        let limits = ConcurrencyLimits::new(&ConcurrencyPolicy::default(), tools);
+        assert_eq!(
+            limits.class_of(&node("core-tools/llm-chat@1.0.0", None)),
+            Some("llm")
+        );
+        assert_eq!(
+            limits.class_of(&node("core-tools/llm-chat", Some("gpu"))),
+            Some("gpu")
+        );
+        assert_eq!(limits.class_of(&node("agent-task", None)), Some("llm"));
+        assert_eq!(limits.class_of(&node("core-tools/json-parse", None)), None);
+    }
+
+    #[tokio::test]
+    async fn classes_and_the_overall_limit_bound_running_nodes() {
+        let policy = ConcurrencyPolicy {
+            max_parallel: Some(3),
+            classes: BTreeMap::from([("llm".into(), 2), ("cpu".into(), 0)]),
+        };
+        let limits = Arc::new(ConcurrencyLimits::new(&policy, HashMap::new()));
+        let peak = |class: Option<&'static str>, tasks: usize| {
+            let limits = limits.clone();
+            async move {
+                let (running, peak) =
+                    (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+                let handles: Vec<_> = (0..tasks)
+                    .map(|_| {
+                        let (limits, running, peak) =
+                            (limits.clone(), running.clone(), peak.clone());
+                        tokio::spawn(async move {
+                            let _slot = limits.acquire(class).await;
+                            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                            peak.fetch_max(now, Ordering::SeqCst);
+                            tokio::time::sleep(Duration::from_millis(20)).await;
+                            running.fetch_sub(1, Ordering::SeqCst);
+                        })
+                    })
+                    .collect();
+                for handle in handles {
+                    handle.await.unwrap();
+                }
+                peak.load(Ordering::SeqCst)
+            }
+        };
+        assert_eq!(peak(Some("llm"), 6).await, 2);
+        // A zero limit is no limit, leaving only the overall one
+        assert_eq!(peak(Some("cpu"), 6).await, 3);
+        assert_eq!(peak(None, 6).await, 3);
+    }
+}
@@ -0,0 +1,263 @@
+//! Output contracts — what a workflow declares its terminal nodes must
+//! produce ([`WorkflowMeta::output_contract`](hb_core::graph::WorkflowMeta)).
+//!
+//! Checked once every node has succeeded: a stub tool upstream that returns
+//! an empty string or `[]` lets a run complete without producing anything,
+//! and the contract is what tells the two apart. A run that breaks it ends
+//! as [`ExecutionStatus::CompletedWithViolations`](hb_core::trace::ExecutionStatus)
+//! with the broken assertions on its record.
+
+use hb_core::graph::{split_variadic_port, OutputAssertion, PortType};
+use hb_core::trace::ContractViolation;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Check `contract` against the node outputs of a completed run.
+pub fn check(
+    contract: &[OutputAssertion],
+    outputs: &HashMap<String, Value>,
+) -> Vec<ContractViolation> {
+    let mut violations = Vec::new();
+    for assertion in contract {
+        let violation = |message: String| ContractViolation {
+            node_id: assertion.node_id.clone(),
+            port: assertion.port.clone(),
+            message,
+        };
+        let Some(output) = outputs.get(&assertion.node_id) else {
+            violations.push(violation(format!(
+                "node '{}' produced no output",
+                assertion.node_id
+            )));
+            continue;
+        };
+        let Some(value) = port_value(output, &assertion.port) else {
+            violations.push(violation(format!("port '{}' is missing", assertion.port)));
+            continue;
+        };
+        if let Some(port_type) = &assertion.port_type {
+            if !is_type(value, port_type) {
+                violations.push(violation(format!(
+                    "expected {port_type:?}, got {}",
+                    type_name(value)
+                )));
+                continue;
+            }
+        }
+        if assertion.non_empty && is_empty(value) {
+            violations.push(violation("value is empty".into()));
+            continue;
+        }
+        if let Some(schema) = &assertion.schema {
+            let mut errors = Vec::new();
+            check_schema(value, schema, "", &mut errors);
+            violations.extend(errors.into_iter().map(violation));
+        }
+    }
+    violations
+}
+
+/// The value of `port` in a node's output; a variadic port `cases.a` reads
+/// `output["cases"]["a"]`.
+fn port_value<'a>(output: &'a Value, port: &str) -> Option<&'a Value> {
+    output
+        .get(port)
+        .or_else(|| match split_variadic_port(port) {
+            (base, Some(key)) => output.get(base)?.get(key),
+            (_, None) => None,
+        })
+}
+
+fn is_type(value: &Value, port_type: &PortType) -> bool {
+    match port_type {
+        PortType::String => value.is_string(),
+        PortType::Number => value.is_number(),
+        PortType::Boolean => value.is_boolean(),
+        PortType::Array => value.is_array(),
+        PortType::Json => value.is_object() || value.is_array(),
+        // Binary payloads travel as base64 strings or file references
+        PortType::Binary => value.is_string() || value.is_object(),
+        PortType::Any => true,
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.trim().is_empty(),
+        Value::Array(items) => items.is_empty(),
+        Value::Object(fields) => fields.is_empty(),
+        Value::Bool(_) | Value::Number(_) => false,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Check the JSON Schema keywords contracts use; unknown keywords are
+/// ignored. Errors name the offending location as a JSON pointer.
+fn check_schema(value: &Value, schema: &Value, at: &str, errors: &mut Vec<String>) {
+    let location = if at.is_empty() { "value" } else { at };
+    if let Some(expected) = schema.get("type").and_then(Value::as_str) {
+        let matches = match expected {
+            "integer" => value.is_i64() || value.is_u64(),
+            "number" => value.is_number(),
+            other => type_name(value) == other,
+        };
+        if !matches {
+            errors.push(format!(
+                "{location}: expected {expected}, got {}",
+                type_name(value)
+            ));
+            return;
+        }
+    }
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            errors.push(format!(
+                "{location}: {value} is not one of the allowed values"
+            ));
+        }
+    }
+    if let (Some(min), Some(text)) = (
+        schema.get("minLength").and_then(Value::as_u64),
+        value.as_str(),
+    ) {
+        if (text.chars().count() as u64) < min {
+            errors.push(format!("{location}: shorter than {min} character(s)"));
+        }
+    }
+    if let Some(number) = value.as_f64() {
+        if let Some(min) = schema
+            .get("minimum")
+            .and_then(Value::as_f64)
+            .filter(|min| number < *min)
+        {
+            errors.push(format!("{location}: {number} is below the minimum {min}"));
+        }
+        if let Some(max) = schema
+            .get("maximum")
+            .and_then(Value::as_f64)
+            .filter(|max| number > *max)
+        {
+            errors.push(format!("{location}: {number} is above the maximum {max}"));
+        }
+    }
+    if let Some(items) = value.as_array() {
+        if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+            if (items.len() as u64) < min {
+                errors.push(format!("{location}: fewer than {min} item(s)"));
+            }
+        }
+        if let Some(item_schema) = schema.get("items") {
+            for (i, item) in items.iter().enumerate() {
+                check_schema(item, item_schema, &format!("{at}/{i}"), errors);
+            }
+        }
+    }
+    if let Some(fields) = value.as_object() {
+        for name in schema
+            .get("required")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let Some(name) = name.as_str() else { continue };
+            if !fields.contains_key(name) {
+                errors.push(format!("{location}: missing required field '{name}'"));
+            }
+        }
+        for (name, property) in schema
+            .get("properties")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+        {
+            if let Some(field) = fields.get(name) {
+                check_schema(field, property, &format!("{at}/{name}"), errors);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn assertion(node_id: &str, port: &str) -> OutputAssertion {
+        OutputAssertion {
+            node_id: node_id.into(),
+            port: port.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn assertions_catch_missing_mistyped_and_empty_outputs() {
+        let outputs = HashMap::from([
+            (
+                "report".to_string(),
+                json!({ "text": "", "rows": [{ "name": "A" }, { "count": 2 }] }),
+            ),
+            (
+                "summary".to_string(),
+                json!({ "text": "Footing pour complete" }),
+            ),
+        ]);
+        let contract = vec![
+            OutputAssertion {
+                non_empty: true,
+                port_type: Some(PortType::String),
+                ..assertion("summary", "text")
+            },
+            OutputAssertion {
+                non_empty: true,
+                ..assertion("report", "text")
+            },
+            OutputAssertion {
+                port_type: Some(PortType::String),
+                ..assertion("report", "rows")
+            },
+            OutputAssertion {
+                schema: Some(json!({
+                    "type": "array",
+                    "minItems": 1,
+                    "items": { "type": "object", "required": ["name"] }
+                })),
+                ..assertion("report", "rows")
+            },
+            assertion("report", "chart"),
+            assertion("export", "path"),
+        ];
+
+        let messages: Vec<(String, String)> = check(&contract, &outputs)
+            .into_iter()
+            .map(|v| (format!("{}.{}", v.node_id, v.port), v.message))
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                ("report.text".to_string(), "value is empty".to_string()),
+                ("report.rows".into(), "expected String, got array".into()),
+                (
+                    "report.rows".into(),
+                    "/1: missing required field 'name'".into()
+                ),
+                ("report.chart".into(), "port 'chart' is missing".into()),
+                (
+                    "export.path".into(),
+                    "node 'export' produced no output".into()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,573 @@
+//! Cron expressions — parsing and validation, human-readable descriptions,
+//! and next-occurrence computation in a timezone.
+//!
+//! Supports the standard five fields (minute, hour, day of month, month, day
+//! of week) with `*`, lists, ranges, steps, month/weekday names, and the
+//! `@hourly`, `@daily`, `@weekly`, `@monthly` and `@yearly` macros. As in
+//! Vixie cron, when both day fields are restricted a day matches either.
+
+use chrono::{
+    DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc,
+};
+use chrono_tz::Tz;
+use thiserror::Error;
+
+/// How far ahead to search before concluding an expression never fires
+/// (e.g. `0 0 30 2 *`).
+const SEARCH_YEARS: i64 = 5;
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum CronError {
+    #[error("expected 5 fields (minute hour day-of-month month day-of-week), got {0}")]
+    FieldCount(usize),
+    #[error("{field}: {message}")]
+    InvalidField {
+        field: &'static str,
+        message: String,
+    },
+    #[error("unknown macro: {0}")]
+    UnknownMacro(String),
+    #[error("unknown timezone: {0}")]
+    UnknownTimezone(String),
+}
+
+impl From<CronError> for hb_core::error::AppError {
+    fn from(e: CronError) -> Self {
+        let err = hb_core::error::AppError::invalid_input(e.to_string());
+        match e.field() {
+            Some(field) => err.with_details(serde_json::json!({ "field": field })),
+            None => err,
+        }
+    }
+}
+
+impl CronError {
+    /// The field the error is in, for highlighting while typing.
+    pub fn field(&self) -> Option<&'static str> {
+        match self {
+            Self::InvalidField { field, .. } => Some(field),
+            _ => None,
+        }
+    }
+}
+
+/// Parse an IANA timezone name (e.g. `Asia/Seoul`).
+pub fn parse_timezone(name: &str) -> Result<Tz, CronError> {
+    name.parse()
+        .map_err(|_| CronError::UnknownTimezone(name.to_string()))
+}
+
+/// Parse an optional timezone name, defaulting to the local zone.
+pub fn resolve_timezone(name: Option<&str>) -> Result<Tz, CronError> {
+    name.map_or_else(|| Ok(local_timezone()), parse_timezone)
+}
+
+/// The machine's timezone, or UTC when it has none with an IANA name.
+pub fn local_timezone() -> Tz {
+    iana_time_zone::get_timezone()
+        .ok()
+        .and_then(|name| name.parse().ok())
+        .unwrap_or(Tz::UTC)
+}
+
+struct FieldKind {
+    name: &'static str,
+    min: u32,
+    max: u32,
+    unit: &'static str,
+    names: &'static [&'static str],
+    /// Value the first entry of `names` stands for.
+    names_start: u32,
+}
+
+const MINUTE: FieldKind = FieldKind {
+    name: "minute",
+    min: 0,
+    max: 59,
+    unit: "minute",
+    names: &[],
+    names_start: 0,
+};
+const HOUR: FieldKind = FieldKind {
+    name: "hour",
+    min: 0,
+    max: 23,
+    unit: "hour",
+    names: &[],
+    names_start: 0,
+};
+const DAY_OF_MONTH: FieldKind = FieldKind {
+    name: "day-of-month",
+    min: 1,
+    max: 31,
+    unit: "day",
+    names: &[],
+    names_start: 0,
+};
+const MONTH: FieldKind = FieldKind {
+    name: "month",
+    min: 1,
+    max: 12,
+    unit: "month",
+    names: &MONTHS,
+    names_start: 1,
+};
+// 7 is accepted as an alias of Sunday.
+const DAY_OF_WEEK: FieldKind = FieldKind {
+    name: "day-of-week",
+    min: 0,
+    max: 7,
+    unit: "day",
+    names: &WEEKDAYS,
+    names_start: 0,
+};
+
+/// One comma-separated part of a field: `start-end/step`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Part {
+    start: u32,
+    end: u32,
+    step: u32,
+    star: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Field {
+    bits: u64,
+    parts: Vec<Part>,
+}
+
+impl Field {
+    fn contains(&self, value: u32) -> bool {
+        self.bits & (1 << value) != 0
+    }
+
+    /// Unrestricted (`*`): matches every value.
+    fn is_any(&self) -> bool {
+        matches!(
+            self.parts.as_slice(),
+            [Part {
+                star: true,
+                step: 1,
+                ..
+            }]
+        )
+    }
+
+    /// Written with a leading `*` (incl. `*/n`), which counts as unrestricted
+    /// for the day-of-month/day-of-week rule.
+    fn is_star(&self) -> bool {
+        self.parts.first().is_some_and(|p| p.star)
+    }
+
+    /// Single values only (no `*`, ranges, or steps).
+    fn singles(&self) -> Option<Vec<u32>> {
+        self.parts
+            .iter()
+            .map(|p| (!p.star && p.start == p.end).then_some(p.start))
+            .collect()
+    }
+}
+
+fn parse_value(text: &str, kind: &FieldKind) -> Result<u32, CronError> {
+    let invalid = |message: String| CronError::InvalidField {
+        field: kind.name,
+        message,
+    };
+    let value = match text.parse::<u32>() {
+        Ok(v) => v,
+        Err(_) => {
+            let upper = text.to_ascii_uppercase();
+            kind.names
+                .iter()
+                .position(|n| upper.len() == 3 && n.to_ascii_uppercase().starts_with(&upper))
+                .map(|i| i as u32 + kind.names_start)
+                .ok_or_else(|| invalid(format!("'{text}' is not a number or name")))?
+        }
+    };
+    if value < kind.min || value > kind.max {
+        return Err(invalid(format!(
+            "{value} is out of range {}-{}",
+            kind.min, kind.max
+        )));
+    }
+    Ok(value)
+}
+
+fn parse_field(text: &str, kind: &FieldKind) -> Result<Field, CronError> {
+    let invalid = |message: String| CronError::InvalidField {
+        field: kind.name,
+        message,
+    };
+    let mut field = Field {
+        bits: 0,
+        parts: Vec::new(),
+    };
+    for item in text.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .map_err(|_| invalid(format!("invalid step '{step}'")))?;
+                if step == 0 {
+                    return Err(invalid("step must be at least 1".into()));
+                }
+                (range, Some(step))
+            }
+            None => (item, None),
+        };
+        let part = if range == "*" {
+            Part {
+                start: kind.min,
+                end: kind.max,
+                step: step.unwrap_or(1),
+                star: true,
+            }
+        } else if let Some((a, b)) = range.split_once('-') {
+            let (start, end) = (parse_value(a, kind)?, parse_value(b, kind)?);
+            if start > end {
+                return Err(invalid(format!("range {a}-{b} is backwards")));
+            }
+            Part {
+                start,
+                end,
+                step: step.unwrap_or(1),
+                star: false,
+            }
+        } else if range.is_empty() {
+            return Err(invalid("empty value".into()));
+        } else {
+            let start = parse_value(range, kind)?;
+            // `a/n` means from `a` to the end of the range in steps of `n`.
+            let end = if step.is_some() { kind.max } else { start };
+            Part {
+                start,
+                end,
+                step: step.unwrap_or(1),
+                star: false,
+            }
+        };
+        let mut v = part.start;
+        while v <= part.end {
+            field.bits |= 1 << v;
+            v += part.step;
+        }
+        field.parts.push(part);
+    }
+    // Day-of-week 7 is Sunday.
+    if kind.name == DAY_OF_WEEK.name && field.contains(7) {
+        field.bits = (field.bits & !(1 << 7)) | 1;
+    }
+    Ok(field)
+}
+
+/// A parsed cron expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronExpr {
+    minutes: Field,
+    hours: Field,
+    days_of_month: Field,
+    months: Field,
+    days_of_week: Field,
+}
+
+impl CronExpr {
+    pub fn parse(expr: &str) -> Result<Self, CronError> {
+        let expr = expr.trim();
+        let expanded = match expr {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            m if m.starts_with('@') => return Err(CronError::UnknownMacro(m.to_string())),
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, dom, month, dow] = fields.as_slice() else {
+            return Err(CronError::FieldCount(fields.len()));
+        };
+        Ok(Self {
+            minutes: parse_field(minute, &MINUTE)?,
+            hours: parse_field(hour, &HOUR)?,
+            days_of_month: parse_field(dom, &DAY_OF_MONTH)?,
+            months: parse_field(month, &MONTH)?,
+            days_of_week: parse_field(dow, &DAY_OF_WEEK)?,
+        })
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let dom = self.days_of_month.contains(date.day());
+        let dow = self
+            .days_of_week
+            .contains(date.weekday().num_days_from_sunday());
+        match (self.days_of_month.is_star(), self.days_of_week.is_star()) {
+            (true, true) => dom && dow,
+            (true, false) => dow,
+            (false, true) => dom,
+            (false, false) => dom || dow,
+        }
+    }
+
+    /// First fire time strictly after `after`, evaluated on the wall clock of
+    /// `tz`. Times skipped by a DST gap do not fire; times repeated by a DST
+    /// overlap fire once, at the earlier instant.
+    pub fn next_after(&self, after: DateTime<Utc>, tz: Tz) -> Option<DateTime<Utc>> {
+        let local = after.with_timezone(&tz).naive_local();
+        let mut t = local.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = t + Duration::days(366 * SEARCH_YEARS);
+
+        while t < limit {
+            if !self.months.contains(t.month()) {
+                let (y, m) = if t.month() == 12 {
+                    (t.year() + 1, 1)
+                } else {
+                    (t.year(), t.month() + 1)
+                };
+                t = NaiveDate::from_ymd_opt(y, m, 1)?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !self.day_matches(t.date()) {
+                t = t.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !self.hours.contains(t.hour()) {
+                t = next_hour(t)?;
+                continue;
+            }
+            if self.minutes.contains(t.minute()) {
+                let fire = match tz.from_local_datetime(&t) {
+                    LocalResult::Single(dt) | LocalResult::Ambiguous(dt, _) => {
+                        Some(dt.with_timezone(&Utc))
+                    }
+                    LocalResult::None => None,
+                };
+                if let Some(fire) = fire.filter(|f| *f > after) {
+                    return Some(fire);
+                }
+            }
+            t += Duration::minutes(1);
+        }
+        None
+    }
+
+    /// The next `count` fire times after `after`.
+    pub fn upcoming(&self, after: DateTime<Utc>, tz: Tz, count: usize) -> Vec<DateTime<Utc>> {
+        let mut times = Vec::with_capacity(count);
+        let mut cursor = after;
+        while times.len() < count {
+            match self.next_after(cursor, tz) {
+                Some(t) => {
+                    times.push(t);
+                    cursor = t;
+                }
+                None => break,
+            }
+        }
+        times
+    }
+
+    /// Human-readable English description, e.g. "At 09:00, Monday through Friday".
+    pub fn describe(&self) -> String {
+        let mut text = self.describe_time();
+        let dom = (!self.days_of_month.is_any()).then(|| {
+            format!(
+                "on day {} of the month",
+                describe_field(&self.days_of_month, &DAY_OF_MONTH)
+            )
+        });
+        let dow =
+            (!self.days_of_week.is_any()).then(|| describe_field(&self.days_of_week, &DAY_OF_WEEK));
+        match (dom, dow) {
+            (Some(dom), Some(dow)) => text.push_str(&format!(", {dom} or {dow}")),
+            (Some(dom), None) => text.push_str(&format!(", {dom}")),
+            (None, Some(dow)) => text.push_str(&format!(", {dow}")),
+            (None, None) => {}
+        }
+        if !self.months.is_any() {
+            text.push_str(&format!(", in {}", describe_field(&self.months, &MONTH)));
+        }
+        text
+    }
+
+    fn describe_time(&self) -> String {
+        if let (Some(minutes), Some(hours)) = (self.minutes.singles(), self.hours.singles()) {
+            if minutes.len() * hours.len() <= 4 {
+                let times: Vec<String> = hours
+                    .iter()
+                    .flat_map(|h| minutes.iter().map(move |m| format!("{h:02}:{m:02}")))
+                    .collect();
+                return format!("At {}", join(&times));
+            }
+        }
+        let minutes = match self.minutes.parts.as_slice() {
+            [Part {
+                star: true,
+                step: 1,
+                ..
+            }] => "Every minute".to_string(),
+            [Part {
+                star: true, step, ..
+            }] => format!("Every {step} minutes"),
+            _ => format!("At minute {}", describe_field(&self.minutes, &MINUTE)),
+        };
+        match self.hours.parts.as_slice() {
+            [Part {
+                star: true,
+                step: 1,
+                ..
+            }] => minutes,
+            [Part {
+                star: true, step, ..
+            }] => format!("{minutes} past every {step} hours"),
+            _ => format!("{minutes} past hour {}", describe_field(&self.hours, &HOUR)),
+        }
+    }
+}
+
+fn next_hour(t: NaiveDateTime) -> Option<NaiveDateTime> {
+    let hour = t.date().and_hms_opt(t.hour(), 0, 0)?;
+    Some(hour + Duration::hours(1))
+}
+
+fn value_name(value: u32, kind: &FieldKind) -> String {
+    if kind.names.is_empty() {
+        return value.to_string();
+    }
+    // `% len` folds day-of-week 7 onto Sunday.
+    kind.names[(value - kind.names_start) as usize % kind.names.len()].to_string()
+}
+
+fn describe_field(field: &Field, kind: &FieldKind) -> String {
+    let parts: Vec<String> = field
+        .parts
+        .iter()
+        .map(|p| match p {
+            Part {
+                star: true, step, ..
+            } => format!("every {step} {}s", kind.unit),
+            Part { start, end, .. } if start == end => value_name(*start, kind),
+            Part {
+                start,
+                end,
+                step: 1,
+                ..
+            } => {
+                format!(
+                    "{} through {}",
+                    value_name(*start, kind),
+                    value_name(*end, kind)
+                )
+            }
+            Part {
+                start, end, step, ..
+            } => format!(
+                "every {step} {}s from {} through {}",
+                kind.unit,
+                value_name(*start, kind),
+                value_name(*end, kind)
+            ),
+        })
+        .collect();
+    join(&parts)
+}
+
+fn join(items: &[String]) -> String {
+    match items {
+        [] => String::new(),
+        [one] => one.clone(),
+        [rest @ .., last] => format!("{} and {last}", rest.join(", ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        assert_eq!(
+            CronExpr::parse("* * *").unwrap_err(),
+            CronError::FieldCount(3)
+        );
+        let err = CronExpr::parse("0 25 * * *").unwrap_err();
+        assert_eq!(err.field(), Some("hour"));
+        assert!(CronExpr::parse("*/0 * * * *").is_err());
+        assert!(CronExpr::parse("0 0 * FOO *").is_err());
+        assert!(CronExpr::parse("@often").is_err());
+        assert!(parse_timezone("Mars/Olympus").is_err());
+    }
+
+    #[test]
+    fn describes_common_expressions() {
+        let d = |e: &str| CronExpr::parse(e).unwrap().describe();
+        assert_eq!(d("* * * * *"), "Every minute");
+        assert_eq!(d("*/15 * * * *"), "Every 15 minutes");
+        assert_eq!(d("0 9 * * 1-5"), "At 09:00, Monday through Friday");
+        assert_eq!(d("30 8,17 * * *"), "At 08:30 and 17:30");
+        assert_eq!(
+            d("0 0 1 jan,jul *"),
+            "At 00:00, on day 1 of the month, in January and July"
+        );
+        assert_eq!(d("0 */2 * * SUN"), "At minute 0 past every 2 hours, Sunday");
+    }
+
+    #[test]
+    fn next_run_respects_timezone() {
+        let cron = CronExpr::parse("0 9 * * 1-5").unwrap();
+        let seoul = parse_timezone("Asia/Seoul").unwrap();
+        // Friday 2024-03-01 10:00 KST → next is Monday 09:00 KST (00:00 UTC).
+        let next = cron.next_after(utc("2024-03-01T01:00:00Z"), seoul).unwrap();
+        assert_eq!(next, utc("2024-03-04T00:00:00Z"));
+
+        let runs = cron.upcoming(utc("2024-03-01T01:00:00Z"), Tz::UTC, 3);
+        assert_eq!(runs[0], utc("2024-03-01T09:00:00Z"));
+        assert_eq!(runs[2], utc("2024-03-05T09:00:00Z"));
+
+        assert!(CronExpr::parse("0 0 30 2 *")
+            .unwrap()
+            .next_after(Utc::now(), Tz::UTC)
+            .is_none());
+
+        // Without a name, schedules follow the machine's clock
+        assert_eq!(resolve_timezone(None).unwrap(), local_timezone());
+        assert_eq!(resolve_timezone(Some("Asia/Seoul")).unwrap(), seoul);
+    }
+
+    #[test]
+    fn dst_gap_is_skipped() {
+        // 02:30 does not exist in New York on 2024-03-10.
+        let cron = CronExpr::parse("30 2 * * *").unwrap();
+        let ny = parse_timezone("America/New_York").unwrap();
+        let next = cron.next_after(utc("2024-03-10T05:00:00Z"), ny).unwrap();
+        assert_eq!(next, utc("2024-03-11T06:30:00Z"));
+    }
+}
@@ -0,0 +1,98 @@
+//! Workspace environment references — substitutes `{{env:NAME}}` in node
+//! config strings with the workspace's managed variables.
+
+use std::collections::BTreeMap;
+
+const OPEN: &str = "{{env:";
+const CLOSE: &str = "}}";
+
+/// Replace every `{{env:NAME}}` in the string values of `value`, at any
+/// depth. References to unknown variables are left as written and returned
+/// so the caller can report them.
+pub fn resolve_env_refs(
+    value: &serde_json::Value,
+    env: &BTreeMap<String, String>,
+) -> (serde_json::Value, Vec<String>) {
+    let mut missing = Vec::new();
+    let resolved = resolve_value(value, env, &mut missing);
+    missing.sort();
+    missing.dedup();
+    (resolved, missing)
+}
+
+fn resolve_value(
+    value: &serde_json::Value,
+    env: &BTreeMap<String, String>,
+    missing: &mut Vec<String>,
+) -> serde_json::Value {
+    match value {
+        serde_json::Value::String(s) if s.contains(OPEN) => {
+            serde_json::Value::String(resolve_str(s, env, missing))
+        }
+        serde_json::Value::Array(items) => items
+            .iter()
+            .map(|item| resolve_value(item, env, missing))
+            .collect(),
+        serde_json::Value::Object(map) => map
+            .iter()
+            .map(|(k, v)| (k.clone(), resolve_value(v, env, missing)))
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
+        other => other.clone(),
+    }
+}
+
+fn resolve_str(s: &str, env: &BTreeMap<String, String>, missing: &mut Vec<String>) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find(OPEN) {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + OPEN.len()..];
+        let Some(end) = after.find(CLOSE) else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        let name = after[..end].trim();
+        match env.get(name) {
+            Some(value) => out.push_str(value),
+            None => {
+                missing.push(name.to_string());
+                out.push_str(&rest[start..start + OPEN.len() + end + CLOSE.len()]);
+            }
+        }
+        rest = &after[end + CLOSE.len()..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn substitutes_nested_references_and_reports_unknown_ones() {
+        let env = BTreeMap::from([
+            ("REGION".to_string(), "ap-northeast-2".to_string()),
+            ("BASE_URL".to_string(), "http://localhost:8080".to_string()),
+        ]);
+        let config = serde_json::json!({
+            "url": "{{env:BASE_URL}}/v1/items?region={{env: REGION }}",
+            "headers": [{ "x-region": "{{env:REGION}}" }],
+            "bucket": "{{env:BUCKET}}",
+            "retries": 3,
+        });
+
+        let (resolved, missing) = resolve_env_refs(&config, &env);
+        assert_eq!(
+            resolved,
+            serde_json::json!({
+                "url": "http://localhost:8080/v1/items?region=ap-northeast-2",
+                "headers": [{ "x-region": "ap-northeast-2" }],
+                "bucket": "{{env:BUCKET}}",
+                "retries": 3,
+            })
+        );
+        assert_eq!(missing, ["BUCKET"]);
+    }
+}
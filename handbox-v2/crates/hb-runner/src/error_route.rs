@@ -0,0 +1,133 @@
+//! Error edges — send a failed node's error to handler nodes instead of
+//! failing the run.
+//!
+//! A node with an outgoing [`EdgeKind::Error`] edge handles its own failure:
+//! the run does not count it as failed, and each of its error edges delivers
+//! the error [`payload`] to the handler's target port. Handlers run only
+//! when one of their error edges carries an error and are skipped when the
+//! upstream node succeeds. Nodes fed only by handled failures, skipped
+//! nodes or the untaken branches of a condition are skipped as well, so the
+//! failed or untaken branch stops while the rest of the workflow goes on.
+
+use hb_core::graph::{EdgeKind, EdgeSpec};
+use hb_core::trace::NodeSpan;
+use std::collections::{HashMap, HashSet};
+
+/// Whether `node_id` has an error edge, so its failures are handled.
+pub fn handles_errors<'a>(node_id: &str, outgoing: impl IntoIterator<Item = &'a EdgeSpec>) -> bool {
+    outgoing
+        .into_iter()
+        .any(|e| e.source_node == node_id && e.kind == EdgeKind::Error)
+}
+
+/// What an error edge carries: the failed node, its tool, the error and the
+/// input it failed on.
+pub fn payload(span: &NodeSpan) -> serde_json::Value {
+    serde_json::json!({
+        "node_id": span.node_id,
+        "tool_ref": span.tool_ref,
+        "error": span.error.as_deref().unwrap_or("node failed"),
+        "input": span.input_json,
+    })
+}
+
+/// Whether `node_id` is routed around: a handler none of whose upstream
+/// nodes failed, or a node whose every input comes from a handled failure,
+/// another routed-around node or an `untaken` branch. `failures` holds the
+/// payloads of the handled failures so far.
+pub fn bypassed<'a>(
+    node_id: &str,
+    incoming: impl IntoIterator<Item = &'a EdgeSpec>,
+    failures: &HashMap<String, serde_json::Value>,
+    bypassed: &HashSet<String>,
+    untaken: impl Fn(&EdgeSpec) -> bool,
+) -> bool {
+    let edges: Vec<&EdgeSpec> = incoming
+        .into_iter()
+        .filter(|e| e.target_node == node_id && e.kind != EdgeKind::Control)
+        .collect();
+    let mut errors = edges.iter().filter(|e| e.kind == EdgeKind::Error).peekable();
+    if errors.peek().is_some() {
+        return !errors.any(|e| failures.contains_key(&e.source_node));
+    }
+    !edges.is_empty()
+        && edges.iter().all(|e| {
+            failures.contains_key(&e.source_node) || bypassed.contains(&e.source_node) || untaken(e)
+        })
+}
+
+/// Add the payloads arriving over error edges to `inputs`. Several errors
+/// at one port arrive as an array, in edge order.
+pub fn add_error_inputs<'a>(
+    node_id: &str,
+    incoming: impl IntoIterator<Item = &'a EdgeSpec>,
+    failures: &HashMap<String, serde_json::Value>,
+    inputs: &mut serde_json::Map<String, serde_json::Value>,
+) {
+    let mut ports: Vec<(&str, Vec<serde_json::Value>)> = Vec::new();
+    for edge in incoming
+        .into_iter()
+        .filter(|e| e.target_node == node_id && e.kind == EdgeKind::Error)
+    {
+        let Some(payload) = failures.get(&edge.source_node) else {
+            continue;
+        };
+        match ports.iter_mut().find(|(p, _)| *p == edge.target_port) {
+            Some((_, values)) => values.push(payload.clone()),
+            None => ports.push((&edge.target_port, vec![payload.clone()])),
+        }
+    }
+    for (port, mut values) in ports {
+        let value = match values.len() {
+            1 => values.pop().unwrap_or_default(),
+            _ => serde_json::Value::Array(values),
+        };
+        inputs.insert(port.to_string(), value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn edge(from: &str, to: &str, port: &str, kind: EdgeKind) -> EdgeSpec {
+        EdgeSpec {
+            id: format!("{from}-{to}"),
+            source_node: from.into(),
+            source_port: "out".into(),
+            target_node: to.into(),
+            target_port: port.into(),
+            kind,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn handlers_run_only_for_failures_and_failed_branches_stop() {
+        // a fails → handler h; a → b (data); b → c (data); x → c (data)
+        let edges = [
+            edge("a", "h", "error", EdgeKind::Error),
+            edge("a", "b", "in", EdgeKind::Data),
+            edge("b", "c", "in", EdgeKind::Data),
+            edge("x", "c", "other", EdgeKind::Data),
+        ];
+        assert!(handles_errors("a", &edges));
+        assert!(!handles_errors("b", &edges));
+
+        let none = HashMap::new();
+        assert!(bypassed("h", &edges, &none, &HashSet::new(), |_| false));
+        assert!(!bypassed("b", &edges, &none, &HashSet::new(), |_| false));
+
+        let failures = HashMap::from([("a".to_string(), json!({ "error": "boom" }))]);
+        assert!(!bypassed("h", &edges, &failures, &HashSet::new(), |_| false));
+        assert!(bypassed("b", &edges, &failures, &HashSet::new(), |_| false));
+        let skipped = HashSet::from(["b".to_string()]);
+        assert!(!bypassed("c", &edges, &failures, &skipped, |_| false), "c still gets x");
+        assert!(!bypassed("x", &edges, &failures, &skipped, |_| false), "sources always run");
+
+        let mut inputs = serde_json::Map::new();
+        add_error_inputs("h", &edges, &failures, &mut inputs);
+        assert_eq!(inputs["error"], json!({ "error": "boom" }));
+    }
+}
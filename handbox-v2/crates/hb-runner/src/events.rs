@@ -0,0 +1,212 @@
+//! Status event log — numbers an execution's status events and keeps them
+//! for replay.
+//!
+//! Nodes run concurrently, so their status events race each other to the
+//! UI. The log gives each event the next sequence number and queues it for
+//! the status callback; whichever caller finds the queue idle delivers it
+//! (and anything queued meanwhile) after releasing the lock, so callbacks
+//! see events in sequence order without blocking other nodes. A client that reconnects mid-run asks for the events
+//! after the last sequence number it saw ([`StatusLog::since`]) and applies
+//! them before anything newer. Streamed "output" chunks are delivered but
+//! not kept; the node's final event carries its whole output.
+
+use crate::scheduler::{NodeStatusEvent, StatusCallback};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+#[derive(Debug, Default)]
+pub struct StatusLog {
+    inner: Mutex<LogState>,
+}
+
+#[derive(Default)]
+struct LogState {
+    last_seq: u64,
+    events: Vec<NodeStatusEvent>,
+    /// Numbered events not yet handed to their callback.
+    undelivered: VecDeque<(NodeStatusEvent, StatusCallback)>,
+    /// Whether a caller is currently draining `undelivered`.
+    delivering: bool,
+}
+
+impl std::fmt::Debug for LogState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LogState")
+            .field("last_seq", &self.last_seq)
+            .field("events", &self.events.len())
+            .field("undelivered", &self.undelivered.len())
+            .finish()
+    }
+}
+
+impl StatusLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number `event`, keep it and pass it to `deliver`. Returns its
+    /// sequence number. `deliver` runs without the log's lock held, but
+    /// never concurrently with another delivery from this log, and may
+    /// record events itself (they are delivered after it returns).
+    ///
+    /// Only the latest "progress" event of each node is kept: a replaying
+    /// client needs the current progress, not every step of it. "output"
+    /// events are not kept at all.
+    pub fn record(&self, mut event: NodeStatusEvent, deliver: Option<&StatusCallback>) -> u64 {
+        let mut state = self.lock();
+        state.last_seq += 1;
+        event.seq = state.last_seq;
+        let seq = event.seq;
+        if event.status == "progress" {
+            state
+                .events
+                .retain(|kept| kept.status != "progress" || kept.node_id != event.node_id);
+        }
+        if event.status != "output" {
+            state.events.push(event.clone());
+        }
+        if let Some(deliver) = deliver {
+            state.undelivered.push_back((event, deliver.clone()));
+        }
+        if state.delivering {
+            // The caller draining the queue delivers it in order
+            return seq;
+        }
+        state.delivering = true;
+        while let Some((event, deliver)) = state.undelivered.pop_front() {
+            drop(state);
+            let delivered =
+                std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| deliver(event)));
+            state = self.lock();
+            if let Err(panic) = delivered {
+                state.delivering = false;
+                drop(state);
+                std::panic::resume_unwind(panic);
+            }
+        }
+        state.delivering = false;
+        seq
+    }
+
+    /// Events recorded after sequence number `after_seq`, in order; 0 for
+    /// all of them.
+    pub fn since(&self, after_seq: u64) -> Vec<NodeStatusEvent> {
+        let state = self.lock();
+        // Sorted by seq, but with gaps where progress events were dropped
+        let start = state.events.partition_point(|event| event.seq <= after_seq);
+        state.events[start..].to_vec()
+    }
+
+    /// Sequence number of the latest event (0 before the first).
+    pub fn last_seq(&self) -> u64 {
+        self.lock().last_seq
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LogState> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hb_tool_executor::Progress;
+    use std::sync::Arc;
+
+    fn event(node_id: &str, status: &str) -> NodeStatusEvent {
+        NodeStatusEvent {
+            execution_id: "exec".into(),
+            node_id: node_id.into(),
+            status: status.into(),
+            seq: 0,
+            output: None,
+            error: None,
+            duration_ms: None,
+            progress: (status == "progress").then(Progress::default),
+            chunk: None,
+        }
+    }
+
+    #[test]
+    fn concurrent_events_are_delivered_in_sequence() {
+        let log = Arc::new(StatusLog::new());
+        let delivered = Arc::new(Mutex::new(Vec::new()));
+        let sink = delivered.clone();
+        let callback: StatusCallback = Arc::new(move |event: NodeStatusEvent| {
+            sink.lock().unwrap().push(event.seq);
+        });
+
+        let threads: Vec<_> = (0..8)
+            .map(|n| {
+                let log = log.clone();
+                let callback = callback.clone();
+                std::thread::spawn(move || {
+                    for status in ["pending", "running", "completed"] {
+                        log.record(event(&format!("n{n}"), status), Some(&callback));
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let delivered = delivered.lock().unwrap();
+        assert_eq!(*delivered, (1..=24).collect::<Vec<u64>>());
+        assert_eq!(log.last_seq(), 24);
+        assert_eq!(
+            log.since(20).iter().map(|e| e.seq).collect::<Vec<_>>(),
+            [21, 22, 23, 24]
+        );
+        assert_eq!(log.since(0).len(), 24);
+    }
+
+    #[test]
+    fn replay_keeps_only_the_latest_progress() {
+        let log = StatusLog::new();
+        log.record(event("split", "running"), None);
+        log.record(event("split", "progress"), None);
+        log.record(event("embed", "progress"), None);
+        log.record(event("split", "progress"), None);
+        // Streamed output is numbered but never replayed
+        assert_eq!(log.record(event("split", "output"), None), 5);
+        log.record(event("split", "completed"), None);
+
+        let replay: Vec<(u64, String, String)> = log
+            .since(0)
+            .into_iter()
+            .map(|e| (e.seq, e.node_id, e.status))
+            .collect();
+        assert_eq!(
+            replay,
+            [
+                (1, "split".into(), "running".into()),
+                (3, "embed".into(), "progress".into()),
+                (4, "split".into(), "progress".into()),
+                (6, "split".into(), "completed".into()),
+            ]
+        );
+        assert_eq!(log.since(3).len(), 2);
+    }
+
+    #[test]
+    fn callbacks_run_outside_the_lock() {
+        let log = Arc::new(StatusLog::new());
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let (inner, sink) = (log.clone(), seen.clone());
+        let callback: StatusCallback = Arc::new(move |event: NodeStatusEvent| {
+            // Reading or recording from the callback must not deadlock
+            sink.lock().unwrap().push((event.seq, inner.last_seq()));
+            if event.status == "completed" {
+                inner.record(self::event(&event.node_id, "archived"), None);
+            }
+        });
+
+        log.record(event("a", "running"), Some(&callback));
+        log.record(event("a", "completed"), Some(&callback));
+
+        assert_eq!(*seen.lock().unwrap(), [(1, 1), (2, 2)]);
+        assert_eq!(log.last_seq(), 3);
+        assert_eq!(log.since(2)[0].status, "archived");
+    }
+}
@@ -0,0 +1,262 @@
+//! Fan-in — combine upstream outputs into a node's input object.
+//!
+//! Incoming data edges are processed in their declaration order in the
+//! workflow's `edges` list, independent of which upstream node finished
+//! first, so a node always sees the same inputs for the same upstream
+//! outputs. How several edges into one port combine is set by the node's
+//! [`FanIn`] mode.
+
+use hb_core::graph::{split_variadic_port, EdgeKind, EdgeSpec, FanIn};
+use hb_core::tool::ToolInterface;
+use std::collections::{HashMap, HashSet};
+
+/// The variadic input ports tools declare, which take edges into
+/// `{port}.{key}` as one object keyed by `key`. A dotted port name on any
+/// other port, such as `result.json`, is an ordinary port.
+#[derive(Debug, Clone)]
+pub struct VariadicPorts {
+    /// Port names by tool ID, and for built-in tools by tool name.
+    by_tool: HashMap<String, HashSet<String>>,
+}
+
+impl Default for VariadicPorts {
+    /// The built-in merge tool's `inputs`, for runs without a tool registry.
+    fn default() -> Self {
+        Self {
+            by_tool: HashMap::from([("merge".to_string(), HashSet::from(["inputs".to_string()]))]),
+        }
+    }
+}
+
+impl VariadicPorts {
+    /// The variadic inputs of `tool_ref`'s tool, if it has any.
+    pub fn of(&self, tool_ref: &str) -> Option<&HashSet<String>> {
+        let tool_id = tool_ref.split('@').next().unwrap_or(tool_ref);
+        let name = tool_id.rsplit('/').next().unwrap_or(tool_id);
+        self.by_tool.get(tool_id).or_else(|| self.by_tool.get(name))
+    }
+}
+
+impl<'a> FromIterator<&'a ToolInterface> for VariadicPorts {
+    /// The built-in ones, and those `tools` declare.
+    fn from_iter<I: IntoIterator<Item = &'a ToolInterface>>(tools: I) -> Self {
+        let mut ports = Self::default();
+        for tool in tools {
+            let variadic: HashSet<String> = tool
+                .input_schema
+                .ports
+                .iter()
+                .filter(|port| port.variadic)
+                .map(|port| port.name.clone())
+                .collect();
+            if !variadic.is_empty() {
+                ports.by_tool.insert(tool.tool_id.clone(), variadic);
+            }
+        }
+        ports
+    }
+}
+
+/// Value an edge carries: the source port of the upstream output (a
+/// variadic port `cases.a` reads `output["cases"]["a"]`), or the whole
+/// output when the port is absent.
+pub(crate) fn edge_value(edge: &EdgeSpec, output: &serde_json::Value) -> serde_json::Value {
+    let value = output.get(&edge.source_port).or_else(|| match split_variadic_port(&edge.source_port) {
+        (base, Some(key)) => output.get(base)?.get(key),
+        (_, None) => None,
+    });
+    match value {
+        Some(v) => v.clone(),
+        None => {
+            tracing::warn!(
+                "[Scheduler] Port '{}' not found in output of '{}', using full output",
+                edge.source_port,
+                edge.source_node
+            );
+            output.clone()
+        }
+    }
+}
+
+/// Whether `edge` leaves an output its node listed under `untaken`: a
+/// branch of a condition that wasn't taken. Listing a variadic port's base
+/// (`cases`) covers all of its keys.
+pub(crate) fn untaken(edge: &EdgeSpec, outputs: &HashMap<String, serde_json::Value>) -> bool {
+    let Some(ports) = outputs
+        .get(&edge.source_node)
+        .and_then(|output| output.get("untaken"))
+        .and_then(|ports| ports.as_array())
+    else {
+        return false;
+    };
+    let base = split_variadic_port(&edge.source_port).0;
+    ports
+        .iter()
+        .filter_map(|port| port.as_str())
+        .any(|port| port == edge.source_port || port == base)
+}
+
+/// Combine the values arriving at one port according to `mode`. Each value
+/// is paired with whether its upstream node succeeded. `None` leaves the
+/// port unset.
+fn combine(mode: FanIn, values: Vec<(bool, serde_json::Value)>) -> Option<serde_json::Value> {
+    match mode {
+        FanIn::FirstWins => values.into_iter().find(|(ok, _)| *ok).map(|(_, v)| v),
+        FanIn::MergeAsArray | FanIn::WaitForAll => {
+            let mut values: Vec<_> = values.into_iter().map(|(_, v)| v).collect();
+            match values.len() {
+                0 => None,
+                // A single edge passes its value through unchanged.
+                1 => values.pop(),
+                _ => Some(serde_json::Value::Array(values)),
+            }
+        }
+    }
+}
+
+/// Build the input object of `node_id` from upstream `outputs`. `succeeded`
+/// holds the upstream nodes that completed successfully, and `variadic`
+/// the node's variadic input ports.
+///
+/// Fails only in [`FanIn::WaitForAll`] mode, when an upstream node did not
+/// complete successfully.
+pub fn gather_inputs<'a>(
+    node_id: &str,
+    mode: FanIn,
+    edges: impl IntoIterator<Item = &'a EdgeSpec>,
+    outputs: &HashMap<String, serde_json::Value>,
+    succeeded: &HashSet<String>,
+    variadic: Option<&HashSet<String>>,
+) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    // Values per target port, in first-edge order.
+    let mut ports: Vec<(&str, Vec<(bool, serde_json::Value)>)> = Vec::new();
+    for edge in edges
+        .into_iter()
+        .filter(|e| e.target_node == node_id && e.kind == EdgeKind::Data)
+    {
+        let ok = succeeded.contains(&edge.source_node);
+        if mode == FanIn::WaitForAll && !ok {
+            return Err(format!(
+                "upstream node '{}' did not complete (fan-in: wait_for_all)",
+                edge.source_node
+            ));
+        }
+        let Some(output) = outputs.get(&edge.source_node) else {
+            tracing::warn!("[Scheduler] Source node '{}' output not found", edge.source_node);
+            continue;
+        };
+        let value = edge_value(edge, output);
+        match ports.iter_mut().find(|(p, _)| *p == edge.target_port) {
+            Some((_, values)) => values.push((ok, value)),
+            None => ports.push((&edge.target_port, vec![(ok, value)])),
+        }
+    }
+
+    let mut inputs = serde_json::Map::new();
+    for (port, values) in ports {
+        let Some(value) = combine(mode, values) else {
+            continue;
+        };
+        let (base, key) = split_variadic_port(port);
+        if let Some(key) = key.filter(|_| variadic.is_some_and(|ports| ports.contains(base))) {
+            // Variadic port → object keyed by the edge's port key
+            let slot = inputs
+                .entry(base.to_string())
+                .or_insert_with(|| serde_json::json!({}));
+            if let Some(obj) = slot.as_object_mut() {
+                obj.insert(key.to_string(), value);
+            }
+        } else {
+            inputs.insert(port.to_string(), value);
+        }
+    }
+    Ok(inputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn edge(from: &str, target_port: &str) -> EdgeSpec {
+        EdgeSpec {
+            id: format!("{from}-{target_port}"),
+            source_node: from.into(),
+            source_port: "out".into(),
+            target_node: "join".into(),
+            target_port: target_port.into(),
+            ..Default::default()
+        }
+    }
+
+    fn fixture() -> (Vec<EdgeSpec>, HashMap<String, serde_json::Value>, HashSet<String>) {
+        // Declared b before a: order must follow the edge list, not node names.
+        let edges = vec![edge("b", "in"), edge("a", "in"), edge("c", "items.x"), edge("c", "result.json")];
+        let outputs = HashMap::from([
+            ("a".to_string(), json!({ "out": 1 })),
+            ("b".to_string(), json!({ "error": "boom" })),
+            ("c".to_string(), json!({ "out": 3 })),
+        ]);
+        let succeeded = HashSet::from(["a".to_string(), "c".to_string()]);
+        (edges, outputs, succeeded)
+    }
+
+    #[test]
+    fn merge_as_array_keeps_edge_order() {
+        let (edges, outputs, succeeded) = fixture();
+        let variadic = HashSet::from(["items".to_string()]);
+        let inputs = gather_inputs("join", FanIn::MergeAsArray, &edges, &outputs, &succeeded, Some(&variadic)).unwrap();
+        assert_eq!(inputs["in"], json!([{ "error": "boom" }, 1]));
+        assert_eq!(inputs["items"], json!({ "x": 3 }));
+        // Only declared variadic ports are split on the dot
+        assert_eq!(inputs["result.json"], json!(3));
+    }
+
+    #[test]
+    fn first_wins_skips_failed_upstream() {
+        let (edges, outputs, succeeded) = fixture();
+        let inputs = gather_inputs("join", FanIn::FirstWins, &edges, &outputs, &succeeded, None).unwrap();
+        assert_eq!(inputs["in"], json!(1));
+    }
+
+    #[test]
+    fn wait_for_all_requires_every_upstream() {
+        let (edges, outputs, mut succeeded) = fixture();
+        let err = gather_inputs("join", FanIn::WaitForAll, &edges, &outputs, &succeeded, None).unwrap_err();
+        assert!(err.contains("'b'"));
+
+        succeeded.insert("b".into());
+        let inputs = gather_inputs("join", FanIn::WaitForAll, &edges, &outputs, &succeeded, None).unwrap();
+        assert_eq!(inputs["in"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn untaken_branches_match_exact_and_variadic_ports() {
+        let outputs = HashMap::from([
+            ("if".to_string(), json!({ "true_out": 1, "untaken": ["false_out"] })),
+            ("switch".to_string(), json!({ "default": 1, "untaken": ["cases"] })),
+        ]);
+        let from = |node: &str, port: &str| {
+            let mut e = edge(node, "in");
+            e.source_port = port.into();
+            e
+        };
+        assert!(untaken(&from("if", "false_out"), &outputs));
+        assert!(!untaken(&from("if", "true_out"), &outputs));
+        assert!(untaken(&from("switch", "cases.a"), &outputs));
+        assert!(!untaken(&from("switch", "default"), &outputs));
+        assert!(!untaken(&from("other", "out"), &outputs));
+    }
+
+    #[test]
+    fn edge_value_reads_variadic_source_ports() {
+        let mut switch = edge("switch", "in");
+        switch.source_port = "cases.승인".into();
+        let output = json!({ "cases": { "승인": { "id": 7 }, "반려": null }, "default": null });
+        assert_eq!(edge_value(&switch, &output), json!({ "id": 7 }));
+
+        // A port literally named with a dot still wins
+        let output = json!({ "cases.승인": 1, "cases": { "승인": 2 } });
+        assert_eq!(edge_value(&switch, &output), json!(1));
+    }
+}
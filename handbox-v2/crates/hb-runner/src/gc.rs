@@ -0,0 +1,359 @@
+//! Garbage collection — removes old execution logs, expired cache entries,
+//! stale journals and orphaned artifact directories according to a
+//! retention config, and reports the space reclaimed.
+
+use crate::cache::ExecutionCache;
+use crate::journal::ExecutionJournal;
+use chrono::{DateTime, Utc};
+use hb_trace::store::{RetentionPolicy, TraceStore};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// What to keep, and how often to collect automatically.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    /// Days to keep finished executions with their spans, edge values and
+    /// manifests (0 keeps them forever).
+    #[serde(default = "default_execution_days")]
+    pub execution_days: u32,
+    /// Most finished executions to keep, pruning the oldest beyond it (0
+    /// keeps any number).
+    #[serde(default)]
+    pub max_executions: u32,
+    /// Megabytes the trace database may take before the oldest executions
+    /// are pruned (0 for no limit).
+    #[serde(default)]
+    pub max_trace_mb: u64,
+    /// Archive the spans of pruned executions as compressed JSON Lines
+    /// before deleting them.
+    #[serde(default)]
+    pub archive_traces: bool,
+    /// Days after which journals left behind by crashed runs are removed.
+    #[serde(default = "default_journal_days")]
+    pub journal_days: u32,
+    /// Delete cache entries past their TTL.
+    #[serde(default = "default_true")]
+    pub expired_cache: bool,
+    /// Delete artifact directories whose owner no longer exists.
+    #[serde(default = "default_true")]
+    pub orphaned_artifacts: bool,
+    /// Keep the scratch directory of a run that failed, for debugging.
+    #[serde(default)]
+    pub keep_failed_scratch: bool,
+    /// Days after which kept scratch directories, and ones left behind by
+    /// crashed runs, are removed.
+    #[serde(default = "default_journal_days")]
+    pub scratch_days: u32,
+    /// Hours between automatic collections (0 runs only on demand).
+    #[serde(default = "default_interval_hours")]
+    pub interval_hours: u32,
+}
+
+fn default_execution_days() -> u32 {
+    30
+}
+
+fn default_journal_days() -> u32 {
+    7
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_interval_hours() -> u32 {
+    24
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            execution_days: default_execution_days(),
+            max_executions: 0,
+            max_trace_mb: 0,
+            archive_traces: false,
+            journal_days: default_journal_days(),
+            expired_cache: true,
+            orphaned_artifacts: true,
+            keep_failed_scratch: false,
+            scratch_days: default_journal_days(),
+            interval_hours: default_interval_hours(),
+        }
+    }
+}
+
+impl RetentionConfig {
+    /// The trace store retention this config asks for, archiving to
+    /// `archive_dir` if `archive_traces` is set.
+    pub fn trace_policy(&self, archive_dir: Option<&Path>) -> RetentionPolicy {
+        RetentionPolicy {
+            max_age_days: (self.execution_days > 0).then_some(self.execution_days),
+            max_executions: (self.max_executions > 0).then_some(self.max_executions as usize),
+            max_bytes: (self.max_trace_mb > 0).then(|| self.max_trace_mb * 1024 * 1024),
+            archive_dir: archive_dir
+                .filter(|_| self.archive_traces)
+                .map(Path::to_path_buf),
+        }
+    }
+
+    /// Interval between automatic collections, if scheduled.
+    pub fn interval(&self) -> Option<Duration> {
+        (self.interval_hours > 0)
+            .then(|| Duration::from_secs(u64::from(self.interval_hours) * 3600))
+    }
+}
+
+/// A directory whose entries are named after their owners (e.g. project
+/// IDs); entries not in `live` are orphans.
+#[derive(Debug, Clone)]
+pub struct OrphanScan {
+    pub dir: PathBuf,
+    pub live: HashSet<String>,
+}
+
+/// Stores and directories to collect from. Missing ones are skipped.
+#[derive(Default)]
+pub struct GcTargets {
+    pub trace_store: Option<Arc<dyn TraceStore>>,
+    pub cache: Option<Arc<ExecutionCache>>,
+    pub journal: Option<Arc<ExecutionJournal>>,
+    pub orphan_scans: Vec<OrphanScan>,
+    /// Root of the executions' scratch directories.
+    pub scratch_dir: Option<PathBuf>,
+    /// Where pruned spans are archived when `archive_traces` is set.
+    pub trace_archive_dir: Option<PathBuf>,
+}
+
+/// Outcome of one collection.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GcReport {
+    pub started_at: Option<DateTime<Utc>>,
+    pub duration_ms: u64,
+    pub executions_removed: usize,
+    pub spans_removed: usize,
+    pub edge_values_removed: usize,
+    pub spans_archived: u64,
+    /// Archive the pruned spans were written to.
+    pub trace_archive: Option<PathBuf>,
+    pub cache_entries_removed: usize,
+    pub journal_files_removed: usize,
+    pub scratch_dirs_removed: usize,
+    pub orphaned_artifacts_removed: Vec<PathBuf>,
+    /// Bytes freed on disk, across databases and files.
+    pub reclaimed_bytes: u64,
+    /// Steps that failed; the remaining steps still ran.
+    pub errors: Vec<String>,
+}
+
+/// Run one collection. Blocking — call from a blocking task.
+pub fn collect(config: &RetentionConfig, targets: &GcTargets) -> GcReport {
+    let started_at = Utc::now();
+    let timer = std::time::Instant::now();
+    let mut report = GcReport {
+        started_at: Some(started_at),
+        ..Default::default()
+    };
+
+    let policy = config.trace_policy(targets.trace_archive_dir.as_deref());
+    let limited = policy.max_age_days.is_some()
+        || policy.max_executions.is_some()
+        || policy.max_bytes.is_some();
+    if let (Some(store), true) = (&targets.trace_store, limited) {
+        match hb_trace::store::vacuum(store.as_ref(), &policy) {
+            Ok(vacuumed) => {
+                report.executions_removed = vacuumed.pruned.executions;
+                report.spans_removed = vacuumed.pruned.spans;
+                report.edge_values_removed = vacuumed.pruned.edge_values;
+                report.spans_archived = vacuumed.archived_spans;
+                report.trace_archive = vacuumed.archive;
+                report.reclaimed_bytes += vacuumed.size_before.saturating_sub(vacuumed.size_after);
+            }
+            Err(e) => report.errors.push(format!("execution logs: {e}")),
+        }
+    }
+
+    if let (Some(cache), true) = (&targets.cache, config.expired_cache) {
+        match reclaim(
+            || cache.database_size(),
+            || cache.cleanup(),
+            || cache.vacuum(),
+        ) {
+            Ok((removed, freed)) => {
+                report.cache_entries_removed = removed;
+                report.reclaimed_bytes += freed;
+            }
+            Err(e) => report.errors.push(format!("cache: {e}")),
+        }
+    }
+
+    if let (Some(journal), true) = (&targets.journal, config.journal_days > 0) {
+        let max_age = Duration::from_secs(u64::from(config.journal_days) * 86_400);
+        match journal.prune_stale(max_age) {
+            Ok((removed, freed)) => {
+                report.journal_files_removed = removed;
+                report.reclaimed_bytes += freed;
+            }
+            Err(e) => report.errors.push(format!("journal: {e}")),
+        }
+    }
+
+    if let (Some(dir), true) = (&targets.scratch_dir, config.scratch_days > 0) {
+        let max_age = Duration::from_secs(u64::from(config.scratch_days) * 86_400);
+        match crate::scratch::prune(dir, max_age) {
+            Ok((removed, freed)) => {
+                report.scratch_dirs_removed = removed;
+                report.reclaimed_bytes += freed;
+            }
+            Err(e) => report.errors.push(format!("scratch: {e}")),
+        }
+    }
+
+    if config.orphaned_artifacts {
+        for scan in &targets.orphan_scans {
+            remove_orphans(scan, &mut report);
+        }
+    }
+
+    report.duration_ms = timer.elapsed().as_millis() as u64;
+    report
+}
+
+/// Delete with `prune`, then vacuum only if something was removed, and
+/// return the prune result with the bytes the database shrank by.
+fn reclaim<T: PrunedCount, E: std::fmt::Display>(
+    size: impl Fn() -> Result<u64, E>,
+    prune: impl FnOnce() -> Result<T, E>,
+    vacuum: impl FnOnce() -> Result<(), E>,
+) -> Result<(T, u64), String> {
+    let before = size().map_err(|e| e.to_string())?;
+    let pruned = prune().map_err(|e| e.to_string())?;
+    if pruned.count() == 0 {
+        return Ok((pruned, 0));
+    }
+    vacuum().map_err(|e| e.to_string())?;
+    let after = size().map_err(|e| e.to_string())?;
+    Ok((pruned, before.saturating_sub(after)))
+}
+
+trait PrunedCount {
+    fn count(&self) -> usize;
+}
+
+impl PrunedCount for usize {
+    fn count(&self) -> usize {
+        *self
+    }
+}
+
+fn remove_orphans(scan: &OrphanScan, report: &mut GcReport) {
+    let Ok(entries) = std::fs::read_dir(&scan.dir) else {
+        return; // Nothing created yet
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let owner = Path::new(&name)
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| name.clone());
+        if scan.live.contains(&owner) {
+            continue;
+        }
+        let path = entry.path();
+        let size = disk_usage(&path);
+        let removed = if path.is_dir() {
+            std::fs::remove_dir_all(&path)
+        } else {
+            std::fs::remove_file(&path)
+        };
+        match removed {
+            Ok(()) => {
+                report.reclaimed_bytes += size;
+                report.orphaned_artifacts_removed.push(path);
+            }
+            Err(e) => report.errors.push(format!("{}: {e}", path.display())),
+        }
+    }
+}
+
+/// Total size of a file or directory tree in bytes.
+pub fn disk_usage(path: &Path) -> u64 {
+    let Ok(meta) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+    std::fs::read_dir(path)
+        .map(|entries| entries.flatten().map(|e| disk_usage(&e.path())).sum())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hb_trace::store::SqliteTraceStore;
+    use uuid::Uuid;
+
+    #[test]
+    fn removes_orphaned_artifacts_and_reports_space() {
+        let dir = std::env::temp_dir().join(format!("hb-gc-{}", Uuid::new_v4()));
+        let live = Uuid::new_v4().to_string();
+        let orphan = Uuid::new_v4().to_string();
+        for id in [&live, &orphan] {
+            std::fs::create_dir_all(dir.join(id).join("memory")).unwrap();
+            std::fs::write(dir.join(id).join("plan.md"), "0123456789").unwrap();
+        }
+
+        let targets = GcTargets {
+            trace_store: Some(Arc::new(SqliteTraceStore::in_memory().unwrap())),
+            cache: Some(Arc::new(ExecutionCache::in_memory().unwrap())),
+            orphan_scans: vec![OrphanScan {
+                dir: dir.clone(),
+                live: HashSet::from([live.clone()]),
+            }],
+            ..Default::default()
+        };
+        let report = collect(&RetentionConfig::default(), &targets);
+
+        assert!(report.errors.is_empty(), "{:?}", report.errors);
+        assert_eq!(report.orphaned_artifacts_removed, vec![dir.join(&orphan)]);
+        assert_eq!(report.reclaimed_bytes, 10);
+        assert!(dir.join(&live).exists());
+
+        let disabled = RetentionConfig {
+            orphaned_artifacts: false,
+            ..Default::default()
+        };
+        std::fs::create_dir_all(dir.join(&orphan)).unwrap();
+        assert!(collect(&disabled, &targets)
+            .orphaned_artifacts_removed
+            .is_empty());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn retention_defaults_fill_missing_fields() {
+        let config: RetentionConfig = serde_json::from_str(r#"{ "interval_hours": 0 }"#).unwrap();
+        assert_eq!(config.execution_days, 30);
+        assert!(config.interval().is_none());
+        assert_eq!(
+            RetentionConfig::default().interval(),
+            Some(Duration::from_secs(86_400))
+        );
+
+        let config: RetentionConfig =
+            serde_json::from_str(r#"{ "max_trace_mb": 200, "archive_traces": true }"#).unwrap();
+        let policy = config.trace_policy(Some(Path::new("archive")));
+        assert_eq!(policy.max_age_days, Some(30));
+        assert_eq!(policy.max_executions, None);
+        assert_eq!(policy.max_bytes, Some(200 * 1024 * 1024));
+        assert_eq!(policy.archive_dir.as_deref(), Some(Path::new("archive")));
+        assert!(RetentionConfig::default()
+            .trace_policy(Some(Path::new("archive")))
+            .archive_dir
+            .is_none());
+    }
+}
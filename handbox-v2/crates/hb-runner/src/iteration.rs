@@ -0,0 +1,177 @@
+//! Loop iterations — the iteration context handed to loop bodies and the
+//! aggregation of per-iteration outputs.
+
+use hb_core::graph::{IterationContext, LoopAggregation};
+use serde_json::Value;
+
+/// Body input for one iteration: the reserved context ports. `iteration`
+/// is kept as an alias of `index` for bodies written against older loops.
+pub fn iteration_input(ctx: &IterationContext) -> Value {
+    let mut input = serde_json::to_value(ctx).unwrap_or_else(|_| serde_json::json!({}));
+    if let Some(obj) = input.as_object_mut() {
+        obj.insert("iteration".into(), ctx.index.into());
+    }
+    input
+}
+
+/// Build the context of iteration `index` out of `total` (unknown for `while`).
+pub fn context(item: Value, index: u32, total: Option<u32>) -> IterationContext {
+    IterationContext {
+        item,
+        index,
+        total,
+        is_first: index == 0,
+        is_last: total.is_some_and(|t| index + 1 == t),
+    }
+}
+
+/// Look up a dotted path (`a.b.0`, optionally prefixed with `$.`) in `value`.
+pub fn select(path: &str, value: &Value) -> Option<Value> {
+    let path = path.strip_prefix("$.").unwrap_or(path);
+    let mut current = value;
+    for part in path.split('.').filter(|p| !p.is_empty()) {
+        current = match current {
+            Value::Array(items) => items.get(part.parse::<usize>().ok()?)?,
+            _ => current.get(part)?,
+        };
+    }
+    Some(current.clone())
+}
+
+/// Evaluate an operand of a reduce expression: a JSON literal or a path.
+fn operand(token: &str, scope: &Value) -> Result<Value, String> {
+    if let Ok(literal) = serde_json::from_str(token) {
+        return Ok(literal);
+    }
+    select(token, scope).ok_or_else(|| format!("unknown reference '{token}'"))
+}
+
+fn arithmetic(lhs: &Value, op: char, rhs: &Value) -> Result<Value, String> {
+    match (lhs, op, rhs) {
+        (Value::Null, '+', v) | (v, '+', Value::Null) => Ok(v.clone()),
+        (Value::String(a), '+', b) => Ok(Value::String(format!("{a}{}", text(b)))),
+        (Value::Array(a), '+', Value::Array(b)) => Ok(Value::Array([a.clone(), b.clone()].concat())),
+        (Value::Array(a), '+', b) => {
+            let mut out = a.clone();
+            out.push(b.clone());
+            Ok(Value::Array(out))
+        }
+        (Value::Object(a), '+', Value::Object(b)) => {
+            let mut out = a.clone();
+            out.extend(b.clone());
+            Ok(Value::Object(out))
+        }
+        (Value::Number(_), _, Value::Number(_)) => {
+            let (a, b) = (lhs.as_f64().unwrap_or(0.0), rhs.as_f64().unwrap_or(0.0));
+            let result = match op {
+                '+' => a + b,
+                '-' => a - b,
+                '*' => a * b,
+                _ if b == 0.0 => return Err("division by zero".into()),
+                _ => a / b,
+            };
+            // Keep integers integral so `acc + 1` stays `1`, not `1.0`.
+            Ok(if result.fract() == 0.0 && result.abs() < i64::MAX as f64 {
+                (result as i64).into()
+            } else {
+                serde_json::json!(result)
+            })
+        }
+        _ => Err(format!("cannot apply '{op}' to {lhs} and {rhs}")),
+    }
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Evaluate a reduce expression: a single operand, or `lhs <op> rhs` with
+/// `op` one of `+ - * /` separated by whitespace (e.g. `acc + item.count`).
+/// `+` also concatenates strings and arrays and merges objects.
+pub fn eval_reduce(expr: &str, scope: &Value) -> Result<Value, String> {
+    let tokens: Vec<&str> = expr.split_whitespace().collect();
+    match tokens.as_slice() {
+        [single] => operand(single, scope),
+        [lhs, op, rhs] if op.len() == 1 && "+-*/".contains(*op) => arithmetic(
+            &operand(lhs, scope)?,
+            op.chars().next().unwrap_or('+'),
+            &operand(rhs, scope)?,
+        ),
+        _ => Err(format!("invalid reduce expression '{expr}'")),
+    }
+}
+
+/// Combine the collected per-iteration values.
+pub fn aggregate(mode: &LoopAggregation, values: &[Value]) -> Result<Value, String> {
+    match mode {
+        LoopAggregation::Array => Ok(Value::Array(values.to_vec())),
+        LoopAggregation::Concat { separator } => {
+            if values.iter().all(Value::is_array) {
+                Ok(Value::Array(
+                    values
+                        .iter()
+                        .filter_map(Value::as_array)
+                        .flatten()
+                        .cloned()
+                        .collect(),
+                ))
+            } else {
+                let parts: Vec<String> = values.iter().map(text).collect();
+                Ok(Value::String(parts.join(separator)))
+            }
+        }
+        LoopAggregation::Reduce { expr, initial } => {
+            values.iter().enumerate().try_fold(initial.clone(), |acc, (index, item)| {
+                let scope = serde_json::json!({ "acc": acc, "item": item, "index": index });
+                eval_reduce(expr, &scope).map_err(|e| format!("reduce failed at iteration {index}: {e}"))
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn context_marks_first_and_last() {
+        let input = iteration_input(&context(json!("x"), 2, Some(3)));
+        assert_eq!(input["item"], json!("x"));
+        assert_eq!(input["index"], json!(2));
+        assert_eq!(input["iteration"], json!(2));
+        assert_eq!(input["total"], json!(3));
+        assert_eq!(input["is_first"], json!(false));
+        assert_eq!(input["is_last"], json!(true));
+        assert!(!context(Value::Null, 0, None).is_last);
+    }
+
+    #[test]
+    fn aggregates_collected_values() {
+        let values = vec![json!("a"), json!("b"), json!("c")];
+        let concat = LoopAggregation::Concat {
+            separator: ", ".into(),
+        };
+        assert_eq!(aggregate(&concat, &values).unwrap(), json!("a, b, c"));
+        assert_eq!(
+            aggregate(&concat, &[json!([1]), json!([2, 3])]).unwrap(),
+            json!([1, 2, 3])
+        );
+
+        let sum = LoopAggregation::Reduce {
+            expr: "acc + item.n".into(),
+            initial: json!(0),
+        };
+        let counts = vec![json!({ "n": 2 }), json!({ "n": 5 })];
+        assert_eq!(aggregate(&sum, &counts).unwrap(), json!(7));
+
+        let bad = LoopAggregation::Reduce {
+            expr: "acc + missing".into(),
+            initial: json!(0),
+        };
+        assert!(aggregate(&bad, &counts).unwrap_err().contains("iteration 0"));
+    }
+}
@@ -0,0 +1,414 @@
+//! Execution Journal — crash-safe, append-only log of scheduler decisions.
+//!
+//! Each execution gets its own JSON Lines file that is fsync'd after every
+//! entry, independent of trace store flushing. After a hard crash the next
+//! launch replays the surviving files to find executions that never finished
+//! and nodes that were still running, and marks them as failed/interrupted.
+
+use crate::RunnerError;
+use chrono::{DateTime, Utc};
+use hb_core::trace::{ExecutionEnvironment, ExecutionRecord, ExecutionStatus, NodeSpan};
+use hb_trace::store::TraceStore;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// Error message recorded on nodes orphaned by a crash.
+const ORPHANED_NODE_ERROR: &str = "Interrupted: application exited while this node was running";
+
+/// One scheduler decision.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum JournalEntry {
+    ExecutionStarted {
+        execution_id: Uuid,
+        workflow_id: Uuid,
+        total_nodes: u32,
+        at: DateTime<Utc>,
+    },
+    NodeStarted {
+        execution_id: Uuid,
+        node_id: String,
+        at: DateTime<Utc>,
+    },
+    CacheKey {
+        execution_id: Uuid,
+        node_id: String,
+        cache_key: String,
+        hit: bool,
+    },
+    NodeFinished {
+        execution_id: Uuid,
+        node_id: String,
+        status: ExecutionStatus,
+        at: DateTime<Utc>,
+    },
+    ExecutionFinished {
+        execution_id: Uuid,
+        status: ExecutionStatus,
+        at: DateTime<Utc>,
+    },
+}
+
+impl JournalEntry {
+    pub fn execution_id(&self) -> Uuid {
+        match self {
+            JournalEntry::ExecutionStarted { execution_id, .. }
+            | JournalEntry::NodeStarted { execution_id, .. }
+            | JournalEntry::CacheKey { execution_id, .. }
+            | JournalEntry::NodeFinished { execution_id, .. }
+            | JournalEntry::ExecutionFinished { execution_id, .. } => *execution_id,
+        }
+    }
+}
+
+/// In-flight state of an execution reconstructed from its journal.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoveredExecution {
+    pub execution_id: Uuid,
+    pub workflow_id: Uuid,
+    pub started_at: DateTime<Utc>,
+    pub total_nodes: u32,
+    /// Final status of every node that finished before the crash.
+    pub finished_nodes: BTreeMap<String, ExecutionStatus>,
+    /// Nodes that started but never finished, with their start time.
+    pub orphaned_nodes: BTreeMap<String, DateTime<Utc>>,
+    /// Cache keys used per node.
+    pub cache_keys: BTreeMap<String, String>,
+}
+
+/// Append-only journal with one file per execution.
+pub struct ExecutionJournal {
+    dir: PathBuf,
+    files: Mutex<HashMap<Uuid, File>>,
+}
+
+impl ExecutionJournal {
+    /// Open (or create) a journal directory.
+    pub fn open(dir: &Path) -> Result<Self, RunnerError> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| RunnerError::Journal(format!("Failed to create journal dir: {e}")))?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            files: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn path_for(&self, execution_id: Uuid) -> PathBuf {
+        self.dir.join(format!("{execution_id}.jsonl"))
+    }
+
+    /// Append an entry and fsync it before returning.
+    /// The journal file is removed once the execution finishes.
+    pub fn append(&self, entry: &JournalEntry) -> Result<(), RunnerError> {
+        let execution_id = entry.execution_id();
+        let mut line = serde_json::to_string(entry)
+            .map_err(|e| RunnerError::Journal(format!("Failed to serialize entry: {e}")))?;
+        line.push('\n');
+
+        let mut files = self
+            .files
+            .lock()
+            .map_err(|_| RunnerError::Journal("Lock poisoned".into()))?;
+        let file = match files.entry(execution_id) {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(self.path_for(execution_id))
+                    .map_err(|e| RunnerError::Journal(format!("Failed to open journal: {e}")))?;
+                e.insert(file)
+            }
+        };
+        file.write_all(line.as_bytes())
+            .and_then(|_| file.sync_data())
+            .map_err(|e| RunnerError::Journal(format!("Failed to write journal: {e}")))?;
+
+        if matches!(entry, JournalEntry::ExecutionFinished { .. }) {
+            files.remove(&execution_id);
+            drop(files);
+            self.discard(execution_id)?;
+        }
+        Ok(())
+    }
+
+    /// Remove the journal file for an execution.
+    pub fn discard(&self, execution_id: Uuid) -> Result<(), RunnerError> {
+        if let Ok(mut files) = self.files.lock() {
+            files.remove(&execution_id);
+        }
+        let path = self.path_for(execution_id);
+        if path.exists() {
+            std::fs::remove_file(&path)
+                .map_err(|e| RunnerError::Journal(format!("Failed to remove journal: {e}")))?;
+        }
+        Ok(())
+    }
+
+    /// Remove journal files not written by this process and last modified
+    /// more than `max_age` ago. Returns the files removed and bytes freed.
+    pub fn prune_stale(&self, max_age: std::time::Duration) -> Result<(usize, u64), RunnerError> {
+        let open: Vec<PathBuf> = match self.files.lock() {
+            Ok(files) => files.keys().map(|id| self.path_for(*id)).collect(),
+            Err(_) => return Err(RunnerError::Journal("Lock poisoned".into())),
+        };
+        let entries = std::fs::read_dir(&self.dir)
+            .map_err(|e| RunnerError::Journal(format!("Failed to read journal dir: {e}")))?;
+
+        let (mut removed, mut bytes) = (0, 0);
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("jsonl") || open.contains(&path) {
+                continue;
+            }
+            let Ok(meta) = entry.metadata() else { continue };
+            let age = meta.modified().ok().and_then(|m| m.elapsed().ok());
+            if age.is_some_and(|age| age > max_age) && std::fs::remove_file(&path).is_ok() {
+                removed += 1;
+                bytes += meta.len();
+            }
+        }
+        Ok((removed, bytes))
+    }
+
+    /// Replay all journal files left on disk and return executions that
+    /// never finished. A torn final line (crash mid-write) is ignored.
+    pub fn recover(&self) -> Result<Vec<RecoveredExecution>, RunnerError> {
+        let entries = std::fs::read_dir(&self.dir)
+            .map_err(|e| RunnerError::Journal(format!("Failed to read journal dir: {e}")))?;
+
+        let mut recovered = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+                continue;
+            }
+            let file = File::open(&path)
+                .map_err(|e| RunnerError::Journal(format!("Failed to open journal: {e}")))?;
+            let journal_entries: Vec<JournalEntry> = BufReader::new(file)
+                .lines()
+                .map_while(Result::ok)
+                .filter_map(|line| serde_json::from_str(&line).ok())
+                .collect();
+            if let Some(execution) = replay(&journal_entries) {
+                recovered.push(execution);
+            }
+        }
+        recovered.sort_by_key(|e| e.started_at);
+        Ok(recovered)
+    }
+
+    /// Recover in-flight executions, record them in the trace store as
+    /// interrupted (with orphaned nodes marked failed), then discard their
+    /// journals. Returns the recovered executions.
+    pub fn reconcile(&self, store: &dyn TraceStore) -> Result<Vec<RecoveredExecution>, RunnerError> {
+        let recovered = self.recover()?;
+        for execution in &recovered {
+            for (node_id, started_at) in &execution.orphaned_nodes {
+                let now = Utc::now();
+                let span = NodeSpan {
+                    span_id: Uuid::new_v4(),
+                    execution_id: execution.execution_id,
+                    node_id: node_id.clone(),
+                    tool_ref: "unknown".into(),
+                    input_json: serde_json::json!({}),
+                    output_json: None,
+                    config_json: serde_json::json!({}),
+                    started_at: *started_at,
+                    completed_at: Some(now),
+                    duration_ms: Some((now - *started_at).num_milliseconds()),
+                    status: ExecutionStatus::Failed,
+                    error: Some(ORPHANED_NODE_ERROR.into()),
+                    cache_hit: false,
+                    environment: ExecutionEnvironment {
+                        platform_version: env!("CARGO_PKG_VERSION").into(),
+                        os: std::env::consts::OS.into(),
+                        tool_version: "unknown".into(),
+                        extra: Default::default(),
+                    },
+                };
+                store
+                    .insert_span(&span)
+                    .map_err(|e| RunnerError::Journal(e.to_string()))?;
+            }
+
+            let count = |pred: fn(&ExecutionStatus) -> bool| {
+                execution.finished_nodes.values().filter(|s| pred(s)).count() as u32
+            };
+            let record = ExecutionRecord {
+                execution_id: execution.execution_id,
+                workflow_id: execution.workflow_id,
+                started_at: execution.started_at,
+                completed_at: Some(Utc::now()),
+                status: ExecutionStatus::Interrupted,
+                total_nodes: execution.total_nodes,
+                completed_nodes: count(|s| {
+                    matches!(s, ExecutionStatus::Completed | ExecutionStatus::CacheHit)
+                }),
+                failed_nodes: count(|s| matches!(s, ExecutionStatus::Failed))
+                    + execution.orphaned_nodes.len() as u32,
+                cache_hits: count(|s| matches!(s, ExecutionStatus::CacheHit)),
+                workspace_id: None,
+                manifest: Default::default(),
+                contract_violations: Vec::new(),
+            };
+            store
+                .upsert_execution(&record)
+                .map_err(|e| RunnerError::Journal(e.to_string()))?;
+            self.discard(execution.execution_id)?;
+        }
+        Ok(recovered)
+    }
+}
+
+/// Fold a single execution's entries into its in-flight state.
+/// Returns `None` if the execution finished (or never started).
+fn replay(entries: &[JournalEntry]) -> Option<RecoveredExecution> {
+    let mut state: Option<RecoveredExecution> = None;
+    for entry in entries {
+        match entry {
+            JournalEntry::ExecutionStarted {
+                execution_id,
+                workflow_id,
+                total_nodes,
+                at,
+            } => {
+                state = Some(RecoveredExecution {
+                    execution_id: *execution_id,
+                    workflow_id: *workflow_id,
+                    started_at: *at,
+                    total_nodes: *total_nodes,
+                    finished_nodes: BTreeMap::new(),
+                    orphaned_nodes: BTreeMap::new(),
+                    cache_keys: BTreeMap::new(),
+                });
+            }
+            JournalEntry::NodeStarted { node_id, at, .. } => {
+                if let Some(s) = state.as_mut() {
+                    s.orphaned_nodes.insert(node_id.clone(), *at);
+                }
+            }
+            JournalEntry::CacheKey {
+                node_id, cache_key, ..
+            } => {
+                if let Some(s) = state.as_mut() {
+                    s.cache_keys.insert(node_id.clone(), cache_key.clone());
+                }
+            }
+            JournalEntry::NodeFinished {
+                node_id, status, ..
+            } => {
+                if let Some(s) = state.as_mut() {
+                    s.orphaned_nodes.remove(node_id);
+                    s.finished_nodes.insert(node_id.clone(), status.clone());
+                }
+            }
+            JournalEntry::ExecutionFinished { .. } => return None,
+        }
+    }
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hb_trace::store::SqliteTraceStore;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("hb-journal-{}", Uuid::new_v4()))
+    }
+
+    #[test]
+    fn recovers_orphaned_nodes_after_crash() {
+        let dir = temp_dir();
+        let journal = ExecutionJournal::open(&dir).unwrap();
+        let exec_id = Uuid::new_v4();
+
+        journal
+            .append(&JournalEntry::ExecutionStarted {
+                execution_id: exec_id,
+                workflow_id: Uuid::new_v4(),
+                total_nodes: 2,
+                at: Utc::now(),
+            })
+            .unwrap();
+        for node in ["a", "b"] {
+            journal
+                .append(&JournalEntry::NodeStarted {
+                    execution_id: exec_id,
+                    node_id: node.into(),
+                    at: Utc::now(),
+                })
+                .unwrap();
+        }
+        journal
+            .append(&JournalEntry::NodeFinished {
+                execution_id: exec_id,
+                node_id: "a".into(),
+                status: ExecutionStatus::Completed,
+                at: Utc::now(),
+            })
+            .unwrap();
+
+        // Simulate a crash: drop the journal and append a torn line.
+        drop(journal);
+        let mut f = OpenOptions::new()
+            .append(true)
+            .open(dir.join(format!("{exec_id}.jsonl")))
+            .unwrap();
+        f.write_all(b"{\"event\":\"node_fin").unwrap();
+
+        let journal = ExecutionJournal::open(&dir).unwrap();
+        let store = SqliteTraceStore::in_memory().unwrap();
+        let recovered = journal.reconcile(&store).unwrap();
+        assert_eq!(recovered.len(), 1);
+        assert!(recovered[0].orphaned_nodes.contains_key("b"));
+        assert_eq!(recovered[0].finished_nodes.len(), 1);
+
+        let record = store.query_execution(exec_id).unwrap().unwrap();
+        assert_eq!(record.status, ExecutionStatus::Interrupted);
+        assert_eq!(record.completed_nodes, 1);
+        assert_eq!(record.failed_nodes, 1);
+
+        let spans = store.query_spans_by_execution(exec_id).unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].node_id, "b");
+        assert_eq!(spans[0].status, ExecutionStatus::Failed);
+
+        // Reconciled journals are discarded.
+        assert!(journal.recover().unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn finished_execution_leaves_no_journal() {
+        let dir = temp_dir();
+        let journal = ExecutionJournal::open(&dir).unwrap();
+        let exec_id = Uuid::new_v4();
+        journal
+            .append(&JournalEntry::ExecutionStarted {
+                execution_id: exec_id,
+                workflow_id: Uuid::new_v4(),
+                total_nodes: 0,
+                at: Utc::now(),
+            })
+            .unwrap();
+        journal
+            .append(&JournalEntry::ExecutionFinished {
+                execution_id: exec_id,
+                status: ExecutionStatus::Completed,
+                at: Utc::now(),
+            })
+            .unwrap();
+
+        assert!(!dir.join(format!("{exec_id}.jsonl")).exists());
+        assert!(journal.recover().unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -1,14 +1,54 @@
 //! hb-runner: DAG execution engine with parallel scheduling, caching, and partial re-execution.
 
 pub mod cache;
+pub mod cancel;
+pub mod capture;
+pub mod checkpoint;
+pub mod concurrency;
 pub mod context;
+pub mod contract;
+pub mod cron;
+pub mod env;
+pub mod error_route;
+pub mod events;
+pub mod fan_in;
+pub mod gc;
+pub mod iteration;
+pub mod journal;
+pub mod liveness;
+pub mod locks;
+pub mod manifest;
+pub mod map_reduce;
+pub mod memo;
 pub mod partial;
+pub mod pause;
 pub mod retry;
+pub mod schedule;
 pub mod scheduler;
+pub mod scratch;
+pub mod shutdown;
+pub mod telemetry;
+pub mod time_limit;
+pub mod watchdog;
+pub mod workflow_ref;
 
 // Re-export commonly used types
-pub use cache::ExecutionCache;
+pub use cache::{Admission, CacheConfig, CacheStats, ExecutionCache};
+pub use cancel::NodeCancellation;
+pub use checkpoint::Checkpoint;
+pub use concurrency::ConcurrencyLimits;
+pub use events::StatusLog;
+pub use gc::{GcReport, GcTargets, RetentionConfig};
+pub use journal::{ExecutionJournal, JournalEntry, RecoveredExecution};
+pub use locks::{LockConfig, LockError, ResourceLocks};
+pub use pause::PauseSignal;
+pub use schedule::{RunEstimate, Schedule, ScheduleCalendar, ScheduleTrigger};
 pub use scheduler::{AgentTaskExecutor, AgentTaskParams, ExecutionContext, NodeStatusEvent, StatusCallback};
+pub use scratch::ScratchSpace;
+pub use shutdown::{ShutdownCoordinator, ShutdownReport};
+pub use telemetry::SchedulerTelemetry;
+pub use time_limit::TimeLimits;
+pub use watchdog::{Watchdog, WatchdogLimits};
 
 use hb_core::graph::WorkflowSpec;
 use hb_core::trace::ExecutionRecord;
@@ -28,6 +68,36 @@ pub enum RunnerError {
     PolicyViolation(String),
     #[error("cancelled")]
     Cancelled,
+    #[error("journal error: {0}")]
+    Journal(String),
+    #[error("cannot resume: {0}")]
+    Resume(String),
+    #[error("{0}")]
+    Watchdog(String),
+}
+
+impl From<RunnerError> for hb_core::error::AppError {
+    fn from(e: RunnerError) -> Self {
+        use hb_core::error::{AppError, ErrorCode};
+        match e {
+            RunnerError::NodeExecution { node_id, message } => {
+                // Keep the hint/code a credential problem deserves.
+                let err = AppError::from_message(message);
+                let code = match err.code {
+                    ErrorCode::Internal => ErrorCode::Execution,
+                    code => code,
+                };
+                AppError { code, ..err }.with_node(node_id)
+            }
+            RunnerError::PolicyViolation(_) => AppError::new(ErrorCode::PolicyViolation, e.to_string()),
+            RunnerError::Watchdog(_) => AppError::new(ErrorCode::Execution, e.to_string()),
+            RunnerError::Cancelled => AppError::new(ErrorCode::Cancelled, e.to_string()),
+            RunnerError::Scheduling(_) | RunnerError::Resume(_) => {
+                AppError::new(ErrorCode::InvalidInput, e.to_string())
+            }
+            RunnerError::Cache(_) | RunnerError::Journal(_) => AppError::new(ErrorCode::Internal, e.to_string()),
+        }
+    }
 }
 
 /// Execute a workflow and return the execution record.
@@ -59,6 +129,16 @@ pub async fn execute_with_context(
     scheduler::run_dag_with_context(execution_id, spec, ctx).await
 }
 
+/// Resume a failed, cancelled or interrupted execution from its failure
+/// frontier. `ctx` needs the trace store the execution was recorded in.
+pub async fn resume_with_context(
+    execution_id: Uuid,
+    spec: &WorkflowSpec,
+    ctx: ExecutionContext,
+) -> Result<ExecutionRecord, RunnerError> {
+    scheduler::resume_execution(execution_id, spec, ctx).await
+}
+
 /// Execute a workflow with caching enabled.
 pub async fn execute_with_cache(
     spec: &WorkflowSpec,
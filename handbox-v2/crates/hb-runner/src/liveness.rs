@@ -0,0 +1,146 @@
+//! Liveness watch — tells a node that is still working from one that hung.
+//!
+//! Tools beat a [`Heartbeat`] as they make progress (progress updates,
+//! process output). While a node runs, its heartbeat is checked a few times
+//! per threshold: after `warn_after` of silence the node is reported as
+//! stalled, and once it beats again as resumed. After `kill_after` the node's
+//! future is dropped, which kills any process it started. Native tools beat
+//! for as long as they run (see [`hb_tool_executor::progress`]), so only
+//! tools running outside the app are stopped for silence.
+
+use hb_tool_executor::Heartbeat;
+use std::future::Future;
+use std::time::Duration;
+
+/// Bounds on how often the heartbeat is checked.
+const MIN_CHECK_INTERVAL: Duration = Duration::from_millis(20);
+const MAX_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A change in a watched node's liveness.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stall {
+    /// Silent for at least the warning threshold.
+    Stalled(Duration),
+    /// Beat again after a warning.
+    Resumed,
+}
+
+/// Outcome of watching a node.
+#[derive(Debug)]
+pub enum Watched<T> {
+    Finished(T),
+    /// Killed after this much silence.
+    Hung(Duration),
+}
+
+/// Run `work`, calling `on_stall` when it goes silent for `warn_after` and
+/// when it beats again, and giving up on it after `kill_after` of silence.
+pub async fn watch<F, S>(
+    work: F,
+    heartbeat: &Heartbeat,
+    warn_after: Option<Duration>,
+    kill_after: Option<Duration>,
+    mut on_stall: S,
+) -> Watched<F::Output>
+where
+    F: Future,
+    S: FnMut(Stall),
+{
+    let Some(shortest) = [warn_after, kill_after].into_iter().flatten().min() else {
+        return Watched::Finished(work.await);
+    };
+    let mut checks =
+        tokio::time::interval((shortest / 4).clamp(MIN_CHECK_INTERVAL, MAX_CHECK_INTERVAL));
+    checks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    tokio::pin!(work);
+    let mut stalled = false;
+    loop {
+        tokio::select! {
+            output = &mut work => return Watched::Finished(output),
+            _ = checks.tick() => {
+                let silence = heartbeat.silence();
+                if kill_after.is_some_and(|kill| silence >= kill) {
+                    return Watched::Hung(silence);
+                }
+                let silent = warn_after.is_some_and(|warn| silence >= warn);
+                if silent && !stalled {
+                    on_stall(Stall::Stalled(silence));
+                } else if !silent && stalled {
+                    on_stall(Stall::Resumed);
+                }
+                stalled = silent;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn warns_on_silence_and_kills_hung_work() {
+        let ms = Duration::from_millis;
+
+        // Silence follows the (paused) runtime clock
+        let heartbeat = Heartbeat::new();
+        tokio::time::advance(ms(30)).await;
+        assert_eq!(heartbeat.silence(), ms(30));
+        heartbeat.beat();
+        assert_eq!(heartbeat.silence(), Duration::ZERO);
+
+        // Beats for a while, goes quiet, then finishes
+        let heartbeat = Heartbeat::new();
+        let beating = heartbeat.clone();
+        let work = async move {
+            for _ in 0..5 {
+                tokio::time::sleep(ms(20)).await;
+                beating.beat();
+            }
+            tokio::time::sleep(ms(200)).await;
+            beating.beat();
+            tokio::time::sleep(ms(50)).await;
+            "done"
+        };
+        let mut changes = Vec::new();
+        let watched = watch(work, &heartbeat, Some(ms(100)), None, |stall| {
+            changes.push(stall)
+        })
+        .await;
+        assert!(matches!(watched, Watched::Finished("done")));
+        assert_eq!(changes, [Stall::Stalled(ms(100)), Stall::Resumed]);
+
+        // Never beats
+        let heartbeat = Heartbeat::new();
+        let mut changes = Vec::new();
+        let watched = watch(
+            std::future::pending::<()>(),
+            &heartbeat,
+            Some(ms(50)),
+            Some(ms(150)),
+            |stall| changes.push(stall),
+        )
+        .await;
+        assert!(matches!(watched, Watched::Hung(silence) if silence >= ms(150)));
+        assert_eq!(changes.len(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn work_kept_alive_is_never_stalled() {
+        let ms = Duration::from_millis;
+        let heartbeat = Heartbeat::new();
+        let reporter = hb_tool_executor::ProgressReporter::default().with_heartbeat(heartbeat.clone());
+        // Like an LLM request that answers after a long silence
+        let work = reporter.keep_alive(async {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            "answer"
+        });
+        let mut changes = Vec::new();
+        let watched = watch(work, &heartbeat, Some(ms(1500)), Some(ms(3000)), |stall| {
+            changes.push(stall)
+        })
+        .await;
+        assert!(matches!(watched, Watched::Finished("answer")));
+        assert!(changes.is_empty(), "{changes:?}");
+    }
+}
@@ -0,0 +1,472 @@
+//! Resource locks — advisory, in-process locks keyed by the resource URIs
+//! tools declare, so that two executions writing the same index or output
+//! file are serialized (or rejected) instead of interleaving.
+//!
+//! Each node holds its locks while it runs, on behalf of its execution.
+//! Locks are re-entrant per execution: nodes of one execution never block
+//! each other, only nodes of other executions. Resources are scoped to the
+//! execution's workspace, so runs in different workspaces never contend.
+
+use hb_core::tool::{ResourceAccess, ResourceDecl};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum LockError {
+    #[error("resource '{uri}' is locked by execution {holder}")]
+    Conflict { uri: String, holder: Uuid },
+    #[error("resource '{uri}' is still locked by execution {holder} after waiting {}s", waited.as_secs())]
+    Timeout {
+        uri: String,
+        holder: Uuid,
+        waited: Duration,
+    },
+}
+
+impl From<LockError> for hb_core::error::AppError {
+    fn from(e: LockError) -> Self {
+        use hb_core::error::{AppError, ErrorCode};
+        let code = match e {
+            LockError::Conflict { .. } => ErrorCode::Conflict,
+            LockError::Timeout { .. } => ErrorCode::Timeout,
+        };
+        AppError::new(code, e.to_string()).with_hint("Wait for the other run to finish, or cancel it.")
+    }
+}
+
+/// One resource an execution wants to lock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockClaim {
+    pub uri: String,
+    pub access: ResourceAccess,
+}
+
+/// A currently held lock, as reported by [`ResourceLocks::held`].
+#[derive(Debug, Clone, Serialize)]
+pub struct HeldLock {
+    pub uri: String,
+    pub access: ResourceAccess,
+    pub executions: Vec<Uuid>,
+}
+
+/// Holders of one resource, with a re-entrancy count per execution.
+#[derive(Debug)]
+enum Holder {
+    Writer(Uuid, u32),
+    Readers(HashMap<Uuid, u32>),
+}
+
+impl Holder {
+    /// The execution blocking `execution_id` from taking `access`, if any.
+    fn blocker(&self, execution_id: Uuid, access: ResourceAccess) -> Option<Uuid> {
+        match (self, access) {
+            (Holder::Writer(owner, _), _) if *owner != execution_id => Some(*owner),
+            (Holder::Writer(..), _) => None,
+            (Holder::Readers(_), ResourceAccess::Read) => None,
+            (Holder::Readers(readers), ResourceAccess::Write) => {
+                readers.keys().find(|&&id| id != execution_id).copied()
+            }
+        }
+    }
+}
+
+/// Lock table shared by every execution in the process.
+#[derive(Default)]
+pub struct ResourceLocks {
+    table: Mutex<HashMap<String, Holder>>,
+    released: Notify,
+}
+
+impl ResourceLocks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take every claim or none of them, failing immediately on conflict.
+    pub fn try_acquire(
+        self: &Arc<Self>,
+        execution_id: Uuid,
+        claims: &[LockClaim],
+    ) -> Result<LockGuard, LockError> {
+        let mut table = self.table.lock().unwrap_or_else(|e| e.into_inner());
+        for claim in claims {
+            if let Some(holder) = table
+                .get(&claim.uri)
+                .and_then(|h| h.blocker(execution_id, claim.access))
+            {
+                return Err(LockError::Conflict {
+                    uri: claim.uri.clone(),
+                    holder,
+                });
+            }
+        }
+        for claim in claims {
+            let holder = table
+                .entry(claim.uri.clone())
+                .or_insert_with(|| initial(claim.access, execution_id));
+            match (holder, claim.access) {
+                (Holder::Writer(_, count), _) => *count += 1,
+                (Holder::Readers(readers), ResourceAccess::Read) => {
+                    *readers.entry(execution_id).or_default() += 1;
+                }
+                // Upgrade: this execution is the only reader.
+                (holder, ResourceAccess::Write) => {
+                    let held = match holder {
+                        Holder::Readers(readers) => {
+                            readers.get(&execution_id).copied().unwrap_or(0)
+                        }
+                        Holder::Writer(_, count) => *count,
+                    };
+                    *holder = Holder::Writer(execution_id, held + 1);
+                }
+            }
+        }
+        Ok(LockGuard {
+            locks: Arc::clone(self),
+            execution_id,
+            uris: claims.iter().map(|c| c.uri.clone()).collect(),
+        })
+    }
+
+    /// Take every claim, waiting up to `wait` for conflicting executions to
+    /// release them. With `wait: None` a conflict is rejected immediately.
+    pub async fn acquire(
+        self: &Arc<Self>,
+        execution_id: Uuid,
+        claims: &[LockClaim],
+        wait: Option<Duration>,
+    ) -> Result<LockGuard, LockError> {
+        let Some(wait) = wait else {
+            return self.try_acquire(execution_id, claims);
+        };
+        let deadline = tokio::time::Instant::now() + wait;
+        loop {
+            // Register for the wakeup before checking, so a release between
+            // the check and the wait is not missed.
+            let released = self.released.notified();
+            tokio::pin!(released);
+            released.as_mut().enable();
+
+            match self.try_acquire(execution_id, claims) {
+                Ok(guard) => return Ok(guard),
+                Err(LockError::Conflict { uri, holder }) => {
+                    if tokio::time::timeout_at(deadline, released).await.is_err() {
+                        return Err(LockError::Timeout {
+                            uri,
+                            holder,
+                            waited: wait,
+                        });
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Every lock currently held, sorted by URI.
+    pub fn held(&self) -> Vec<HeldLock> {
+        let table = self.table.lock().unwrap_or_else(|e| e.into_inner());
+        let mut held: Vec<HeldLock> = table
+            .iter()
+            .map(|(uri, holder)| match holder {
+                Holder::Writer(id, _) => HeldLock {
+                    uri: uri.clone(),
+                    access: ResourceAccess::Write,
+                    executions: vec![*id],
+                },
+                Holder::Readers(readers) => HeldLock {
+                    uri: uri.clone(),
+                    access: ResourceAccess::Read,
+                    executions: readers.keys().copied().collect(),
+                },
+            })
+            .collect();
+        held.sort_by(|a, b| a.uri.cmp(&b.uri));
+        held
+    }
+
+    fn release(&self, execution_id: Uuid, uris: &[String]) {
+        let mut table = self.table.lock().unwrap_or_else(|e| e.into_inner());
+        for uri in uris {
+            let emptied = match table.get_mut(uri) {
+                Some(Holder::Writer(id, count)) if *id == execution_id => {
+                    *count -= 1;
+                    *count == 0
+                }
+                Some(Holder::Readers(readers)) => {
+                    if let Some(count) = readers.get_mut(&execution_id) {
+                        *count -= 1;
+                        if *count == 0 {
+                            readers.remove(&execution_id);
+                        }
+                    }
+                    readers.is_empty()
+                }
+                _ => false,
+            };
+            if emptied {
+                table.remove(uri);
+            }
+        }
+        drop(table);
+        self.released.notify_waiters();
+    }
+}
+
+fn initial(access: ResourceAccess, execution_id: Uuid) -> Holder {
+    match access {
+        ResourceAccess::Write => Holder::Writer(execution_id, 0),
+        ResourceAccess::Read => Holder::Readers(HashMap::new()),
+    }
+}
+
+/// Releases its locks when dropped.
+pub struct LockGuard {
+    locks: Arc<ResourceLocks>,
+    execution_id: Uuid,
+    uris: Vec<String>,
+}
+
+impl Drop for LockGuard {
+    fn drop(&mut self) {
+        self.locks.release(self.execution_id, &self.uris);
+    }
+}
+
+/// Resources declared by one tool, plus its config defaults used to fill
+/// `{config.*}` placeholders the node leaves unset.
+#[derive(Debug, Clone, Default)]
+pub struct ToolResources {
+    pub resources: Vec<ResourceDecl>,
+    pub config_defaults: Map<String, Value>,
+}
+
+/// Lock settings for one execution.
+#[derive(Clone)]
+pub struct LockConfig {
+    pub table: Arc<ResourceLocks>,
+    /// Declared resources keyed by tool ID (without `@version`).
+    pub tools: HashMap<String, ToolResources>,
+    /// How long to wait for a conflicting execution; `None` rejects at once.
+    pub wait: Option<Duration>,
+    /// Workspace the execution runs in, which prefixes every locked URI.
+    pub workspace: Option<Uuid>,
+}
+
+impl LockConfig {
+    /// Lock `claims` for a node of `execution_id` until the guard drops.
+    pub async fn acquire(&self, execution_id: Uuid, claims: &[LockClaim]) -> Result<LockGuard, LockError> {
+        let scoped: Vec<LockClaim> = claims
+            .iter()
+            .map(|claim| LockClaim {
+                uri: self.scoped(&claim.uri),
+                access: claim.access,
+            })
+            .collect();
+        self.table.acquire(execution_id, &scoped, self.wait).await
+    }
+
+    /// The lock table key of `uri` in this execution's workspace.
+    fn scoped(&self, uri: &str) -> String {
+        match self.workspace {
+            Some(workspace) => format!("{workspace}:{uri}"),
+            None => uri.to_string(),
+        }
+    }
+
+    /// Resolve the claims a node makes. Declarations whose placeholders have
+    /// no value are skipped.
+    pub fn claims(
+        &self,
+        tool_ref: &str,
+        config: &Map<String, Value>,
+        input: &Value,
+    ) -> Vec<LockClaim> {
+        let tool_id = tool_ref.split('@').next().unwrap_or(tool_ref);
+        let Some(tool) = self.tools.get(tool_id) else {
+            return vec![];
+        };
+        let mut merged = tool.config_defaults.clone();
+        merged.extend(config.clone());
+        tool.resources
+            .iter()
+            .filter_map(|decl| {
+                let uri = decl.resolve(&merged, input);
+                if uri.is_none() {
+                    tracing::debug!("Unresolved resource '{}' for {}", decl.uri, tool_ref);
+                }
+                uri.map(|uri| LockClaim {
+                    uri,
+                    access: decl.access,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claim(uri: &str, access: ResourceAccess) -> LockClaim {
+        LockClaim {
+            uri: uri.into(),
+            access,
+        }
+    }
+
+    #[test]
+    fn writers_exclude_other_executions() {
+        let locks = Arc::new(ResourceLocks::new());
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let write = [claim("index://docs", ResourceAccess::Write)];
+        let read = [claim("index://docs", ResourceAccess::Read)];
+
+        let guard = locks.try_acquire(a, &write).unwrap();
+        // Re-entrant for the same execution.
+        let nested = locks.try_acquire(a, &read).unwrap();
+        let err = locks.try_acquire(b, &read).err().unwrap();
+        assert_eq!(
+            err,
+            LockError::Conflict {
+                uri: "index://docs".into(),
+                holder: a
+            }
+        );
+        assert!(err.to_string().contains("is locked by execution"));
+
+        drop(nested);
+        assert_eq!(locks.held().len(), 1);
+        drop(guard);
+        assert!(locks.held().is_empty());
+
+        let r1 = locks.try_acquire(a, &read).unwrap();
+        let _r2 = locks.try_acquire(b, &read).unwrap();
+        assert_eq!(locks.held()[0].executions.len(), 2);
+        assert!(locks.try_acquire(a, &write).is_err());
+        drop(r1);
+    }
+
+    #[test]
+    fn acquisition_is_all_or_nothing() {
+        let locks = Arc::new(ResourceLocks::new());
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let _held = locks
+            .try_acquire(a, &[claim("file:///out.pdf", ResourceAccess::Write)])
+            .unwrap();
+        let claims = [
+            claim("index://docs", ResourceAccess::Write),
+            claim("file:///out.pdf", ResourceAccess::Write),
+        ];
+        assert!(locks.try_acquire(b, &claims).is_err());
+        assert_eq!(locks.held().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn waiters_proceed_after_release() {
+        let locks = Arc::new(ResourceLocks::new());
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let claims = [claim("index://docs", ResourceAccess::Write)];
+        let guard = locks.try_acquire(a, &claims).unwrap();
+
+        let waiter = {
+            let locks = Arc::clone(&locks);
+            let claims = claims.clone();
+            tokio::spawn(async move {
+                locks
+                    .acquire(b, &claims, Some(Duration::from_secs(5)))
+                    .await
+                    .map(|_| ())
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        drop(guard);
+        assert!(waiter.await.unwrap().is_ok());
+
+        let _guard = locks.try_acquire(a, &claims).unwrap();
+        let err = locks
+            .acquire(b, &claims, Some(Duration::from_millis(20)))
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err, LockError::Timeout { .. }));
+    }
+
+    #[test]
+    fn claims_fill_config_defaults() {
+        let mut tools = HashMap::new();
+        tools.insert(
+            "core-tools/vector-store".to_string(),
+            ToolResources {
+                resources: vec![ResourceDecl {
+                    uri: "index://{config.index_name}".into(),
+                    access: ResourceAccess::Write,
+                }],
+                config_defaults: serde_json::json!({ "index_name": "default" })
+                    .as_object()
+                    .cloned()
+                    .unwrap(),
+            },
+        );
+        let config = LockConfig {
+            table: Arc::new(ResourceLocks::new()),
+            tools,
+            wait: None,
+            workspace: None,
+        };
+        let claims = config.claims("core-tools/vector-store@1.0.0", &Map::new(), &Value::Null);
+        assert_eq!(
+            claims,
+            vec![claim("index://default", ResourceAccess::Write)]
+        );
+        assert!(config
+            .claims("core-tools/file-read", &Map::new(), &Value::Null)
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn concurrent_writers_hold_a_resource_one_at_a_time() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let table = Arc::new(ResourceLocks::new());
+        let workspace = Some(Uuid::new_v4());
+        let config = |workspace| LockConfig {
+            table: Arc::clone(&table),
+            tools: HashMap::new(),
+            wait: Some(Duration::from_secs(10)),
+            workspace,
+        };
+        let claims = [claim("index://docs", ResourceAccess::Write)];
+        let (holding, most) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+
+        let writers: Vec<_> = (0..8)
+            .map(|_| {
+                let (config, claims) = (config(workspace), claims.clone());
+                let (holding, most) = (Arc::clone(&holding), Arc::clone(&most));
+                tokio::spawn(async move {
+                    let _guard = config.acquire(Uuid::new_v4(), &claims).await.unwrap();
+                    most.fetch_max(holding.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                    holding.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.await.unwrap();
+        }
+        assert_eq!(most.load(Ordering::SeqCst), 1);
+        assert!(table.held().is_empty());
+
+        // The same URI in another workspace is another resource
+        let _held = config(workspace).acquire(Uuid::new_v4(), &claims).await.unwrap();
+        let other = LockConfig { wait: None, ..config(Some(Uuid::new_v4())) };
+        assert!(other.acquire(Uuid::new_v4(), &claims).await.is_ok());
+        assert!(LockConfig { wait: None, ..config(workspace) }.acquire(Uuid::new_v4(), &claims).await.is_err());
+    }
+}
@@ -0,0 +1,93 @@
+//! Output manifest — derives the files, indexes and external endpoints a node
+//! touched from its tool's resource declarations and its tool_ref.
+
+use crate::locks::LockClaim;
+use hb_core::tool::ResourceAccess;
+use hb_core::trace::{ArtifactKind, ExecutionManifest};
+
+/// Classify a resolved resource URI. Reads of files and indexes are not
+/// artifacts; network endpoints are recorded whatever the access.
+fn classify(claim: &LockClaim, llm_provider: Option<&str>) -> Option<(ArtifactKind, String)> {
+    let (scheme, rest) = claim.uri.split_once("://")?;
+    let write = claim.access == ResourceAccess::Write;
+    match scheme {
+        "file" if write => Some((ArtifactKind::FileWritten, rest.to_string())),
+        "index" if write => Some((ArtifactKind::IndexUpdated, rest.to_string())),
+        // `llm://{model}` names a model of whichever provider the execution
+        // uses; a bare `llm://` any of its models.
+        "llm" => {
+            let provider = llm_provider.unwrap_or("default");
+            let target = if rest.is_empty() { format!("llm://{provider}") } else { format!("llm://{provider}/{rest}") };
+            Some((ArtifactKind::ExternalCall, target))
+        }
+        "http" | "https" => Some((ArtifactKind::ExternalCall, claim.uri.clone())),
+        _ => None,
+    }
+}
+
+/// Record what a successfully completed node touched.
+pub fn record_node(
+    manifest: &mut ExecutionManifest,
+    node_id: &str,
+    tool_ref: &str,
+    claims: &[LockClaim],
+    llm_provider: Option<&str>,
+) {
+    for (kind, target) in claims.iter().filter_map(|c| classify(c, llm_provider)) {
+        manifest.record(kind, &target, node_id);
+    }
+    if let Some(rest) = tool_ref.strip_prefix("mcp://") {
+        let server = rest.split('/').next().unwrap_or(rest);
+        manifest.record(
+            ArtifactKind::ExternalCall,
+            &format!("mcp://{server}"),
+            node_id,
+        );
+    } else if tool_ref == "agent-task" {
+        let endpoint = format!("llm://{}", llm_provider.unwrap_or("default"));
+        manifest.record(ArtifactKind::ExternalCall, &endpoint, node_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_writes_and_endpoints() {
+        let claim = |uri: &str, access| LockClaim {
+            uri: uri.into(),
+            access,
+        };
+        let claims = [
+            claim("file:///out/report.pdf", ResourceAccess::Write),
+            claim("file:///in/data.csv", ResourceAccess::Read),
+            claim("index://docs", ResourceAccess::Write),
+            claim("llm://claude-3-haiku", ResourceAccess::Read),
+        ];
+        let mut manifest = ExecutionManifest::default();
+        record_node(
+            &mut manifest,
+            "n1",
+            "core-tools/llm-chat",
+            &claims,
+            Some("bedrock"),
+        );
+        record_node(&mut manifest, "n2", "mcp://github/create_issue", &[], None);
+
+        let targets: Vec<(ArtifactKind, &str)> = manifest
+            .artifacts
+            .iter()
+            .map(|a| (a.kind, a.target.as_str()))
+            .collect();
+        assert_eq!(
+            targets,
+            [
+                (ArtifactKind::FileWritten, "/out/report.pdf"),
+                (ArtifactKind::IndexUpdated, "docs"),
+                (ArtifactKind::ExternalCall, "llm://bedrock/claude-3-haiku"),
+                (ArtifactKind::ExternalCall, "mcp://github"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,53 @@
+//! Map-reduce sharding — splits a map-reduce node's input into shards.
+
+use serde_json::Value;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ShardError {
+    #[error("map-reduce items must be an array, got {0}")]
+    NotAnArray(&'static str),
+}
+
+/// Shard a map-reduce node's `items` value (see [`shard`]); anything but
+/// an array is an error.
+pub fn shard_items(items: &Value, shard_size: u32) -> Result<Vec<Value>, ShardError> {
+    match items {
+        Value::Array(items) => Ok(shard(items, shard_size)),
+        Value::Null => Err(ShardError::NotAnArray("null")),
+        Value::Bool(_) => Err(ShardError::NotAnArray("a boolean")),
+        Value::Number(_) => Err(ShardError::NotAnArray("a number")),
+        Value::String(_) => Err(ShardError::NotAnArray("a string")),
+        Value::Object(_) => Err(ShardError::NotAnArray("an object")),
+    }
+}
+
+/// Split `items` into shards of `shard_size` (treated as at least 1). With a
+/// shard size of 1 each shard is the bare item rather than a one-element array.
+pub fn shard(items: &[Value], shard_size: u32) -> Vec<Value> {
+    let size = shard_size.max(1) as usize;
+    if size == 1 {
+        return items.to_vec();
+    }
+    items
+        .chunks(size)
+        .map(|chunk| Value::Array(chunk.to_vec()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn shards_by_size() {
+        let items: Vec<Value> = (1..=5).map(|i| json!(i)).collect();
+        assert_eq!(shard(&items, 2), vec![json!([1, 2]), json!([3, 4]), json!([5])]);
+        assert_eq!(shard(&items, 1), items);
+        assert_eq!(shard(&items, 0).len(), 5);
+        assert!(shard(&[], 3).is_empty());
+        assert_eq!(shard_items(&json!([1, 2, 3]), 2).unwrap().len(), 2);
+        assert_eq!(shard_items(&json!({ "a": 1 }), 2), Err(ShardError::NotAnArray("an object")));
+    }
+}
@@ -0,0 +1,509 @@
+//! Subgraph memoization — reuse the outputs of whole chains of pure nodes
+//! across executions.
+//!
+//! A pure node (see [`ToolInterface::is_pure`](hb_core::tool::ToolInterface::is_pure))
+//! whose upstream nodes are all pure gets a subgraph key: the hash of its
+//! tool, config and incoming edges, with the subgraph keys of the upstream
+//! nodes in place of their IDs. The key covers everything the node's output
+//! depends on, whatever the nodes are called.
+//!
+//! A chain ends at a pure node that feeds an impure one, or nothing. After a
+//! successful run, the outputs of every node in the chain are cached together
+//! under the end node's key. The next run finds them before scheduling
+//! anything and skips the whole chain, so editing the last node of a workflow
+//! doesn't re-run the ingestion before it.
+//!
+//! Memoization is opt-in per workflow ([`WorkflowMeta::memoize`](hb_core::graph::WorkflowMeta::memoize)).
+//! Tools that read files are keyed on the size and modification time of
+//! every file their config names, and only when all of their inputs come
+//! from that config: a path that arrives over an edge, or names no file, keeps
+//! the node (and everything below it) out of chains.
+
+use crate::cache::ExecutionCache;
+use crate::env::resolve_env_refs;
+use hb_core::graph::{NodeEntry, WorkflowSpec};
+use hb_core::tool::ToolInterface;
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// Cache keys of chains, apart from node cache keys.
+const KEY_PREFIX: &str = "subgraph:";
+
+/// Tools whose chains may be memoized, by tool ID without `@version`.
+#[derive(Debug, Clone, Default)]
+pub struct PureTools {
+    /// Tools whose output depends only on their inputs and config.
+    pub pure: HashSet<String>,
+    /// Pure tools whose output also depends on the files they read.
+    pub reads_files: HashSet<String>,
+}
+
+impl PureTools {
+    pub fn is_empty(&self) -> bool {
+        self.pure.is_empty()
+    }
+}
+
+impl<'a> FromIterator<&'a ToolInterface> for PureTools {
+    /// The pure ones among `tools`.
+    fn from_iter<I: IntoIterator<Item = &'a ToolInterface>>(tools: I) -> Self {
+        let mut pure = Self::default();
+        for tool in tools.into_iter().filter(|tool| tool.is_pure()) {
+            if tool.reads_files() {
+                pure.reads_files.insert(tool.tool_id.clone());
+            }
+            pure.pure.insert(tool.tool_id.clone());
+        }
+        pure
+    }
+}
+
+/// Subgraph keys of a workflow's pure nodes and the chain outputs found for
+/// them.
+#[derive(Debug, Default)]
+pub struct MemoPlan {
+    keys: HashMap<String, String>,
+    /// End node and members (in declaration order) of chains of two or more
+    /// nodes. Single nodes are left to the node cache.
+    chains: Vec<(String, Vec<String>)>,
+    /// Outputs of nodes in chains found in the cache.
+    hits: HashMap<String, Value>,
+}
+
+impl MemoPlan {
+    /// Key the pure nodes of `spec` and look up their chains. Empty unless
+    /// the workflow opts in to memoization. `inputs` holds what nodes get
+    /// from outside the graph, as in a referenced workflow, by node ID.
+    pub fn build(
+        spec: &WorkflowSpec,
+        inputs: &HashMap<String, Value>,
+        pure_tools: &PureTools,
+        env: &BTreeMap<String, String>,
+        workspace_root: Option<&str>,
+        cache: &ExecutionCache,
+    ) -> Self {
+        if !spec.meta.memoize {
+            return Self::default();
+        }
+        let keys = subgraph_keys(spec, inputs, pure_tools, env, workspace_root);
+        let chains = chains(spec, &keys);
+        let mut hits = HashMap::new();
+        for (end, members) in &chains {
+            let Ok(Some(Value::Object(outputs))) =
+                cache.lookup(&format!("{KEY_PREFIX}{}", keys[end]))
+            else {
+                continue;
+            };
+            let found: Option<Vec<_>> = members
+                .iter()
+                .map(|id| Some((id.clone(), outputs.get(&keys[id])?.clone())))
+                .collect();
+            hits.extend(found.into_iter().flatten());
+        }
+        Self { keys, chains, hits }
+    }
+
+    /// The cached output of `node_id`, when its chain was found.
+    pub fn hit(&self, node_id: &str) -> Option<&Value> {
+        self.hits.get(node_id)
+    }
+
+    /// Subgraph key of `node_id`, if it is pure all the way up.
+    pub fn key(&self, node_id: &str) -> Option<&str> {
+        self.keys.get(node_id).map(String::as_str)
+    }
+
+    /// Cache the chains that ran and whose nodes all succeeded. `durations`
+    /// holds the run time of each node, which decides whether a chain is
+    /// worth keeping. Returns the number of chains cached.
+    pub fn store(
+        &self,
+        spec: &WorkflowSpec,
+        cache: &ExecutionCache,
+        outputs: &HashMap<String, Value>,
+        succeeded: &HashSet<String>,
+        durations: &HashMap<String, i64>,
+    ) -> usize {
+        let index = spec.index();
+        let mut stored = 0;
+        for (end, members) in &self.chains {
+            if self.hits.contains_key(end) || !members.iter().all(|id| succeeded.contains(id)) {
+                continue;
+            }
+            let Some(chain_outputs) = members
+                .iter()
+                .map(|id| Some((self.keys[id].clone(), outputs.get(id)?.clone())))
+                .collect::<Option<Map<_, _>>>()
+            else {
+                continue;
+            };
+            let compute_ms: i64 = members.iter().filter_map(|id| durations.get(id)).sum();
+            // The chain expires with its shortest-lived node
+            let ttl = members
+                .iter()
+                .filter_map(|id| match index.node(id) {
+                    Some(NodeEntry::Primitive(n)) => n.cache.as_ref().map(|c| c.ttl_secs),
+                    _ => None,
+                })
+                .filter(|&ttl| ttl > 0)
+                .min();
+            let admitted = cache.offer(
+                &format!("{KEY_PREFIX}{}", self.keys[end]),
+                &Value::Object(chain_outputs),
+                ttl,
+                Duration::from_millis(compute_ms.max(0) as u64),
+            );
+            match admitted {
+                Ok(admission) if admission.is_admitted() => stored += 1,
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Failed to cache the chain ending at '{end}': {e}"),
+            }
+        }
+        stored
+    }
+}
+
+/// Subgraph key of every pure node whose upstream nodes all have one.
+fn subgraph_keys(
+    spec: &WorkflowSpec,
+    inputs: &HashMap<String, Value>,
+    pure_tools: &PureTools,
+    env: &BTreeMap<String, String>,
+    workspace_root: Option<&str>,
+) -> HashMap<String, String> {
+    let mut keys: HashMap<String, String> = HashMap::new();
+    // Key nodes once all their sources are keyed; stops when a pass adds none
+    loop {
+        let mut added = false;
+        for entry in &spec.nodes {
+            let NodeEntry::Primitive(node) = entry else {
+                continue;
+            };
+            let tool_id = node.tool_ref.split('@').next().unwrap_or(&node.tool_ref);
+            let cacheable = node.cache.as_ref().is_none_or(|c| c.enabled);
+            if keys.contains_key(&node.id)
+                || node.disabled
+                || !cacheable
+                || !pure_tools.pure.contains(tool_id)
+            {
+                continue;
+            }
+            let incoming: Option<Vec<Value>> = spec
+                .edges
+                .iter()
+                .filter(|e| e.target_node == node.id)
+                .map(|e| {
+                    Some(json!({
+                        "source": keys.get(&e.source_node)?,
+                        "source_port": e.source_port,
+                        "target_port": e.target_port,
+                        "kind": e.kind,
+                        "transform": e.transform,
+                    }))
+                })
+                .collect();
+            let Some(incoming) = incoming else {
+                continue;
+            };
+            let (config, _) = resolve_env_refs(&Value::Object(node.config.clone()), env);
+            let files = if pure_tools.reads_files.contains(tool_id) {
+                match file_fingerprints(&config, workspace_root) {
+                    Some(files) if incoming.is_empty() && !inputs.contains_key(&node.id) => files,
+                    _ => continue,
+                }
+            } else {
+                Vec::new()
+            };
+            let mut canonical = json!({
+                "tool": node.tool_ref,
+                "config": config,
+                "fan_in": node.fan_in,
+                "incoming": incoming,
+                "root": workspace_root,
+                "files": files,
+            });
+            if let Some(input) = inputs.get(&node.id) {
+                canonical["inputs"] = input.clone();
+            }
+            let key = hex::encode(Sha256::digest(canonical.to_string().as_bytes()));
+            keys.insert(node.id.clone(), key);
+            added = true;
+        }
+        if !added {
+            return keys;
+        }
+    }
+}
+
+/// What a node's cached output depends on besides its inputs and config:
+/// the workspace root relative paths resolve against, and the size and
+/// modification time of the files its inputs or config name.
+pub fn data_version(inputs: &Value, config: &Value, workspace_root: Option<&str>) -> Option<String> {
+    let files = file_fingerprints(&json!([inputs, config]), workspace_root);
+    match (workspace_root, files) {
+        (root, Some(files)) => Some(format!("{}{}", root.unwrap_or_default(), Value::Array(files))),
+        (root, None) => root.map(String::from),
+    }
+}
+
+/// Longest string value looked up as a file path.
+const MAX_PATH_LEN: usize = 1024;
+
+/// Path, size and modification time of every file a config value names
+/// (relative paths against `workspace_root`); `None` when it names none.
+fn file_fingerprints(config: &Value, workspace_root: Option<&str>) -> Option<Vec<Value>> {
+    fn strings<'a>(value: &'a Value, found: &mut Vec<&'a str>) {
+        match value {
+            Value::String(s) if s.len() <= MAX_PATH_LEN && !s.contains('\n') => found.push(s),
+            Value::Array(items) => items.iter().for_each(|v| strings(v, found)),
+            Value::Object(map) => map.values().for_each(|v| strings(v, found)),
+            _ => {}
+        }
+    }
+    let mut values = Vec::new();
+    strings(config, &mut values);
+    let files: Vec<Value> = values
+        .into_iter()
+        .filter_map(|value| {
+            let path = Path::new(value);
+            let path = match workspace_root {
+                Some(root) if path.is_relative() => Path::new(root).join(path),
+                _ => path.to_path_buf(),
+            };
+            let meta = std::fs::metadata(&path).ok().filter(|m| m.is_file())?;
+            let modified = meta
+                .modified()
+                .ok()?
+                .duration_since(UNIX_EPOCH)
+                .ok()?
+                .as_nanos();
+            Some(json!([value, meta.len(), modified.to_string()]))
+        })
+        .collect();
+    (!files.is_empty()).then_some(files)
+}
+
+/// Chains of two or more keyed nodes, by the keyed node they end at.
+fn chains(spec: &WorkflowSpec, keys: &HashMap<String, String>) -> Vec<(String, Vec<String>)> {
+    let ends = spec.nodes.iter().map(NodeEntry::id).filter(|id| {
+        keys.contains_key(*id) && {
+            let mut consumers = spec.edges.iter().filter(|e| e.source_node == *id);
+            consumers.clone().next().is_none()
+                || consumers.any(|e| !keys.contains_key(&e.target_node))
+        }
+    });
+    ends.filter_map(|end| {
+        // Everything upstream of a keyed node is keyed
+        let mut members: HashSet<&str> = HashSet::from([end]);
+        let mut frontier = vec![end];
+        while let Some(id) = frontier.pop() {
+            for edge in spec.edges.iter().filter(|e| e.target_node == id) {
+                if members.insert(&edge.source_node) {
+                    frontier.push(&edge.source_node);
+                }
+            }
+        }
+        (members.len() > 1).then(|| {
+            let ordered = spec
+                .nodes
+                .iter()
+                .map(NodeEntry::id)
+                .filter(|id| members.contains(id))
+                .map(String::from)
+                .collect();
+            (end.to_string(), ordered)
+        })
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hb_core::graph::{EdgeSpec, NodeSpec};
+
+    fn node(id: &str, tool: &str, config: Value) -> NodeEntry {
+        NodeEntry::Primitive(NodeSpec {
+            id: id.into(),
+            tool_ref: format!("{tool}@1.0.0"),
+            config: config.as_object().cloned().unwrap_or_default(),
+            ..Default::default()
+        })
+    }
+
+    fn edge(source: &str, target: &str) -> EdgeSpec {
+        EdgeSpec {
+            id: format!("{source}-{target}"),
+            source_node: source.into(),
+            source_port: "out".into(),
+            target_node: target.into(),
+            target_port: "in".into(),
+            ..Default::default()
+        }
+    }
+
+    /// read → split → embed → report, with a pure ingestion chain.
+    fn pipeline(report_prompt: &str) -> WorkflowSpec {
+        let mut spec = WorkflowSpec {
+            nodes: vec![
+                node("read", "file-read", json!({ "path": "docs/a.txt" })),
+                node("split", "text-split", json!({ "chunk_size": 500 })),
+                node("embed", "embedding", json!({})),
+                node("report", "llm-chat", json!({ "prompt": report_prompt })),
+            ],
+            edges: vec![
+                edge("read", "split"),
+                edge("split", "embed"),
+                edge("embed", "report"),
+            ],
+            ..Default::default()
+        };
+        spec.meta.memoize = true;
+        spec
+    }
+
+    fn pure() -> PureTools {
+        PureTools {
+            pure: ["file-read", "text-split", "embedding"].map(String::from).into(),
+            reads_files: ["file-read".to_string()].into(),
+        }
+    }
+
+    /// A workspace root holding `docs/a.txt`.
+    fn workspace() -> std::path::PathBuf {
+        let root = std::env::temp_dir().join(format!("hb-memo-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("docs")).unwrap();
+        std::fs::write(root.join("docs/a.txt"), "Footing pour").unwrap();
+        root
+    }
+
+    #[test]
+    fn keys_follow_content_not_node_ids() {
+        let dir = workspace();
+        let root = dir.to_str();
+        let spec = pipeline("Summarize");
+        let keys = subgraph_keys(&spec, &HashMap::new(), &pure(), &BTreeMap::new(), root);
+        assert_eq!(keys.len(), 3);
+        assert!(!keys.contains_key("report"));
+        assert_eq!(
+            chains(&spec, &keys),
+            [(
+                "embed".to_string(),
+                vec!["read".into(), "split".into(), "embed".into()]
+            )]
+        );
+
+        // Renamed nodes, same chain
+        let mut renamed = spec.clone();
+        for entry in &mut renamed.nodes {
+            if let NodeEntry::Primitive(n) = entry {
+                n.id = format!("x-{}", n.id);
+            }
+        }
+        for e in &mut renamed.edges {
+            e.source_node = format!("x-{}", e.source_node);
+            e.target_node = format!("x-{}", e.target_node);
+        }
+        let renamed_keys = subgraph_keys(&renamed, &HashMap::new(), &pure(), &BTreeMap::new(), root);
+        assert_eq!(renamed_keys["x-embed"], keys["embed"]);
+
+        // A changed upstream config changes every key below it
+        let mut edited = spec.clone();
+        if let NodeEntry::Primitive(n) = &mut edited.nodes[1] {
+            n.config.insert("chunk_size".into(), json!(800));
+        }
+        let edited_keys = subgraph_keys(&edited, &HashMap::new(), &pure(), &BTreeMap::new(), root);
+        assert_eq!(edited_keys["read"], keys["read"]);
+        assert_ne!(edited_keys["split"], keys["split"]);
+        assert_ne!(edited_keys["embed"], keys["embed"]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn file_reads_are_keyed_on_the_files_they_read() {
+        let dir = workspace();
+        let root = dir.to_str();
+        let spec = pipeline("Summarize");
+        let keys = subgraph_keys(&spec, &HashMap::new(), &pure(), &BTreeMap::new(), root);
+
+        // A changed file changes every key below it
+        std::fs::write(dir.join("docs/a.txt"), "Footing pour, revised").unwrap();
+        let changed = subgraph_keys(&spec, &HashMap::new(), &pure(), &BTreeMap::new(), root);
+        assert_ne!(changed["read"], keys["read"]);
+        assert_ne!(changed["embed"], keys["embed"]);
+
+        // A missing file, or a path from upstream, is never keyed
+        std::fs::remove_file(dir.join("docs/a.txt")).unwrap();
+        assert!(subgraph_keys(&spec, &HashMap::new(), &pure(), &BTreeMap::new(), root).is_empty());
+        std::fs::write(dir.join("docs/a.txt"), "Footing pour").unwrap();
+        let mut piped = spec.clone();
+        piped.nodes.insert(0, node("name", "text-template", json!({ "template": "docs/a.txt" })));
+        piped.edges.push(edge("name", "read"));
+        let mut tools = pure();
+        tools.pure.insert("text-template".into());
+        let piped_keys = subgraph_keys(&piped, &HashMap::new(), &tools, &BTreeMap::new(), root);
+        assert_eq!(piped_keys.keys().collect::<Vec<_>>(), ["name"]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn workflows_opt_in_to_memoization() {
+        let dir = workspace();
+        let cache = ExecutionCache::in_memory().unwrap();
+        let mut spec = pipeline("Summarize");
+        spec.meta.memoize = false;
+        let plan = MemoPlan::build(&spec, &HashMap::new(), &pure(), &BTreeMap::new(), dir.to_str(), &cache);
+        assert!(plan.key("read").is_none());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn stored_chains_are_found_after_editing_the_last_node() {
+        let dir = workspace();
+        let root = dir.to_str();
+        let cache = ExecutionCache::in_memory().unwrap();
+        let env = BTreeMap::new();
+        let spec = pipeline("Summarize");
+        let plan = MemoPlan::build(&spec, &HashMap::new(), &pure(), &env, root, &cache);
+        assert!(plan.hit("read").is_none());
+
+        let outputs: HashMap<String, Value> = ["read", "split", "embed", "report"]
+            .into_iter()
+            .map(|id| (id.to_string(), json!({ "out": format!("{id} output") })))
+            .collect();
+        let succeeded: HashSet<String> = outputs.keys().cloned().collect();
+        let durations: HashMap<String, i64> = outputs.keys().map(|id| (id.clone(), 400)).collect();
+        assert_eq!(
+            plan.store(&spec, &cache, &outputs, &succeeded, &durations),
+            1
+        );
+
+        let edited = pipeline("Write a detailed report");
+        let plan = MemoPlan::build(&edited, &HashMap::new(), &pure(), &env, root, &cache);
+        for id in ["read", "split", "embed"] {
+            assert_eq!(plan.hit(id), Some(&outputs[id]));
+        }
+        assert!(plan.hit("report").is_none());
+        // Found chains are not stored again
+        assert_eq!(
+            plan.store(&edited, &cache, &outputs, &succeeded, &durations),
+            0
+        );
+
+        // A failed node keeps its chain out of the cache
+        let other = pipeline("x");
+        let mut other_pure = pure();
+        other_pure.pure.insert("llm-chat".into());
+        let plan = MemoPlan::build(&other, &HashMap::new(), &other_pure, &env, root, &cache);
+        let mut partial = succeeded.clone();
+        partial.remove("report");
+        assert_eq!(
+            plan.store(&other, &cache, &outputs, &partial, &durations),
+            0
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -44,32 +44,17 @@ mod tests {
                 NodeEntry::Primitive(NodeSpec {
                     id: "a".into(),
                     tool_ref: "t".into(),
-                    config: Default::default(),
-                    position: None,
-                    label: None,
-                    disabled: false,
-                    retry: None,
-                    cache: None,
+                    ..Default::default()
                 }),
                 NodeEntry::Primitive(NodeSpec {
                     id: "b".into(),
                     tool_ref: "t".into(),
-                    config: Default::default(),
-                    position: None,
-                    label: None,
-                    disabled: false,
-                    retry: None,
-                    cache: None,
+                    ..Default::default()
                 }),
                 NodeEntry::Primitive(NodeSpec {
                     id: "c".into(),
                     tool_ref: "t".into(),
-                    config: Default::default(),
-                    position: None,
-                    label: None,
-                    disabled: false,
-                    retry: None,
-                    cache: None,
+                    ..Default::default()
                 }),
             ],
             edges: vec![
@@ -79,8 +64,7 @@ mod tests {
                     source_port: "out".into(),
                     target_node: "b".into(),
                     target_port: "in".into(),
-                    kind: EdgeKind::Data,
-                    transform: None,
+                    ..Default::default()
                 },
                 EdgeSpec {
                     id: "e2".into(),
@@ -88,8 +72,7 @@ mod tests {
                     source_port: "out".into(),
                     target_node: "c".into(),
                     target_port: "in".into(),
-                    kind: EdgeKind::Data,
-                    transform: None,
+                    ..Default::default()
                 },
             ],
             ..Default::default()
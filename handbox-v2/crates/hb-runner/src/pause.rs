@@ -0,0 +1,119 @@
+//! Pause control — holds an execution between levels without cancelling it.
+//!
+//! Pausing stops the scheduler from dispatching further nodes; nodes already
+//! running finish as usual and their outputs are kept. The scheduler waits at
+//! the next level boundary — or the next step of a loop, branch, composite
+//! or referenced workflow body — until the execution is resumed or
+//! cancelled. Time spent paused is kept, so the execution's time limit can
+//! leave it out.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+/// How often a paused execution looks at its cancellation flag.
+const CANCEL_POLL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Default)]
+pub struct PauseSignal {
+    paused: AtomicBool,
+    resumed: Notify,
+    /// Time paused before the current pause, and when that one began.
+    clock: Mutex<(Duration, Option<Instant>)>,
+}
+
+impl PauseSignal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pause; false if already paused.
+    pub fn pause(&self) -> bool {
+        let mut clock = self.clock.lock().unwrap();
+        let paused = !self.paused.swap(true, Ordering::SeqCst);
+        if paused {
+            clock.1 = Some(Instant::now());
+        }
+        paused
+    }
+
+    /// Resume; false if not paused.
+    pub fn resume(&self) -> bool {
+        let mut clock = self.clock.lock().unwrap();
+        let was_paused = self.paused.swap(false, Ordering::SeqCst);
+        if let Some(since) = clock.1.take() {
+            clock.0 += since.elapsed();
+        }
+        drop(clock);
+        self.resumed.notify_waiters();
+        was_paused
+    }
+
+    /// Total time spent paused, the current pause included.
+    pub fn paused_for(&self) -> Duration {
+        let clock = self.clock.lock().unwrap();
+        clock.0 + clock.1.map_or(Duration::ZERO, |since| since.elapsed())
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// Wait until resumed, or until `cancelled` is set.
+    pub async fn wait(&self, cancelled: &AtomicBool) {
+        while self.is_paused() && !cancelled.load(Ordering::Relaxed) {
+            let resumed = self.resumed.notified();
+            tokio::pin!(resumed);
+            // Registered before the check, so a resume in between is not missed
+            resumed.as_mut().enable();
+            if !self.is_paused() {
+                break;
+            }
+            let _ = tokio::time::timeout(CANCEL_POLL, resumed).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn waits_until_resumed_or_cancelled() {
+        let signal = Arc::new(PauseSignal::new());
+        let cancelled = Arc::new(AtomicBool::new(false));
+        assert!(signal.pause());
+        assert!(!signal.pause());
+
+        let waiter = {
+            let (signal, cancelled) = (signal.clone(), cancelled.clone());
+            tokio::spawn(async move { signal.wait(&cancelled).await })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiter.is_finished());
+        assert!(signal.resume());
+        tokio::time::timeout(Duration::from_secs(1), waiter).await.unwrap().unwrap();
+        assert!(!signal.resume());
+
+        signal.pause();
+        cancelled.store(true, Ordering::Relaxed);
+        tokio::time::timeout(Duration::from_secs(1), signal.wait(&cancelled)).await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn counts_time_spent_paused() {
+        let signal = PauseSignal::new();
+        signal.pause();
+        tokio::time::advance(Duration::from_secs(5)).await;
+        assert_eq!(signal.paused_for(), Duration::from_secs(5));
+        signal.resume();
+        tokio::time::advance(Duration::from_secs(3)).await;
+        signal.pause();
+        tokio::time::advance(Duration::from_secs(2)).await;
+        signal.resume();
+        assert_eq!(signal.paused_for(), Duration::from_secs(7));
+    }
+}
@@ -1,8 +1,32 @@
 //! Retry logic with exponential backoff.
+//!
+//! [`AdaptiveRetry`] tunes the policy per tool: a failure is matched
+//! against the error types the tool declares, so permanent errors stop at
+//! once and rate limits back off longer, and the tool's recent success rate
+//! adjusts how many attempts are worth making. A node's own `retry` policy
+//! is never adjusted, only cut short for permanent errors.
 
 use hb_core::graph::RetryPolicy;
+use hb_core::tool::ErrorModel;
+use hb_core::trace::ToolOutcomes;
+use std::collections::HashMap;
 use std::time::Duration;
 
+/// Runs needed before a tool's success rate is trusted.
+const MIN_HISTORY_RUNS: u32 = 10;
+
+/// Below this success rate a tool is considered flaky.
+const FLAKY_SUCCESS_RATE: f64 = 0.9;
+
+/// Below this success rate failures are mostly not transient.
+const FAILING_SUCCESS_RATE: f64 = 0.5;
+
+/// Upper bound on retries added for flaky tools.
+const MAX_ADAPTIVE_RETRIES: u32 = 5;
+
+/// Smallest first delay after a rate limit.
+const RATE_LIMIT_MIN_BACKOFF_MS: u64 = 5_000;
+
 /// Compute the delay before retry attempt `attempt` (0-based).
 pub fn compute_delay(policy: &RetryPolicy, attempt: u32) -> Duration {
     let delay_ms = (policy.backoff_ms as f64) * policy.backoff_multiplier.powi(attempt as i32);
@@ -15,9 +39,159 @@ pub fn should_retry(policy: &RetryPolicy, attempt: u32) -> bool {
     attempt < policy.max_retries
 }
 
+/// How a failed attempt is treated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    /// Declared non-retryable; retrying cannot help.
+    Permanent,
+    /// Declared retryable.
+    Transient,
+    /// Declared retryable and caused by a rate limit.
+    RateLimited,
+    /// Not matched by any declared error type.
+    Unknown,
+}
+
+/// Retry metadata of one tool.
+#[derive(Debug, Clone, Default)]
+pub struct ToolRetryInfo {
+    pub error_model: Option<ErrorModel>,
+    pub outcomes: Option<ToolOutcomes>,
+}
+
+/// Per-tool retry metadata for one execution, keyed by tool ID (without
+/// `@version`). Tools without an entry use the node or default policy.
+#[derive(Debug, Clone, Default)]
+pub struct AdaptiveRetry {
+    pub tools: HashMap<String, ToolRetryInfo>,
+}
+
+impl AdaptiveRetry {
+    fn tool(&self, tool_ref: &str) -> Option<&ToolRetryInfo> {
+        self.tools
+            .get(tool_ref.split('@').next().unwrap_or(tool_ref))
+    }
+
+    /// Policy for a node: its own override as is, otherwise the tool's
+    /// declared default adjusted to the tool's recent success rate.
+    pub fn policy(&self, tool_ref: &str, node_retry: Option<&RetryPolicy>) -> RetryPolicy {
+        if let Some(policy) = node_retry {
+            return policy.clone();
+        }
+        let Some(tool) = self.tool(tool_ref) else {
+            return RetryPolicy::default();
+        };
+        let mut policy = tool
+            .error_model
+            .as_ref()
+            .map(|m| m.default_retry.clone())
+            .unwrap_or_default();
+        let Some(outcomes) = tool
+            .outcomes
+            .as_ref()
+            .filter(|o| o.runs >= MIN_HISTORY_RUNS)
+        else {
+            return policy;
+        };
+        let rate = outcomes.success_rate();
+        if rate < FAILING_SUCCESS_RATE {
+            // Mostly failing for good: one retry is enough to catch a blip
+            policy.max_retries = policy.max_retries.min(1);
+        } else if rate < FLAKY_SUCCESS_RATE {
+            // Flaky: failures usually clear, given more attempts and time
+            if policy.max_retries < MAX_ADAPTIVE_RETRIES {
+                policy.max_retries += 1;
+            }
+            policy.backoff_ms = policy.backoff_ms.saturating_mul(2);
+        }
+        policy
+    }
+
+    /// Match an error message against the tool's declared error types.
+    pub fn classify(&self, tool_ref: &str, error: &str) -> FailureKind {
+        let Some(model) = self.tool(tool_ref).and_then(|t| t.error_model.as_ref()) else {
+            return FailureKind::Unknown;
+        };
+        let error = error.to_lowercase();
+        let Some(matched) = model
+            .error_types
+            .iter()
+            .find(|t| error_matches(&t.code, &error))
+        else {
+            return FailureKind::Unknown;
+        };
+        if !matched.retryable {
+            FailureKind::Permanent
+        } else if is_rate_limit(&matched.code) {
+            FailureKind::RateLimited
+        } else {
+            FailureKind::Transient
+        }
+    }
+
+    /// Whether another attempt should follow a failure of `kind`. Tools
+    /// that aren't idempotent only retry failures they declare retryable.
+    pub fn should_retry(
+        &self,
+        tool_ref: &str,
+        policy: &RetryPolicy,
+        attempt: u32,
+        kind: FailureKind,
+    ) -> bool {
+        let idempotent = self
+            .tool(tool_ref)
+            .and_then(|t| t.error_model.as_ref())
+            .is_none_or(|m| m.idempotent);
+        match kind {
+            FailureKind::Permanent => false,
+            FailureKind::Unknown if !idempotent => false,
+            _ => should_retry(policy, attempt),
+        }
+    }
+}
+
+/// Delay before retry `attempt` after a failure of `kind`; rate limits
+/// start from a longer delay.
+pub fn delay_for(policy: &RetryPolicy, attempt: u32, kind: FailureKind) -> Duration {
+    if kind != FailureKind::RateLimited {
+        return compute_delay(policy, attempt);
+    }
+    let policy = RetryPolicy {
+        backoff_ms: policy.backoff_ms.max(RATE_LIMIT_MIN_BACKOFF_MS),
+        backoff_multiplier: policy.backoff_multiplier.max(2.0),
+        max_backoff_ms: policy.max_backoff_ms.max(RATE_LIMIT_MIN_BACKOFF_MS),
+        ..policy.clone()
+    };
+    compute_delay(&policy, attempt)
+}
+
+/// Error messages carry either the code itself (`RATE_LIMITED`) or its
+/// words (`rate limited`); a few common codes have well-known phrasings.
+fn error_matches(code: &str, error: &str) -> bool {
+    let code = code.to_lowercase();
+    let words = code.replace('_', " ");
+    if error.contains(&code) || error.contains(&words) {
+        return true;
+    }
+    let aliases: &[&str] = if is_rate_limit(&code) {
+        &["rate limit", "too many requests", "429"]
+    } else if code.contains("timeout") || code.contains("timed_out") {
+        &["timed out", "timeout"]
+    } else {
+        &[]
+    };
+    aliases.iter().any(|a| error.contains(a))
+}
+
+fn is_rate_limit(code: &str) -> bool {
+    let code = code.to_lowercase();
+    code.contains("rate_limit") || code.contains("throttl") || code == "too_many_requests"
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use hb_core::tool::ErrorType;
 
     #[test]
     fn exponential_backoff() {
@@ -44,4 +218,98 @@ mod tests {
         assert!(should_retry(&policy, 2));
         assert!(!should_retry(&policy, 3));
     }
+
+    fn error_type(code: &str, retryable: bool) -> ErrorType {
+        ErrorType {
+            code: code.into(),
+            description: String::new(),
+            retryable,
+        }
+    }
+
+    fn adaptive(idempotent: bool, runs: u32, failures: u32) -> AdaptiveRetry {
+        let info = ToolRetryInfo {
+            error_model: Some(ErrorModel {
+                error_types: vec![
+                    error_type("FILE_NOT_FOUND", false),
+                    error_type("RATE_LIMITED", true),
+                    error_type("API_ERROR", true),
+                ],
+                idempotent,
+                default_retry: RetryPolicy {
+                    max_retries: 2,
+                    backoff_ms: 500,
+                    backoff_multiplier: 2.0,
+                    max_backoff_ms: 10_000,
+                },
+            }),
+            outcomes: Some(ToolOutcomes {
+                tool_id: "t/api".into(),
+                runs,
+                failures,
+            }),
+        };
+        AdaptiveRetry {
+            tools: HashMap::from([("t/api".to_string(), info)]),
+        }
+    }
+
+    #[test]
+    fn classifies_by_declared_error_types() {
+        let retry = adaptive(true, 0, 0);
+        let kind = |e: &str| retry.classify("t/api@1.0.0", e);
+        assert_eq!(kind("File not found: /tmp/x.csv"), FailureKind::Permanent);
+        assert_eq!(kind("HTTP 429 Too Many Requests"), FailureKind::RateLimited);
+        assert_eq!(kind("API_ERROR: upstream 502"), FailureKind::Transient);
+        assert_eq!(kind("something else"), FailureKind::Unknown);
+        assert_eq!(
+            retry.classify("t/other", "File not found"),
+            FailureKind::Unknown
+        );
+
+        let policy = retry.policy("t/api", None);
+        assert!(!retry.should_retry("t/api", &policy, 0, FailureKind::Permanent));
+        assert!(retry.should_retry("t/api", &policy, 0, FailureKind::Unknown));
+        let strict = adaptive(false, 0, 0);
+        assert!(!strict.should_retry("t/api", &policy, 0, FailureKind::Unknown));
+        assert!(strict.should_retry("t/api", &policy, 0, FailureKind::Transient));
+
+        assert_eq!(
+            delay_for(&policy, 0, FailureKind::Transient),
+            Duration::from_millis(500)
+        );
+        assert_eq!(
+            delay_for(&policy, 1, FailureKind::RateLimited),
+            Duration::from_millis(10_000)
+        );
+    }
+
+    #[test]
+    fn adapts_attempts_to_success_rate() {
+        let node_policy = RetryPolicy {
+            max_retries: 7,
+            ..Default::default()
+        };
+        // The node's own policy is left alone
+        assert_eq!(
+            adaptive(true, 100, 90)
+                .policy("t/api", Some(&node_policy))
+                .max_retries,
+            7
+        );
+
+        // Too little history: the declared default
+        let policy = adaptive(true, 5, 4).policy("t/api", None);
+        assert_eq!((policy.max_retries, policy.backoff_ms), (2, 500));
+
+        let reliable = adaptive(true, 100, 2).policy("t/api", None);
+        assert_eq!((reliable.max_retries, reliable.backoff_ms), (2, 500));
+        let flaky = adaptive(true, 100, 25).policy("t/api", None);
+        assert_eq!((flaky.max_retries, flaky.backoff_ms), (3, 1_000));
+        let failing = adaptive(true, 100, 80).policy("t/api", None);
+        assert_eq!(failing.max_retries, 1);
+
+        // Unknown tools keep the global default
+        assert_eq!(AdaptiveRetry::default().policy("t/x", None).max_retries, 3);
+    }
 }
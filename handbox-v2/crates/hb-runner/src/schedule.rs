@@ -0,0 +1,372 @@
+//! Workflow schedules and calendar queries — upcoming runs, overlapping runs,
+//! and run estimates derived from execution history.
+
+use crate::cron::{self, CronError, CronExpr};
+use chrono::{DateTime, Duration, Utc};
+use hb_core::trace::{ExecutionRecord, ExecutionStatus};
+use hb_policy::estimate::Range;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Duration assumed for runs of workflows without any completed history.
+const DEFAULT_RUN_DURATION_MS: i64 = 60_000;
+
+/// When a schedule fires.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ScheduleTrigger {
+    /// Fire once at a fixed time.
+    Once { at: DateTime<Utc> },
+    /// Fire every `every_secs` seconds, starting at `starting_at`.
+    Interval {
+        every_secs: u64,
+        starting_at: DateTime<Utc>,
+    },
+    /// Fire per a cron expression on the wall clock of `timezone` (an IANA
+    /// name; the local zone when unset).
+    Cron {
+        expr: String,
+        #[serde(default)]
+        timezone: Option<String>,
+    },
+}
+
+impl ScheduleTrigger {
+    /// First fire time strictly after `after`.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Once { at } => (*at > after).then_some(*at),
+            Self::Interval {
+                every_secs,
+                starting_at,
+            } => {
+                if *every_secs == 0 {
+                    return None;
+                }
+                if *starting_at > after {
+                    return Some(*starting_at);
+                }
+                let every = *every_secs as i64;
+                let elapsed = (after - *starting_at).num_seconds();
+                let periods = elapsed / every + 1;
+                Some(*starting_at + Duration::seconds(periods * every))
+            }
+            Self::Cron { expr, timezone } => {
+                let tz = cron::resolve_timezone(timezone.as_deref()).ok()?;
+                CronExpr::parse(expr).ok()?.next_after(after, tz)
+            }
+        }
+    }
+
+    /// Check that the trigger can fire: a valid cron expression and timezone.
+    pub fn validate(&self) -> Result<(), CronError> {
+        if let Self::Cron { expr, timezone } = self {
+            CronExpr::parse(expr)?;
+            cron::resolve_timezone(timezone.as_deref())?;
+        }
+        Ok(())
+    }
+}
+
+/// A workflow scheduled to run automatically.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Schedule {
+    pub id: Uuid,
+    pub workflow_id: Uuid,
+    pub name: String,
+    pub trigger: ScheduleTrigger,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Workspace the runs start in (none for runs outside a workspace).
+    #[serde(default)]
+    pub workspace_id: Option<Uuid>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Schedule {
+    /// Fire times in `[from, until)`, at most `limit` of them.
+    pub fn occurrences(
+        &self,
+        from: DateTime<Utc>,
+        until: DateTime<Utc>,
+        limit: usize,
+    ) -> Vec<DateTime<Utc>> {
+        let mut times = Vec::new();
+        if !self.enabled {
+            return times;
+        }
+        // `next_after` is exclusive, so step back a tick to include `from`.
+        let mut cursor = from - Duration::milliseconds(1);
+        while times.len() < limit {
+            match self.trigger.next_after(cursor) {
+                Some(t) if t < until => {
+                    times.push(t);
+                    cursor = t;
+                }
+                _ => break,
+            }
+        }
+        times
+    }
+
+    /// Whether the schedule fires in `(after, until]`.
+    pub fn due(&self, after: DateTime<Utc>, until: DateTime<Utc>) -> bool {
+        self.enabled && self.trigger.next_after(after).is_some_and(|t| t <= until)
+    }
+}
+
+/// Expected run characteristics of a workflow, derived from past executions.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RunEstimate {
+    /// Number of finished executions the estimate is based on.
+    pub sample_size: usize,
+    pub avg_duration_ms: Option<i64>,
+    pub avg_nodes: f64,
+    pub cache_hit_rate: f64,
+    pub failure_rate: f64,
+    /// Cost of one run, from the tools' recorded averages and hints (unset
+    /// when the workflow isn't known).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<Range>,
+}
+
+impl RunEstimate {
+    /// Build an estimate from execution records; unfinished runs are ignored.
+    pub fn from_history(records: &[ExecutionRecord]) -> Self {
+        let finished: Vec<_> = records
+            .iter()
+            .filter(|r| {
+                matches!(
+                    r.status,
+                    ExecutionStatus::Completed
+                        | ExecutionStatus::CompletedWithViolations
+                        | ExecutionStatus::Failed
+                ) && r.completed_at.is_some()
+            })
+            .collect();
+        if finished.is_empty() {
+            return Self::default();
+        }
+
+        let n = finished.len();
+        let total_ms: i64 = finished
+            .iter()
+            .filter_map(|r| r.completed_at.map(|end| (end - r.started_at).num_milliseconds()))
+            .sum();
+        let total_nodes: u32 = finished.iter().map(|r| r.total_nodes).sum();
+        let cache_hits: u32 = finished.iter().map(|r| r.cache_hits).sum();
+        let failures = finished
+            .iter()
+            .filter(|r| r.status == ExecutionStatus::Failed)
+            .count();
+
+        Self {
+            sample_size: n,
+            avg_duration_ms: Some(total_ms / n as i64),
+            avg_nodes: total_nodes as f64 / n as f64,
+            cache_hit_rate: if total_nodes > 0 {
+                cache_hits as f64 / total_nodes as f64
+            } else {
+                0.0
+            },
+            failure_rate: failures as f64 / n as f64,
+            cost_usd: None,
+        }
+    }
+
+    fn duration(&self) -> Duration {
+        Duration::milliseconds(self.avg_duration_ms.unwrap_or(DEFAULT_RUN_DURATION_MS))
+    }
+}
+
+/// One upcoming run on the calendar.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduledRun {
+    pub schedule_id: Uuid,
+    pub workflow_id: Uuid,
+    pub schedule_name: String,
+    pub starts_at: DateTime<Utc>,
+    /// Estimated end based on history (or a default when there is none).
+    pub estimated_end: DateTime<Utc>,
+}
+
+/// Two upcoming runs whose estimated windows overlap.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleConflict {
+    pub first: ScheduledRun,
+    pub second: ScheduledRun,
+    /// Both runs belong to the same workflow.
+    pub same_workflow: bool,
+}
+
+/// Calendar view of upcoming automation.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleCalendar {
+    pub from: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    /// Upcoming runs ordered by start time.
+    pub runs: Vec<ScheduledRun>,
+    pub conflicts: Vec<ScheduleConflict>,
+    /// Run estimates keyed by workflow ID.
+    pub estimates: HashMap<Uuid, RunEstimate>,
+}
+
+/// Compute upcoming runs (at most `per_schedule` per schedule) in `[from, until)`
+/// and the pairs of runs that are expected to overlap.
+pub fn build_calendar(
+    schedules: &[Schedule],
+    estimates: HashMap<Uuid, RunEstimate>,
+    from: DateTime<Utc>,
+    until: DateTime<Utc>,
+    per_schedule: usize,
+) -> ScheduleCalendar {
+    let mut runs: Vec<ScheduledRun> = schedules
+        .iter()
+        .flat_map(|s| {
+            let duration = estimates
+                .get(&s.workflow_id)
+                .map(RunEstimate::duration)
+                .unwrap_or_else(|| Duration::milliseconds(DEFAULT_RUN_DURATION_MS));
+            s.occurrences(from, until, per_schedule)
+                .into_iter()
+                .map(move |t| ScheduledRun {
+                    schedule_id: s.id,
+                    workflow_id: s.workflow_id,
+                    schedule_name: s.name.clone(),
+                    starts_at: t,
+                    estimated_end: t + duration,
+                })
+        })
+        .collect();
+    runs.sort_by_key(|r| r.starts_at);
+
+    // Runs are sorted by start, so each run only needs comparing against the
+    // following runs that start before it ends.
+    let mut conflicts = Vec::new();
+    for (i, run) in runs.iter().enumerate() {
+        for other in runs[i + 1..]
+            .iter()
+            .take_while(|o| o.starts_at < run.estimated_end)
+        {
+            conflicts.push(ScheduleConflict {
+                first: run.clone(),
+                second: other.clone(),
+                same_workflow: run.workflow_id == other.workflow_id,
+            });
+        }
+    }
+
+    ScheduleCalendar {
+        from,
+        until,
+        runs,
+        conflicts,
+        estimates,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(workflow_id: Uuid, every_secs: u64, starting_at: DateTime<Utc>) -> Schedule {
+        Schedule {
+            id: Uuid::new_v4(),
+            workflow_id,
+            name: "nightly".into(),
+            trigger: ScheduleTrigger::Interval {
+                every_secs,
+                starting_at,
+            },
+            enabled: true,
+            workspace_id: None,
+        }
+    }
+
+    #[test]
+    fn interval_occurrences_are_aligned_to_start() {
+        let start = Utc::now();
+        let schedule = interval(Uuid::new_v4(), 3600, start);
+        let from = start + Duration::minutes(90);
+        let times = schedule.occurrences(from, from + Duration::days(1), 3);
+        assert_eq!(
+            times,
+            vec![
+                start + Duration::hours(2),
+                start + Duration::hours(3),
+                start + Duration::hours(4),
+            ]
+        );
+    }
+
+    #[test]
+    fn schedules_are_due_once_per_fire_time() {
+        let start = Utc::now();
+        let mut schedule = interval(Uuid::new_v4(), 60, start);
+        assert!(schedule.due(start - Duration::seconds(1), start));
+        assert!(!schedule.due(start, start + Duration::seconds(30)));
+        assert!(schedule.due(start + Duration::seconds(30), start + Duration::seconds(60)));
+
+        schedule.enabled = false;
+        assert!(!schedule.due(start - Duration::seconds(1), start));
+    }
+
+    #[test]
+    fn cron_trigger_uses_its_timezone() {
+        let trigger = ScheduleTrigger::Cron {
+            expr: "0 9 * * *".into(),
+            timezone: Some("Asia/Seoul".into()),
+        };
+        assert!(trigger.validate().is_ok());
+        let next = trigger.next_after("2024-03-01T01:00:00Z".parse().unwrap());
+        assert_eq!(next, Some("2024-03-02T00:00:00Z".parse().unwrap()));
+
+        let bad = ScheduleTrigger::Cron {
+            expr: "0 9 * *".into(),
+            timezone: None,
+        };
+        assert!(bad.validate().is_err());
+        assert_eq!(bad.next_after(Utc::now()), None);
+    }
+
+    #[test]
+    fn overlapping_runs_are_reported() {
+        let start = Utc::now();
+        let wf = Uuid::new_v4();
+        let hourly = interval(wf, 3600, start);
+        let other = interval(Uuid::new_v4(), 7200, start + Duration::minutes(30));
+
+        // History says this workflow takes 45 minutes.
+        let record = ExecutionRecord {
+            execution_id: Uuid::new_v4(),
+            workflow_id: wf,
+            started_at: start - Duration::days(1),
+            completed_at: Some(start - Duration::days(1) + Duration::minutes(45)),
+            status: ExecutionStatus::Completed,
+            total_nodes: 4,
+            completed_nodes: 4,
+            failed_nodes: 0,
+            cache_hits: 1,
+            workspace_id: None,
+            manifest: Default::default(),
+            contract_violations: Vec::new(),
+        };
+        let estimates = HashMap::from([(wf, RunEstimate::from_history(&[record]))]);
+
+        let calendar = build_calendar(
+            &[hourly, other],
+            estimates,
+            start,
+            start + Duration::hours(2),
+            10,
+        );
+        assert_eq!(calendar.runs.len(), 3);
+        assert_eq!(calendar.conflicts.len(), 1);
+        assert!(!calendar.conflicts[0].same_workflow);
+        assert_eq!(calendar.estimates[&wf].cache_hit_rate, 0.25);
+    }
+}
@@ -2,18 +2,47 @@
 //! Supports caching, retry policies, streaming status updates, and control flow nodes.
 
 use crate::cache::{compute_cache_key, ExecutionCache};
-use crate::retry::{compute_delay, should_retry};
+use crate::cancel::NodeCancellation;
+use crate::checkpoint::Checkpoint;
+use crate::concurrency::{ConcurrencyLimits, Slot};
+use crate::contract;
+use crate::events::StatusLog;
+use crate::liveness::{self, Stall, Watched};
+use crate::capture;
+use crate::env::resolve_env_refs;
+use crate::error_route;
+use crate::fan_in::{self, VariadicPorts};
+use crate::iteration;
+use crate::journal::{ExecutionJournal, JournalEntry};
+use crate::locks::LockConfig;
+use crate::manifest;
+use crate::memo::{self, MemoPlan, PureTools};
+use crate::pause::PauseSignal;
+use crate::map_reduce;
+use crate::retry::{delay_for, AdaptiveRetry};
+use crate::scratch::{self, ScratchSpace};
+use crate::telemetry::SchedulerTelemetry;
+use crate::time_limit::{self, Deadline, TimeLimits};
+use crate::watchdog::{Watchdog, WatchdogLimits};
+use crate::workflow_ref;
 use crate::RunnerError;
 use chrono::Utc;
 use hb_core::graph::{
-    ConditionalSpec, EdgeKind, LoopSpec, NodeEntry, NodeSpec, SubgraphSpec, WorkflowSpec,
+    ConditionalSpec, EdgeKind, EdgeSpec, LoopSpec, MapReduceSpec, NodeEntry, NodeSpec, PortMapping,
+    SubgraphSpec, WorkflowRefSpec, WorkflowSpec,
 };
+use hb_core::policy::{EdgeCapturePolicy, LivenessPolicy};
+use hb_core::project::WorkspaceContext;
 use hb_core::tool::RuntimeSpec;
-use hb_core::trace::{ExecutionEnvironment, ExecutionRecord, ExecutionStatus, NodeSpan};
+use hb_core::trace::{ExecutionEnvironment, ExecutionManifest, ExecutionRecord, ExecutionStatus, NodeSpan};
 use hb_mcp::McpClient;
-use hb_tool_executor::{execute, ToolInput};
-use std::collections::{HashMap, HashSet, VecDeque};
+use hb_tool_executor::{
+    execute, CommandGate, Heartbeat, OutputChunk, OutputStream, Progress, ProgressReporter, Secrets,
+    ToolInput,
+};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::future::Future;
+use std::path::PathBuf;
 use std::pin::Pin;
 use std::sync::Arc;
 use tokio::sync::Mutex;
@@ -27,10 +56,19 @@ type McpClientCache = Arc<Mutex<HashMap<String, Arc<Mutex<McpClient>>>>>;
 pub struct NodeStatusEvent {
     pub execution_id: String,
     pub node_id: String,
-    pub status: String, // "pending", "running", "completed", "failed", "cache_hit", "skipped"
+    pub status: String, // "pending", "paused", "running", "progress", "output", "stalled", "completed", "failed", "cache_hit", "skipped", "cancelled"
+    /// Position in the execution's event order, from 1 (set by the
+    /// [`StatusLog`] when the event is emitted).
+    pub seq: u64,
     pub output: Option<serde_json::Value>,
     pub error: Option<String>,
     pub duration_ms: Option<i64>,
+    /// Set on "progress" events from long-running tools.
+    pub progress: Option<Progress>,
+    /// Set on "output" events: a piece of output streamed before the node
+    /// finishes, e.g. LLM tokens or process stdout.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk: Option<OutputChunk>,
 }
 
 /// Callback type for status updates.
@@ -61,14 +99,70 @@ pub struct ExecutionContext {
     pub execution_cache: Option<Arc<ExecutionCache>>,
     pub status_callback: Option<StatusCallback>,
     pub cancelled: Arc<std::sync::atomic::AtomicBool>,
+    /// Holds the execution before its next level while paused.
+    pub pause: Arc<PauseSignal>,
+    /// Nodes cancelled on their own, with their dependents, while the rest
+    /// of the execution runs on.
+    pub node_cancellation: Arc<NodeCancellation>,
     /// If true, stop execution immediately when any node fails.
     pub fail_fast: bool,
     /// Optional executor for agent-task nodes. Without this, agent-task returns a stub.
     pub agent_executor: Option<AgentTaskExecutor>,
     /// Optional trace store for persisting NodeSpan records.
-    pub trace_store: Option<Arc<hb_trace::store::TraceStore>>,
+    pub trace_store: Option<Arc<dyn hb_trace::store::TraceStore>>,
     /// Explicit LLM provider to use for workflow node execution.
     pub llm_provider: Option<String>,
+    /// Optional crash-safe journal of scheduler decisions.
+    pub journal: Option<Arc<ExecutionJournal>>,
+    /// Which edge values to persist to the trace store (none when unset).
+    pub edge_capture: Option<EdgeCapturePolicy>,
+    /// Advisory locks on the resources tools declare (unlocked when unset).
+    pub resource_locks: Option<LockConfig>,
+    /// How many tool nodes run at once, overall and per resource class
+    /// (unlimited when unset).
+    pub concurrency: Option<Arc<ConcurrencyLimits>>,
+    /// Per-tool retry tuning (node or default policies only when unset).
+    pub adaptive_retry: Option<Arc<AdaptiveRetry>>,
+    /// Files, indexes and endpoints touched so far, filled in as nodes complete.
+    pub manifest: Arc<std::sync::Mutex<ExecutionManifest>>,
+    /// Workspace environment variables for `{{env:NAME}}` references and
+    /// process runtimes.
+    pub env: Arc<BTreeMap<String, String>>,
+    /// When silent nodes are reported as stalled or killed as hung.
+    pub liveness: LivenessPolicy,
+    /// Numbered status events of the execution, for replay.
+    pub status_log: Arc<StatusLog>,
+    /// Programs shell-exec and bash nodes may start, and who approves the rest.
+    pub command_gate: CommandGate,
+    /// Runtimes of tools that run outside the app, keyed by tool ID (without
+    /// `@version`); other tools run natively.
+    pub tool_runtimes: Arc<HashMap<String, RuntimeSpec>>,
+    /// Secrets process runtimes may set as variables.
+    pub secrets: Secrets,
+    /// Workspace passed to native tools as the `_context` input (none when
+    /// the run has no workspace).
+    pub workspace: Option<Arc<WorkspaceContext>>,
+    /// Tools whose chains are memoized across executions of workflows that
+    /// opt in (requires a cache).
+    pub pure_tools: Arc<PureTools>,
+    /// Input ports that gather `{port}.{key}` edges into one object.
+    pub variadic_ports: Arc<VariadicPorts>,
+    /// Where each execution gets its scratch directory (none when unset).
+    pub scratch: Option<ScratchSpace>,
+    /// This execution's scratch directory, once created.
+    scratch_dir: Option<PathBuf>,
+    /// Wall-clock limits on the execution and each tool node (none when
+    /// unset).
+    pub time_limits: TimeLimits,
+    /// When this execution's nodes became ready, started and finished.
+    pub telemetry: Arc<SchedulerTelemetry>,
+    /// Saved workflows that workflow reference nodes may run, by ID.
+    pub workflows: Arc<HashMap<Uuid, WorkflowSpec>>,
+    /// Caps on node executions and workflow reference nesting.
+    pub watchdog: Arc<Watchdog>,
+    /// Workflows running around the current node, outermost first, by ID
+    /// and name.
+    workflow_chain: Vec<(Uuid, String)>,
 }
 
 impl Default for ExecutionContext {
@@ -78,10 +172,34 @@ impl Default for ExecutionContext {
             execution_cache: None,
             status_callback: None,
             cancelled: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            pause: Default::default(),
+            node_cancellation: Default::default(),
             fail_fast: true, // Default to stopping on first error
             agent_executor: None,
             trace_store: None,
             llm_provider: None,
+            journal: None,
+            edge_capture: None,
+            resource_locks: None,
+            concurrency: None,
+            adaptive_retry: None,
+            manifest: Default::default(),
+            env: Default::default(),
+            liveness: Default::default(),
+            status_log: Default::default(),
+            command_gate: Default::default(),
+            tool_runtimes: Default::default(),
+            secrets: Default::default(),
+            workspace: None,
+            pure_tools: Default::default(),
+            variadic_ports: Default::default(),
+            scratch: None,
+            scratch_dir: None,
+            time_limits: TimeLimits::default(),
+            telemetry: Default::default(),
+            workflows: Default::default(),
+            watchdog: Default::default(),
+            workflow_chain: Vec::new(),
         }
     }
 }
@@ -123,7 +241,7 @@ impl ExecutionContext {
     }
 
     /// Set the trace store for persisting execution spans.
-    pub fn with_trace_store(mut self, store: Arc<hb_trace::store::TraceStore>) -> Self {
+    pub fn with_trace_store(mut self, store: Arc<dyn hb_trace::store::TraceStore>) -> Self {
         self.trace_store = Some(store);
         self
     }
@@ -134,12 +252,193 @@ impl ExecutionContext {
         self
     }
 
-    fn emit_status(&self, event: NodeStatusEvent) {
-        if let Some(cb) = &self.status_callback {
-            cb(event);
+    /// Set the write-ahead journal used for crash recovery.
+    pub fn with_journal(mut self, journal: Arc<ExecutionJournal>) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    /// Persist edge values per the given policy (requires a trace store).
+    pub fn with_edge_capture(mut self, policy: EdgeCapturePolicy) -> Self {
+        self.edge_capture = Some(policy);
+        self
+    }
+
+    /// Lock declared resources so concurrent executions don't interleave
+    /// writes. The declarations also feed the output manifest.
+    pub fn with_resource_locks(mut self, locks: LockConfig) -> Self {
+        self.resource_locks = Some(locks);
+        self
+    }
+
+    /// Limit how many tool nodes run at once, overall and per resource class.
+    pub fn with_concurrency(mut self, limits: ConcurrencyLimits) -> Self {
+        self.concurrency = Some(Arc::new(limits));
+        self
+    }
+
+    /// Tune each tool's retry policy from its declared errors and recent
+    /// outcomes.
+    pub fn with_adaptive_retry(mut self, retry: AdaptiveRetry) -> Self {
+        self.adaptive_retry = Some(Arc::new(retry));
+        self
+    }
+
+    /// Set the workspace environment variables.
+    pub fn with_env(mut self, env: BTreeMap<String, String>) -> Self {
+        self.env = Arc::new(env);
+        self
+    }
+
+    /// Warn about, or kill, nodes that stop sending heartbeats.
+    pub fn with_liveness(mut self, policy: LivenessPolicy) -> Self {
+        self.liveness = policy;
+        self
+    }
+
+    /// Record status events in `log`, e.g. one the app keeps for clients
+    /// that reconnect mid-run.
+    pub fn with_status_log(mut self, log: Arc<StatusLog>) -> Self {
+        self.status_log = log;
+        self
+    }
+
+    /// Set the command allowlist and approver for shell-exec and bash nodes.
+    pub fn with_command_gate(mut self, gate: CommandGate) -> Self {
+        self.command_gate = gate;
+        self
+    }
+
+    /// Run these tools with their declared runtime instead of natively.
+    pub fn with_tool_runtimes(mut self, runtimes: HashMap<String, RuntimeSpec>) -> Self {
+        self.tool_runtimes = Arc::new(runtimes);
+        self
+    }
+
+    /// Secrets process runtimes may reference.
+    pub fn with_secrets(mut self, secrets: Secrets) -> Self {
+        self.secrets = secrets;
+        self
+    }
+
+    /// Set the workspace tools resolve relative paths against.
+    pub fn with_workspace(mut self, workspace: WorkspaceContext) -> Self {
+        self.workspace = Some(Arc::new(workspace));
+        self
+    }
+
+    /// Reuse the outputs of chains of these tools from earlier executions.
+    pub fn with_pure_tools(mut self, tools: PureTools) -> Self {
+        self.pure_tools = Arc::new(tools);
+        self
+    }
+
+    /// Set the variadic input ports of the registered tools.
+    pub fn with_variadic_ports(mut self, ports: VariadicPorts) -> Self {
+        self.variadic_ports = Arc::new(ports);
+        self
+    }
+
+    /// Give each execution a scratch directory from `scratch`.
+    pub fn with_scratch(mut self, scratch: ScratchSpace) -> Self {
+        self.scratch = Some(scratch);
+        self
+    }
+
+    /// Stop nodes, and the run, that exceed these limits.
+    pub fn with_time_limits(mut self, limits: TimeLimits) -> Self {
+        self.time_limits = limits;
+        self
+    }
+
+    /// Workflows that reference nodes run, from
+    /// [`workflow_ref::collect`](crate::workflow_ref::collect).
+    pub fn with_workflows(mut self, workflows: HashMap<Uuid, WorkflowSpec>) -> Self {
+        self.workflows = Arc::new(workflows);
+        self
+    }
+
+    /// Stop the run once it goes past these caps.
+    pub fn with_watchdog(mut self, limits: WatchdogLimits) -> Self {
+        self.watchdog = Arc::new(Watchdog::new(limits));
+        self
+    }
+
+    /// Snapshot of the output manifest collected so far.
+    pub fn manifest(&self) -> ExecutionManifest {
+        self.manifest.lock().map(|m| m.clone()).unwrap_or_default()
+    }
+
+    /// The runtime of `tool_ref`'s tool: its declared runtime, or native.
+    fn runtime_for(&self, tool_ref: &str) -> &RuntimeSpec {
+        let tool_id = tool_ref.split('@').next().unwrap_or(tool_ref);
+        self.tool_runtimes.get(tool_id).unwrap_or(&RuntimeSpec::Native)
+    }
+
+    /// The variadic input ports of `node`'s tool (none for sub-graph nodes).
+    fn variadic_inputs(&self, node: &NodeEntry) -> Option<&HashSet<String>> {
+        match node {
+            NodeEntry::Primitive(node) => self.variadic_ports.of(&node.tool_ref),
+            _ => None,
         }
     }
 
+    fn emit_status(&self, event: NodeStatusEvent) {
+        self.status_log.record(event, self.status_callback.as_ref());
+    }
+
+    /// Reporter that turns a node's tool progress into "progress" status
+    /// events (none without a status callback) and beats `heartbeat`.
+    fn progress_reporter(&self, execution_id: Uuid, node_id: &str, heartbeat: Heartbeat) -> ProgressReporter {
+        let Some(cb) = self.status_callback.clone() else {
+            return ProgressReporter::default().with_heartbeat(heartbeat);
+        };
+        let log = self.status_log.clone();
+        let execution_id = execution_id.to_string();
+        let node_id = node_id.to_string();
+        ProgressReporter::new(move |progress| {
+            let event = NodeStatusEvent {
+                execution_id: execution_id.clone(),
+                node_id: node_id.clone(),
+                status: "progress".into(),
+                seq: 0,
+                output: None,
+                error: None,
+                duration_ms: None,
+                progress: Some(progress),
+                chunk: None,
+            };
+            log.record(event, Some(&cb));
+        })
+        .with_heartbeat(heartbeat)
+    }
+
+    /// Stream that turns a node's partial output into "output" status
+    /// events (none without a status callback) and beats `heartbeat`.
+    fn output_stream(&self, execution_id: Uuid, node_id: &str, heartbeat: Heartbeat) -> OutputStream {
+        let Some(cb) = self.status_callback.clone() else {
+            return OutputStream::default().with_heartbeat(heartbeat);
+        };
+        let log = self.status_log.clone();
+        let execution_id = execution_id.to_string();
+        let node_id = node_id.to_string();
+        OutputStream::new(move |chunk| {
+            let event = NodeStatusEvent {
+                execution_id: execution_id.clone(),
+                node_id: node_id.clone(),
+                status: "output".into(),
+                seq: 0,
+                output: None,
+                error: None,
+                duration_ms: None,
+                progress: None,
+                chunk: Some(chunk),
+            };
+            log.record(event, Some(&cb));
+        })
+        .with_heartbeat(heartbeat)
+    }
+
     /// Record a span to the trace store (if configured). Non-blocking — logs errors.
     fn record_span(&self, span: &NodeSpan) {
         if let Some(ref store) = self.trace_store {
@@ -148,6 +447,79 @@ impl ExecutionContext {
             }
         }
     }
+
+    /// Record the values entering `node_id` (if edge capture and a trace
+    /// store are configured). Logs errors.
+    /// While the execution is paused, hold the caller before it starts
+    /// `node_ids`, which are reported as paused; nodes already running
+    /// finish. False once the execution is cancelled.
+    async fn pause_point(&self, execution_id: Uuid, node_ids: &[String]) -> bool {
+        if self.pause.is_paused() {
+            for node_id in node_ids {
+                self.emit_status(NodeStatusEvent {
+                    execution_id: execution_id.to_string(),
+                    node_id: node_id.clone(),
+                    status: "paused".into(),
+                    seq: 0,
+                    output: None,
+                    error: None,
+                    duration_ms: None,
+                    progress: None,
+                    chunk: None,
+                });
+            }
+            tracing::info!("Execution {execution_id} paused");
+            self.pause.wait(&self.cancelled).await;
+        }
+        !self.cancelled.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    fn record_edge_values<'a>(
+        &self,
+        execution_id: Uuid,
+        node_id: &str,
+        edges: impl IntoIterator<Item = &'a hb_core::graph::EdgeSpec>,
+        outputs: &HashMap<String, serde_json::Value>,
+    ) {
+        let (Some(policy), Some(store)) = (&self.edge_capture, &self.trace_store) else {
+            return;
+        };
+        for value in capture::capture_inputs(policy, execution_id, node_id, edges, outputs) {
+            if let Err(e) = store.insert_edge_value(&value) {
+                tracing::warn!("Failed to record value of edge {}: {e}", value.edge_id);
+            }
+        }
+    }
+
+    /// Append a scheduler decision to the journal (if configured). Logs errors.
+    fn journal(&self, entry: JournalEntry) {
+        if let Some(ref journal) = self.journal {
+            if let Err(e) = journal.append(&entry) {
+                tracing::warn!("Failed to append journal entry: {e}");
+            }
+        }
+    }
+
+    /// Record the workflow-level execution record (if a trace store is configured).
+    fn record_execution(&self, record: &ExecutionRecord) {
+        if let Some(ref store) = self.trace_store {
+            if let Err(e) = store.upsert_execution(record) {
+                tracing::warn!("Failed to record execution {}: {e}", record.execution_id);
+            }
+        }
+    }
+
+    /// Record the scheduler metrics of the execution (if a trace store is
+    /// configured).
+    fn record_scheduler_metrics(&self, execution_id: Uuid) {
+        if let Some(ref store) = self.trace_store {
+            let max_parallel = self.concurrency.as_ref().and_then(|c| c.max_parallel());
+            let metrics = self.telemetry.metrics(execution_id, max_parallel);
+            if let Err(e) = store.insert_scheduler_metrics(&metrics) {
+                tracing::warn!("Failed to record scheduler metrics of {execution_id}: {e}");
+            }
+        }
+    }
 }
 
 /// Run a workflow DAG with topological level-based scheduling.
@@ -177,9 +549,70 @@ pub async fn run_dag_with_context(
     spec: &WorkflowSpec,
     ctx: ExecutionContext,
 ) -> Result<ExecutionRecord, RunnerError> {
-    let started_at = Utc::now();
+    run_dag_from(execution_id, spec, ctx, Checkpoint::default(), Utc::now()).await
+}
+
+/// Resume a failed, cancelled or interrupted execution under the same ID.
+/// Nodes whose outputs the trace store still holds (see [`Checkpoint`]) are
+/// restored; the rest of the DAG runs again.
+pub async fn resume_execution(
+    execution_id: Uuid,
+    spec: &WorkflowSpec,
+    ctx: ExecutionContext,
+) -> Result<ExecutionRecord, RunnerError> {
+    let store = ctx
+        .trace_store
+        .clone()
+        .ok_or_else(|| RunnerError::Resume("no trace store to restore outputs from".into()))?;
+    let (record, checkpoint) = Checkpoint::load(store.as_ref(), execution_id, spec, &ctx.workflows, &ctx.env)?;
+    tracing::info!(
+        "Resuming execution {execution_id}: {} of {} node(s) restored",
+        checkpoint.outputs.len(),
+        spec.nodes.len()
+    );
+    run_dag_from(execution_id, spec, ctx, checkpoint, record.started_at).await
+}
+
+/// Run the nodes of `spec` not restored by `checkpoint` in a fresh scratch
+/// directory, removed afterwards.
+async fn run_dag_from(
+    execution_id: Uuid,
+    spec: &WorkflowSpec,
+    mut ctx: ExecutionContext,
+    checkpoint: Checkpoint,
+    started_at: chrono::DateTime<Utc>,
+) -> Result<ExecutionRecord, RunnerError> {
+    workflow_ref::check(spec, &ctx.workflows).map_err(RunnerError::Scheduling)?;
+    ctx.workflow_chain = vec![(spec.id, spec.meta.name.clone())];
+    if let Some(invalid) = hb_core::expr::check_nodes(&spec.nodes, |id| ctx.workflows.get(&id)).first() {
+        return Err(RunnerError::Scheduling(invalid.to_string()));
+    }
+    let scratch = ctx.scratch.clone();
+    if let Some(space) = &scratch {
+        match space.create(execution_id) {
+            Ok(dir) => ctx.scratch_dir = Some(dir),
+            Err(e) => tracing::warn!("Failed to create scratch directory for {execution_id}: {e}"),
+        }
+    }
+    let result = run_levels(execution_id, spec, ctx, checkpoint, started_at).await;
+    if let Some(space) = &scratch {
+        let completed = matches!(&result, Ok(record) if record.status == ExecutionStatus::Completed);
+        space.finish(execution_id, completed);
+    }
+    result
+}
+
+async fn run_levels(
+    execution_id: Uuid,
+    spec: &WorkflowSpec,
+    ctx: ExecutionContext,
+    checkpoint: Checkpoint,
+    started_at: chrono::DateTime<Utc>,
+) -> Result<ExecutionRecord, RunnerError> {
     let total_nodes = spec.nodes.len() as u32;
     let ctx = Arc::new(ctx);
+    let deadline = ctx.time_limits.execution.map(|limit| Deadline::new(limit, ctx.pause.clone()));
+    ctx.node_cancellation.track(spec, &ctx.workflows);
 
     if spec.nodes.is_empty() {
         return Ok(ExecutionRecord {
@@ -192,168 +625,425 @@ pub async fn run_dag_with_context(
             completed_nodes: 0,
             failed_nodes: 0,
             cache_hits: 0,
+            workspace_id: ctx.workspace.as_ref().map(|w| w.workspace_id),
+            manifest: Default::default(),
+            contract_violations: Vec::new(),
+        });
+    }
+
+    let mut record = ExecutionRecord {
+        execution_id,
+        workflow_id: spec.id,
+        started_at,
+        completed_at: None,
+        status: ExecutionStatus::Running,
+        total_nodes,
+        // Restored nodes count as completed with their earlier output
+        completed_nodes: checkpoint.outputs.len() as u32,
+        failed_nodes: 0,
+        cache_hits: 0,
+        workspace_id: ctx.workspace.as_ref().map(|w| w.workspace_id),
+        // Stored with the first record; later ones leave it in place
+        manifest: ExecutionManifest {
+            spec: Some(spec.clone()),
+            ..Default::default()
+        },
+        contract_violations: Vec::new(),
+    };
+    ctx.record_execution(&record);
+    ctx.journal(JournalEntry::ExecutionStarted {
+        execution_id,
+        workflow_id: spec.id,
+        total_nodes,
+        at: started_at,
+    });
+    let mut finish = JournalFinish {
+        ctx: &ctx,
+        execution_id,
+        status: ExecutionStatus::Failed,
+    };
+
+    for (node_id, output) in &checkpoint.outputs {
+        ctx.emit_status(NodeStatusEvent {
+            execution_id: execution_id.to_string(),
+            node_id: node_id.clone(),
+            status: "cache_hit".into(),
+            seq: 0,
+            output: Some(output.clone()),
+            error: None,
+            duration_ms: Some(0),
+            progress: None,
+            chunk: None,
         });
     }
 
+    let run = run_graph(execution_id, spec, &GraphScope::default(), &checkpoint, deadline.as_ref(), &ctx).await;
+    record.completed_at = Some(Utc::now());
+    record.completed_nodes = run.completed_nodes;
+    record.failed_nodes = run.failed_nodes;
+    record.cache_hits = run.cache_hits;
+    record.manifest = ctx.manifest();
+
+    if run.stopped {
+        record.status = ExecutionStatus::Cancelled;
+        ctx.record_execution(&record);
+        ctx.record_scheduler_metrics(execution_id);
+        finish.status = ExecutionStatus::Cancelled;
+        return Err(RunnerError::Cancelled);
+    }
+
+    let runaway = ctx.watchdog.tripped().map(str::to_string);
+    record.status = if run.failed_nodes > 0 || run.out_of_time || runaway.is_some() {
+        ExecutionStatus::Failed
+    } else {
+        // Nodes cancelled on their own leave the rest of the run to finish;
+        // only a run with no failed nodes is held to the contract
+        record.contract_violations = contract::check(&spec.meta.output_contract, &run.outputs);
+        if record.contract_violations.is_empty() {
+            ExecutionStatus::Completed
+        } else {
+            tracing::warn!(
+                "Execution {execution_id} broke {} output contract assertion(s)",
+                record.contract_violations.len()
+            );
+            ExecutionStatus::CompletedWithViolations
+        }
+    };
+    ctx.record_execution(&record);
+    ctx.record_scheduler_metrics(execution_id);
+    finish.status = record.status.clone();
+    drop(finish);
+
+    if let Some(reason) = runaway {
+        return Err(RunnerError::Watchdog(reason));
+    }
+    if let Some(limit) = ctx.time_limits.execution.filter(|_| run.out_of_time) {
+        return Err(RunnerError::PolicyViolation(format!(
+            "execution exceeded its time limit of {}s; {} of {total_nodes} node(s) completed",
+            limit.as_secs(),
+            run.completed_nodes
+        )));
+    }
+    Ok(record)
+}
+
+/// Where a graph runs in the execution. The top-level workflow's nodes
+/// keep their IDs; a referenced workflow's are reported as
+/// `{prefix}{inner_id}` and get what `input_mapping` routes to them from
+/// the referencing node's input.
+#[derive(Default)]
+struct GraphScope<'a> {
+    prefix: String,
+    input_mapping: &'a [PortMapping],
+    input_json: Option<&'a serde_json::Value>,
+}
+
+impl GraphScope<'_> {
+    fn is_top_level(&self) -> bool {
+        self.prefix.is_empty()
+    }
+
+    /// Inputs routed in from outside the graph, by inner node ID.
+    fn routed(&self) -> HashMap<String, serde_json::Value> {
+        let mut routed: HashMap<String, serde_json::Value> = HashMap::new();
+        let Some(input_json) = self.input_json else {
+            return routed;
+        };
+        for mapping in self.input_mapping {
+            if let Some(value) = input_json.get(&mapping.external_port) {
+                let inputs = routed.entry(mapping.node.clone()).or_insert_with(|| serde_json::json!({}));
+                inputs[&mapping.port] = value.clone();
+            }
+        }
+        routed
+    }
+}
+
+/// What ran in one graph of the execution.
+#[derive(Default)]
+struct GraphRun {
+    outputs: HashMap<String, serde_json::Value>,
+    completed_nodes: u32,
+    failed_nodes: u32,
+    cache_hits: u32,
+    /// The execution time limit stopped the run.
+    out_of_time: bool,
+    /// The whole execution was cancelled before the run finished.
+    stopped: bool,
+    /// The first failure no error edge handled, as `'node_id': reason`.
+    error: Option<String>,
+}
+
+impl GraphRun {
+    fn failed(&mut self, node_id: &str, reason: impl std::fmt::Display) {
+        self.failed_nodes += 1;
+        self.error.get_or_insert_with(|| format!("'{node_id}': {reason}"));
+    }
+}
+
+/// Run `spec` level by level; nodes in the same level run in parallel.
+/// Pauses, cancellation, error edges, concurrency slots and memoized
+/// chains apply at every depth the same way.
+async fn run_graph(
+    execution_id: Uuid,
+    spec: &WorkflowSpec,
+    scope: &GraphScope<'_>,
+    checkpoint: &Checkpoint,
+    deadline: Option<&Deadline>,
+    ctx: &Arc<ExecutionContext>,
+) -> GraphRun {
     // Build adjacency and in-degree maps
     let (adj, mut in_degree) = build_dag(spec);
 
     // Topological sort by levels (Kahn's algorithm)
     let levels = topo_levels(&adj, &mut in_degree, spec);
+    let index = spec.index();
+    let routed = scope.routed();
+
+    // Chains of pure nodes that ran before are skipped as a whole
+    let memo = match ctx.execution_cache {
+        Some(ref cache) if !ctx.pure_tools.is_empty() => {
+            let workspace_root = ctx.workspace.as_ref().map(|w| w.root_path.as_str());
+            MemoPlan::build(spec, &routed, &ctx.pure_tools, &ctx.env, workspace_root, cache)
+        }
+        _ => MemoPlan::default(),
+    };
+    let mut durations: HashMap<String, i64> = HashMap::new();
+
+    let mut run = GraphRun::default();
+    // Nodes that completed successfully (or hit the cache), for fan-in.
+    let mut succeeded: HashSet<String> = HashSet::new();
+    // Error payloads of failed nodes with an error edge, and the nodes
+    // routed around because of them.
+    let mut handled: HashMap<String, serde_json::Value> = HashMap::new();
+    let mut bypassed: HashSet<String> = HashSet::new();
+
+    for (node_id, output) in &checkpoint.outputs {
+        run.outputs.insert(node_id.clone(), output.clone());
+        succeeded.insert(node_id.clone());
+        run.completed_nodes += 1;
+    }
 
-    let mut completed_nodes = 0u32;
-    let mut failed_nodes = 0u32;
-    let mut cache_hits = 0u32;
-    let mut node_outputs: HashMap<String, serde_json::Value> = HashMap::new();
+    for (depth, level) in levels.iter().enumerate() {
+        let level: Vec<String> = level.iter().filter(|id| !checkpoint.contains(id)).cloned().collect();
+        let scoped: Vec<String> = level.iter().map(|id| format!("{}{id}", scope.prefix)).collect();
 
-    // Execute level by level — nodes in the same level run in parallel
-    for level in &levels {
         // Check for cancellation
-        if ctx.cancelled.load(std::sync::atomic::Ordering::Relaxed) {
-            return Err(RunnerError::Cancelled);
+        if !ctx.pause_point(execution_id, &scoped).await {
+            run.stopped = true;
+            return run;
+        }
+
+        // Nothing starts once the execution is out of time
+        if deadline.is_some_and(Deadline::passed) {
+            run.out_of_time = true;
+            break;
         }
 
         let mut handles = Vec::new();
+        let cancelled = ctx.node_cancellation.affected(spec, &scope.prefix);
 
-        for node_id in level {
-            let node = spec.nodes.iter().find(|n| n.id() == node_id);
+        for (node_id, nid) in level.iter().zip(&scoped) {
+            let node = index.node(node_id);
             let node_clone = node.cloned();
             let exec_id = execution_id;
-            let nid = node_id.clone();
+            let nid = nid.clone();
             let ctx_clone = ctx.clone();
-            let edges = spec.edges.clone();
+            let deadline = deadline.cloned();
+
+            // Cancelled nodes and whatever depends on them do not start
+            if cancelled.contains(node_id) {
+                let span = cancelled_node(exec_id, &nid, node, Utc::now(), ctx);
+                handles.push(tokio::spawn(async move { Ok((span, serde_json::Value::Null)) }));
+                continue;
+            }
+
+            // Handlers with no error to handle, and branches cut off by a
+            // handled failure or an untaken condition, are skipped
+            let untaken = |edge: &EdgeSpec| fan_in::untaken(edge, &run.outputs);
+            if error_route::bypassed(node_id, index.incoming(node_id), &handled, &bypassed, untaken) {
+                bypassed.insert(node_id.clone());
+                let span = stopped_node(exec_id, &nid, node, Utc::now(), ctx, ExecutionStatus::Skipped, None);
+                handles.push(tokio::spawn(async move { Ok((span, serde_json::Value::Null)) }));
+                continue;
+            }
 
             // Emit pending status
             ctx.emit_status(NodeStatusEvent {
                 execution_id: exec_id.to_string(),
                 node_id: nid.clone(),
                 status: "pending".into(),
+                seq: 0,
                 output: None,
                 error: None,
                 duration_ms: None,
+                progress: None,
+                chunk: None,
             });
 
-            // Gather inputs from upstream edges.
-            // When multiple edges target the same port, collect values into an array.
-            let mut inputs = serde_json::Map::new();
-            // Track which ports have multiple incoming edges
-            let mut port_edge_counts: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
-            for edge in &edges {
-                if edge.target_node == *node_id && edge.kind == EdgeKind::Data {
-                    *port_edge_counts.entry(edge.target_port.clone()).or_insert(0) += 1;
-                }
+            // Edge IDs are only unique within the top-level workflow
+            if scope.is_top_level() {
+                ctx.record_edge_values(exec_id, node_id, index.incoming(node_id), &run.outputs);
             }
-            for edge in &edges {
-                if edge.target_node == *node_id && edge.kind == EdgeKind::Data {
-                    tracing::debug!(
-                        "[Scheduler] Edge: {} ({}) -> {} ({})",
-                        edge.source_node, edge.source_port,
-                        edge.target_node, edge.target_port
-                    );
-                    if let Some(val) = node_outputs.get(&edge.source_node) {
-                        tracing::debug!(
-                            "[Scheduler] Source output keys: {:?}",
-                            val.as_object().map(|o| o.keys().collect::<Vec<_>>())
-                        );
-                        let new_val = if let Some(port_val) = val.get(&edge.source_port) {
-                            tracing::info!(
-                                "[Scheduler] Mapped {} -> {} (len: {} chars)",
-                                edge.source_port, edge.target_port,
-                                port_val.as_str().map(|s| s.len()).unwrap_or(0)
-                            );
-                            port_val.clone()
-                        } else {
-                            tracing::warn!(
-                                "[Scheduler] Port '{}' not found in output, using full output",
-                                edge.source_port
-                            );
-                            val.clone()
-                        };
-
-                        let multi = port_edge_counts.get(&edge.target_port).copied().unwrap_or(1) > 1;
-                        if multi {
-                            // Multiple edges → collect into array
-                            match inputs.get_mut(&edge.target_port) {
-                                Some(serde_json::Value::Array(arr)) => {
-                                    arr.push(new_val);
-                                }
-                                Some(existing) => {
-                                    let prev = existing.clone();
-                                    *existing = serde_json::Value::Array(vec![prev, new_val]);
-                                }
-                                None => {
-                                    inputs.insert(edge.target_port.clone(), serde_json::Value::Array(vec![new_val]));
-                                }
-                            }
-                        } else {
-                            // Single edge → insert directly (backward compatible)
-                            inputs.insert(edge.target_port.clone(), new_val);
-                        }
-                    } else {
-                        tracing::warn!(
-                            "[Scheduler] Source node '{}' output not found",
-                            edge.source_node
-                        );
-                    }
+
+            // Gather inputs from upstream edges per the node's fan-in mode.
+            let mode = node.map(NodeEntry::fan_in).unwrap_or_default();
+            let variadic = node.and_then(|node| ctx.variadic_inputs(node));
+            let mut inputs = match fan_in::gather_inputs(node_id, mode, index.incoming(node_id), &run.outputs, &succeeded, variadic) {
+                Ok(inputs) => inputs,
+                Err(err) => {
+                    ctx.emit_status(NodeStatusEvent {
+                        execution_id: exec_id.to_string(),
+                        node_id: nid.clone(),
+                        status: "failed".into(),
+                        seq: 0,
+                        output: None,
+                        error: Some(err.clone()),
+                        duration_ms: None,
+                        progress: None,
+                        chunk: None,
+                    });
+                    let span = create_error_span(exec_id, &nid, &err);
+                    handles.push(tokio::spawn(async move {
+                        Ok((span, serde_json::json!({ "error": err })))
+                    }));
+                    continue;
                 }
+            };
+            error_route::add_error_inputs(node_id, index.incoming(node_id), &handled, &mut inputs);
+            if let Some(serde_json::Value::Object(extra)) = routed.get(node_id) {
+                inputs.extend(extra.clone());
             }
-
             let input_json = serde_json::Value::Object(inputs);
 
+            if let (Some(output), Some(key)) = (memo.hit(node_id), memo.key(node_id)) {
+                let output = output.clone();
+                let span = memoized_node(exec_id, &nid, node, input_json, &output, key, ctx);
+                handles.push(tokio::spawn(async move { Ok((span, output)) }));
+                continue;
+            }
+
+            // Scheduler metrics describe the top-level graph
+            let top_level = scope.is_top_level();
+            if top_level {
+                ctx.telemetry.ready(&nid, depth as u32);
+            }
             handles.push(tokio::spawn(async move {
-                execute_node_entry(exec_id, &nid, node_clone.as_ref(), input_json, ctx_clone).await
+                let started_at = Utc::now();
+                let cancellation = ctx_clone.node_cancellation.clone();
+                // Dropping the node's future stops it mid-flight
+                let result = tokio::select! {
+                    result = execute_node_entry(exec_id, &nid, node_clone.as_ref(), input_json, ctx_clone.clone()) => result,
+                    () = cancellation.cancelled(&nid) => {
+                        let span = cancelled_node(exec_id, &nid, node_clone.as_ref(), started_at, &ctx_clone);
+                        Ok((span, serde_json::Value::Null))
+                    }
+                    () = time_limit::reached(deadline.as_ref()) => {
+                        let message = "stopped at the execution time limit".to_string();
+                        let span = stopped_node(
+                            exec_id,
+                            &nid,
+                            node_clone.as_ref(),
+                            started_at,
+                            &ctx_clone,
+                            ExecutionStatus::Failed,
+                            Some(message.clone()),
+                        );
+                        ctx_clone.record_span(&span);
+                        Err(RunnerError::PolicyViolation(message))
+                    }
+                };
+                if top_level {
+                    ctx_clone.telemetry.finished(&nid);
+                }
+                result
             }));
         }
 
         // Await all tasks in this level
         for (i, handle) in handles.into_iter().enumerate() {
+            let nid = &level[i];
+            let handles_errors = error_route::handles_errors(nid, index.outgoing(nid));
             match handle.await {
                 Ok(Ok((span, output))) => {
-                    let nid = &level[i];
-                    node_outputs.insert(nid.clone(), output);
+                    run.outputs.insert(nid.clone(), output);
+                    if let Some(ms) = span.duration_ms {
+                        durations.insert(nid.clone(), ms);
+                    }
                     // Record span to trace store
                     ctx.record_span(&span);
                     match span.status {
-                        ExecutionStatus::Completed => completed_nodes += 1,
+                        ExecutionStatus::Completed => {
+                            run.completed_nodes += 1;
+                            succeeded.insert(nid.clone());
+                        }
                         ExecutionStatus::CacheHit => {
-                            completed_nodes += 1;
-                            cache_hits += 1;
+                            run.completed_nodes += 1;
+                            run.cache_hits += 1;
+                            succeeded.insert(nid.clone());
+                        }
+                        // Its dependents are cancelled too; the rest goes on
+                        ExecutionStatus::Cancelled => {}
+                        ExecutionStatus::Skipped if bypassed.contains(nid) => {}
+                        ExecutionStatus::Failed if handles_errors => {
+                            tracing::info!("Node {nid} failed; routing its error to the error edge");
+                            handled.insert(nid.clone(), error_route::payload(&span));
                         }
-                        _ => failed_nodes += 1,
+                        status => run.failed(nid, span.error.unwrap_or_else(|| format!("{status:?}"))),
                     }
                 }
-                Ok(Err(_)) => failed_nodes += 1,
+                Ok(Err(RunnerError::PolicyViolation(message))) => {
+                    run.failed(nid, message);
+                    run.out_of_time = true;
+                }
+                Ok(Err(e)) if handles_errors => {
+                    tracing::info!("Node {nid} failed; routing its error to the error edge");
+                    let span = create_error_span(execution_id, &scoped[i], &e.to_string());
+                    handled.insert(nid.clone(), error_route::payload(&span));
+                }
+                Ok(Err(e)) => run.failed(nid, e),
                 Err(e) => {
                     tracing::error!("Task join error: {e}");
-                    failed_nodes += 1;
+                    run.failed(nid, e);
                 }
             }
         }
 
+        if run.out_of_time || ctx.watchdog.tripped().is_some() {
+            break;
+        }
+
         // Fail-fast: stop execution if any node in this level failed
-        if ctx.fail_fast && failed_nodes > 0 {
-            tracing::warn!("Fail-fast: stopping execution after {} failure(s)", failed_nodes);
+        if ctx.fail_fast && run.failed_nodes > 0 {
+            tracing::warn!("Fail-fast: stopping execution after {} failure(s)", run.failed_nodes);
             break;
         }
     }
 
-    let status = if failed_nodes > 0 {
-        ExecutionStatus::Failed
-    } else {
-        ExecutionStatus::Completed
-    };
+    if let Some(ref cache) = ctx.execution_cache {
+        memo.store(spec, cache, &run.outputs, &succeeded, &durations);
+    }
+    run
+}
 
-    Ok(ExecutionRecord {
-        execution_id,
-        workflow_id: spec.id,
-        started_at,
-        completed_at: Some(Utc::now()),
-        status,
-        total_nodes,
-        completed_nodes,
-        failed_nodes,
-        cache_hits,
-    })
+/// Journals `ExecutionFinished` when dropped, so however [`run_levels`]
+/// exits once started, the execution's journal file is closed and removed.
+/// Only a process that dies mid-run leaves its journal for recovery.
+struct JournalFinish<'a> {
+    ctx: &'a ExecutionContext,
+    execution_id: Uuid,
+    /// Status journaled; a run that exits without setting it failed.
+    status: ExecutionStatus,
+}
+
+impl Drop for JournalFinish<'_> {
+    fn drop(&mut self) {
+        self.ctx.journal(JournalEntry::ExecutionFinished {
+            execution_id: self.execution_id,
+            status: self.status.clone(),
+            at: Utc::now(),
+        });
+    }
 }
 
 /// Execute a node entry (handles all node types).
@@ -365,19 +1055,39 @@ async fn execute_node_entry(
     input_json: serde_json::Value,
     ctx: Arc<ExecutionContext>,
 ) -> Result<(NodeSpan, serde_json::Value), RunnerError> {
+    if let Err(reason) = ctx.watchdog.node_started(node_id) {
+        let span = stopped_node(execution_id, node_id, node, Utc::now(), &ctx, ExecutionStatus::Failed, Some(reason.clone()));
+        return Ok((span, serde_json::json!({ "error": reason })));
+    }
+
+    // Tool nodes stay pending until a slot of their resource class is free
+    let slot = match (node, &ctx.concurrency) {
+        (Some(NodeEntry::Primitive(n)), Some(limits)) => Some(limits.acquire(limits.class_of(n)).await),
+        _ => None,
+    };
+    ctx.telemetry.started(node_id);
+
     // Emit running status
     ctx.emit_status(NodeStatusEvent {
         execution_id: execution_id.to_string(),
         node_id: node_id.to_string(),
         status: "running".into(),
+        seq: 0,
         output: None,
         error: None,
         duration_ms: None,
+        progress: None,
+        chunk: None,
+    });
+    ctx.journal(JournalEntry::NodeStarted {
+        execution_id,
+        node_id: node_id.to_string(),
+        at: Utc::now(),
     });
 
-    let result = match node {
+    let mut result = match node {
         Some(NodeEntry::Primitive(n)) => {
-            execute_primitive_node(execution_id, node_id, n, input_json, ctx.clone()).await
+            execute_primitive_node(execution_id, node_id, n, input_json, ctx.clone(), slot).await
         }
         Some(NodeEntry::Composite(n)) => {
             execute_composite_node(execution_id, node_id, n, input_json, ctx.clone()).await
@@ -388,6 +1098,12 @@ async fn execute_node_entry(
         Some(NodeEntry::Loop(n)) => {
             execute_loop_node(execution_id, node_id, n, input_json, ctx.clone()).await
         }
+        Some(NodeEntry::MapReduce(n)) => {
+            execute_map_reduce_node(execution_id, node_id, n, input_json, ctx.clone()).await
+        }
+        Some(NodeEntry::WorkflowRef(n)) => {
+            execute_workflow_ref_node(execution_id, node_id, n, input_json, ctx.clone()).await
+        }
         None => {
             let err = "Node not found".to_string();
             let span = create_error_span(execution_id, node_id, &err);
@@ -395,14 +1111,37 @@ async fn execute_node_entry(
                 execution_id: execution_id.to_string(),
                 node_id: node_id.to_string(),
                 status: "failed".into(),
+                seq: 0,
                 output: None,
                 error: Some(err.clone()),
                 duration_ms: None,
+                progress: None,
+                chunk: None,
             });
             Ok((span, serde_json::json!({ "error": err })))
         }
     };
 
+    // Structured nodes have no single config, so a resume compares them whole
+    if let (Some(node), Ok((span, _))) = (node, &mut result) {
+        if !matches!(node, NodeEntry::Primitive(_)) {
+            let fingerprint = crate::checkpoint::fingerprint(node, &ctx.workflows, &ctx.env);
+            span.environment
+                .extra
+                .insert(crate::checkpoint::FINGERPRINT_KEY.into(), fingerprint.into());
+        }
+    }
+
+    ctx.journal(JournalEntry::NodeFinished {
+        execution_id,
+        node_id: node_id.to_string(),
+        status: match result {
+            Ok((ref span, _)) => span.status.clone(),
+            Err(_) => ExecutionStatus::Failed,
+        },
+        at: Utc::now(),
+    });
+
     // Emit completion status
     if let Ok((ref span, ref output)) = result {
         let status_str = match span.status {
@@ -415,26 +1154,38 @@ async fn execute_node_entry(
             execution_id: execution_id.to_string(),
             node_id: node_id.to_string(),
             status: status_str.into(),
+            seq: 0,
             output: Some(output.clone()),
             error: span.error.clone(),
             duration_ms: span.duration_ms,
+            progress: None,
+            chunk: None,
         });
     }
 
     result
 }
 
-/// Execute a primitive node with caching and retry support.
+/// Execute a primitive node with caching and retry support. `slot` is the
+/// node's concurrency slot, given up while it waits to retry.
 async fn execute_primitive_node(
     execution_id: Uuid,
     node_id: &str,
     node: &NodeSpec,
     input_json: serde_json::Value,
     ctx: Arc<ExecutionContext>,
+    mut slot: Option<Slot>,
 ) -> Result<(NodeSpan, serde_json::Value), RunnerError> {
     let started_at = Utc::now();
     let tool_ref = &node.tool_ref;
-    let config_json = serde_json::Value::Object(node.config.clone());
+    let (config_json, missing_env) =
+        resolve_env_refs(&serde_json::Value::Object(node.config.clone()), &ctx.env);
+    if !missing_env.is_empty() {
+        tracing::warn!(
+            "Node '{node_id}' references undefined environment variables: {}",
+            missing_env.join(", ")
+        );
+    }
 
     // Skip disabled nodes
     if node.disabled {
@@ -457,12 +1208,27 @@ async fn execute_primitive_node(
         return Ok((span, serde_json::json!({})));
     }
 
-    // Check cache if enabled
+    // Check cache if enabled. Relative paths mean different files in
+    // different workspaces, so the root is part of the key, and so are the
+    // sizes and modification times of the files the node names.
     let cache_policy = node.cache.clone().unwrap_or_default();
-    if cache_policy.enabled {
+    let workspace_root = ctx.workspace.as_ref().map(|w| w.root_path.as_str());
+    let data_version = if cache_policy.enabled && ctx.execution_cache.is_some() {
+        memo::data_version(&input_json, &config_json, workspace_root)
+    } else {
+        None
+    };
+    if cache_policy.enabled {
         if let Some(ref exec_cache) = ctx.execution_cache {
-            let cache_key = compute_cache_key(tool_ref, &input_json, &config_json, None);
-            if let Ok(Some(cached_output)) = exec_cache.lookup(&cache_key) {
+            let cache_key = compute_cache_key(tool_ref, &input_json, &config_json, data_version.as_deref());
+            let cached = exec_cache.lookup(&cache_key).ok().flatten();
+            ctx.journal(JournalEntry::CacheKey {
+                execution_id,
+                node_id: node_id.into(),
+                cache_key,
+                hit: cached.is_some(),
+            });
+            if let Some(cached_output) = cached {
                 let span = NodeSpan {
                     span_id: Uuid::new_v4(),
                     execution_id,
@@ -484,31 +1250,97 @@ async fn execute_primitive_node(
         }
     }
 
+    // Lock declared resources for the duration of the node
+    let claims = ctx
+        .resource_locks
+        .as_ref()
+        .map(|locks| locks.claims(tool_ref, &node.config, &input_json))
+        .unwrap_or_default();
+    let _resource_guard = match ctx.resource_locks {
+        Some(ref locks) => {
+            match locks.acquire(execution_id, &claims).await {
+                Ok(guard) => Some(guard),
+                Err(e) => {
+                    let message = e.to_string();
+                    let span = NodeSpan {
+                        span_id: Uuid::new_v4(),
+                        execution_id,
+                        node_id: node_id.into(),
+                        tool_ref: tool_ref.clone(),
+                        input_json,
+                        output_json: Some(serde_json::json!({ "error": &message })),
+                        config_json,
+                        started_at,
+                        completed_at: Some(Utc::now()),
+                        duration_ms: Some(0),
+                        status: ExecutionStatus::Failed,
+                        error: Some(message.clone()),
+                        cache_hit: false,
+                        environment: default_environment(),
+                    };
+                    return Ok((span, serde_json::json!({ "error": message })));
+                }
+            }
+        }
+        None => None,
+    };
+
     // Execute with retry
-    let retry_policy = node.retry.clone().unwrap_or_default();
+    let adaptive = ctx.adaptive_retry.clone().unwrap_or_default();
+    let retry_policy = adaptive.policy(tool_ref, node.retry.as_ref());
     let mut attempt = 0u32;
     let mut last_error: String;
+    // The node limit covers all attempts, retry delays included
+    let node_deadline = ctx.time_limits.node.map(|limit| tokio::time::Instant::now() + limit);
 
     loop {
-        let (output, status, error, duration_ms) = if tool_ref == "agent-task" {
-            execute_agent_task(node_id, &input_json, &config_json, &ctx).await
-        } else if tool_ref.starts_with("mcp://") {
-            execute_mcp_tool(tool_ref, &input_json, &config_json, ctx.mcp_cache.clone()).await
-        } else {
-            execute_native_tool(tool_ref, &input_json, &config_json, ctx.llm_provider.as_deref()).await
+        let run = async {
+            if tool_ref == "agent-task" {
+                execute_agent_task(node_id, &input_json, &config_json, &ctx).await
+            } else if tool_ref.starts_with("mcp://") {
+                execute_mcp_tool(tool_ref, &input_json, &config_json, ctx.mcp_cache.clone()).await
+            } else {
+                let heartbeat = Heartbeat::new();
+                let progress = ctx.progress_reporter(execution_id, node_id, heartbeat.clone());
+                let stream = ctx.output_stream(execution_id, node_id, heartbeat.clone());
+                let runtime = ctx.runtime_for(tool_ref);
+                let work = execute_tool(runtime, tool_ref, &input_json, &config_json, &ctx, progress, stream);
+                watch_node(execution_id, node_id, work, &heartbeat, &ctx).await
+            }
+        };
+        let (output, status, error, duration_ms) = match node_deadline {
+            Some(deadline) => match tokio::time::timeout_at(deadline, run).await {
+                Ok(result) => result,
+                Err(_) => {
+                    let limit = ctx.time_limits.node.unwrap_or_default();
+                    last_error = RunnerError::PolicyViolation(format!(
+                        "node exceeded its time limit of {}s",
+                        limit.as_secs()
+                    ))
+                    .to_string();
+                    tracing::warn!("Node {node_id} stopped: {last_error}");
+                    break;
+                }
+            },
+            None => run.await,
         };
 
         if status == ExecutionStatus::Completed {
-            // Store in cache if enabled
+            if let Ok(mut manifest) = ctx.manifest.lock() {
+                manifest::record_node(&mut manifest, node_id, tool_ref, &claims, ctx.llm_provider.as_deref());
+            }
+
+            // Store in cache if enabled and worth keeping
             if cache_policy.enabled {
                 if let Some(ref exec_cache) = ctx.execution_cache {
-                    let cache_key = compute_cache_key(tool_ref, &input_json, &config_json, None);
+                    let cache_key = compute_cache_key(tool_ref, &input_json, &config_json, data_version.as_deref());
                     let ttl = if cache_policy.ttl_secs > 0 {
                         Some(cache_policy.ttl_secs)
                     } else {
                         None
                     };
-                    let _ = exec_cache.store(&cache_key, &output, ttl);
+                    let compute = std::time::Duration::from_millis(duration_ms.max(0) as u64);
+                    let _ = exec_cache.offer(&cache_key, &output, ttl, compute);
                 }
             }
 
@@ -534,16 +1366,26 @@ async fn execute_primitive_node(
         // Handle failure with retry
         last_error = error.unwrap_or_else(|| "Unknown error".into());
 
-        if should_retry(&retry_policy, attempt) {
-            let delay = compute_delay(&retry_policy, attempt);
+        let kind = adaptive.classify(tool_ref, &last_error);
+        if adaptive.should_retry(tool_ref, &retry_policy, attempt, kind) {
+            let delay = delay_for(&retry_policy, attempt, kind);
             tracing::warn!(
-                "Node {} failed (attempt {}), retrying in {:?}: {}",
+                "Node {} failed (attempt {}, {:?}), retrying in {:?}: {}",
                 node_id,
                 attempt + 1,
+                kind,
                 delay,
                 last_error
             );
-            tokio::time::sleep(delay).await;
+            // Other nodes may use the slot until the retry
+            let waiting = slot.take().is_some();
+            match node_deadline {
+                Some(deadline) => tokio::time::sleep_until(deadline.min(tokio::time::Instant::now() + delay)).await,
+                None => tokio::time::sleep(delay).await,
+            }
+            if let (true, Some(limits)) = (waiting, &ctx.concurrency) {
+                slot = Some(limits.acquire(limits.class_of(node)).await);
+            }
             attempt += 1;
         } else {
             break;
@@ -571,7 +1413,7 @@ async fn execute_primitive_node(
 }
 
 /// Execute a composite node (sub-graph).
-/// Executes subgraph nodes sequentially.
+/// Executes subgraph nodes in dependency order, like a referenced workflow.
 #[async_recursion::async_recursion]
 async fn execute_composite_node(
     execution_id: Uuid,
@@ -581,49 +1423,133 @@ async fn execute_composite_node(
     ctx: Arc<ExecutionContext>,
 ) -> Result<(NodeSpan, serde_json::Value), RunnerError> {
     let started_at = Utc::now();
-    let mut outputs = serde_json::Map::new();
-    let mut all_completed = true;
-
-    // Execute subgraph nodes sequentially (simplified - no DAG scheduling)
-    for subnode in &node.subgraph.nodes {
-        let node_input = input_json.clone();
-        let result = execute_node_entry(
-            execution_id,
-            subnode.id(),
-            Some(subnode),
-            node_input,
-            ctx.clone(),
-        )
-        .await;
 
-        match result {
-            Ok((span, output)) => {
-                outputs.insert(subnode.id().to_string(), output);
-                if span.status != ExecutionStatus::Completed && span.status != ExecutionStatus::CacheHit {
-                    all_completed = false;
-                }
-            }
-            Err(_) => {
-                all_completed = false;
-            }
+    // Apply composite-level config to the internal nodes before running them.
+    let subgraph = match node.resolved_subgraph() {
+        Ok(subgraph) => subgraph,
+        Err(err) => {
+            ctx.emit_status(NodeStatusEvent {
+                execution_id: execution_id.to_string(),
+                node_id: node_id.to_string(),
+                status: "failed".into(),
+                seq: 0,
+                output: None,
+                error: Some(err.clone()),
+                duration_ms: None,
+                progress: None,
+                chunk: None,
+            });
+            return Ok((
+                create_error_span(execution_id, node_id, &err),
+                serde_json::json!({ "error": err }),
+            ));
         }
+    };
+
+    let graph = WorkflowSpec {
+        nodes: subgraph.nodes,
+        edges: subgraph.edges,
+        ..Default::default()
+    };
+    // Without input mappings, nodes with nothing upstream get the whole input
+    let entry = Some(&input_json).filter(|_| node.input_mapping.is_empty());
+    let InnerRun { outputs, error } =
+        run_inner_graph(execution_id, &graph, &node.input_mapping, &input_json, entry, ctx.clone()).await;
+
+    let output = mapped_output(outputs, &node.output_mapping);
+    let (status, error) = match error {
+        None => (ExecutionStatus::Completed, None),
+        Some(err) => (ExecutionStatus::Failed, Some(format!("Subgraph node {err}"))),
+    };
+
+    let span = NodeSpan {
+        span_id: Uuid::new_v4(),
+        execution_id,
+        node_id: node_id.into(),
+        tool_ref: "composite".into(),
+        input_json,
+        output_json: Some(output.clone()),
+        config_json: serde_json::Value::Object(node.config.clone()),
+        started_at,
+        completed_at: Some(Utc::now()),
+        duration_ms: Some((Utc::now() - started_at).num_milliseconds()),
+        status,
+        error,
+        cache_hit: false,
+        environment: default_environment(),
+    };
+
+    Ok((span, output))
+}
+
+/// Execute a workflow reference node: the referenced workflow's DAG, run
+/// like the top-level one (see [`run_graph`]), with inner nodes reported as
+/// `{node_id}/{inner_id}`.
+#[async_recursion::async_recursion]
+async fn execute_workflow_ref_node(
+    execution_id: Uuid,
+    node_id: &str,
+    node: &WorkflowRefSpec,
+    input_json: serde_json::Value,
+    ctx: Arc<ExecutionContext>,
+) -> Result<(NodeSpan, serde_json::Value), RunnerError> {
+    let started_at = Utc::now();
+    let Some(spec) = ctx.workflows.get(&node.workflow_id).cloned() else {
+        let err = format!("referenced workflow {} was not found", node.workflow_id);
+        ctx.emit_status(NodeStatusEvent {
+            execution_id: execution_id.to_string(),
+            node_id: node_id.to_string(),
+            status: "failed".into(),
+            seq: 0,
+            output: None,
+            error: Some(err.clone()),
+            duration_ms: None,
+            progress: None,
+            chunk: None,
+        });
+        return Ok((
+            create_error_span(execution_id, node_id, &err),
+            serde_json::json!({ "error": err }),
+        ));
+    };
+    if let Err(reason) = ctx
+        .watchdog
+        .enter_workflow(node_id, &ctx.workflow_chain, spec.id, &spec.meta.name)
+    {
+        let span = stopped_node(execution_id, node_id, None, started_at, &ctx, ExecutionStatus::Failed, Some(reason.clone()));
+        return Ok((span, serde_json::json!({ "error": reason })));
     }
 
-    let output = serde_json::Value::Object(outputs);
-    let (status, error) = if all_completed {
-        (ExecutionStatus::Completed, None)
+    let mut inner_ctx = (*ctx).clone();
+    inner_ctx.workflow_chain.push((spec.id, spec.meta.name.clone()));
+    let scope = GraphScope {
+        prefix: format!("{node_id}/"),
+        input_mapping: &node.input_mapping,
+        input_json: Some(&input_json),
+    };
+    // The execution's time limit stops this node as a whole
+    let run = run_graph(execution_id, &spec, &scope, &Checkpoint::default(), None, &Arc::new(inner_ctx)).await;
+
+    let (status, error) = if run.stopped {
+        (ExecutionStatus::Cancelled, Some("execution was cancelled".to_string()))
+    } else if let Some(err) = &run.error {
+        (
+            ExecutionStatus::Failed,
+            Some(format!("workflow '{}' failed at {err}", spec.meta.name)),
+        )
     } else {
-        (ExecutionStatus::Failed, Some("Some subgraph nodes failed".into()))
+        (ExecutionStatus::Completed, None)
     };
+    let output = mapped_output(run.outputs, &node.output_mapping);
 
     let span = NodeSpan {
         span_id: Uuid::new_v4(),
         execution_id,
         node_id: node_id.into(),
-        tool_ref: "composite".into(),
+        tool_ref: "workflow_ref".into(),
         input_json,
         output_json: Some(output.clone()),
-        config_json: serde_json::json!({}),
+        config_json: serde_json::json!({ "workflow_id": node.workflow_id }),
         started_at,
         completed_at: Some(Utc::now()),
         duration_ms: Some((Utc::now() - started_at).num_milliseconds()),
@@ -636,6 +1562,133 @@ async fn execute_composite_node(
     Ok((span, output))
 }
 
+/// What ran inside a composite.
+struct InnerRun {
+    outputs: HashMap<String, serde_json::Value>,
+    /// The first failure no error edge handled, as `'inner_id': reason`.
+    error: Option<String>,
+}
+
+/// Run the nodes of a composite level by level.
+/// Inner nodes get their upstream outputs plus what `input_mapping` routes
+/// to them from `input_json`; `entry`, when set, is the whole input of
+/// inner nodes with neither. Failures with error edges go to their handlers
+/// as at the top level.
+async fn run_inner_graph(
+    execution_id: Uuid,
+    graph: &WorkflowSpec,
+    input_mapping: &[PortMapping],
+    input_json: &serde_json::Value,
+    entry: Option<&serde_json::Value>,
+    ctx: Arc<ExecutionContext>,
+) -> InnerRun {
+    let index = graph.index();
+    let (adj, mut in_degree) = build_dag(graph);
+    let mut outputs: HashMap<String, serde_json::Value> = HashMap::new();
+    let mut succeeded: HashSet<String> = HashSet::new();
+    let mut handled: HashMap<String, serde_json::Value> = HashMap::new();
+    let mut bypassed: HashSet<String> = HashSet::new();
+    let mut error = None;
+
+    'levels: for level in topo_levels(&adj, &mut in_degree, graph) {
+        if !ctx.pause_point(execution_id, &level).await {
+            error.get_or_insert("stopped: the execution was cancelled".to_string());
+            break;
+        }
+        let cancelled = ctx.node_cancellation.affected(graph, "");
+        for inner_id in &level {
+            let inner = index.node(inner_id);
+            if cancelled.contains(inner_id) {
+                cancelled_node(execution_id, inner_id, inner, Utc::now(), &ctx);
+                continue;
+            }
+            let untaken = |edge: &EdgeSpec| fan_in::untaken(edge, &outputs);
+            if error_route::bypassed(inner_id, index.incoming(inner_id), &handled, &bypassed, untaken) {
+                bypassed.insert(inner_id.clone());
+                stopped_node(execution_id, inner_id, inner, Utc::now(), &ctx, ExecutionStatus::Skipped, None);
+                continue;
+            }
+            let mode = inner.map(NodeEntry::fan_in).unwrap_or_default();
+            let variadic = inner.and_then(|inner| ctx.variadic_inputs(inner));
+            let mut inputs = match fan_in::gather_inputs(inner_id, mode, index.incoming(inner_id), &outputs, &succeeded, variadic) {
+                Ok(inputs) => inputs,
+                Err(err) => {
+                    error.get_or_insert(format!("'{inner_id}': {err}"));
+                    if ctx.fail_fast {
+                        break 'levels;
+                    }
+                    continue;
+                }
+            };
+            error_route::add_error_inputs(inner_id, index.incoming(inner_id), &handled, &mut inputs);
+            let mut mapped = false;
+            for mapping in input_mapping.iter().filter(|m| &m.node == inner_id) {
+                mapped = true;
+                if let Some(value) = input_json.get(&mapping.external_port) {
+                    inputs.insert(mapping.port.clone(), value.clone());
+                }
+            }
+            let input = match entry {
+                Some(entry) if !mapped && index.incoming(inner_id).next().is_none() => entry.clone(),
+                _ => serde_json::Value::Object(inputs),
+            };
+
+            let Some(result) = execute_unless_cancelled(execution_id, inner_id, inner, input, &ctx).await else {
+                continue;
+            };
+            let handles_errors = error_route::handles_errors(inner_id, index.outgoing(inner_id));
+            match result {
+                Ok((span, output))
+                    if matches!(span.status, ExecutionStatus::Completed | ExecutionStatus::CacheHit) =>
+                {
+                    succeeded.insert(inner_id.clone());
+                    outputs.insert(inner_id.clone(), output);
+                }
+                Ok((span, output)) if handles_errors => {
+                    handled.insert(inner_id.clone(), error_route::payload(&span));
+                    outputs.insert(inner_id.clone(), output);
+                }
+                Ok((span, output)) => {
+                    let reason = span.error.unwrap_or_else(|| format!("{:?}", span.status));
+                    error.get_or_insert(format!("'{inner_id}': {reason}"));
+                    outputs.insert(inner_id.clone(), output);
+                }
+                Err(e) if handles_errors => {
+                    let span = create_error_span(execution_id, inner_id, &e.to_string());
+                    handled.insert(inner_id.clone(), error_route::payload(&span));
+                }
+                Err(e) => {
+                    error.get_or_insert(format!("'{inner_id}': {e}"));
+                }
+            }
+            if (error.is_some() && ctx.fail_fast) || ctx.watchdog.tripped().is_some() {
+                break 'levels;
+            }
+        }
+    }
+
+    InnerRun { outputs, error }
+}
+
+/// The outputs of a composite or referenced workflow: the inner ports
+/// `output_mapping` picks, or every inner output keyed by node ID. When
+/// several mappings feed one external port, the last inner node that
+/// produced the port wins, so a fallback can stand in for its primary.
+fn mapped_output(outputs: HashMap<String, serde_json::Value>, output_mapping: &[PortMapping]) -> serde_json::Value {
+    if output_mapping.is_empty() {
+        return serde_json::Value::Object(outputs.into_iter().collect());
+    }
+    serde_json::Value::Object(
+        output_mapping
+            .iter()
+            .filter_map(|m| {
+                let value = outputs.get(&m.node)?.get(&m.port)?;
+                Some((m.external_port.clone(), value.clone()))
+            })
+            .collect(),
+    )
+}
+
 /// Execute a conditional node (if/switch).
 /// Executes branch nodes sequentially.
 #[async_recursion::async_recursion]
@@ -649,7 +1702,8 @@ async fn execute_conditional_node(
     let started_at = Utc::now();
 
     // Evaluate condition expression
-    let condition_result = evaluate_condition(&node.condition_expr, &input_json);
+    let condition = evaluate_condition(&node.condition_expr, &input_json);
+    let condition_result = condition.as_ref().ok().cloned().flatten();
 
     // Find matching branch
     let branch_subgraph: Option<&SubgraphSpec> = node
@@ -657,15 +1711,18 @@ async fn execute_conditional_node(
         .iter()
         .find(|b| {
             if let Some(result) = &condition_result {
-                &b.value == result
+                hb_core::expr::equals(&b.value, result)
             } else {
                 false
             }
         })
         .map(|b| &b.body)
-        .or(node.default_branch.as_ref());
+        .or(node.default_branch.as_ref())
+        .filter(|_| condition.is_ok());
 
-    let (output, status, error) = if let Some(subgraph) = branch_subgraph {
+    let (output, status, error) = if let Err(e) = condition {
+        (serde_json::json!({ "error": e }), ExecutionStatus::Failed, Some(e))
+    } else if let Some(subgraph) = branch_subgraph {
         // Execute branch nodes sequentially
         let mut outputs = serde_json::Map::new();
         let mut all_completed = true;
@@ -722,7 +1779,8 @@ async fn execute_conditional_node(
 }
 
 /// Execute a loop node (for_each, while, repeat).
-/// Executes body nodes sequentially.
+/// Executes body nodes sequentially; each body node receives the reserved
+/// iteration context ports (see [`hb_core::graph::IterationContext`]).
 #[async_recursion::async_recursion]
 async fn execute_loop_node(
     execution_id: Uuid,
@@ -732,15 +1790,21 @@ async fn execute_loop_node(
     ctx: Arc<ExecutionContext>,
 ) -> Result<(NodeSpan, serde_json::Value), RunnerError> {
     let started_at = Utc::now();
+    let clock = std::time::Instant::now();
     let mut results: Vec<serde_json::Value> = Vec::new();
     let mut iteration = 0u32;
     let mut all_completed = true;
+    // First expression that failed to evaluate
+    let mut expr_error: Option<String> = None;
 
     match node.kind {
         hb_core::graph::LoopKind::ForEach => {
             // Get items from input using items_expr
             let items = if let Some(ref expr) = node.items_expr {
-                evaluate_jmespath(expr, &input_json).unwrap_or(serde_json::json!([]))
+                evaluate_field("items_expr", expr, &input_json).unwrap_or_else(|e| {
+                    expr_error = Some(e);
+                    serde_json::json!([])
+                })
             } else {
                 input_json
                     .get("items")
@@ -749,12 +1813,16 @@ async fn execute_loop_node(
             };
 
             if let Some(arr) = items.as_array() {
-                for item in arr.iter().take(node.max_iterations as usize) {
-                    let iter_input = serde_json::json!({ "item": item, "index": iteration });
+                let total = arr.len().min(node.max_iterations as usize) as u32;
+                for item in arr.iter().take(total as usize) {
+                    if ctx.watchdog.tripped().is_some() {
+                        break;
+                    }
+                    let iter_ctx = iteration::context(item.clone(), iteration, Some(total));
                     let output = execute_subgraph_nodes(
                         execution_id,
                         &node.body,
-                        iter_input,
+                        iteration::iteration_input(&iter_ctx),
                         ctx.clone(),
                         &mut all_completed,
                     )
@@ -766,19 +1834,26 @@ async fn execute_loop_node(
         }
         hb_core::graph::LoopKind::While => {
             while iteration < node.max_iterations {
+                if ctx.watchdog.tripped().is_some() {
+                    break;
+                }
                 // Check condition
                 if let Some(ref expr) = node.condition_expr {
-                    let condition = evaluate_condition(expr, &input_json);
-                    if condition != Some(serde_json::json!(true)) {
-                        break;
+                    match evaluate_field("condition_expr", expr, &input_json) {
+                        Ok(condition) if hb_core::expr::truthy(&condition) => {}
+                        Ok(_) => break,
+                        Err(e) => {
+                            expr_error = Some(e);
+                            break;
+                        }
                     }
                 }
 
-                let iter_input = serde_json::json!({ "iteration": iteration });
+                let iter_ctx = iteration::context(serde_json::Value::Null, iteration, None);
                 let output = execute_subgraph_nodes(
                     execution_id,
                     &node.body,
-                    iter_input,
+                    iteration::iteration_input(&iter_ctx),
                     ctx.clone(),
                     &mut all_completed,
                 )
@@ -789,26 +1864,59 @@ async fn execute_loop_node(
         }
         hb_core::graph::LoopKind::Repeat => {
             for i in 0..node.max_iterations {
-                let iter_input = serde_json::json!({ "iteration": i });
+                if ctx.watchdog.tripped().is_some() {
+                    break;
+                }
+                let iter_ctx =
+                    iteration::context(serde_json::Value::Null, i, Some(node.max_iterations));
                 let output = execute_subgraph_nodes(
                     execution_id,
                     &node.body,
-                    iter_input,
+                    iteration::iteration_input(&iter_ctx),
                     ctx.clone(),
                     &mut all_completed,
                 )
                 .await;
                 results.push(output);
+                iteration += 1;
             }
         }
     }
 
+    if matches!(node.kind, hb_core::graph::LoopKind::While) && iteration == node.max_iterations {
+        tracing::warn!("Loop {node_id} stopped at max_iterations ({iteration}) with its condition still true");
+    }
+    ctx.telemetry
+        .loop_finished(node_id, iteration, node.max_iterations, clock.elapsed());
+
+    let collected: Vec<serde_json::Value> = match &node.collect_expr {
+        Some(expr) => results
+            .iter()
+            .map(|r| {
+                evaluate_field("collect_expr", expr, r).unwrap_or_else(|e| {
+                    expr_error.get_or_insert(e);
+                    serde_json::Value::Null
+                })
+            })
+            .collect(),
+        None => results.clone(),
+    };
+    let (aggregate, aggregate_error) = match iteration::aggregate(&node.aggregation, &collected) {
+        Ok(value) => (value, None),
+        Err(e) => (serde_json::Value::Null, Some(e)),
+    };
+
     let output = serde_json::json!({
         "results": results,
-        "iterations": iteration
+        "iterations": iteration,
+        "aggregate": aggregate
     });
 
-    let (status, error) = if all_completed {
+    let (status, error) = if let Some(e) = expr_error.or(aggregate_error) {
+        (ExecutionStatus::Failed, Some(e))
+    } else if let Some(reason) = ctx.watchdog.tripped() {
+        (ExecutionStatus::Failed, Some(reason.to_string()))
+    } else if all_completed {
         (ExecutionStatus::Completed, None)
     } else {
         (ExecutionStatus::Failed, Some("Some loop iterations failed".into()))
@@ -821,7 +1929,132 @@ async fn execute_loop_node(
         tool_ref: "loop".into(),
         input_json,
         output_json: Some(output.clone()),
-        config_json: serde_json::json!({ "max_iterations": node.max_iterations }),
+        config_json: serde_json::json!({
+            "max_iterations": node.max_iterations,
+            "collect_expr": node.collect_expr,
+            "aggregation": node.aggregation,
+        }),
+        started_at,
+        completed_at: Some(Utc::now()),
+        duration_ms: Some((Utc::now() - started_at).num_milliseconds()),
+        status,
+        error,
+        cache_hit: false,
+        environment: default_environment(),
+    };
+
+    Ok((span, output))
+}
+
+/// Execute a map-reduce node: run `map` over each shard of the input with at
+/// most `max_concurrency` shards in flight, then `reduce` over the results.
+#[async_recursion::async_recursion]
+async fn execute_map_reduce_node(
+    execution_id: Uuid,
+    node_id: &str,
+    node: &MapReduceSpec,
+    input_json: serde_json::Value,
+    ctx: Arc<ExecutionContext>,
+) -> Result<(NodeSpan, serde_json::Value), RunnerError> {
+    let started_at = Utc::now();
+
+    // Without an items_expr, no `items` input means nothing to map
+    let items = match node.items_expr {
+        Some(ref expr) => evaluate_field("items_expr", expr, &input_json),
+        None => Ok(input_json.get("items").cloned().unwrap_or(serde_json::json!([]))),
+    };
+    let shards = items.and_then(|items| map_reduce::shard_items(&items, node.shard_size).map_err(|e| e.to_string()));
+    let mut expr_error: Option<String> = shards.as_ref().err().cloned();
+    let shards = shards.unwrap_or_default();
+    let total = shards.len() as u32;
+
+    let map = Arc::new(node.map.clone());
+    let max_concurrency = node.max_concurrency.max(1) as usize;
+    let mut outputs: Vec<serde_json::Value> = vec![serde_json::Value::Null; shards.len()];
+    let mut all_completed = true;
+    let mut in_flight = tokio::task::JoinSet::new();
+    let mut pending = shards.into_iter().enumerate();
+
+    loop {
+        while in_flight.len() < max_concurrency {
+            let Some((index, shard)) = pending.next() else {
+                break;
+            };
+            let iter_ctx = iteration::context(shard, index as u32, Some(total));
+            let input = iteration::iteration_input(&iter_ctx);
+            let (map, ctx) = (map.clone(), ctx.clone());
+            in_flight.spawn(async move {
+                let mut completed = true;
+                let output = execute_subgraph_nodes(execution_id, &map, input, ctx, &mut completed).await;
+                (index, output, completed)
+            });
+        }
+        match in_flight.join_next().await {
+            Some(Ok((index, output, completed))) => {
+                outputs[index] = output;
+                all_completed &= completed;
+            }
+            Some(Err(e)) => {
+                tracing::error!("Map shard task panicked in '{node_id}': {e}");
+                all_completed = false;
+            }
+            None => break,
+        }
+    }
+
+    let results: Vec<serde_json::Value> = match &node.collect_expr {
+        Some(expr) => outputs
+            .iter()
+            .map(|o| {
+                evaluate_field("collect_expr", expr, o).unwrap_or_else(|e| {
+                    expr_error.get_or_insert(e);
+                    serde_json::Value::Null
+                })
+            })
+            .collect(),
+        None => outputs,
+    };
+
+    let reduced = match &node.reduce {
+        Some(reduce) if expr_error.is_none() => {
+            execute_subgraph_nodes(
+                execution_id,
+                reduce,
+                serde_json::json!({ "results": results }),
+                ctx.clone(),
+                &mut all_completed,
+            )
+            .await
+        }
+        _ => serde_json::Value::Null,
+    };
+
+    let output = serde_json::json!({
+        "results": results,
+        "shards": total,
+        "reduced": reduced
+    });
+
+    let (status, error) = if let Some(e) = expr_error {
+        (ExecutionStatus::Failed, Some(e))
+    } else if all_completed {
+        (ExecutionStatus::Completed, None)
+    } else {
+        (ExecutionStatus::Failed, Some("Some map-reduce shards failed".into()))
+    };
+
+    let span = NodeSpan {
+        span_id: Uuid::new_v4(),
+        execution_id,
+        node_id: node_id.into(),
+        tool_ref: "map_reduce".into(),
+        input_json,
+        output_json: Some(output.clone()),
+        config_json: serde_json::json!({
+            "shard_size": node.shard_size,
+            "max_concurrency": node.max_concurrency,
+            "collect_expr": node.collect_expr,
+        }),
         started_at,
         completed_at: Some(Utc::now()),
         duration_ms: Some((Utc::now() - started_at).num_milliseconds()),
@@ -846,14 +2079,18 @@ async fn execute_subgraph_nodes(
     let mut outputs = serde_json::Map::new();
 
     for subnode in &subgraph.nodes {
-        let result = execute_node_entry(
-            execution_id,
-            subnode.id(),
-            Some(subnode),
-            input.clone(),
-            ctx.clone(),
-        )
-        .await;
+        if !ctx.pause_point(execution_id, &[subnode.id().to_string()]).await {
+            *all_completed = false;
+            break;
+        }
+        if ctx.node_cancellation.is_cancelled(subnode.id()) {
+            cancelled_node(execution_id, subnode.id(), Some(subnode), Utc::now(), &ctx);
+            continue;
+        }
+        let Some(result) = execute_unless_cancelled(execution_id, subnode.id(), Some(subnode), input.clone(), &ctx).await
+        else {
+            continue;
+        };
 
         match result {
             Ok((span, output)) => {
@@ -871,30 +2108,53 @@ async fn execute_subgraph_nodes(
     serde_json::Value::Object(outputs)
 }
 
-/// Evaluate a JMESPath expression on input.
-fn evaluate_jmespath(expr: &str, input: &serde_json::Value) -> Option<serde_json::Value> {
-    // Simple JMESPath evaluation - for complex expressions, use jmespath crate
-    if expr.starts_with("$.") {
-        let path = expr.strip_prefix("$.").unwrap_or(expr);
-        let mut current = input.clone();
-        for part in path.split('.') {
-            current = current.get(part)?.clone();
+/// Run a node of a sub-graph, or stop it when it is cancelled on its own
+/// (None), which leaves the rest of the sub-graph to run.
+async fn execute_unless_cancelled(
+    execution_id: Uuid,
+    node_id: &str,
+    node: Option<&NodeEntry>,
+    input: serde_json::Value,
+    ctx: &Arc<ExecutionContext>,
+) -> Option<Result<(NodeSpan, serde_json::Value), RunnerError>> {
+    let started_at = Utc::now();
+    tokio::select! {
+        result = execute_node_entry(execution_id, node_id, node, input, ctx.clone()) => Some(result),
+        () = ctx.node_cancellation.cancelled(node_id) => {
+            cancelled_node(execution_id, node_id, node, started_at, ctx);
+            None
         }
-        Some(current)
-    } else {
-        input.get(expr).cloned()
     }
 }
 
-/// Evaluate a condition expression.
-fn evaluate_condition(expr: &str, input: &serde_json::Value) -> Option<serde_json::Value> {
-    // Simple condition evaluation
-    if let Some(value) = evaluate_jmespath(expr, input) {
-        return Some(value);
+/// Evaluate the expression in a node's `field` (see [`hb_core::expr`])
+/// against `input`. Syntax errors are caught before the run starts, so
+/// errors here come from evaluation, e.g. a division by zero.
+///
+/// Fields written before the expression engine keep their meaning: text
+/// naming a top-level field exactly reads that field, even where it would
+/// parse as something else (`order-id`), and JSON that isn't an expression
+/// (`{"a": 1}`) is that literal.
+fn evaluate_field(field: &str, expr: &str, input: &serde_json::Value) -> Result<serde_json::Value, String> {
+    if let Some(value) = input.get(expr) {
+        return Ok(value.clone());
     }
+    match hb_core::expr::evaluate(expr, input) {
+        Err(e @ hb_core::expr::ExprError::Syntax { .. }) => {
+            serde_json::from_str(expr).map_err(|_| format!("{field} '{expr}': {e}"))
+        }
+        result => result.map_err(|e| format!("{field} '{expr}': {e}")),
+    }
+}
 
-    // Try parsing as literal
-    serde_json::from_str(expr).ok()
+/// Evaluate a condition expression. A `null` result (e.g. a missing field)
+/// is no result, so the default branch is taken; a field that exists is
+/// its value, `null` included, as it always was.
+fn evaluate_condition(expr: &str, input: &serde_json::Value) -> Result<Option<serde_json::Value>, String> {
+    if let Some(value) = input.get(expr) {
+        return Ok(Some(value.clone()));
+    }
+    evaluate_field("condition_expr", expr, input).map(|value| Some(value).filter(|v| !v.is_null()))
 }
 
 /// Execute an agent-task node via the injected AgentTaskExecutor callback.
@@ -964,22 +2224,87 @@ async fn execute_agent_task(
     }
 }
 
-/// Execute a tool via Native runtime (hb-tool-executor).
-async fn execute_native_tool(
+/// Run a node's tool under the liveness policy: "stalled" status events
+/// while it is silent, and a failed attempt if it is killed as hung.
+async fn watch_node(
+    execution_id: Uuid,
+    node_id: &str,
+    work: impl Future<Output = (serde_json::Value, ExecutionStatus, Option<String>, i64)>,
+    heartbeat: &Heartbeat,
+    ctx: &ExecutionContext,
+) -> (serde_json::Value, ExecutionStatus, Option<String>, i64) {
+    let start = std::time::Instant::now();
+    let policy = &ctx.liveness;
+    let status = |status: &str, error: Option<String>| NodeStatusEvent {
+        execution_id: execution_id.to_string(),
+        node_id: node_id.to_string(),
+        status: status.into(),
+        seq: 0,
+        output: None,
+        error,
+        duration_ms: None,
+        progress: None,
+        chunk: None,
+    };
+    let on_stall = |stall| match stall {
+        Stall::Stalled(silence) => {
+            tracing::warn!("Node {node_id} has sent no heartbeat for {}s", silence.as_secs());
+            ctx.emit_status(status(
+                "stalled",
+                Some(format!("No sign of progress for {}s", silence.as_secs())),
+            ));
+        }
+        Stall::Resumed => ctx.emit_status(status("running", None)),
+    };
+    match liveness::watch(work, heartbeat, policy.warn_after(), policy.kill_after(), on_stall).await {
+        Watched::Finished(result) => result,
+        Watched::Hung(silence) => {
+            let message = format!("Node hung: no sign of progress for {}s, killed", silence.as_secs());
+            tracing::error!("{node_id}: {message}");
+            (
+                serde_json::json!({ "error": &message }),
+                ExecutionStatus::Failed,
+                Some(message),
+                start.elapsed().as_millis() as i64,
+            )
+        }
+    }
+}
+
+/// Execute a tool with its runtime (hb-tool-executor).
+async fn execute_tool(
+    runtime: &RuntimeSpec,
     tool_ref: &str,
     input_json: &serde_json::Value,
     config_json: &serde_json::Value,
-    llm_provider: Option<&str>,
+    ctx: &ExecutionContext,
+    progress: ProgressReporter,
+    stream: OutputStream,
 ) -> (serde_json::Value, ExecutionStatus, Option<String>, i64) {
+    let mut inputs = input_json.clone();
+    if let (Some(workspace), Some(map)) = (&ctx.workspace, inputs.as_object_mut()) {
+        map.insert(
+            WorkspaceContext::INPUT_KEY.to_string(),
+            serde_json::to_value(workspace.as_ref()).unwrap_or_default(),
+        );
+    }
     let tool_input = ToolInput {
         tool_ref: tool_ref.to_string(),
-        inputs: input_json.clone(),
+        inputs,
         config: config_json.clone(),
-        llm_provider: llm_provider.map(|s| s.to_string()),
+        llm_provider: ctx.llm_provider.clone(),
+        env: match &ctx.scratch_dir {
+            Some(dir) => scratch::process_env(&ctx.env, dir),
+            None => (*ctx.env).clone(),
+        },
+        progress,
+        stream,
+        commands: ctx.command_gate.clone(),
+        secrets: ctx.secrets.clone(),
+        scratch_dir: ctx.scratch_dir.clone(),
     };
 
-    let runtime = RuntimeSpec::Native;
-    let exec_result = execute(&runtime, &tool_input).await;
+    let exec_result = execute(runtime, &tool_input).await;
 
     match exec_result {
         Ok(tool_output) => (
@@ -990,7 +2315,7 @@ async fn execute_native_tool(
         ),
         Err(e) => {
             let err_msg = e.to_string();
-            tracing::error!("Native tool execution failed: {err_msg}");
+            tracing::error!("Tool execution failed: {err_msg}");
             (
                 serde_json::json!({ "error": err_msg }),
                 ExecutionStatus::Failed,
@@ -1094,7 +2419,133 @@ fn default_environment() -> ExecutionEnvironment {
     }
 }
 
-fn create_error_span(execution_id: Uuid, node_id: &str, error: &str) -> NodeSpan {
+/// Report a node whose output came with its chain from the subgraph memo,
+/// and build its span.
+fn memoized_node(
+    execution_id: Uuid,
+    node_id: &str,
+    node: Option<&NodeEntry>,
+    input_json: serde_json::Value,
+    output: &serde_json::Value,
+    memo_key: &str,
+    ctx: &ExecutionContext,
+) -> NodeSpan {
+    let (tool_ref, config_json) = match node {
+        Some(NodeEntry::Primitive(n)) => (n.tool_ref.clone(), serde_json::Value::Object(n.config.clone())),
+        _ => ("unknown".into(), serde_json::json!({})),
+    };
+    ctx.journal(JournalEntry::CacheKey {
+        execution_id,
+        node_id: node_id.into(),
+        cache_key: memo_key.into(),
+        hit: true,
+    });
+    ctx.journal(JournalEntry::NodeFinished {
+        execution_id,
+        node_id: node_id.into(),
+        status: ExecutionStatus::CacheHit,
+        at: Utc::now(),
+    });
+    ctx.emit_status(NodeStatusEvent {
+        execution_id: execution_id.to_string(),
+        node_id: node_id.into(),
+        status: "cache_hit".into(),
+        seq: 0,
+        output: Some(output.clone()),
+        error: None,
+        duration_ms: Some(0),
+        progress: None,
+        chunk: None,
+    });
+    let now = Utc::now();
+    NodeSpan {
+        span_id: Uuid::new_v4(),
+        execution_id,
+        node_id: node_id.into(),
+        tool_ref,
+        input_json,
+        output_json: Some(output.clone()),
+        config_json,
+        started_at: now,
+        completed_at: Some(now),
+        duration_ms: Some(0),
+        status: ExecutionStatus::CacheHit,
+        error: None,
+        cache_hit: true,
+        environment: default_environment(),
+    }
+}
+
+/// Report a node cancelled before or while running, and build its span.
+fn cancelled_node(
+    execution_id: Uuid,
+    node_id: &str,
+    node: Option<&NodeEntry>,
+    started_at: chrono::DateTime<Utc>,
+    ctx: &ExecutionContext,
+) -> NodeSpan {
+    stopped_node(execution_id, node_id, node, started_at, ctx, ExecutionStatus::Cancelled, None)
+}
+
+/// Report a node stopped from outside — cancelled, out of time, or skipped
+/// by error routing — and build its span.
+fn stopped_node(
+    execution_id: Uuid,
+    node_id: &str,
+    node: Option<&NodeEntry>,
+    started_at: chrono::DateTime<Utc>,
+    ctx: &ExecutionContext,
+    status: ExecutionStatus,
+    error: Option<String>,
+) -> NodeSpan {
+    let (tool_ref, config_json) = match node {
+        Some(NodeEntry::Primitive(n)) => (n.tool_ref.clone(), serde_json::Value::Object(n.config.clone())),
+        _ => ("unknown".into(), serde_json::json!({})),
+    };
+    let now = Utc::now();
+    let duration_ms = (now - started_at).num_milliseconds();
+    ctx.journal(JournalEntry::NodeFinished {
+        execution_id,
+        node_id: node_id.into(),
+        status: status.clone(),
+        at: now,
+    });
+    ctx.emit_status(NodeStatusEvent {
+        execution_id: execution_id.to_string(),
+        node_id: node_id.into(),
+        status: match status {
+            ExecutionStatus::Cancelled => "cancelled",
+            ExecutionStatus::Skipped => "skipped",
+            _ => "failed",
+        }
+        .into(),
+        seq: 0,
+        output: None,
+        error: error.clone(),
+        duration_ms: Some(duration_ms),
+        progress: None,
+        chunk: None,
+    });
+    tracing::info!("Node {node_id} of execution {execution_id} stopped: {status:?}");
+    NodeSpan {
+        span_id: Uuid::new_v4(),
+        execution_id,
+        node_id: node_id.into(),
+        tool_ref,
+        input_json: serde_json::json!({}),
+        output_json: None,
+        config_json,
+        started_at,
+        completed_at: Some(now),
+        duration_ms: Some(duration_ms),
+        status,
+        error,
+        cache_hit: false,
+        environment: default_environment(),
+    }
+}
+
+fn create_error_span(execution_id: Uuid, node_id: &str, error: &str) -> NodeSpan {
     NodeSpan {
         span_id: Uuid::new_v4(),
         execution_id,
@@ -1113,7 +2564,8 @@ fn create_error_span(execution_id: Uuid, node_id: &str, error: &str) -> NodeSpan
     }
 }
 
-/// Build adjacency list and in-degree map from workflow spec (data edges only).
+/// Build adjacency list and in-degree map from workflow spec (data and error
+/// edges; error handlers run after the node whose errors they handle).
 fn build_dag(spec: &WorkflowSpec) -> (HashMap<String, Vec<String>>, HashMap<String, usize>) {
     let mut adj: HashMap<String, Vec<String>> = HashMap::new();
     let mut in_degree: HashMap<String, usize> = HashMap::new();
@@ -1124,7 +2576,7 @@ fn build_dag(spec: &WorkflowSpec) -> (HashMap<String, Vec<String>>, HashMap<Stri
     }
 
     for edge in &spec.edges {
-        if edge.kind == EdgeKind::Data {
+        if edge.kind != EdgeKind::Control {
             adj.entry(edge.source_node.clone())
                 .or_default()
                 .push(edge.target_node.clone());
@@ -1195,32 +2647,17 @@ mod tests {
                 NodeEntry::Primitive(NodeSpec {
                     id: "a".into(),
                     tool_ref: "core/read@1.0".into(),
-                    config: Default::default(),
-                    position: None,
-                    label: None,
-                    disabled: false,
-                    retry: None,
-                    cache: None,
+                    ..Default::default()
                 }),
                 NodeEntry::Primitive(NodeSpec {
                     id: "b".into(),
                     tool_ref: "core/split@1.0".into(),
-                    config: Default::default(),
-                    position: None,
-                    label: None,
-                    disabled: false,
-                    retry: None,
-                    cache: None,
+                    ..Default::default()
                 }),
                 NodeEntry::Primitive(NodeSpec {
                     id: "c".into(),
                     tool_ref: "core/llm@1.0".into(),
-                    config: Default::default(),
-                    position: None,
-                    label: None,
-                    disabled: false,
-                    retry: None,
-                    cache: None,
+                    ..Default::default()
                 }),
             ],
             edges: vec![
@@ -1230,8 +2667,7 @@ mod tests {
                     source_port: "out".into(),
                     target_node: "b".into(),
                     target_port: "in".into(),
-                    kind: EdgeKind::Data,
-                    transform: None,
+                    ..Default::default()
                 },
                 EdgeSpec {
                     id: "e2".into(),
@@ -1239,8 +2675,7 @@ mod tests {
                     source_port: "out".into(),
                     target_node: "c".into(),
                     target_port: "in".into(),
-                    kind: EdgeKind::Data,
-                    transform: None,
+                    ..Default::default()
                 },
             ],
             ..Default::default()
@@ -1280,11 +2715,8 @@ mod tests {
                 id: "agent1".into(),
                 tool_ref: "agent-task".into(),
                 config,
-                position: None,
                 label: Some("Agent Node".into()),
-                disabled: false,
-                retry: None,
-                cache: None,
+                ..Default::default()
             })],
             edges: vec![],
             ..Default::default()
@@ -1322,11 +2754,7 @@ mod tests {
                 id: "agent2".into(),
                 tool_ref: "agent-task".into(),
                 config,
-                position: None,
-                label: None,
-                disabled: false,
-                retry: None,
-                cache: None,
+                ..Default::default()
             })],
             edges: vec![],
             ..Default::default()
@@ -1345,12 +2773,7 @@ mod tests {
             nodes: vec![NodeEntry::Primitive(NodeSpec {
                 id: "agent3".into(),
                 tool_ref: "agent-task".into(),
-                config: Default::default(),
-                position: None,
-                label: None,
-                disabled: false,
-                retry: None,
-                cache: None,
+                ..Default::default()
             })],
             edges: vec![],
             ..Default::default()
@@ -1375,12 +2798,7 @@ mod tests {
                 NodeEntry::Primitive(NodeSpec {
                     id: "reader".into(),
                     tool_ref: "display-output".into(),
-                    config: Default::default(),
-                    position: None,
-                    label: None,
-                    disabled: false,
-                    retry: None,
-                    cache: None,
+                    ..Default::default()
                 }),
                 NodeEntry::Primitive(NodeSpec {
                     id: "agent4".into(),
@@ -1390,11 +2808,7 @@ mod tests {
                         m.insert("prompt".into(), serde_json::json!("Summarize"));
                         m
                     },
-                    position: None,
-                    label: None,
-                    disabled: false,
-                    retry: None,
-                    cache: None,
+                    ..Default::default()
                 }),
             ],
             edges: vec![EdgeSpec {
@@ -1403,8 +2817,7 @@ mod tests {
                 source_port: "output".into(),
                 target_node: "agent4".into(),
                 target_port: "context".into(),
-                kind: EdgeKind::Data,
-                transform: None,
+                ..Default::default()
             }],
             ..Default::default()
         };
@@ -1429,4 +2842,1248 @@ mod tests {
         let ctx_val = received_context.lock().await;
         assert!(ctx_val.is_object(), "Agent should receive upstream data as context");
     }
+
+    #[tokio::test]
+    async fn native_tools_resolve_paths_in_the_workspace() {
+        let root = std::env::temp_dir().join(format!("hb-scheduler-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("메모.txt"), "기초 타설").unwrap();
+        let spec = WorkflowSpec {
+            nodes: vec![NodeEntry::Primitive(NodeSpec {
+                id: "read".into(),
+                tool_ref: "file-read".into(),
+                config: serde_json::json!({ "file_path": "메모.txt" }).as_object().unwrap().clone(),
+                retry: Some(RetryPolicy {
+                    max_retries: 0,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })],
+            ..Default::default()
+        };
+        let workspace = WorkspaceContext {
+            workspace_id: Uuid::new_v4(),
+            root_path: root.to_string_lossy().into_owned(),
+            data_sources: vec![],
+        };
+
+        let ctx = ExecutionContext::default().with_workspace(workspace);
+        let record = run_dag_with_context(Uuid::new_v4(), &spec, ctx).await.unwrap();
+        assert_eq!(record.completed_nodes, 1);
+        // Without a workspace the path is relative to the process
+        let record = run_dag(Uuid::new_v4(), &spec).await.unwrap();
+        assert_eq!(record.failed_nodes, 1);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn tools_run_with_their_declared_runtime() {
+        let spec = WorkflowSpec {
+            nodes: vec![NodeEntry::Primitive(NodeSpec {
+                id: "convert".into(),
+                tool_ref: "gis/convert@1.0.0".into(),
+                retry: Some(RetryPolicy {
+                    max_retries: 0,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })],
+            ..Default::default()
+        };
+        let runtime: RuntimeSpec = serde_json::from_value(serde_json::json!({
+            "kind": "process",
+            "command": "sh",
+            "args": ["-c", "cat >/dev/null; [ \"$TOKEN\" = s3cret ] && printf '{\"ok\":true}'"],
+            "env": { "TOKEN": { "secret": "VWORLD_KEY" } },
+        }))
+        .unwrap();
+        let ctx = || {
+            ExecutionContext::default()
+                .with_tool_runtimes(HashMap::from([("gis/convert".to_string(), runtime.clone())]))
+        };
+
+        // Without the secret the process can't start
+        let record = run_dag_with_context(Uuid::new_v4(), &spec, ctx()).await.unwrap();
+        assert_eq!(record.failed_nodes, 1);
+
+        let secrets = Secrets::new(BTreeMap::from([("VWORLD_KEY".to_string(), "s3cret".to_string())]));
+        let record = run_dag_with_context(Uuid::new_v4(), &spec, ctx().with_secrets(secrets))
+            .await
+            .unwrap();
+        assert_eq!(record.completed_nodes, 1);
+    }
+
+    #[tokio::test]
+    async fn scratch_directories_last_as_long_as_the_run() {
+        let root = std::env::temp_dir().join(format!("hb-scheduler-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("memo.txt"), "pour").unwrap();
+        let read = |path: &str| WorkflowSpec {
+            nodes: vec![NodeEntry::Primitive(NodeSpec {
+                id: "read".into(),
+                tool_ref: "file-read".into(),
+                config: serde_json::json!({ "file_path": path }).as_object().unwrap().clone(),
+                retry: Some(RetryPolicy {
+                    max_retries: 0,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })],
+            ..Default::default()
+        };
+        let space = ScratchSpace::new(root.join("scratch")).keep_failed(true);
+        let ctx = || {
+            ExecutionContext::default()
+                .with_workspace(WorkspaceContext {
+                    workspace_id: Uuid::new_v4(),
+                    root_path: root.to_string_lossy().into_owned(),
+                    data_sources: vec![],
+                })
+                .with_scratch(space.clone())
+        };
+
+        let completed = Uuid::new_v4();
+        let record = run_dag_with_context(completed, &read("memo.txt"), ctx()).await.unwrap();
+        assert_eq!(record.status, ExecutionStatus::Completed);
+        assert!(!space.dir(completed).exists());
+
+        // Paths are looked up in the scratch directory, kept after the failure
+        let failed = Uuid::new_v4();
+        let record = run_dag_with_context(failed, &read("scratch://memo.txt"), ctx()).await.unwrap();
+        assert_eq!(record.status, ExecutionStatus::Failed);
+        assert!(space.dir(failed).is_dir());
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn pure_chains_are_skipped_on_the_next_run() {
+        let root = std::env::temp_dir().join(format!("hb-scheduler-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("memo.txt"), "Footing pour\n\nRebar placement").unwrap();
+        let node = |id: &str, tool: &str, config: serde_json::Value, cache: Option<CachePolicy>| {
+            NodeEntry::Primitive(NodeSpec {
+                id: id.into(),
+                tool_ref: tool.into(),
+                config: config.as_object().unwrap().clone(),
+                retry: Some(RetryPolicy {
+                    max_retries: 0,
+                    ..Default::default()
+                }),
+                cache,
+                ..Default::default()
+            })
+        };
+        let edge = |source: &str, source_port: &str, target: &str, target_port: &str| EdgeSpec {
+            id: format!("{source}-{target}"),
+            source_node: source.into(),
+            source_port: source_port.into(),
+            target_node: target.into(),
+            target_port: target_port.into(),
+            ..Default::default()
+        };
+        let uncached = CachePolicy {
+            enabled: false,
+            ttl_secs: 0,
+        };
+        let mut spec = WorkflowSpec {
+            nodes: vec![
+                node("read", "file-read", serde_json::json!({ "file_path": "memo.txt" }), None),
+                node("split", "text-split", serde_json::json!({}), None),
+                node("show", "display-output", serde_json::json!({}), Some(uncached)),
+            ],
+            edges: vec![
+                edge("read", "content", "split", "text"),
+                edge("split", "chunks", "show", "value"),
+            ],
+            ..Default::default()
+        };
+        spec.meta.memoize = true;
+        let workspace = WorkspaceContext {
+            workspace_id: Uuid::new_v4(),
+            root_path: root.to_string_lossy().into_owned(),
+            data_sources: vec![],
+        };
+        let cache = Arc::new(ExecutionCache::in_memory().unwrap().with_config(
+            crate::cache::CacheConfig {
+                min_compute_ms: 0,
+                ..Default::default()
+            },
+        ));
+        let pure = PureTools {
+            pure: ["file-read", "text-split"].map(String::from).into(),
+            reads_files: ["file-read".to_string()].into(),
+        };
+        let ctx = || {
+            ExecutionContext::default()
+                .with_workspace(workspace.clone())
+                .with_cache(cache.clone())
+                .with_pure_tools(pure.clone())
+        };
+
+        let record = run_dag_with_context(Uuid::new_v4(), &spec, ctx()).await.unwrap();
+        assert_eq!((record.completed_nodes, record.cache_hits), (3, 0));
+
+        // The chain is found in one lookup; neither node runs or is looked up
+        let hits_before = cache.stats().memory_hits;
+        let record = run_dag_with_context(Uuid::new_v4(), &spec, ctx()).await.unwrap();
+        assert_eq!((record.completed_nodes, record.cache_hits), (3, 2));
+        assert_eq!(cache.stats().memory_hits, hits_before + 1);
+
+        // An edited file runs the chain again
+        std::fs::write(root.join("memo.txt"), "Footing pour\n\nFormwork removal").unwrap();
+        let record = run_dag_with_context(Uuid::new_v4(), &spec, ctx()).await.unwrap();
+        assert_eq!((record.completed_nodes, record.cache_hits), (3, 0));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn resumed_executions_restore_completed_nodes() {
+        let root = std::env::temp_dir().join(format!("hb-scheduler-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("memo.txt"), "Footing pour").unwrap();
+        let node = |id: &str, tool: &str, config: serde_json::Value| {
+            NodeEntry::Primitive(NodeSpec {
+                id: id.into(),
+                tool_ref: tool.into(),
+                config: config.as_object().unwrap().clone(),
+                retry: Some(RetryPolicy {
+                    max_retries: 0,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+        };
+        let edge = |source: &str, target: &str, target_port: &str| EdgeSpec {
+            id: format!("{source}-{target}"),
+            source_node: source.into(),
+            source_port: "content".into(),
+            target_node: target.into(),
+            target_port: target_port.into(),
+            ..Default::default()
+        };
+        let spec = WorkflowSpec {
+            nodes: vec![
+                node("memo", "file-read", serde_json::json!({ "file_path": "memo.txt" })),
+                node("log", "file-read", serde_json::json!({ "file_path": "log.txt" })),
+                node("merge", "text-merge", serde_json::json!({})),
+            ],
+            edges: vec![edge("memo", "merge", "a"), edge("log", "merge", "b")],
+            ..Default::default()
+        };
+        let workspace = WorkspaceContext {
+            workspace_id: Uuid::new_v4(),
+            root_path: root.to_string_lossy().into_owned(),
+            data_sources: vec![],
+        };
+        let store: Arc<dyn hb_trace::store::TraceStore> =
+            Arc::new(hb_trace::store::SqliteTraceStore::in_memory().unwrap());
+        let ctx = || {
+            ExecutionContext::default()
+                .with_workspace(workspace.clone())
+                .with_trace_store(store.clone())
+        };
+
+        // log.txt is missing, so the run fails after reading memo.txt
+        let execution_id = Uuid::new_v4();
+        let record = run_dag_with_context(execution_id, &spec, ctx()).await.unwrap();
+        assert_eq!(record.status, ExecutionStatus::Failed);
+        assert_eq!(record.completed_nodes, 1);
+
+        // memo.txt is gone too, but its output is restored rather than re-read
+        std::fs::remove_file(root.join("memo.txt")).unwrap();
+        std::fs::write(root.join("log.txt"), "Rebar placement").unwrap();
+        let resumed = resume_execution(execution_id, &spec, ctx()).await.unwrap();
+        assert_eq!(resumed.status, ExecutionStatus::Completed);
+        assert_eq!((resumed.completed_nodes, resumed.failed_nodes), (3, 0));
+        assert_eq!(resumed.started_at, record.started_at);
+        let spans = store.query_spans_by_execution(execution_id).unwrap();
+        assert_eq!(spans.iter().filter(|s| s.node_id == "memo").count(), 1);
+
+        // A completed run has nothing to resume
+        assert!(matches!(
+            resume_execution(execution_id, &spec, ctx()).await,
+            Err(RunnerError::Resume(_))
+        ));
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn paused_executions_dispatch_nothing_until_resumed() {
+        let spec = WorkflowSpec {
+            nodes: vec![NodeEntry::Primitive(NodeSpec {
+                id: "wait".into(),
+                tool_ref: "delay".into(),
+                config: serde_json::json!({ "delay_ms": 1 }).as_object().unwrap().clone(),
+                ..Default::default()
+            })],
+            ..Default::default()
+        };
+        let statuses = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let ctx = {
+            let statuses = statuses.clone();
+            ExecutionContext::default()
+                .with_status_callback(move |event| statuses.lock().unwrap().push(event.status))
+        };
+        let pause = ctx.pause.clone();
+        assert!(pause.pause());
+
+        let run = tokio::spawn(async move { run_dag_with_context(Uuid::new_v4(), &spec, ctx).await });
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert_eq!(*statuses.lock().unwrap(), ["paused"]);
+
+        pause.resume();
+        let record = run.await.unwrap().unwrap();
+        assert_eq!(record.status, ExecutionStatus::Completed);
+        assert_eq!(statuses.lock().unwrap()[..3], ["paused", "pending", "running"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn pauses_hold_loop_bodies_and_stop_the_execution_clock() {
+        let step = NodeEntry::Primitive(NodeSpec {
+            id: "step".into(),
+            tool_ref: "delay".into(),
+            config: serde_json::json!({ "delay_ms": 100 }).as_object().unwrap().clone(),
+            ..Default::default()
+        });
+        let spec = WorkflowSpec {
+            nodes: vec![NodeEntry::Loop(LoopSpec {
+                id: "twice".into(),
+                kind: hb_core::graph::LoopKind::Repeat,
+                body: SubgraphSpec {
+                    nodes: vec![step],
+                    edges: vec![],
+                },
+                max_iterations: 2,
+                condition_expr: None,
+                items_expr: None,
+                collect_expr: None,
+                aggregation: Default::default(),
+            })],
+            ..Default::default()
+        };
+        let ctx = ExecutionContext::default().with_time_limits(TimeLimits {
+            execution: Some(std::time::Duration::from_secs(1)),
+            ..Default::default()
+        });
+        let pause = ctx.pause.clone();
+        let statuses = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let ctx = {
+            let statuses = statuses.clone();
+            let pause = pause.clone();
+            // Pause as soon as the first iteration's step finishes
+            ctx.with_status_callback(move |event| {
+                if event.node_id == "step" && event.status == "completed" {
+                    pause.pause();
+                }
+                statuses.lock().unwrap().push((event.node_id, event.status));
+            })
+        };
+
+        let run = tokio::spawn(async move { run_dag_with_context(Uuid::new_v4(), &spec, ctx).await });
+        // Held well past the execution's time limit
+        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        let step_events = |statuses: &[(String, String)]| {
+            statuses.iter().filter(|(id, _)| id == "step").map(|(_, status)| status.clone()).collect::<Vec<_>>()
+        };
+        assert_eq!(step_events(&statuses.lock().unwrap()), ["running", "completed", "paused"]);
+
+        pause.resume();
+        let record = run.await.unwrap().unwrap();
+        assert_eq!(record.status, ExecutionStatus::Completed);
+        assert_eq!(
+            step_events(&statuses.lock().unwrap())[3..],
+            ["running", "completed"]
+        );
+    }
+
+    #[tokio::test]
+    async fn cancelled_nodes_stop_their_dependents_only() {
+        let delay = |id: &str, ms: u64| {
+            NodeEntry::Primitive(NodeSpec {
+                id: id.into(),
+                tool_ref: "delay".into(),
+                config: serde_json::json!({ "delay_ms": ms }).as_object().unwrap().clone(),
+                ..Default::default()
+            })
+        };
+        // slow → after, and an unrelated branch other → last
+        let edge = |source: &str, target: &str| EdgeSpec {
+            id: format!("{source}-{target}"),
+            source_node: source.into(),
+            source_port: "trigger".into(),
+            target_node: target.into(),
+            target_port: "trigger".into(),
+            ..Default::default()
+        };
+        let spec = WorkflowSpec {
+            nodes: vec![delay("slow", 60_000), delay("after", 1), delay("other", 1), delay("last", 1)],
+            edges: vec![edge("slow", "after"), edge("other", "last")],
+            ..Default::default()
+        };
+        let store: Arc<dyn hb_trace::store::TraceStore> =
+            Arc::new(hb_trace::store::SqliteTraceStore::in_memory().unwrap());
+        let ctx = ExecutionContext::default().with_trace_store(store.clone());
+        let cancellation = ctx.node_cancellation.clone();
+        let execution_id = Uuid::new_v4();
+
+        let run = tokio::spawn(async move { run_dag_with_context(execution_id, &spec, ctx).await });
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(cancellation.cancel("nope").is_err());
+        assert!(cancellation.cancel("slow").unwrap());
+        let record = tokio::time::timeout(std::time::Duration::from_secs(5), run)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        // The other branch finished, so the run did too
+        assert_eq!(record.status, ExecutionStatus::Completed);
+        assert_eq!((record.completed_nodes, record.failed_nodes), (2, 0));
+        store.flush().unwrap();
+        let status = |node: &str| {
+            let spans = store.query_spans_by_execution(execution_id).unwrap();
+            spans.into_iter().find(|s| s.node_id == node).unwrap().status
+        };
+        assert_eq!(status("slow"), ExecutionStatus::Cancelled);
+        assert_eq!(status("after"), ExecutionStatus::Cancelled);
+        assert_eq!(status("last"), ExecutionStatus::Completed);
+    }
+
+    #[tokio::test]
+    async fn nodes_of_referenced_workflows_cancel_by_their_scoped_id() {
+        let delay = |id: &str, ms: u64| {
+            NodeEntry::Primitive(NodeSpec {
+                id: id.into(),
+                tool_ref: "delay".into(),
+                config: serde_json::json!({ "delay_ms": ms }).as_object().unwrap().clone(),
+                ..Default::default()
+            })
+        };
+        let inner = WorkflowSpec {
+            nodes: vec![delay("slow", 60_000), delay("quick", 1)],
+            ..Default::default()
+        };
+        let outer = WorkflowSpec {
+            nodes: vec![NodeEntry::WorkflowRef(WorkflowRefSpec {
+                id: "sub".into(),
+                workflow_id: inner.id,
+                input_ports: Vec::new(),
+                output_ports: Vec::new(),
+                input_mapping: Vec::new(),
+                output_mapping: Vec::new(),
+                position: None,
+                label: None,
+            })],
+            ..Default::default()
+        };
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let ctx = ExecutionContext::default()
+            .with_status_callback(move |e: NodeStatusEvent| sink.lock().unwrap().push((e.node_id, e.status)))
+            .with_workflows(HashMap::from([(inner.id, inner.clone())]));
+        let cancellation = ctx.node_cancellation.clone();
+
+        let run = tokio::spawn(async move { run_dag_with_context(Uuid::new_v4(), &outer, ctx).await });
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(cancellation.cancel("slow").is_err());
+        assert!(cancellation.cancel("sub/slow").unwrap());
+        let record = tokio::time::timeout(std::time::Duration::from_secs(5), run)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(record.status, ExecutionStatus::Completed);
+        let events = events.lock().unwrap();
+        assert!(events.contains(&("sub/slow".into(), "cancelled".into())));
+        assert!(events.contains(&("sub/quick".into(), "completed".into())));
+    }
+
+    #[tokio::test]
+    async fn resource_classes_limit_nodes_running_at_once() {
+        // Four independent nodes in one level; three share the "llm" class
+        let delay = |id: &str, class: Option<&str>| {
+            NodeEntry::Primitive(NodeSpec {
+                id: id.into(),
+                tool_ref: "delay".into(),
+                config: serde_json::json!({ "delay_ms": 30 }).as_object().unwrap().clone(),
+                resource_class: class.map(str::to_string),
+                ..Default::default()
+            })
+        };
+        let spec = WorkflowSpec {
+            nodes: vec![delay("a", Some("llm")), delay("b", Some("llm")), delay("c", Some("llm")), delay("d", None)],
+            ..Default::default()
+        };
+        let policy = hb_core::policy::ConcurrencyPolicy {
+            max_parallel: None,
+            classes: BTreeMap::from([("llm".into(), 1)]),
+        };
+        let running = Arc::new(std::sync::Mutex::new((HashSet::new(), 0usize)));
+        let ctx = {
+            let running = running.clone();
+            ExecutionContext::default()
+                .with_concurrency(ConcurrencyLimits::new(&policy, HashMap::new()))
+                .with_status_callback(move |event| {
+                    let mut running = running.lock().unwrap();
+                    match event.status.as_str() {
+                        "running" if event.node_id != "d" => {
+                            running.0.insert(event.node_id);
+                        }
+                        "completed" => {
+                            running.0.remove(&event.node_id);
+                        }
+                        _ => {}
+                    }
+                    running.1 = running.1.max(running.0.len());
+                })
+        };
+
+        let started = std::time::Instant::now();
+        let record = run_dag_with_context(Uuid::new_v4(), &spec, ctx).await.unwrap();
+        assert_eq!(record.completed_nodes, 4);
+        assert_eq!(running.lock().unwrap().1, 1);
+        assert!(started.elapsed() >= std::time::Duration::from_millis(90));
+    }
+
+    #[tokio::test]
+    async fn nodes_waiting_to_retry_free_their_slot() {
+        // One slot: "a" fails and backs off while "b" waits for the slot
+        let node = |id: &str, tool_ref: &str, retry: Option<RetryPolicy>| {
+            NodeEntry::Primitive(NodeSpec {
+                id: id.into(),
+                tool_ref: tool_ref.into(),
+                retry,
+                ..Default::default()
+            })
+        };
+        let retry = RetryPolicy {
+            max_retries: 1,
+            backoff_ms: 300,
+            backoff_multiplier: 1.0,
+            max_backoff_ms: 300,
+        };
+        let spec = WorkflowSpec {
+            nodes: vec![node("a", "json-parse", Some(retry)), node("b", "delay", None)],
+            ..Default::default()
+        };
+        let policy = hb_core::policy::ConcurrencyPolicy {
+            max_parallel: Some(1),
+            classes: BTreeMap::new(),
+        };
+        let finished = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let ctx = {
+            let finished = finished.clone();
+            ExecutionContext::default()
+                .with_concurrency(ConcurrencyLimits::new(&policy, HashMap::new()))
+                .with_status_callback(move |event| {
+                    if matches!(event.status.as_str(), "completed" | "failed") {
+                        finished.lock().unwrap().push(event.node_id);
+                    }
+                })
+        };
+
+        run_dag_with_context(Uuid::new_v4(), &spec, ctx).await.unwrap();
+        assert_eq!(*finished.lock().unwrap(), ["b", "a"]);
+    }
+
+    #[tokio::test]
+    async fn time_limits_stop_slow_nodes_and_runs() {
+        let delay = |id: &str, ms: u64| {
+            NodeEntry::Primitive(NodeSpec {
+                id: id.into(),
+                tool_ref: "delay".into(),
+                config: serde_json::json!({ "delay_ms": ms }).as_object().unwrap().clone(),
+                retry: Some(RetryPolicy {
+                    max_retries: 3,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+        };
+        let edge = |source: &str, target: &str| EdgeSpec {
+            id: format!("{source}-{target}"),
+            source_node: source.into(),
+            source_port: "trigger".into(),
+            target_node: target.into(),
+            target_port: "trigger".into(),
+            ..Default::default()
+        };
+        let store: Arc<dyn hb_trace::store::TraceStore> =
+            Arc::new(hb_trace::store::SqliteTraceStore::in_memory().unwrap());
+        let ctx = |limits: TimeLimits| {
+            ExecutionContext::default()
+                .with_trace_store(store.clone())
+                .with_time_limits(limits)
+        };
+        let second = Some(std::time::Duration::from_millis(1000));
+
+        // A node past its limit fails without retrying; the run goes on
+        let spec = WorkflowSpec {
+            nodes: vec![delay("slow", 60_000), delay("quick", 1)],
+            ..Default::default()
+        };
+        let started = std::time::Instant::now();
+        let limits = TimeLimits {
+            execution: None,
+            node: second,
+        };
+        let record = run_dag_with_context(Uuid::new_v4(), &spec, ctx(limits)).await.unwrap();
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        assert_eq!((record.completed_nodes, record.failed_nodes), (1, 1));
+
+        // At the run limit, running nodes stop and later levels never start
+        let spec = WorkflowSpec {
+            nodes: vec![delay("quick", 1), delay("slow", 60_000), delay("after", 1)],
+            edges: vec![edge("quick", "slow"), edge("slow", "after")],
+            ..Default::default()
+        };
+        let execution_id = Uuid::new_v4();
+        let limits = TimeLimits {
+            execution: second,
+            node: None,
+        };
+        let result = run_dag_with_context(execution_id, &spec, ctx(limits)).await;
+        match result {
+            Err(RunnerError::PolicyViolation(message)) => assert!(message.contains("1 of 3"), "{message}"),
+            other => panic!("expected a policy violation, got {other:?}"),
+        }
+        store.flush().unwrap();
+        let record = store.query_execution(execution_id).unwrap().unwrap();
+        assert_eq!(record.status, ExecutionStatus::Failed);
+        let spans = store.query_spans_by_execution(execution_id).unwrap();
+        let status = |node: &str| spans.iter().find(|s| s.node_id == node).map(|s| s.status.clone());
+        assert_eq!(status("quick"), Some(ExecutionStatus::Completed));
+        assert_eq!(status("slow"), Some(ExecutionStatus::Failed));
+        assert_eq!(status("after"), None);
+    }
+
+    #[tokio::test]
+    async fn runs_that_exit_with_an_error_close_their_journal() {
+        let delay = |id: &str, ms: u64| {
+            NodeEntry::Primitive(NodeSpec {
+                id: id.into(),
+                tool_ref: "delay".into(),
+                config: serde_json::json!({ "delay_ms": ms }).as_object().unwrap().clone(),
+                ..Default::default()
+            })
+        };
+        let dir = std::env::temp_dir().join(format!("hb-journal-{}", Uuid::new_v4()));
+        let journal = Arc::new(ExecutionJournal::open(&dir).unwrap());
+        let spec = WorkflowSpec {
+            nodes: vec![delay("slow", 60_000)],
+            ..Default::default()
+        };
+
+        // Past the run's time limit
+        let ctx = ExecutionContext::default()
+            .with_journal(journal.clone())
+            .with_time_limits(TimeLimits {
+                execution: Some(std::time::Duration::from_millis(50)),
+                node: None,
+            });
+        let result = run_dag_with_context(Uuid::new_v4(), &spec, ctx).await;
+        assert!(matches!(result, Err(RunnerError::PolicyViolation(_))), "{result:?}");
+
+        // Cancelled before the first level
+        let ctx = ExecutionContext::default().with_journal(journal.clone());
+        ctx.cancelled.store(true, std::sync::atomic::Ordering::Relaxed);
+        let result = run_dag_with_context(Uuid::new_v4(), &spec, ctx).await;
+        assert!(matches!(result, Err(RunnerError::Cancelled)), "{result:?}");
+
+        assert!(journal.recover().unwrap().is_empty());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn empty_outputs_break_the_output_contract() {
+        let spec = |default_value: &str| WorkflowSpec {
+            meta: WorkflowMeta {
+                output_contract: vec![OutputAssertion {
+                    node_id: "ask".into(),
+                    port: "text".into(),
+                    port_type: Some(PortType::String),
+                    non_empty: true,
+                    schema: None,
+                }],
+                ..Default::default()
+            },
+            nodes: vec![NodeEntry::Primitive(NodeSpec {
+                id: "ask".into(),
+                tool_ref: "user-input".into(),
+                config: serde_json::json!({ "default_value": default_value }).as_object().unwrap().clone(),
+                ..Default::default()
+            })],
+            ..Default::default()
+        };
+        let store: Arc<dyn hb_trace::store::TraceStore> =
+            Arc::new(hb_trace::store::SqliteTraceStore::in_memory().unwrap());
+        let ctx = || ExecutionContext::default().with_trace_store(store.clone());
+
+        let record = run_dag_with_context(Uuid::new_v4(), &spec("Footing pour"), ctx()).await.unwrap();
+        assert_eq!(record.status, ExecutionStatus::Completed);
+
+        let execution_id = Uuid::new_v4();
+        let record = run_dag_with_context(execution_id, &spec(""), ctx()).await.unwrap();
+        assert_eq!(record.status, ExecutionStatus::CompletedWithViolations);
+        assert_eq!((record.completed_nodes, record.failed_nodes), (1, 0));
+        store.flush().unwrap();
+        let stored = store.query_execution(execution_id).unwrap().unwrap();
+        assert_eq!(stored.status, ExecutionStatus::CompletedWithViolations);
+        assert_eq!(stored.contract_violations, record.contract_violations);
+        assert_eq!(stored.contract_violations[0].message, "value is empty");
+    }
+
+    #[tokio::test]
+    async fn scheduler_metrics_show_nodes_queued_behind_the_limit() {
+        let delay = |id: &str| {
+            NodeEntry::Primitive(NodeSpec {
+                id: id.into(),
+                tool_ref: "delay".into(),
+                config: serde_json::json!({ "delay_ms": 30 }).as_object().unwrap().clone(),
+                ..Default::default()
+            })
+        };
+        let spec = WorkflowSpec {
+            nodes: vec![delay("a"), delay("b"), delay("c")],
+            ..Default::default()
+        };
+        let policy = hb_core::policy::ConcurrencyPolicy {
+            max_parallel: Some(1),
+            classes: BTreeMap::new(),
+        };
+        let store: Arc<dyn hb_trace::store::TraceStore> =
+            Arc::new(hb_trace::store::SqliteTraceStore::in_memory().unwrap());
+        let ctx = ExecutionContext::default()
+            .with_trace_store(store.clone())
+            .with_concurrency(ConcurrencyLimits::new(&policy, HashMap::new()));
+
+        let execution_id = Uuid::new_v4();
+        run_dag_with_context(execution_id, &spec, ctx).await.unwrap();
+        store.flush().unwrap();
+        let metrics = store.query_scheduler_metrics(execution_id).unwrap().unwrap();
+
+        assert_eq!(metrics.nodes.len(), 3);
+        assert_eq!((metrics.peak_running, metrics.capacity), (1, 1));
+        let mut queued: Vec<u64> = metrics.nodes.iter().map(|n| n.queued_ms).collect();
+        queued.sort_unstable();
+        assert!(queued[1] >= 30 && queued[2] >= 60, "{queued:?}");
+        assert!(metrics.wall_ms >= 90);
+        assert!(metrics.samples.iter().any(|s| s.ready == 2 && s.running == 1));
+        assert!(store.query_scheduler_metrics(Uuid::new_v4()).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn workflow_refs_run_the_referenced_graph_through_their_mappings() {
+        let primitive = |id: &str, tool_ref: &str, config: serde_json::Value| {
+            NodeEntry::Primitive(NodeSpec {
+                id: id.into(),
+                tool_ref: tool_ref.into(),
+                config: config.as_object().cloned().unwrap_or_default(),
+                ..Default::default()
+            })
+        };
+        let edge = |from: &str, from_port: &str, to: &str, to_port: &str| EdgeSpec {
+            id: format!("{from}-{to}"),
+            source_node: from.into(),
+            source_port: from_port.into(),
+            target_node: to.into(),
+            target_port: to_port.into(),
+            ..Default::default()
+        };
+        let mapping = |external: &str, node: &str, port: &str| PortMapping {
+            external_port: external.into(),
+            node: node.into(),
+            port: port.into(),
+        };
+        let reference = |id: &str, workflow_id: Uuid| {
+            NodeEntry::WorkflowRef(WorkflowRefSpec {
+                id: id.into(),
+                workflow_id,
+                input_ports: Vec::new(),
+                output_ports: Vec::new(),
+                input_mapping: vec![mapping("text", "show", "data")],
+                output_mapping: vec![mapping("result", "last", "data")],
+                position: None,
+                label: None,
+            })
+        };
+
+        let inner = WorkflowSpec {
+            nodes: vec![
+                primitive("show", "display-output", serde_json::json!({})),
+                primitive("last", "display-output", serde_json::json!({})),
+            ],
+            edges: vec![edge("show", "data", "last", "data")],
+            ..Default::default()
+        };
+        let outer = WorkflowSpec {
+            nodes: vec![
+                primitive("start", "user-input", serde_json::json!({ "default_value": "Footing" })),
+                reference("sub", inner.id),
+            ],
+            edges: vec![edge("start", "text", "sub", "text")],
+            ..Default::default()
+        };
+
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let ctx = ExecutionContext::default()
+            .with_status_callback(move |e: NodeStatusEvent| sink.lock().unwrap().push(e))
+            .with_workflows(HashMap::from([(inner.id, inner.clone())]));
+        let record = run_dag_with_context(Uuid::new_v4(), &outer, ctx).await.unwrap();
+        assert_eq!(record.status, ExecutionStatus::Completed);
+
+        let completed = |id: &str| {
+            events
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|e| e.node_id == id && e.status == "completed")
+                .and_then(|e| e.output.clone())
+        };
+        assert_eq!(completed("sub"), Some(serde_json::json!({ "result": "Footing" })));
+        assert!(completed("sub/last").is_some(), "inner nodes are reported under the reference");
+
+        // A workflow that ends up running itself is rejected before it starts
+        let mut looping = inner.clone();
+        looping.nodes.push(reference("again", inner.id));
+        let ctx = ExecutionContext::default().with_workflows(HashMap::from([(inner.id, looping)]));
+        let err = run_dag_with_context(Uuid::new_v4(), &outer, ctx).await.unwrap_err();
+        assert!(err.to_string().contains("workflow reference cycle"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn referenced_workflows_route_failures_to_their_error_edges() {
+        let primitive = |id: &str, tool_ref: &str| {
+            NodeEntry::Primitive(NodeSpec {
+                id: id.into(),
+                tool_ref: tool_ref.into(),
+                retry: Some(RetryPolicy {
+                    max_retries: 0,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+        };
+        let inner = WorkflowSpec {
+            nodes: vec![primitive("agent", "agent-task"), primitive("handler", "display-output")],
+            edges: vec![EdgeSpec {
+                id: "agent-handler".into(),
+                source_node: "agent".into(),
+                source_port: "result".into(),
+                target_node: "handler".into(),
+                target_port: "data".into(),
+                kind: EdgeKind::Error,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let outer = WorkflowSpec {
+            nodes: vec![NodeEntry::WorkflowRef(WorkflowRefSpec {
+                id: "sub".into(),
+                workflow_id: inner.id,
+                input_ports: Vec::new(),
+                output_ports: Vec::new(),
+                input_mapping: Vec::new(),
+                output_mapping: Vec::new(),
+                position: None,
+                label: None,
+            })],
+            ..Default::default()
+        };
+
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let ctx = ExecutionContext::default()
+            .with_status_callback(move |e: NodeStatusEvent| sink.lock().unwrap().push(e))
+            .with_agent_executor(|_params: AgentTaskParams| {
+                Box::pin(async { Err("LLM connection failed".to_string()) })
+            })
+            .with_workflows(HashMap::from([(inner.id, inner.clone())]));
+        let record = run_dag_with_context(Uuid::new_v4(), &outer, ctx).await.unwrap();
+        assert_eq!(record.status, ExecutionStatus::Completed);
+        let handled = events
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|e| e.node_id == "sub/handler" && e.status == "completed");
+        assert!(handled, "the inner handler ran");
+    }
+
+    #[tokio::test]
+    async fn error_edges_route_failures_to_handlers_and_the_run_goes_on() {
+        let primitive = |id: &str, tool_ref: &str| {
+            NodeEntry::Primitive(NodeSpec {
+                id: id.into(),
+                tool_ref: tool_ref.into(),
+                retry: Some(RetryPolicy {
+                    max_retries: 0,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+        };
+        let edge = |from: &str, to: &str, kind: EdgeKind| EdgeSpec {
+            id: format!("{from}-{to}"),
+            source_node: from.into(),
+            source_port: "result".into(),
+            target_node: to.into(),
+            target_port: "data".into(),
+            kind,
+            ..Default::default()
+        };
+        let spec = WorkflowSpec {
+            nodes: vec![
+                primitive("agent", "agent-task"),
+                primitive("after", "display-output"),
+                primitive("handler", "display-output"),
+                primitive("other", "display-output"),
+            ],
+            edges: vec![
+                edge("agent", "after", EdgeKind::Data),
+                edge("agent", "handler", EdgeKind::Error),
+            ],
+            ..Default::default()
+        };
+        let run = |fail: bool| {
+            let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let sink = events.clone();
+            let ctx = ExecutionContext::default()
+                .with_status_callback(move |e: NodeStatusEvent| sink.lock().unwrap().push(e))
+                .with_agent_executor(move |_params: AgentTaskParams| {
+                    Box::pin(async move {
+                        if fail {
+                            Err("LLM connection failed".to_string())
+                        } else {
+                            Ok(serde_json::json!({ "result": "done" }))
+                        }
+                    })
+                });
+            let spec = spec.clone();
+            async move {
+                let record = run_dag_with_context(Uuid::new_v4(), &spec, ctx).await.unwrap();
+                let statuses: HashMap<String, String> = events
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .map(|e| (e.node_id.clone(), e.status.clone()))
+                    .collect();
+                let handler_input = events
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .find(|e| e.node_id == "handler" && e.status == "completed")
+                    .and_then(|e| e.output.clone());
+                (record, statuses, handler_input)
+            }
+        };
+
+        // The failure goes to the handler, its data branch is skipped and the
+        // run completes even though fail-fast is on
+        let (record, statuses, handler_output) = run(true).await;
+        assert_eq!(record.status, ExecutionStatus::Completed);
+        assert_eq!((record.completed_nodes, record.failed_nodes), (2, 0));
+        assert_eq!(statuses["agent"], "failed");
+        assert_eq!(statuses["after"], "skipped");
+        assert_eq!(statuses["other"], "completed");
+        let handler_output = handler_output.expect("handler ran").to_string();
+        assert!(handler_output.contains("LLM connection failed"), "{handler_output}");
+
+        // Without a failure the handler has nothing to do
+        let (record, statuses, _) = run(false).await;
+        assert_eq!(record.status, ExecutionStatus::Completed);
+        assert_eq!((record.completed_nodes, record.failed_nodes), (3, 0));
+        assert_eq!(statuses["handler"], "skipped");
+        assert_eq!(statuses["after"], "completed");
+    }
+
+    #[tokio::test]
+    async fn untaken_condition_branches_are_skipped_downstream() {
+        let primitive = |id: &str, tool_ref: &str| {
+            NodeEntry::Primitive(NodeSpec {
+                id: id.into(),
+                tool_ref: tool_ref.into(),
+                ..Default::default()
+            })
+        };
+        let edge = |from: &str, port: &str, to: &str| EdgeSpec {
+            id: format!("{from}-{to}"),
+            source_node: from.into(),
+            source_port: port.into(),
+            target_node: to.into(),
+            target_port: "data".into(),
+            ..Default::default()
+        };
+        // gate tests a missing value, so only the false branch is taken
+        let spec = WorkflowSpec {
+            nodes: vec![
+                primitive("gate", "condition"),
+                primitive("yes", "display-output"),
+                primitive("yes_after", "display-output"),
+                primitive("no", "display-output"),
+            ],
+            edges: vec![
+                edge("gate", "true_out", "yes"),
+                edge("yes", "result", "yes_after"),
+                edge("gate", "false_out", "no"),
+            ],
+            ..Default::default()
+        };
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let ctx = ExecutionContext::default()
+            .with_status_callback(move |e: NodeStatusEvent| sink.lock().unwrap().push(e));
+        let record = run_dag_with_context(Uuid::new_v4(), &spec, ctx).await.unwrap();
+        let statuses: HashMap<String, String> = events
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|e| (e.node_id.clone(), e.status.clone()))
+            .collect();
+        assert_eq!(record.status, ExecutionStatus::Completed);
+        assert_eq!((record.completed_nodes, record.failed_nodes), (2, 0));
+        assert_eq!(statuses["yes"], "skipped");
+        assert_eq!(statuses["yes_after"], "skipped");
+        assert_eq!(statuses["no"], "completed");
+    }
+
+    #[test]
+    fn conditions_written_before_the_expression_engine_keep_their_meaning() {
+        let input = serde_json::json!({ "order-id": 7, "order": 10, "id": 3, "flag": null, "score": 0.9 });
+        // An exact field name wins over reading it as arithmetic
+        assert_eq!(evaluate_condition("order-id", &input).unwrap(), Some(7.into()));
+        assert_eq!(evaluate_condition("flag", &input).unwrap(), Some(serde_json::Value::Null));
+        assert_eq!(evaluate_condition("$.score", &input).unwrap(), Some(0.9.into()));
+        assert_eq!(
+            evaluate_condition(r#"{"a": 1}"#, &input).unwrap(),
+            Some(serde_json::json!({ "a": 1 }))
+        );
+        assert_eq!(evaluate_condition("missing", &input).unwrap(), None);
+        // New expressions work alongside
+        assert_eq!(evaluate_condition("score > 0.5", &input).unwrap(), Some(true.into()));
+        assert!(evaluate_condition("score >", &input).is_err());
+    }
+
+    #[tokio::test]
+    async fn invalid_expressions_are_rejected_before_the_run_starts() {
+        let spec = WorkflowSpec {
+            nodes: vec![NodeEntry::Conditional(ConditionalSpec {
+                id: "gate".into(),
+                kind: hb_core::graph::ConditionalKind::If,
+                condition_expr: "score >".into(),
+                branches: vec![],
+                default_branch: None,
+            })],
+            ..Default::default()
+        };
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let ctx = ExecutionContext::default()
+            .with_status_callback(move |e: NodeStatusEvent| sink.lock().unwrap().push(e));
+        let err = run_dag_with_context(Uuid::new_v4(), &spec, ctx).await.unwrap_err();
+        assert!(err.to_string().contains("'gate' condition_expr"), "{err}");
+        assert!(events.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn map_reduce_maps_each_shard_and_rejects_items_that_are_not_a_list() {
+        let show = |id: &str| {
+            NodeEntry::Primitive(NodeSpec {
+                id: id.into(),
+                tool_ref: "display-output".into(),
+                ..Default::default()
+            })
+        };
+        // The items are literals, as no run inputs reach a top-level node
+        let spec = |items: &str| WorkflowSpec {
+            nodes: vec![NodeEntry::MapReduce(MapReduceSpec {
+                id: "fan".into(),
+                items_expr: Some(items.into()),
+                shard_size: 2,
+                max_concurrency: 2,
+                map: SubgraphSpec {
+                    nodes: vec![show("each")],
+                    edges: vec![],
+                },
+                collect_expr: Some("each.displayed".into()),
+                reduce: Some(SubgraphSpec {
+                    nodes: vec![show("sum")],
+                    edges: vec![],
+                }),
+            })],
+            ..Default::default()
+        };
+        let store: Arc<dyn hb_trace::store::TraceStore> =
+            Arc::new(hb_trace::store::SqliteTraceStore::in_memory().unwrap());
+        let run = |items: &str| {
+            let ctx = ExecutionContext::default().with_trace_store(store.clone());
+            let (spec, store) = (spec(items), store.clone());
+            async move {
+                let execution_id = Uuid::new_v4();
+                let record = run_dag_with_context(execution_id, &spec, ctx).await.unwrap();
+                store.flush().unwrap();
+                let span = store
+                    .query_spans_by_execution(execution_id)
+                    .unwrap()
+                    .into_iter()
+                    .find(|s| s.node_id == "fan")
+                    .unwrap();
+                (record.status, span)
+            }
+        };
+
+        let (status, span) = run("[1, 2, 3]").await;
+        assert_eq!(status, ExecutionStatus::Completed);
+        let output = span.output_json.unwrap();
+        assert_eq!(output["shards"], 2);
+        assert_eq!(output["results"], serde_json::json!([true, true]));
+        assert_eq!(output["reduced"]["sum"]["displayed"], true);
+
+        let (status, span) = run(r#"{"a": 1}"#).await;
+        assert_eq!(status, ExecutionStatus::Failed);
+        assert_eq!(span.error.as_deref(), Some("map-reduce items must be an array, got an object"));
+        assert_eq!(span.output_json.unwrap()["reduced"], serde_json::Value::Null);
+    }
+
+    #[tokio::test]
+    async fn the_watchdog_stops_a_loop_that_never_ends() {
+        let body = NodeEntry::Primitive(NodeSpec {
+            id: "show".into(),
+            tool_ref: "display-output".into(),
+            ..Default::default()
+        });
+        let spec = WorkflowSpec {
+            nodes: vec![NodeEntry::Loop(LoopSpec {
+                id: "forever".into(),
+                kind: hb_core::graph::LoopKind::While,
+                body: SubgraphSpec {
+                    nodes: vec![body],
+                    edges: vec![],
+                },
+                max_iterations: u32::MAX,
+                condition_expr: None,
+                items_expr: None,
+                collect_expr: None,
+                aggregation: Default::default(),
+            })],
+            ..Default::default()
+        };
+        let store: Arc<dyn hb_trace::store::TraceStore> =
+            Arc::new(hb_trace::store::SqliteTraceStore::in_memory().unwrap());
+        let ctx = ExecutionContext::default()
+            .with_trace_store(store.clone())
+            .with_watchdog(WatchdogLimits {
+                max_node_executions: 50,
+                ..Default::default()
+            });
+
+        let execution_id = Uuid::new_v4();
+        let err = run_dag_with_context(execution_id, &spec, ctx).await.unwrap_err();
+        assert!(matches!(err, RunnerError::Watchdog(_)), "{err}");
+        assert!(err.to_string().contains("more than 50 node executions"), "{err}");
+
+        store.flush().unwrap();
+        let record = store.query_execution(execution_id).unwrap().unwrap();
+        assert_eq!(record.status, ExecutionStatus::Failed);
+        let metrics = store.query_scheduler_metrics(execution_id).unwrap().unwrap();
+        assert_eq!(metrics.loops.len(), 1);
+        assert_eq!(metrics.loops[0].node_id, "forever");
+        assert_eq!(metrics.loops[0].iterations, 50);
+    }
+
+    /// A composite from the bundled stdlib pack, with the inner nodes named
+    /// in `agents` (at any depth) running as agent tasks without retries, so
+    /// tests decide what they return.
+    fn stdlib_composite(id: &str, agents: &[&str]) -> NodeEntry {
+        fn swap(nodes: &mut [NodeEntry], agents: &[&str]) {
+            for node in nodes {
+                if let NodeEntry::Primitive(n) = node {
+                    if agents.contains(&n.id.as_str()) {
+                        n.tool_ref = "agent-task".into();
+                        n.retry = Some(RetryPolicy {
+                            max_retries: 0,
+                            ..Default::default()
+                        });
+                    }
+                }
+                for subgraph in node.subgraphs_mut() {
+                    swap(&mut subgraph.nodes, agents);
+                }
+            }
+        }
+        let path = format!("{}/../../packs/stdlib/composites/{id}.json", env!("CARGO_MANIFEST_DIR"));
+        let definition: hb_core::pack::CompositeDefinition =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        let mut node = NodeEntry::Composite(definition.instantiate(id));
+        for subgraph in node.subgraphs_mut() {
+            swap(&mut subgraph.nodes, agents);
+        }
+        node
+    }
+
+    #[tokio::test]
+    async fn stdlib_composites_run_as_documented() {
+        let ctx = Arc::new(ExecutionContext::default().with_agent_executor(|p: AgentTaskParams| {
+            Box::pin(async move {
+                match p.node_id.as_str() {
+                    "primary" => Err("rate limited".to_string()),
+                    "call" => Ok(serde_json::json!({ "items": [p.context["params"]["page"]] })),
+                    other => Ok(serde_json::json!({ "response": format!("{other} answered") })),
+                }
+            })
+        }));
+        let run = |node: NodeEntry, input: serde_json::Value| {
+            let ctx = ctx.clone();
+            async move {
+                let (span, output) = execute_node_entry(Uuid::new_v4(), node.id(), Some(&node), input, ctx)
+                    .await
+                    .unwrap();
+                assert_eq!(span.status, ExecutionStatus::Completed, "{:?}", span.error);
+                output
+            }
+        };
+
+        // Retry with fallback: the fallback answers when the primary fails
+        let output = run(
+            stdlib_composite("retry-with-fallback", &["primary", "fallback"]),
+            serde_json::json!({ "prompt": "hi" }),
+        )
+        .await;
+        assert_eq!(output["response"], "fallback answered");
+
+        // Cache-through: a miss asks the model and fills the cache, a hit reads it
+        let cache = std::env::temp_dir().join(format!("hb-cache-through-{}.txt", Uuid::new_v4()));
+        let input = serde_json::json!({ "cache_path": cache.to_string_lossy(), "prompt": "hi" });
+        let cache_through = stdlib_composite("cache-through", &["generate"]);
+        assert_eq!(run(cache_through.clone(), input.clone()).await["response"], "generate answered");
+        std::fs::write(&cache, "cached answer").unwrap();
+        assert_eq!(run(cache_through, input).await["response"], "cached answer");
+        let _ = std::fs::remove_file(&cache);
+
+        // Validate then branch: the value leaves through the side the rule picks
+        let mut validate = stdlib_composite("validate-then-branch", &[]);
+        if let NodeEntry::Composite(n) = &mut validate {
+            n.config.insert("rule".into(), "score >= 0.8".into());
+        }
+        let output = run(validate.clone(), serde_json::json!({ "value": { "score": 0.9 } })).await;
+        assert_eq!((output["valid"]["score"].clone(), output["invalid"].clone()), (0.9.into(), serde_json::Value::Null));
+        let output = run(validate, serde_json::json!({ "value": { "score": 0.2 } })).await;
+        assert_eq!(output["invalid"]["score"], 0.2);
+
+        // Batch API call: one call per request, results in request order,
+        // with config reaching the call inside the map body
+        let mut batch = stdlib_composite("batch-api-call", &["call"]);
+        let NodeEntry::Composite(n) = &mut batch else { unreachable!() };
+        n.config.insert("url".into(), "https://example.test/api".into());
+        let resolved = n.resolved_subgraph().unwrap();
+        let NodeEntry::MapReduce(batches) = &resolved.nodes[0] else { unreachable!() };
+        let NodeEntry::Composite(request) = &batches.map.nodes[0] else { unreachable!() };
+        let NodeEntry::Primitive(call) = &request.subgraph.nodes[0] else { unreachable!() };
+        assert_eq!(call.config["url"], "https://example.test/api");
+        let requests = serde_json::json!({ "requests": [{ "page": 1 }, { "page": 2 }, { "page": 3 }] });
+        assert_eq!(run(batch, requests).await["results"], serde_json::json!([[1], [2], [3]]));
+    }
 }
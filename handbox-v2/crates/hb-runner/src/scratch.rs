@@ -0,0 +1,131 @@
+//! Scratch directories — an isolated directory per execution for the
+//! intermediate files its tools write, so runs don't litter the user's
+//! folders or collide with each other.
+//!
+//! Tools see the directory as [`ToolInput::scratch_dir`](hb_tool_executor::ToolInput)
+//! and `scratch://` paths; processes they start get it as their temp
+//! directory. It is removed when the run ends, or kept after a failed run
+//! when configured so, until garbage collection prunes it.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use uuid::Uuid;
+
+/// Environment variables pointing processes at the scratch directory.
+const TEMP_VARS: [&str; 4] = ["HB_SCRATCH_DIR", "TMPDIR", "TEMP", "TMP"];
+
+#[derive(Debug, Clone)]
+pub struct ScratchSpace {
+    root: PathBuf,
+    keep_failed: bool,
+}
+
+impl ScratchSpace {
+    /// Scratch directories under `root`, one per execution ID.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            keep_failed: false,
+        }
+    }
+
+    /// Keep the directory of a run that did not complete, for debugging.
+    pub fn keep_failed(mut self, keep: bool) -> Self {
+        self.keep_failed = keep;
+        self
+    }
+
+    pub fn dir(&self, execution_id: Uuid) -> PathBuf {
+        self.root.join(execution_id.to_string())
+    }
+
+    /// Create the directory of `execution_id`. A resumed run gets back the
+    /// directory its failed attempt kept.
+    pub fn create(&self, execution_id: Uuid) -> std::io::Result<PathBuf> {
+        let dir = self.dir(execution_id);
+        std::fs::create_dir_all(&dir)?;
+        Ok(dir)
+    }
+
+    /// Clean up after `execution_id`; returns whether the directory was kept.
+    pub fn finish(&self, execution_id: Uuid, completed: bool) -> bool {
+        let dir = self.dir(execution_id);
+        if !completed && self.keep_failed {
+            tracing::info!("Kept scratch directory of failed execution: {}", dir.display());
+            return true;
+        }
+        if let Err(e) = std::fs::remove_dir_all(&dir) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!("Failed to remove scratch directory {}: {e}", dir.display());
+            }
+        }
+        false
+    }
+}
+
+/// `env` plus the variables that make processes use `dir` for temporary
+/// files.
+pub fn process_env(env: &BTreeMap<String, String>, dir: &Path) -> BTreeMap<String, String> {
+    let mut env = env.clone();
+    for name in TEMP_VARS {
+        env.insert(name.to_string(), dir.to_string_lossy().into_owned());
+    }
+    env
+}
+
+/// Remove scratch directories under `root` not touched for `max_age` — kept
+/// from failed runs, or left behind by a crash. Returns how many were
+/// removed and the bytes freed.
+pub fn prune(root: &Path, max_age: Duration) -> std::io::Result<(usize, u64)> {
+    let entries = match std::fs::read_dir(root) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((0, 0)),
+        Err(e) => return Err(e),
+    };
+    let now = SystemTime::now();
+    let (mut removed, mut freed) = (0, 0);
+    for entry in entries.flatten() {
+        let modified = entry.metadata().and_then(|m| m.modified()).unwrap_or(now);
+        if now.duration_since(modified).unwrap_or_default() < max_age {
+            continue;
+        }
+        let path = entry.path();
+        let size = crate::gc::disk_usage(&path);
+        if std::fs::remove_dir_all(&path).is_ok() {
+            removed += 1;
+            freed += size;
+        }
+    }
+    Ok((removed, freed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directories_are_removed_unless_kept_after_failure() {
+        let root = std::env::temp_dir().join(format!("hb-scratch-{}", Uuid::new_v4()));
+        let space = ScratchSpace::new(&root);
+        let (ok, failed) = (Uuid::new_v4(), Uuid::new_v4());
+
+        std::fs::write(space.create(ok).unwrap().join("part.json"), "{}").unwrap();
+        assert!(!space.finish(ok, true));
+        assert!(!space.dir(ok).exists());
+
+        space.create(failed).unwrap();
+        assert!(!space.finish(failed, false));
+        assert!(!space.dir(failed).exists());
+
+        let space = space.keep_failed(true);
+        space.create(failed).unwrap();
+        assert!(space.finish(failed, false));
+        assert!(space.dir(failed).exists());
+        assert!(!space.finish(ok, true), "completed runs are never kept");
+
+        assert_eq!(prune(&root, Duration::from_secs(3600)).unwrap().0, 0);
+        assert_eq!(prune(&root, Duration::ZERO).unwrap().0, 1);
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,295 @@
+//! Shutdown Coordinator — cancel in-flight executions on app exit and record them
+//! as interrupted so they can be offered for resumption on the next launch.
+
+use crate::RunnerError;
+use chrono::{DateTime, Utc};
+use hb_trace::store::TraceStore;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// How often `shutdown` polls for executions to drain.
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// An execution currently tracked by the coordinator.
+#[derive(Debug, Clone)]
+struct ActiveExecution {
+    workflow_id: Uuid,
+    started_at: DateTime<Utc>,
+    cancelled: Arc<AtomicBool>,
+}
+
+/// Outcome of a coordinated shutdown.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct ShutdownReport {
+    /// Executions that outlived the grace period and were recorded as
+    /// interrupted.
+    pub interrupted: Vec<Uuid>,
+    /// Executions that were still running when the grace period elapsed.
+    pub timed_out: Vec<Uuid>,
+    /// Whether the trace store was flushed successfully.
+    pub trace_flushed: bool,
+}
+
+/// Tracks running executions and tears them down in an orderly way on exit.
+#[derive(Default)]
+pub struct ShutdownCoordinator {
+    active: Mutex<HashMap<Uuid, ActiveExecution>>,
+    shutting_down: AtomicBool,
+    trace_store: Mutex<Option<Arc<dyn TraceStore>>>,
+}
+
+impl ShutdownCoordinator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the trace store used to record interrupted executions. The
+    /// coordinator lets go of it at the end of `shutdown`, so the owner can
+    /// close it.
+    pub fn set_trace_store(&self, store: Arc<dyn TraceStore>) {
+        if let Ok(mut guard) = self.trace_store.lock() {
+            *guard = Some(store);
+        }
+    }
+
+    /// Whether a shutdown has been initiated.
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
+
+    /// Register a running execution. Refused once shutdown has started.
+    pub fn register(
+        &self,
+        execution_id: Uuid,
+        workflow_id: Uuid,
+        cancelled: Arc<AtomicBool>,
+    ) -> Result<(), RunnerError> {
+        if self.is_shutting_down() {
+            return Err(RunnerError::Scheduling(
+                "application is shutting down".into(),
+            ));
+        }
+        let mut active = self
+            .active
+            .lock()
+            .map_err(|_| RunnerError::Scheduling("Lock poisoned".into()))?;
+        active.insert(
+            execution_id,
+            ActiveExecution {
+                workflow_id,
+                started_at: Utc::now(),
+                cancelled,
+            },
+        );
+        Ok(())
+    }
+
+    /// Remove an execution that finished (successfully or not).
+    pub fn complete(&self, execution_id: Uuid) {
+        if let Ok(mut active) = self.active.lock() {
+            active.remove(&execution_id);
+        }
+    }
+
+    /// IDs of all executions currently tracked.
+    pub fn active_executions(&self) -> Vec<Uuid> {
+        self.active
+            .lock()
+            .map(|a| a.keys().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Cancel every running execution, wait up to `grace` for them to stop,
+    /// record those still running as interrupted, and flush the trace store.
+    pub async fn shutdown(&self, grace: Duration) -> ShutdownReport {
+        self.shutting_down.store(true, Ordering::SeqCst);
+
+        let snapshot: Vec<(Uuid, ActiveExecution)> = self
+            .active
+            .lock()
+            .map(|a| a.iter().map(|(id, e)| (*id, e.clone())).collect())
+            .unwrap_or_default();
+
+        for (id, exec) in &snapshot {
+            tracing::info!(
+                "Shutdown: cancelling execution {id} (workflow {}, started {})",
+                exec.workflow_id,
+                exec.started_at
+            );
+            exec.cancelled.store(true, Ordering::SeqCst);
+        }
+
+        // Give executions a chance to observe the flag and stop on their own.
+        let deadline = tokio::time::Instant::now() + grace;
+        while !self.active_executions().is_empty() && tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+        }
+
+        let mut report = ShutdownReport {
+            timed_out: self.active_executions(),
+            ..Default::default()
+        };
+
+        // Only runs that outlived the grace period are interrupted; the rest
+        // stopped on their own and recorded how they ended.
+        let store = self.trace_store.lock().ok().and_then(|mut g| g.take());
+        for (id, exec) in &snapshot {
+            if !report.timed_out.contains(id) {
+                continue;
+            }
+            let Some(ref store) = store else {
+                report.interrupted.push(*id);
+                continue;
+            };
+            match store.mark_execution_interrupted(*id) {
+                Ok(true) => report.interrupted.push(*id),
+                // Finished just after the deadline — keep its status
+                Ok(false) if !matches!(store.query_execution(*id), Ok(None)) => {}
+                Ok(false) => {
+                    // Never reached the scheduler — record it from scratch.
+                    let record = hb_core::trace::ExecutionRecord {
+                        execution_id: *id,
+                        workflow_id: exec.workflow_id,
+                        started_at: exec.started_at,
+                        completed_at: Some(Utc::now()),
+                        status: hb_core::trace::ExecutionStatus::Interrupted,
+                        total_nodes: 0,
+                        completed_nodes: 0,
+                        failed_nodes: 0,
+                        cache_hits: 0,
+                        workspace_id: None,
+                        manifest: Default::default(),
+                        contract_violations: Vec::new(),
+                    };
+                    match store.upsert_execution(&record) {
+                        Ok(()) => report.interrupted.push(*id),
+                        Err(e) => {
+                            tracing::warn!("Failed to record interrupted execution {id}: {e}")
+                        }
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to mark execution {id} interrupted: {e}");
+                }
+            }
+        }
+
+        if let Some(ref store) = store {
+            match store.flush() {
+                Ok(()) => report.trace_flushed = true,
+                Err(e) => tracing::warn!("Failed to flush trace store on shutdown: {e}"),
+            }
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hb_core::trace::{ExecutionRecord, ExecutionStatus};
+    use hb_trace::store::SqliteTraceStore;
+
+    #[tokio::test]
+    async fn shutdown_cancels_and_records_interrupted() {
+        let store = Arc::new(SqliteTraceStore::in_memory().unwrap());
+        let coordinator = Arc::new(ShutdownCoordinator::new());
+        coordinator.set_trace_store(store.clone());
+
+        let record = |execution_id, status| ExecutionRecord {
+            execution_id,
+            workflow_id: Uuid::new_v4(),
+            started_at: Utc::now(),
+            completed_at: None,
+            status,
+            total_nodes: 1,
+            completed_nodes: 0,
+            failed_nodes: 0,
+            cache_hits: 0,
+            workspace_id: None,
+            manifest: Default::default(),
+            contract_violations: Vec::new(),
+        };
+
+        // Stops once cancelled, recording how it ended
+        let drained = Uuid::new_v4();
+        let drained_flag = Arc::new(AtomicBool::new(false));
+        coordinator
+            .register(drained, Uuid::new_v4(), drained_flag.clone())
+            .unwrap();
+        store
+            .upsert_execution(&record(drained, ExecutionStatus::Running))
+            .unwrap();
+        let worker = {
+            let coordinator = coordinator.clone();
+            let store = store.clone();
+            let flag = drained_flag.clone();
+            tokio::spawn(async move {
+                while !flag.load(Ordering::SeqCst) {
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                }
+                store
+                    .upsert_execution(&record(drained, ExecutionStatus::Cancelled))
+                    .unwrap();
+                coordinator.complete(drained);
+            })
+        };
+
+        // Ignores the flag
+        let stuck = Uuid::new_v4();
+        let stuck_flag = Arc::new(AtomicBool::new(false));
+        coordinator
+            .register(stuck, Uuid::new_v4(), stuck_flag.clone())
+            .unwrap();
+        store
+            .upsert_execution(&record(stuck, ExecutionStatus::Running))
+            .unwrap();
+
+        let report = coordinator.shutdown(Duration::from_millis(300)).await;
+        worker.await.unwrap();
+
+        assert!(drained_flag.load(Ordering::SeqCst));
+        assert!(stuck_flag.load(Ordering::SeqCst));
+        assert_eq!(report.interrupted, vec![stuck]);
+        assert_eq!(report.timed_out, vec![stuck]);
+        assert!(report.trace_flushed);
+
+        let status = |id| store.query_execution(id).unwrap().unwrap().status;
+        assert_eq!(status(drained), ExecutionStatus::Cancelled);
+        assert_eq!(status(stuck), ExecutionStatus::Interrupted);
+
+        // The coordinator let go of the store, so it can be closed
+        let store = Arc::try_unwrap(store).ok().expect("coordinator still holds the store");
+        store.close().unwrap();
+    }
+
+    #[tokio::test]
+    async fn timed_out_execution_without_a_record_is_recorded() {
+        let store = Arc::new(SqliteTraceStore::in_memory().unwrap());
+        let coordinator = ShutdownCoordinator::new();
+        coordinator.set_trace_store(store.clone());
+        let exec_id = Uuid::new_v4();
+        coordinator
+            .register(exec_id, Uuid::new_v4(), Arc::new(AtomicBool::new(false)))
+            .unwrap();
+
+        let report = coordinator.shutdown(Duration::from_millis(10)).await;
+        assert_eq!(report.interrupted, vec![exec_id]);
+        let record = store.query_execution(exec_id).unwrap().unwrap();
+        assert_eq!(record.status, ExecutionStatus::Interrupted);
+    }
+
+    #[tokio::test]
+    async fn register_refused_after_shutdown() {
+        let coordinator = ShutdownCoordinator::new();
+        let report = coordinator.shutdown(Duration::from_millis(10)).await;
+        assert!(report.interrupted.is_empty());
+        assert!(coordinator
+            .register(Uuid::new_v4(), Uuid::new_v4(), Arc::new(AtomicBool::new(false)))
+            .is_err());
+    }
+}
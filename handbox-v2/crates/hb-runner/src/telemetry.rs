@@ -0,0 +1,233 @@
+//! Scheduler telemetry — when each node became ready, got a slot and
+//! finished, summed up per execution as [`SchedulerMetrics`] in the trace
+//! store.
+//!
+//! A node is ready once the scheduler dispatches its level and running once
+//! it holds its concurrency slot, so queue time is time spent waiting on
+//! [`ConcurrencyLimits`](crate::ConcurrencyLimits). Only top-level nodes are
+//! tracked; the bodies of composite, loop and map-reduce nodes count as part
+//! of their parent's run time.
+
+use hb_core::trace::{LoopTiming, NodeTiming, QueueSample, SchedulerMetrics};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+#[derive(Debug, Default)]
+pub struct SchedulerTelemetry {
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// When the first node became ready.
+    origin: Option<Instant>,
+    nodes: HashMap<String, Timeline>,
+    /// Node IDs in the order they became ready.
+    order: Vec<String>,
+    samples: Vec<QueueSample>,
+    ready: u32,
+    running: u32,
+    peak_running: u32,
+    loops: Vec<LoopTiming>,
+}
+
+#[derive(Debug)]
+struct Timeline {
+    level: u32,
+    ready_at: Duration,
+    started_at: Option<Duration>,
+    finished_at: Option<Duration>,
+}
+
+impl SchedulerTelemetry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `node_id` of DAG level `level` waits for a slot.
+    pub fn ready(&self, node_id: &str, level: u32) {
+        self.update(|state, now| {
+            let timeline = Timeline {
+                level,
+                ready_at: now,
+                started_at: None,
+                finished_at: None,
+            };
+            if state.nodes.insert(node_id.to_string(), timeline).is_none() {
+                state.order.push(node_id.to_string());
+            }
+            state.ready += 1;
+        });
+    }
+
+    /// `node_id` got its slot and runs. Nodes that were never ready, such as
+    /// the children of a composite node, are ignored.
+    pub fn started(&self, node_id: &str) {
+        self.update(|state, now| {
+            let Some(timeline) = state.nodes.get_mut(node_id).filter(|t| t.started_at.is_none()) else {
+                return;
+            };
+            timeline.started_at = Some(now);
+            state.ready -= 1;
+            state.running += 1;
+            state.peak_running = state.peak_running.max(state.running);
+        });
+    }
+
+    /// `node_id` finished, or was stopped before it got a slot.
+    pub fn finished(&self, node_id: &str) {
+        self.update(|state, now| {
+            let Some(timeline) = state.nodes.get_mut(node_id).filter(|t| t.finished_at.is_none()) else {
+                return;
+            };
+            timeline.finished_at = Some(now);
+            if timeline.started_at.is_some() {
+                state.running -= 1;
+            } else {
+                state.ready -= 1;
+            }
+        });
+    }
+
+    /// Metrics of the execution so far. `max_parallel` is the overall
+    /// concurrency limit, if any.
+    pub fn metrics(&self, execution_id: Uuid, max_parallel: Option<usize>) -> SchedulerMetrics {
+        let Ok(state) = self.state.lock() else {
+            return SchedulerMetrics {
+                execution_id,
+                ..Default::default()
+            };
+        };
+        let elapsed = state.origin.map(|o| o.elapsed()).unwrap_or_default();
+        let mut widths: HashMap<u32, u32> = HashMap::new();
+        let mut wall = Duration::ZERO;
+        let mut busy = Duration::ZERO;
+        let nodes: Vec<NodeTiming> = state
+            .order
+            .iter()
+            .filter_map(|id| Some((id, state.nodes.get(id)?)))
+            .map(|(id, t)| {
+                *widths.entry(t.level).or_default() += 1;
+                let finished_at = t.finished_at.unwrap_or(elapsed);
+                let started_at = t.started_at.unwrap_or(finished_at);
+                wall = wall.max(finished_at);
+                busy += finished_at.saturating_sub(started_at);
+                NodeTiming {
+                    node_id: id.clone(),
+                    level: t.level,
+                    queued_ms: millis(started_at.saturating_sub(t.ready_at)),
+                    run_ms: millis(finished_at.saturating_sub(started_at)),
+                }
+            })
+            .collect();
+
+        let widest = widths.values().copied().max().unwrap_or(0);
+        let capacity = match max_parallel {
+            Some(limit) => widest.min(limit as u32),
+            None => widest,
+        };
+        let average_parallelism = if wall.is_zero() {
+            0.0
+        } else {
+            busy.as_secs_f64() / wall.as_secs_f64()
+        };
+        let utilization = if capacity == 0 {
+            0.0
+        } else {
+            (average_parallelism / capacity as f64).min(1.0)
+        };
+        SchedulerMetrics {
+            execution_id,
+            wall_ms: millis(wall),
+            samples: state.samples.clone(),
+            nodes,
+            peak_running: state.peak_running,
+            average_parallelism,
+            capacity,
+            utilization,
+            loops: state.loops.clone(),
+        }
+    }
+
+    /// Loop node `node_id`, at any depth, finished after `iterations` of at
+    /// most `max_iterations`.
+    pub fn loop_finished(&self, node_id: &str, iterations: u32, max_iterations: u32, run: Duration) {
+        if let Ok(mut state) = self.state.lock() {
+            state.loops.push(LoopTiming {
+                node_id: node_id.to_string(),
+                iterations,
+                max_iterations,
+                run_ms: millis(run),
+            });
+        }
+    }
+
+    /// Apply `change` at the current time and sample the queue after it.
+    fn update(&self, change: impl FnOnce(&mut State, Duration)) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        let now = match state.origin {
+            Some(origin) => origin.elapsed(),
+            None => {
+                state.origin = Some(Instant::now());
+                Duration::ZERO
+            }
+        };
+        change(&mut state, now);
+        let sample = QueueSample {
+            at_ms: millis(now),
+            ready: state.ready,
+            running: state.running,
+        };
+        // Changes within the same millisecond share one sample
+        match state.samples.last_mut() {
+            Some(last) if last.at_ms == sample.at_ms => *last = sample,
+            _ => state.samples.push(sample),
+        }
+    }
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queue_time_and_parallelism_follow_the_slots() {
+        let telemetry = SchedulerTelemetry::new();
+        for id in ["a", "b", "c"] {
+            telemetry.ready(id, 0);
+        }
+        telemetry.started("a");
+        telemetry.started("b");
+        telemetry.started("child-of-b");
+        std::thread::sleep(Duration::from_millis(30));
+        telemetry.finished("a");
+        telemetry.started("c");
+        std::thread::sleep(Duration::from_millis(30));
+        telemetry.finished("b");
+        telemetry.finished("c");
+
+        let metrics = telemetry.metrics(Uuid::nil(), Some(2));
+        assert_eq!(metrics.peak_running, 2);
+        assert_eq!(metrics.capacity, 2);
+        let nodes: Vec<_> = metrics.nodes.iter().map(|n| n.node_id.as_str()).collect();
+        assert_eq!(nodes, ["a", "b", "c"]);
+        assert!(metrics.nodes[0].queued_ms < 30);
+        assert!(metrics.nodes[2].queued_ms >= 30, "c waited for a's slot");
+        assert!(metrics.nodes[1].run_ms >= 60);
+        // Two slots busy for the whole run
+        assert!(metrics.average_parallelism > 1.5, "{}", metrics.average_parallelism);
+        assert!(metrics.utilization > 0.75 && metrics.utilization <= 1.0);
+
+        let last = metrics.samples.last().unwrap();
+        assert_eq!((last.ready, last.running), (0, 0));
+        assert!(metrics.samples.iter().any(|s| s.ready == 1));
+    }
+}
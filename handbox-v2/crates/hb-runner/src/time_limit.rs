@@ -0,0 +1,122 @@
+//! Time limits — the policy's wall-clock caps on a whole execution and on
+//! each tool node (`CostLimit::max_execution_time_secs` and
+//! `max_node_time_secs`).
+//!
+//! A node past its limit is stopped and fails without further retries. At
+//! the execution limit the nodes still running are stopped, none are
+//! started, and the run ends with a policy violation; what completed stays
+//! in the trace store. The execution limit does not run while the execution
+//! is paused.
+//!
+//! Stopping a node drops its work: process tools are killed and async tools
+//! stop at their next await. Synchronous native tools run on the blocking
+//! pool, so the node still fails on time, but their thread can't be
+//! interrupted and runs on until the tool returns.
+
+use crate::pause::PauseSignal;
+use hb_core::policy::CostLimit;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimeLimits {
+    pub execution: Option<Duration>,
+    pub node: Option<Duration>,
+}
+
+impl From<&CostLimit> for TimeLimits {
+    fn from(limit: &CostLimit) -> Self {
+        let secs = |limit: Option<u64>| limit.filter(|s| *s > 0).map(Duration::from_secs);
+        Self {
+            execution: secs(limit.max_execution_time_secs),
+            node: secs(limit.max_node_time_secs),
+        }
+    }
+}
+
+impl TimeLimits {
+    pub fn is_limited(&self) -> bool {
+        self.execution.is_some() || self.node.is_some()
+    }
+}
+
+/// When an execution runs out of time. Time spent paused does not count,
+/// so a resumed run still has what it had left.
+#[derive(Debug, Clone)]
+pub struct Deadline {
+    at: Instant,
+    pause: Arc<PauseSignal>,
+}
+
+impl Deadline {
+    /// `limit` from now, stopped while `pause` holds the execution.
+    pub fn new(limit: Duration, pause: Arc<PauseSignal>) -> Self {
+        Self {
+            at: Instant::now() + limit,
+            pause,
+        }
+    }
+
+    /// The deadline with the pauses so far added.
+    fn current(&self) -> Instant {
+        self.at + self.pause.paused_for()
+    }
+
+    pub fn passed(&self) -> bool {
+        !self.pause.is_paused() && Instant::now() >= self.current()
+    }
+}
+
+/// Resolves once `deadline` has passed, or never without one.
+pub async fn reached(deadline: Option<&Deadline>) {
+    let Some(deadline) = deadline else {
+        return std::future::pending().await;
+    };
+    let never = AtomicBool::new(false);
+    loop {
+        deadline.pause.wait(&never).await;
+        tokio::time::sleep_until(deadline.current()).await;
+        // A pause in the meantime moved it
+        if deadline.passed() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_and_missing_limits_are_no_limit() {
+        let limit = CostLimit {
+            max_execution_time_secs: Some(0),
+            max_node_time_secs: Some(30),
+            max_cost_usd: None,
+            max_tokens: None,
+        };
+        let limits = TimeLimits::from(&limit);
+        assert_eq!(limits.execution, None);
+        assert_eq!(limits.node, Some(Duration::from_secs(30)));
+        assert!(limits.is_limited());
+        assert!(!TimeLimits::default().is_limited());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn time_spent_paused_does_not_count() {
+        let pause = Arc::new(PauseSignal::new());
+        let deadline = Deadline::new(Duration::from_secs(10), pause.clone());
+        tokio::time::advance(Duration::from_secs(4)).await;
+        pause.pause();
+        tokio::time::advance(Duration::from_secs(60)).await;
+        assert!(!deadline.passed());
+
+        pause.resume();
+        let resumed = Instant::now();
+        reached(Some(&deadline)).await;
+        assert_eq!(resumed.elapsed(), Duration::from_secs(6));
+        assert!(deadline.passed());
+    }
+}
@@ -0,0 +1,153 @@
+//! Execution watchdog — global safeguards against runs that would never
+//! end: a cap on node executions per run (every loop iteration, map shard
+//! and inner node counts), a cap on how deep workflow references nest, and
+//! detection of a reference chain that comes back to a workflow it is
+//! already running.
+//!
+//! A tripped watchdog fails the node that tripped it, stops loops and
+//! levels from starting more work, and ends the run with
+//! [`RunnerError::Watchdog`](crate::RunnerError::Watchdog) naming the node,
+//! instead of letting a While loop whose condition never turns false fill
+//! the trace store.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchdogLimits {
+    /// Node executions per run, at any depth.
+    pub max_node_executions: u64,
+    /// Workflow references running inside one another.
+    pub max_workflow_depth: usize,
+}
+
+impl Default for WatchdogLimits {
+    fn default() -> Self {
+        Self {
+            max_node_executions: 100_000,
+            max_workflow_depth: 8,
+        }
+    }
+}
+
+/// One execution's watchdog, shared by every node of the run.
+#[derive(Debug, Default)]
+pub struct Watchdog {
+    limits: WatchdogLimits,
+    executions: AtomicU64,
+    /// Why the run was stopped; set once.
+    tripped: OnceLock<String>,
+}
+
+impl Watchdog {
+    pub fn new(limits: WatchdogLimits) -> Self {
+        Self {
+            limits,
+            ..Default::default()
+        }
+    }
+
+    pub fn limits(&self) -> WatchdogLimits {
+        self.limits
+    }
+
+    /// Node executions counted so far.
+    pub fn executions(&self) -> u64 {
+        self.executions.load(Ordering::Relaxed)
+    }
+
+    /// Why the run was stopped, once the watchdog tripped.
+    pub fn tripped(&self) -> Option<&str> {
+        self.tripped.get().map(String::as_str)
+    }
+
+    /// Count `node_id` starting. Fails once the run is over its cap or the
+    /// watchdog already tripped.
+    pub fn node_started(&self, node_id: &str) -> Result<(), String> {
+        if let Some(reason) = self.tripped() {
+            return Err(reason.to_string());
+        }
+        let count = self.executions.fetch_add(1, Ordering::Relaxed) + 1;
+        if count > self.limits.max_node_executions {
+            return Err(self.trip(format!(
+                "watchdog stopped the run at '{node_id}': more than {} node executions, \
+                 likely a loop that never ends",
+                self.limits.max_node_executions
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check that `node_id` may run `workflow` from inside `chain`, the
+    /// workflows already running around it (outermost first, by ID and name).
+    pub fn enter_workflow(
+        &self,
+        node_id: &str,
+        chain: &[(Uuid, String)],
+        workflow: Uuid,
+        name: &str,
+    ) -> Result<(), String> {
+        if let Some(start) = chain.iter().position(|(id, _)| *id == workflow) {
+            let names: Vec<&str> = chain[start..]
+                .iter()
+                .map(|(_, n)| n.as_str())
+                .chain([name])
+                .collect();
+            return Err(self.trip(format!(
+                "watchdog stopped the run at '{node_id}': workflow reference recursion {}",
+                names.join(" → ")
+            )));
+        }
+        if chain.len() >= self.limits.max_workflow_depth {
+            return Err(self.trip(format!(
+                "watchdog stopped the run at '{node_id}': workflow references nest deeper than {}",
+                self.limits.max_workflow_depth
+            )));
+        }
+        Ok(())
+    }
+
+    /// Stop the run for `reason`; the first reason is the one kept.
+    fn trip(&self, reason: String) -> String {
+        let reason = self.tripped.get_or_init(|| reason).clone();
+        tracing::warn!("{reason}");
+        reason
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trips_on_the_execution_cap_and_reference_recursion() {
+        let watchdog = Watchdog::new(WatchdogLimits {
+            max_node_executions: 2,
+            max_workflow_depth: 2,
+        });
+        assert!(watchdog.node_started("a").is_ok());
+        assert!(watchdog.node_started("b").is_ok());
+        let reason = watchdog.node_started("loop/body").unwrap_err();
+        assert!(reason.contains("'loop/body'") && reason.contains("more than 2"), "{reason}");
+        // Once tripped, nothing else starts and the first reason stays
+        assert_eq!(watchdog.node_started("c").unwrap_err(), reason);
+        assert_eq!(watchdog.tripped(), Some(reason.as_str()));
+
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let chain = vec![(a, "A".to_string()), (b, "B".to_string())];
+        let fresh = Watchdog::default();
+        assert!(fresh.enter_workflow("ref", &chain[..1], b, "B").is_ok());
+        let reason = fresh.enter_workflow("ref/ref", &chain, a, "A").unwrap_err();
+        assert!(reason.ends_with("A → B → A"), "{reason}");
+
+        let shallow = Watchdog::new(WatchdogLimits {
+            max_workflow_depth: 2,
+            ..Default::default()
+        });
+        let reason = shallow
+            .enter_workflow("x", &chain, Uuid::new_v4(), "C")
+            .unwrap_err();
+        assert!(reason.contains("deeper than 2"), "{reason}");
+    }
+}
@@ -0,0 +1,181 @@
+//! Workflow references — nodes that run another saved workflow by ID
+//! ([`WorkflowRefSpec`](hb_core::graph::WorkflowRefSpec)).
+//!
+//! The runner does not load workflows itself: the caller gathers every
+//! workflow a run can reach with [`collect`] and hands them over through
+//! [`ExecutionContext::with_workflows`](crate::ExecutionContext::with_workflows),
+//! so it also decides which workflows a run may reach. A referenced workflow
+//! runs like the top-level one: pauses, error edges, concurrency slots and
+//! memoized chains all apply inside it.
+//! [`check`] rejects a run whose references are missing or loop back on
+//! themselves before any node starts.
+
+use hb_core::graph::{CompositeNodeSpec, NodeEntry, SubgraphSpec, WorkflowSpec};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Every workflow `root` references, directly or through other referenced
+/// workflows. IDs `lookup` cannot find are left out; [`check`] reports them.
+pub fn collect(
+    root: &WorkflowSpec,
+    lookup: impl Fn(Uuid) -> Option<WorkflowSpec>,
+) -> HashMap<Uuid, WorkflowSpec> {
+    let mut library = HashMap::new();
+    let mut pending = root.workflow_refs();
+    while let Some(id) = pending.pop() {
+        if library.contains_key(&id) {
+            continue;
+        }
+        if let Some(spec) = lookup(id) {
+            pending.extend(spec.workflow_refs());
+            library.insert(id, spec);
+        }
+    }
+    library
+}
+
+/// Check that every workflow `root` reaches is in `library` and that none
+/// of them, `root` included, ends up referencing itself.
+pub fn check(root: &WorkflowSpec, library: &HashMap<Uuid, WorkflowSpec>) -> Result<(), String> {
+    fn visit<'a>(
+        spec: &'a WorkflowSpec,
+        library: &'a HashMap<Uuid, WorkflowSpec>,
+        path: &mut Vec<&'a WorkflowSpec>,
+        done: &mut Vec<Uuid>,
+    ) -> Result<(), String> {
+        if let Some(start) = path.iter().position(|s| s.id == spec.id) {
+            let names: Vec<String> = path[start..]
+                .iter()
+                .chain([&spec])
+                .map(|s| name(s))
+                .collect();
+            return Err(format!("workflow reference cycle: {}", names.join(" → ")));
+        }
+        if done.contains(&spec.id) {
+            return Ok(());
+        }
+        path.push(spec);
+        for id in spec.workflow_refs() {
+            let referenced = library.get(&id).ok_or_else(|| {
+                format!(
+                    "workflow '{}' references workflow {id}, which was not found",
+                    name(spec)
+                )
+            })?;
+            visit(referenced, library, path, done)?;
+        }
+        path.pop();
+        done.push(spec.id);
+        Ok(())
+    }
+    visit(root, library, &mut Vec::new(), &mut Vec::new())
+}
+
+/// `spec` with every reference replaced by a composite node holding the
+/// referenced graph, so policy checks and cost estimates see the nodes that
+/// will actually run. References missing from `library` stay as they are;
+/// call [`check`] first so there are no cycles.
+pub fn expand(spec: &WorkflowSpec, library: &HashMap<Uuid, WorkflowSpec>) -> WorkflowSpec {
+    fn expand_nodes(nodes: &mut [NodeEntry], library: &HashMap<Uuid, WorkflowSpec>) {
+        for node in nodes {
+            if let NodeEntry::WorkflowRef(n) = node {
+                let Some(referenced) = library.get(&n.workflow_id) else {
+                    continue;
+                };
+                *node = NodeEntry::Composite(CompositeNodeSpec {
+                    id: n.id.clone(),
+                    subgraph: SubgraphSpec {
+                        nodes: referenced.nodes.clone(),
+                        edges: referenced.edges.clone(),
+                    },
+                    input_ports: n.input_ports.clone(),
+                    output_ports: n.output_ports.clone(),
+                    input_mapping: n.input_mapping.clone(),
+                    output_mapping: n.output_mapping.clone(),
+                    exposed_config: Vec::new(),
+                    config: Default::default(),
+                    position: n.position,
+                    label: n.label.clone(),
+                    presentation: Default::default(),
+                });
+            }
+            for subgraph in node.subgraphs_mut() {
+                expand_nodes(&mut subgraph.nodes, library);
+            }
+        }
+    }
+    let mut expanded = spec.clone();
+    expand_nodes(&mut expanded.nodes, library);
+    expanded
+}
+
+fn name(spec: &WorkflowSpec) -> String {
+    if spec.meta.name.is_empty() {
+        spec.id.to_string()
+    } else {
+        spec.meta.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hb_core::graph::WorkflowRefSpec;
+
+    fn workflow(name: &str, refs: &[Uuid]) -> WorkflowSpec {
+        let mut spec = WorkflowSpec {
+            id: Uuid::new_v4(),
+            nodes: refs
+                .iter()
+                .enumerate()
+                .map(|(i, id)| {
+                    NodeEntry::WorkflowRef(WorkflowRefSpec {
+                        id: format!("ref{i}"),
+                        workflow_id: *id,
+                        input_ports: Vec::new(),
+                        output_ports: Vec::new(),
+                        input_mapping: Vec::new(),
+                        output_mapping: Vec::new(),
+                        position: None,
+                        label: None,
+                    })
+                })
+                .collect(),
+            ..Default::default()
+        };
+        spec.meta.name = name.into();
+        spec
+    }
+
+    #[test]
+    fn missing_and_cyclic_references_are_rejected() {
+        let leaf = workflow("leaf", &[]);
+        let mid = workflow("mid", &[leaf.id]);
+        let root = workflow("root", &[mid.id, leaf.id]);
+        let saved = [leaf.clone(), mid.clone()];
+        let library = collect(&root, |id| saved.iter().find(|s| s.id == id).cloned());
+        assert_eq!(library.len(), 2);
+        assert_eq!(check(&root, &library), Ok(()));
+
+        let expanded = expand(&root, &library);
+        let NodeEntry::Composite(c) = &expanded.nodes[0] else {
+            panic!("reference not expanded");
+        };
+        assert!(matches!(c.subgraph.nodes[0], NodeEntry::Composite(_)));
+
+        let err = check(&root, &HashMap::new()).unwrap_err();
+        assert!(err.contains("not found"), "{err}");
+
+        // root → mid → loop → mid
+        let mut looping = workflow("loop", &[]);
+        let mid = workflow("mid", &[looping.id]);
+        looping.nodes = workflow("", &[mid.id]).nodes;
+        let root = workflow("root", &[mid.id]);
+        let saved = [mid.clone(), looping.clone()];
+        let library = collect(&root, |id| saved.iter().find(|s| s.id == id).cloned());
+        assert_eq!(
+            check(&root, &library),
+            Err("workflow reference cycle: mid → loop → mid".into())
+        );
+    }
+}
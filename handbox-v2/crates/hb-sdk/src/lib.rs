@@ -0,0 +1,639 @@
+//! hb-sdk: the Handbox engine for embedding in other Rust applications.
+//!
+//! An [`Engine`] owns what the desktop app keeps in its data directory — the
+//! project store, the trace store, the node output cache and the tool packs —
+//! and runs workflows against them the way the app does. Everything an
+//! embedder needs is re-exported here, so depending on `hb-sdk` alone is
+//! enough; the internal crates behind it may change between releases, this
+//! API follows semver.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), hb_sdk::SdkError> {
+//! let engine = hb_sdk::Engine::open("./handbox-data")?;
+//! let spec = engine.load_workflow("summarize.json")?;
+//! let issues = engine.validate(&spec);
+//! assert!(issues.iter().all(|i| i.severity != hb_sdk::Severity::Error));
+//! let record = engine.execute(&spec, hb_sdk::RunOptions::default()).await?;
+//! for span in engine.spans(record.execution_id)? {
+//!     println!("{} {:?}", span.node_id, span.status);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub use hb_compiler::CompilerError;
+pub use hb_core::graph::validate::ValidateOptions;
+pub use hb_core::graph::{
+    Severity, SpecIssue, SpecMigrator, UpgradeError, UpgradedSpec, WorkflowSpec, SPEC_VERSION,
+};
+pub use hb_core::project::WorkspaceConfig;
+pub use hb_core::tool::ToolInterface;
+pub use hb_core::trace::{ExecutionRecord, ExecutionStatus, NodeSpan};
+pub use hb_mcp::registry::ToolRegistry;
+pub use hb_policy::PolicyError;
+pub use hb_project::{ProjectError, ProjectStore};
+pub use hb_runner::{
+    ExecutionContext, NodeStatusEvent, RunnerError, StatusCallback, WatchdogLimits,
+};
+pub use hb_trace::store::TraceStore;
+pub use hb_trace::TraceError;
+
+use hb_core::project::WorkspaceContext;
+use hb_core::tool::RuntimeSpec;
+use hb_project::ProjectManager;
+use hb_runner::{ConcurrencyLimits, ExecutionCache, ScratchSpace, TimeLimits};
+use hb_tool_executor::CommandGate;
+use hb_trace::store::SqliteTraceStore;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Debug, Error)]
+pub enum SdkError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Project(#[from] ProjectError),
+    #[error(transparent)]
+    Trace(#[from] TraceError),
+    #[error(transparent)]
+    Runner(#[from] RunnerError),
+    #[error(transparent)]
+    Compiler(#[from] CompilerError),
+    #[error(transparent)]
+    Policy(#[from] PolicyError),
+    #[error(transparent)]
+    Upgrade(#[from] UpgradeError),
+    #[error("packs: {0}")]
+    Packs(String),
+    #[error("invalid workflow: {}", first_error(.0))]
+    Invalid(Vec<SpecIssue>),
+    #[error("workflow reference: {0}")]
+    WorkflowRef(String),
+    #[error("not found: {0}")]
+    NotFound(String),
+}
+
+fn first_error(issues: &[SpecIssue]) -> String {
+    issues
+        .iter()
+        .find(|i| i.severity == Severity::Error)
+        .map(ToString::to_string)
+        .unwrap_or_default()
+}
+
+impl From<SdkError> for hb_core::error::AppError {
+    fn from(e: SdkError) -> Self {
+        use hb_core::error::{AppError, ErrorCode};
+        match e {
+            SdkError::Project(e) => e.into(),
+            SdkError::Trace(e) => e.into(),
+            SdkError::Runner(e) => e.into(),
+            SdkError::Compiler(e) => e.into(),
+            SdkError::Policy(e) => e.into(),
+            SdkError::Invalid(_) | SdkError::Upgrade(_) | SdkError::WorkflowRef(_) => {
+                AppError::new(ErrorCode::InvalidInput, e.to_string())
+            }
+            SdkError::NotFound(_) => AppError::new(ErrorCode::NotFound, e.to_string()),
+            SdkError::Io(_) | SdkError::Packs(_) => {
+                AppError::new(ErrorCode::Internal, e.to_string())
+            }
+        }
+    }
+}
+
+/// How [`Engine::execute`] runs a workflow.
+#[derive(Clone, Default)]
+pub struct RunOptions {
+    /// Workspace whose environment variables, root path, data source
+    /// policy, time limits and concurrency limits apply.
+    pub workspace_id: Option<Uuid>,
+    /// Receives every node status event as the run progresses.
+    pub status_callback: Option<StatusCallback>,
+    /// Caps on node executions and workflow reference nesting (the
+    /// runner's defaults when unset).
+    pub watchdog: Option<WatchdogLimits>,
+}
+
+impl RunOptions {
+    pub fn in_workspace(mut self, workspace_id: Uuid) -> Self {
+        self.workspace_id = Some(workspace_id);
+        self
+    }
+
+    pub fn on_status<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(NodeStatusEvent) + Send + Sync + 'static,
+    {
+        self.status_callback = Some(Arc::new(callback));
+        self
+    }
+
+    pub fn with_watchdog(mut self, limits: WatchdogLimits) -> Self {
+        self.watchdog = Some(limits);
+        self
+    }
+}
+
+/// The Handbox engine: stores, tools and saved workflows in one place.
+pub struct Engine {
+    data_dir: Option<PathBuf>,
+    projects: Box<dyn ProjectStore>,
+    traces: Arc<dyn TraceStore>,
+    cache: Option<Arc<ExecutionCache>>,
+    tools: ToolRegistry,
+    /// Workflows that workflow reference nodes may run, by ID.
+    workflows: HashMap<Uuid, WorkflowSpec>,
+}
+
+impl Engine {
+    /// Open (or create) a data directory laid out like the app's:
+    /// `projects.db`, `traces.db`, `cache.db`, `scratch/`, and tool packs
+    /// under `packs/` when present.
+    pub fn open(data_dir: impl AsRef<Path>) -> Result<Self, SdkError> {
+        let data_dir = data_dir.as_ref();
+        std::fs::create_dir_all(data_dir)?;
+        let mut engine = Self {
+            data_dir: Some(data_dir.to_path_buf()),
+            projects: Box::new(ProjectManager::open(&data_dir.join("projects.db"))?),
+            traces: Arc::new(SqliteTraceStore::open(&data_dir.join("traces.db"))?),
+            cache: Some(Arc::new(ExecutionCache::new(&data_dir.join("cache.db"))?)),
+            tools: ToolRegistry::new(),
+            workflows: HashMap::new(),
+        };
+        let packs = data_dir.join("packs");
+        if packs.is_dir() {
+            engine.load_packs(packs)?;
+        }
+        Ok(engine)
+    }
+
+    /// An engine that keeps nothing on disk, e.g. for tests.
+    pub fn in_memory() -> Result<Self, SdkError> {
+        Ok(Self {
+            data_dir: None,
+            projects: Box::new(ProjectManager::in_memory()?),
+            traces: Arc::new(SqliteTraceStore::in_memory()?),
+            cache: None,
+            tools: ToolRegistry::new(),
+            workflows: HashMap::new(),
+        })
+    }
+
+    pub fn data_dir(&self) -> Option<&Path> {
+        self.data_dir.as_deref()
+    }
+
+    /// The project store, for workspaces, environment variables, activity
+    /// and reviews.
+    pub fn projects(&self) -> &dyn ProjectStore {
+        self.projects.as_ref()
+    }
+
+    pub fn workspaces(&self) -> Result<Vec<WorkspaceConfig>, SdkError> {
+        Ok(self.projects.list_workspaces()?)
+    }
+
+    pub fn workspace(&self, id: Uuid) -> Result<WorkspaceConfig, SdkError> {
+        Ok(self.projects.get_workspace(id)?)
+    }
+
+    /// Register the tools of every pack under `packs_dir`. Returns how many
+    /// were loaded; a tool already registered is replaced.
+    pub fn load_packs(&mut self, packs_dir: impl AsRef<Path>) -> Result<usize, SdkError> {
+        let packs_dir = packs_dir.as_ref().to_string_lossy();
+        let tools = hb_mcp::registry::scan_packs(&packs_dir).map_err(SdkError::Packs)?;
+        let count = tools.len();
+        for tool in tools {
+            self.tools.register(tool);
+        }
+        Ok(count)
+    }
+
+    pub fn register_tool(&mut self, tool: ToolInterface) {
+        self.tools.register(tool);
+    }
+
+    pub fn tools(&self) -> &ToolRegistry {
+        &self.tools
+    }
+
+    /// Look up a tool by ID, with or without an `@version` suffix.
+    pub fn tool(&self, tool_ref: &str) -> Option<&ToolInterface> {
+        self.tools
+            .get(tool_ref.split('@').next().unwrap_or(tool_ref))
+    }
+
+    /// Read a workflow file of any supported spec version, upgraded to the
+    /// current one.
+    pub fn load_workflow(&self, path: impl AsRef<Path>) -> Result<WorkflowSpec, SdkError> {
+        self.parse_workflow(&std::fs::read_to_string(path)?)
+    }
+
+    /// [`Engine::load_workflow`] for JSON already in memory.
+    pub fn parse_workflow(&self, json: &str) -> Result<WorkflowSpec, SdkError> {
+        let upgraded = WorkflowSpec::from_json(json)?;
+        if !upgraded.applied.is_empty() {
+            tracing::info!(
+                "Upgraded workflow {} from spec {}: {}",
+                upgraded.spec.id,
+                upgraded.from_version,
+                upgraded.applied.join("; ")
+            );
+        }
+        Ok(upgraded.spec)
+    }
+
+    /// Make `spec` available to workflow reference nodes of later runs.
+    pub fn add_workflow(&mut self, spec: WorkflowSpec) {
+        self.workflows.insert(spec.id, spec);
+    }
+
+    pub fn workflow(&self, id: Uuid) -> Option<&WorkflowSpec> {
+        self.workflows.get(&id)
+    }
+
+    /// Compile a natural-language prompt into a workflow.
+    pub async fn compile(&self, prompt: &str) -> Result<WorkflowSpec, SdkError> {
+        Ok(hb_compiler::compile(prompt).await?)
+    }
+
+    /// Lint `spec` against the registered tools' ports.
+    pub fn validate(&self, spec: &WorkflowSpec) -> Vec<SpecIssue> {
+        let tool_ports = |tool_ref: &str| {
+            self.tool(tool_ref).map(|tool| {
+                (
+                    tool.input_schema.ports.clone(),
+                    tool.output_schema.ports.clone(),
+                )
+            })
+        };
+        hb_core::graph::validate::validate(
+            spec,
+            ValidateOptions {
+                tool_ports: Some(&tool_ports),
+                values: None,
+            },
+        )
+    }
+
+    /// Run `spec` to completion. Specs with validation errors are refused
+    /// before any node runs; spans and the record go to the trace store.
+    pub async fn execute(
+        &self,
+        spec: &WorkflowSpec,
+        options: RunOptions,
+    ) -> Result<ExecutionRecord, SdkError> {
+        let issues = self.validate(spec);
+        if issues.iter().any(|i| i.severity == Severity::Error) {
+            return Err(SdkError::Invalid(issues));
+        }
+        let ctx = self.context(spec, &options)?;
+        Ok(hb_runner::execute_with_context(spec, ctx).await?)
+    }
+
+    /// The execution context [`Engine::execute`] runs `spec` with, for
+    /// embedders that add to it and call the runner themselves.
+    pub fn context(
+        &self,
+        spec: &WorkflowSpec,
+        options: &RunOptions,
+    ) -> Result<ExecutionContext, SdkError> {
+        let library = hb_runner::workflow_ref::collect(spec, |id| self.workflows.get(&id).cloned());
+        hb_runner::workflow_ref::check(spec, &library).map_err(SdkError::WorkflowRef)?;
+
+        let mut ctx = ExecutionContext::default()
+            .with_trace_store(Arc::clone(&self.traces))
+            .with_workflows(library.clone());
+        if let Some(cache) = &self.cache {
+            ctx = ctx.with_cache(Arc::clone(cache));
+        }
+        if let Some(dir) = &self.data_dir {
+            ctx = ctx.with_scratch(ScratchSpace::new(dir.join("scratch")));
+        }
+        if let Some(callback) = &options.status_callback {
+            let callback = Arc::clone(callback);
+            ctx = ctx.with_status_callback(move |event| callback(event));
+        }
+        if let Some(limits) = options.watchdog {
+            ctx = ctx.with_watchdog(limits);
+        }
+
+        let pure_tools = self
+            .tools
+            .list()
+            .into_iter()
+            .collect();
+        ctx = ctx.with_pure_tools(pure_tools);
+        let variadic_ports = self
+            .tools
+            .list()
+            .into_iter()
+            .collect();
+        ctx = ctx.with_variadic_ports(variadic_ports);
+        let runtimes = self
+            .tools
+            .list()
+            .into_iter()
+            .filter(|tool| !matches!(tool.runtime, RuntimeSpec::Native))
+            .map(|tool| (tool.tool_id.clone(), tool.runtime.clone()))
+            .collect();
+        ctx = ctx.with_tool_runtimes(runtimes);
+
+        let Some(workspace_id) = options.workspace_id else {
+            return Ok(ctx);
+        };
+        let workspace = self.projects.get_workspace(workspace_id)?;
+        ctx = ctx
+            .with_env(self.projects.env_map(workspace_id)?)
+            .with_workspace(WorkspaceContext::from(&workspace));
+        if let Some(policy) = &workspace.default_policy {
+            let expanded = hb_runner::workflow_ref::expand(spec, &library);
+            hb_policy::engine::check_data_sources(
+                &policy.sensitive_data,
+                &expanded,
+                &workspace.data_sources,
+            )?;
+            let tool_classes = self
+                .tools
+                .list()
+                .into_iter()
+                .filter_map(|tool| Some((tool.tool_id.clone(), tool.resource_class()?.to_string())))
+                .collect();
+            // Headless: allowlisted programs run, nobody is asked about others
+            ctx = ctx
+                .with_command_gate(CommandGate::new(policy.commands.clone()))
+                .with_liveness(policy.liveness.clone())
+                .with_time_limits(TimeLimits::from(&policy.cost_limit))
+                .with_concurrency(ConcurrencyLimits::new(&policy.concurrency, tool_classes));
+            if policy.edge_capture.enabled {
+                ctx = ctx.with_edge_capture(policy.edge_capture.clone());
+            }
+        }
+        Ok(ctx)
+    }
+
+    /// The trace store, for queries beyond the ones below.
+    pub fn traces(&self) -> &Arc<dyn TraceStore> {
+        &self.traces
+    }
+
+    pub fn execution(&self, execution_id: Uuid) -> Result<ExecutionRecord, SdkError> {
+        self.traces
+            .query_execution(execution_id)?
+            .ok_or_else(|| SdkError::NotFound(format!("execution {execution_id}")))
+    }
+
+    /// The most recent executions of a workflow, newest first.
+    pub fn executions(
+        &self,
+        workflow_id: Uuid,
+        limit: usize,
+    ) -> Result<Vec<ExecutionRecord>, SdkError> {
+        Ok(self
+            .traces
+            .query_executions_by_workflow(workflow_id, limit)?)
+    }
+
+    /// The node spans of an execution.
+    pub fn spans(&self, execution_id: Uuid) -> Result<Vec<NodeSpan>, SdkError> {
+        Ok(self.traces.query_spans_by_execution(execution_id)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hb_core::graph::{NodeEntry, NodeSpec};
+    use std::sync::Mutex;
+
+    fn node(id: &str, tool_ref: &str) -> NodeEntry {
+        NodeEntry::Primitive(NodeSpec {
+            id: id.into(),
+            tool_ref: tool_ref.into(),
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn an_embedded_engine_loads_validates_runs_and_traces() {
+        let mut engine = Engine::in_memory().unwrap();
+        let packs = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../packs");
+        assert!(engine.load_packs(packs).unwrap() > 0);
+        assert!(engine.tool("core-tools/llm-chat@1.0.0").is_some());
+
+        let spec = WorkflowSpec {
+            nodes: vec![node("show", "core-tools/display-output")],
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&spec).unwrap();
+        let spec = engine.parse_workflow(&json).unwrap();
+
+        let events = Arc::new(Mutex::new(0));
+        let seen = Arc::clone(&events);
+        let options = RunOptions::default().on_status(move |_| *seen.lock().unwrap() += 1);
+        let record = engine.execute(&spec, options).await.unwrap();
+        assert_eq!(record.status, ExecutionStatus::Completed);
+        assert!(*events.lock().unwrap() > 0);
+
+        engine.traces().flush().unwrap();
+        assert_eq!(
+            engine.execution(record.execution_id).unwrap().workflow_id,
+            spec.id
+        );
+        assert_eq!(engine.executions(spec.id, 10).unwrap().len(), 1);
+        assert!(engine
+            .spans(record.execution_id)
+            .unwrap()
+            .iter()
+            .any(|s| s.node_id == "show"));
+
+        // Broken specs are refused before anything runs
+        let broken = WorkflowSpec {
+            nodes: vec![node("a", "x"), node("a", "y")],
+            ..Default::default()
+        };
+        let err = engine
+            .execute(&broken, RunOptions::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SdkError::Invalid(_)), "{err}");
+    }
+
+    fn repo_packs() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../../packs")
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("hb-sdk-{name}-{}", Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn an_opened_data_directory_loads_its_packs_and_keeps_runs() {
+        let dir = temp_dir("open");
+        // A pack with one tool, laid out like the app's packs/
+        let tools = dir.join("packs/site/tools");
+        std::fs::create_dir_all(&tools).unwrap();
+        let display = std::fs::read_to_string(repo_packs().join("core-tools/tools/display-output.json")).unwrap();
+        let mut tool: serde_json::Value = serde_json::from_str(&display).unwrap();
+        tool["tool_id"] = "site/display-output".into();
+        std::fs::write(tools.join("display-output.json"), tool.to_string()).unwrap();
+
+        let engine = Engine::open(&dir).unwrap();
+        assert_eq!(engine.data_dir(), Some(dir.as_path()));
+        assert!(engine.tool("site/display-output@1.0.0").is_some());
+        let spec = WorkflowSpec {
+            nodes: vec![node("show", "site/display-output")],
+            ..Default::default()
+        };
+        let record = engine.execute(&spec, RunOptions::default()).await.unwrap();
+        engine.traces().flush().unwrap();
+        for file in ["projects.db", "traces.db", "cache.db"] {
+            assert!(dir.join(file).exists(), "{file}");
+        }
+        drop(engine);
+
+        let engine = Engine::open(&dir).unwrap();
+        assert_eq!(engine.execution(record.execution_id).unwrap().status, ExecutionStatus::Completed);
+        assert_eq!(engine.spans(record.execution_id).unwrap().len(), 1);
+        drop(engine);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn loading_packs_registers_their_tools() {
+        let mut engine = Engine::in_memory().unwrap();
+        assert!(engine.tool("core-tools/file-read").is_none());
+        let loaded = engine.load_packs(repo_packs()).unwrap();
+        assert_eq!(loaded, engine.tools().list().len());
+        assert!(engine.tool("core-tools/file-read").is_some());
+        assert!(engine.tool("core-tools/file-read@1.0.0").is_some());
+
+        let err = engine.load_packs(temp_dir("missing")).unwrap_err();
+        assert!(matches!(err, SdkError::Packs(_)), "{err}");
+        assert_eq!(engine.tools().list().len(), loaded);
+    }
+
+    #[tokio::test]
+    async fn compiled_specs_validate_and_broken_ones_are_refused() {
+        let mut engine = Engine::in_memory().unwrap();
+        engine.load_packs(repo_packs()).unwrap();
+
+        let spec = engine.compile("이 문서를 요약해줘").await.unwrap();
+        assert!(!spec.nodes.is_empty());
+        let errors: Vec<_> = engine
+            .validate(&spec)
+            .into_iter()
+            .filter(|i| i.severity == Severity::Error)
+            .collect();
+        assert!(errors.is_empty(), "{errors:?}");
+
+        // An edge into a port the tool doesn't have
+        let broken = WorkflowSpec {
+            nodes: vec![node("ask", "core-tools/user-input"), node("show", "core-tools/display-output")],
+            edges: vec![hb_core::graph::EdgeSpec {
+                id: "ask-show".into(),
+                source_node: "ask".into(),
+                source_port: "text".into(),
+                target_node: "show".into(),
+                target_port: "nope".into(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let issues = engine.validate(&broken);
+        assert!(
+            issues.iter().any(|i| i.severity == Severity::Error && i.to_string().contains("nope")),
+            "{issues:?}"
+        );
+        match engine.execute(&broken, RunOptions::default()).await {
+            Err(SdkError::Invalid(refused)) => assert_eq!(refused.len(), issues.len()),
+            other => panic!("expected the spec to be refused, got {other:?}"),
+        }
+        assert!(engine.executions(broken.id, 10).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn runs_in_a_workspace_see_its_environment() {
+        let mut engine = Engine::in_memory().unwrap();
+        engine.load_packs(repo_packs()).unwrap();
+        let now = chrono::Utc::now();
+        let workspace = WorkspaceConfig {
+            id: Uuid::new_v4(),
+            name: "현장 A".into(),
+            description: None,
+            root_path: String::new(),
+            created_at: now,
+            updated_at: now,
+            data_sources: vec![],
+            indexes: vec![],
+            default_policy: None,
+            llm_providers: vec![],
+            timezone: None,
+            members: vec![],
+            review_policy: None,
+        };
+        let id = engine.projects().create_workspace(&workspace).unwrap();
+        engine
+            .projects()
+            .set_env(
+                id,
+                &hb_core::project::EnvVar {
+                    name: "SITE".into(),
+                    value: "세종 3공구".into(),
+                    description: None,
+                    updated_at: now,
+                },
+            )
+            .unwrap();
+        assert_eq!(engine.workspaces().unwrap().len(), 1);
+
+        let mut ask = node("ask", "core-tools/user-input");
+        if let NodeEntry::Primitive(spec) = &mut ask {
+            spec.config.insert("default_value".into(), "{{env:SITE}}".into());
+        }
+        let spec = WorkflowSpec {
+            nodes: vec![ask],
+            ..Default::default()
+        };
+        let record = engine.execute(&spec, RunOptions::default().in_workspace(id)).await.unwrap();
+        engine.traces().flush().unwrap();
+        let spans = engine.spans(record.execution_id).unwrap();
+        assert_eq!(spans[0].output_json.as_ref().unwrap()["text"], "세종 3공구");
+
+        let err = engine
+            .execute(&spec, RunOptions::default().in_workspace(Uuid::new_v4()))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SdkError::Project(ProjectError::NotFound(_))), "{err}");
+    }
+
+    #[tokio::test]
+    async fn trace_queries_list_runs_newest_first() {
+        let mut engine = Engine::in_memory().unwrap();
+        engine.load_packs(repo_packs()).unwrap();
+        let spec = WorkflowSpec {
+            nodes: vec![node("show", "core-tools/display-output")],
+            ..Default::default()
+        };
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            ids.push(engine.execute(&spec, RunOptions::default()).await.unwrap().execution_id);
+        }
+        engine.traces().flush().unwrap();
+
+        let recent: Vec<Uuid> = engine
+            .executions(spec.id, 2)
+            .unwrap()
+            .iter()
+            .map(|r| r.execution_id)
+            .collect();
+        assert_eq!(recent, [ids[2], ids[1]]);
+        assert!(engine.executions(Uuid::new_v4(), 10).unwrap().is_empty());
+        let missing = Uuid::new_v4();
+        assert!(matches!(engine.execution(missing), Err(SdkError::NotFound(_))));
+        assert!(engine.spans(missing).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,133 @@
+//! Bedrock model discovery — which models the account can invoke, and which
+//! of them need an inference profile, per region from the Bedrock control
+//! plane. The catalog is cached and feeds the routing in
+//! [`hb_tool_executor::bedrock`]; when discovery fails the routing falls
+//! back to its built-in list of on-demand models.
+
+use crate::commands::llm::sign_aws_request_with_query;
+pub use hb_tool_executor::bedrock::{needs_profile, resolve, BedrockCatalog, ModelTarget};
+use hb_tool_executor::bedrock::parse_catalog;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+/// How long a discovered catalog is trusted.
+const CATALOG_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// How long to wait before retrying a failed discovery.
+const RETRY_AFTER: Duration = Duration::from_secs(10 * 60);
+
+struct CacheEntry {
+    region: String,
+    at: Instant,
+    catalog: Option<BedrockCatalog>,
+}
+
+static CATALOG: RwLock<Option<CacheEntry>> = RwLock::new(None);
+
+/// Drop the cached catalog, e.g. after the credentials changed.
+pub fn clear_catalog() {
+    if let Ok(mut cache) = CATALOG.write() {
+        *cache = None;
+    }
+}
+
+/// The catalog for `region`, discovering it if the cache is stale. `None`
+/// when discovery failed recently.
+pub async fn catalog(access_key: &str, secret_key: &str, region: &str) -> Option<BedrockCatalog> {
+    if let Ok(cache) = CATALOG.read() {
+        if let Some(entry) = cache.as_ref().filter(|e| e.region == region) {
+            let ttl = if entry.catalog.is_some() {
+                CATALOG_TTL
+            } else {
+                RETRY_AFTER
+            };
+            if entry.at.elapsed() < ttl {
+                return entry.catalog.clone();
+            }
+        }
+    }
+
+    let catalog = match discover(access_key, secret_key, region).await {
+        Ok(catalog) => Some(catalog),
+        Err(e) => {
+            tracing::warn!("Bedrock model discovery failed in {region}: {e}");
+            None
+        }
+    };
+    if let Ok(mut cache) = CATALOG.write() {
+        *cache = Some(CacheEntry {
+            region: region.to_string(),
+            at: Instant::now(),
+            catalog: catalog.clone(),
+        });
+    }
+    catalog
+}
+
+/// List the text models and system-defined inference profiles visible to the
+/// account in `region`.
+async fn discover(
+    access_key: &str,
+    secret_key: &str,
+    region: &str,
+) -> Result<BedrockCatalog, String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(15))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let models = control_plane_get(
+        &client,
+        access_key,
+        secret_key,
+        region,
+        "/foundation-models",
+        "byOutputModality=TEXT",
+    )
+    .await?;
+    // One page covers every system-defined profile in a region
+    let profiles = control_plane_get(
+        &client,
+        access_key,
+        secret_key,
+        region,
+        "/inference-profiles",
+        "maxResults=1000",
+    )
+    .await?;
+
+    Ok(parse_catalog(&models, &profiles))
+}
+
+async fn control_plane_get(
+    client: &reqwest::Client,
+    access_key: &str,
+    secret_key: &str,
+    region: &str,
+    path: &str,
+    query: &str,
+) -> Result<serde_json::Value, String> {
+    let host = format!("bedrock.{region}.amazonaws.com");
+    let (authorization, amz_date, payload_hash, _) = sign_aws_request_with_query(
+        access_key, secret_key, region, "bedrock", "GET", &host, path, query, b"",
+    );
+    let response = client
+        .get(format!("https://{host}{path}?{query}"))
+        .header("X-Amz-Date", amz_date)
+        .header("X-Amz-Content-Sha256", payload_hash)
+        .header("Authorization", authorization)
+        .header("Accept", "application/json")
+        .send()
+        .await
+        .map_err(|e| format!("HTTP request failed: {e}"))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("HTTP {status}: {body}"));
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Response parse failed: {e}"))
+}
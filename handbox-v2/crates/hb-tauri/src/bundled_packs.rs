@@ -0,0 +1,126 @@
+//! Packs that ship inside the app. They are written into the packs
+//! directory on launch when missing or older than the bundled release, so
+//! their content is there without the user installing anything. A newer
+//! release the user installed is kept.
+
+use crate::pack_content;
+use std::fs;
+use std::path::Path;
+
+/// A pack bundled with the app: its ID and files, by path in the pack.
+struct BundledPack {
+    id: &'static str,
+    files: &'static [(&'static str, &'static str)],
+}
+
+const STDLIB: BundledPack = BundledPack {
+    id: "stdlib",
+    files: &[
+        (
+            "manifest.json",
+            include_str!("../../../packs/stdlib/manifest.json"),
+        ),
+        (
+            "composites/retry-with-fallback.json",
+            include_str!("../../../packs/stdlib/composites/retry-with-fallback.json"),
+        ),
+        (
+            "composites/cache-through.json",
+            include_str!("../../../packs/stdlib/composites/cache-through.json"),
+        ),
+        (
+            "composites/validate-then-branch.json",
+            include_str!("../../../packs/stdlib/composites/validate-then-branch.json"),
+        ),
+        (
+            "composites/batch-api-call.json",
+            include_str!("../../../packs/stdlib/composites/batch-api-call.json"),
+        ),
+    ],
+};
+
+const BUNDLED: [BundledPack; 1] = [STDLIB];
+
+/// Install the bundled packs that are missing from `packs_dir` or older
+/// there, and return the IDs installed.
+pub fn install(packs_dir: &Path) -> Result<Vec<String>, String> {
+    let installed = crate::pack_updater::installed_versions(packs_dir);
+    let mut written = Vec::new();
+    for pack in &BUNDLED {
+        let version = bundled_version(pack)?;
+        if installed
+            .iter()
+            .any(|(id, v)| id == pack.id && *v >= version)
+        {
+            continue;
+        }
+        let target = packs_dir.join(pack.id);
+        if target.exists() {
+            fs::remove_dir_all(&target)
+                .map_err(|e| format!("Failed to remove {}: {e}", target.display()))?;
+        }
+        for (file, content) in pack.files {
+            let path = target.join(file);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            fs::write(&path, content)
+                .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+        }
+        pack_content::sync(packs_dir, pack.id)?;
+        tracing::info!("Installed bundled pack '{}' {version}", pack.id);
+        written.push(pack.id.to_string());
+    }
+    Ok(written)
+}
+
+fn bundled_version(pack: &BundledPack) -> Result<semver::Version, String> {
+    let manifest = pack
+        .files
+        .iter()
+        .find(|(file, _)| *file == "manifest.json")
+        .ok_or_else(|| format!("Bundled pack '{}' has no manifest", pack.id))?;
+    let manifest: serde_json::Value =
+        serde_json::from_str(manifest.1).map_err(|e| e.to_string())?;
+    let version = manifest
+        .get("version")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default();
+    semver::Version::parse(version).map_err(|e| format!("Bundled pack '{}': {e}", pack.id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hb_core::pack::PackContentKind;
+
+    #[test]
+    fn bundled_packs_install_once_and_keep_newer_releases() {
+        let root = std::env::temp_dir().join(format!("hb-bundled-{}", uuid::Uuid::new_v4()));
+        let packs = root.join("packs");
+        fs::create_dir_all(&packs).unwrap();
+
+        assert_eq!(install(&packs).unwrap(), vec!["stdlib"]);
+        let composites = pack_content::list(&packs, Some(PackContentKind::Composite));
+        let ids: Vec<_> = composites.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            [
+                "retry-with-fallback",
+                "cache-through",
+                "validate-then-branch",
+                "batch-api-call"
+            ]
+        );
+        assert!(install(&packs).unwrap().is_empty());
+
+        // A newer release installed by the user stays
+        let manifest = packs.join("stdlib/manifest.json");
+        let newer = fs::read_to_string(&manifest)
+            .unwrap()
+            .replace("\"1.0.0\"", "\"9.0.0\"");
+        fs::write(&manifest, newer).unwrap();
+        assert!(install(&packs).unwrap().is_empty());
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -3,6 +3,7 @@
 //! Key feature: `agent_dispatch_parallel` spawns N parallel agent loops
 //! with different system prompts/personas for multi-agent orchestration.
 
+use hb_core::error::AppError;
 use crate::commands::agent_loop::{run_agent_loop, AgentConversationState, AgentLoopRequest};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -237,7 +238,7 @@ impl AgentOrchestratorState {
 #[tauri::command]
 pub async fn agent_start_orchestrator(
     state: State<'_, Arc<AgentOrchestratorState>>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     let mut is_running = state.is_running.write().await;
     if *is_running {
         return Ok(false);
@@ -256,7 +257,7 @@ pub async fn agent_start_orchestrator(
 #[tauri::command]
 pub async fn agent_stop_orchestrator(
     state: State<'_, Arc<AgentOrchestratorState>>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     let mut is_running = state.is_running.write().await;
     *is_running = false;
 
@@ -273,7 +274,7 @@ pub async fn agent_stop_orchestrator(
 pub async fn agent_register(
     def: AgentDef,
     state: State<'_, Arc<AgentOrchestratorState>>,
-) -> Result<AgentInstance, String> {
+) -> Result<AgentInstance, AppError> {
     let now = chrono::Utc::now().to_rfc3339();
 
     // Store definition
@@ -318,7 +319,7 @@ pub async fn agent_register(
 pub async fn agent_unregister(
     instance_id: String,
     state: State<'_, Arc<AgentOrchestratorState>>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     let mut instances = state.instances.write().await;
     let removed = instances.remove(&instance_id).is_some();
 
@@ -336,7 +337,7 @@ pub async fn agent_update_status(
     instance_id: String,
     status: AgentStatus,
     state: State<'_, Arc<AgentOrchestratorState>>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     let mut instances = state.instances.write().await;
 
     if let Some(instance) = instances.get_mut(&instance_id) {
@@ -360,7 +361,7 @@ pub async fn agent_update_status(
 #[tauri::command]
 pub async fn agent_list_instances(
     state: State<'_, Arc<AgentOrchestratorState>>,
-) -> Result<Vec<AgentInstance>, String> {
+) -> Result<Vec<AgentInstance>, AppError> {
     let instances = state.instances.read().await;
     Ok(instances.values().cloned().collect())
 }
@@ -370,7 +371,7 @@ pub async fn agent_list_instances(
 pub async fn agent_get_instance(
     instance_id: String,
     state: State<'_, Arc<AgentOrchestratorState>>,
-) -> Result<Option<AgentInstance>, String> {
+) -> Result<Option<AgentInstance>, AppError> {
     let instances = state.instances.read().await;
     Ok(instances.get(&instance_id).cloned())
 }
@@ -385,7 +386,7 @@ pub async fn agent_create_task(
     max_retries: Option<usize>,
     parent_task_id: Option<String>,
     state: State<'_, Arc<AgentOrchestratorState>>,
-) -> Result<AgentTask, String> {
+) -> Result<AgentTask, AppError> {
     let config = state.config.read().await;
     let now = chrono::Utc::now().to_rfc3339();
 
@@ -435,7 +436,7 @@ pub async fn agent_assign_task(
     task_id: String,
     agent_id: String,
     state: State<'_, Arc<AgentOrchestratorState>>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     let mut tasks = state.tasks.write().await;
     let mut instances = state.instances.write().await;
 
@@ -463,7 +464,7 @@ pub async fn agent_assign_task(
 pub async fn agent_start_task(
     task_id: String,
     state: State<'_, Arc<AgentOrchestratorState>>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     let mut tasks = state.tasks.write().await;
     let task = tasks.get_mut(&task_id).ok_or("Task not found")?;
 
@@ -491,7 +492,7 @@ pub async fn agent_complete_task(
     task_id: String,
     result: TaskResult,
     state: State<'_, Arc<AgentOrchestratorState>>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     let mut tasks = state.tasks.write().await;
     let task = tasks.get_mut(&task_id).ok_or("Task not found")?;
 
@@ -540,12 +541,12 @@ pub async fn agent_complete_task(
 pub async fn agent_cancel_task(
     task_id: String,
     state: State<'_, Arc<AgentOrchestratorState>>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     let mut tasks = state.tasks.write().await;
     let task = tasks.get_mut(&task_id).ok_or("Task not found")?;
 
     if matches!(task.status, TaskStatus::Running) {
-        return Err("Cannot cancel running task".to_string());
+        return Err("Cannot cancel running task".into());
     }
 
     task.status = TaskStatus::Cancelled;
@@ -570,7 +571,7 @@ pub async fn agent_cancel_task(
 pub async fn agent_get_task(
     task_id: String,
     state: State<'_, Arc<AgentOrchestratorState>>,
-) -> Result<Option<AgentTask>, String> {
+) -> Result<Option<AgentTask>, AppError> {
     let tasks = state.tasks.read().await;
     Ok(tasks.get(&task_id).cloned())
 }
@@ -580,7 +581,7 @@ pub async fn agent_get_task(
 pub async fn agent_list_tasks(
     status_filter: Option<TaskStatus>,
     state: State<'_, Arc<AgentOrchestratorState>>,
-) -> Result<Vec<AgentTask>, String> {
+) -> Result<Vec<AgentTask>, AppError> {
     let tasks = state.tasks.read().await;
     let mut result: Vec<AgentTask> = tasks.values().cloned().collect();
 
@@ -595,7 +596,7 @@ pub async fn agent_list_tasks(
 #[tauri::command]
 pub async fn agent_get_pending_tasks(
     state: State<'_, Arc<AgentOrchestratorState>>,
-) -> Result<Vec<AgentTask>, String> {
+) -> Result<Vec<AgentTask>, AppError> {
     let pending = state.pending_tasks.read().await;
     let tasks = state.tasks.read().await;
 
@@ -611,7 +612,7 @@ pub async fn agent_get_pending_tasks(
 #[tauri::command]
 pub async fn agent_get_stats(
     state: State<'_, Arc<AgentOrchestratorState>>,
-) -> Result<OrchestratorStats, String> {
+) -> Result<OrchestratorStats, AppError> {
     let instances = state.instances.read().await;
     let tasks = state.tasks.read().await;
 
@@ -642,7 +643,7 @@ pub async fn agent_get_stats(
 pub async fn agent_get_events(
     limit: Option<usize>,
     state: State<'_, Arc<AgentOrchestratorState>>,
-) -> Result<Vec<OrchestrationEvent>, String> {
+) -> Result<Vec<OrchestrationEvent>, AppError> {
     let events = state.events.read().await;
     let limit = limit.unwrap_or(100);
     let start = events.len().saturating_sub(limit);
@@ -654,7 +655,7 @@ pub async fn agent_get_events(
 pub async fn agent_update_config(
     config: OrchestrationConfig,
     state: State<'_, Arc<AgentOrchestratorState>>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     let mut current = state.config.write().await;
     *current = config;
     Ok(true)
@@ -664,7 +665,7 @@ pub async fn agent_update_config(
 #[tauri::command]
 pub async fn agent_get_config(
     state: State<'_, Arc<AgentOrchestratorState>>,
-) -> Result<OrchestrationConfig, String> {
+) -> Result<OrchestrationConfig, AppError> {
     let config = state.config.read().await;
     Ok(config.clone())
 }
@@ -673,7 +674,7 @@ pub async fn agent_get_config(
 #[tauri::command]
 pub async fn agent_process_pending(
     state: State<'_, Arc<AgentOrchestratorState>>,
-) -> Result<usize, String> {
+) -> Result<usize, AppError> {
     let is_running = state.is_running.read().await;
     if !*is_running {
         return Ok(0);
@@ -849,7 +850,7 @@ pub async fn agent_execute_task(
     state: State<'_, Arc<AgentOrchestratorState>>,
     conversations: State<'_, Arc<AgentConversationState>>,
     app: AppHandle,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, AppError> {
     // Get task info
     let (task_payload, agent_id, task_type) = {
         let mut tasks = state.tasks.write().await;
@@ -985,7 +986,7 @@ pub async fn agent_execute_task(
             "iterations": result.total_iterations,
             "usage": result.usage,
         })),
-        Err(e) => Err(e),
+        Err(e) => Err(e.into()),
     }
 }
 
@@ -996,7 +997,7 @@ pub async fn agent_dispatch_parallel(
     request: MultiAgentRequest,
     conversations: State<'_, Arc<AgentConversationState>>,
     app: AppHandle,
-) -> Result<MultiAgentResult, String> {
+) -> Result<MultiAgentResult, AppError> {
     let num_agents = request.agents.len();
 
     let _ = app.emit("agent-orchestration", serde_json::json!({
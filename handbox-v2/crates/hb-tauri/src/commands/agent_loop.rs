@@ -7,10 +7,13 @@
 //! - Permission system for dangerous commands
 //! - Sub-agent spawning
 
+use hb_core::error::AppError;
 use crate::commands::llm::{invoke_llm, invoke_llm_stream, ChatMessage, EmbeddingRequest, LLMRequest, create_embedding, ToolDefinition, ToolCall};
 use crate::commands::system_tools;
 use crate::commands::mcp::{self, McpState};
 use crate::commands::vector_store::{self, VectorCollection, VectorEntry, VectorStoreState};
+use crate::notifications::{Notification, NotificationKind, NotificationService};
+use hb_tool_executor::splitter::{split_text, SplitConfig, Strategy};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::future::Future;
@@ -416,40 +419,45 @@ fn check_command_safety(command: &str) -> Result<Option<String>, String> {
 
 /// Ask user for permission to execute a dangerous command.
 /// Emits permission-request event and waits for response (max 60s).
-async fn request_permission(app: &AppHandle, command: &str, warning: &str, conv_id: &str) -> bool {
+pub(crate) async fn request_permission(app: &AppHandle, command: &str, warning: &str, conv_id: &str) -> bool {
     use tokio::sync::oneshot;
 
     let request_id = uuid::Uuid::new_v4().to_string();
 
-    // Emit permission request to frontend
-    let _ = app.emit(PERMISSION_REQUEST_EVENT, json!({
-        "request_id": request_id,
-        "conversation_id": conv_id,
-        "command": command,
-        "warning": warning,
-    }));
-
-    // Wait for response via a Tauri event listener
+    // Listen before asking, so an answer that comes back at once is not lost
     let (tx, rx) = oneshot::channel::<bool>();
-    let tx = Arc::new(tokio::sync::Mutex::new(Some(tx)));
+    let tx = std::sync::Mutex::new(Some(tx));
     let rid = request_id.clone();
-
-    let app_clone = app.clone();
-    let listener_id = app_clone.listen(PERMISSION_RESPONSE_EVENT, move |event: tauri::Event| {
+    let listener_id = app.listen(PERMISSION_RESPONSE_EVENT, move |event: tauri::Event| {
         if let Ok(payload) = serde_json::from_str::<Value>(event.payload()) {
             if payload["request_id"].as_str() == Some(&rid) {
                 let approved = payload["approved"].as_bool().unwrap_or(false);
-                let tx_lock = tx.clone();
-                // Send response (ignore error if already sent)
-                tokio::spawn(async move {
-                    if let Some(sender) = tx_lock.lock().await.take() {
-                        let _ = sender.send(approved);
-                    }
-                });
+                // Only the first answer counts
+                if let Some(sender) = tx.lock().ok().and_then(|mut tx| tx.take()) {
+                    let _ = sender.send(approved);
+                }
             }
         }
     });
 
+    // Emit permission request to frontend
+    let _ = app.emit(PERMISSION_REQUEST_EVENT, json!({
+        "request_id": request_id,
+        "conversation_id": conv_id,
+        "command": command,
+        "warning": warning,
+    }));
+
+    // Alert the user even if the window is not focused
+    if let Some(notifications) = app.try_state::<Arc<NotificationService>>() {
+        let notification = Notification::new(
+            NotificationKind::ApprovalRequested,
+            "Approval required",
+            format!("{warning}\n{command}"),
+        );
+        notifications.notify(app, notification).await;
+    }
+
     // Wait up to 60 seconds for user response
     let result = match tokio::time::timeout(
         std::time::Duration::from_secs(60),
@@ -713,6 +721,7 @@ Use these tools to create visual workflows on the canvas. IMPORTANT RULES:
 |----------------|------------------------------------|----------------------------------|--------------------------------------|
 | file-read      | path:string                        | content:string, size:number      | file_path, encoding                  |
 | pdf-read       | path:string                        | content:string, pages:number     | file_path, page_range                |
+| pdf-table-extract | path:string                     | tables:array, table:json, count:number | file_path, pages, llm_repair   |
 | files-read     | (none)                             | contents:array                   | file_paths                           |
 | folder-read    | (none)                             | files:array                      | folder_path, pattern                 |
 | file-write     | path:string, content:string        | success:boolean                  | file_path                            |
@@ -720,32 +729,47 @@ Use these tools to create visual workflows on the canvas. IMPORTANT RULES:
 | display-output | data:any                           | (none)                           | format                               |
 | llm-chat       | prompt:string, context:string      | response:string                  | model, temperature, system_prompt    |
 | llm-summarize  | text:string                        | summary:string                   | max_length, model                    |
+| summarize-long | text:string, path:string           | summary:string, chunk_summaries:array | file_path, chunk_size, style, language |
 | embedding      | text:string                        | vector:array                     | model                                |
 | text-split     | text:string                        | chunks:array                     | chunk_size, overlap                  |
+| file-split     | path:string                        | chunks:array, headings:array     | chunk_size, overlap, strategy        |
 | text-merge     | texts:array                        | merged:string                    | separator                            |
 | text-template  | variables:json                     | result:string                    | template                             |
-| regex-extract  | text:string                        | matches:array                    | pattern                              |
+| regex-extract  | text:string                        | matches:array, captures:array    | pattern, global, multiline           |
+| clause-split   | text:string, path:string           | clauses:array, count:number      | file_path                            |
+| doc-compare    | old:string, new:string             | changes:array, summary:json, report:string | old_path, new_path, llm_review |
 | json-parse     | json_string:string                 | data:json                        | (none)                               |
 | json-path      | data:json                          | result:any                       | expression                           |
 | csv-read       | path:string                        | rows:array                       | delimiter, file_path                 |
-| data-filter    | items:array                        | filtered:array                   | condition                            |
-| condition      | value:any                          | true:any, false:any              | expression                           |
+| data-filter    | items:array                        | filtered:array, count:number     | condition, fields, sort_by, limit    |
+| doc-extract-fields | text:string, path:string       | fields:json, valid:boolean, errors:array | fields, file_path, llm_fallback |
+| condition      | value:any                          | true_out:any, false_out:any      | expression, mode, cases              |
 | loop           | items:array                        | results:array                    | max_iterations                       |
-| merge          | input_a:any, input_b:any           | merged:json                      | (none)                               |
+| merge          | inputs.<key>:any (variadic)        | merged:json                      | strategy                             |
 | http-request   | (none)                             | response:string, status:number   | url, method, headers, body           |
+| public-api-fetch | params:json                      | items:array, count:number, total_count:number | service, params, url, max_pages |
+| shell-exec     | stdin:string                       | stdout:string, exit_code:number  | command, args, timeout_ms            |
 | vector-store   | chunks:array, vectors:array        | index_id:string                  | index_name                           |
 | vector-search  | query_vector:array                 | results:array                    | top_k, index_name                    |
+| graph-extract  | text:any, source:string            | triples:array, added:number      | graph, entity_types, relations       |
+| graph-query    | entity:string, subject/predicate/object:string | context:string, triples:array | graph, mode, depth, limit  |
+| audio-transcribe | path:string                      | transcript:string, segments:array, text:string | file_path, language, vocabulary |
+| speaker-diarize | segments:array, text:string       | transcript:string, segments:array, speakers:array | method, speakers, turn_gap |
+| meeting-minutes | transcript:string, segments:array, path:string | minutes:string, action_items:array, decisions:array | language, context |
 
 #### CRITICAL Workflow Creation Rules:
 1. **ALWAYS set config fields** — every node MUST have its required config set via workflow_set_config:
-   - file-read/pdf-read: MUST set `file_path` to the actual file path (absolute path on Windows, use backslashes)
+   - file-read/pdf-read/pdf-table-extract: MUST set `file_path` to the actual file path (absolute path on Windows, use backslashes)
    - file-write: MUST set `file_path` for the output file path
    - csv-read: MUST set `file_path` via config (path input comes from edge or config)
    - llm-chat: MUST set `model` (default: "claude-sonnet-4-20250514"), `system_prompt` (describe the task), optionally `temperature`
    - display-output: MUST set `format` ("text", "json", "markdown", or "html") AND connect `data` input port
    - text-template: MUST set `template` with `{{variable}}` placeholders
-   - data-filter: MUST set `condition` (e.g., "field > 10")
+   - data-filter: MUST set `condition` (e.g., "field > 10 && status == 'open'"; quote strings)
+   - doc-extract-fields: MUST set `fields` to the fields to extract, e.g. [{"name":"invoice_no","labels":["송장번호"],"required":true},{"name":"total","type":"number"}]
+   - public-api-fetch: MUST set `service` (kma-forecast, apt-trade, building-register, air-quality, or data-go-kr with `url`) and `params`, e.g. {"LAWD_CD":"11110","DEAL_YMD":"202401"} for apt-trade
    - http-request: MUST set `url`, `method` (GET/POST/PUT/DELETE), optionally `headers` (JSON object), `body`
+   - shell-exec: MUST set `command` (program name) and `args` as a JSON list (no shell syntax; programs not on the allowlist ask the user)
 2. **When user mentions a file**, resolve the absolute path and set it directly in config
 3. **When user attaches a file** (shown as [첨부 파일: path]), use that exact path in config
 4. **NEVER leave file_path empty** — if the user mentioned a file, put its path in config immediately
@@ -756,12 +780,20 @@ Use these tools to create visual workflows on the canvas. IMPORTANT RULES:
 #### Port Connection Reference (exact output→input mapping):
 - file-read.content → llm-chat.context OR text-split.text OR display-output.data
 - pdf-read.content → llm-chat.context OR text-split.text OR display-output.data
-- llm-chat.response → file-write.content OR display-output.data OR text-split.text OR merge.input_a/input_b
+- pdf-table-extract.table → data-filter.items OR to-excel.data; pdf-table-extract.tables → llm-chat.context (as JSON)
+- llm-chat.response → file-write.content OR display-output.data OR text-split.text OR merge.inputs.0/inputs.1/...
 - text-split.chunks → embedding.text (via loop) OR vector-store.chunks
 - csv-read.rows → data-filter.items OR display-output.data
 - data-filter.filtered → display-output.data OR llm-chat.context (as JSON)
+- pdf-read.content → summarize-long.text (or set file_path) for documents too long for one llm-chat; summarize-long.summary → display-output.data OR file-write.content
+- pdf-read.content → doc-compare.old / doc-compare.new (or set old_path/new_path); doc-compare.report → display-output.data OR llm-chat.context
+- clause-split.clauses → loop.items (review clause by clause)
+- pdf-read.content → doc-extract-fields.text; doc-extract-fields.fields → text-template.variables OR display-output.data
 - text-merge.merged → llm-chat.prompt OR file-write.content OR display-output.data
 - vector-search.results → llm-chat.context
+- pdf-read.content / text-split.chunks → graph-extract.text; graph-query.context → llm-chat.context
+- audio-transcribe.segments → speaker-diarize.segments; speaker-diarize.segments → meeting-minutes.segments; meeting-minutes.minutes → display-output.data OR file-write.content
+- public-api-fetch.items → data-filter.items OR to-excel.data OR llm-chat.context (as JSON)
 - http-request.response → llm-chat.context OR json-parse.json_string OR display-output.data
 - merge.merged → llm-chat.context OR display-output.data (use merge to combine N outputs into one)
 
@@ -1057,18 +1089,7 @@ fn load_project_instructions(working_dir: &str) -> String {
 // ============================================================
 
 fn get_data_dir() -> Result<std::path::PathBuf, String> {
-    #[cfg(target_os = "windows")]
-    {
-        std::env::var("APPDATA")
-            .map(|p| std::path::PathBuf::from(p).join("Handbox"))
-            .map_err(|_| "APPDATA not found".to_string())
-    }
-    #[cfg(not(target_os = "windows"))]
-    {
-        dirs::data_dir()
-            .map(|p| p.join("handbox"))
-            .ok_or_else(|| "Data dir not found".to_string())
-    }
+    Ok(crate::config::data_dir())
 }
 
 fn get_project_memory_dir(project_id: &str) -> Result<std::path::PathBuf, String> {
@@ -1233,8 +1254,8 @@ fn dispatch_tool<'a>(action: &'a AgentAction, app: &'a AppHandle, conv_id: &'a s
                 match system_tools::tool_web_fetch(url.clone(), timeout, max).await {
                     Ok(result) => return Ok(result["text"].as_str().unwrap_or("").to_string()),
                     Err(e) => {
-                        let is_transient = e.contains("timeout") || e.contains("connection") || e.contains("reset");
-                        last_err = e;
+                        let is_transient = e.message.contains("timeout") || e.message.contains("connection") || e.message.contains("reset");
+                        last_err = e.to_string();
                         if !is_transient { break; }
                     }
                 }
@@ -2652,29 +2673,19 @@ fn scope_collection_name(collection: &str, project_id: Option<&str>) -> String {
     }
 }
 
-/// Split text into overlapping chunks for RAG ingestion.
-/// Uses 20% overlap between chunks for context continuity.
+/// Split text into chunks for RAG ingestion, following headings, code
+/// blocks and tables. Uses 20% overlap where a paragraph is cut. Text that
+/// fits in one chunk is kept as it is.
 fn chunk_text(text: &str, chunk_size: usize) -> Vec<String> {
     if text.len() <= chunk_size {
         return vec![text.to_string()];
     }
-    let overlap = chunk_size / 5; // 20% overlap
-    let step = chunk_size - overlap;
-    let chars: Vec<char> = text.chars().collect();
-    let mut chunks = Vec::new();
-    let mut start = 0;
-    while start < chars.len() {
-        let end = (start + chunk_size).min(chars.len());
-        let chunk: String = chars[start..end].iter().collect();
-        if !chunk.trim().is_empty() {
-            chunks.push(chunk);
-        }
-        if end >= chars.len() {
-            break;
-        }
-        start += step;
-    }
-    chunks
+    let config = SplitConfig::new(chunk_size, chunk_size / 5, Strategy::Structure);
+    split_text(text, &config)
+        .into_iter()
+        .map(|chunk| chunk.text)
+        .filter(|chunk| !chunk.trim().is_empty())
+        .collect()
 }
 
 /// Enhanced sub-agent loop with configurable iterations, write permissions, and model override
@@ -3517,8 +3528,8 @@ pub async fn agent_run_loop(
     request: AgentLoopRequest,
     conversations: State<'_, Arc<AgentConversationState>>,
     app: AppHandle,
-) -> Result<AgentLoopResult, String> {
-    run_agent_loop(request, &*conversations, &app).await
+) -> Result<AgentLoopResult, AppError> {
+    run_agent_loop(request, &conversations, &app).await.map_err(AppError::from)
 }
 
 /// List conversations with metadata (sorted by updated_at desc).
@@ -3528,7 +3539,7 @@ pub async fn agent_run_loop(
 pub async fn agent_list_conversations(
     project_id: Option<String>,
     conversations: State<'_, Arc<AgentConversationState>>,
-) -> Result<Value, String> {
+) -> Result<Value, AppError> {
     let metas = conversations.metadata.read().await;
     let mut list: Vec<&ConversationMeta> = metas.values()
         .filter(|m| {
@@ -3552,7 +3563,7 @@ pub async fn agent_list_conversations(
 pub async fn agent_get_conversation(
     conversation_id: String,
     conversations: State<'_, Arc<AgentConversationState>>,
-) -> Result<Value, String> {
+) -> Result<Value, AppError> {
     let convs = conversations.conversations.read().await;
     let metas = conversations.metadata.read().await;
 
@@ -3567,7 +3578,7 @@ pub async fn agent_get_conversation(
             })).collect::<Vec<_>>(),
         }))
     } else {
-        Err(format!("Conversation '{}' not found", conversation_id))
+        Err(format!("Conversation '{}' not found", conversation_id).into())
     }
 }
 
@@ -3576,7 +3587,7 @@ pub async fn agent_get_conversation(
 pub async fn agent_clear_conversation(
     conversation_id: String,
     conversations: State<'_, Arc<AgentConversationState>>,
-) -> Result<Value, String> {
+) -> Result<Value, AppError> {
     {
         let mut convs = conversations.conversations.write().await;
         convs.remove(&conversation_id);
@@ -3594,7 +3605,7 @@ pub async fn agent_clear_conversation(
 pub async fn agent_cancel_loop(
     conversation_id: Option<String>,
     conversations: State<'_, Arc<AgentConversationState>>,
-) -> Result<Value, String> {
+) -> Result<Value, AppError> {
     let flags = conversations.cancellation_flags.read().await;
 
     if let Some(cid) = conversation_id {
@@ -3603,7 +3614,7 @@ pub async fn agent_cancel_loop(
             flag.store(true, Ordering::Relaxed);
             Ok(json!({ "success": true, "cancelled": cid }))
         } else {
-            Err(format!("No running agent loop found for conversation '{cid}'"))
+            Err(format!("No running agent loop found for conversation '{cid}'").into())
         }
     } else {
         // Cancel ALL running loops
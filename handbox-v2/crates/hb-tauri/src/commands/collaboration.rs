@@ -2,12 +2,24 @@
 //!
 //! Note: Full WebSocket support requires additional setup with a dedicated server.
 //! This module provides the backend state management and session handling.
-
+//! Sessions are recorded for later playback (see [`crate::session_recording`]).
+
+use crate::commands::execution::{run_workflow, ExecutionTrackerState};
+use crate::session_recording::{PlaybackFrame, Recording, RecordingSummary, SessionRecorder};
+use crate::state::AppState;
+use hb_core::error::{AppError, ErrorCode};
+use hb_core::graph::WorkflowSpec;
+use hb_core::project::{ActivityEntry, ActivityKind, Permission};
+use hb_runner::{NodeStatusEvent, StatusCallback};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
-use tauri::State;
-use tokio::sync::RwLock;
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::{mpsc, RwLock};
+
+/// Tauri event carrying every update of a shared execution.
+pub const SHARED_EXECUTION_EVENT: &str = "collab-execution";
 
 // ========== Types ==========
 
@@ -46,6 +58,11 @@ pub struct SessionSettings {
     pub max_collaborators: usize,
     pub auto_save: bool,
     pub auto_save_interval: u64,
+    /// Include node outputs in shared execution events, while everyone in
+    /// the session is a member of the run's shared workspace. Participants
+    /// always see status, errors and timing.
+    #[serde(default)]
+    pub share_outputs: bool,
 }
 
 impl Default for SessionSettings {
@@ -57,6 +74,7 @@ impl Default for SessionSettings {
             max_collaborators: 10,
             auto_save: true,
             auto_save_interval: 5000,
+            share_outputs: false,
         }
     }
 }
@@ -71,6 +89,20 @@ pub struct CollaborationSession {
     pub created_at: String,
     pub is_active: bool,
     pub settings: SessionSettings,
+    /// Shared executions currently running.
+    #[serde(default)]
+    pub executions: Vec<SharedExecution>,
+}
+
+/// An execution started from a session and broadcast to its participants.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedExecution {
+    pub execution_id: String,
+    pub workflow_id: String,
+    pub started_by: String,
+    pub started_at: String,
+    /// Participants online when the execution started.
+    pub watchers: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -119,15 +151,18 @@ pub struct CollaborationState {
     chat_history: RwLock<HashMap<String, Vec<ChatMessage>>>,
     events: RwLock<HashMap<String, Vec<CollaborationEvent>>>,
     invites: RwLock<HashMap<String, CollaborationInvite>>,
+    recorder: SessionRecorder,
 }
 
-impl Default for CollaborationState {
-    fn default() -> Self {
+impl CollaborationState {
+    /// Session recordings are kept in `recordings_dir`.
+    pub fn new(recordings_dir: PathBuf) -> Self {
         Self {
             sessions: RwLock::new(HashMap::new()),
             chat_history: RwLock::new(HashMap::new()),
             events: RwLock::new(HashMap::new()),
             invites: RwLock::new(HashMap::new()),
+            recorder: SessionRecorder::new(recordings_dir),
         }
     }
 }
@@ -149,12 +184,50 @@ impl CollaborationState {
             timestamp: chrono::Utc::now().to_rfc3339(),
             payload,
         };
+        self.recorder.record(&event);
         events.push(event);
         if events.len() > 500 {
             events.drain(0..250);
         }
     }
 
+    /// Append an event to a session's log, if the session exists.
+    async fn record_event(
+        &self,
+        session_id: &str,
+        event_type: &str,
+        user_id: &str,
+        payload: serde_json::Value,
+    ) {
+        let mut events = self.events.write().await;
+        if let Some(session_events) = events.get_mut(session_id) {
+            self.emit_event(session_events, event_type, session_id, user_id, payload);
+        }
+    }
+
+    /// Record a change to `workflow_id` in every active session on it, so
+    /// its recording can replay the change. Without `user_id` the change is
+    /// attributed to each session's owner, who hosts it.
+    pub async fn record_workflow_change(
+        &self,
+        workflow_id: &str,
+        user_id: Option<&str>,
+        event_type: &str,
+        payload: serde_json::Value,
+    ) {
+        let sessions: Vec<(String, String)> = self
+            .sessions
+            .read()
+            .await
+            .values()
+            .filter(|s| s.is_active && s.workflow_id == workflow_id)
+            .map(|s| (s.id.clone(), user_id.unwrap_or(&s.owner).to_string()))
+            .collect();
+        for (session_id, user_id) in sessions {
+            self.record_event(&session_id, event_type, &user_id, payload.clone()).await;
+        }
+    }
+
     fn get_next_color(&self, collaborator_count: usize) -> String {
         CURSOR_COLORS[collaborator_count % CURSOR_COLORS.len()].to_string()
     }
@@ -171,7 +244,8 @@ pub async fn collab_create_session(
     user_name: String,
     settings: Option<SessionSettings>,
     state: State<'_, Arc<CollaborationState>>,
-) -> Result<CollaborationSession, String> {
+    app_state: State<'_, AppState>,
+) -> Result<CollaborationSession, AppError> {
     let now = chrono::Utc::now().to_rfc3339();
     let session_id = uuid::Uuid::new_v4().to_string();
 
@@ -195,8 +269,12 @@ pub async fn collab_create_session(
         created_at: now,
         is_active: true,
         settings: settings.unwrap_or_default(),
+        executions: Vec::new(),
     };
 
+    let workflow = app_state.workflows.read().await.get(&session.workflow_id).cloned();
+    state.recorder.start(&session, workflow);
+
     let mut sessions = state.sessions.write().await;
     sessions.insert(session_id.clone(), session.clone());
 
@@ -226,18 +304,18 @@ pub async fn collab_join_session(
     user_name: String,
     role: Option<CollaboratorRole>,
     state: State<'_, Arc<CollaborationState>>,
-) -> Result<CollaborationSession, String> {
+) -> Result<CollaborationSession, AppError> {
     let mut sessions = state.sessions.write().await;
     let session = sessions.get_mut(&session_id).ok_or("Session not found")?;
 
     // Check if session is active
     if !session.is_active {
-        return Err("Session is not active".to_string());
+        return Err("Session is not active".into());
     }
 
     // Check max collaborators
     if session.collaborators.len() >= session.settings.max_collaborators {
-        return Err("Session is full".to_string());
+        return Err("Session is full".into());
     }
 
     // Check if already in session
@@ -284,7 +362,7 @@ pub async fn collab_leave_session(
     session_id: String,
     user_id: String,
     state: State<'_, Arc<CollaborationState>>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     let mut sessions = state.sessions.write().await;
     let session = sessions.get_mut(&session_id).ok_or("Session not found")?;
 
@@ -322,7 +400,7 @@ pub async fn collab_leave_session(
 pub async fn collab_get_session(
     session_id: String,
     state: State<'_, Arc<CollaborationState>>,
-) -> Result<Option<CollaborationSession>, String> {
+) -> Result<Option<CollaborationSession>, AppError> {
     let sessions = state.sessions.read().await;
     Ok(sessions.get(&session_id).cloned())
 }
@@ -331,7 +409,7 @@ pub async fn collab_get_session(
 #[tauri::command]
 pub async fn collab_list_sessions(
     state: State<'_, Arc<CollaborationState>>,
-) -> Result<Vec<CollaborationSession>, String> {
+) -> Result<Vec<CollaborationSession>, AppError> {
     let sessions = state.sessions.read().await;
     Ok(sessions.values().filter(|s| s.is_active).cloned().collect())
 }
@@ -343,7 +421,7 @@ pub async fn collab_update_cursor(
     user_id: String,
     cursor: CursorPosition,
     state: State<'_, Arc<CollaborationState>>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     let mut sessions = state.sessions.write().await;
     let session = sessions.get_mut(&session_id).ok_or("Session not found")?;
 
@@ -374,7 +452,7 @@ pub async fn collab_update_selection(
     user_id: String,
     node_ids: Vec<String>,
     state: State<'_, Arc<CollaborationState>>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     let mut sessions = state.sessions.write().await;
     let session = sessions.get_mut(&session_id).ok_or("Session not found")?;
 
@@ -407,7 +485,7 @@ pub async fn collab_send_message(
     content: String,
     reply_to_id: Option<String>,
     state: State<'_, Arc<CollaborationState>>,
-) -> Result<ChatMessage, String> {
+) -> Result<ChatMessage, AppError> {
     let message = ChatMessage {
         id: uuid::Uuid::new_v4().to_string(),
         session_id: session_id.clone(),
@@ -444,7 +522,7 @@ pub async fn collab_get_chat_history(
     session_id: String,
     limit: Option<usize>,
     state: State<'_, Arc<CollaborationState>>,
-) -> Result<Vec<ChatMessage>, String> {
+) -> Result<Vec<ChatMessage>, AppError> {
     let chat = state.chat_history.read().await;
     let history = chat.get(&session_id).cloned().unwrap_or_default();
     let limit = limit.unwrap_or(50);
@@ -460,7 +538,7 @@ pub async fn collab_add_reaction(
     user_id: String,
     emoji: String,
     state: State<'_, Arc<CollaborationState>>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     let mut chat = state.chat_history.write().await;
     let history = chat.get_mut(&session_id).ok_or("Session not found")?;
 
@@ -471,7 +549,7 @@ pub async fn collab_add_reaction(
         }
         Ok(true)
     } else {
-        Err("Message not found".to_string())
+        Err("Message not found".into())
     }
 }
 
@@ -483,7 +561,7 @@ pub async fn collab_remove_reaction(
     user_id: String,
     emoji: String,
     state: State<'_, Arc<CollaborationState>>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     let mut chat = state.chat_history.write().await;
     let history = chat.get_mut(&session_id).ok_or("Session not found")?;
 
@@ -493,7 +571,7 @@ pub async fn collab_remove_reaction(
         }
         Ok(true)
     } else {
-        Err("Message not found".to_string())
+        Err("Message not found".into())
     }
 }
 
@@ -505,7 +583,7 @@ pub async fn collab_broadcast_change(
     change_type: String,
     payload: serde_json::Value,
     state: State<'_, Arc<CollaborationState>>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     // Verify session and permissions
     let sessions = state.sessions.read().await;
     let session = sessions.get(&session_id).ok_or("Session not found")?;
@@ -515,7 +593,7 @@ pub async fn collab_broadcast_change(
 
     let role = &collaborator.role;
     if !session.settings.allow_editing && !matches!(role, CollaboratorRole::Owner) {
-        return Err("Editing not allowed".to_string());
+        return Err("Editing not allowed".into());
     }
 
     drop(sessions);
@@ -542,7 +620,7 @@ pub async fn collab_get_events(
     since_timestamp: Option<String>,
     limit: Option<usize>,
     state: State<'_, Arc<CollaborationState>>,
-) -> Result<Vec<CollaborationEvent>, String> {
+) -> Result<Vec<CollaborationEvent>, AppError> {
     let events = state.events.read().await;
     let session_events = events.get(&session_id).cloned().unwrap_or_default();
 
@@ -568,7 +646,7 @@ pub async fn collab_create_invite(
     role: CollaboratorRole,
     expires_hours: Option<u64>,
     state: State<'_, Arc<CollaborationState>>,
-) -> Result<CollaborationInvite, String> {
+) -> Result<CollaborationInvite, AppError> {
     // Verify session and permissions
     let sessions = state.sessions.read().await;
     let session = sessions.get(&session_id).ok_or("Session not found")?;
@@ -578,11 +656,11 @@ pub async fn collab_create_invite(
 
     let user_role = &collaborator.role;
     if matches!(user_role, CollaboratorRole::Viewer) {
-        return Err("Viewers cannot invite".to_string());
+        return Err("Viewers cannot invite".into());
     }
 
     if !session.settings.allow_invite && !matches!(user_role, CollaboratorRole::Owner) {
-        return Err("Invites not allowed".to_string());
+        return Err("Invites not allowed".into());
     }
 
     drop(sessions);
@@ -611,7 +689,7 @@ pub async fn collab_create_invite(
 pub async fn collab_get_invite(
     invite_id: String,
     state: State<'_, Arc<CollaborationState>>,
-) -> Result<Option<CollaborationInvite>, String> {
+) -> Result<Option<CollaborationInvite>, AppError> {
     let invites = state.invites.read().await;
     Ok(invites.get(&invite_id).cloned())
 }
@@ -623,7 +701,7 @@ pub async fn collab_accept_invite(
     user_id: String,
     user_name: String,
     state: State<'_, Arc<CollaborationState>>,
-) -> Result<CollaborationSession, String> {
+) -> Result<CollaborationSession, AppError> {
     let mut invites = state.invites.write().await;
     let invite = invites.get_mut(&invite_id).ok_or("Invite not found")?;
 
@@ -632,11 +710,11 @@ pub async fn collab_accept_invite(
         .map_err(|_| "Invalid expiry date")?;
     if chrono::Utc::now() > expires {
         invite.status = "expired".to_string();
-        return Err("Invite has expired".to_string());
+        return Err("Invite has expired".into());
     }
 
     if invite.status != "pending" {
-        return Err(format!("Invite is {}", invite.status));
+        return Err(format!("Invite is {}", invite.status).into());
     }
 
     invite.status = "accepted".to_string();
@@ -656,13 +734,13 @@ pub async fn collab_update_settings(
     user_id: String,
     settings: SessionSettings,
     state: State<'_, Arc<CollaborationState>>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     let mut sessions = state.sessions.write().await;
     let session = sessions.get_mut(&session_id).ok_or("Session not found")?;
 
     // Only owner can update settings
     if session.owner != user_id {
-        return Err("Only owner can update settings".to_string());
+        return Err("Only owner can update settings".into());
     }
 
     session.settings = settings;
@@ -675,7 +753,7 @@ pub async fn collab_heartbeat(
     session_id: String,
     user_id: String,
     state: State<'_, Arc<CollaborationState>>,
-) -> Result<Vec<Collaborator>, String> {
+) -> Result<Vec<Collaborator>, AppError> {
     let mut sessions = state.sessions.write().await;
     let session = sessions.get_mut(&session_id).ok_or("Session not found")?;
 
@@ -709,13 +787,13 @@ pub async fn collab_close_session(
     session_id: String,
     user_id: String,
     state: State<'_, Arc<CollaborationState>>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     let mut sessions = state.sessions.write().await;
     let session = sessions.get_mut(&session_id).ok_or("Session not found")?;
 
     // Only owner can close
     if session.owner != user_id {
-        return Err("Only owner can close session".to_string());
+        return Err("Only owner can close session".into());
     }
 
     session.is_active = false;
@@ -734,3 +812,504 @@ pub async fn collab_close_session(
 
     Ok(true)
 }
+
+/// Updates forwarded from a shared execution to its session, in order.
+enum SharedUpdate {
+    Status(Box<NodeStatusEvent>),
+    Finished(serde_json::Value),
+}
+
+/// Who is calling a session command: the team sync user when sync is on,
+/// otherwise this machine's user, who hosts the session. Never taken from
+/// the client, which could name any participant.
+fn caller(app_state: &AppState, session: &CollaborationSession) -> String {
+    let config = crate::team_sync::SyncConfig::load(&app_state.data_dir);
+    match config.user {
+        Some(user) if config.is_active() => user,
+        _ => session.owner.clone(),
+    }
+}
+
+/// Whether `user_id` may start or cancel executions in `session`: owners
+/// always, editors when the session allows execution, viewers never.
+fn may_run(session: &CollaborationSession, user_id: &str) -> Result<(), AppError> {
+    let collaborator = session
+        .collaborators
+        .iter()
+        .find(|c| c.id == user_id)
+        .ok_or_else(|| "User not in session".to_string())?;
+    let allowed = match collaborator.role {
+        CollaboratorRole::Owner => true,
+        CollaboratorRole::Editor => session.settings.allow_execution,
+        CollaboratorRole::Viewer => false,
+    };
+    if !allowed {
+        return Err(AppError::new(
+            ErrorCode::PermissionDenied,
+            "Running workflows is not allowed for this participant",
+        ));
+    }
+    Ok(())
+}
+
+impl CollaborationState {
+    /// Register an execution `user_id` starts from a session, if their role
+    /// allows it.
+    async fn begin_execution(
+        &self,
+        session_id: &str,
+        user_id: &str,
+        execution_id: uuid::Uuid,
+    ) -> Result<SharedExecution, AppError> {
+        let mut sessions = self.sessions.write().await;
+        let session = sessions.get_mut(session_id).ok_or("Session not found")?;
+        if !session.is_active {
+            return Err("Session is closed".into());
+        }
+        may_run(session, user_id)?;
+
+        let shared = SharedExecution {
+            execution_id: execution_id.to_string(),
+            workflow_id: session.workflow_id.clone(),
+            started_by: user_id.to_string(),
+            started_at: chrono::Utc::now().to_rfc3339(),
+            watchers: session.collaborators.iter().filter(|c| c.is_online).map(|c| c.id.clone()).collect(),
+        };
+        session.executions.push(shared.clone());
+        Ok(shared)
+    }
+
+    /// Whether node outputs may go to everyone in the session: it shares
+    /// them and, for a run in a shared workspace (`members` set), everyone
+    /// in the session is a member. Checked per update, so outputs stop as
+    /// soon as someone outside the workspace joins.
+    async fn outputs_visible(&self, session_id: &str, members: Option<&[String]>) -> bool {
+        let sessions = self.sessions.read().await;
+        let Some(session) = sessions.get(session_id) else {
+            return false;
+        };
+        session.settings.share_outputs
+            && members.is_none_or(|members| session.collaborators.iter().all(|c| members.contains(&c.id)))
+    }
+
+    /// Log an update of a shared execution, returning the event to broadcast.
+    /// Outputs, including those of finished nodes in status events, are
+    /// left out unless [`Self::outputs_visible`].
+    async fn share_update(
+        &self,
+        session_id: &str,
+        user_id: &str,
+        update: SharedUpdate,
+        members: Option<&[String]>,
+    ) -> (&'static str, serde_json::Value) {
+        let outputs = self.outputs_visible(session_id, members).await;
+        let (event_type, payload) = match update {
+            SharedUpdate::Status(mut event) => {
+                if !outputs {
+                    event.output = None;
+                    event.chunk = None;
+                }
+                ("execution_status", serde_json::to_value(&event).unwrap_or_default())
+            }
+            SharedUpdate::Finished(mut summary) => {
+                if !outputs {
+                    if let Some(summary) = summary.as_object_mut() {
+                        summary.remove("record");
+                    }
+                }
+                ("execution_completed", summary)
+            }
+        };
+        self.record_event(session_id, event_type, user_id, payload.clone()).await;
+        (event_type, payload)
+    }
+}
+
+/// Run the session's workflow on behalf of a participant, broadcasting its
+/// node status to everyone in the session. Owners may always run; editors
+/// when the session allows execution; viewers never. Outputs are left out of
+/// the broadcast unless the session shares them and, in a shared workspace,
+/// everyone in the session is a member of it; the caller always gets the
+/// full result.
+#[tauri::command]
+pub async fn collab_execute_workflow(
+    session_id: String,
+    workspace_id: Option<String>,
+    state: State<'_, Arc<CollaborationState>>,
+    app_state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<serde_json::Value, AppError> {
+    let user_id = {
+        let sessions = state.sessions.read().await;
+        let session = sessions.get(&session_id).ok_or("Session not found")?;
+        caller(&app_state, session)
+    };
+    let members = match crate::commands::workflow::parse_workspace_id(workspace_id.clone())? {
+        Some(id) => {
+            let workspace = app_state
+                .project_manager
+                .read()
+                .await
+                .get_workspace(id)
+                .map_err(|e| AppError::not_found(e.to_string()))?;
+            workspace
+                .is_shared()
+                .then(|| workspace.members.into_iter().map(|m| m.user).collect::<Vec<_>>())
+        }
+        None => None,
+    };
+    let execution_uuid = uuid::Uuid::new_v4();
+    let shared = state.begin_execution(&session_id, &user_id, execution_uuid).await?;
+    let started = serde_json::to_value(&shared).unwrap_or_default();
+    state.record_event(&session_id, "execution_started", &user_id, started.clone()).await;
+    emit_shared(&app, &session_id, "execution_started", &started);
+
+    // Forward status events through a channel so they reach the session log
+    // in order, without blocking the scheduler
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let forward = {
+        let collab = state.inner().clone();
+        let app = app.clone();
+        let session_id = session_id.clone();
+        let user_id = user_id.clone();
+        tauri::async_runtime::spawn(async move {
+            while let Some(update) = rx.recv().await {
+                let (event_type, payload) =
+                    collab.share_update(&session_id, &user_id, update, members.as_deref()).await;
+                emit_shared(&app, &session_id, event_type, &payload);
+                if event_type == "execution_completed" {
+                    break;
+                }
+            }
+        })
+    };
+    let observer: StatusCallback = {
+        let tx = tx.clone();
+        Arc::new(move |event: NodeStatusEvent| {
+            // Streamed chunks stay local; the shared log gets the final output
+            if event.status != "output" {
+                let _ = tx.send(SharedUpdate::Status(Box::new(event)));
+            }
+        })
+    };
+
+    let result = run_workflow(
+        &app,
+        &shared.workflow_id,
+        workspace_id.as_deref(),
+        execution_uuid,
+        Some(observer),
+    )
+    .await;
+
+    let summary = match &result {
+        Ok(record) => serde_json::json!({
+            "execution_id": shared.execution_id,
+            "status": record.get("status"),
+            "record": record,
+        }),
+        Err(e) => serde_json::json!({
+            "execution_id": shared.execution_id,
+            "status": "failed",
+            "error": e.message,
+        }),
+    };
+    let _ = tx.send(SharedUpdate::Finished(summary));
+    if let Err(e) = forward.await {
+        tracing::warn!("Shared execution forwarder failed: {e}");
+    }
+
+    if let Some(session) = state.sessions.write().await.get_mut(&session_id) {
+        session.executions.retain(|e| e.execution_id != shared.execution_id);
+    }
+    result
+}
+
+/// Cancel a shared execution of a session. Whoever may start executions
+/// in the session may cancel them; viewers may not.
+#[tauri::command]
+pub async fn collab_cancel_execution(
+    session_id: String,
+    execution_id: String,
+    state: State<'_, Arc<CollaborationState>>,
+    app_state: State<'_, AppState>,
+    tracker: State<'_, Arc<ExecutionTrackerState>>,
+) -> Result<(), AppError> {
+    let user_id = {
+        let sessions = state.sessions.read().await;
+        let session = sessions.get(&session_id).ok_or("Session not found")?;
+        let user_id = caller(&app_state, session);
+        if !session.executions.iter().any(|e| e.execution_id == execution_id) {
+            return Err(AppError::not_found(format!(
+                "Execution {execution_id} is not running in this session"
+            )));
+        }
+        may_run(session, &user_id)?;
+        user_id
+    };
+    tracker.cancel(&execution_id).await?;
+    state
+        .record_event(
+            &session_id,
+            "execution_cancelled",
+            &user_id,
+            serde_json::json!({ "execution_id": execution_id }),
+        )
+        .await;
+    Ok(())
+}
+
+fn emit_shared(app: &AppHandle, session_id: &str, event_type: &str, payload: &serde_json::Value) {
+    let event = serde_json::json!({
+        "session_id": session_id,
+        "event_type": event_type,
+        "payload": payload,
+    });
+    if let Err(e) = app.emit(SHARED_EXECUTION_EVENT, event) {
+        tracing::warn!("Failed to emit shared execution event: {e}");
+    }
+}
+
+/// Recorded sessions, most recent first
+#[tauri::command]
+pub async fn collab_list_recordings(
+    state: State<'_, Arc<CollaborationState>>,
+) -> Result<Vec<RecordingSummary>, AppError> {
+    Ok(state.recorder.list())
+}
+
+/// Full recording of a session: initial workflow and every event
+#[tauri::command]
+pub async fn collab_get_recording(
+    session_id: String,
+    state: State<'_, Arc<CollaborationState>>,
+) -> Result<Recording, AppError> {
+    state.recorder.load(&session_id).map_err(AppError::not_found)
+}
+
+/// Reconstruct the workflow at a point in a recorded session, given as an
+/// event count (`position`) or a time (`at`, RFC 3339); defaults to the end
+#[tauri::command]
+pub async fn collab_playback(
+    session_id: String,
+    position: Option<usize>,
+    at: Option<String>,
+    state: State<'_, Arc<CollaborationState>>,
+) -> Result<PlaybackFrame, AppError> {
+    let recording = state.recorder.load(&session_id).map_err(AppError::not_found)?;
+    Ok(recording.playback(playback_position(&recording, position, at)?))
+}
+
+/// Replace the session's workflow with its state at a point in the
+/// recording, e.g. to undo a bad collaborative edit
+#[tauri::command]
+pub async fn collab_restore_from_recording(
+    session_id: String,
+    position: Option<usize>,
+    at: Option<String>,
+    workspace_id: Option<String>,
+    state: State<'_, Arc<CollaborationState>>,
+    app_state: State<'_, AppState>,
+) -> Result<WorkflowSpec, AppError> {
+    let workspace_id = crate::commands::workflow::parse_workspace_id(workspace_id)?;
+    let access = app_state.authorize(workspace_id, Permission::EditWorkflow).await?;
+    let recording = state.recorder.load(&session_id).map_err(AppError::not_found)?;
+    let frame = recording.playback(playback_position(&recording, position, at)?);
+    let mut spec = frame.workflow.ok_or_else(|| {
+        AppError::invalid_input("The recording has no workflow state at that point")
+    })?;
+    spec.meta.updated_at = chrono::Utc::now();
+
+    let id = spec.id.to_string();
+    app_state.workflows.write().await.insert(id.clone(), spec.clone());
+    app_state
+        .record_activity(access.stamp(
+            ActivityEntry::new(
+                ActivityKind::WorkflowEdited,
+                id,
+                format!(
+                    "Restored workflow '{}' from collaboration session '{}'",
+                    spec.meta.name, recording.name
+                ),
+            )
+            .in_workspace(workspace_id)
+            .with_details(serde_json::json!({
+                "session_id": session_id,
+                "position": frame.position,
+            })),
+        ))
+        .await;
+    app_state.refresh_thumbnail(&spec).await;
+    Ok(spec)
+}
+
+fn playback_position(
+    recording: &Recording,
+    position: Option<usize>,
+    at: Option<String>,
+) -> Result<usize, AppError> {
+    match (position, at) {
+        (Some(position), _) => Ok(position),
+        (None, Some(at)) => {
+            let at = chrono::DateTime::parse_from_rfc3339(&at)
+                .map_err(|e| AppError::invalid_input(format!("Invalid time '{at}': {e}")))?;
+            Ok(recording.position_at(at.with_timezone(&chrono::Utc)))
+        }
+        (None, None) => Ok(recording.events.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::execution::ExecutionInfo;
+    use tauri::Manager;
+
+    fn collaborator(id: &str, role: CollaboratorRole) -> Collaborator {
+        Collaborator {
+            id: id.into(),
+            name: id.into(),
+            color: CURSOR_COLORS[0].into(),
+            is_online: true,
+            last_active: chrono::Utc::now().to_rfc3339(),
+            role,
+            cursor: None,
+            selection: Vec::new(),
+        }
+    }
+
+    async fn session_state(collaborators: Vec<Collaborator>, settings: SessionSettings) -> CollaborationState {
+        let dir = std::env::temp_dir().join(format!("hb-collab-{}", uuid::Uuid::new_v4()));
+        let state = CollaborationState::new(dir);
+        let session = CollaborationSession {
+            id: "s1".into(),
+            workflow_id: "wf".into(),
+            name: "기초 검토".into(),
+            owner: "ada".into(),
+            collaborators,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            is_active: true,
+            settings,
+            executions: Vec::new(),
+        };
+        state.sessions.write().await.insert(session.id.clone(), session);
+        state.events.write().await.insert("s1".into(), Vec::new());
+        state
+    }
+
+    #[tokio::test]
+    async fn viewers_cannot_start_or_cancel_shared_executions() {
+        let settings = SessionSettings {
+            allow_execution: true,
+            ..Default::default()
+        };
+        let collaborators = vec![
+            collaborator("ada", CollaboratorRole::Owner),
+            collaborator("vic", CollaboratorRole::Viewer),
+        ];
+        let state = session_state(collaborators, settings).await;
+
+        let err = state.begin_execution("s1", "vic", uuid::Uuid::new_v4()).await.unwrap_err();
+        assert_eq!(err.code, ErrorCode::PermissionDenied);
+        let execution_id = uuid::Uuid::new_v4();
+        state.begin_execution("s1", "ada", execution_id).await.unwrap();
+
+        let tracker = ExecutionTrackerState::default();
+        let cancelled = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        tracker.executions.lock().await.insert(
+            execution_id.to_string(),
+            ExecutionInfo {
+                status: "running".into(),
+                cancelled: cancelled.clone(),
+                pause: Default::default(),
+                node_cancellation: Default::default(),
+                started_at: chrono::Utc::now(),
+                events: Default::default(),
+                workspace_id: None,
+                finished_at: None,
+            },
+        );
+        let app = tauri::test::mock_app();
+        app.manage(Arc::new(state));
+        app.manage(Arc::new(tracker));
+
+        // The caller is the synced user, whatever the client says
+        app.manage(crate::state::testing::team_state("vic").await);
+        let cancel = || collab_cancel_execution("s1".into(), execution_id.to_string(), app.state(), app.state(), app.state());
+        let err = cancel().await.unwrap_err();
+        assert_eq!(err.code, ErrorCode::PermissionDenied);
+        assert!(!cancelled.load(std::sync::atomic::Ordering::Relaxed));
+
+        // Without team sync, it is whoever hosts the session
+        let app_state = app.state::<AppState>();
+        let mut config = crate::team_sync::SyncConfig::load(&app_state.data_dir);
+        config.enabled = false;
+        config.save(&app_state.data_dir).unwrap();
+        cancel().await.unwrap();
+        assert!(cancelled.load(std::sync::atomic::Ordering::Relaxed));
+    }
+
+    #[tokio::test]
+    async fn shared_outputs_only_reach_workspace_members() {
+        let settings = SessionSettings {
+            allow_execution: true,
+            share_outputs: true,
+            ..Default::default()
+        };
+        let collaborators = vec![
+            collaborator("ada", CollaboratorRole::Owner),
+            collaborator("bob", CollaboratorRole::Editor),
+        ];
+        let state = session_state(collaborators, settings).await;
+        let status = || {
+            SharedUpdate::Status(Box::new(NodeStatusEvent {
+                execution_id: "e1".into(),
+                node_id: "n1".into(),
+                status: "completed".into(),
+                seq: 1,
+                output: Some(serde_json::json!({ "text": "철근 D13" })),
+                error: None,
+                duration_ms: Some(5),
+                progress: None,
+                chunk: None,
+            }))
+        };
+        let finished = || SharedUpdate::Finished(serde_json::json!({ "status": "completed", "record": {} }));
+        let both = ["ada".to_string(), "bob".to_string()];
+        let ada = ["ada".to_string()];
+
+        // Everyone in the session is a member, or the run is in no shared workspace
+        let (_, payload) = state.share_update("s1", "ada", status(), Some(&both)).await;
+        assert_eq!(payload["output"]["text"], "철근 D13");
+        let (_, payload) = state.share_update("s1", "ada", status(), None).await;
+        assert!(payload["output"].is_object());
+
+        // Bob is not in the workspace: nobody gets outputs, only status
+        let (_, payload) = state.share_update("s1", "ada", status(), Some(&ada)).await;
+        assert!(payload["output"].is_null());
+        assert_eq!(payload["status"], "completed");
+        let mut streamed = NodeStatusEvent {
+            status: "output".into(),
+            output: None,
+            chunk: Some(hb_tool_executor::OutputChunk {
+                port: "response".into(),
+                text: "철근".into(),
+                index: 0,
+            }),
+            ..match status() {
+                SharedUpdate::Status(event) => *event,
+                SharedUpdate::Finished(_) => unreachable!(),
+            }
+        };
+        streamed.seq = 2;
+        let (_, payload) = state.share_update("s1", "ada", SharedUpdate::Status(Box::new(streamed)), Some(&ada)).await;
+        assert!(payload["chunk"].is_null());
+        let (event_type, payload) = state.share_update("s1", "ada", finished(), Some(&ada)).await;
+        assert_eq!(event_type, "execution_completed");
+        assert!(payload.get("record").is_none());
+
+        let events = state.events.read().await;
+        let logged: Vec<bool> = events["s1"].iter().map(|e| e.payload["output"].is_object()).collect();
+        assert_eq!(logged, [true, true, false, false, false]);
+    }
+}
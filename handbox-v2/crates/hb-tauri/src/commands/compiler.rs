@@ -1,18 +1,41 @@
 //! Compiler command — convert prompt to WorkflowSpec.
 
+use hb_core::error::AppError;
 use crate::state::AppState;
+use hb_compiler::diagnostics::NodePorts;
 use hb_core::graph::WorkflowSpec;
 use tauri::State;
 
+/// Compile `prompt` into a stored workflow. Types inferred through `any`
+/// ports that don't fit where they end up are logged, or with `strict` fail
+/// the compile.
 #[tauri::command]
 pub async fn compile_prompt(
     prompt: String,
+    strict: Option<bool>,
     state: State<'_, AppState>,
-) -> Result<WorkflowSpec, String> {
+) -> Result<WorkflowSpec, AppError> {
     let spec = hb_compiler::compile(&prompt)
         .await
         .map_err(|e| format!("Compilation failed: {e}"))?;
 
+    let warnings = {
+        let registry = state.tool_registry.read().await;
+        let resolve = |tool_ref: &str| {
+            let tool_id = tool_ref.split('@').next().unwrap_or(tool_ref);
+            registry.get(tool_id).map(|tool| NodePorts {
+                inputs: tool.input_schema.ports.clone(),
+                outputs: tool.output_schema.ports.clone(),
+                config: tool.config_schema.clone(),
+            })
+        };
+        hb_compiler::type_checker::check_inferred(&spec, &resolve, strict.unwrap_or(false))
+            .map_err(|e| format!("Compilation failed: {e}"))?
+    };
+    for warning in warnings {
+        tracing::warn!("Compiled workflow {}: {}: {}", spec.id, warning.node_id, warning.message);
+    }
+
     // Store the generated workflow
     let id = spec.id.to_string();
     state.workflows.write().await.insert(id, spec.clone());
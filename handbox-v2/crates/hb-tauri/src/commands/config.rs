@@ -0,0 +1,184 @@
+//! App configuration commands — data directory, portable mode, diagnostics,
+//! retention / garbage collection, and usage reporting.
+
+use hb_core::error::AppError;
+use crate::config::{self, AppConfig};
+use crate::doctor::{self, DoctorReport};
+use crate::state::AppState;
+use crate::usage::{self, UsageConfig, UsageReport, WorkspaceUsage};
+use hb_core::project::Permission;
+use hb_runner::{GcReport, RetentionConfig};
+use serde_json::json;
+use std::path::PathBuf;
+use tauri::State;
+
+/// Return the effective data directory, where it came from, and the config file.
+#[tauri::command]
+pub async fn get_data_dir_config() -> Result<serde_json::Value, AppError> {
+    let resolved = config::init();
+    let config = AppConfig::load(&resolved.config_path);
+    Ok(json!({
+        "data_dir": resolved.path,
+        "source": resolved.source,
+        "config_path": resolved.config_path,
+        "config": config,
+    }))
+}
+
+/// Change the data directory (or toggle portable mode). Takes effect on the
+/// next launch; when `migrate` is set, existing data is moved at that point.
+#[tauri::command]
+pub async fn set_data_dir(
+    data_dir: Option<String>,
+    portable: bool,
+    migrate: bool,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, AppError> {
+    let resolved = config::init();
+    let config_path = config::config_path_for(portable, resolved);
+    let mut config = AppConfig::load(&config_path);
+    config.data_dir = data_dir.filter(|d| !d.trim().is_empty()).map(PathBuf::from);
+    config.portable = portable;
+    config.migrate_from = if migrate {
+        Some(state.data_dir.clone())
+    } else {
+        None
+    };
+    config.save(&config_path)?;
+
+    Ok(json!({
+        "config_path": config_path,
+        "config": config,
+        "restart_required": true,
+    }))
+}
+
+/// Run environment diagnostics (data dir, databases, runtimes, credentials, ports).
+#[tauri::command]
+pub async fn run_doctor(state: State<'_, AppState>) -> Result<DoctorReport, AppError> {
+    let creds = state.llm_credentials.read().await.clone();
+    Ok(doctor::run(&state.data_dir, &creds).await)
+}
+
+#[tauri::command]
+pub async fn get_retention_config(state: State<'_, AppState>) -> Result<RetentionConfig, AppError> {
+    Ok(state.retention_config())
+}
+
+/// Save the retention config. A changed interval applies from the next
+/// scheduled collection.
+#[tauri::command]
+pub async fn set_retention_config(
+    config: RetentionConfig,
+    state: State<'_, AppState>,
+) -> Result<(), AppError> {
+    let content = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
+    std::fs::write(state.retention_path(), content).map_err(AppError::from)
+}
+
+/// Collect garbage now and report what was removed and the space reclaimed.
+#[tauri::command]
+pub async fn run_garbage_collection(state: State<'_, AppState>) -> Result<GcReport, AppError> {
+    Ok(state.run_gc().await)
+}
+
+#[tauri::command]
+pub async fn get_usage_config(state: State<'_, AppState>) -> Result<UsageConfig, AppError> {
+    Ok(UsageConfig::load(&state.data_dir))
+}
+
+#[tauri::command]
+pub async fn set_usage_config(
+    config: UsageConfig,
+    state: State<'_, AppState>,
+) -> Result<(), AppError> {
+    config.save(&state.data_dir).map_err(AppError::from)
+}
+
+/// Disk usage and LLM token consumption over the last `days` days (30 by
+/// default), with warnings for exceeded soft quotas. `workspace_id` limits
+/// the per-workspace section to one workspace.
+#[tauri::command]
+pub async fn get_usage_report(
+    days: Option<u32>,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<UsageReport, AppError> {
+    let only = match workspace_id.as_deref() {
+        Some(id) => {
+            let uuid: uuid::Uuid = id.parse()?;
+            state.authorize(Some(uuid), Permission::View).await?;
+            Some(uuid)
+        }
+        None => None,
+    };
+    let window_days = days.unwrap_or(usage::DEFAULT_WINDOW_DAYS).max(1);
+    let now = chrono::Utc::now();
+    let since = now - chrono::Duration::days(i64::from(window_days));
+    let config = UsageConfig::load(&state.data_dir);
+
+    let tokens = match state.trace_store.read().await.clone() {
+        Some(store) => tokio::task::spawn_blocking(move || store.query_token_usage(since))
+            .await
+            .map_err(|e| format!("Usage query failed: {e}"))??,
+        None => vec![],
+    };
+
+    let (workspaces, executions) = {
+        let pm = state.project_manager.read().await;
+        let workspaces: Vec<_> = pm
+            .list_workspaces()?
+            .into_iter()
+            .filter(|ws| only.is_none_or(|id| ws.id == id))
+            .collect();
+        let executions: Vec<_> = workspaces
+            .iter()
+            .map(|ws| usage::workspace_executions(pm.as_ref(), ws.id, since))
+            .collect();
+        (workspaces, executions)
+    };
+
+    let data_dir = state.data_dir.clone();
+    let (disk, workspace_disks) = tokio::task::spawn_blocking({
+        let workspaces = workspaces.clone();
+        move || {
+            let disks: Vec<_> = workspaces
+                .iter()
+                .map(|ws| usage::workspace_disk(&data_dir, ws))
+                .collect();
+            (usage::data_dir_usage(&data_dir), disks)
+        }
+    })
+    .await
+    .map_err(|e| format!("Disk usage scan failed: {e}"))?;
+
+    let workspaces = workspaces
+        .into_iter()
+        .zip(workspace_disks)
+        .zip(executions)
+        .map(|((ws, disk), executions)| WorkspaceUsage {
+            workspace_id: ws.id,
+            name: ws.name,
+            disk,
+            providers: usage::summarize(
+                tokens.iter().filter(|u| executions.contains(&u.execution_id)),
+                &config,
+            ),
+        })
+        .collect();
+
+    let mut report = UsageReport {
+        generated_at: now,
+        since,
+        window_days,
+        disk,
+        workspaces,
+        providers: usage::summarize(&tokens, &config),
+        warnings: vec![],
+    };
+    report.warnings = usage::check_quotas(&report, &config.quotas);
+    for warning in &report.warnings {
+        tracing::warn!("Usage quota exceeded: {}", warning.message);
+    }
+    Ok(report)
+}
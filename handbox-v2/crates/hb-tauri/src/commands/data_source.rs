@@ -0,0 +1,283 @@
+//! Data source commands — registration and sensitive data classification.
+//!
+//! Scanning is opt-in: a source is only sampled when `scan_data_source` is
+//! called for it. The resulting labels let a workspace policy restrict which
+//! tools may consume the source (see `hb_policy::engine::check_data_sources`).
+
+use crate::state::AppState;
+use hb_core::error::AppError;
+use hb_core::project::{
+    ActivityEntry, ActivityKind, DataClassification, DataSource, DataSourceKind, Permission,
+    SensitiveCategory,
+};
+use hb_policy::classify;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::Path;
+use tauri::State;
+
+/// Bytes read from each sampled file.
+const SAMPLE_BYTES_PER_FILE: u64 = 256 * 1024;
+
+/// Files sampled from a directory source.
+const MAX_SAMPLED_FILES: usize = 50;
+
+#[tauri::command]
+pub async fn list_data_sources(
+    workspace_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<DataSource>, AppError> {
+    let uuid: uuid::Uuid = workspace_id.parse()?;
+    state.authorize(Some(uuid), Permission::View).await?;
+    let pm = state.project_manager.read().await;
+    Ok(pm.get_workspace(uuid)?.data_sources)
+}
+
+/// Register a data source, or update the one with the same ID. Changing a
+/// source's location drops its classification.
+#[tauri::command]
+pub async fn save_data_source(
+    workspace_id: String,
+    source: DataSource,
+    state: State<'_, AppState>,
+) -> Result<DataSource, AppError> {
+    let uuid: uuid::Uuid = workspace_id.parse()?;
+    if source.id.trim().is_empty() {
+        return Err(AppError::invalid_input("A data source ID is required"));
+    }
+    state
+        .authorize(Some(uuid), Permission::ManageWorkspace)
+        .await?;
+    update_sources(&state, uuid, |sources| {
+        let mut source = source;
+        match sources.iter_mut().find(|s| s.id == source.id) {
+            Some(existing) => {
+                if existing.kind == source.kind && existing.path_or_uri == source.path_or_uri {
+                    source.classification = existing.classification.take();
+                } else {
+                    source.classification = None;
+                }
+                *existing = source.clone();
+            }
+            None => sources.push(source.clone()),
+        }
+        Ok(source)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn remove_data_source(
+    workspace_id: String,
+    source_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), AppError> {
+    let uuid: uuid::Uuid = workspace_id.parse()?;
+    state
+        .authorize(Some(uuid), Permission::ManageWorkspace)
+        .await?;
+    update_sources(&state, uuid, |sources| {
+        let before = sources.len();
+        sources.retain(|s| s.id != source_id);
+        if sources.len() == before {
+            return Err(AppError::not_found(format!(
+                "Data source not found: {source_id}"
+            )));
+        }
+        Ok(())
+    })
+    .await
+}
+
+/// Sample a local file or directory source and label it with the sensitive
+/// data categories found. Other source kinds can only be labelled by hand.
+#[tauri::command]
+pub async fn scan_data_source(
+    workspace_id: String,
+    source_id: String,
+    state: State<'_, AppState>,
+) -> Result<DataClassification, AppError> {
+    let uuid: uuid::Uuid = workspace_id.parse()?;
+    let access = state
+        .authorize(Some(uuid), Permission::ManageWorkspace)
+        .await?;
+    let source = find_source(&state, uuid, &source_id).await?;
+    if !matches!(
+        source.kind,
+        DataSourceKind::LocalFile | DataSourceKind::LocalDirectory
+    ) {
+        return Err(AppError::invalid_input(format!(
+            "Only local files and directories can be scanned; label '{}' by hand instead",
+            source.name
+        )));
+    }
+
+    let path = source.path_or_uri.clone();
+    let (matches, sampled_bytes) =
+        tokio::task::spawn_blocking(move || sample(&hb_core::path::resolve(&path)))
+        .await
+        .map_err(|e| format!("Scan failed: {e}"))??;
+    let classification = DataClassification {
+        labels: matches.keys().copied().collect(),
+        matches,
+        sampled_bytes,
+        manual: false,
+        classified_at: chrono::Utc::now(),
+    };
+
+    store_classification(&state, uuid, &source_id, classification.clone()).await?;
+    let summary = format!("Scanned data source '{}'", source.name);
+    state
+        .record_activity(
+            access.stamp(
+                ActivityEntry::new(ActivityKind::DataClassified, &source_id, summary)
+                    .in_workspace(Some(uuid))
+                    .with_details(json!({ "classification": classification })),
+            ),
+        )
+        .await;
+    Ok(classification)
+}
+
+/// Set a source's labels by hand, overriding any scan. An empty list marks
+/// the source as holding no sensitive data.
+#[tauri::command]
+pub async fn set_data_source_labels(
+    workspace_id: String,
+    source_id: String,
+    labels: Vec<SensitiveCategory>,
+    state: State<'_, AppState>,
+) -> Result<DataClassification, AppError> {
+    let uuid: uuid::Uuid = workspace_id.parse()?;
+    let access = state
+        .authorize(Some(uuid), Permission::ChangePolicy)
+        .await?;
+    let source = find_source(&state, uuid, &source_id).await?;
+
+    let mut labels = labels;
+    labels.sort();
+    labels.dedup();
+    let classification = DataClassification {
+        labels,
+        matches: BTreeMap::new(),
+        sampled_bytes: 0,
+        manual: true,
+        classified_at: chrono::Utc::now(),
+    };
+
+    store_classification(&state, uuid, &source_id, classification.clone()).await?;
+    let summary = format!("Labelled data source '{}'", source.name);
+    state
+        .record_activity(
+            access.stamp(
+                ActivityEntry::new(ActivityKind::DataClassified, &source_id, summary)
+                    .in_workspace(Some(uuid))
+                    .with_details(json!({ "classification": classification })),
+            ),
+        )
+        .await;
+    Ok(classification)
+}
+
+async fn find_source(
+    state: &AppState,
+    workspace_id: uuid::Uuid,
+    source_id: &str,
+) -> Result<DataSource, AppError> {
+    let pm = state.project_manager.read().await;
+    pm.get_workspace(workspace_id)?
+        .data_sources
+        .into_iter()
+        .find(|s| s.id == source_id)
+        .ok_or_else(|| AppError::not_found(format!("Data source not found: {source_id}")))
+}
+
+async fn store_classification(
+    state: &AppState,
+    workspace_id: uuid::Uuid,
+    source_id: &str,
+    classification: DataClassification,
+) -> Result<(), AppError> {
+    update_sources(state, workspace_id, |sources| {
+        let source = sources
+            .iter_mut()
+            .find(|s| s.id == source_id)
+            .ok_or_else(|| AppError::not_found(format!("Data source not found: {source_id}")))?;
+        source.classification = Some(classification);
+        Ok(())
+    })
+    .await
+}
+
+async fn update_sources<T>(
+    state: &AppState,
+    workspace_id: uuid::Uuid,
+    update: impl FnOnce(&mut Vec<DataSource>) -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    let pm = state.project_manager.read().await;
+    let mut ws = pm.get_workspace(workspace_id)?;
+    let result = update(&mut ws.data_sources)?;
+    ws.updated_at = chrono::Utc::now();
+    pm.update_workspace(workspace_id, &ws)?;
+    Ok(result)
+}
+
+/// Scan the start of a file, or of the first files found under a
+/// directory. Files that aren't text are skipped.
+fn sample(path: &Path) -> Result<(BTreeMap<SensitiveCategory, usize>, u64), AppError> {
+    let mut matches = BTreeMap::new();
+    let mut sampled = 0;
+    if path.is_file() {
+        let text = read_sample(path).ok_or_else(|| {
+            AppError::invalid_input(format!("Cannot read {} as text", path.display()))
+        })?;
+        sampled += text.len() as u64;
+        classify::scan_into(&mut matches, &text);
+        return Ok((matches, sampled));
+    }
+    if !path.is_dir() {
+        return Err(AppError::not_found(format!(
+            "No such file or directory: {}",
+            path.display()
+        )));
+    }
+
+    let mut pending = vec![path.to_path_buf()];
+    let mut files = 0;
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut entries: Vec<_> = entries.flatten().map(|e| e.path()).collect();
+        entries.sort();
+        for entry in entries {
+            if entry.is_dir() {
+                pending.push(entry);
+            } else if let Some(text) = read_sample(&entry) {
+                sampled += text.len() as u64;
+                classify::scan_into(&mut matches, &text);
+                files += 1;
+                if files >= MAX_SAMPLED_FILES {
+                    return Ok((matches, sampled));
+                }
+            }
+        }
+    }
+    Ok((matches, sampled))
+}
+
+fn read_sample(path: &Path) -> Option<String> {
+    let mut bytes = Vec::new();
+    std::fs::File::open(path)
+        .ok()?
+        .take(SAMPLE_BYTES_PER_FILE)
+        .read_to_end(&mut bytes)
+        .ok()?;
+    let decoded = hb_tool_executor::encoding::decode(&bytes, None).ok()?;
+    // Zero bytes mean binary, except in UTF-16 text
+    if !decoded.encoding.starts_with("UTF-16") && bytes.contains(&0) {
+        return None;
+    }
+    Some(decoded.text)
+}
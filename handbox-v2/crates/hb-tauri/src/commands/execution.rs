@@ -1,27 +1,61 @@
 //! Execution commands — run workflows via hb-runner with real-time status streaming.
 
-use crate::commands::agent_loop::{run_agent_loop, AgentLoopRequest, AgentConversationState};
+use hb_core::error::{AppError, ErrorCode};
+use crate::commands::agent_loop::{request_permission, run_agent_loop, AgentLoopRequest, AgentConversationState};
+use crate::notifications::{Notification, NotificationKind, NotificationService};
 use crate::state::AppState;
-use hb_runner::{AgentTaskParams, ExecutionContext, NodeStatusEvent};
+use hb_core::graph::NodeEntry;
+use hb_core::policy::CostLimit;
+use hb_core::project::{ActivityEntry, ActivityKind, Permission, WorkspaceContext};
+use hb_core::tool::RuntimeSpec;
+use hb_runner::locks::{HeldLock, ToolResources};
+use hb_runner::retry::{AdaptiveRetry, ToolRetryInfo};
+use hb_runner::{
+    AgentTaskParams, ConcurrencyLimits, ExecutionContext, LockConfig, NodeCancellation, NodeStatusEvent, PauseSignal, StatusCallback,
+    StatusLog, TimeLimits,
+};
+use hb_tool_executor::{CommandGate, CommandRequest, Secrets};
 use serde_json::json;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
-use tauri::{AppHandle, Emitter, State};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
 use tokio::sync::Mutex;
 
 /// Event name for node status updates
 const NODE_STATUS_EVENT: &str = "node-status";
 
+/// How long a node waits for another execution to release a resource it
+/// needs before failing.
+const RESOURCE_LOCK_WAIT: Duration = Duration::from_secs(60);
+
+/// Days of recorded runs that retries are tuned on.
+const RETRY_HISTORY_DAYS: i64 = 30;
+
+/// How long a finished execution stays tracked, so a client that reconnects
+/// right after it ends still gets its status and final events.
+const FINISHED_RETENTION: chrono::Duration = chrono::Duration::minutes(10);
+
 // ============================================================================
 // Execution tracking state
 // ============================================================================
 
 #[derive(Debug, Clone)]
 pub struct ExecutionInfo {
-    pub status: String,  // "running", "completed", "failed", "cancelled"
+    pub status: String,  // "running", "paused", "completed", "failed", "cancelled"
     pub cancelled: Arc<AtomicBool>,
+    pub pause: Arc<PauseSignal>,
+    pub node_cancellation: Arc<NodeCancellation>,
     pub started_at: chrono::DateTime<chrono::Utc>,
+    /// Status events so far, for clients that reconnect mid-run.
+    pub events: Arc<StatusLog>,
+    /// Workspace the execution runs in; controlling it needs permission to
+    /// run workflows there.
+    pub workspace_id: Option<uuid::Uuid>,
+    /// When the execution ended; finished executions are dropped from the
+    /// tracker [`FINISHED_RETENTION`] later.
+    pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 pub struct ExecutionTrackerState {
@@ -36,6 +70,69 @@ impl Default for ExecutionTrackerState {
     }
 }
 
+impl ExecutionTrackerState {
+    /// Start tracking an execution, dropping executions that finished more
+    /// than [`FINISHED_RETENTION`] ago.
+    pub(crate) async fn track(&self, execution_id: String, info: ExecutionInfo) {
+        let mut execs = self.executions.lock().await;
+        let cutoff = chrono::Utc::now() - FINISHED_RETENTION;
+        execs.retain(|_, info| info.finished_at.is_none_or(|at| at > cutoff));
+        execs.insert(execution_id, info);
+    }
+
+    /// Record the final status of an execution.
+    pub(crate) async fn finish(&self, execution_id: &str, status: &str) {
+        if let Some(info) = self.executions.lock().await.get_mut(execution_id) {
+            info.status = status.to_string();
+            info.finished_at = Some(chrono::Utc::now());
+        }
+    }
+
+    /// Stop a running or paused execution; nodes already running are
+    /// stopped, and nothing more starts.
+    pub(crate) async fn cancel(&self, execution_id: &str) -> Result<(), AppError> {
+        let mut execs = self.executions.lock().await;
+        let info = execs
+            .get_mut(execution_id)
+            .ok_or_else(|| AppError::not_found(format!("Execution not found: {execution_id}")))?;
+        if info.status == "running" || info.status == "paused" {
+            info.cancelled.store(true, Ordering::Relaxed);
+            info.status = "cancelled".to_string();
+            tracing::info!("Execution {} cancelled", execution_id);
+        }
+        Ok(())
+    }
+}
+
+/// Check that the caller may pause, resume or cancel a tracked execution:
+/// that takes permission to run workflows in the workspace it runs in.
+async fn authorize_control(
+    state: &AppState,
+    tracker: &ExecutionTrackerState,
+    execution_id: &str,
+) -> Result<(), AppError> {
+    let workspace_id = tracker
+        .executions
+        .lock()
+        .await
+        .get(execution_id)
+        .ok_or_else(|| AppError::not_found(format!("Execution not found: {execution_id}")))?
+        .workspace_id;
+    state.authorize(workspace_id, Permission::Execute).await?;
+    Ok(())
+}
+
+/// Whether any node, at any depth, runs one of `tools` (IDs without a
+/// version).
+fn runs_any(nodes: &[NodeEntry], tools: &HashSet<String>) -> bool {
+    nodes.iter().any(|entry| match entry {
+        NodeEntry::Primitive(node) => {
+            tools.contains(node.tool_ref.split('@').next().unwrap_or(&node.tool_ref))
+        }
+        _ => entry.subgraphs().into_iter().any(|sub| runs_any(&sub.nodes, tools)),
+    })
+}
+
 // ============================================================================
 // Commands
 // ============================================================================
@@ -43,25 +140,193 @@ impl Default for ExecutionTrackerState {
 #[tauri::command]
 pub async fn execute_workflow(
     workflow_id: String,
-    state: State<'_, AppState>,
-    tracker: State<'_, Arc<ExecutionTrackerState>>,
-    conversations: State<'_, Arc<AgentConversationState>>,
+    workspace_id: Option<String>,
     app: AppHandle,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, AppError> {
+    run_workflow(&app, &workflow_id, workspace_id.as_deref(), uuid::Uuid::new_v4(), None).await
+}
+
+/// Run a stored workflow, streaming node status to the frontend. `observer`
+/// also receives every status event, e.g. to share it with a collaboration
+/// session.
+pub(crate) async fn run_workflow(
+    app: &AppHandle,
+    workflow_id: &str,
+    workspace_id: Option<&str>,
+    execution_uuid: uuid::Uuid,
+    observer: Option<StatusCallback>,
+) -> Result<serde_json::Value, AppError> {
+    let state = app.state::<AppState>();
     let workflows = state.workflows.read().await;
     let spec = workflows
-        .get(&workflow_id)
+        .get(workflow_id)
         .ok_or_else(|| format!("Workflow not found: {workflow_id}"))?
         .clone();
     drop(workflows); // Release lock before execution
 
-    // Create execution context with status callback
-    let app_clone = app.clone();
-    let ctx = ExecutionContext::default().with_status_callback(move |event: NodeStatusEvent| {
-        if let Err(e) = app_clone.emit(NODE_STATUS_EVENT, &event) {
-            tracing::warn!("Failed to emit node status event: {e}");
+    run_spec(app, spec, workspace_id, execution_uuid, observer).await
+}
+
+/// Run a failed, cancelled or interrupted execution of a stored workflow
+/// again under the same ID, in the workspace it first ran in: nodes that
+/// completed keep their recorded outputs, only the rest of the workflow
+/// runs. Paused executions continue with [`resume_execution`] instead.
+#[tauri::command]
+pub async fn resume_from_checkpoint(
+    execution_id: String,
+    app: AppHandle,
+) -> Result<serde_json::Value, AppError> {
+    let execution_uuid: uuid::Uuid = execution_id
+        .parse()
+        .map_err(|_| AppError::invalid_input(format!("Invalid execution ID: {execution_id}")))?;
+    let state = app.state::<AppState>();
+    let record = {
+        let guard = state.trace_store.read().await;
+        let store = guard.as_ref().ok_or("Trace store not initialized")?;
+        store
+            .query_execution(execution_uuid)?
+            .ok_or_else(|| AppError::not_found(format!("Execution not found: {execution_id}")))?
+    };
+    let workspace_id = crate::commands::trace::execution_workspace(&state, &record)
+        .await?
+        .map(|id| id.to_string());
+    let workflow_id = record.workflow_id.to_string();
+    let spec = state
+        .workflows
+        .read()
+        .await
+        .get(&workflow_id)
+        .ok_or_else(|| AppError::not_found(format!("Workflow not found: {workflow_id}")))?
+        .clone();
+
+    start_spec(&app, spec, workspace_id.as_deref(), execution_uuid, None, RunKind::Resume).await
+}
+
+/// Run `spec` as [`run_workflow`] does, whether or not it is stored (e.g.
+/// an approved scheduled run).
+pub(crate) async fn run_spec(
+    app: &AppHandle,
+    spec: hb_core::graph::WorkflowSpec,
+    workspace_id: Option<&str>,
+    execution_uuid: uuid::Uuid,
+    observer: Option<StatusCallback>,
+) -> Result<serde_json::Value, AppError> {
+    start_spec(app, spec, workspace_id, execution_uuid, observer, RunKind::Run).await
+}
+
+/// Run a test case of `spec` for a publish check. Unlike [`run_spec`] it
+/// sends no notification and records no activity, so checking a workflow
+/// leaves no trace beyond its execution record.
+pub(crate) async fn run_test_case(
+    app: &AppHandle,
+    spec: hb_core::graph::WorkflowSpec,
+    workspace_id: Option<&str>,
+    execution_uuid: uuid::Uuid,
+) -> Result<serde_json::Value, AppError> {
+    start_spec(app, spec, workspace_id, execution_uuid, None, RunKind::Test).await
+}
+
+/// What [`start_spec`] is running a workflow for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RunKind {
+    /// A new run started by the user or a schedule.
+    Run,
+    /// The earlier execution `execution_uuid`, continued from its checkpoint.
+    Resume,
+    /// A publish check's test case: a new run without notification or
+    /// activity entry.
+    Test,
+}
+
+/// Run `spec` as `kind` says, under `execution_uuid`.
+async fn start_spec(
+    app: &AppHandle,
+    spec: hb_core::graph::WorkflowSpec,
+    workspace_id: Option<&str>,
+    execution_uuid: uuid::Uuid,
+    observer: Option<StatusCallback>,
+    kind: RunKind,
+) -> Result<serde_json::Value, AppError> {
+    let state = app.state::<AppState>();
+    let tracker = app.state::<Arc<ExecutionTrackerState>>();
+    let conversations = app.state::<Arc<AgentConversationState>>();
+    let notifications = app.state::<Arc<NotificationService>>();
+
+    let ws_id = crate::commands::workflow::parse_workspace_id(workspace_id.map(str::to_string))?;
+    let access = state.authorize(ws_id, Permission::Execute).await?;
+
+    let workspace = match ws_id {
+        Some(ws_id) => state.project_manager.read().await.get_workspace(ws_id).ok(),
+        None => None,
+    };
+
+    // Saved workflows that reference nodes run, and the spec with them
+    // expanded so the policy checks below see every node that will run
+    let library = {
+        let workflows = state.workflows.read().await;
+        hb_runner::workflow_ref::collect(&spec, |id| workflows.get(&id.to_string()).cloned())
+    };
+    hb_runner::workflow_ref::check(&spec, &library).map_err(AppError::invalid_input)?;
+    // Runs reach workflows of their own workspace and of none, as far as the
+    // caller may view them
+    let referenced: Vec<String> = library.keys().map(uuid::Uuid::to_string).collect();
+    for (id, home) in crate::commands::workflow::workflow_workspaces(&state, &referenced).await? {
+        if home.is_some() && home != ws_id {
+            return Err(AppError::new(
+                ErrorCode::PermissionDenied,
+                format!("Referenced workflow {id} belongs to another workspace"),
+            ));
         }
-    });
+        state.authorize(home, Permission::View).await?;
+    }
+    let expanded = hb_runner::workflow_ref::expand(&spec, &library);
+
+    // Refuse to feed labelled data sources to tools the policy rules out
+    if let Some(ws) = &workspace {
+        if let Some(policy) = &ws.default_policy {
+            hb_policy::engine::check_data_sources(&policy.sensitive_data, &expanded, &ws.data_sources)?;
+        }
+    }
+
+    // Refuse runs whose estimate already exceeds the policy's cost limits,
+    // or without a policy the app's spend quota
+    let limit = match workspace.as_ref().and_then(|ws| ws.default_policy.as_ref()) {
+        Some(policy) => policy.cost_limit.clone(),
+        None => CostLimit {
+            max_execution_time_secs: None,
+            max_node_time_secs: None,
+            max_cost_usd: crate::usage::UsageConfig::load(&state.data_dir).quotas.max_cost_usd,
+            max_tokens: None,
+        },
+    };
+    let limited = limit.max_cost_usd.is_some()
+        || limit.max_tokens.is_some()
+        || limit.max_execution_time_secs.is_some()
+        || limit.max_node_time_secs.is_some();
+    if limited {
+        // Runs take the workflow's variable defaults
+        let variables = hb_core::graph::params::defaults(&spec.variables);
+        let catalog = super::trace::cost_catalog(&state).await;
+        let estimate = hb_policy::estimate::estimate_cost(&expanded, &variables, &catalog);
+        for warning in hb_policy::budget::preflight(&limit, &estimate)? {
+            tracing::warn!("Workflow {}: {warning}", spec.id);
+        }
+    }
+
+    // Create execution context with status callback. The log numbers the
+    // events and keeps them for clients that reconnect mid-run.
+    let app_clone = app.clone();
+    let status_log = Arc::new(StatusLog::new());
+    let ctx = ExecutionContext::default()
+        .with_status_log(status_log.clone())
+        .with_status_callback(move |event: NodeStatusEvent| {
+            if let Err(e) = app_clone.emit(NODE_STATUS_EVENT, &event) {
+                tracing::warn!("Failed to emit node status event: {e}");
+            }
+            if let Some(observer) = &observer {
+                observer(event);
+            }
+        });
 
     // Inject trace store for span recording
     let ctx = {
@@ -73,6 +338,187 @@ pub async fn execute_workflow(
         }
     };
 
+    // Inject node output cache
+    let ctx = match state.execution_cache.read().await.as_ref() {
+        Some(cache) => ctx.with_cache(Arc::clone(cache)),
+        None => ctx,
+    };
+
+    // Inject crash-recovery journal
+    let ctx = match state.journal.read().await.as_ref() {
+        Some(journal) => ctx.with_journal(Arc::clone(journal)),
+        None => ctx,
+    };
+
+    // Inject the workspace's environment variables
+    let ctx = match ws_id {
+        Some(ws_id) => ctx.with_env(state.project_manager.read().await.env_map(ws_id)?),
+        None => ctx,
+    };
+
+    // Let tools resolve relative paths against the workspace root
+    let ctx = match &workspace {
+        Some(ws) => ctx.with_workspace(WorkspaceContext::from(ws)),
+        None => ctx,
+    };
+
+    // Programs outside the workspace's command allowlist ask the user first
+    let command_policy = workspace
+        .as_ref()
+        .and_then(|ws| ws.default_policy.as_ref())
+        .map(|policy| policy.commands.clone())
+        .unwrap_or_default();
+    let approval_app = app.clone();
+    let gate = CommandGate::new(command_policy).with_approver(move |request: CommandRequest| {
+        let app = approval_app.clone();
+        async move {
+            let warning = format!("'{}' is not on the command allowlist", request.program);
+            request_permission(&app, &request.display(), &warning, &execution_uuid.to_string()).await
+        }
+    });
+    let ctx = ctx.with_command_gate(gate).with_workflows(library);
+
+    // Pack tools run with the runtime they declare; process runtimes may
+    // reference service keys from the credential store
+    let runtimes = state
+        .tool_registry
+        .read()
+        .await
+        .list()
+        .into_iter()
+        .filter(|tool| !matches!(tool.runtime, RuntimeSpec::Native))
+        .map(|tool| (tool.tool_id.clone(), tool.runtime.clone()))
+        .collect();
+    let secrets = Secrets::new(state.llm_credentials.read().await.service_keys.clone());
+    let ctx = ctx.with_tool_runtimes(runtimes).with_secrets(secrets);
+
+    // Warn about (or kill) nodes that go silent, per the workspace policy
+    let ctx = match workspace.as_ref().and_then(|ws| ws.default_policy.as_ref()) {
+        Some(policy) => ctx.with_liveness(policy.liveness.clone()),
+        None => ctx,
+    };
+
+    // Stop nodes and the run at the policy's time limits
+    let ctx = match workspace.as_ref().and_then(|ws| ws.default_policy.as_ref()) {
+        Some(policy) => ctx.with_time_limits(TimeLimits::from(&policy.cost_limit)),
+        None => ctx,
+    };
+
+    let concurrency = workspace
+        .as_ref()
+        .and_then(|ws| ws.default_policy.as_ref())
+        .map(|policy| policy.concurrency.clone())
+        .unwrap_or_default();
+
+    // Inject the workspace's edge capture policy for time-travel inspection
+    let edge_capture = workspace
+        .and_then(|ws| ws.default_policy)
+        .map(|policy| policy.edge_capture)
+        .filter(|capture| capture.enabled);
+    let ctx = match edge_capture {
+        Some(capture) => ctx.with_edge_capture(capture),
+        None => ctx,
+    };
+
+    // Serialize executions that write the same indexes or output files
+    let tools = state
+        .tool_registry
+        .read()
+        .await
+        .list()
+        .into_iter()
+        .filter(|tool| !tool.resources.is_empty())
+        .map(|tool| {
+            let config_defaults = tool
+                .config_schema
+                .iter()
+                .filter_map(|f| Some((f.name.clone(), f.default_value.clone()?)))
+                .collect();
+            let resources = ToolResources {
+                resources: tool.resources.clone(),
+                config_defaults,
+            };
+            (tool.tool_id.clone(), resources)
+        })
+        .collect();
+    let ctx = ctx.with_resource_locks(LockConfig {
+        table: Arc::clone(&state.resource_locks),
+        tools,
+        wait: Some(RESOURCE_LOCK_WAIT),
+        workspace: ws_id,
+    });
+
+    // Keep wide levels from firing more LLM calls or containers at once than
+    // the workspace policy allows
+    let tool_classes = state
+        .tool_registry
+        .read()
+        .await
+        .list()
+        .into_iter()
+        .filter_map(|tool| Some((tool.tool_id.clone(), tool.resource_class()?.to_string())))
+        .collect();
+    let ctx = ctx.with_concurrency(ConcurrencyLimits::new(&concurrency, tool_classes));
+
+    // Skip chains of pure tools whose subgraph ran before, in workflows
+    // that opt in
+    let pure_tools = state
+        .tool_registry
+        .read()
+        .await
+        .list()
+        .into_iter()
+        .collect();
+    let ctx = ctx.with_pure_tools(pure_tools);
+    let variadic_ports = state
+        .tool_registry
+        .read()
+        .await
+        .list()
+        .into_iter()
+        .collect();
+    let ctx = ctx.with_variadic_ports(variadic_ports);
+
+    // Intermediate files go to a directory of the run's own
+    let ctx = ctx.with_scratch(state.scratch_space());
+
+    // Tune retries per tool from its declared error types and recent outcomes
+    let since = chrono::Utc::now() - chrono::Duration::days(RETRY_HISTORY_DAYS);
+    let outcomes = match state.trace_store.read().await.as_ref() {
+        Some(store) => store.query_tool_outcomes(since).unwrap_or_else(|e| {
+            tracing::warn!("Failed to load tool outcomes for retry tuning: {e}");
+            Vec::new()
+        }),
+        None => Vec::new(),
+    };
+    let mut outcomes: HashMap<_, _> = outcomes
+        .into_iter()
+        .map(|o| (o.tool_id.clone(), o))
+        .collect();
+    let mut retry_tools: HashMap<String, ToolRetryInfo> = state
+        .tool_registry
+        .read()
+        .await
+        .list()
+        .into_iter()
+        .map(|tool| {
+            let info = ToolRetryInfo {
+                error_model: Some(tool.error_model.clone()),
+                outcomes: outcomes.remove(&tool.tool_id),
+            };
+            (tool.tool_id.clone(), info)
+        })
+        .collect();
+    // MCP and agent tools have history but no declared error model
+    retry_tools.extend(outcomes.into_iter().map(|(id, o)| {
+        let info = ToolRetryInfo {
+            error_model: None,
+            outcomes: Some(o),
+        };
+        (id, info)
+    }));
+    let ctx = ctx.with_adaptive_retry(AdaptiveRetry { tools: retry_tools });
+
     // Inject active LLM provider from user settings
     let ctx = {
         let creds = state.llm_credentials.read().await;
@@ -83,6 +529,22 @@ pub async fn execute_workflow(
         }
     };
 
+    // LLM nodes on llama.cpp need its server up before they run
+    if state.llm_credentials.read().await.active_provider.as_deref() == Some("llamacpp") {
+        let llm_tools: HashSet<String> = state
+            .tool_registry
+            .read()
+            .await
+            .list()
+            .into_iter()
+            .filter(|tool| tool.resource_class() == Some("llm"))
+            .map(|tool| tool.tool_id.clone())
+            .collect();
+        if runs_any(&expanded.nodes, &llm_tools) {
+            crate::local_models::ensure_any_server().await?;
+        }
+    }
+
     // Inject agent task executor so agent-task nodes run the real agent loop
     let resolved_provider = state.llm_credentials.read().await.active_provider.clone();
     let conversations_arc = conversations.inner().clone();
@@ -126,34 +588,106 @@ pub async fn execute_workflow(
     });
 
     // Register execution for tracking
-    let execution_id = uuid::Uuid::new_v4().to_string();
+    let execution_id = execution_uuid.to_string();
     let cancelled_flag = ctx.cancelled.clone();
-    {
-        let mut execs = tracker.executions.lock().await;
-        execs.insert(execution_id.clone(), ExecutionInfo {
+    let pause = ctx.pause.clone();
+    let node_cancellation = ctx.node_cancellation.clone();
+    state
+        .shutdown
+        .register(execution_uuid, spec.id, cancelled_flag.clone())?;
+    tracker
+        .track(execution_id.clone(), ExecutionInfo {
             status: "running".to_string(),
             cancelled: cancelled_flag,
+            pause,
+            node_cancellation,
             started_at: chrono::Utc::now(),
-        });
-    }
+            events: status_log,
+            workspace_id: ws_id,
+            finished_at: None,
+        })
+        .await;
 
-    // Execute with streaming
-    let result = hb_runner::execute_with_context(&spec, ctx).await;
+    // Execute with streaming (same ID as the tracker so traces line up)
+    let result = if kind == RunKind::Resume {
+        hb_runner::scheduler::resume_execution(execution_uuid, &spec, ctx).await
+    } else {
+        hb_runner::scheduler::run_dag_with_context(execution_uuid, &spec, ctx).await
+    };
+    state.shutdown.complete(execution_uuid);
 
     // Update status
-    {
-        let mut execs = tracker.executions.lock().await;
-        if let Some(info) = execs.get_mut(&execution_id) {
-            info.status = match &result {
-                Ok(_) => "completed".to_string(),
-                Err(hb_runner::RunnerError::Cancelled) => "cancelled".to_string(),
-                Err(_) => "failed".to_string(),
-            };
+    let status = match &result {
+        Ok(record) if record.status == hb_core::trace::ExecutionStatus::Cancelled => "cancelled",
+        Ok(record) if record.status == hb_core::trace::ExecutionStatus::CompletedWithViolations => {
+            "completed_with_violations"
         }
+        Ok(_) => "completed",
+        Err(hb_runner::RunnerError::Cancelled) => "cancelled",
+        Err(_) => "failed",
+    };
+    tracker.finish(&execution_id, status).await;
+
+    // Notify on completion or failure (cancellation was user-initiated)
+    let workflow_name = spec.meta.name.clone();
+    let notification = match &result {
+        Ok(record) if record.status == hb_core::trace::ExecutionStatus::Failed => Some(Notification::new(
+            NotificationKind::ExecutionFailed,
+            format!("{workflow_name} failed"),
+            format!("{} of {} node(s) failed", record.failed_nodes, record.total_nodes),
+        )),
+        // Completed, but the outputs aren't what the workflow promises
+        Ok(record) if !record.contract_violations.is_empty() => Some(Notification::new(
+            NotificationKind::ExecutionFailed,
+            format!("{workflow_name} broke its output contract"),
+            record
+                .contract_violations
+                .iter()
+                .map(|v| format!("{}.{}: {}", v.node_id, v.port, v.message))
+                .collect::<Vec<_>>()
+                .join("; "),
+        )),
+        Ok(record) => Some(Notification::new(
+            NotificationKind::ExecutionCompleted,
+            format!("{workflow_name} completed"),
+            format!("{} node(s) completed, {} from cache", record.completed_nodes, record.cache_hits),
+        )),
+        Err(hb_runner::RunnerError::Cancelled) => None,
+        Err(e) => Some(Notification::new(
+            NotificationKind::ExecutionFailed,
+            format!("{workflow_name} failed"),
+            e.to_string(),
+        )),
+    };
+    if let Some(notification) = notification.filter(|_| kind != RunKind::Test) {
+        let notification = notification
+            .with_workspace(workspace_id.map(str::to_string))
+            .with_execution(execution_id.clone());
+        notifications.notify(app, notification).await;
+    }
+
+    let outcome = match &result {
+        Ok(record) => serde_json::to_value(&record.status).unwrap_or_default(),
+        Err(hb_runner::RunnerError::Cancelled) => json!("cancelled"),
+        Err(_) => json!("failed"),
+    };
+    let summary = match kind {
+        RunKind::Run => Some(format!("Ran workflow '{workflow_name}'")),
+        RunKind::Resume => Some(format!("Resumed workflow '{workflow_name}'")),
+        RunKind::Test => None,
+    };
+    if let Some(summary) = summary {
+        state
+            .record_activity(access.stamp(
+                ActivityEntry::new(ActivityKind::ExecutionRun, execution_id.clone(), summary)
+                    .in_workspace(ws_id)
+                    .with_details(json!({ "workflow_id": spec.id, "status": outcome })),
+            ))
+            .await;
     }
 
-    let record = result.map_err(|e| format!("Execution failed: {e}"))?;
-    let mut value = serde_json::to_value(&record).map_err(|e| e.to_string())?;
+    let record = result?;
+    let mut value = serde_json::to_value(&record)?;
     // Attach execution_id so frontend can track/cancel
     if let Some(obj) = value.as_object_mut() {
         obj.insert("execution_id".to_string(), json!(execution_id));
@@ -165,25 +699,26 @@ pub async fn execute_workflow(
 #[tauri::command]
 pub async fn execute_workflow_simple(
     workflow_id: String,
+    workspace_id: Option<String>,
     state: State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, AppError> {
+    let ws_id = crate::commands::workflow::parse_workspace_id(workspace_id)?;
+    state.authorize(ws_id, Permission::Execute).await?;
     let workflows = state.workflows.read().await;
     let spec = workflows
         .get(&workflow_id)
         .ok_or_else(|| format!("Workflow not found: {workflow_id}"))?;
 
-    let record = hb_runner::execute(spec)
-        .await
-        .map_err(|e| format!("Execution failed: {e}"))?;
+    let record = hb_runner::execute(spec).await?;
 
-    serde_json::to_value(&record).map_err(|e| e.to_string())
+    serde_json::to_value(&record).map_err(AppError::from)
 }
 
 #[tauri::command]
 pub async fn get_execution_status(
     execution_id: String,
     tracker: State<'_, Arc<ExecutionTrackerState>>,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let execs = tracker.executions.lock().await;
     if let Some(info) = execs.get(&execution_id) {
         Ok(info.status.clone())
@@ -192,22 +727,138 @@ pub async fn get_execution_status(
     }
 }
 
+/// Pause a running execution: nodes already running finish, but no new ones
+/// start until it is resumed with [`resume_execution`] (or cancelled).
 #[tauri::command]
-pub async fn cancel_execution(
+pub async fn pause_execution(
     execution_id: String,
+    state: State<'_, AppState>,
     tracker: State<'_, Arc<ExecutionTrackerState>>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
+    authorize_control(&state, &tracker, &execution_id).await?;
     let mut execs = tracker.executions.lock().await;
-    if let Some(info) = execs.get_mut(&execution_id) {
-        if info.status == "running" {
-            info.cancelled.store(true, Ordering::Relaxed);
-            info.status = "cancelled".to_string();
-            tracing::info!("Execution {} cancelled", execution_id);
+    let info = execs
+        .get_mut(&execution_id)
+        .ok_or_else(|| format!("Execution not found: {execution_id}"))?;
+    match info.status.as_str() {
+        "running" => {
+            info.pause.pause();
+            info.status = "paused".to_string();
+            tracing::info!("Execution {} pausing", execution_id);
+            Ok(())
         }
-        Ok(())
-    } else {
-        Err(format!("Execution not found: {execution_id}"))
+        "paused" => Ok(()),
+        status => Err(format!("Execution {execution_id} is {status}").into()),
+    }
+}
+
+/// Let a paused execution dispatch nodes again.
+#[tauri::command]
+pub async fn resume_execution(
+    execution_id: String,
+    state: State<'_, AppState>,
+    tracker: State<'_, Arc<ExecutionTrackerState>>,
+) -> Result<(), AppError> {
+    authorize_control(&state, &tracker, &execution_id).await?;
+    let mut execs = tracker.executions.lock().await;
+    let info = execs
+        .get_mut(&execution_id)
+        .ok_or_else(|| format!("Execution not found: {execution_id}"))?;
+    match info.status.as_str() {
+        "paused" => {
+            info.pause.resume();
+            info.status = "running".to_string();
+            tracing::info!("Execution {} resumed", execution_id);
+            Ok(())
+        }
+        "running" => Ok(()),
+        status => Err(format!("Execution {execution_id} is {status}").into()),
+    }
+}
+
+#[tauri::command]
+pub async fn cancel_execution(
+    execution_id: String,
+    state: State<'_, AppState>,
+    tracker: State<'_, Arc<ExecutionTrackerState>>,
+) -> Result<(), AppError> {
+    authorize_control(&state, &tracker, &execution_id).await?;
+    tracker.cancel(&execution_id).await
+}
+
+/// Cancel one node of a running execution and everything downstream of it;
+/// unrelated branches keep running, and the run completes when they do. A
+/// node that is running is stopped. Nested nodes are named as their status
+/// events name them, e.g. `{ref_node}/{inner_id}` in a referenced workflow.
+#[tauri::command]
+pub async fn cancel_node(
+    execution_id: String,
+    node_id: String,
+    state: State<'_, AppState>,
+    tracker: State<'_, Arc<ExecutionTrackerState>>,
+) -> Result<(), AppError> {
+    authorize_control(&state, &tracker, &execution_id).await?;
+    let execs = tracker.executions.lock().await;
+    let info = execs
+        .get(&execution_id)
+        .ok_or_else(|| format!("Execution not found: {execution_id}"))?;
+    if info.status != "running" && info.status != "paused" {
+        return Err(format!("Execution {execution_id} is {}", info.status).into());
+    }
+    if info.node_cancellation.cancel(&node_id).map_err(AppError::not_found)? {
+        tracing::info!("Node {} of execution {} cancelled", node_id, execution_id);
     }
+    Ok(())
+}
+
+/// Status events of an execution after sequence number `after_seq` (all of
+/// them when unset), so a client that missed events can catch up.
+#[tauri::command]
+pub async fn get_execution_events(
+    execution_id: String,
+    after_seq: Option<u64>,
+    tracker: State<'_, Arc<ExecutionTrackerState>>,
+) -> Result<Vec<NodeStatusEvent>, AppError> {
+    let execs = tracker.executions.lock().await;
+    let info = execs
+        .get(&execution_id)
+        .ok_or_else(|| format!("Execution not found: {execution_id}"))?;
+    Ok(info.events.since(after_seq.unwrap_or(0)))
+}
+
+/// IDs of executions still running, e.g. for a window that reloaded.
+#[tauri::command]
+pub async fn list_running_executions(
+    tracker: State<'_, Arc<ExecutionTrackerState>>,
+) -> Result<Vec<String>, AppError> {
+    let execs = tracker.executions.lock().await;
+    Ok(execs
+        .iter()
+        .filter(|(_, info)| info.status == "running")
+        .map(|(id, _)| id.clone())
+        .collect())
+}
+
+/// List executions interrupted by a previous shutdown or crash, so the UI can
+/// offer to resume them.
+#[tauri::command]
+pub async fn list_interrupted_executions(
+    state: State<'_, AppState>,
+) -> Result<Vec<serde_json::Value>, AppError> {
+    let guard = state.trace_store.read().await;
+    let store = guard.as_ref().ok_or("Trace store not initialized")?;
+    let records = store
+        .query_executions_by_status(&hb_core::trace::ExecutionStatus::Interrupted)?;
+    Ok(records
+        .iter()
+        .filter_map(|r| serde_json::to_value(r).ok())
+        .collect())
+}
+
+/// List resource locks currently held by running executions.
+#[tauri::command]
+pub async fn list_resource_locks(state: State<'_, AppState>) -> Result<Vec<HeldLock>, AppError> {
+    Ok(state.resource_locks.held())
 }
 
 /// Execute a single agent-task node (called from frontend for agent nodes on canvas)
@@ -221,7 +872,7 @@ pub async fn execute_agent_node(
     working_dir: Option<String>,
     conversations: State<'_, Arc<AgentConversationState>>,
     app: AppHandle,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, AppError> {
     // Emit running status
     let _ = app.emit(NODE_STATUS_EVENT, json!({
         "node_id": node_id,
@@ -249,7 +900,7 @@ pub async fn execute_agent_node(
         project_id: None,
     };
 
-    match run_agent_loop(request, &*conversations, &app).await {
+    match run_agent_loop(request, &conversations, &app).await {
         Ok(result) => {
             let _ = app.emit(NODE_STATUS_EVENT, json!({
                 "node_id": node_id,
@@ -273,7 +924,88 @@ pub async fn execute_agent_node(
                 "status": "failed",
                 "error": e,
             }));
-            Err(e)
+            Err(e.into())
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::testing::{shared_workspace, team_state};
+    use hb_core::error::ErrorCode;
+    use hb_core::project::Role;
+
+    fn tracked(workspace_id: uuid::Uuid) -> (ExecutionTrackerState, Arc<AtomicBool>) {
+        let tracker = ExecutionTrackerState::default();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let info = ExecutionInfo {
+            status: "running".into(),
+            cancelled: cancelled.clone(),
+            pause: Default::default(),
+            node_cancellation: Default::default(),
+            started_at: chrono::Utc::now(),
+            events: Default::default(),
+            workspace_id: Some(workspace_id),
+            finished_at: None,
+        };
+        tracker.executions.try_lock().unwrap().insert("run".into(), info);
+        (tracker, cancelled)
+    }
+
+    #[tokio::test]
+    async fn controlling_a_run_needs_execute_in_its_workspace() {
+        let state = team_state("vic").await;
+        let ws = shared_workspace(&state, &[("vic", Role::Viewer)]).await;
+        let (tracker, cancelled) = tracked(ws);
+        let app = tauri::test::mock_app();
+        app.manage(state);
+        app.manage(Arc::new(tracker));
+
+        let err = pause_execution("run".into(), app.state(), app.state()).await.unwrap_err();
+        assert_eq!(err.code, ErrorCode::PermissionDenied);
+        let err = cancel_node("run".into(), "a".into(), app.state(), app.state())
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::PermissionDenied);
+        let err = cancel_execution("run".into(), app.state(), app.state()).await.unwrap_err();
+        assert_eq!(err.code, ErrorCode::PermissionDenied);
+        assert!(!cancelled.load(Ordering::Relaxed));
+
+        let err = cancel_execution("nope".into(), app.state(), app.state()).await.unwrap_err();
+        assert_eq!(err.code, ErrorCode::NotFound);
+    }
+
+    #[tokio::test]
+    async fn runners_may_pause_and_cancel() {
+        let state = team_state("rita").await;
+        let ws = shared_workspace(&state, &[("rita", Role::Runner)]).await;
+        let (tracker, cancelled) = tracked(ws);
+        let app = tauri::test::mock_app();
+        app.manage(state);
+        app.manage(Arc::new(tracker));
+
+        pause_execution("run".into(), app.state(), app.state()).await.unwrap();
+        cancel_execution("run".into(), app.state(), app.state()).await.unwrap();
+        assert!(cancelled.load(Ordering::Relaxed));
+    }
+
+    #[tokio::test]
+    async fn finished_executions_are_evicted() {
+        let (tracker, _) = tracked(uuid::Uuid::new_v4());
+        tracker.finish("run", "completed").await;
+        let info = tracker.executions.lock().await["run"].clone();
+        assert_eq!(info.status, "completed");
+
+        // Still there for clients that reconnect right away
+        tracker.track("next".into(), info.clone()).await;
+        assert!(tracker.executions.lock().await.contains_key("run"));
+
+        tracker.executions.lock().await.get_mut("run").unwrap().finished_at =
+            Some(chrono::Utc::now() - FINISHED_RETENTION - chrono::Duration::seconds(1));
+        tracker.track("last".into(), ExecutionInfo { finished_at: None, ..info }).await;
+        let execs = tracker.executions.lock().await;
+        assert!(!execs.contains_key("run"));
+        assert!(execs.contains_key("next") && execs.contains_key("last"));
+    }
+}
@@ -3,6 +3,7 @@
 //! Provides Tauri commands for reading, transforming, and analyzing
 //! geospatial data formats.
 
+use hb_core::error::AppError;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
@@ -71,11 +72,11 @@ pub struct PropertyStatistics {
 
 /// Read a GeoJSON file and parse its contents
 #[command]
-pub async fn gis_read_geojson(file_path: String) -> Result<GisReadResult, String> {
+pub async fn gis_read_geojson(file_path: String) -> Result<GisReadResult, AppError> {
     let path = Path::new(&file_path);
 
     if !path.exists() {
-        return Err(format!("File not found: {}", file_path));
+        return Err(format!("File not found: {}", file_path).into());
     }
 
     let content = std::fs::read_to_string(path)
@@ -101,23 +102,23 @@ pub async fn gis_read_geojson(file_path: String) -> Result<GisReadResult, String
 /// Read a Shapefile (.shp + .dbf + .prj)
 /// Parses the binary .shp format directly without external dependencies.
 #[command]
-pub async fn gis_read_shapefile(file_path: String) -> Result<GisReadResult, String> {
+pub async fn gis_read_shapefile(file_path: String) -> Result<GisReadResult, AppError> {
     let path = Path::new(&file_path);
     if !path.exists() {
-        return Err(format!("Shapefile not found: {}", file_path));
+        return Err(format!("Shapefile not found: {}", file_path).into());
     }
 
     let shp_data = std::fs::read(path)
         .map_err(|e| format!("Failed to read shapefile: {}", e))?;
 
     if shp_data.len() < 100 {
-        return Err("Invalid shapefile: header too small".to_string());
+        return Err("Invalid shapefile: header too small".into());
     }
 
     // Validate magic number
     let file_code = u32::from_be_bytes([shp_data[0], shp_data[1], shp_data[2], shp_data[3]]);
     if file_code != 9994 {
-        return Err(format!("Invalid shapefile magic: {}", file_code));
+        return Err(format!("Invalid shapefile magic: {}", file_code).into());
     }
 
     let _shape_type = u32::from_le_bytes([shp_data[32], shp_data[33], shp_data[34], shp_data[35]]);
@@ -205,10 +206,10 @@ pub async fn gis_read_shapefile(file_path: String) -> Result<GisReadResult, Stri
 /// Read a GeoPackage file (SQLite-based)
 /// Parses the minimal SQLite structure to extract features.
 #[command]
-pub async fn gis_read_geopackage(file_path: String, layer_name: Option<String>) -> Result<GisReadResult, String> {
+pub async fn gis_read_geopackage(file_path: String, layer_name: Option<String>) -> Result<GisReadResult, AppError> {
     let path = Path::new(&file_path);
     if !path.exists() {
-        return Err(format!("GeoPackage not found: {}", file_path));
+        return Err(format!("GeoPackage not found: {}", file_path).into());
     }
 
     // GeoPackage is SQLite. We read the raw bytes and extract basic info.
@@ -217,7 +218,7 @@ pub async fn gis_read_geopackage(file_path: String, layer_name: Option<String>)
 
     // Validate SQLite magic
     if data.len() < 16 || &data[0..16] != b"SQLite format 3\0" {
-        return Err("Not a valid SQLite/GeoPackage file".to_string());
+        return Err("Not a valid SQLite/GeoPackage file".into());
     }
 
     // Without a full SQLite parser, we provide file info and suggest conversion
@@ -241,7 +242,7 @@ pub async fn gis_read_geopackage(file_path: String, layer_name: Option<String>)
         if tables.is_empty() { "unknown".to_string() } else { tables.join(", ") },
         file_path,
         layer_name.as_deref().unwrap_or("")
-    ))
+    ).into())
 }
 
 /// Write features to a GeoJSON file
@@ -250,7 +251,7 @@ pub async fn gis_write_geojson(
     features: FeatureCollection,
     output_path: String,
     pretty: Option<bool>,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let json = if pretty.unwrap_or(true) {
         serde_json::to_string_pretty(&features)
     } else {
@@ -269,17 +270,17 @@ pub async fn gis_write_geojson(
 
 /// Calculate bounding box for features
 #[command]
-pub async fn gis_calculate_bounds(features: FeatureCollection) -> Result<Option<BoundingBox>, String> {
+pub async fn gis_calculate_bounds(features: FeatureCollection) -> Result<Option<BoundingBox>, AppError> {
     Ok(calculate_bounds(&features))
 }
 
 /// Calculate centroid for a feature
 #[command]
-pub async fn gis_calculate_centroid(geometry: serde_json::Value) -> Result<[f64; 2], String> {
+pub async fn gis_calculate_centroid(geometry: serde_json::Value) -> Result<[f64; 2], AppError> {
     let coords = extract_coordinates(&geometry)?;
 
     if coords.is_empty() {
-        return Err("No coordinates found".to_string());
+        return Err("No coordinates found".into());
     }
 
     let mut sum_x = 0.0;
@@ -296,7 +297,7 @@ pub async fn gis_calculate_centroid(geometry: serde_json::Value) -> Result<[f64;
 
 /// Calculate area for polygon features (approximate, assumes planar)
 #[command]
-pub async fn gis_calculate_area(geometry: serde_json::Value) -> Result<f64, String> {
+pub async fn gis_calculate_area(geometry: serde_json::Value) -> Result<f64, AppError> {
     let geom_type = geometry.get("type")
         .and_then(|t| t.as_str())
         .ok_or("Missing geometry type")?;
@@ -310,7 +311,7 @@ pub async fn gis_calculate_area(geometry: serde_json::Value) -> Result<f64, Stri
             if let Some(ring) = coords.first().and_then(|r| r.as_array()) {
                 Ok(calculate_ring_area(ring))
             } else {
-                Err("Invalid polygon structure".to_string())
+                Err("Invalid polygon structure".into())
             }
         }
         "MultiPolygon" => {
@@ -328,13 +329,13 @@ pub async fn gis_calculate_area(geometry: serde_json::Value) -> Result<f64, Stri
             }
             Ok(total_area)
         }
-        _ => Err(format!("Cannot calculate area for geometry type: {}", geom_type)),
+        _ => Err(format!("Cannot calculate area for geometry type: {}", geom_type).into()),
     }
 }
 
 /// Calculate length for line features
 #[command]
-pub async fn gis_calculate_length(geometry: serde_json::Value) -> Result<f64, String> {
+pub async fn gis_calculate_length(geometry: serde_json::Value) -> Result<f64, AppError> {
     let geom_type = geometry.get("type")
         .and_then(|t| t.as_str())
         .ok_or("Missing geometry type")?;
@@ -360,7 +361,7 @@ pub async fn gis_calculate_length(geometry: serde_json::Value) -> Result<f64, St
             }
             Ok(total_length)
         }
-        _ => Err(format!("Cannot calculate length for geometry type: {}", geom_type)),
+        _ => Err(format!("Cannot calculate length for geometry type: {}", geom_type).into()),
     }
 }
 
@@ -369,7 +370,7 @@ pub async fn gis_calculate_length(geometry: serde_json::Value) -> Result<f64, St
 pub async fn gis_property_statistics(
     features: FeatureCollection,
     field_name: String,
-) -> Result<PropertyStatistics, String> {
+) -> Result<PropertyStatistics, AppError> {
     let mut values: Vec<f64> = Vec::new();
     let mut unique_strings: std::collections::HashSet<String> = std::collections::HashSet::new();
 
@@ -420,7 +421,7 @@ pub async fn gis_filter_features(
     field_name: String,
     operator: String,
     value: serde_json::Value,
-) -> Result<FeatureCollection, String> {
+) -> Result<FeatureCollection, AppError> {
     let filtered: Vec<GeoJsonFeature> = features.features
         .into_iter()
         .filter(|f| {
@@ -465,7 +466,7 @@ pub async fn gis_transform_crs(
     features: FeatureCollection,
     source_crs: String,
     target_crs: String,
-) -> Result<FeatureCollection, String> {
+) -> Result<FeatureCollection, AppError> {
     let transform_fn: Box<dyn Fn(f64, f64) -> (f64, f64)> = match (source_crs.as_str(), target_crs.as_str()) {
         ("EPSG:4326", "EPSG:3857") => Box::new(wgs84_to_web_mercator),
         ("EPSG:3857", "EPSG:4326") => Box::new(web_mercator_to_wgs84),
@@ -481,7 +482,7 @@ pub async fn gis_transform_crs(
             "CRS transformation from {} to {} not supported. \
              Supported: EPSG:4326, EPSG:3857, EPSG:5186, EPSG:5187",
             source_crs, target_crs
-        )),
+        ).into()),
     };
 
     let mut transformed = features;
@@ -507,7 +508,7 @@ pub async fn gis_buffer(
     geometry: serde_json::Value,
     distance: f64,
     segments: Option<usize>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, AppError> {
     let geom_type = geometry.get("type").and_then(|t| t.as_str()).unwrap_or("");
     let segs = segments.unwrap_or(16);
 
@@ -532,7 +533,7 @@ pub async fn gis_buffer(
                 "coordinates": [ring]
             }))
         }
-        _ => Err(format!("Buffer not supported for geometry type: {}", geom_type)),
+        _ => Err(format!("Buffer not supported for geometry type: {}", geom_type).into()),
     }
 }
 
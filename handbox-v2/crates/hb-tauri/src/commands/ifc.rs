@@ -3,6 +3,7 @@
 //! Provides Tauri commands for reading, analyzing, and extracting
 //! data from IFC (Industry Foundation Classes) files.
 
+use hb_core::error::AppError;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
@@ -98,11 +99,11 @@ pub struct IfcReadResult {
 
 /// Read and parse an IFC file
 #[command]
-pub async fn ifc_read_file(file_path: String) -> Result<IfcReadResult, String> {
+pub async fn ifc_read_file(file_path: String) -> Result<IfcReadResult, AppError> {
     let path = Path::new(&file_path);
 
     if !path.exists() {
-        return Err(format!("IFC file not found: {}", file_path));
+        return Err(format!("IFC file not found: {}", file_path).into());
     }
 
     let content = std::fs::read_to_string(path)
@@ -116,7 +117,7 @@ pub async fn ifc_read_file(file_path: String) -> Result<IfcReadResult, String> {
 
 /// Parse IFC content from string
 #[command]
-pub async fn ifc_parse_content(content: String) -> Result<IfcReadResult, String> {
+pub async fn ifc_parse_content(content: String) -> Result<IfcReadResult, AppError> {
     let model = parse_ifc_content(&content)?;
     let statistics = calculate_statistics(&model);
 
@@ -129,13 +130,13 @@ pub async fn ifc_parse_content(content: String) -> Result<IfcReadResult, String>
 
 /// Extract spatial hierarchy (Project > Site > Building > Storey > Space)
 #[command]
-pub async fn ifc_extract_hierarchy(model: IfcModel) -> Result<Option<IfcHierarchyNode>, String> {
+pub async fn ifc_extract_hierarchy(model: IfcModel) -> Result<Option<IfcHierarchyNode>, AppError> {
     Ok(build_spatial_hierarchy(&model))
 }
 
 /// Get element summary by type
 #[command]
-pub async fn ifc_get_element_summary(model: IfcModel) -> Result<Vec<IfcElementSummary>, String> {
+pub async fn ifc_get_element_summary(model: IfcModel) -> Result<Vec<IfcElementSummary>, AppError> {
     let mut summaries: HashMap<String, Vec<IfcEntityRef>> = HashMap::new();
 
     for (id, entity) in &model.entities {
@@ -165,7 +166,7 @@ pub async fn ifc_get_element_summary(model: IfcModel) -> Result<Vec<IfcElementSu
 pub async fn ifc_get_entity_properties(
     model: IfcModel,
     entity_id: String,
-) -> Result<Vec<IfcPropertySet>, String> {
+) -> Result<Vec<IfcPropertySet>, AppError> {
     let entity = model.entities.get(&entity_id)
         .ok_or_else(|| format!("Entity not found: {}", entity_id))?;
 
@@ -232,7 +233,7 @@ pub async fn ifc_get_entity_properties(
 pub async fn ifc_get_entity_quantities(
     model: IfcModel,
     entity_id: String,
-) -> Result<Vec<IfcQuantity>, String> {
+) -> Result<Vec<IfcQuantity>, AppError> {
     let entity = model.entities.get(&entity_id)
         .ok_or_else(|| format!("Entity not found: {}", entity_id))?;
 
@@ -270,7 +271,7 @@ pub async fn ifc_search_entities(
     model: IfcModel,
     query: String,
     entity_type: Option<String>,
-) -> Result<Vec<IfcEntityRef>, String> {
+) -> Result<Vec<IfcEntityRef>, AppError> {
     let query_lower = query.to_lowercase();
 
     let results: Vec<IfcEntityRef> = model.entities
@@ -310,7 +311,7 @@ pub async fn ifc_search_entities(
 
 /// Get model statistics
 #[command]
-pub async fn ifc_get_statistics(model: IfcModel) -> Result<IfcModelStatistics, String> {
+pub async fn ifc_get_statistics(model: IfcModel) -> Result<IfcModelStatistics, AppError> {
     Ok(calculate_statistics(&model))
 }
 
@@ -323,7 +324,7 @@ pub async fn ifc_get_statistics(model: IfcModel) -> Result<IfcModelStatistics, S
 pub async fn ifc_export_summary_json(
     model: IfcModel,
     output_path: String,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let stats = calculate_statistics(&model);
     let json = serde_json::to_string_pretty(&stats)
         .map_err(|e| format!("Failed to serialize: {}", e))?;
@@ -340,7 +341,7 @@ pub async fn ifc_export_elements_csv(
     model: IfcModel,
     output_path: String,
     element_types: Option<Vec<String>>,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let mut csv = "GlobalId,Type,Name,Description\n".to_string();
 
     for (_, entity) in &model.entities {
@@ -381,7 +382,7 @@ pub async fn ifc_modify_entity(
     mut model: IfcModel,
     entity_id: String,
     updates: HashMap<String, serde_json::Value>,
-) -> Result<IfcModel, String> {
+) -> Result<IfcModel, AppError> {
     let entity = model.entities.get_mut(&entity_id)
         .ok_or_else(|| format!("Entity not found: {}", entity_id))?;
 
@@ -405,7 +406,7 @@ pub async fn ifc_add_entity(
     name: Option<String>,
     description: Option<String>,
     attributes: Option<HashMap<String, serde_json::Value>>,
-) -> Result<IfcModel, String> {
+) -> Result<IfcModel, AppError> {
     // Generate a new entity ID (find max existing + 1)
     let max_id = model.entities.values()
         .map(|e| e.id)
@@ -433,7 +434,7 @@ pub async fn ifc_add_entity(
 pub async fn ifc_remove_entity(
     mut model: IfcModel,
     entity_id: String,
-) -> Result<IfcModel, String> {
+) -> Result<IfcModel, AppError> {
     model.entities.remove(&entity_id)
         .ok_or_else(|| format!("Entity not found: {}", entity_id))?;
     model.entity_count = model.entities.len();
@@ -447,7 +448,7 @@ pub async fn ifc_clone_entity(
     mut model: IfcModel,
     entity_id: String,
     new_name: Option<String>,
-) -> Result<IfcModel, String> {
+) -> Result<IfcModel, AppError> {
     let source = model.entities.get(&entity_id)
         .ok_or_else(|| format!("Entity not found: {}", entity_id))?
         .clone();
@@ -482,7 +483,7 @@ pub async fn ifc_clone_entity(
 pub async fn ifc_write_file(
     model: IfcModel,
     output_path: String,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let content = serialize_ifc_model(&model)?;
 
     std::fs::write(&output_path, &content)
@@ -497,7 +498,7 @@ pub async fn ifc_write_file(
 pub async fn ifc_merge_models(
     mut base_model: IfcModel,
     merge_model: IfcModel,
-) -> Result<IfcModel, String> {
+) -> Result<IfcModel, AppError> {
     // Find max ID in base model
     let max_id = base_model.entities.values()
         .map(|e| e.id)
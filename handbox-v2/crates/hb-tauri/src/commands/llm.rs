@@ -1,6 +1,11 @@
 //! LLM Provider commands for Handbox v2
 //! Supports AWS Bedrock (Signature V4), OpenAI, Anthropic, and local LLM endpoints.
 
+use crate::bedrock::{self, ModelTarget};
+use crate::credential_health::CredentialHealth;
+use crate::local_models;
+use hb_core::error::AppError;
+use hb_core::project::{ActivityEntry, ActivityKind, EnvVar, Permission};
 use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -147,7 +152,7 @@ fn get_signature_key(secret_key: &str, date_stamp: &str, region: &str, service:
 }
 
 /// Sign a request using AWS Signature V4
-fn sign_aws_request(
+pub(crate) fn sign_aws_request(
     access_key: &str,
     secret_key: &str,
     region: &str,
@@ -156,6 +161,23 @@ fn sign_aws_request(
     host: &str,
     uri: &str,
     payload: &[u8],
+) -> (String, String, String, String) {
+    sign_aws_request_with_query(access_key, secret_key, region, service, method, host, uri, "", payload)
+}
+
+/// Sign a request using AWS Signature V4. `query` must already be in
+/// canonical form (sorted, URI-encoded).
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn sign_aws_request_with_query(
+    access_key: &str,
+    secret_key: &str,
+    region: &str,
+    service: &str,
+    method: &str,
+    host: &str,
+    uri: &str,
+    query: &str,
+    payload: &[u8],
 ) -> (String, String, String, String) {
     let now = chrono::Utc::now();
     let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
@@ -170,9 +192,10 @@ fn sign_aws_request(
     let signed_headers = "host;x-amz-date";
 
     let canonical_request = format!(
-        "{}\n{}\n\n{}\n{}\n{}",
+        "{}\n{}\n{}\n{}\n{}\n{}",
         method,
         uri,
+        query,
         canonical_headers,
         signed_headers,
         payload_hash
@@ -208,25 +231,40 @@ fn sign_aws_request(
     (authorization, amz_date, payload_hash, debug_info)
 }
 
-/// Normalize model ID to Bedrock format
-fn normalize_model_id(raw_id: &str) -> String {
-    let stripped = if raw_id.starts_with("us.") || raw_id.starts_with("eu.") || raw_id.starts_with("apac.") {
-        raw_id.split_once('.').map(|(_, rest)| rest).unwrap_or(raw_id)
-    } else {
-        raw_id
-    };
+/// Resolve a requested model id against the account's Bedrock catalog.
+async fn resolve_bedrock_target(
+    access_key: &str,
+    secret_key: &str,
+    model_id: &str,
+    region: &str,
+) -> ModelTarget {
+    let catalog = bedrock::catalog(access_key, secret_key, region).await;
+    bedrock::resolve(model_id, region, catalog.as_ref())
+}
 
-    if stripped.contains("claude-3-5-sonnet") || stripped.contains("claude-sonnet-4") {
-        "anthropic.claude-3-5-sonnet-20240620-v1:0".to_string()
-    } else if stripped.contains("claude-3-opus") || stripped.contains("claude-opus-4") {
-        "anthropic.claude-3-opus-20240229-v1:0".to_string()
-    } else if stripped.contains("claude-3-haiku") || stripped.contains("claude-haiku") {
-        "anthropic.claude-3-haiku-20240307-v1:0".to_string()
-    } else if stripped.starts_with("anthropic.") {
-        stripped.to_string()
-    } else {
-        "anthropic.claude-3-5-sonnet-20240620-v1:0".to_string()
+/// Invoke a resolved target, trying each region in turn and switching to the
+/// inference profile if Bedrock refuses on-demand invocation. Returns the
+/// response and the id that served it.
+async fn call_bedrock_target(
+    access_key: &str,
+    secret_key: &str,
+    target: &ModelTarget,
+    body: &serde_json::Value,
+) -> Result<(serde_json::Value, String), String> {
+    let mut last_error = String::from("no region to try");
+    for region in &target.regions {
+        match call_bedrock(access_key, secret_key, &target.invoke_id, region, body).await {
+            Ok(resp) => return Ok((resp, target.invoke_id.clone())),
+            Err(e) => last_error = e,
+        }
+        if let Some(profile) = target.profile_fallback.as_deref() {
+            if bedrock::needs_profile(&last_error) {
+                let profiled = bedrock::resolve(profile, region, None);
+                return Box::pin(call_bedrock_target(access_key, secret_key, &profiled, body)).await;
+            }
+        }
     }
+    Err(last_error)
 }
 
 /// Call Bedrock with AWS Signature V4 authentication
@@ -418,6 +456,29 @@ async fn call_openai(
     chat_messages: &Option<Vec<ChatMessage>>,
     tools: &Option<Vec<ToolDefinition>>,
     tool_choice: &Option<String>,
+) -> Result<LLMResponse, String> {
+    call_chat_completions(
+        "https://api.openai.com/v1/chat/completions", Some(api_key), "OpenAI",
+        model, prompt, system_prompt, max_tokens, temperature,
+        chat_messages, tools, tool_choice,
+    ).await
+}
+
+/// Call an OpenAI-compatible chat completions endpoint (OpenAI itself or a
+/// local llama-server).
+#[allow(clippy::too_many_arguments)]
+async fn call_chat_completions(
+    url: &str,
+    api_key: Option<&str>,
+    label: &str,
+    model: &str,
+    prompt: &str,
+    system_prompt: Option<&str>,
+    max_tokens: i32,
+    temperature: f32,
+    chat_messages: &Option<Vec<ChatMessage>>,
+    tools: &Option<Vec<ToolDefinition>>,
+    tool_choice: &Option<String>,
 ) -> Result<LLMResponse, String> {
     let mut messages = Vec::new();
 
@@ -468,19 +529,20 @@ async fn call_openai(
     }
 
     let client = reqwest::Client::new();
-    let response = client
-        .post("https://api.openai.com/v1/chat/completions")
-        .header("Authorization", format!("Bearer {}", api_key))
-        .header("Content-Type", "application/json")
+    let mut request = client.post(url).header("Content-Type", "application/json");
+    if let Some(key) = api_key {
+        request = request.header("Authorization", format!("Bearer {}", key));
+    }
+    let response = request
         .json(&body)
         .send()
         .await
-        .map_err(|e| format!("OpenAI request failed: {}", e))?;
+        .map_err(|e| format!("{} request failed: {}", label, e))?;
 
     let status = response.status();
     if !status.is_success() {
         let error_body = response.text().await.unwrap_or_default();
-        return Err(format!("OpenAI API error (HTTP {}): {}", status, error_body));
+        return Err(format!("{} API error (HTTP {}): {}", label, status, error_body));
     }
 
     let result: serde_json::Value = response
@@ -667,14 +729,42 @@ async fn call_local_llm(
 // Tauri Commands
 // ============================================================
 
+/// Check that the current user may manage credentials, when the settings
+/// were opened from a shared workspace.
+async fn authorize_credentials(
+    state: &crate::state::AppState,
+    workspace_id: Option<String>,
+) -> Result<(Option<uuid::Uuid>, crate::state::Access), AppError> {
+    let workspace_id = crate::commands::workflow::parse_workspace_id(workspace_id)?;
+    let access = state.authorize(workspace_id, Permission::ManageCredentials).await?;
+    Ok((workspace_id, access))
+}
+
+/// Record a credential change in the activity log. Never include secrets.
+async fn record_credentials_changed(
+    state: &crate::state::AppState,
+    (workspace_id, access): (Option<uuid::Uuid>, crate::state::Access),
+    provider: &str,
+    summary: &str,
+) {
+    state
+        .record_activity(access.stamp(
+            ActivityEntry::new(ActivityKind::CredentialsChanged, provider, summary)
+                .in_workspace(workspace_id),
+        ))
+        .await;
+}
+
 /// Set AWS Bedrock credentials (Access Key ID + Secret Access Key)
 #[tauri::command]
 pub async fn set_bedrock_credentials(
     access_key_id: String,
     secret_access_key: String,
     region: Option<String>,
+    workspace_id: Option<String>,
     state: tauri::State<'_, crate::state::AppState>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
+    let audit = authorize_credentials(&state, workspace_id).await?;
     // Set environment variables
     std::env::set_var("AWS_ACCESS_KEY_ID", &access_key_id);
     std::env::set_var("AWS_SECRET_ACCESS_KEY", &secret_access_key);
@@ -684,12 +774,16 @@ pub async fn set_bedrock_credentials(
 
     // Save to persistent storage
     let mut creds = state.llm_credentials.write().await;
+    state.credential_health.write().await.forget("bedrock");
+    bedrock::clear_catalog();
     creds.aws_access_key_id = Some(access_key_id);
     creds.aws_secret_access_key = Some(secret_access_key);
     if region.is_some() {
         creds.bedrock_region = region;
     }
     creds.save(&state.credentials_path())?;
+    drop(creds);
+    record_credentials_changed(&state, audit, "bedrock", "Updated AWS Bedrock credentials").await;
 
     Ok(true)
 }
@@ -698,13 +792,17 @@ pub async fn set_bedrock_credentials(
 #[tauri::command]
 pub async fn set_bedrock_region(
     region: String,
+    workspace_id: Option<String>,
     state: tauri::State<'_, crate::state::AppState>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
+    let audit = authorize_credentials(&state, workspace_id).await?;
     std::env::set_var("AWS_REGION", &region);
 
     let mut creds = state.llm_credentials.write().await;
     creds.bedrock_region = Some(region);
     creds.save(&state.credentials_path())?;
+    drop(creds);
+    record_credentials_changed(&state, audit, "bedrock", "Changed AWS Bedrock region").await;
 
     Ok(true)
 }
@@ -713,13 +811,18 @@ pub async fn set_bedrock_region(
 #[tauri::command]
 pub async fn set_openai_api_key(
     api_key: String,
+    workspace_id: Option<String>,
     state: tauri::State<'_, crate::state::AppState>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
+    let audit = authorize_credentials(&state, workspace_id).await?;
     std::env::set_var("OPENAI_API_KEY", &api_key);
 
     let mut creds = state.llm_credentials.write().await;
+    state.credential_health.write().await.forget("openai");
     creds.openai_api_key = Some(api_key);
     creds.save(&state.credentials_path())?;
+    drop(creds);
+    record_credentials_changed(&state, audit, "openai", "Updated OpenAI API key").await;
 
     Ok(true)
 }
@@ -728,13 +831,18 @@ pub async fn set_openai_api_key(
 #[tauri::command]
 pub async fn set_anthropic_api_key(
     api_key: String,
+    workspace_id: Option<String>,
     state: tauri::State<'_, crate::state::AppState>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
+    let audit = authorize_credentials(&state, workspace_id).await?;
     std::env::set_var("ANTHROPIC_API_KEY", &api_key);
 
     let mut creds = state.llm_credentials.write().await;
+    state.credential_health.write().await.forget("anthropic");
     creds.anthropic_api_key = Some(api_key);
     creds.save(&state.credentials_path())?;
+    drop(creds);
+    record_credentials_changed(&state, audit, "anthropic", "Updated Anthropic API key").await;
 
     Ok(true)
 }
@@ -743,13 +851,62 @@ pub async fn set_anthropic_api_key(
 #[tauri::command]
 pub async fn set_local_llm_endpoint(
     endpoint: String,
+    workspace_id: Option<String>,
     state: tauri::State<'_, crate::state::AppState>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
+    let audit = authorize_credentials(&state, workspace_id).await?;
     std::env::set_var("LOCAL_LLM_ENDPOINT", &endpoint);
 
     let mut creds = state.llm_credentials.write().await;
+    state.credential_health.write().await.forget("local");
     creds.local_endpoint = Some(endpoint);
     creds.save(&state.credentials_path())?;
+    drop(creds);
+    record_credentials_changed(&state, audit, "local", "Changed local LLM endpoint").await;
+
+    Ok(true)
+}
+
+/// Set the key of an API service (persistent), under the environment
+/// variable its tools read, e.g. `DATA_GO_KR_SERVICE_KEY` for public-api-fetch.
+#[tauri::command]
+pub async fn set_service_key(
+    name: String,
+    key: String,
+    workspace_id: Option<String>,
+    state: tauri::State<'_, crate::state::AppState>,
+) -> Result<bool, AppError> {
+    if !EnvVar::is_valid_name(&name) {
+        return Err(format!("Invalid key name: {name}").into());
+    }
+    let audit = authorize_credentials(&state, workspace_id).await?;
+    std::env::set_var(&name, &key);
+
+    let mut creds = state.llm_credentials.write().await;
+    creds.service_keys.insert(name.clone(), key);
+    creds.save(&state.credentials_path())?;
+    drop(creds);
+    record_credentials_changed(&state, audit, &name, &format!("Updated service key {name}")).await;
+
+    Ok(true)
+}
+
+/// Remove the key of an API service (persistent)
+#[tauri::command]
+pub async fn remove_service_key(
+    name: String,
+    workspace_id: Option<String>,
+    state: tauri::State<'_, crate::state::AppState>,
+) -> Result<bool, AppError> {
+    let audit = authorize_credentials(&state, workspace_id).await?;
+    let mut creds = state.llm_credentials.write().await;
+    if creds.service_keys.remove(&name).is_none() {
+        return Ok(false);
+    }
+    std::env::remove_var(&name);
+    creds.save(&state.credentials_path())?;
+    drop(creds);
+    record_credentials_changed(&state, audit, &name, &format!("Removed service key {name}")).await;
 
     Ok(true)
 }
@@ -759,7 +916,7 @@ pub async fn set_local_llm_endpoint(
 pub async fn set_active_llm_provider(
     provider: String,
     state: tauri::State<'_, crate::state::AppState>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     let mut creds = state.llm_credentials.write().await;
     creds.active_provider = Some(provider);
     creds.save(&state.credentials_path())?;
@@ -769,8 +926,10 @@ pub async fn set_active_llm_provider(
 /// Clear all credentials (persistent)
 #[tauri::command]
 pub async fn clear_llm_credentials(
+    workspace_id: Option<String>,
     state: tauri::State<'_, crate::state::AppState>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
+    let audit = authorize_credentials(&state, workspace_id).await?;
     std::env::remove_var("AWS_ACCESS_KEY_ID");
     std::env::remove_var("AWS_SECRET_ACCESS_KEY");
     std::env::remove_var("AWS_REGION");
@@ -779,8 +938,17 @@ pub async fn clear_llm_credentials(
     std::env::remove_var("LOCAL_LLM_ENDPOINT");
 
     let mut creds = state.llm_credentials.write().await;
-    *creds = crate::state::LLMCredentials::default();
+    // Service keys and the team token are not LLM credentials and stay
+    *creds = crate::state::LLMCredentials {
+        service_keys: std::mem::take(&mut creds.service_keys),
+        team_sync_token: creds.team_sync_token.take(),
+        ..Default::default()
+    };
+    state.credential_health.write().await.clear();
+    bedrock::clear_catalog();
     creds.save(&state.credentials_path())?;
+    drop(creds);
+    record_credentials_changed(&state, audit, "all", "Cleared all LLM credentials").await;
 
     Ok(true)
 }
@@ -793,13 +961,15 @@ pub struct CredentialStatus {
     pub has_openai: bool,
     pub has_anthropic: bool,
     pub local_endpoint: Option<String>,
+    /// Names of the API service keys set.
+    pub service_keys: Vec<String>,
 }
 
 /// Get saved credential status
 #[tauri::command]
 pub async fn get_credential_status(
     state: tauri::State<'_, crate::state::AppState>,
-) -> Result<CredentialStatus, String> {
+) -> Result<CredentialStatus, AppError> {
     let creds = state.llm_credentials.read().await;
     Ok(CredentialStatus {
         has_bedrock: creds.aws_access_key_id.is_some() && creds.aws_secret_access_key.is_some(),
@@ -807,12 +977,35 @@ pub async fn get_credential_status(
         has_openai: creds.openai_api_key.is_some(),
         has_anthropic: creds.anthropic_api_key.is_some(),
         local_endpoint: creds.local_endpoint.clone(),
+        service_keys: creds.service_keys.keys().cloned().collect(),
     })
 }
 
+/// Cached result of the last background credential check.
+#[tauri::command]
+pub async fn get_credential_health(
+    state: tauri::State<'_, crate::state::AppState>,
+) -> Result<Vec<CredentialHealth>, AppError> {
+    Ok(state.credential_health.read().await.entries())
+}
+
+/// Validate all configured credentials now and return the fresh results.
+#[tauri::command]
+pub async fn check_credential_health(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, crate::state::AppState>,
+) -> Result<Vec<CredentialHealth>, AppError> {
+    use tauri::Emitter;
+    let changed = state.refresh_credential_health().await;
+    if !changed.is_empty() {
+        let _ = app.emit(crate::credential_health::HEALTH_CHANGED_EVENT, &changed);
+    }
+    Ok(state.credential_health.read().await.entries())
+}
+
 /// Test LLM connection
 #[tauri::command]
-pub async fn test_llm_connection(provider: String) -> Result<ConnectionResult, String> {
+pub async fn test_llm_connection(provider: String) -> Result<ConnectionResult, AppError> {
     match provider.as_str() {
         "bedrock" => {
             let access_key = match std::env::var("AWS_ACCESS_KEY_ID") {
@@ -956,44 +1149,96 @@ pub async fn test_llm_connection(provider: String) -> Result<ConnectionResult, S
                 }),
             }
         }
-        _ => Err(format!("Unknown provider: {}", provider)),
+        "llamacpp" => {
+            let error = match local_models::list_models().await {
+                Ok(models) if models.is_empty() => {
+                    Some("No GGUF models installed. Download one in Settings.".to_string())
+                }
+                Ok(_) => local_models::server_version().await.err(),
+                Err(e) => Some(e),
+            };
+            Ok(ConnectionResult {
+                connected: error.is_none(),
+                provider: "llamacpp".to_string(),
+                region: None,
+                error,
+            })
+        }
+        _ => Err(format!("Unknown provider: {}", provider).into()),
     }
 }
 
 /// List available models
 #[tauri::command]
-pub async fn list_llm_models(provider: String) -> Result<Vec<ModelInfo>, String> {
+pub async fn list_llm_models(provider: String) -> Result<Vec<ModelInfo>, AppError> {
     match provider.as_str() {
-        "bedrock" => Ok(vec![
-            ModelInfo {
-                id: "anthropic.claude-3-5-sonnet-20240620-v1:0".to_string(),
-                name: "Claude 3.5 Sonnet".to_string(),
-                provider: "bedrock".to_string(),
-                max_tokens: 200000,
-                supports_vision: true,
-            },
-            ModelInfo {
-                id: "anthropic.claude-3-opus-20240229-v1:0".to_string(),
-                name: "Claude 3 Opus".to_string(),
-                provider: "bedrock".to_string(),
-                max_tokens: 200000,
-                supports_vision: true,
-            },
-            ModelInfo {
-                id: "anthropic.claude-3-haiku-20240307-v1:0".to_string(),
-                name: "Claude 3 Haiku".to_string(),
-                provider: "bedrock".to_string(),
-                max_tokens: 200000,
-                supports_vision: true,
-            },
-            ModelInfo {
-                id: "anthropic.claude-3-5-haiku-20241022-v1:0".to_string(),
-                name: "Claude 3.5 Haiku".to_string(),
-                provider: "bedrock".to_string(),
-                max_tokens: 200000,
-                supports_vision: true,
-            },
-        ]),
+        "bedrock" => {
+            if let (Ok(access_key), Ok(secret_key)) = (
+                std::env::var("AWS_ACCESS_KEY_ID"),
+                std::env::var("AWS_SECRET_ACCESS_KEY"),
+            ) {
+                let region = std::env::var("AWS_REGION").unwrap_or_else(|_| "us-east-1".to_string());
+                if let Some(catalog) = bedrock::catalog(&access_key, &secret_key, &region).await {
+                    return Ok(catalog
+                        .models
+                        .iter()
+                        .map(|m| ModelInfo {
+                            id: m.invoke_id(&region),
+                            name: format!("{} ({})", m.name, m.provider_name),
+                            provider: "bedrock".to_string(),
+                            max_tokens: 200000,
+                            supports_vision: m.supports_vision,
+                        })
+                        .collect());
+                }
+            }
+            // Discovery unavailable — offer the common Anthropic models;
+            // newer ones are routed through an inference profile on invoke
+            Ok(vec![
+                ModelInfo {
+                    id: "anthropic.claude-sonnet-4-20250514-v1:0".to_string(),
+                    name: "Claude Sonnet 4".to_string(),
+                    provider: "bedrock".to_string(),
+                    max_tokens: 200000,
+                    supports_vision: true,
+                },
+                ModelInfo {
+                    id: "anthropic.claude-3-7-sonnet-20250219-v1:0".to_string(),
+                    name: "Claude 3.7 Sonnet".to_string(),
+                    provider: "bedrock".to_string(),
+                    max_tokens: 200000,
+                    supports_vision: true,
+                },
+                ModelInfo {
+                    id: "anthropic.claude-3-5-sonnet-20240620-v1:0".to_string(),
+                    name: "Claude 3.5 Sonnet".to_string(),
+                    provider: "bedrock".to_string(),
+                    max_tokens: 200000,
+                    supports_vision: true,
+                },
+                ModelInfo {
+                    id: "anthropic.claude-3-opus-20240229-v1:0".to_string(),
+                    name: "Claude 3 Opus".to_string(),
+                    provider: "bedrock".to_string(),
+                    max_tokens: 200000,
+                    supports_vision: true,
+                },
+                ModelInfo {
+                    id: "anthropic.claude-3-haiku-20240307-v1:0".to_string(),
+                    name: "Claude 3 Haiku".to_string(),
+                    provider: "bedrock".to_string(),
+                    max_tokens: 200000,
+                    supports_vision: true,
+                },
+                ModelInfo {
+                    id: "anthropic.claude-3-5-haiku-20241022-v1:0".to_string(),
+                    name: "Claude 3.5 Haiku".to_string(),
+                    provider: "bedrock".to_string(),
+                    max_tokens: 200000,
+                    supports_vision: true,
+                },
+            ])
+        }
         "openai" => Ok(vec![
             ModelInfo {
                 id: "gpt-4o".to_string(),
@@ -1080,18 +1325,32 @@ pub async fn list_llm_models(provider: String) -> Result<Vec<ModelInfo>, String>
                 _ => Ok(vec![]),
             }
         }
-        _ => Err(format!("Unknown provider: {}", provider)),
+        "llamacpp" => Ok(local_models::list_models()
+            .await?
+            .into_iter()
+            .filter(|m| m.error.is_none())
+            .map(|m| ModelInfo {
+                name: m.info.as_ref().and_then(|i| i.name.clone()).unwrap_or_else(|| m.id.clone()),
+                max_tokens: local_models::CONTEXT_SIZE as i32,
+                id: m.id,
+                provider: "llamacpp".to_string(),
+                supports_vision: false,
+            })
+            .collect()),
+        _ => Err(format!("Unknown provider: {}", provider).into()),
     }
 }
 
 /// Invoke LLM
 #[tauri::command]
-pub async fn invoke_llm(request: LLMRequest) -> Result<LLMResponse, String> {
+pub async fn invoke_llm(request: LLMRequest) -> Result<LLMResponse, AppError> {
     let provider = request.provider.as_deref().unwrap_or("bedrock");
     let model_id = request.model_id.clone().unwrap_or_else(|| {
         match provider {
             "openai" => "gpt-4o".to_string(),
             "local" => "llama3.2".to_string(),
+            // First installed model
+            "llamacpp" => String::new(),
             "anthropic" => "claude-3-5-sonnet-20241022".to_string(),
             _ => "anthropic.claude-3-5-sonnet-20240620-v1:0".to_string(),
         }
@@ -1106,7 +1365,7 @@ pub async fn invoke_llm(request: LLMRequest) -> Result<LLMResponse, String> {
             let secret_key = std::env::var("AWS_SECRET_ACCESS_KEY")
                 .map_err(|_| "No AWS Secret Access Key configured. Please set your credentials in Settings.")?;
             let region = std::env::var("AWS_REGION").unwrap_or_else(|_| "us-east-1".to_string());
-            let base_model_id = normalize_model_id(&model_id);
+            let target = resolve_bedrock_target(&access_key, &secret_key, &model_id, &region).await;
 
             let msgs = build_messages_json(&request.messages, &request.prompt);
             let mut body = serde_json::json!({
@@ -1144,47 +1403,39 @@ pub async fn invoke_llm(request: LLMRequest) -> Result<LLMResponse, String> {
                 }
             }
 
-            // Try multiple regions
-            let regions = [region.as_str(), "us-east-1", "us-west-2"];
-            let mut last_error = String::new();
-
-            for r in regions {
-                match call_bedrock(&access_key, &secret_key, &base_model_id, r, &body).await {
-                    Ok(resp) => {
-                        // Parse content blocks (may contain text + tool_use)
-                        let mut text = String::new();
-                        let mut tool_calls = Vec::new();
-                        if let Some(content) = resp["content"].as_array() {
-                            for block in content {
-                                match block["type"].as_str() {
-                                    Some("text") => text.push_str(block["text"].as_str().unwrap_or("")),
-                                    Some("tool_use") => {
-                                        tool_calls.push(ToolCall {
-                                            id: block["id"].as_str().unwrap_or("").to_string(),
-                                            name: block["name"].as_str().unwrap_or("").to_string(),
-                                            input: block["input"].clone(),
-                                        });
-                                    }
-                                    _ => {}
+            match call_bedrock_target(&access_key, &secret_key, &target, &body).await {
+                Ok((resp, invoked_id)) => {
+                    // Parse content blocks (may contain text + tool_use)
+                    let mut text = String::new();
+                    let mut tool_calls = Vec::new();
+                    if let Some(content) = resp["content"].as_array() {
+                        for block in content {
+                            match block["type"].as_str() {
+                                Some("text") => text.push_str(block["text"].as_str().unwrap_or("")),
+                                Some("tool_use") => {
+                                    tool_calls.push(ToolCall {
+                                        id: block["id"].as_str().unwrap_or("").to_string(),
+                                        name: block["name"].as_str().unwrap_or("").to_string(),
+                                        input: block["input"].clone(),
+                                    });
                                 }
+                                _ => {}
                             }
                         }
-                        let input_tokens = resp["usage"]["input_tokens"].as_i64().unwrap_or(0) as i32;
-                        let output_tokens = resp["usage"]["output_tokens"].as_i64().unwrap_or(0) as i32;
-                        let stop_reason = resp["stop_reason"].as_str().map(|s| s.to_string());
-                        return Ok(LLMResponse {
-                            text,
-                            model: base_model_id,
-                            usage: TokenUsage { input_tokens, output_tokens },
-                            tool_calls: if tool_calls.is_empty() { None } else { Some(tool_calls) },
-                            stop_reason,
-                        });
                     }
-                    Err(e) => last_error = e,
+                    let input_tokens = resp["usage"]["input_tokens"].as_i64().unwrap_or(0) as i32;
+                    let output_tokens = resp["usage"]["output_tokens"].as_i64().unwrap_or(0) as i32;
+                    let stop_reason = resp["stop_reason"].as_str().map(|s| s.to_string());
+                    Ok(LLMResponse {
+                        text,
+                        model: invoked_id,
+                        usage: TokenUsage { input_tokens, output_tokens },
+                        tool_calls: if tool_calls.is_empty() { None } else { Some(tool_calls) },
+                        stop_reason,
+                    })
                 }
+                Err(e) => Err(format!("Bedrock call failed: {}", e).into()),
             }
-
-            Err(format!("Bedrock call failed: {}", last_error))
         }
         "openai" => {
             let api_key = std::env::var("OPENAI_API_KEY")
@@ -1193,7 +1444,7 @@ pub async fn invoke_llm(request: LLMRequest) -> Result<LLMResponse, String> {
                 &api_key, &model_id, &request.prompt,
                 request.system_prompt.as_deref(), max_tokens, temperature,
                 &request.messages, &request.tools, &request.tool_choice,
-            ).await
+            ).await.map_err(AppError::from)
         }
         "anthropic" => {
             let api_key = std::env::var("ANTHROPIC_API_KEY")
@@ -1202,7 +1453,7 @@ pub async fn invoke_llm(request: LLMRequest) -> Result<LLMResponse, String> {
                 &api_key, &model_id, &request.prompt,
                 request.system_prompt.as_deref(), max_tokens, temperature,
                 &request.messages, &request.tools, &request.tool_choice,
-            ).await
+            ).await.map_err(AppError::from)
         }
         "local" => {
             let endpoint = std::env::var("LOCAL_LLM_ENDPOINT")
@@ -1217,13 +1468,27 @@ pub async fn invoke_llm(request: LLMRequest) -> Result<LLMResponse, String> {
             resp.stop_reason = Some("end_turn".to_string());
             Ok(resp)
         }
-        _ => Err(format!("Unknown provider: {}", provider)),
+        "llamacpp" => {
+            let model_id = if model_id.is_empty() {
+                local_models::default_model().await?
+            } else {
+                model_id
+            };
+            let base_url = local_models::ensure_server(&model_id).await?;
+            call_chat_completions(
+                &format!("{base_url}/v1/chat/completions"), None, "llama-server",
+                &model_id, &request.prompt,
+                request.system_prompt.as_deref(), max_tokens, temperature,
+                &request.messages, &request.tools, &request.tool_choice,
+            ).await.map_err(AppError::from)
+        }
+        _ => Err(format!("Unknown provider: {}", provider).into()),
     }
 }
 
 /// Create embedding
 #[tauri::command]
-pub async fn create_embedding(request: EmbeddingRequest) -> Result<EmbeddingResponse, String> {
+pub async fn create_embedding(request: EmbeddingRequest) -> Result<EmbeddingResponse, AppError> {
     let access_key = std::env::var("AWS_ACCESS_KEY_ID")
         .map_err(|_| "No AWS Access Key ID configured")?;
     let secret_key = std::env::var("AWS_SECRET_ACCESS_KEY")
@@ -1259,7 +1524,7 @@ pub async fn invoke_llm_stream(
     request: LLMRequest,
     stream_id: String,
     app: tauri::AppHandle,
-) -> Result<LLMResponse, String> {
+) -> Result<LLMResponse, AppError> {
     use tauri::Emitter;
 
     let provider = request.provider.as_deref().unwrap_or("bedrock");
@@ -1281,7 +1546,7 @@ pub async fn invoke_llm_stream(
                 request.system_prompt.as_deref(),
                 max_tokens, temperature, &stream_id, &app,
                 &request.messages, &request.tools, &request.tool_choice,
-            ).await
+            ).await.map_err(AppError::from)
         }
         "bedrock" => {
             let access_key = std::env::var("AWS_ACCESS_KEY_ID")
@@ -1289,13 +1554,13 @@ pub async fn invoke_llm_stream(
             let secret_key = std::env::var("AWS_SECRET_ACCESS_KEY")
                 .map_err(|_| "No AWS Secret Access Key configured")?;
             let region = std::env::var("AWS_REGION").unwrap_or_else(|_| "us-east-1".to_string());
-            let base_model = normalize_model_id(&model_id);
+            let target = resolve_bedrock_target(&access_key, &secret_key, &model_id, &region).await;
             stream_bedrock(
-                &access_key, &secret_key, &base_model, &region,
+                &access_key, &secret_key, &target,
                 &request.prompt, request.system_prompt.as_deref(),
                 max_tokens, temperature, &stream_id, &app,
                 &request.messages, &request.tools, &request.tool_choice,
-            ).await
+            ).await.map_err(AppError::from)
         }
         _ => {
             // Fallback: non-streaming invoke + emit complete text
@@ -1512,8 +1777,7 @@ async fn stream_anthropic(
 async fn stream_bedrock(
     access_key: &str,
     secret_key: &str,
-    model_id: &str,
-    region: &str,
+    target: &ModelTarget,
     prompt: &str,
     system_prompt: Option<&str>,
     max_tokens: i32,
@@ -1527,6 +1791,8 @@ async fn stream_bedrock(
     use tauri::Emitter;
 
     let service = "bedrock";
+    let model_id = target.invoke_id.as_str();
+    let region = target.regions.first().map_or("us-east-1", String::as_str);
     let host = format!("bedrock-runtime.{region}.amazonaws.com");
     let encoded_model_id = model_id.replace(":", "%3A");
     let uri = format!("/model/{encoded_model_id}/invoke-with-response-stream");
@@ -1608,21 +1874,18 @@ async fn stream_bedrock(
             "system": system_prompt.unwrap_or(""),
             "messages": fallback_msgs
         });
-        let regions = [region, "us-east-1", "us-west-2"];
-        for r in regions {
-            if let Ok(resp) = call_bedrock(access_key, secret_key, model_id, r, &body_val).await {
-                let text = resp["content"][0]["text"].as_str().unwrap_or("").to_string();
-                let it = resp["usage"]["input_tokens"].as_i64().unwrap_or(0) as i32;
-                let ot = resp["usage"]["output_tokens"].as_i64().unwrap_or(0) as i32;
-                let _ = app.emit("llm-stream", serde_json::json!({
-                    "stream_id": stream_id, "type": "text", "text": &text,
-                }));
-                let _ = app.emit("llm-stream", serde_json::json!({
-                    "stream_id": stream_id, "type": "done",
-                    "usage": { "input_tokens": it, "output_tokens": ot },
-                }));
-                return Ok(LLMResponse { text, model: model_id.to_string(), usage: TokenUsage { input_tokens: it, output_tokens: ot }, tool_calls: None, stop_reason: Some("end_turn".to_string()) });
-            }
+        if let Ok((resp, invoked_id)) = call_bedrock_target(access_key, secret_key, target, &body_val).await {
+            let text = resp["content"][0]["text"].as_str().unwrap_or("").to_string();
+            let it = resp["usage"]["input_tokens"].as_i64().unwrap_or(0) as i32;
+            let ot = resp["usage"]["output_tokens"].as_i64().unwrap_or(0) as i32;
+            let _ = app.emit("llm-stream", serde_json::json!({
+                "stream_id": stream_id, "type": "text", "text": &text,
+            }));
+            let _ = app.emit("llm-stream", serde_json::json!({
+                "stream_id": stream_id, "type": "done",
+                "usage": { "input_tokens": it, "output_tokens": ot },
+            }));
+            return Ok(LLMResponse { text, model: invoked_id, usage: TokenUsage { input_tokens: it, output_tokens: ot }, tool_calls: None, stop_reason: Some("end_turn".to_string()) });
         }
         return Err(format!("Bedrock stream error: {error_body}"));
     }
@@ -1792,22 +2055,19 @@ async fn stream_bedrock(
             "system": system_prompt.unwrap_or(""),
             "messages": fallback_msgs
         });
-        let regions = [region, "us-east-1", "us-west-2"];
-        for r in regions {
-            if let Ok(resp) = call_bedrock(access_key, secret_key, model_id, r, &body_val).await {
-                let text = resp["content"][0]["text"].as_str().unwrap_or("").to_string();
-                if !text.is_empty() {
-                    let it = resp["usage"]["input_tokens"].as_i64().unwrap_or(0) as i32;
-                    let ot = resp["usage"]["output_tokens"].as_i64().unwrap_or(0) as i32;
-                    let _ = app.emit("llm-stream", serde_json::json!({
-                        "stream_id": stream_id, "type": "text", "text": &text,
-                    }));
-                    let _ = app.emit("llm-stream", serde_json::json!({
-                        "stream_id": stream_id, "type": "done",
-                        "usage": { "input_tokens": it, "output_tokens": ot },
-                    }));
-                    return Ok(LLMResponse { text, model: model_id.to_string(), usage: TokenUsage { input_tokens: it, output_tokens: ot }, tool_calls: None, stop_reason: Some("end_turn".to_string()) });
-                }
+        if let Ok((resp, invoked_id)) = call_bedrock_target(access_key, secret_key, target, &body_val).await {
+            let text = resp["content"][0]["text"].as_str().unwrap_or("").to_string();
+            if !text.is_empty() {
+                let it = resp["usage"]["input_tokens"].as_i64().unwrap_or(0) as i32;
+                let ot = resp["usage"]["output_tokens"].as_i64().unwrap_or(0) as i32;
+                let _ = app.emit("llm-stream", serde_json::json!({
+                    "stream_id": stream_id, "type": "text", "text": &text,
+                }));
+                let _ = app.emit("llm-stream", serde_json::json!({
+                    "stream_id": stream_id, "type": "done",
+                    "usage": { "input_tokens": it, "output_tokens": ot },
+                }));
+                return Ok(LLMResponse { text, model: invoked_id, usage: TokenUsage { input_tokens: it, output_tokens: ot }, tool_calls: None, stop_reason: Some("end_turn".to_string()) });
             }
         }
         return Err("Bedrock returned empty response — check model ID and credentials".to_string());
@@ -1828,3 +2088,33 @@ async fn stream_bedrock(
         stop_reason: Some(stop_reason),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::testing::{shared_workspace, team_state};
+    use hb_core::error::ErrorCode;
+    use hb_core::project::Role;
+    use tauri::Manager;
+
+    #[tokio::test]
+    async fn credentials_need_admin_in_every_shared_workspace() {
+        let state = team_state("ada").await;
+        let own = shared_workspace(&state, &[("ada", Role::Admin)]).await;
+        shared_workspace(&state, &[("ada", Role::Viewer), ("bob", Role::Admin)]).await;
+        let app = tauri::test::mock_app();
+        app.manage(state);
+
+        // Credentials are shared by every workspace, so being admin of the
+        // one the settings were opened from is not enough
+        for workspace_id in [None, Some(own.to_string())] {
+            let err = set_bedrock_region("eu-west-1".into(), workspace_id, app.state())
+                .await
+                .unwrap_err();
+            assert_eq!(err.code, ErrorCode::PermissionDenied);
+        }
+        let state = app.state::<crate::state::AppState>();
+        assert!(state.llm_credentials.read().await.bedrock_region.is_none());
+        assert!(!state.credentials_path().exists());
+    }
+}
@@ -0,0 +1,70 @@
+//! Local model commands — manage GGUF models for the llama.cpp provider.
+
+use crate::local_models::{self, LocalModel, SystemMemory, VerifyReport};
+use hb_core::error::AppError;
+use hb_tool_executor::progress::{Progress, ProgressReporter};
+use tauri::{AppHandle, Emitter};
+
+/// Event emitted while a model downloads.
+const DOWNLOAD_EVENT: &str = "local-model-download";
+
+#[tauri::command]
+pub async fn list_local_models() -> Result<Vec<LocalModel>, AppError> {
+    local_models::list_models().await.map_err(AppError::from)
+}
+
+#[tauri::command]
+pub async fn get_system_memory() -> Result<SystemMemory, AppError> {
+    Ok(local_models::system_memory().await)
+}
+
+/// Download a GGUF model. `file_name` defaults to the URL's last segment;
+/// progress is emitted as `local-model-download` events.
+#[tauri::command]
+pub async fn download_local_model(
+    url: String,
+    file_name: Option<String>,
+    sha256: Option<String>,
+    app: AppHandle,
+) -> Result<LocalModel, AppError> {
+    let emit_url = url.clone();
+    let reporter = ProgressReporter::new(move |progress: Progress| {
+        let _ = app.emit(
+            DOWNLOAD_EVENT,
+            serde_json::json!({ "url": emit_url, "progress": progress }),
+        );
+    });
+    local_models::download_model(&url, file_name, sha256, |done, total| match total {
+        Some(total) => reporter.items(done, total, "downloading"),
+        None => reporter.report(Progress {
+            current: Some(done),
+            ..Progress::step("downloading")
+        }),
+    })
+    .await
+    .map_err(AppError::from)
+}
+
+/// Re-hash a model and check its header.
+#[tauri::command]
+pub async fn verify_local_model(
+    id: String,
+    sha256: Option<String>,
+) -> Result<VerifyReport, AppError> {
+    local_models::verify_model(&id, sha256)
+        .await
+        .map_err(AppError::from)
+}
+
+#[tauri::command]
+pub async fn delete_local_model(id: String) -> Result<bool, AppError> {
+    local_models::delete_model(&id)
+        .await
+        .map_err(AppError::from)
+}
+
+/// Unload the running model to free memory.
+#[tauri::command]
+pub async fn stop_local_model_server() -> Result<bool, AppError> {
+    Ok(local_models::stop_server().await)
+}
@@ -2,10 +2,15 @@
 //!
 //! Provides workflow sharing, discovery, and community features.
 
+use hb_compiler::diagnostics::{IncrementalValidator, NodePorts};
+use hb_compiler::publish::{self, CheckKind, CheckResult, CheckStatus, Finding, PublishReport};
+use hb_core::error::{AppError, ErrorCode};
+use hb_core::graph::WorkflowSpec;
+use hb_core::trace::ExecutionStatus;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tauri::State;
+use tauri::{AppHandle, Manager, State};
 use tokio::sync::RwLock;
 
 // ========== Types ==========
@@ -297,6 +302,26 @@ impl Default for MarketplaceState {
     }
 }
 
+impl MarketplaceState {
+    /// All listed workflows, sorted by id.
+    pub async fn snapshot(&self) -> Vec<MarketplaceWorkflow> {
+        let mut workflows: Vec<_> = self.workflows.read().await.values().cloned().collect();
+        workflows.sort_by(|a, b| a.id.cmp(&b.id));
+        workflows
+    }
+
+    /// Add workflows from a snapshot, keeping existing listings with the same
+    /// id. Returns how many were added.
+    pub async fn import(&self, snapshot: Vec<MarketplaceWorkflow>) -> usize {
+        let mut workflows = self.workflows.write().await;
+        let before = workflows.len();
+        for workflow in snapshot {
+            workflows.entry(workflow.id.clone()).or_insert(workflow);
+        }
+        workflows.len() - before
+    }
+}
+
 // ========== Commands ==========
 
 /// Search workflows
@@ -304,7 +329,7 @@ impl Default for MarketplaceState {
 pub async fn marketplace_search(
     filters: MarketplaceFilters,
     state: State<'_, Arc<MarketplaceState>>,
-) -> Result<SearchResult, String> {
+) -> Result<SearchResult, AppError> {
     let workflows = state.workflows.read().await;
     let mut results: Vec<MarketplaceWorkflow> = workflows.values().cloned().collect();
 
@@ -395,7 +420,7 @@ pub async fn marketplace_search(
 pub async fn marketplace_get_workflow(
     id: String,
     state: State<'_, Arc<MarketplaceState>>,
-) -> Result<Option<MarketplaceWorkflow>, String> {
+) -> Result<Option<MarketplaceWorkflow>, AppError> {
     let workflows = state.workflows.read().await;
     Ok(workflows.get(&id).cloned())
 }
@@ -404,7 +429,7 @@ pub async fn marketplace_get_workflow(
 #[tauri::command]
 pub async fn marketplace_get_featured(
     state: State<'_, Arc<MarketplaceState>>,
-) -> Result<Vec<MarketplaceWorkflow>, String> {
+) -> Result<Vec<MarketplaceWorkflow>, AppError> {
     let workflows = state.workflows.read().await;
     Ok(workflows.values().filter(|w| w.is_featured).cloned().collect())
 }
@@ -414,7 +439,7 @@ pub async fn marketplace_get_featured(
 pub async fn marketplace_get_popular(
     limit: Option<usize>,
     state: State<'_, Arc<MarketplaceState>>,
-) -> Result<Vec<MarketplaceWorkflow>, String> {
+) -> Result<Vec<MarketplaceWorkflow>, AppError> {
     let workflows = state.workflows.read().await;
     let mut results: Vec<MarketplaceWorkflow> = workflows.values().cloned().collect();
     results.sort_by(|a, b| b.downloads.cmp(&a.downloads));
@@ -427,7 +452,7 @@ pub async fn marketplace_get_popular(
 #[tauri::command]
 pub async fn marketplace_get_categories(
     state: State<'_, Arc<MarketplaceState>>,
-) -> Result<Vec<CategoryInfo>, String> {
+) -> Result<Vec<CategoryInfo>, AppError> {
     let workflows = state.workflows.read().await;
 
     let categories = vec![
@@ -466,7 +491,7 @@ pub async fn marketplace_download(
     workflow_id: String,
     user_id: String,
     state: State<'_, Arc<MarketplaceState>>,
-) -> Result<MarketplaceWorkflow, String> {
+) -> Result<MarketplaceWorkflow, AppError> {
     // Get workflow
     let mut workflows = state.workflows.write().await;
     let workflow = workflows.get_mut(&workflow_id).ok_or("Workflow not found")?;
@@ -490,7 +515,7 @@ pub async fn marketplace_like(
     workflow_id: String,
     user_id: String,
     state: State<'_, Arc<MarketplaceState>>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     let mut likes = state.user_likes.write().await;
     let user_likes = likes.entry(user_id).or_insert_with(Vec::new);
 
@@ -515,7 +540,7 @@ pub async fn marketplace_unlike(
     workflow_id: String,
     user_id: String,
     state: State<'_, Arc<MarketplaceState>>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     let mut likes = state.user_likes.write().await;
     let user_likes = likes.entry(user_id).or_insert_with(Vec::new);
 
@@ -540,7 +565,7 @@ pub async fn marketplace_is_liked(
     workflow_id: String,
     user_id: String,
     state: State<'_, Arc<MarketplaceState>>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     let likes = state.user_likes.read().await;
     Ok(likes.get(&user_id).map_or(false, |l| l.contains(&workflow_id)))
 }
@@ -550,7 +575,7 @@ pub async fn marketplace_is_liked(
 pub async fn marketplace_get_reviews(
     workflow_id: String,
     state: State<'_, Arc<MarketplaceState>>,
-) -> Result<Vec<WorkflowReview>, String> {
+) -> Result<Vec<WorkflowReview>, AppError> {
     let reviews = state.reviews.read().await;
     Ok(reviews.get(&workflow_id).cloned().unwrap_or_default())
 }
@@ -565,9 +590,9 @@ pub async fn marketplace_submit_review(
     title: String,
     content: String,
     state: State<'_, Arc<MarketplaceState>>,
-) -> Result<WorkflowReview, String> {
+) -> Result<WorkflowReview, AppError> {
     if rating < 1 || rating > 5 {
-        return Err("Rating must be between 1 and 5".to_string());
+        return Err("Rating must be between 1 and 5".into());
     }
 
     let now = chrono::Utc::now().to_rfc3339();
@@ -615,7 +640,7 @@ pub async fn marketplace_create_collection(
     description: String,
     is_public: bool,
     state: State<'_, Arc<MarketplaceState>>,
-) -> Result<WorkflowCollection, String> {
+) -> Result<WorkflowCollection, AppError> {
     let now = chrono::Utc::now().to_rfc3339();
 
     let collection = WorkflowCollection {
@@ -642,12 +667,12 @@ pub async fn marketplace_add_to_collection(
     workflow_id: String,
     user_id: String,
     state: State<'_, Arc<MarketplaceState>>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     let mut collections = state.collections.write().await;
     let collection = collections.get_mut(&collection_id).ok_or("Collection not found")?;
 
     if collection.user_id != user_id {
-        return Err("Not your collection".to_string());
+        return Err("Not your collection".into());
     }
 
     if collection.workflow_ids.contains(&workflow_id) {
@@ -667,12 +692,12 @@ pub async fn marketplace_remove_from_collection(
     workflow_id: String,
     user_id: String,
     state: State<'_, Arc<MarketplaceState>>,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     let mut collections = state.collections.write().await;
     let collection = collections.get_mut(&collection_id).ok_or("Collection not found")?;
 
     if collection.user_id != user_id {
-        return Err("Not your collection".to_string());
+        return Err("Not your collection".into());
     }
 
     let len_before = collection.workflow_ids.len();
@@ -691,7 +716,7 @@ pub async fn marketplace_remove_from_collection(
 pub async fn marketplace_get_collections(
     user_id: String,
     state: State<'_, Arc<MarketplaceState>>,
-) -> Result<Vec<WorkflowCollection>, String> {
+) -> Result<Vec<WorkflowCollection>, AppError> {
     let collections = state.collections.read().await;
     Ok(collections.values().filter(|c| c.user_id == user_id).cloned().collect())
 }
@@ -701,7 +726,7 @@ pub async fn marketplace_get_collections(
 pub async fn marketplace_get_downloads(
     user_id: String,
     state: State<'_, Arc<MarketplaceState>>,
-) -> Result<Vec<String>, String> {
+) -> Result<Vec<String>, AppError> {
     let downloads = state.user_downloads.read().await;
     Ok(downloads.get(&user_id).cloned().unwrap_or_default())
 }
@@ -711,19 +736,206 @@ pub async fn marketplace_get_downloads(
 pub async fn marketplace_get_likes(
     user_id: String,
     state: State<'_, Arc<MarketplaceState>>,
-) -> Result<Vec<String>, String> {
+) -> Result<Vec<String>, AppError> {
     let likes = state.user_likes.read().await;
     Ok(likes.get(&user_id).cloned().unwrap_or_default())
 }
 
-/// Publish workflow
+// ========== Publish checks ==========
+
+/// Run the publish pipeline on `request`: lint, validation, secret scan and
+/// license check, then the workflow's test cases. Tests execute the
+/// workflow's tools, so they only run once every static check has passed.
+async fn check_publish(
+    app: &AppHandle,
+    request: &PublishRequest,
+    workspace_id: Option<&str>,
+) -> PublishReport {
+    let state = app.state::<crate::state::AppState>();
+    let spec = match WorkflowSpec::from_json(&request.workflow_data) {
+        Ok(upgraded) => upgraded.spec,
+        Err(e) => {
+            let finding = Finding::error(
+                "invalid_workflow_data",
+                format!("Workflow data is not a workflow: {e}"),
+            );
+            let skipped = "The workflow could not be read";
+            return PublishReport::new(vec![
+                CheckResult::skipped(CheckKind::Lint, skipped),
+                CheckResult::from_findings(CheckKind::Validation, vec![finding]),
+                CheckResult::skipped(CheckKind::Secrets, skipped),
+                CheckResult::skipped(CheckKind::Licenses, skipped),
+                CheckResult::skipped(CheckKind::Tests, skipped),
+            ]);
+        }
+    };
+
+    let validation = {
+        let registry = state.tool_registry.read().await;
+        let resolve = |tool_ref: &str| {
+            let tool_id = tool_ref.split('@').next().unwrap_or(tool_ref);
+            registry.get(tool_id).map(|tool| NodePorts {
+                inputs: tool.input_schema.ports.clone(),
+                outputs: tool.output_schema.ports.clone(),
+                config: tool.config_schema.clone(),
+            })
+        };
+        IncrementalValidator::new().validate_all(&spec, &resolve)
+    };
+
+    let packs_dir = state.data_dir.parent().unwrap_or(&state.data_dir).join("packs");
+    let pack_licenses: HashMap<String, String> = spec
+        .required_packs
+        .iter()
+        .filter_map(|p| {
+            let manifest_path = packs_dir.join(&p.pack_id).join("manifest.json");
+            let content = std::fs::read_to_string(manifest_path).ok()?;
+            let manifest: serde_json::Value = serde_json::from_str(&content).ok()?;
+            let license = manifest.get("license").and_then(|l| l.as_str()).unwrap_or_default();
+            Some((p.pack_id.clone(), license.to_string()))
+        })
+        .collect();
+
+    let mut checks = vec![
+        CheckResult::from_findings(CheckKind::Lint, publish::lint(&spec)),
+        CheckResult::from_findings(CheckKind::Validation, publish::validation(&validation)),
+        CheckResult::from_findings(CheckKind::Secrets, publish::scan_secrets(&spec)),
+        CheckResult::from_findings(
+            CheckKind::Licenses,
+            publish::check_licenses(&spec, &request.license, &pack_licenses),
+        ),
+    ];
+    let tests = if spec.tests.is_empty() {
+        CheckResult::skipped(CheckKind::Tests, "The workflow has no test cases")
+    } else if checks.iter().any(|c| c.status == CheckStatus::Failed) {
+        CheckResult::skipped(CheckKind::Tests, "Earlier checks failed")
+    } else {
+        CheckResult::from_findings(CheckKind::Tests, run_tests(app, &spec, workspace_id).await)
+    };
+    checks.push(tests);
+    PublishReport::new(checks)
+}
+
+/// Run each test case of `spec` and compare the outcome with its
+/// expectations.
+async fn run_tests(app: &AppHandle, spec: &WorkflowSpec, workspace_id: Option<&str>) -> Vec<Finding> {
+    let state = app.state::<crate::state::AppState>();
+    let mut findings = Vec::new();
+    for test in &spec.tests {
+        let execution_id = uuid::Uuid::new_v4();
+        let record =
+            crate::commands::execution::run_test_case(app, spec.clone(), workspace_id, execution_id)
+                .await;
+        let status = match record {
+            Ok(record) => {
+                serde_json::from_value(record["status"].clone()).unwrap_or(ExecutionStatus::Failed)
+            }
+            Err(e) => {
+                findings.push(Finding::error("test_failed", format!("{}: {}", test.name, e.message)));
+                continue;
+            }
+        };
+        if status != ExecutionStatus::Completed {
+            findings.push(Finding::error(
+                "test_failed",
+                format!("{}: the run ended as {status:?}", test.name),
+            ));
+            continue;
+        }
+        if test.expected_outputs.is_empty() {
+            continue;
+        }
+
+        let spans = match state.trace_store.read().await.as_ref() {
+            Some(store) => store.query_spans_by_execution(execution_id).map_err(|e| e.to_string()),
+            None => Err("trace storage is unavailable".to_string()),
+        };
+        let spans = match spans {
+            Ok(spans) => spans,
+            Err(e) => {
+                findings.push(Finding::error(
+                    "test_failed",
+                    format!("{}: outputs can't be checked: {e}", test.name),
+                ));
+                continue;
+            }
+        };
+        for (node_id, expected) in &test.expected_outputs {
+            // The last output wins for nodes that ran more than once
+            let actual = spans
+                .iter()
+                .rev()
+                .filter(|s| &s.node_id == node_id)
+                .find_map(|s| s.output_json.as_ref());
+            let message = match actual {
+                None => format!("{}: the node produced no output", test.name),
+                Some(actual) if !publish::output_matches(expected, actual) => {
+                    format!("{}: output {actual} does not match {expected}", test.name)
+                }
+                Some(_) => continue,
+            };
+            findings.push(Finding::error("unexpected_output", message).on_node(node_id));
+        }
+    }
+    findings
+}
+
+/// Run the publish checks without publishing, e.g. to show the report in
+/// the publish dialog.
+#[tauri::command]
+pub async fn marketplace_check_publish(
+    request: PublishRequest,
+    workspace_id: Option<String>,
+    app: AppHandle,
+) -> Result<PublishReport, AppError> {
+    Ok(check_publish(&app, &request, workspace_id.as_deref()).await)
+}
+
+/// Publish workflow. A workflow under a review policy, from the named
+/// workspace or the one it was submitted in, must be approved and is
+/// published as the approved stored revision. Every publish must pass the
+/// publish checks; a failed report is returned as the error's details.
 #[tauri::command]
 pub async fn marketplace_publish(
     request: PublishRequest,
     user_id: String,
     user_name: String,
+    workflow_id: Option<String>,
+    workspace_id: Option<String>,
+    app: AppHandle,
     state: State<'_, Arc<MarketplaceState>>,
-) -> Result<MarketplaceWorkflow, String> {
+    app_state: State<'_, crate::state::AppState>,
+) -> Result<MarketplaceWorkflow, AppError> {
+    let workspace = crate::commands::workflow::parse_workspace_id(workspace_id.clone())?;
+    // The client's data names the stored workflow even when it doesn't pass
+    // the ID, so the gate can't be skipped by leaving it out
+    let workflow_id = workflow_id.or_else(|| {
+        WorkflowSpec::from_json(&request.workflow_data)
+            .ok()
+            .map(|upgraded| upgraded.spec.id.to_string())
+    });
+    let mut request = request;
+    if let Some(approved) = crate::commands::workflow::require_approval(
+        &app_state,
+        workspace,
+        workflow_id.as_deref().unwrap_or_default(),
+    )
+    .await?
+    {
+        // Publish the revision that was reviewed, not whatever the client sent
+        request.workflow_data = serde_json::to_string(&approved)
+            .map_err(|e| AppError::internal(format!("Failed to serialize workflow: {e}")))?;
+    }
+
+    let report = check_publish(&app, &request, workspace_id.as_deref()).await;
+    if !report.passed {
+        return Err(AppError::new(
+            ErrorCode::PolicyViolation,
+            format!("Publish checks failed: {}", report.summary()),
+        )
+        .with_details(serde_json::to_value(&report).unwrap_or_default()));
+    }
+
     let now = chrono::Utc::now().to_rfc3339();
 
     let workflow = MarketplaceWorkflow {
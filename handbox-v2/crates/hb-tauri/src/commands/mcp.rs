@@ -3,6 +3,7 @@
 //! Provides Tauri commands for connecting to and managing MCP servers,
 //! discovering tools, and invoking tool calls.
 
+use hb_core::error::AppError;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::process::{Child, Command, Stdio};
@@ -123,7 +124,7 @@ impl Default for McpState {
 pub async fn mcp_add_server(
     state: State<'_, McpState>,
     config: McpServerConfig,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let mut servers = state.servers.lock().await;
 
     let id = config.id.clone();
@@ -145,7 +146,7 @@ pub async fn mcp_add_server(
 pub async fn mcp_remove_server(
     state: State<'_, McpState>,
     server_id: String,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let mut servers = state.servers.lock().await;
 
     // Kill process if running
@@ -163,7 +164,7 @@ pub async fn mcp_remove_server(
 pub async fn mcp_connect_server(
     state: State<'_, McpState>,
     server_id: String,
-) -> Result<McpServerStatus, String> {
+) -> Result<McpServerStatus, AppError> {
     let mut servers = state.servers.lock().await;
 
     let server = servers.get_mut(&server_id)
@@ -226,11 +227,11 @@ pub async fn mcp_connect_server(
                 Ok(Ok((process, Err(e)))) => {
                     server.process = Some(process);
                     server.status = "error".to_string();
-                    Err(format!("Tool discovery failed: {e}"))
+                    Err(format!("Tool discovery failed: {e}").into())
                 }
                 Ok(Err(e)) => {
                     server.status = "error".to_string();
-                    Err(format!("Discovery task panicked: {e}"))
+                    Err(format!("Discovery task panicked: {e}").into())
                 }
                 Err(_) => {
                     server.status = "error".to_string();
@@ -238,7 +239,7 @@ pub async fn mcp_connect_server(
                     if let Some(mut p) = server.process.take() {
                         let _ = p.kill();
                     }
-                    Err("Tool discovery timed out (30s)".to_string())
+                    Err("Tool discovery timed out (30s)".into())
                 }
             }
         }
@@ -283,11 +284,11 @@ pub async fn mcp_connect_server(
                 }
                 Ok(Err(e)) => {
                     server.status = "error".to_string();
-                    Err(format!("SSE initialization failed: {e}"))
+                    Err(format!("SSE initialization failed: {e}").into())
                 }
                 Err(_) => {
                     server.status = "error".to_string();
-                    Err("SSE connection timed out (30s)".to_string())
+                    Err("SSE connection timed out (30s)".into())
                 }
             }
         }
@@ -331,11 +332,11 @@ pub async fn mcp_connect_server(
                 }
                 Ok(Err(e)) => {
                     server.status = "error".to_string();
-                    Err(format!("WebSocket initialization failed: {e}"))
+                    Err(format!("WebSocket initialization failed: {e}").into())
                 }
                 Err(_) => {
                     server.status = "error".to_string();
-                    Err("WebSocket connection timed out (30s)".to_string())
+                    Err("WebSocket connection timed out (30s)".into())
                 }
             }
         }
@@ -347,7 +348,7 @@ pub async fn mcp_connect_server(
 pub async fn mcp_disconnect_server(
     state: State<'_, McpState>,
     server_id: String,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let mut servers = state.servers.lock().await;
 
     if let Some(server) = servers.get_mut(&server_id) {
@@ -369,7 +370,7 @@ pub async fn mcp_disconnect_server(
 #[command]
 pub async fn mcp_list_servers(
     state: State<'_, McpState>,
-) -> Result<Vec<McpServerStatus>, String> {
+) -> Result<Vec<McpServerStatus>, AppError> {
     let servers = state.servers.lock().await;
 
     Ok(servers.values()
@@ -388,7 +389,7 @@ pub async fn mcp_list_servers(
 pub async fn mcp_get_tools(
     state: State<'_, McpState>,
     server_id: String,
-) -> Result<Vec<McpTool>, String> {
+) -> Result<Vec<McpTool>, AppError> {
     let servers = state.servers.lock().await;
 
     let server = servers.get(&server_id)
@@ -406,14 +407,14 @@ pub async fn mcp_get_tools(
 pub async fn mcp_call_tool(
     state: State<'_, McpState>,
     request: McpToolCallRequest,
-) -> Result<McpToolCallResult, String> {
+) -> Result<McpToolCallResult, AppError> {
     let mut servers = state.servers.lock().await;
 
     let server = servers.get_mut(&request.server_id)
         .ok_or_else(|| format!("Server not found: {}", request.server_id))?;
 
     if server.status != "connected" {
-        return Err("Server not connected".to_string());
+        return Err("Server not connected".into());
     }
 
     match server.config.transport {
@@ -441,15 +442,15 @@ pub async fn mcp_call_tool(
                 }
                 Ok(Ok((process, Err(e)))) => {
                     server.process = Some(process);
-                    Err(e)
+                    Err(e.into())
                 }
                 Ok(Err(e)) => {
                     server.status = "error".to_string();
-                    Err(format!("Tool call task panicked: {e}"))
+                    Err(format!("Tool call task panicked: {e}").into())
                 }
                 Err(_) => {
                     server.status = "error".to_string();
-                    Err("Tool call timed out (60s)".to_string())
+                    Err("Tool call timed out (60s)".into())
                 }
             }
         }
@@ -488,7 +489,7 @@ pub async fn mcp_call_tool(
                     if let Some(s) = servers.get_mut(&request.server_id) {
                         s.status = "error".to_string();
                     }
-                    Err("Tool call timed out (60s)".to_string())
+                    Err("Tool call timed out (60s)".into())
                 }
             }
         }
@@ -504,7 +505,7 @@ pub async fn mcp_call_tool(
 pub async fn mcp_health_check(
     state: State<'_, McpState>,
     server_id: String,
-) -> Result<bool, String> {
+) -> Result<bool, AppError> {
     let servers = state.servers.lock().await;
 
     if let Some(server) = servers.get(&server_id) {
@@ -4,16 +4,25 @@ pub mod agent;
 pub mod agent_loop;
 pub mod collaboration;
 pub mod compiler;
+pub mod config;
+pub mod data_source;
 pub mod execution;
 pub mod gis;
 pub mod ifc;
 pub mod llm;
+pub mod local_models;
 pub mod marketplace;
 pub mod mcp;
+pub mod offline_bundle;
+pub mod notification;
+pub mod onboarding;
 pub mod pack;
 pub mod project;
+pub mod schedule;
 pub mod system_tools;
+pub mod team_sync;
 pub mod tool;
 pub mod trace;
+pub mod validation;
 pub mod vector_store;
 pub mod workflow;
@@ -0,0 +1,84 @@
+//! Notification commands — preferences, test delivery, budget reporting and
+//! the webhook delivery log.
+
+use hb_core::error::AppError;
+use crate::notifications::{
+    DeliveryReport, Notification, NotificationConfig, NotificationKind, NotificationPreferences,
+    NotificationService,
+};
+use crate::state::AppState;
+use crate::webhooks::WebhookDelivery;
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub async fn get_notification_config(
+    service: State<'_, Arc<NotificationService>>,
+) -> Result<NotificationConfig, AppError> {
+    Ok(service.config().await)
+}
+
+/// Set preferences for a workspace (or the defaults when `workspace_id` is omitted).
+#[tauri::command]
+pub async fn set_notification_preferences(
+    workspace_id: Option<String>,
+    preferences: NotificationPreferences,
+    service: State<'_, Arc<NotificationService>>,
+) -> Result<(), AppError> {
+    service.set_preferences(workspace_id, preferences).await.map_err(AppError::from)
+}
+
+/// Send a test notification through every channel enabled for the workspace.
+#[tauri::command]
+pub async fn send_test_notification(
+    workspace_id: Option<String>,
+    service: State<'_, Arc<NotificationService>>,
+    app: AppHandle,
+) -> Result<DeliveryReport, AppError> {
+    let notification = Notification::new(
+        NotificationKind::ExecutionCompleted,
+        "Test notification",
+        "Notifications are working.",
+    )
+    .with_workspace(workspace_id);
+    Ok(service.notify(&app, notification).await)
+}
+
+/// Report budget usage; returns the threshold crossed, if a notification fired.
+#[tauri::command]
+pub async fn record_budget_usage(
+    workspace_id: Option<String>,
+    scope: String,
+    spent: f64,
+    limit: f64,
+    service: State<'_, Arc<NotificationService>>,
+    app: AppHandle,
+) -> Result<Option<f64>, AppError> {
+    Ok(service
+        .record_budget(&app, workspace_id, &scope, spent, limit)
+        .await)
+}
+
+/// Send a test event to one of the workspace's signed webhooks.
+#[tauri::command]
+pub async fn test_webhook(
+    workspace_id: Option<String>,
+    webhook_id: String,
+    service: State<'_, Arc<NotificationService>>,
+    state: State<'_, AppState>,
+) -> Result<WebhookDelivery, AppError> {
+    let credentials = state.llm_credentials.read().await.clone();
+    service
+        .test_webhook(workspace_id, &webhook_id, &credentials)
+        .await
+        .map_err(AppError::from)
+}
+
+/// Recent signed webhook deliveries, newest first, of one workspace or all.
+#[tauri::command]
+pub async fn list_webhook_deliveries(
+    workspace_id: Option<String>,
+    service: State<'_, Arc<NotificationService>>,
+) -> Result<Vec<WebhookDelivery>, AppError> {
+    Ok(service.webhook_deliveries(workspace_id.as_deref()).await)
+}
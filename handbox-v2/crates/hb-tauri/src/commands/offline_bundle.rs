@@ -0,0 +1,196 @@
+//! Offline bundle commands — export and install single-file bundles for
+//! air-gapped machines.
+
+use crate::commands::marketplace::MarketplaceState;
+use crate::local_models;
+use crate::offline_bundle::{self, BundleContents, BundleInstall, OfflineBundleManifest};
+use crate::state::AppState;
+use hb_core::error::AppError;
+use hb_core::project::{ActivityEntry, ActivityKind};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::State;
+
+/// What to export. Omitted lists mean "everything installed".
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OfflineBundleOptions {
+    pub pack_ids: Option<Vec<String>>,
+    /// Open workflows to include as templates.
+    pub workflow_ids: Option<Vec<String>>,
+    pub model_ids: Option<Vec<String>>,
+    #[serde(default)]
+    pub include_model_files: bool,
+    #[serde(default = "default_true")]
+    pub include_marketplace: bool,
+    /// Where to write the bundle, e.g. a removable drive. Defaults to
+    /// Documents/Handbox/exports.
+    pub path: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Serialize)]
+pub struct OfflineBundle {
+    pub path: String,
+    pub manifest: OfflineBundleManifest,
+}
+
+fn keep(selection: &Option<Vec<String>>, id: &str) -> bool {
+    selection
+        .as_ref()
+        .is_none_or(|ids| ids.iter().any(|s| s == id))
+}
+
+/// Build an offline bundle from installed packs, open workflows, local
+/// models and the marketplace listings.
+#[tauri::command]
+pub async fn export_offline_bundle(
+    options: OfflineBundleOptions,
+    state: State<'_, AppState>,
+    marketplace: State<'_, Arc<MarketplaceState>>,
+) -> Result<OfflineBundle, AppError> {
+    let packs_dir = state
+        .data_dir
+        .parent()
+        .unwrap_or(&state.data_dir)
+        .join("packs");
+    let mut pack_ids: Vec<String> = crate::pack_updater::installed_versions(&packs_dir)
+        .into_iter()
+        .map(|(id, _)| id)
+        .filter(|id| keep(&options.pack_ids, id))
+        .collect();
+    pack_ids.sort();
+    if let Some(requested) = &options.pack_ids {
+        if let Some(missing) = requested.iter().find(|id| !pack_ids.contains(id)) {
+            return Err(AppError::not_found(format!(
+                "Pack not installed: {missing}"
+            )));
+        }
+    }
+
+    let mut templates: Vec<_> = state
+        .workflows
+        .read()
+        .await
+        .iter()
+        .filter(|(id, _)| keep(&options.workflow_ids, id))
+        .map(|(_, spec)| spec.clone())
+        .collect();
+    templates.sort_by(|a, b| a.meta.name.cmp(&b.meta.name));
+    let models = local_models::list_models()
+        .await?
+        .into_iter()
+        .filter(|m| keep(&options.model_ids, &m.id))
+        .collect();
+    let marketplace = if options.include_marketplace {
+        marketplace.snapshot().await
+    } else {
+        Vec::new()
+    };
+
+    let path = match &options.path {
+        Some(path) => PathBuf::from(path),
+        None => dirs::document_dir()
+            .or_else(dirs::download_dir)
+            .unwrap_or_else(|| state.data_dir.clone())
+            .join("Handbox")
+            .join("exports")
+            .join(format!(
+                "handbox-offline-{}.zip",
+                chrono::Utc::now().format("%Y%m%d-%H%M%S")
+            )),
+    };
+    let include_model_files = options.include_model_files;
+    let bundle_path = path.clone();
+    let manifest = tokio::task::spawn_blocking(move || {
+        offline_bundle::write_bundle(
+            &bundle_path,
+            BundleContents {
+                packs_dir: &packs_dir,
+                pack_ids,
+                templates,
+                models,
+                include_model_files,
+                marketplace,
+            },
+        )
+    })
+    .await
+    .map_err(|e| format!("Bundle task failed: {e}"))??;
+
+    tracing::info!(
+        "Exported offline bundle to {} ({} packs, {} templates, {} models)",
+        path.display(),
+        manifest.packs.len(),
+        manifest.templates.len(),
+        manifest.models.len()
+    );
+    Ok(OfflineBundle {
+        path: path.to_string_lossy().to_string(),
+        manifest,
+    })
+}
+
+/// Show what a bundle contains before installing it.
+#[tauri::command]
+pub async fn preview_offline_bundle(path: String) -> Result<OfflineBundleManifest, AppError> {
+    offline_bundle::read_manifest(std::path::Path::new(&path)).map_err(AppError::from)
+}
+
+/// Install a bundle: newer packs, bundled model files, templates as open
+/// workflows and marketplace listings. Nothing already present is replaced
+/// except packs older than the bundled version.
+#[tauri::command]
+pub async fn install_offline_bundle(
+    path: String,
+    state: State<'_, AppState>,
+    marketplace: State<'_, Arc<MarketplaceState>>,
+) -> Result<BundleInstall, AppError> {
+    let packs_dir = state
+        .data_dir
+        .parent()
+        .unwrap_or(&state.data_dir)
+        .join("packs");
+    let bundle_path = PathBuf::from(&path);
+    let unpacked = tokio::task::spawn_blocking(move || {
+        offline_bundle::install_bundle(&bundle_path, &packs_dir, &local_models::models_dir())
+    })
+    .await
+    .map_err(|e| format!("Bundle task failed: {e}"))??;
+
+    let mut install = unpacked.install;
+    {
+        let mut workflows = state.workflows.write().await;
+        for spec in unpacked.templates {
+            if let Entry::Vacant(entry) = workflows.entry(spec.id.to_string()) {
+                entry.insert(spec);
+                install.templates_added += 1;
+            }
+        }
+    }
+    install.marketplace_added = marketplace.import(unpacked.marketplace).await;
+
+    for outcome in &install.packs_installed {
+        state
+            .record_activity(
+                ActivityEntry::new(
+                    ActivityKind::PackInstalled,
+                    &outcome.pack_id,
+                    format!(
+                        "Installed pack '{}' {} from an offline bundle",
+                        outcome.pack_id, outcome.installed_version
+                    ),
+                )
+                .with_details(serde_json::json!({ "source": path })),
+            )
+            .await;
+    }
+    if !install.failed.is_empty() {
+        tracing::warn!("Offline bundle {path}: {}", install.failed.join("; "));
+    }
+    Ok(install)
+}
@@ -0,0 +1,113 @@
+//! Onboarding commands — preview and create a workspace from the setup
+//! wizard's answers.
+
+use crate::credential_health::{self, CredentialHealth};
+use crate::onboarding::{self, OnboardingAnswers, OnboardingPlan};
+use crate::state::AppState;
+use hb_core::error::AppError;
+use hb_core::project::{ActivityEntry, ActivityKind};
+use serde::Serialize;
+use tauri::State;
+
+/// A planned or created workspace, with the check of its provider's credential.
+#[derive(Debug, Clone, Serialize)]
+pub struct OnboardingResult {
+    #[serde(flatten)]
+    pub plan: OnboardingPlan,
+    /// `None` when the provider can't be checked or has no credential.
+    pub credential: Option<CredentialHealth>,
+}
+
+/// What `complete_onboarding` would create for these answers, without
+/// creating anything.
+#[tauri::command]
+pub async fn preview_onboarding(
+    answers: OnboardingAnswers,
+    state: State<'_, AppState>,
+) -> Result<OnboardingResult, AppError> {
+    let plan = onboarding::plan(&answers).map_err(AppError::invalid_input)?;
+    Ok(check_credential(&state, &answers, plan).await)
+}
+
+/// Create the workspace, its starter workflows and provider configuration
+/// from the wizard's answers. A missing or failing credential is reported,
+/// not refused, so the user can fix it from the new workspace.
+#[tauri::command]
+pub async fn complete_onboarding(
+    answers: OnboardingAnswers,
+    state: State<'_, AppState>,
+) -> Result<OnboardingResult, AppError> {
+    let plan = onboarding::plan(&answers).map_err(AppError::invalid_input)?;
+    // Connection strings live in the credential store, not the workspace config
+    if !plan.secrets.is_empty() {
+        let mut creds = state.llm_credentials.write().await;
+        creds.service_keys.extend(plan.secrets.clone());
+        creds.save(&state.credentials_path())?;
+    }
+    let workspace_id = state.project_manager.read().await.create_workspace(&plan.workspace)?;
+    super::project::init_project_dir(&state, workspace_id)?;
+
+    for spec in &plan.workflows {
+        let id = spec.id.to_string();
+        state
+            .workflows
+            .write()
+            .await
+            .insert(id.clone(), spec.clone());
+        state
+            .record_activity(
+                ActivityEntry::new(
+                    ActivityKind::WorkflowCreated,
+                    id,
+                    format!("Created starter workflow '{}'", spec.meta.name),
+                )
+                .in_workspace(Some(workspace_id)),
+            )
+            .await;
+        state.refresh_thumbnail(spec).await;
+    }
+
+    // Use the chosen provider unless one is already active
+    if let Some(provider) = onboarding::credential_provider(&answers.llm_provider) {
+        let mut creds = state.llm_credentials.write().await;
+        if creds.active_provider.is_none() {
+            creds.active_provider = Some(provider.to_string());
+            creds.save(&state.credentials_path())?;
+        }
+    }
+
+    tracing::info!(
+        "Onboarded workspace '{}' with {} starter workflows",
+        plan.workspace.name,
+        plan.workflows.len()
+    );
+    Ok(check_credential(&state, &answers, plan).await)
+}
+
+async fn check_credential(
+    state: &AppState,
+    answers: &OnboardingAnswers,
+    mut plan: OnboardingPlan,
+) -> OnboardingResult {
+    let Some(provider) = onboarding::credential_provider(&answers.llm_provider) else {
+        return OnboardingResult {
+            plan,
+            credential: None,
+        };
+    };
+    let creds = state.llm_credentials.read().await.clone();
+    let credential = credential_health::check_one(&creds, provider).await;
+    match &credential {
+        None => plan
+            .warnings
+            .push(format!("No {provider} credential is configured yet")),
+        Some(health) if health.state != credential_health::HealthState::Valid => {
+            plan.warnings.push(match &health.message {
+                Some(message) => format!("{}: {message}", health.summary()),
+                None => health.summary(),
+            })
+        }
+        Some(_) => {}
+    }
+    OnboardingResult { plan, credential }
+}
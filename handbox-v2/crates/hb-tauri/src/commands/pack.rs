@@ -1,12 +1,21 @@
-//! Pack management commands — list, install, and inspect packs.
+//! Pack management commands — list, install, inspect, and update packs.
 
+use hb_core::error::AppError;
+use crate::pack_content::{self, InstalledContent};
+use crate::pack_report::{self, PackReport, ReportFormat};
+use crate::pack_updater::{self, AvailableUpdate, PackSource, PackSourceConfig, UpdateOutcome};
 use crate::state::AppState;
-use tauri::State;
+use crate::commands::validation;
+use hb_compiler::migrate::{self, MigrationReport};
+use hb_core::graph::NodeEntry;
+use hb_core::pack::{CompositeDefinition, PackContentKind};
+use hb_core::project::{ActivityEntry, ActivityKind};
+use tauri::{AppHandle, State};
 
 #[tauri::command]
 pub async fn list_packs(
     state: State<'_, AppState>,
-) -> Result<Vec<serde_json::Value>, String> {
+) -> Result<Vec<serde_json::Value>, AppError> {
     let packs_dir = state.data_dir.parent().unwrap_or(&state.data_dir).join("packs");
 
     // Also check the built-in packs directory
@@ -37,16 +46,16 @@ pub async fn list_packs(
 pub async fn get_pack(
     pack_id: String,
     state: State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, AppError> {
     let packs_dir = state.data_dir.parent().unwrap_or(&state.data_dir).join("packs");
     let manifest_path = packs_dir.join(&pack_id).join("manifest.json");
 
     if !manifest_path.exists() {
-        return Err(format!("Pack not found: {pack_id}"));
+        return Err(format!("Pack not found: {pack_id}").into());
     }
 
     let content = std::fs::read_to_string(&manifest_path).map_err(|e| e.to_string())?;
-    serde_json::from_str(&content).map_err(|e| e.to_string())
+    serde_json::from_str(&content).map_err(AppError::from)
 }
 
 /// Install a pack from a local directory path.
@@ -56,10 +65,10 @@ pub async fn install_pack(
     source_path: String,
     _version: String,
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    let source = std::path::Path::new(&source_path);
+) -> Result<(), AppError> {
+    let source = &hb_core::path::resolve(&source_path);
     if !source.exists() {
-        return Err(format!("Source path does not exist: {source_path}"));
+        return Err(format!("Source path does not exist: {source_path}").into());
     }
 
     // Validate that source has a manifest.json
@@ -76,6 +85,7 @@ pub async fn install_pack(
         .get("id")
         .and_then(|v| v.as_str())
         .ok_or("Manifest missing 'id' field")?;
+    pack_content::check_pack_id(pack_id)?;
 
     // Copy to packs directory
     let packs_dir = state.data_dir.parent().unwrap_or(&state.data_dir).join("packs");
@@ -89,7 +99,16 @@ pub async fn install_pack(
     // Recursively copy files
     copy_dir_recursive(source, &target).map_err(|e| format!("Failed to copy pack: {e}"))?;
 
+    pack_content::sync(&packs_dir, pack_id)?;
+
     tracing::info!("Installed pack '{pack_id}' from {source_path}");
+    let version = manifest.get("version").and_then(|v| v.as_str()).unwrap_or("unknown");
+    state
+        .record_activity(
+            ActivityEntry::new(ActivityKind::PackInstalled, pack_id, format!("Installed pack '{pack_id}' {version}"))
+                .with_details(serde_json::json!({ "source": source_path })),
+        )
+        .await;
     Ok(())
 }
 
@@ -107,3 +126,247 @@ fn copy_dir_recursive(src: &std::path::Path, dst: &std::path::Path) -> std::io::
     }
     Ok(())
 }
+
+/// List configured pack update sources.
+#[tauri::command]
+pub async fn list_pack_sources(
+    state: State<'_, AppState>,
+) -> Result<Vec<PackSource>, AppError> {
+    Ok(PackSourceConfig::load(&state.data_dir).sources)
+}
+
+/// Replace the configured pack update sources.
+#[tauri::command]
+pub async fn set_pack_sources(
+    sources: Vec<PackSource>,
+    state: State<'_, AppState>,
+) -> Result<(), AppError> {
+    PackSourceConfig { sources }.save(&state.data_dir).map_err(AppError::from)
+}
+
+/// Check all enabled sources for newer versions of installed packs.
+#[tauri::command]
+pub async fn check_pack_updates(
+    state: State<'_, AppState>,
+) -> Result<Vec<AvailableUpdate>, AppError> {
+    let packs_dir = state.data_dir.parent().unwrap_or(&state.data_dir).join("packs");
+    let sources = PackSourceConfig::load(&state.data_dir).sources;
+    let workflows = state.workflows.read().await;
+    Ok(pack_updater::check_updates(&packs_dir, &sources, workflows.values()).await)
+}
+
+/// Apply the latest available update for a pack.
+///
+/// Refused when workflows pin a version range the update falls outside of,
+/// unless `force` is set.
+#[tauri::command]
+pub async fn apply_pack_update(
+    pack_id: String,
+    force: bool,
+    state: State<'_, AppState>,
+) -> Result<UpdateOutcome, AppError> {
+    let packs_dir = state.data_dir.parent().unwrap_or(&state.data_dir).join("packs");
+    let sources = PackSourceConfig::load(&state.data_dir).sources;
+    let update = {
+        let workflows = state.workflows.read().await;
+        pack_updater::check_updates(&packs_dir, &sources, workflows.values())
+            .await
+            .into_iter()
+            .find(|u| u.pack_id == pack_id)
+            .ok_or_else(|| format!("No update available for pack: {pack_id}"))?
+    };
+
+    if !force && !update.incompatible_workflows.is_empty() {
+        let names: Vec<_> = update
+            .incompatible_workflows
+            .iter()
+            .map(|w| format!("{} ({})", w.workflow_name, w.version_range))
+            .collect();
+        return Err(format!(
+            "{pack_id}@{} is outside the version pinned by: {}",
+            update.latest_version,
+            names.join(", ")
+        ).into());
+    }
+
+    let outcome = pack_updater::apply_update(&packs_dir, &update).await?;
+    state
+        .record_activity(
+            ActivityEntry::new(
+                ActivityKind::PackUpdated,
+                &pack_id,
+                format!("Updated pack '{pack_id}' to {}", outcome.installed_version),
+            )
+            .with_details(serde_json::json!({ "source": update.source_id, "forced": force })),
+        )
+        .await;
+    Ok(outcome)
+}
+
+/// Restore the version replaced by the last update of a pack.
+#[tauri::command]
+pub async fn rollback_pack_update(
+    pack_id: String,
+    state: State<'_, AppState>,
+) -> Result<UpdateOutcome, AppError> {
+    let packs_dir = state.data_dir.parent().unwrap_or(&state.data_dir).join("packs");
+    let outcome = pack_updater::rollback(&packs_dir, &pack_id)?;
+    state
+        .record_activity(ActivityEntry::new(
+            ActivityKind::PackUpdated,
+            &pack_id,
+            format!("Rolled back pack '{pack_id}' to {}", outcome.installed_version),
+        ))
+        .await;
+    Ok(outcome)
+}
+
+/// Report how saved workflows migrate across the last update of a pack.
+#[tauri::command]
+pub async fn plan_pack_migration(
+    pack_id: String,
+    state: State<'_, AppState>,
+) -> Result<MigrationReport, AppError> {
+    let packs_dir = state.data_dir.parent().unwrap_or(&state.data_dir).join("packs");
+    let upgrade = pack_updater::last_upgrade(&packs_dir, &pack_id)?;
+    let workflows = state.workflows.read().await;
+    Ok(migrate::report(&upgrade, workflows.values()))
+}
+
+/// Migrate saved workflows across the last update of a pack: all affected
+/// ones, or only `workflow_ids`. Nodes needing attention are left as they
+/// were; the returned report lists them.
+#[tauri::command]
+pub async fn apply_pack_migration(
+    pack_id: String,
+    workflow_ids: Option<Vec<String>>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<MigrationReport, AppError> {
+    let packs_dir = state.data_dir.parent().unwrap_or(&state.data_dir).join("packs");
+    let upgrade = pack_updater::last_upgrade(&packs_dir, &pack_id)?;
+    let mut report = {
+        let workflows = state.workflows.read().await;
+        migrate::report(&upgrade, workflows.values())
+    };
+    if let Some(ids) = &workflow_ids {
+        report.workflows.retain(|w| ids.contains(&w.workflow_id));
+    }
+
+    for migration in &report.workflows {
+        if migration.changes.is_empty() {
+            continue;
+        }
+        let spec = &migration.migrated;
+        state.workflows.write().await.insert(migration.workflow_id.clone(), spec.clone());
+        state
+            .record_activity(
+                ActivityEntry::new(
+                    ActivityKind::WorkflowEdited,
+                    &migration.workflow_id,
+                    format!(
+                        "Migrated workflow '{}' to {pack_id}@{}",
+                        spec.meta.name, report.to_version
+                    ),
+                )
+                .with_details(serde_json::json!({
+                    "changes": migration.changes.len(),
+                    "issues": migration.issues.len(),
+                })),
+            )
+            .await;
+        state.refresh_thumbnail(spec).await;
+        validation::publish_diagnostics(&app, &state, spec, None).await;
+    }
+    Ok(report)
+}
+
+/// Composites, compile templates, prompt libraries and eval suites installed
+/// from packs, optionally of one kind.
+#[tauri::command]
+pub async fn list_pack_contents(
+    kind: Option<PackContentKind>,
+    state: State<'_, AppState>,
+) -> Result<Vec<InstalledContent>, AppError> {
+    let packs_dir = state.data_dir.parent().unwrap_or(&state.data_dir).join("packs");
+    Ok(pack_content::list(&packs_dir, kind))
+}
+
+/// The file of one installed composite, compile template, prompt library or
+/// eval suite.
+#[tauri::command]
+pub async fn read_pack_content(
+    kind: PackContentKind,
+    pack_id: String,
+    content_id: String,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, AppError> {
+    let packs_dir = state.data_dir.parent().unwrap_or(&state.data_dir).join("packs");
+    Ok(pack_content::read(&packs_dir, kind, &pack_id, &content_id)?)
+}
+
+/// A copy of an installed pack composite, ready to add to a workflow as
+/// `node_id`.
+#[tauri::command]
+pub async fn instantiate_pack_composite(
+    pack_id: String,
+    composite_id: String,
+    node_id: String,
+    state: State<'_, AppState>,
+) -> Result<NodeEntry, AppError> {
+    let packs_dir = state.data_dir.parent().unwrap_or(&state.data_dir).join("packs");
+    let content = pack_content::read(&packs_dir, PackContentKind::Composite, &pack_id, &composite_id)?;
+    let definition: CompositeDefinition = serde_json::from_value(content)?;
+    Ok(NodeEntry::Composite(definition.instantiate(&node_id)))
+}
+
+/// Inventory of installed packs: versions, licenses, declared dependencies
+/// and tool permissions.
+#[tauri::command]
+pub async fn get_pack_report(
+    state: State<'_, AppState>,
+) -> Result<PackReport, AppError> {
+    let packs_dir = state.data_dir.parent().unwrap_or(&state.data_dir).join("packs");
+    Ok(build_report(packs_dir).await?)
+}
+
+/// Build the pack inventory on the blocking pool, since it hashes every
+/// pack's files.
+async fn build_report(packs_dir: std::path::PathBuf) -> Result<PackReport, String> {
+    tokio::task::spawn_blocking(move || pack_report::build(&packs_dir))
+        .await
+        .map_err(|e| format!("Pack report failed: {e}"))
+}
+
+/// Write the pack inventory as JSON or CSV for security review. Defaults to
+/// Documents/Handbox/exports; returns the path written.
+#[tauri::command]
+pub async fn export_pack_report(
+    format: ReportFormat,
+    output_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, AppError> {
+    let packs_dir = state.data_dir.parent().unwrap_or(&state.data_dir).join("packs");
+    let report = build_report(packs_dir).await?;
+    let content = pack_report::render(&report, format)?;
+
+    let path = match output_path {
+        Some(path) => std::path::PathBuf::from(path),
+        None => dirs::document_dir()
+            .or_else(dirs::download_dir)
+            .unwrap_or_else(|| state.data_dir.clone())
+            .join("Handbox")
+            .join("exports")
+            .join(format!(
+                "handbox-packs-{}.{}",
+                report.generated_at.format("%Y%m%d-%H%M%S"),
+                format.extension()
+            )),
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(&path, content)
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    Ok(path.to_string_lossy().to_string())
+}
@@ -1,6 +1,12 @@
 //! Project/Workspace management commands.
 
+use hb_core::error::AppError;
 use crate::state::AppState;
+use hb_core::policy::Policy;
+use hb_core::project::{
+    ActivityEntry, ActivityKind, ActivityPage, EnvVar, Permission, ReviewPolicy, Role,
+    WorkspaceMember,
+};
 use serde_json::json;
 use tauri::State;
 
@@ -10,7 +16,7 @@ pub async fn create_project(
     description: Option<String>,
     _root_path: Option<String>,
     state: State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, AppError> {
     let pm = state.project_manager.read().await;
 
     let config = hb_core::project::WorkspaceConfig {
@@ -24,11 +30,24 @@ pub async fn create_project(
         indexes: vec![],
         default_policy: None,
         llm_providers: vec![],
+        timezone: None,
+        members: vec![],
+        review_policy: None,
     };
 
     let id = pm.create_workspace(&config).map_err(|e| e.to_string())?;
+    init_project_dir(&state, id)?;
 
-    // Create project directories (memory, plan)
+    Ok(json!({
+        "id": id.to_string(),
+        "name": name,
+        "description": config.description,
+        "created_at": config.created_at.to_rfc3339(),
+    }))
+}
+
+/// Create a new project's directories (memory, plan).
+pub(crate) fn init_project_dir(state: &AppState, id: uuid::Uuid) -> Result<(), String> {
     let project_dir = state.data_dir.join("projects").join(id.to_string());
     let memory_dir = project_dir.join("memory");
     std::fs::create_dir_all(&memory_dir).map_err(|e| format!("Failed to create project dir: {e}"))?;
@@ -38,20 +57,14 @@ pub async fn create_project(
     if !plan_path.exists() {
         std::fs::write(&plan_path, "").map_err(|e| format!("Failed to create plan file: {e}"))?;
     }
-
-    Ok(json!({
-        "id": id.to_string(),
-        "name": name,
-        "description": config.description,
-        "created_at": config.created_at.to_rfc3339(),
-    }))
+    Ok(())
 }
 
 #[tauri::command]
 pub async fn get_project(
     id: String,
     state: State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, AppError> {
     let pm = state.project_manager.read().await;
     let uuid: uuid::Uuid = id.parse().map_err(|e: uuid::Error| e.to_string())?;
     let ws = pm.get_workspace(uuid).map_err(|e| e.to_string())?;
@@ -69,7 +82,7 @@ pub async fn get_project(
 #[tauri::command]
 pub async fn list_projects(
     state: State<'_, AppState>,
-) -> Result<Vec<serde_json::Value>, String> {
+) -> Result<Vec<serde_json::Value>, AppError> {
     let pm = state.project_manager.read().await;
     let workspaces = pm.list_workspaces().map_err(|e| e.to_string())?;
 
@@ -88,9 +101,10 @@ pub async fn list_projects(
 pub async fn delete_project(
     id: String,
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    let pm = state.project_manager.read().await;
+) -> Result<(), AppError> {
     let uuid: uuid::Uuid = id.parse().map_err(|e: uuid::Error| e.to_string())?;
+    state.authorize(Some(uuid), Permission::ManageWorkspace).await?;
+    let pm = state.project_manager.read().await;
     pm.delete_workspace(uuid).map_err(|e| e.to_string())?;
 
     // Clean up project directory
@@ -101,3 +115,240 @@ pub async fn delete_project(
 
     Ok(())
 }
+
+/// Set (or clear) the timezone a project's schedules are evaluated in.
+#[tauri::command]
+pub async fn set_project_timezone(
+    id: String,
+    timezone: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), AppError> {
+    let uuid: uuid::Uuid = id.parse().map_err(|e: uuid::Error| e.to_string())?;
+    hb_runner::cron::resolve_timezone(timezone.as_deref()).map_err(|e| e.to_string())?;
+    state.authorize(Some(uuid), Permission::ManageWorkspace).await?;
+    let pm = state.project_manager.read().await;
+    let mut ws = pm.get_workspace(uuid).map_err(|e| e.to_string())?;
+    ws.timezone = timezone;
+    ws.updated_at = chrono::Utc::now();
+    pm.update_workspace(uuid, &ws).map_err(AppError::from)
+}
+
+/// Set (or clear) a project's default policy.
+#[tauri::command]
+pub async fn set_project_policy(
+    id: String,
+    policy: Option<Policy>,
+    state: State<'_, AppState>,
+) -> Result<(), AppError> {
+    let uuid: uuid::Uuid = id.parse().map_err(|e: uuid::Error| e.to_string())?;
+    let access = state.authorize(Some(uuid), Permission::ChangePolicy).await?;
+    {
+        let pm = state.project_manager.read().await;
+        let mut ws = pm.get_workspace(uuid).map_err(|e| e.to_string())?;
+        ws.default_policy = policy.clone();
+        ws.updated_at = chrono::Utc::now();
+        pm.update_workspace(uuid, &ws).map_err(|e| e.to_string())?;
+    }
+
+    let summary = if policy.is_some() { "Updated default policy" } else { "Cleared default policy" };
+    state
+        .record_activity(access.stamp(
+            ActivityEntry::new(ActivityKind::PolicyChanged, &id, summary)
+                .in_workspace(Some(uuid))
+                .with_details(json!({ "policy": policy })),
+        ))
+        .await;
+    Ok(())
+}
+
+/// Require (or with `None`, stop requiring) approval of workflows before
+/// they are published or scheduled from a project.
+#[tauri::command]
+pub async fn set_project_review_policy(
+    id: String,
+    policy: Option<ReviewPolicy>,
+    state: State<'_, AppState>,
+) -> Result<(), AppError> {
+    let uuid: uuid::Uuid = id.parse()?;
+    let access = state.authorize(Some(uuid), Permission::ChangePolicy).await?;
+    {
+        let pm = state.project_manager.read().await;
+        let mut ws = pm.get_workspace(uuid)?;
+        ws.review_policy = policy.clone();
+        ws.updated_at = chrono::Utc::now();
+        pm.update_workspace(uuid, &ws)?;
+    }
+
+    let summary = if policy.is_some() { "Required workflow review" } else { "Stopped requiring workflow review" };
+    state
+        .record_activity(access.stamp(
+            ActivityEntry::new(ActivityKind::PolicyChanged, &id, summary)
+                .in_workspace(Some(uuid))
+                .with_details(json!({ "review_policy": policy })),
+        ))
+        .await;
+    Ok(())
+}
+
+/// Page through the activity feed, newest first. Omit `workspace_id` for
+/// activity across all workspaces.
+#[tauri::command]
+pub async fn get_activity(
+    workspace_id: Option<String>,
+    kinds: Option<Vec<ActivityKind>>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<ActivityPage, AppError> {
+    let workspace_id = crate::commands::workflow::parse_workspace_id(workspace_id)?;
+    let pm = state.project_manager.read().await;
+    pm.query_activity(
+        workspace_id,
+        &kinds.unwrap_or_default(),
+        offset.unwrap_or(0),
+        limit.unwrap_or(50).min(500),
+    )
+    .map_err(AppError::from)
+}
+
+/// A project's environment variables, sorted by name.
+#[tauri::command]
+pub async fn list_project_env(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<EnvVar>, AppError> {
+    let uuid: uuid::Uuid = id.parse()?;
+    let pm = state.project_manager.read().await;
+    pm.list_env(uuid).map_err(AppError::from)
+}
+
+/// Create or update a non-secret environment variable for a project.
+#[tauri::command]
+pub async fn set_project_env(
+    id: String,
+    name: String,
+    value: String,
+    description: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<EnvVar, AppError> {
+    let uuid: uuid::Uuid = id.parse()?;
+    state.authorize(Some(uuid), Permission::ManageWorkspace).await?;
+    let var = EnvVar {
+        name,
+        value,
+        description,
+        updated_at: chrono::Utc::now(),
+    };
+    let pm = state.project_manager.read().await;
+    pm.get_workspace(uuid)?;
+    pm.set_env(uuid, &var)?;
+    Ok(var)
+}
+
+/// Remove a project environment variable.
+#[tauri::command]
+pub async fn delete_project_env(
+    id: String,
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<(), AppError> {
+    let uuid: uuid::Uuid = id.parse()?;
+    state.authorize(Some(uuid), Permission::ManageWorkspace).await?;
+    let pm = state.project_manager.read().await;
+    if !pm.delete_env(uuid, &name)? {
+        return Err(AppError::not_found(format!("Environment variable not found: {name}")));
+    }
+    Ok(())
+}
+
+/// Members of a shared project and their roles; empty when not shared.
+#[tauri::command]
+pub async fn list_project_members(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<WorkspaceMember>, AppError> {
+    let uuid: uuid::Uuid = id.parse()?;
+    state.authorize(Some(uuid), Permission::View).await?;
+    let pm = state.project_manager.read().await;
+    Ok(pm.get_workspace(uuid)?.members)
+}
+
+/// Add a member or change their role. Adding the first member shares the
+/// project, with the current team sync user as its admin.
+#[tauri::command]
+pub async fn set_project_member(
+    id: String,
+    user: String,
+    role: Role,
+    state: State<'_, AppState>,
+) -> Result<Vec<WorkspaceMember>, AppError> {
+    let uuid: uuid::Uuid = id.parse()?;
+    let user = user.trim().to_string();
+    if user.is_empty() {
+        return Err(AppError::invalid_input("A user name is required"));
+    }
+    let access = state.authorize(Some(uuid), Permission::ManageWorkspace).await?;
+    update_members(&state, uuid, &access, |ws| {
+        if !ws.is_shared() {
+            let admin = access.actor.clone().ok_or_else(|| {
+                AppError::invalid_input("Turn on team sync with a user name to share a project")
+            })?;
+            ws.members.push(WorkspaceMember { user: admin, role: Role::Admin });
+        }
+        match ws.members.iter_mut().find(|m| m.user == user) {
+            Some(member) => member.role = role,
+            None => ws.members.push(WorkspaceMember { user: user.clone(), role }),
+        }
+        Ok(format!("Set {user} as {}", role.as_str()))
+    })
+    .await
+}
+
+/// Remove a member. Removing the last member stops sharing the project.
+#[tauri::command]
+pub async fn remove_project_member(
+    id: String,
+    user: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<WorkspaceMember>, AppError> {
+    let uuid: uuid::Uuid = id.parse()?;
+    let access = state.authorize(Some(uuid), Permission::ManageWorkspace).await?;
+    update_members(&state, uuid, &access, |ws| {
+        let before = ws.members.len();
+        ws.members.retain(|m| m.user != user);
+        if ws.members.len() == before {
+            return Err(AppError::not_found(format!("Not a member: {user}")));
+        }
+        Ok(format!("Removed {user}"))
+    })
+    .await
+}
+
+/// Apply a membership change, keeping at least one admin in a shared
+/// project, and record it in the activity log.
+async fn update_members(
+    state: &AppState,
+    id: uuid::Uuid,
+    access: &crate::state::Access,
+    change: impl FnOnce(&mut hb_core::project::WorkspaceConfig) -> Result<String, AppError>,
+) -> Result<Vec<WorkspaceMember>, AppError> {
+    let (summary, members) = {
+        let pm = state.project_manager.read().await;
+        let mut ws = pm.get_workspace(id)?;
+        let summary = change(&mut ws)?;
+        if ws.is_shared() && !ws.members.iter().any(|m| m.role == Role::Admin) {
+            return Err(AppError::invalid_input("A shared project needs at least one admin"));
+        }
+        ws.updated_at = chrono::Utc::now();
+        pm.update_workspace(id, &ws)?;
+        (summary, ws.members)
+    };
+    state
+        .record_activity(access.stamp(
+            ActivityEntry::new(ActivityKind::MembersChanged, id.to_string(), summary)
+                .in_workspace(Some(id))
+                .with_details(json!({ "members": members })),
+        ))
+        .await;
+    Ok(members)
+}
@@ -0,0 +1,270 @@
+//! Schedule commands — manage workflow schedules and query the run calendar.
+//! [`fire_due`] starts the runs as they come due.
+
+use hb_core::error::AppError;
+use crate::notifications::{Notification, NotificationKind, NotificationService};
+use crate::state::AppState;
+use chrono::{DateTime, Duration, Utc};
+use hb_runner::cron::{self, CronExpr};
+use hb_runner::schedule::{self, RunEstimate, Schedule, ScheduleCalendar, ScheduleTrigger};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, State};
+
+/// How often schedules are checked for runs that came due.
+pub const FIRING_CHECK: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Past executions considered when estimating a workflow's run time.
+const HISTORY_SAMPLE: usize = 20;
+
+/// Default number of occurrences returned per schedule.
+const DEFAULT_PER_SCHEDULE: usize = 10;
+
+/// Default number of upcoming runs shown in a cron preview.
+const DEFAULT_PREVIEW_RUNS: usize = 5;
+
+/// Most upcoming runs a cron preview computes.
+const MAX_PREVIEW_RUNS: usize = 100;
+
+fn schedules_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("schedules.json")
+}
+
+fn load_schedules(data_dir: &Path) -> Vec<Schedule> {
+    std::fs::read_to_string(schedules_path(data_dir))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_schedules(data_dir: &Path, schedules: &[Schedule]) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(schedules).map_err(|e| e.to_string())?;
+    std::fs::write(schedules_path(data_dir), content).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_schedules(state: State<'_, AppState>) -> Result<Vec<Schedule>, AppError> {
+    Ok(load_schedules(&state.data_dir))
+}
+
+/// Create or replace a schedule (matched by ID). Cron schedules without a
+/// timezone take the workspace's, so they fire as previewed. Enabling a
+/// schedule in a workspace with a review policy requires the workflow to
+/// be approved.
+#[tauri::command]
+pub async fn save_schedule(
+    schedule: Schedule,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Schedule, AppError> {
+    schedule
+        .trigger
+        .validate()
+        .map_err(|e| format!("Invalid schedule: {e}"))?;
+    let mut schedule = schedule;
+    let workspace_id = crate::commands::workflow::parse_workspace_id(workspace_id)?;
+    schedule.workspace_id = schedule.workspace_id.or(workspace_id);
+    if let (ScheduleTrigger::Cron { timezone: timezone @ None, .. }, Some(ws_id)) =
+        (&mut schedule.trigger, schedule.workspace_id)
+    {
+        *timezone = state
+            .project_manager
+            .read()
+            .await
+            .get_workspace(ws_id)
+            .ok()
+            .and_then(|ws| ws.timezone);
+    }
+    if schedule.enabled {
+        let workflow_id = schedule.workflow_id.to_string();
+        crate::commands::workflow::require_approval(&state, schedule.workspace_id, &workflow_id).await?;
+    }
+    let mut schedules = load_schedules(&state.data_dir);
+    match schedules.iter_mut().find(|s| s.id == schedule.id) {
+        Some(existing) => *existing = schedule.clone(),
+        None => schedules.push(schedule.clone()),
+    }
+    save_schedules(&state.data_dir, &schedules)?;
+    Ok(schedule)
+}
+
+#[tauri::command]
+pub async fn delete_schedule(
+    schedule_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), AppError> {
+    let id: uuid::Uuid = schedule_id
+        .parse()
+        .map_err(|e: uuid::Error| e.to_string())?;
+    let mut schedules = load_schedules(&state.data_dir);
+    let before = schedules.len();
+    schedules.retain(|s| s.id != id);
+    if schedules.len() == before {
+        return Err(format!("Schedule not found: {schedule_id}").into());
+    }
+    save_schedules(&state.data_dir, &schedules).map_err(AppError::from)
+}
+
+/// Upcoming runs between `from` and `until` (default: now → 7 days), with
+/// overlapping runs and per-workflow estimates of duration and cost from
+/// execution history.
+#[tauri::command]
+pub async fn get_schedule_calendar(
+    from: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    per_schedule: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<ScheduleCalendar, AppError> {
+    let from = from.unwrap_or_else(Utc::now);
+    let until = until.unwrap_or(from + Duration::days(7));
+    if until <= from {
+        return Err("'until' must be after 'from'".into());
+    }
+    let schedules = load_schedules(&state.data_dir);
+
+    let mut estimates = HashMap::new();
+    let guard = state.trace_store.read().await;
+    if let Some(store) = guard.as_ref() {
+        for s in &schedules {
+            if estimates.contains_key(&s.workflow_id) {
+                continue;
+            }
+            let history = store
+                .query_executions_by_workflow(s.workflow_id, HISTORY_SAMPLE)
+                .map_err(|e| e.to_string())?;
+            estimates.insert(s.workflow_id, RunEstimate::from_history(&history));
+        }
+    }
+    drop(guard);
+
+    // Scheduled runs take the workflow's variable defaults
+    let catalog = super::trace::cost_catalog(&state).await;
+    let workflows = state.workflows.read().await;
+    for (workflow_id, estimate) in &mut estimates {
+        if let Some(spec) = workflows.get(&workflow_id.to_string()) {
+            let variables = hb_core::graph::params::defaults(&spec.variables);
+            let cost = hb_policy::estimate::estimate_cost(spec, &variables, &catalog);
+            estimate.cost_usd = Some(cost.cost_usd);
+        }
+    }
+
+    Ok(schedule::build_calendar(
+        &schedules,
+        estimates,
+        from,
+        until,
+        per_schedule.unwrap_or(DEFAULT_PER_SCHEDULE),
+    ))
+}
+
+#[derive(serde::Serialize)]
+pub struct CronPreview {
+    pub valid: bool,
+    /// Human-readable description (empty when invalid).
+    pub description: String,
+    /// Timezone the runs were computed in.
+    pub timezone: String,
+    pub next_runs: Vec<DateTime<Utc>>,
+    pub error: Option<String>,
+    /// Cron field the error is in, for highlighting.
+    pub error_field: Option<String>,
+}
+
+/// Inline feedback for a cron expression being typed: validity, description
+/// and the next runs (at most [`MAX_PREVIEW_RUNS`]) in the given timezone,
+/// else the workspace's, else the local one.
+#[tauri::command]
+pub async fn preview_cron(
+    expression: String,
+    timezone: Option<String>,
+    workspace_id: Option<String>,
+    count: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<CronPreview, AppError> {
+    let timezone = match timezone {
+        Some(tz) => Some(tz),
+        None => match workspace_id.as_deref().and_then(|id| id.parse().ok()) {
+            Some(ws_id) => state
+                .project_manager
+                .read()
+                .await
+                .get_workspace(ws_id)
+                .ok()
+                .and_then(|ws| ws.timezone),
+            None => None,
+        },
+    };
+    let timezone_name = timezone
+        .clone()
+        .unwrap_or_else(|| cron::local_timezone().name().to_string());
+
+    let parsed = cron::resolve_timezone(timezone.as_deref())
+        .and_then(|tz| CronExpr::parse(&expression).map(|expr| (expr, tz)));
+    Ok(match parsed {
+        Ok((expr, tz)) => CronPreview {
+            valid: true,
+            description: expr.describe(),
+            timezone: timezone_name,
+            next_runs: expr.upcoming(
+                Utc::now(),
+                tz,
+                count.unwrap_or(DEFAULT_PREVIEW_RUNS).min(MAX_PREVIEW_RUNS),
+            ),
+            error: None,
+            error_field: None,
+        },
+        Err(e) => CronPreview {
+            valid: false,
+            description: String::new(),
+            timezone: timezone_name,
+            next_runs: vec![],
+            error_field: e.field().map(str::to_string),
+            error: Some(e.to_string()),
+        },
+    })
+}
+
+/// Start the runs of schedules that fired in `(after, until]`, each in the
+/// schedule's workspace. Where publishing needs an approved revision, that
+/// revision is what runs. Runs that can't start or fail send a
+/// [`NotificationKind::ScheduleFailed`] notification.
+pub async fn fire_due(app: &AppHandle, after: DateTime<Utc>, until: DateTime<Utc>) {
+    let state = app.state::<AppState>();
+    for schedule in load_schedules(&state.data_dir) {
+        if !schedule.due(after, until) {
+            continue;
+        }
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = fire(&app, &schedule).await {
+                tracing::warn!("Scheduled run of '{}' failed: {}", schedule.name, e.message);
+                let notification = Notification::new(
+                    NotificationKind::ScheduleFailed,
+                    format!("Schedule '{}' failed", schedule.name),
+                    e.message,
+                )
+                .with_workspace(schedule.workspace_id.map(|id| id.to_string()));
+                let notifier = app.state::<Arc<NotificationService>>();
+                notifier.notify(&app, notification).await;
+            }
+        });
+    }
+}
+
+async fn fire(app: &AppHandle, schedule: &Schedule) -> Result<serde_json::Value, AppError> {
+    let state = app.state::<AppState>();
+    let workflow_id = schedule.workflow_id.to_string();
+    let workspace_id = schedule.workspace_id.map(|id| id.to_string());
+    let execution_id = uuid::Uuid::new_v4();
+    tracing::info!("Schedule '{}' starting execution {execution_id}", schedule.name);
+    match crate::commands::workflow::require_approval(&state, schedule.workspace_id, &workflow_id).await? {
+        Some(approved) => {
+            crate::commands::execution::run_spec(app, approved, workspace_id.as_deref(), execution_id, None).await
+        }
+        None => {
+            crate::commands::execution::run_workflow(app, &workflow_id, workspace_id.as_deref(), execution_id, None)
+                .await
+        }
+    }
+}
@@ -2,6 +2,7 @@
 //! Provides: bash execute, file operations, code search, glob search,
 //! project tree, web search, web fetch, git operations.
 
+use hb_core::error::AppError;
 use serde_json::{json, Value};
 use std::path::{Path, PathBuf};
 
@@ -144,7 +145,7 @@ pub async fn tool_bash_execute(
     command: String,
     working_dir: Option<String>,
     timeout_ms: Option<u64>,
-) -> Result<Value, String> {
+) -> Result<Value, AppError> {
     let timeout = std::time::Duration::from_millis(timeout_ms.unwrap_or(30_000).min(120_000));
     let cwd = working_dir
         .map(PathBuf::from)
@@ -192,9 +193,9 @@ pub async fn tool_bash_execute(
                 }
             }))
         }
-        Ok(Err(e)) => Err(format!("Process error: {e}")),
+        Ok(Err(e)) => Err(format!("Process error: {e}").into()),
         Err(_) => {
-            Err(format!("Command timed out after {}ms", timeout.as_millis()))
+            Err(format!("Command timed out after {}ms", timeout.as_millis()).into())
         }
     }
 }
@@ -208,15 +209,17 @@ pub async fn tool_file_read(
     path: String,
     offset: Option<usize>,
     limit: Option<usize>,
-) -> Result<Value, String> {
+) -> Result<Value, AppError> {
     let path = PathBuf::from(&path);
     if !path.exists() {
-        return Err(format!("File not found: {}", path.display()));
+        return Err(format!("File not found: {}", path.display()).into());
     }
 
-    let content = tokio::fs::read_to_string(&path)
+    let bytes = tokio::fs::read(&path)
         .await
         .map_err(|e| format!("Failed to read file: {e}"))?;
+    let decoded = hb_tool_executor::encoding::decode(&bytes, None).map_err(|e| e.to_string())?;
+    let content = decoded.text;
 
     let lines: Vec<&str> = content.lines().collect();
     let total_lines = lines.len();
@@ -234,6 +237,8 @@ pub async fn tool_file_read(
         "total_lines": total_lines,
         "offset": start,
         "lines_shown": count,
+        "encoding": decoded.encoding,
+        "had_errors": decoded.had_errors,
         "text": selected.join("\n")
     }))
 }
@@ -247,7 +252,7 @@ pub async fn tool_file_write(
     path: String,
     content: String,
     create_dirs: Option<bool>,
-) -> Result<Value, String> {
+) -> Result<Value, AppError> {
     let path = PathBuf::from(&path);
 
     if create_dirs.unwrap_or(true) {
@@ -283,15 +288,15 @@ pub async fn tool_file_edit(
     old_string: String,
     new_string: String,
     replace_all: Option<bool>,
-) -> Result<Value, String> {
+) -> Result<Value, AppError> {
     // Guard: empty old_string would match everywhere and corrupt the file
     if old_string.is_empty() {
-        return Err("old_string cannot be empty — it would match every position in the file".to_string());
+        return Err("old_string cannot be empty — it would match every position in the file".into());
     }
 
     let path = PathBuf::from(&path);
     if !path.exists() {
-        return Err(format!("File not found: {}", path.display()));
+        return Err(format!("File not found: {}", path.display()).into());
     }
 
     let content = tokio::fs::read_to_string(&path)
@@ -303,7 +308,7 @@ pub async fn tool_file_edit(
         return Err(format!(
             "old_string not found in {}. Make sure it matches exactly.",
             path.display()
-        ));
+        ).into());
     }
 
     if !replace_all.unwrap_or(false) && occurrences > 1 {
@@ -311,7 +316,7 @@ pub async fn tool_file_edit(
             "old_string found {} times in {}. Use replace_all=true or provide more context.",
             occurrences,
             path.display()
-        ));
+        ).into());
     }
 
     let new_content = if replace_all.unwrap_or(false) {
@@ -342,10 +347,10 @@ pub async fn tool_file_edit_lines(
     line_start: usize,
     line_end: Option<usize>,
     new_text: String,
-) -> Result<Value, String> {
+) -> Result<Value, AppError> {
     let path = PathBuf::from(&path);
     if !path.exists() {
-        return Err(format!("File not found: {}", path.display()));
+        return Err(format!("File not found: {}", path.display()).into());
     }
 
     let content = tokio::fs::read_to_string(&path)
@@ -366,7 +371,7 @@ pub async fn tool_file_edit_lines(
             "line_start {} out of range (1..{})",
             line_start,
             total + 1
-        ));
+        ).into());
     }
 
     let end = line_end.unwrap_or(line_start).min(total);
@@ -423,7 +428,7 @@ pub async fn tool_grep_search(
     glob_filter: Option<String>,
     max_results: Option<usize>,
     context_lines: Option<usize>,
-) -> Result<Value, String> {
+) -> Result<Value, AppError> {
     let search_path = path.unwrap_or_else(|| ".".to_string());
     let max = max_results.unwrap_or(50).min(200);
     let ctx = context_lines.unwrap_or(0);
@@ -717,7 +722,7 @@ pub async fn tool_glob_search(
     pattern: String,
     path: Option<String>,
     max_results: Option<usize>,
-) -> Result<Value, String> {
+) -> Result<Value, AppError> {
     let search_dir = PathBuf::from(path.as_deref().unwrap_or("."));
     let max = max_results.unwrap_or(100).min(500);
 
@@ -795,7 +800,7 @@ pub async fn tool_project_tree(
     path: Option<String>,
     max_depth: Option<usize>,
     max_entries: Option<usize>,
-) -> Result<Value, String> {
+) -> Result<Value, AppError> {
     let root = PathBuf::from(path.as_deref().unwrap_or("."));
     let depth = max_depth.unwrap_or(4).min(8);
     let max = max_entries.unwrap_or(500).min(2000);
@@ -924,7 +929,7 @@ pub async fn tool_web_fetch(
     url: String,
     timeout_ms: Option<u64>,
     max_chars: Option<usize>,
-) -> Result<Value, String> {
+) -> Result<Value, AppError> {
     let timeout = std::time::Duration::from_millis(timeout_ms.unwrap_or(15_000).min(60_000));
     let max = max_chars.unwrap_or(50_000);
     let start = std::time::Instant::now();
@@ -972,7 +977,7 @@ pub async fn tool_http_request(
     params: Option<serde_json::Value>,
     timeout_ms: Option<u64>,
     max_chars: Option<usize>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, AppError> {
     let method_str = method.unwrap_or_else(|| "GET".to_string()).to_uppercase();
     let timeout = std::time::Duration::from_millis(timeout_ms.unwrap_or(30_000).min(120_000));
     let max = max_chars.unwrap_or(100_000);
@@ -1190,7 +1195,7 @@ fn extract_title(html: &str) -> String {
 pub async fn tool_web_search(
     query: String,
     max_results: Option<usize>,
-) -> Result<Value, String> {
+) -> Result<Value, AppError> {
     let max = max_results.unwrap_or(8).min(20);
     let encoded = urlencoding::encode(&query);
 
@@ -1451,9 +1456,9 @@ fn decode_ddg_url(url: &str) -> String {
 #[tauri::command]
 pub async fn tool_git_status(
     path: Option<String>,
-) -> Result<Value, String> {
+) -> Result<Value, AppError> {
     let cwd = path.unwrap_or_else(|| ".".to_string());
-    run_git_command(&["status", "--porcelain", "-b"], &cwd).await
+    run_git_command(&["status", "--porcelain", "-b"], &cwd).await.map_err(AppError::from)
 }
 
 // ============================================================
@@ -1465,7 +1470,7 @@ pub async fn tool_git_diff(
     path: Option<String>,
     staged: Option<bool>,
     file_path: Option<String>,
-) -> Result<Value, String> {
+) -> Result<Value, AppError> {
     let cwd = path.unwrap_or_else(|| ".".to_string());
     let mut args = vec!["diff"];
     if staged.unwrap_or(false) {
@@ -1475,7 +1480,7 @@ pub async fn tool_git_diff(
         args.push("--");
         args.push(fp);
     }
-    run_git_command(&args, &cwd).await
+    run_git_command(&args, &cwd).await.map_err(AppError::from)
 }
 
 // ============================================================
@@ -1487,14 +1492,14 @@ pub async fn tool_git_log(
     path: Option<String>,
     max_count: Option<usize>,
     oneline: Option<bool>,
-) -> Result<Value, String> {
+) -> Result<Value, AppError> {
     let cwd = path.unwrap_or_else(|| ".".to_string());
     let count_str = format!("-{}", max_count.unwrap_or(10).min(50));
     let mut args = vec!["log", &count_str];
     if oneline.unwrap_or(true) {
         args.push("--oneline");
     }
-    run_git_command(&args, &cwd).await
+    run_git_command(&args, &cwd).await.map_err(AppError::from)
 }
 
 // ============================================================
@@ -1506,7 +1511,7 @@ pub async fn tool_git_commit(
     message: String,
     path: Option<String>,
     files: Option<Vec<String>>,
-) -> Result<Value, String> {
+) -> Result<Value, AppError> {
     let cwd = path.unwrap_or_else(|| ".".to_string());
 
     // Stage files
@@ -1520,7 +1525,7 @@ pub async fn tool_git_commit(
     }
 
     // Commit
-    run_git_command(&["commit", "-m", &message], &cwd).await
+    run_git_command(&["commit", "-m", &message], &cwd).await.map_err(AppError::from)
 }
 
 async fn run_git_command(args: &[&str], cwd: &str) -> Result<Value, String> {
@@ -1551,7 +1556,7 @@ async fn run_git_command(args: &[&str], cwd: &str) -> Result<Value, String> {
 #[tauri::command]
 pub async fn tool_memory_read(
     key: Option<String>,
-) -> Result<Value, String> {
+) -> Result<Value, AppError> {
     let memory_dir = get_memory_dir()?;
 
     if let Some(k) = key {
@@ -1597,7 +1602,7 @@ pub async fn tool_memory_read(
 pub async fn tool_memory_write(
     key: String,
     value: Value,
-) -> Result<Value, String> {
+) -> Result<Value, AppError> {
     let memory_dir = get_memory_dir()?;
     tokio::fs::create_dir_all(&memory_dir)
         .await
@@ -1617,18 +1622,7 @@ pub async fn tool_memory_write(
 }
 
 fn get_memory_dir() -> Result<PathBuf, String> {
-    #[cfg(target_os = "windows")]
-    {
-        std::env::var("APPDATA")
-            .map(|p| PathBuf::from(p).join("Handbox").join("agent_memory"))
-            .map_err(|_| "APPDATA not found".to_string())
-    }
-    #[cfg(not(target_os = "windows"))]
-    {
-        dirs::data_dir()
-            .map(|p| p.join("handbox").join("agent_memory"))
-            .ok_or_else(|| "Data dir not found".to_string())
-    }
+    Ok(crate::config::data_dir().join("agent_memory"))
 }
 
 // ============================================================
@@ -2465,7 +2459,7 @@ mod tests {
     async fn test_file_read_not_found() {
         let result = tool_file_read("/nonexistent/path/file.txt".to_string(), None, None).await;
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("not found"));
+        assert!(result.unwrap_err().message.contains("not found"));
     }
 
     #[tokio::test]
@@ -2503,7 +2497,7 @@ mod tests {
             None,
         ).await;
         assert!(edit_nf.is_err());
-        assert!(edit_nf.unwrap_err().contains("not found"));
+        assert!(edit_nf.unwrap_err().message.contains("not found"));
 
         // Multiple occurrences without replace_all should error
         tool_file_write(
@@ -2518,7 +2512,7 @@ mod tests {
             Some(false),
         ).await;
         assert!(edit_multi.is_err());
-        assert!(edit_multi.unwrap_err().contains("2 times"));
+        assert!(edit_multi.unwrap_err().message.contains("2 times"));
 
         // replace_all should work
         let edit_all = tool_file_edit(
@@ -2554,7 +2548,7 @@ mod tests {
             None,
         ).await;
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("cannot be empty"));
+        assert!(result.unwrap_err().message.contains("cannot be empty"));
 
         // File should be unchanged
         let content = tokio::fs::read_to_string(&path).await.unwrap();
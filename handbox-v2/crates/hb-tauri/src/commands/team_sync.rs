@@ -0,0 +1,88 @@
+//! Team sync commands — share the local library with a self-hosted team server.
+
+use crate::state::AppState;
+use crate::team_sync::{self, SyncConfig, SyncKind, SyncReport, SyncSettings, SyncStatus};
+use hb_core::error::{AppError, ErrorCode};
+use tauri::State;
+
+#[tauri::command]
+pub async fn get_team_sync_config(state: State<'_, AppState>) -> Result<SyncSettings, AppError> {
+    let credentials = state.llm_credentials.read().await;
+    Ok(SyncSettings::new(&SyncConfig::load(&state.data_dir), &credentials))
+}
+
+/// Update sync settings. `token: None` keeps the stored token; an empty
+/// string clears it. The token goes to the credential store. Enabling sync
+/// asks the server whom the token belongs to; that user is the name
+/// workspace roles are checked against.
+#[tauri::command]
+pub async fn set_team_sync_config(
+    server_url: String,
+    token: Option<String>,
+    enabled: bool,
+    interval_minutes: u64,
+    state: State<'_, AppState>,
+) -> Result<SyncSettings, AppError> {
+    let mut config = SyncConfig::load(&state.data_dir);
+    if enabled && server_url.trim().is_empty() {
+        return Err(AppError::invalid_input(
+            "A server URL is required to enable team sync",
+        ));
+    }
+    config.server_url = server_url.trim().to_string();
+    config.enabled = enabled;
+    config.interval_minutes = interval_minutes;
+    let token = match token {
+        Some(token) => Some(token).filter(|t| !t.is_empty()),
+        None => state.llm_credentials.read().await.team_sync_token.clone(),
+    };
+    config.user = if enabled {
+        Some(
+            team_sync::verify_identity(&config, token.as_deref())
+                .await
+                .map_err(|e| AppError::new(ErrorCode::PermissionDenied, e))?,
+        )
+    } else {
+        None
+    };
+    let mut credentials = state.llm_credentials.write().await;
+    if credentials.team_sync_token != token {
+        credentials.team_sync_token = token;
+        credentials.save(&state.credentials_path())?;
+    }
+    config.save(&state.data_dir)?;
+    Ok(SyncSettings::new(&config, &credentials))
+}
+
+/// Pending local changes and known conflicts, without contacting the server.
+#[tauri::command]
+pub async fn get_team_sync_status(state: State<'_, AppState>) -> Result<SyncStatus, AppError> {
+    Ok(team_sync::status(&state).await)
+}
+
+#[tauri::command]
+pub async fn run_team_sync(state: State<'_, AppState>) -> Result<SyncReport, AppError> {
+    team_sync::sync(&state).await.map_err(AppError::from)
+}
+
+/// Settle a conflict; `keep` is "local" or "remote".
+#[tauri::command]
+pub async fn resolve_team_sync_conflict(
+    kind: SyncKind,
+    id: String,
+    keep: String,
+    state: State<'_, AppState>,
+) -> Result<(), AppError> {
+    let keep_local = match keep.as_str() {
+        "local" => true,
+        "remote" => false,
+        other => {
+            return Err(AppError::invalid_input(format!(
+                "Unknown side '{other}', expected 'local' or 'remote'"
+            )))
+        }
+    };
+    team_sync::resolve_conflict(&state, kind, &id, keep_local)
+        .await
+        .map_err(AppError::from)
+}
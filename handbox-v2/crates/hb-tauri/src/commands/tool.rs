@@ -1,12 +1,16 @@
 //! Tool registry commands — backed by AppState.
 
+use hb_core::error::AppError;
 use crate::state::AppState;
+use hb_core::tool::{ConfigOption, OptionsSource};
+use hb_mcp::config_form::{self, ConfigForm, ProbedOptions};
+use hb_mcp::palette::{self, Palette};
 use tauri::State;
 
 #[tauri::command]
 pub async fn list_tools(
     state: State<'_, AppState>,
-) -> Result<Vec<serde_json::Value>, String> {
+) -> Result<Vec<serde_json::Value>, AppError> {
     let registry = state.tool_registry.read().await;
     let tools: Vec<serde_json::Value> = registry
         .list()
@@ -20,19 +24,19 @@ pub async fn list_tools(
 pub async fn get_tool(
     tool_id: String,
     state: State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, AppError> {
     let registry = state.tool_registry.read().await;
     let tool = registry
         .get(&tool_id)
         .ok_or_else(|| format!("Tool not found: {tool_id}"))?;
-    serde_json::to_value(tool).map_err(|e| e.to_string())
+    serde_json::to_value(tool).map_err(AppError::from)
 }
 
 #[tauri::command]
 pub async fn search_tools(
     query: String,
     state: State<'_, AppState>,
-) -> Result<Vec<serde_json::Value>, String> {
+) -> Result<Vec<serde_json::Value>, AppError> {
     let registry = state.tool_registry.read().await;
     let tag = hb_core::tool::CapabilityTag::new(&query);
     let tools: Vec<serde_json::Value> = registry
@@ -47,12 +51,104 @@ pub async fn search_tools(
 pub async fn load_packs(
     packs_dir: String,
     state: State<'_, AppState>,
-) -> Result<usize, String> {
+) -> Result<usize, AppError> {
     let tools = hb_mcp::registry::scan_packs(&packs_dir).map_err(|e| e.to_string())?;
     let count = tools.len();
     let mut registry = state.tool_registry.write().await;
     for tool in tools {
         registry.register(tool);
     }
+
+    let bundles = palette::scan_locale_bundles(&packs_dir).map_err(|e| e.to_string())?;
+    let mut catalog = state.palette_catalog.write().await;
+    for (locale, bundle) in bundles {
+        catalog.add_bundle(&locale, bundle);
+    }
     Ok(count)
 }
+
+/// Registered tools grouped into palette categories, with display names,
+/// descriptions, and port/config labels localized for `locale` (default "en").
+#[tauri::command]
+pub async fn get_palette(
+    locale: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Palette, AppError> {
+    let registry = state.tool_registry.read().await;
+    let catalog = state.palette_catalog.read().await;
+    let locale = locale.unwrap_or_else(|| palette::FALLBACK_LOCALE.to_string());
+    Ok(palette::build_palette(&registry, &catalog, &locale))
+}
+
+/// The property panel form for a node of `tool_ref`: its config fields,
+/// localized, with options from live sources (the active provider's models,
+/// installed local models, stored service key names), visibility and
+/// validation evaluated against the node's `config`.
+#[tauri::command]
+pub async fn get_node_config_form(
+    tool_ref: String,
+    config: Option<serde_json::Map<String, serde_json::Value>>,
+    locale: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ConfigForm, AppError> {
+    let tool_id = tool_ref.split('@').next().unwrap_or(&tool_ref);
+    let tool = state
+        .tool_registry
+        .read()
+        .await
+        .get(tool_id)
+        .cloned()
+        .ok_or_else(|| format!("Tool not found: {tool_id}"))?;
+
+    let mut probed = ProbedOptions::new();
+    for source in config_form::option_sources(&tool) {
+        probed.insert(source, probe_options(source, &state).await);
+    }
+
+    let catalog = state.palette_catalog.read().await;
+    let locale = locale.unwrap_or_else(|| palette::FALLBACK_LOCALE.to_string());
+    Ok(config_form::build_form(
+        &tool,
+        &catalog,
+        &locale,
+        &config.unwrap_or_default(),
+        &probed,
+    ))
+}
+
+/// Options from a live source; a source that can't be reached has none, so
+/// the form falls back to the field's static options.
+async fn probe_options(source: OptionsSource, state: &AppState) -> Vec<ConfigOption> {
+    let option = |label: String, value: String| ConfigOption {
+        label,
+        value: value.into(),
+    };
+    match source {
+        OptionsSource::LlmModels => {
+            let provider = state.llm_credentials.read().await.active_provider.clone();
+            let Some(provider) = provider else {
+                return vec![];
+            };
+            super::llm::list_llm_models(provider)
+                .await
+                .unwrap_or_default()
+                .into_iter()
+                .map(|m| option(m.name, m.id))
+                .collect()
+        }
+        OptionsSource::LocalModels => crate::local_models::list_models()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|m| option(m.id.clone(), m.id))
+            .collect(),
+        OptionsSource::ServiceKeys => state
+            .llm_credentials
+            .read()
+            .await
+            .service_keys
+            .keys()
+            .map(|k| option(k.clone(), k.clone()))
+            .collect(),
+    }
+}
@@ -1,13 +1,31 @@
 //! Trace query commands — backed by AppState.
 
+use hb_core::error::AppError;
+use crate::debug_bundle::{self, BundleManifest, Redactor};
+use crate::doctor;
+use crate::run_viewer::{self, ViewerBundle};
 use crate::state::AppState;
+use crate::usage::UsageConfig;
+use hb_compiler::optimize::{self, OptimizationReport};
+use hb_core::project::{ActivityEntry, Permission};
+use hb_core::trace::{ArtifactKind, EdgeValue, ExecutionManifest, ExecutionRecord, SchedulerMetrics};
+use hb_policy::estimate::{self, CostCatalog, CostEstimate, TokenPrice};
+use hb_runner::CacheStats;
+use hb_trace::query::ExecutionDiff;
+use serde_json::json;
 use tauri::State;
 
+/// Recent activity entries searched for an execution's own entries.
+const BUNDLE_ACTIVITY_SCAN: usize = 1_000;
+
+/// Days of recorded runs cost estimates average over.
+const COST_HISTORY_DAYS: i64 = 30;
+
 #[tauri::command]
 pub async fn get_traces(
     execution_id: String,
     state: State<'_, AppState>,
-) -> Result<Vec<serde_json::Value>, String> {
+) -> Result<Vec<serde_json::Value>, AppError> {
     let guard = state.trace_store.read().await;
     let store = guard.as_ref().ok_or("Trace store not initialized")?;
     let ex_id: uuid::Uuid = execution_id
@@ -27,7 +45,7 @@ pub async fn get_traces(
 pub async fn get_span(
     span_id: String,
     state: State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, AppError> {
     let guard = state.trace_store.read().await;
     let store = guard.as_ref().ok_or("Trace store not initialized")?;
     let sp_id: uuid::Uuid = span_id
@@ -37,22 +55,501 @@ pub async fn get_span(
         .query_span(sp_id)
         .map_err(|e| e.to_string())?
         .ok_or("Span not found")?;
-    serde_json::to_value(&span).map_err(|e| e.to_string())
+    serde_json::to_value(&span).map_err(AppError::from)
+}
+
+/// Value that crossed an edge during a run (requires edge capture to have
+/// been enabled by the workspace policy).
+#[tauri::command]
+pub async fn get_edge_value(
+    execution_id: String,
+    edge_id: String,
+    state: State<'_, AppState>,
+) -> Result<Option<EdgeValue>, AppError> {
+    let guard = state.trace_store.read().await;
+    let store = guard.as_ref().ok_or("Trace store not initialized")?;
+    let ex_id: uuid::Uuid = execution_id
+        .parse()
+        .map_err(|e: uuid::Error| e.to_string())?;
+    store
+        .query_edge_value(ex_id, &edge_id)
+        .map_err(AppError::from)
+}
+
+/// Captured values that entered a node during a run.
+#[tauri::command]
+pub async fn get_node_inputs(
+    execution_id: String,
+    node_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<EdgeValue>, AppError> {
+    let guard = state.trace_store.read().await;
+    let store = guard.as_ref().ok_or("Trace store not initialized")?;
+    let ex_id: uuid::Uuid = execution_id
+        .parse()
+        .map_err(|e: uuid::Error| e.to_string())?;
+    store
+        .query_node_inputs(ex_id, &node_id)
+        .map_err(AppError::from)
+}
+
+/// Files written, indexes updated and external endpoints called by a run.
+#[tauri::command]
+pub async fn get_execution_manifest(
+    execution_id: String,
+    state: State<'_, AppState>,
+) -> Result<ExecutionManifest, AppError> {
+    let guard = state.trace_store.read().await;
+    let store = guard.as_ref().ok_or("Trace store not initialized")?;
+    let ex_id: uuid::Uuid = execution_id
+        .parse()
+        .map_err(|e: uuid::Error| e.to_string())?;
+    store.query_manifest(ex_id).map_err(AppError::from)
+}
+
+/// How the scheduler spent a run: queue depth over time, per-node queue
+/// and run time, and parallelism used. None for runs recorded without them.
+#[tauri::command]
+pub async fn get_scheduler_metrics(
+    execution_id: String,
+    state: State<'_, AppState>,
+) -> Result<Option<SchedulerMetrics>, AppError> {
+    let guard = state.trace_store.read().await;
+    let store = guard.as_ref().ok_or("Trace store not initialized")?;
+    let ex_id: uuid::Uuid = execution_id
+        .parse()
+        .map_err(|e: uuid::Error| e.to_string())?;
+    store.query_scheduler_metrics(ex_id).map_err(AppError::from)
+}
+
+/// What differs node by node between two runs of the same workflow.
+#[tauri::command]
+pub async fn diff_executions(
+    execution_a: String,
+    execution_b: String,
+    state: State<'_, AppState>,
+) -> Result<ExecutionDiff, AppError> {
+    let guard = state.trace_store.read().await;
+    let store = guard.as_ref().ok_or("Trace store not initialized")?;
+    let parse = |id: &str| id.parse::<uuid::Uuid>().map_err(|e| e.to_string());
+    hb_trace::query::diff_executions(store.as_ref(), parse(&execution_a)?, parse(&execution_b)?)
+        .map_err(AppError::from)
+}
+
+/// Runs that touched a file path, index name or endpoint, newest first.
+#[tauri::command]
+pub async fn find_executions_by_artifact(
+    target: String,
+    kind: Option<ArtifactKind>,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<ExecutionRecord>, AppError> {
+    let guard = state.trace_store.read().await;
+    let store = guard.as_ref().ok_or("Trace store not initialized")?;
+    store
+        .query_executions_by_artifact(&target, kind, limit.unwrap_or(50))
+        .map_err(AppError::from)
+}
+
+/// Optimization hints for a workflow from the spans of its last
+/// `executions` runs (20 by default).
+#[tauri::command]
+pub async fn analyze_workflow_performance(
+    workflow_id: String,
+    executions: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<OptimizationReport, AppError> {
+    let spec = state
+        .workflows
+        .read()
+        .await
+        .get(&workflow_id)
+        .cloned()
+        .ok_or_else(|| AppError::not_found(format!("Workflow not found: {workflow_id}")))?;
+    let guard = state.trace_store.read().await;
+    let store = guard.as_ref().ok_or("Trace store not initialized")?;
+    let mut spans = Vec::new();
+    for record in store.query_executions_by_workflow(spec.id, executions.unwrap_or(20).max(1))? {
+        spans.extend(store.query_spans_by_execution(record.execution_id)?);
+    }
+    let config = UsageConfig::load(&state.data_dir);
+    Ok(optimize::analyze(&spec, &spans, &config.rate_limits))
+}
+
+/// Tool cost hints, recorded per-tool averages and configured model prices.
+pub(crate) async fn cost_catalog(state: &AppState) -> CostCatalog {
+    let hints = state
+        .tool_registry
+        .read()
+        .await
+        .list()
+        .into_iter()
+        .map(|tool| (tool.tool_id.clone(), tool.cost_hint.clone()))
+        .collect();
+    let since = chrono::Utc::now() - chrono::Duration::days(COST_HISTORY_DAYS);
+    let history = match state.trace_store.read().await.as_ref() {
+        Some(store) => store.query_tool_averages(since).unwrap_or_else(|e| {
+            tracing::warn!("Failed to load tool averages for cost estimates: {e}");
+            Vec::new()
+        }),
+        None => Vec::new(),
+    };
+    let prices = UsageConfig::load(&state.data_dir)
+        .prices
+        .into_iter()
+        .map(|p| TokenPrice {
+            model: p.model,
+            input_per_million_usd: p.input_per_million_usd,
+            output_per_million_usd: p.output_per_million_usd,
+        })
+        .collect();
+    CostCatalog {
+        hints,
+        history: history.into_iter().map(|h| (h.tool_id.clone(), h)).collect(),
+        prices,
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct WorkflowCostEstimate {
+    #[serde(flatten)]
+    pub estimate: CostEstimate,
+    /// Limits of the workspace policy the run may exceed.
+    pub budget_warnings: Vec<String>,
+    /// Set when the run certainly exceeds a limit and would be refused.
+    pub budget_error: Option<String>,
+}
+
+/// Cost and duration range of running a workflow with `variables`, checked
+/// against the cost limits of the workspace's policy.
+#[tauri::command]
+pub async fn estimate_workflow_cost(
+    workflow_id: String,
+    variables: Option<serde_json::Map<String, serde_json::Value>>,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<WorkflowCostEstimate, AppError> {
+    let spec = state
+        .workflows
+        .read()
+        .await
+        .get(&workflow_id)
+        .cloned()
+        .ok_or_else(|| AppError::not_found(format!("Workflow not found: {workflow_id}")))?;
+    let catalog = cost_catalog(&state).await;
+    let estimate = estimate::estimate_cost(&spec, &variables.unwrap_or_default(), &catalog);
+
+    let policy = match workspace_id.and_then(|id| id.parse().ok()) {
+        Some(ws_id) => state
+            .project_manager
+            .read()
+            .await
+            .get_workspace(ws_id)
+            .ok()
+            .and_then(|ws| ws.default_policy),
+        None => None,
+    };
+    let (budget_warnings, budget_error) = match policy {
+        Some(policy) => match hb_policy::budget::preflight(&policy.cost_limit, &estimate) {
+            Ok(warnings) => (warnings, None),
+            Err(e) => (Vec::new(), Some(e.to_string())),
+        },
+        None => (Vec::new(), None),
+    };
+    Ok(WorkflowCostEstimate {
+        estimate,
+        budget_warnings,
+        budget_error,
+    })
+}
+
+/// Hit rate, admissions and evictions of the node output cache since the
+/// app started.
+#[tauri::command]
+pub async fn get_cache_stats(state: State<'_, AppState>) -> Result<CacheStats, AppError> {
+    let guard = state.execution_cache.read().await;
+    let cache = guard.as_ref().ok_or("Execution cache not initialized")?;
+    Ok(cache.stats())
 }
 
+#[derive(serde::Serialize)]
+pub struct TraceExport {
+    pub path: String,
+    pub spans: u64,
+}
+
+/// Write an execution's spans to `output_path` as a JSON array, or as JSON
+/// Lines when `format` is "jsonl". JSON Lines are streamed from the store,
+/// so large runs export without being held in memory.
 #[tauri::command]
 pub async fn export_traces(
     execution_id: String,
-    _format: String,
+    format: String,
+    output_path: String,
     state: State<'_, AppState>,
-) -> Result<String, String> {
+) -> Result<TraceExport, AppError> {
     let guard = state.trace_store.read().await;
     let store = guard.as_ref().ok_or("Trace store not initialized")?;
     let ex_id: uuid::Uuid = execution_id
         .parse()
         .map_err(|e: uuid::Error| e.to_string())?;
-    let spans = store
-        .query_spans_by_execution(ex_id)
-        .map_err(|e| e.to_string())?;
-    hb_trace::export::export_json(&spans).map_err(|e| e.to_string())
+    let file = std::fs::File::create(&output_path)?;
+    let spans = if format.eq_ignore_ascii_case("jsonl") {
+        let query = hb_trace::query::TraceQuery {
+            execution_id: Some(ex_id),
+            ..Default::default()
+        };
+        hb_trace::export::export_spans_jsonl(store.as_ref(), &query, file)?
+    } else {
+        let spans = store
+            .query_spans_by_execution(ex_id)
+            .map_err(|e| e.to_string())?;
+        std::io::Write::write_all(
+            &mut std::io::BufWriter::new(file),
+            hb_trace::export::export_json(&spans)?.as_bytes(),
+        )?;
+        spans.len() as u64
+    };
+    Ok(TraceExport {
+        path: output_path,
+        spans,
+    })
+}
+
+#[derive(serde::Serialize)]
+pub struct DebugBundle {
+    pub path: String,
+    pub manifest: BundleManifest,
+}
+
+/// Gather everything needed to triage an execution — its record, workflow,
+/// spans, journal, activity and the app environment — into one redacted zip
+/// under Documents/Handbox/exports.
+#[tauri::command]
+pub async fn export_debug_bundle(
+    execution_id: String,
+    state: State<'_, AppState>,
+) -> Result<DebugBundle, AppError> {
+    let ex_id: uuid::Uuid = execution_id
+        .parse()
+        .map_err(|e: uuid::Error| e.to_string())?;
+    let (record, spans) = {
+        let guard = state.trace_store.read().await;
+        let store = guard.as_ref().ok_or("Trace store not initialized")?;
+        let record = store
+            .query_execution(ex_id)?
+            .ok_or_else(|| AppError::not_found(format!("Execution not found: {execution_id}")))?;
+        (record, store.query_spans_by_execution(ex_id)?)
+    };
+
+    let workspace_id = execution_workspace(&state, &record).await?;
+    state.authorize(workspace_id, Permission::View).await?;
+    let activity = execution_activity(&state, &execution_id).await?;
+
+    // The workflow as it ran; older records fall back to its current version
+    let spec = match &record.manifest.spec {
+        Some(spec) => Some(spec.clone()),
+        None => state
+            .workflows
+            .read()
+            .await
+            .values()
+            .find(|w| w.id == record.workflow_id)
+            .cloned(),
+    };
+    let credentials = state.llm_credentials.read().await.clone();
+    let mut redactor = Redactor::new(
+        known_secrets(&state, workspace_id).await?,
+        dirs::home_dir().as_deref(),
+    );
+
+    let environment = json!({
+        "app_version": env!("CARGO_PKG_VERSION"),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "active_provider": credentials.active_provider,
+        "tools_registered": state.tool_registry.read().await.list().len(),
+        "doctor": doctor::run(&state.data_dir, &credentials).await,
+    });
+    let mut files = vec![
+        ("execution.json".to_string(), redactor.json(&record)?),
+        ("spans.json".to_string(), redactor.json(&spans)?),
+        ("activity.json".to_string(), redactor.json(&activity)?),
+        ("environment.json".to_string(), redactor.json(&environment)?),
+    ];
+    if let Some(spec) = &spec {
+        files.insert(1, ("workflow.json".to_string(), redactor.json(spec)?));
+    }
+    // Journals only outlive executions that never finished
+    let journal = state.data_dir.join("journal").join(format!("{ex_id}.jsonl"));
+    if let Ok(content) = std::fs::read_to_string(&journal) {
+        files.push(("journal.jsonl".to_string(), redactor.text(&content).into_bytes()));
+    }
+
+    let created_at = chrono::Utc::now();
+    let manifest = BundleManifest {
+        bundle_version: debug_bundle::BUNDLE_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        execution_id: execution_id.clone(),
+        created_at,
+        files: Vec::new(),
+        redactions: redactor.redactions,
+    };
+    let path = export_dir(&state).join(format!(
+        "handbox-debug-{}-{}.zip",
+        &execution_id[..8.min(execution_id.len())],
+        created_at.format("%Y%m%d-%H%M%S")
+    ));
+    let manifest = debug_bundle::write_bundle(&path, manifest, &files)?;
+    Ok(DebugBundle {
+        path: path.to_string_lossy().to_string(),
+        manifest,
+    })
+}
+
+#[derive(serde::Serialize)]
+pub struct RunViewerExport {
+    pub path: String,
+    pub redactions: usize,
+    pub values_included: bool,
+}
+
+/// Package a finished execution as a self-contained HTML page for people
+/// without Handbox, redacted per the workspace's sharing policy, under
+/// Documents/Handbox/exports.
+#[tauri::command]
+pub async fn export_run_viewer(
+    execution_id: String,
+    state: State<'_, AppState>,
+) -> Result<RunViewerExport, AppError> {
+    let ex_id: uuid::Uuid = execution_id
+        .parse()
+        .map_err(|e: uuid::Error| e.to_string())?;
+    let (record, spans) = {
+        let guard = state.trace_store.read().await;
+        let store = guard.as_ref().ok_or("Trace store not initialized")?;
+        let record = store
+            .query_execution(ex_id)?
+            .ok_or_else(|| AppError::not_found(format!("Execution not found: {execution_id}")))?;
+        (record, store.query_spans_by_execution(ex_id)?)
+    };
+    if record.completed_at.is_none() {
+        return Err(AppError::invalid_input("Only finished executions can be shared"));
+    }
+
+    let workspace_id = execution_workspace(&state, &record).await?;
+    state.authorize(workspace_id, Permission::View).await?;
+    let policy = match workspace_id {
+        Some(ws_id) => state
+            .project_manager
+            .read()
+            .await
+            .get_workspace(ws_id)
+            .ok()
+            .and_then(|ws| ws.default_policy)
+            .map(|policy| policy.sharing)
+            .unwrap_or_default(),
+        None => Default::default(),
+    };
+
+    // The workflow as it ran, not as it has been edited since
+    let spec = record.manifest.spec.clone();
+    let mut redactor = Redactor::new(
+        known_secrets(&state, workspace_id).await?,
+        dirs::home_dir().as_deref(),
+    )
+    .with_keys(&policy.redact_keys);
+    let bundle = run_viewer::build(&record, spec.as_ref(), &spans, &policy, &mut redactor)?;
+    let html = run_viewer::render_html(&bundle)?;
+
+    let dir = export_dir(&state);
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!(
+        "handbox-run-{}-{}.html",
+        &execution_id[..8.min(execution_id.len())],
+        bundle.created_at.format("%Y%m%d-%H%M%S")
+    ));
+    std::fs::write(&path, html).map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    Ok(RunViewerExport {
+        path: path.to_string_lossy().to_string(),
+        redactions: bundle.redactions,
+        values_included: bundle.values_included,
+    })
+}
+
+/// Open a page written by [`export_run_viewer`] for read-only viewing.
+#[tauri::command]
+pub async fn load_run_viewer(path: String) -> Result<ViewerBundle, AppError> {
+    let html = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {path}: {e}"))?;
+    run_viewer::load(&html).map_err(AppError::invalid_input)
+}
+
+/// The workspace `record` ran in. Records written before runs stored their
+/// workspace fall back to the activity log.
+pub(crate) async fn execution_workspace(
+    state: &AppState,
+    record: &ExecutionRecord,
+) -> Result<Option<uuid::Uuid>, AppError> {
+    if record.workspace_id.is_some() {
+        return Ok(record.workspace_id);
+    }
+    Ok(execution_activity(state, &record.execution_id.to_string())
+        .await?
+        .iter()
+        .find_map(|e| e.workspace_id))
+}
+
+/// The execution's own activity entries, which name its workspace.
+async fn execution_activity(
+    state: &AppState,
+    execution_id: &str,
+) -> Result<Vec<ActivityEntry>, AppError> {
+    Ok(state
+        .project_manager
+        .read()
+        .await
+        .query_activity(None, &[], 0, BUNDLE_ACTIVITY_SCAN)?
+        .entries
+        .into_iter()
+        .filter(|e| e.subject_id == execution_id)
+        .collect())
+}
+
+/// LLM credentials, service keys and the workspace's environment
+/// variables, for the redactor.
+async fn known_secrets(
+    state: &AppState,
+    workspace_id: Option<uuid::Uuid>,
+) -> Result<Vec<String>, AppError> {
+    let credentials = state.llm_credentials.read().await.clone();
+    let mut secrets: Vec<String> = [
+        credentials.aws_access_key_id,
+        credentials.aws_secret_access_key,
+        credentials.openai_api_key,
+        credentials.anthropic_api_key,
+        credentials.team_sync_token,
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    // Service keys travel in URLs, decoded and re-encoded
+    for key in credentials.service_keys.into_values() {
+        let decoded = hb_tool_executor::public_api::normalize_key(&key);
+        secrets.push(urlencoding::encode(&decoded).into_owned());
+        secrets.push(decoded);
+        secrets.push(key);
+    }
+    if let Some(ws_id) = workspace_id {
+        secrets.extend(state.project_manager.read().await.env_map(ws_id)?.into_values());
+    }
+    Ok(secrets)
+}
+
+/// Where exports are written: Documents/Handbox/exports.
+fn export_dir(state: &AppState) -> std::path::PathBuf {
+    dirs::document_dir()
+        .or_else(dirs::download_dir)
+        .unwrap_or_else(|| state.data_dir.clone())
+        .join("Handbox")
+        .join("exports")
 }
@@ -0,0 +1,136 @@
+//! Live validation — incremental graph diagnostics pushed to the editor.
+
+use hb_core::error::AppError;
+use crate::state::AppState;
+use hb_compiler::diagnostics::{NodePorts, ValidationUpdate};
+use hb_compiler::suggest::{self, ConnectionSuggestions};
+use hb_core::graph::validate::{self, ValidateOptions};
+use hb_core::graph::{SpecIssue, WorkflowSpec};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Runtime, State};
+
+/// Event carrying diagnostics updates for a workflow.
+pub const DIAGNOSTICS_EVENT: &str = "workflow-diagnostics";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsEvent {
+    pub workflow_id: String,
+    /// True when every node was re-validated (the editor should replace all
+    /// diagnostics rather than patch them).
+    pub full: bool,
+    #[serde(flatten)]
+    pub update: ValidationUpdate,
+}
+
+/// Re-validate `spec` and emit the result. `changed = None` validates the
+/// whole workflow; otherwise only the changed nodes and their neighbours are
+/// re-checked (falling back to a full pass the first time a workflow is seen).
+pub async fn publish_diagnostics<R: Runtime>(
+    app: &AppHandle<R>,
+    state: &AppState,
+    spec: &WorkflowSpec,
+    changed: Option<Vec<String>>,
+) -> DiagnosticsEvent {
+    let registry = state.tool_registry.read().await;
+    let resolve = |tool_ref: &str| {
+        let tool_id = tool_ref.split('@').next().unwrap_or(tool_ref);
+        registry.get(tool_id).map(|tool| NodePorts {
+            inputs: tool.input_schema.ports.clone(),
+            outputs: tool.output_schema.ports.clone(),
+            config: tool.config_schema.clone(),
+        })
+    };
+
+    let workflow_id = spec.id.to_string();
+    let mut validators = state.validators.write().await;
+    let (full, update) = match (validators.get_mut(&workflow_id), changed) {
+        (Some(validator), Some(changed)) => (false, validator.revalidate(spec, &changed, &resolve)),
+        _ => {
+            let validator = validators.entry(workflow_id.clone()).or_default();
+            (true, validator.validate_all(spec, &resolve))
+        }
+    };
+
+    let event = DiagnosticsEvent {
+        workflow_id,
+        full,
+        update,
+    };
+    let _ = app.emit(DIAGNOSTICS_EVENT, &event);
+    event
+}
+
+/// Fully validate a workflow (e.g. when the editor opens it).
+#[tauri::command]
+pub async fn validate_workflow(
+    id: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<DiagnosticsEvent, AppError> {
+    let spec = state
+        .workflows
+        .read()
+        .await
+        .get(&id)
+        .cloned()
+        .ok_or_else(|| format!("Workflow not found: {id}"))?;
+    Ok(publish_diagnostics(&app, &state, &spec, None).await)
+}
+
+/// Structural lint of a saved workflow: dangling edges, duplicate node IDs,
+/// unknown ports, Data cycles and nodes that never run. With `values` (run
+/// parameters) required variables without a value are reported too.
+#[tauri::command]
+pub async fn lint_workflow(
+    id: String,
+    values: Option<serde_json::Map<String, serde_json::Value>>,
+    state: State<'_, AppState>,
+) -> Result<Vec<SpecIssue>, AppError> {
+    let spec = state
+        .workflows
+        .read()
+        .await
+        .get(&id)
+        .cloned()
+        .ok_or_else(|| AppError::not_found(format!("Workflow not found: {id}")))?;
+    let registry = state.tool_registry.read().await;
+    let tool_ports = |tool_ref: &str| {
+        let tool_id = tool_ref.split('@').next().unwrap_or(tool_ref);
+        registry
+            .get(tool_id)
+            .map(|tool| (tool.input_schema.ports.clone(), tool.output_schema.ports.clone()))
+    };
+    let options = ValidateOptions {
+        tool_ports: Some(&tool_ports),
+        values: values.as_ref(),
+    };
+    Ok(validate::validate(&spec, options))
+}
+
+/// Suggestions shown when an edge is dropped from `node_id`'s output `port`:
+/// ranked compatible inputs on existing nodes and tools to insert.
+#[tauri::command]
+pub async fn suggest_connections(
+    workflow_id: String,
+    node_id: String,
+    port: String,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<ConnectionSuggestions, AppError> {
+    let spec = state
+        .workflows
+        .read()
+        .await
+        .get(&workflow_id)
+        .cloned()
+        .ok_or_else(|| AppError::not_found(format!("Workflow not found: {workflow_id}")))?;
+    let registry = state.tool_registry.read().await;
+    let tools = registry.list();
+    suggest::suggest_connections(&spec, &node_id, &port, &tools, limit.unwrap_or(10))
+        .map_err(AppError::from)
+}
+
+/// Drop cached diagnostics for a workflow that is no longer open.
+pub async fn forget(state: &AppState, workflow_id: &str) {
+    state.validators.write().await.remove(workflow_id);
+}
@@ -4,6 +4,7 @@
 //! Collections are persisted to JSON files in the app data directory.
 //! Designed for the agent tool pipeline: embed → store → search → rerank.
 
+use hb_core::error::AppError;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
@@ -120,7 +121,7 @@ impl VectorStoreState {
         // Ensure directory exists
         std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create dir: {e}"))?;
 
-        let path = dir.join(format!("{}.json", sanitize_filename(name)));
+        let path = dir.join(collection_file_name(name));
         let content = serde_json::to_string(coll)
             .map_err(|e| format!("Failed to serialize: {e}"))?;
         std::fs::write(&path, content)
@@ -133,12 +134,17 @@ impl VectorStoreState {
     pub async fn remove_persisted_collection(&self, name: &str) {
         let dir = self.persist_dir.read().await;
         if let Some(dir) = dir.as_ref() {
-            let path = dir.join(format!("{}.json", sanitize_filename(name)));
+            let path = dir.join(collection_file_name(name));
             let _ = std::fs::remove_file(path);
         }
     }
 }
 
+/// File a collection is persisted to, relative to the persistence directory.
+pub(crate) fn collection_file_name(name: &str) -> String {
+    format!("{}.json", sanitize_filename(name))
+}
+
 /// Sanitize collection name for use as a filename.
 fn sanitize_filename(name: &str) -> String {
     name.chars()
@@ -198,11 +204,11 @@ pub async fn vector_create_collection(
     name: String,
     dimension: usize,
     state: State<'_, Arc<VectorStoreState>>,
-) -> Result<Value, String> {
+) -> Result<Value, AppError> {
     let mut collections = state.collections.write().await;
 
     if collections.contains_key(&name) {
-        return Err(format!("Collection '{}' already exists", name));
+        return Err(format!("Collection '{}' already exists", name).into());
     }
 
     collections.insert(name.clone(), VectorCollection {
@@ -228,7 +234,7 @@ pub async fn vector_create_collection(
 #[command]
 pub async fn vector_list_collections(
     state: State<'_, Arc<VectorStoreState>>,
-) -> Result<Value, String> {
+) -> Result<Value, AppError> {
     let collections = state.collections.read().await;
 
     let list: Vec<Value> = collections.values().map(|c| json!({
@@ -250,7 +256,7 @@ pub async fn vector_list_collections(
 pub async fn vector_delete_collection(
     name: String,
     state: State<'_, Arc<VectorStoreState>>,
-) -> Result<Value, String> {
+) -> Result<Value, AppError> {
     let mut collections = state.collections.write().await;
     let removed = collections.remove(&name).is_some();
     drop(collections);
@@ -279,7 +285,7 @@ pub async fn vector_store(
     collection: String,
     entries: Vec<VectorEntry>,
     state: State<'_, Arc<VectorStoreState>>,
-) -> Result<Value, String> {
+) -> Result<Value, AppError> {
     let (inserted, updated, total) = {
         let mut collections = state.collections.write().await;
 
@@ -295,7 +301,7 @@ pub async fn vector_store(
                 return Err(format!(
                     "Vector dimension mismatch: expected {}, got {} for entry '{}'",
                     coll.dimension, entry.vector.len(), entry.id
-                ));
+                ).into());
             }
 
             // Upsert: update if exists, insert if not
@@ -337,7 +343,7 @@ pub async fn vector_search(
     filter: Option<HashMap<String, Value>>,
     metric: Option<String>,
     state: State<'_, Arc<VectorStoreState>>,
-) -> Result<Value, String> {
+) -> Result<Value, AppError> {
     let collections = state.collections.read().await;
 
     let coll = collections.get(&collection)
@@ -347,7 +353,7 @@ pub async fn vector_search(
         return Err(format!(
             "Query vector dimension mismatch: expected {}, got {}",
             coll.dimension, query_vector.len()
-        ));
+        ).into());
     }
 
     let k = top_k.unwrap_or(5).min(100);
@@ -413,7 +419,7 @@ pub async fn vector_search(
 pub async fn vector_collection_info(
     name: String,
     state: State<'_, Arc<VectorStoreState>>,
-) -> Result<Value, String> {
+) -> Result<Value, AppError> {
     let collections = state.collections.read().await;
 
     let coll = collections.get(&name)
@@ -434,7 +440,7 @@ pub async fn vector_delete(
     collection: String,
     ids: Vec<String>,
     state: State<'_, Arc<VectorStoreState>>,
-) -> Result<Value, String> {
+) -> Result<Value, AppError> {
     let (deleted, remaining) = {
         let mut collections = state.collections.write().await;
 
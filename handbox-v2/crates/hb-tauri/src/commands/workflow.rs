@@ -1,15 +1,73 @@
 //! Workflow CRUD commands — backed by in-memory store.
 
-use crate::state::AppState;
-use hb_core::graph::WorkflowSpec;
-use tauri::State;
+use hb_core::error::AppError;
+use crate::commands::collaboration::CollaborationState;
+use crate::commands::validation;
+use crate::session_recording::{EDIT_OP_EVENT, SNAPSHOT_EVENT};
+use crate::state::{Access, AppState};
+use hb_core::graph::params::{self, ParamError};
+use hb_core::graph::{
+    PastedFragment, Position, SpecAssembler, SpecOutline, SpecPage, WorkflowFragment, WorkflowSpec,
+};
+use hb_core::project::{ActivityEntry, ActivityKind, Permission, ReviewPolicy};
+use hb_project::journal::{EditOp, JournalEntry, UndoState};
+use hb_project::review::{ReviewDecision, ReviewState, WorkflowReview};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, Runtime, State};
+
+/// Parse an optional workspace ID passed from the frontend.
+pub(crate) fn parse_workspace_id(workspace_id: Option<String>) -> Result<Option<uuid::Uuid>, String> {
+    workspace_id
+        .filter(|id| !id.is_empty())
+        .map(|id| id.parse().map_err(|e: uuid::Error| e.to_string()))
+        .transpose()
+}
+
+/// Activity entries read per page when looking up workflow workspaces.
+const WORKSPACE_SCAN_PAGE: usize = 500;
+
+/// The workspace each of `ids` was last created or edited in, from the
+/// activity log. Workflows with no such entry belong to no workspace.
+pub(crate) async fn workflow_workspaces(
+    state: &AppState,
+    ids: &[String],
+) -> Result<HashMap<String, Option<uuid::Uuid>>, AppError> {
+    let mut found = HashMap::new();
+    let kinds = [ActivityKind::WorkflowCreated, ActivityKind::WorkflowEdited];
+    let mut offset = 0;
+    while found.len() < ids.len() {
+        let page = state
+            .project_manager
+            .read()
+            .await
+            .query_activity(None, &kinds, offset, WORKSPACE_SCAN_PAGE)?;
+        // Newest first, so the first entry seen for a workflow is its latest
+        for entry in &page.entries {
+            if ids.contains(&entry.subject_id) && !found.contains_key(&entry.subject_id) {
+                found.insert(entry.subject_id.clone(), entry.workspace_id);
+            }
+        }
+        if !page.has_more {
+            break;
+        }
+        offset += page.entries.len();
+    }
+    for id in ids {
+        found.entry(id.clone()).or_insert(None);
+    }
+    Ok(found)
+}
 
 #[tauri::command]
 pub async fn create_workflow(
     name: String,
     description: String,
+    workspace_id: Option<String>,
     state: State<'_, AppState>,
-) -> Result<WorkflowSpec, String> {
+) -> Result<WorkflowSpec, AppError> {
+    let workspace_id = parse_workspace_id(workspace_id)?;
+    let access = state.authorize(workspace_id, Permission::EditWorkflow).await?;
     let spec = WorkflowSpec {
         meta: hb_core::graph::WorkflowMeta {
             name,
@@ -19,7 +77,14 @@ pub async fn create_workflow(
         ..Default::default()
     };
     let id = spec.id.to_string();
-    state.workflows.write().await.insert(id, spec.clone());
+    state.workflows.write().await.insert(id.clone(), spec.clone());
+    state
+        .record_activity(access.stamp(
+            ActivityEntry::new(ActivityKind::WorkflowCreated, id, format!("Created workflow '{}'", spec.meta.name))
+                .in_workspace(workspace_id),
+        ))
+        .await;
+    state.refresh_thumbnail(&spec).await;
     Ok(spec)
 }
 
@@ -27,54 +92,705 @@ pub async fn create_workflow(
 pub async fn get_workflow(
     id: String,
     state: State<'_, AppState>,
-) -> Result<WorkflowSpec, String> {
+) -> Result<WorkflowSpec, AppError> {
     state
         .workflows
         .read()
         .await
         .get(&id)
         .cloned()
-        .ok_or_else(|| format!("Workflow not found: {id}"))
+        .ok_or_else(|| AppError::not_found(format!("Workflow not found: {id}")))
 }
 
 #[tauri::command]
 pub async fn list_workflows(
     state: State<'_, AppState>,
-) -> Result<Vec<WorkflowSpec>, String> {
+) -> Result<Vec<WorkflowSpec>, AppError> {
     Ok(state.workflows.read().await.values().cloned().collect())
 }
 
 #[tauri::command]
 pub async fn update_workflow(
     spec: WorkflowSpec,
+    workspace_id: Option<String>,
+    app: AppHandle,
     state: State<'_, AppState>,
-) -> Result<WorkflowSpec, String> {
-    let id = spec.id.to_string();
-    state.workflows.write().await.insert(id, spec.clone());
+) -> Result<WorkflowSpec, AppError> {
+    let workspace_id = parse_workspace_id(workspace_id)?;
+    let access = state.authorize(workspace_id, Permission::EditWorkflow).await?;
+    // Journaled edits were made against the graph this replaces
+    state.project_manager.read().await.clear_journal(spec.id)?;
+    store_edited(&spec, workspace_id, &access, &app, &state).await;
     Ok(spec)
 }
 
+/// Store an edited workflow and record, render and validate it.
+async fn store_edited(
+    spec: &WorkflowSpec,
+    workspace_id: Option<uuid::Uuid>,
+    access: &Access,
+    app: &AppHandle,
+    state: &AppState,
+) {
+    let id = spec.id.to_string();
+    state.workflows.write().await.insert(id.clone(), spec.clone());
+    state
+        .record_activity(access.stamp(
+            ActivityEntry::new(ActivityKind::WorkflowEdited, id, format!("Edited workflow '{}'", spec.meta.name))
+                .in_workspace(workspace_id),
+        ))
+        .await;
+    state.refresh_thumbnail(spec).await;
+    validation::publish_diagnostics(app, state, spec, None).await;
+    if let Ok(snapshot) = serde_json::to_value(spec) {
+        record_in_sessions(app, &spec.id.to_string(), access.actor.as_deref(), SNAPSHOT_EVENT, snapshot).await;
+    }
+}
+
+/// Record a change in the collaboration sessions on the workflow.
+async fn record_in_sessions<R: Runtime>(
+    app: &AppHandle<R>,
+    workflow_id: &str,
+    actor: Option<&str>,
+    event_type: &str,
+    payload: serde_json::Value,
+) {
+    if let Some(collaboration) = app.try_state::<Arc<CollaborationState>>() {
+        collaboration
+            .record_workflow_change(workflow_id, actor, event_type, payload)
+            .await;
+    }
+}
+
 #[tauri::command]
 pub async fn delete_workflow(
     id: String,
+    workspace_id: Option<String>,
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    state.workflows.write().await.remove(&id);
+) -> Result<(), AppError> {
+    let workspace_id = parse_workspace_id(workspace_id)?;
+    let access = state.authorize(workspace_id, Permission::EditWorkflow).await?;
+    if let Some(spec) = state.workflows.write().await.remove(&id) {
+        if let Err(e) = state.thumbnails.read().await.delete(spec.id) {
+            tracing::warn!("Failed to delete thumbnail for {id}: {e}");
+        }
+        validation::forget(&state, &id).await;
+        crate::team_sync::forget_workflow(&state.data_dir, &id);
+        state
+            .record_activity(access.stamp(
+                ActivityEntry::new(ActivityKind::WorkflowDeleted, id, format!("Deleted workflow '{}'", spec.meta.name))
+                    .in_workspace(workspace_id),
+            ))
+            .await;
+    }
     Ok(())
 }
 
 #[tauri::command]
 pub async fn import_workflow(
     json: String,
+    workspace_id: Option<String>,
+    app: AppHandle,
     state: State<'_, AppState>,
-) -> Result<WorkflowSpec, String> {
-    let spec: WorkflowSpec =
-        serde_json::from_str(&json).map_err(|e| format!("Invalid JSON: {e}"))?;
+) -> Result<WorkflowSpec, AppError> {
+    let workspace_id = parse_workspace_id(workspace_id)?;
+    let access = state.authorize(workspace_id, Permission::EditWorkflow).await?;
+    let upgraded = WorkflowSpec::from_json(&json).map_err(|e| format!("Invalid workflow: {e}"))?;
+    if !upgraded.applied.is_empty() {
+        tracing::info!(
+            "Upgraded imported workflow from spec {}: {}",
+            upgraded.from_version,
+            upgraded.applied.join("; ")
+        );
+    }
+    let spec = upgraded.spec;
     let id = spec.id.to_string();
-    state.workflows.write().await.insert(id, spec.clone());
+    state.workflows.write().await.insert(id.clone(), spec.clone());
+    state
+        .record_activity(access.stamp(
+            ActivityEntry::new(ActivityKind::WorkflowCreated, id, format!("Imported workflow '{}'", spec.meta.name))
+                .in_workspace(workspace_id),
+        ))
+        .await;
+    state.refresh_thumbnail(&spec).await;
+    validation::publish_diagnostics(&app, &state, &spec, None).await;
     Ok(spec)
 }
 
+/// Nodes and edges per page when the caller doesn't say.
+const DEFAULT_PAGE_SIZE: usize = 200;
+/// Most nodes and edges sent in one page.
+const MAX_PAGE_SIZE: usize = 2000;
+
+/// A workflow without its nodes and edges, for loading large workflows page
+/// by page with [`get_workflow_page`].
+#[tauri::command]
+pub async fn get_workflow_outline(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<SpecOutline, AppError> {
+    let workflows = state.workflows.read().await;
+    let spec = workflows
+        .get(&id)
+        .ok_or_else(|| AppError::not_found(format!("Workflow not found: {id}")))?;
+    Ok(spec.outline())
+}
+
+/// Nodes and edges of a workflow from `offset`, with composite subgraphs
+/// left for [`get_workflow_subgraph`].
+#[tauri::command]
+pub async fn get_workflow_page(
+    id: String,
+    offset: usize,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<SpecPage, AppError> {
+    let workflows = state.workflows.read().await;
+    let spec = workflows
+        .get(&id)
+        .ok_or_else(|| AppError::not_found(format!("Workflow not found: {id}")))?;
+    Ok(spec.page(offset, limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE)))
+}
+
+/// The subgraph of the composite at `path` (composite IDs joined with `/`),
+/// loaded when the composite is opened.
+#[tauri::command]
+pub async fn get_workflow_subgraph(
+    id: String,
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<SpecPage, AppError> {
+    let workflows = state.workflows.read().await;
+    let spec = workflows
+        .get(&id)
+        .ok_or_else(|| AppError::not_found(format!("Workflow not found: {id}")))?;
+    spec.subgraph_page(&path)
+        .ok_or_else(|| AppError::not_found(format!("No composite node at '{path}' in workflow {id}")))
+}
+
+/// How far pasted nodes land from where they were copied, when the editor
+/// doesn't place them.
+const PASTE_OFFSET: Position = Position { x: 40.0, y: 40.0 };
+
+/// The selected nodes of a workflow as fragment text for the clipboard.
+#[tauri::command]
+pub async fn copy_workflow_fragment(
+    id: String,
+    node_ids: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<String, AppError> {
+    let workflows = state.workflows.read().await;
+    let spec = workflows
+        .get(&id)
+        .ok_or_else(|| AppError::not_found(format!("Workflow not found: {id}")))?;
+    let fragment = spec
+        .copy_fragment(&node_ids)
+        .map_err(|e| AppError::invalid_input(e.to_string()))?;
+    Ok(fragment.to_text())
+}
+
+/// A pasted fragment and what it needs that the target does not have.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FragmentPasteReport {
+    pub spec: WorkflowSpec,
+    pub pasted: PastedFragment,
+    /// Tool refs with no tool in the registry.
+    pub missing_tools: Vec<String>,
+    /// Referenced workflows that are not in this project.
+    pub missing_workflows: Vec<uuid::Uuid>,
+    /// `{{env:NAME}}` variables the workspace does not define.
+    pub missing_env: Vec<String>,
+}
+
+/// Paste fragment text — copied from another workflow or shared in chat —
+/// into a workflow. Malformed fragments are rejected; unresolved
+/// dependencies are reported and show up as diagnostics.
+#[tauri::command]
+pub async fn paste_workflow_fragment(
+    id: String,
+    text: String,
+    offset: Option<Position>,
+    workspace_id: Option<String>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<FragmentPasteReport, AppError> {
+    let workspace_id = parse_workspace_id(workspace_id)?;
+    let access = state.authorize(workspace_id, Permission::EditWorkflow).await?;
+    let fragment = WorkflowFragment::parse(&text).map_err(|e| AppError::invalid_input(e.to_string()))?;
+
+    let mut spec = state
+        .workflows
+        .read()
+        .await
+        .get(&id)
+        .cloned()
+        .ok_or_else(|| AppError::not_found(format!("Workflow not found: {id}")))?;
+    let pasted = spec
+        .paste_fragment(fragment, offset.unwrap_or(PASTE_OFFSET))
+        .map_err(|e| AppError::invalid_input(e.to_string()))?;
+
+    let missing_tools = {
+        let registry = state.tool_registry.read().await;
+        pasted
+            .requires
+            .tools
+            .iter()
+            .filter(|tool_ref| registry.get(tool_ref.split('@').next().unwrap_or(tool_ref)).is_none())
+            .cloned()
+            .collect()
+    };
+    let missing_workflows = {
+        let workflows = state.workflows.read().await;
+        pasted
+            .requires
+            .workflows
+            .iter()
+            .filter(|wf| !workflows.contains_key(&wf.to_string()))
+            .copied()
+            .collect()
+    };
+    let env = match workspace_id {
+        Some(ws_id) => state.project_manager.read().await.env_map(ws_id)?,
+        None => Default::default(),
+    };
+    let missing_env = pasted
+        .requires
+        .env
+        .iter()
+        .filter(|name| !env.contains_key(*name))
+        .cloned()
+        .collect();
+
+    store_edited(&spec, workspace_id, &access, &app, &state).await;
+    Ok(FragmentPasteReport {
+        spec,
+        pasted,
+        missing_tools,
+        missing_workflows,
+        missing_env,
+    })
+}
+
+/// Start saving a workflow page by page; returns the upload ID the pages
+/// are sent under. Replaces an unfinished upload of the same workflow.
+#[tauri::command]
+pub async fn begin_workflow_upload(
+    outline: SpecOutline,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, AppError> {
+    let workspace_id = parse_workspace_id(workspace_id)?;
+    state.authorize(workspace_id, Permission::EditWorkflow).await?;
+    let upload_id = uuid::Uuid::new_v4().to_string();
+    let mut uploads = state.workflow_uploads.write().await;
+    uploads.retain(|_, upload| upload.workflow_id() != outline.id);
+    uploads.insert(upload_id.clone(), SpecAssembler::new(outline));
+    Ok(upload_id)
+}
+
+/// Add the next page of an upload. A page out of order ends the upload.
+#[tauri::command]
+pub async fn append_workflow_page(
+    upload_id: String,
+    page: SpecPage,
+    state: State<'_, AppState>,
+) -> Result<(), AppError> {
+    let mut uploads = state.workflow_uploads.write().await;
+    let upload = uploads
+        .get_mut(&upload_id)
+        .ok_or_else(|| AppError::not_found(format!("Upload not found: {upload_id}")))?;
+    if let Err(e) = upload.push(page) {
+        uploads.remove(&upload_id);
+        return Err(AppError::invalid_input(e.to_string()));
+    }
+    Ok(())
+}
+
+/// Store the uploaded workflow. Subgraphs the editor never loaded are kept
+/// from the stored version.
+#[tauri::command]
+pub async fn finish_workflow_upload(
+    upload_id: String,
+    workspace_id: Option<String>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<SpecOutline, AppError> {
+    let workspace_id = parse_workspace_id(workspace_id)?;
+    let access = state.authorize(workspace_id, Permission::EditWorkflow).await?;
+    let upload = state
+        .workflow_uploads
+        .write()
+        .await
+        .remove(&upload_id)
+        .ok_or_else(|| AppError::not_found(format!("Upload not found: {upload_id}")))?;
+    let id = upload.workflow_id().to_string();
+    let spec = upload
+        .finish(state.workflows.read().await.get(&id))
+        .map_err(|e| AppError::invalid_input(e.to_string()))?;
+    store_edited(&spec, workspace_id, &access, &app, &state).await;
+    Ok(spec.outline())
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct EditResult {
+    pub spec: WorkflowSpec,
+    /// The applied, undone or redone edit; `None` when there was nothing to undo/redo.
+    pub entry: Option<JournalEntry>,
+    pub undo: UndoState,
+}
+
+/// Shared body of the edit/undo/redo commands: run `step` against the stored
+/// workflow under the write lock and report the new undo state.
+async fn edit_step<R: Runtime>(
+    id: &str,
+    actor: Option<&str>,
+    app: &AppHandle<R>,
+    state: &AppState,
+    step: impl FnOnce(
+        &dyn hb_project::ProjectStore,
+        &mut WorkflowSpec,
+    ) -> Result<Option<JournalEntry>, hb_project::ProjectError>,
+) -> Result<EditResult, String> {
+    let result = {
+        let mut workflows = state.workflows.write().await;
+        let spec = workflows
+            .get_mut(id)
+            .ok_or_else(|| format!("Workflow not found: {id}"))?;
+        let pm = state.project_manager.read().await;
+        let entry = step(&**pm, spec).map_err(|e| e.to_string())?;
+        let undo = pm.undo_state(spec.id).map_err(|e| e.to_string())?;
+        EditResult {
+            spec: spec.clone(),
+            entry,
+            undo,
+        }
+    };
+    if let Some(ref entry) = result.entry {
+        validation::publish_diagnostics(app, state, &result.spec, Some(entry.affected_nodes())).await;
+        // An undone entry was applied backwards
+        let applied = if entry.undone { &entry.inverse } else { &entry.op };
+        if let Ok(op) = serde_json::to_value(applied) {
+            record_in_sessions(app, id, actor, EDIT_OP_EVENT, op).await;
+        }
+    }
+    Ok(result)
+}
+
+/// Apply a granular edit (add/move/connect/set config, ...) and journal it.
+/// While team sync is on the edit is journaled under the team sync user.
+#[tauri::command]
+pub async fn apply_workflow_edit<R: Runtime>(
+    id: String,
+    op: EditOp,
+    workspace_id: Option<String>,
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+) -> Result<EditResult, AppError> {
+    let workspace_id = parse_workspace_id(workspace_id)?;
+    let access = state.authorize(workspace_id, Permission::EditWorkflow).await?;
+    let actor = access.actor.clone();
+    edit_step(&id, actor.as_deref(), &app, &state, |pm, spec| pm.apply_edit(spec, op, access.actor).map(Some))
+        .await
+        .map_err(AppError::from)
+}
+
+#[tauri::command]
+pub async fn undo_workflow_edit<R: Runtime>(
+    id: String,
+    workspace_id: Option<String>,
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+) -> Result<EditResult, AppError> {
+    let workspace_id = parse_workspace_id(workspace_id)?;
+    let access = state.authorize(workspace_id, Permission::EditWorkflow).await?;
+    edit_step(&id, access.actor.as_deref(), &app, &state, |pm, spec| pm.undo_edit(spec))
+        .await
+        .map_err(AppError::from)
+}
+
+#[tauri::command]
+pub async fn redo_workflow_edit<R: Runtime>(
+    id: String,
+    workspace_id: Option<String>,
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+) -> Result<EditResult, AppError> {
+    let workspace_id = parse_workspace_id(workspace_id)?;
+    let access = state.authorize(workspace_id, Permission::EditWorkflow).await?;
+    edit_step(&id, access.actor.as_deref(), &app, &state, |pm, spec| pm.redo_edit(spec))
+        .await
+        .map_err(AppError::from)
+}
+
+/// Journal entries after `after_seq` (default: all), oldest first.
+#[tauri::command]
+pub async fn get_edit_journal(
+    id: String,
+    after_seq: Option<i64>,
+    state: State<'_, AppState>,
+) -> Result<Vec<JournalEntry>, AppError> {
+    let workflow_id: uuid::Uuid = id.parse().map_err(|e: uuid::Error| e.to_string())?;
+    state
+        .project_manager
+        .read()
+        .await
+        .journal_since(workflow_id, after_seq.unwrap_or(0))
+        .map_err(AppError::from)
+}
+
+#[derive(serde::Serialize)]
+pub struct ReviewStatus {
+    pub review: WorkflowReview,
+    /// The review state for the workflow as it is now; edits since
+    /// submission make it a draft again.
+    pub effective_state: ReviewState,
+    /// The workspace's review policy; `None` when review isn't required.
+    pub policy: Option<ReviewPolicy>,
+}
+
+async fn load_review(
+    state: &AppState,
+    id: &str,
+    workspace_id: Option<uuid::Uuid>,
+) -> Result<(WorkflowSpec, WorkflowReview, Option<ReviewPolicy>), AppError> {
+    let spec = state
+        .workflows
+        .read()
+        .await
+        .get(id)
+        .cloned()
+        .ok_or_else(|| AppError::not_found(format!("Workflow not found: {id}")))?;
+    let pm = state.project_manager.read().await;
+    let review = pm.get_review(spec.id)?.unwrap_or_else(|| WorkflowReview::new(spec.id));
+    // A submitted workflow stays under the policy of the workspace it was
+    // submitted in, whichever workspace the caller names
+    let policy = match review.workspace_id.or(workspace_id) {
+        Some(ws) => pm.get_workspace(ws)?.review_policy,
+        None => None,
+    };
+    Ok((spec, review, policy))
+}
+
+/// Fail unless the workflow may be published or scheduled: it must be
+/// approved when the given workspace, or the one it was submitted for
+/// review in, has a review policy. Returns the approved stored revision
+/// when approval applies, so callers act on what was reviewed.
+pub(crate) async fn require_approval(
+    state: &AppState,
+    workspace_id: Option<uuid::Uuid>,
+    workflow_id: &str,
+) -> Result<Option<WorkflowSpec>, AppError> {
+    let stored = state.workflows.read().await.get(workflow_id).cloned();
+    let pm = state.project_manager.read().await;
+    let review = match &stored {
+        Some(spec) => pm.get_review(spec.id)?,
+        None => None,
+    };
+    let mut gated = false;
+    for ws in [workspace_id, review.as_ref().and_then(|r| r.workspace_id)]
+        .into_iter()
+        .flatten()
+    {
+        gated |= pm.get_workspace(ws)?.review_policy.is_some();
+    }
+    drop(pm);
+    if !gated {
+        return Ok(None);
+    }
+    if workflow_id.is_empty() {
+        return Err(AppError::invalid_input(
+            "This workspace only allows approved workflows; pass the workflow ID",
+        ));
+    }
+    let spec = stored.ok_or_else(|| AppError::not_found(format!("Workflow not found: {workflow_id}")))?;
+    review
+        .unwrap_or_else(|| WorkflowReview::new(spec.id))
+        .require_approved(&spec)?;
+    Ok(Some(spec))
+}
+
+#[tauri::command]
+pub async fn get_workflow_review(
+    id: String,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ReviewStatus, AppError> {
+    let workspace_id = parse_workspace_id(workspace_id)?;
+    let (spec, review, policy) = load_review(&state, &id, workspace_id).await?;
+    Ok(ReviewStatus {
+        effective_state: review.effective_state(&spec),
+        review,
+        policy,
+    })
+}
+
+/// Submit a workflow, as it is now, for review.
+#[tauri::command]
+pub async fn submit_workflow_review(
+    id: String,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ReviewStatus, AppError> {
+    let workspace_id = parse_workspace_id(workspace_id)?;
+    let access = state.authorize(workspace_id, Permission::EditWorkflow).await?;
+    let (spec, mut review, policy) = load_review(&state, &id, workspace_id).await?;
+    review.submit(&spec, access.actor.clone())?;
+    review.workspace_id = review.workspace_id.or(workspace_id);
+    state.project_manager.read().await.save_review(&review)?;
+    state
+        .record_activity(access.stamp(
+            ActivityEntry::new(ActivityKind::ReviewDecision, &id, format!("Submitted workflow '{}' for review", spec.meta.name))
+                .in_workspace(workspace_id)
+                .with_details(serde_json::json!({ "state": review.state })),
+        ))
+        .await;
+    Ok(ReviewStatus {
+        effective_state: review.state,
+        review,
+        policy,
+    })
+}
+
+/// Approve or reject a workflow in review. Reviewers need the Approve
+/// permission in the workspace the workflow was submitted in, and the
+/// policy's approver role when workspace roles apply.
+#[tauri::command]
+pub async fn decide_workflow_review(
+    id: String,
+    approve: bool,
+    comment: Option<String>,
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ReviewStatus, AppError> {
+    let workspace_id = parse_workspace_id(workspace_id)?;
+    let (spec, mut review, policy) = load_review(&state, &id, workspace_id).await?;
+    let workspace_id = review.workspace_id.or(workspace_id);
+    let access = state.authorize(workspace_id, Permission::Approve).await?;
+    let decision = ReviewDecision {
+        reviewer: access.actor.clone(),
+        role: access.role,
+        approved: approve,
+        comment: comment.filter(|c| !c.trim().is_empty()),
+        decided_at: chrono::Utc::now(),
+    };
+    let details = serde_json::json!({ "approved": approve, "comment": decision.comment });
+    let new_state = review.decide(&spec, &policy.clone().unwrap_or_default(), decision)?;
+    state.project_manager.read().await.save_review(&review)?;
+
+    let summary = match (approve, new_state) {
+        (false, _) => format!("Rejected workflow '{}'", spec.meta.name),
+        (true, ReviewState::Approved) => format!("Approved workflow '{}'", spec.meta.name),
+        (true, _) => format!("Approved workflow '{}' (more approvals needed)", spec.meta.name),
+    };
+    state
+        .record_activity(access.stamp(
+            ActivityEntry::new(ActivityKind::ReviewDecision, &id, summary)
+                .in_workspace(workspace_id)
+                .with_details(details),
+        ))
+        .await;
+    Ok(ReviewStatus {
+        effective_state: new_state,
+        review,
+        policy,
+    })
+}
+
+#[derive(serde::Serialize)]
+pub struct WorkflowThumbnail {
+    pub workflow_id: String,
+    /// "svg" or "png".
+    pub format: String,
+    /// SVG markup, or a `data:image/png;base64,` URL for PNG.
+    pub data: String,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Thumbnail for a workflow list entry or marketplace card. Served from the
+/// project DB cache; rendered on demand when missing or stale.
+#[tauri::command]
+pub async fn get_workflow_thumbnail(
+    id: String,
+    format: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<WorkflowThumbnail, AppError> {
+    use base64::Engine;
+
+    let format = format.unwrap_or_else(|| "svg".into());
+    if format != "svg" && format != "png" {
+        return Err(format!("Unsupported thumbnail format: {format}").into());
+    }
+    let spec = state
+        .workflows
+        .read()
+        .await
+        .get(&id)
+        .cloned()
+        .ok_or_else(|| format!("Workflow not found: {id}"))?;
+    let thumb = state.thumbnail(&spec).await?;
+
+    let data = if format == "png" {
+        format!(
+            "data:image/png;base64,{}",
+            base64::engine::general_purpose::STANDARD.encode(&thumb.png)
+        )
+    } else {
+        thumb.svg
+    };
+    Ok(WorkflowThumbnail {
+        workflow_id: id,
+        format,
+        data,
+        updated_at: thumb.updated_at,
+    })
+}
+
+/// JSON form schema for a workflow's run parameters (its variables).
+#[tauri::command]
+pub async fn get_workflow_parameter_form(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, AppError> {
+    let workflows = state.workflows.read().await;
+    let spec = workflows
+        .get(&id)
+        .ok_or_else(|| format!("Workflow not found: {id}"))?;
+    Ok(params::form_schema(&spec.variables))
+}
+
+#[derive(serde::Serialize)]
+pub struct ParameterValidation {
+    pub valid: bool,
+    /// Submitted values with defaults filled in (empty when invalid).
+    pub values: serde_json::Map<String, serde_json::Value>,
+    pub errors: Vec<ParamError>,
+}
+
+/// Validate run parameters submitted from the parameter form.
+#[tauri::command]
+pub async fn validate_workflow_parameters(
+    id: String,
+    values: serde_json::Map<String, serde_json::Value>,
+    state: State<'_, AppState>,
+) -> Result<ParameterValidation, AppError> {
+    let workflows = state.workflows.read().await;
+    let spec = workflows
+        .get(&id)
+        .ok_or_else(|| format!("Workflow not found: {id}"))?;
+    Ok(match params::validate_values(&spec.variables, &values) {
+        Ok(values) => ParameterValidation {
+            valid: true,
+            values,
+            errors: vec![],
+        },
+        Err(errors) => ParameterValidation {
+            valid: false,
+            values: Default::default(),
+            errors,
+        },
+    })
+}
+
 #[derive(serde::Serialize)]
 pub struct ExportResult {
     pub path: String,
@@ -85,7 +801,7 @@ pub async fn export_workflow_file(
     content: String,
     filename: String,
     state: State<'_, AppState>,
-) -> Result<ExportResult, String> {
+) -> Result<ExportResult, AppError> {
     // Get documents directory or use data_dir
     let docs_dir = dirs::document_dir()
         .or_else(|| dirs::download_dir())
@@ -105,3 +821,174 @@ pub async fn export_workflow_file(
         path: file_path.to_string_lossy().to_string(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::testing::{shared_workspace, team_state};
+    use hb_core::error::ErrorCode;
+    use hb_core::project::Role;
+    use tauri::Manager;
+
+    fn add_node(id: &str) -> EditOp {
+        EditOp::AddNode {
+            node: serde_json::from_value(serde_json::json!({
+                "kind": "primitive",
+                "id": id,
+                "tool_ref": "core-tools/display-output",
+            }))
+            .unwrap(),
+            index: None,
+        }
+    }
+
+    fn act_as(state: &AppState, user: &str) {
+        let mut config = crate::team_sync::SyncConfig::load(&state.data_dir);
+        config.user = Some(user.into());
+        config.save(&state.data_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn reviews_stay_with_the_workspace_they_were_submitted_in() {
+        let state = team_state("kim").await;
+        let ws = shared_workspace(
+            &state,
+            &[("kim", Role::Editor), ("val", Role::Viewer), ("lee", Role::Admin)],
+        )
+        .await;
+        {
+            let pm = state.project_manager.read().await;
+            let mut config = pm.get_workspace(ws).unwrap();
+            config.review_policy = Some(ReviewPolicy::default());
+            pm.update_workspace(ws, &config).unwrap();
+        }
+        let spec = WorkflowSpec::default();
+        let id = spec.id.to_string();
+        state.workflows.write().await.insert(id.clone(), spec);
+        let app = tauri::test::mock_app();
+        app.manage(state);
+        let state: State<'_, AppState> = app.state();
+
+        submit_workflow_review(id.clone(), Some(ws.to_string()), app.state()).await.unwrap();
+        // Naming no workspace doesn't lift the policy it was submitted under
+        assert!(require_approval(&state, None, &id).await.is_err());
+
+        act_as(&state, "val");
+        let err = decide_workflow_review(id.clone(), true, None, None, app.state())
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err.code, ErrorCode::PermissionDenied);
+
+        act_as(&state, "lee");
+        let status = decide_workflow_review(id.clone(), true, None, None, app.state())
+            .await
+            .unwrap();
+        assert_eq!(status.effective_state, ReviewState::Approved);
+        let approved = require_approval(&state, None, &id).await.unwrap();
+        assert_eq!(approved.map(|spec| spec.id.to_string()), Some(id));
+    }
+
+    #[tokio::test]
+    async fn edits_need_an_editor_role_in_the_named_workspace() {
+        let state = team_state("vera").await;
+        let ws = shared_workspace(&state, &[("vera", Role::Viewer), ("ed", Role::Editor)]).await;
+        let spec = WorkflowSpec::default();
+        let id = spec.id.to_string();
+        state.workflows.write().await.insert(id.clone(), spec);
+        let app = tauri::test::mock_app();
+        app.manage(state);
+
+        // Leaving the workspace out no longer skips the check
+        let err = apply_workflow_edit(id.clone(), add_node("a"), None, app.handle().clone(), app.state())
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::PermissionDenied);
+
+        let err = apply_workflow_edit(
+            id.clone(),
+            add_node("a"),
+            Some(ws.to_string()),
+            app.handle().clone(),
+            app.state(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.code, ErrorCode::PermissionDenied, "{}", err.message);
+        let err = undo_workflow_edit(id.clone(), None, app.handle().clone(), app.state())
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::PermissionDenied);
+        assert!(app.state::<AppState>().workflows.read().await[&id].nodes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn edits_are_journaled_under_the_sync_user() {
+        let state = team_state("ed").await;
+        let ws = shared_workspace(&state, &[("ed", Role::Editor)]).await;
+        let spec = WorkflowSpec::default();
+        let id = spec.id.to_string();
+        state.workflows.write().await.insert(id.clone(), spec);
+        let app = tauri::test::mock_app();
+        app.manage(state);
+
+        let result = apply_workflow_edit(
+            id,
+            add_node("a"),
+            Some(ws.to_string()),
+            app.handle().clone(),
+            app.state(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.spec.nodes.len(), 1);
+        assert_eq!(result.entry.unwrap().actor.as_deref(), Some("ed"));
+    }
+
+    #[tokio::test]
+    async fn session_recordings_replay_edits_made_during_the_session() {
+        use crate::commands::collaboration::{collab_create_session, collab_playback};
+
+        let state = team_state("ed").await;
+        let ws = shared_workspace(&state, &[("ed", Role::Editor)]).await;
+        let spec = WorkflowSpec::default();
+        let id = spec.id.to_string();
+        state.workflows.write().await.insert(id.clone(), spec);
+        let recordings = state.data_dir.join("collab_recordings");
+        let app = tauri::test::mock_app();
+        app.manage(state);
+        app.manage(Arc::new(CollaborationState::new(recordings)));
+        let session = collab_create_session(
+            id.clone(),
+            "Review".into(),
+            "ed".into(),
+            "Ed".into(),
+            None,
+            app.state(),
+            app.state(),
+        )
+        .await
+        .unwrap();
+
+        let ws = Some(ws.to_string());
+        for node in ["a", "b"] {
+            apply_workflow_edit(
+                id.clone(),
+                add_node(node),
+                ws.clone(),
+                app.handle().clone(),
+                app.state(),
+            )
+            .await
+            .unwrap();
+        }
+        undo_workflow_edit(id.clone(), ws, app.handle().clone(), app.state()).await.unwrap();
+
+        let frame = collab_playback(session.id, None, None, app.state()).await.unwrap();
+        assert_eq!(frame.applied, 3);
+        assert!(frame.errors.is_empty());
+        let workflow = frame.workflow.unwrap();
+        let nodes: Vec<_> = workflow.nodes.iter().map(|n| n.id().to_string()).collect();
+        assert_eq!(nodes, ["a"]);
+    }
+}
@@ -0,0 +1,383 @@
+//! App configuration — data directory resolution, portable mode, and migration.
+//!
+//! Resolution order for the data directory:
+//! 1. `HANDBOX_DATA_DIR` environment variable
+//! 2. Portable mode (`HANDBOX_PORTABLE=1`, or `portable: true` in a
+//!    `handbox.config.json` next to the executable) → `<exe dir>/data`
+//! 3. `data_dir` from the config file (next to the executable, then the
+//!    per-user config directory)
+//! 4. Platform default (`%APPDATA%\Handbox` on Windows, XDG data dir elsewhere)
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Config file name, looked up next to the executable and in the user config dir.
+pub const CONFIG_FILE_NAME: &str = "handbox.config.json";
+
+/// Environment variable overriding the data directory.
+pub const DATA_DIR_ENV: &str = "HANDBOX_DATA_DIR";
+
+/// Environment variable enabling portable mode.
+pub const PORTABLE_ENV: &str = "HANDBOX_PORTABLE";
+
+/// Persisted app configuration.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppConfig {
+    /// Explicit data directory.
+    #[serde(default)]
+    pub data_dir: Option<PathBuf>,
+    /// Keep all data next to the executable.
+    #[serde(default)]
+    pub portable: bool,
+    /// Data directory to move into `data_dir` on next launch.
+    #[serde(default)]
+    pub migrate_from: Option<PathBuf>,
+}
+
+impl AppConfig {
+    /// Load a config file; missing or invalid files yield the default config.
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save the config file.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, content).map_err(|e| e.to_string())
+    }
+}
+
+/// Where the effective data directory came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataDirSource {
+    Environment,
+    Portable,
+    ConfigFile,
+    PlatformDefault,
+}
+
+/// The resolved data directory and the config that produced it.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedDataDir {
+    pub path: PathBuf,
+    pub source: DataDirSource,
+    /// Config file that was read (or would be written to).
+    pub config_path: PathBuf,
+}
+
+/// Inputs to data directory resolution, split out so it can be tested
+/// without touching the real environment.
+#[derive(Debug, Clone, Default)]
+pub struct ResolveInputs {
+    pub env_data_dir: Option<String>,
+    pub env_portable: bool,
+    pub exe_dir: Option<PathBuf>,
+    pub user_config_dir: Option<PathBuf>,
+    pub platform_default: Option<PathBuf>,
+}
+
+impl ResolveInputs {
+    /// Gather inputs from the running process.
+    pub fn from_environment() -> Self {
+        Self {
+            env_data_dir: std::env::var(DATA_DIR_ENV).ok().filter(|s| !s.is_empty()),
+            env_portable: std::env::var(PORTABLE_ENV)
+                .map(|v| matches!(v.as_str(), "1" | "true" | "yes"))
+                .unwrap_or(false),
+            exe_dir: std::env::current_exe()
+                .ok()
+                .and_then(|p| p.parent().map(Path::to_path_buf)),
+            user_config_dir: dirs::config_dir().map(|p| p.join("handbox")),
+            platform_default: platform_data_dir(),
+        }
+    }
+}
+
+/// Resolve the data directory from the given inputs.
+pub fn resolve(inputs: &ResolveInputs) -> ResolvedDataDir {
+    let exe_config = inputs.exe_dir.as_ref().map(|d| d.join(CONFIG_FILE_NAME));
+    let user_config = inputs.user_config_dir.as_ref().map(|d| d.join(CONFIG_FILE_NAME));
+
+    let exe_cfg = exe_config.as_deref().filter(|p| p.exists()).map(AppConfig::load);
+    let user_cfg = user_config.as_deref().filter(|p| p.exists()).map(AppConfig::load);
+
+    // Portable installs keep their config next to the executable.
+    let portable = inputs.env_portable || exe_cfg.as_ref().is_some_and(|c| c.portable);
+    let config_path = if portable || exe_cfg.is_some() {
+        exe_config.clone()
+    } else {
+        user_config.clone()
+    }
+    .unwrap_or_else(|| PathBuf::from(CONFIG_FILE_NAME));
+
+    if let Some(ref dir) = inputs.env_data_dir {
+        return ResolvedDataDir {
+            path: PathBuf::from(dir),
+            source: DataDirSource::Environment,
+            config_path,
+        };
+    }
+
+    if portable {
+        if let Some(ref exe_dir) = inputs.exe_dir {
+            return ResolvedDataDir {
+                path: exe_dir.join("data"),
+                source: DataDirSource::Portable,
+                config_path,
+            };
+        }
+    }
+
+    if let Some(dir) = exe_cfg
+        .as_ref()
+        .and_then(|c| c.data_dir.clone())
+        .or_else(|| user_cfg.as_ref().and_then(|c| c.data_dir.clone()))
+    {
+        return ResolvedDataDir {
+            path: dir,
+            source: DataDirSource::ConfigFile,
+            config_path,
+        };
+    }
+
+    ResolvedDataDir {
+        path: inputs
+            .platform_default
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(".")),
+        source: DataDirSource::PlatformDefault,
+        config_path,
+    }
+}
+
+fn platform_data_dir() -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    {
+        std::env::var("APPDATA")
+            .ok()
+            .map(|p| PathBuf::from(p).join("Handbox"))
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        dirs::data_dir().map(|p| p.join("handbox"))
+    }
+}
+
+/// Config file that controls the given mode: portable installs must keep
+/// their config next to the executable to be detected.
+pub fn config_path_for(portable: bool, resolved: &ResolvedDataDir) -> PathBuf {
+    if portable {
+        if let Some(exe_dir) = ResolveInputs::from_environment().exe_dir {
+            return exe_dir.join(CONFIG_FILE_NAME);
+        }
+    }
+    resolved.config_path.clone()
+}
+
+static RESOLVED: OnceLock<ResolvedDataDir> = OnceLock::new();
+
+/// Resolve the data directory once per process, applying any pending migration.
+pub fn init() -> &'static ResolvedDataDir {
+    RESOLVED.get_or_init(|| {
+        let resolved = resolve(&ResolveInputs::from_environment());
+        let mut config = AppConfig::load(&resolved.config_path);
+        if let Some(from) = config.migrate_from.take() {
+            match migrate_data_dir(&from, &resolved.path) {
+                Ok(report) => {
+                    tracing::info!(
+                        "Migrated {} file(s) from {:?} to {:?}",
+                        report.files_moved,
+                        from,
+                        resolved.path
+                    );
+                    for path in &report.renamed {
+                        tracing::warn!(
+                            "Kept migrated {:?} beside an existing file of the same name",
+                            path
+                        );
+                    }
+                    if let Err(e) = config.save(&resolved.config_path) {
+                        tracing::warn!("Failed to clear pending migration: {e}");
+                    }
+                }
+                Err(e) => tracing::warn!("Data directory migration failed: {e}"),
+            }
+        }
+        resolved
+    })
+}
+
+/// The effective data directory for this process.
+pub fn data_dir() -> PathBuf {
+    init().path.clone()
+}
+
+/// Summary of a completed migration.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MigrationReport {
+    pub files_moved: usize,
+    pub bytes_moved: u64,
+    /// Where source files that clashed with an existing file in the target
+    /// were put instead (`<name>.migrated`, numbered if that is taken too).
+    pub renamed: Vec<PathBuf>,
+}
+
+/// Move everything under `from` into `to`, then remove `from`.
+///
+/// Files are copied first and the source is only deleted once every copy
+/// succeeded, so a failure part-way leaves the original data intact.
+/// Existing files in `to` are not overwritten: a source file whose name is
+/// taken is copied next to it under a new name and listed in the report.
+pub fn migrate_data_dir(from: &Path, to: &Path) -> Result<MigrationReport, String> {
+    if !from.exists() {
+        return Ok(MigrationReport::default());
+    }
+    if from == to {
+        return Ok(MigrationReport::default());
+    }
+    if to.starts_with(from) {
+        return Err(format!(
+            "Target {} is inside source {}",
+            to.display(),
+            from.display()
+        ));
+    }
+
+    let mut report = MigrationReport::default();
+    copy_tree(from, to, &mut report).map_err(|e| format!("Failed to copy data: {e}"))?;
+    fs::remove_dir_all(from).map_err(|e| format!("Copied data but failed to remove source: {e}"))?;
+    Ok(report)
+}
+
+fn copy_tree(src: &Path, dst: &Path, report: &mut MigrationReport) -> std::io::Result<()> {
+    fs::create_dir_all(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let src_path = entry.path();
+        let dst_path = dst.join(entry.file_name());
+        if src_path.is_dir() {
+            copy_tree(&src_path, &dst_path, report)?;
+            continue;
+        }
+        let dst_path = if dst_path.exists() {
+            let renamed = free_name(&dst_path);
+            report.renamed.push(renamed.clone());
+            renamed
+        } else {
+            dst_path
+        };
+        report.bytes_moved += fs::copy(&src_path, &dst_path)?;
+        report.files_moved += 1;
+    }
+    Ok(())
+}
+
+/// `<path>.migrated`, or `<path>.migrated-<n>` for the first free `n`.
+fn free_name(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let mut candidate = path.with_file_name(format!("{name}.migrated"));
+    let mut n = 2;
+    while candidate.exists() {
+        candidate = path.with_file_name(format!("{name}.migrated-{n}"));
+        n += 1;
+    }
+    candidate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("hb-config-{name}-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn env_override_wins() {
+        let exe = temp_dir("exe");
+        let resolved = resolve(&ResolveInputs {
+            env_data_dir: Some("/custom/data".into()),
+            env_portable: true,
+            exe_dir: Some(exe.clone()),
+            ..Default::default()
+        });
+        assert_eq!(resolved.source, DataDirSource::Environment);
+        assert_eq!(resolved.path, PathBuf::from("/custom/data"));
+        let _ = fs::remove_dir_all(exe);
+    }
+
+    #[test]
+    fn portable_config_keeps_data_next_to_exe() {
+        let exe = temp_dir("portable");
+        AppConfig { portable: true, ..Default::default() }
+            .save(&exe.join(CONFIG_FILE_NAME))
+            .unwrap();
+        let resolved = resolve(&ResolveInputs {
+            exe_dir: Some(exe.clone()),
+            platform_default: Some(PathBuf::from("/default")),
+            ..Default::default()
+        });
+        assert_eq!(resolved.source, DataDirSource::Portable);
+        assert_eq!(resolved.path, exe.join("data"));
+        assert_eq!(resolved.config_path, exe.join(CONFIG_FILE_NAME));
+        let _ = fs::remove_dir_all(exe);
+    }
+
+    #[test]
+    fn falls_back_to_platform_default() {
+        let resolved = resolve(&ResolveInputs {
+            platform_default: Some(PathBuf::from("/default")),
+            ..Default::default()
+        });
+        assert_eq!(resolved.source, DataDirSource::PlatformDefault);
+        assert_eq!(resolved.path, PathBuf::from("/default"));
+    }
+
+    #[test]
+    fn migration_moves_files() {
+        let root = temp_dir("migrate");
+        let from = root.join("old");
+        let to = root.join("new");
+        fs::create_dir_all(from.join("projects")).unwrap();
+        fs::write(from.join("traces.db"), b"db").unwrap();
+        fs::write(from.join("projects").join("plan.md"), b"plan").unwrap();
+
+        let report = migrate_data_dir(&from, &to).unwrap();
+        assert_eq!(report.files_moved, 2);
+        assert!(!from.exists());
+        assert_eq!(fs::read(to.join("projects").join("plan.md")).unwrap(), b"plan");
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn migration_keeps_both_sides_of_a_name_clash() {
+        let root = temp_dir("migrate-clash");
+        let from = root.join("old");
+        let to = root.join("new");
+        fs::create_dir_all(&from).unwrap();
+        fs::create_dir_all(&to).unwrap();
+        fs::write(from.join("traces.db"), b"old").unwrap();
+        fs::write(to.join("traces.db"), b"new").unwrap();
+        fs::write(to.join("traces.db.migrated"), b"earlier").unwrap();
+
+        let report = migrate_data_dir(&from, &to).unwrap();
+        assert_eq!(report.files_moved, 1);
+        assert_eq!(report.renamed, [to.join("traces.db.migrated-2")]);
+        assert!(!from.exists());
+        assert_eq!(fs::read(to.join("traces.db")).unwrap(), b"new");
+        assert_eq!(fs::read(to.join("traces.db.migrated")).unwrap(), b"earlier");
+        assert_eq!(fs::read(to.join("traces.db.migrated-2")).unwrap(), b"old");
+        let _ = fs::remove_dir_all(root);
+    }
+}
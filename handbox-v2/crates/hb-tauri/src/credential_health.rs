@@ -0,0 +1,342 @@
+//! Credential health — periodically validates the configured provider
+//! credentials with cheap calls (model listings, STS GetCallerIdentity) and
+//! reports when one expires or is rejected, before a scheduled run needs it.
+
+use crate::state::LLMCredentials;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// How often the background task re-validates credentials.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Delay before the first check after launch.
+pub const STARTUP_DELAY: Duration = Duration::from_secs(30);
+
+/// Event emitted with the entries whose state changed.
+pub const HEALTH_CHANGED_EVENT: &str = "credential-health-changed";
+
+/// Timeout for a single validation call.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthState {
+    Valid,
+    /// The provider refused the credential (revoked, wrong key, ...).
+    Rejected,
+    /// The credential was accepted once but has since expired.
+    Expired,
+    /// The provider could not be reached; the credential may still be fine.
+    Unreachable,
+}
+
+impl HealthState {
+    /// Whether runs using this credential are expected to fail.
+    pub fn is_failing(self) -> bool {
+        matches!(self, Self::Rejected | Self::Expired)
+    }
+}
+
+/// Latest validation result for one provider.
+#[derive(Debug, Clone, Serialize)]
+pub struct CredentialHealth {
+    /// "bedrock" | "openai" | "anthropic" | "local"
+    pub provider: String,
+    pub state: HealthState,
+    pub message: Option<String>,
+    pub checked_at: DateTime<Utc>,
+    /// When the provider entered its current state.
+    pub since: DateTime<Utc>,
+    pub latency_ms: u64,
+}
+
+impl CredentialHealth {
+    /// One-line description for notifications.
+    pub fn summary(&self) -> String {
+        let what = match self.state {
+            HealthState::Valid => "is working again",
+            HealthState::Rejected => "was rejected",
+            HealthState::Expired => "has expired",
+            HealthState::Unreachable => "could not be verified",
+        };
+        format!("{} credential {what}", self.provider)
+    }
+}
+
+/// Cached results, keyed by provider.
+#[derive(Debug, Default)]
+pub struct HealthCache {
+    entries: HashMap<String, CredentialHealth>,
+    /// Bumped by [`HealthCache::forget`], so a round of checks that started
+    /// before a credential changed can't write its stale results.
+    generation: u64,
+}
+
+impl HealthCache {
+    /// Cached entries, sorted by provider.
+    pub fn entries(&self) -> Vec<CredentialHealth> {
+        let mut entries: Vec<_> = self.entries.values().cloned().collect();
+        entries.sort_by(|a, b| a.provider.cmp(&b.provider));
+        entries
+    }
+
+    /// Current generation; pass it to [`HealthCache::update`] with the
+    /// results of checks started now.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Replace the cache with a full round of results and return the entries
+    /// whose state changed. Providers missing from `results` are no longer
+    /// configured and are dropped. Results of a round started at an older
+    /// `generation` are discarded.
+    pub fn update(
+        &mut self,
+        generation: u64,
+        results: Vec<CredentialHealth>,
+    ) -> Vec<CredentialHealth> {
+        if generation != self.generation {
+            return Vec::new();
+        }
+        let mut previous = std::mem::take(&mut self.entries);
+        let mut changed = Vec::new();
+        for mut health in results {
+            match previous.remove(&health.provider) {
+                Some(prev) if prev.state == health.state => health.since = prev.since,
+                // A first successful check is not news
+                None if health.state == HealthState::Valid => {}
+                _ => changed.push(health.clone()),
+            }
+            self.entries.insert(health.provider.clone(), health);
+        }
+        changed
+    }
+
+    /// Drop a provider's entry, e.g. after its credential was replaced.
+    pub fn forget(&mut self, provider: &str) {
+        self.entries.remove(provider);
+        self.generation += 1;
+    }
+
+    /// Drop every entry, e.g. after all credentials were cleared.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.generation += 1;
+    }
+}
+
+/// Providers whose credentials can be validated, in check order.
+const PROVIDERS: [&str; 4] = ["bedrock", "openai", "anthropic", "local"];
+
+/// Validate every configured provider.
+pub async fn check_all(creds: &LLMCredentials) -> Vec<CredentialHealth> {
+    let Some(client) = probe_client() else {
+        return vec![];
+    };
+    let mut results = Vec::new();
+    for provider in PROVIDERS {
+        results.extend(check_provider(&client, creds, provider).await);
+    }
+    results
+}
+
+/// Validate one provider; `None` when it has no credential configured.
+pub async fn check_one(creds: &LLMCredentials, provider: &str) -> Option<CredentialHealth> {
+    check_provider(&probe_client()?, creds, provider).await
+}
+
+fn probe_client() -> Option<reqwest::Client> {
+    match reqwest::Client::builder().timeout(PROBE_TIMEOUT).build() {
+        Ok(client) => Some(client),
+        Err(e) => {
+            tracing::warn!("Failed to build HTTP client for credential checks: {e}");
+            None
+        }
+    }
+}
+
+async fn check_provider(
+    client: &reqwest::Client,
+    creds: &LLMCredentials,
+    provider: &str,
+) -> Option<CredentialHealth> {
+    match provider {
+        "bedrock" => {
+            let (Some(access_key), Some(secret_key)) =
+                (&creds.aws_access_key_id, &creds.aws_secret_access_key)
+            else {
+                return None;
+            };
+            Some(probe(provider, check_aws(client, access_key, secret_key)).await)
+        }
+        "openai" => {
+            let key = creds.openai_api_key.as_ref()?;
+            let request = client
+                .get("https://api.openai.com/v1/models")
+                .bearer_auth(key);
+            Some(probe(provider, send(request)).await)
+        }
+        "anthropic" => {
+            let key = creds.anthropic_api_key.as_ref()?;
+            let request = client
+                .get("https://api.anthropic.com/v1/models")
+                .header("x-api-key", key)
+                .header("anthropic-version", "2023-06-01");
+            Some(probe(provider, send(request)).await)
+        }
+        "local" => {
+            let endpoint = creds.local_endpoint.as_ref()?;
+            let request = client.get(format!("{}/api/tags", endpoint.trim_end_matches('/')));
+            Some(probe(provider, send(request)).await)
+        }
+        _ => None,
+    }
+}
+
+/// Time a check and wrap its outcome.
+async fn probe(
+    provider: &str,
+    check: impl std::future::Future<Output = (HealthState, Option<String>)>,
+) -> CredentialHealth {
+    let start = Instant::now();
+    let (state, message) = check.await;
+    let now = Utc::now();
+    CredentialHealth {
+        provider: provider.to_string(),
+        state,
+        message,
+        checked_at: now,
+        since: now,
+        latency_ms: start.elapsed().as_millis() as u64,
+    }
+}
+
+async fn send(request: reqwest::RequestBuilder) -> (HealthState, Option<String>) {
+    match request.send().await {
+        Ok(resp) => {
+            let status = resp.status().as_u16();
+            let body = resp.text().await.unwrap_or_default();
+            classify_response(status, &body)
+        }
+        Err(e) => (HealthState::Unreachable, Some(e.to_string())),
+    }
+}
+
+/// STS GetCallerIdentity needs no permissions, so it only fails when the
+/// keys themselves are bad.
+async fn check_aws(
+    client: &reqwest::Client,
+    access_key: &str,
+    secret_key: &str,
+) -> (HealthState, Option<String>) {
+    let host = "sts.amazonaws.com";
+    let body = "Action=GetCallerIdentity&Version=2011-06-15";
+    let (authorization, amz_date, payload_hash, _) = crate::commands::llm::sign_aws_request(
+        access_key,
+        secret_key,
+        "us-east-1",
+        "sts",
+        "POST",
+        host,
+        "/",
+        body.as_bytes(),
+    );
+    let request = client
+        .post(format!("https://{host}/"))
+        .header("X-Amz-Date", amz_date)
+        .header("X-Amz-Content-Sha256", payload_hash)
+        .header("Authorization", authorization)
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(body);
+    send(request).await
+}
+
+/// Map a provider's HTTP response to a health state.
+fn classify_response(status: u16, body: &str) -> (HealthState, Option<String>) {
+    let detail = || {
+        let snippet: String = body.chars().take(200).collect();
+        Some(format!("HTTP {status}: {snippet}"))
+    };
+    match status {
+        200..=299 => (HealthState::Valid, None),
+        401 | 403 => {
+            let lower = body.to_lowercase();
+            if lower.contains("expired") {
+                (HealthState::Expired, detail())
+            } else {
+                (HealthState::Rejected, detail())
+            }
+        }
+        _ => (HealthState::Unreachable, detail()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn health(provider: &str, state: HealthState) -> CredentialHealth {
+        let now = Utc::now();
+        CredentialHealth {
+            provider: provider.into(),
+            state,
+            message: None,
+            checked_at: now,
+            since: now,
+            latency_ms: 0,
+        }
+    }
+
+    #[test]
+    fn classifies_auth_failures() {
+        assert_eq!(classify_response(200, "").0, HealthState::Valid);
+        assert_eq!(
+            classify_response(403, "<Code>ExpiredToken</Code>").0,
+            HealthState::Expired
+        );
+        assert_eq!(
+            classify_response(401, "{\"error\":\"invalid_api_key\"}").0,
+            HealthState::Rejected
+        );
+        assert_eq!(classify_response(503, "").0, HealthState::Unreachable);
+    }
+
+    #[test]
+    fn reports_only_state_changes() {
+        let mut cache = HealthCache::default();
+        let changed = cache.update(0, vec![
+            health("openai", HealthState::Valid),
+            health("bedrock", HealthState::Rejected),
+        ]);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].provider, "bedrock");
+        let since = cache.entries()[0].since;
+
+        let changed = cache.update(0, vec![
+            health("openai", HealthState::Expired),
+            health("bedrock", HealthState::Rejected),
+        ]);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].state, HealthState::Expired);
+        assert_eq!(cache.entries()[0].since, since);
+
+        cache.update(0, vec![health("openai", HealthState::Expired)]);
+        assert_eq!(cache.entries().len(), 1);
+    }
+
+    #[test]
+    fn late_rounds_do_not_overwrite_a_forgotten_provider() {
+        let mut cache = HealthCache::default();
+        let generation = cache.generation();
+        // The credential is replaced while the round is still running
+        cache.forget("openai");
+        let changed = cache.update(generation, vec![health("openai", HealthState::Rejected)]);
+        assert!(changed.is_empty());
+        assert!(cache.entries().is_empty());
+
+        cache.update(cache.generation(), vec![health("openai", HealthState::Valid)]);
+        assert_eq!(cache.entries().len(), 1);
+    }
+}
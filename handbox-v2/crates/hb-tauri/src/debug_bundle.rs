@@ -0,0 +1,238 @@
+//! Debug bundles — one zip per execution for attaching to bug reports.
+//!
+//! Everything written to a bundle passes through a [`Redactor`] first:
+//! known secret values (LLM credentials, workspace environment variables),
+//! values under secret-looking keys, common token formats and the user's
+//! home directory are replaced, so a bundle can be shared as is.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::io::Write;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Bumped when the bundle layout changes.
+pub const BUNDLE_VERSION: u32 = 1;
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Secret values shorter than this are not searched for; they would match
+/// ordinary text.
+const MIN_SECRET_LEN: usize = 6;
+
+/// Object keys whose values are always redacted (matched case-insensitively
+/// as substrings).
+const SECRET_KEYS: &[&str] = &[
+    "api_key",
+    "apikey",
+    "token",
+    "secret",
+    "password",
+    "authorization",
+    "credential",
+    "private_key",
+    "cookie",
+    "service_key",
+    "servicekey",
+];
+
+fn token_patterns() -> &'static [regex_lite::Regex] {
+    static PATTERNS: OnceLock<Vec<regex_lite::Regex>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        hb_compiler::publish::TOKEN_PATTERNS
+            .iter()
+            .map(|p| regex_lite::Regex::new(p).expect("valid pattern"))
+            .collect()
+    })
+}
+
+/// Replaces secrets in text and JSON, counting what it replaced.
+#[derive(Debug, Default)]
+pub struct Redactor {
+    secrets: Vec<String>,
+    /// Extra keys redacted like [`SECRET_KEYS`], lowercased.
+    keys: Vec<String>,
+    home: Option<String>,
+    pub redactions: usize,
+}
+
+impl Redactor {
+    pub fn new(secrets: impl IntoIterator<Item = String>, home: Option<&Path>) -> Self {
+        let mut secrets: Vec<String> = secrets
+            .into_iter()
+            .filter(|s| s.len() >= MIN_SECRET_LEN)
+            .collect();
+        // Longest first, so a secret containing another is replaced whole
+        secrets.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        secrets.dedup();
+        Self {
+            secrets,
+            keys: Vec::new(),
+            home: home
+                .map(|h| h.to_string_lossy().into_owned())
+                .filter(|h| h.len() > 1),
+            redactions: 0,
+        }
+    }
+
+    /// Also redact values under `keys`.
+    pub fn with_keys(mut self, keys: &[String]) -> Self {
+        self.keys = keys.iter().map(|k| k.to_lowercase()).filter(|k| !k.is_empty()).collect();
+        self
+    }
+
+    pub fn text(&mut self, text: &str) -> String {
+        let mut out = text.to_string();
+        for secret in &self.secrets {
+            let found = out.matches(secret.as_str()).count();
+            if found > 0 {
+                self.redactions += found;
+                out = out.replace(secret.as_str(), REDACTED);
+            }
+        }
+        for pattern in token_patterns() {
+            let found = pattern.find_iter(&out).count();
+            if found > 0 {
+                self.redactions += found;
+                out = pattern.replace_all(&out, REDACTED).into_owned();
+            }
+        }
+        if let Some(home) = &self.home {
+            out = out.replace(home.as_str(), "~");
+        }
+        out
+    }
+
+    pub fn value(&mut self, value: &mut Value) {
+        match value {
+            Value::String(s) => *s = self.text(s),
+            Value::Array(items) => items.iter_mut().for_each(|v| self.value(v)),
+            Value::Object(map) => {
+                for (key, v) in map.iter_mut() {
+                    let key = key.to_lowercase();
+                    let secret_key = SECRET_KEYS.iter().any(|k| key.contains(k))
+                        || self.keys.iter().any(|k| key.contains(k.as_str()));
+                    // Only strings: `max_tokens` and the like stay readable
+                    if secret_key && v.as_str().is_some_and(|s| !s.is_empty()) {
+                        self.redactions += 1;
+                        *v = Value::String(REDACTED.into());
+                    } else {
+                        self.value(v);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Serialize `data` as pretty JSON with secrets redacted.
+    pub fn json<T: Serialize>(&mut self, data: &T) -> Result<Vec<u8>, String> {
+        let mut value = serde_json::to_value(data).map_err(|e| e.to_string())?;
+        self.value(&mut value);
+        serde_json::to_vec_pretty(&value).map_err(|e| e.to_string())
+    }
+}
+
+/// Describes the bundle; written as `manifest.json`.
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleManifest {
+    pub bundle_version: u32,
+    pub app_version: String,
+    pub execution_id: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub files: Vec<String>,
+    /// Values replaced by the redactor.
+    pub redactions: usize,
+}
+
+/// Write `files` (name, content) and a manifest to a zip at `path`.
+pub fn write_bundle(
+    path: &Path,
+    mut manifest: BundleManifest,
+    files: &[(String, Vec<u8>)],
+) -> Result<BundleManifest, String> {
+    manifest.files = files.iter().map(|(name, _)| name.clone()).collect();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let file = std::fs::File::create(path)
+        .map_err(|e| format!("Failed to create {}: {e}", path.display()))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    let manifest_json = serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?;
+    let entries = std::iter::once(("manifest.json", manifest_json.as_slice())).chain(
+        files
+            .iter()
+            .map(|(name, data)| (name.as_str(), data.as_slice())),
+    );
+    for (name, data) in entries {
+        zip.start_file(name, options).map_err(|e| e.to_string())?;
+        zip.write_all(data).map_err(|e| e.to_string())?;
+    }
+    zip.finish().map_err(|e| e.to_string())?;
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn redacts_known_secrets_keys_tokens_and_home() {
+        let mut redactor = Redactor::new(
+            ["my-db-password-42".to_string(), "abc".to_string()],
+            Some(Path::new("/home/kim")),
+        );
+        let mut value = json!({
+            "config": {
+                "api_key": "anything",
+                "Authorization": "Basic xyz",
+                "token_limit_note": "",
+                "max_tokens": 512,
+                "path": "/home/kim/data/in.csv",
+                "dsn": "postgres://app:my-db-password-42@db/prod",
+                "model": "abc-large",
+            },
+            "error": "401 for sk-abcdefghijklmnop1234 with Bearer eyJhbGciOi.payload",
+        });
+        redactor.value(&mut value);
+
+        assert_eq!(value["config"]["api_key"], REDACTED);
+        assert_eq!(value["config"]["Authorization"], REDACTED);
+        // Empty and non-string values under secret keys are left alone
+        assert_eq!(value["config"]["token_limit_note"], "");
+        assert_eq!(value["config"]["max_tokens"], 512);
+        assert_eq!(value["config"]["path"], "~/data/in.csv");
+        assert_eq!(value["config"]["dsn"], "postgres://app:[REDACTED]@db/prod");
+        // Short "secrets" are ignored
+        assert_eq!(value["config"]["model"], "abc-large");
+        assert_eq!(value["error"], "401 for [REDACTED] with [REDACTED]");
+        assert_eq!(redactor.redactions, 5);
+    }
+
+    #[test]
+    fn bundle_lists_its_files() {
+        let dir = std::env::temp_dir().join(format!("hb-bundle-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("bundle.zip");
+        let manifest = BundleManifest {
+            bundle_version: BUNDLE_VERSION,
+            app_version: "0.0.0".into(),
+            execution_id: "x".into(),
+            created_at: chrono::Utc::now(),
+            files: vec![],
+            redactions: 0,
+        };
+        let files = vec![("spans.json".to_string(), b"[]".to_vec())];
+        let manifest = write_bundle(&path, manifest, &files).unwrap();
+        assert_eq!(manifest.files, ["spans.json"]);
+
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let names: Vec<_> = archive.file_names().map(str::to_string).collect();
+        assert_eq!(names.len(), 2);
+        assert!(archive.by_name("manifest.json").is_ok());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,442 @@
+//! Doctor — environment diagnostics with remediation hints.
+//!
+//! Used by the settings UI (`run_doctor` command) and the CLI (`handbox --doctor`).
+
+use crate::state::LLMCredentials;
+use serde::Serialize;
+use std::path::Path;
+use std::time::Duration;
+
+/// Timeout for external probes (process spawns, TCP connects).
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// SQLite databases kept in the data directory.
+const DATABASES: &[&str] = &["traces.db", "projects.db", "cache.db"];
+
+/// Port used by the frontend dev server in debug builds.
+#[cfg(debug_assertions)]
+const DEV_SERVER_PORT: u16 = 5173;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Ok,
+    Info,
+    Warning,
+    Error,
+}
+
+/// A single diagnostic result.
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    /// Stable identifier, e.g. "data_dir.writable".
+    pub id: String,
+    pub category: String,
+    pub severity: Severity,
+    pub message: String,
+    /// What the user can do about it (absent when nothing is wrong).
+    pub remediation: Option<String>,
+}
+
+impl Finding {
+    fn new(id: &str, category: &str, severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            category: category.into(),
+            severity,
+            message: message.into(),
+            remediation: None,
+        }
+    }
+
+    fn fix(mut self, remediation: impl Into<String>) -> Self {
+        self.remediation = Some(remediation.into());
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DoctorReport {
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+    /// Worst severity across all findings.
+    pub overall: Severity,
+    pub findings: Vec<Finding>,
+}
+
+/// Run every check and collect the findings.
+pub async fn run(data_dir: &Path, credentials: &LLMCredentials) -> DoctorReport {
+    let mut findings = vec![check_data_dir_writable(data_dir)];
+    findings.extend(
+        DATABASES
+            .iter()
+            .map(|db| check_sqlite_integrity(&data_dir.join(db))),
+    );
+    findings.push(check_python().await);
+    findings.push(check_docker().await);
+    findings.extend(check_credentials(credentials).await);
+    findings.extend(check_ports().await);
+
+    DoctorReport {
+        generated_at: chrono::Utc::now(),
+        overall: findings
+            .iter()
+            .map(|f| f.severity)
+            .max()
+            .unwrap_or(Severity::Ok),
+        findings,
+    }
+}
+
+/// Verify the data directory exists (or can be created) and accepts writes.
+pub fn check_data_dir_writable(data_dir: &Path) -> Finding {
+    const ID: &str = "data_dir.writable";
+    if let Err(e) = std::fs::create_dir_all(data_dir) {
+        return Finding::new(ID, "storage", Severity::Error, format!(
+            "Cannot create data directory {}: {e}", data_dir.display()
+        ))
+        .fix("Choose a different data directory in Settings, or set HANDBOX_DATA_DIR / enable portable mode.");
+    }
+    let probe = data_dir.join(".handbox-doctor-probe");
+    match std::fs::write(&probe, b"ok").and_then(|_| std::fs::remove_file(&probe)) {
+        Ok(()) => Finding::new(
+            ID,
+            "storage",
+            Severity::Ok,
+            format!("Data directory {} is writable", data_dir.display()),
+        ),
+        Err(e) => Finding::new(
+            ID,
+            "storage",
+            Severity::Error,
+            format!("Data directory {} is not writable: {e}", data_dir.display()),
+        )
+        .fix("Check folder permissions or move the data directory (Settings → Data directory)."),
+    }
+}
+
+/// Run `PRAGMA integrity_check` on a SQLite database (missing files are fine).
+pub fn check_sqlite_integrity(path: &Path) -> Finding {
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("database");
+    let id = format!("sqlite.{name}");
+    if !path.exists() {
+        return Finding::new(
+            &id,
+            "storage",
+            Severity::Info,
+            format!("{name} not created yet"),
+        );
+    }
+    let result =
+        rusqlite::Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .and_then(|conn| {
+                conn.query_row("PRAGMA integrity_check", [], |row| row.get::<_, String>(0))
+            });
+
+    match result {
+        Ok(ref s) if s == "ok" => Finding::new(
+            &id,
+            "storage",
+            Severity::Ok,
+            format!("{name} passed integrity check"),
+        ),
+        Ok(s) => Finding::new(
+            &id,
+            "storage",
+            Severity::Error,
+            format!("{name} failed integrity check: {s}"),
+        )
+        .fix(format!(
+            "Quit Handbox, back up and remove {} — it will be recreated on next launch.",
+            path.display()
+        )),
+        Err(e) => Finding::new(
+            &id,
+            "storage",
+            Severity::Error,
+            format!("Cannot open {name}: {e}"),
+        )
+        .fix("Make sure no other Handbox instance is running, then retry."),
+    }
+}
+
+/// Run a command and return the first line of its output.
+async fn probe_version(program: &str, args: &[&str]) -> Option<String> {
+    let output = tokio::time::timeout(
+        PROBE_TIMEOUT,
+        tokio::process::Command::new(program)
+            .args(args)
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await
+    .ok()?
+    .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // Python 2 prints its version on stderr.
+    let text = if output.stdout.is_empty() {
+        output.stderr
+    } else {
+        output.stdout
+    };
+    String::from_utf8_lossy(&text)
+        .lines()
+        .next()
+        .map(|l| l.trim().to_string())
+}
+
+async fn check_python() -> Finding {
+    const ID: &str = "runtime.python";
+    for program in ["python3", "python"] {
+        if let Some(version) = probe_version(program, &["--version"]).await {
+            return Finding::new(
+                ID,
+                "runtime",
+                Severity::Ok,
+                format!("{version} ({program})"),
+            );
+        }
+    }
+    Finding::new(ID, "runtime", Severity::Warning, "Python was not found on PATH")
+        .fix("Install Python 3.10+ and make sure `python3` (or `python` on Windows) is on PATH; packs with a Python runtime need it.")
+}
+
+async fn check_docker() -> Finding {
+    const ID: &str = "runtime.docker";
+    match probe_version("docker", &["--version"]).await {
+        None => Finding::new(
+            ID,
+            "runtime",
+            Severity::Info,
+            "Docker was not found on PATH",
+        )
+        .fix("Install Docker Desktop if you use packs with a Docker runtime."),
+        Some(version) => {
+            match probe_version("docker", &["info", "--format", "{{.ServerVersion}}"]).await {
+                Some(_) => Finding::new(ID, "runtime", Severity::Ok, version),
+                None => Finding::new(
+                    ID,
+                    "runtime",
+                    Severity::Warning,
+                    format!("{version} is installed but the daemon is not reachable"),
+                )
+                .fix("Start Docker Desktop (or the docker service) and retry."),
+            }
+        }
+    }
+}
+
+/// Try a TCP connection to `host:port`.
+async fn tcp_reachable(host: &str, port: u16) -> bool {
+    matches!(
+        tokio::time::timeout(PROBE_TIMEOUT, tokio::net::TcpStream::connect((host, port))).await,
+        Ok(Ok(_))
+    )
+}
+
+/// Check that credentials exist for each configured provider and that its
+/// endpoint is reachable over the network. No billable API calls are made.
+async fn check_credentials(creds: &LLMCredentials) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    let bedrock = creds.aws_access_key_id.is_some() || creds.aws_secret_access_key.is_some();
+    if bedrock {
+        let id = "credentials.bedrock";
+        if creds.aws_access_key_id.is_none() || creds.aws_secret_access_key.is_none() {
+            findings.push(Finding::new(id, "credentials", Severity::Error, "AWS credentials are incomplete")
+                .fix("Enter both the Access Key ID and Secret Access Key in Settings → LLM providers."));
+        } else {
+            let region = creds
+                .bedrock_region
+                .clone()
+                .unwrap_or_else(|| "us-east-1".into());
+            let host = format!("bedrock-runtime.{region}.amazonaws.com");
+            findings.push(endpoint_finding(id, "AWS Bedrock", &host, 443).await);
+        }
+    }
+    if creds.openai_api_key.is_some() {
+        findings
+            .push(endpoint_finding("credentials.openai", "OpenAI", "api.openai.com", 443).await);
+    }
+    if creds.anthropic_api_key.is_some() {
+        findings.push(
+            endpoint_finding(
+                "credentials.anthropic",
+                "Anthropic",
+                "api.anthropic.com",
+                443,
+            )
+            .await,
+        );
+    }
+    if let Some(ref endpoint) = creds.local_endpoint {
+        let id = "credentials.local";
+        match url::Url::parse(endpoint) {
+            Ok(u) => {
+                let host = u.host_str().unwrap_or("localhost").to_string();
+                let port = u.port_or_known_default().unwrap_or(80);
+                let mut finding = endpoint_finding(id, "Local LLM server", &host, port).await;
+                if finding.severity != Severity::Ok {
+                    finding.remediation = Some(format!(
+                        "Start your local model server (e.g. `ollama serve`) or update the endpoint ({endpoint})."
+                    ));
+                }
+                findings.push(finding);
+            }
+            Err(e) => findings.push(
+                Finding::new(
+                    id,
+                    "credentials",
+                    Severity::Error,
+                    format!("Invalid local endpoint '{endpoint}': {e}"),
+                )
+                .fix("Use a full URL such as http://localhost:11434."),
+            ),
+        }
+    }
+
+    if let Some(ref active) = creds.active_provider {
+        let configured = match active.as_str() {
+            "bedrock" => bedrock,
+            "openai" => creds.openai_api_key.is_some(),
+            "anthropic" => creds.anthropic_api_key.is_some(),
+            "local" => creds.local_endpoint.is_some(),
+            "llamacpp" => {
+                findings.push(llama_server_finding().await);
+                let ready = crate::local_models::list_models()
+                    .await
+                    .is_ok_and(|models| models.iter().any(|m| m.error.is_none()));
+                if !ready {
+                    findings.push(
+                        Finding::new(
+                            "credentials.llamacpp",
+                            "credentials",
+                            Severity::Error,
+                            "No readable GGUF model is installed for llama.cpp",
+                        )
+                        .fix("Download a model in Settings → Local models."),
+                    );
+                }
+                // The findings above say what is missing
+                true
+            }
+            _ => false,
+        };
+        if !configured {
+            findings.push(
+                Finding::new(
+                    "credentials.active",
+                    "credentials",
+                    Severity::Error,
+                    format!("Active provider '{active}' has no credentials configured"),
+                )
+                .fix("Configure credentials for the active provider or pick a different one."),
+            );
+        }
+    } else if findings.is_empty() {
+        findings.push(
+            Finding::new(
+                "credentials.none",
+                "credentials",
+                Severity::Warning,
+                "No LLM provider is configured",
+            )
+            .fix("Add an API key or local endpoint in Settings → LLM providers."),
+        );
+    }
+
+    findings
+}
+
+async fn llama_server_finding() -> Finding {
+    let id = "credentials.llama_server";
+    match crate::local_models::server_version().await {
+        Ok(version) => Finding::new(
+            id,
+            "credentials",
+            Severity::Ok,
+            format!("llama-server is installed {version}").trim_end().to_string(),
+        ),
+        Err(e) => Finding::new(id, "credentials", Severity::Error, e)
+            .fix("Install llama.cpp and put llama-server on PATH, or set LLAMA_SERVER_PATH."),
+    }
+}
+
+async fn endpoint_finding(id: &str, label: &str, host: &str, port: u16) -> Finding {
+    if tcp_reachable(host, port).await {
+        Finding::new(
+            id,
+            "credentials",
+            Severity::Ok,
+            format!("{label} endpoint {host}:{port} is reachable"),
+        )
+    } else {
+        Finding::new(
+            id,
+            "credentials",
+            Severity::Warning,
+            format!("{label} endpoint {host}:{port} is not reachable"),
+        )
+        .fix("Check your network connection, proxy, or firewall settings.")
+    }
+}
+
+/// Check the ports Handbox's local servers rely on.
+async fn check_ports() -> Vec<Finding> {
+    #[cfg(debug_assertions)]
+    {
+        // In dev builds the frontend server should already be listening here;
+        // if nothing is, the window will stay blank.
+        if tcp_reachable("127.0.0.1", DEV_SERVER_PORT).await {
+            vec![Finding::new(
+                "ports.dev_server",
+                "ports",
+                Severity::Ok,
+                format!("Dev server is listening on port {DEV_SERVER_PORT}"),
+            )]
+        } else {
+            vec![Finding::new("ports.dev_server", "ports", Severity::Warning, format!(
+                "Nothing is listening on dev server port {DEV_SERVER_PORT}"
+            ))
+            .fix("Run `npm run dev` in the frontend directory, or free the port if another app holds it.")]
+        }
+    }
+    #[cfg(not(debug_assertions))]
+    {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writable_data_dir_is_ok() {
+        let dir = std::env::temp_dir().join(format!("hb-doctor-{}", uuid::Uuid::new_v4()));
+        let finding = check_data_dir_writable(&dir);
+        assert_eq!(finding.severity, Severity::Ok);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn corrupt_database_is_reported() {
+        let dir = std::env::temp_dir().join(format!("hb-doctor-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db = dir.join("traces.db");
+        std::fs::write(&db, b"definitely not sqlite").unwrap();
+
+        let finding = check_sqlite_integrity(&db);
+        assert_eq!(finding.severity, Severity::Error);
+        assert!(finding.remediation.is_some());
+        assert_eq!(
+            check_sqlite_integrity(&dir.join("missing.db")).severity,
+            Severity::Info
+        );
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,1045 @@
+//! Local GGUF models — a managed model directory, download with checksum
+//! verification, memory requirement checks, and a llama.cpp `llama-server`
+//! process that serves the selected model over its OpenAI-compatible API.
+//!
+//! This is the "llamacpp" LLM provider, for machines where Ollama can't be
+//! installed: the only requirement is the single `llama-server` binary.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::{BufReader, Read, Seek};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Context window the server is started with and memory is estimated for.
+pub const CONTEXT_SIZE: u64 = 4096;
+
+/// Scratch buffers llama.cpp allocates on top of weights and KV cache.
+const COMPUTE_OVERHEAD: u64 = 512 * 1024 * 1024;
+
+/// How long a model may take to load before the server is given up on.
+const LOAD_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// How long an `nvidia-smi` reading is reused.
+const GPU_PROBE_TTL: Duration = Duration::from_secs(30);
+
+/// Environment variable the llm-chat tool finds the running server's base
+/// URL in.
+pub const ENDPOINT_ENV: &str = "LLAMACPP_ENDPOINT";
+
+const GGUF_MAGIC: &[u8; 4] = b"GGUF";
+
+/// Directory holding downloaded `.gguf` files.
+pub fn models_dir() -> PathBuf {
+    crate::config::data_dir().join("models")
+}
+
+// ============================================================
+// GGUF header
+// ============================================================
+
+/// Metadata read from a GGUF file header.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GgufInfo {
+    pub version: u32,
+    pub tensor_count: u64,
+    pub architecture: Option<String>,
+    pub name: Option<String>,
+    /// Quantization, e.g. "Q4_K_M".
+    pub file_type: Option<String>,
+    pub context_length: Option<u64>,
+    pub block_count: Option<u64>,
+    pub embedding_length: Option<u64>,
+    pub head_count: Option<u64>,
+    pub head_count_kv: Option<u64>,
+}
+
+/// Read the metadata section of a GGUF file. Tensor data is not touched.
+pub fn read_gguf_info(path: &Path) -> Result<GgufInfo, String> {
+    let file =
+        std::fs::File::open(path).map_err(|e| format!("Cannot open {}: {e}", path.display()))?;
+    parse_gguf(&mut BufReader::new(file))
+}
+
+fn parse_gguf<R: Read + Seek>(r: &mut R) -> Result<GgufInfo, String> {
+    let mut magic = [0u8; 4];
+    r.read_exact(&mut magic)
+        .map_err(|_| "File too short for a GGUF header")?;
+    if &magic != GGUF_MAGIC {
+        return Err("Not a GGUF file (bad magic)".into());
+    }
+    let version = read_u32(r)?;
+    if version < 2 {
+        return Err(format!(
+            "Unsupported GGUF version {version}; re-download a current build of the model"
+        ));
+    }
+    let tensor_count = read_u64(r)?;
+    let kv_count = read_u64(r)?;
+
+    let mut numbers: HashMap<String, u64> = HashMap::new();
+    let mut strings: HashMap<String, String> = HashMap::new();
+    for _ in 0..kv_count {
+        let key = read_string(r)?;
+        let value_type = read_u32(r)?;
+        match value_type {
+            8 => {
+                strings.insert(key, read_string(r)?);
+            }
+            9 => skip_array(r)?,
+            t => {
+                if let Some(n) = read_scalar(r, t)? {
+                    numbers.insert(key, n);
+                }
+            }
+        }
+    }
+
+    let architecture = strings.get("general.architecture").cloned();
+    let arch_key = |suffix: &str| {
+        architecture
+            .as_ref()
+            .and_then(|arch| numbers.get(&format!("{arch}.{suffix}")).copied())
+    };
+    Ok(GgufInfo {
+        version,
+        tensor_count,
+        name: strings.get("general.name").cloned(),
+        file_type: numbers.get("general.file_type").map(|&t| file_type_name(t)),
+        context_length: arch_key("context_length"),
+        block_count: arch_key("block_count"),
+        embedding_length: arch_key("embedding_length"),
+        head_count: arch_key("attention.head_count"),
+        head_count_kv: arch_key("attention.head_count_kv"),
+        architecture,
+    })
+}
+
+fn read_u32<R: Read>(r: &mut R) -> Result<u32, String> {
+    let mut buf = [0u8; 4];
+    r.read_exact(&mut buf)
+        .map_err(|_| "Truncated GGUF header")?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64<R: Read>(r: &mut R) -> Result<u64, String> {
+    let mut buf = [0u8; 8];
+    r.read_exact(&mut buf)
+        .map_err(|_| "Truncated GGUF header")?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_string<R: Read>(r: &mut R) -> Result<String, String> {
+    let len = read_u64(r)?;
+    if len > 1 << 24 {
+        return Err("Corrupt GGUF header (string too long)".into());
+    }
+    let mut buf = vec![0u8; len as usize];
+    r.read_exact(&mut buf)
+        .map_err(|_| "Truncated GGUF header")?;
+    Ok(String::from_utf8_lossy(&buf).into_owned())
+}
+
+/// Size in bytes of a fixed-size GGUF value type.
+fn scalar_size(value_type: u32) -> Result<u64, String> {
+    match value_type {
+        0 | 1 | 7 => Ok(1),
+        2 | 3 => Ok(2),
+        4..=6 => Ok(4),
+        10..=12 => Ok(8),
+        t => Err(format!("Corrupt GGUF header (unknown value type {t})")),
+    }
+}
+
+/// Read a scalar; integers are returned, floats and negatives are skipped.
+fn read_scalar<R: Read>(r: &mut R, value_type: u32) -> Result<Option<u64>, String> {
+    let size = scalar_size(value_type)? as usize;
+    let mut buf = [0u8; 8];
+    r.read_exact(&mut buf[..size])
+        .map_err(|_| "Truncated GGUF header")?;
+    let unsigned = u64::from_le_bytes(buf);
+    Ok(match value_type {
+        0 | 2 | 4 | 10 | 7 => Some(unsigned),
+        1 => u64::try_from(buf[0] as i8).ok(),
+        3 => u64::try_from(i16::from_le_bytes([buf[0], buf[1]])).ok(),
+        5 => u64::try_from(i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]])).ok(),
+        11 => u64::try_from(unsigned as i64).ok(),
+        _ => None,
+    })
+}
+
+/// Arrays (tokenizer vocabularies etc.) are not needed and can be large.
+fn skip_array<R: Read + Seek>(r: &mut R) -> Result<(), String> {
+    let item_type = read_u32(r)?;
+    let len = read_u64(r)?;
+    match item_type {
+        8 => {
+            for _ in 0..len {
+                let n = read_u64(r)?;
+                skip(r, n)?;
+            }
+        }
+        9 => {
+            for _ in 0..len {
+                skip_array(r)?;
+            }
+        }
+        t => skip(r, scalar_size(t)?.saturating_mul(len))?,
+    }
+    Ok(())
+}
+
+fn skip<R: Seek>(r: &mut R, n: u64) -> Result<(), String> {
+    let n = i64::try_from(n).map_err(|_| "Corrupt GGUF header (length overflow)")?;
+    r.seek(std::io::SeekFrom::Current(n))
+        .map(|_| ())
+        .map_err(|_| "Truncated GGUF header".into())
+}
+
+fn file_type_name(file_type: u64) -> String {
+    let name = match file_type {
+        0 => "F32",
+        1 => "F16",
+        2 => "Q4_0",
+        3 => "Q4_1",
+        7 => "Q8_0",
+        8 => "Q5_0",
+        9 => "Q5_1",
+        10 => "Q2_K",
+        11 => "Q3_K_S",
+        12 => "Q3_K_M",
+        13 => "Q3_K_L",
+        14 => "Q4_K_S",
+        15 => "Q4_K_M",
+        16 => "Q5_K_S",
+        17 => "Q5_K_M",
+        18 => "Q6_K",
+        32 => "BF16",
+        other => return format!("type {other}"),
+    };
+    name.to_string()
+}
+
+// ============================================================
+// Memory requirements
+// ============================================================
+
+/// Memory available to run models.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SystemMemory {
+    pub total_ram_bytes: Option<u64>,
+    pub available_ram_bytes: Option<u64>,
+    pub gpus: Vec<GpuMemory>,
+    /// GPU and CPU share RAM (Apple Silicon).
+    pub unified_memory: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GpuMemory {
+    pub name: String,
+    pub total_bytes: u64,
+    pub free_bytes: u64,
+}
+
+/// Where a model can run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Fit {
+    /// Fully offloaded to a GPU.
+    Gpu,
+    /// In system RAM on the CPU.
+    Cpu,
+    Insufficient,
+    /// Free memory could not be determined.
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MemoryCheck {
+    pub weights_bytes: u64,
+    pub kv_cache_bytes: u64,
+    pub required_bytes: u64,
+    pub fit: Fit,
+    pub message: String,
+}
+
+/// Estimate the memory needed to serve a model with [`CONTEXT_SIZE`] tokens
+/// of context: the weights as stored, an f16 KV cache, and compute buffers.
+pub fn memory_check(
+    size_bytes: u64,
+    info: Option<&GgufInfo>,
+    system: &SystemMemory,
+) -> MemoryCheck {
+    let kv_cache_bytes = info.map_or(0, |info| {
+        let (Some(layers), Some(embd), Some(heads)) =
+            (info.block_count, info.embedding_length, info.head_count)
+        else {
+            return 0;
+        };
+        let kv_heads = info.head_count_kv.unwrap_or(heads);
+        let kv_width = (embd * kv_heads).checked_div(heads).unwrap_or(embd);
+        let ctx = info
+            .context_length
+            .map_or(CONTEXT_SIZE, |c| c.min(CONTEXT_SIZE));
+        2 * layers * ctx * kv_width * 2
+    });
+    let required_bytes = size_bytes + kv_cache_bytes + COMPUTE_OVERHEAD;
+
+    let gpu_free = system.gpus.iter().map(|g| g.free_bytes).max();
+    let ram_free = system.available_ram_bytes.or(system.total_ram_bytes);
+    let gib = |b: u64| b as f64 / (1024.0 * 1024.0 * 1024.0);
+    let (fit, message) = if gpu_free.is_some_and(|free| free >= required_bytes) {
+        (
+            Fit::Gpu,
+            format!("Needs {:.1} GiB; fits in GPU memory", gib(required_bytes)),
+        )
+    } else if let Some(free) = ram_free {
+        if free >= required_bytes {
+            let place = if system.unified_memory {
+                "unified memory"
+            } else {
+                "RAM (CPU inference)"
+            };
+            (
+                Fit::Cpu,
+                format!("Needs {:.1} GiB; fits in {place}", gib(required_bytes)),
+            )
+        } else {
+            (
+                Fit::Insufficient,
+                format!(
+                    "Needs {:.1} GiB but only {:.1} GiB is free; choose a smaller quantization",
+                    gib(required_bytes),
+                    gib(free)
+                ),
+            )
+        }
+    } else {
+        (
+            Fit::Unknown,
+            format!("Needs about {:.1} GiB", gib(required_bytes)),
+        )
+    };
+
+    MemoryCheck {
+        weights_bytes: size_bytes,
+        kv_cache_bytes,
+        required_bytes,
+        fit,
+        message,
+    }
+}
+
+/// Probe RAM and NVIDIA GPU memory. Missing tools just leave fields empty.
+pub async fn system_memory() -> SystemMemory {
+    let mut memory = SystemMemory {
+        unified_memory: cfg!(all(target_os = "macos", target_arch = "aarch64")),
+        ..Default::default()
+    };
+
+    #[cfg(target_os = "linux")]
+    if let Ok(meminfo) = tokio::fs::read_to_string("/proc/meminfo").await {
+        let field = |name: &str| {
+            meminfo
+                .lines()
+                .find(|l| l.starts_with(name))
+                .and_then(|l| l.split_whitespace().nth(1))
+                .and_then(|kb| kb.parse::<u64>().ok())
+                .map(|kb| kb * 1024)
+        };
+        memory.total_ram_bytes = field("MemTotal:");
+        memory.available_ram_bytes = field("MemAvailable:");
+    }
+    #[cfg(target_os = "macos")]
+    if let Some(out) = command_output("sysctl", &["-n", "hw.memsize"]).await {
+        memory.total_ram_bytes = out.trim().parse().ok();
+    }
+    #[cfg(target_os = "windows")]
+    if let Some(out) = command_output(
+        "powershell",
+        &[
+            "-NoProfile",
+            "-Command",
+            "$os = Get-CimInstance Win32_OperatingSystem; \"$($os.TotalVisibleMemorySize) $($os.FreePhysicalMemory)\"",
+        ],
+    )
+    .await
+    {
+        let mut kb = out.split_whitespace().filter_map(|v| v.parse::<u64>().ok());
+        memory.total_ram_bytes = kb.next().map(|v| v * 1024);
+        memory.available_ram_bytes = kb.next().map(|v| v * 1024);
+    }
+
+    memory.gpus = gpu_memory().await;
+    memory
+}
+
+/// Latest `nvidia-smi` reading, shared by every caller for
+/// [`GPU_PROBE_TTL`]: listing models probes once, not once per model.
+static GPU_PROBE: std::sync::Mutex<Option<(tokio::time::Instant, Vec<GpuMemory>)>> =
+    std::sync::Mutex::new(None);
+
+/// NVIDIA GPUs and their free memory; none without `nvidia-smi`.
+async fn gpu_memory() -> Vec<GpuMemory> {
+    if let Ok(probe) = GPU_PROBE.lock() {
+        if let Some((_, gpus)) = probe.as_ref().filter(|(at, _)| at.elapsed() < GPU_PROBE_TTL) {
+            return gpus.clone();
+        }
+    }
+    let mut gpus = Vec::new();
+    if let Some(out) = command_output(
+        "nvidia-smi",
+        &[
+            "--query-gpu=name,memory.total,memory.free",
+            "--format=csv,noheader,nounits",
+        ],
+    )
+    .await
+    {
+        for line in out.lines() {
+            let parts: Vec<&str> = line.split(',').map(str::trim).collect();
+            if let [name, total, free] = parts[..] {
+                if let (Ok(total), Ok(free)) = (total.parse::<u64>(), free.parse::<u64>()) {
+                    gpus.push(GpuMemory {
+                        name: name.to_string(),
+                        total_bytes: total * 1024 * 1024,
+                        free_bytes: free * 1024 * 1024,
+                    });
+                }
+            }
+        }
+    }
+    if let Ok(mut probe) = GPU_PROBE.lock() {
+        *probe = Some((tokio::time::Instant::now(), gpus.clone()));
+    }
+    gpus
+}
+
+async fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = tokio::time::timeout(
+        Duration::from_secs(10),
+        tokio::process::Command::new(program)
+            .args(args)
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await
+    .ok()?
+    .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// ============================================================
+// Model directory
+// ============================================================
+
+/// Download provenance stored next to a model as `<file>.meta.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ModelMeta {
+    source_url: String,
+    sha256: String,
+    downloaded_at: DateTime<Utc>,
+}
+
+/// An installed model file.
+#[derive(Debug, Clone, Serialize)]
+pub struct LocalModel {
+    /// File name, used as the model id.
+    pub id: String,
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    pub source_url: Option<String>,
+    pub sha256: Option<String>,
+    pub info: Option<GgufInfo>,
+    /// Why the header could not be read.
+    pub error: Option<String>,
+    pub memory: MemoryCheck,
+}
+
+/// Installed models, sorted by file name.
+pub async fn list_models() -> Result<Vec<LocalModel>, String> {
+    let dir = models_dir();
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+    let system = system_memory().await;
+    let mut models = Vec::new();
+    let entries =
+        std::fs::read_dir(&dir).map_err(|e| format!("Cannot read {}: {e}", dir.display()))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("gguf") {
+            continue;
+        }
+        models.push(describe(&path, &system));
+    }
+    models.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(models)
+}
+
+fn describe(path: &Path, system: &SystemMemory) -> LocalModel {
+    let id = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+    let size_bytes = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    let meta = read_meta(path);
+    let (info, error) = match read_gguf_info(path) {
+        Ok(info) => (Some(info), None),
+        Err(e) => (None, Some(e)),
+    };
+    LocalModel {
+        memory: memory_check(size_bytes, info.as_ref(), system),
+        id,
+        path: path.to_path_buf(),
+        size_bytes,
+        source_url: meta.as_ref().map(|m| m.source_url.clone()),
+        sha256: meta.map(|m| m.sha256),
+        info,
+        error,
+    }
+}
+
+fn meta_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".meta.json");
+    path.with_file_name(name)
+}
+
+fn read_meta(path: &Path) -> Option<ModelMeta> {
+    let text = std::fs::read_to_string(meta_path(path)).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+/// Path of an installed model, rejecting ids that escape the directory.
+pub fn model_path(id: &str) -> Result<PathBuf, String> {
+    validate_file_name(id)?;
+    let path = models_dir().join(id);
+    if !path.is_file() {
+        return Err(format!("Local model '{id}' is not installed"));
+    }
+    Ok(path)
+}
+
+fn validate_file_name(name: &str) -> Result<(), String> {
+    if name.is_empty()
+        || name.contains(['/', '\\'])
+        || name.starts_with('.')
+        || !name.ends_with(".gguf")
+    {
+        return Err(format!(
+            "Invalid model file name '{name}' (expected e.g. model.Q4_K_M.gguf)"
+        ));
+    }
+    Ok(())
+}
+
+/// Delete a model and its metadata. Returns whether it existed.
+pub async fn delete_model(id: &str) -> Result<bool, String> {
+    validate_file_name(id)?;
+    let path = models_dir().join(id);
+    if !path.exists() {
+        return Ok(false);
+    }
+    if server_model().await.as_deref() == Some(id) {
+        stop_server().await;
+    }
+    std::fs::remove_file(&path).map_err(|e| format!("Failed to delete {id}: {e}"))?;
+    let _ = std::fs::remove_file(meta_path(&path));
+    Ok(true)
+}
+
+/// Result of checking a model file's integrity.
+#[derive(Debug, Clone, Serialize)]
+pub struct VerifyReport {
+    pub id: String,
+    pub sha256: String,
+    pub expected_sha256: Option<String>,
+    /// `None` when there is no checksum to compare against.
+    pub checksum_ok: Option<bool>,
+    pub header_ok: bool,
+    /// Header parsed and checksum (if any) matched.
+    pub valid: bool,
+    pub error: Option<String>,
+}
+
+/// Hash a model and parse its header. The expected checksum defaults to the
+/// one recorded at download.
+pub async fn verify_model(
+    id: &str,
+    expected_sha256: Option<String>,
+) -> Result<VerifyReport, String> {
+    let path = model_path(id)?;
+    let expected = expected_sha256
+        .or_else(|| read_meta(&path).map(|m| m.sha256))
+        .map(|s| s.trim().to_lowercase());
+    let hash_path = path.clone();
+    let sha256 = tokio::task::spawn_blocking(move || hash_file(&hash_path))
+        .await
+        .map_err(|e| format!("Hash task failed: {e}"))??;
+    let header = read_gguf_info(&path);
+    let checksum_ok = expected.as_ref().map(|e| *e == sha256);
+    Ok(VerifyReport {
+        id: id.to_string(),
+        checksum_ok,
+        expected_sha256: expected,
+        sha256,
+        header_ok: header.is_ok(),
+        valid: header.is_ok() && checksum_ok != Some(false),
+        error: header.err(),
+    })
+}
+
+fn hash_file(path: &Path) -> Result<String, String> {
+    let mut file =
+        std::fs::File::open(path).map_err(|e| format!("Cannot open {}: {e}", path.display()))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).map_err(|e| format!("Read failed: {e}"))?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Download a model into the model directory, hashing it on the way. The
+/// file only appears under its final name once the checksum (if given) and
+/// the GGUF header check pass. `on_progress` receives bytes done and total.
+pub async fn download_model(
+    url: &str,
+    file_name: Option<String>,
+    expected_sha256: Option<String>,
+    on_progress: impl Fn(u64, Option<u64>),
+) -> Result<LocalModel, String> {
+    let file_name = match file_name {
+        Some(name) => name,
+        None => url::Url::parse(url)
+            .map_err(|e| format!("Invalid URL: {e}"))?
+            .path_segments()
+            .and_then(|mut s| s.next_back())
+            .map(|s| {
+                urlencoding::decode(s)
+                    .map(|s| s.into_owned())
+                    .unwrap_or_else(|_| s.to_string())
+            })
+            .unwrap_or_default(),
+    };
+    validate_file_name(&file_name)?;
+
+    let dir = models_dir();
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Cannot create {}: {e}", dir.display()))?;
+    let path = dir.join(&file_name);
+    if path.exists() {
+        return Err(format!("'{file_name}' is already installed"));
+    }
+    let part = dir.join(format!("{file_name}.part"));
+
+    let mut response = reqwest::get(url)
+        .await
+        .map_err(|e| format!("Download failed: {e}"))?;
+    if !response.status().is_success() {
+        return Err(format!("Download failed: HTTP {}", response.status()));
+    }
+    let total = response.content_length();
+
+    let result: Result<String, String> = async {
+        use tokio::io::AsyncWriteExt;
+        let mut file = tokio::fs::File::create(&part)
+            .await
+            .map_err(|e| format!("Cannot write {}: {e}", part.display()))?;
+        let mut hasher = Sha256::new();
+        let mut downloaded = 0u64;
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| format!("Download interrupted: {e}"))?
+        {
+            if downloaded == 0 && !chunk.starts_with(GGUF_MAGIC) {
+                return Err("The URL does not point to a GGUF file".to_string());
+            }
+            hasher.update(&chunk);
+            file.write_all(&chunk)
+                .await
+                .map_err(|e| format!("Write failed: {e}"))?;
+            downloaded += chunk.len() as u64;
+            on_progress(downloaded, total);
+        }
+        file.flush()
+            .await
+            .map_err(|e| format!("Write failed: {e}"))?;
+        Ok(hex::encode(hasher.finalize()))
+    }
+    .await;
+
+    let sha256 = match result {
+        Ok(sha256) => sha256,
+        Err(e) => {
+            let _ = std::fs::remove_file(&part);
+            return Err(e);
+        }
+    };
+    if let Some(expected) = expected_sha256.map(|s| s.trim().to_lowercase()) {
+        if expected != sha256 {
+            let _ = std::fs::remove_file(&part);
+            return Err(format!(
+                "Checksum mismatch: expected {expected}, got {sha256}"
+            ));
+        }
+    }
+    if let Err(e) = read_gguf_info(&part) {
+        let _ = std::fs::remove_file(&part);
+        return Err(e);
+    }
+
+    std::fs::rename(&part, &path).map_err(|e| format!("Failed to finalize download: {e}"))?;
+    write_meta(&path, url, sha256);
+    Ok(describe(&path, &system_memory().await))
+}
+
+fn write_meta(path: &Path, source_url: &str, sha256: String) {
+    let meta = ModelMeta {
+        source_url: source_url.to_string(),
+        sha256,
+        downloaded_at: Utc::now(),
+    };
+    if let Ok(json) = serde_json::to_string_pretty(&meta) {
+        let _ = std::fs::write(meta_path(path), json);
+    }
+}
+
+/// Copy a model from `reader` into `dir` as `id`, with the same checksum and
+/// header checks as a download. Returns the file's checksum.
+pub fn install_model_file(
+    dir: &Path,
+    id: &str,
+    mut reader: impl Read,
+    expected_sha256: Option<&str>,
+    source_url: &str,
+) -> Result<String, String> {
+    validate_file_name(id)?;
+    std::fs::create_dir_all(dir).map_err(|e| format!("Cannot create {}: {e}", dir.display()))?;
+    let path = dir.join(id);
+    if path.exists() {
+        return Err(format!("'{id}' is already installed"));
+    }
+    let part = dir.join(format!("{id}.part"));
+
+    let copied = (|| {
+        let mut file = std::fs::File::create(&part)
+            .map_err(|e| format!("Cannot write {}: {e}", part.display()))?;
+        let mut hasher = Sha256::new();
+        let mut buf = vec![0u8; 1024 * 1024];
+        loop {
+            let n = reader
+                .read(&mut buf)
+                .map_err(|e| format!("Read failed: {e}"))?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buf[..n]);
+            std::io::Write::write_all(&mut file, &buf[..n])
+                .map_err(|e| format!("Write failed: {e}"))?;
+        }
+        let sha256 = hex::encode(hasher.finalize());
+        if let Some(expected) = expected_sha256.map(|s| s.trim().to_lowercase()) {
+            if expected != sha256 {
+                return Err(format!(
+                    "Checksum mismatch: expected {expected}, got {sha256}"
+                ));
+            }
+        }
+        read_gguf_info(&part)?;
+        Ok(sha256)
+    })();
+    let sha256 = match copied {
+        Ok(sha256) => sha256,
+        Err(e) => {
+            let _ = std::fs::remove_file(&part);
+            return Err(e);
+        }
+    };
+
+    std::fs::rename(&part, &path).map_err(|e| format!("Failed to finalize {id}: {e}"))?;
+    write_meta(&path, source_url, sha256.clone());
+    Ok(sha256)
+}
+
+// ============================================================
+// llama-server process
+// ============================================================
+
+struct RunningServer {
+    model_id: String,
+    base_url: String,
+    child: tokio::process::Child,
+}
+
+/// The running server. Held only to look at or swap it, never while a
+/// model loads.
+static SERVER: std::sync::Mutex<Option<RunningServer>> = std::sync::Mutex::new(None);
+
+/// Serializes server starts, so two requests don't load two servers.
+static STARTING: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+fn server() -> std::sync::MutexGuard<'static, Option<RunningServer>> {
+    SERVER.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// The `llama-server` binary: `LLAMA_SERVER_PATH`, then `<data dir>/bin`,
+/// then `PATH`.
+fn server_binary() -> PathBuf {
+    if let Ok(path) = std::env::var("LLAMA_SERVER_PATH") {
+        return PathBuf::from(path);
+    }
+    let exe = if cfg!(windows) {
+        "llama-server.exe"
+    } else {
+        "llama-server"
+    };
+    let bundled = crate::config::data_dir().join("bin").join(exe);
+    if bundled.is_file() {
+        bundled
+    } else {
+        PathBuf::from(exe)
+    }
+}
+
+/// Model currently loaded by the server, if it is running.
+pub async fn server_model() -> Option<String> {
+    running_url(None).map(|(model_id, _)| model_id)
+}
+
+/// Model id and base URL of the running server, if it serves `model_id`
+/// (any model when `None`). Forgets a server that exited.
+fn running_url(model_id: Option<&str>) -> Option<(String, String)> {
+    let mut server = server();
+    let running = server.as_mut()?;
+    if !matches!(running.child.try_wait(), Ok(None)) {
+        *server = None;
+        std::env::remove_var(ENDPOINT_ENV);
+        return None;
+    }
+    model_id
+        .is_none_or(|id| running.model_id == id)
+        .then(|| (running.model_id.clone(), running.base_url.clone()))
+}
+
+/// The model to serve when none was picked: the first installed one that
+/// can be read.
+pub async fn default_model() -> Result<String, String> {
+    list_models()
+        .await?
+        .into_iter()
+        .find(|m| m.error.is_none())
+        .map(|m| m.id)
+        .ok_or_else(|| "No GGUF models installed. Download one in Settings.".to_string())
+}
+
+/// Base URL of the running server, starting it with the default model if
+/// none is running.
+pub async fn ensure_any_server() -> Result<String, String> {
+    match running_url(None) {
+        Some((_, base_url)) => Ok(base_url),
+        None => ensure_server(&default_model().await?).await,
+    }
+}
+
+/// Base URL of a server with `model_id` loaded, starting or switching the
+/// server as needed.
+pub async fn ensure_server(model_id: &str) -> Result<String, String> {
+    if let Some((_, base_url)) = running_url(Some(model_id)) {
+        return Ok(base_url);
+    }
+    let _starting = STARTING.lock().await;
+    // Another request may have loaded it while this one waited
+    if let Some((_, base_url)) = running_url(Some(model_id)) {
+        return Ok(base_url);
+    }
+    let previous = server().take();
+    if let Some(mut previous) = previous {
+        let _ = previous.child.kill().await;
+    }
+
+    let path = model_path(model_id)?;
+    let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    let info = read_gguf_info(&path)?;
+    let check = memory_check(size, Some(&info), &system_memory().await);
+    if check.fit == Fit::Insufficient {
+        return Err(check.message);
+    }
+    let gpu_layers = if check.fit == Fit::Gpu || cfg!(target_os = "macos") {
+        "999"
+    } else {
+        "0"
+    };
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|l| l.local_addr())
+        .map(|a| a.port())
+        .map_err(|e| format!("No free port for llama-server: {e}"))?;
+    let binary = server_binary();
+    let mut child = tokio::process::Command::new(&binary)
+        .arg("--model")
+        .arg(&path)
+        .args(["--host", "127.0.0.1", "--port", &port.to_string()])
+        .args(["--ctx-size", &CONTEXT_SIZE.to_string(), "--n-gpu-layers", gpu_layers])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| {
+            format!(
+                "Failed to start {}: {e}. Install llama.cpp and put llama-server on PATH or set LLAMA_SERVER_PATH.",
+                binary.display()
+            )
+        })?;
+
+    let base_url = format!("http://127.0.0.1:{port}");
+    let client = reqwest::Client::new();
+    let deadline = tokio::time::Instant::now() + LOAD_TIMEOUT;
+    loop {
+        if let Ok(Some(status)) = child.try_wait() {
+            return Err(format!(
+                "llama-server exited while loading {model_id} ({status})"
+            ));
+        }
+        if let Ok(resp) = client.get(format!("{base_url}/health")).send().await {
+            if resp.status().is_success() {
+                break;
+            }
+        }
+        if tokio::time::Instant::now() >= deadline {
+            let _ = child.kill().await;
+            return Err(format!("Timed out loading {model_id} into llama-server"));
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+
+    tracing::info!("llama-server serving {model_id} on {base_url}");
+    std::env::set_var(ENDPOINT_ENV, &base_url);
+    *server() = Some(RunningServer {
+        model_id: model_id.to_string(),
+        base_url: base_url.clone(),
+        child,
+    });
+    Ok(base_url)
+}
+
+/// Version string of the `llama-server` binary, or why it can't be run.
+pub async fn server_version() -> Result<String, String> {
+    let binary = server_binary();
+    let output = tokio::time::timeout(
+        Duration::from_secs(10),
+        tokio::process::Command::new(&binary)
+            .arg("--version")
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await
+    .map_err(|_| format!("{} --version timed out", binary.display()))?
+    .map_err(|e| {
+        format!(
+            "llama-server not found ({}): {e}. Install llama.cpp and put llama-server on PATH or set LLAMA_SERVER_PATH.",
+            binary.display()
+        )
+    })?;
+    // llama.cpp prints its build info to stderr
+    let text = String::from_utf8_lossy(&output.stderr).into_owned()
+        + &String::from_utf8_lossy(&output.stdout);
+    Ok(text
+        .lines()
+        .find(|l| l.contains("version"))
+        .unwrap_or("")
+        .trim()
+        .to_string())
+}
+
+/// Stop the server. Returns whether one was running.
+pub async fn stop_server() -> bool {
+    let running = server().take();
+    let Some(mut running) = running else {
+        return false;
+    };
+    std::env::remove_var(ENDPOINT_ENV);
+    let _ = running.child.kill().await;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gguf_string(out: &mut Vec<u8>, s: &str) {
+        out.extend((s.len() as u64).to_le_bytes());
+        out.extend(s.as_bytes());
+    }
+
+    fn sample_header() -> Vec<u8> {
+        let mut out = b"GGUF".to_vec();
+        out.extend(3u32.to_le_bytes());
+        out.extend(0u64.to_le_bytes());
+        out.extend(6u64.to_le_bytes());
+        gguf_string(&mut out, "general.architecture");
+        out.extend(8u32.to_le_bytes());
+        gguf_string(&mut out, "llama");
+        gguf_string(&mut out, "general.file_type");
+        out.extend(4u32.to_le_bytes());
+        out.extend(15u32.to_le_bytes());
+        gguf_string(&mut out, "tokenizer.ggml.tokens");
+        out.extend(9u32.to_le_bytes());
+        out.extend(8u32.to_le_bytes());
+        out.extend(2u64.to_le_bytes());
+        gguf_string(&mut out, "<s>");
+        gguf_string(&mut out, "</s>");
+        gguf_string(&mut out, "llama.block_count");
+        out.extend(4u32.to_le_bytes());
+        out.extend(32u32.to_le_bytes());
+        gguf_string(&mut out, "llama.embedding_length");
+        out.extend(4u32.to_le_bytes());
+        out.extend(4096u32.to_le_bytes());
+        gguf_string(&mut out, "llama.attention.head_count");
+        out.extend(4u32.to_le_bytes());
+        out.extend(32u32.to_le_bytes());
+        out
+    }
+
+    #[test]
+    fn parses_header_metadata_and_skips_arrays() {
+        let info = parse_gguf(&mut std::io::Cursor::new(sample_header())).unwrap();
+        assert_eq!(info.architecture.as_deref(), Some("llama"));
+        assert_eq!(info.file_type.as_deref(), Some("Q4_K_M"));
+        assert_eq!(info.block_count, Some(32));
+        assert_eq!(info.head_count, Some(32));
+
+        let err = parse_gguf(&mut std::io::Cursor::new(b"GGML....".to_vec())).unwrap_err();
+        assert!(err.contains("bad magic"));
+    }
+
+    #[test]
+    fn checks_memory_fit() {
+        let info = parse_gguf(&mut std::io::Cursor::new(sample_header())).unwrap();
+        let gib = 1024 * 1024 * 1024;
+        let system = SystemMemory {
+            available_ram_bytes: Some(8 * gib),
+            gpus: vec![GpuMemory {
+                name: "test".into(),
+                total_bytes: 6 * gib,
+                free_bytes: 5 * gib,
+            }],
+            ..Default::default()
+        };
+
+        // 2 (K+V) * 32 layers * 4096 ctx * 4096 width * 2 bytes = 2 GiB
+        let check = memory_check(4 * gib, Some(&info), &system);
+        assert_eq!(check.kv_cache_bytes, 2 * gib);
+        assert_eq!(check.fit, Fit::Cpu);
+
+        assert_eq!(memory_check(gib, Some(&info), &system).fit, Fit::Gpu);
+        assert_eq!(
+            memory_check(6 * gib, Some(&info), &system).fit,
+            Fit::Insufficient
+        );
+    }
+}
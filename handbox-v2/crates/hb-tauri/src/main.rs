@@ -2,8 +2,26 @@
 
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod bedrock;
+mod bundled_packs;
 mod commands;
+mod config;
+mod credential_health;
+mod debug_bundle;
+mod doctor;
+mod local_models;
+mod notifications;
+mod offline_bundle;
+mod onboarding;
+mod pack_content;
+mod pack_report;
+mod pack_updater;
+mod run_viewer;
+mod session_recording;
 mod state;
+mod team_sync;
+mod usage;
+mod webhooks;
 
 use commands::agent::AgentOrchestratorState;
 use commands::agent_loop::AgentConversationState;
@@ -12,21 +30,58 @@ use commands::execution::ExecutionTrackerState;
 use commands::marketplace::MarketplaceState;
 use commands::mcp::McpState;
 use commands::vector_store::VectorStoreState;
+use notifications::{Notification, NotificationKind, NotificationService};
 use state::AppState;
-use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+
+/// Delay before the first automatic garbage collection after launch.
+const GC_STARTUP_DELAY: Duration = Duration::from_secs(300);
+
+/// How often to re-read the retention config while automatic collection is off.
+const GC_IDLE_CHECK: Duration = Duration::from_secs(3600);
 
 fn main() {
     tracing_subscriber::fmt::init();
 
-    let data_dir = dirs_data_dir().unwrap_or_else(|| PathBuf::from("."));
+    let data_dir = config::data_dir();
     let app_state = AppState::new(data_dir.clone());
 
+    // `handbox --doctor` prints diagnostics as JSON and exits
+    if std::env::args().any(|a| a == "--doctor") {
+        let creds = app_state.llm_credentials.blocking_read().clone();
+        let report = tauri::async_runtime::block_on(doctor::run(&data_dir, &creds));
+        match serde_json::to_string_pretty(&report) {
+            Ok(json) => println!("{json}"),
+            Err(e) => eprintln!("Failed to serialize doctor report: {e}"),
+        }
+        std::process::exit(if report.overall == doctor::Severity::Error { 1 } else { 0 });
+    }
+
     // Initialize trace store
     if let Err(e) = app_state.init_trace_store() {
         tracing::warn!("Failed to init trace store: {e}");
     }
 
+    // Open the node output cache
+    if let Err(e) = app_state.init_execution_cache() {
+        tracing::warn!("{e}");
+    }
+
+    // Recover executions interrupted by a crash
+    match app_state.init_journal() {
+        Ok(0) => {}
+        Ok(n) => tracing::warn!("Recovered {n} execution(s) interrupted by a crash"),
+        Err(e) => tracing::warn!("Failed to init execution journal: {e}"),
+    }
+
+    // Install the packs that ship with the app, e.g. the standard library
+    let packs_dir = data_dir.parent().unwrap_or(&data_dir).join("packs");
+    if let Err(e) = bundled_packs::install(&packs_dir) {
+        tracing::warn!("Failed to install bundled packs: {e}");
+    }
+
     // Initialize MCP state
     let mcp_state = McpState::default();
 
@@ -34,7 +89,7 @@ fn main() {
     let agent_state = Arc::new(AgentOrchestratorState::default());
 
     // Initialize Collaboration state
-    let collab_state = Arc::new(CollaborationState::default());
+    let collab_state = Arc::new(CollaborationState::new(data_dir.join("collab_recordings")));
 
     // Initialize Marketplace state
     let marketplace_state = Arc::new(MarketplaceState::default());
@@ -53,20 +108,31 @@ fn main() {
         match hb_project::ProjectManager::open(&projects_db) {
             Ok(pm) => {
                 let mut guard = app_state.project_manager.blocking_write();
-                *guard = pm;
+                *guard = Box::new(pm);
                 tracing::info!("ProjectManager opened at {:?}", projects_db);
             }
             Err(e) => {
                 tracing::warn!("Failed to open ProjectManager: {e}");
             }
         }
+        match hb_project::thumbnail::ThumbnailCache::open(&projects_db) {
+            Ok(cache) => *app_state.thumbnails.blocking_write() = cache,
+            Err(e) => tracing::warn!("Failed to open thumbnail cache: {e}"),
+        }
     }
 
+    // Bring back the workflows team sync keeps on disk
+    tauri::async_runtime::block_on(team_sync::restore_workflows(&app_state));
+
     // Initialize Execution Tracker state
     let execution_tracker = Arc::new(ExecutionTrackerState::default());
 
+    // Initialize notification service (per-workspace preferences)
+    let notification_service = Arc::new(NotificationService::new(data_dir.clone()));
+
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
         .manage(app_state)
         .manage(mcp_state)
         .manage(agent_state)
@@ -75,6 +141,111 @@ fn main() {
         .manage(agent_conv_state)
         .manage(vector_store_state)
         .manage(execution_tracker)
+        .manage(notification_service)
+        .setup(|app| {
+            // Periodically check pack sources and notify the UI of updates
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let mut interval = tokio::time::interval(pack_updater::CHECK_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    let state = handle.state::<AppState>();
+                    let packs_dir = state.data_dir.parent().unwrap_or(&state.data_dir).join("packs");
+                    let sources = pack_updater::PackSourceConfig::load(&state.data_dir).sources;
+                    if sources.is_empty() {
+                        continue;
+                    }
+                    let workflows = state.workflows.read().await.clone();
+                    let updates =
+                        pack_updater::check_updates(&packs_dir, &sources, workflows.values()).await;
+                    if !updates.is_empty() {
+                        let _ = handle.emit(pack_updater::UPDATES_AVAILABLE_EVENT, &updates);
+                    }
+                }
+            });
+
+            // Collect garbage shortly after launch, then on the configured interval
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let mut wait = GC_STARTUP_DELAY;
+                loop {
+                    tokio::time::sleep(wait).await;
+                    let state = handle.state::<AppState>();
+                    let Some(interval) = state.retention_config().interval() else {
+                        wait = GC_IDLE_CHECK;
+                        continue;
+                    };
+                    let report = state.run_gc().await;
+                    tracing::info!(
+                        "Garbage collection reclaimed {} bytes ({} errors)",
+                        report.reclaimed_bytes,
+                        report.errors.len()
+                    );
+                    wait = interval;
+                }
+            });
+
+            // Re-validate provider credentials and warn when one stops working
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                tokio::time::sleep(credential_health::STARTUP_DELAY).await;
+                let mut interval = tokio::time::interval(credential_health::CHECK_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    let state = handle.state::<AppState>();
+                    let changed = state.refresh_credential_health().await;
+                    if changed.is_empty() {
+                        continue;
+                    }
+                    let _ = handle.emit(credential_health::HEALTH_CHANGED_EVENT, &changed);
+                    let notifier = handle.state::<Arc<NotificationService>>();
+                    for health in changed.iter().filter(|h| h.state.is_failing()) {
+                        let body = health.message.clone().unwrap_or_default();
+                        let notification =
+                            Notification::new(NotificationKind::CredentialFailed, health.summary(), body);
+                        notifier.notify(&handle, notification).await;
+                    }
+                }
+            });
+
+            // Start scheduled runs as they come due; runs missed while the
+            // app was closed are not made up
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let mut checked = chrono::Utc::now();
+                let mut interval = tokio::time::interval(commands::schedule::FIRING_CHECK);
+                loop {
+                    interval.tick().await;
+                    let now = chrono::Utc::now();
+                    commands::schedule::fire_due(&handle, checked, now).await;
+                    checked = now;
+                }
+            });
+
+            // Sync with the team server on the configured interval
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let mut last_sync: Option<std::time::Instant> = None;
+                loop {
+                    tokio::time::sleep(team_sync::IDLE_CHECK).await;
+                    let state = handle.state::<AppState>();
+                    let Some(interval) = team_sync::SyncConfig::load(&state.data_dir).interval() else {
+                        continue;
+                    };
+                    if last_sync.is_some_and(|at| at.elapsed() < interval) {
+                        continue;
+                    }
+                    last_sync = Some(std::time::Instant::now());
+                    match team_sync::sync(&state).await {
+                        Ok(report) => {
+                            let _ = handle.emit(team_sync::SYNC_COMPLETED_EVENT, &report);
+                        }
+                        Err(e) => tracing::warn!("Team sync failed: {e}"),
+                    }
+                }
+            });
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             // Workflow CRUD
             commands::workflow::create_workflow,
@@ -83,30 +254,135 @@ fn main() {
             commands::workflow::update_workflow,
             commands::workflow::delete_workflow,
             commands::workflow::import_workflow,
+            commands::workflow::get_workflow_outline,
+            commands::workflow::get_workflow_page,
+            commands::workflow::get_workflow_subgraph,
+            commands::workflow::copy_workflow_fragment,
+            commands::workflow::paste_workflow_fragment,
+            commands::workflow::begin_workflow_upload,
+            commands::workflow::append_workflow_page,
+            commands::workflow::finish_workflow_upload,
+            commands::workflow::get_workflow_thumbnail,
+            commands::workflow::get_workflow_parameter_form,
+            commands::workflow::validate_workflow_parameters,
+            commands::workflow::apply_workflow_edit,
+            commands::workflow::undo_workflow_edit,
+            commands::workflow::redo_workflow_edit,
+            commands::workflow::get_edit_journal,
+            commands::workflow::get_workflow_review,
+            commands::workflow::submit_workflow_review,
+            commands::workflow::decide_workflow_review,
+            commands::validation::validate_workflow,
+            commands::validation::lint_workflow,
+            commands::validation::suggest_connections,
             commands::workflow::export_workflow_file,
             // Execution
             commands::execution::execute_workflow,
             commands::execution::execute_agent_node,
             commands::execution::get_execution_status,
+            commands::execution::get_execution_events,
+            commands::execution::list_running_executions,
+            commands::execution::pause_execution,
             commands::execution::cancel_execution,
+            commands::execution::cancel_node,
+            commands::execution::resume_execution,
+            commands::execution::resume_from_checkpoint,
+            commands::execution::list_interrupted_executions,
+            commands::execution::list_resource_locks,
+            // App configuration
+            commands::config::get_data_dir_config,
+            commands::config::set_data_dir,
+            commands::config::run_doctor,
+            commands::config::get_retention_config,
+            commands::config::set_retention_config,
+            commands::config::run_garbage_collection,
+            commands::config::get_usage_config,
+            commands::config::set_usage_config,
+            commands::config::get_usage_report,
+            // Notifications
+            commands::notification::get_notification_config,
+            commands::notification::set_notification_preferences,
+            commands::notification::send_test_notification,
+            commands::notification::record_budget_usage,
+            commands::notification::test_webhook,
+            commands::notification::list_webhook_deliveries,
+            // Schedules
+            commands::schedule::list_schedules,
+            commands::schedule::save_schedule,
+            commands::schedule::delete_schedule,
+            commands::schedule::get_schedule_calendar,
+            commands::schedule::preview_cron,
             // Project management
             commands::project::create_project,
             commands::project::get_project,
             commands::project::list_projects,
             commands::project::delete_project,
+            commands::project::set_project_policy,
+            commands::project::set_project_timezone,
+            commands::project::set_project_review_policy,
+            commands::data_source::list_data_sources,
+            commands::data_source::save_data_source,
+            commands::data_source::remove_data_source,
+            commands::data_source::scan_data_source,
+            commands::data_source::set_data_source_labels,
+            commands::project::get_activity,
+            commands::project::list_project_env,
+            commands::project::set_project_env,
+            commands::project::delete_project_env,
+            commands::project::list_project_members,
+            commands::project::set_project_member,
+            commands::project::remove_project_member,
+            commands::onboarding::preview_onboarding,
+            commands::onboarding::complete_onboarding,
             // Tool registry
             commands::tool::list_tools,
             commands::tool::get_tool,
             commands::tool::search_tools,
             commands::tool::load_packs,
+            commands::tool::get_palette,
+            commands::tool::get_node_config_form,
             // Trace
             commands::trace::get_traces,
             commands::trace::get_span,
+            commands::trace::get_edge_value,
+            commands::trace::get_node_inputs,
+            commands::trace::get_execution_manifest,
+            commands::trace::get_scheduler_metrics,
+            commands::trace::diff_executions,
+            commands::trace::find_executions_by_artifact,
             commands::trace::export_traces,
+            commands::trace::analyze_workflow_performance,
+            commands::trace::get_cache_stats,
+            commands::trace::estimate_workflow_cost,
+            commands::trace::export_debug_bundle,
+            commands::trace::export_run_viewer,
+            commands::trace::load_run_viewer,
             // Pack management
             commands::pack::list_packs,
             commands::pack::get_pack,
             commands::pack::install_pack,
+            commands::pack::list_pack_sources,
+            commands::pack::set_pack_sources,
+            commands::pack::check_pack_updates,
+            commands::pack::apply_pack_update,
+            commands::pack::rollback_pack_update,
+            commands::pack::plan_pack_migration,
+            commands::pack::apply_pack_migration,
+            commands::pack::list_pack_contents,
+            commands::pack::read_pack_content,
+            commands::pack::instantiate_pack_composite,
+            commands::pack::get_pack_report,
+            commands::pack::export_pack_report,
+            // Offline bundles
+            commands::offline_bundle::export_offline_bundle,
+            commands::offline_bundle::preview_offline_bundle,
+            commands::offline_bundle::install_offline_bundle,
+            // Team sync
+            commands::team_sync::get_team_sync_config,
+            commands::team_sync::set_team_sync_config,
+            commands::team_sync::get_team_sync_status,
+            commands::team_sync::run_team_sync,
+            commands::team_sync::resolve_team_sync_conflict,
             // Compiler
             commands::compiler::compile_prompt,
             // LLM
@@ -116,13 +392,23 @@ fn main() {
             commands::llm::set_anthropic_api_key,
             commands::llm::clear_llm_credentials,
             commands::llm::set_local_llm_endpoint,
+            commands::llm::set_service_key,
+            commands::llm::remove_service_key,
             commands::llm::set_active_llm_provider,
             commands::llm::test_llm_connection,
             commands::llm::list_llm_models,
             commands::llm::invoke_llm,
             commands::llm::invoke_llm_stream,
+            commands::local_models::list_local_models,
+            commands::local_models::get_system_memory,
+            commands::local_models::download_local_model,
+            commands::local_models::verify_local_model,
+            commands::local_models::delete_local_model,
+            commands::local_models::stop_local_model_server,
             commands::llm::create_embedding,
             commands::llm::get_credential_status,
+            commands::llm::get_credential_health,
+            commands::llm::check_credential_health,
             // GIS
             commands::gis::gis_read_geojson,
             commands::gis::gis_read_shapefile,
@@ -205,6 +491,12 @@ fn main() {
             commands::collaboration::collab_update_settings,
             commands::collaboration::collab_heartbeat,
             commands::collaboration::collab_close_session,
+            commands::collaboration::collab_execute_workflow,
+            commands::collaboration::collab_cancel_execution,
+            commands::collaboration::collab_list_recordings,
+            commands::collaboration::collab_get_recording,
+            commands::collaboration::collab_playback,
+            commands::collaboration::collab_restore_from_recording,
             // System Tools (Claude Code level)
             commands::system_tools::tool_bash_execute,
             commands::system_tools::tool_file_read,
@@ -255,21 +547,21 @@ fn main() {
             commands::marketplace::marketplace_get_collections,
             commands::marketplace::marketplace_get_downloads,
             commands::marketplace::marketplace_get_likes,
+            commands::marketplace::marketplace_check_publish,
             commands::marketplace::marketplace_publish,
         ])
-        .run(tauri::generate_context!())
-        .expect("error running Handbox");
-}
-
-fn dirs_data_dir() -> Option<PathBuf> {
-    #[cfg(target_os = "windows")]
-    {
-        std::env::var("APPDATA")
-            .ok()
-            .map(|p| PathBuf::from(p).join("Handbox"))
-    }
-    #[cfg(not(target_os = "windows"))]
-    {
-        dirs::data_dir().map(|p| p.join("handbox"))
-    }
+        .build(tauri::generate_context!())
+        .expect("error building Handbox")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                let state = app.state::<AppState>();
+                let report = tauri::async_runtime::block_on(state.shutdown());
+                tauri::async_runtime::block_on(local_models::stop_server());
+                tracing::info!(
+                    "Shutdown complete: {} interrupted, {} timed out",
+                    report.interrupted.len(),
+                    report.timed_out.len()
+                );
+            }
+        });
 }
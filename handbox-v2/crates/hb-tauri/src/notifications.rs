@@ -0,0 +1,467 @@
+//! Notification service — OS notifications plus optional webhook and email
+//! delivery for execution events, with per-workspace preferences persisted in
+//! `notifications.json` in the data directory. Signed webhooks with retries
+//! are sent by [`WebhookDispatcher`].
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+use tokio::sync::{Mutex, RwLock};
+
+use crate::state::{AppState, LLMCredentials};
+use crate::webhooks::{WebhookDelivery, WebhookDispatcher, WebhookEndpoint};
+
+/// Event emitted to the frontend for every delivered notification.
+pub const NOTIFICATION_EVENT: &str = "notification";
+
+/// Timeout for webhook deliveries.
+const WEBHOOK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    ExecutionCompleted,
+    ExecutionFailed,
+    BudgetThreshold,
+    ApprovalRequested,
+    CredentialFailed,
+    /// A scheduled run could not start or failed.
+    ScheduleFailed,
+}
+
+impl NotificationKind {
+    pub const ALL: [NotificationKind; 6] = [
+        Self::ExecutionCompleted,
+        Self::ExecutionFailed,
+        Self::BudgetThreshold,
+        Self::ApprovalRequested,
+        Self::CredentialFailed,
+        Self::ScheduleFailed,
+    ];
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Notification {
+    pub kind: NotificationKind,
+    pub title: String,
+    pub body: String,
+    pub workspace_id: Option<String>,
+    pub execution_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Notification {
+    pub fn new(kind: NotificationKind, title: impl Into<String>, body: impl Into<String>) -> Self {
+        Self {
+            kind,
+            title: title.into(),
+            body: body.into(),
+            workspace_id: None,
+            execution_id: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    pub fn with_workspace(mut self, workspace_id: Option<String>) -> Self {
+        self.workspace_id = workspace_id;
+        self
+    }
+
+    pub fn with_execution(mut self, execution_id: impl Into<String>) -> Self {
+        self.execution_id = Some(execution_id.into());
+        self
+    }
+}
+
+/// SMTP settings for email delivery. The password is read from the named
+/// environment variable so it never lands in the config file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailSettings {
+    pub smtp_host: String,
+    #[serde(default = "default_smtp_port")]
+    pub smtp_port: u16,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password_env: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationPreferences {
+    /// Show OS-level notifications.
+    #[serde(default = "default_true")]
+    pub os: bool,
+    /// Event kinds to deliver.
+    #[serde(default = "default_events")]
+    pub events: Vec<NotificationKind>,
+    /// POST each notification as JSON to this URL.
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    /// Signed webhooks for selected event kinds, retried on failure.
+    #[serde(default)]
+    pub webhooks: Vec<WebhookEndpoint>,
+    #[serde(default)]
+    pub email: Option<EmailSettings>,
+    /// Budget fractions (0.0–1.0+) that trigger a `BudgetThreshold` notification.
+    #[serde(default = "default_budget_thresholds")]
+    pub budget_thresholds: Vec<f64>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_events() -> Vec<NotificationKind> {
+    NotificationKind::ALL.to_vec()
+}
+
+fn default_budget_thresholds() -> Vec<f64> {
+    vec![0.5, 0.8, 1.0]
+}
+
+impl Default for NotificationPreferences {
+    fn default() -> Self {
+        Self {
+            os: true,
+            events: default_events(),
+            webhook_url: None,
+            webhooks: Vec::new(),
+            email: None,
+            budget_thresholds: default_budget_thresholds(),
+        }
+    }
+}
+
+impl NotificationPreferences {
+    pub fn wants(&self, kind: NotificationKind) -> bool {
+        self.events.contains(&kind)
+    }
+}
+
+/// Persisted notification config: defaults plus per-workspace overrides.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotificationConfig {
+    #[serde(default)]
+    pub default: NotificationPreferences,
+    #[serde(default)]
+    pub workspaces: HashMap<String, NotificationPreferences>,
+}
+
+impl NotificationConfig {
+    pub fn path(data_dir: &Path) -> PathBuf {
+        data_dir.join("notifications.json")
+    }
+
+    pub fn load(data_dir: &Path) -> Self {
+        std::fs::read_to_string(Self::path(data_dir))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, data_dir: &Path) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(Self::path(data_dir), content).map_err(|e| e.to_string())
+    }
+
+    /// Preferences for a workspace, falling back to the defaults.
+    pub fn preferences_for(&self, workspace_id: Option<&str>) -> &NotificationPreferences {
+        workspace_id
+            .and_then(|id| self.workspaces.get(id))
+            .unwrap_or(&self.default)
+    }
+}
+
+/// Which channels a notification was delivered through.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DeliveryReport {
+    pub suppressed: bool,
+    pub os: bool,
+    pub webhook: Option<Result<(), String>>,
+    /// Signed webhooks queued; their outcomes show up in the delivery log.
+    pub webhooks_queued: usize,
+    pub email: Option<Result<(), String>>,
+}
+
+/// Highest threshold in `thresholds` that `fraction` has reached.
+pub fn highest_crossed(thresholds: &[f64], fraction: f64) -> Option<f64> {
+    thresholds
+        .iter()
+        .copied()
+        .filter(|t| fraction >= *t)
+        .fold(None, |acc: Option<f64>, t| Some(acc.map_or(t, |a| a.max(t))))
+}
+
+pub struct NotificationService {
+    data_dir: PathBuf,
+    config: RwLock<NotificationConfig>,
+    /// Highest budget threshold already reported, keyed by budget scope.
+    budget_fired: Mutex<HashMap<String, f64>>,
+    http: reqwest::Client,
+    webhooks: Arc<WebhookDispatcher>,
+}
+
+impl NotificationService {
+    pub fn new(data_dir: PathBuf) -> Self {
+        let config = NotificationConfig::load(&data_dir);
+        let http = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self {
+            data_dir,
+            config: RwLock::new(config),
+            budget_fired: Mutex::new(HashMap::new()),
+            webhooks: Arc::new(WebhookDispatcher::new(WEBHOOK_TIMEOUT)),
+            http,
+        }
+    }
+
+    pub async fn config(&self) -> NotificationConfig {
+        self.config.read().await.clone()
+    }
+
+    /// Set preferences for a workspace, or the defaults when `workspace_id` is `None`.
+    pub async fn set_preferences(
+        &self,
+        workspace_id: Option<String>,
+        prefs: NotificationPreferences,
+    ) -> Result<(), String> {
+        let mut config = self.config.write().await;
+        match workspace_id {
+            Some(id) => {
+                config.workspaces.insert(id, prefs);
+            }
+            None => config.default = prefs,
+        }
+        config.save(&self.data_dir)
+    }
+
+    /// Deliver a notification through every channel enabled for its workspace.
+    pub async fn notify(&self, app: &AppHandle, notification: Notification) -> DeliveryReport {
+        let prefs = self
+            .config
+            .read()
+            .await
+            .preferences_for(notification.workspace_id.as_deref())
+            .clone();
+        if !prefs.wants(notification.kind) {
+            return DeliveryReport {
+                suppressed: true,
+                ..Default::default()
+            };
+        }
+
+        let _ = app.emit(NOTIFICATION_EVENT, &notification);
+
+        let mut report = DeliveryReport::default();
+        if prefs.os {
+            match app
+                .notification()
+                .builder()
+                .title(&notification.title)
+                .body(&notification.body)
+                .show()
+            {
+                Ok(()) => report.os = true,
+                Err(e) => tracing::warn!("Failed to show OS notification: {e}"),
+            }
+        }
+        if let Some(ref url) = prefs.webhook_url {
+            let result = self.send_webhook(url, &notification).await;
+            if let Err(ref e) = result {
+                tracing::warn!("Notification webhook failed: {e}");
+            }
+            report.webhook = Some(result);
+        }
+        // Retries can take minutes, so signed webhooks go out in the background
+        let endpoints: Vec<_> = prefs.webhooks.into_iter().filter(|e| e.wants(notification.kind)).collect();
+        if !endpoints.is_empty() {
+            let credentials = app.state::<AppState>().llm_credentials.read().await.clone();
+            for endpoint in endpoints {
+                let secret = endpoint.secret(&credentials);
+                let dispatcher = self.webhooks.clone();
+                let notification = notification.clone();
+                tauri::async_runtime::spawn(async move {
+                    dispatcher.deliver(&endpoint, secret, &notification).await;
+                });
+                report.webhooks_queued += 1;
+            }
+        }
+        if let Some(ref email) = prefs.email {
+            let result = send_email(email, &notification).await;
+            if let Err(ref e) = result {
+                tracing::warn!("Notification email failed: {e}");
+            }
+            report.email = Some(result);
+        }
+        report
+    }
+
+    /// Record budget usage for `scope` (e.g. an execution ID) and notify when
+    /// spending crosses a configured threshold for the first time.
+    pub async fn record_budget(
+        &self,
+        app: &AppHandle,
+        workspace_id: Option<String>,
+        scope: &str,
+        spent: f64,
+        limit: f64,
+    ) -> Option<f64> {
+        if limit <= 0.0 {
+            return None;
+        }
+        let fraction = spent / limit;
+        let thresholds = self
+            .config
+            .read()
+            .await
+            .preferences_for(workspace_id.as_deref())
+            .budget_thresholds
+            .clone();
+        let crossed = highest_crossed(&thresholds, fraction)?;
+        {
+            let mut fired = self.budget_fired.lock().await;
+            if fired.get(scope).is_some_and(|prev| *prev >= crossed) {
+                return None;
+            }
+            fired.insert(scope.to_string(), crossed);
+        }
+
+        let notification = Notification::new(
+            NotificationKind::BudgetThreshold,
+            format!("Budget {:.0}% used", crossed * 100.0),
+            format!("{scope}: spent {spent:.2} of {limit:.2}"),
+        )
+        .with_workspace(workspace_id);
+        self.notify(app, notification).await;
+        Some(crossed)
+    }
+
+    /// Latest signed webhook deliveries, newest first.
+    pub async fn webhook_deliveries(&self, workspace_id: Option<&str>) -> Vec<WebhookDelivery> {
+        self.webhooks.recent(workspace_id).await
+    }
+
+    /// Send a test notification to one signed webhook and wait for the
+    /// outcome, retries included.
+    pub async fn test_webhook(
+        &self,
+        workspace_id: Option<String>,
+        endpoint_id: &str,
+        credentials: &LLMCredentials,
+    ) -> Result<WebhookDelivery, String> {
+        let endpoint = self
+            .config
+            .read()
+            .await
+            .preferences_for(workspace_id.as_deref())
+            .webhooks
+            .iter()
+            .find(|e| e.id == endpoint_id)
+            .cloned()
+            .ok_or_else(|| format!("Webhook not found: {endpoint_id}"))?;
+        let notification = Notification::new(
+            NotificationKind::ExecutionCompleted,
+            "Test webhook",
+            "Webhooks are working.",
+        )
+        .with_workspace(workspace_id);
+        Ok(self
+            .webhooks
+            .deliver(&endpoint, endpoint.secret(credentials), &notification)
+            .await)
+    }
+
+    async fn send_webhook(&self, url: &str, notification: &Notification) -> Result<(), String> {
+        self.http
+            .post(url)
+            .json(notification)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+async fn send_email(settings: &EmailSettings, notification: &Notification) -> Result<(), String> {
+    use lettre::message::Mailbox;
+    use lettre::transport::smtp::authentication::Credentials;
+    use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+    let from: Mailbox = settings
+        .from
+        .parse()
+        .map_err(|e| format!("Invalid sender '{}': {e}", settings.from))?;
+    let mut builder = Message::builder()
+        .from(from)
+        .subject(format!("[Handbox] {}", notification.title));
+    for to in &settings.to {
+        builder = builder.to(to.parse().map_err(|e| format!("Invalid recipient '{to}': {e}"))?);
+    }
+    let message = builder
+        .body(notification.body.clone())
+        .map_err(|e| e.to_string())?;
+
+    let mut transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&settings.smtp_host)
+        .map_err(|e| e.to_string())?
+        .port(settings.smtp_port);
+    if let Some(ref username) = settings.username {
+        let password = settings
+            .password_env
+            .as_ref()
+            .and_then(|var| std::env::var(var).ok())
+            .unwrap_or_default();
+        transport = transport.credentials(Credentials::new(username.clone(), password));
+    }
+    transport
+        .build()
+        .send(message)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn workspace_preferences_override_defaults() {
+        let mut config = NotificationConfig::default();
+        config.workspaces.insert(
+            "ws-1".into(),
+            NotificationPreferences {
+                os: false,
+                events: vec![NotificationKind::ExecutionFailed],
+                ..Default::default()
+            },
+        );
+
+        let ws = config.preferences_for(Some("ws-1"));
+        assert!(!ws.os);
+        assert!(!ws.wants(NotificationKind::ExecutionCompleted));
+        assert!(ws.wants(NotificationKind::ExecutionFailed));
+        assert!(config.preferences_for(Some("other")).wants(NotificationKind::ExecutionCompleted));
+        assert!(config.preferences_for(None).os);
+    }
+
+    #[test]
+    fn highest_crossed_threshold() {
+        let thresholds = [0.5, 0.8, 1.0];
+        assert_eq!(highest_crossed(&thresholds, 0.3), None);
+        assert_eq!(highest_crossed(&thresholds, 0.85), Some(0.8));
+        assert_eq!(highest_crossed(&thresholds, 1.2), Some(1.0));
+    }
+}
@@ -0,0 +1,554 @@
+//! Offline bundles — one zip carrying packs, workflow templates, local model
+//! metadata (optionally the model files) and a marketplace snapshot, for
+//! machines that never see the network.
+//!
+//! Layout:
+//!
+//! ```text
+//! manifest.json                 what the bundle holds, with checksums
+//! packs/index.json              pack source index (see `pack_updater`)
+//! packs/<id>-<version>.tar.gz
+//! templates/<workflow id>.json
+//! models/<file>.gguf            only when model files were included
+//! marketplace/workflows.json
+//! ```
+//!
+//! `packs/` is a valid pack source on its own, so an extracted bundle on a
+//! shared drive can also serve updates to other air-gapped machines.
+
+use crate::commands::marketplace::MarketplaceWorkflow;
+use crate::local_models::{self, GgufInfo, LocalModel};
+use crate::pack_updater::{self, PackIndex, PackRelease, UpdateOutcome};
+use crate::team_sync;
+use chrono::{DateTime, Utc};
+use hb_core::graph::WorkflowSpec;
+use hb_core::pack::PackManifest;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// Bumped when the bundle layout changes.
+pub const BUNDLE_VERSION: u32 = 1;
+
+const MANIFEST: &str = "manifest.json";
+const PACK_INDEX: &str = "packs/index.json";
+const MARKETPLACE: &str = "marketplace/workflows.json";
+
+/// A pack archive in the bundle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundledPack {
+    pub id: String,
+    pub version: String,
+    pub sha256: String,
+    pub size_bytes: u64,
+    pub file: String,
+}
+
+/// A workflow template in the bundle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundledTemplate {
+    pub id: String,
+    pub name: String,
+    pub file: String,
+}
+
+/// A local model described by the bundle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundledModel {
+    pub id: String,
+    pub size_bytes: u64,
+    pub sha256: Option<String>,
+    pub source_url: Option<String>,
+    pub info: Option<GgufInfo>,
+    /// Path inside the bundle; `None` when only the metadata was exported.
+    pub file: Option<String>,
+}
+
+/// Describes the bundle; written as `manifest.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OfflineBundleManifest {
+    pub bundle_version: u32,
+    pub app_version: String,
+    pub created_at: DateTime<Utc>,
+    pub packs: Vec<BundledPack>,
+    pub templates: Vec<BundledTemplate>,
+    pub models: Vec<BundledModel>,
+    pub marketplace_workflows: usize,
+}
+
+/// What to put in a bundle.
+pub struct BundleContents<'a> {
+    pub packs_dir: &'a Path,
+    /// Installed packs to include.
+    pub pack_ids: Vec<String>,
+    pub templates: Vec<WorkflowSpec>,
+    pub models: Vec<LocalModel>,
+    /// Copy model files too, not just their metadata. Models are large.
+    pub include_model_files: bool,
+    pub marketplace: Vec<MarketplaceWorkflow>,
+}
+
+/// Result of installing a bundle.
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleInstall {
+    pub manifest: OfflineBundleManifest,
+    pub packs_installed: Vec<UpdateOutcome>,
+    /// Packs already installed at the same or a newer version.
+    pub packs_skipped: Vec<String>,
+    pub models_installed: Vec<String>,
+    /// Models listed without a file that are not installed here. They have
+    /// to be copied over by hand and checked with the listed checksum.
+    pub models_missing: Vec<BundledModel>,
+    pub templates_added: usize,
+    pub marketplace_added: usize,
+    /// Items that could not be installed; the rest of the bundle still is.
+    pub failed: Vec<String>,
+}
+
+/// Templates and marketplace listings read from a bundle, to be merged into
+/// app state by the caller.
+pub struct UnpackedBundle {
+    pub install: BundleInstall,
+    pub templates: Vec<WorkflowSpec>,
+    pub marketplace: Vec<MarketplaceWorkflow>,
+}
+
+/// Pack ids become directory names on install.
+fn validate_pack_id(id: &str) -> Result<(), String> {
+    let valid = !id.is_empty()
+        && !id.starts_with('.')
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid pack id '{id}'"))
+    }
+}
+
+fn hash_reader(mut reader: impl Read) -> std::io::Result<String> {
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut reader, &mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Write a bundle to `path`. The file only appears under its final name once
+/// it is complete.
+pub fn write_bundle(
+    path: &Path,
+    contents: BundleContents<'_>,
+) -> Result<OfflineBundleManifest, String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let mut part = path.as_os_str().to_os_string();
+    part.push(".part");
+    let part = std::path::PathBuf::from(part);
+
+    let written = write_entries(&part, contents);
+    match written {
+        Ok(manifest) => {
+            std::fs::rename(&part, path)
+                .map_err(|e| format!("Failed to finalize {}: {e}", path.display()))?;
+            Ok(manifest)
+        }
+        Err(e) => {
+            let _ = std::fs::remove_file(&part);
+            Err(e)
+        }
+    }
+}
+
+fn write_entries(
+    path: &Path,
+    contents: BundleContents<'_>,
+) -> Result<OfflineBundleManifest, String> {
+    let file = std::fs::File::create(path)
+        .map_err(|e| format!("Failed to create {}: {e}", path.display()))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let deflated = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    // Archives and model weights don't compress; models may pass 4 GiB
+    let stored = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Stored)
+        .large_file(true);
+    let add = |zip: &mut zip::ZipWriter<std::fs::File>, name: &str, data: &[u8]| {
+        zip.start_file(name, deflated)
+            .and_then(|_| zip.write_all(data).map_err(Into::into))
+            .map_err(|e| format!("Failed to write {name}: {e}"))
+    };
+
+    let mut manifest = OfflineBundleManifest {
+        bundle_version: BUNDLE_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: Utc::now(),
+        packs: Vec::new(),
+        templates: Vec::new(),
+        models: Vec::new(),
+        marketplace_workflows: contents.marketplace.len(),
+    };
+
+    let installed = pack_updater::installed_versions(contents.packs_dir);
+    let mut index = PackIndex::default();
+    for pack_id in &contents.pack_ids {
+        validate_pack_id(pack_id)?;
+        let version = installed
+            .iter()
+            .find(|(id, _)| id == pack_id)
+            .map(|(_, v)| v.to_string())
+            .ok_or_else(|| format!("Pack not installed: {pack_id}"))?;
+        let pack_dir = contents.packs_dir.join(pack_id);
+        let archive = team_sync::pack_archive(&pack_dir)?;
+        let kinds = std::fs::read_to_string(pack_dir.join("manifest.json"))
+            .ok()
+            .and_then(|content| serde_json::from_str::<PackManifest>(&content).ok())
+            .map(|manifest| manifest.content_kinds())
+            .unwrap_or_default();
+        let location = format!("{pack_id}-{version}.tar.gz");
+        let file = format!("packs/{location}");
+        zip.start_file(file.as_str(), stored)
+            .and_then(|_| zip.write_all(&archive).map_err(Into::into))
+            .map_err(|e| format!("Failed to write {file}: {e}"))?;
+        index.packs.push(PackRelease {
+            id: pack_id.clone(),
+            version: version.clone(),
+            changelog: String::new(),
+            location,
+            contents: kinds,
+        });
+        manifest.packs.push(BundledPack {
+            id: pack_id.clone(),
+            version,
+            sha256: hex::encode(Sha256::digest(&archive)),
+            size_bytes: archive.len() as u64,
+            file,
+        });
+    }
+    let index = serde_json::to_vec_pretty(&index).map_err(|e| e.to_string())?;
+    add(&mut zip, PACK_INDEX, &index)?;
+
+    for spec in &contents.templates {
+        let file = format!("templates/{}.json", spec.id);
+        let data = serde_json::to_vec_pretty(spec).map_err(|e| e.to_string())?;
+        add(&mut zip, &file, &data)?;
+        manifest.templates.push(BundledTemplate {
+            id: spec.id.to_string(),
+            name: spec.meta.name.clone(),
+            file,
+        });
+    }
+
+    for model in &contents.models {
+        let mut sha256 = model.sha256.clone();
+        let mut file = None;
+        if contents.include_model_files {
+            let name = format!("models/{}", model.id);
+            let open = || {
+                std::fs::File::open(&model.path)
+                    .map_err(|e| format!("Cannot open {}: {e}", model.path.display()))
+            };
+            if sha256.is_none() {
+                sha256 = Some(hash_reader(open()?).map_err(|e| e.to_string())?);
+            }
+            zip.start_file(name.as_str(), stored)
+                .map_err(|e| format!("Failed to write {name}: {e}"))?;
+            std::io::copy(&mut open()?, &mut zip)
+                .map_err(|e| format!("Failed to write {name}: {e}"))?;
+            file = Some(name);
+        }
+        manifest.models.push(BundledModel {
+            id: model.id.clone(),
+            size_bytes: model.size_bytes,
+            sha256,
+            source_url: model.source_url.clone(),
+            info: model.info.clone(),
+            file,
+        });
+    }
+
+    let marketplace =
+        serde_json::to_vec_pretty(&contents.marketplace).map_err(|e| e.to_string())?;
+    add(&mut zip, MARKETPLACE, &marketplace)?;
+
+    let manifest_json = serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?;
+    add(&mut zip, MANIFEST, &manifest_json)?;
+    zip.finish().map_err(|e| e.to_string())?;
+    Ok(manifest)
+}
+
+fn open_bundle(path: &Path) -> Result<zip::ZipArchive<std::fs::File>, String> {
+    let file =
+        std::fs::File::open(path).map_err(|e| format!("Cannot open {}: {e}", path.display()))?;
+    zip::ZipArchive::new(file).map_err(|e| format!("Not an offline bundle: {e}"))
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(
+    zip: &mut zip::ZipArchive<std::fs::File>,
+    name: &str,
+) -> Result<T, String> {
+    let entry = zip
+        .by_name(name)
+        .map_err(|e| format!("Bundle is missing {name}: {e}"))?;
+    serde_json::from_reader(entry).map_err(|e| format!("Invalid {name} in bundle: {e}"))
+}
+
+fn read_manifest_from(
+    zip: &mut zip::ZipArchive<std::fs::File>,
+) -> Result<OfflineBundleManifest, String> {
+    let manifest: OfflineBundleManifest = read_json(zip, MANIFEST)?;
+    if manifest.bundle_version > BUNDLE_VERSION {
+        return Err(format!(
+            "Bundle format {} is newer than this app supports ({BUNDLE_VERSION})",
+            manifest.bundle_version
+        ));
+    }
+    Ok(manifest)
+}
+
+/// Read a bundle's manifest without installing anything.
+pub fn read_manifest(path: &Path) -> Result<OfflineBundleManifest, String> {
+    read_manifest_from(&mut open_bundle(path)?)
+}
+
+/// Install the packs and model files in a bundle and read its templates and
+/// marketplace snapshot. Packs are only installed over older versions, and
+/// every archive and model is checked against the manifest checksum first.
+pub fn install_bundle(
+    path: &Path,
+    packs_dir: &Path,
+    models_dir: &Path,
+) -> Result<UnpackedBundle, String> {
+    let mut zip = open_bundle(path)?;
+    let manifest = read_manifest_from(&mut zip)?;
+    let mut install = BundleInstall {
+        manifest: manifest.clone(),
+        packs_installed: Vec::new(),
+        packs_skipped: Vec::new(),
+        models_installed: Vec::new(),
+        models_missing: Vec::new(),
+        templates_added: 0,
+        marketplace_added: 0,
+        failed: Vec::new(),
+    };
+
+    let installed = pack_updater::installed_versions(packs_dir);
+    for pack in &manifest.packs {
+        let result = (|| {
+            validate_pack_id(&pack.id)?;
+            let version = semver::Version::parse(&pack.version)
+                .map_err(|e| format!("invalid version '{}': {e}", pack.version))?;
+            if installed
+                .iter()
+                .any(|(id, current)| *id == pack.id && *current >= version)
+            {
+                return Ok(None);
+            }
+            let mut archive = Vec::new();
+            zip.by_name(&pack.file)
+                .map_err(|e| format!("missing {}: {e}", pack.file))?
+                .read_to_end(&mut archive)
+                .map_err(|e| format!("unreadable {}: {e}", pack.file))?;
+            if hex::encode(Sha256::digest(&archive)) != pack.sha256 {
+                return Err("checksum mismatch".to_string());
+            }
+            pack_updater::install_archive(packs_dir, &pack.id, archive.as_slice(), None).map(Some)
+        })();
+        match result {
+            Ok(Some(outcome)) => install.packs_installed.push(outcome),
+            Ok(None) => install.packs_skipped.push(pack.id.clone()),
+            Err(e) => install
+                .failed
+                .push(format!("Pack {}@{}: {e}", pack.id, pack.version)),
+        }
+    }
+
+    for model in &manifest.models {
+        if models_dir.join(&model.id).is_file() {
+            continue;
+        }
+        let Some(file) = &model.file else {
+            install.models_missing.push(model.clone());
+            continue;
+        };
+        let source = model.source_url.as_deref().unwrap_or("offline-bundle");
+        let result = zip
+            .by_name(file)
+            .map_err(|e| format!("missing {file}: {e}"))
+            .and_then(|entry| {
+                local_models::install_model_file(
+                    models_dir,
+                    &model.id,
+                    entry,
+                    model.sha256.as_deref(),
+                    source,
+                )
+            });
+        match result {
+            Ok(_) => install.models_installed.push(model.id.clone()),
+            Err(e) => install.failed.push(format!("Model {}: {e}", model.id)),
+        }
+    }
+
+    let mut templates = Vec::new();
+    for template in &manifest.templates {
+        let spec = read_json(&mut zip, &template.file).and_then(|value| {
+            WorkflowSpec::from_value(value).map_err(|e| format!("Invalid {}: {e}", template.file))
+        });
+        match spec {
+            Ok(upgraded) => templates.push(upgraded.spec),
+            Err(e) => install
+                .failed
+                .push(format!("Template {}: {e}", template.name)),
+        }
+    }
+    let marketplace = read_json(&mut zip, MARKETPLACE)?;
+
+    Ok(UnpackedBundle {
+        install,
+        templates,
+        marketplace,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("hb-offline-{name}-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_pack(dir: &Path, id: &str, version: &str) {
+        fs::create_dir_all(dir.join("templates")).unwrap();
+        fs::write(
+            dir.join("manifest.json"),
+            serde_json::json!({ "id": id, "version": version }).to_string(),
+        )
+        .unwrap();
+        fs::write(dir.join("templates/basic.json"), "{}").unwrap();
+    }
+
+    fn model(dir: &Path, id: &str, with_file: bool) -> LocalModel {
+        let path = dir.join(id);
+        if with_file {
+            // Smallest valid GGUF: magic, version, no tensors, no metadata
+            let mut header = b"GGUF".to_vec();
+            header.extend(3u32.to_le_bytes());
+            header.extend(0u64.to_le_bytes());
+            header.extend(0u64.to_le_bytes());
+            fs::write(&path, header).unwrap();
+        }
+        LocalModel {
+            id: id.to_string(),
+            path,
+            size_bytes: 24,
+            source_url: Some(format!("https://models.example/{id}")),
+            sha256: None,
+            info: None,
+            error: None,
+            memory: local_models::memory_check(24, None, &Default::default()),
+        }
+    }
+
+    #[test]
+    fn round_trips_packs_models_and_templates() {
+        let source = temp_dir("source");
+        write_pack(&source.join("packs/rag-pack"), "rag-pack", "1.2.0");
+        let models = source.join("models");
+        fs::create_dir_all(&models).unwrap();
+        let mut template = WorkflowSpec::default();
+        template.meta.name = "Site report".into();
+
+        let bundle = source.join("out/bundle.zip");
+        let manifest = write_bundle(
+            &bundle,
+            BundleContents {
+                packs_dir: &source.join("packs"),
+                pack_ids: vec!["rag-pack".into()],
+                templates: vec![template.clone()],
+                models: vec![model(&models, "tiny.gguf", true)],
+                include_model_files: true,
+                marketplace: vec![],
+            },
+        )
+        .unwrap();
+        assert_eq!(manifest.packs[0].file, "packs/rag-pack-1.2.0.tar.gz");
+        assert!(manifest.models[0].sha256.is_some());
+        assert_eq!(
+            read_manifest(&bundle).unwrap().templates[0].name,
+            "Site report"
+        );
+        assert!(!bundle.with_extension("zip.part").exists());
+
+        // A machine with an older rag-pack and no models
+        let target = temp_dir("target");
+        let packs = target.join("packs");
+        write_pack(&packs.join("rag-pack"), "rag-pack", "1.0.0");
+        let unpacked = install_bundle(&bundle, &packs, &target.join("models")).unwrap();
+        let install = unpacked.install;
+        assert!(install.failed.is_empty(), "{:?}", install.failed);
+        assert_eq!(
+            install.packs_installed[0].previous_version.as_deref(),
+            Some("1.0.0")
+        );
+        assert!(packs.join("rag-pack/templates/basic.json").exists());
+        assert_eq!(install.models_installed, ["tiny.gguf"]);
+        assert!(target.join("models/tiny.gguf.meta.json").exists());
+        assert_eq!(unpacked.templates[0].id, template.id);
+
+        // Installing again changes nothing
+        let again = install_bundle(&bundle, &packs, &target.join("models")).unwrap();
+        assert_eq!(again.install.packs_skipped, ["rag-pack"]);
+        assert!(again.install.models_installed.is_empty());
+
+        let _ = fs::remove_dir_all(source);
+        let _ = fs::remove_dir_all(target);
+    }
+
+    #[test]
+    fn metadata_only_models_are_reported_missing() {
+        let dir = temp_dir("meta");
+        let bundle = dir.join("bundle.zip");
+        write_bundle(
+            &bundle,
+            BundleContents {
+                packs_dir: &dir.join("packs"),
+                pack_ids: vec![],
+                templates: vec![],
+                models: vec![model(&dir, "big.Q4_K_M.gguf", false)],
+                include_model_files: false,
+                marketplace: vec![],
+            },
+        )
+        .unwrap();
+
+        let unpacked = install_bundle(&bundle, &dir.join("packs"), &dir.join("models")).unwrap();
+        let missing = &unpacked.install.models_missing;
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].file, None);
+        assert_eq!(
+            missing[0].source_url.as_deref(),
+            Some("https://models.example/big.Q4_K_M.gguf")
+        );
+        assert!(write_bundle(
+            &dir.join("bad.zip"),
+            BundleContents {
+                packs_dir: &dir.join("packs"),
+                pack_ids: vec!["../escape".into()],
+                templates: vec![],
+                models: vec![],
+                include_model_files: false,
+                marketplace: vec![],
+            },
+        )
+        .is_err());
+        assert!(!dir.join("bad.zip.part").exists());
+        let _ = fs::remove_dir_all(dir);
+    }
+}
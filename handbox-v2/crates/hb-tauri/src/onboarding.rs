@@ -0,0 +1,478 @@
+//! Workspace onboarding — turns a new user's answers to a few questions
+//! (what they work on, where their data lives, which LLM provider they use)
+//! into a ready workspace: a data source for their data, a default policy,
+//! the provider's configuration and starter workflows built from the
+//! compiler's templates, so they don't start from an empty canvas.
+
+use chrono::Utc;
+use hb_compiler::classifier::TaskType;
+use hb_compiler::slot_filler::Slots;
+use hb_compiler::template;
+use hb_core::graph::{NodeEntry, WorkflowSpec};
+use hb_core::policy::{
+    CommandPolicy, ConcurrencyPolicy, CostLimit, EdgeCapturePolicy, LivenessPolicy, PermissionSet, Policy,
+    SensitiveDataPolicy, SensitiveDataRule, ToolWhitelist,
+};
+use hb_core::project::{
+    DataSource, DataSourceKind, LlmProviderConfig, LlmProviderType, SensitiveCategory,
+    WorkspaceConfig,
+};
+use hb_core::tool::Permission;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// ID of the data source created for the user's data location.
+pub const DATA_SOURCE_ID: &str = "primary-data";
+
+/// Metadata key naming the credential store entry that holds a database
+/// source's full connection string.
+pub const CONNECTION_SECRET_KEY: &str = "connection_secret";
+
+/// Spend a run may reach before the cost preflight refuses it, for cloud
+/// providers. Local models are not limited.
+const CLOUD_MAX_COST_USD: f64 = 5.0;
+
+/// Nodes silent this long are killed (a stall warning comes much earlier).
+const KILL_AFTER_SECS: u64 = 30 * 60;
+
+/// Identifiers and card numbers stay on the machine when the provider is a
+/// cloud service: only tools that don't call a model may read them.
+const LOCAL_ONLY: [SensitiveCategory; 3] = [
+    SensitiveCategory::ResidentRegistrationNumber,
+    SensitiveCategory::SocialSecurityNumber,
+    SensitiveCategory::CardNumber,
+];
+
+/// What the user mostly works on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Domain {
+    /// Documents, papers and knowledge bases.
+    Research,
+    DataAnalysis,
+    SoftwareDevelopment,
+    CustomerSupport,
+    Translation,
+    General,
+}
+
+impl Domain {
+    /// Templates the starter workflows are built from.
+    fn starter_tasks(self) -> Vec<TaskType> {
+        match self {
+            Self::Research => vec![TaskType::Rag, TaskType::Summarize, TaskType::QaExtraction],
+            Self::DataAnalysis => vec![TaskType::DataAnalysis, TaskType::ReportGeneration],
+            Self::SoftwareDevelopment => vec![TaskType::CodeReview, TaskType::Summarize],
+            Self::CustomerSupport => vec![
+                TaskType::KnowledgeBaseBuild,
+                TaskType::Rag,
+                TaskType::SentimentAnalysis,
+            ],
+            Self::Translation => vec![TaskType::Translation, TaskType::Summarize],
+            Self::General => vec![TaskType::Summarize, TaskType::Rag],
+        }
+    }
+
+    fn tag(self) -> &'static str {
+        match self {
+            Self::Research => "research",
+            Self::DataAnalysis => "data-analysis",
+            Self::SoftwareDevelopment => "software-development",
+            Self::CustomerSupport => "customer-support",
+            Self::Translation => "translation",
+            Self::General => "general",
+        }
+    }
+}
+
+/// Answers to the onboarding questions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnboardingAnswers {
+    /// Name of the workspace to create.
+    pub name: String,
+    pub domain: Domain,
+    /// Directory, file, URL or connection string of the user's data.
+    #[serde(default)]
+    pub data_location: Option<String>,
+    pub llm_provider: LlmProviderType,
+    /// Model to use; the provider's usual default when unset.
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Project root; the data directory when unset and the data is local.
+    #[serde(default)]
+    pub root_path: Option<String>,
+}
+
+/// What onboarding will create.
+#[derive(Debug, Clone, Serialize)]
+pub struct OnboardingPlan {
+    pub workspace: WorkspaceConfig,
+    pub workflows: Vec<WorkflowSpec>,
+    /// Things the user should look at, e.g. a data location that doesn't exist.
+    pub warnings: Vec<String>,
+    /// Values for the credential store's service keys, by name: database
+    /// connection strings, which may hold passwords. Never sent to the UI.
+    #[serde(skip)]
+    pub secrets: BTreeMap<String, String>,
+}
+
+/// Provider name used by the credential store and health checks, for the
+/// provider types the app can validate.
+pub fn credential_provider(provider: &LlmProviderType) -> Option<&'static str> {
+    match provider {
+        LlmProviderType::OpenAi => Some("openai"),
+        LlmProviderType::Anthropic => Some("anthropic"),
+        LlmProviderType::AwsBedrock => Some("bedrock"),
+        LlmProviderType::Ollama => Some("local"),
+        LlmProviderType::GoogleVertex | LlmProviderType::Custom => None,
+    }
+}
+
+/// Build the workspace, policy and starter workflows for `answers`.
+pub fn plan(answers: &OnboardingAnswers) -> Result<OnboardingPlan, String> {
+    let name = answers.name.trim();
+    if name.is_empty() {
+        return Err("A workspace name is required".into());
+    }
+
+    let mut warnings = Vec::new();
+    let location = answers
+        .data_location
+        .as_deref()
+        .map(str::trim)
+        .filter(|l| !l.is_empty());
+    let workspace_id = uuid::Uuid::new_v4();
+    let mut secrets = BTreeMap::new();
+    let mut data_source = location.map(|location| data_source(location, &mut warnings));
+    if let Some(source) = &mut data_source {
+        secrets.extend(move_connection_secret(workspace_id, source));
+    }
+    if data_source.is_none() {
+        warnings.push(
+            "No data location given; starter workflows read placeholder files until one is added"
+                .into(),
+        );
+    }
+
+    let root_path = match (&answers.root_path, &data_source) {
+        (Some(root), _) if !root.trim().is_empty() => root.trim().to_string(),
+        (_, Some(source)) if source.kind == DataSourceKind::LocalDirectory => {
+            source.path_or_uri.clone()
+        }
+        (_, Some(source)) if source.kind == DataSourceKind::LocalFile => {
+            Path::new(&source.path_or_uri)
+                .parent()
+                .map(|p| p.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        }
+        _ => String::new(),
+    };
+
+    let mut slots = Slots::default();
+    if let Some(source) = &data_source {
+        slots.values.insert(
+            "data_source".into(),
+            serde_json::Value::String(source.path_or_uri.clone()),
+        );
+    }
+    let mut workflows = Vec::new();
+    for task in answers.domain.starter_tasks() {
+        match template::match_template(&task, &slots) {
+            Ok(Some(mut spec)) => {
+                spec.meta.tags = vec!["starter".into(), answers.domain.tag().into()];
+                workflows.push(spec);
+            }
+            Ok(None) => {}
+            Err(e) => warnings.push(format!("Skipped a starter workflow: {e}")),
+        }
+    }
+
+    if credential_provider(&answers.llm_provider).is_none() {
+        warnings.push(format!(
+            "{:?} credentials can't be checked here; make sure the provider is configured",
+            answers.llm_provider
+        ));
+    }
+
+    let now = Utc::now();
+    let workspace = WorkspaceConfig {
+        id: workspace_id,
+        name: name.to_string(),
+        description: Some(format!("{} workspace", answers.domain.tag())),
+        root_path,
+        created_at: now,
+        updated_at: now,
+        data_sources: data_source.into_iter().collect(),
+        indexes: vec![],
+        default_policy: Some(default_policy(&answers.llm_provider, &workflows)),
+        llm_providers: vec![provider_config(answers)],
+        timezone: None,
+        members: vec![],
+        review_policy: None,
+    };
+    Ok(OnboardingPlan {
+        workspace,
+        workflows,
+        warnings,
+        secrets,
+    })
+}
+
+/// Take a database source's connection string out of its config: the full
+/// string goes to the credential store under the returned name, which the
+/// source's metadata records, and the source keeps it without the password
+/// or query. Other sources are left alone.
+pub fn move_connection_secret(
+    workspace_id: uuid::Uuid,
+    source: &mut DataSource,
+) -> Option<(String, String)> {
+    if source.kind != DataSourceKind::Database {
+        return None;
+    }
+    let name = format!("DB_{}_{}", source.id, workspace_id.simple())
+        .to_ascii_uppercase()
+        .replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+    let redacted = without_credentials(&source.path_or_uri);
+    let full = std::mem::replace(&mut source.path_or_uri, redacted);
+    source
+        .metadata
+        .insert(CONNECTION_SECRET_KEY.into(), name.clone().into());
+    Some((name, full))
+}
+
+/// `uri` without the password in its user info and without its query, where
+/// drivers also take passwords.
+fn without_credentials(uri: &str) -> String {
+    let uri = uri.split(['?', '#']).next().unwrap_or(uri);
+    let Some((scheme, rest)) = uri.split_once("://") else {
+        return uri.to_string();
+    };
+    let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    match authority.rsplit_once('@') {
+        Some((user_info, host)) => match user_info.split(':').next() {
+            Some(user) if !user.is_empty() => format!("{scheme}://{user}@{host}{path}"),
+            _ => format!("{scheme}://{host}{path}"),
+        },
+        None => uri.to_string(),
+    }
+}
+
+/// A data source for the user's data location, guessing its kind.
+fn data_source(location: &str, warnings: &mut Vec<String>) -> DataSource {
+    let lower = location.to_ascii_lowercase();
+    let scheme = lower.split_once("://").map(|(scheme, _)| scheme);
+    let (kind, path_or_uri) = match scheme {
+        Some("http" | "https") => (DataSourceKind::WebUrl, location.to_string()),
+        Some("s3") => (DataSourceKind::S3, location.to_string()),
+        Some("postgres" | "postgresql" | "mysql" | "sqlite" | "mongodb") => {
+            (DataSourceKind::Database, location.to_string())
+        }
+        Some(_) => (DataSourceKind::Api, location.to_string()),
+        None => {
+            let path = hb_core::path::resolve(location);
+            let kind = if path.is_dir() {
+                DataSourceKind::LocalDirectory
+            } else if path.is_file() {
+                DataSourceKind::LocalFile
+            } else {
+                warnings.push(format!(
+                    "Data location {} does not exist yet",
+                    path.display()
+                ));
+                if path.extension().is_some() {
+                    DataSourceKind::LocalFile
+                } else {
+                    DataSourceKind::LocalDirectory
+                }
+            };
+            (kind, path.to_string_lossy().into_owned())
+        }
+    };
+    DataSource {
+        id: DATA_SOURCE_ID.into(),
+        name: "Primary data".into(),
+        kind,
+        path_or_uri,
+        metadata: Default::default(),
+        classification: None,
+    }
+}
+
+/// Read and write files and reach the network, but start no programs;
+/// cloud providers get a spending limit and keep identifiers local.
+fn default_policy(provider: &LlmProviderType, workflows: &[WorkflowSpec]) -> Policy {
+    let local = matches!(provider, LlmProviderType::Ollama);
+    let local_tools: Vec<String> = workflows
+        .iter()
+        .flat_map(|spec| &spec.nodes)
+        .filter_map(|node| match node {
+            NodeEntry::Primitive(node) => node.tool_ref.split('@').next(),
+            _ => None,
+        })
+        .filter(|tool| !tool.contains("llm") && !tool.contains("embedding"))
+        .map(String::from)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let sensitive_data = if local {
+        SensitiveDataPolicy::default()
+    } else {
+        SensitiveDataPolicy {
+            rules: LOCAL_ONLY
+                .into_iter()
+                .map(|category| SensitiveDataRule {
+                    category,
+                    allowed_tools: local_tools.clone(),
+                })
+                .collect(),
+        }
+    };
+    Policy {
+        permissions: PermissionSet {
+            granted: ["fs.read", "fs.write", "network.outbound"]
+                .into_iter()
+                .map(Permission::new)
+                .collect(),
+            denied: vec![Permission::new("process.exec")],
+        },
+        cost_limit: CostLimit {
+            max_execution_time_secs: None,
+            max_node_time_secs: None,
+            max_cost_usd: (!local).then_some(CLOUD_MAX_COST_USD),
+            max_tokens: None,
+        },
+        tool_whitelist: ToolWhitelist {
+            allowed_tools: vec![],
+            blocked_tools: vec![],
+        },
+        edge_capture: EdgeCapturePolicy::default(),
+        sensitive_data,
+        commands: CommandPolicy::default(),
+        liveness: LivenessPolicy {
+            kill_after_secs: Some(KILL_AFTER_SECS),
+            ..Default::default()
+        },
+        concurrency: ConcurrencyPolicy::default(),
+        sharing: Default::default(),
+    }
+}
+
+fn provider_config(answers: &OnboardingAnswers) -> LlmProviderConfig {
+    let (id, name, credential_ref, default_model) = match answers.llm_provider {
+        LlmProviderType::OpenAi => ("openai", "OpenAI", "openai_api_key", Some("gpt-4o-mini")),
+        LlmProviderType::Anthropic => (
+            "anthropic",
+            "Anthropic",
+            "anthropic_api_key",
+            Some("claude-sonnet-4-20250514"),
+        ),
+        LlmProviderType::AwsBedrock => (
+            "bedrock",
+            "AWS Bedrock",
+            "aws_access_key_id",
+            Some("anthropic.claude-3-5-sonnet-20240620-v1:0"),
+        ),
+        LlmProviderType::Ollama => ("local", "Ollama", "local_endpoint", Some("llama3.2")),
+        LlmProviderType::GoogleVertex => ("vertex", "Google Vertex AI", "google_vertex", None),
+        LlmProviderType::Custom => ("custom", "Custom", "custom", None),
+    };
+    LlmProviderConfig {
+        id: id.into(),
+        name: name.into(),
+        provider_type: answers.llm_provider.clone(),
+        credential_ref: credential_ref.into(),
+        default_model: answers
+            .model
+            .clone()
+            .filter(|m| !m.trim().is_empty())
+            .or(default_model.map(String::from)),
+        enabled: true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answers(domain: Domain, provider: LlmProviderType) -> OnboardingAnswers {
+        OnboardingAnswers {
+            name: "Papers".into(),
+            domain,
+            data_location: None,
+            llm_provider: provider,
+            model: None,
+            root_path: None,
+        }
+    }
+
+    #[test]
+    fn plans_a_workspace_around_local_data() {
+        let dir = std::env::temp_dir().join(format!("hb-onboarding-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut answers = answers(Domain::Research, LlmProviderType::Anthropic);
+        answers.data_location = Some(dir.to_string_lossy().into_owned());
+
+        let plan = plan(&answers).unwrap();
+        let ws = &plan.workspace;
+        assert_eq!(ws.data_sources[0].kind, DataSourceKind::LocalDirectory);
+        assert_eq!(ws.root_path, ws.data_sources[0].path_or_uri);
+        assert_eq!(ws.llm_providers[0].credential_ref, "anthropic_api_key");
+        assert_eq!(plan.workflows.len(), 3);
+        assert!(plan.workflows[0].meta.description.contains(&ws.root_path));
+        assert!(plan.warnings.is_empty(), "{:?}", plan.warnings);
+
+        let policy = ws.default_policy.as_ref().unwrap();
+        assert_eq!(policy.cost_limit.max_cost_usd, Some(CLOUD_MAX_COST_USD));
+        let rule = &policy.sensitive_data.rules[0];
+        assert!(rule
+            .allowed_tools
+            .contains(&"core-tools/file-read".to_string()));
+        assert!(!rule.allowed_tools.iter().any(|t| t.contains("llm")));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn guesses_remote_sources_and_warns_about_gaps() {
+        let mut answers = answers(Domain::DataAnalysis, LlmProviderType::Ollama);
+        answers.data_location = Some("postgres://db/sales".into());
+        answers.model = Some("qwen2.5".into());
+        let plan = super::plan(&answers).unwrap();
+        assert_eq!(
+            plan.workspace.data_sources[0].kind,
+            DataSourceKind::Database
+        );
+        assert_eq!(plan.workspace.root_path, "");
+        assert_eq!(
+            plan.workspace.llm_providers[0].default_model.as_deref(),
+            Some("qwen2.5")
+        );
+        let policy = plan.workspace.default_policy.unwrap();
+        assert!(policy.cost_limit.max_cost_usd.is_none());
+        assert!(policy.sensitive_data.rules.is_empty());
+
+        let mut answers = self::answers(Domain::DataAnalysis, LlmProviderType::Ollama);
+        let url = "postgres://sales:pa55@db:5432/sales?sslmode=require";
+        answers.data_location = Some(url.into());
+        let plan = super::plan(&answers).unwrap();
+        let source = &plan.workspace.data_sources[0];
+        assert_eq!(source.path_or_uri, "postgres://sales@db:5432/sales");
+        let name = source.metadata[CONNECTION_SECRET_KEY].as_str().unwrap();
+        assert_eq!(plan.secrets[name], url);
+        let shown = serde_json::to_string(&plan).unwrap();
+        assert!(!shown.contains("pa55"), "{shown}");
+
+        let mut answers = self::answers(Domain::General, LlmProviderType::Custom);
+        answers.data_location = Some("/no/such/place/notes.txt".into());
+        let plan = super::plan(&answers).unwrap();
+        assert_eq!(
+            plan.workspace.data_sources[0].kind,
+            DataSourceKind::LocalFile
+        );
+        assert_eq!(plan.warnings.len(), 2);
+
+        let mut answers = self::answers(Domain::General, LlmProviderType::OpenAi);
+        answers.name = "  ".into();
+        assert!(super::plan(&answers).is_err());
+    }
+}
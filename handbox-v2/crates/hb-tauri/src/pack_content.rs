@@ -0,0 +1,321 @@
+//! Pack content library — composites, compile templates, prompt libraries and
+//! eval suites shipped by packs, installed next to the packs directory under
+//! one layout whichever pack they came from. The editor instantiates
+//! composites from here; the other kinds are listed and read through the
+//! pack commands, and neither the compiler nor an eval runner picks them up
+//! on its own yet:
+//!
+//! ```text
+//! library/composites/<pack id>/...
+//! library/compile-templates/<pack id>/content.json
+//! library/compile-templates/<pack id>/<file as listed in the manifest>
+//! library/prompts/<pack id>/...
+//! library/evals/<pack id>/...
+//! ```
+//!
+//! `content.json` records the pack version each entry came from. The library
+//! is rebuilt for a pack whenever it is installed, updated or rolled back, so
+//! it always matches the installed version.
+
+use hb_core::pack::{
+    CompileTemplate, CompositeDefinition, EvalSuite, PackContentKind, PackManifest, PromptLibrary,
+};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Index file written into each pack's directory of a kind.
+const INDEX_FILE: &str = "content.json";
+
+/// One installed composite, compile template, prompt library or eval suite.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstalledContent {
+    pub kind: PackContentKind,
+    pub pack_id: String,
+    /// Version of the pack the content was installed from.
+    pub version: String,
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// Path of the installed file.
+    pub path: PathBuf,
+}
+
+/// Check that `pack_id` is usable as a directory name: ASCII letters,
+/// digits, `.`, `_` and `-`, without `..`.
+pub fn check_pack_id(pack_id: &str) -> Result<(), String> {
+    let valid = !pack_id.is_empty()
+        && pack_id != "."
+        && !pack_id.contains("..")
+        && pack_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid pack ID '{pack_id}'"))
+    }
+}
+
+/// `path`, resolved through symlinks, if it stays inside `root`.
+fn contained(root: &Path, path: &Path) -> Result<PathBuf, String> {
+    let root = root.canonicalize().map_err(|e| format!("{}: {e}", root.display()))?;
+    let path = path.canonicalize().map_err(|e| e.to_string())?;
+    if path.starts_with(&root) {
+        Ok(path)
+    } else {
+        Err("path leaves the pack directory".into())
+    }
+}
+
+/// The content library that belongs to `packs_dir`.
+pub fn library_dir(packs_dir: &Path) -> PathBuf {
+    packs_dir.with_file_name("library")
+}
+
+/// Rebuild the library entries of `pack_id` from its installed manifest.
+/// Entries of a pack that is no longer installed, or no longer ships a kind,
+/// are removed. Files that are missing or do not parse as their kind are
+/// skipped and returned as problems.
+pub fn sync(packs_dir: &Path, pack_id: &str) -> Result<Vec<String>, String> {
+    check_pack_id(pack_id)?;
+    let pack_dir = packs_dir.join(pack_id);
+    // A pack directory that links elsewhere installs nothing
+    let manifest = contained(packs_dir, &pack_dir)
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join("manifest.json")).ok())
+        .and_then(|content| serde_json::from_str::<PackManifest>(&content).ok());
+    let library = library_dir(packs_dir);
+
+    let mut problems = Vec::new();
+    for kind in PackContentKind::LIBRARY {
+        let target = library.join(kind.install_dir()).join(pack_id);
+        if target.exists() {
+            fs::remove_dir_all(&target)
+                .map_err(|e| format!("Failed to clear {}: {e}", target.display()))?;
+        }
+        let Some(manifest) = manifest.as_ref().filter(|m| m.id == pack_id) else {
+            continue;
+        };
+        let mut index = Vec::new();
+        for file in manifest.files(kind) {
+            match install_file(&pack_dir, &target, kind, file, manifest) {
+                Ok(entry) => index.push(entry),
+                Err(e) => problems.push(format!("{pack_id}: {file}: {e}")),
+            }
+        }
+        if !index.is_empty() {
+            let content = serde_json::to_string_pretty(&index).map_err(|e| e.to_string())?;
+            fs::write(target.join(INDEX_FILE), content).map_err(|e| e.to_string())?;
+        }
+    }
+    for problem in &problems {
+        tracing::warn!("Skipped pack content {problem}");
+    }
+    Ok(problems)
+}
+
+fn install_file(
+    pack_dir: &Path,
+    target: &Path,
+    kind: PackContentKind,
+    file: &str,
+    manifest: &PackManifest,
+) -> Result<InstalledContent, String> {
+    let relative = Path::new(file);
+    if !relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Err("path leaves the pack directory".into());
+    }
+    let content =
+        fs::read_to_string(contained(pack_dir, &pack_dir.join(relative))?).map_err(|e| e.to_string())?;
+    let (id, name, description) =
+        match kind {
+            PackContentKind::Composite => serde_json::from_str::<CompositeDefinition>(&content)
+                .map(|c| (c.id, c.name, c.description)),
+            PackContentKind::CompileTemplate => serde_json::from_str::<CompileTemplate>(&content)
+                .map(|t| (t.id, t.name, t.description)),
+            PackContentKind::PromptLibrary => serde_json::from_str::<PromptLibrary>(&content)
+                .map(|l| (l.id, l.name, l.description)),
+            PackContentKind::EvalSuite => {
+                serde_json::from_str::<EvalSuite>(&content).map(|s| (s.id, s.name, s.description))
+            }
+            _ => {
+                return Err(format!(
+                    "{kind:?} content is not installed into the library"
+                ))
+            }
+        }
+        .map_err(|e| format!("not a valid {}: {e}", kind.install_dir()))?;
+
+    let path = target.join(relative);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::write(&path, content).map_err(|e| e.to_string())?;
+    Ok(InstalledContent {
+        kind,
+        pack_id: manifest.id.clone(),
+        version: manifest.version.clone(),
+        id,
+        name,
+        description,
+        path,
+    })
+}
+
+/// Installed library content, optionally of one kind, by kind then pack.
+pub fn list(packs_dir: &Path, kind: Option<PackContentKind>) -> Vec<InstalledContent> {
+    let library = library_dir(packs_dir);
+    let mut installed = Vec::new();
+    for kind in PackContentKind::LIBRARY
+        .into_iter()
+        .filter(|k| kind.is_none_or(|kind| kind == *k))
+    {
+        let Ok(entries) = fs::read_dir(library.join(kind.install_dir())) else {
+            continue;
+        };
+        let mut packs: Vec<_> = entries.flatten().map(|e| e.path()).collect();
+        packs.sort();
+        for pack in packs {
+            if let Some(index) = fs::read_to_string(pack.join(INDEX_FILE))
+                .ok()
+                .and_then(|content| serde_json::from_str::<Vec<InstalledContent>>(&content).ok())
+            {
+                installed.extend(index);
+            }
+        }
+    }
+    installed
+}
+
+/// Read one installed entry's file.
+pub fn read(
+    packs_dir: &Path,
+    kind: PackContentKind,
+    pack_id: &str,
+    id: &str,
+) -> Result<serde_json::Value, String> {
+    let entry = list(packs_dir, Some(kind))
+        .into_iter()
+        .find(|e| e.pack_id == pack_id && e.id == id)
+        .ok_or_else(|| {
+            format!(
+                "No {} '{id}' installed from pack '{pack_id}'",
+                kind.install_dir()
+            )
+        })?;
+    let content = fs::read_to_string(&entry.path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn write_pack(packs: &Path, version: &str, with_evals: bool) {
+        let dir = packs.join("recipes");
+        fs::create_dir_all(dir.join("compile")).unwrap();
+        fs::create_dir_all(dir.join("evals")).unwrap();
+        fs::write(
+            dir.join("compile/summarize.json"),
+            json!({
+                "id": "summarize-pdf",
+                "name": "Summarize a PDF",
+                "keywords": ["summarize", "pdf"],
+                "slots": [{ "name": "data_source", "default": "doc.pdf" }],
+                "workflow": {
+                    "version": "0.1.0",
+                    "meta": { "name": "Summarize {{data_source}}", "description": "" },
+                    "nodes": [],
+                    "edges": [],
+                },
+            })
+            .to_string(),
+        )
+        .unwrap();
+        fs::write(dir.join("compile/broken.json"), "{}").unwrap();
+        fs::write(
+            dir.join("evals/summaries.json"),
+            json!({
+                "id": "summaries",
+                "name": "Summaries",
+                "target": "summarize-pdf",
+                "cases": [{ "name": "short", "inputs": { "prompt": "summarize a.pdf" } }],
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let mut manifest = json!({
+            "pack_version": "0.1.0",
+            "id": "recipes",
+            "version": version,
+            "name": "Recipes",
+            "description": "",
+            "author": "Tests",
+            "license": "MIT",
+            "platform_version": "0.1.0",
+            "category": "custom",
+            "compile_templates": ["compile/summarize.json", "compile/broken.json", "../escape.json"],
+        });
+        if with_evals {
+            manifest["eval_suites"] = json!(["evals/summaries.json"]);
+        }
+        fs::write(dir.join("manifest.json"), manifest.to_string()).unwrap();
+    }
+
+    #[test]
+    fn installs_each_kind_under_its_own_directory_and_follows_the_pack() {
+        let root = std::env::temp_dir().join(format!("hb-content-{}", uuid::Uuid::new_v4()));
+        let packs = root.join("packs");
+        write_pack(&packs, "1.0.0", true);
+
+        let problems = sync(&packs, "recipes").unwrap();
+        assert_eq!(problems.len(), 2);
+        let installed = list(&packs, None);
+        assert_eq!(installed.len(), 2);
+        assert_eq!(installed[0].kind, PackContentKind::CompileTemplate);
+        assert_eq!(installed[0].version, "1.0.0");
+        assert!(root
+            .join("library/compile-templates/recipes/compile/summarize.json")
+            .exists());
+        assert!(root
+            .join("library/evals/recipes/evals/summaries.json")
+            .exists());
+        let suite = read(&packs, PackContentKind::EvalSuite, "recipes", "summaries").unwrap();
+        assert_eq!(suite["target"], "summarize-pdf");
+
+        write_pack(&packs, "1.1.0", false);
+        sync(&packs, "recipes").unwrap();
+        let installed = list(&packs, None);
+        assert_eq!(installed.len(), 1);
+        assert_eq!(installed[0].version, "1.1.0");
+        assert!(list(&packs, Some(PackContentKind::EvalSuite)).is_empty());
+
+        fs::remove_dir_all(packs.join("recipes")).unwrap();
+        sync(&packs, "recipes").unwrap();
+        assert!(list(&packs, None).is_empty());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn pack_ids_stay_inside_the_library() {
+        for id in ["stdlib", "acme.recipes-2", "v1_0"] {
+            assert!(check_pack_id(id).is_ok(), "{id}");
+        }
+        for id in ["", ".", "..", "../packs", "a/b", "a\\b", "recipes..old", "팩"] {
+            assert!(check_pack_id(id).is_err(), "{id}");
+        }
+
+        let root = std::env::temp_dir().join(format!("hb-content-{}", uuid::Uuid::new_v4()));
+        let packs = root.join("packs");
+        fs::create_dir_all(root.join("library/evals/victim")).unwrap();
+        fs::create_dir_all(&packs).unwrap();
+        assert!(sync(&packs, "../library/evals/victim").is_err());
+        assert!(root.join("library/evals/victim").exists());
+        let _ = fs::remove_dir_all(&root);
+    }
+}
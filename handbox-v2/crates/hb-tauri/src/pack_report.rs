@@ -0,0 +1,330 @@
+//! Pack inventory report — a software bill of materials for installed packs:
+//! versions, licenses, declared runtime dependencies and what each tool is
+//! allowed to do, for security review before the app is approved.
+
+use crate::pack_updater;
+use hb_core::pack::{PackDependencySpec, PackManifest};
+use hb_core::tool::{RuntimeSpec, SideEffect, ToolInterface};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Output format of an exported report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    Json,
+    Csv,
+}
+
+impl ReportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+        }
+    }
+}
+
+/// A tool shipped by a pack.
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolEntry {
+    pub tool_id: String,
+    pub version: String,
+    pub side_effect: SideEffect,
+    pub permissions: Vec<String>,
+    /// Runtime kind, e.g. "native", "python", "docker".
+    pub runtime: String,
+    /// Command, script, image, module or server the runtime points at.
+    pub runtime_target: Option<String>,
+}
+
+/// An installed pack.
+#[derive(Debug, Clone, Serialize)]
+pub struct PackEntry {
+    pub id: String,
+    pub version: String,
+    pub name: String,
+    pub author: String,
+    pub license: String,
+    pub platform_version: String,
+    pub dependencies: Vec<PackDependencySpec>,
+    pub python: Option<String>,
+    pub docker: bool,
+    pub native_deps: Vec<String>,
+    pub os: Vec<String>,
+    pub tools: Vec<ToolEntry>,
+    /// Hash of the pack's file names and contents.
+    pub sha256: Option<String>,
+    /// Tool files that are missing or unreadable, and similar gaps.
+    pub problems: Vec<String>,
+}
+
+/// Report over every installed pack.
+#[derive(Debug, Clone, Serialize)]
+pub struct PackReport {
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+    pub app_version: String,
+    pub packs: Vec<PackEntry>,
+    /// License → number of packs using it.
+    pub licenses: BTreeMap<String, usize>,
+    /// Every permission requested by any tool.
+    pub permissions: BTreeSet<String>,
+    /// Directories without a readable manifest.
+    pub unreadable: Vec<String>,
+}
+
+fn runtime(spec: &RuntimeSpec) -> (&'static str, Option<String>) {
+    match spec {
+        RuntimeSpec::Native => ("native", None),
+        RuntimeSpec::Process(process) => {
+            let command = std::iter::once(&process.command).chain(&process.args);
+            ("process", Some(command.cloned().collect::<Vec<_>>().join(" ")))
+        }
+        RuntimeSpec::Python { script } => ("python", Some(script.clone())),
+        RuntimeSpec::Docker { image } => ("docker", Some(image.clone())),
+        RuntimeSpec::Wasm { module } => ("wasm", Some(module.clone())),
+        RuntimeSpec::Mcp { server_id } => ("mcp", Some(server_id.clone())),
+    }
+}
+
+fn read_pack(dir: &Path, manifest: PackManifest) -> PackEntry {
+    let mut problems = Vec::new();
+    let mut tools = Vec::new();
+    for relative in &manifest.tools {
+        let tool = std::fs::read_to_string(dir.join(relative))
+            .map_err(|e| e.to_string())
+            .and_then(|content| {
+                serde_json::from_str::<ToolInterface>(&content).map_err(|e| e.to_string())
+            });
+        match tool {
+            Ok(tool) => {
+                let (kind, target) = runtime(&tool.runtime);
+                tools.push(ToolEntry {
+                    tool_id: tool.tool_id,
+                    version: tool.version,
+                    side_effect: tool.side_effect,
+                    permissions: tool.required_permissions.into_iter().map(|p| p.0).collect(),
+                    runtime: kind.to_string(),
+                    runtime_target: target,
+                });
+            }
+            Err(e) => problems.push(format!("{relative}: {e}")),
+        }
+    }
+    if manifest.license.trim().is_empty() {
+        problems.push("No license declared".into());
+    }
+    let requirements = manifest.runtime_requirements.unwrap_or_default();
+    PackEntry {
+        sha256: pack_updater::pack_hash(dir).map_err(|e| problems.push(e)).ok(),
+        id: manifest.id,
+        version: manifest.version,
+        name: manifest.name,
+        author: manifest.author,
+        license: manifest.license,
+        platform_version: manifest.platform_version,
+        dependencies: manifest.dependencies,
+        python: requirements.python,
+        docker: requirements.docker.unwrap_or(false),
+        native_deps: requirements.native_deps,
+        os: requirements.os,
+        tools,
+        problems,
+    }
+}
+
+/// Build the report for every pack under `packs_dir`, sorted by id.
+pub fn build(packs_dir: &Path) -> PackReport {
+    let mut packs = Vec::new();
+    let mut unreadable = Vec::new();
+    let entries = std::fs::read_dir(packs_dir).into_iter().flatten().flatten();
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let dir = entry.path();
+        if name.starts_with('.') || !dir.is_dir() {
+            continue;
+        }
+        let manifest = std::fs::read_to_string(dir.join("manifest.json"))
+            .ok()
+            .and_then(|content| serde_json::from_str::<PackManifest>(&content).ok());
+        match manifest {
+            Some(manifest) => packs.push(read_pack(&dir, manifest)),
+            None => unreadable.push(name),
+        }
+    }
+    packs.sort_by(|a, b| a.id.cmp(&b.id));
+    unreadable.sort();
+
+    let mut licenses = BTreeMap::new();
+    let mut permissions = BTreeSet::new();
+    for pack in &packs {
+        *licenses.entry(pack.license.clone()).or_insert(0) += 1;
+        for tool in &pack.tools {
+            permissions.extend(tool.permissions.iter().cloned());
+        }
+    }
+    PackReport {
+        generated_at: chrono::Utc::now(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        packs,
+        licenses,
+        permissions,
+        unreadable,
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    // Spreadsheets run cells starting with these as formulas
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{value}")
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+/// One row per tool (pack columns repeated); packs without tools get a
+/// single row with the tool columns empty. Lists are `;`-separated.
+pub fn to_csv(report: &PackReport) -> String {
+    let mut out = String::from(
+        "pack_id,pack_version,name,author,license,platform_version,dependencies,\
+         python,docker,native_deps,os,sha256,tool_id,tool_version,side_effect,\
+         permissions,runtime,runtime_target\n",
+    );
+    for pack in &report.packs {
+        let dependencies: Vec<_> = pack
+            .dependencies
+            .iter()
+            .map(|d| format!("{} {}", d.pack_id, d.version_range))
+            .collect();
+        let pack_columns = [
+            pack.id.clone(),
+            pack.version.clone(),
+            pack.name.clone(),
+            pack.author.clone(),
+            pack.license.clone(),
+            pack.platform_version.clone(),
+            dependencies.join(";"),
+            pack.python.clone().unwrap_or_default(),
+            pack.docker.to_string(),
+            pack.native_deps.join(";"),
+            pack.os.join(";"),
+            pack.sha256.clone().unwrap_or_default(),
+        ];
+        let tool_rows: Vec<[String; 6]> = if pack.tools.is_empty() {
+            vec![Default::default()]
+        } else {
+            pack.tools
+                .iter()
+                .map(|tool| {
+                    [
+                        tool.tool_id.clone(),
+                        tool.version.clone(),
+                        serde_json::to_value(&tool.side_effect)
+                            .ok()
+                            .and_then(|v| v.as_str().map(str::to_string))
+                            .unwrap_or_default(),
+                        tool.permissions.join(";"),
+                        tool.runtime.clone(),
+                        tool.runtime_target.clone().unwrap_or_default(),
+                    ]
+                })
+                .collect()
+        };
+        for tool_columns in tool_rows {
+            let row: Vec<_> = pack_columns
+                .iter()
+                .chain(tool_columns.iter())
+                .map(|v| csv_field(v))
+                .collect();
+            out.push_str(&row.join(","));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Render the report in `format`.
+pub fn render(report: &PackReport, format: ReportFormat) -> Result<String, String> {
+    match format {
+        ReportFormat::Json => serde_json::to_string_pretty(report).map_err(|e| e.to_string()),
+        ReportFormat::Csv => Ok(to_csv(report)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn reports_packs_tools_and_problems() {
+        let packs = std::env::temp_dir().join(format!("hb-pack-report-{}", uuid::Uuid::new_v4()));
+        let dir = packs.join("ocr-pack");
+        fs::create_dir_all(dir.join("tools")).unwrap();
+        fs::write(
+            dir.join("manifest.json"),
+            serde_json::json!({
+                "pack_version": "0.1.0",
+                "id": "ocr-pack",
+                "version": "2.0.1",
+                "name": "OCR, scanned drawings",
+                "description": "",
+                "author": "Site Team",
+                "license": "Apache-2.0",
+                "platform_version": "0.1.0",
+                "category": "document",
+                "tools": ["tools/ocr.json", "tools/missing.json"],
+                "runtime_requirements": { "python": ">=3.10", "native_deps": ["tesseract"] },
+            })
+            .to_string(),
+        )
+        .unwrap();
+        fs::write(
+            dir.join("tools/ocr.json"),
+            serde_json::json!({
+                "tool_id": "ocr-pack/ocr",
+                "version": "2.0.1",
+                "display_name": "OCR",
+                "description": "",
+                "capability_tags": ["file.parse.image"],
+                "input_schema": { "ports": [] },
+                "output_schema": { "ports": [] },
+                "side_effect": "read",
+                "required_permissions": ["fs.read"],
+                "cost_hint": { "time": "fast", "monetary": "free", "scales_with_input": true },
+                "error_model": { "error_types": [], "idempotent": true, "default_retry": {
+                    "max_retries": 0, "backoff_ms": 1000, "backoff_multiplier": 2.0,
+                    "max_backoff_ms": 30000 } },
+                "runtime": { "kind": "python", "script": "ocr.py" },
+            })
+            .to_string(),
+        )
+        .unwrap();
+        fs::create_dir_all(packs.join("broken")).unwrap();
+
+        let report = build(&packs);
+        assert_eq!(report.unreadable, ["broken"]);
+        let pack = &report.packs[0];
+        assert_eq!(pack.native_deps, ["tesseract"]);
+        assert_eq!(pack.tools.len(), 1);
+        assert_eq!(pack.tools[0].runtime_target.as_deref(), Some("ocr.py"));
+        assert!(pack.problems[0].starts_with("tools/missing.json"));
+        assert_eq!(report.licenses["Apache-2.0"], 1);
+        assert!(report.permissions.contains("fs.read"));
+
+        let csv = to_csv(&report);
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].starts_with("ocr-pack,2.0.1,\"OCR, scanned drawings\",Site Team,"));
+        assert!(lines[1].ends_with(",ocr-pack/ocr,2.0.1,read,fs.read,python,ocr.py"));
+        assert_eq!(csv_field("=HYPERLINK(\"x\")"), "\"'=HYPERLINK(\"\"x\"\")\"");
+        assert_eq!(csv_field("-1"), "'-1");
+        let _ = fs::remove_dir_all(packs);
+    }
+}
@@ -0,0 +1,657 @@
+//! Pack updater — check configured sources for newer pack versions and apply
+//! them atomically, keeping the previous version around for rollback.
+//!
+//! A source is a directory or URL that serves an `index.json`:
+//!
+//! ```json
+//! { "packs": [ { "id": "rag-pack", "version": "1.2.0",
+//!                "changelog": "...", "location": "rag-pack-1.2.0.tar.gz" } ] }
+//! ```
+//!
+//! `location` is resolved relative to the source and points at either a pack
+//! directory (local sources) or a `.tar.gz` archive of one.
+
+use crate::pack_content;
+use hb_compiler::migrate::PackUpgrade;
+use hb_core::graph::WorkflowSpec;
+use hb_core::pack::{PackContentKind, PackManifest};
+use hb_core::tool::ToolInterface;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How often the background task checks sources for updates.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Event emitted when the background check finds updates.
+pub const UPDATES_AVAILABLE_EVENT: &str = "pack-updates-available";
+
+/// Staging and backup directories inside the packs directory.
+const STAGING_DIR: &str = ".staging";
+const BACKUP_DIR: &str = ".backup";
+
+/// A configured place to look for pack releases.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackSource {
+    pub id: String,
+    /// Directory path or http(s) URL serving `index.json`.
+    pub url: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Persisted list of pack sources (`pack_sources.json` in the data dir).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PackSourceConfig {
+    #[serde(default)]
+    pub sources: Vec<PackSource>,
+}
+
+impl PackSourceConfig {
+    pub fn path(data_dir: &Path) -> PathBuf {
+        data_dir.join("pack_sources.json")
+    }
+
+    pub fn load(data_dir: &Path) -> Self {
+        fs::read_to_string(Self::path(data_dir))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, data_dir: &Path) -> Result<(), String> {
+        fs::create_dir_all(data_dir).map_err(|e| e.to_string())?;
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(Self::path(data_dir), content).map_err(|e| e.to_string())
+    }
+}
+
+/// A release listed in a source index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackRelease {
+    pub id: String,
+    pub version: String,
+    #[serde(default)]
+    pub changelog: String,
+    pub location: String,
+    /// Kinds of content the release ships; empty for sources that predate them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contents: Vec<PackContentKind>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PackIndex {
+    #[serde(default)]
+    pub packs: Vec<PackRelease>,
+}
+
+/// A workflow whose pinned version range excludes the new release.
+#[derive(Debug, Clone, Serialize)]
+pub struct IncompatibleWorkflow {
+    pub workflow_id: String,
+    pub workflow_name: String,
+    pub version_range: String,
+}
+
+/// An update available for an installed pack.
+#[derive(Debug, Clone, Serialize)]
+pub struct AvailableUpdate {
+    pub pack_id: String,
+    pub installed_version: String,
+    pub latest_version: String,
+    pub changelog: String,
+    pub source_id: String,
+    /// Resolved location of the release (directory or archive URL).
+    pub location: String,
+    pub incompatible_workflows: Vec<IncompatibleWorkflow>,
+}
+
+/// Result of applying or rolling back an update.
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateOutcome {
+    pub pack_id: String,
+    pub previous_version: Option<String>,
+    pub installed_version: String,
+}
+
+/// Read `version` from each installed pack's manifest.
+pub fn installed_versions(packs_dir: &Path) -> Vec<(String, semver::Version)> {
+    let Ok(entries) = fs::read_dir(packs_dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|e| !e.file_name().to_string_lossy().starts_with('.'))
+        .filter_map(|e| read_manifest_version(&e.path()).ok())
+        .collect()
+}
+
+fn read_manifest_version(pack_dir: &Path) -> Result<(String, semver::Version), String> {
+    let content = fs::read_to_string(pack_dir.join("manifest.json"))
+        .map_err(|e| format!("Failed to read manifest: {e}"))?;
+    let manifest: serde_json::Value =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid manifest: {e}"))?;
+    let id = manifest
+        .get("id")
+        .and_then(|v| v.as_str())
+        .ok_or("Manifest missing 'id' field")?;
+    let version = manifest
+        .get("version")
+        .and_then(|v| v.as_str())
+        .ok_or("Manifest missing 'version' field")?;
+    let version = semver::Version::parse(version)
+        .map_err(|e| format!("Invalid version '{version}' in manifest: {e}"))?;
+    Ok((id.to_string(), version))
+}
+
+/// Resolve a release location relative to its source.
+fn resolve_location(source_url: &str, location: &str) -> String {
+    if location.contains("://") || Path::new(location).is_absolute() {
+        return location.to_string();
+    }
+    if is_remote(source_url) {
+        format!("{}/{}", source_url.trim_end_matches('/'), location)
+    } else {
+        hb_core::path::from_user_input(source_url)
+            .join(location)
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+fn is_remote(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+/// Fetch a source's index.
+pub async fn fetch_index(source: &PackSource) -> Result<PackIndex, String> {
+    let content = if is_remote(&source.url) {
+        let url = format!("{}/index.json", source.url.trim_end_matches('/'));
+        reqwest::get(&url)
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("Failed to fetch {url}: {e}"))?
+            .text()
+            .await
+            .map_err(|e| e.to_string())?
+    } else {
+        fs::read_to_string(hb_core::path::resolve(&source.url).join("index.json"))
+            .map_err(|e| format!("Failed to read index of '{}': {e}", source.id))?
+    };
+    serde_json::from_str(&content).map_err(|e| format!("Invalid index in '{}': {e}", source.id))
+}
+
+/// Workflows whose `required_packs` pin excludes `version`.
+pub fn incompatible_workflows<'a>(
+    pack_id: &str,
+    version: &semver::Version,
+    workflows: impl IntoIterator<Item = &'a WorkflowSpec>,
+) -> Vec<IncompatibleWorkflow> {
+    workflows
+        .into_iter()
+        .flat_map(|wf| {
+            wf.required_packs
+                .iter()
+                .filter(|dep| dep.pack_id == pack_id)
+                .filter(|dep| {
+                    semver::VersionReq::parse(&dep.version_range)
+                        .map(|req| !req.matches(version))
+                        .unwrap_or(false)
+                })
+                .map(|dep| IncompatibleWorkflow {
+                    workflow_id: wf.id.to_string(),
+                    workflow_name: wf.meta.name.clone(),
+                    version_range: dep.version_range.clone(),
+                })
+        })
+        .collect()
+}
+
+/// Compare installed packs against the given source indexes and report the
+/// newest release of each pack that is ahead of the installed version.
+pub fn find_updates<'a>(
+    installed: &[(String, semver::Version)],
+    indexes: &[(PackSource, PackIndex)],
+    workflows: impl IntoIterator<Item = &'a WorkflowSpec> + Clone,
+) -> Vec<AvailableUpdate> {
+    let mut updates = Vec::new();
+    for (pack_id, current) in installed {
+        let newest = indexes
+            .iter()
+            .flat_map(|(source, index)| index.packs.iter().map(move |r| (source, r)))
+            .filter(|(_, r)| &r.id == pack_id)
+            .filter_map(|(source, r)| {
+                semver::Version::parse(&r.version)
+                    .ok()
+                    .map(|v| (source, r, v))
+            })
+            .filter(|(_, _, v)| v > current)
+            .max_by(|a, b| a.2.cmp(&b.2));
+
+        if let Some((source, release, version)) = newest {
+            updates.push(AvailableUpdate {
+                pack_id: pack_id.clone(),
+                installed_version: current.to_string(),
+                latest_version: version.to_string(),
+                changelog: release.changelog.clone(),
+                source_id: source.id.clone(),
+                location: resolve_location(&source.url, &release.location),
+                incompatible_workflows: incompatible_workflows(
+                    pack_id,
+                    &version,
+                    workflows.clone(),
+                ),
+            });
+        }
+    }
+    updates
+}
+
+/// Check every enabled source; unreachable sources are logged and skipped.
+pub async fn check_updates<'a>(
+    packs_dir: &Path,
+    sources: &[PackSource],
+    workflows: impl IntoIterator<Item = &'a WorkflowSpec> + Clone,
+) -> Vec<AvailableUpdate> {
+    let mut indexes = Vec::new();
+    for source in sources.iter().filter(|s| s.enabled) {
+        match fetch_index(source).await {
+            Ok(index) => indexes.push((source.clone(), index)),
+            Err(e) => tracing::warn!("Pack source '{}' unavailable: {e}", source.id),
+        }
+    }
+    find_updates(&installed_versions(packs_dir), &indexes, workflows)
+}
+
+/// Download or copy a release into `staging`.
+async fn stage_release(location: &str, staging: &Path) -> Result<(), String> {
+    if staging.exists() {
+        fs::remove_dir_all(staging).map_err(|e| format!("Failed to clear staging: {e}"))?;
+    }
+    fs::create_dir_all(staging).map_err(|e| format!("Failed to create staging dir: {e}"))?;
+
+    if is_remote(location) {
+        let bytes = reqwest::get(location)
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("Failed to download {location}: {e}"))?
+            .bytes()
+            .await
+            .map_err(|e| e.to_string())?;
+        unpack_tar_gz(&bytes[..], staging)
+    } else {
+        let path = &hb_core::path::resolve(location);
+        if path.is_dir() {
+            copy_dir_recursive(path, staging).map_err(|e| format!("Failed to copy pack: {e}"))
+        } else {
+            let file = fs::File::open(path).map_err(|e| format!("Failed to open {location}: {e}"))?;
+            unpack_tar_gz(file, staging)
+        }
+    }
+}
+
+fn unpack_tar_gz(reader: impl std::io::Read, dest: &Path) -> Result<(), String> {
+    tar::Archive::new(flate2::read::GzDecoder::new(reader))
+        .unpack(dest)
+        .map_err(|e| format!("Failed to unpack archive: {e}"))?;
+    // Archives commonly wrap the pack in a single top-level directory.
+    let entries: Vec<_> = fs::read_dir(dest)
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .collect();
+    if !dest.join("manifest.json").exists() && entries.len() == 1 && entries[0].path().is_dir() {
+        let inner = entries[0].path();
+        for entry in fs::read_dir(&inner).map_err(|e| e.to_string())?.flatten() {
+            fs::rename(entry.path(), dest.join(entry.file_name())).map_err(|e| e.to_string())?;
+        }
+        fs::remove_dir(&inner).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Install `update` into `packs_dir`.
+///
+/// The release is staged and validated first; the swap is two renames, and
+/// the previous version is kept under `.backup/` so it can be restored with
+/// [`rollback`]. If the swap fails part-way, the previous version is put back.
+pub async fn apply_update(packs_dir: &Path, update: &AvailableUpdate) -> Result<UpdateOutcome, String> {
+    pack_content::check_pack_id(&update.pack_id)?;
+    let staging = packs_dir.join(STAGING_DIR).join(&update.pack_id);
+    if let Err(e) = stage_release(&update.location, &staging).await {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+
+    let (staged_id, staged_version) = read_manifest_version(&staging)?;
+    if staged_id != update.pack_id || staged_version.to_string() != update.latest_version {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(format!(
+            "Release mismatch: expected {}@{}, got {staged_id}@{staged_version}",
+            update.pack_id, update.latest_version
+        ));
+    }
+
+    swap_in(packs_dir, &update.pack_id, &staging)
+}
+
+/// Install a `.tar.gz` pack archive received from elsewhere (e.g. a team
+/// server) as `pack_id`, the same way as an update. With `expected_hash`,
+/// the unpacked files must have that [`pack_hash`] or nothing is installed.
+pub fn install_archive(
+    packs_dir: &Path,
+    pack_id: &str,
+    archive: impl std::io::Read,
+    expected_hash: Option<&str>,
+) -> Result<UpdateOutcome, String> {
+    pack_content::check_pack_id(pack_id)?;
+    let staging = packs_dir.join(STAGING_DIR).join(pack_id);
+    if staging.exists() {
+        fs::remove_dir_all(&staging).map_err(|e| format!("Failed to clear staging: {e}"))?;
+    }
+    fs::create_dir_all(&staging).map_err(|e| format!("Failed to create staging dir: {e}"))?;
+    let staged = unpack_tar_gz(archive, &staging)
+        .and_then(|_| match expected_hash {
+            Some(expected) if pack_hash(&staging)? != expected => {
+                Err(format!("Pack '{pack_id}' does not match its published hash"))
+            }
+            _ => Ok(()),
+        })
+        .and_then(|_| read_manifest_version(&staging));
+    match staged {
+        Ok((staged_id, _)) if staged_id == pack_id => swap_in(packs_dir, pack_id, &staging),
+        Ok((staged_id, _)) => {
+            let _ = fs::remove_dir_all(&staging);
+            Err(format!("Archive contains pack '{staged_id}', expected '{pack_id}'"))
+        }
+        Err(e) => {
+            let _ = fs::remove_dir_all(&staging);
+            Err(e)
+        }
+    }
+}
+
+/// Files under `dir` as (relative path, absolute path), sorted.
+fn pack_files(dir: &Path) -> std::io::Result<Vec<(String, PathBuf)>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        for entry in fs::read_dir(&current)?.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if let Ok(relative) = path.strip_prefix(dir) {
+                files.push((relative.to_string_lossy().replace('\\', "/"), path));
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Hash of a pack directory's file names and contents.
+pub fn pack_hash(dir: &Path) -> Result<String, String> {
+    let mut hasher = Sha256::new();
+    for (relative, path) in pack_files(dir).map_err(|e| e.to_string())? {
+        let bytes = fs::read(&path).map_err(|e| format!("Failed to read {relative}: {e}"))?;
+        hasher.update(relative.as_bytes());
+        hasher.update([0]);
+        hasher.update((bytes.len() as u64).to_le_bytes());
+        hasher.update(&bytes);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Replace `packs_dir/<pack_id>` with `new_dir`, backing up the current one.
+fn swap_in(packs_dir: &Path, pack_id: &str, new_dir: &Path) -> Result<UpdateOutcome, String> {
+    let target = packs_dir.join(pack_id);
+    let backup = packs_dir.join(BACKUP_DIR).join(pack_id);
+    let previous_version = read_manifest_version(&target).ok().map(|(_, v)| v.to_string());
+
+    if target.exists() {
+        if backup.exists() {
+            fs::remove_dir_all(&backup).map_err(|e| format!("Failed to clear old backup: {e}"))?;
+        }
+        fs::create_dir_all(packs_dir.join(BACKUP_DIR)).map_err(|e| e.to_string())?;
+        fs::rename(&target, &backup).map_err(|e| format!("Failed to back up pack: {e}"))?;
+    }
+
+    if let Err(e) = fs::rename(new_dir, &target) {
+        if backup.exists() {
+            let _ = fs::rename(&backup, &target);
+        }
+        return Err(format!("Failed to install pack, previous version restored: {e}"));
+    }
+
+    let (_, installed) = read_manifest_version(&target)?;
+    if let Err(e) = pack_content::sync(packs_dir, pack_id) {
+        tracing::warn!("Failed to install content of pack '{pack_id}': {e}");
+    }
+    tracing::info!(
+        "Updated pack '{pack_id}' {} -> {installed}",
+        previous_version.as_deref().unwrap_or("(none)")
+    );
+    Ok(UpdateOutcome {
+        pack_id: pack_id.to_string(),
+        previous_version,
+        installed_version: installed.to_string(),
+    })
+}
+
+/// Restore the version that was replaced by the last update of `pack_id`.
+pub fn rollback(packs_dir: &Path, pack_id: &str) -> Result<UpdateOutcome, String> {
+    let backup = packs_dir.join(BACKUP_DIR).join(pack_id);
+    if !backup.exists() {
+        return Err(format!("No previous version of '{pack_id}' to roll back to"));
+    }
+    // Move the backup out first so swapping in does not overwrite it.
+    let restoring = packs_dir.join(STAGING_DIR).join(format!("{pack_id}.rollback"));
+    if restoring.exists() {
+        fs::remove_dir_all(&restoring).map_err(|e| e.to_string())?;
+    }
+    fs::create_dir_all(packs_dir.join(STAGING_DIR)).map_err(|e| e.to_string())?;
+    fs::rename(&backup, &restoring).map_err(|e| format!("Failed to stage rollback: {e}"))?;
+    swap_in(packs_dir, pack_id, &restoring)
+}
+
+/// The last update of `pack_id`, from the version kept for rollback to the
+/// installed one, with the migrations the installed release declares.
+pub fn last_upgrade(packs_dir: &Path, pack_id: &str) -> Result<PackUpgrade, String> {
+    let backup = packs_dir.join(BACKUP_DIR).join(pack_id);
+    if !backup.exists() {
+        return Err(format!("No previous version of '{pack_id}' to migrate from"));
+    }
+    let (old_manifest, old_tools) = read_pack_tools(&backup)?;
+    let (new_manifest, new_tools) = read_pack_tools(&packs_dir.join(pack_id))?;
+    let parse = |version: &str| {
+        semver::Version::parse(version).map_err(|e| format!("Invalid version '{version}': {e}"))
+    };
+    let (from_version, to_version) = (parse(&old_manifest.version)?, parse(&new_manifest.version)?);
+    // After a rollback the kept version is the newer one
+    if from_version >= to_version {
+        return Err(format!("'{pack_id}' was not upgraded from {from_version}; nothing to migrate"));
+    }
+    Ok(PackUpgrade {
+        pack_id: pack_id.to_string(),
+        from_version,
+        to_version,
+        old_tools,
+        new_tools,
+        migrations: new_manifest.migrations,
+    })
+}
+
+fn read_pack_tools(pack_dir: &Path) -> Result<(PackManifest, HashMap<String, ToolInterface>), String> {
+    let content = fs::read_to_string(pack_dir.join("manifest.json"))
+        .map_err(|e| format!("Failed to read manifest: {e}"))?;
+    let manifest: PackManifest =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid manifest: {e}"))?;
+    let mut tools = HashMap::new();
+    for relative in &manifest.tools {
+        let content = fs::read_to_string(pack_dir.join(relative))
+            .map_err(|e| format!("Failed to read {relative}: {e}"))?;
+        let tool: ToolInterface =
+            serde_json::from_str(&content).map_err(|e| format!("Invalid tool {relative}: {e}"))?;
+        tools.insert(tool.tool_id.clone(), tool);
+    }
+    Ok((manifest, tools))
+}
+
+fn copy_dir_recursive(src: &Path, dst: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let src_path = entry.path();
+        let dst_path = dst.join(entry.file_name());
+        if src_path.is_dir() {
+            copy_dir_recursive(&src_path, &dst_path)?;
+        } else {
+            fs::copy(&src_path, &dst_path)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hb_core::graph::PackDependency;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("hb-updater-{name}-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_pack(dir: &Path, id: &str, version: &str) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(
+            dir.join("manifest.json"),
+            serde_json::json!({ "id": id, "version": version }).to_string(),
+        )
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn update_then_rollback() {
+        let root = temp_dir("apply");
+        let packs = root.join("packs");
+        let source_dir = root.join("source");
+        write_pack(&packs.join("rag-pack"), "rag-pack", "1.0.0");
+        write_pack(&source_dir.join("rag-pack-1.1.0"), "rag-pack", "1.1.0");
+        fs::write(
+            source_dir.join("index.json"),
+            r#"{"packs":[{"id":"rag-pack","version":"1.1.0","changelog":"Faster chunking","location":"rag-pack-1.1.0"}]}"#,
+        )
+        .unwrap();
+
+        let source = PackSource {
+            id: "local".into(),
+            url: source_dir.to_string_lossy().into_owned(),
+            enabled: true,
+        };
+        let updates = check_updates(&packs, &[source], std::iter::empty()).await;
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].changelog, "Faster chunking");
+
+        let outcome = apply_update(&packs, &updates[0]).await.unwrap();
+        assert_eq!(outcome.previous_version.as_deref(), Some("1.0.0"));
+        assert_eq!(read_manifest_version(&packs.join("rag-pack")).unwrap().1.to_string(), "1.1.0");
+
+        let restored = rollback(&packs, "rag-pack").unwrap();
+        assert_eq!(restored.installed_version, "1.0.0");
+        assert!(installed_versions(&packs).iter().all(|(id, _)| id == "rag-pack"));
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn archives_must_match_the_expected_hash() {
+        let root = temp_dir("archive");
+        let packs = root.join("packs");
+        let source = root.join("source");
+        write_pack(&source, "geo-pack", "2.0.0");
+        let archive = crate::team_sync::pack_archive(&source).unwrap();
+        let hash = pack_hash(&source).unwrap();
+
+        let err = install_archive(&packs, "geo-pack", archive.as_slice(), Some("0000")).unwrap_err();
+        assert!(err.contains("hash"), "{err}");
+        assert!(!packs.join("geo-pack").exists());
+
+        let outcome = install_archive(&packs, "geo-pack", archive.as_slice(), Some(&hash)).unwrap();
+        assert_eq!(outcome.installed_version, "2.0.0");
+        assert_eq!(pack_hash(&packs.join("geo-pack")).unwrap(), hash);
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn last_upgrade_reads_both_releases() {
+        let root = temp_dir("migrate");
+        let packs = root.join("packs");
+        let release = |dir: &Path, version: &str, migrations: serde_json::Value| {
+            fs::create_dir_all(dir.join("tools")).unwrap();
+            let manifest = serde_json::json!({
+                "pack_version": "0.1.0", "id": "rag-pack", "version": version,
+                "name": "RAG", "description": "", "author": "", "license": "MIT",
+                "platform_version": "0.1.0", "category": "rag",
+                "tools": ["tools/chunk.json"], "migrations": migrations,
+            });
+            fs::write(dir.join("manifest.json"), manifest.to_string()).unwrap();
+            let tool = serde_json::json!({
+                "tool_id": "rag-pack/chunk", "version": version, "display_name": "Chunk",
+                "description": "", "capability_tags": [],
+                "input_schema": { "ports": [] }, "output_schema": { "ports": [] },
+                "side_effect": "none", "required_permissions": [],
+                "cost_hint": { "time": "fast", "monetary": "free" },
+                "error_model": { "error_types": [], "idempotent": true,
+                                 "default_retry": { "max_retries": 0, "backoff_ms": 0, "backoff_multiplier": 1.0 } },
+                "runtime": { "kind": "native" },
+            });
+            fs::write(dir.join("tools/chunk.json"), tool.to_string()).unwrap();
+        };
+        release(&packs.join("rag-pack"), "1.0.0", serde_json::json!([]));
+        assert!(last_upgrade(&packs, "rag-pack").is_err());
+
+        let staged = root.join("rag-pack-2.0.0");
+        release(
+            &staged,
+            "2.0.0",
+            serde_json::json!([{ "tool_id": "rag-pack/chunk",
+                                 "rules": [{ "op": "rename_config", "from": "size", "to": "chunk_size" }] }]),
+        );
+        swap_in(&packs, "rag-pack", &staged).unwrap();
+        let upgrade = last_upgrade(&packs, "rag-pack").unwrap();
+        assert_eq!((upgrade.from_version.major, upgrade.to_version.major), (1, 2));
+        assert_eq!(upgrade.old_tools["rag-pack/chunk"].version, "1.0.0");
+        assert_eq!(upgrade.migrations.len(), 1);
+
+        // Rolled back, the kept release is the newer one
+        rollback(&packs, "rag-pack").unwrap();
+        assert!(last_upgrade(&packs, "rag-pack").is_err());
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn pinned_workflows_are_flagged() {
+        let mut pinned = WorkflowSpec::default();
+        pinned.required_packs.push(PackDependency {
+            pack_id: "rag-pack".into(),
+            version_range: "~1.0".into(),
+        });
+        let mut loose = WorkflowSpec::default();
+        loose.required_packs.push(PackDependency {
+            pack_id: "rag-pack".into(),
+            version_range: "^1.0.0".into(),
+        });
+
+        let version = semver::Version::parse("1.1.0").unwrap();
+        let flagged = incompatible_workflows("rag-pack", &version, [&pinned, &loose]);
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].workflow_id, pinned.id.to_string());
+    }
+}
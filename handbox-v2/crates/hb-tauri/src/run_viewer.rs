@@ -0,0 +1,497 @@
+//! Run viewer — a completed execution packaged as one self-contained HTML
+//! file (workflow, spans, a summary report and the graph) that opens in any
+//! browser, for stakeholders who don't have Handbox installed.
+//!
+//! The data is embedded as JSON and rendered by inline script, so the file
+//! needs nothing from the network. Everything passes through a [`Redactor`]
+//! first, and the workspace's [`SharePolicy`] decides whether values and
+//! config are kept at all. [`load`] reads the data back so the app can show
+//! a received bundle read-only.
+
+use crate::debug_bundle::Redactor;
+use chrono::{DateTime, Utc};
+use hb_core::graph::layout;
+use hb_core::graph::{NodeEntry, WorkflowSpec};
+use hb_core::policy::SharePolicy;
+use hb_core::trace::{ContractViolation, ExecutionRecord, ExecutionStatus, NodeSpan};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Bumped when the embedded data changes shape.
+pub const VIEWER_VERSION: u32 = 1;
+
+/// Width of the embedded graph in pixels.
+const GRAPH_WIDTH: u32 = 960;
+
+/// Slowest nodes listed in the report.
+const SLOWEST_NODES: usize = 5;
+
+/// Marks the embedded data, for [`load`].
+const DATA_TAG: &str = r#"<script type="application/json" id="handbox-run">"#;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeFailure {
+    pub node_id: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeDuration {
+    pub node_id: String,
+    pub duration_ms: i64,
+}
+
+/// Summary shown at the top of the viewer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunReport {
+    pub workflow_name: String,
+    pub status: ExecutionStatus,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub duration_ms: Option<i64>,
+    pub total_nodes: u32,
+    pub completed_nodes: u32,
+    pub failed_nodes: u32,
+    pub cache_hits: u32,
+    pub failures: Vec<NodeFailure>,
+    pub slowest: Vec<NodeDuration>,
+    pub contract_violations: Vec<ContractViolation>,
+}
+
+/// Everything the viewer shows, already redacted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewerBundle {
+    pub viewer_version: u32,
+    pub app_version: String,
+    pub created_at: DateTime<Utc>,
+    pub execution_id: String,
+    pub report: RunReport,
+    pub workflow: Option<Value>,
+    pub record: Value,
+    pub spans: Vec<Value>,
+    /// The workflow graph as SVG.
+    pub graph_svg: Option<String>,
+    /// Whether node inputs and outputs were kept.
+    pub values_included: bool,
+    /// Values replaced by the redactor.
+    pub redactions: usize,
+}
+
+/// Package an execution under `policy`.
+pub fn build(
+    record: &ExecutionRecord,
+    spec: Option<&WorkflowSpec>,
+    spans: &[NodeSpan],
+    policy: &SharePolicy,
+    redactor: &mut Redactor,
+) -> Result<ViewerBundle, String> {
+    let (workflow, graph_svg) = match spec {
+        Some(spec) => {
+            let mut spec = spec.clone();
+            if !policy.include_config {
+                strip_config(&mut spec.nodes);
+            }
+            let mut value = serde_json::to_value(&spec).map_err(|e| e.to_string())?;
+            redactor.value(&mut value);
+            // The graph is drawn from the redacted workflow, so labels are
+            // redacted before they are escaped into SVG; a workflow the
+            // redactor broke is shared without one
+            let graph_svg = serde_json::from_value::<WorkflowSpec>(value.clone())
+                .ok()
+                .map(|spec| hb_project::thumbnail::render_svg(&layout::layout(&spec), GRAPH_WIDTH));
+            (Some(value), graph_svg)
+        }
+        None => (None, None),
+    };
+
+    let spans = spans
+        .iter()
+        .map(|span| {
+            let mut span = span.clone();
+            if !policy.include_values {
+                span.input_json = Value::Null;
+                span.output_json = None;
+            }
+            if !policy.include_config {
+                span.config_json = Value::Null;
+            }
+            let mut value = serde_json::to_value(&span).map_err(|e| e.to_string())?;
+            redactor.value(&mut value);
+            Ok(value)
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    // The workflow is shared on its own, under the policy
+    let mut shared = record.clone();
+    shared.manifest.spec = None;
+    let mut record_json = serde_json::to_value(&shared).map_err(|e| e.to_string())?;
+    redactor.value(&mut record_json);
+    let report = report(record, spec, &spans, redactor);
+
+    Ok(ViewerBundle {
+        viewer_version: VIEWER_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: Utc::now(),
+        execution_id: record.execution_id.to_string(),
+        report,
+        workflow,
+        record: record_json,
+        spans,
+        graph_svg,
+        values_included: policy.include_values,
+        redactions: redactor.redactions,
+    })
+}
+
+fn strip_config(nodes: &mut [NodeEntry]) {
+    for node in nodes {
+        match node {
+            NodeEntry::Primitive(n) => n.config.clear(),
+            NodeEntry::Composite(n) => n.config.clear(),
+            _ => {}
+        }
+        for subgraph in node.subgraphs_mut() {
+            strip_config(&mut subgraph.nodes);
+        }
+    }
+}
+
+/// Summary of the record and its (already redacted) spans.
+fn report(
+    record: &ExecutionRecord,
+    spec: Option<&WorkflowSpec>,
+    spans: &[Value],
+    redactor: &mut Redactor,
+) -> RunReport {
+    let text = |span: &Value, key: &str| span.get(key).and_then(Value::as_str).map(str::to_string);
+    let failures = spans
+        .iter()
+        .filter(|s| s.get("status").and_then(Value::as_str) == Some("failed"))
+        .filter_map(|s| {
+            Some(NodeFailure {
+                node_id: text(s, "node_id")?,
+                error: text(s, "error").unwrap_or_default(),
+            })
+        })
+        .collect();
+    let mut slowest: Vec<NodeDuration> = spans
+        .iter()
+        .filter_map(|s| {
+            Some(NodeDuration {
+                node_id: text(s, "node_id")?,
+                duration_ms: s.get("duration_ms")?.as_i64()?,
+            })
+        })
+        .collect();
+    slowest.sort_by_key(|n| std::cmp::Reverse(n.duration_ms));
+    slowest.truncate(SLOWEST_NODES);
+
+    RunReport {
+        workflow_name: redactor.text(spec.map(|s| s.meta.name.as_str()).unwrap_or_default()),
+        status: record.status.clone(),
+        started_at: record.started_at,
+        completed_at: record.completed_at,
+        duration_ms: record
+            .completed_at
+            .map(|end| (end - record.started_at).num_milliseconds()),
+        total_nodes: record.total_nodes,
+        completed_nodes: record.completed_nodes,
+        failed_nodes: record.failed_nodes,
+        cache_hits: record.cache_hits,
+        failures,
+        slowest,
+        contract_violations: record
+            .contract_violations
+            .iter()
+            .map(|v| ContractViolation {
+                message: redactor.text(&v.message),
+                ..v.clone()
+            })
+            .collect(),
+    }
+}
+
+/// The viewer as one HTML page.
+pub fn render_html(bundle: &ViewerBundle) -> Result<String, String> {
+    let data = script_json(&serde_json::to_string(bundle).map_err(|e| e.to_string())?);
+    let title = if bundle.report.workflow_name.is_empty() {
+        format!("Run {}", bundle.execution_id)
+    } else {
+        format!(
+            "{} — run {}",
+            bundle.report.workflow_name, bundle.execution_id
+        )
+    };
+    Ok(fill(
+        VIEWER_TEMPLATE,
+        &[
+            ("title", &escape(&title)),
+            ("graph", bundle.graph_svg.as_deref().unwrap_or_default()),
+            ("data", &format!("{DATA_TAG}{data}</script>")),
+        ],
+    ))
+}
+
+/// Substitute each `{{name}}` in `template` once; placeholders inside the
+/// substituted text are left alone.
+fn fill(template: &str, values: &[(&str, &str)]) -> String {
+    let mut html = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start..].find("}}") else {
+            break;
+        };
+        let end = start + len + 2;
+        html.push_str(&rest[..start]);
+        match values.iter().find(|(name, _)| *name == &rest[start + 2..start + len]) {
+            Some((_, value)) => html.push_str(value),
+            None => html.push_str(&rest[start..end]),
+        }
+        rest = &rest[end..];
+    }
+    html.push_str(rest);
+    html
+}
+
+/// JSON that can sit inside a script element: `<`, `>` and `&` can't start
+/// markup, and the line separators older script engines reject are escaped.
+fn script_json(json: &str) -> String {
+    let mut out = String::with_capacity(json.len());
+    for c in json.chars() {
+        match c {
+            '<' | '>' | '&' | '\u{2028}' | '\u{2029}' => {
+                out.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Read the data back out of a page written by [`render_html`].
+pub fn load(html: &str) -> Result<ViewerBundle, String> {
+    let start = html
+        .find(DATA_TAG)
+        .map(|i| i + DATA_TAG.len())
+        .ok_or("Not a Handbox run viewer")?;
+    let end = html[start..]
+        .find("</script>")
+        .map(|i| start + i)
+        .ok_or("Run viewer data is truncated")?;
+    let bundle: ViewerBundle = serde_json::from_str(&html[start..end])
+        .map_err(|e| format!("Invalid run viewer data: {e}"))?;
+    if bundle.viewer_version > VIEWER_VERSION {
+        return Err(format!(
+            "Run viewer version {} is newer than this app supports ({VIEWER_VERSION})",
+            bundle.viewer_version
+        ));
+    }
+    Ok(bundle)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const VIEWER_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{{title}}</title>
+<style>
+  body { font: 14px/1.5 system-ui, sans-serif; margin: 0; background: #0f172a; color: #e2e8f0; }
+  main { max-width: 1100px; margin: 0 auto; padding: 24px; }
+  h1 { font-size: 20px; margin: 0 0 4px; }
+  h2 { font-size: 15px; margin: 28px 0 8px; color: #94a3b8; text-transform: uppercase; letter-spacing: .05em; }
+  .muted { color: #94a3b8; }
+  .stats { display: flex; flex-wrap: wrap; gap: 12px; margin-top: 16px; }
+  .stat { background: #1e293b; border-radius: 8px; padding: 10px 14px; min-width: 110px; }
+  .stat b { display: block; font-size: 18px; }
+  .graph { background: #1e293b; border-radius: 8px; padding: 12px; overflow: auto; }
+  .graph svg { max-width: 100%; height: auto; }
+  table { width: 100%; border-collapse: collapse; }
+  td, th { text-align: left; padding: 6px 8px; border-bottom: 1px solid #1e293b; vertical-align: top; }
+  .completed, .cache_hit { color: #34d399; } .failed { color: #f87171; } .skipped, .cancelled { color: #fbbf24; }
+  details pre { background: #020617; padding: 8px; border-radius: 6px; overflow: auto; max-height: 320px; white-space: pre-wrap; }
+</style>
+</head>
+<body>
+<main>
+  <h1 id="title"></h1>
+  <div class="muted" id="subtitle"></div>
+  <div class="stats" id="stats"></div>
+  <section id="problems"></section>
+  <h2>Workflow</h2>
+  <div class="graph">{{graph}}</div>
+  <h2>Nodes</h2>
+  <table><thead><tr><th>Node</th><th>Tool</th><th>Status</th><th>Duration</th><th></th></tr></thead><tbody id="spans"></tbody></table>
+  <p class="muted" id="footer"></p>
+</main>
+{{data}}
+<script>
+  const run = JSON.parse(document.getElementById('handbox-run').textContent);
+  const r = run.report;
+  const el = (tag, text, cls) => { const e = document.createElement(tag); if (text != null) e.textContent = text; if (cls) e.className = cls; return e; };
+  const ms = (v) => v == null ? '—' : v < 1000 ? v + ' ms' : (v / 1000).toFixed(1) + ' s';
+  document.getElementById('title').textContent = r.workflow_name || 'Workflow run';
+  document.getElementById('subtitle').textContent = 'Run ' + run.execution_id + ' · started ' + new Date(r.started_at).toLocaleString();
+  const stats = document.getElementById('stats');
+  [['Status', r.status.replace(/_/g, ' '), r.status], ['Duration', ms(r.duration_ms)], ['Nodes', r.total_nodes],
+   ['Completed', r.completed_nodes], ['Failed', r.failed_nodes], ['From cache', r.cache_hits]].forEach(([label, value, cls]) => {
+    const s = el('div', null, 'stat'); s.append(el('b', String(value), cls), el('span', label, 'muted')); stats.append(s);
+  });
+  const problems = document.getElementById('problems');
+  const list = (heading, items) => {
+    if (!items.length) return;
+    problems.append(el('h2', heading));
+    const ul = el('ul'); items.forEach((t) => ul.append(el('li', t))); problems.append(ul);
+  };
+  list('Failures', r.failures.map((f) => f.node_id + ': ' + f.error));
+  list('Output contract', r.contract_violations.map((v) => v.node_id + '.' + v.port + ': ' + v.message));
+  list('Slowest nodes', r.slowest.map((n) => n.node_id + ' — ' + ms(n.duration_ms)));
+  const body = document.getElementById('spans');
+  run.spans.forEach((s) => {
+    const tr = el('tr');
+    tr.append(el('td', s.node_id), el('td', s.tool_ref, 'muted'), el('td', s.status.replace(/_/g, ' '), s.status), el('td', ms(s.duration_ms)));
+    const td = el('td');
+    const d = el('details'); d.append(el('summary', 'Details'));
+    const detail = { error: s.error, input: s.input_json, output: s.output_json, config: s.config_json };
+    Object.entries(detail).filter(([, v]) => v != null).forEach(([k, v]) => {
+      d.append(el('div', k, 'muted'), el('pre', typeof v === 'string' ? v : JSON.stringify(v, null, 2)));
+    });
+    td.append(d); tr.append(td); body.append(tr);
+  });
+  document.getElementById('footer').textContent = 'Shared from Handbox ' + run.app_version + ' on ' + new Date(run.created_at).toLocaleString() +
+    (run.values_included ? '' : ' · node inputs and outputs left out') + ' · ' + run.redactions + ' value(s) redacted';
+</script>
+</body>
+</html>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hb_core::trace::ExecutionEnvironment;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn span(node_id: &str, status: ExecutionStatus, output: Value) -> NodeSpan {
+        NodeSpan {
+            span_id: Uuid::new_v4(),
+            execution_id: Uuid::nil(),
+            node_id: node_id.into(),
+            tool_ref: "llm-chat".into(),
+            input_json: json!({ "prompt": "Summarize the pour log" }),
+            output_json: Some(output),
+            config_json: json!({ "api_key": "sk-abcdefghijklmnop1234" }),
+            started_at: Utc::now(),
+            completed_at: Some(Utc::now()),
+            duration_ms: Some(1200),
+            status,
+            error: None,
+            cache_hit: false,
+            environment: ExecutionEnvironment {
+                platform_version: "0.0.0".into(),
+                os: "linux".into(),
+                tool_version: "1.0.0".into(),
+                extra: Default::default(),
+            },
+        }
+    }
+
+    fn record() -> ExecutionRecord {
+        ExecutionRecord {
+            execution_id: Uuid::nil(),
+            workflow_id: Uuid::nil(),
+            started_at: Utc::now(),
+            completed_at: Some(Utc::now()),
+            status: ExecutionStatus::Failed,
+            total_nodes: 2,
+            completed_nodes: 1,
+            failed_nodes: 1,
+            cache_hits: 0,
+            workspace_id: None,
+            manifest: Default::default(),
+            contract_violations: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn bundles_are_redacted_and_round_trip_through_the_page() {
+        let mut failed = span("export", ExecutionStatus::Failed, json!(null));
+        failed.error = Some("upload rejected for client Hanul Build".into());
+        let spans = vec![
+            span(
+                "summary",
+                ExecutionStatus::Completed,
+                json!({ "text": "</script><b>done</b> {{data}} \u{2028}&" }),
+            ),
+            failed,
+        ];
+        let policy = SharePolicy {
+            redact_keys: vec!["prompt".into()],
+            ..Default::default()
+        };
+        let mut redactor =
+            Redactor::new(["Hanul Build".to_string()], None).with_keys(&policy.redact_keys);
+        let bundle = build(&record(), None, &spans, &policy, &mut redactor).unwrap();
+
+        assert_eq!(bundle.spans[0]["config_json"]["api_key"], "[REDACTED]");
+        assert_eq!(bundle.spans[0]["input_json"]["prompt"], "[REDACTED]");
+        assert_eq!(
+            bundle.report.failures[0].error,
+            "upload rejected for client [REDACTED]"
+        );
+        assert_eq!(bundle.report.slowest.len(), 2);
+
+        let html = render_html(&bundle).unwrap();
+        assert!(
+            !html.contains("</script><b>"),
+            "values cannot close the data element"
+        );
+        assert!(!html.contains('\u{2028}'));
+        assert_eq!(html.matches(DATA_TAG).count(), 1, "placeholders in values stay as they are");
+        let loaded = load(&html).unwrap();
+        assert_eq!(
+            loaded.spans[0]["output_json"]["text"],
+            "</script><b>done</b> {{data}} \u{2028}&"
+        );
+        assert_eq!(loaded.redactions, bundle.redactions);
+        assert!(load("<html></html>").is_err());
+
+        // Without values only statuses, timings and errors remain
+        let policy = SharePolicy {
+            include_values: false,
+            include_config: false,
+            ..Default::default()
+        };
+        let bundle = build(&record(), None, &spans, &policy, &mut Redactor::default()).unwrap();
+        assert!(bundle
+            .spans
+            .iter()
+            .all(|s| s["input_json"].is_null() && s["output_json"].is_null()));
+        assert!(bundle.spans[1]["error"].is_string());
+        assert!(!bundle.values_included);
+    }
+
+    #[test]
+    fn the_graph_is_drawn_from_the_redacted_workflow() {
+        let spec: WorkflowSpec = serde_json::from_value(json!({
+            "version": "0.1.0",
+            "meta": { "name": "Pour log" },
+            "nodes": [{ "kind": "primitive", "id": "a", "tool_ref": "llm-chat", "label": "Hanul & Co" }],
+            "edges": [],
+        }))
+        .unwrap();
+        let mut redactor = Redactor::new(["Hanul & Co".to_string()], None);
+        let bundle = build(&record(), Some(&spec), &[], &SharePolicy::default(), &mut redactor).unwrap();
+
+        let svg = bundle.graph_svg.unwrap();
+        assert!(svg.contains("[REDACTED]"), "{svg}");
+        assert!(!svg.contains("Hanul"), "{svg}");
+        assert_eq!(bundle.workflow.unwrap()["nodes"][0]["label"], "[REDACTED]");
+    }
+}
@@ -0,0 +1,456 @@
+//! Recording and playback of collaboration sessions.
+//!
+//! Every collaboration event — ops, chat, cursor and presence changes — is
+//! appended to `{dir}/{session_id}.jsonl`, after a header holding the
+//! workflow as it was when the session started. Playback replays the
+//! recorded ops up to a point in the session to reconstruct the workflow as
+//! it was then, for review or to recover from a bad edit.
+//!
+//! Two change types rebuild the workflow: [`EDIT_OP_EVENT`], whose payload is
+//! an [`EditOp`], and [`SNAPSHOT_EVENT`], whose payload is a whole
+//! [`WorkflowSpec`]. The workflow commands record them in every active
+//! session on the workflow they change. Other events are kept for review
+//! only.
+//!
+//! Lines are written by a background thread, so recording never blocks a
+//! session. A recording stops growing at [`MAX_RECORDING_BYTES`], and
+//! recordings older than [`RETENTION`] are removed when the app starts.
+
+use crate::commands::collaboration::{CollaborationEvent, CollaborationSession};
+use chrono::{DateTime, Utc};
+use hb_core::graph::WorkflowSpec;
+use hb_project::journal::EditOp;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Change type of a broadcast granular edit.
+pub const EDIT_OP_EVENT: &str = "edit_op";
+
+/// Change type of a broadcast full workflow snapshot.
+pub const SNAPSHOT_EVENT: &str = "workflow_snapshot";
+
+/// Size at which a recording stops taking new events.
+pub const MAX_RECORDING_BYTES: u64 = 64 * 1024 * 1024;
+
+/// How long recordings are kept.
+pub const RETENTION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// One line of a recording file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+enum Record {
+    Started {
+        session_id: String,
+        workflow_id: String,
+        name: String,
+        started_at: DateTime<Utc>,
+        /// `None` when the workflow wasn't loaded when the session started;
+        /// playback then begins at the first snapshot.
+        workflow: Option<Box<WorkflowSpec>>,
+    },
+    Event {
+        event: CollaborationEvent,
+    },
+}
+
+/// A recorded session.
+#[derive(Debug, Clone, Serialize)]
+pub struct Recording {
+    pub session_id: String,
+    pub workflow_id: String,
+    pub name: String,
+    pub started_at: DateTime<Utc>,
+    pub initial: Option<WorkflowSpec>,
+    pub events: Vec<CollaborationEvent>,
+}
+
+/// A recording without its events, for listing.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingSummary {
+    pub session_id: String,
+    pub workflow_id: String,
+    pub name: String,
+    pub started_at: DateTime<Utc>,
+    /// Timestamp of the last event.
+    pub last_event_at: Option<String>,
+    pub event_count: usize,
+}
+
+/// A recorded op that could not be replayed.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlaybackError {
+    pub event_id: String,
+    pub message: String,
+}
+
+/// The workflow reconstructed at a point in a session.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlaybackFrame {
+    pub session_id: String,
+    /// Number of events replayed.
+    pub position: usize,
+    pub total_events: usize,
+    /// The last replayed event.
+    pub event: Option<CollaborationEvent>,
+    /// `None` when nothing recorded so far describes the whole workflow.
+    pub workflow: Option<WorkflowSpec>,
+    /// Workflow-changing events replayed successfully.
+    pub applied: usize,
+    pub errors: Vec<PlaybackError>,
+}
+
+impl Recording {
+    /// Number of events at or before `at`.
+    pub fn position_at(&self, at: DateTime<Utc>) -> usize {
+        self.events
+            .iter()
+            .take_while(|e| DateTime::parse_from_rfc3339(&e.timestamp).is_ok_and(|t| t <= at))
+            .count()
+    }
+
+    /// Reconstruct the workflow after the first `position` events. Ops that
+    /// fail to apply are skipped and reported.
+    pub fn playback(&self, position: usize) -> PlaybackFrame {
+        let position = position.min(self.events.len());
+        let mut workflow = self.initial.clone();
+        let mut applied = 0;
+        let mut errors = Vec::new();
+
+        for event in &self.events[..position] {
+            let result = match event.event_type.as_str() {
+                SNAPSHOT_EVENT => serde_json::from_value::<WorkflowSpec>(event.payload.clone())
+                    .map(|spec| workflow = Some(spec))
+                    .map_err(|e| format!("Invalid snapshot: {e}")),
+                EDIT_OP_EVENT => match (
+                    &mut workflow,
+                    serde_json::from_value::<EditOp>(event.payload.clone()),
+                ) {
+                    (_, Err(e)) => Err(format!("Invalid edit: {e}")),
+                    (None, Ok(_)) => Err("No workflow state to apply the edit to".to_string()),
+                    (Some(spec), Ok(op)) => op.apply(spec).map(drop).map_err(|e| e.to_string()),
+                },
+                _ => continue,
+            };
+            match result {
+                Ok(()) => applied += 1,
+                Err(message) => errors.push(PlaybackError {
+                    event_id: event.id.clone(),
+                    message,
+                }),
+            }
+        }
+
+        PlaybackFrame {
+            session_id: self.session_id.clone(),
+            position,
+            total_events: self.events.len(),
+            event: position.checked_sub(1).map(|i| self.events[i].clone()),
+            workflow,
+            applied,
+            errors,
+        }
+    }
+}
+
+/// What the writer thread is asked to do.
+enum WriteRequest {
+    Append(PathBuf, Record),
+    /// Answer once everything sent before has been written.
+    Flush(mpsc::Sender<()>),
+}
+
+/// Appends collaboration events to per-session recording files.
+pub struct SessionRecorder {
+    dir: PathBuf,
+    writer: mpsc::Sender<WriteRequest>,
+}
+
+impl SessionRecorder {
+    pub fn new(dir: PathBuf) -> Self {
+        let (writer, requests) = mpsc::channel();
+        let writer_dir = dir.clone();
+        let spawned = std::thread::Builder::new()
+            .name("session-recorder".into())
+            .spawn(move || write_recordings(&writer_dir, requests));
+        if let Err(e) = spawned {
+            tracing::warn!("Session recording is off, its writer did not start: {e}");
+        }
+        Self { dir, writer }
+    }
+
+    fn path(&self, session_id: &str) -> Result<PathBuf, String> {
+        // Session IDs come from the frontend; only accept the UUIDs we issue
+        uuid::Uuid::parse_str(session_id)
+            .map_err(|_| format!("Invalid session ID: {session_id}"))?;
+        Ok(self.dir.join(format!("{session_id}.jsonl")))
+    }
+
+    fn append(&self, session_id: &str, record: Record) -> Result<(), String> {
+        let path = self.path(session_id)?;
+        self.writer
+            .send(WriteRequest::Append(path, record))
+            .map_err(|_| "the recording writer has stopped".to_string())
+    }
+
+    /// Wait until every event recorded so far is on disk.
+    fn flush(&self) {
+        let (done, wait) = mpsc::channel();
+        if self.writer.send(WriteRequest::Flush(done)).is_ok() {
+            let _ = wait.recv();
+        }
+    }
+
+    /// Start a recording with the session's initial workflow. Failures are
+    /// logged; recording must not get in the way of the session.
+    pub fn start(&self, session: &CollaborationSession, workflow: Option<WorkflowSpec>) {
+        let record = Record::Started {
+            session_id: session.id.clone(),
+            workflow_id: session.workflow_id.clone(),
+            name: session.name.clone(),
+            started_at: Utc::now(),
+            workflow: workflow.map(Box::new),
+        };
+        if let Err(e) = self.append(&session.id, record) {
+            tracing::warn!("Failed to start recording of session {}: {e}", session.id);
+        }
+    }
+
+    /// Record an event. Failures are logged.
+    pub fn record(&self, event: &CollaborationEvent) {
+        let record = Record::Event {
+            event: event.clone(),
+        };
+        if let Err(e) = self.append(&event.session_id, record) {
+            tracing::warn!(
+                "Failed to record event of session {}: {e}",
+                event.session_id
+            );
+        }
+    }
+
+    pub fn load(&self, session_id: &str) -> Result<Recording, String> {
+        let path = self.path(session_id)?;
+        self.flush();
+        let content = fs::read_to_string(&path)
+            .map_err(|_| format!("No recording for session {session_id}"))?;
+        parse_recording(&content)
+            .ok_or_else(|| format!("Recording of session {session_id} has no header"))
+    }
+
+    /// Recorded sessions, most recent first.
+    pub fn list(&self) -> Vec<RecordingSummary> {
+        self.flush();
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return vec![];
+        };
+        let mut summaries: Vec<RecordingSummary> = entries
+            .flatten()
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "jsonl"))
+            .filter_map(|entry| load_file(&entry.path()))
+            .map(|recording| RecordingSummary {
+                last_event_at: recording.events.last().map(|e| e.timestamp.clone()),
+                event_count: recording.events.len(),
+                session_id: recording.session_id,
+                workflow_id: recording.workflow_id,
+                name: recording.name,
+                started_at: recording.started_at,
+            })
+            .collect();
+        summaries.sort_by_key(|s| std::cmp::Reverse(s.started_at));
+        summaries
+    }
+}
+
+/// The writer thread: removes expired recordings, then appends lines until
+/// the recorder is dropped.
+fn write_recordings(dir: &Path, requests: mpsc::Receiver<WriteRequest>) {
+    remove_expired(dir, RETENTION);
+    let mut full = HashSet::new();
+    for request in requests {
+        let (path, record) = match request {
+            WriteRequest::Append(path, record) => (path, record),
+            WriteRequest::Flush(done) => {
+                let _ = done.send(());
+                continue;
+            }
+        };
+        if full.contains(&path) {
+            continue;
+        }
+        let written = serde_json::to_string(&record)
+            .map_err(|e| e.to_string())
+            .and_then(|mut line| {
+                line.push('\n');
+                let size = fs::metadata(&path).map_or(0, |m| m.len());
+                if size + line.len() as u64 > MAX_RECORDING_BYTES {
+                    full.insert(path.clone());
+                    return Err(format!(
+                        "recording reached {} MiB, later events are not kept",
+                        MAX_RECORDING_BYTES / (1024 * 1024)
+                    ));
+                }
+                fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&path)
+                    .and_then(|mut file| file.write_all(line.as_bytes()))
+                    .map_err(|e| e.to_string())
+            });
+        if let Err(e) = written {
+            tracing::warn!("Failed to record to {}: {e}", path.display());
+        }
+    }
+}
+
+/// Remove recordings last written more than `retention` ago.
+fn remove_expired(dir: &Path, retention: Duration) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.flatten().map(|e| e.path()) {
+        let expired = path.extension().is_some_and(|ext| ext == "jsonl")
+            && fs::metadata(&path)
+                .and_then(|m| m.modified())
+                .is_ok_and(|modified| modified.elapsed().is_ok_and(|age| age > retention));
+        if expired {
+            if let Err(e) = fs::remove_file(&path) {
+                tracing::warn!("Failed to remove expired recording {}: {e}", path.display());
+            }
+        }
+    }
+}
+
+fn load_file(path: &Path) -> Option<Recording> {
+    parse_recording(&fs::read_to_string(path).ok()?)
+}
+
+/// Parse a recording, skipping lines that don't parse (e.g. one cut short
+/// by a crash).
+fn parse_recording(content: &str) -> Option<Recording> {
+    let mut records = content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Record>(line).ok());
+    let Some(Record::Started {
+        session_id,
+        workflow_id,
+        name,
+        started_at,
+        workflow,
+    }) = records.next()
+    else {
+        return None;
+    };
+    let events = records
+        .filter_map(|record| match record {
+            Record::Event { event } => Some(event),
+            Record::Started { .. } => None,
+        })
+        .collect();
+    Some(Recording {
+        session_id,
+        workflow_id,
+        name,
+        started_at,
+        initial: workflow.map(|w| *w),
+        events,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::collaboration::SessionSettings;
+
+    fn event(session_id: &str, event_type: &str, payload: serde_json::Value) -> CollaborationEvent {
+        CollaborationEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            event_type: event_type.into(),
+            session_id: session_id.into(),
+            user_id: "kim".into(),
+            timestamp: Utc::now().to_rfc3339(),
+            payload,
+        }
+    }
+
+    fn add_node(id: &str) -> serde_json::Value {
+        serde_json::json!({
+            "op": "add_node",
+            "node": { "kind": "primitive", "id": id, "tool_ref": "core-tools/echo@1.0.0" },
+        })
+    }
+
+    #[test]
+    fn playback_reconstructs_workflow_at_each_position() {
+        let dir = std::env::temp_dir().join(format!("hb-recording-{}", uuid::Uuid::new_v4()));
+        let recorder = SessionRecorder::new(dir.clone());
+        let session = CollaborationSession {
+            id: uuid::Uuid::new_v4().to_string(),
+            workflow_id: "wf".into(),
+            name: "Review".into(),
+            owner: "kim".into(),
+            collaborators: vec![],
+            created_at: Utc::now().to_rfc3339(),
+            is_active: true,
+            settings: SessionSettings::default(),
+            executions: vec![],
+        };
+        recorder.start(&session, Some(WorkflowSpec::default()));
+        for e in [
+            event(&session.id, EDIT_OP_EVENT, add_node("a")),
+            event(
+                &session.id,
+                "chat_message",
+                serde_json::json!({ "content": "hi" }),
+            ),
+            event(&session.id, EDIT_OP_EVENT, add_node("b")),
+            event(&session.id, EDIT_OP_EVENT, add_node("a")),
+        ] {
+            recorder.record(&e);
+        }
+
+        let recording = recorder.load(&session.id).unwrap();
+        assert_eq!(recording.events.len(), 4);
+        assert_eq!(recorder.list().len(), 1);
+
+        let start = recording.playback(0);
+        assert_eq!(start.workflow.unwrap().nodes.len(), 0);
+
+        let middle = recording.playback(2);
+        assert_eq!(middle.workflow.unwrap().nodes.len(), 1);
+        assert_eq!(middle.event.unwrap().event_type, "chat_message");
+
+        // The duplicate add fails and is reported, not fatal
+        let end = recording.playback(usize::MAX);
+        assert_eq!(end.position, 4);
+        assert_eq!(end.applied, 2);
+        assert_eq!(end.errors.len(), 1);
+        assert_eq!(end.workflow.unwrap().nodes.len(), 2);
+
+        assert!(recorder.load("../etc/passwd").is_err());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn old_recordings_are_removed() {
+        let dir = std::env::temp_dir().join(format!("hb-recording-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let old = dir.join(format!("{}.jsonl", uuid::Uuid::new_v4()));
+        fs::write(&old, "").unwrap();
+        let file = fs::File::options().write(true).open(&old).unwrap();
+        file.set_modified(std::time::SystemTime::now() - RETENTION - Duration::from_secs(60))
+            .unwrap();
+        let recent = dir.join(format!("{}.jsonl", uuid::Uuid::new_v4()));
+        fs::write(&recent, "").unwrap();
+
+        remove_expired(&dir, RETENTION);
+        assert!(!old.exists());
+        assert!(recent.exists());
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -1,16 +1,25 @@
 //! Application state shared across all Tauri commands.
 
+use crate::credential_health::{self, CredentialHealth, HealthCache};
+use hb_compiler::diagnostics::IncrementalValidator;
+use hb_mcp::palette::PaletteCatalog;
 use hb_mcp::registry::ToolRegistry;
-use hb_project::ProjectManager;
-use hb_trace::store::TraceStore;
+use hb_project::thumbnail::{self, Thumbnail, ThumbnailCache};
+use hb_project::{ProjectManager, ProjectStore};
+use hb_runner::gc::{self, GcReport, GcTargets, OrphanScan, RetentionConfig};
+use hb_runner::{ExecutionCache, ExecutionJournal, ResourceLocks, ScratchSpace, ShutdownCoordinator, ShutdownReport};
+use hb_trace::store::{SqliteTraceStore, TraceStore};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 
-use hb_core::graph::WorkflowSpec;
+use hb_core::graph::{SpecAssembler, WorkflowSpec};
+use hb_core::error::{AppError, ErrorCode};
+use hb_core::project::{ActivityEntry, ActivityKind, Permission, Role};
 
 /// LLM credentials storage
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -23,9 +32,16 @@ pub struct LLMCredentials {
     pub openai_api_key: Option<String>,
     pub anthropic_api_key: Option<String>,
     pub local_endpoint: Option<String>,
-    /// Active LLM provider selected by the user: "bedrock"|"openai"|"anthropic"|"local"
+    /// Active LLM provider selected by the user: "bedrock"|"openai"|"anthropic"|"local"|"llamacpp"
     #[serde(default)]
     pub active_provider: Option<String>,
+    /// Keys for public data and other API services, by the environment
+    /// variable tools read them from (e.g. `DATA_GO_KR_SERVICE_KEY`).
+    #[serde(default)]
+    pub service_keys: BTreeMap<String, String>,
+    /// Bearer token for the team sync server.
+    #[serde(default)]
+    pub team_sync_token: Option<String>,
 }
 
 impl LLMCredentials {
@@ -56,30 +72,77 @@ pub struct AppState {
     /// Tool registry (loaded from packs).
     pub tool_registry: Arc<RwLock<ToolRegistry>>,
 
-    /// Trace store (SQLite), Arc-wrapped for sharing with execution context.
-    pub trace_store: Arc<RwLock<Option<Arc<TraceStore>>>>,
+    /// Localized palette strings (loaded from pack locale bundles).
+    pub palette_catalog: Arc<RwLock<PaletteCatalog>>,
+
+    /// Trace store (SQLite by default), Arc-wrapped for sharing with
+    /// execution context.
+    pub trace_store: Arc<RwLock<Option<Arc<dyn TraceStore>>>>,
 
-    /// Project manager.
-    pub project_manager: Arc<RwLock<ProjectManager>>,
+    /// Project store (SQLite by default).
+    pub project_manager: Arc<RwLock<Box<dyn ProjectStore>>>,
+
+    /// Rendered workflow thumbnails, kept in the project database.
+    pub thumbnails: Arc<RwLock<ThumbnailCache>>,
 
     /// In-memory workflow storage (Phase 1; Phase 2 will use SQLite).
     pub workflows: Arc<RwLock<HashMap<String, WorkflowSpec>>>,
 
+    /// Workflows being saved page by page, keyed by upload ID.
+    pub workflow_uploads: Arc<RwLock<HashMap<String, SpecAssembler>>>,
+
+    /// Live diagnostics per workflow, updated incrementally as edits arrive.
+    pub validators: Arc<RwLock<HashMap<String, IncrementalValidator>>>,
+
     /// LLM credentials (persistent storage).
     pub llm_credentials: Arc<RwLock<LLMCredentials>>,
 
     /// Execution plans for Plan → Execute pipeline
     pub execution_plans: Arc<RwLock<HashMap<String, crate::commands::agent_loop::ExecutionPlan>>>,
 
+    /// Node output cache (SQLite) for nodes with a cache policy.
+    pub execution_cache: Arc<RwLock<Option<Arc<ExecutionCache>>>>,
+
+    /// Write-ahead journal of scheduler decisions for crash recovery.
+    pub journal: Arc<RwLock<Option<Arc<ExecutionJournal>>>>,
+
+    /// Coordinates cancellation of running executions on app exit.
+    pub shutdown: Arc<ShutdownCoordinator>,
+
+    /// Advisory locks on tool-declared resources, shared by all executions.
+    pub resource_locks: Arc<ResourceLocks>,
+
+    /// Latest validation result per configured provider credential.
+    pub credential_health: Arc<RwLock<HealthCache>>,
+
     /// Data directory for the app.
     pub data_dir: PathBuf,
 }
 
+/// How long running executions get to stop on their own during shutdown.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+
+/// Who is performing an authorized action, for attributing activity.
+#[derive(Debug, Clone, Default)]
+pub struct Access {
+    pub actor: Option<String>,
+    /// Role in a shared workspace; `None` when roles don't apply.
+    pub role: Option<Role>,
+}
+
+impl Access {
+    /// Attribute an activity entry to this user and role.
+    pub fn stamp(&self, entry: ActivityEntry) -> ActivityEntry {
+        entry.by(self.actor.clone(), self.role)
+    }
+}
+
 impl AppState {
     pub fn new(data_dir: PathBuf) -> Self {
         // Load credentials from file
         let creds_path = data_dir.join("llm_credentials.json");
-        let credentials = LLMCredentials::load(&creds_path);
+        let mut credentials = LLMCredentials::load(&creds_path);
+        crate::team_sync::migrate_token(&data_dir, &mut credentials, &creds_path);
 
         // Set environment variables from saved credentials
         // AWS Bedrock credentials
@@ -101,14 +164,26 @@ impl AppState {
         if let Some(ref endpoint) = credentials.local_endpoint {
             std::env::set_var("LOCAL_LLM_ENDPOINT", endpoint);
         }
+        for (name, key) in &credentials.service_keys {
+            std::env::set_var(name, key);
+        }
 
         Self {
             tool_registry: Arc::new(RwLock::new(ToolRegistry::new())),
+            palette_catalog: Arc::new(RwLock::new(PaletteCatalog::new())),
             trace_store: Arc::new(RwLock::new(None)),
-            project_manager: Arc::new(RwLock::new(ProjectManager::new())),
+            project_manager: Arc::new(RwLock::new(Box::new(ProjectManager::new()))),
+            thumbnails: Arc::new(RwLock::new(ThumbnailCache::new())),
             workflows: Arc::new(RwLock::new(HashMap::new())),
+            workflow_uploads: Arc::new(RwLock::new(HashMap::new())),
+            validators: Arc::new(RwLock::new(HashMap::new())),
             llm_credentials: Arc::new(RwLock::new(credentials)),
             execution_plans: Arc::new(RwLock::new(HashMap::new())),
+            execution_cache: Arc::new(RwLock::new(None)),
+            journal: Arc::new(RwLock::new(None)),
+            shutdown: Arc::new(ShutdownCoordinator::new()),
+            resource_locks: Arc::new(ResourceLocks::new()),
+            credential_health: Arc::new(RwLock::new(HealthCache::default())),
             data_dir,
         }
     }
@@ -118,15 +193,315 @@ impl AppState {
         self.data_dir.join("llm_credentials.json")
     }
 
+    /// Validate every configured credential, cache the results and return
+    /// the entries whose state changed since the last check.
+    pub async fn refresh_credential_health(&self) -> Vec<CredentialHealth> {
+        let generation = self.credential_health.read().await.generation();
+        let creds = self.llm_credentials.read().await.clone();
+        let results = credential_health::check_all(&creds).await;
+        self.credential_health.write().await.update(generation, results)
+    }
+
     /// Initialize trace store at data_dir/traces.db
     pub fn init_trace_store(&self) -> Result<(), String> {
         let path = self.data_dir.join("traces.db");
         let store =
-            TraceStore::open(&path).map_err(|e| format!("Failed to open trace store: {e}"))?;
+            SqliteTraceStore::open(&path).map_err(|e| format!("Failed to open trace store: {e}"))?;
+        let store: Arc<dyn TraceStore> = Arc::new(store);
+        self.shutdown.set_trace_store(store.clone());
         // We can't await here in a sync context, so we use try_write
         if let Ok(mut guard) = self.trace_store.try_write() {
-            *guard = Some(Arc::new(store));
+            *guard = Some(store);
         }
         Ok(())
     }
+
+    /// Open the node output cache at data_dir/cache.db
+    pub fn init_execution_cache(&self) -> Result<(), String> {
+        let cache = ExecutionCache::new(&self.data_dir.join("cache.db"))
+            .map_err(|e| format!("Failed to open execution cache: {e}"))?;
+        if let Ok(mut guard) = self.execution_cache.try_write() {
+            *guard = Some(Arc::new(cache));
+        }
+        Ok(())
+    }
+
+    /// Path of the retention config (data_dir/retention.json).
+    pub fn retention_path(&self) -> PathBuf {
+        self.data_dir.join("retention.json")
+    }
+
+    /// Load the retention config; missing or invalid files yield the defaults.
+    pub fn retention_config(&self) -> RetentionConfig {
+        fs::read_to_string(self.retention_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Stores and directories garbage collection may clean up.
+    pub async fn gc_targets(&self) -> GcTargets {
+        // Without the live set every project directory would look orphaned,
+        // so skip the scan unless the project database is available.
+        let live_projects = {
+            let pm = self.project_manager.read().await;
+            match pm.list_workspaces() {
+                Ok(workspaces) if pm.is_persistent() => {
+                    Some(workspaces.iter().map(|ws| ws.id.to_string()).collect())
+                }
+                Ok(_) => None,
+                Err(e) => {
+                    tracing::warn!("Skipping orphaned project cleanup: {e}");
+                    None
+                }
+            }
+        };
+        GcTargets {
+            trace_store: self.trace_store.read().await.clone(),
+            cache: self.execution_cache.read().await.clone(),
+            journal: self.journal.read().await.clone(),
+            orphan_scans: live_projects
+                .map(|live| OrphanScan {
+                    dir: self.data_dir.join("projects"),
+                    live,
+                })
+                .into_iter()
+                .collect(),
+            scratch_dir: Some(self.data_dir.join("scratch")),
+            trace_archive_dir: Some(self.data_dir.join("trace-archive")),
+        }
+    }
+
+    /// Scratch directories of executions (data_dir/scratch), kept after
+    /// failed runs if the retention config says so.
+    pub fn scratch_space(&self) -> ScratchSpace {
+        ScratchSpace::new(self.data_dir.join("scratch"))
+            .keep_failed(self.retention_config().keep_failed_scratch)
+    }
+
+    /// Run garbage collection with the saved retention config.
+    pub async fn run_gc(&self) -> GcReport {
+        let config = self.retention_config();
+        let targets = self.gc_targets().await;
+        tokio::task::spawn_blocking(move || gc::collect(&config, &targets))
+            .await
+            .unwrap_or_else(|e| GcReport {
+                errors: vec![format!("garbage collection panicked: {e}")],
+                ..Default::default()
+            })
+    }
+
+    /// Open the execution journal at data_dir/journal and reconcile any
+    /// executions left in flight by a crash. Call after `init_trace_store`.
+    pub fn init_journal(&self) -> Result<usize, String> {
+        let journal = ExecutionJournal::open(&self.data_dir.join("journal"))
+            .map_err(|e| format!("Failed to open execution journal: {e}"))?;
+
+        let mut recovered = 0;
+        if let Ok(guard) = self.trace_store.try_read() {
+            if let Some(store) = guard.as_ref() {
+                recovered = journal
+                    .reconcile(store.as_ref())
+                    .map_err(|e| format!("Failed to recover execution journal: {e}"))?
+                    .len();
+            }
+        }
+
+        if let Ok(mut guard) = self.journal.try_write() {
+            *guard = Some(Arc::new(journal));
+        }
+        Ok(recovered)
+    }
+
+    /// Append to the activity feed. Failures are logged, never surfaced —
+    /// the activity log must not block the action it describes.
+    pub async fn record_activity(&self, entry: ActivityEntry) {
+        if let Err(e) = self.project_manager.read().await.record_activity(&entry) {
+            tracing::warn!("Failed to record activity: {e}");
+        }
+    }
+
+    /// Check the current user's role for an action in a workspace. The user
+    /// is the team sync user name; roles only apply while team sync is on,
+    /// and then only in workspaces that have members.
+    ///
+    /// While team sync is on, workspace actions other than viewing need the
+    /// workspace they act in. Credentials are shared by every workspace, so managing them needs
+    /// the role in all shared ones, as does changing policy when no
+    /// workspace is given. Refusals are recorded in the activity log.
+    pub async fn authorize(
+        &self,
+        workspace_id: Option<uuid::Uuid>,
+        permission: Permission,
+    ) -> Result<Access, AppError> {
+        let config = crate::team_sync::SyncConfig::load(&self.data_dir);
+        if !config.is_active() {
+            return Ok(Access { actor: None, role: None });
+        }
+        let actor = config.user.clone();
+
+        let global = permission == Permission::ManageCredentials
+            || (permission == Permission::ChangePolicy && workspace_id.is_none());
+        let workspaces = {
+            let pm = self.project_manager.read().await;
+            match workspace_id {
+                _ if global => pm
+                    .list_workspaces()
+                    .map_err(|e| AppError::internal(e.to_string()))?
+                    .into_iter()
+                    .filter(|ws| ws.is_shared())
+                    .collect(),
+                Some(id) => vec![pm
+                    .get_workspace(id)
+                    .map_err(|e| AppError::not_found(e.to_string()))?],
+                None if permission == Permission::View => Vec::new(),
+                None => {
+                    let e = AppError::new(
+                        ErrorCode::PermissionDenied,
+                        format!(
+                            "Choose a workspace: {} needs one while team sync is on",
+                            permission.describe()
+                        ),
+                    );
+                    drop(pm);
+                    self.record_denial(None, actor, None, permission, &e).await;
+                    return Err(e);
+                }
+            }
+        };
+
+        let mut roles = Vec::new();
+        for ws in &workspaces {
+            match ws.authorize(actor.as_deref(), permission) {
+                Ok(role) => roles.extend(role),
+                Err(e) => {
+                    let role = ws.role_of(actor.as_deref());
+                    self.record_denial(Some(ws.id), actor, role, permission, &e).await;
+                    return Err(e);
+                }
+            }
+        }
+        // Attributed to the least privileged role it was allowed under
+        Ok(Access {
+            actor,
+            role: roles.into_iter().min(),
+        })
+    }
+
+    async fn record_denial(
+        &self,
+        workspace_id: Option<uuid::Uuid>,
+        actor: Option<String>,
+        role: Option<Role>,
+        permission: Permission,
+        e: &AppError,
+    ) {
+        let target = workspace_id.map(|id| id.to_string()).unwrap_or_default();
+        let entry = ActivityEntry::new(ActivityKind::AccessDenied, target, &e.message)
+            .in_workspace(workspace_id)
+            .by(actor, role)
+            .with_details(serde_json::json!({ "permission": permission }));
+        self.record_activity(entry).await;
+    }
+
+    /// Re-render a workflow's cached thumbnail after a save. Failures are
+    /// logged; thumbnails are re-rendered on demand when missing.
+    pub async fn refresh_thumbnail(&self, spec: &WorkflowSpec) {
+        if let Err(e) = self.thumbnail(spec).await {
+            tracing::warn!("Failed to render thumbnail for {}: {e}", spec.id);
+        }
+    }
+
+    /// A workflow's thumbnail: the cached one if it was rendered from the
+    /// same graph, or a new render. Rendering runs on the blocking pool,
+    /// without holding the cache lock.
+    pub async fn thumbnail(&self, spec: &WorkflowSpec) -> Result<Thumbnail, AppError> {
+        let cached = self.thumbnails.read().await.get(spec.id)?;
+        if let Some(cached) = cached.filter(|cached| cached.spec_hash == thumbnail::spec_hash(spec)) {
+            return Ok(cached);
+        }
+        let owned = spec.clone();
+        let thumb = tokio::task::spawn_blocking(move || thumbnail::render(&owned))
+            .await
+            .map_err(|e| AppError::internal(format!("Thumbnail render failed: {e}")))??;
+        self.thumbnails.read().await.save(&thumb)?;
+        Ok(thumb)
+    }
+
+    /// Gracefully shut down: cancel running executions, record the ones that
+    /// do not stop in time as interrupted, flush traces, and close database
+    /// connections.
+    pub async fn shutdown(&self) -> ShutdownReport {
+        let report = self.shutdown.shutdown(SHUTDOWN_GRACE).await;
+
+        if let Some(store) = self.trace_store.write().await.take() {
+            // The coordinator gave its reference up in `shutdown`; executions
+            // that timed out may still hold one, in which case the store is
+            // only flushed.
+            if let Err(e) = store.close() {
+                tracing::warn!("Failed to close trace store: {e}");
+            }
+        }
+
+        if let Err(e) = self.project_manager.write().await.close() {
+            tracing::warn!("Failed to close project database: {e}");
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::*;
+    use hb_core::project::{WorkspaceConfig, WorkspaceMember};
+
+    /// App state in a fresh data directory, with in-memory projects and team
+    /// sync on as `user`.
+    pub async fn team_state(user: &str) -> AppState {
+        let dir = std::env::temp_dir().join(format!("hb-team-{}", uuid::Uuid::new_v4()));
+        crate::team_sync::SyncConfig {
+            server_url: "https://team.example.com".into(),
+            enabled: true,
+            user: Some(user.into()),
+            ..Default::default()
+        }
+        .save(&dir)
+        .unwrap();
+        let state = AppState::new(dir);
+        *state.project_manager.write().await = Box::new(ProjectManager::in_memory().unwrap());
+        state
+    }
+
+    /// Create a workspace shared with `members`.
+    pub async fn shared_workspace(state: &AppState, members: &[(&str, Role)]) -> uuid::Uuid {
+        let now = chrono::Utc::now();
+        let config = WorkspaceConfig {
+            id: uuid::Uuid::new_v4(),
+            name: "Team".into(),
+            description: None,
+            root_path: String::new(),
+            created_at: now,
+            updated_at: now,
+            data_sources: vec![],
+            indexes: vec![],
+            default_policy: None,
+            llm_providers: vec![],
+            timezone: None,
+            members: members
+                .iter()
+                .map(|(user, role)| WorkspaceMember {
+                    user: user.to_string(),
+                    role: *role,
+                })
+                .collect(),
+            review_policy: None,
+        };
+        state
+            .project_manager
+            .read()
+            .await
+            .create_workspace(&config)
+            .unwrap()
+    }
 }
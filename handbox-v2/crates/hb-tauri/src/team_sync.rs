@@ -0,0 +1,1068 @@
+//! Team server sync — share workflows, packs, workspace policies and
+//! workspace members through a self-hosted team server instead of the public
+//! marketplace.
+//!
+//! Sync is local-first: everything is edited locally and [`sync`] reconciles
+//! with the server when it is reachable. Turning sync off (or losing the
+//! connection) leaves the app fully usable; local changes are pushed on the
+//! next successful sync. The server keeps a version number per item and
+//! refuses writes based on a stale version:
+//!
+//! - `GET {server}/api/v1/items` → `{ "items": [ItemMeta, ...] }`
+//! - `GET {server}/api/v1/items/{kind}/{id}` → `ItemMeta` plus `content`
+//! - `PUT {server}/api/v1/items/{kind}/{id}` with
+//!   `{ "base_version": 3, "hash": "...", "name": "...", "content": ... }`
+//!   → the stored `ItemMeta`, or `409 Conflict` when `base_version` is not
+//!   the server's current version (`null` creates the item)
+//! - `GET {server}/api/v1/me` → `{ "user": "..." }`, the user the token was
+//!   issued to
+//!
+//! Requests carry `Authorization: Bearer <token>`; the token is kept with
+//! the other credentials ([`LLMCredentials`]), not in the sync settings.
+//! While sync is on, the user the server reports for the token is the
+//! identity workspace roles are checked against (see
+//! [`AppState::authorize`]). Deletions are not propagated: an item deleted
+//! locally stays deleted until someone changes it on the server. Pulled
+//! packs are only installed if their files match the hash the server lists.
+//!
+//! The workflow library only lives in memory, so synced workflows are also
+//! kept in `team_sync/workflows/` and restored at startup; otherwise one
+//! missing after a restart would look deleted and never be pulled again.
+
+use crate::pack_updater::{self, pack_hash};
+use crate::state::{AppState, LLMCredentials};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use hb_core::graph::WorkflowSpec;
+use hb_core::policy::Policy;
+use hb_core::project::{ActivityEntry, ActivityKind, WorkspaceMember};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Event emitted after a background sync.
+pub const SYNC_COMPLETED_EVENT: &str = "team-sync-completed";
+
+/// How often the background task checks whether a sync is due.
+pub const IDLE_CHECK: Duration = Duration::from_secs(60);
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Serializes syncs so a manual sync and the background task don't race
+/// over the ledger.
+static SYNC_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// What kind of library item is synced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncKind {
+    Workflow,
+    Pack,
+    /// A workspace's default policy, keyed by workspace ID.
+    Policy,
+    /// A shared workspace's member roles, keyed by workspace ID.
+    Members,
+}
+
+impl SyncKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Workflow => "workflow",
+            Self::Pack => "pack",
+            Self::Policy => "policy",
+            Self::Members => "members",
+        }
+    }
+}
+
+fn item_key(kind: SyncKind, id: &str) -> String {
+    format!("{}/{id}", kind.as_str())
+}
+
+/// Persisted sync settings (`team_sync.json` in the data dir).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncConfig {
+    /// Base URL of the team server, e.g. `https://handbox.example.com`.
+    #[serde(default)]
+    pub server_url: String,
+    /// Bearer token saved by older versions; only read, to move it into
+    /// the credential store (see [`migrate_token`]).
+    #[serde(default, skip_serializing)]
+    pub token: Option<String>,
+    /// When off, the app works offline and nothing leaves this machine.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Minutes between automatic syncs; 0 syncs only on request.
+    #[serde(default)]
+    pub interval_minutes: u64,
+    /// The user the server issued the token to, used for workspace roles
+    /// and the activity log. Set by [`verify_identity`], never by the user.
+    #[serde(default)]
+    pub user: Option<String>,
+}
+
+impl SyncConfig {
+    pub fn path(data_dir: &Path) -> PathBuf {
+        data_dir.join("team_sync.json")
+    }
+
+    pub fn load(data_dir: &Path) -> Self {
+        fs::read_to_string(Self::path(data_dir))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, data_dir: &Path) -> Result<(), String> {
+        fs::create_dir_all(data_dir).map_err(|e| e.to_string())?;
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(Self::path(data_dir), content).map_err(|e| e.to_string())
+    }
+
+    /// Whether sync is turned on and points at a server.
+    pub fn is_active(&self) -> bool {
+        self.enabled && !self.server_url.trim().is_empty()
+    }
+
+    /// Interval between automatic syncs, if scheduled.
+    pub fn interval(&self) -> Option<Duration> {
+        (self.is_active() && self.interval_minutes > 0)
+            .then(|| Duration::from_secs(self.interval_minutes * 60))
+    }
+}
+
+/// Sync settings as shown to the UI; the token itself is never returned.
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncSettings {
+    pub server_url: String,
+    pub enabled: bool,
+    pub interval_minutes: u64,
+    pub user: Option<String>,
+    pub has_token: bool,
+}
+
+impl SyncSettings {
+    pub fn new(config: &SyncConfig, credentials: &LLMCredentials) -> Self {
+        Self {
+            server_url: config.server_url.clone(),
+            enabled: config.enabled,
+            interval_minutes: config.interval_minutes,
+            user: config.user.clone(),
+            has_token: credentials.team_sync_token.as_deref().is_some_and(|t| !t.is_empty()),
+        }
+    }
+}
+
+/// Move a token an older version saved in `team_sync.json` into
+/// `credentials`, saved at `credentials_path`, and drop it from the file.
+pub fn migrate_token(data_dir: &Path, credentials: &mut LLMCredentials, credentials_path: &Path) {
+    let config = SyncConfig::load(data_dir);
+    let Some(token) = config.token.clone().filter(|t| !t.is_empty()) else {
+        return;
+    };
+    if credentials.team_sync_token.is_none() {
+        credentials.team_sync_token = Some(token);
+        if let Err(e) = credentials.save(&credentials_path.to_path_buf()) {
+            tracing::warn!("Failed to move the team sync token to the credential store: {e}");
+            return;
+        }
+    }
+    if let Err(e) = config.save(data_dir) {
+        tracing::warn!("Failed to remove the team sync token from its settings: {e}");
+    }
+}
+
+/// Server version and local content hash of an item as of its last sync.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncedVersion {
+    pub version: u64,
+    pub hash: String,
+}
+
+/// What was last synced (`team_sync_state.json` in the data dir).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncLedger {
+    #[serde(default)]
+    pub items: BTreeMap<String, SyncedVersion>,
+    /// Conflicts found by the last sync, kept so they show up offline.
+    #[serde(default)]
+    pub conflicts: Vec<SyncConflict>,
+    #[serde(default)]
+    pub last_sync: Option<DateTime<Utc>>,
+}
+
+impl SyncLedger {
+    pub fn path(data_dir: &Path) -> PathBuf {
+        data_dir.join("team_sync_state.json")
+    }
+
+    pub fn load(data_dir: &Path) -> Self {
+        fs::read_to_string(Self::path(data_dir))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, data_dir: &Path) -> Result<(), String> {
+        fs::create_dir_all(data_dir).map_err(|e| e.to_string())?;
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(Self::path(data_dir), content).map_err(|e| e.to_string())
+    }
+}
+
+/// An item as listed by the server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemMeta {
+    pub kind: SyncKind,
+    pub id: String,
+    pub version: u64,
+    pub hash: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub updated_by: Option<String>,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RemoteItem {
+    #[serde(flatten)]
+    meta: ItemMeta,
+    content: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct Identity {
+    user: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ItemList {
+    #[serde(default)]
+    items: Vec<ItemMeta>,
+}
+
+/// An item in the local library.
+#[derive(Debug, Clone)]
+struct LocalItem {
+    kind: SyncKind,
+    id: String,
+    name: String,
+    hash: String,
+}
+
+/// An item changed both locally and on the server since the last sync.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncConflict {
+    pub kind: SyncKind,
+    pub id: String,
+    pub name: String,
+    pub remote_version: u64,
+    pub remote_updated_by: Option<String>,
+}
+
+/// Outcome of a sync. Items are identified as `kind/id`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncReport {
+    pub pushed: Vec<String>,
+    pub pulled: Vec<String>,
+    pub conflicts: Vec<SyncConflict>,
+    pub errors: Vec<String>,
+    /// The server could not be reached; nothing (more) was synced.
+    pub offline: bool,
+    pub synced_at: DateTime<Utc>,
+}
+
+/// Local view of the sync state; computed without contacting the server.
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncStatus {
+    pub active: bool,
+    pub last_sync: Option<DateTime<Utc>>,
+    /// Local items changed since they were last synced.
+    pub pending: Vec<String>,
+    pub conflicts: Vec<SyncConflict>,
+}
+
+/// What to do with one item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Skip,
+    Push,
+    Pull,
+    /// Both sides hold the same content; only the ledger needs updating.
+    Record,
+    Conflict,
+}
+
+/// Decide how to reconcile an item from its local content hash, its server
+/// metadata and what was last synced.
+pub fn plan(
+    local: Option<&str>,
+    remote: Option<&ItemMeta>,
+    base: Option<&SyncedVersion>,
+) -> Action {
+    match (local, remote, base) {
+        (None, None, _) => Action::Skip,
+        // New locally, or missing on the server: (re)create it there.
+        (Some(_), None, _) => Action::Push,
+        // Deleted locally and unchanged remotely: leave it deleted here.
+        (None, Some(r), Some(b)) if r.version == b.version => Action::Skip,
+        (None, Some(_), _) => Action::Pull,
+        (Some(hash), Some(r), None) => {
+            if hash == r.hash {
+                Action::Record
+            } else {
+                Action::Conflict
+            }
+        }
+        (Some(hash), Some(r), Some(b)) => {
+            let local_changed = hash != b.hash;
+            let remote_changed = r.version != b.version;
+            match (local_changed, remote_changed) {
+                (false, false) => Action::Skip,
+                (true, false) => Action::Push,
+                (false, true) => Action::Pull,
+                (true, true) if hash == r.hash => Action::Record,
+                (true, true) => Action::Conflict,
+            }
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+enum RequestError {
+    #[error("team server unreachable: {0}")]
+    Offline(String),
+    #[error("item was changed on the server")]
+    Conflict,
+    #[error("item not found on the server")]
+    NotFound,
+    #[error("{0}")]
+    Failed(String),
+}
+
+struct Client {
+    http: reqwest::Client,
+    base: String,
+    token: Option<String>,
+}
+
+impl Client {
+    fn new(config: &SyncConfig, token: Option<&str>) -> Result<Self, String> {
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            http,
+            base: format!("{}/api/v1", config.server_url.trim().trim_end_matches('/')),
+            token: token.filter(|t| !t.is_empty()).map(str::to_string),
+        })
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let request = self.http.request(method, format!("{}/{path}", self.base));
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    async fn send(request: reqwest::RequestBuilder) -> Result<reqwest::Response, RequestError> {
+        let response = request.send().await.map_err(|e| {
+            if e.is_connect() || e.is_timeout() {
+                RequestError::Offline(e.to_string())
+            } else {
+                RequestError::Failed(e.to_string())
+            }
+        })?;
+        match response.status() {
+            reqwest::StatusCode::CONFLICT => Err(RequestError::Conflict),
+            reqwest::StatusCode::NOT_FOUND => Err(RequestError::NotFound),
+            status if status.is_success() => Ok(response),
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                Err(RequestError::Failed(format!(
+                    "server returned {status}: {body}"
+                )))
+            }
+        }
+    }
+
+    fn item_path(kind: SyncKind, id: &str) -> String {
+        format!("items/{}/{}", kind.as_str(), urlencoding::encode(id))
+    }
+
+    async fn list(&self) -> Result<Vec<ItemMeta>, RequestError> {
+        let response = Self::send(self.request(reqwest::Method::GET, "items")).await?;
+        let list: ItemList = response
+            .json()
+            .await
+            .map_err(|e| RequestError::Failed(format!("Invalid item list: {e}")))?;
+        Ok(list.items)
+    }
+
+    async fn whoami(&self) -> Result<String, RequestError> {
+        let response = Self::send(self.request(reqwest::Method::GET, "me")).await?;
+        let identity: Identity = response
+            .json()
+            .await
+            .map_err(|e| RequestError::Failed(format!("Invalid identity: {e}")))?;
+        Ok(identity.user)
+    }
+
+    async fn get(&self, kind: SyncKind, id: &str) -> Result<RemoteItem, RequestError> {
+        let response =
+            Self::send(self.request(reqwest::Method::GET, &Self::item_path(kind, id))).await?;
+        response
+            .json()
+            .await
+            .map_err(|e| RequestError::Failed(format!("Invalid item: {e}")))
+    }
+
+    async fn put(
+        &self,
+        item: &LocalItem,
+        content: serde_json::Value,
+        base_version: Option<u64>,
+    ) -> Result<ItemMeta, RequestError> {
+        let body = serde_json::json!({
+            "base_version": base_version,
+            "hash": item.hash,
+            "name": item.name,
+            "content": content,
+        });
+        let request = self
+            .request(reqwest::Method::PUT, &Self::item_path(item.kind, &item.id))
+            .json(&body);
+        Self::send(request)
+            .await?
+            .json()
+            .await
+            .map_err(|e| RequestError::Failed(format!("Invalid response: {e}")))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Local library
+// ---------------------------------------------------------------------------
+
+fn packs_dir(state: &AppState) -> PathBuf {
+    state
+        .data_dir
+        .parent()
+        .unwrap_or(&state.data_dir)
+        .join("packs")
+}
+
+/// SHA-256 of a JSON value with object keys sorted, so the hash doesn't
+/// depend on map ordering.
+fn content_hash(value: &serde_json::Value) -> String {
+    fn feed(hasher: &mut Sha256, value: &serde_json::Value) {
+        match value {
+            serde_json::Value::Object(map) => {
+                let mut entries: Vec<_> = map.iter().collect();
+                entries.sort_by(|a, b| a.0.cmp(b.0));
+                hasher.update(b"{");
+                for (key, value) in entries {
+                    hasher.update(serde_json::to_string(key).unwrap_or_default());
+                    hasher.update(b":");
+                    feed(hasher, value);
+                    hasher.update(b",");
+                }
+                hasher.update(b"}");
+            }
+            serde_json::Value::Array(items) => {
+                hasher.update(b"[");
+                for item in items {
+                    feed(hasher, item);
+                    hasher.update(b",");
+                }
+                hasher.update(b"]");
+            }
+            scalar => hasher.update(scalar.to_string()),
+        }
+    }
+    let mut hasher = Sha256::new();
+    feed(&mut hasher, value);
+    hex::encode(hasher.finalize())
+}
+
+pub fn pack_archive(dir: &Path) -> Result<Vec<u8>, String> {
+    let encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+    builder
+        .append_dir_all(".", dir)
+        .map_err(|e| format!("Failed to archive pack: {e}"))?;
+    builder
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .map_err(|e| format!("Failed to archive pack: {e}"))
+}
+
+async fn collect_local(state: &AppState) -> Vec<LocalItem> {
+    let mut items = Vec::new();
+
+    for spec in state.workflows.read().await.values() {
+        if let Ok(value) = serde_json::to_value(spec) {
+            items.push(LocalItem {
+                kind: SyncKind::Workflow,
+                id: spec.id.to_string(),
+                name: spec.meta.name.clone(),
+                hash: content_hash(&value),
+            });
+        }
+    }
+
+    match state.project_manager.read().await.list_workspaces() {
+        Ok(workspaces) => {
+            for ws in workspaces {
+                if ws.is_shared() {
+                    if let Ok(value) = serde_json::to_value(&ws.members) {
+                        items.push(LocalItem {
+                            kind: SyncKind::Members,
+                            id: ws.id.to_string(),
+                            name: ws.name.clone(),
+                            hash: content_hash(&value),
+                        });
+                    }
+                }
+                let Some(value) = ws.default_policy.and_then(|p| serde_json::to_value(p).ok())
+                else {
+                    continue;
+                };
+                items.push(LocalItem {
+                    kind: SyncKind::Policy,
+                    id: ws.id.to_string(),
+                    name: ws.name,
+                    hash: content_hash(&value),
+                });
+            }
+        }
+        Err(e) => tracing::warn!("Team sync skipped workspaces: {e}"),
+    }
+
+    for (id, _) in pack_updater::installed_versions(&packs_dir(state)) {
+        match pack_hash(&packs_dir(state).join(&id)) {
+            Ok(hash) => items.push(LocalItem {
+                kind: SyncKind::Pack,
+                name: id.clone(),
+                id,
+                hash,
+            }),
+            Err(e) => tracing::warn!("Team sync skipped pack '{id}': {e}"),
+        }
+    }
+    items
+}
+
+/// Content to upload for a local item.
+async fn local_content(state: &AppState, item: &LocalItem) -> Result<serde_json::Value, String> {
+    match item.kind {
+        SyncKind::Workflow => {
+            let workflows = state.workflows.read().await;
+            let spec = workflows
+                .get(&item.id)
+                .ok_or_else(|| format!("Workflow not found: {}", item.id))?;
+            serde_json::to_value(spec).map_err(|e| e.to_string())
+        }
+        SyncKind::Policy | SyncKind::Members => {
+            let id = item.id.parse().map_err(|e: uuid::Error| e.to_string())?;
+            let ws = state
+                .project_manager
+                .read()
+                .await
+                .get_workspace(id)
+                .map_err(|e| e.to_string())?;
+            if item.kind == SyncKind::Members {
+                serde_json::to_value(ws.members).map_err(|e| e.to_string())
+            } else {
+                serde_json::to_value(ws.default_policy).map_err(|e| e.to_string())
+            }
+        }
+        SyncKind::Pack => {
+            let archive = pack_archive(&packs_dir(state).join(&item.id))?;
+            Ok(serde_json::json!({
+                "archive": base64::engine::general_purpose::STANDARD.encode(archive),
+            }))
+        }
+    }
+}
+
+/// Install a pulled item locally. Returns the resulting local content hash,
+/// or an empty hash when the item has nowhere to go (a policy or members of
+/// a workspace that doesn't exist here).
+async fn apply_remote(state: &AppState, item: RemoteItem) -> Result<String, String> {
+    let meta = &item.meta;
+    let (kind, summary, workspace_id, hash) = match meta.kind {
+        SyncKind::Workflow => {
+            let spec = WorkflowSpec::from_value(item.content)
+                .map_err(|e| format!("Invalid workflow: {e}"))?
+                .spec;
+            if spec.id.to_string() != meta.id {
+                return Err(format!(
+                    "Workflow ID mismatch: expected {}, got {}",
+                    meta.id, spec.id
+                ));
+            }
+            let hash = content_hash(&serde_json::to_value(&spec).map_err(|e| e.to_string())?);
+            let summary = format!("Pulled workflow '{}' from the team server", spec.meta.name);
+            state
+                .workflows
+                .write()
+                .await
+                .insert(meta.id.clone(), spec.clone());
+            state.refresh_thumbnail(&spec).await;
+            (ActivityKind::WorkflowEdited, summary, None, hash)
+        }
+        SyncKind::Policy => {
+            let policy: Policy =
+                serde_json::from_value(item.content).map_err(|e| format!("Invalid policy: {e}"))?;
+            let id = meta.id.parse().map_err(|e: uuid::Error| e.to_string())?;
+            let pm = state.project_manager.read().await;
+            let Ok(mut ws) = pm.get_workspace(id) else {
+                return Ok(String::new());
+            };
+            let hash = content_hash(&serde_json::to_value(&policy).map_err(|e| e.to_string())?);
+            ws.default_policy = Some(policy);
+            ws.updated_at = Utc::now();
+            pm.update_workspace(id, &ws).map_err(|e| e.to_string())?;
+            let summary = format!(
+                "Pulled default policy of '{}' from the team server",
+                ws.name
+            );
+            (ActivityKind::PolicyChanged, summary, Some(id), hash)
+        }
+        SyncKind::Members => {
+            let members: Vec<WorkspaceMember> = serde_json::from_value(item.content)
+                .map_err(|e| format!("Invalid member list: {e}"))?;
+            let id = meta.id.parse().map_err(|e: uuid::Error| e.to_string())?;
+            let pm = state.project_manager.read().await;
+            let Ok(mut ws) = pm.get_workspace(id) else {
+                return Ok(String::new());
+            };
+            let hash = content_hash(&serde_json::to_value(&members).map_err(|e| e.to_string())?);
+            ws.members = members;
+            ws.updated_at = Utc::now();
+            pm.update_workspace(id, &ws).map_err(|e| e.to_string())?;
+            let summary = format!("Pulled members of '{}' from the team server", ws.name);
+            (ActivityKind::MembersChanged, summary, Some(id), hash)
+        }
+        SyncKind::Pack => {
+            let archive = item
+                .content
+                .get("archive")
+                .and_then(|v| v.as_str())
+                .ok_or("Pack content is missing 'archive'")?;
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(archive)
+                .map_err(|e| format!("Invalid pack archive: {e}"))?;
+            let packs_dir = packs_dir(state);
+            let outcome =
+                pack_updater::install_archive(&packs_dir, &meta.id, &bytes[..], Some(&meta.hash))?;
+            let hash = pack_hash(&packs_dir.join(&meta.id))?;
+            let summary = format!(
+                "Pulled pack '{}' {} from the team server",
+                meta.id, outcome.installed_version
+            );
+            (ActivityKind::PackUpdated, summary, None, hash)
+        }
+    };
+
+    let mut entry = ActivityEntry::new(kind, &meta.id, summary)
+        .in_workspace(workspace_id)
+        .with_details(serde_json::json!({ "team_sync_version": meta.version }));
+    entry.actor = meta.updated_by.clone();
+    state.record_activity(entry).await;
+    Ok(hash)
+}
+
+// ---------------------------------------------------------------------------
+// Sync
+// ---------------------------------------------------------------------------
+
+/// A client for the configured server, if sync is on.
+async fn active_client(state: &AppState) -> Result<Client, String> {
+    let config = SyncConfig::load(&state.data_dir);
+    if !config.is_active() {
+        return Err("Team sync is off; working offline".into());
+    }
+    let token = state.llm_credentials.read().await.team_sync_token.clone();
+    Client::new(&config, token.as_deref())
+}
+
+/// Ask the server who `token` belongs to. The answer, not anything the
+/// client claims, is the identity workspace roles are checked against.
+pub async fn verify_identity(config: &SyncConfig, token: Option<&str>) -> Result<String, String> {
+    if token.is_none_or(str::is_empty) {
+        return Err("A team token is required to enable team sync".into());
+    }
+    let user = Client::new(config, token)?
+        .whoami()
+        .await
+        .map_err(|e| format!("Could not verify the team token: {e}"))?;
+    let user = user.trim().to_string();
+    if user.is_empty() {
+        return Err("The team server did not name the token's user".into());
+    }
+    Ok(user)
+}
+
+/// Local sync state: what would be pushed and the last known conflicts.
+pub async fn status(state: &AppState) -> SyncStatus {
+    let config = SyncConfig::load(&state.data_dir);
+    let ledger = SyncLedger::load(&state.data_dir);
+    let pending = collect_local(state)
+        .await
+        .into_iter()
+        .map(|item| (item_key(item.kind, &item.id), item.hash))
+        .filter(|(key, hash)| ledger.items.get(key).is_none_or(|base| &base.hash != hash))
+        .map(|(key, _)| key)
+        .collect();
+    SyncStatus {
+        active: config.is_active(),
+        last_sync: ledger.last_sync,
+        pending,
+        conflicts: ledger.conflicts,
+    }
+}
+
+/// Push local changes, pull remote ones and report conflicts.
+pub async fn sync(state: &AppState) -> Result<SyncReport, String> {
+    let client = active_client(state).await?;
+    let _guard = SYNC_LOCK.lock().await;
+    let mut report = SyncReport {
+        pushed: Vec::new(),
+        pulled: Vec::new(),
+        conflicts: Vec::new(),
+        errors: Vec::new(),
+        offline: false,
+        synced_at: Utc::now(),
+    };
+
+    let remote = match client.list().await {
+        Ok(items) => items,
+        Err(RequestError::Offline(e)) => {
+            report.offline = true;
+            report.errors.push(e);
+            return Ok(report);
+        }
+        Err(e) => return Err(e.to_string()),
+    };
+    let remote: BTreeMap<String, ItemMeta> = remote
+        .into_iter()
+        .map(|meta| (item_key(meta.kind, &meta.id), meta))
+        .collect();
+    let local: BTreeMap<String, LocalItem> = collect_local(state)
+        .await
+        .into_iter()
+        .map(|item| (item_key(item.kind, &item.id), item))
+        .collect();
+    let mut ledger = SyncLedger::load(&state.data_dir);
+    let keys: BTreeSet<&String> = local.keys().chain(remote.keys()).collect();
+
+    for key in keys {
+        let (l, r) = (local.get(key), remote.get(key));
+        let action = plan(l.map(|l| l.hash.as_str()), r, ledger.items.get(key));
+        let result = match (action, l, r) {
+            (Action::Push, Some(l), r) => {
+                let pushed = match local_content(state, l).await {
+                    Ok(content) => client.put(l, content, r.map(|r| r.version)).await,
+                    Err(e) => Err(RequestError::Failed(e)),
+                };
+                pushed.map(|meta| {
+                    report.pushed.push(key.clone());
+                    SyncedVersion {
+                        version: meta.version,
+                        hash: l.hash.clone(),
+                    }
+                })
+            }
+            (Action::Pull, _, Some(r)) => match client.get(r.kind, &r.id).await {
+                Ok(item) => {
+                    let version = item.meta.version;
+                    apply_remote(state, item)
+                        .await
+                        .map(|hash| {
+                            report.pulled.push(key.clone());
+                            SyncedVersion { version, hash }
+                        })
+                        .map_err(RequestError::Failed)
+                }
+                Err(e) => Err(e),
+            },
+            (Action::Record, Some(l), Some(r)) => Ok(SyncedVersion {
+                version: r.version,
+                hash: l.hash.clone(),
+            }),
+            (Action::Conflict, _, Some(_)) => Err(RequestError::Conflict),
+            _ => continue,
+        };
+
+        match result {
+            Ok(synced) => {
+                ledger.items.insert(key.clone(), synced);
+            }
+            Err(RequestError::Conflict) => {
+                // A 409 on push means the item changed since it was listed
+                let (kind, id, name) = match (l, r) {
+                    (Some(l), _) => (l.kind, l.id.clone(), l.name.clone()),
+                    (None, Some(r)) => (r.kind, r.id.clone(), r.name.clone()),
+                    (None, None) => continue,
+                };
+                report.conflicts.push(SyncConflict {
+                    kind,
+                    id,
+                    name,
+                    remote_version: r.map_or(0, |r| r.version),
+                    remote_updated_by: r.and_then(|r| r.updated_by.clone()),
+                });
+            }
+            Err(RequestError::Offline(e)) => {
+                report.offline = true;
+                report.errors.push(e);
+                break;
+            }
+            Err(e) => report.errors.push(format!("{key}: {e}")),
+        }
+    }
+
+    ledger.conflicts = report.conflicts.clone();
+    if !report.offline {
+        ledger.last_sync = Some(report.synced_at);
+    }
+    keep_synced_workflows(state, &ledger).await;
+    ledger.save(&state.data_dir)?;
+    Ok(report)
+}
+
+/// Settle a conflict by keeping the local copy (overwriting the server) or
+/// the server copy (overwriting the local one).
+pub async fn resolve_conflict(
+    state: &AppState,
+    kind: SyncKind,
+    id: &str,
+    keep_local: bool,
+) -> Result<(), String> {
+    let client = active_client(state).await?;
+    let _guard = SYNC_LOCK.lock().await;
+    let key = item_key(kind, id);
+
+    let synced = if keep_local {
+        let item = collect_local(state)
+            .await
+            .into_iter()
+            .find(|item| item.kind == kind && item.id == id)
+            .ok_or_else(|| format!("No local copy of {key}"))?;
+        let base_version = match client.get(kind, id).await {
+            Ok(remote) => Some(remote.meta.version),
+            Err(RequestError::NotFound) => None,
+            Err(e) => return Err(e.to_string()),
+        };
+        let content = local_content(state, &item).await?;
+        let meta = client
+            .put(&item, content, base_version)
+            .await
+            .map_err(|e| e.to_string())?;
+        SyncedVersion {
+            version: meta.version,
+            hash: item.hash,
+        }
+    } else {
+        let remote = client.get(kind, id).await.map_err(|e| e.to_string())?;
+        let version = remote.meta.version;
+        let hash = apply_remote(state, remote).await?;
+        SyncedVersion { version, hash }
+    };
+
+    let mut ledger = SyncLedger::load(&state.data_dir);
+    ledger.items.insert(key, synced);
+    ledger.conflicts.retain(|c| !(c.kind == kind && c.id == id));
+    keep_synced_workflows(state, &ledger).await;
+    ledger.save(&state.data_dir)
+}
+
+fn synced_workflows_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("team_sync").join("workflows")
+}
+
+/// Write the synced workflows in the library to disk.
+async fn keep_synced_workflows(state: &AppState, ledger: &SyncLedger) {
+    let dir = synced_workflows_dir(&state.data_dir);
+    if let Err(e) = fs::create_dir_all(&dir) {
+        tracing::warn!("Failed to keep synced workflows: {e}");
+        return;
+    }
+    let workflows = state.workflows.read().await;
+    let prefix = item_key(SyncKind::Workflow, "");
+    for key in ledger.items.keys() {
+        let Some(spec) = key.strip_prefix(&prefix).and_then(|id| workflows.get(id)) else {
+            continue;
+        };
+        let written = serde_json::to_string_pretty(spec)
+            .map_err(|e| e.to_string())
+            .and_then(|json| fs::write(dir.join(format!("{}.json", spec.id)), json).map_err(|e| e.to_string()));
+        if let Err(e) = written {
+            tracing::warn!("Failed to keep synced workflow {}: {e}", spec.id);
+        }
+    }
+}
+
+/// Put the synced workflows kept on disk back into the library, unless a
+/// workflow with the same ID is already there.
+pub async fn restore_workflows(state: &AppState) {
+    let Ok(entries) = fs::read_dir(synced_workflows_dir(&state.data_dir)) else {
+        return;
+    };
+    let mut workflows = state.workflows.write().await;
+    for path in entries.flatten().map(|e| e.path()) {
+        let spec = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|json| WorkflowSpec::from_json(&json).map_err(|e| e.to_string()));
+        match spec {
+            Ok(upgraded) => {
+                let spec = upgraded.spec;
+                workflows.entry(spec.id.to_string()).or_insert(spec);
+            }
+            Err(e) => tracing::warn!("Skipped synced workflow {}: {e}", path.display()),
+        }
+    }
+}
+
+/// Stop keeping a deleted workflow, so it is not restored at the next start.
+pub fn forget_workflow(data_dir: &Path, id: &str) {
+    let Ok(id) = id.parse::<uuid::Uuid>() else {
+        return;
+    };
+    let path = synced_workflows_dir(data_dir).join(format!("{id}.json"));
+    if let Err(e) = fs::remove_file(&path) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            tracing::warn!("Failed to remove synced workflow {id}: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(version: u64, hash: &str) -> ItemMeta {
+        ItemMeta {
+            kind: SyncKind::Workflow,
+            id: "wf".into(),
+            version,
+            hash: hash.into(),
+            name: String::new(),
+            updated_by: None,
+            updated_at: None,
+        }
+    }
+
+    fn base(version: u64, hash: &str) -> SyncedVersion {
+        SyncedVersion {
+            version,
+            hash: hash.into(),
+        }
+    }
+
+    #[test]
+    fn plan_follows_three_way_comparison() {
+        let synced = base(2, "a");
+        assert_eq!(
+            plan(Some("a"), Some(&meta(2, "a")), Some(&synced)),
+            Action::Skip
+        );
+        assert_eq!(
+            plan(Some("b"), Some(&meta(2, "a")), Some(&synced)),
+            Action::Push
+        );
+        assert_eq!(
+            plan(Some("a"), Some(&meta(3, "c")), Some(&synced)),
+            Action::Pull
+        );
+        assert_eq!(
+            plan(Some("b"), Some(&meta(3, "c")), Some(&synced)),
+            Action::Conflict
+        );
+        assert_eq!(
+            plan(Some("c"), Some(&meta(3, "c")), Some(&synced)),
+            Action::Record
+        );
+        assert_eq!(plan(Some("a"), None, None), Action::Push);
+        assert_eq!(plan(None, Some(&meta(1, "a")), None), Action::Pull);
+        // Deleted here, untouched there: stays deleted
+        assert_eq!(plan(None, Some(&meta(2, "a")), Some(&synced)), Action::Skip);
+        // First sync of an item that exists on both sides
+        assert_eq!(plan(Some("a"), Some(&meta(1, "a")), None), Action::Record);
+        assert_eq!(plan(Some("b"), Some(&meta(1, "a")), None), Action::Conflict);
+    }
+
+    #[test]
+    fn content_hash_ignores_key_order() {
+        let a = serde_json::json!({ "x": 1, "y": { "b": [1, 2], "a": null } });
+        let b = serde_json::json!({ "y": { "a": null, "b": [1, 2] }, "x": 1 });
+        assert_eq!(content_hash(&a), content_hash(&b));
+        assert_ne!(
+            content_hash(&a),
+            content_hash(&serde_json::json!({ "x": 2 }))
+        );
+    }
+
+    #[test]
+    fn old_tokens_move_to_the_credential_store() {
+        let dir = std::env::temp_dir().join(format!("hb-sync-token-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            SyncConfig::path(&dir),
+            r#"{ "server_url": "https://team.example.com", "token": "tk-1", "enabled": true }"#,
+        )
+        .unwrap();
+        let credentials_path = dir.join("llm_credentials.json");
+        let mut credentials = LLMCredentials::default();
+
+        migrate_token(&dir, &mut credentials, &credentials_path);
+        assert_eq!(credentials.team_sync_token.as_deref(), Some("tk-1"));
+        assert_eq!(
+            LLMCredentials::load(&credentials_path).team_sync_token.as_deref(),
+            Some("tk-1")
+        );
+        let settings = fs::read_to_string(SyncConfig::path(&dir)).unwrap();
+        assert!(!settings.contains("tk-1"), "{settings}");
+        assert!(SyncConfig::load(&dir).is_active());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn synced_workflows_survive_a_restart() {
+        let state = crate::state::testing::team_state("kim").await;
+        let spec = WorkflowSpec::default();
+        let id = spec.id.to_string();
+        let unsynced = WorkflowSpec::default();
+        {
+            let mut workflows = state.workflows.write().await;
+            workflows.insert(id.clone(), spec.clone());
+            workflows.insert(unsynced.id.to_string(), unsynced.clone());
+        }
+        let mut ledger = SyncLedger::default();
+        ledger.items.insert(item_key(SyncKind::Workflow, &id), base(1, "a"));
+        keep_synced_workflows(&state, &ledger).await;
+
+        let restarted = AppState::new(state.data_dir.clone());
+        restore_workflows(&restarted).await;
+        let workflows = restarted.workflows.read().await;
+        assert!(workflows.contains_key(&id));
+        assert!(!workflows.contains_key(&unsynced.id.to_string()));
+        drop(workflows);
+
+        // Deleted workflows are not brought back
+        forget_workflow(&state.data_dir, &id);
+        let restarted = AppState::new(state.data_dir.clone());
+        restore_workflows(&restarted).await;
+        assert!(restarted.workflows.read().await.is_empty());
+        let _ = fs::remove_dir_all(&state.data_dir);
+    }
+}
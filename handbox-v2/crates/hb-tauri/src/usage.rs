@@ -0,0 +1,526 @@
+//! Usage reporting — disk space per workspace and LLM tokens per provider,
+//! checked against optional soft quotas.
+//!
+//! Token counts come from the spans of LLM nodes in the trace store, so they
+//! cover what the retention config still keeps. An execution belongs to the
+//! workspace its `ExecutionRun` activity entry was recorded in. Quotas never
+//! block anything; exceeding one only adds a warning to the report.
+
+use hb_core::project::{ActivityKind, WorkspaceConfig};
+use hb_core::trace::TokenUsage;
+use hb_project::ProjectStore;
+use hb_runner::gc::disk_usage;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Report window when none is given.
+pub const DEFAULT_WINDOW_DAYS: u32 = 30;
+
+/// Activity entries fetched per page when attributing executions.
+const ACTIVITY_PAGE: usize = 500;
+
+/// Soft quotas, model prices and rate limits (data_dir/usage.json).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageConfig {
+    #[serde(default)]
+    pub quotas: UsageQuotas,
+    /// Prices used to estimate spend; models without one have no cost.
+    #[serde(default)]
+    pub prices: Vec<ModelPrice>,
+    /// Requests per minute a tool's backing service accepts, by tool ID.
+    /// Only used for optimization hints.
+    #[serde(default)]
+    pub rate_limits: BTreeMap<String, u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageQuotas {
+    /// Disk space a single workspace may use.
+    #[serde(default)]
+    pub max_workspace_bytes: Option<u64>,
+    /// Disk space the whole data directory may use.
+    #[serde(default)]
+    pub max_total_bytes: Option<u64>,
+    /// Tokens per provider (e.g. "openai") within the report window.
+    #[serde(default)]
+    pub max_provider_tokens: BTreeMap<String, u64>,
+    /// Estimated spend across providers within the report window.
+    #[serde(default)]
+    pub max_cost_usd: Option<f64>,
+}
+
+/// Price of a model, in USD per million tokens. `model` matches model IDs
+/// that start with it; the longest match wins.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub model: String,
+    pub input_per_million_usd: f64,
+    pub output_per_million_usd: f64,
+}
+
+impl UsageConfig {
+    pub fn path(data_dir: &Path) -> PathBuf {
+        data_dir.join("usage.json")
+    }
+
+    /// Load the config; missing or invalid files yield the defaults.
+    pub fn load(data_dir: &Path) -> Self {
+        fs::read_to_string(Self::path(data_dir))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, data_dir: &Path) -> Result<(), String> {
+        fs::create_dir_all(data_dir).map_err(|e| e.to_string())?;
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(Self::path(data_dir), content).map_err(|e| e.to_string())
+    }
+
+    fn price_of(&self, model: &str) -> Option<&ModelPrice> {
+        self.prices
+            .iter()
+            .filter(|p| !p.model.is_empty() && model.starts_with(&p.model))
+            .max_by_key(|p| p.model.len())
+    }
+}
+
+/// Disk space used by the whole data directory, in bytes.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DiskUsage {
+    /// SQLite databases (projects, traces, cache).
+    pub databases: u64,
+    /// Per-project artifact directories.
+    pub artifacts: u64,
+    /// Persisted vector indexes.
+    pub indexes: u64,
+    /// Execution journals and collaboration recordings.
+    pub logs: u64,
+    /// Everything in the data directory, including the above.
+    pub total: u64,
+}
+
+/// Disk space attributable to one workspace, in bytes.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WorkspaceDisk {
+    pub artifacts: u64,
+    pub indexes: u64,
+    pub total: u64,
+}
+
+/// Tokens spent on one day.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DailyUsage {
+    pub day: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: Option<f64>,
+}
+
+/// Tokens spent through one provider.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProviderUsage {
+    pub provider: String,
+    pub models: Vec<String>,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub calls: u32,
+    /// Estimated spend on models with a configured price.
+    pub cost_usd: Option<f64>,
+    /// Oldest day first.
+    pub daily: Vec<DailyUsage>,
+}
+
+impl ProviderUsage {
+    pub fn tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceUsage {
+    pub workspace_id: Uuid,
+    pub name: String,
+    pub disk: WorkspaceDisk,
+    pub providers: Vec<ProviderUsage>,
+}
+
+/// A soft quota that was exceeded.
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaWarning {
+    /// What the quota applies to: "app", "workspace:{id}" or "provider:{name}".
+    pub scope: String,
+    pub message: String,
+    pub used: f64,
+    pub limit: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageReport {
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+    /// Start of the token window.
+    pub since: chrono::DateTime<chrono::Utc>,
+    pub window_days: u32,
+    pub disk: DiskUsage,
+    pub workspaces: Vec<WorkspaceUsage>,
+    /// Tokens across all executions, including those outside any workspace.
+    pub providers: Vec<ProviderUsage>,
+    pub warnings: Vec<QuotaWarning>,
+}
+
+/// Provider a model ID belongs to.
+pub fn provider_of(model: &str) -> &'static str {
+    let model = model.to_ascii_lowercase();
+    let bedrock_prefixes = [
+        "anthropic.",
+        "amazon.",
+        "meta.",
+        "mistral.",
+        "cohere.",
+        "ai21.",
+    ];
+    if model.is_empty() {
+        "unknown"
+    } else if bedrock_prefixes.iter().any(|p| {
+        model.starts_with(p)
+            || model
+                .split_once('.')
+                .is_some_and(|(_, rest)| rest.starts_with(p))
+    }) {
+        "bedrock"
+    } else if model.starts_with("claude") {
+        "anthropic"
+    } else if ["gpt-", "o1", "o3", "o4", "text-embedding-"]
+        .iter()
+        .any(|p| model.starts_with(p))
+    {
+        "openai"
+    } else if model.starts_with("gemini") {
+        "google"
+    } else {
+        "local"
+    }
+}
+
+/// Group token usage by provider, with daily totals and estimated cost.
+pub fn summarize<'a>(
+    usage: impl IntoIterator<Item = &'a TokenUsage>,
+    config: &UsageConfig,
+) -> Vec<ProviderUsage> {
+    let mut providers: BTreeMap<&str, ProviderUsage> = BTreeMap::new();
+    let mut days: BTreeMap<(&str, &str), DailyUsage> = BTreeMap::new();
+    for u in usage {
+        let provider = provider_of(&u.model);
+        let cost = config.price_of(&u.model).map(|price| {
+            (u.input_tokens as f64 * price.input_per_million_usd
+                + u.output_tokens as f64 * price.output_per_million_usd)
+                / 1_000_000.0
+        });
+
+        let entry = providers.entry(provider).or_insert_with(|| ProviderUsage {
+            provider: provider.to_string(),
+            ..Default::default()
+        });
+        entry.input_tokens += u.input_tokens;
+        entry.output_tokens += u.output_tokens;
+        entry.calls += u.calls;
+        entry.cost_usd = add_cost(entry.cost_usd, cost);
+        if !entry.models.contains(&u.model) {
+            entry.models.push(u.model.clone());
+        }
+
+        let day = days
+            .entry((provider, &u.day))
+            .or_insert_with(|| DailyUsage {
+                day: u.day.clone(),
+                ..Default::default()
+            });
+        day.input_tokens += u.input_tokens;
+        day.output_tokens += u.output_tokens;
+        day.cost_usd = add_cost(day.cost_usd, cost);
+    }
+
+    for ((provider, _), day) in days {
+        if let Some(entry) = providers.get_mut(provider) {
+            entry.daily.push(day);
+        }
+    }
+    providers
+        .into_values()
+        .map(|mut p| {
+            p.models.sort();
+            p
+        })
+        .collect()
+}
+
+fn add_cost(total: Option<f64>, cost: Option<f64>) -> Option<f64> {
+    match (total, cost) {
+        (None, None) => None,
+        (a, b) => Some(a.unwrap_or(0.0) + b.unwrap_or(0.0)),
+    }
+}
+
+/// Disk usage of the data directory by category.
+pub fn data_dir_usage(data_dir: &Path) -> DiskUsage {
+    let databases = fs::read_dir(data_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| {
+                    let name = e.file_name().to_string_lossy().into_owned();
+                    [".db", ".db-wal", ".db-shm"]
+                        .iter()
+                        .any(|ext| name.ends_with(ext))
+                })
+                .map(|e| disk_usage(&e.path()))
+                .sum()
+        })
+        .unwrap_or(0);
+    DiskUsage {
+        databases,
+        artifacts: disk_usage(&data_dir.join("projects")),
+        indexes: disk_usage(&data_dir.join("vector_store")),
+        logs: disk_usage(&data_dir.join("journal"))
+            + disk_usage(&data_dir.join("collab_recordings")),
+        total: disk_usage(data_dir),
+    }
+}
+
+/// Disk usage of a workspace: its artifact directory and the vector
+/// indexes it configures.
+pub fn workspace_disk(data_dir: &Path, ws: &WorkspaceConfig) -> WorkspaceDisk {
+    let artifacts = disk_usage(&data_dir.join("projects").join(ws.id.to_string()));
+    let indexes = ws
+        .indexes
+        .iter()
+        .map(|index| {
+            let file = crate::commands::vector_store::collection_file_name(&index.name);
+            disk_usage(&data_dir.join("vector_store").join(file))
+        })
+        .sum();
+    WorkspaceDisk {
+        artifacts,
+        indexes,
+        total: artifacts + indexes,
+    }
+}
+
+/// Executions run in a workspace since `since`, from the activity feed.
+pub fn workspace_executions(
+    pm: &dyn ProjectStore,
+    workspace_id: Uuid,
+    since: chrono::DateTime<chrono::Utc>,
+) -> HashSet<Uuid> {
+    let mut executions = HashSet::new();
+    let mut offset = 0;
+    loop {
+        let page = match pm.query_activity(
+            Some(workspace_id),
+            &[ActivityKind::ExecutionRun],
+            offset,
+            ACTIVITY_PAGE,
+        ) {
+            Ok(page) => page,
+            Err(e) => {
+                tracing::warn!("Failed to read activity of workspace {workspace_id}: {e}");
+                break;
+            }
+        };
+        let mut reached_start = false;
+        for entry in &page.entries {
+            if entry.created_at < since {
+                reached_start = true;
+                break;
+            }
+            if let Ok(id) = entry.subject_id.parse() {
+                executions.insert(id);
+            }
+        }
+        if reached_start || !page.has_more {
+            break;
+        }
+        offset += page.entries.len();
+    }
+    executions
+}
+
+/// Warnings for the quotas `report` exceeds.
+pub fn check_quotas(report: &UsageReport, quotas: &UsageQuotas) -> Vec<QuotaWarning> {
+    let mut warnings = Vec::new();
+    let mut warn = |scope: String, message: String, used: f64, limit: f64| {
+        if used > limit {
+            warnings.push(QuotaWarning {
+                scope,
+                message,
+                used,
+                limit,
+            });
+        }
+    };
+
+    if let Some(limit) = quotas.max_total_bytes {
+        warn(
+            "app".into(),
+            format!(
+                "Data directory uses {} of {}",
+                format_bytes(report.disk.total),
+                format_bytes(limit)
+            ),
+            report.disk.total as f64,
+            limit as f64,
+        );
+    }
+    if let Some(limit) = quotas.max_workspace_bytes {
+        for ws in &report.workspaces {
+            warn(
+                format!("workspace:{}", ws.workspace_id),
+                format!(
+                    "Workspace '{}' uses {} of {}",
+                    ws.name,
+                    format_bytes(ws.disk.total),
+                    format_bytes(limit)
+                ),
+                ws.disk.total as f64,
+                limit as f64,
+            );
+        }
+    }
+    for provider in &report.providers {
+        if let Some(&limit) = quotas.max_provider_tokens.get(&provider.provider) {
+            warn(
+                format!("provider:{}", provider.provider),
+                format!(
+                    "{} used {} of {limit} tokens in the last {} days",
+                    provider.provider,
+                    provider.tokens(),
+                    report.window_days
+                ),
+                provider.tokens() as f64,
+                limit as f64,
+            );
+        }
+    }
+    if let Some(limit) = quotas.max_cost_usd {
+        let spent: f64 = report.providers.iter().filter_map(|p| p.cost_usd).sum();
+        warn(
+            "app".into(),
+            format!(
+                "Estimated spend ${spent:.2} exceeds ${limit:.2} in the last {} days",
+                report.window_days
+            ),
+            spent,
+            limit,
+        );
+    }
+    warnings
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(model: &str, day: &str, input: u64, output: u64) -> TokenUsage {
+        TokenUsage {
+            execution_id: Uuid::new_v4(),
+            model: model.into(),
+            day: day.into(),
+            input_tokens: input,
+            output_tokens: output,
+            calls: 1,
+        }
+    }
+
+    #[test]
+    fn summarizes_per_provider_and_flags_quotas() {
+        let config = UsageConfig {
+            quotas: UsageQuotas {
+                max_provider_tokens: [("openai".to_string(), 1_000)].into(),
+                max_cost_usd: Some(1.0),
+                ..Default::default()
+            },
+            prices: vec![
+                ModelPrice {
+                    model: "gpt-4o".into(),
+                    input_per_million_usd: 1000.0,
+                    output_per_million_usd: 1000.0,
+                },
+                ModelPrice {
+                    model: "gpt-4o-mini".into(),
+                    input_per_million_usd: 0.0,
+                    output_per_million_usd: 0.0,
+                },
+            ],
+            ..Default::default()
+        };
+        let rows = [
+            usage("gpt-4o", "2026-10-01", 600, 100),
+            usage("gpt-4o-mini", "2026-10-01", 300, 100),
+            usage("gpt-4o", "2026-10-02", 100, 0),
+            usage("llama3", "2026-10-02", 50, 50),
+        ];
+        let providers = summarize(&rows, &config);
+        assert_eq!(providers.len(), 2);
+
+        let local = &providers[0];
+        assert_eq!(
+            (local.provider.as_str(), local.tokens(), local.cost_usd),
+            ("local", 100, None)
+        );
+
+        let openai = &providers[1];
+        assert_eq!(openai.tokens(), 1_200);
+        assert_eq!(openai.calls, 3);
+        assert_eq!(openai.models, ["gpt-4o", "gpt-4o-mini"]);
+        assert!((openai.cost_usd.unwrap() - 0.8).abs() < 1e-9);
+        assert_eq!(openai.daily.len(), 2);
+        assert_eq!(openai.daily[0].input_tokens, 900);
+
+        let report = UsageReport {
+            generated_at: chrono::Utc::now(),
+            since: chrono::Utc::now(),
+            window_days: DEFAULT_WINDOW_DAYS,
+            disk: DiskUsage::default(),
+            workspaces: vec![],
+            providers,
+            warnings: vec![],
+        };
+        let warnings = check_quotas(&report, &config.quotas);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].scope, "provider:openai");
+    }
+
+    #[test]
+    fn maps_models_to_providers() {
+        assert_eq!(provider_of("claude-3-haiku-20240307"), "anthropic");
+        assert_eq!(
+            provider_of("anthropic.claude-3-sonnet-20240229-v1:0"),
+            "bedrock"
+        );
+        assert_eq!(
+            provider_of("us.anthropic.claude-3-5-sonnet-20241022-v2:0"),
+            "bedrock"
+        );
+        assert_eq!(provider_of("gpt-4o-mini"), "openai");
+        assert_eq!(provider_of("llama3.1:8b"), "local");
+        assert_eq!(provider_of(""), "unknown");
+    }
+}
@@ -0,0 +1,319 @@
+//! Outbound webhooks — signed POSTs of workspace events to external systems
+//! (ticketing, chat-ops), retried with backoff.
+//!
+//! Each endpoint picks the [`NotificationKind`]s it receives. The body is the
+//! notification as JSON plus a delivery ID; when the endpoint names a secret,
+//! `X-Handbox-Signature` carries `sha256=<hex>`, the HMAC-SHA256 of
+//! `"{timestamp}.{body}"` with `timestamp` taken from `X-Handbox-Timestamp`,
+//! so receivers can reject forged and replayed requests. Redirects are not
+//! followed, so signed bodies only reach the configured URL, and a
+//! `Retry-After` answer sets the wait before the next attempt.
+
+use crate::notifications::{Notification, NotificationKind};
+use crate::state::LLMCredentials;
+use chrono::{DateTime, Utc};
+use hb_core::graph::RetryPolicy;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::VecDeque;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// Deliveries kept for [`WebhookDispatcher::recent`].
+const RECENT_DELIVERIES: usize = 100;
+
+/// Longest `Retry-After` wait honoured.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(600);
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookEndpoint {
+    #[serde(default = "new_id")]
+    pub id: String,
+    pub url: String,
+    /// Event kinds delivered to this endpoint.
+    pub events: Vec<NotificationKind>,
+    /// Name of the signing secret among the credential store's service
+    /// keys, so it never lands in the config file. Requests are unsigned
+    /// when unset, and not sent while the named secret is missing.
+    #[serde(default, alias = "secret_env")]
+    pub secret_name: Option<String>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Attempts after the first failure, and the backoff between them.
+    #[serde(default)]
+    pub retry: RetryPolicy,
+}
+
+fn new_id() -> String {
+    Uuid::new_v4().to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl WebhookEndpoint {
+    pub fn wants(&self, kind: NotificationKind) -> bool {
+        self.enabled && self.events.contains(&kind)
+    }
+
+    /// The signing secret, if the endpoint names one.
+    pub fn secret(&self, credentials: &LLMCredentials) -> Result<Option<String>, String> {
+        let Some(name) = &self.secret_name else {
+            return Ok(None);
+        };
+        match credentials.service_keys.get(name) {
+            Some(secret) if !secret.is_empty() => Ok(Some(secret.clone())),
+            _ => Err(format!("signing secret '{name}' is not in the credential store")),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Payload<'a> {
+    delivery_id: Uuid,
+    #[serde(flatten)]
+    notification: &'a Notification,
+}
+
+/// Outcome of one delivery, after its retries.
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookDelivery {
+    pub delivery_id: Uuid,
+    pub endpoint_id: String,
+    pub workspace_id: Option<String>,
+    pub kind: NotificationKind,
+    pub attempts: u32,
+    /// HTTP status of the last attempt, if one got a response.
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+/// `sha256=<hex>` signature of `body` sent at `timestamp`.
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(format!("{timestamp}.{body}").as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Whether a response status is worth another attempt: server errors, rate
+/// limits and timeouts. Other client errors will not go away on their own.
+fn retryable(status: reqwest::StatusCode) -> bool {
+    status.is_server_error()
+        || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        || status == reqwest::StatusCode::REQUEST_TIMEOUT
+}
+
+/// Wait asked for by a `Retry-After` header, in seconds or as an HTTP date,
+/// at most [`MAX_RETRY_AFTER`].
+fn retry_after(headers: &reqwest::header::HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?.trim();
+    let wait = match value.parse::<u64>() {
+        Ok(secs) => Duration::from_secs(secs),
+        Err(_) => {
+            let at = DateTime::parse_from_rfc2822(value).ok()?;
+            (at.with_timezone(&Utc) - now).to_std().unwrap_or_default()
+        }
+    };
+    Some(wait.min(MAX_RETRY_AFTER))
+}
+
+pub struct WebhookDispatcher {
+    http: reqwest::Client,
+    recent: Mutex<VecDeque<WebhookDelivery>>,
+}
+
+impl WebhookDispatcher {
+    pub fn new(timeout: Duration) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(timeout)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap_or_default();
+        Self {
+            http,
+            recent: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Latest deliveries, newest first, optionally of one workspace.
+    pub async fn recent(&self, workspace_id: Option<&str>) -> Vec<WebhookDelivery> {
+        self.recent
+            .lock()
+            .await
+            .iter()
+            .rev()
+            .filter(|d| workspace_id.is_none() || d.workspace_id.as_deref() == workspace_id)
+            .cloned()
+            .collect()
+    }
+
+    /// POST `notification` to `endpoint`, signed with `secret` (see
+    /// [`WebhookEndpoint::secret`]) and retrying per its policy. Nothing is
+    /// sent when the endpoint's secret is missing.
+    pub async fn deliver(
+        &self,
+        endpoint: &WebhookEndpoint,
+        secret: Result<Option<String>, String>,
+        notification: &Notification,
+    ) -> WebhookDelivery {
+        let delivery_id = Uuid::new_v4();
+        let body = serde_json::to_string(&Payload {
+            delivery_id,
+            notification,
+        })
+        .unwrap_or_default();
+        let mut delivery = WebhookDelivery {
+            delivery_id,
+            endpoint_id: endpoint.id.clone(),
+            workspace_id: notification.workspace_id.clone(),
+            kind: notification.kind,
+            attempts: 0,
+            status: None,
+            error: None,
+            delivered_at: None,
+        };
+        let secret = match secret {
+            Ok(secret) => secret,
+            Err(e) => {
+                delivery.error = Some(e);
+                return self.finish(endpoint, delivery).await;
+            }
+        };
+
+        loop {
+            delivery.attempts += 1;
+            let timestamp = Utc::now().timestamp();
+            let mut request = self
+                .http
+                .post(&endpoint.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header("X-Handbox-Event", event_name(notification.kind))
+                .header("X-Handbox-Delivery", delivery_id.to_string())
+                .header("X-Handbox-Timestamp", timestamp.to_string());
+            if let Some(secret) = &secret {
+                request = request.header("X-Handbox-Signature", sign(secret, timestamp, &body));
+            }
+            let mut wait = None;
+            let retry = match request.body(body.clone()).send().await {
+                Ok(response) if response.status().is_success() => {
+                    delivery.status = Some(response.status().as_u16());
+                    delivery.error = None;
+                    delivery.delivered_at = Some(Utc::now());
+                    break;
+                }
+                Ok(response) => {
+                    delivery.status = Some(response.status().as_u16());
+                    delivery.error = Some(format!("endpoint answered {}", response.status()));
+                    wait = retry_after(response.headers(), Utc::now());
+                    retryable(response.status())
+                }
+                Err(e) => {
+                    delivery.status = None;
+                    delivery.error = Some(e.to_string());
+                    true
+                }
+            };
+            let retries = delivery.attempts - 1;
+            if !retry || !hb_runner::retry::should_retry(&endpoint.retry, retries) {
+                break;
+            }
+            let wait = wait.unwrap_or_else(|| hb_runner::retry::compute_delay(&endpoint.retry, retries));
+            tokio::time::sleep(wait).await;
+        }
+        self.finish(endpoint, delivery).await
+    }
+
+    /// Log a failed delivery and keep it for [`Self::recent`].
+    async fn finish(&self, endpoint: &WebhookEndpoint, delivery: WebhookDelivery) -> WebhookDelivery {
+        if let Some(error) = &delivery.error {
+            tracing::warn!(
+                "Webhook {} failed after {} attempt(s): {error}",
+                endpoint.url,
+                delivery.attempts
+            );
+        }
+        let mut recent = self.recent.lock().await;
+        if recent.len() == RECENT_DELIVERIES {
+            recent.pop_front();
+        }
+        recent.push_back(delivery.clone());
+        delivery
+    }
+}
+
+/// `X-Handbox-Event` value, e.g. `execution_completed`.
+fn event_name(kind: NotificationKind) -> String {
+    serde_json::to_value(kind)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signatures_cover_timestamp_and_body() {
+        let body = r#"{"kind":"execution_failed"}"#;
+        let signature = sign("s3cret", 1_700_000_000, body);
+        assert!(signature.starts_with("sha256="));
+        assert_eq!(signature.len(), "sha256=".len() + 64);
+        assert_eq!(signature, sign("s3cret", 1_700_000_000, body));
+        assert_ne!(signature, sign("s3cret", 1_700_000_001, body));
+        assert_ne!(signature, sign("other", 1_700_000_000, body));
+
+        assert!(retryable(reqwest::StatusCode::BAD_GATEWAY));
+        assert!(retryable(reqwest::StatusCode::TOO_MANY_REQUESTS));
+        assert!(!retryable(reqwest::StatusCode::NOT_FOUND));
+        assert_eq!(
+            event_name(NotificationKind::ScheduleFailed),
+            "schedule_failed"
+        );
+    }
+
+    #[test]
+    fn retry_after_takes_seconds_or_a_date() {
+        let now: DateTime<Utc> = "2026-03-02T09:00:00Z".parse().unwrap();
+        let headers = |value: &str| {
+            let mut headers = reqwest::header::HeaderMap::new();
+            headers.insert(reqwest::header::RETRY_AFTER, value.parse().unwrap());
+            headers
+        };
+        assert_eq!(retry_after(&headers("30"), now), Some(Duration::from_secs(30)));
+        assert_eq!(
+            retry_after(&headers("Mon, 02 Mar 2026 09:02:00 GMT"), now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(retry_after(&headers("86400"), now), Some(MAX_RETRY_AFTER));
+        assert_eq!(retry_after(&headers("soon"), now), None);
+        assert_eq!(retry_after(&Default::default(), now), None);
+    }
+
+    #[test]
+    fn named_secrets_come_from_the_credential_store() {
+        let mut endpoint: WebhookEndpoint = serde_json::from_value(serde_json::json!({
+            "url": "https://hooks.example.com/handbox",
+            "events": ["schedule_failed"],
+            "secret_env": "TICKETS_WEBHOOK_SECRET",
+        }))
+        .unwrap();
+        let mut credentials = LLMCredentials::default();
+        assert!(endpoint.secret(&credentials).is_err(), "missing secrets refuse delivery");
+
+        credentials
+            .service_keys
+            .insert("TICKETS_WEBHOOK_SECRET".into(), "s3cret".into());
+        assert_eq!(endpoint.secret(&credentials).unwrap().as_deref(), Some("s3cret"));
+
+        endpoint.secret_name = None;
+        assert_eq!(endpoint.secret(&credentials).unwrap(), None);
+    }
+}
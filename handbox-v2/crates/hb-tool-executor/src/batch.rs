@@ -0,0 +1,235 @@
+//! Request batching for model backends.
+//!
+//! A [`Batcher`] coalesces items submitted concurrently — e.g. embedding
+//! requests from parallel nodes — into one backend call. A batch is sent when
+//! it reaches `max_batch` items, when the next item would take it past
+//! `max_weight` (e.g. the provider's tokens per request), or `max_wait` after
+//! its first item arrived, so a lone request is delayed by at most the
+//! latency window. When a batch fails, its items are retried one at a time,
+//! so one bad input fails only the node that sent it.
+//!
+//! Only embeddings are batched. Chat completions are sent one per node; an
+//! OpenAI-compatible API takes one conversation per request.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, Semaphore};
+
+/// Batch sizing and latency window.
+#[derive(Debug, Clone, Copy)]
+pub struct BatchConfig {
+    pub max_batch: usize,
+    /// Total weight of a batch, as measured by the batcher's `weigh`. An
+    /// item heavier than this on its own is sent alone.
+    pub max_weight: usize,
+    pub max_wait: Duration,
+    /// Batches sent to the backend at once.
+    pub max_in_flight: usize,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            max_batch: 32,
+            max_weight: usize::MAX,
+            max_wait: Duration::from_millis(20),
+            max_in_flight: 2,
+        }
+    }
+}
+
+type Reply<O> = oneshot::Sender<Result<O, String>>;
+
+/// Handle for submitting items to a batching worker. Cheap to clone.
+pub struct Batcher<I, O> {
+    tx: mpsc::UnboundedSender<(I, Reply<O>)>,
+}
+
+impl<I, O> Clone for Batcher<I, O> {
+    fn clone(&self) -> Self {
+        Self { tx: self.tx.clone() }
+    }
+}
+
+impl<I: Clone + Send + 'static, O: Send + 'static> Batcher<I, O> {
+    /// Start a worker on the current Tokio runtime. `weigh` measures an item
+    /// against `max_weight`; `handler` must return one output per input, in
+    /// order.
+    pub fn new<W, F, Fut>(config: BatchConfig, weigh: W, handler: F) -> Self
+    where
+        W: Fn(&I) -> usize + Send + 'static,
+        F: Fn(Vec<I>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Vec<O>, String>> + Send + 'static,
+    {
+        let (tx, mut rx) = mpsc::unbounded_channel::<(I, Reply<O>)>();
+        let handler = Arc::new(handler);
+        let slots = Arc::new(Semaphore::new(config.max_in_flight.max(1)));
+        tokio::spawn(async move {
+            // An item that did not fit the previous batch starts the next one
+            let mut carried = None;
+            loop {
+                let first = match carried.take() {
+                    Some(item) => item,
+                    None => match rx.recv().await {
+                        Some(item) => item,
+                        None => break,
+                    },
+                };
+                // Wait for a free slot before collecting, so items queued
+                // meanwhile join this batch instead of waiting for the next
+                let Ok(permit) = slots.clone().acquire_owned().await else {
+                    break;
+                };
+                let mut weight = weigh(&first.0);
+                let mut batch = vec![first];
+                let deadline = tokio::time::Instant::now() + config.max_wait;
+                while batch.len() < config.max_batch {
+                    match tokio::time::timeout_at(deadline, rx.recv()).await {
+                        Ok(Some(item)) => {
+                            let item_weight = weigh(&item.0);
+                            if weight.saturating_add(item_weight) > config.max_weight {
+                                carried = Some(item);
+                                break;
+                            }
+                            weight += item_weight;
+                            batch.push(item);
+                        }
+                        Ok(None) | Err(_) => break,
+                    }
+                }
+
+                let handler = handler.clone();
+                tokio::spawn(async move {
+                    let _permit = permit;
+                    let (items, replies): (Vec<I>, Vec<Reply<O>>) = batch.into_iter().unzip();
+                    let count = items.len();
+                    tracing::debug!("Sending batch of {count}");
+                    let error = match handler(items.clone()).await {
+                        Ok(outputs) if outputs.len() == count => {
+                            for (reply, output) in replies.into_iter().zip(outputs) {
+                                let _ = reply.send(Ok(output));
+                            }
+                            return;
+                        }
+                        Ok(outputs) => format!("backend returned {} results for {count} inputs", outputs.len()),
+                        Err(error) => error,
+                    };
+                    if count == 1 {
+                        for reply in replies {
+                            let _ = reply.send(Err(error.clone()));
+                        }
+                        return;
+                    }
+                    tracing::warn!("Batch of {count} failed ({error}); retrying its items one at a time");
+                    for (item, reply) in items.into_iter().zip(replies) {
+                        let result = match handler(vec![item]).await {
+                            Ok(mut outputs) if outputs.len() == 1 => Ok(outputs.remove(0)),
+                            Ok(outputs) => Err(format!("backend returned {} results for 1 input", outputs.len())),
+                            Err(error) => Err(error),
+                        };
+                        let _ = reply.send(result);
+                    }
+                });
+            }
+        });
+        Self { tx }
+    }
+
+    /// Queue an item and wait for its result.
+    pub async fn submit(&self, item: I) -> Result<O, String> {
+        let (reply, result) = oneshot::channel();
+        self.tx
+            .send((item, reply))
+            .map_err(|_| "batch worker stopped".to_string())?;
+        result.await.map_err(|_| "batch worker stopped".to_string())?
+    }
+
+    /// Whether the worker has shut down (e.g. its runtime ended).
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn coalesces_concurrent_submissions() {
+        let sizes = Arc::new(Mutex::new(Vec::new()));
+        let seen = sizes.clone();
+        let batcher = Batcher::new(
+            BatchConfig {
+                max_batch: 8,
+                max_wait: Duration::from_millis(50),
+                max_in_flight: 1,
+                ..Default::default()
+            },
+            |_: &u32| 1,
+            move |items: Vec<u32>| {
+                seen.lock().unwrap().push(items.len());
+                async move { Ok(items.into_iter().map(|i| i * 2).collect()) }
+            },
+        );
+
+        let mut tasks = tokio::task::JoinSet::new();
+        for i in 0..20u32 {
+            let batcher = batcher.clone();
+            tasks.spawn(async move { (i, batcher.submit(i).await) });
+        }
+        while let Some(joined) = tasks.join_next().await {
+            let (i, result) = joined.unwrap();
+            assert_eq!(result.unwrap(), i * 2);
+        }
+
+        let sizes = sizes.lock().unwrap();
+        assert_eq!(sizes.iter().sum::<usize>(), 20);
+        assert!(sizes.len() <= 4, "batches: {sizes:?}");
+        assert!(sizes.iter().all(|&n| n <= 8));
+    }
+
+    #[tokio::test]
+    async fn caps_batches_by_weight_and_retries_failed_batches_per_item() {
+        let batches = Arc::new(Mutex::new(Vec::new()));
+        let seen = batches.clone();
+        let batcher = Batcher::new(
+            BatchConfig {
+                max_batch: 8,
+                max_weight: 10,
+                max_wait: Duration::from_millis(50),
+                max_in_flight: 1,
+            },
+            |&i: &u32| i as usize,
+            // 3 is an input the backend rejects, failing its whole batch
+            move |items: Vec<u32>| {
+                seen.lock().unwrap().push(items.clone());
+                async move {
+                    if items.contains(&3) {
+                        return Err("bad input".to_string());
+                    }
+                    Ok(items)
+                }
+            },
+        );
+
+        let mut tasks = tokio::task::JoinSet::new();
+        for i in [4u32, 4, 4, 3, 1] {
+            let batcher = batcher.clone();
+            tasks.spawn(async move { (i, batcher.submit(i).await) });
+            tokio::task::yield_now().await;
+        }
+        while let Some(joined) = tasks.join_next().await {
+            let (i, result) = joined.unwrap();
+            if i == 3 {
+                assert_eq!(result.unwrap_err(), "bad input");
+            } else {
+                assert_eq!(result.unwrap(), i);
+            }
+        }
+
+        // The third 4 would have made 12, so it starts the next batch
+        assert_eq!(*batches.lock().unwrap(), [vec![4, 4], vec![4, 3, 1], vec![4], vec![3], vec![1]]);
+    }
+}
@@ -0,0 +1,376 @@
+//! Bedrock model routing — maps a requested model id onto something the
+//! account can invoke (a base model, a cross-region inference profile such
+//! as `us.anthropic...`, or an ARN) and picks the regions to try.
+//!
+//! Without a discovered [`BedrockCatalog`], a built-in list of the models
+//! that still accept on-demand invocation decides; every other model goes
+//! through the inference profile of the region's geography.
+
+use serde::Serialize;
+
+/// Geography prefixes of system-defined inference profiles.
+const PROFILE_GEOS: [&str; 5] = ["us", "eu", "apac", "us-gov", "global"];
+
+/// Regions tried for base model ids when the configured one fails.
+const FALLBACK_REGIONS: [&str; 2] = ["us-east-1", "us-west-2"];
+
+/// Anthropic models that still accept on-demand invocation by base id.
+/// Everything newer is only reachable through an inference profile.
+const ON_DEMAND_CLAUDE: [&str; 6] = [
+    "claude-instant",
+    "claude-v2",
+    "claude-3-haiku",
+    "claude-3-sonnet",
+    "claude-3-opus",
+    "claude-3-5-sonnet-20240620",
+];
+
+/// Where and as what a model is invoked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelTarget {
+    /// Id placed in the `/model/{id}/invoke` path.
+    pub invoke_id: String,
+    /// Regions to try, in order.
+    pub regions: Vec<String>,
+    /// Inference profile to retry with if Bedrock rejects on-demand
+    /// invocation of `invoke_id`.
+    pub profile_fallback: Option<String>,
+}
+
+/// A model available to the account in one region.
+#[derive(Debug, Clone, Serialize)]
+pub struct BedrockModel {
+    pub model_id: String,
+    pub name: String,
+    pub provider_name: String,
+    /// Whether the base id can be invoked directly.
+    pub on_demand: bool,
+    pub supports_vision: bool,
+    /// System-defined inference profiles routing to this model.
+    pub inference_profiles: Vec<String>,
+}
+
+impl BedrockModel {
+    /// The id to invoke this model with from `region`.
+    pub fn invoke_id(&self, region: &str) -> String {
+        if self.on_demand {
+            return self.model_id.clone();
+        }
+        pick_profile(&self.inference_profiles, region)
+            .unwrap_or_else(|| profile_id(&self.model_id, region))
+    }
+}
+
+/// Models discovered for one account and region.
+#[derive(Debug, Clone)]
+pub struct BedrockCatalog {
+    pub models: Vec<BedrockModel>,
+}
+
+impl BedrockCatalog {
+    pub fn get(&self, model_id: &str) -> Option<&BedrockModel> {
+        self.models.iter().find(|m| m.model_id == model_id)
+    }
+}
+
+/// Resolve a requested model id for invocation from `region`.
+pub fn resolve(raw_id: &str, region: &str, catalog: Option<&BedrockCatalog>) -> ModelTarget {
+    let raw_id = raw_id.trim();
+
+    // ARNs (application profiles, provisioned throughput) carry their region
+    if raw_id.starts_with("arn:") {
+        let arn_region = raw_id.split(':').nth(3).filter(|r| !r.is_empty());
+        return ModelTarget {
+            invoke_id: raw_id.to_string(),
+            regions: vec![arn_region.unwrap_or(region).to_string()],
+            profile_fallback: None,
+        };
+    }
+
+    if let Some((geo, _)) = split_profile(raw_id) {
+        return ModelTarget {
+            invoke_id: raw_id.to_string(),
+            regions: profile_regions(geo, region),
+            profile_fallback: None,
+        };
+    }
+
+    let base = to_base_model_id(raw_id);
+    let known = catalog.and_then(|c| c.get(&base));
+    let on_demand = match known {
+        Some(model) => model.on_demand,
+        None => is_on_demand(&base),
+    };
+    if !on_demand {
+        let invoke_id = match known {
+            Some(model) => model.invoke_id(region),
+            None => profile_id(&base, region),
+        };
+        let geo = split_profile(&invoke_id).map_or("us", |(geo, _)| geo);
+        return ModelTarget {
+            regions: profile_regions(geo, region),
+            invoke_id,
+            profile_fallback: None,
+        };
+    }
+
+    let mut regions = vec![region.to_string()];
+    for r in FALLBACK_REGIONS {
+        if !regions.iter().any(|existing| existing == r) {
+            regions.push(r.to_string());
+        }
+    }
+    ModelTarget {
+        profile_fallback: Some(profile_id(&base, region)),
+        invoke_id: base,
+        regions,
+    }
+}
+
+/// Whether an error from Bedrock asks for an inference profile instead of
+/// the base model id.
+pub fn needs_profile(error: &str) -> bool {
+    let lower = error.to_lowercase();
+    lower.contains("on-demand throughput") || lower.contains("inference profile")
+}
+
+/// Strip a geography prefix and turn Anthropic API names such as
+/// `claude-sonnet-4-20250514` into Bedrock ids.
+pub fn to_base_model_id(raw_id: &str) -> String {
+    let id = split_profile(raw_id).map_or(raw_id, |(_, base)| base);
+    if id.starts_with("claude-") {
+        let versioned = if id.contains(':') {
+            id.to_string()
+        } else {
+            format!("{id}-v1:0")
+        };
+        format!("anthropic.{versioned}")
+    } else {
+        id.to_string()
+    }
+}
+
+/// Geography of the inference profiles usable from `region`.
+pub fn geo_for_region(region: &str) -> Option<&'static str> {
+    if region.starts_with("us-gov-") {
+        Some("us-gov")
+    } else if region.starts_with("us-") || region.starts_with("ca-") {
+        Some("us")
+    } else if region.starts_with("eu-") {
+        Some("eu")
+    } else if region.starts_with("ap-") {
+        Some("apac")
+    } else {
+        None
+    }
+}
+
+fn split_profile(id: &str) -> Option<(&str, &str)> {
+    PROFILE_GEOS.iter().find_map(|geo| {
+        id.strip_prefix(geo)
+            .and_then(|rest| rest.strip_prefix('.'))
+            .map(|base| (*geo, base))
+    })
+}
+
+fn profile_id(base: &str, region: &str) -> String {
+    format!("{}.{base}", geo_for_region(region).unwrap_or("us"))
+}
+
+/// Prefer a profile for the region's geography, then a global one.
+fn pick_profile(profiles: &[String], region: &str) -> Option<String> {
+    let geo = geo_for_region(region);
+    profiles
+        .iter()
+        .find(|p| split_profile(p).map(|(g, _)| g) == geo)
+        .or_else(|| profiles.iter().find(|p| p.starts_with("global.")))
+        .cloned()
+}
+
+/// A profile is invoked from a source region inside its geography; the
+/// configured region is used when it qualifies.
+fn profile_regions(geo: &str, region: &str) -> Vec<String> {
+    let defaults: &[&str] = match geo {
+        "us" => &["us-east-1", "us-west-2"],
+        "eu" => &["eu-central-1", "eu-west-1"],
+        "apac" => &["ap-northeast-1", "ap-southeast-1"],
+        "us-gov" => &["us-gov-west-1"],
+        _ => &[],
+    };
+    let mut regions = Vec::new();
+    if geo == "global" || geo_for_region(region) == Some(geo) || defaults.is_empty() {
+        regions.push(region.to_string());
+    }
+    for r in defaults {
+        if !regions.iter().any(|existing| existing == r) {
+            regions.push(r.to_string());
+        }
+    }
+    regions
+}
+
+fn is_on_demand(base: &str) -> bool {
+    match base.strip_prefix("anthropic.") {
+        Some(model) => ON_DEMAND_CLAUDE
+            .iter()
+            .any(|prefix| model.starts_with(prefix)),
+        None => true,
+    }
+}
+
+/// Build a catalog from the control plane's `ListFoundationModels` and
+/// `ListInferenceProfiles` responses.
+pub fn parse_catalog(models: &serde_json::Value, profiles: &serde_json::Value) -> BedrockCatalog {
+    let strs = |value: &serde_json::Value| -> Vec<String> {
+        value
+            .as_array()
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|v| v.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let mut catalog = BedrockCatalog { models: Vec::new() };
+    for summary in models["modelSummaries"].as_array().into_iter().flatten() {
+        let Some(model_id) = summary["modelId"].as_str() else {
+            continue;
+        };
+        if summary["modelLifecycle"]["status"].as_str() == Some("LEGACY") {
+            continue;
+        }
+        let inference_types = strs(&summary["inferenceTypesSupported"]);
+        if inference_types.is_empty() {
+            continue;
+        }
+        catalog.models.push(BedrockModel {
+            model_id: model_id.to_string(),
+            name: summary["modelName"]
+                .as_str()
+                .unwrap_or(model_id)
+                .to_string(),
+            provider_name: summary["providerName"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            on_demand: inference_types.iter().any(|t| t == "ON_DEMAND"),
+            supports_vision: strs(&summary["inputModalities"])
+                .iter()
+                .any(|m| m == "IMAGE"),
+            inference_profiles: Vec::new(),
+        });
+    }
+
+    for profile in profiles["inferenceProfileSummaries"]
+        .as_array()
+        .into_iter()
+        .flatten()
+    {
+        let Some(profile_id) = profile["inferenceProfileId"].as_str() else {
+            continue;
+        };
+        if profile["status"].as_str().is_some_and(|s| s != "ACTIVE") {
+            continue;
+        }
+        let targets = profile["models"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|m| {
+                m["modelArn"]
+                    .as_str()
+                    .and_then(|arn| arn.rsplit_once("foundation-model/"))
+                    .map(|(_, id)| id)
+            });
+        for target in targets {
+            if let Some(model) = catalog.models.iter_mut().find(|m| m.model_id == target) {
+                if !model.inference_profiles.iter().any(|p| p == profile_id) {
+                    model.inference_profiles.push(profile_id.to_string());
+                }
+            }
+        }
+    }
+
+    // A profile-only model without a profile in this region can't be invoked
+    catalog
+        .models
+        .retain(|m| m.on_demand || !m.inference_profiles.is_empty());
+    catalog
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes_newer_models_through_regional_profiles() {
+        let target = resolve("claude-sonnet-4-20250514", "ap-northeast-2", None);
+        assert_eq!(
+            target.invoke_id,
+            "apac.anthropic.claude-sonnet-4-20250514-v1:0"
+        );
+        assert_eq!(target.regions[0], "ap-northeast-2");
+
+        // An explicit profile from another geography moves to its home region
+        let target = resolve(
+            "us.anthropic.claude-opus-4-20250514-v1:0",
+            "eu-west-1",
+            None,
+        );
+        assert_eq!(target.invoke_id, "us.anthropic.claude-opus-4-20250514-v1:0");
+        assert_eq!(target.regions, ["us-east-1", "us-west-2"]);
+
+        let target = resolve("anthropic.claude-3-haiku-20240307-v1:0", "eu-west-1", None);
+        assert_eq!(target.invoke_id, "anthropic.claude-3-haiku-20240307-v1:0");
+        assert_eq!(target.regions, ["eu-west-1", "us-east-1", "us-west-2"]);
+        assert_eq!(
+            target.profile_fallback.as_deref(),
+            Some("eu.anthropic.claude-3-haiku-20240307-v1:0")
+        );
+    }
+
+    #[test]
+    fn uses_discovered_availability() {
+        let models = serde_json::json!({ "modelSummaries": [
+            {
+                "modelId": "anthropic.claude-3-haiku-20240307-v1:0",
+                "modelName": "Claude 3 Haiku",
+                "providerName": "Anthropic",
+                "inputModalities": ["TEXT", "IMAGE"],
+                "inferenceTypesSupported": ["INFERENCE_PROFILE"],
+            },
+            {
+                "modelId": "amazon.nova-pro-v1:0",
+                "modelName": "Nova Pro",
+                "providerName": "Amazon",
+                "inputModalities": ["TEXT"],
+                "inferenceTypesSupported": ["ON_DEMAND", "INFERENCE_PROFILE"],
+            },
+            {
+                "modelId": "anthropic.claude-unlisted-v1:0",
+                "inferenceTypesSupported": ["INFERENCE_PROFILE"],
+            },
+        ]});
+        let profiles = serde_json::json!({ "inferenceProfileSummaries": [{
+            "inferenceProfileId": "global.anthropic.claude-3-haiku-20240307-v1:0",
+            "status": "ACTIVE",
+            "models": [{ "modelArn": "arn:aws:bedrock:us-east-1::foundation-model/anthropic.claude-3-haiku-20240307-v1:0" }],
+        }]});
+        let catalog = parse_catalog(&models, &profiles);
+        assert_eq!(catalog.models.len(), 2);
+        assert!(catalog.models[0].supports_vision);
+
+        let target = resolve(
+            "anthropic.claude-3-haiku-20240307-v1:0",
+            "us-east-1",
+            Some(&catalog),
+        );
+        assert_eq!(
+            target.invoke_id,
+            "global.anthropic.claude-3-haiku-20240307-v1:0"
+        );
+        assert_eq!(target.regions, ["us-east-1"]);
+    }
+}
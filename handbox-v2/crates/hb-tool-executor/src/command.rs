@@ -0,0 +1,182 @@
+//! Command gate for the shell-exec and bash tools.
+//!
+//! Programs on the workspace's allowlist ([`CommandPolicy`]) start directly.
+//! Any other program is shown to an approver — in the app, the user — and
+//! runs only if approved. Without an approver such programs are refused, so
+//! a headless run never starts something nobody allowed.
+
+use crate::ExecutorError;
+use hb_core::policy::CommandPolicy;
+use serde::Serialize;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// A program a node wants to start.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CommandRequest {
+    pub tool_ref: String,
+    pub program: String,
+    pub args: Vec<String>,
+    pub working_dir: Option<String>,
+}
+
+impl CommandRequest {
+    /// The command line for display, with arguments quoted where needed.
+    /// Only for showing to people; the program never goes through a shell.
+    pub fn display(&self) -> String {
+        std::iter::once(&self.program)
+            .chain(&self.args)
+            .map(|word| {
+                if word.is_empty()
+                    || word.contains(|c: char| c.is_whitespace() || "\"'$;&|<>`".contains(c))
+                {
+                    format!("\"{}\"", word.replace('"', "\\\""))
+                } else {
+                    word.clone()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Decides whether a program outside the allowlist may run.
+pub type CommandApprover =
+    Arc<dyn Fn(CommandRequest) -> Pin<Box<dyn Future<Output = bool> + Send>> + Send + Sync>;
+
+/// Allowlist plus approver, carried by [`ToolInput`](crate::ToolInput). The
+/// default gate has an empty allowlist and no approver: it refuses everything.
+#[derive(Clone, Default)]
+pub struct CommandGate {
+    policy: Arc<CommandPolicy>,
+    approver: Option<CommandApprover>,
+}
+
+impl fmt::Debug for CommandGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CommandGate")
+            .field("allowed_programs", &self.policy.allowed_programs)
+            .field("approver", &self.approver.is_some())
+            .finish()
+    }
+}
+
+impl CommandGate {
+    pub fn new(policy: CommandPolicy) -> Self {
+        Self {
+            policy: Arc::new(policy),
+            approver: None,
+        }
+    }
+
+    pub fn with_approver<F, Fut>(mut self, approver: F) -> Self
+    where
+        F: Fn(CommandRequest) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = bool> + Send + 'static,
+    {
+        self.approver = Some(Arc::new(move |request| Box::pin(approver(request))));
+        self
+    }
+
+    /// Whether `request` may run. Returns whether approval was needed.
+    pub async fn check(&self, request: &CommandRequest) -> Result<bool, ExecutorError> {
+        if self.policy.allows(&request.program) {
+            return Ok(false);
+        }
+        let Some(approve) = &self.approver else {
+            return Err(ExecutorError::NotAllowed(format!(
+                "'{}' is not on the workspace's command allowlist",
+                request.program
+            )));
+        };
+        if approve(request.clone()).await {
+            Ok(true)
+        } else {
+            Err(ExecutorError::NotAllowed(format!(
+                "Running '{}' was not approved",
+                request.display()
+            )))
+        }
+    }
+}
+
+/// Variables that change which program runs or what it loads before its
+/// own code starts. The workspace cannot set them for gated programs, or an
+/// allowlisted name could resolve to something else.
+const PROTECTED_ENV: &[&str] = &[
+    "PATH", "PATHEXT", "IFS", "ENV", "BASH_ENV", "SHELLOPTS", "BASHOPTS", "PS4", "CDPATH",
+    "NODE_OPTIONS", "PYTHONSTARTUP", "PYTHONPATH", "PERL5OPT", "PERL5LIB", "RUBYOPT",
+    "COMSPEC",
+];
+
+/// The workspace environment variables a gated program may be started with.
+pub(crate) fn command_env(
+    env: &std::collections::BTreeMap<String, String>,
+) -> impl Iterator<Item = (&String, &String)> {
+    env.iter().filter(|(name, _)| {
+        let name = name.to_ascii_uppercase();
+        let protected = PROTECTED_ENV.contains(&name.as_str())
+            || name.starts_with("LD_")
+            || name.starts_with("DYLD_");
+        if protected {
+            tracing::warn!("Not passing workspace variable {name} to a gated command");
+        }
+        !protected
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn request(program: &str) -> CommandRequest {
+        CommandRequest {
+            tool_ref: "core-tools/shell-exec@1.0.0".into(),
+            program: program.into(),
+            args: vec!["-f".into(), "GeoJSON".into(), "필지 경계.json".into()],
+            working_dir: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn allowlisted_programs_skip_approval() {
+        let asked = Arc::new(AtomicUsize::new(0));
+        let counter = asked.clone();
+        let gate = CommandGate::new(CommandPolicy {
+            allowed_programs: vec!["ogr2ogr".into()],
+        })
+        .with_approver(move |request: CommandRequest| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async move { request.program == "gdalinfo" }
+        });
+
+        assert!(!gate.check(&request("ogr2ogr")).await.unwrap());
+        assert!(gate.check(&request("gdalinfo")).await.unwrap());
+        let err = gate.check(&request("curl")).await.unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("curl -f GeoJSON \"필지 경계.json\""),
+            "{err}"
+        );
+        assert_eq!(asked.load(Ordering::SeqCst), 2);
+
+        // Nobody to ask: refused outright
+        assert!(CommandGate::default()
+            .check(&request("ogr2ogr"))
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn loader_and_lookup_variables_are_not_passed_on() {
+        let env = std::collections::BTreeMap::from(
+            [("PATH", "/tmp/evil"), ("ld_preload", "x.so"), ("DYLD_INSERT_LIBRARIES", "x"), ("GDAL_DATA", "/gdal")]
+                .map(|(k, v)| (k.to_string(), v.to_string())),
+        );
+        let kept: Vec<_> = command_env(&env).map(|(k, _)| k.as_str()).collect();
+        assert_eq!(kept, ["GDAL_DATA"]);
+    }
+}
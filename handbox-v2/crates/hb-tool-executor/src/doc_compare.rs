@@ -0,0 +1,562 @@
+//! Clause-level document comparison for `clause-split` and `doc-compare`.
+//!
+//! A document is split into clauses at numbered lines ("제5조", "Article 3",
+//! "2.1", "①", "(a)") and blank lines; sub-items carry the number of the
+//! article they belong to ("제5조 ②"). Clauses of two versions are aligned
+//! in order by the similarity of their text, ignoring the numbering so
+//! renumbered clauses still match; clauses left over that match each other
+//! out of order were moved. Each change is classified, and tagged when it
+//! touches numbers (amounts, dates, periods), obligations (shall / may /
+//! 하여야 / 할 수 있다) or negation — the changes a reviewer must not miss.
+
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// A clause or paragraph of a document.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Clause {
+    /// Position in the document, from 0.
+    pub index: usize,
+    /// The clause's number, qualified by its article for sub-items.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number: Option<String>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Unchanged,
+    Modified,
+    Added,
+    Deleted,
+    Moved,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    None,
+    Minor,
+    Major,
+}
+
+/// A run of words kept, removed or added by a modification.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiffOp {
+    /// `equal`, `delete` or `insert`.
+    pub op: &'static str,
+    pub text: String,
+}
+
+/// One entry of the change list.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Change {
+    pub kind: ChangeKind,
+    pub severity: Severity,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<Clause>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<Clause>,
+    /// Similarity of the clause texts, from 0 to 1.
+    pub similarity: f64,
+    /// What the change touches: `numbers`, `obligation`, `negation`,
+    /// `renumbered`, `wording`.
+    pub tags: Vec<&'static str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub diff: Vec<DiffOp>,
+    /// What the change means for the parties, when reviewed by the LLM.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub impact: Option<String>,
+}
+
+/// Split `text` into clauses.
+pub fn split_clauses(text: &str) -> Vec<Clause> {
+    let mut clauses: Vec<Clause> = Vec::new();
+    let mut article: Option<String> = None;
+    let mut current: Option<(Option<String>, String)> = None;
+    let mut finish = |current: &mut Option<(Option<String>, String)>| {
+        if let Some((number, text)) = current.take() {
+            if !text.trim().is_empty() {
+                clauses.push(Clause {
+                    index: clauses.len(),
+                    number,
+                    text: text.trim().to_string(),
+                });
+            }
+        }
+    };
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() {
+            finish(&mut current);
+            continue;
+        }
+        match clause_label(line) {
+            Some((label, top_level, _)) => {
+                finish(&mut current);
+                let number = if top_level {
+                    article = Some(label.clone());
+                    label
+                } else {
+                    match &article {
+                        Some(article) => format!("{article} {label}"),
+                        None => label,
+                    }
+                };
+                current = Some((Some(number), line.to_string()));
+            }
+            None => match &mut current {
+                Some((_, text)) => {
+                    text.push(' ');
+                    text.push_str(line);
+                }
+                None => current = Some((None, line.to_string())),
+            },
+        }
+    }
+    finish(&mut current);
+    clauses
+}
+
+/// The number a line opens a clause with, whether it is an article rather
+/// than an item within one, and where the number ends in the line.
+fn clause_label(line: &str) -> Option<(String, bool, usize)> {
+    static ARTICLE: OnceLock<Regex> = OnceLock::new();
+    static ITEM: OnceLock<Regex> = OnceLock::new();
+    let article = ARTICLE.get_or_init(|| {
+        Regex::new(r"^(?:제\s*\d+\s*조(?:의\s*\d+)?|(?i:article|section|clause)\s+\d+(?:\.\d+)*|\d+(?:\.\d+)+\.?|\d+\.)(?:\s|\(|$)")
+            .expect("valid regex")
+    });
+    let item = ITEM.get_or_init(|| {
+        Regex::new(r"^(?:[①-⑳]|\(\d{1,2}\)|\d{1,2}\)|\([a-z]\)|[a-z]\)|[가-하]\.)(?:\s|$)")
+            .expect("valid regex")
+    });
+    let (m, top_level) = match article.find(line) {
+        Some(m) => (m, true),
+        None => (item.find(line)?, false),
+    };
+    // The match takes the character after the number to check it ends there
+    let label = m.as_str().trim_end_matches(['(', ' ', '\t']);
+    let normalized = label.split_whitespace().collect::<Vec<_>>().join(" ");
+    Some((normalized, top_level, label.len()))
+}
+
+/// The clause text without its number, for comparing.
+fn body(clause: &Clause) -> &str {
+    match clause_label(&clause.text) {
+        Some((_, _, end)) => clause.text[end..].trim(),
+        None => clause.text.trim(),
+    }
+}
+
+/// Character bigrams of `text` without whitespace, lowercased, with counts.
+fn bigrams(text: &str) -> HashMap<(char, char), u32> {
+    let chars: Vec<char> = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    let mut grams = HashMap::new();
+    if chars.len() == 1 {
+        *grams.entry((chars[0], ' ')).or_default() += 1;
+    }
+    for pair in chars.windows(2) {
+        *grams.entry((pair[0], pair[1])).or_default() += 1;
+    }
+    grams
+}
+
+/// Dice coefficient of two bigram multisets.
+fn dice(a: &HashMap<(char, char), u32>, b: &HashMap<(char, char), u32>) -> f64 {
+    let total: u32 = a.values().sum::<u32>() + b.values().sum::<u32>();
+    if total == 0 {
+        return 1.0;
+    }
+    let shared: u32 = a
+        .iter()
+        .map(|(gram, n)| (*n).min(b.get(gram).copied().unwrap_or(0)))
+        .sum();
+    f64::from(2 * shared) / f64::from(total)
+}
+
+/// Compare two versions clause by clause. Clauses whose similarity is
+/// below `threshold` are never paired. Changes follow the new version,
+/// with deleted clauses where they stood in the old one.
+pub fn compare(old: &[Clause], new: &[Clause], threshold: f64) -> Vec<Change> {
+    let old_grams: Vec<_> = old.iter().map(|c| bigrams(body(c))).collect();
+    let new_grams: Vec<_> = new.iter().map(|c| bigrams(body(c))).collect();
+    let sim: Vec<Vec<f64>> = old_grams
+        .iter()
+        .map(|a| new_grams.iter().map(|b| dice(a, b)).collect())
+        .collect();
+
+    // In-order alignment maximizing total similarity
+    let (n, m) = (old.len(), new.len());
+    let mut score = vec![vec![0.0f64; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            let pair = if sim[i][j] >= threshold {
+                sim[i][j] + score[i + 1][j + 1]
+            } else {
+                f64::MIN
+            };
+            score[i][j] = pair.max(score[i + 1][j]).max(score[i][j + 1]);
+        }
+    }
+    let mut path = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n
+            && j < m
+            && sim[i][j] >= threshold
+            && score[i][j] == sim[i][j] + score[i + 1][j + 1]
+        {
+            path.push((Some(i), Some(j)));
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || score[i][j] == score[i + 1][j]) {
+            path.push((Some(i), None));
+            i += 1;
+        } else {
+            path.push((None, Some(j)));
+            j += 1;
+        }
+    }
+
+    // Clauses left over on both sides that match each other were moved
+    let unmatched_old: Vec<usize> = path
+        .iter()
+        .filter_map(|step| match step {
+            (Some(i), None) => Some(*i),
+            _ => None,
+        })
+        .collect();
+    let unmatched_new: Vec<usize> = path
+        .iter()
+        .filter_map(|step| match step {
+            (None, Some(j)) => Some(*j),
+            _ => None,
+        })
+        .collect();
+    let mut candidates: Vec<(usize, usize)> = unmatched_old
+        .iter()
+        .flat_map(|&i| unmatched_new.iter().map(move |&j| (i, j)))
+        .filter(|&(i, j)| sim[i][j] >= threshold)
+        .collect();
+    candidates.sort_by(|a, b| sim[b.0][b.1].total_cmp(&sim[a.0][a.1]));
+    let mut moved_to: HashMap<usize, usize> = HashMap::new();
+    let mut moved_from: HashMap<usize, usize> = HashMap::new();
+    for (i, j) in candidates {
+        if !moved_from.contains_key(&i) && !moved_to.contains_key(&j) {
+            moved_from.insert(i, j);
+            moved_to.insert(j, i);
+        }
+    }
+
+    path.into_iter()
+        .filter_map(|step| match step {
+            (Some(i), Some(j)) => Some(classify(&old[i], &new[j], sim[i][j], false)),
+            (Some(i), None) if moved_from.contains_key(&i) => None,
+            (Some(i), None) => Some(Change {
+                kind: ChangeKind::Deleted,
+                severity: Severity::Major,
+                old: Some(old[i].clone()),
+                new: None,
+                similarity: 0.0,
+                tags: Vec::new(),
+                diff: Vec::new(),
+                impact: None,
+            }),
+            (None, Some(j)) => Some(match moved_to.get(&j) {
+                Some(&i) => classify(&old[i], &new[j], sim[i][j], true),
+                None => Change {
+                    kind: ChangeKind::Added,
+                    severity: Severity::Major,
+                    old: None,
+                    new: Some(new[j].clone()),
+                    similarity: 0.0,
+                    tags: Vec::new(),
+                    diff: Vec::new(),
+                    impact: None,
+                },
+            }),
+            (None, None) => None,
+        })
+        .collect()
+}
+
+fn classify(old: &Clause, new: &Clause, similarity: f64, moved: bool) -> Change {
+    let (old_body, new_body) = (body(old), body(new));
+    let same_text = normalize(old_body) == normalize(new_body);
+    let mut tags = Vec::new();
+    if old.number.is_some() && new.number.is_some() && old.number != new.number {
+        tags.push("renumbered");
+    }
+    let mut diff = Vec::new();
+    if !same_text {
+        if numbers(old_body) != numbers(new_body) {
+            tags.push("numbers");
+        }
+        if obligations(old_body) != obligations(new_body) {
+            tags.push("obligation");
+        }
+        if negations(old_body) != negations(new_body) {
+            tags.push("negation");
+        }
+        if !tags
+            .iter()
+            .any(|t| matches!(*t, "numbers" | "obligation" | "negation"))
+        {
+            tags.push("wording");
+        }
+        diff = word_diff(old_body, new_body);
+    }
+    let substantive = tags
+        .iter()
+        .any(|t| matches!(*t, "numbers" | "obligation" | "negation"));
+    let (kind, severity) = match (moved, same_text) {
+        (false, true) => (ChangeKind::Unchanged, Severity::None),
+        (true, true) => (ChangeKind::Moved, Severity::Minor),
+        (moved, false) => (
+            if moved {
+                ChangeKind::Moved
+            } else {
+                ChangeKind::Modified
+            },
+            if substantive || similarity < 0.8 {
+                Severity::Major
+            } else {
+                Severity::Minor
+            },
+        ),
+    };
+    Change {
+        kind,
+        severity,
+        old: Some(old.clone()),
+        new: Some(new.clone()),
+        similarity: (similarity * 1000.0).round() / 1000.0,
+        tags,
+        diff,
+        impact: None,
+    }
+}
+
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The numbers a clause states, in order, without thousands separators.
+fn numbers(text: &str) -> Vec<String> {
+    static NUMBER: OnceLock<Regex> = OnceLock::new();
+    NUMBER
+        .get_or_init(|| Regex::new(r"\d[\d,]*(?:\.\d+)?").expect("valid regex"))
+        .find_iter(text)
+        .map(|m| m.as_str().replace(',', ""))
+        .collect()
+}
+
+/// Which kinds of obligation a clause states: duty, permission, prohibition.
+fn obligations(text: &str) -> [bool; 3] {
+    let lower = text.to_lowercase();
+    let has = |words: &[&str]| words.iter().any(|w| lower.contains(w));
+    let prohibition = has(&[
+        "shall not",
+        "must not",
+        "may not",
+        "할 수 없",
+        "하지 못",
+        "해서는 안",
+        "하여서는 아니",
+        "금지",
+    ]);
+    let duty = has(&[
+        "shall ",
+        "must ",
+        "is required to",
+        "하여야",
+        "해야",
+        "할 의무",
+    ]);
+    let permission = has(&[" may ", "is entitled to", "할 수 있", "할 권리"]);
+    [duty, permission, prohibition]
+}
+
+fn negations(text: &str) -> usize {
+    let lower = text.to_lowercase();
+    [" not ", "n't", " no ", " never ", "없", "않", "아니", "못"]
+        .iter()
+        .map(|w| lower.matches(w).count())
+        .sum()
+}
+
+/// Word-level diff of two texts, as runs of kept, removed and added words.
+fn word_diff(old: &str, new: &str) -> Vec<DiffOp> {
+    let a: Vec<&str> = old.split_whitespace().collect();
+    let b: Vec<&str> = new.split_whitespace().collect();
+    let mut ops: Vec<DiffOp> = Vec::new();
+    let mut push = |op: &'static str, word: &str| match ops.last_mut() {
+        Some(last) if last.op == op => {
+            last.text.push(' ');
+            last.text.push_str(word);
+        }
+        _ => ops.push(DiffOp {
+            op,
+            text: word.to_string(),
+        }),
+    };
+    // Too long to align word by word: show it as replaced
+    if a.len() * b.len() > 4_000_000 {
+        push("delete", old);
+        push("insert", new);
+        return ops;
+    }
+    let mut lcs = vec![vec![0u32; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            push("equal", a[i]);
+            i += 1;
+            j += 1;
+        } else if i < a.len() && (j == b.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            push("delete", a[i]);
+            i += 1;
+        } else {
+            push("insert", b[j]);
+            j += 1;
+        }
+    }
+    ops
+}
+
+/// A markdown change report for reviewers, in document order, leaving
+/// unchanged clauses out.
+pub fn report(changes: &[Change]) -> String {
+    let count = |kind: ChangeKind| changes.iter().filter(|c| c.kind == kind).count();
+    let mut out = format!(
+        "## Changes: {} modified, {} added, {} deleted, {} moved\n",
+        count(ChangeKind::Modified),
+        count(ChangeKind::Added),
+        count(ChangeKind::Deleted),
+        count(ChangeKind::Moved)
+    );
+    for change in changes.iter().filter(|c| c.kind != ChangeKind::Unchanged) {
+        let clause = change.new.as_ref().or(change.old.as_ref());
+        let label = clause
+            .and_then(|c| c.number.clone())
+            .unwrap_or_else(|| format!("¶{}", clause.map_or(0, |c| c.index + 1)));
+        let kind = serde_json::to_value(change.kind).ok();
+        let kind = kind.as_ref().and_then(|v| v.as_str()).unwrap_or_default();
+        let mut notes = change.tags.clone();
+        if change.severity == Severity::Major {
+            notes.push("major");
+        }
+        out.push_str(&format!("\n### {label} — {kind}"));
+        if !notes.is_empty() {
+            out.push_str(&format!(" ({})", notes.join(", ")));
+        }
+        out.push('\n');
+        if let Some(old) = &change.old {
+            out.push_str(&format!("- Before: {}\n", old.text));
+        }
+        if let Some(new) = &change.new {
+            out.push_str(&format!("- After: {}\n", new.text));
+        }
+        if let Some(impact) = &change.impact {
+            out.push_str(&format!("- Impact: {impact}\n"));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_articles_and_items() {
+        let clauses = split_clauses(
+            "용역 계약서\n\n제1조(목적) 이 계약은 설계 용역에 관한 사항을 정한다.\n\
+             제2조(대금)\n① 발주자는 대금을 지급한다.\n② 지급은 30일 이내로\n한다.\n\n\
+             Article 3 Term. This agreement ends on 31 December.\n(a) Either party may terminate.",
+        );
+        let numbers: Vec<_> = clauses.iter().map(|c| c.number.as_deref()).collect();
+        assert_eq!(
+            numbers,
+            [
+                None,
+                Some("제1조"),
+                Some("제2조"),
+                Some("제2조 ①"),
+                Some("제2조 ②"),
+                Some("Article 3"),
+                Some("Article 3 (a)")
+            ]
+        );
+        assert_eq!(clauses[4].text, "② 지급은 30일 이내로 한다.");
+        assert_eq!(
+            body(&clauses[1]),
+            "(목적) 이 계약은 설계 용역에 관한 사항을 정한다."
+        );
+        assert_eq!(body(&clauses[3]), "발주자는 대금을 지급한다.");
+    }
+
+    #[test]
+    fn aligns_versions_and_classifies_changes() {
+        let old = split_clauses(
+            "제1조(목적) 이 계약은 설계 용역에 관한 사항을 정한다.\n\
+             제2조(대금) 발주자는 계약금액 50,000,000원을 지급한다.\n\
+             제3조(기간) 수급인은 착수일부터 90일 이내에 용역을 완료하여야 한다.\n\
+             제4조(비밀유지) 수급인은 업무상 알게 된 정보를 누설하여서는 아니 된다.\n\
+             제5조(분쟁) 분쟁은 서울중앙지방법원을 관할로 한다.",
+        );
+        let new = split_clauses(
+            "제1조(목적) 이 계약은 설계 용역에 관한 사항을 정한다.\n\
+             제2조(분쟁) 분쟁은 서울중앙지방법원을 관할로 한다.\n\
+             제3조(대금) 발주자는 계약금액 55,000,000원을 지급한다.\n\
+             제4조(기간) 수급인은 착수일부터 90일 이내에 용역을 완료할 수 있다.\n\
+             제5조(지체상금) 수급인은 지체일수마다 계약금액의 1천분의 1을 지체상금으로 납부한다.",
+        );
+        let changes = compare(&old, &new, 0.5);
+        let kinds: Vec<_> = changes.iter().map(|c| c.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                ChangeKind::Unchanged,
+                ChangeKind::Moved,
+                ChangeKind::Modified,
+                ChangeKind::Modified,
+                ChangeKind::Deleted,
+                ChangeKind::Added
+            ]
+        );
+        assert_eq!(changes[1].tags, ["renumbered"]);
+        assert_eq!(
+            (changes[1].old.as_ref().unwrap().index, changes[1].severity),
+            (4, Severity::Minor)
+        );
+        assert_eq!(changes[2].tags, ["renumbered", "numbers"]);
+        assert!(changes[2].diff.contains(&DiffOp {
+            op: "insert",
+            text: "55,000,000원을".into()
+        }));
+        assert!(changes[3].tags.contains(&"obligation"));
+        assert_eq!(changes[3].severity, Severity::Major);
+
+        let report = report(&changes);
+        assert!(report.starts_with("## Changes: 2 modified, 1 added, 1 deleted, 1 moved"));
+        assert!(report.contains("### 제3조 — modified (renumbered, numbers, major)"));
+    }
+}
@@ -1,11 +1,139 @@
 //! Docker container executor.
+//!
+//! The tool runs as `docker run --rm -i <image>`, with the same stdin/stdout
+//! contract as a process tool (see [`crate::local::execute_process`]) and the
+//! workspace's environment variables set in the container. A
+//! missing image is pulled first, and the pull is reported as progress, one
+//! item per layer.
 
-use crate::{ExecutorError, ToolInput, ToolOutput};
+use crate::progress::PROGRESS_ENV;
+use crate::{local, ExecutorError, Progress, ProgressReporter, ToolInput, ToolOutput};
+use std::collections::{BTreeMap, HashMap};
+use std::process::Stdio;
 
 /// Execute a tool inside a Docker container.
-pub async fn execute(_image: &str, _input: &ToolInput) -> Result<ToolOutput, ExecutorError> {
-    // Phase 3 will implement Docker-based isolated execution.
-    Err(ExecutorError::UnsupportedRuntime(
-        "Docker executor not yet implemented".into(),
-    ))
+pub async fn execute(image: &str, input: &ToolInput) -> Result<ToolOutput, ExecutorError> {
+    if !image_present(image).await? {
+        pull(image, &input.progress).await?;
+    }
+    let mut command = tokio::process::Command::new("docker");
+    command.args(run_args(image, &input.env));
+    local::run_child(command, "docker", input).await
+}
+
+/// `docker run` arguments; the variables go to the container, not the
+/// `docker` command itself.
+fn run_args(image: &str, env: &BTreeMap<String, String>) -> Vec<String> {
+    let mut args: Vec<String> = ["run", "--rm", "-i"].map(String::from).into();
+    for (name, value) in env {
+        args.push("--env".into());
+        args.push(format!("{name}={value}"));
+    }
+    args.extend(["--env".into(), format!("{PROGRESS_ENV}=1"), image.into()]);
+    args
+}
+
+async fn image_present(image: &str) -> Result<bool, ExecutorError> {
+    let status = tokio::process::Command::new("docker")
+        .args(["image", "inspect", image])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await
+        .map_err(|e| ExecutorError::Process(format!("Docker is not available: {e}")))?;
+    Ok(status.success())
+}
+
+/// `docker pull`, reporting layers done as they complete.
+async fn pull(image: &str, progress: &ProgressReporter) -> Result<(), ExecutorError> {
+    use tokio::io::AsyncBufReadExt;
+
+    let step = format!("pulling {image}");
+    progress.report(Progress::step(step.clone()));
+    let mut child = tokio::process::Command::new("docker")
+        .args(["pull", image])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| ExecutorError::Process(format!("Docker is not available: {e}")))?;
+
+    let mut pull = PullProgress::default();
+    if let Some(stdout) = child.stdout.take() {
+        let mut lines = tokio::io::BufReader::new(stdout).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            progress.beat();
+            if let Some(update) = pull.update(&line) {
+                progress.report(update.with_step(step.clone()));
+            }
+        }
+    }
+    let output = child
+        .wait_with_output()
+        .await
+        .map_err(|e| ExecutorError::Process(e.to_string()))?;
+    if !output.status.success() {
+        return Err(ExecutorError::ExecutionFailed(format!(
+            "docker pull {image} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+/// Layers of an image pull and whether each is done, from `docker pull`'s
+/// `<layer>: <status>` lines.
+#[derive(Debug, Default)]
+struct PullProgress {
+    layers: HashMap<String, bool>,
+}
+
+impl PullProgress {
+    /// Take one output line; the new progress when it changed a layer.
+    fn update(&mut self, line: &str) -> Option<Progress> {
+        let (layer, status) = line.split_once(": ")?;
+        if layer.len() != 12 || !layer.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let done = matches!(status.trim(), "Pull complete" | "Already exists");
+        let was_done = self.layers.insert(layer.to_string(), done);
+        if was_done == Some(done) {
+            return None;
+        }
+        let finished = self.layers.values().filter(|&&done| done).count();
+        Some(Progress::items(finished as u64, self.layers.len() as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pull_output_counts_finished_layers() {
+        let mut pull = PullProgress::default();
+        let lines = [
+            "latest: Pulling from library/python",
+            "a2abf6c4d29d: Already exists",
+            "c16bf6d4e3f1: Pulling fs layer",
+            "c16bf6d4e3f1: Downloading",
+            "c16bf6d4e3f1: Pull complete",
+            "Digest: sha256:0123",
+        ];
+        let updates: Vec<_> = lines
+            .iter()
+            .filter_map(|line| pull.update(line))
+            .map(|p| (p.current.unwrap(), p.total.unwrap()))
+            .collect();
+        assert_eq!(updates, [(1, 1), (1, 2), (2, 2)]);
+    }
+
+    #[test]
+    fn workspace_variables_go_to_the_container() {
+        let env = BTreeMap::from([("REGION".to_string(), "ap-northeast-2".to_string())]);
+        assert_eq!(
+            run_args("tools/ocr:1", &env),
+            ["run", "--rm", "-i", "--env", "REGION=ap-northeast-2", "--env", "HB_PROGRESS=1", "tools/ocr:1"]
+        );
+    }
 }
@@ -0,0 +1,312 @@
+//! Text encoding detection for ingested files.
+//!
+//! Korean business documents are often EUC-KR/CP949 or UTF-16 (Excel's
+//! "Unicode text" export) rather than UTF-8. Files are decoded by BOM first,
+//! then as UTF-8 if valid, then as UTF-16 or CP949 by inspection; a node can
+//! override detection with an `encoding` config value (a WHATWG label such
+//! as "euc-kr" or "utf-16le", or "cp949").
+
+use crate::ExecutorError;
+use encoding_rs::{CoderResult, Decoder, Encoding, EUC_KR, UTF_16BE, UTF_16LE, UTF_8};
+use std::io::{self, Read};
+
+/// Config value that asks for detection.
+pub const AUTO: &str = "auto";
+
+/// Bytes inspected when looking for UTF-16 without a BOM.
+const UTF16_SAMPLE: usize = 4096;
+
+/// Decoded text and how it was decoded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decoded {
+    pub text: String,
+    /// Encoding name, e.g. "UTF-8", "EUC-KR", "UTF-16LE".
+    pub encoding: &'static str,
+    /// Some bytes were invalid and replaced with U+FFFD.
+    pub had_errors: bool,
+}
+
+/// Encoding requested in a node's config; `None` means detect.
+pub fn from_config(config: &serde_json::Value) -> Option<&str> {
+    config
+        .get("encoding")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|label| !label.is_empty() && !label.eq_ignore_ascii_case(AUTO))
+}
+
+/// Decode `bytes` with the encoding named by `label`, or a detected one.
+/// A byte order mark always wins.
+pub fn decode(bytes: &[u8], label: Option<&str>) -> Result<Decoded, ExecutorError> {
+    let encoding = choose(bytes, label)?;
+    let (text, used, had_errors) = encoding.decode(bytes);
+    Ok(Decoded {
+        text: text.into_owned(),
+        encoding: used.name(),
+        had_errors,
+    })
+}
+
+/// Decodes a reader to UTF-8 as it is read, for files too large to load.
+/// The encoding is detected from the first few kilobytes.
+pub struct DecodeReader<R> {
+    inner: R,
+    decoder: Decoder,
+    encoding: &'static str,
+    input: Vec<u8>,
+    consumed: usize,
+    output: Vec<u8>,
+    delivered: usize,
+    eof: bool,
+    done: bool,
+}
+
+impl<R: Read> DecodeReader<R> {
+    pub fn new(mut inner: R, label: Option<&str>) -> Result<Self, ExecutorError> {
+        let mut input = vec![0; UTF16_SAMPLE];
+        let mut filled = 0;
+        while filled < input.len() {
+            match inner.read(&mut input[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    return Err(ExecutorError::ExecutionFailed(format!(
+                        "Failed to read: {e}"
+                    )))
+                }
+            }
+        }
+        input.truncate(filled);
+        let encoding = choose(&input, label)?;
+        // Same as `decode`: a BOM overrides the chosen encoding
+        let (encoding, _) = Encoding::for_bom(&input).unwrap_or((encoding, 0));
+        Ok(Self {
+            inner,
+            decoder: encoding.new_decoder(),
+            encoding: encoding.name(),
+            eof: filled < UTF16_SAMPLE,
+            input,
+            consumed: 0,
+            output: Vec::new(),
+            delivered: 0,
+            done: false,
+        })
+    }
+
+    /// Name of the encoding being decoded.
+    pub fn encoding(&self) -> &'static str {
+        self.encoding
+    }
+}
+
+impl<R: Read> Read for DecodeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.delivered < self.output.len() {
+                let n = buf.len().min(self.output.len() - self.delivered);
+                buf[..n].copy_from_slice(&self.output[self.delivered..self.delivered + n]);
+                self.delivered += n;
+                return Ok(n);
+            }
+            if self.done {
+                return Ok(0);
+            }
+            if self.consumed == self.input.len() && !self.eof {
+                self.input.resize(UTF16_SAMPLE, 0);
+                let n = self.inner.read(&mut self.input)?;
+                self.input.truncate(n);
+                self.consumed = 0;
+                self.eof = n == 0;
+            }
+            let pending = &self.input[self.consumed..];
+            let capacity = self
+                .decoder
+                .max_utf8_buffer_length(pending.len())
+                .unwrap_or(UTF16_SAMPLE * 3)
+                .max(4);
+            self.output.resize(capacity, 0);
+            let (result, read, written, _) =
+                self.decoder
+                    .decode_to_utf8(pending, &mut self.output, self.eof);
+            self.consumed += read;
+            self.output.truncate(written);
+            self.delivered = 0;
+            if self.eof && result == CoderResult::InputEmpty {
+                self.done = true;
+            }
+        }
+    }
+}
+
+/// The encoding named by `label`, or the one detected in `bytes`.
+fn choose(bytes: &[u8], label: Option<&str>) -> Result<&'static Encoding, ExecutorError> {
+    match label {
+        Some(label) => lookup(label)
+            .ok_or_else(|| ExecutorError::ExecutionFailed(format!("Unknown encoding '{label}'"))),
+        None => Ok(detect(bytes)),
+    }
+}
+
+/// WHATWG labels plus the Windows names Korean users know (CP949 is what
+/// encoding_rs calls EUC-KR).
+fn lookup(label: &str) -> Option<&'static Encoding> {
+    match label.to_ascii_lowercase().as_str() {
+        "cp949" | "ms949" | "uhc" => Some(EUC_KR),
+        _ => Encoding::for_label(label.as_bytes()),
+    }
+}
+
+/// Guess the encoding of `bytes`.
+pub fn detect(bytes: &[u8]) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return encoding;
+    }
+    if let Some(encoding) = utf16_without_bom(bytes) {
+        return encoding;
+    }
+    if is_utf8(bytes) {
+        return UTF_8;
+    }
+    if is_cp949(bytes) {
+        return EUC_KR;
+    }
+    UTF_8
+}
+
+/// Valid UTF-8, allowing a sequence cut off at the end (sampled files).
+fn is_utf8(bytes: &[u8]) -> bool {
+    match std::str::from_utf8(bytes) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none(),
+    }
+}
+
+/// Decodes as CP949 (encoding_rs's EUC-KR) without errors, allowing a lead
+/// byte cut off at the end.
+fn is_cp949(bytes: &[u8]) -> bool {
+    let clean = |b: &[u8]| {
+        EUC_KR
+            .decode_without_bom_handling_and_without_replacement(b)
+            .is_some()
+    };
+    clean(bytes) || (bytes.len() > 1 && clean(&bytes[..bytes.len() - 1]))
+}
+
+/// Mostly-ASCII UTF-16 text has a zero in every other byte.
+fn utf16_without_bom(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(UTF16_SAMPLE)];
+    let pairs = sample.len() / 2;
+    if pairs < 2 {
+        return None;
+    }
+    let zeros_at = |offset: usize| {
+        sample
+            .chunks_exact(2)
+            .filter(|pair| pair[offset] == 0)
+            .count()
+    };
+    let (even, odd) = (zeros_at(0), zeros_at(1));
+    // Korean text has few zero bytes, so the threshold is low; the other
+    // position must be nearly free of them
+    let threshold = pairs / 5;
+    if odd > threshold && even <= pairs / 50 {
+        Some(UTF_16LE)
+    } else if even > threshold && odd <= pairs / 50 {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "품명,수량\n철근 D13,120\n";
+
+    fn utf16(text: &str, big_endian: bool) -> Vec<u8> {
+        text.encode_utf16()
+            .flat_map(|unit| {
+                if big_endian {
+                    unit.to_be_bytes()
+                } else {
+                    unit.to_le_bytes()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn detects_common_korean_encodings() {
+        let (cp949, _, _) = EUC_KR.encode(TEXT);
+        let cases: Vec<(Vec<u8>, &str)> = vec![
+            (TEXT.as_bytes().to_vec(), "UTF-8"),
+            (
+                [b"\xEF\xBB\xBF".as_slice(), TEXT.as_bytes()].concat(),
+                "UTF-8",
+            ),
+            (cp949.into_owned(), "EUC-KR"),
+            (
+                [b"\xFF\xFE".to_vec(), utf16(TEXT, false)].concat(),
+                "UTF-16LE",
+            ),
+            (utf16(TEXT, false), "UTF-16LE"),
+            (utf16(TEXT, true), "UTF-16BE"),
+        ];
+        for (bytes, expected) in cases {
+            let decoded = decode(&bytes, None).unwrap();
+            assert_eq!(decoded.encoding, expected);
+            assert_eq!(decoded.text, TEXT, "decoding as {expected}");
+            assert!(!decoded.had_errors);
+        }
+    }
+
+    #[test]
+    fn truncated_samples_keep_their_encoding() {
+        let utf8 = TEXT.as_bytes();
+        // Cut in the middle of "품"
+        assert_eq!(detect(&utf8[..1]).name(), "UTF-8");
+        assert_eq!(detect(&utf8[..2]).name(), "UTF-8");
+        let (cp949, _, _) = EUC_KR.encode(TEXT);
+        assert_eq!(detect(&cp949[..3]).name(), "EUC-KR");
+    }
+
+    #[test]
+    fn decode_reader_streams_in_small_reads() {
+        let long = TEXT.repeat(500);
+        let (cp949, _, _) = EUC_KR.encode(&long);
+        let utf16 = [b"\xFF\xFE".to_vec(), utf16(&long, false)].concat();
+        for (bytes, expected) in [(cp949.into_owned(), "EUC-KR"), (utf16, "UTF-16LE")] {
+            let mut reader = DecodeReader::new(bytes.as_slice(), None).unwrap();
+            assert_eq!(reader.encoding(), expected);
+            let mut text = Vec::new();
+            let mut buf = [0; 7];
+            loop {
+                let n = reader.read(&mut buf).unwrap();
+                if n == 0 {
+                    break;
+                }
+                text.extend_from_slice(&buf[..n]);
+            }
+            assert_eq!(String::from_utf8(text).unwrap(), long);
+        }
+    }
+
+    #[test]
+    fn config_overrides_detection() {
+        let config = serde_json::json!({ "encoding": "cp949" });
+        let (cp949, _, _) = EUC_KR.encode(TEXT);
+        let decoded = decode(&cp949, from_config(&config)).unwrap();
+        assert_eq!(decoded.text, TEXT);
+
+        assert_eq!(
+            from_config(&serde_json::json!({ "encoding": "auto" })),
+            None
+        );
+        assert_eq!(from_config(&serde_json::json!({})), None);
+        // Forcing the wrong encoding is reported, not silently accepted
+        assert!(decode(&cp949, Some("utf-8")).unwrap().had_errors);
+        assert!(decode(b"x", Some("klingon")).is_err());
+    }
+}
@@ -0,0 +1,404 @@
+//! Field extraction for `doc-extract-fields`.
+//!
+//! Fields are described by a small schema — a name, the labels the field is
+//! printed under, a type, whether it is required and an optional pattern —
+//! given either as a list of field specs or as a JSON Schema object
+//! (`properties`, `required`, `format: "date"`, and `x-labels` or the
+//! description for labels). Values are found next to their labels
+//! ("Invoice No: 123", "발행일   2024.03.05", or a label with the value on
+//! the line below), converted to their type and checked against the schema.
+//! Fields the layout doesn't give up are left to the LLM fallback.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldType {
+    #[default]
+    String,
+    Number,
+    Integer,
+    /// Normalized to `YYYY-MM-DD`.
+    Date,
+    Boolean,
+}
+
+/// One field to extract.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldSpec {
+    pub name: String,
+    /// Labels the field is printed under; the name, with `_` read as a
+    /// space, is always tried too.
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default, rename = "type")]
+    pub field_type: FieldType,
+    #[serde(default)]
+    pub required: bool,
+    /// Regex a string value must match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl FieldSpec {
+    fn labels(&self) -> Vec<String> {
+        let mut labels = self.labels.clone();
+        labels.push(self.name.replace('_', " "));
+        // Longest first, so "issue date" wins over "date"
+        labels.sort_by_key(|l| std::cmp::Reverse(l.chars().count()));
+        labels.retain(|l| !l.trim().is_empty());
+        labels
+    }
+}
+
+/// Why a field's value was rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// Read the field schema from a tool's `fields` config: a list of field
+/// specs (or bare names), or a JSON Schema object.
+pub fn parse_schema(value: &Value) -> Result<Vec<FieldSpec>, String> {
+    let fields = match value {
+        Value::Array(items) => items
+            .iter()
+            .map(|item| match item {
+                Value::String(name) => Ok(FieldSpec {
+                    name: name.clone(),
+                    labels: Vec::new(),
+                    field_type: FieldType::String,
+                    required: false,
+                    pattern: None,
+                    description: None,
+                }),
+                other => serde_json::from_value(other.clone())
+                    .map_err(|e| format!("Invalid field {other}: {e}")),
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        Value::Object(schema) if schema.contains_key("properties") => {
+            let required: Vec<&str> = schema
+                .get("required")
+                .and_then(|r| r.as_array())
+                .map(|r| r.iter().filter_map(|v| v.as_str()).collect())
+                .unwrap_or_default();
+            let properties = schema["properties"]
+                .as_object()
+                .ok_or("Schema 'properties' must be an object")?;
+            properties
+                .iter()
+                .map(|(name, property)| {
+                    schema_field(name, property, required.contains(&name.as_str()))
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        }
+        Value::String(text) => {
+            return parse_schema(
+                &serde_json::from_str(text)
+                    .map_err(|e| format!("Field schema is not JSON: {e}"))?,
+            )
+        }
+        _ => return Err("Fields must be a list of fields or a JSON Schema with properties".into()),
+    };
+    if fields.is_empty() {
+        return Err("No fields to extract".into());
+    }
+    for field in &fields {
+        if let Some(pattern) = &field.pattern {
+            Regex::new(pattern).map_err(|e| format!("Field '{}' pattern: {e}", field.name))?;
+        }
+    }
+    Ok(fields)
+}
+
+fn schema_field(name: &str, property: &Value, required: bool) -> Result<FieldSpec, String> {
+    let text = |key: &str| property.get(key).and_then(|v| v.as_str());
+    let field_type = match (text("type"), text("format")) {
+        (_, Some("date")) => FieldType::Date,
+        (Some("number"), _) => FieldType::Number,
+        (Some("integer"), _) => FieldType::Integer,
+        (Some("boolean"), _) => FieldType::Boolean,
+        (Some("string") | None, _) => FieldType::String,
+        (Some(other), _) => return Err(format!("Field '{name}' has unsupported type '{other}'")),
+    };
+    let mut labels: Vec<String> = property
+        .get("x-labels")
+        .and_then(|l| l.as_array())
+        .map(|l| {
+            l.iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    if let Some(title) = text("title") {
+        labels.push(title.to_string());
+    }
+    Ok(FieldSpec {
+        name: name.to_string(),
+        labels,
+        field_type,
+        required,
+        pattern: text("pattern").map(str::to_string),
+        description: text("description").map(str::to_string),
+    })
+}
+
+/// The raw text printed next to each field's label, for the fields found.
+pub fn find_by_layout(text: &str, fields: &[FieldSpec]) -> Map<String, Value> {
+    let lines: Vec<&str> = text.lines().map(str::trim).collect();
+    let mut found = Map::new();
+    for field in fields {
+        let labels = field.labels();
+        let value = lines.iter().enumerate().find_map(|(i, line)| {
+            labels.iter().find_map(|label| {
+                let rest = after_label(line, label)?;
+                if rest.is_empty() {
+                    // Label on its own line: the value is on the next one
+                    let next = lines[i + 1..].iter().find(|l| !l.is_empty())?;
+                    let value = first_cell(next);
+                    (!starts_with_any_label(value, fields)).then(|| value.to_string())
+                } else {
+                    Some(first_cell(rest).to_string())
+                }
+            })
+        });
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            found.insert(field.name.clone(), Value::String(value));
+        }
+    }
+    found
+}
+
+/// The text after `label` on `line`, with the separator skipped, when the
+/// label stands as a whole word at the start of the line or of a cell.
+fn after_label<'a>(line: &'a str, label: &str) -> Option<&'a str> {
+    let lower = line.to_lowercase();
+    let label_lower = label.to_lowercase();
+    // Lowercasing can change byte lengths; only match where offsets agree
+    if lower.len() != line.len() {
+        return None;
+    }
+    let mut from = 0;
+    while let Some(at) = lower[from..].find(&label_lower).map(|i| i + from) {
+        from = at + label_lower.len();
+        let before = line[..at].trim_end_matches(['-', '*', '•', '·', ' ', '\t', '|', '[', '(']);
+        let starts_cell = before.is_empty()
+            || line[..at].ends_with("  ")
+            || line[..at].ends_with('\t')
+            || line[..at].trim_end().ends_with('|');
+        let rest = &line[at + label_lower.len()..];
+        let ends_word = !rest.chars().next().is_some_and(|c| c.is_alphanumeric());
+        if !starts_cell || !ends_word {
+            continue;
+        }
+        let rest = rest
+            .trim_start_matches([')', ']'])
+            .trim_start()
+            .trim_start_matches([':', '：', '=', '|', '-', '–'])
+            .trim();
+        return Some(rest);
+    }
+    None
+}
+
+/// The first cell of a line: up to a tab, a `|` or a run of two spaces.
+fn first_cell(text: &str) -> &str {
+    let end = [text.find('\t'), text.find(" | "), text.find("  ")]
+        .into_iter()
+        .flatten()
+        .min()
+        .unwrap_or(text.len());
+    text[..end].trim()
+}
+
+fn starts_with_any_label(text: &str, fields: &[FieldSpec]) -> bool {
+    fields
+        .iter()
+        .flat_map(|f| f.labels())
+        .any(|label| after_label(text, &label).is_some())
+}
+
+/// Convert a raw value to `field`'s type and check it against the schema.
+pub fn coerce(field: &FieldSpec, raw: &Value) -> Result<Value, String> {
+    let text = match raw {
+        Value::String(s) => s.trim().to_string(),
+        Value::Null => return Err("no value".into()),
+        other => other.to_string(),
+    };
+    if text.is_empty() {
+        return Err("no value".into());
+    }
+    let value = match field.field_type {
+        FieldType::String => json!(text),
+        FieldType::Number => {
+            json!(parse_amount(&text).ok_or(format!("expected a number, found '{text}'"))?)
+        }
+        FieldType::Integer => {
+            let number =
+                parse_amount(&text).ok_or(format!("expected a whole number, found '{text}'"))?;
+            if number.fract() != 0.0 {
+                return Err(format!("expected a whole number, found '{text}'"));
+            }
+            json!(number as i64)
+        }
+        FieldType::Date => {
+            json!(parse_date(&text).ok_or(format!("expected a date, found '{text}'"))?)
+        }
+        FieldType::Boolean => {
+            json!(parse_bool(&text).ok_or(format!("expected yes or no, found '{text}'"))?)
+        }
+    };
+    if let (Some(pattern), Value::String(s)) = (&field.pattern, &value) {
+        if !Regex::new(pattern).is_ok_and(|re| re.is_match(s)) {
+            return Err(format!("'{s}' does not match {pattern}"));
+        }
+    }
+    Ok(value)
+}
+
+/// A number as printed on a document: currency signs and thousands
+/// separators are ignored, parentheses mean a negative amount, and Korean
+/// units scale it ("1.5만원" is 15000).
+fn parse_amount(text: &str) -> Option<f64> {
+    let re = Regex::new(r"(-)?\s*[₩$€£¥]?\s*(\d[\d,]*(?:\.\d+)?)\s*(억|만|천)?").ok()?;
+    let caps = re.captures(text)?;
+    let mut value: f64 = caps[2].replace(',', "").parse().ok()?;
+    value *= match caps.get(3).map(|m| m.as_str()) {
+        Some("억") => 100_000_000.0,
+        Some("만") => 10_000.0,
+        Some("천") => 1_000.0,
+        _ => 1.0,
+    };
+    let negative = caps.get(1).is_some()
+        || (text.trim_start().starts_with('(') && text.trim_end().ends_with(')'));
+    Some(if negative { -value } else { value })
+}
+
+/// A date in a common printed form, as `YYYY-MM-DD`.
+fn parse_date(text: &str) -> Option<String> {
+    use chrono::NaiveDate;
+    let ymd = |y: &str, m: &str, d: &str| {
+        NaiveDate::from_ymd_opt(y.parse().ok()?, m.parse().ok()?, d.parse().ok()?)
+            .map(|date| date.format("%Y-%m-%d").to_string())
+    };
+    if let Some(c) =
+        Regex::new(r"(\d{4})\s*(?:년\s*|[./-])\s*(\d{1,2})\s*(?:월\s*|[./-])\s*(\d{1,2})")
+            .ok()?
+            .captures(text)
+    {
+        return ymd(&c[1], &c[2], &c[3]);
+    }
+    // Day first, as on European and most international forms
+    if let Some(c) = Regex::new(r"\b(\d{1,2})[./-](\d{1,2})[./-](\d{4})\b")
+        .ok()?
+        .captures(text)
+    {
+        return ymd(&c[3], &c[2], &c[1]);
+    }
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    let month = |name: &str| {
+        let name = name.to_lowercase();
+        MONTHS
+            .iter()
+            .position(|m| name.starts_with(m))
+            .map(|i| (i + 1).to_string())
+    };
+    let lower = text.to_lowercase();
+    if let Some(c) = Regex::new(r"([a-z]{3,9})\.?\s+(\d{1,2}),?\s+(\d{4})")
+        .ok()?
+        .captures(&lower)
+    {
+        return ymd(&c[3], &month(&c[1])?, &c[2]);
+    }
+    if let Some(c) = Regex::new(r"(\d{1,2})\s+([a-z]{3,9})\.?,?\s+(\d{4})")
+        .ok()?
+        .captures(&lower)
+    {
+        return ymd(&c[3], &month(&c[2])?, &c[1]);
+    }
+    None
+}
+
+fn parse_bool(text: &str) -> Option<bool> {
+    match text.trim().to_lowercase().as_str() {
+        "yes" | "y" | "true" | "checked" | "x" | "☑" | "☒" | "✓" | "✔" | "예" | "네" | "있음"
+        | "해당" | "o" => Some(true),
+        "no" | "n" | "false" | "unchecked" | "☐" | "아니오" | "아니요" | "없음" | "해당없음"
+        | "해당 없음" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_typed_values_next_to_labels() {
+        let fields = parse_schema(&json!([
+            { "name": "invoice_number", "labels": ["Invoice No", "송장번호"], "required": true, "pattern": "^INV-" },
+            { "name": "issue_date", "labels": ["Date", "발행일"], "type": "date" },
+            { "name": "total", "labels": ["Total Due", "합계금액"], "type": "number", "required": true },
+            { "name": "vat_included", "labels": ["부가세 포함"], "type": "boolean" },
+            { "name": "supplier", "labels": ["공급자"] },
+            "notes"
+        ]))
+        .unwrap();
+        let text = "INVOICE\n\
+            Invoice No: INV-2024-031    Date: 5 March 2024\n\
+            공급자\n(주)한빛건설\n\
+            합계금액 : ₩12,500,000\n\
+            부가세 포함 | 예\n\
+            Updated 2024-03-06";
+        let raw = find_by_layout(text, &fields);
+        assert_eq!(raw["invoice_number"], "INV-2024-031");
+        assert_eq!(raw["supplier"], "(주)한빛건설");
+        assert!(!raw.contains_key("notes"));
+
+        let value =
+            |name: &str| coerce(fields.iter().find(|f| f.name == name).unwrap(), &raw[name]);
+        assert_eq!(value("issue_date").unwrap(), "2024-03-05");
+        assert_eq!(value("total").unwrap(), json!(12_500_000.0));
+        assert_eq!(value("vat_included").unwrap(), true);
+        assert!(coerce(&fields[0], &json!("2024-031"))
+            .unwrap_err()
+            .contains("does not match"));
+        assert_eq!(parse_amount("(1,200.50)"), Some(-1200.5));
+        assert_eq!(parse_date("2024년 3월 5일"), Some("2024-03-05".into()));
+    }
+
+    #[test]
+    fn reads_json_schema_fields() {
+        let fields = parse_schema(&json!({
+            "type": "object",
+            "properties": {
+                "permit_no": { "type": "string", "x-labels": ["허가번호"] },
+                "expires": { "type": "string", "format": "date", "title": "만료일" },
+                "floors": { "type": "integer" }
+            },
+            "required": ["permit_no"]
+        }))
+        .unwrap();
+        let permit = fields.iter().find(|f| f.name == "permit_no").unwrap();
+        assert!(permit.required);
+        assert_eq!(permit.labels, ["허가번호"]);
+        let expires = fields.iter().find(|f| f.name == "expires").unwrap();
+        assert_eq!(
+            (expires.field_type, expires.labels[0].as_str()),
+            (FieldType::Date, "만료일")
+        );
+        assert!(coerce(
+            fields.iter().find(|f| f.name == "floors").unwrap(),
+            &json!("3.5층")
+        )
+        .is_err());
+        assert!(parse_schema(&json!({ "properties": { "x": { "type": "array" } } })).is_err());
+    }
+}
@@ -0,0 +1,349 @@
+//! Knowledge graph store for `graph-extract` and `graph-query`.
+//!
+//! Facts are (subject, predicate, object) triples kept in a SQLite file, each
+//! with its provenance: the document it was read from, the chunk of that
+//! document, and the sentence that states it. The same fact found in two
+//! documents is kept once per document, so answers can cite every source.
+//! One file holds any number of named graphs. Entities and predicates match
+//! without regard to case, and `*` in a pattern matches any run of text.
+
+use crate::ExecutorError;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// A fact with its provenance.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Triple {
+    pub subject: String,
+    pub predicate: String,
+    pub object: String,
+    /// Document the fact was extracted from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Index of the chunk of `source` the fact was extracted from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk: Option<u64>,
+    /// The passage that states the fact.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evidence: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+}
+
+/// Triples to find; `None` matches anything.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pattern {
+    pub subject: Option<String>,
+    pub predicate: Option<String>,
+    pub object: Option<String>,
+}
+
+/// The entities reachable from a starting entity and the triples that
+/// connect them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Neighborhood {
+    /// Reached entities, nearest first, starting with the entity asked for.
+    pub entities: Vec<String>,
+    pub triples: Vec<Triple>,
+}
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS triples (
+        id INTEGER PRIMARY KEY,
+        graph TEXT NOT NULL,
+        subject TEXT NOT NULL,
+        predicate TEXT NOT NULL,
+        object TEXT NOT NULL,
+        source TEXT,
+        chunk INTEGER,
+        evidence TEXT,
+        confidence REAL,
+        created_at TEXT NOT NULL
+    );
+    CREATE UNIQUE INDEX IF NOT EXISTS triples_fact ON triples
+        (graph, subject, predicate, object, COALESCE(source, ''), COALESCE(chunk, -1));
+    CREATE INDEX IF NOT EXISTS triples_subject ON triples (graph, subject COLLATE NOCASE);
+    CREATE INDEX IF NOT EXISTS triples_object ON triples (graph, object COLLATE NOCASE);
+";
+
+const COLUMNS: &str = "subject, predicate, object, source, chunk, evidence, confidence";
+
+/// One named graph in a SQLite file.
+pub struct GraphStore {
+    conn: Connection,
+    graph: String,
+}
+
+impl GraphStore {
+    /// Open `graph` in the file at `path`, creating both if needed.
+    pub fn open(path: &Path, graph: &str) -> Result<Self, ExecutorError> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                ExecutorError::ExecutionFailed(format!("Cannot create {}: {e}", parent.display()))
+            })?;
+        }
+        let conn = Connection::open(path).map_err(|e| {
+            ExecutorError::ExecutionFailed(format!(
+                "Cannot open graph store {}: {e}",
+                path.display()
+            ))
+        })?;
+        conn.execute_batch(SCHEMA).map_err(db_error)?;
+        Ok(Self {
+            conn,
+            graph: graph.to_string(),
+        })
+    }
+
+    /// Add triples, skipping ones already stored and ones with an empty part.
+    /// Returns how many were added.
+    pub fn insert(&mut self, triples: &[Triple]) -> Result<usize, ExecutorError> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let tx = self.conn.transaction().map_err(db_error)?;
+        let mut added = 0;
+        {
+            let mut insert = tx
+                .prepare(
+                    "INSERT OR IGNORE INTO triples
+                     (graph, subject, predicate, object, source, chunk, evidence, confidence, created_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                )
+                .map_err(db_error)?;
+            for triple in triples {
+                let (subject, predicate, object) = (
+                    triple.subject.trim(),
+                    triple.predicate.trim(),
+                    triple.object.trim(),
+                );
+                if subject.is_empty() || predicate.is_empty() || object.is_empty() {
+                    continue;
+                }
+                added += insert
+                    .execute(params![
+                        self.graph,
+                        subject,
+                        predicate,
+                        object,
+                        triple.source,
+                        triple.chunk.map(|c| c as i64),
+                        triple.evidence,
+                        triple.confidence,
+                        now,
+                    ])
+                    .map_err(db_error)?;
+            }
+        }
+        tx.commit().map_err(db_error)?;
+        Ok(added)
+    }
+
+    /// Triples matching `pattern`, oldest first, at most `limit` of them.
+    pub fn find(&self, pattern: &Pattern, limit: usize) -> Result<Vec<Triple>, ExecutorError> {
+        let mut sql = format!("SELECT {COLUMNS} FROM triples WHERE graph = ?1");
+        let mut values = vec![self.graph.clone()];
+        for (column, value) in [
+            ("subject", &pattern.subject),
+            ("predicate", &pattern.predicate),
+            ("object", &pattern.object),
+        ] {
+            let Some(value) = value
+                .as_deref()
+                .map(str::trim)
+                .filter(|v| !v.is_empty() && *v != "*")
+            else {
+                continue;
+            };
+            values.push(like_pattern(value));
+            sql.push_str(&format!(" AND {column} LIKE ?{} ESCAPE '\\'", values.len()));
+        }
+        sql.push_str(&format!(" ORDER BY id LIMIT {limit}"));
+
+        let mut statement = self.conn.prepare(&sql).map_err(db_error)?;
+        let rows = statement
+            .query_map(rusqlite::params_from_iter(values), row_to_triple)
+            .map_err(db_error)?;
+        rows.collect::<Result<_, _>>().map_err(db_error)
+    }
+
+    /// Entities within `depth` hops of `entity`, following relations in
+    /// either direction, with at most `limit` connecting triples.
+    pub fn neighborhood(
+        &self,
+        entity: &str,
+        depth: usize,
+        limit: usize,
+    ) -> Result<Neighborhood, ExecutorError> {
+        let mut statement = self
+            .conn
+            .prepare(&format!(
+                "SELECT id, {COLUMNS} FROM triples
+                 WHERE graph = ?1 AND (subject = ?2 COLLATE NOCASE OR object = ?2 COLLATE NOCASE)
+                 ORDER BY id"
+            ))
+            .map_err(db_error)?;
+
+        // Start from the stored spelling of the entity when there is one
+        let start = self
+            .conn
+            .query_row(
+                "SELECT CASE WHEN subject = ?2 COLLATE NOCASE THEN subject ELSE object END
+                 FROM triples
+                 WHERE graph = ?1 AND (subject = ?2 COLLATE NOCASE OR object = ?2 COLLATE NOCASE)
+                 ORDER BY id LIMIT 1",
+                params![self.graph, entity.trim()],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+            .map_err(db_error)?
+            .unwrap_or_else(|| entity.trim().to_string());
+
+        let mut result = Neighborhood {
+            entities: vec![start.clone()],
+            triples: Vec::new(),
+        };
+        let mut reached: HashSet<String> = HashSet::from([start.to_lowercase()]);
+        let mut seen_triples = HashSet::new();
+        let mut frontier = vec![start];
+        for _ in 0..depth {
+            let mut next = Vec::new();
+            for current in &frontier {
+                let rows = statement
+                    .query_map(params![self.graph, current], |row| {
+                        Ok((row.get::<_, i64>(0)?, row_to_triple_at(row, 1)?))
+                    })
+                    .map_err(db_error)?;
+                for row in rows {
+                    let (id, triple) = row.map_err(db_error)?;
+                    if result.triples.len() >= limit {
+                        return Ok(result);
+                    }
+                    if !seen_triples.insert(id) {
+                        continue;
+                    }
+                    let other = if triple.subject.eq_ignore_ascii_case(current) {
+                        &triple.object
+                    } else {
+                        &triple.subject
+                    };
+                    if reached.insert(other.to_lowercase()) {
+                        result.entities.push(other.clone());
+                        next.push(other.clone());
+                    }
+                    result.triples.push(triple);
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            frontier = next;
+        }
+        Ok(result)
+    }
+}
+
+/// A `LIKE` pattern matching `value` exactly, with `*` as a wildcard.
+fn like_pattern(value: &str) -> String {
+    let mut pattern = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' | '%' | '_' => {
+                pattern.push('\\');
+                pattern.push(c);
+            }
+            '*' => pattern.push('%'),
+            c => pattern.push(c),
+        }
+    }
+    pattern
+}
+
+fn row_to_triple(row: &rusqlite::Row) -> rusqlite::Result<Triple> {
+    row_to_triple_at(row, 0)
+}
+
+fn row_to_triple_at(row: &rusqlite::Row, first: usize) -> rusqlite::Result<Triple> {
+    Ok(Triple {
+        subject: row.get(first)?,
+        predicate: row.get(first + 1)?,
+        object: row.get(first + 2)?,
+        source: row.get(first + 3)?,
+        chunk: row.get::<_, Option<i64>>(first + 4)?.map(|c| c as u64),
+        evidence: row.get(first + 5)?,
+        confidence: row.get(first + 6)?,
+    })
+}
+
+fn db_error(e: rusqlite::Error) -> ExecutorError {
+    ExecutorError::ExecutionFailed(format!("Graph store error: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triple(subject: &str, predicate: &str, object: &str, source: &str) -> Triple {
+        Triple {
+            subject: subject.into(),
+            predicate: predicate.into(),
+            object: object.into(),
+            source: Some(source.into()),
+            chunk: Some(0),
+            evidence: None,
+            confidence: None,
+        }
+    }
+
+    #[test]
+    fn stores_facts_once_per_source_and_walks_neighborhoods() {
+        let path = std::env::temp_dir()
+            .join(format!("hb-graph-{}", uuid::Uuid::new_v4()))
+            .join("kg.sqlite");
+        let mut store = GraphStore::open(&path, "contracts").unwrap();
+        let facts = [
+            triple("Acme", "supplies", "Steel beams", "a.pdf"),
+            triple("Acme", "supplies", "Steel beams", "b.pdf"),
+            triple("Steel beams", "conforms_to", "KS D 3503", "a.pdf"),
+            triple("KS D 3503", "superseded_by", "KS D 3503:2021", "std.pdf"),
+            triple("Builder Co", "contracts_with", "acme", "a.pdf"),
+            triple("", "supplies", "nothing", "a.pdf"),
+        ];
+        assert_eq!(store.insert(&facts).unwrap(), 5);
+        assert_eq!(store.insert(&facts).unwrap(), 0);
+
+        let sources = store
+            .find(
+                &Pattern {
+                    subject: Some("ACME".into()),
+                    predicate: Some("supplies".into()),
+                    object: None,
+                },
+                10,
+            )
+            .unwrap();
+        assert_eq!(sources.len(), 2);
+        let standards = store
+            .find(
+                &Pattern {
+                    object: Some("KS D 3503*".into()),
+                    ..Default::default()
+                },
+                10,
+            )
+            .unwrap();
+        assert_eq!(standards.len(), 2);
+
+        let near = store.neighborhood("acme", 1, 100).unwrap();
+        assert_eq!(near.entities, ["Acme", "Steel beams", "Builder Co"]);
+        assert_eq!(near.triples.len(), 3);
+        let far = store.neighborhood("acme", 3, 100).unwrap();
+        assert_eq!(far.entities.len(), 5);
+        assert_eq!(store.neighborhood("acme", 3, 2).unwrap().triples.len(), 2);
+
+        // Graphs in the same file are separate
+        let other = GraphStore::open(&path, "standards").unwrap();
+        assert!(other.find(&Pattern::default(), 10).unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
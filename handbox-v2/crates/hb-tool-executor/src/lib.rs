@@ -1,13 +1,34 @@
 //! hb-tool-executor: Isolated tool execution via multiple runtimes.
 
+pub mod batch;
+pub mod bedrock;
+pub mod command;
+pub mod doc_compare;
 pub mod docker;
+pub mod encoding;
+pub mod fields;
+pub mod graph;
 pub mod local;
+pub mod meeting;
+pub mod pdf_table;
+pub mod progress;
+pub mod public_api;
 pub mod python;
+pub mod secrets;
+pub mod splitter;
+pub mod stream;
 pub mod timeout;
 pub mod wasm;
 
+pub use command::{CommandGate, CommandRequest};
+use hb_core::project::WorkspaceContext;
 use hb_core::tool::RuntimeSpec;
+pub use progress::{Heartbeat, Progress, ProgressReporter, ProgressSink};
+pub use secrets::Secrets;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+pub use stream::{ChunkSink, LineChunks, OutputChunk, OutputStream};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -20,6 +41,27 @@ pub enum ExecutorError {
     UnsupportedRuntime(String),
     #[error("process error: {0}")]
     Process(String),
+    #[error("missing credential: {0}")]
+    MissingCredential(String),
+    #[error("not allowed: {0}")]
+    NotAllowed(String),
+}
+
+impl From<ExecutorError> for hb_core::error::AppError {
+    fn from(e: ExecutorError) -> Self {
+        use hb_core::error::{AppError, ErrorCode};
+        match e {
+            ExecutorError::MissingCredential(_) => AppError::new(ErrorCode::MissingCredential, e.to_string())
+                .with_hint("Add an API key or local endpoint in Settings → LLM providers."),
+            ExecutorError::Timeout(_) => AppError::new(ErrorCode::Timeout, e.to_string()),
+            ExecutorError::NotAllowed(_) => AppError::new(ErrorCode::PermissionDenied, e.to_string())
+                .with_hint("Add the program to the workspace's command allowlist, or approve it when asked."),
+            ExecutorError::UnsupportedRuntime(_) => AppError::new(ErrorCode::InvalidInput, e.to_string()),
+            ExecutorError::ExecutionFailed(_) | ExecutorError::Process(_) => {
+                AppError::new(ErrorCode::Execution, e.to_string())
+            }
+        }
+    }
 }
 
 /// Input passed to a tool executor.
@@ -32,6 +74,76 @@ pub struct ToolInput {
     /// When set, LLM tools use this provider instead of auto-detecting from env vars.
     #[serde(default)]
     pub llm_provider: Option<String>,
+    /// Workspace environment variables, set on process-based runtimes.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Where long-running tools report progress (dropped by default).
+    #[serde(skip)]
+    pub progress: ProgressReporter,
+    /// Where tools stream partial output (dropped by default).
+    #[serde(skip)]
+    pub stream: OutputStream,
+    /// Which programs shell-exec may start (none by default).
+    #[serde(skip)]
+    pub commands: CommandGate,
+    /// Secrets process runtimes may set as variables (none by default).
+    #[serde(skip)]
+    pub secrets: Secrets,
+    /// The execution's scratch directory for intermediate files, removed
+    /// after the run (none outside a run).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scratch_dir: Option<PathBuf>,
+}
+
+/// Prefix of paths inside the execution's scratch directory, e.g.
+/// `scratch://pages/1.json`.
+pub const SCRATCH_SCHEME: &str = "scratch://";
+
+impl ToolInput {
+    /// The workspace the run belongs to, from the reserved `_context` input.
+    pub fn workspace(&self) -> Option<WorkspaceContext> {
+        WorkspaceContext::from_inputs(&self.inputs)
+    }
+
+    /// Root of the run's workspace, if it has one.
+    pub fn root_dir(&self) -> Option<PathBuf> {
+        self.inputs
+            .get(WorkspaceContext::INPUT_KEY)?
+            .get("root_path")?
+            .as_str()
+            .filter(|root| !root.trim().is_empty())
+            .map(hb_core::path::from_user_input)
+    }
+
+    /// A path from config or inputs to read: relative paths are taken from
+    /// the workspace root, not the app's working directory, and
+    /// `scratch://` paths from the scratch directory.
+    pub fn resolve_path(&self, raw: &str) -> PathBuf {
+        if let Some(path) = self.scratch_path(raw) {
+            return path;
+        }
+        hb_core::path::resolve_in(raw, self.root_dir().as_deref())
+    }
+
+    /// A path from config or inputs to write to, resolved like
+    /// [`resolve_path`](Self::resolve_path).
+    pub fn output_path(&self, raw: &str) -> PathBuf {
+        if let Some(path) = self.scratch_path(raw) {
+            return path;
+        }
+        hb_core::path::for_io(&hb_core::path::from_user_input_in(raw, self.root_dir().as_deref()))
+    }
+
+    /// `raw` inside the scratch directory, if it is a `scratch://` path.
+    /// Components that would leave the directory are dropped.
+    fn scratch_path(&self, raw: &str) -> Option<PathBuf> {
+        let relative = raw.trim().strip_prefix(SCRATCH_SCHEME)?;
+        let dir = self.scratch_dir.as_ref()?;
+        let inside = std::path::Path::new(relative)
+            .components()
+            .filter(|c| matches!(c, std::path::Component::Normal(_)));
+        Some(inside.fold(dir.clone(), |path, c| path.join(c)))
+    }
 }
 
 /// Output returned from a tool executor.
@@ -47,8 +159,10 @@ pub async fn execute(
     input: &ToolInput,
 ) -> Result<ToolOutput, ExecutorError> {
     match runtime {
-        RuntimeSpec::Native => local::execute_native(input).await,
-        RuntimeSpec::Process { command } => local::execute_process(command, input).await,
+        // Boxed: the native dispatch future is large, and nested scheduler
+        // nodes would carry it on the stack
+        RuntimeSpec::Native => input.progress.keep_alive(Box::pin(local::execute_native(input))).await,
+        RuntimeSpec::Process(process) => local::execute_process(process, input).await,
         RuntimeSpec::Python { script } => python::execute(script, input).await,
         RuntimeSpec::Docker { image } => docker::execute(image, input).await,
         RuntimeSpec::Wasm { module } => wasm::execute(module, input).await,
@@ -1,7 +1,17 @@
 //! Local executor — native in-process tool implementations and child process spawning.
 
-use crate::{ExecutorError, ToolInput, ToolOutput};
-use std::time::Instant;
+use crate::batch::{BatchConfig, Batcher};
+use crate::bedrock;
+use crate::command::CommandRequest;
+use crate::encoding;
+use crate::graph::{GraphStore, Pattern, Triple};
+use crate::progress::PROGRESS_ENV;
+use crate::splitter::{SplitConfig, Splitter, Strategy};
+use crate::{ExecutorError, LineChunks, OutputStream, Progress, ProgressReporter, ToolInput, ToolOutput};
+use hb_core::path;
+use hb_core::tool::{fill_placeholders, EnvValue, ProcessSpec};
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
 
 /// Execute a tool natively in-process.
 pub async fn execute_native(input: &ToolInput) -> Result<ToolOutput, ExecutorError> {
@@ -11,7 +21,7 @@ pub async fn execute_native(input: &ToolInput) -> Result<ToolOutput, ExecutorErr
     let tool_name = input
         .tool_ref
         .split('/')
-        .last()
+        .next_back()
         .unwrap_or(&input.tool_ref)
         .split('@')
         .next()
@@ -19,19 +29,23 @@ pub async fn execute_native(input: &ToolInput) -> Result<ToolOutput, ExecutorErr
 
     tracing::info!("[Executor] tool_ref='{}' -> tool_name='{}'", input.tool_ref, tool_name);
 
+    // Synchronous tools run on the blocking pool, so that a node time limit
+    // can give up on them instead of waiting for them to return
+    if let Some(tool) = blocking_tool(tool_name) {
+        let owned = input.clone();
+        let outputs = tokio::task::spawn_blocking(move || tool(&owned))
+            .await
+            .map_err(|e| ExecutorError::ExecutionFailed(format!("Tool '{tool_name}' panicked: {e}")))??;
+        return Ok(ToolOutput {
+            outputs,
+            duration_ms: start.elapsed().as_millis() as u64,
+        });
+    }
+
     let outputs = match tool_name {
-        "file-read" => execute_file_read(input)?,
-        "pdf-read" => execute_pdf_read(input)?,
-        "file-write" => execute_file_write(input)?,
-        "text-split" => execute_text_split(input)?,
-        "text-merge" => execute_text_merge(input)?,
-        "text-template" => execute_text_template(input)?,
-        "json-parse" => execute_json_parse(input)?,
-        "json-path" => execute_json_path(input)?,
-        "csv-read" => execute_csv_read(input)?,
-        "data-filter" => execute_data_filter(input)?,
-        "regex-extract" => execute_regex_extract(input)?,
-        "merge" => execute_merge(input)?,
+        "pdf-table-extract" => execute_pdf_table_extract(input).await?,
+        "doc-extract-fields" => execute_doc_extract_fields(input).await?,
+        "doc-compare" => execute_doc_compare(input).await?,
         "delay" => execute_delay(input).await?,
         "display-output" => {
             // Pass through any data input
@@ -46,31 +60,26 @@ pub async fn execute_native(input: &ToolInput) -> Result<ToolOutput, ExecutorErr
                 .to_string();
             serde_json::json!({ "text": text })
         }
-        "condition" => execute_condition(input)?,
         // LLM tools
         "llm-chat" => execute_llm_chat(input).await?,
         "llm-summarize" => execute_llm_summarize(input).await?,
+        "summarize-long" => execute_summarize_long(input).await?,
         "embedding" => execute_embedding(input).await?,
-        "vector-store" => execute_vector_store(input)?,
-        "vector-search" => execute_vector_search(input)?,
         "reranker" => execute_reranker(input).await?,
+        // Knowledge graph tools
+        "graph-extract" => execute_graph_extract(input).await?,
+        // Meeting tools
+        "audio-transcribe" => execute_audio_transcribe(input).await?,
+        "speaker-diarize" => execute_speaker_diarize(input).await?,
+        "meeting-minutes" => execute_meeting_minutes(input).await?,
         // System tools (workflow node execution)
         "bash-execute" | "bash" | "shell" => execute_bash(input).await?,
+        "shell-exec" => execute_shell_exec(input).await?,
         "grep-search" | "code-search" => execute_grep(input).await?,
         "web-search" => execute_web_search(input).await?,
         "web-fetch" | "http-fetch" => execute_web_fetch(input).await?,
         "http-request" => execute_http_request(input).await?,
-        // GIS tools
-        "gis-read" | "geojson-read" => execute_gis_read(input)?,
-        "gis-write" | "geojson-write" => execute_gis_write(input)?,
-        "gis-transform" | "crs-transform" => execute_gis_transform(input)?,
-        // IFC tools
-        "ifc-read" => execute_ifc_read(input)?,
-        "ifc-query" => execute_ifc_query(input)?,
-        // Multi-file read
-        "files-read" => execute_files_read(input)?,
-        // Folder read
-        "folder-read" => execute_folder_read(input)?,
+        "public-api-fetch" => execute_public_api_fetch(input).await?,
         // Agent task (delegate to agent loop — returns stub here, real exec in hb-tauri)
         "agent-task" => {
             serde_json::json!({
@@ -95,9 +104,64 @@ pub async fn execute_native(input: &ToolInput) -> Result<ToolOutput, ExecutorErr
     })
 }
 
+type BlockingTool = fn(&ToolInput) -> Result<serde_json::Value, ExecutorError>;
+
+/// Native tools that do their work synchronously. [`execute_native`] runs
+/// them on the blocking pool; a thread given up on still runs until the
+/// tool returns.
+fn blocking_tool(tool_name: &str) -> Option<BlockingTool> {
+    Some(match tool_name {
+        "file-read" => execute_file_read,
+        "pdf-read" => execute_pdf_read,
+        "clause-split" => execute_clause_split,
+        "file-write" => execute_file_write,
+        "text-split" => execute_text_split,
+        "file-split" => execute_file_split,
+        "text-merge" => execute_text_merge,
+        "text-template" => execute_text_template,
+        "json-parse" => execute_json_parse,
+        "json-path" => execute_json_path,
+        "csv-read" => execute_csv_read,
+        "data-filter" => execute_data_filter,
+        "regex-extract" => execute_regex_extract,
+        "merge" => execute_merge,
+        "condition" => execute_condition,
+        "vector-store" => execute_vector_store,
+        "vector-search" => execute_vector_search,
+        "graph-query" => execute_graph_query,
+        // GIS tools
+        "gis-read" | "geojson-read" => execute_gis_read,
+        "gis-write" | "geojson-write" => execute_gis_write,
+        "gis-transform" | "crs-transform" => execute_gis_transform,
+        // IFC tools
+        "ifc-read" => execute_ifc_read,
+        "ifc-query" => execute_ifc_query,
+        "files-read" => execute_files_read,
+        "folder-read" => execute_folder_read,
+        _ => return None,
+    })
+}
+
 /// Execute a tool by spawning a child process.
 pub async fn execute_process(
-    command: &str,
+    spec: &ProcessSpec,
+    input: &ToolInput,
+) -> Result<ToolOutput, ExecutorError> {
+    let launch = ProcessLaunch::new(spec, input)?;
+    let mut command = tokio::process::Command::new(&launch.program);
+    command.args(&launch.args).envs(&input.env).envs(&launch.env);
+    if let Some(dir) = &launch.working_dir {
+        command.current_dir(dir);
+    }
+    run_child(command, &launch.program, input).await
+}
+
+/// Run a tool's process: the node's input as JSON on stdin, its result as
+/// JSON on stdout, and progress lines on stderr (see [`crate::progress`]),
+/// which `HB_PROGRESS=1` in its environment tells it are read.
+pub(crate) async fn run_child(
+    mut command: tokio::process::Command,
+    program: &str,
     input: &ToolInput,
 ) -> Result<ToolOutput, ExecutorError> {
     let start = Instant::now();
@@ -105,14 +169,17 @@ pub async fn execute_process(
     let input_json =
         serde_json::to_string(&input).map_err(|e| ExecutorError::ExecutionFailed(e.to_string()))?;
 
-    let mut child = tokio::process::Command::new(command)
+    // Killed if the runner gives up on the node, e.g. when it hangs
+    let mut child = command
+        .env(PROGRESS_ENV, "1")
+        .kill_on_drop(true)
         .stdin(std::process::Stdio::piped())
         .stdout(std::process::Stdio::piped())
         .stderr(std::process::Stdio::piped())
         .spawn()
-        .map_err(|e| ExecutorError::Process(e.to_string()))?;
+        .map_err(|e| ExecutorError::Process(format!("Failed to start '{program}': {e}")))?;
 
-    if let Some(stdin) = child.stdin.as_mut() {
+    if let Some(mut stdin) = child.stdin.take() {
         use tokio::io::AsyncWriteExt;
         stdin
             .write_all(input_json.as_bytes())
@@ -120,22 +187,65 @@ pub async fn execute_process(
             .map_err(|e| ExecutorError::Process(e.to_string()))?;
     }
 
-    let output = child
-        .wait_with_output()
-        .await
-        .map_err(|e| ExecutorError::Process(e.to_string()))?;
+    // Forward progress lines as they arrive; keep the rest for error reports
+    let stderr_task = child.stderr.take().map(|stderr| {
+        let progress = input.progress.clone();
+        tokio::spawn(async move {
+            use tokio::io::AsyncBufReadExt;
+            let mut lines = tokio::io::BufReader::new(stderr).lines();
+            let mut rest = String::new();
+            while let Ok(Some(line)) = lines.next_line().await {
+                progress.beat();
+                match Progress::parse_line(&line) {
+                    Some(update) => progress.report(update),
+                    None => {
+                        rest.push_str(&line);
+                        rest.push('\n');
+                    }
+                }
+            }
+            rest
+        })
+    });
+
+    // Stream stdout as it arrives; the whole of it is the tool's result
+    let stdout_task = child.stdout.take().map(|stdout| {
+        let mut lines = input.stream.lines("stdout");
+        tokio::spawn(async move {
+            use tokio::io::AsyncReadExt;
+            let mut stdout = stdout;
+            let (mut all, mut buf) = (Vec::new(), [0u8; 8192]);
+            while let Ok(n) = stdout.read(&mut buf).await {
+                if n == 0 {
+                    break;
+                }
+                lines.write(&buf[..n]);
+                all.extend_from_slice(&buf[..n]);
+            }
+            lines.finish();
+            all
+        })
+    });
+
+    let status = child.wait().await.map_err(|e| ExecutorError::Process(e.to_string()))?;
+    let stdout = match stdout_task {
+        Some(task) => task.await.unwrap_or_default(),
+        None => Vec::new(),
+    };
+    let stderr = match stderr_task {
+        Some(task) => task.await.unwrap_or_default(),
+        None => String::new(),
+    };
 
     let duration_ms = start.elapsed().as_millis() as u64;
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
+    if !status.success() {
         return Err(ExecutorError::ExecutionFailed(format!(
-            "Process exited with {}: {stderr}",
-            output.status
+            "Process exited with {status}: {stderr}"
         )));
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stdout = String::from_utf8_lossy(&stdout);
     let outputs: serde_json::Value =
         serde_json::from_str(&stdout).unwrap_or(serde_json::json!({ "raw": stdout.to_string() }));
 
@@ -145,6 +255,98 @@ pub async fn execute_process(
     })
 }
 
+/// Program, arguments, variables and directory for one process run, with
+/// the node's placeholders filled in.
+#[derive(Debug, PartialEq)]
+struct ProcessLaunch {
+    program: String,
+    args: Vec<String>,
+    env: BTreeMap<String, String>,
+    working_dir: Option<std::path::PathBuf>,
+}
+
+impl ProcessLaunch {
+    fn new(spec: &ProcessSpec, input: &ToolInput) -> Result<Self, ExecutorError> {
+        let empty = serde_json::Map::new();
+        let config = input.config.as_object().unwrap_or(&empty);
+        let fill = |text: &str, what: &str| {
+            fill_placeholders(text, config, &input.inputs).ok_or_else(|| {
+                ExecutorError::ExecutionFailed(format!(
+                    "{what} '{text}' uses a placeholder this node doesn't set"
+                ))
+            })
+        };
+
+        // A command that names an existing file may contain spaces
+        let (program, args) = if spec.args.is_empty() && !std::path::Path::new(&spec.command).exists() {
+            let mut words = split_words(&spec.command).into_iter();
+            let program = words.next().unwrap_or_default();
+            (program, words.collect())
+        } else {
+            let args = spec.args.iter().map(|arg| fill(arg, "Argument")).collect::<Result<_, _>>()?;
+            (spec.command.clone(), args)
+        };
+        if program.is_empty() {
+            return Err(ExecutorError::Process("Process runtime has no command".into()));
+        }
+
+        let mut env = BTreeMap::new();
+        for (name, value) in &spec.env {
+            let value = match value {
+                EnvValue::Literal(text) => fill(text, &format!("Variable {name}"))?,
+                EnvValue::Secret { secret } => input
+                    .secrets
+                    .get(secret)
+                    .ok_or_else(|| {
+                        ExecutorError::MissingCredential(format!(
+                            "{name} needs the secret {secret}, which is not in the credential store"
+                        ))
+                    })?
+                    .to_string(),
+            };
+            env.insert(name.clone(), value);
+        }
+
+        let working_dir = match &spec.working_dir {
+            Some(dir) => {
+                let dir = input.resolve_path(&fill(dir, "Working directory")?);
+                if !dir.is_dir() {
+                    return Err(ExecutorError::Process(format!(
+                        "Working directory '{}' does not exist",
+                        path::display(&dir)
+                    )));
+                }
+                Some(dir)
+            }
+            // Processes start in the workspace root when there is one
+            None => input.root_dir().filter(|root| root.is_dir()),
+        };
+
+        Ok(Self { program, args, env, working_dir })
+    }
+}
+
+/// Split a command line into words on whitespace; single or double quotes
+/// group words. Backslashes are kept, for Windows paths.
+fn split_words(command: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote = None;
+    for c in command.chars() {
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (None, _) if c.is_whitespace() => words.extend(word.take()),
+            _ => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    words
+}
+
 // ---- Native tool implementations ----
 
 fn execute_file_read(input: &ToolInput) -> Result<serde_json::Value, ExecutorError> {
@@ -166,11 +368,40 @@ fn execute_file_read(input: &ToolInput) -> Result<serde_json::Value, ExecutorErr
         return execute_pdf_read(input);
     }
 
-    let content = std::fs::read_to_string(path)
+    let decoded = read_text(path, input)?;
+    let size = decoded.text.len();
+
+    Ok(serde_json::json!({
+        "content": decoded.text,
+        "size": size,
+        "encoding": decoded.encoding,
+        "had_errors": decoded.had_errors,
+    }))
+}
+
+/// Read a text file, decoding it with the node's `encoding` config or the
+/// detected encoding.
+fn read_text(path: &str, input: &ToolInput) -> Result<encoding::Decoded, ExecutorError> {
+    let bytes = std::fs::read(input.resolve_path(path))
         .map_err(|e| ExecutorError::ExecutionFailed(format!("Failed to read {path}: {e}")))?;
-    let size = content.len();
+    let decoded = encoding::decode(&bytes, encoding::from_config(&input.config))?;
+    if decoded.had_errors {
+        tracing::warn!("{path} has bytes that aren't valid {}; they were replaced", decoded.encoding);
+    }
+    Ok(decoded)
+}
 
-    Ok(serde_json::json!({ "content": content, "size": size }))
+/// Read the text of a document: a PDF's extracted text, or a text file.
+fn read_document(path: &str, input: &ToolInput) -> Result<String, ExecutorError> {
+    if path.to_lowercase().ends_with(".pdf") {
+        let pdf_input = ToolInput {
+            inputs: serde_json::json!({ "path": path }),
+            ..input.clone()
+        };
+        Ok(execute_pdf_read(&pdf_input)?["content"].as_str().unwrap_or_default().to_string())
+    } else {
+        Ok(read_text(path, input)?.text)
+    }
 }
 
 fn execute_pdf_read(input: &ToolInput) -> Result<serde_json::Value, ExecutorError> {
@@ -190,12 +421,13 @@ fn execute_pdf_read(input: &ToolInput) -> Result<serde_json::Value, ExecutorErro
     }
 
     // Check file exists
-    if !std::path::Path::new(path).exists() {
+    let resolved = input.resolve_path(path);
+    if !resolved.exists() {
         return Err(ExecutorError::ExecutionFailed(format!("PDF file not found: {path}")));
     }
 
     // Read PDF bytes
-    let bytes = std::fs::read(path)
+    let bytes = std::fs::read(&resolved)
         .map_err(|e| ExecutorError::ExecutionFailed(format!("Failed to read PDF file {path}: {e}")))?;
 
     // Extract text from PDF with panic catching (pdf-extract can panic on some fonts)
@@ -228,6 +460,312 @@ fn execute_pdf_read(input: &ToolInput) -> Result<serde_json::Value, ExecutorErro
     }))
 }
 
+async fn execute_pdf_table_extract(input: &ToolInput) -> Result<serde_json::Value, ExecutorError> {
+    use crate::pdf_table::{self, Method, Table, TableOptions};
+
+    let path = input
+        .inputs
+        .get("path")
+        .and_then(|v| v.as_str())
+        .or_else(|| input.config.get("file_path").and_then(|v| v.as_str()))
+        .filter(|p| !p.trim().is_empty())
+        .ok_or_else(|| ExecutorError::ExecutionFailed("Missing 'path' input or 'file_path' config".into()))?;
+    let resolved = input.resolve_path(path);
+    let bytes = std::fs::read(&resolved)
+        .map_err(|e| ExecutorError::ExecutionFailed(format!("Failed to read PDF file {path}: {e}")))?;
+
+    let options = TableOptions {
+        header: input.config.get("header").and_then(|v| v.as_bool()).unwrap_or(true),
+        min_rows: input.config.get("min_rows").and_then(|v| v.as_u64()).unwrap_or(2) as usize,
+        pages: match input.config.get("pages").and_then(|v| v.as_str()) {
+            Some(pages) => pdf_table::parse_pages(pages).map_err(ExecutorError::ExecutionFailed)?,
+            None => Vec::new(),
+        },
+    };
+    // lopdf can panic on malformed content streams, like pdf-extract on fonts
+    let mut tables = std::panic::catch_unwind(|| pdf_table::extract_tables(&bytes, &options))
+        .map_err(|_| ExecutorError::ExecutionFailed(format!("Could not read the page layout of {path}")))??;
+
+    // Ask the LLM to rebuild tables the heuristics weren't sure about
+    let repair = input.config.get("llm_repair").and_then(|v| v.as_bool()).unwrap_or(false);
+    let threshold = input.config.get("repair_below").and_then(|v| v.as_f64()).unwrap_or(0.7);
+    let mut repaired = 0;
+    for table in tables.iter_mut().filter(|t| repair && t.confidence < threshold) {
+        let chat_input = ToolInput {
+            tool_ref: input.tool_ref.clone(),
+            inputs: serde_json::json!({ "prompt": table.source_text }),
+            config: serde_json::json!({
+                "system_prompt": "The text is a table read from a PDF; cells may be split, merged or \
+                    shifted into the wrong column. Rebuild the table. Return ONLY JSON of the form \
+                    {\"cells\": [[...], ...]} with one array of strings per row, header row first, \
+                    every row the same length. Keep the cell text exactly as written.",
+                "model": input.config.get("model").cloned().unwrap_or(serde_json::json!("claude-3-haiku-20240307")),
+                "max_tokens": 4096,
+                "temperature": 0.0
+            }),
+            llm_provider: input.llm_provider.clone(),
+            env: input.env.clone(),
+            progress: input.progress.clone(),
+            stream: Default::default(),
+            commands: input.commands.clone(),
+            secrets: input.secrets.clone(),
+            scratch_dir: input.scratch_dir.clone(),
+        };
+        let response = execute_llm_chat(&chat_input).await?;
+        let text = response.get("response").and_then(|v| v.as_str()).unwrap_or("");
+        let cells = text
+            .find('{')
+            .zip(text.rfind('}'))
+            .and_then(|(start, end)| serde_json::from_str::<serde_json::Value>(text.get(start..=end)?).ok())
+            .and_then(|v| serde_json::from_value::<Vec<Vec<String>>>(v.get("cells")?.clone()).ok())
+            .filter(|cells| cells.len() > usize::from(options.header));
+        match cells {
+            Some(cells) => {
+                *table = Table::from_cells(table.id.clone(), table.page, Method::LlmRepaired, cells, options.header, 0.8);
+                repaired += 1;
+            }
+            None => tracing::warn!("[PDF Table] LLM repair of {} returned no usable table", table.id),
+        }
+    }
+
+    Ok(serde_json::json!({
+        "table": tables.first(),
+        "tables": tables,
+        "count": tables.len(),
+        "repaired": repaired
+    }))
+}
+
+async fn execute_doc_extract_fields(input: &ToolInput) -> Result<serde_json::Value, ExecutorError> {
+    use crate::fields;
+
+    let schema = input
+        .config
+        .get("fields")
+        .or_else(|| input.inputs.get("fields"))
+        .ok_or_else(|| ExecutorError::ExecutionFailed("Missing 'fields' config".into()))?;
+    let specs = fields::parse_schema(schema).map_err(ExecutorError::ExecutionFailed)?;
+
+    let text = document_input(input, "text", "path", "file_path")?;
+
+    let mut values = serde_json::Map::new();
+    let mut sources = serde_json::Map::new();
+    let mut errors = std::collections::BTreeMap::new();
+    let raw = fields::find_by_layout(&text, &specs);
+    for spec in &specs {
+        match raw.get(&spec.name).map(|v| fields::coerce(spec, v)) {
+            Some(Ok(value)) => {
+                values.insert(spec.name.clone(), value);
+                sources.insert(spec.name.clone(), serde_json::json!("layout"));
+            }
+            Some(Err(message)) => {
+                errors.insert(spec.name.clone(), message);
+            }
+            None => {}
+        }
+    }
+
+    // Ask the LLM for the fields the layout didn't give a valid value for
+    let pending: Vec<&fields::FieldSpec> = specs.iter().filter(|s| !values.contains_key(&s.name)).collect();
+    let llm_fallback = input.config.get("llm_fallback").and_then(|v| v.as_bool()).unwrap_or(true);
+    if llm_fallback && !pending.is_empty() && !text.trim().is_empty() {
+        let max_chars = input.config.get("max_chars").and_then(|v| v.as_u64()).unwrap_or(12_000) as usize;
+        let document: String = text.chars().take(max_chars).collect();
+        let chat_input = ToolInput {
+            tool_ref: input.tool_ref.clone(),
+            inputs: serde_json::json!({
+                "prompt": format!(
+                    "Fields:\n{}\n\nDocument:\n{document}",
+                    serde_json::to_string_pretty(&pending).unwrap_or_default()
+                )
+            }),
+            config: serde_json::json!({
+                "system_prompt": "Extract the listed fields from the document, which may be an invoice, \
+                    permit or form in any language. Return ONLY a JSON object with one key per field \
+                    name. Copy each value as written in the document; use null when the document does \
+                    not state it. Never guess.",
+                "model": input.config.get("model").cloned().unwrap_or(serde_json::json!("claude-3-haiku-20240307")),
+                "max_tokens": 2048,
+                "temperature": 0.0
+            }),
+            llm_provider: input.llm_provider.clone(),
+            env: input.env.clone(),
+            progress: input.progress.clone(),
+            stream: Default::default(),
+            commands: input.commands.clone(),
+            secrets: input.secrets.clone(),
+            scratch_dir: input.scratch_dir.clone(),
+        };
+        let response = execute_llm_chat(&chat_input).await?;
+        let reply = response.get("response").and_then(|v| v.as_str()).unwrap_or("");
+        let answer = reply
+            .find('{')
+            .zip(reply.rfind('}'))
+            .and_then(|(start, end)| serde_json::from_str::<serde_json::Value>(reply.get(start..=end)?).ok());
+        match answer {
+            Some(answer) => {
+                for spec in pending {
+                    let Some(raw) = answer.get(&spec.name).filter(|v| !v.is_null()) else { continue };
+                    match fields::coerce(spec, raw) {
+                        Ok(value) => {
+                            values.insert(spec.name.clone(), value);
+                            sources.insert(spec.name.clone(), serde_json::json!("llm"));
+                            errors.remove(&spec.name);
+                        }
+                        Err(message) => {
+                            errors.insert(spec.name.clone(), message);
+                        }
+                    }
+                }
+            }
+            None => tracing::warn!("[Doc Fields] LLM fallback returned no JSON object"),
+        }
+    }
+
+    let missing: Vec<&str> = specs
+        .iter()
+        .filter(|s| s.required && !values.contains_key(&s.name) && !errors.contains_key(&s.name))
+        .map(|s| s.name.as_str())
+        .collect();
+    let mut errors: Vec<fields::FieldError> = errors
+        .into_iter()
+        .map(|(field, message)| fields::FieldError { field, message })
+        .collect();
+    errors.extend(missing.iter().map(|name| fields::FieldError {
+        field: name.to_string(),
+        message: "required field not found".into(),
+    }));
+    // Optional fields without a value are null, so the output has every field
+    for spec in &specs {
+        values.entry(spec.name.clone()).or_insert(serde_json::Value::Null);
+    }
+
+    Ok(serde_json::json!({
+        "fields": values,
+        "valid": errors.is_empty(),
+        "errors": errors,
+        "missing": missing,
+        "sources": sources
+    }))
+}
+
+fn execute_clause_split(input: &ToolInput) -> Result<serde_json::Value, ExecutorError> {
+    let text = document_input(input, "text", "path", "file_path")?;
+    let clauses = crate::doc_compare::split_clauses(&text);
+    Ok(serde_json::json!({ "clauses": clauses, "count": clauses.len() }))
+}
+
+async fn execute_doc_compare(input: &ToolInput) -> Result<serde_json::Value, ExecutorError> {
+    use crate::doc_compare::{self, ChangeKind, Severity};
+
+    let old = doc_compare::split_clauses(&document_input(input, "old", "old_path", "old_path")?);
+    let new = doc_compare::split_clauses(&document_input(input, "new", "new_path", "new_path")?);
+    let threshold = input.config.get("threshold").and_then(|v| v.as_f64()).unwrap_or(0.5);
+    let mut changes = doc_compare::compare(&old, &new, threshold);
+
+    // Have the LLM say what each major change means for the parties
+    let review = input.config.get("llm_review").and_then(|v| v.as_bool()).unwrap_or(false);
+    let major: Vec<usize> = (0..changes.len())
+        .filter(|&i| changes[i].severity == Severity::Major)
+        .collect();
+    if review && !major.is_empty() {
+        let listing: Vec<serde_json::Value> = major
+            .iter()
+            .map(|&i| {
+                let change = &changes[i];
+                serde_json::json!({
+                    "id": i,
+                    "kind": change.kind,
+                    "tags": change.tags,
+                    "before": change.old.as_ref().map(|c| &c.text),
+                    "after": change.new.as_ref().map(|c| &c.text),
+                })
+            })
+            .collect();
+        let chat_input = ToolInput {
+            tool_ref: input.tool_ref.clone(),
+            inputs: serde_json::json!({
+                "prompt": serde_json::to_string_pretty(&listing).unwrap_or_default()
+            }),
+            config: serde_json::json!({
+                "system_prompt": "You review changes between two versions of a contract. For each \
+                    change, say in one sentence, in the language of the clause, what it changes for \
+                    the parties (obligations, amounts, deadlines, liability, rights). Return ONLY a \
+                    JSON array of {\"id\": <id>, \"impact\": \"...\"}.",
+                "model": input.config.get("model").cloned().unwrap_or(serde_json::json!("claude-3-haiku-20240307")),
+                "max_tokens": 4096,
+                "temperature": 0.0
+            }),
+            llm_provider: input.llm_provider.clone(),
+            env: input.env.clone(),
+            progress: input.progress.clone(),
+            stream: Default::default(),
+            commands: input.commands.clone(),
+            secrets: input.secrets.clone(),
+            scratch_dir: input.scratch_dir.clone(),
+        };
+        let response = execute_llm_chat(&chat_input).await?;
+        let reply = response.get("response").and_then(|v| v.as_str()).unwrap_or("");
+        let reviews = reply
+            .find('[')
+            .zip(reply.rfind(']'))
+            .and_then(|(start, end)| serde_json::from_str::<Vec<serde_json::Value>>(reply.get(start..=end)?).ok());
+        match reviews {
+            Some(reviews) => {
+                for review in reviews {
+                    let id = review.get("id").and_then(|v| v.as_u64()).map(|id| id as usize);
+                    let impact = review.get("impact").and_then(|v| v.as_str());
+                    if let (Some(id), Some(impact)) = (id.filter(|id| major.contains(id)), impact) {
+                        changes[id].impact = Some(impact.to_string());
+                    }
+                }
+            }
+            None => tracing::warn!("[Doc Compare] LLM review returned no JSON array"),
+        }
+    }
+
+    let count = |kind: ChangeKind| changes.iter().filter(|c| c.kind == kind).count();
+    let summary = serde_json::json!({
+        "modified": count(ChangeKind::Modified),
+        "added": count(ChangeKind::Added),
+        "deleted": count(ChangeKind::Deleted),
+        "moved": count(ChangeKind::Moved),
+        "unchanged": count(ChangeKind::Unchanged),
+        "major": changes.iter().filter(|c| c.severity == Severity::Major).count(),
+    });
+    let report = doc_compare::report(&changes);
+    if !input.config.get("include_unchanged").and_then(|v| v.as_bool()).unwrap_or(false) {
+        changes.retain(|c| c.kind != ChangeKind::Unchanged);
+    }
+    Ok(serde_json::json!({
+        "changes": changes,
+        "count": changes.len(),
+        "summary": summary,
+        "report": report
+    }))
+}
+
+/// A document's text from the `text_port` input, or read from the path in
+/// the `path_port` input or the `path_config` config.
+fn document_input(
+    input: &ToolInput,
+    text_port: &str,
+    path_port: &str,
+    path_config: &str,
+) -> Result<String, ExecutorError> {
+    if let Some(text) = input.inputs.get(text_port).and_then(|v| v.as_str()) {
+        return Ok(text.to_string());
+    }
+    let path = input
+        .inputs
+        .get(path_port)
+        .and_then(|v| v.as_str())
+        .or_else(|| input.config.get(path_config).and_then(|v| v.as_str()))
+        .filter(|p| !p.trim().is_empty())
+        .ok_or_else(|| ExecutorError::ExecutionFailed(format!("Missing '{text_port}' or '{path_port}' input")))?;
+    read_document(path, input)
+}
+
 /// Basic PDF text extraction fallback using lopdf directly
 fn extract_pdf_text_basic(bytes: &[u8]) -> Result<String, ExecutorError> {
     use std::io::Cursor;
@@ -266,7 +804,14 @@ fn execute_file_write(input: &ToolInput) -> Result<serde_json::Value, ExecutorEr
         .and_then(|v| v.as_str())
         .ok_or_else(|| ExecutorError::ExecutionFailed("Missing 'content' input".into()))?;
 
-    std::fs::write(path, content)
+    let target = input.output_path(path);
+    // Subdirectories of the scratch directory are made on demand
+    if path.trim().starts_with(crate::SCRATCH_SCHEME) {
+        if let Some(parent) = target.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+    }
+    std::fs::write(target, content)
         .map_err(|e| ExecutorError::ExecutionFailed(format!("Failed to write {path}: {e}")))?;
 
     Ok(serde_json::json!({ "path": path, "size": content.len() }))
@@ -281,21 +826,25 @@ fn execute_files_read(input: &ToolInput) -> Result<serde_json::Value, ExecutorEr
         .unwrap_or_default();
 
     let mut contents = Vec::new();
-    for fp in &file_paths {
+    let total = file_paths.len() as u64;
+    for (i, fp) in file_paths.iter().enumerate() {
+        input.progress.items(i as u64, total, "reading files");
         let path = fp.as_str().unwrap_or("");
         if path.is_empty() { continue; }
-        match std::fs::read_to_string(path) {
-            Ok(content) => {
+        match read_text(path, input) {
+            Ok(decoded) => {
                 contents.push(serde_json::json!({
                     "path": path,
-                    "content": content,
-                    "size": content.len(),
+                    "size": decoded.text.len(),
+                    "content": decoded.text,
+                    "encoding": decoded.encoding,
+                    "had_errors": decoded.had_errors,
                 }));
             }
             Err(e) => {
                 contents.push(serde_json::json!({
                     "path": path,
-                    "error": format!("Failed to read: {e}"),
+                    "error": e.to_string(),
                 }));
             }
         }
@@ -323,10 +872,15 @@ fn execute_folder_read(input: &ToolInput) -> Result<serde_json::Value, ExecutorE
         .and_then(|v| v.as_bool())
         .unwrap_or(false);
 
+    // Brackets and other glob characters in folder names are literal
+    let folder = glob::Pattern::escape(&path::display(&path::from_user_input_in(
+        folder_path,
+        input.root_dir().as_deref(),
+    )));
     let glob_pattern = if recursive {
-        format!("{folder_path}/**/{pattern}")
+        format!("{folder}/**/{pattern}")
     } else {
-        format!("{folder_path}/{pattern}")
+        format!("{folder}/{pattern}")
     };
 
     let mut files = Vec::new();
@@ -351,41 +905,65 @@ fn execute_folder_read(input: &ToolInput) -> Result<serde_json::Value, ExecutorE
     Ok(serde_json::json!({ "files": files, "count": files.len() }))
 }
 
+/// Chunks a split may produce unless its `max_chunks` config says otherwise.
+const DEFAULT_MAX_CHUNKS: u64 = 100_000;
+
 fn execute_text_split(input: &ToolInput) -> Result<serde_json::Value, ExecutorError> {
+    let config = SplitConfig::from_config(&input.config, Strategy::Characters)?;
     let text = input
         .inputs
         .get("text")
         .and_then(|v| v.as_str())
         .unwrap_or("");
+    collect_chunks(Splitter::new(text.as_bytes(), config), text.len() as u64, input)
+}
 
-    let chunk_size = input
-        .config
-        .get("chunk_size")
-        .and_then(|v| v.as_u64())
-        .unwrap_or(1000) as usize;
+/// Split a file as it is read, so memory use follows the chunk size rather
+/// than the file size.
+fn execute_file_split(input: &ToolInput) -> Result<serde_json::Value, ExecutorError> {
+    let config = SplitConfig::from_config(&input.config, Strategy::Structure)?;
+    let path = input
+        .inputs
+        .get("path")
+        .and_then(|v| v.as_str())
+        .or_else(|| input.config.get("file_path").and_then(|v| v.as_str()))
+        .filter(|p| !p.trim().is_empty())
+        .ok_or_else(|| ExecutorError::ExecutionFailed("No file path provided".into()))?;
+    let file = std::fs::File::open(input.resolve_path(path))
+        .map_err(|e| ExecutorError::ExecutionFailed(format!("Failed to read {path}: {e}")))?;
+    let total = file.metadata().map(|m| m.len()).unwrap_or(0);
+    let reader = encoding::DecodeReader::new(file, encoding::from_config(&input.config))?;
+    collect_chunks(Splitter::new(std::io::BufReader::new(reader), config), total, input)
+}
 
-    let overlap = input
+/// Chunk texts, the heading each falls under and their count, failing once
+/// there are more than `max_chunks`.
+fn collect_chunks<R: std::io::BufRead>(
+    mut splitter: Splitter<R>,
+    total: u64,
+    input: &ToolInput,
+) -> Result<serde_json::Value, ExecutorError> {
+    let max_chunks = input
         .config
-        .get("overlap")
+        .get("max_chunks")
         .and_then(|v| v.as_u64())
-        .unwrap_or(100) as usize;
-
+        .unwrap_or(DEFAULT_MAX_CHUNKS);
     let mut chunks = Vec::new();
-    let chars: Vec<char> = text.chars().collect();
-    let mut start = 0;
-
-    while start < chars.len() {
-        let end = (start + chunk_size).min(chars.len());
-        let chunk: String = chars[start..end].iter().collect();
-        chunks.push(chunk);
-        if end >= chars.len() {
-            break;
+    let mut headings = Vec::new();
+    while let Some(chunk) = splitter.next() {
+        let chunk = chunk.map_err(|e| ExecutorError::ExecutionFailed(format!("Failed to read text: {e}")))?;
+        if chunks.len() as u64 == max_chunks {
+            return Err(ExecutorError::ExecutionFailed(format!(
+                "Text splits into more than {max_chunks} chunks; raise chunk_size or max_chunks"
+            )));
         }
-        start = end.saturating_sub(overlap);
+        // Decoded bytes can outnumber file bytes, so clamp
+        input.progress.items(splitter.bytes_read().min(total), total, "splitting text");
+        chunks.push(serde_json::Value::String(chunk.text));
+        headings.push(chunk.heading.map_or(serde_json::Value::Null, serde_json::Value::String));
     }
-
     let count = chunks.len();
-    Ok(serde_json::json!({ "chunks": chunks, "count": count }))
+    Ok(serde_json::json!({ "chunks": chunks, "headings": headings, "count": count }))
 }
 
 fn execute_text_merge(input: &ToolInput) -> Result<serde_json::Value, ExecutorError> {
@@ -484,7 +1062,7 @@ fn execute_json_path(input: &ToolInput) -> Result<serde_json::Value, ExecutorErr
     // Simple dot-path extraction (Phase 2 will use JMESPath)
     let result = expression
         .split('.')
-        .fold(Some(&data), |acc, key| acc.and_then(|v| v.get(key)))
+        .try_fold(&data, |acc, key| acc.get(key))
         .cloned()
         .unwrap_or(serde_json::json!(null));
 
@@ -504,8 +1082,8 @@ fn execute_csv_read(input: &ToolInput) -> Result<serde_json::Value, ExecutorErro
         return Err(ExecutorError::ExecutionFailed("File path is empty. Please configure the file path.".into()));
     }
 
-    let content = std::fs::read_to_string(path)
-        .map_err(|e| ExecutorError::ExecutionFailed(format!("Failed to read {path}: {e}")))?;
+    let decoded = read_text(path, input)?;
+    let content = decoded.text;
 
     let delimiter = input
         .config
@@ -515,7 +1093,10 @@ fn execute_csv_read(input: &ToolInput) -> Result<serde_json::Value, ExecutorErro
 
     let lines: Vec<&str> = content.lines().collect();
     if lines.is_empty() {
-        return Ok(serde_json::json!({ "rows": [], "headers": [], "count": 0 }));
+        return Ok(serde_json::json!({
+            "rows": [], "headers": [], "count": 0,
+            "encoding": decoded.encoding, "had_errors": decoded.had_errors,
+        }));
     }
 
     let headers: Vec<String> = lines[0].split(delimiter).map(|s| s.trim().to_string()).collect();
@@ -534,66 +1115,192 @@ fn execute_csv_read(input: &ToolInput) -> Result<serde_json::Value, ExecutorErro
         .collect();
 
     let count = rows.len();
-    Ok(serde_json::json!({ "rows": rows, "headers": headers, "count": count }))
+    Ok(serde_json::json!({
+        "rows": rows, "headers": headers, "count": count,
+        "encoding": decoded.encoding, "had_errors": decoded.had_errors,
+    }))
 }
 
+/// Filters, projects, sorts and pages `items` — an array, or a table
+/// (`{ "rows": [...], "headers": [...] }` as csv-read produces). Each row is
+/// the root of the `condition`, `fields` and `sort_by` expressions.
 fn execute_data_filter(input: &ToolInput) -> Result<serde_json::Value, ExecutorError> {
-    let items = input
-        .inputs
-        .get("items")
-        .and_then(|v| v.as_array())
-        .cloned()
-        .unwrap_or_default();
+    use hb_core::expr::{self, Expr};
 
-    let condition = input
-        .config
-        .get("condition")
-        .and_then(|v| v.as_str())
-        .unwrap_or("");
+    let (items, headers) = match input.inputs.get("items") {
+        Some(serde_json::Value::Array(items)) => (items.clone(), None),
+        Some(serde_json::Value::Object(table)) if table.get("rows").is_some_and(|r| r.is_array()) => {
+            let rows = table["rows"].as_array().cloned().unwrap_or_default();
+            (rows, table.get("headers").cloned())
+        }
+        Some(other) => {
+            let kind = match other {
+                serde_json::Value::Null => "null",
+                serde_json::Value::Bool(_) => "a boolean",
+                serde_json::Value::Number(_) => "a number",
+                serde_json::Value::String(_) => "a string",
+                _ => "an object without rows",
+            };
+            return Err(ExecutorError::ExecutionFailed(format!(
+                "Data filter needs an array or a table with rows, got {kind}"
+            )));
+        }
+        None => return Err(ExecutorError::ExecutionFailed("Missing 'items' input".into())),
+    };
+
+    let text = |name: &str| {
+        input
+            .config
+            .get(name)
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+    };
+    let parse = |name: &str, text: &str| {
+        Expr::parse(text).map_err(|e| ExecutorError::ExecutionFailed(format!("{name} '{text}': {e}")))
+    };
+    // `expression` is the name older workflows used
+    let condition = text("condition")
+        .or_else(|| text("expression"))
+        .map(|c| parse("Condition", c))
+        .transpose()?;
+    let sort_by = text("sort_by").map(|s| parse("Sort key", s)).transpose()?;
+    let fields = filter_fields(input.config.get("fields"))?
+        .into_iter()
+        .map(|(name, text)| Ok((name, parse("Field", &text)?)))
+        .collect::<Result<Vec<_>, ExecutorError>>()?;
+    // A bare word that no row has is almost always an unquoted string
+    // (`status == open`), which would otherwise quietly match nothing
+    if !items.is_empty() && items.iter().all(|item| item.is_object()) {
+        let known = |name: &str| items.iter().any(|item| item.get(name).is_some());
+        let named = condition.iter().chain(&sort_by).chain(fields.iter().map(|(_, f)| f));
+        if let Some(name) = named.flat_map(|e| e.fields()).find(|name| !known(name)) {
+            return Err(ExecutorError::ExecutionFailed(format!(
+                "Unknown field '{name}': no row has it. Quote text values ('{name}')"
+            )));
+        }
+    }
+    let number = |name: &str| input.config.get(name).and_then(|v| v.as_u64()).map(|n| n as usize);
+    let offset = number("offset").unwrap_or(0);
+    let limit = number("limit").filter(|&n| n > 0);
+    let descending = input.config.get("order").and_then(|v| v.as_str()) == Some("desc");
+
+    let failed = |i: usize, e: expr::ExprError| ExecutorError::ExecutionFailed(format!("Row {i}: {e}"));
+    let mut rows = Vec::new();
+    for (i, item) in items.into_iter().enumerate() {
+        if let Some(condition) = &condition {
+            if !condition.test(&item).map_err(|e| failed(i, e))? {
+                continue;
+            }
+        }
+        let key = match &sort_by {
+            Some(sort_by) => sort_by.evaluate(&item).map_err(|e| failed(i, e))?,
+            None => serde_json::Value::Null,
+        };
+        rows.push((i, key, item));
+    }
 
-    if condition.is_empty() {
-        let count = items.len();
-        return Ok(serde_json::json!({ "filtered": items, "count": count }));
+    if sort_by.is_some() {
+        // Stable, and rows without a key stay last in either order
+        rows.sort_by(|(_, a, _), (_, b, _)| match (a.is_null(), b.is_null(), descending) {
+            (false, false, true) => expr::sort_order(b, a),
+            _ => expr::sort_order(a, b),
+        });
     }
 
-    // Parse simple conditions: "field op value"
-    let filtered: Vec<serde_json::Value> = items
-        .into_iter()
-        .filter(|item| {
-            let parts: Vec<&str> = condition.splitn(3, ' ').collect();
-            if parts.len() < 3 { return true; }
-            let (field, op, value_str) = (parts[0], parts[1], parts[2]);
-            let field_val = item.get(field);
-            match field_val {
-                Some(fv) => {
-                    if let (Some(fv_num), Ok(val_num)) = (fv.as_f64(), value_str.parse::<f64>()) {
-                        match op {
-                            ">" | "gt" => fv_num > val_num,
-                            "<" | "lt" => fv_num < val_num,
-                            ">=" | "gte" => fv_num >= val_num,
-                            "<=" | "lte" => fv_num <= val_num,
-                            "==" | "eq" => (fv_num - val_num).abs() < f64::EPSILON,
-                            "!=" | "ne" => (fv_num - val_num).abs() >= f64::EPSILON,
-                            _ => true,
-                        }
-                    } else {
-                        let fv_str = fv.as_str().unwrap_or(&fv.to_string()).to_string();
-                        let val_clean = value_str.trim_matches('"').trim_matches('\'');
-                        match op {
-                            "==" | "eq" => fv_str == val_clean,
-                            "!=" | "ne" => fv_str != val_clean,
-                            "contains" => fv_str.contains(val_clean),
-                            _ => true,
-                        }
-                    }
-                }
-                None => false,
+    let total = rows.len();
+    let page = rows.into_iter().skip(offset).take(limit.unwrap_or(usize::MAX));
+    let mut filtered = Vec::new();
+    for (i, _, item) in page {
+        if fields.is_empty() {
+            filtered.push(item);
+            continue;
+        }
+        let mut row = serde_json::Map::new();
+        for (name, field) in &fields {
+            row.insert(name.clone(), field.evaluate(&item).map_err(|e| failed(i, e))?);
+        }
+        filtered.push(serde_json::Value::Object(row));
+    }
+
+    let headers = if fields.is_empty() {
+        headers.unwrap_or(serde_json::Value::Null)
+    } else {
+        serde_json::json!(fields.iter().map(|(name, _)| name).collect::<Vec<_>>())
+    };
+    let count = filtered.len();
+    Ok(serde_json::json!({
+        "filtered": filtered, "count": count, "total": total, "headers": headers,
+    }))
+}
+
+/// Projection as (output name, expression text). Accepts a JSON object of
+/// name → expression, a JSON array of expressions, or a comma-separated
+/// string where `expr as name` renames a field.
+fn filter_fields(config: Option<&serde_json::Value>) -> Result<Vec<(String, String)>, ExecutorError> {
+    let entries: Vec<String> = match config {
+        None | Some(serde_json::Value::Null) => return Ok(Vec::new()),
+        Some(serde_json::Value::Object(map)) => {
+            return map
+                .iter()
+                .map(|(name, v)| match v.as_str() {
+                    Some(text) => Ok((name.clone(), text.to_string())),
+                    None => Err(ExecutorError::ExecutionFailed(format!("Field '{name}' must be an expression"))),
+                })
+                .collect();
+        }
+        Some(serde_json::Value::Array(items)) => items.iter().map(case_label).collect(),
+        Some(serde_json::Value::String(text)) => match serde_json::from_str(text) {
+            Ok(parsed @ (serde_json::Value::Object(_) | serde_json::Value::Array(_))) => {
+                return filter_fields(Some(&parsed));
+            }
+            _ => split_top_level(text),
+        },
+        Some(other) => {
+            return Err(ExecutorError::ExecutionFailed(format!("Fields must be a list, got {other}")));
+        }
+    };
+    Ok(entries
+        .iter()
+        .map(|entry| entry.trim())
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| match entry.rsplit_once(" as ") {
+            Some((text, name)) if !name.contains(['\'', '"', ')', ']']) => {
+                (name.trim().to_string(), text.trim().to_string())
             }
+            _ => (entry.to_string(), entry.to_string()),
         })
-        .collect();
+        .collect())
+}
 
-    let count = filtered.len();
-    Ok(serde_json::json!({ "filtered": filtered, "count": count }))
+/// Split on commas outside quotes, parentheses and brackets.
+fn split_top_level(text: &str) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let (mut depth, mut quote) = (0i32, None);
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(_), '\\') => {
+                parts.last_mut().unwrap().push(c);
+                if let Some(escaped) = chars.next() {
+                    parts.last_mut().unwrap().push(escaped);
+                }
+                continue;
+            }
+            (Some(q), _) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '(' | '[') => depth += 1,
+            (None, ')' | ']') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                parts.push(String::new());
+                continue;
+            }
+            _ => {}
+        }
+        parts.last_mut().unwrap().push(c);
+    }
+    parts
 }
 
 fn execute_regex_extract(input: &ToolInput) -> Result<serde_json::Value, ExecutorError> {
@@ -607,33 +1314,82 @@ fn execute_regex_extract(input: &ToolInput) -> Result<serde_json::Value, Executo
         .config
         .get("pattern")
         .and_then(|v| v.as_str())
-        .unwrap_or(".*");
+        .filter(|p| !p.is_empty())
+        .ok_or_else(|| ExecutorError::ExecutionFailed("Missing 'pattern' config".into()))?;
+    let flag = |name: &str, default: bool| {
+        input
+            .config
+            .get(name)
+            .and_then(|v| v.as_bool())
+            .unwrap_or(default)
+    };
 
-    let re = regex::Regex::new(pattern)
+    let re = regex::RegexBuilder::new(pattern)
+        .case_insensitive(flag("case_insensitive", false))
+        .multi_line(flag("multiline", false))
+        .dot_matches_new_line(flag("dot_all", false))
+        .build()
         .map_err(|e| ExecutorError::ExecutionFailed(format!("Invalid regex pattern: {e}")))?;
-
-    let matches: Vec<serde_json::Value> = re
-        .find_iter(text)
-        .map(|m| serde_json::json!({
-            "text": m.as_str(),
-            "start": m.start(),
-            "end": m.end(),
-        }))
-        .collect();
-
-    let groups: Vec<Vec<String>> = re
-        .captures_iter(text)
-        .map(|cap| cap.iter().map(|m| m.map(|m| m.as_str().to_string()).unwrap_or_default()).collect())
-        .collect();
+    // Only the first match unless `global`
+    let limit = if flag("global", true) { usize::MAX } else { 1 };
+
+    let mut matches = Vec::new();
+    let mut groups = Vec::new();
+    let mut captures = Vec::new();
+    for cap in re.captures_iter(text).take(limit) {
+        let whole = cap.get(0).expect("group 0 is the whole match");
+        matches.push(serde_json::json!({
+            "text": whole.as_str(),
+            "start": whole.start(),
+            "end": whole.end(),
+        }));
+        groups.push(
+            cap.iter()
+                .map(|m| m.map(|m| m.as_str().to_string()).unwrap_or_default())
+                .collect::<Vec<_>>(),
+        );
+        // Named groups by name, others by number; unmatched groups are null
+        let fields: serde_json::Map<String, serde_json::Value> = re
+            .capture_names()
+            .enumerate()
+            .skip(1)
+            .map(|(i, name)| {
+                let key = name.map_or_else(|| i.to_string(), str::to_string);
+                (key, serde_json::json!(cap.get(i).map(|m| m.as_str())))
+            })
+            .collect();
+        captures.push(serde_json::Value::Object(fields));
+    }
 
     let count = matches.len();
-    Ok(serde_json::json!({ "matches": matches, "groups": groups, "count": count }))
+    Ok(serde_json::json!({
+        "matches": matches,
+        "groups": groups,
+        "captures": captures,
+        "count": count,
+    }))
+}
+
+/// Keyed inputs of the merge node: the variadic `inputs` port (ordered by key,
+/// numeric keys numerically), or the legacy `input_a`/`input_b`/`input_c` ports.
+fn merge_inputs(input: &ToolInput) -> Vec<(String, serde_json::Value)> {
+    if let Some(obj) = input.inputs.get("inputs").and_then(|v| v.as_object()) {
+        let mut entries: Vec<(String, serde_json::Value)> =
+            obj.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        entries.sort_by(|(a, _), (b, _)| match (a.parse::<u64>(), b.parse::<u64>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            _ => a.cmp(b),
+        });
+        return entries;
+    }
+    ["input_a", "input_b", "input_c"]
+        .iter()
+        .filter_map(|name| input.inputs.get(*name).map(|v| (name.to_string(), v.clone())))
+        .collect()
 }
 
 fn execute_merge(input: &ToolInput) -> Result<serde_json::Value, ExecutorError> {
-    let a = input.inputs.get("input_a").cloned().unwrap_or(serde_json::json!(null));
-    let b = input.inputs.get("input_b").cloned().unwrap_or(serde_json::json!(null));
-    let c = input.inputs.get("input_c").cloned();
+    let entries = merge_inputs(input);
 
     let strategy = input
         .config
@@ -642,60 +1398,144 @@ fn execute_merge(input: &ToolInput) -> Result<serde_json::Value, ExecutorError>
         .unwrap_or("concat");
 
     let merged = match strategy {
-        "array" => {
-            let mut arr = vec![a, b];
-            if let Some(c_val) = c {
-                arr.push(c_val);
-            }
-            serde_json::json!(arr)
-        }
+        "array" => serde_json::Value::Array(entries.into_iter().map(|(_, v)| v).collect()),
         "object_merge" => {
             let mut obj = serde_json::Map::new();
-            if let Some(a_obj) = a.as_object() {
-                obj.extend(a_obj.clone());
-            }
-            if let Some(b_obj) = b.as_object() {
-                obj.extend(b_obj.clone());
-            }
-            if let Some(c_val) = c {
-                if let Some(c_obj) = c_val.as_object() {
-                    obj.extend(c_obj.clone());
+            for (_, v) in entries {
+                if let serde_json::Value::Object(o) = v {
+                    obj.extend(o);
                 }
             }
             serde_json::Value::Object(obj)
         }
+        "keyed" => serde_json::Value::Object(entries.into_iter().collect()),
         _ => {
             // concat
-            let a_str = match &a { serde_json::Value::String(s) => s.clone(), v => v.to_string() };
-            let b_str = match &b { serde_json::Value::String(s) => s.clone(), v => v.to_string() };
-            let mut result = format!("{a_str}\n{b_str}");
-            if let Some(c_val) = c {
-                let c_str = match &c_val { serde_json::Value::String(s) => s.clone(), v => v.to_string() };
-                result.push('\n');
-                result.push_str(&c_str);
-            }
-            serde_json::json!(result)
+            let parts: Vec<String> = entries
+                .into_iter()
+                .map(|(_, v)| match v {
+                    serde_json::Value::String(s) => s,
+                    v => v.to_string(),
+                })
+                .collect();
+            serde_json::json!(parts.join("\n"))
         }
     };
 
     Ok(serde_json::json!({ "merged": merged }))
 }
 
+/// Evaluates `expression` against the `value` input (or tests the value
+/// itself when there is none). In "if" mode the value goes to `true_out` or
+/// `false_out`; in "switch" mode it goes to the `cases.<label>` output whose
+/// case value equals the result, or to `default`. The other outputs are
+/// listed in `untaken`.
 fn execute_condition(input: &ToolInput) -> Result<serde_json::Value, ExecutorError> {
     let value = input.inputs.get("value").cloned().unwrap_or(serde_json::json!(false));
+    let expression = input
+        .config
+        .get("expression")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .unwrap_or("");
 
-    let truthy = match &value {
-        serde_json::Value::Bool(b) => *b,
-        serde_json::Value::Null => false,
-        serde_json::Value::Number(n) => n.as_f64().unwrap_or(0.0) != 0.0,
-        serde_json::Value::String(s) => !s.is_empty(),
-        _ => true,
+    let result = if expression.is_empty() {
+        value.clone()
+    } else {
+        hb_core::expr::evaluate(expression, &value).map_err(|e| {
+            ExecutorError::ExecutionFailed(format!("Condition '{expression}': {e}"))
+        })?
     };
 
-    if truthy {
-        Ok(serde_json::json!({ "true_out": value, "false_out": null }))
-    } else {
-        Ok(serde_json::json!({ "true_out": null, "false_out": value }))
+    // `untaken` names the outputs of the branches not taken; the scheduler
+    // skips whatever hangs off them
+    let mode = input.config.get("mode").and_then(|v| v.as_str()).unwrap_or("if");
+    if mode != "switch" {
+        return Ok(if hb_core::expr::truthy(&result) {
+            serde_json::json!({ "true_out": value, "false_out": null, "result": result, "untaken": ["false_out"] })
+        } else {
+            serde_json::json!({ "true_out": null, "false_out": value, "result": result, "untaken": ["true_out"] })
+        });
+    }
+
+    let mut matched = None;
+    let mut cases = serde_json::Map::new();
+    let mut untaken = Vec::new();
+    for (label, case) in switch_cases(input.config.get("cases"))? {
+        let hit = matched.is_none() && hb_core::expr::equals(&case, &result);
+        if hit {
+            matched = Some(label.clone());
+        } else {
+            untaken.push(format!("cases.{label}"));
+        }
+        cases.insert(label, if hit { value.clone() } else { serde_json::Value::Null });
+    }
+    let default = match matched {
+        Some(_) => {
+            untaken.push("default".to_string());
+            serde_json::Value::Null
+        }
+        None => {
+            untaken = vec!["cases".to_string()];
+            value
+        }
+    };
+    Ok(serde_json::json!({
+        "cases": cases,
+        "default": default,
+        "matched": matched,
+        "result": result,
+        "untaken": untaken,
+    }))
+}
+
+/// Switch cases as (label, value) pairs. Accepts a JSON array of scalars or
+/// `{ "label", "value" }` objects, or a comma-separated string.
+fn switch_cases(
+    config: Option<&serde_json::Value>,
+) -> Result<Vec<(String, serde_json::Value)>, ExecutorError> {
+    let items = match config {
+        None | Some(serde_json::Value::Null) => return Ok(Vec::new()),
+        Some(serde_json::Value::String(text)) => match serde_json::from_str(text) {
+            Ok(serde_json::Value::Array(items)) => items,
+            _ => text
+                .split(',')
+                .map(str::trim)
+                .filter(|case| !case.is_empty())
+                // "1" and "true" match numbers and booleans
+                .map(|case| serde_json::from_str(case).unwrap_or_else(|_| case.into()))
+                .collect(),
+        },
+        Some(serde_json::Value::Array(items)) => items.clone(),
+        Some(other) => {
+            return Err(ExecutorError::ExecutionFailed(format!(
+                "Switch cases must be a list, got {other}"
+            )))
+        }
+    };
+    items
+        .into_iter()
+        .map(|item| match item {
+            serde_json::Value::Object(mut case) => {
+                let value = case.remove("value").unwrap_or(serde_json::Value::Null);
+                let label = match case.remove("label") {
+                    Some(serde_json::Value::String(label)) => label,
+                    _ => case_label(&value),
+                };
+                Ok((label, value))
+            }
+            serde_json::Value::Array(_) => Err(ExecutorError::ExecutionFailed(
+                "Switch cases must be values or { label, value } objects".into(),
+            )),
+            value => Ok((case_label(&value), value)),
+        })
+        .collect()
+}
+
+fn case_label(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
     }
 }
 
@@ -782,13 +1622,13 @@ async fn execute_llm_chat(input: &ToolInput) -> Result<serde_json::Value, Execut
         match provider.as_str() {
             "anthropic" => {
                 let api_key = std::env::var("ANTHROPIC_API_KEY")
-                    .map_err(|_| ExecutorError::ExecutionFailed("Anthropic API key not configured. Set credentials in Settings.".into()))?;
-                return call_anthropic_api(&api_key, model, &full_prompt, &system_prompt, max_tokens, temperature).await;
+                    .map_err(|_| ExecutorError::MissingCredential("Anthropic API key not configured. Set credentials in Settings.".into()))?;
+                return call_anthropic_api(&api_key, model, &full_prompt, &system_prompt, max_tokens, temperature, &input.stream).await;
             }
             "openai" => {
                 let api_key = std::env::var("OPENAI_API_KEY")
-                    .map_err(|_| ExecutorError::ExecutionFailed("OpenAI API key not configured. Set credentials in Settings.".into()))?;
-                return call_openai_api(&api_key, model, &full_prompt, &system_prompt, max_tokens, temperature).await;
+                    .map_err(|_| ExecutorError::MissingCredential("OpenAI API key not configured. Set credentials in Settings.".into()))?;
+                return call_openai_api(&api_key, model, &full_prompt, &system_prompt, max_tokens, temperature, &input.stream).await;
             }
             "bedrock" => {
                 if let (Ok(access_key), Ok(secret_key)) = (
@@ -802,12 +1642,19 @@ async fn execute_llm_chat(input: &ToolInput) -> Result<serde_json::Value, Execut
                     let region = std::env::var("AWS_REGION").unwrap_or_else(|_| "us-east-1".to_string());
                     return call_bedrock_api(&api_key, &region, model, &full_prompt, &system_prompt, max_tokens, temperature).await;
                 }
-                return Err(ExecutorError::ExecutionFailed("AWS Bedrock credentials not configured. Set credentials in Settings.".into()));
+                return Err(ExecutorError::MissingCredential("AWS Bedrock credentials not configured. Set credentials in Settings.".into()));
             }
             "local" => {
                 let endpoint = std::env::var("LOCAL_LLM_ENDPOINT")
                     .unwrap_or_else(|_| "http://localhost:11434".to_string());
-                return call_local_llm_api(&endpoint, model, &full_prompt, &system_prompt, max_tokens, temperature).await;
+                return call_local_llm_api(&endpoint, model, &full_prompt, &system_prompt, max_tokens, temperature, &input.stream).await;
+            }
+            "llamacpp" => {
+                // The app starts llama-server before the run and publishes its URL
+                let endpoint = std::env::var("LLAMACPP_ENDPOINT")
+                    .map_err(|_| ExecutorError::MissingCredential("llama.cpp server is not running. Install a model in Settings.".into()))?;
+                let url = format!("{}/v1/chat/completions", endpoint.trim_end_matches('/'));
+                return call_chat_completions("llama.cpp", &url, None, model, &full_prompt, &system_prompt, max_tokens, temperature, &input.stream).await;
             }
             _ => {
                 tracing::warn!("[LLM Chat] Unknown provider '{}', falling through to auto-detect", provider);
@@ -817,12 +1664,12 @@ async fn execute_llm_chat(input: &ToolInput) -> Result<serde_json::Value, Execut
 
     // Fallback: auto-detect from environment variables (backward compat)
     if let Ok(api_key) = std::env::var("ANTHROPIC_API_KEY") {
-        return call_anthropic_api(&api_key, model, &full_prompt, &system_prompt, max_tokens, temperature).await;
+        return call_anthropic_api(&api_key, model, &full_prompt, &system_prompt, max_tokens, temperature, &input.stream).await;
     }
 
     if let Ok(api_key) = std::env::var("OPENAI_API_KEY") {
         let openai_model = if model.contains("claude") { "gpt-4o" } else { model };
-        return call_openai_api(&api_key, openai_model, &full_prompt, &system_prompt, max_tokens, temperature).await;
+        return call_openai_api(&api_key, openai_model, &full_prompt, &system_prompt, max_tokens, temperature, &input.stream).await;
     }
 
     if let (Ok(access_key), Ok(secret_key)) = (
@@ -839,10 +1686,10 @@ async fn execute_llm_chat(input: &ToolInput) -> Result<serde_json::Value, Execut
     }
 
     if let Ok(endpoint) = std::env::var("LOCAL_LLM_ENDPOINT") {
-        return call_local_llm_api(&endpoint, model, &full_prompt, &system_prompt, max_tokens, temperature).await;
+        return call_local_llm_api(&endpoint, model, &full_prompt, &system_prompt, max_tokens, temperature, &input.stream).await;
     }
 
-    Err(ExecutorError::ExecutionFailed(
+    Err(ExecutorError::MissingCredential(
         "No LLM API key configured. Set ANTHROPIC_API_KEY, OPENAI_API_KEY, AWS_ACCESS_KEY_ID+AWS_SECRET_ACCESS_KEY, or LOCAL_LLM_ENDPOINT.".into()
     ))
 }
@@ -913,6 +1760,12 @@ async fn execute_llm_summarize(input: &ToolInput) -> Result<serde_json::Value, E
             "temperature": 0.3
         }),
         llm_provider: input.llm_provider.clone(),
+        env: input.env.clone(),
+        progress: input.progress.clone(),
+        stream: Default::default(),
+        commands: input.commands.clone(),
+        secrets: input.secrets.clone(),
+        scratch_dir: input.scratch_dir.clone(),
     };
 
     let result = execute_llm_chat(&modified_input).await?;
@@ -921,30 +1774,717 @@ async fn execute_llm_summarize(input: &ToolInput) -> Result<serde_json::Value, E
     Ok(serde_json::json!({ "summary": summary }))
 }
 
+/// Summaries of chunks at most this many characters are grouped together in
+/// each reduce step of `summarize-long`.
+const DEFAULT_GROUP_CHARS: usize = 12_000;
+
+async fn execute_summarize_long(input: &ToolInput) -> Result<serde_json::Value, ExecutorError> {
+    use crate::splitter::split_text;
+
+    let text = document_input(input, "text", "path", "file_path")?;
+    if text.trim().is_empty() {
+        return Err(ExecutorError::ExecutionFailed(
+            "No text provided for summarization. Check that the input connection is correct.".into()
+        ));
+    }
+
+    // Chunks default to a size a cheap model reads comfortably in one call
+    let mut split_config = input.config.clone();
+    if let Some(config) = split_config.as_object_mut() {
+        config.entry("chunk_size").or_insert(serde_json::json!(8000));
+        config.entry("overlap").or_insert(serde_json::json!(200));
+    }
+    let chunks = split_text(&text, &SplitConfig::from_config(&split_config, Strategy::Structure)?);
+    tracing::info!("[Summarize Long] {} chars in {} chunks", text.len(), chunks.len());
+
+    let config_str = |key: &str| {
+        input.config.get(key).and_then(|v| v.as_str()).filter(|s| !s.trim().is_empty())
+    };
+    let language_instruction = match config_str("language").unwrap_or("auto") {
+        "korean" | "ko" => "You MUST respond in Korean (한국어).",
+        "english" | "en" => "You MUST respond in English.",
+        "japanese" | "ja" => "You MUST respond in Japanese (日本語).",
+        "chinese" | "zh" => "You MUST respond in Chinese (中文).",
+        _ => "Respond in the same language as the input text.",
+    };
+    let chunk_prompt = config_str("chunk_prompt").unwrap_or(
+        "Summarize this section of a longer document. Keep every key fact, figure, \
+        name and conclusion; drop repetition and filler.",
+    );
+    let reduce_prompt = "Merge these consecutive section summaries of one document into a \
+        single summary. Keep every key fact, figure, name and conclusion, in document order.";
+    let final_prompt = match config_str("final_prompt") {
+        Some(prompt) => prompt.to_string(),
+        None => format!(
+            "Write a {} summary of the whole document from these section summaries. \
+            Focus on the key points and main ideas.",
+            config_str("style").unwrap_or("concise")
+        ),
+    };
+    let chunk_tokens = input.config.get("chunk_max_tokens").and_then(|v| v.as_u64()).unwrap_or(1024);
+    let final_tokens = input.config.get("max_tokens").and_then(|v| v.as_u64()).unwrap_or(2048);
+
+    // Map: summarize every chunk on its own
+    let total = chunks.len() as u64;
+    let mut chunk_summaries = Vec::with_capacity(chunks.len());
+    for (i, chunk) in chunks.iter().enumerate() {
+        input.progress.items(i as u64, total, "summarizing chunks");
+        let prompt = match &chunk.heading {
+            Some(heading) => format!("Section: {heading}\n\n{}", chunk.text),
+            None => chunk.text.clone(),
+        };
+        let system_prompt = format!("{chunk_prompt} {language_instruction}");
+        chunk_summaries.push(summary_chat(input, &system_prompt, &prompt, chunk_tokens).await?);
+    }
+    input.progress.items(total, total, "summarizing chunks");
+
+    // Reduce: merge neighbouring summaries until they fit in one final call
+    let group_chars = input
+        .config
+        .get("group_chars")
+        .and_then(|v| v.as_u64())
+        .map_or(DEFAULT_GROUP_CHARS, |n| n as usize);
+    let mut summaries = chunk_summaries.clone();
+    let mut levels = 1;
+    while summaries.len() > 1 && summaries.iter().map(|s| s.len()).sum::<usize>() > group_chars {
+        let lengths: Vec<usize> = summaries.iter().map(|s| s.len()).collect();
+        let groups = group_by_budget(&lengths, group_chars);
+        let total = groups.len() as u64;
+        let mut merged = Vec::with_capacity(groups.len());
+        for (i, group) in groups.into_iter().enumerate() {
+            input.progress.items(i as u64, total, &format!("merging summaries (level {levels})"));
+            if group.len() == 1 {
+                merged.push(summaries[group.start].clone());
+                continue;
+            }
+            let system_prompt = format!("{reduce_prompt} {language_instruction}");
+            let prompt = summaries[group].join("\n\n---\n\n");
+            merged.push(summary_chat(input, &system_prompt, &prompt, chunk_tokens).await?);
+        }
+        summaries = merged;
+        levels += 1;
+    }
+
+    // Final synthesis over whatever is left
+    input.progress.report(crate::progress::Progress::step("writing final summary"));
+    let system_prompt = format!("{final_prompt} {language_instruction}");
+    let summary = summary_chat(input, &system_prompt, &summaries.join("\n\n---\n\n"), final_tokens).await?;
+
+    let chunk_summaries: Vec<serde_json::Value> = chunks
+        .iter()
+        .zip(chunk_summaries)
+        .enumerate()
+        .map(|(index, (chunk, summary))| {
+            serde_json::json!({ "index": index, "heading": chunk.heading, "summary": summary })
+        })
+        .collect();
+    Ok(serde_json::json!({
+        "summary": summary,
+        "chunk_summaries": chunk_summaries,
+        "chunks": chunks.len(),
+        "levels": levels
+    }))
+}
+
+/// One summarization call through llm-chat, returning the reply text.
+async fn summary_chat(
+    input: &ToolInput,
+    system_prompt: &str,
+    prompt: &str,
+    max_tokens: u64,
+) -> Result<String, ExecutorError> {
+    let chat_input = ToolInput {
+        tool_ref: input.tool_ref.clone(),
+        inputs: serde_json::json!({ "prompt": prompt }),
+        config: serde_json::json!({
+            "system_prompt": system_prompt,
+            "model": input.config.get("model").cloned().unwrap_or(serde_json::json!("claude-3-haiku-20240307")),
+            "max_tokens": max_tokens,
+            "temperature": 0.3
+        }),
+        llm_provider: input.llm_provider.clone(),
+        env: input.env.clone(),
+        progress: input.progress.clone(),
+        stream: Default::default(),
+        commands: input.commands.clone(),
+        secrets: input.secrets.clone(),
+        scratch_dir: input.scratch_dir.clone(),
+    };
+    let result = execute_llm_chat(&chat_input).await?;
+    Ok(result.get("response").and_then(|v| v.as_str()).unwrap_or("").to_string())
+}
+
+/// Split items of the given lengths into consecutive groups of at most
+/// `budget` characters. A group always takes at least two items when it can,
+/// so every reduce step shrinks the list even if single items are oversized.
+fn group_by_budget(lengths: &[usize], budget: usize) -> Vec<std::ops::Range<usize>> {
+    let mut groups = Vec::new();
+    let mut start = 0;
+    let mut size = 0;
+    for (i, &len) in lengths.iter().enumerate() {
+        if i - start >= 2 && size + len > budget {
+            groups.push(start..i);
+            start = i;
+            size = 0;
+        }
+        size += len;
+    }
+    if start < lengths.len() {
+        groups.push(start..lengths.len());
+    }
+    groups
+}
+
+/// Endpoint audio goes to when neither the node nor `WHISPER_ENDPOINT` sets
+/// one.
+const DEFAULT_TRANSCRIPTION_ENDPOINT: &str = "https://api.openai.com/v1/audio/transcriptions";
+
+/// Lines labelled per LLM call by `speaker-diarize`.
+const DIARIZE_BATCH: usize = 200;
+
+/// Transcripts longer than this are turned into notes chunk by chunk before
+/// the minutes are written.
+const DEFAULT_MINUTES_CHARS: usize = 30_000;
+
+const MINUTES_PROMPT: &str = "You write meeting minutes from a transcript or from notes taken during \
+    the meeting. Return ONLY a JSON object: {\"title\": \"...\", \"summary\": \"3-5 sentences\", \
+    \"attendees\": [\"...\"], \"topics\": [{\"title\": \"...\", \"points\": [\"...\"]}], \
+    \"decisions\": [\"...\"], \"action_items\": [{\"task\": \"...\", \"owner\": \"...\", \"due\": \"...\"}]}. \
+    List only decisions that were actually made and tasks someone agreed to do. Use null for an owner \
+    or due date nobody stated. Attendees are the people who spoke or were named as present; leave out \
+    generic labels such as \"Speaker 1\".";
+
+const MEETING_NOTES_PROMPT: &str = "Take notes on this part of a meeting transcript: topics discussed \
+    with the key points, decisions made, and tasks agreed with who owns them and when they are due. \
+    Keep names, numbers and dates exactly.";
+
+async fn execute_audio_transcribe(input: &ToolInput) -> Result<serde_json::Value, ExecutorError> {
+    use crate::meeting;
+
+    let path = input
+        .inputs
+        .get("path")
+        .and_then(|v| v.as_str())
+        .or_else(|| input.config.get("file_path").and_then(|v| v.as_str()))
+        .filter(|p| !p.trim().is_empty())
+        .ok_or_else(|| ExecutorError::ExecutionFailed("Missing 'path' input or 'file_path' config".into()))?;
+
+    // Subtitles and transcripts exported by meeting apps need no network
+    let lower = path.to_lowercase();
+    let (segments, language, source) = if lower.ends_with(".srt") || lower.ends_with(".vtt") {
+        (meeting::parse_subtitles(&read_text(path, input)?.text), None, "subtitles")
+    } else if lower.ends_with(".txt") || lower.ends_with(".md") {
+        (meeting::parse_transcript(&read_text(path, input)?.text), None, "transcript")
+    } else {
+        let (segments, language) = transcribe_audio(path, input).await?;
+        (segments, language, "audio")
+    };
+
+    let text = segments.iter().map(|s| s.text.as_str()).collect::<Vec<_>>().join(" ");
+    let duration = segments.iter().filter_map(|s| s.end.or(s.start)).fold(0.0, f64::max);
+    Ok(serde_json::json!({
+        "text": text,
+        "transcript": meeting::render_transcript(&segments),
+        "segments": segments,
+        "duration": duration,
+        "language": language,
+        "source": source
+    }))
+}
+
+/// Send an audio file to a Whisper-compatible transcription endpoint and
+/// return its segments and detected language.
+async fn transcribe_audio(
+    path: &str,
+    input: &ToolInput,
+) -> Result<(Vec<crate::meeting::Segment>, Option<String>), ExecutorError> {
+    let audio = std::fs::read(input.resolve_path(path))
+        .map_err(|e| ExecutorError::ExecutionFailed(format!("Failed to read {path}: {e}")))?;
+    let endpoint = input
+        .config
+        .get("endpoint")
+        .and_then(|v| v.as_str())
+        .filter(|e| !e.trim().is_empty())
+        .map(str::to_string)
+        .or_else(|| std::env::var("WHISPER_ENDPOINT").ok())
+        .unwrap_or_else(|| DEFAULT_TRANSCRIPTION_ENDPOINT.to_string());
+    // Self-hosted servers usually take no key
+    let api_key = std::env::var("OPENAI_API_KEY").ok();
+    if api_key.is_none() && endpoint == DEFAULT_TRANSCRIPTION_ENDPOINT {
+        return Err(ExecutorError::MissingCredential(
+            "OpenAI API key not configured for transcription. Set credentials in Settings or a WHISPER_ENDPOINT.".into(),
+        ));
+    }
+
+    let config_str = |key: &str| {
+        input.config.get(key).and_then(|v| v.as_str()).filter(|s| !s.trim().is_empty())
+    };
+    let mut fields = vec![
+        ("model", config_str("model").unwrap_or("whisper-1")),
+        ("response_format", "verbose_json"),
+    ];
+    if let Some(language) = config_str("language").filter(|l| *l != "auto") {
+        fields.push(("language", language));
+    }
+    // Names and jargon the recording uses, to spell them right
+    if let Some(prompt) = config_str("vocabulary") {
+        fields.push(("prompt", prompt));
+    }
+    let file_name = std::path::Path::new(path)
+        .file_name()
+        .map_or("audio".into(), |n| n.to_string_lossy());
+    let boundary = format!("handbox-{}", uuid::Uuid::new_v4().simple());
+    let body = crate::meeting::multipart_body(&boundary, &fields, &file_name, &audio);
+
+    input.progress.report(Progress::step("transcribing audio"));
+    let mut request = reqwest::Client::new()
+        .post(&endpoint)
+        .header("Content-Type", format!("multipart/form-data; boundary={boundary}"))
+        .body(body);
+    if let Some(api_key) = api_key {
+        request = request.header("Authorization", format!("Bearer {api_key}"));
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| ExecutorError::ExecutionFailed(format!("Transcription request failed: {e}")))?;
+    if !response.status().is_success() {
+        let error_body = response.text().await.unwrap_or_default();
+        return Err(ExecutorError::ExecutionFailed(format!("Transcription error: {error_body}")));
+    }
+    let reply: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| ExecutorError::ExecutionFailed(format!("Response parse failed: {e}")))?;
+    let language = reply.get("language").and_then(|v| v.as_str()).map(str::to_string);
+    Ok((crate::meeting::segments_from_reply(&reply), language))
+}
+
+async fn execute_speaker_diarize(input: &ToolInput) -> Result<serde_json::Value, ExecutorError> {
+    use crate::meeting::{self, Diarization, Segment};
+
+    let mut segments: Vec<Segment> = match input.inputs.get("segments") {
+        Some(serde_json::Value::Array(items)) => serde_json::from_value(serde_json::Value::Array(items.clone()))
+            .map_err(|e| ExecutorError::ExecutionFailed(format!("Invalid segments: {e}")))?,
+        _ => match input.inputs.get("text").and_then(|v| v.as_str()) {
+            Some(text) => meeting::parse_transcript(text),
+            None => {
+                return Err(ExecutorError::ExecutionFailed(
+                    "Connect the 'segments' of audio-transcribe or a transcript 'text'".into(),
+                ))
+            }
+        },
+    };
+    let speakers = input.config.get("speakers").and_then(|v| v.as_u64()).unwrap_or(2) as usize;
+    let turn_gap = input.config.get("turn_gap").and_then(|v| v.as_f64()).unwrap_or(1.5);
+
+    let method = match input.config.get("method").and_then(|v| v.as_str()) {
+        Some("llm") => {
+            llm_diarize(input, &mut segments, speakers).await?;
+            Diarization::Llm
+        }
+        _ => meeting::diarize(&mut segments, speakers, turn_gap),
+    };
+    Ok(serde_json::json!({
+        "transcript": meeting::render_transcript(&segments),
+        "speakers": meeting::speakers(&segments),
+        "segments": segments,
+        "method": method
+    }))
+}
+
+/// Have the LLM say who speaks each segment, a batch of lines at a time.
+async fn llm_diarize(
+    input: &ToolInput,
+    segments: &mut [crate::meeting::Segment],
+    speakers: usize,
+) -> Result<(), ExecutorError> {
+    let total = segments.len().div_ceil(DIARIZE_BATCH) as u64;
+    let mut named: Vec<String> = Vec::new();
+    for (batch, offset) in (0..segments.len()).step_by(DIARIZE_BATCH).enumerate() {
+        input.progress.items(batch as u64, total, "labelling speakers");
+        let lines = &segments[offset..(offset + DIARIZE_BATCH).min(segments.len())];
+        let listing: Vec<String> = lines
+            .iter()
+            .enumerate()
+            .map(|(i, s)| format!("{}: {}", offset + i, s.text))
+            .collect();
+        let mut system_prompt = format!(
+            "You label who speaks each line of a meeting transcript. About {speakers} people speak. \
+            Use their names when they introduce or address each other, otherwise Speaker 1, Speaker 2 \
+            and so on. Return ONLY a JSON array of {{\"id\": <line id>, \"speaker\": \"...\"}} covering every line."
+        );
+        if !named.is_empty() {
+            system_prompt.push_str(&format!(" Speakers so far: {}.", named.join(", ")));
+        }
+        let chat_input = ToolInput {
+            tool_ref: input.tool_ref.clone(),
+            inputs: serde_json::json!({ "prompt": listing.join("\n") }),
+            config: serde_json::json!({
+                "system_prompt": system_prompt,
+                "model": input.config.get("model").cloned().unwrap_or(serde_json::json!("claude-3-haiku-20240307")),
+                "max_tokens": 4096,
+                "temperature": 0.0
+            }),
+            llm_provider: input.llm_provider.clone(),
+            env: input.env.clone(),
+            progress: input.progress.clone(),
+            stream: Default::default(),
+            commands: input.commands.clone(),
+            secrets: input.secrets.clone(),
+            scratch_dir: input.scratch_dir.clone(),
+        };
+        let response = execute_llm_chat(&chat_input).await?;
+        let reply = response.get("response").and_then(|v| v.as_str()).unwrap_or("");
+        let labels = reply
+            .find('[')
+            .zip(reply.rfind(']'))
+            .and_then(|(start, end)| serde_json::from_str::<Vec<serde_json::Value>>(reply.get(start..=end)?).ok())
+            .unwrap_or_else(|| {
+                tracing::warn!("[Speaker Diarize] LLM returned no JSON array");
+                Vec::new()
+            });
+        for label in labels {
+            let id = label.get("id").and_then(|v| v.as_u64()).map(|id| id as usize);
+            let speaker = label.get("speaker").and_then(|v| v.as_str()).map(str::trim);
+            if let (Some(segment), Some(speaker)) = (id.and_then(|id| segments.get_mut(id)), speaker) {
+                if !speaker.is_empty() {
+                    segment.speaker = Some(speaker.to_string());
+                }
+            }
+        }
+        named = crate::meeting::speakers(&segments[..(offset + DIARIZE_BATCH).min(segments.len())]);
+    }
+    input.progress.items(total, total, "labelling speakers");
+
+    // Lines the LLM skipped continue the previous speaker
+    let mut current = None;
+    for segment in segments.iter_mut() {
+        match &segment.speaker {
+            Some(speaker) => current = Some(speaker.clone()),
+            None => segment.speaker = current.clone(),
+        }
+    }
+    Ok(())
+}
+
+async fn execute_meeting_minutes(input: &ToolInput) -> Result<serde_json::Value, ExecutorError> {
+    use crate::meeting::{self, Minutes, Segment};
+    use crate::splitter::split_text;
+
+    let (transcript, speakers) = match input.inputs.get("segments") {
+        Some(serde_json::Value::Array(items)) => {
+            let segments: Vec<Segment> = serde_json::from_value(serde_json::Value::Array(items.clone()))
+                .map_err(|e| ExecutorError::ExecutionFailed(format!("Invalid segments: {e}")))?;
+            (meeting::render_transcript(&segments), meeting::speakers(&segments))
+        }
+        _ => {
+            let transcript = document_input(input, "transcript", "path", "file_path")?;
+            let speakers = meeting::speakers(&meeting::parse_transcript(&transcript));
+            (transcript, speakers)
+        }
+    };
+    if transcript.trim().is_empty() {
+        return Err(ExecutorError::ExecutionFailed("The transcript is empty".into()));
+    }
+
+    let language = input.config.get("language").and_then(|v| v.as_str()).unwrap_or("auto");
+    let hangul = transcript.chars().filter(|c| ('\u{AC00}'..='\u{D7A3}').contains(c)).count();
+    let latin = transcript.chars().filter(|c| c.is_ascii_alphabetic()).count();
+    let korean = match language {
+        "korean" | "ko" => true,
+        "auto" => hangul > latin,
+        _ => false,
+    };
+    let language_instruction = match language {
+        "korean" | "ko" => "You MUST respond in Korean (한국어).",
+        "english" | "en" => "You MUST respond in English.",
+        "japanese" | "ja" => "You MUST respond in Japanese (日本語).",
+        "chinese" | "zh" => "You MUST respond in Chinese (中文).",
+        _ => "Respond in the same language as the transcript.",
+    };
+
+    // Long meetings are noted down part by part first
+    let max_chars = input
+        .config
+        .get("max_chars")
+        .and_then(|v| v.as_u64())
+        .map_or(DEFAULT_MINUTES_CHARS, |n| n as usize);
+    let material = if transcript.len() > max_chars {
+        let chunks = split_text(&transcript, &SplitConfig::new(max_chars / 3, 0, Strategy::Structure));
+        let total = chunks.len() as u64;
+        let mut notes = Vec::with_capacity(chunks.len());
+        for (i, chunk) in chunks.iter().enumerate() {
+            input.progress.items(i as u64, total, "taking notes");
+            let system_prompt = format!("{MEETING_NOTES_PROMPT} {language_instruction}");
+            notes.push(summary_chat(input, &system_prompt, &chunk.text, 2048).await?);
+        }
+        input.progress.items(total, total, "taking notes");
+        format!("Notes taken during the meeting, in order:\n\n{}", notes.join("\n\n---\n\n"))
+    } else {
+        format!("Transcript:\n\n{transcript}")
+    };
+
+    let mut system_prompt = format!("{MINUTES_PROMPT} {language_instruction}");
+    if let Some(context) = input.config.get("context").and_then(|v| v.as_str()).filter(|c| !c.trim().is_empty()) {
+        system_prompt.push_str(&format!(" About the meeting: {context}"));
+    }
+    let named: Vec<&String> = speakers.iter().filter(|s| !s.starts_with("Speaker ")).collect();
+    let prompt = if named.is_empty() {
+        material
+    } else {
+        let named: Vec<&str> = named.iter().map(|s| s.as_str()).collect();
+        format!("Speakers: {}\n\n{material}", named.join(", "))
+    };
+    input.progress.report(Progress::step("writing minutes"));
+    let max_tokens = input.config.get("max_tokens").and_then(|v| v.as_u64()).unwrap_or(4096);
+    let reply = summary_chat(input, &system_prompt, &prompt, max_tokens).await?;
+    let minutes = meeting::parse_minutes(&reply).unwrap_or_else(|| {
+        tracing::warn!("[Meeting Minutes] LLM returned no JSON object");
+        Minutes {
+            summary: reply.clone(),
+            ..Default::default()
+        }
+    });
+
+    Ok(serde_json::json!({
+        "minutes": meeting::render_minutes(&minutes, korean),
+        "title": minutes.title,
+        "summary": minutes.summary,
+        "attendees": minutes.attendees,
+        "topics": minutes.topics,
+        "decisions": minutes.decisions,
+        "action_items": minutes.action_items
+    }))
+}
+
 async fn execute_embedding(input: &ToolInput) -> Result<serde_json::Value, ExecutorError> {
+    let backend = EmbeddingBackend::select(input)?;
+
+    // Batch mode: embed every entry of `texts`, reporting progress per chunk
+    if let Some(texts) = input.inputs.get("texts").and_then(|v| v.as_array()) {
+        let texts: Vec<String> = texts
+            .iter()
+            .map(|t| t.as_str().unwrap_or("").to_string())
+            .collect();
+        let vectors = embed_many(&backend, texts, &input.progress).await?;
+        let dimension = vectors.first().map_or(0, |v| v.len());
+        let mut outputs = serde_json::json!({
+            "vectors": vectors,
+            "dimension": dimension,
+            "count": vectors.len()
+        });
+        if backend == EmbeddingBackend::Stub {
+            outputs["stub"] = serde_json::json!(true);
+        }
+        return Ok(outputs);
+    }
+
     let text = input
         .inputs
         .get("text")
         .and_then(|v| v.as_str())
         .unwrap_or("");
+    let vector = embed_many(&backend, vec![text.to_string()], &ProgressReporter::default())
+        .await?
+        .pop()
+        .unwrap_or_default();
+    let mut outputs = serde_json::json!({
+        "vector": vector,
+        "dimension": vector.len()
+    });
+    if backend == EmbeddingBackend::Stub {
+        outputs["stub"] = serde_json::json!(true);
+    }
+    Ok(outputs)
+}
 
-    // Try OpenAI embeddings first, then Bedrock
-    if let Ok(api_key) = std::env::var("OPENAI_API_KEY") {
-        return call_openai_embedding(&api_key, text).await;
+/// Where embeddings are computed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum EmbeddingBackend {
+    OpenAi { api_key: String, model: String },
+    /// Ollama-compatible server (`/api/embed`).
+    Local { endpoint: String, model: String },
+    Bedrock { api_key: String, region: String },
+    /// Hash-based vectors for testing without a provider.
+    Stub,
+}
+
+impl EmbeddingBackend {
+    /// Use the node's provider when set, otherwise the first configured one.
+    fn select(input: &ToolInput) -> Result<Self, ExecutorError> {
+        let model = input.config.get("model").and_then(|v| v.as_str());
+        let openai = |api_key| Self::OpenAi {
+            api_key,
+            model: model.unwrap_or("text-embedding-3-small").to_string(),
+        };
+        let local = |endpoint| Self::Local {
+            endpoint,
+            // The OpenAI default from the tool schema means "not chosen"
+            model: model
+                .filter(|m| !m.starts_with("text-embedding-"))
+                .unwrap_or("nomic-embed-text")
+                .to_string(),
+        };
+        let bedrock = |api_key| Self::Bedrock {
+            api_key,
+            region: std::env::var("AWS_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
+        };
+
+        match input.llm_provider.as_deref() {
+            Some("openai") => {
+                let api_key = std::env::var("OPENAI_API_KEY").map_err(|_| {
+                    ExecutorError::MissingCredential("OpenAI API key not configured. Set credentials in Settings.".into())
+                })?;
+                return Ok(openai(api_key));
+            }
+            Some("local") => {
+                let endpoint = std::env::var("LOCAL_LLM_ENDPOINT")
+                    .unwrap_or_else(|_| "http://localhost:11434".to_string());
+                return Ok(local(endpoint));
+            }
+            Some("bedrock") => {
+                if let Ok(api_key) = std::env::var("BEDROCK_API_KEY") {
+                    return Ok(bedrock(api_key));
+                }
+            }
+            _ => {}
+        }
+
+        // A local model picked in the node config implies the local server
+        if model.is_some_and(|m| !m.starts_with("text-embedding-") && !m.starts_with("amazon.")) {
+            let endpoint = std::env::var("LOCAL_LLM_ENDPOINT")
+                .unwrap_or_else(|_| "http://localhost:11434".to_string());
+            return Ok(local(endpoint));
+        }
+
+        // Auto-detect from environment variables (backward compat)
+        if let Ok(api_key) = std::env::var("OPENAI_API_KEY") {
+            return Ok(openai(api_key));
+        }
+        if let Ok(api_key) = std::env::var("BEDROCK_API_KEY") {
+            return Ok(bedrock(api_key));
+        }
+        if let Ok(endpoint) = std::env::var("LOCAL_LLM_ENDPOINT") {
+            return Ok(local(endpoint));
+        }
+        Ok(Self::Stub)
     }
 
-    if let Ok(api_key) = std::env::var("BEDROCK_API_KEY") {
-        let region = std::env::var("AWS_REGION").unwrap_or_else(|_| "us-east-1".to_string());
-        return call_bedrock_embedding(&api_key, &region, text).await;
+    /// Batch limits for backends that accept several inputs per call.
+    fn batch_config(&self) -> Option<BatchConfig> {
+        match self {
+            // Local models: keep batches GPU-sized and one in flight
+            Self::Local { .. } => Some(BatchConfig {
+                max_batch: 32,
+                max_weight: 16_384,
+                max_wait: Duration::from_millis(20),
+                max_in_flight: 1,
+            }),
+            // OpenAI takes at most 300k tokens per request
+            Self::OpenAi { .. } => Some(BatchConfig {
+                max_batch: 256,
+                max_weight: 200_000,
+                max_wait: Duration::from_millis(20),
+                max_in_flight: 4,
+            }),
+            Self::Bedrock { .. } | Self::Stub => None,
+        }
     }
 
-    // Fallback: generate a simple hash-based "embedding" for testing
-    let hash = simple_hash_embedding(text);
-    Ok(serde_json::json!({
-        "vector": hash,
-        "dimension": hash.len(),
-        "stub": true
-    }))
+    /// Names the backend's batcher without holding its secret: the provider,
+    /// the credential it uses, and its model or endpoint.
+    fn batch_key(&self) -> String {
+        match self {
+            Self::OpenAi { model, .. } => format!("openai:OPENAI_API_KEY:{model}"),
+            Self::Local { endpoint, model } => format!("local:{endpoint}:{model}"),
+            Self::Bedrock { region, .. } => format!("bedrock:BEDROCK_API_KEY:{region}"),
+            Self::Stub => "stub".into(),
+        }
+    }
+
+    async fn embed_batch(&self, texts: Vec<String>) -> Result<Vec<Vec<f64>>, ExecutorError> {
+        match self {
+            Self::OpenAi { api_key, model } => call_openai_embedding(api_key, model, &texts).await,
+            Self::Local { endpoint, model } => call_local_embedding(endpoint, model, &texts).await,
+            Self::Bedrock { api_key, region } => {
+                let mut vectors = Vec::with_capacity(texts.len());
+                for text in &texts {
+                    vectors.push(call_bedrock_embedding(api_key, region, text).await?);
+                }
+                Ok(vectors)
+            }
+            Self::Stub => Ok(texts.iter().map(|t| simple_hash_embedding(t)).collect()),
+        }
+    }
+}
+
+type EmbeddingBatcher = Batcher<String, Vec<f64>>;
+
+/// One batcher per backend, shared by every node in the process so that
+/// concurrent requests from parallel branches end up in the same calls.
+/// Keyed by [`EmbeddingBackend::batch_key`], next to the backend it sends to.
+static EMBEDDING_BATCHERS: std::sync::OnceLock<
+    std::sync::Mutex<HashMap<String, (EmbeddingBackend, EmbeddingBatcher)>>,
+> = std::sync::OnceLock::new();
+
+fn embedding_batcher(backend: &EmbeddingBackend, config: BatchConfig) -> EmbeddingBatcher {
+    let batchers = EMBEDDING_BATCHERS.get_or_init(Default::default);
+    let mut batchers = batchers.lock().unwrap_or_else(|e| e.into_inner());
+    let key = backend.batch_key();
+    // A changed key in the same slot (e.g. a rotated API key) gets a new batcher
+    if let Some((_, batcher)) = batchers.get(&key).filter(|(b, batcher)| b == backend && !batcher.is_closed()) {
+        return batcher.clone();
+    }
+    let owned = backend.clone();
+    let batcher = Batcher::new(config, |text: &String| embedding_tokens(text), move |texts: Vec<String>| {
+        let backend = owned.clone();
+        async move { backend.embed_batch(texts).await.map_err(|e| e.to_string()) }
+    });
+    batchers.insert(key, (backend.clone(), batcher.clone()));
+    batcher
+}
+
+/// An upper estimate of the tokens in `text`: a token is at least three
+/// bytes of UTF-8 in English and about one CJK character.
+fn embedding_tokens(text: &str) -> usize {
+    text.len() / 3 + 1
+}
+
+/// Embed `texts` in order, coalescing with other nodes' requests when the
+/// backend supports batching.
+async fn embed_many(
+    backend: &EmbeddingBackend,
+    texts: Vec<String>,
+    progress: &ProgressReporter,
+) -> Result<Vec<Vec<f64>>, ExecutorError> {
+    let total = texts.len() as u64;
+    progress.items(0, total, "embedding chunks");
+
+    let Some(config) = backend.batch_config() else {
+        let mut vectors = Vec::with_capacity(texts.len());
+        for (i, text) in texts.into_iter().enumerate() {
+            vectors.extend(backend.embed_batch(vec![text]).await?);
+            progress.items(i as u64 + 1, total, "embedding chunks");
+        }
+        return Ok(vectors);
+    };
+
+    let batcher = embedding_batcher(backend, config);
+    let mut tasks = tokio::task::JoinSet::new();
+    for (i, text) in texts.into_iter().enumerate() {
+        let batcher = batcher.clone();
+        tasks.spawn(async move { (i, batcher.submit(text).await) });
+    }
+    let mut vectors = vec![Vec::new(); total as usize];
+    let mut done = 0;
+    while let Some(joined) = tasks.join_next().await {
+        let (i, result) = joined.map_err(|e| ExecutorError::ExecutionFailed(format!("Embedding task failed: {e}")))?;
+        vectors[i] = result.map_err(ExecutorError::ExecutionFailed)?;
+        done += 1;
+        progress.items(done, total, "embedding chunks");
+    }
+    Ok(vectors)
 }
 
 fn execute_vector_store(input: &ToolInput) -> Result<serde_json::Value, ExecutorError> {
@@ -961,6 +2501,7 @@ fn execute_vector_store(input: &ToolInput) -> Result<serde_json::Value, Executor
 
     // Generate embeddings for chunks if vectors not provided
     let entries: Vec<serde_json::Value> = (0..count).map(|i| {
+        input.progress.items(i as u64 + 1, count as u64, "indexing chunks");
         let vec = vectors.as_array()
             .and_then(|a| a.get(i))
             .cloned()
@@ -1102,49 +2643,244 @@ async fn execute_reranker(input: &ToolInput) -> Result<serde_json::Value, Execut
     }))
 }
 
-// ---- System Tools for Workflow Nodes ----
+// ---- Knowledge Graph Tools ----
 
-async fn execute_bash(input: &ToolInput) -> Result<serde_json::Value, ExecutorError> {
-    let command = input.inputs.get("command")
-        .or_else(|| input.config.get("command"))
+const GRAPH_EXTRACT_PROMPT: &str = "You extract a knowledge graph from documents such as contracts \
+and standards. Return ONLY a JSON array of facts stated in the text, each as \
+{\"subject\": ..., \"predicate\": ..., \"object\": ..., \"evidence\": ..., \"confidence\": ...}. \
+Use the shortest name that identifies an entity and spell it the same way every time. \
+Write predicates in snake_case (e.g. \"supplies\", \"conforms_to\", \"has_deadline\"). \
+\"evidence\" is the sentence the fact comes from, quoted from the text; \"confidence\" is 0 to 1. \
+Keep entity names in the language of the text. Return [] when the text states no facts.";
+
+/// Open the graph named by the `graph` config in the `db_path` file,
+/// relative to the workspace root.
+fn open_graph(input: &ToolInput) -> Result<(GraphStore, String), ExecutorError> {
+    let graph = input.config.get("graph")
         .and_then(|v| v.as_str())
-        .unwrap_or("echo 'no command'");
-    let working_dir = input.config.get("working_dir")
+        .filter(|g| !g.trim().is_empty())
+        .unwrap_or("default")
+        .to_string();
+    let db_path = input.config.get("db_path")
         .and_then(|v| v.as_str())
-        .unwrap_or(".");
-    let timeout_ms = input.config.get("timeout_ms")
-        .and_then(|v| v.as_u64())
-        .unwrap_or(30_000);
+        .filter(|p| !p.trim().is_empty())
+        .unwrap_or("knowledge-graph.sqlite");
+    Ok((GraphStore::open(&input.output_path(db_path), &graph)?, graph))
+}
 
-    let timeout = std::time::Duration::from_millis(timeout_ms.min(120_000));
+async fn execute_graph_extract(input: &ToolInput) -> Result<serde_json::Value, ExecutorError> {
+    let source = input.inputs.get("source")
+        .or_else(|| input.config.get("source"))
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .map(str::to_string);
+
+    // Facts extracted upstream are stored as given
+    let triples = if let Some(given) = input.inputs.get("triples").filter(|v| v.is_array()) {
+        let mut triples: Vec<Triple> = serde_json::from_value(given.clone())
+            .map_err(|e| ExecutorError::ExecutionFailed(format!("Invalid triples: {e}")))?;
+        for triple in &mut triples {
+            if triple.source.is_none() {
+                triple.source = source.clone();
+            }
+        }
+        triples
+    } else {
+        let chunks: Vec<String> = match input.inputs.get("text").or_else(|| input.inputs.get("chunks")) {
+            Some(serde_json::Value::String(s)) => vec![s.clone()],
+            Some(serde_json::Value::Array(arr)) => arr.iter()
+                .filter_map(|v| v.as_str())
+                .map(str::to_string)
+                .collect(),
+            _ => Vec::new(),
+        };
+        let chunks: Vec<_> = chunks.into_iter().enumerate().filter(|(_, c)| !c.trim().is_empty()).collect();
+        if chunks.is_empty() {
+            return Err(ExecutorError::ExecutionFailed(
+                "No text provided for graph extraction. Connect a document or its chunks to 'text'.".into()
+            ));
+        }
 
-    #[cfg(target_os = "windows")]
-    let child = tokio::process::Command::new("cmd")
-        .args(["/C", command])
-        .current_dir(working_dir)
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::piped())
-        .spawn();
+        let mut system_prompt = GRAPH_EXTRACT_PROMPT.to_string();
+        for (key, label) in [("entity_types", "Entity types to extract"), ("relations", "Relations to extract")] {
+            if let Some(hint) = input.config.get(key).and_then(|v| v.as_str()).filter(|h| !h.trim().is_empty()) {
+                system_prompt.push_str(&format!(" {label}: {hint}."));
+            }
+        }
 
-    #[cfg(not(target_os = "windows"))]
-    let child = tokio::process::Command::new("sh")
-        .args(["-c", command])
-        .current_dir(working_dir)
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::piped())
-        .spawn();
+        let total = chunks.len() as u64;
+        let mut triples = Vec::new();
+        for (done, (index, chunk)) in chunks.into_iter().enumerate() {
+            input.progress.items(done as u64, total, "extracting facts");
+            let chat_input = ToolInput {
+                tool_ref: input.tool_ref.clone(),
+                inputs: serde_json::json!({ "prompt": chunk }),
+                config: serde_json::json!({
+                    "system_prompt": system_prompt,
+                    "model": input.config.get("model").cloned().unwrap_or(serde_json::json!("claude-3-haiku-20240307")),
+                    "max_tokens": input.config.get("max_tokens").cloned().unwrap_or(serde_json::json!(4096)),
+                    "temperature": 0.0
+                }),
+                llm_provider: input.llm_provider.clone(),
+                env: input.env.clone(),
+                progress: input.progress.clone(),
+                stream: Default::default(),
+                commands: input.commands.clone(),
+                secrets: input.secrets.clone(),
+                scratch_dir: input.scratch_dir.clone(),
+            };
+            let result = execute_llm_chat(&chat_input).await?;
+            let response = result.get("response").and_then(|v| v.as_str()).unwrap_or("");
+            for mut triple in parse_extracted_triples(response) {
+                triple.source = source.clone();
+                triple.chunk = Some(index as u64);
+                triples.push(triple);
+            }
+        }
+        input.progress.items(total, total, "extracting facts");
+        triples
+    };
 
-    let child = child.map_err(|e| ExecutorError::Process(format!("Spawn failed: {e}")))?;
+    let (mut store, graph) = open_graph(input)?;
+    let added = store.insert(&triples)?;
+    Ok(serde_json::json!({
+        "triples": triples,
+        "count": triples.len(),
+        "added": added,
+        "graph": graph
+    }))
+}
 
-    match tokio::time::timeout(timeout, child.wait_with_output()).await {
-        Ok(Ok(output)) => {
-            let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-            let exit_code = output.status.code().unwrap_or(-1);
-            Ok(serde_json::json!({
-                "stdout": stdout,
-                "stderr": stderr,
-                "exit_code": exit_code,
+/// The facts in an extraction response, which may wrap the JSON array in
+/// prose or a code fence. Entries that aren't facts are dropped.
+fn parse_extracted_triples(response: &str) -> Vec<Triple> {
+    let (Some(start), Some(end)) = (response.find('['), response.rfind(']')) else {
+        return Vec::new();
+    };
+    if end < start {
+        return Vec::new();
+    }
+    serde_json::from_str::<Vec<serde_json::Value>>(&response[start..=end])
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|v| serde_json::from_value(v).ok())
+        .collect()
+}
+
+fn execute_graph_query(input: &ToolInput) -> Result<serde_json::Value, ExecutorError> {
+    let param = |key: &str| {
+        input.inputs.get(key)
+            .or_else(|| input.config.get(key))
+            .and_then(|v| v.as_str())
+            .filter(|v| !v.trim().is_empty())
+            .map(str::to_string)
+    };
+    let limit = input.config.get("limit").and_then(|v| v.as_u64()).unwrap_or(50) as usize;
+    let (store, graph) = open_graph(input)?;
+
+    let mode = input.config.get("mode").and_then(|v| v.as_str()).unwrap_or("auto");
+    let entity = param("entity");
+    let (entities, triples) = match (mode, entity) {
+        ("neighborhood", None) => {
+            return Err(ExecutorError::ExecutionFailed(
+                "Neighborhood queries need an 'entity' to start from.".into()
+            ));
+        }
+        ("neighborhood" | "auto", Some(entity)) => {
+            let depth = input.config.get("depth").and_then(|v| v.as_u64()).unwrap_or(1) as usize;
+            let found = store.neighborhood(&entity, depth, limit)?;
+            (found.entities, found.triples)
+        }
+        _ => {
+            let pattern = Pattern {
+                subject: param("subject"),
+                predicate: param("predicate"),
+                object: param("object"),
+            };
+            let triples = store.find(&pattern, limit)?;
+            let mut entities: Vec<String> = Vec::new();
+            for triple in &triples {
+                for entity in [&triple.subject, &triple.object] {
+                    if !entities.contains(entity) {
+                        entities.push(entity.clone());
+                    }
+                }
+            }
+            (entities, triples)
+        }
+    };
+
+    // One fact per line, with its source, for an LLM prompt's context
+    let context = triples.iter()
+        .map(|t| {
+            let mut line = format!("{} —{}→ {}", t.subject, t.predicate, t.object);
+            if let Some(source) = &t.source {
+                line.push_str(&format!(" [{source}]"));
+            }
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Ok(serde_json::json!({
+        "triples": triples,
+        "entities": entities,
+        "context": context,
+        "count": triples.len(),
+        "graph": graph
+    }))
+}
+
+// ---- System Tools for Workflow Nodes ----
+
+/// Runs a command line through the system shell. The shell itself goes
+/// through the command gate, so the line runs only if the shell is on the
+/// allowlist or the approver accepts the whole line.
+async fn execute_bash(input: &ToolInput) -> Result<serde_json::Value, ExecutorError> {
+    let command = input.inputs.get("command")
+        .or_else(|| input.config.get("command"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("echo 'no command'");
+    let working_dir = input.resolve_path(
+        input.config.get("working_dir")
+            .and_then(|v| v.as_str())
+            .unwrap_or("."),
+    );
+    let (shell, flag) = if cfg!(target_os = "windows") { ("cmd", "/C") } else { ("sh", "-c") };
+    input
+        .commands
+        .check(&CommandRequest {
+            tool_ref: input.tool_ref.clone(),
+            program: shell.into(),
+            args: vec![flag.into(), command.into()],
+            working_dir: Some(path::display(&working_dir)),
+        })
+        .await?;
+    let timeout_ms = input.config.get("timeout_ms")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(30_000);
+
+    let timeout = std::time::Duration::from_millis(timeout_ms.min(120_000));
+
+    let child = tokio::process::Command::new(shell)
+        .args([flag, command])
+        .current_dir(working_dir)
+        .envs(crate::command::command_env(&input.env))
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn();
+
+    let child = child.map_err(|e| ExecutorError::Process(format!("Spawn failed: {e}")))?;
+
+    match tokio::time::timeout(timeout, child.wait_with_output()).await {
+        Ok(Ok(output)) => {
+            let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            let exit_code = output.status.code().unwrap_or(-1);
+            Ok(serde_json::json!({
+                "stdout": stdout,
+                "stderr": stderr,
+                "exit_code": exit_code,
                 "output": if exit_code == 0 { stdout } else { format!("Exit {exit_code}\n{stdout}{stderr}") }
             }))
         }
@@ -1153,6 +2889,170 @@ async fn execute_bash(input: &ToolInput) -> Result<serde_json::Value, ExecutorEr
     }
 }
 
+/// Default shell-exec limits: 30 seconds, 1 MiB per output stream.
+const SHELL_EXEC_TIMEOUT_MS: u64 = 30_000;
+const SHELL_EXEC_MAX_TIMEOUT_MS: u64 = 600_000;
+const SHELL_EXEC_MAX_OUTPUT: usize = 1024 * 1024;
+/// The most `max_output_bytes` can raise the per-stream limit to.
+const SHELL_EXEC_OUTPUT_CEILING: u64 = 64 * 1024 * 1024;
+
+/// Runs one program without a shell, after the command gate allows it.
+/// `args` entries are single arguments with `{input.<port>}` and
+/// `{config.<key>}` filled in; the `stdin` input, if any, is written to the
+/// program. Output beyond `max_output_bytes` per stream (at most 64 MiB)
+/// is dropped; variables that pick the program or what it loads, such as
+/// `PATH` or `LD_PRELOAD`, are not taken from the workspace.
+async fn execute_shell_exec(input: &ToolInput) -> Result<serde_json::Value, ExecutorError> {
+    use tokio::io::AsyncWriteExt;
+
+    let command = input
+        .config
+        .get("command")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .ok_or_else(|| ExecutorError::ExecutionFailed("Missing 'command' config".into()))?;
+    let args = match input.config.get("args") {
+        None | Some(serde_json::Value::Null) => Vec::new(),
+        Some(serde_json::Value::String(text)) if text.trim().is_empty() => Vec::new(),
+        Some(serde_json::Value::String(text)) => serde_json::from_str::<Vec<String>>(text)
+            .map_err(|_| ExecutorError::ExecutionFailed("'args' must be a JSON list of strings".into()))?,
+        Some(value) => serde_json::from_value::<Vec<String>>(value.clone())
+            .map_err(|_| ExecutorError::ExecutionFailed("'args' must be a list of strings".into()))?,
+    };
+    let spec = ProcessSpec {
+        command: command.to_string(),
+        args,
+        env: BTreeMap::new(),
+        working_dir: input.config.get("working_dir").and_then(|v| v.as_str()).filter(|d| !d.trim().is_empty()).map(str::to_string),
+    };
+    let launch = ProcessLaunch::new(&spec, input)?;
+
+    let request = CommandRequest {
+        tool_ref: input.tool_ref.clone(),
+        program: launch.program.clone(),
+        args: launch.args.clone(),
+        working_dir: launch.working_dir.as_deref().map(path::display),
+    };
+    let approved = input.commands.check(&request).await?;
+
+    let timeout_ms = input
+        .config
+        .get("timeout_ms")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(SHELL_EXEC_TIMEOUT_MS)
+        .clamp(1, SHELL_EXEC_MAX_TIMEOUT_MS);
+    let max_output = input
+        .config
+        .get("max_output_bytes")
+        .and_then(|v| v.as_u64())
+        .map_or(SHELL_EXEC_MAX_OUTPUT, |n| n.clamp(1, SHELL_EXEC_OUTPUT_CEILING) as usize);
+    let stdin_text = match input.inputs.get("stdin") {
+        None | Some(serde_json::Value::Null) => None,
+        Some(serde_json::Value::String(s)) => Some(s.clone()),
+        Some(other) => Some(other.to_string()),
+    };
+
+    let mut command = tokio::process::Command::new(&launch.program);
+    command
+        .args(&launch.args)
+        .envs(crate::command::command_env(&input.env))
+        .stdin(if stdin_text.is_some() { std::process::Stdio::piped() } else { std::process::Stdio::null() })
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(dir) = &launch.working_dir {
+        command.current_dir(dir);
+    }
+    let start = Instant::now();
+    let mut child = command
+        .spawn()
+        .map_err(|e| ExecutorError::Process(format!("Failed to start '{}': {e}", launch.program)))?;
+
+    let stdin = child.stdin.take();
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    let run = async {
+        let write = async {
+            if let (Some(mut stdin), Some(text)) = (stdin, stdin_text) {
+                // The program may exit without reading its input
+                let _ = stdin.write_all(text.as_bytes()).await;
+            }
+        };
+        let (_, stdout, stderr, status) = tokio::join!(
+            write,
+            read_capped(stdout, max_output, &input.progress, input.stream.lines("stdout")),
+            read_capped(stderr, max_output, &input.progress, input.stream.lines("stderr")),
+            child.wait(),
+        );
+        Ok::<_, std::io::Error>((stdout?, stderr?, status?))
+    };
+    let ((stdout, stdout_truncated), (stderr, stderr_truncated), status) =
+        match tokio::time::timeout(Duration::from_millis(timeout_ms), run).await {
+            Ok(result) => result.map_err(|e| ExecutorError::Process(format!("Process error: {e}")))?,
+            // Dropping the future kills the child
+            Err(_) => return Err(ExecutorError::Timeout(timeout_ms)),
+        };
+
+    let text = |bytes: &[u8]| encoding::decode(bytes, None).map(|d| d.text).unwrap_or_default();
+    let (stdout, stderr) = (text(&stdout), text(&stderr));
+    let exit_code = status.code();
+    let fail_on_error = input.config.get("fail_on_error").and_then(|v| v.as_bool()).unwrap_or(true);
+    if fail_on_error && !status.success() {
+        let tail: String = {
+            let chars: Vec<char> = stderr.trim_end().chars().collect();
+            chars[chars.len().saturating_sub(2000)..].iter().collect()
+        };
+        return Err(ExecutorError::ExecutionFailed(format!(
+            "'{}' exited with {status}: {tail}",
+            request.display()
+        )));
+    }
+    Ok(serde_json::json!({
+        "stdout": stdout,
+        "stderr": stderr,
+        "exit_code": exit_code,
+        "truncated": stdout_truncated || stderr_truncated,
+        "command": request.display(),
+        "approved": approved,
+        "duration_ms": start.elapsed().as_millis() as u64,
+    }))
+}
+
+/// Read a stream to the end, keeping at most `cap` bytes so a chatty
+/// program can't exhaust memory (the rest is read and dropped, so it never
+/// blocks on a full pipe). Returns the bytes kept and whether any were dropped.
+/// Output counts as a heartbeat, and is streamed as it arrives.
+async fn read_capped<R>(
+    reader: Option<R>,
+    cap: usize,
+    progress: &ProgressReporter,
+    mut lines: LineChunks,
+) -> std::io::Result<(Vec<u8>, bool)>
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    use tokio::io::AsyncReadExt;
+    let Some(mut reader) = reader else {
+        return Ok((Vec::new(), false));
+    };
+    let mut kept = Vec::new();
+    let mut truncated = false;
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = reader.read(&mut buf).await?;
+        if n == 0 {
+            lines.finish();
+            return Ok((kept, truncated));
+        }
+        progress.beat();
+        lines.write(&buf[..n]);
+        let room = cap.saturating_sub(kept.len());
+        truncated |= n > room;
+        kept.extend_from_slice(&buf[..n.min(room)]);
+    }
+}
+
 async fn execute_grep(input: &ToolInput) -> Result<serde_json::Value, ExecutorError> {
     let pattern = input.inputs.get("pattern")
         .or_else(|| input.config.get("pattern"))
@@ -1426,6 +3326,146 @@ async fn execute_http_request(input: &ToolInput) -> Result<serde_json::Value, Ex
     }))
 }
 
+// ---- Public Data API Tool (data.go.kr-style services) ----
+
+/// Fetch records from a public data API page by page, normalized to JSON
+/// whether the service answers in XML or JSON.
+async fn execute_public_api_fetch(input: &ToolInput) -> Result<serde_json::Value, ExecutorError> {
+    use crate::public_api::{self, KeyPlacement, Service};
+
+    let service = Service::from_config(&input.config)
+        .map_err(|e| ExecutorError::ExecutionFailed(format!("public-api-fetch: {e}")))?;
+
+    // Connected parameters win over the ones in config
+    let mut params = BTreeMap::new();
+    for source in [input.config.get("params"), input.inputs.get("params")] {
+        // The editor sends JSON fields as text
+        let source = match source {
+            Some(serde_json::Value::String(s)) if !s.trim().is_empty() => Some(
+                serde_json::from_str::<serde_json::Value>(s)
+                    .map_err(|e| ExecutorError::ExecutionFailed(format!("public-api-fetch: params is not JSON: {e}")))?,
+            ),
+            other => other.cloned(),
+        };
+        for (name, value) in source.as_ref().and_then(|v| v.as_object()).into_iter().flatten() {
+            let value = match value {
+                serde_json::Value::String(s) => s.clone(),
+                serde_json::Value::Null => continue,
+                other => other.to_string(),
+            };
+            params.insert(name.clone(), value);
+        }
+    }
+    let missing = service.missing_params(&params);
+    if !missing.is_empty() {
+        return Err(ExecutorError::ExecutionFailed(format!(
+            "{} needs the parameters {}",
+            service.name,
+            missing.join(", ")
+        )));
+    }
+
+    let key = input
+        .config
+        .get("service_key")
+        .and_then(|v| v.as_str())
+        .filter(|k| !k.trim().is_empty())
+        .map(str::to_string)
+        .or_else(|| input.env.get(&service.key_env).cloned())
+        .or_else(|| std::env::var(&service.key_env).ok())
+        .filter(|k| !k.trim().is_empty())
+        .map(|k| public_api::normalize_key(&k))
+        .ok_or_else(|| {
+            ExecutorError::MissingCredential(format!(
+                "{} needs a service key; add {} under API keys in Settings",
+                service.name, service.key_env
+            ))
+        })?;
+
+    let max_pages = input.config.get("max_pages").and_then(|v| v.as_u64()).unwrap_or(10).max(1) as u32;
+    let max_items = input.config.get("max_items").and_then(|v| v.as_u64()).filter(|&n| n > 0).map(|n| n as usize);
+    let timeout_secs = input.config.get("timeout").and_then(|v| v.as_u64()).unwrap_or(30);
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(timeout_secs))
+        .build()
+        .map_err(|e| ExecutorError::ExecutionFailed(format!("HTTP client error: {e}")))?;
+
+    let mut items = Vec::new();
+    let mut total = None;
+    let mut pages = 0;
+    let mut calls_today = 0;
+    for page in 1..=max_pages {
+        let (wait, used) = public_api::reserve_call(&service).map_err(|used| {
+            ExecutorError::ExecutionFailed(format!(
+                "{}: daily limit of {} calls reached ({used} made today); raise daily_limit if the key allows more",
+                service.name,
+                service.daily_limit.unwrap_or_default()
+            ))
+        })?;
+        calls_today = used;
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+
+        let mut request = client
+            .get(&service.url)
+            .header("User-Agent", "Handbox/2.0")
+            .query(&service.page_query(&params, page));
+        request = match &service.key {
+            KeyPlacement::Query(name) => request.query(&[(name, &key)]),
+            KeyPlacement::Header { name, prefix } => request.header(name.as_str(), format!("{prefix}{key}")),
+        };
+        let response = request
+            .send()
+            .await
+            // The URL carries the key when it goes in the query
+            .map_err(|e| ExecutorError::ExecutionFailed(format!("{}: request failed: {}", service.name, e.without_url())))?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| ExecutorError::ExecutionFailed(format!("{}: failed to read reply: {}", service.name, e.without_url())))?;
+
+        // Error documents come with error statuses too; read those first
+        let body = match public_api::parse_body(&text) {
+            Ok(body) => body,
+            Err(_) if !status.is_success() => {
+                let start: String = text.trim().chars().take(200).collect();
+                return Err(ExecutorError::ExecutionFailed(format!("{}: HTTP {status}: {start}", service.name)));
+            }
+            Err(e) => return Err(ExecutorError::ExecutionFailed(format!("{}: {e}", service.name))),
+        };
+        let reply = public_api::read_page(&service, &body)
+            .map_err(|fault| ExecutorError::ExecutionFailed(format!("{}: {fault}", service.name)))?;
+
+        pages += 1;
+        total = reply.total.or(total);
+        let received = reply.items.len();
+        items.extend(reply.items);
+        input.progress.items(items.len() as u64, total.unwrap_or(items.len() as u64), "fetching records");
+
+        let last_page = received < service.page_size as usize
+            || total.is_some_and(|t| items.len() as u64 >= t)
+            || max_items.is_some_and(|max| items.len() >= max);
+        if last_page {
+            break;
+        }
+    }
+    if let Some(max) = max_items {
+        items.truncate(max);
+    }
+
+    Ok(serde_json::json!({
+        "items": items,
+        "count": items.len(),
+        "total_count": total,
+        "pages": pages,
+        "service": service.id,
+        "calls_today": calls_today,
+        "daily_limit": service.daily_limit,
+    }))
+}
+
 // ---- GIS Tools for Workflow Nodes ----
 
 fn execute_gis_read(input: &ToolInput) -> Result<serde_json::Value, ExecutorError> {
@@ -1434,7 +3474,7 @@ fn execute_gis_read(input: &ToolInput) -> Result<serde_json::Value, ExecutorErro
         .and_then(|v| v.as_str())
         .unwrap_or("");
 
-    let content = std::fs::read_to_string(file_path)
+    let content = std::fs::read_to_string(input.resolve_path(file_path))
         .map_err(|e| ExecutorError::ExecutionFailed(format!("Failed to read GIS file: {e}")))?;
 
     let fc: serde_json::Value = serde_json::from_str(&content)
@@ -1462,7 +3502,7 @@ fn execute_gis_write(input: &ToolInput) -> Result<serde_json::Value, ExecutorErr
     let json = serde_json::to_string_pretty(&features)
         .map_err(|e| ExecutorError::ExecutionFailed(format!("Serialization failed: {e}")))?;
 
-    std::fs::write(output_path, &json)
+    std::fs::write(input.output_path(output_path), &json)
         .map_err(|e| ExecutorError::ExecutionFailed(format!("Write failed: {e}")))?;
 
     Ok(serde_json::json!({
@@ -1575,7 +3615,7 @@ fn execute_ifc_read(input: &ToolInput) -> Result<serde_json::Value, ExecutorErro
         .and_then(|v| v.as_str())
         .unwrap_or("");
 
-    let content = std::fs::read_to_string(file_path)
+    let content = std::fs::read_to_string(input.resolve_path(file_path))
         .map_err(|e| ExecutorError::ExecutionFailed(format!("Failed to read IFC file: {e}")))?;
 
     // Basic STEP parsing: count entities and extract types
@@ -1613,7 +3653,7 @@ fn execute_ifc_query(input: &ToolInput) -> Result<serde_json::Value, ExecutorErr
         .and_then(|v| v.as_str())
         .unwrap_or("");
 
-    let content = std::fs::read_to_string(file_path)
+    let content = std::fs::read_to_string(input.resolve_path(file_path))
         .map_err(|e| ExecutorError::ExecutionFailed(format!("Failed to read IFC file: {e}")))?;
 
     let query_upper = entity_type.to_uppercase();
@@ -1641,6 +3681,104 @@ fn execute_ifc_query(input: &ToolInput) -> Result<serde_json::Value, ExecutorErr
 
 // ---- LLM API Helpers ----
 
+/// Wire format of a streamed LLM reply.
+#[derive(Debug, Clone, Copy)]
+enum ReplyFormat {
+    /// Server-sent events with `content_block_delta` text.
+    Anthropic,
+    /// Server-sent events with `choices[0].delta.content`, usage last.
+    OpenAi,
+    /// One JSON object per line, the last with `done` and the counts.
+    Ollama,
+}
+
+/// A reply assembled from its streamed pieces.
+#[derive(Debug, Default, PartialEq)]
+struct StreamedReply {
+    text: String,
+    input_tokens: i64,
+    output_tokens: i64,
+}
+
+impl StreamedReply {
+    /// Apply one line of the stream, sending new text to `stream`.
+    fn apply(&mut self, format: ReplyFormat, line: &str, stream: &OutputStream) {
+        let json = match format {
+            ReplyFormat::Anthropic | ReplyFormat::OpenAi => line.trim().strip_prefix("data:").map(str::trim),
+            ReplyFormat::Ollama => Some(line.trim()),
+        };
+        let Some(event) = json.and_then(|j| serde_json::from_str::<serde_json::Value>(j).ok()) else {
+            return;
+        };
+        let count = |v: &serde_json::Value| v.as_i64();
+        let piece = match format {
+            ReplyFormat::Anthropic => {
+                if let Some(n) = count(&event["message"]["usage"]["input_tokens"]) {
+                    self.input_tokens = n;
+                }
+                if let Some(n) = count(&event["usage"]["output_tokens"]) {
+                    self.output_tokens = n;
+                }
+                event["delta"]["text"].as_str()
+            }
+            ReplyFormat::OpenAi => {
+                if let Some(n) = count(&event["usage"]["prompt_tokens"]) {
+                    self.input_tokens = n;
+                }
+                if let Some(n) = count(&event["usage"]["completion_tokens"]) {
+                    self.output_tokens = n;
+                }
+                event["choices"][0]["delta"]["content"].as_str()
+            }
+            ReplyFormat::Ollama => {
+                if let Some(n) = count(&event["prompt_eval_count"]) {
+                    self.input_tokens = n;
+                }
+                if let Some(n) = count(&event["eval_count"]) {
+                    self.output_tokens = n;
+                }
+                event["response"].as_str()
+            }
+        };
+        if let Some(piece) = piece.filter(|p| !p.is_empty()) {
+            self.text.push_str(piece);
+            stream.send("response", piece);
+        }
+    }
+
+    fn into_output(self, model: &str) -> serde_json::Value {
+        serde_json::json!({
+            "response": self.text,
+            "model": model,
+            "input_tokens": self.input_tokens,
+            "output_tokens": self.output_tokens
+        })
+    }
+}
+
+/// Read a streamed LLM reply to the end, sending its text as it arrives.
+async fn read_streamed_reply(
+    mut response: reqwest::Response,
+    format: ReplyFormat,
+    stream: &OutputStream,
+) -> Result<StreamedReply, ExecutorError> {
+    let mut reply = StreamedReply::default();
+    let mut pending = Vec::new();
+    while let Some(bytes) = response
+        .chunk()
+        .await
+        .map_err(|e| ExecutorError::ExecutionFailed(format!("Reading the streamed reply failed: {e}")))?
+    {
+        pending.extend_from_slice(&bytes);
+        while let Some(end) = pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=end).collect();
+            reply.apply(format, &String::from_utf8_lossy(&line), stream);
+        }
+    }
+    reply.apply(format, &String::from_utf8_lossy(&pending), stream);
+    Ok(reply)
+}
+
 async fn call_anthropic_api(
     api_key: &str,
     model: &str,
@@ -1648,13 +3786,15 @@ async fn call_anthropic_api(
     system_prompt: &str,
     max_tokens: i32,
     temperature: f32,
+    stream: &OutputStream,
 ) -> Result<serde_json::Value, ExecutorError> {
     let body = serde_json::json!({
         "model": model,
         "max_tokens": max_tokens,
         "temperature": temperature,
         "system": system_prompt,
-        "messages": [{"role": "user", "content": prompt}]
+        "messages": [{"role": "user", "content": prompt}],
+        "stream": stream.is_enabled()
     });
 
     let client = reqwest::Client::new();
@@ -1672,6 +3812,9 @@ async fn call_anthropic_api(
         let error_body = response.text().await.unwrap_or_default();
         return Err(ExecutorError::ExecutionFailed(format!("Anthropic API error: {error_body}")));
     }
+    if stream.is_enabled() {
+        return Ok(read_streamed_reply(response, ReplyFormat::Anthropic, stream).await?.into_output(model));
+    }
 
     let result: serde_json::Value = response
         .json()
@@ -1695,8 +3838,27 @@ async fn call_openai_api(
     system_prompt: &str,
     max_tokens: i32,
     temperature: f32,
+    stream: &OutputStream,
 ) -> Result<serde_json::Value, ExecutorError> {
-    let body = serde_json::json!({
+    let url = "https://api.openai.com/v1/chat/completions";
+    call_chat_completions("OpenAI", url, Some(api_key), model, prompt, system_prompt, max_tokens, temperature, stream).await
+}
+
+/// Call an OpenAI-compatible chat completions endpoint; `service` names it
+/// in errors. Local servers such as llama.cpp take no API key.
+#[allow(clippy::too_many_arguments)]
+async fn call_chat_completions(
+    service: &str,
+    url: &str,
+    api_key: Option<&str>,
+    model: &str,
+    prompt: &str,
+    system_prompt: &str,
+    max_tokens: i32,
+    temperature: f32,
+    stream: &OutputStream,
+) -> Result<serde_json::Value, ExecutorError> {
+    let mut body = serde_json::json!({
         "model": model,
         "messages": [
             {"role": "system", "content": system_prompt},
@@ -1705,20 +3867,29 @@ async fn call_openai_api(
         "max_tokens": max_tokens,
         "temperature": temperature
     });
+    if stream.is_enabled() {
+        body["stream"] = serde_json::json!(true);
+        body["stream_options"] = serde_json::json!({ "include_usage": true });
+    }
 
-    let client = reqwest::Client::new();
-    let response = client
-        .post("https://api.openai.com/v1/chat/completions")
-        .header("Authorization", format!("Bearer {api_key}"))
+    let mut request = reqwest::Client::new()
+        .post(url)
         .header("Content-Type", "application/json")
-        .json(&body)
+        .json(&body);
+    if let Some(api_key) = api_key {
+        request = request.header("Authorization", format!("Bearer {api_key}"));
+    }
+    let response = request
         .send()
         .await
-        .map_err(|e| ExecutorError::ExecutionFailed(format!("OpenAI request failed: {e}")))?;
+        .map_err(|e| ExecutorError::ExecutionFailed(format!("{service} request failed: {e}")))?;
 
     if !response.status().is_success() {
         let error_body = response.text().await.unwrap_or_default();
-        return Err(ExecutorError::ExecutionFailed(format!("OpenAI API error: {error_body}")));
+        return Err(ExecutorError::ExecutionFailed(format!("{service} API error: {error_body}")));
+    }
+    if stream.is_enabled() {
+        return Ok(read_streamed_reply(response, ReplyFormat::OpenAi, stream).await?.into_output(model));
     }
 
     let result: serde_json::Value = response
@@ -1745,65 +3916,134 @@ async fn call_bedrock_api(
     max_tokens: i32,
     temperature: f32,
 ) -> Result<serde_json::Value, ExecutorError> {
-    let model_id = if model.starts_with("anthropic.") {
-        model.to_string()
-    } else {
-        format!("anthropic.{}", model.replace("claude-", "claude-"))
-    };
+    let body = bedrock_messages_body(prompt, system_prompt, max_tokens, temperature);
+    let target = bedrock::resolve(model, region, None);
+    let (result, model_id) = invoke_bedrock_target(target, |model_id, region| {
+        let body = &body;
+        async move {
+            let url = format!("https://bedrock-runtime.{region}.amazonaws.com/model/{model_id}/invoke");
+            let response = reqwest::Client::new()
+                .post(&url)
+                .header("Authorization", format!("Bearer {api_key}"))
+                .header("Content-Type", "application/json")
+                .json(body)
+                .send()
+                .await
+                .map_err(|e| format!("Bedrock request failed: {e}"))?;
+            bedrock_response(response).await
+        }
+    })
+    .await?;
+    Ok(bedrock_chat_output(&result, model_id))
+}
 
-    let body = serde_json::json!({
+#[allow(clippy::too_many_arguments)]
+async fn call_bedrock_iam_api(
+    access_key: &str,
+    secret_key: &str,
+    region: &str,
+    model: &str,
+    prompt: &str,
+    system_prompt: &str,
+    max_tokens: i32,
+    temperature: f32,
+) -> Result<serde_json::Value, ExecutorError> {
+    let body = bedrock_messages_body(prompt, system_prompt, max_tokens, temperature);
+    let payload = serde_json::to_vec(&body)
+        .map_err(|e| ExecutorError::ExecutionFailed(format!("JSON serialization failed: {e}")))?;
+    let target = bedrock::resolve(model, region, None);
+    let (result, model_id) = invoke_bedrock_target(target, |model_id, region| {
+        let payload = payload.clone();
+        async move {
+            let response = signed_bedrock_request(access_key, secret_key, &region, &model_id, payload)
+                .send()
+                .await
+                .map_err(|e| format!("Bedrock IAM request failed: {e}"))?;
+            bedrock_response(response).await
+        }
+    })
+    .await?;
+    Ok(bedrock_chat_output(&result, model_id))
+}
+
+fn bedrock_messages_body(
+    prompt: &str,
+    system_prompt: &str,
+    max_tokens: i32,
+    temperature: f32,
+) -> serde_json::Value {
+    serde_json::json!({
         "anthropic_version": "bedrock-2023-05-31",
         "max_tokens": max_tokens,
         "temperature": temperature,
         "system": system_prompt,
         "messages": [{"role": "user", "content": prompt}]
-    });
-
-    let url = format!(
-        "https://bedrock-runtime.{}.amazonaws.com/model/{}/invoke",
-        region, model_id
-    );
+    })
+}
 
-    let client = reqwest::Client::new();
-    let response = client
-        .post(&url)
-        .header("Authorization", format!("Bearer {api_key}"))
-        .header("Content-Type", "application/json")
-        .json(&body)
-        .send()
-        .await
-        .map_err(|e| ExecutorError::ExecutionFailed(format!("Bedrock request failed: {e}")))?;
+fn bedrock_chat_output(result: &serde_json::Value, model_id: String) -> serde_json::Value {
+    let text = result["content"][0]["text"].as_str().unwrap_or("").to_string();
+    serde_json::json!({
+        "response": text,
+        "model": model_id,
+        "input_tokens": result["usage"]["input_tokens"].as_i64().unwrap_or(0),
+        "output_tokens": result["usage"]["output_tokens"].as_i64().unwrap_or(0)
+    })
+}
 
+async fn bedrock_response(response: reqwest::Response) -> Result<serde_json::Value, String> {
     if !response.status().is_success() {
-        let error_body = response.text().await.unwrap_or_default();
-        return Err(ExecutorError::ExecutionFailed(format!("Bedrock API error: {error_body}")));
+        return Err(response.text().await.unwrap_or_default());
     }
-
-    let result: serde_json::Value = response
+    response
         .json()
         .await
-        .map_err(|e| ExecutorError::ExecutionFailed(format!("Response parse failed: {e}")))?;
-
-    let text = result["content"][0]["text"].as_str().unwrap_or("").to_string();
+        .map_err(|e| format!("Response parse failed: {e}"))
+}
 
-    Ok(serde_json::json!({
-        "response": text,
-        "model": model_id,
-        "input_tokens": result["usage"]["input_tokens"].as_i64().unwrap_or(0),
-        "output_tokens": result["usage"]["output_tokens"].as_i64().unwrap_or(0)
-    }))
+/// Invoke a routed model, trying each region in turn and switching to the
+/// inference profile if Bedrock refuses on-demand invocation. Returns the
+/// response and the id that served it.
+async fn invoke_bedrock_target<F, Fut>(
+    mut target: bedrock::ModelTarget,
+    invoke: F,
+) -> Result<(serde_json::Value, String), ExecutorError>
+where
+    F: Fn(String, String) -> Fut,
+    Fut: std::future::Future<Output = Result<serde_json::Value, String>>,
+{
+    let mut last_error = String::from("no region to try");
+    loop {
+        let mut profiled = None;
+        for region in &target.regions {
+            match invoke(target.invoke_id.clone(), region.clone()).await {
+                Ok(result) => return Ok((result, target.invoke_id)),
+                Err(e) => last_error = e,
+            }
+            if let Some(profile) = target.profile_fallback.as_deref() {
+                if bedrock::needs_profile(&last_error) {
+                    profiled = Some(bedrock::resolve(profile, region, None));
+                    break;
+                }
+            }
+        }
+        match profiled {
+            Some(next) => target = next,
+            None => {
+                return Err(ExecutorError::ExecutionFailed(format!("Bedrock API error: {last_error}")));
+            }
+        }
+    }
 }
 
-async fn call_bedrock_iam_api(
+/// A Bedrock `invoke` request signed with AWS Signature V4.
+fn signed_bedrock_request(
     access_key: &str,
     secret_key: &str,
     region: &str,
-    model: &str,
-    prompt: &str,
-    system_prompt: &str,
-    max_tokens: i32,
-    temperature: f32,
-) -> Result<serde_json::Value, ExecutorError> {
+    model_id: &str,
+    payload: Vec<u8>,
+) -> reqwest::RequestBuilder {
     use chrono::Utc;
     use hmac::{Hmac, Mac};
     use sha2::{Digest, Sha256};
@@ -1830,42 +4070,6 @@ async fn call_bedrock_iam_api(
         hex::encode(hasher.finalize())
     }
 
-    // Convert model name to Bedrock model ID format
-    let model_id = if model.starts_with("anthropic.") {
-        model.to_string()
-    } else {
-        // Map common model names to Bedrock model IDs
-        match model {
-            "claude-3-5-sonnet-20240620" | "claude-3-5-sonnet" | "claude-sonnet-4-20250514" =>
-                "anthropic.claude-3-5-sonnet-20240620-v1:0".to_string(),
-            "claude-3-5-sonnet-20241022" =>
-                "anthropic.claude-3-5-sonnet-20241022-v2:0".to_string(),
-            "claude-3-haiku-20240307" | "claude-3-haiku" =>
-                "anthropic.claude-3-haiku-20240307-v1:0".to_string(),
-            "claude-3-sonnet-20240229" | "claude-3-sonnet" =>
-                "anthropic.claude-3-sonnet-20240229-v1:0".to_string(),
-            "claude-3-opus-20240229" | "claude-3-opus" =>
-                "anthropic.claude-3-opus-20240229-v1:0".to_string(),
-            _ if model.contains("claude") =>
-                // Try to construct a valid ID
-                format!("anthropic.{}-v1:0", model),
-            _ =>
-                // Default to Claude 3.5 Sonnet
-                "anthropic.claude-3-5-sonnet-20240620-v1:0".to_string(),
-        }
-    };
-
-    let body = serde_json::json!({
-        "anthropic_version": "bedrock-2023-05-31",
-        "max_tokens": max_tokens,
-        "temperature": temperature,
-        "system": system_prompt,
-        "messages": [{"role": "user", "content": prompt}]
-    });
-
-    let payload = serde_json::to_vec(&body)
-        .map_err(|e| ExecutorError::ExecutionFailed(format!("JSON serialization failed: {e}")))?;
-
     let host = format!("bedrock-runtime.{}.amazonaws.com", region);
     // Manually encode model_id for canonical URI (: -> %3A)
     let encoded_model_id = model_id.replace(":", "%3A");
@@ -1914,36 +4118,13 @@ async fn call_bedrock_iam_api(
         algorithm, access_key, credential_scope, signed_headers_str, signature
     );
 
-    let client = reqwest::Client::new();
-    let response = client
+    reqwest::Client::new()
         .post(&url)
         .header("Authorization", &authorization_header)
         .header("Content-Type", "application/json")
         .header("x-amz-date", &amz_date)
         .header("x-amz-content-sha256", &payload_hash)
         .body(payload)
-        .send()
-        .await
-        .map_err(|e| ExecutorError::ExecutionFailed(format!("Bedrock IAM request failed: {e}")))?;
-
-    if !response.status().is_success() {
-        let error_body = response.text().await.unwrap_or_default();
-        return Err(ExecutorError::ExecutionFailed(format!("Bedrock API error: {error_body}")));
-    }
-
-    let result: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| ExecutorError::ExecutionFailed(format!("Response parse failed: {e}")))?;
-
-    let text = result["content"][0]["text"].as_str().unwrap_or("").to_string();
-
-    Ok(serde_json::json!({
-        "response": text,
-        "model": model_id,
-        "input_tokens": result["usage"]["input_tokens"].as_i64().unwrap_or(0),
-        "output_tokens": result["usage"]["output_tokens"].as_i64().unwrap_or(0)
-    }))
 }
 
 async fn call_local_llm_api(
@@ -1953,6 +4134,7 @@ async fn call_local_llm_api(
     system_prompt: &str,
     max_tokens: i32,
     temperature: f32,
+    stream: &OutputStream,
 ) -> Result<serde_json::Value, ExecutorError> {
     let url = format!("{}/api/generate", endpoint.trim_end_matches('/'));
 
@@ -1960,7 +4142,7 @@ async fn call_local_llm_api(
         "model": model,
         "prompt": prompt,
         "system": system_prompt,
-        "stream": false,
+        "stream": stream.is_enabled(),
         "options": {
             "num_predict": max_tokens,
             "temperature": temperature
@@ -1980,6 +4162,9 @@ async fn call_local_llm_api(
         let error_body = response.text().await.unwrap_or_default();
         return Err(ExecutorError::ExecutionFailed(format!("Local LLM error: {error_body}")));
     }
+    if stream.is_enabled() {
+        return Ok(read_streamed_reply(response, ReplyFormat::Ollama, stream).await?.into_output(model));
+    }
 
     let result: serde_json::Value = response
         .json()
@@ -1996,10 +4181,14 @@ async fn call_local_llm_api(
     }))
 }
 
-async fn call_openai_embedding(api_key: &str, text: &str) -> Result<serde_json::Value, ExecutorError> {
+async fn call_openai_embedding(
+    api_key: &str,
+    model: &str,
+    texts: &[String],
+) -> Result<Vec<Vec<f64>>, ExecutorError> {
     let body = serde_json::json!({
-        "model": "text-embedding-3-small",
-        "input": text
+        "model": model,
+        "input": texts
     });
 
     let client = reqwest::Client::new();
@@ -2022,18 +4211,62 @@ async fn call_openai_embedding(api_key: &str, text: &str) -> Result<serde_json::
         .await
         .map_err(|e| ExecutorError::ExecutionFailed(format!("Response parse failed: {e}")))?;
 
-    let embedding: Vec<f64> = result["data"][0]["embedding"]
+    // Entries carry their input index; don't rely on response order
+    let mut vectors = vec![Vec::new(); texts.len()];
+    for (pos, entry) in result["data"].as_array().into_iter().flatten().enumerate() {
+        let index = entry["index"].as_u64().map_or(pos, |i| i as usize);
+        if let Some(slot) = vectors.get_mut(index) {
+            *slot = float_array(&entry["embedding"]);
+        }
+    }
+    Ok(vectors)
+}
+
+/// Batch embeddings from an Ollama-compatible server.
+async fn call_local_embedding(
+    endpoint: &str,
+    model: &str,
+    texts: &[String],
+) -> Result<Vec<Vec<f64>>, ExecutorError> {
+    let url = format!("{}/api/embed", endpoint.trim_end_matches('/'));
+    let body = serde_json::json!({
+        "model": model,
+        "input": texts
+    });
+
+    let client = reqwest::Client::new();
+    let response = client
+        .post(&url)
+        .header("Content-Type", "application/json")
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| ExecutorError::ExecutionFailed(format!("Local embedding request failed: {e}")))?;
+
+    if !response.status().is_success() {
+        let error_body = response.text().await.unwrap_or_default();
+        return Err(ExecutorError::ExecutionFailed(format!("Local embedding error: {error_body}")));
+    }
+
+    let result: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| ExecutorError::ExecutionFailed(format!("Response parse failed: {e}")))?;
+
+    Ok(result["embeddings"]
         .as_array()
-        .map(|arr| arr.iter().filter_map(|v| v.as_f64()).collect())
-        .unwrap_or_default();
+        .map(|arr| arr.iter().map(float_array).collect())
+        .unwrap_or_default())
+}
 
-    Ok(serde_json::json!({
-        "vector": embedding,
-        "dimension": embedding.len()
-    }))
+fn float_array(value: &serde_json::Value) -> Vec<f64> {
+    value
+        .as_array()
+        .map(|arr| arr.iter().filter_map(|v| v.as_f64()).collect())
+        .unwrap_or_default()
 }
 
-async fn call_bedrock_embedding(api_key: &str, region: &str, text: &str) -> Result<serde_json::Value, ExecutorError> {
+async fn call_bedrock_embedding(api_key: &str, region: &str, text: &str) -> Result<Vec<f64>, ExecutorError> {
     let model_id = "amazon.titan-embed-text-v1";
     let url = format!(
         "https://bedrock-runtime.{}.amazonaws.com/model/{}/invoke",
@@ -2062,15 +4295,7 @@ async fn call_bedrock_embedding(api_key: &str, region: &str, text: &str) -> Resu
         .await
         .map_err(|e| ExecutorError::ExecutionFailed(format!("Response parse failed: {e}")))?;
 
-    let embedding: Vec<f64> = result["embedding"]
-        .as_array()
-        .map(|arr| arr.iter().filter_map(|v| v.as_f64()).collect())
-        .unwrap_or_default();
-
-    Ok(serde_json::json!({
-        "vector": embedding,
-        "dimension": embedding.len()
-    }))
+    Ok(float_array(&result["embedding"]))
 }
 
 /// Simple hash-based embedding for testing (not a real embedding)
@@ -2088,3 +4313,703 @@ fn simple_hash_embedding(text: &str) -> Vec<f64> {
     }
     embeddings
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use unicode_normalization::UnicodeNormalization;
+
+    fn input(inputs: serde_json::Value, config: serde_json::Value) -> ToolInput {
+        ToolInput {
+            tool_ref: "core-tools/file-read@1.0.0".into(),
+            inputs,
+            config,
+            llm_provider: None,
+            env: Default::default(),
+            progress: Default::default(),
+            stream: Default::default(),
+            commands: Default::default(),
+            secrets: Default::default(),
+            scratch_dir: None,
+        }
+    }
+
+    #[test]
+    fn scratch_paths_stay_in_the_scratch_directory() {
+        let dir = std::env::temp_dir().join(format!("hb-local-{}", uuid::Uuid::new_v4()));
+        let scratch = |inputs: serde_json::Value| ToolInput {
+            scratch_dir: Some(dir.clone()),
+            ..input(inputs, serde_json::json!({}))
+        };
+        execute_file_write(&scratch(serde_json::json!({
+            "path": "scratch://pages/../../1.txt",
+            "content": "draft",
+        })))
+        .unwrap();
+        assert!(dir.join("pages/1.txt").exists());
+        let read = execute_file_read(&scratch(serde_json::json!({ "path": "scratch://pages/1.txt" }))).unwrap();
+        assert_eq!(read["content"], "draft");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn file_tools_accept_korean_and_bracketed_paths() {
+        let dir = std::env::temp_dir()
+            .join(format!("hb-local-{}", uuid::Uuid::new_v4()))
+            .join("[2024] 현장");
+        std::fs::create_dir_all(&dir).unwrap();
+        // Written with the decomposed spelling macOS uses, read with the composed one
+        let decomposed: String = "도면 목록.csv".nfd().collect();
+        std::fs::write(dir.join(&decomposed), "id,name\n1,기둥\n").unwrap();
+
+        let typed = format!("\"{}\"", dir.join("도면 목록.csv").display());
+        let read = execute_csv_read(&input(serde_json::json!({ "path": typed }), serde_json::json!({})))
+            .unwrap();
+        assert_eq!(read["rows"][0]["name"], "기둥");
+
+        let listed = execute_folder_read(&input(
+            serde_json::json!({}),
+            serde_json::json!({ "folder_path": dir.to_string_lossy(), "pattern": "*.csv" }),
+        ))
+        .unwrap();
+        assert_eq!(listed["count"], 1);
+        std::fs::remove_dir_all(dir.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn relative_paths_resolve_against_workspace_root() {
+        let root = std::env::temp_dir().join(format!("hb-local-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("자료")).unwrap();
+        let context = serde_json::json!({
+            "workspace_id": uuid::Uuid::new_v4(),
+            "root_path": root.to_string_lossy(),
+        });
+
+        let written = execute_file_write(&input(
+            serde_json::json!({ "path": "자료/메모.txt", "content": "기초 타설", "_context": context }),
+            serde_json::json!({}),
+        ))
+        .unwrap();
+        assert_eq!(written["path"], "자료/메모.txt");
+        assert_eq!(std::fs::read_to_string(root.join("자료/메모.txt")).unwrap(), "기초 타설");
+
+        let read = execute_file_read(&input(
+            serde_json::json!({ "path": "자료/메모.txt", "_context": context }),
+            serde_json::json!({}),
+        ))
+        .unwrap();
+        assert_eq!(read["content"], "기초 타설");
+        let listed = execute_folder_read(&input(
+            serde_json::json!({ "_context": context }),
+            serde_json::json!({ "folder_path": "자료", "pattern": "*.txt" }),
+        ))
+        .unwrap();
+        assert_eq!(listed["count"], 1);
+
+        // Without a workspace, relative paths are the process's as before
+        assert!(execute_file_read(&input(serde_json::json!({ "path": "자료/메모.txt" }), serde_json::json!({}))).is_err());
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn text_tools_decode_korean_encodings() {
+        let dir = std::env::temp_dir().join(format!("hb-local-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (cp949, _, _) = encoding_rs::EUC_KR.encode("품명,수량\n철근,120\n");
+        std::fs::write(dir.join("자재.csv"), &cp949).unwrap();
+        let utf16: Vec<u8> = [0xFF, 0xFE]
+            .into_iter()
+            .chain("공정 보고".encode_utf16().flat_map(u16::to_le_bytes))
+            .collect();
+        std::fs::write(dir.join("보고.txt"), utf16).unwrap();
+
+        let csv_path = dir.join("자재.csv").to_string_lossy().into_owned();
+        let read = execute_csv_read(&input(serde_json::json!({ "path": csv_path }), serde_json::json!({})))
+            .unwrap();
+        assert_eq!(read["rows"][0]["품명"], "철근");
+        assert_eq!(read["encoding"], "EUC-KR");
+
+        let text_path = dir.join("보고.txt").to_string_lossy().into_owned();
+        let read = execute_file_read(&input(serde_json::json!({ "path": text_path }), serde_json::json!({})))
+            .unwrap();
+        assert_eq!(read["content"], "공정 보고");
+        assert_eq!(read["encoding"], "UTF-16LE");
+
+        let forced = execute_file_read(&input(
+            serde_json::json!({ "path": csv_path }),
+            serde_json::json!({ "encoding": "cp949" }),
+        ))
+        .unwrap();
+        assert!(forced["content"].as_str().unwrap().starts_with("품명"));
+        assert_eq!(forced["had_errors"], false);
+        let misread = execute_file_read(&input(
+            serde_json::json!({ "path": csv_path }),
+            serde_json::json!({ "encoding": "utf-8" }),
+        ))
+        .unwrap();
+        assert_eq!(misread["had_errors"], true);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn regex_extract_outputs_named_groups() {
+        let text = "도면 A-101 rev 2\n도면 s-204\n";
+        let extract = |config: serde_json::Value| {
+            execute_regex_extract(&input(serde_json::json!({ "text": text }), config))
+        };
+        let pattern = r"(?P<sheet>[A-Z])-(?P<number>\d+)(?: rev (\d+))?";
+
+        let all = extract(serde_json::json!({ "pattern": pattern, "case_insensitive": true })).unwrap();
+        assert_eq!(all["count"], 2);
+        assert_eq!(all["captures"][0], serde_json::json!({ "sheet": "A", "number": "101", "3": "2" }));
+        assert_eq!(all["captures"][1], serde_json::json!({ "sheet": "s", "number": "204", "3": null }));
+
+        let first = extract(serde_json::json!({ "pattern": pattern, "global": false })).unwrap();
+        assert_eq!(first["count"], 1);
+
+        let lines = extract(serde_json::json!({ "pattern": r"^도면 (\S+)$", "multiline": true })).unwrap();
+        assert_eq!(lines["groups"], serde_json::json!([["도면 s-204", "s-204"]]));
+
+        assert!(extract(serde_json::json!({ "pattern": "(unclosed" })).is_err());
+        assert!(extract(serde_json::json!({})).is_err());
+    }
+
+    #[test]
+    fn file_split_streams_files_by_section() {
+        let dir = std::env::temp_dir().join(format!("hb-local-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let text = "# 시방서\n일반 사항.\n\n## 철근\n철근은 KS D 3504에 따른다.\n";
+        let (cp949, _, _) = encoding_rs::EUC_KR.encode(text);
+        let file = dir.join("시방서.md");
+        std::fs::write(&file, &cp949).unwrap();
+
+        let split = execute_file_split(&input(
+            serde_json::json!({ "path": file.to_string_lossy() }),
+            serde_json::json!({ "chunk_size": 200 }),
+        ))
+        .unwrap();
+        assert_eq!(split["chunks"], serde_json::json!(["# 시방서\n일반 사항.", "## 철근\n철근은 KS D 3504에 따른다."]));
+        assert_eq!(split["headings"][1], "시방서 > 철근");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn text_split_keeps_the_fixed_size_default() {
+        let split = |text: &str, config| execute_text_split(&input(serde_json::json!({ "text": text }), config));
+        let chunks = split("abcdefgh", serde_json::json!({ "chunk_size": 4, "overlap": 1 })).unwrap();
+        assert_eq!(chunks["chunks"], serde_json::json!(["abcd", "defg", "gh"]));
+        // Headings don't start chunks unless the structure strategy is chosen
+        let chunks = split("# a\nb\n# c\n", serde_json::json!({ "chunk_size": 100 })).unwrap();
+        assert_eq!(chunks["chunks"], serde_json::json!(["# a\nb\n# c\n"]));
+        assert_eq!(split("", serde_json::json!({})).unwrap()["count"], 0);
+
+        let capped = split("abcdefgh", serde_json::json!({ "chunk_size": 2, "overlap": 0, "max_chunks": 3 }));
+        assert!(capped.unwrap_err().to_string().contains("more than 3 chunks"));
+    }
+
+    #[test]
+    fn condition_evaluates_expressions_and_switches() {
+        let order = serde_json::json!({ "status": "검토", "amount": 1_250_000 });
+        let condition = |config: serde_json::Value| {
+            execute_condition(&input(serde_json::json!({ "value": order }), config))
+        };
+
+        let branch = condition(serde_json::json!({ "expression": "amount > 1000000 && status != '반려'" })).unwrap();
+        assert_eq!(branch["true_out"], order);
+        assert_eq!(branch["false_out"], serde_json::Value::Null);
+        assert_eq!(branch["untaken"], serde_json::json!(["false_out"]));
+        // Without an expression the value itself is tested, as before
+        let plain = condition(serde_json::json!({})).unwrap();
+        assert_eq!(plain["true_out"], order);
+
+        let switch = condition(serde_json::json!({
+            "expression": "status",
+            "mode": "switch",
+            "cases": [{ "label": "approve", "value": "승인" }, "검토", "반려"],
+        }))
+        .unwrap();
+        assert_eq!(switch["matched"], "검토");
+        assert_eq!(switch["cases"]["검토"], order);
+        assert_eq!(switch["cases"]["approve"], serde_json::Value::Null);
+        assert_eq!(switch["default"], serde_json::Value::Null);
+        assert_eq!(switch["untaken"], serde_json::json!(["cases.approve", "cases.반려", "default"]));
+
+        let fallthrough = condition(serde_json::json!({
+            "expression": "len(status)",
+            "mode": "switch",
+            "cases": "1, 3",
+        }))
+        .unwrap();
+        assert_eq!(fallthrough["matched"], serde_json::Value::Null);
+        assert_eq!(fallthrough["default"], order);
+        assert_eq!(fallthrough["untaken"], serde_json::json!(["cases"]));
+
+        let err = condition(serde_json::json!({ "expression": "amount >" })).unwrap_err();
+        assert!(err.to_string().contains("Condition 'amount >'"));
+    }
+
+    #[test]
+    fn data_filter_filters_projects_sorts_and_pages() {
+        let rows = serde_json::json!([
+            { "품명": "철근 D13", "수량": 120, "단가": 950 },
+            { "품명": "레미콘", "수량": 40, "단가": 85000 },
+            { "품명": "철근 D10", "수량": 300, "단가": 900 },
+            { "품명": "거푸집", "단가": 12000 },
+        ]);
+        let filter = |items: &serde_json::Value, config: serde_json::Value| {
+            execute_data_filter(&input(serde_json::json!({ "items": items }), config))
+        };
+
+        let out = filter(&rows, serde_json::json!({
+            "condition": "starts_with(품명, '철근') && 수량 >= 100",
+            "fields": "품명, 수량 * 단가 as 금액",
+            "sort_by": "수량 * 단가",
+            "order": "desc",
+        }))
+        .unwrap();
+        assert_eq!(out["total"], 2);
+        assert_eq!(out["headers"], serde_json::json!(["품명", "금액"]));
+        assert_eq!(out["filtered"][0], serde_json::json!({ "품명": "철근 D10", "금액": 270000 }));
+
+        let table = serde_json::json!({ "headers": ["품명", "수량", "단가"], "rows": rows });
+        let out = filter(&table, serde_json::json!({
+            "sort_by": "수량", "order": "desc", "offset": 1, "limit": 2,
+        }))
+        .unwrap();
+        let names: Vec<_> = out["filtered"].as_array().unwrap().iter().map(|r| r["품명"].clone()).collect();
+        assert_eq!(names, ["철근 D13", "레미콘"]);
+        assert_eq!((out["count"].clone(), out["total"].clone()), (2.into(), 4.into()));
+        assert_eq!(out["headers"], table["headers"]);
+
+        // The old config key still filters instead of passing everything through
+        let out = filter(&rows, serde_json::json!({ "expression": "수량 < 100" })).unwrap();
+        assert_eq!(out["count"], 1);
+
+        assert!(filter(&rows, serde_json::json!({ "condition": "수량 >" })).is_err());
+        // Old-style word operators still work; unquoted text is rejected
+        let out = filter(&rows, serde_json::json!({ "condition": "수량 gte 120" })).unwrap();
+        assert_eq!(out["count"], 2);
+        let err = filter(&rows, serde_json::json!({ "condition": "품명 eq 레미콘" })).unwrap_err();
+        assert!(err.to_string().contains("Unknown field '레미콘'"), "{err}");
+        assert!(filter(&serde_json::json!("not rows"), serde_json::json!({})).is_err());
+    }
+
+    #[test]
+    fn process_launch_fills_node_values_without_a_shell() {
+        let spec: ProcessSpec = serde_json::from_value(serde_json::json!({
+            "command": "ogr2ogr",
+            "args": ["-f", "GeoJSON", "{config.output}", "{input.path}"],
+            "env": { "GDAL_CACHEMAX": "{config.cache_mb}", "VWORLD_KEY": { "secret": "VWORLD_KEY" } },
+        }))
+        .unwrap();
+        let node = input(
+            serde_json::json!({ "path": "C:\\현장 자료\\필지.shp" }),
+            serde_json::json!({ "output": "out.json; del *", "cache_mb": 512 }),
+        );
+        let err = ProcessLaunch::new(&spec, &node).unwrap_err();
+        assert!(matches!(err, ExecutorError::MissingCredential(_)), "{err}");
+
+        let mut spec = spec;
+        spec.env.remove("VWORLD_KEY");
+        let launch = ProcessLaunch::new(&spec, &node).unwrap();
+        assert_eq!(launch.program, "ogr2ogr");
+        assert_eq!(launch.args, ["-f", "GeoJSON", "out.json; del *", "C:\\현장 자료\\필지.shp"]);
+        assert_eq!(launch.env["GDAL_CACHEMAX"], "512");
+
+        // A bare command line still runs, split into words
+        let bare = ProcessSpec { command: r#"python "my tools/convert.py" --fast"#.into(), ..Default::default() };
+        let launch = ProcessLaunch::new(&bare, &node).unwrap();
+        assert_eq!((launch.program.as_str(), launch.args), ("python", vec!["my tools/convert.py".to_string(), "--fast".into()]));
+
+        spec.args.push("{config.missing}".into());
+        assert!(ProcessLaunch::new(&spec, &node).is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn process_runs_in_working_dir_with_secret_env() {
+        let dir = std::env::temp_dir().join(format!("hb-process-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let spec: ProcessSpec = serde_json::from_value(serde_json::json!({
+            "command": "sh",
+            "args": [
+                "-c",
+                "cat >/dev/null; printf '{\"arg\":\"%s\",\"token\":\"%s\",\"dir\":\"%s\",\"progress\":\"%s\"}' \"$1\" \"$TOKEN\" \"$PWD\" \"$HB_PROGRESS\"",
+                "sh",
+                "{config.name}",
+            ],
+            "env": { "TOKEN": { "secret": "VWORLD_KEY" } },
+            "working_dir": "{config.dir}",
+        }))
+        .unwrap();
+        let node = ToolInput {
+            secrets: crate::Secrets::new([("VWORLD_KEY".to_string(), "s3cret".to_string())].into()),
+            ..input(
+                serde_json::json!({}),
+                serde_json::json!({ "name": "a b; echo injected", "dir": dir.to_string_lossy() }),
+            )
+        };
+        let output = execute_process(&spec, &node).await.unwrap().outputs;
+        assert_eq!(output["arg"], "a b; echo injected");
+        assert_eq!(output["token"], "s3cret");
+        assert_eq!(output["progress"], "1");
+        assert_eq!(
+            std::fs::canonicalize(output["dir"].as_str().unwrap()).unwrap(),
+            std::fs::canonicalize(&dir).unwrap()
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn shell_exec_runs_allowlisted_programs_only() {
+        use crate::command::CommandGate;
+        use hb_core::policy::CommandPolicy;
+
+        let mut node = input(
+            serde_json::json!({ "stdin": "철근 D13\n", "name": "a b; echo injected" }),
+            serde_json::json!({
+                "command": "sh",
+                "args": ["-c", "cat; printf '%s' \"$1\"; head -c 5000 /dev/zero >&2", "sh", "{input.name}"],
+                "max_output_bytes": 1000,
+                "fail_on_error": false,
+            }),
+        );
+        // Not allowlisted and nobody to ask
+        let err = execute_shell_exec(&node).await.unwrap_err();
+        assert!(matches!(err, ExecutorError::NotAllowed(_)), "{err}");
+
+        node.commands = CommandGate::new(CommandPolicy {
+            allowed_programs: vec!["sh".into()],
+        });
+        // The workspace cannot point the allowlisted name elsewhere
+        node.env.insert("PATH".into(), "/nonexistent".into());
+        let output = execute_shell_exec(&node).await.unwrap();
+        assert_eq!(output["stdout"], "철근 D13\na b; echo injected");
+        assert_eq!(output["stderr"].as_str().unwrap().len(), 1000);
+        assert_eq!(output["truncated"], true);
+        assert_eq!(output["exit_code"], 0);
+        assert_eq!(output["approved"], false);
+
+        node.config["args"] = serde_json::json!(["-c", "echo 실패 >&2; exit 3"]);
+        node.config["fail_on_error"] = serde_json::json!(true);
+        let err = execute_shell_exec(&node).await.unwrap_err();
+        assert!(err.to_string().contains("실패"), "{err}");
+
+        node.config["args"] = serde_json::json!(["-c", "sleep 5"]);
+        node.config["timeout_ms"] = serde_json::json!(100);
+        assert!(matches!(
+            execute_shell_exec(&node).await.unwrap_err(),
+            ExecutorError::Timeout(100)
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn bash_tools_go_through_the_command_gate() {
+        use crate::command::CommandGate;
+
+        let dir = std::env::temp_dir().join(format!("hb-bash-{}", uuid::Uuid::new_v4()));
+        let marker = dir.join("ran");
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut node = input(
+            serde_json::json!({ "command": format!("touch '{}'; echo 완료", marker.display()) }),
+            serde_json::json!({}),
+        );
+        for tool in ["bash-execute", "bash", "shell"] {
+            node.tool_ref = format!("core-tools/{tool}@1.0.0");
+            let err = execute_native(&node).await.unwrap_err();
+            assert!(matches!(err, ExecutorError::NotAllowed(_)), "{tool}: {err}");
+        }
+        assert!(!marker.exists());
+
+        // The approver sees the whole command line
+        node.commands = CommandGate::default().with_approver(|request: CommandRequest| async move {
+            request.program == "sh" && request.args[1].ends_with("echo 완료")
+        });
+        let output = execute_native(&node).await.unwrap().outputs;
+        assert_eq!(output["stdout"], "완료\n");
+        assert!(marker.exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn shell_exec_streams_output_lines() {
+        use crate::command::CommandGate;
+        use hb_core::policy::CommandPolicy;
+        use std::sync::{Arc, Mutex};
+
+        let chunks = Arc::new(Mutex::new(Vec::new()));
+        let mut node = input(
+            serde_json::json!({}),
+            serde_json::json!({ "command": "sh", "args": ["-c", "echo 타설; echo 양생 >&2; printf 끝"] }),
+        );
+        node.commands = CommandGate::new(CommandPolicy {
+            allowed_programs: vec!["sh".into()],
+        });
+        let sink = chunks.clone();
+        node.stream = OutputStream::new(move |chunk| sink.lock().unwrap().push((chunk.port, chunk.text)));
+        let output = execute_shell_exec(&node).await.unwrap();
+        assert_eq!(output["stdout"], "타설\n끝");
+
+        let mut chunks = chunks.lock().unwrap().clone();
+        chunks.sort();
+        let expected = [("stderr", "양생\n"), ("stdout", "끝"), ("stdout", "타설\n")];
+        assert_eq!(chunks, expected.map(|(p, t)| (p.to_string(), t.to_string())));
+    }
+
+    #[test]
+    fn streamed_replies_collect_text_and_usage() {
+        use std::sync::{Arc, Mutex};
+
+        let sent = Arc::new(Mutex::new(String::new()));
+        let sink = sent.clone();
+        let stream = OutputStream::new(move |chunk| sink.lock().unwrap().push_str(&chunk.text));
+        let cases = [
+            (
+                ReplyFormat::Anthropic,
+                vec![
+                    "event: message_start",
+                    r#"data: {"type":"message_start","message":{"usage":{"input_tokens":12}}}"#,
+                    r#"data: {"type":"content_block_delta","delta":{"type":"text_delta","text":"기초 "}}"#,
+                    r#"data: {"type":"content_block_delta","delta":{"type":"text_delta","text":"타설"}}"#,
+                    r#"data: {"type":"message_delta","usage":{"output_tokens":3}}"#,
+                ],
+            ),
+            (
+                ReplyFormat::OpenAi,
+                vec![
+                    r#"data: {"choices":[{"delta":{"content":"기초 "}}]}"#,
+                    r#"data: {"choices":[{"delta":{"content":"타설"}}]}"#,
+                    r#"data: {"choices":[],"usage":{"prompt_tokens":12,"completion_tokens":3}}"#,
+                    "data: [DONE]",
+                ],
+            ),
+            (
+                ReplyFormat::Ollama,
+                vec![
+                    r#"{"response":"기초 ","done":false}"#,
+                    r#"{"response":"타설","done":false}"#,
+                    r#"{"response":"","done":true,"prompt_eval_count":12,"eval_count":3}"#,
+                ],
+            ),
+        ];
+        for (format, lines) in cases {
+            sent.lock().unwrap().clear();
+            let mut reply = StreamedReply::default();
+            for line in lines {
+                reply.apply(format, line, &stream);
+            }
+            let expected = StreamedReply {
+                text: "기초 타설".into(),
+                input_tokens: 12,
+                output_tokens: 3,
+            };
+            assert_eq!(reply, expected, "{format:?}");
+            assert_eq!(*sent.lock().unwrap(), "기초 타설");
+        }
+    }
+
+    #[tokio::test]
+    async fn graph_tools_store_given_facts_and_answer_queries() {
+        let db = std::env::temp_dir()
+            .join(format!("hb-graph-tools-{}", uuid::Uuid::new_v4()))
+            .join("kg.sqlite");
+        let config = serde_json::json!({ "db_path": db.display().to_string(), "graph": "site" });
+        let response = "Facts:\n```json\n[{\"subject\": \"시공사\", \"predicate\": \"must_submit\", \
+            \"object\": \"품질관리계획서\", \"evidence\": \"시공사는 품질관리계획서를 제출한다.\"}, {\"subject\": \"x\"}]\n```";
+        let triples = parse_extracted_triples(response);
+        assert_eq!(triples.len(), 1);
+
+        let stored = execute_graph_extract(&input(
+            serde_json::json!({ "triples": triples, "source": "contract.pdf" }),
+            config.clone(),
+        ))
+        .await
+        .unwrap();
+        assert_eq!(stored["added"], 1);
+        assert_eq!(stored["triples"][0]["source"], "contract.pdf");
+
+        let found = execute_graph_query(&input(
+            serde_json::json!({ "predicate": "must_*" }),
+            config.clone(),
+        ))
+        .unwrap();
+        assert_eq!(found["count"], 1);
+        assert_eq!(found["context"], "시공사 —must_submit→ 품질관리계획서 [contract.pdf]");
+
+        let near = execute_graph_query(&input(serde_json::json!({ "entity": "품질관리계획서" }), config))
+            .unwrap();
+        assert_eq!(near["entities"], serde_json::json!(["품질관리계획서", "시공사"]));
+        let _ = std::fs::remove_dir_all(db.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn doc_extract_fields_validates_against_the_schema() {
+        let config = serde_json::json!({
+            "fields": [
+                { "name": "permit_no", "labels": ["허가번호"], "required": true },
+                { "name": "issued", "labels": ["허가일자"], "type": "date", "required": true },
+                { "name": "floor_area", "labels": ["연면적"], "type": "number" },
+                { "name": "owner", "labels": ["건축주"], "required": true }
+            ],
+            "llm_fallback": false
+        });
+        let text = "건축허가서\n허가번호: 2024-건축과-0117\n허가일자  2024년 3월 15일\n연면적 : 약 m²\n";
+        let result = execute_doc_extract_fields(&input(serde_json::json!({ "text": text }), config))
+            .await
+            .unwrap();
+        assert_eq!(result["fields"]["permit_no"], "2024-건축과-0117");
+        assert_eq!(result["fields"]["issued"], "2024-03-15");
+        assert_eq!(result["fields"]["floor_area"], serde_json::Value::Null);
+        assert_eq!(result["sources"]["issued"], "layout");
+        assert_eq!(result["valid"], false);
+        assert_eq!(result["missing"], serde_json::json!(["owner"]));
+        assert_eq!(result["errors"][0]["field"], "floor_area");
+    }
+
+    #[tokio::test]
+    async fn doc_compare_lists_clause_changes() {
+        let old = "1. The Supplier shall deliver the goods within 30 days.\n\
+                   2. The Buyer shall pay within 60 days of delivery.\n\
+                   3. This agreement is governed by the laws of Korea.";
+        let new = "1. The Supplier shall deliver the goods within 45 days.\n\
+                   2. The Buyer may pay within 60 days of delivery.\n\
+                   3. This agreement is governed by the laws of Korea.\n\
+                   4. Either party may terminate on 30 days notice.";
+        let split = execute_clause_split(&input(serde_json::json!({ "text": new }), serde_json::json!({})))
+            .unwrap();
+        assert_eq!(split["count"], 4);
+        assert_eq!(split["clauses"][3]["number"], "4.");
+
+        let result = execute_doc_compare(&input(
+            serde_json::json!({ "old": old, "new": new }),
+            serde_json::json!({}),
+        ))
+        .await
+        .unwrap();
+        assert_eq!(
+            result["summary"],
+            serde_json::json!({ "modified": 2, "added": 1, "deleted": 0, "moved": 0, "unchanged": 1, "major": 3 })
+        );
+        assert_eq!(result["count"], 3);
+        assert_eq!(result["changes"][0]["tags"], serde_json::json!(["numbers"]));
+        assert_eq!(result["changes"][1]["tags"], serde_json::json!(["obligation"]));
+        assert_eq!(result["changes"][2]["kind"], "added");
+    }
+
+    #[test]
+    fn summary_groups_fit_the_budget_and_always_shrink() {
+        assert_eq!(group_by_budget(&[400, 400, 400, 400, 400], 1000), vec![0..2, 2..4, 4..5]);
+        assert_eq!(group_by_budget(&[300, 300, 300, 300], 1000), vec![0..3, 3..4]);
+        // Oversized summaries still pair up so the next level is smaller
+        assert_eq!(group_by_budget(&[5000, 5000, 5000], 1000), vec![0..2, 2..3]);
+        assert_eq!(group_by_budget(&[], 1000), Vec::<std::ops::Range<usize>>::new());
+    }
+
+    #[tokio::test]
+    async fn public_api_fetch_checks_parameters_and_key_before_calling() {
+        let config = serde_json::json!({
+            "service": "apt-trade",
+            "key_env": "HB_TEST_UNSET_PUBLIC_API_KEY",
+            "params": "{\"LAWD_CD\": \"11650\"}",
+        });
+        let err = execute_public_api_fetch(&input(serde_json::json!({}), config.clone()))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("DEAL_YMD"), "{err}");
+
+        let err = execute_public_api_fetch(&input(
+            serde_json::json!({ "params": { "DEAL_YMD": 202609 } }),
+            config,
+        ))
+        .await
+        .unwrap_err();
+        assert!(matches!(err, ExecutorError::MissingCredential(ref m) if m.contains("HB_TEST_UNSET_PUBLIC_API_KEY")));
+
+        // A failed request doesn't repeat the URL, key included
+        let err = execute_public_api_fetch(&input(
+            serde_json::json!({ "params": { "DEAL_YMD": 202609 } }),
+            serde_json::json!({
+                "service": "apt-trade",
+                "url": "http://127.0.0.1:1/api",
+                "service_key": "hb-test-service-key-4f2a",
+                "params": "{\"LAWD_CD\": \"11650\"}",
+                "timeout": 2,
+            }),
+        ))
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("request failed"), "{err}");
+        assert!(!err.to_string().contains("hb-test-service-key-4f2a"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn meeting_subtitles_are_transcribed_and_diarized_offline() {
+        let dir = std::env::temp_dir().join(format!("hb-local-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("회의.vtt");
+        std::fs::write(
+            &path,
+            "WEBVTT\n\n00:00:01.000 --> 00:00:03.000\n김민수: 타설 일정부터 보죠.\n\n\
+             00:00:03.200 --> 00:00:05.000\n월요일 오전입니다.\n\n\
+             00:00:06.000 --> 00:00:08.000\n이영호: 철근은 금요일에 들어옵니다.\n",
+        )
+        .unwrap();
+
+        let transcribed = execute_audio_transcribe(&input(
+            serde_json::json!({ "path": path.to_string_lossy() }),
+            serde_json::json!({}),
+        ))
+        .await
+        .unwrap();
+        assert_eq!(transcribed["source"], "subtitles");
+        assert_eq!(transcribed["duration"], 8.0);
+
+        let diarized = execute_speaker_diarize(&input(
+            serde_json::json!({ "segments": transcribed["segments"] }),
+            serde_json::json!({}),
+        ))
+        .await
+        .unwrap();
+        assert_eq!(diarized["method"], "labels");
+        assert_eq!(diarized["speakers"], serde_json::json!(["김민수", "이영호"]));
+        assert_eq!(
+            diarized["transcript"],
+            "[00:00:01] 김민수: 타설 일정부터 보죠. 월요일 오전입니다.\n[00:00:06] 이영호: 철근은 금요일에 들어옵니다."
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn bedrock_calls_follow_the_routing_table() {
+        let calls = std::sync::Mutex::new(Vec::new());
+        let invoke = |model_id: String, region: String| {
+            calls.lock().unwrap().push(format!("{region}/{model_id}"));
+            async move {
+                if model_id.starts_with("anthropic.") {
+                    Err("Invocation with on-demand throughput isn't supported.".to_string())
+                } else {
+                    Ok(serde_json::json!({ "content": [{ "text": "ok" }] }))
+                }
+            }
+        };
+
+        // A model without on-demand access moves to the region's profile
+        let target = bedrock::resolve("claude-3-haiku-20240307", "eu-west-1", None);
+        let (_, served_by) = invoke_bedrock_target(target, invoke).await.unwrap();
+        assert_eq!(served_by, "eu.anthropic.claude-3-haiku-20240307-v1:0");
+        assert_eq!(
+            *calls.lock().unwrap(),
+            [
+                "eu-west-1/anthropic.claude-3-haiku-20240307-v1:0",
+                "eu-west-1/eu.anthropic.claude-3-haiku-20240307-v1:0",
+            ]
+        );
+
+        // Newer models are never mapped onto an older one
+        let target = bedrock::resolve("claude-sonnet-4-20250514", "us-east-1", None);
+        assert_eq!(target.invoke_id, "us.anthropic.claude-sonnet-4-20250514-v1:0");
+    }
+}
@@ -0,0 +1,507 @@
+//! Meeting helpers — transcript segments from speech-to-text replies,
+//! subtitle files and plain transcripts, simple speaker diarization, and the
+//! minutes written from them.
+
+use serde::{Deserialize, Serialize};
+
+/// One stretch of speech. Times are in seconds from the start of the
+/// recording, when known.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Segment {
+    #[serde(default)]
+    pub start: Option<f64>,
+    #[serde(default)]
+    pub end: Option<f64>,
+    #[serde(default)]
+    pub speaker: Option<String>,
+    pub text: String,
+}
+
+/// Segments of a Whisper-style `verbose_json` reply, or its whole text as
+/// one segment when it has none.
+pub fn segments_from_reply(reply: &serde_json::Value) -> Vec<Segment> {
+    let segments: Vec<Segment> = reply
+        .get("segments")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|s| {
+            let text = s.get("text")?.as_str()?.trim();
+            (!text.is_empty()).then(|| Segment {
+                start: s.get("start").and_then(|v| v.as_f64()),
+                end: s.get("end").and_then(|v| v.as_f64()),
+                speaker: s
+                    .get("speaker")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string),
+                text: text.to_string(),
+            })
+        })
+        .collect();
+    if !segments.is_empty() {
+        return segments;
+    }
+    match reply.get("text").and_then(|v| v.as_str()).map(str::trim) {
+        Some(text) if !text.is_empty() => vec![Segment {
+            start: None,
+            end: None,
+            speaker: None,
+            text: text.to_string(),
+        }],
+        _ => Vec::new(),
+    }
+}
+
+/// Parse SRT or WebVTT subtitles. WebVTT voice tags (`<v Name>`) become the
+/// speaker; other markup is dropped.
+pub fn parse_subtitles(text: &str) -> Vec<Segment> {
+    let text = text.replace("\r\n", "\n");
+    let mut segments = Vec::new();
+    for block in text.split("\n\n") {
+        let mut lines = block.lines().skip_while(|l| !l.contains("-->"));
+        let Some(timing) = lines.next() else {
+            continue;
+        };
+        let (start, end) = match timing.split_once("-->") {
+            Some((start, end)) => (
+                parse_timestamp(start.trim()),
+                // Cue settings may follow the end time
+                end.split_whitespace().next().and_then(parse_timestamp),
+            ),
+            None => continue,
+        };
+        let mut speaker = None;
+        let mut parts = Vec::new();
+        for line in lines {
+            if let Some(rest) = line.trim().strip_prefix("<v") {
+                if let Some((name, _)) = rest.split_once('>') {
+                    speaker = Some(name.trim().trim_start_matches('.').trim().to_string());
+                }
+            }
+            let line = strip_tags(line);
+            if !line.is_empty() {
+                parts.push(line);
+            }
+        }
+        if !parts.is_empty() {
+            segments.push(Segment {
+                start,
+                end,
+                speaker: speaker.filter(|s| !s.is_empty()),
+                text: parts.join(" "),
+            });
+        }
+    }
+    segments
+}
+
+/// Parse a plain transcript: one segment per non-empty line, with an
+/// optional leading `[hh:mm:ss]` and `Name:` label.
+pub fn parse_transcript(text: &str) -> Vec<Segment> {
+    text.lines()
+        .filter_map(|line| {
+            let mut line = line.trim();
+            let mut start = None;
+            if let Some(rest) = line.strip_prefix('[') {
+                if let Some((stamp, rest)) = rest.split_once(']') {
+                    if let Some(time) = parse_timestamp(stamp.trim()) {
+                        start = Some(time);
+                        line = rest.trim();
+                    }
+                }
+            }
+            if line.is_empty() {
+                return None;
+            }
+            let (speaker, text) = match split_label(line) {
+                Some((speaker, text)) => (Some(speaker.to_string()), text),
+                None => (None, line),
+            };
+            Some(Segment {
+                start,
+                end: None,
+                speaker,
+                text: text.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// `hh:mm:ss.mmm`, `hh:mm:ss,mmm`, `mm:ss.mmm` or `mm:ss` in seconds.
+pub fn parse_timestamp(stamp: &str) -> Option<f64> {
+    let stamp = stamp.replace(',', ".");
+    let parts: Vec<&str> = stamp.split(':').collect();
+    if !(2..=3).contains(&parts.len()) {
+        return None;
+    }
+    let mut seconds = 0.0;
+    for part in &parts {
+        if part.is_empty() || !part.chars().all(|c| c.is_ascii_digit() || c == '.') {
+            return None;
+        }
+        seconds = seconds * 60.0 + part.parse::<f64>().ok()?;
+    }
+    Some(seconds)
+}
+
+/// `hh:mm:ss` for a time in seconds.
+pub fn format_timestamp(seconds: f64) -> String {
+    let total = seconds.max(0.0) as u64;
+    format!(
+        "{:02}:{:02}:{:02}",
+        total / 3600,
+        total / 60 % 60,
+        total % 60
+    )
+}
+
+/// Split `Name: text` into the speaker and the text. Labels are short and
+/// not numbers, so times ("10:30") and sentences with colons stay text.
+pub fn split_label(line: &str) -> Option<(&str, &str)> {
+    let (label, text) = line.split_once(':').or_else(|| line.split_once('：'))?;
+    let is_time = label.ends_with(|c: char| c.is_ascii_digit())
+        && text.starts_with(|c: char| c.is_ascii_digit());
+    let label = label.trim();
+    let text = text.trim();
+    let plausible = !is_time
+        && !label.is_empty()
+        && label.chars().count() <= 30
+        && label.split_whitespace().count() <= 4
+        && !label.chars().all(|c| c.is_ascii_digit())
+        && !label.contains(['.', ',', '?', '!', '"', '(']);
+    (plausible && !text.is_empty()).then_some((label, text))
+}
+
+fn strip_tags(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut in_tag = false;
+    for c in line.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out.trim().to_string()
+}
+
+/// How speakers were told apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Diarization {
+    /// Names or labels already in the transcript.
+    Labels,
+    /// A new turn after each pause, speakers taken in rotation.
+    Pauses,
+    /// The LLM read who is speaking from what is said.
+    Llm,
+}
+
+/// Give every segment a speaker. Labels in the transcript win; without
+/// any, a pause of at least `turn_gap` seconds (or, without times, every
+/// segment) starts the next of `speakers` speakers in rotation. That is only
+/// a guess at who spoke; the LLM pass in the tool does better.
+pub fn diarize(segments: &mut [Segment], speakers: usize, turn_gap: f64) -> Diarization {
+    for segment in segments.iter_mut().filter(|s| s.speaker.is_none()) {
+        if let Some((speaker, text)) = split_label(&segment.text) {
+            let (speaker, text) = (speaker.to_string(), text.to_string());
+            segment.speaker = Some(speaker);
+            segment.text = text;
+        }
+    }
+
+    if segments.iter().any(|s| s.speaker.is_some()) {
+        let mut current: Option<String> = None;
+        for segment in segments.iter_mut() {
+            match &segment.speaker {
+                Some(speaker) => current = Some(speaker.clone()),
+                None => segment.speaker = current.clone(),
+            }
+        }
+        return Diarization::Labels;
+    }
+
+    let speakers = speakers.max(1);
+    let mut turn = 0;
+    let mut previous_end: Option<f64> = None;
+    for (i, segment) in segments.iter_mut().enumerate() {
+        let new_turn = match (previous_end, segment.start) {
+            (Some(end), Some(start)) => start - end >= turn_gap,
+            _ => i > 0,
+        };
+        if new_turn {
+            turn += 1;
+        }
+        segment.speaker = Some(format!("Speaker {}", turn % speakers + 1));
+        previous_end = segment.end.or(segment.start);
+    }
+    Diarization::Pauses
+}
+
+/// Merge consecutive segments of the same speaker into turns.
+pub fn merge_turns(segments: &[Segment]) -> Vec<Segment> {
+    let mut turns: Vec<Segment> = Vec::new();
+    for segment in segments {
+        match turns.last_mut() {
+            Some(turn) if turn.speaker == segment.speaker => {
+                turn.text.push(' ');
+                turn.text.push_str(&segment.text);
+                turn.end = segment.end.or(turn.end);
+            }
+            _ => turns.push(segment.clone()),
+        }
+    }
+    turns
+}
+
+/// One line per turn: `[hh:mm:ss] Speaker: text`.
+pub fn render_transcript(segments: &[Segment]) -> String {
+    merge_turns(segments)
+        .iter()
+        .map(|turn| {
+            let mut line = String::new();
+            if let Some(start) = turn.start {
+                line.push_str(&format!("[{}] ", format_timestamp(start)));
+            }
+            if let Some(speaker) = &turn.speaker {
+                line.push_str(&format!("{speaker}: "));
+            }
+            line.push_str(&turn.text);
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Distinct speakers in order of first appearance.
+pub fn speakers(segments: &[Segment]) -> Vec<String> {
+    let mut seen: Vec<String> = Vec::new();
+    for speaker in segments.iter().filter_map(|s| s.speaker.as_ref()) {
+        if !seen.contains(speaker) {
+            seen.push(speaker.clone());
+        }
+    }
+    seen
+}
+
+/// A multipart/form-data body with text `fields` and one file part named
+/// `file`.
+pub fn multipart_body(
+    boundary: &str,
+    fields: &[(&str, &str)],
+    file_name: &str,
+    file: &[u8],
+) -> Vec<u8> {
+    let mut body = Vec::with_capacity(file.len() + 512);
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!("--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n")
+                .as_bytes(),
+        );
+    }
+    let file_name = file_name.replace('"', "");
+    body.extend_from_slice(
+        format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{file_name}\"\r\n\
+             Content-Type: application/octet-stream\r\n\r\n"
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(file);
+    body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+    body
+}
+
+/// Something to do after the meeting.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ActionItem {
+    pub task: String,
+    #[serde(default)]
+    pub owner: Option<String>,
+    #[serde(default)]
+    pub due: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Topic {
+    pub title: String,
+    #[serde(default)]
+    pub points: Vec<String>,
+}
+
+/// Minutes as the LLM returns them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Minutes {
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub summary: String,
+    #[serde(default)]
+    pub attendees: Vec<String>,
+    #[serde(default)]
+    pub topics: Vec<Topic>,
+    #[serde(default)]
+    pub decisions: Vec<String>,
+    #[serde(default)]
+    pub action_items: Vec<ActionItem>,
+}
+
+/// The JSON object in an LLM reply, read as minutes.
+pub fn parse_minutes(reply: &str) -> Option<Minutes> {
+    let (start, end) = reply.find('{').zip(reply.rfind('}'))?;
+    serde_json::from_str(reply.get(start..=end)?).ok()
+}
+
+/// Minutes as Markdown, with Korean headings when `korean`.
+pub fn render_minutes(minutes: &Minutes, korean: bool) -> String {
+    let [summary, attendees, topics, decisions, actions, owner, due, none] = if korean {
+        [
+            "요약",
+            "참석자",
+            "논의 내용",
+            "결정 사항",
+            "실행 항목",
+            "담당",
+            "기한",
+            "없음",
+        ]
+    } else {
+        [
+            "Summary",
+            "Attendees",
+            "Discussion",
+            "Decisions",
+            "Action items",
+            "Owner",
+            "Due",
+            "None",
+        ]
+    };
+    let title = if minutes.title.trim().is_empty() {
+        if korean {
+            "회의록"
+        } else {
+            "Meeting minutes"
+        }
+    } else {
+        minutes.title.trim()
+    };
+
+    let mut out = format!("# {title}\n\n");
+    if !minutes.attendees.is_empty() {
+        out.push_str(&format!(
+            "**{attendees}:** {}\n\n",
+            minutes.attendees.join(", ")
+        ));
+    }
+    if !minutes.summary.trim().is_empty() {
+        out.push_str(&format!("## {summary}\n\n{}\n\n", minutes.summary.trim()));
+    }
+    if !minutes.topics.is_empty() {
+        out.push_str(&format!("## {topics}\n\n"));
+        for topic in &minutes.topics {
+            out.push_str(&format!("### {}\n\n", topic.title));
+            for point in &topic.points {
+                out.push_str(&format!("- {point}\n"));
+            }
+            out.push('\n');
+        }
+    }
+    out.push_str(&format!("## {decisions}\n\n"));
+    if minutes.decisions.is_empty() {
+        out.push_str(&format!("- {none}\n"));
+    }
+    for decision in &minutes.decisions {
+        out.push_str(&format!("- {decision}\n"));
+    }
+    out.push_str(&format!("\n## {actions}\n\n"));
+    if minutes.action_items.is_empty() {
+        out.push_str(&format!("- {none}\n"));
+    }
+    for item in &minutes.action_items {
+        out.push_str(&format!("- [ ] {}", item.task));
+        let details: Vec<String> = [(owner, &item.owner), (due, &item.due)]
+            .into_iter()
+            .filter_map(|(label, value)| Some(format!("{label}: {}", value.as_deref()?)))
+            .collect();
+        if !details.is_empty() {
+            out.push_str(&format!(" ({})", details.join(", ")));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subtitles_and_transcripts_become_segments() {
+        let vtt = "WEBVTT\n\n1\n00:00:01.000 --> 00:00:03.500 align:start\n<v 김민수>공정 회의를 시작하겠습니다.</v>\n\n\
+                   2\n00:00:04.000 --> 00:00:06.000\n<v Lee>Rebar arrives Friday.\n";
+        let segments = parse_subtitles(vtt);
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].speaker.as_deref(), Some("김민수"));
+        assert_eq!(segments[0].text, "공정 회의를 시작하겠습니다.");
+        assert_eq!((segments[1].start, segments[1].end), (Some(4.0), Some(6.0)));
+
+        let srt = "1\r\n00:01:02,500 --> 00:01:04,000\r\nHello\r\nthere\r\n";
+        assert_eq!(parse_subtitles(srt)[0].text, "Hello there");
+        assert_eq!(parse_subtitles(srt)[0].start, Some(62.5));
+
+        let plain =
+            parse_transcript("[00:00:05] 박지은: 10:30에 다시 모이죠\nMeeting starts at 10:30\n");
+        assert_eq!(plain[0].speaker.as_deref(), Some("박지은"));
+        assert_eq!(plain[0].text, "10:30에 다시 모이죠");
+        assert_eq!(plain[0].start, Some(5.0));
+        assert_eq!(plain[1].speaker, None);
+    }
+
+    #[test]
+    fn diarization_uses_labels_then_pauses() {
+        let segment = |start: f64, end: f64, text: &str| Segment {
+            start: Some(start),
+            end: Some(end),
+            speaker: None,
+            text: text.into(),
+        };
+        let mut labelled = vec![
+            segment(0.0, 2.0, "A: hi"),
+            segment(2.1, 3.0, "more"),
+            segment(3.0, 4.0, "B: ok"),
+        ];
+        assert_eq!(diarize(&mut labelled, 2, 1.0), Diarization::Labels);
+        assert_eq!(speakers(&labelled), ["A", "B"]);
+        assert_eq!(
+            render_transcript(&labelled),
+            "[00:00:00] A: hi more\n[00:00:03] B: ok"
+        );
+
+        let mut timed = vec![
+            segment(0.0, 2.0, "x"),
+            segment(2.2, 3.0, "y"),
+            segment(5.0, 6.0, "z"),
+        ];
+        assert_eq!(diarize(&mut timed, 2, 1.0), Diarization::Pauses);
+        let names: Vec<_> = timed
+            .iter()
+            .map(|s| s.speaker.as_deref().unwrap())
+            .collect();
+        assert_eq!(names, ["Speaker 1", "Speaker 1", "Speaker 2"]);
+    }
+
+    #[test]
+    fn minutes_render_with_action_items() {
+        let reply = r#"Here you go: {"title": "공정 회의", "summary": "철근 입고 일정 확인",
+            "decisions": ["타설은 다음 주 월요일"],
+            "action_items": [{"task": "철근 입고 확인", "owner": "이영호", "due": "금요일"}, {"task": "안전 점검"}]}"#;
+        let minutes = parse_minutes(reply).unwrap();
+        let markdown = render_minutes(&minutes, true);
+        assert!(markdown.starts_with("# 공정 회의\n"));
+        assert!(markdown
+            .contains("- [ ] 철근 입고 확인 (담당: 이영호, 기한: 금요일)\n- [ ] 안전 점검\n"));
+        assert!(markdown.contains("## 결정 사항\n\n- 타설은 다음 주 월요일\n"));
+    }
+}
@@ -0,0 +1,866 @@
+//! PDF table extraction for `pdf-table-extract`.
+//!
+//! Each page's content stream is read for positioned text runs and ruling
+//! lines. Tables are then found two ways:
+//!
+//! - `ruled`: horizontal and vertical rules that touch form a grid, and each
+//!   text run goes into the cell it falls in.
+//! - `whitespace`: consecutive lines that break into two or more columns at
+//!   wide gaps, with the column starts lined up, form a table.
+//!
+//! Tables come out the way `csv-read` returns one — `headers` plus `rows`
+//! keyed by header — so `data-filter` and the export tools take them as is.
+//! Pages whose text can't be placed fall back to the plain text, split into
+//! columns at runs of spaces or tabs.
+
+use crate::ExecutorError;
+use lopdf::content::Content;
+use lopdf::{Document, Encoding, Object};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Rules closer than this (in points) count as the same line.
+const RULE_TOLERANCE: f64 = 2.0;
+
+/// How a table was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Method {
+    Ruled,
+    Whitespace,
+    LlmRepaired,
+}
+
+/// One extracted table.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Table {
+    /// `p<page>-t<n>`, unique within the document.
+    pub id: String,
+    /// 1-based page number.
+    pub page: u32,
+    pub method: Method,
+    pub headers: Vec<String>,
+    /// Data rows keyed by header.
+    pub rows: Vec<serde_json::Value>,
+    /// Every row as read, header row included.
+    pub cells: Vec<Vec<String>>,
+    pub count: usize,
+    /// 0 to 1; ragged whitespace tables score low.
+    pub confidence: f64,
+    /// The table's lines as they appear on the page, for LLM repair.
+    #[serde(skip)]
+    pub source_text: String,
+}
+
+impl Table {
+    /// Build a table from its grid. With `header`, the first row names the
+    /// columns; otherwise they are named `column_1`, `column_2`, ...
+    pub fn from_cells(
+        id: String,
+        page: u32,
+        method: Method,
+        cells: Vec<Vec<String>>,
+        header: bool,
+        confidence: f64,
+    ) -> Self {
+        let width = cells.iter().map(Vec::len).max().unwrap_or(0);
+        let cells: Vec<Vec<String>> = cells
+            .into_iter()
+            .map(|mut row| {
+                row.resize(width, String::new());
+                row
+            })
+            .collect();
+        let mut headers: Vec<String> = Vec::with_capacity(width);
+        for i in 0..width {
+            let name = match cells.first().filter(|_| header) {
+                Some(row) if !row[i].trim().is_empty() => row[i].trim().to_string(),
+                _ => format!("column_{}", i + 1),
+            };
+            // Keep keys unique so no column is lost from the row objects
+            let name = if headers.contains(&name) {
+                format!("{name}_{}", i + 1)
+            } else {
+                name
+            };
+            headers.push(name);
+        }
+        let rows: Vec<serde_json::Value> = cells
+            .iter()
+            .skip(usize::from(header))
+            .map(|row| {
+                serde_json::Value::Object(
+                    headers
+                        .iter()
+                        .zip(row)
+                        .map(|(h, v)| (h.clone(), serde_json::json!(v)))
+                        .collect(),
+                )
+            })
+            .collect();
+        let source_text = cells
+            .iter()
+            .map(|row| row.join(" | "))
+            .collect::<Vec<_>>()
+            .join("\n");
+        Self {
+            id,
+            page,
+            method,
+            headers,
+            count: rows.len(),
+            rows,
+            cells,
+            confidence,
+            source_text,
+        }
+    }
+}
+
+/// What to extract.
+#[derive(Debug, Clone)]
+pub struct TableOptions {
+    /// Whether the first row of a table names its columns.
+    pub header: bool,
+    /// Fewest data rows for a whitespace table; ruled tables need one.
+    pub min_rows: usize,
+    /// 1-based pages to read; all when empty.
+    pub pages: Vec<u32>,
+}
+
+impl Default for TableOptions {
+    fn default() -> Self {
+        Self {
+            header: true,
+            min_rows: 2,
+            pages: Vec::new(),
+        }
+    }
+}
+
+/// Extract the tables of a PDF, page by page.
+pub fn extract_tables(bytes: &[u8], options: &TableOptions) -> Result<Vec<Table>, ExecutorError> {
+    let doc = Document::load_mem(bytes)
+        .map_err(|e| ExecutorError::ExecutionFailed(format!("Failed to parse PDF: {e}")))?;
+    let mut tables = Vec::new();
+    for (page, page_id) in doc.get_pages() {
+        if !options.pages.is_empty() && !options.pages.contains(&page) {
+            continue;
+        }
+        let layout = read_layout(&doc, page_id).unwrap_or_default();
+        let found = if layout.runs.is_empty() {
+            // Nothing placeable: fall back to the page's plain text
+            let text = doc.extract_text(&[page]).unwrap_or_default();
+            tables_from_text(&text, page, options)
+        } else {
+            tables_from_layout(layout, page, options)
+        };
+        tables.extend(found);
+    }
+    Ok(tables)
+}
+
+/// Parse a page selection such as `"1-3, 5"`.
+pub fn parse_pages(spec: &str) -> Result<Vec<u32>, String> {
+    let mut pages = Vec::new();
+    for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let bad = || format!("Invalid page selection '{part}'");
+        match part.split_once('-') {
+            Some((from, to)) => {
+                let from: u32 = from.trim().parse().map_err(|_| bad())?;
+                let to: u32 = to.trim().parse().map_err(|_| bad())?;
+                if from == 0 || to < from {
+                    return Err(bad());
+                }
+                pages.extend(from..=to);
+            }
+            None => pages.push(part.parse().ok().filter(|&p| p > 0).ok_or_else(bad)?),
+        }
+    }
+    Ok(pages)
+}
+
+// ---- Page layout ----
+
+#[derive(Debug, Clone, PartialEq)]
+struct TextRun {
+    x: f64,
+    y: f64,
+    width: f64,
+    size: f64,
+    text: String,
+}
+
+/// A horizontal or vertical line segment, from its lower to its higher end.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Rule {
+    x0: f64,
+    y0: f64,
+    x1: f64,
+    y1: f64,
+}
+
+impl Rule {
+    fn new(a: (f64, f64), b: (f64, f64)) -> Option<Self> {
+        let (x0, x1) = (a.0.min(b.0), a.0.max(b.0));
+        let (y0, y1) = (a.1.min(b.1), a.1.max(b.1));
+        let rule = Self { x0, y0, x1, y1 };
+        (rule.is_horizontal() || rule.is_vertical()).then_some(rule)
+    }
+
+    fn is_horizontal(&self) -> bool {
+        self.y1 - self.y0 <= RULE_TOLERANCE && self.x1 - self.x0 > RULE_TOLERANCE
+    }
+
+    fn is_vertical(&self) -> bool {
+        self.x1 - self.x0 <= RULE_TOLERANCE && self.y1 - self.y0 > RULE_TOLERANCE
+    }
+
+    fn touches(&self, other: &Rule) -> bool {
+        self.x0 <= other.x1 + RULE_TOLERANCE
+            && other.x0 <= self.x1 + RULE_TOLERANCE
+            && self.y0 <= other.y1 + RULE_TOLERANCE
+            && other.y0 <= self.y1 + RULE_TOLERANCE
+    }
+}
+
+#[derive(Debug, Default)]
+struct Layout {
+    runs: Vec<TextRun>,
+    rules: Vec<Rule>,
+}
+
+/// A 2D affine transform `[a b c d e f]`, as PDF writes them.
+type Matrix = [f64; 6];
+
+const IDENTITY: Matrix = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+fn multiply(m: &Matrix, n: &Matrix) -> Matrix {
+    [
+        m[0] * n[0] + m[1] * n[2],
+        m[0] * n[1] + m[1] * n[3],
+        m[2] * n[0] + m[3] * n[2],
+        m[2] * n[1] + m[3] * n[3],
+        m[4] * n[0] + m[5] * n[2] + n[4],
+        m[4] * n[1] + m[5] * n[3] + n[5],
+    ]
+}
+
+fn apply(m: &Matrix, x: f64, y: f64) -> (f64, f64) {
+    (m[0] * x + m[2] * y + m[4], m[1] * x + m[3] * y + m[5])
+}
+
+fn number(object: &Object) -> Option<f64> {
+    match object {
+        Object::Integer(i) => Some(*i as f64),
+        Object::Real(r) => Some(*r as f64),
+        _ => None,
+    }
+}
+
+/// Estimated advance of `text` at font size 1: CJK characters are about a
+/// square wide, others about half of one.
+fn text_advance(text: &str) -> f64 {
+    text.chars()
+        .map(|c| if is_wide(c) { 1.0 } else { 0.5 })
+        .sum()
+}
+
+fn is_wide(c: char) -> bool {
+    matches!(c, '\u{1100}'..='\u{11FF}' | '\u{2E80}'..='\u{9FFF}' | '\u{AC00}'..='\u{D7A3}' | '\u{F900}'..='\u{FAFF}' | '\u{FF00}'..='\u{FF60}')
+}
+
+/// Read the positioned text and ruling lines of a page. Form XObjects are
+/// not followed.
+fn read_layout(doc: &Document, page_id: lopdf::ObjectId) -> Result<Layout, lopdf::Error> {
+    let encodings: BTreeMap<Vec<u8>, Encoding> = doc
+        .get_page_fonts(page_id)?
+        .into_iter()
+        .filter_map(|(name, font)| font.get_font_encoding(doc).ok().map(|e| (name, e)))
+        .collect();
+    let content = Content::decode(&doc.get_page_content(page_id)?)?;
+
+    let mut layout = Layout::default();
+    let mut ctm = IDENTITY;
+    let mut stack = Vec::new();
+    let (mut tm, mut tlm) = (IDENTITY, IDENTITY);
+    let (mut size, mut leading) = (10.0, 0.0);
+    let mut encoding = None;
+    let mut path: Vec<((f64, f64), (f64, f64))> = Vec::new();
+    let mut current = (0.0, 0.0);
+
+    for op in &content.operations {
+        let args: Vec<f64> = op.operands.iter().filter_map(number).collect();
+        match op.operator.as_str() {
+            "q" => stack.push(ctm),
+            "Q" => ctm = stack.pop().unwrap_or(IDENTITY),
+            "cm" if args.len() == 6 => {
+                ctm = multiply(
+                    &[args[0], args[1], args[2], args[3], args[4], args[5]],
+                    &ctm,
+                )
+            }
+            "BT" => (tm, tlm) = (IDENTITY, IDENTITY),
+            "Tf" => {
+                encoding = op
+                    .operands
+                    .first()
+                    .and_then(|o| o.as_name().ok())
+                    .and_then(|name| encodings.get(name));
+                size = args.first().copied().unwrap_or(size);
+            }
+            "TL" if !args.is_empty() => leading = args[0],
+            "Td" | "TD" if args.len() == 2 => {
+                if op.operator == "TD" {
+                    leading = -args[1];
+                }
+                tlm = multiply(&[1.0, 0.0, 0.0, 1.0, args[0], args[1]], &tlm);
+                tm = tlm;
+            }
+            "Tm" if args.len() == 6 => {
+                tlm = [args[0], args[1], args[2], args[3], args[4], args[5]];
+                tm = tlm;
+            }
+            "T*" => {
+                tlm = multiply(&[1.0, 0.0, 0.0, 1.0, 0.0, -leading], &tlm);
+                tm = tlm;
+            }
+            "Tj" | "TJ" | "'" | "\"" => {
+                if op.operator != "Tj" && op.operator != "TJ" {
+                    tlm = multiply(&[1.0, 0.0, 0.0, 1.0, 0.0, -leading], &tlm);
+                    tm = tlm;
+                }
+                let Some(encoding) = encoding else { continue };
+                let mut text = String::new();
+                let mut adjust = 0.0;
+                for operand in &op.operands {
+                    match operand {
+                        Object::String(bytes, _) => text
+                            .push_str(&Document::decode_text(encoding, bytes).unwrap_or_default()),
+                        Object::Array(parts) => {
+                            for part in parts {
+                                match part {
+                                    Object::String(bytes, _) => text.push_str(
+                                        &Document::decode_text(encoding, bytes).unwrap_or_default(),
+                                    ),
+                                    other => {
+                                        // Large negative kerning is a space
+                                        let kern = number(other).unwrap_or(0.0);
+                                        if kern < -200.0 {
+                                            text.push(' ');
+                                        }
+                                        adjust -= kern / 1000.0;
+                                    }
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                let trm = multiply(&tm, &ctm);
+                let scale = (trm[0] * trm[0] + trm[1] * trm[1]).sqrt();
+                let (x, y) = apply(&trm, 0.0, 0.0);
+                let advance = (text_advance(&text) + adjust) * size;
+                if !text.trim().is_empty() {
+                    layout.runs.push(TextRun {
+                        x,
+                        y,
+                        width: advance * scale,
+                        size: (size * scale).abs().max(1.0),
+                        text,
+                    });
+                }
+                tm = multiply(&[1.0, 0.0, 0.0, 1.0, advance, 0.0], &tm);
+            }
+            "m" if args.len() == 2 => current = apply(&ctm, args[0], args[1]),
+            "l" if args.len() == 2 => {
+                let to = apply(&ctm, args[0], args[1]);
+                path.push((current, to));
+                current = to;
+            }
+            "re" if args.len() == 4 => {
+                let (x, y, w, h) = (args[0], args[1], args[2], args[3]);
+                let corners = [
+                    apply(&ctm, x, y),
+                    apply(&ctm, x + w, y),
+                    apply(&ctm, x + w, y + h),
+                    apply(&ctm, x, y + h),
+                ];
+                // A thin filled rectangle is a rule; a larger one a cell border
+                if w.abs() <= RULE_TOLERANCE || h.abs() <= RULE_TOLERANCE {
+                    path.push((corners[0], corners[2]));
+                } else {
+                    for i in 0..4 {
+                        path.push((corners[i], corners[(i + 1) % 4]));
+                    }
+                }
+            }
+            "S" | "s" | "f" | "F" | "f*" | "B" | "B*" | "b" | "b*" => {
+                layout
+                    .rules
+                    .extend(path.drain(..).filter_map(|(a, b)| Rule::new(a, b)));
+            }
+            "n" => path.clear(),
+            _ => {}
+        }
+    }
+    Ok(layout)
+}
+
+// ---- Table detection ----
+
+fn tables_from_layout(layout: Layout, page: u32, options: &TableOptions) -> Vec<Table> {
+    let mut runs = layout.runs;
+    let mut tables = Vec::new();
+    for grid in grids(&layout.rules) {
+        let (table, rest) = fill_grid(&grid, runs, page, tables.len(), options);
+        runs = rest;
+        tables.extend(table);
+    }
+    let lines = lines_from_runs(runs);
+    tables.extend(whitespace_tables(&lines, page, tables.len(), options));
+    tables
+}
+
+/// Column boundaries (left to right) and row boundaries (top to bottom).
+struct Grid {
+    xs: Vec<f64>,
+    ys: Vec<f64>,
+}
+
+/// Groups of touching rules with at least two distinct horizontal and two
+/// distinct vertical lines.
+fn grids(rules: &[Rule]) -> Vec<Grid> {
+    let mut group: Vec<usize> = (0..rules.len()).collect();
+    fn root(group: &mut [usize], mut i: usize) -> usize {
+        while group[i] != i {
+            group[i] = group[group[i]];
+            i = group[i];
+        }
+        i
+    }
+    for i in 0..rules.len() {
+        for j in i + 1..rules.len() {
+            if rules[i].touches(&rules[j]) {
+                let (a, b) = (root(&mut group, i), root(&mut group, j));
+                group[a] = b;
+            }
+        }
+    }
+    let mut components: BTreeMap<usize, Vec<Rule>> = BTreeMap::new();
+    for (i, rule) in rules.iter().enumerate() {
+        let r = root(&mut group, i);
+        components.entry(r).or_default().push(*rule);
+    }
+
+    let mut grids: Vec<Grid> = components
+        .into_values()
+        .filter_map(|rules| {
+            let xs = distinct(rules.iter().filter(|r| r.is_vertical()).map(|r| r.x0));
+            let mut ys = distinct(rules.iter().filter(|r| r.is_horizontal()).map(|r| r.y0));
+            ys.reverse();
+            (xs.len() >= 2 && ys.len() >= 2).then_some(Grid { xs, ys })
+        })
+        .collect();
+    // Top of the page first
+    grids.sort_by(|a, b| b.ys[0].total_cmp(&a.ys[0]));
+    grids
+}
+
+/// Sorted values with near-duplicates merged.
+fn distinct(values: impl Iterator<Item = f64>) -> Vec<f64> {
+    let mut values: Vec<f64> = values.collect();
+    values.sort_by(f64::total_cmp);
+    values.dedup_by(|b, a| (*b - *a).abs() <= RULE_TOLERANCE);
+    values
+}
+
+/// Put the runs inside `grid` into its cells; returns the table and the runs
+/// outside it.
+fn fill_grid(
+    grid: &Grid,
+    runs: Vec<TextRun>,
+    page: u32,
+    index: usize,
+    options: &TableOptions,
+) -> (Option<Table>, Vec<TextRun>) {
+    let (columns, rows) = (grid.xs.len() - 1, grid.ys.len() - 1);
+    let mut cells: Vec<Vec<Vec<TextRun>>> = vec![vec![Vec::new(); columns]; rows];
+    let mut rest = Vec::new();
+    for run in runs {
+        // The middle of the run's first glyph decides its cell
+        let (x, y) = (run.x + run.size * 0.25, run.y + run.size * 0.3);
+        let column = grid.xs.windows(2).position(|w| w[0] <= x && x < w[1]);
+        let row = grid.ys.windows(2).position(|w| w[1] <= y && y < w[0]);
+        match (row, column) {
+            (Some(row), Some(column)) => cells[row][column].push(run),
+            _ => rest.push(run),
+        }
+    }
+    let cells: Vec<Vec<String>> = cells
+        .into_iter()
+        .map(|row| row.into_iter().map(cell_text).collect::<Vec<_>>())
+        .filter(|row| row.iter().any(|c| !c.is_empty()))
+        .collect();
+    if cells.len() < 1 + usize::from(options.header) || columns < 2 {
+        return (None, rest);
+    }
+    let table = Table::from_cells(
+        format!("p{page}-t{}", index + 1),
+        page,
+        Method::Ruled,
+        cells,
+        options.header,
+        0.9,
+    );
+    (Some(table), rest)
+}
+
+/// The text of one cell, line by line from the top.
+fn cell_text(mut runs: Vec<TextRun>) -> String {
+    runs.sort_by(|a, b| b.y.total_cmp(&a.y).then(a.x.total_cmp(&b.x)));
+    lines_from_runs(runs)
+        .into_iter()
+        .map(|line| {
+            line.cells
+                .iter()
+                .map(|c| c.text.as_str())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim()
+        .to_string()
+}
+
+/// A line of text split into cells at wide gaps.
+#[derive(Debug, Clone)]
+struct Line {
+    y: f64,
+    size: f64,
+    cells: Vec<Cell>,
+}
+
+#[derive(Debug, Clone)]
+struct Cell {
+    x: f64,
+    end: f64,
+    text: String,
+}
+
+impl Cell {
+    /// Whether the cell starts or ends (for right-aligned numbers) in line
+    /// with `column`.
+    fn aligns_with(&self, column: &Cell, tolerance: f64) -> bool {
+        (self.x - column.x).abs() <= tolerance || (self.end - column.end).abs() <= tolerance
+    }
+}
+
+/// Group runs into lines, top to bottom, and each line into cells where the
+/// gap between runs is wider than about a character.
+fn lines_from_runs(mut runs: Vec<TextRun>) -> Vec<Line> {
+    runs.sort_by(|a, b| b.y.total_cmp(&a.y));
+    let mut lines: Vec<(f64, f64, Vec<TextRun>)> = Vec::new();
+    for run in runs {
+        match lines.last_mut() {
+            Some((y, size, line)) if (*y - run.y).abs() < size.max(run.size) * 0.5 => {
+                *size = size.max(run.size);
+                line.push(run);
+            }
+            _ => lines.push((run.y, run.size, vec![run])),
+        }
+    }
+    lines
+        .into_iter()
+        .map(|(y, size, mut runs)| {
+            runs.sort_by(|a, b| a.x.total_cmp(&b.x));
+            let mut cells: Vec<Cell> = Vec::new();
+            let mut end = f64::NEG_INFINITY;
+            for run in runs {
+                let gap = run.x - end;
+                end = end.max(run.x + run.width);
+                match cells.last_mut() {
+                    Some(cell) if gap < run.size * 1.5 => {
+                        if gap > run.size * 0.2 {
+                            cell.text.push(' ');
+                        }
+                        cell.text.push_str(run.text.trim());
+                        cell.end = end;
+                    }
+                    _ => cells.push(Cell {
+                        x: run.x,
+                        end,
+                        text: run.text.trim().to_string(),
+                    }),
+                }
+            }
+            Line { y, size, cells }
+        })
+        .collect()
+}
+
+/// Tables in plain text, where columns are separated by tabs or two or
+/// more spaces. Positions are counted in display columns, two for CJK
+/// characters, so columns of Korean text line up as they do on screen.
+pub fn tables_from_text(text: &str, page: u32, options: &TableOptions) -> Vec<Table> {
+    let separator = regex::Regex::new(r"\t+|\s{2,}").expect("valid separator pattern");
+    let width = |s: &str| text_advance(s) * 2.0;
+    let lines: Vec<Line> = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let mut cells = Vec::new();
+            let mut from = 0;
+            let pieces = separator
+                .find_iter(line)
+                .map(|m| (m.start(), m.end()))
+                .chain(std::iter::once((line.len(), line.len())));
+            for (start, end) in pieces {
+                let piece = &line[from..start];
+                if !piece.trim().is_empty() {
+                    let x = width(&line[..from])
+                        + width(&piece[..piece.len() - piece.trim_start().len()]);
+                    cells.push(Cell {
+                        x,
+                        end: x + width(piece.trim()),
+                        text: piece.trim().to_string(),
+                    });
+                }
+                from = end;
+            }
+            // One text line is one unit of height, one column one of width
+            Line {
+                y: -(i as f64),
+                size: 1.0,
+                cells,
+            }
+        })
+        .collect();
+    whitespace_tables(&lines, page, 0, options)
+}
+
+/// Runs of aligned multi-column lines.
+fn whitespace_tables(
+    lines: &[Line],
+    page: u32,
+    first_index: usize,
+    options: &TableOptions,
+) -> Vec<Table> {
+    let mut tables = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        if lines[i].cells.len() < 2 {
+            i += 1;
+            continue;
+        }
+        // The first line of a block sets the columns
+        let columns = &lines[i].cells;
+        let tolerance = lines[i].size * 2.0;
+        let mut block = vec![lines[i].clone()];
+        let mut j = i + 1;
+        while j < lines.len() {
+            let (line, previous) = (&lines[j], &lines[j - 1]);
+            let aligned = line.cells.len() >= 2
+                && line
+                    .cells
+                    .iter()
+                    .all(|cell| columns.iter().any(|c| cell.aligns_with(c, tolerance)));
+            let close = previous.y - line.y <= previous.size.max(line.size) * 2.5;
+            if !aligned || !close {
+                break;
+            }
+            block.push(line.clone());
+            j += 1;
+        }
+
+        let data_rows = block.len() - usize::from(options.header);
+        if data_rows >= options.min_rows.max(1) {
+            let mut filled = 0;
+            let cells: Vec<Vec<String>> = block
+                .iter()
+                .map(|line| {
+                    let mut row = vec![String::new(); columns.len()];
+                    for cell in &line.cells {
+                        let column = nearest(columns, cell, tolerance);
+                        if !row[column].is_empty() {
+                            row[column].push(' ');
+                        }
+                        row[column].push_str(&cell.text);
+                    }
+                    if row.iter().all(|c| !c.is_empty()) {
+                        filled += 1;
+                    }
+                    row
+                })
+                .collect();
+            // Rows with empty cells are fine, but many make the split doubtful
+            let confidence = 0.4 + 0.4 * filled as f64 / block.len() as f64;
+            tables.push(Table::from_cells(
+                format!("p{page}-t{}", first_index + tables.len() + 1),
+                page,
+                Method::Whitespace,
+                cells,
+                options.header,
+                (confidence * 100.0).round() / 100.0,
+            ));
+            i = j;
+        } else {
+            i += 1;
+        }
+    }
+    tables
+}
+
+/// The column a cell belongs to: the first it aligns with, else the one
+/// whose start is nearest.
+fn nearest(columns: &[Cell], cell: &Cell, tolerance: f64) -> usize {
+    columns
+        .iter()
+        .position(|c| cell.aligns_with(c, tolerance))
+        .or_else(|| {
+            columns
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| (cell.x - a.x).abs().total_cmp(&(cell.x - b.x).abs()))
+                .map(|(i, _)| i)
+        })
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::content::Operation;
+    use lopdf::{dictionary, Stream};
+
+    /// A one-page PDF drawing `operations` with Helvetica as /F1.
+    fn pdf(operations: Vec<Operation>) -> Vec<u8> {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+            "Encoding" => "WinAnsiEncoding",
+        });
+        let content = Content { operations };
+        let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content_id,
+            "Resources" => dictionary! { "Font" => dictionary! { "F1" => font_id } },
+            "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+        });
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![page_id.into()],
+                "Count" => 1,
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog_id);
+        let mut bytes = Vec::new();
+        doc.save_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    fn text(x: f64, y: f64, s: &str) -> Vec<Operation> {
+        vec![
+            Operation::new("BT", vec![]),
+            Operation::new("Tf", vec!["F1".into(), 10.into()]),
+            Operation::new("Td", vec![(x as f32).into(), (y as f32).into()]),
+            Operation::new("Tj", vec![Object::string_literal(s)]),
+            Operation::new("ET", vec![]),
+        ]
+    }
+
+    #[test]
+    fn reads_ruled_grids_into_cells() {
+        let mut ops = Vec::new();
+        // A 3x3 grid of cell borders from (50, 700) to (350, 760)
+        for row in 0..3 {
+            for column in 0..3 {
+                ops.push(Operation::new(
+                    "re",
+                    vec![
+                        (50 + column * 100).into(),
+                        (700 + row * 20).into(),
+                        100.into(),
+                        20.into(),
+                    ],
+                ));
+            }
+        }
+        ops.push(Operation::new("S", vec![]));
+        for (row, values) in [
+            ["Item", "Qty", "Price"],
+            ["Bolt", "120", "0.25"],
+            ["Nut", "", "0.10"],
+        ]
+        .iter()
+        .enumerate()
+        {
+            for (column, value) in values.iter().enumerate() {
+                if !value.is_empty() {
+                    ops.extend(text(
+                        55.0 + column as f64 * 100.0,
+                        745.0 - row as f64 * 20.0,
+                        value,
+                    ));
+                }
+            }
+        }
+        ops.extend(text(50.0, 600.0, "Totals are shown without tax."));
+
+        let tables = extract_tables(&pdf(ops), &TableOptions::default()).unwrap();
+        assert_eq!(tables.len(), 1);
+        let table = &tables[0];
+        assert_eq!((table.id.as_str(), table.method), ("p1-t1", Method::Ruled));
+        assert_eq!(table.headers, ["Item", "Qty", "Price"]);
+        assert_eq!(table.count, 2);
+        assert_eq!(table.rows[0]["Qty"], "120");
+        assert_eq!(table.rows[1]["Qty"], "");
+        assert_eq!(table.rows[1]["Price"], "0.10");
+    }
+
+    #[test]
+    fn finds_aligned_columns_without_rules() {
+        let mut ops = text(50.0, 800.0, "Quarterly report");
+        for (row, values) in [
+            ["Region", "Sales"],
+            ["North", "1,200"],
+            ["South Coast", "980"],
+        ]
+        .iter()
+        .enumerate()
+        {
+            let y = 760.0 - row as f64 * 14.0;
+            ops.extend(text(50.0, y, values[0]));
+            ops.extend(text(200.0, y, values[1]));
+        }
+        let tables = extract_tables(&pdf(ops), &TableOptions::default()).unwrap();
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].method, Method::Whitespace);
+        assert_eq!(tables[0].headers, ["Region", "Sales"]);
+        assert_eq!(tables[0].rows[1]["Region"], "South Coast");
+        assert_eq!(tables[0].confidence, 0.8);
+    }
+
+    #[test]
+    fn splits_plain_text_at_wide_gaps() {
+        let text = "자재 목록\n\n품명        수량    단가\n철근 D13     120   8,500\n레미콘        35\n\n비고: 부가세 별도";
+        let tables = tables_from_text(text, 2, &TableOptions::default());
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].id, "p2-t1");
+        assert_eq!(tables[0].headers, ["품명", "수량", "단가"]);
+        assert_eq!(tables[0].rows[0]["품명"], "철근 D13");
+        assert_eq!(tables[0].rows[1]["단가"], "");
+        assert!(tables[0].confidence < 0.8);
+        assert_eq!(parse_pages("1-3, 5").unwrap(), [1, 2, 3, 5]);
+        assert!(parse_pages("3-1").is_err());
+    }
+}
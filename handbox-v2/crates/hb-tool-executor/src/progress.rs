@@ -0,0 +1,300 @@
+//! Progress reporting for long-running tools.
+//!
+//! Native tools call [`ProgressReporter::report`] on the reporter carried by
+//! their [`ToolInput`](crate::ToolInput). Process tools write lines of the form
+//!
+//! ```text
+//! HB_PROGRESS {"current": 120, "total": 10000, "step": "embedding chunks"}
+//! ```
+//!
+//! to stderr; every field is optional and `fraction` (0.0–1.0) may be given
+//! instead of `current`/`total`. Other stderr output is left untouched.
+//! Process and Docker tools run with `HB_PROGRESS=1` in their environment,
+//! so a tool can tell its progress lines will be read. The Docker runtime
+//! reports its own image pulls as progress too.
+//!
+//! Every update, and for process tools every line on stderr, is also a
+//! heartbeat: the runner watches the [`Heartbeat`] to tell a node that is
+//! still working from one that hung. A tool with nothing to report can call
+//! [`ProgressReporter::beat`], or print an empty `HB_PROGRESS {}` line.
+//!
+//! Native tools often wait on a call that reports nothing until it returns,
+//! such as an LLM request without streaming. [`execute`](crate::execute)
+//! runs them under [`ProgressReporter::keep_alive`], so they count as alive
+//! while in flight; their own timeouts and the node's time limit bound them.
+//! Only tools running outside the app are judged by their silence.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Stderr line prefix process tools use to report progress.
+pub const PROGRESS_PREFIX: &str = "HB_PROGRESS ";
+
+/// Set to `1` in the environment of tools whose progress lines are read.
+pub const PROGRESS_ENV: &str = "HB_PROGRESS";
+
+/// Minimum gap between forwarded updates for the same step.
+const MIN_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How often [`ProgressReporter::keep_alive`] beats.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How far a tool has got. A missing `fraction` means indeterminate.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Progress {
+    /// Completed share of the work, 0.0–1.0.
+    #[serde(default)]
+    pub fraction: Option<f64>,
+    #[serde(default)]
+    pub current: Option<u64>,
+    #[serde(default)]
+    pub total: Option<u64>,
+    /// What the tool is doing now, e.g. "pulling image" or "parsing".
+    #[serde(default)]
+    pub step: Option<String>,
+}
+
+impl Progress {
+    /// `current` of `total` items done.
+    pub fn items(current: u64, total: u64) -> Self {
+        Self {
+            fraction: None,
+            current: Some(current),
+            total: Some(total),
+            step: None,
+        }
+        .normalized()
+    }
+
+    /// An indeterminate update naming the current step.
+    pub fn step(step: impl Into<String>) -> Self {
+        Self {
+            step: Some(step.into()),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_step(mut self, step: impl Into<String>) -> Self {
+        self.step = Some(step.into());
+        self
+    }
+
+    /// Derive `fraction` from the counts when missing and clamp it to 0–1.
+    fn normalized(mut self) -> Self {
+        if self.fraction.is_none() {
+            if let (Some(current), Some(total)) = (self.current, self.total) {
+                if total > 0 {
+                    self.fraction = Some(current as f64 / total as f64);
+                }
+            }
+        }
+        self.fraction = self.fraction.map(|f| f.clamp(0.0, 1.0));
+        self
+    }
+
+    fn is_done(&self) -> bool {
+        self.fraction.is_some_and(|f| f >= 1.0)
+    }
+
+    /// Parse a `HB_PROGRESS` stderr line; `None` for any other line.
+    pub fn parse_line(line: &str) -> Option<Self> {
+        let json = line.trim_end().strip_prefix(PROGRESS_PREFIX)?;
+        serde_json::from_str::<Self>(json).ok().map(Self::normalized)
+    }
+}
+
+/// When a running tool last showed signs of life. Clones share the clock.
+#[derive(Debug, Clone)]
+pub struct Heartbeat {
+    /// On tokio's clock, so tests with paused time see silences advance.
+    started: tokio::time::Instant,
+    /// Milliseconds after `started` of the latest beat.
+    last_ms: Arc<AtomicU64>,
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Heartbeat {
+    /// A heartbeat that beat just now.
+    pub fn new() -> Self {
+        Self {
+            started: tokio::time::Instant::now(),
+            last_ms: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn beat(&self) {
+        let now = self.started.elapsed().as_millis() as u64;
+        self.last_ms.fetch_max(now, Ordering::Relaxed);
+    }
+
+    /// Time since the latest beat.
+    pub fn silence(&self) -> Duration {
+        let last = Duration::from_millis(self.last_ms.load(Ordering::Relaxed));
+        self.started.elapsed().saturating_sub(last)
+    }
+}
+
+/// Receives progress updates, e.g. to forward them to the UI.
+pub type ProgressSink = Arc<dyn Fn(Progress) + Send + Sync>;
+
+/// When the last update was forwarded, and for which step.
+type LastForwarded = Option<(Instant, Option<String>)>;
+
+/// Throttled handle tools report progress through. The default reporter
+/// drops every update.
+#[derive(Clone, Default)]
+pub struct ProgressReporter {
+    sink: Option<ProgressSink>,
+    last: Arc<Mutex<LastForwarded>>,
+    heartbeat: Option<Heartbeat>,
+}
+
+impl fmt::Debug for ProgressReporter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgressReporter")
+            .field("enabled", &self.sink.is_some())
+            .field("heartbeat", &self.heartbeat.is_some())
+            .finish()
+    }
+}
+
+impl ProgressReporter {
+    pub fn new<F>(sink: F) -> Self
+    where
+        F: Fn(Progress) + Send + Sync + 'static,
+    {
+        Self {
+            sink: Some(Arc::new(sink)),
+            last: Default::default(),
+            heartbeat: None,
+        }
+    }
+
+    /// Also beat `heartbeat` on every update and [`beat`](Self::beat).
+    pub fn with_heartbeat(mut self, heartbeat: Heartbeat) -> Self {
+        self.heartbeat = Some(heartbeat);
+        self
+    }
+
+    /// Show the tool is alive without reporting progress.
+    pub fn beat(&self) {
+        if let Some(heartbeat) = &self.heartbeat {
+            heartbeat.beat();
+        }
+    }
+
+    /// Run `work`, beating every second until it finishes.
+    pub async fn keep_alive<F: std::future::Future>(&self, work: F) -> F::Output {
+        let Some(heartbeat) = &self.heartbeat else {
+            return work.await;
+        };
+        let mut beats = tokio::time::interval(KEEP_ALIVE_INTERVAL);
+        tokio::pin!(work);
+        loop {
+            tokio::select! {
+                output = &mut work => return output,
+                _ = beats.tick() => heartbeat.beat(),
+            }
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.sink.is_some()
+    }
+
+    /// Forward an update. Updates within 100ms of the previous one are
+    /// dropped unless they start a new step or finish the work.
+    pub fn report(&self, progress: Progress) {
+        self.beat();
+        let Some(sink) = &self.sink else {
+            return;
+        };
+        let progress = progress.normalized();
+        {
+            let Ok(mut last) = self.last.lock() else {
+                return;
+            };
+            let now = Instant::now();
+            let forward = match last.as_ref() {
+                None => true,
+                Some((at, step)) => {
+                    progress.is_done()
+                        || *step != progress.step
+                        || now.duration_since(*at) >= MIN_INTERVAL
+                }
+            };
+            if !forward {
+                return;
+            }
+            *last = Some((now, progress.step.clone()));
+        }
+        sink(progress);
+    }
+
+    /// Shorthand for `report(Progress::items(current, total).with_step(step))`.
+    pub fn items(&self, current: u64, total: u64, step: &str) {
+        if self.is_enabled() {
+            self.report(Progress::items(current, total).with_step(step));
+        } else {
+            self.beat();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn throttles_updates_but_keeps_step_changes_and_completion() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let reporter = ProgressReporter::new(move |p| sink.lock().unwrap().push(p));
+
+        for i in 0..=1000 {
+            reporter.items(i, 1000, "embedding");
+        }
+        reporter.report(Progress::step("storing"));
+
+        let seen = seen.lock().unwrap();
+        assert!(seen.len() < 10, "{} updates forwarded", seen.len());
+        assert_eq!(seen[0].fraction, Some(0.0));
+        assert!(seen.iter().any(|p| p.fraction == Some(1.0)));
+        assert_eq!(seen.last().unwrap().step.as_deref(), Some("storing"));
+    }
+
+    #[test]
+    fn updates_beat_even_when_throttled() {
+        let heartbeat = Heartbeat::new();
+        let reporter = ProgressReporter::default().with_heartbeat(heartbeat.clone());
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(heartbeat.silence() >= Duration::from_millis(30));
+        // No sink: the update goes nowhere but still counts as a sign of life
+        reporter.items(1, 10, "parsing");
+        assert!(heartbeat.silence() < Duration::from_millis(30));
+        std::thread::sleep(Duration::from_millis(30));
+        reporter.beat();
+        assert!(heartbeat.silence() < Duration::from_millis(30));
+    }
+
+    #[test]
+    fn parses_process_progress_lines() {
+        let p = Progress::parse_line("HB_PROGRESS {\"current\": 3, \"total\": 4}\n").unwrap();
+        assert_eq!(p.fraction, Some(0.75));
+        assert_eq!(
+            Progress::parse_line("HB_PROGRESS {\"fraction\": 1.5, \"step\": \"pull\"}")
+                .unwrap()
+                .fraction,
+            Some(1.0)
+        );
+        assert!(Progress::parse_line("warning: something").is_none());
+    }
+}
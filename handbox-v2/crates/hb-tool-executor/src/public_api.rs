@@ -0,0 +1,599 @@
+//! Public data APIs — presets and plumbing for data.go.kr-style services:
+//! where the service key goes, daily quotas and request pacing, XML or JSON
+//! replies normalized to one JSON shape, and page-by-page fetching.
+//!
+//! Services on the portal share a layout: a `serviceKey` query parameter,
+//! `pageNo`/`numOfRows` paging, and a `response.header` result code next to
+//! `response.body.items.item`. Presets fill in what differs per service; a
+//! custom service is described in config the same way.
+
+use chrono::{FixedOffset, NaiveDate, Utc};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Environment variable (or app secret) holding the portal's service key.
+pub const DATA_GO_KR_KEY: &str = "DATA_GO_KR_SERVICE_KEY";
+
+/// Preset IDs, in the order they are offered.
+pub const PRESETS: [&str; 5] = [
+    "kma-forecast",
+    "apt-trade",
+    "building-register",
+    "air-quality",
+    "data-go-kr",
+];
+
+/// Where the service key goes in a request.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyPlacement {
+    Query(String),
+    /// Header value is `prefix` followed by the key.
+    Header {
+        name: String,
+        prefix: String,
+    },
+}
+
+/// How to call one API and read its replies.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Service {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    /// Environment variable or app secret holding the key.
+    pub key_env: String,
+    pub key: KeyPlacement,
+    /// Parameter asking for JSON, for services that answer XML by default.
+    pub format: Option<(String, String)>,
+    pub page_param: String,
+    pub size_param: String,
+    pub page_size: u32,
+    pub items_path: Vec<String>,
+    pub total_path: Vec<String>,
+    /// Calls a development key may make per day, if limited.
+    pub daily_limit: Option<u32>,
+    /// Shortest gap between two requests to the service.
+    pub min_interval: Duration,
+    /// Parameters the service refuses to answer without.
+    pub required: Vec<String>,
+}
+
+impl Service {
+    /// A portal service at `url` with the portal's defaults.
+    fn data_go_kr(id: &str, name: &str, url: &str) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            url: url.into(),
+            key_env: DATA_GO_KR_KEY.into(),
+            key: KeyPlacement::Query("serviceKey".into()),
+            format: None,
+            page_param: "pageNo".into(),
+            size_param: "numOfRows".into(),
+            page_size: 100,
+            items_path: path("response.body.items.item"),
+            total_path: path("response.body.totalCount"),
+            daily_limit: Some(1000),
+            min_interval: Duration::from_millis(100),
+            required: Vec::new(),
+        }
+    }
+
+    fn format(mut self, param: &str, value: &str) -> Self {
+        self.format = Some((param.into(), value.into()));
+        self
+    }
+
+    fn required(mut self, params: &[&str]) -> Self {
+        self.required = params.iter().map(|p| p.to_string()).collect();
+        self
+    }
+
+    /// The preset `id`, if there is one.
+    pub fn preset(id: &str) -> Option<Self> {
+        let service = match id {
+            "kma-forecast" => Self::data_go_kr(
+                id,
+                "기상청 단기예보",
+                "https://apis.data.go.kr/1360000/VilageFcstInfoService_2.0/getVilageFcst",
+            )
+            .format("dataType", "JSON")
+            .required(&["base_date", "base_time", "nx", "ny"]),
+            "apt-trade" => Self::data_go_kr(
+                id,
+                "국토교통부 아파트 매매 실거래가",
+                "https://apis.data.go.kr/1613000/RTMSDataSvcAptTrade/getRTMSDataSvcAptTrade",
+            )
+            .required(&["LAWD_CD", "DEAL_YMD"]),
+            "building-register" => Self::data_go_kr(
+                id,
+                "건축물대장 표제부",
+                "https://apis.data.go.kr/1613000/BldRgstHubService/getBrTitleInfo",
+            )
+            .format("_type", "json")
+            .required(&["sigunguCd", "bjdongCd"]),
+            "air-quality" => Self {
+                daily_limit: Some(500),
+                ..Self::data_go_kr(
+                    id,
+                    "에어코리아 측정소별 실시간 대기오염",
+                    "https://apis.data.go.kr/B552584/ArpltnInforInqireSvc/getMsrstnAcctoRltmMesureDnsty",
+                )
+                .format("returnType", "json")
+                .required(&["stationName", "dataTerm"])
+            },
+            // Any other portal service, at the URL given in config
+            "data-go-kr" => Self::data_go_kr(id, "공공데이터포털", ""),
+            _ => return None,
+        };
+        Some(service)
+    }
+
+    /// The service named by `config.service` (the generic portal service
+    /// when unset), with any of its settings overridden by config.
+    pub fn from_config(config: &Value) -> Result<Self, String> {
+        let id = config
+            .get("service")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .unwrap_or("data-go-kr");
+        let mut service = Self::preset(id)
+            .ok_or_else(|| format!("unknown service '{id}'; use one of {}", PRESETS.join(", ")))?;
+
+        let text = |key: &str| {
+            config
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+        };
+        if let Some(url) = text("url") {
+            service.url = url.into();
+        }
+        if let Some(key_env) = text("key_env") {
+            service.key_env = key_env.into();
+        }
+        if let Some(param) = text("key_param") {
+            service.key = KeyPlacement::Query(param.into());
+        }
+        if let Some(header) = text("key_header") {
+            service.key = KeyPlacement::Header {
+                name: header.into(),
+                // Untrimmed: prefixes usually end in a space
+                prefix: config
+                    .get("key_prefix")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .into(),
+            };
+        }
+        if let Some(format) = text("format_param") {
+            service.format = match format.split_once('=') {
+                Some((param, value)) => Some((param.trim().into(), value.trim().into())),
+                None => return Err(format!("format_param '{format}' must look like name=value")),
+            };
+        }
+        if let Some(param) = text("page_param") {
+            service.page_param = param.into();
+        }
+        if let Some(param) = text("size_param") {
+            service.size_param = param.into();
+        }
+        if let Some(items) = text("items_path") {
+            service.items_path = path(items);
+        }
+        if let Some(total) = text("total_path") {
+            service.total_path = path(total);
+        }
+        if let Some(size) = config.get("page_size").and_then(|v| v.as_u64()) {
+            service.page_size = size.clamp(1, 10_000) as u32;
+        }
+        match config.get("daily_limit").and_then(|v| v.as_u64()) {
+            Some(0) => service.daily_limit = None,
+            Some(limit) => service.daily_limit = Some(limit as u32),
+            None => {}
+        }
+
+        if service.url.is_empty() {
+            return Err(format!("{} needs the API URL in 'url'", service.id));
+        }
+        Ok(service)
+    }
+
+    /// Required parameters missing from `params`.
+    pub fn missing_params(&self, params: &BTreeMap<String, String>) -> Vec<String> {
+        self.required
+            .iter()
+            .filter(|p| params.get(*p).is_none_or(|v| v.trim().is_empty()))
+            .cloned()
+            .collect()
+    }
+
+    /// Query parameters of page `page` (from 1), key excluded.
+    pub fn page_query(
+        &self,
+        params: &BTreeMap<String, String>,
+        page: u32,
+    ) -> Vec<(String, String)> {
+        let mut query = Vec::new();
+        if let Some((param, value)) = &self.format {
+            query.push((param.clone(), value.clone()));
+        }
+        query.push((self.page_param.clone(), page.to_string()));
+        query.push((self.size_param.clone(), self.page_size.to_string()));
+        // Given parameters win over the defaults above
+        for (name, value) in params {
+            match query.iter_mut().find(|(q, _)| q == name) {
+                Some(entry) => entry.1 = value.clone(),
+                None => query.push((name.clone(), value.clone())),
+            }
+        }
+        query
+    }
+}
+
+fn path(dotted: &str) -> Vec<String> {
+    dotted
+        .split('.')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// The service key as the portal expects it sent. Keys are issued in an
+/// "encoded" form as well; those are decoded so they are not encoded twice.
+pub fn normalize_key(key: &str) -> String {
+    let key = key.trim();
+    if key.contains('%') {
+        if let Ok(decoded) = urlencoding::decode(key) {
+            return decoded.into_owned();
+        }
+    }
+    key.to_string()
+}
+
+// ---------------------------------------------------------------------------
+// Replies
+// ---------------------------------------------------------------------------
+
+/// Parse a reply body, XML or JSON, into JSON. XML elements become objects
+/// keyed by child name (repeated children become arrays), text-only
+/// elements become trimmed strings and attributes become `@name` keys.
+pub fn parse_body(text: &str) -> Result<Value, String> {
+    let text = text.trim_start_matches('\u{feff}').trim();
+    if text.starts_with('<') {
+        let doc =
+            roxmltree::Document::parse(text).map_err(|e| format!("invalid XML reply: {e}"))?;
+        let root = doc.root_element();
+        let mut object = Map::new();
+        object.insert(root.tag_name().name().to_string(), element_value(root));
+        return Ok(Value::Object(object));
+    }
+    serde_json::from_str(text).map_err(|_| {
+        let start: String = text.chars().take(200).collect();
+        format!("reply is neither JSON nor XML: {start}")
+    })
+}
+
+fn element_value(node: roxmltree::Node) -> Value {
+    let text: String = node
+        .children()
+        .filter(|c| c.is_text())
+        .filter_map(|c| c.text())
+        .collect();
+    let text = text.trim();
+    let children: Vec<_> = node.children().filter(|c| c.is_element()).collect();
+    if children.is_empty() && node.attributes().len() == 0 {
+        return Value::String(text.to_string());
+    }
+
+    let mut object = Map::new();
+    for attr in node.attributes() {
+        object.insert(
+            format!("@{}", attr.name()),
+            Value::String(attr.value().to_string()),
+        );
+    }
+    for child in children {
+        let name = child.tag_name().name().to_string();
+        let value = element_value(child);
+        match object.get_mut(&name) {
+            Some(Value::Array(values)) => values.push(value),
+            Some(existing) => *existing = Value::Array(vec![existing.take(), value]),
+            None => {
+                object.insert(name, value);
+            }
+        }
+    }
+    if !text.is_empty() {
+        object.insert("#text".into(), Value::String(text.to_string()));
+    }
+    Value::Object(object)
+}
+
+/// An error the service reported in its reply.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiFault {
+    pub code: String,
+    pub message: String,
+}
+
+impl ApiFault {
+    /// Whether waiting and calling again may help.
+    pub fn retryable(&self) -> bool {
+        matches!(self.code.as_str(), "01" | "02" | "04" | "05" | "99")
+    }
+
+    /// Whether the day's calls for the key are used up.
+    pub fn quota_exceeded(&self) -> bool {
+        self.code == "22"
+    }
+}
+
+impl std::fmt::Display for ApiFault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let hint = match self.code.as_str() {
+            "10" | "11" => "a parameter is missing or invalid",
+            "12" => "the service does not exist or was retired",
+            "20" => "the key is not approved for this service; apply for it on the portal",
+            "22" => "the key's daily call limit is used up",
+            "30" => "the service key is not registered; check it was copied whole",
+            "31" => "the key's usage period has ended; extend it on the portal",
+            "32" => "requests are not allowed from this IP address",
+            _ => "",
+        };
+        write!(f, "service error {}: {}", self.code, self.message)?;
+        if !hint.is_empty() {
+            write!(f, " ({hint})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Result codes that mean success, and ones that mean "nothing found".
+const OK_CODES: [&str; 4] = ["00", "0", "000", "INFO-000"];
+const NO_DATA_CODES: [&str; 2] = ["03", "INFO-200"];
+
+/// One page of a reply.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Page {
+    pub items: Vec<Value>,
+    /// Records across all pages, when the reply says.
+    pub total: Option<u64>,
+}
+
+/// Read the items of a parsed reply, or the error it reports.
+pub fn read_page(service: &Service, body: &Value) -> Result<Page, ApiFault> {
+    if let Some(fault) = fault(body) {
+        if NO_DATA_CODES.contains(&fault.code.as_str()) {
+            return Ok(Page::default());
+        }
+        return Err(fault);
+    }
+
+    let items = match at(body, &service.items_path) {
+        Some(value) => items_of(value),
+        // Some services put the array one level up (`items: [...]`)
+        None => match service.items_path.split_last() {
+            Some((_, parent)) => at(body, parent).map(items_of).unwrap_or_default(),
+            None => Vec::new(),
+        },
+    };
+    let total = at(body, &service.total_path).and_then(|v| match v {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    });
+    Ok(Page { items, total })
+}
+
+/// The error in a reply's header, if any. Covers the usual `response.header`
+/// and the gateway's `OpenAPI_ServiceResponse` error document.
+fn fault(body: &Value) -> Option<ApiFault> {
+    let field = |v: &Value, keys: &[&str]| {
+        keys.iter().find_map(|k| match v.get(k) {
+            Some(Value::String(s)) if !s.is_empty() => Some(s.clone()),
+            Some(Value::Number(n)) => Some(n.to_string()),
+            _ => None,
+        })
+    };
+    if let Some(header) = body.pointer("/OpenAPI_ServiceResponse/cmmMsgHeader") {
+        return Some(ApiFault {
+            code: field(header, &["returnReasonCode"]).unwrap_or_else(|| "99".into()),
+            message: field(header, &["returnAuthMsg", "errMsg"]).unwrap_or_default(),
+        });
+    }
+    let header = body
+        .pointer("/response/header")
+        .or_else(|| body.get("header"))?;
+    let code = field(header, &["resultCode", "returnReasonCode"])?;
+    if OK_CODES.contains(&code.as_str()) {
+        return None;
+    }
+    Some(ApiFault {
+        code,
+        message: field(header, &["resultMsg", "returnAuthMsg", "errMsg"]).unwrap_or_default(),
+    })
+}
+
+fn at<'a>(body: &'a Value, path: &[String]) -> Option<&'a Value> {
+    path.iter().try_fold(body, |v, key| v.get(key))
+}
+
+/// A single XML record comes back as an object, none as an empty string.
+fn items_of(value: &Value) -> Vec<Value> {
+    match value {
+        Value::Array(items) => items.clone(),
+        Value::Object(_) => vec![value.clone()],
+        _ => Vec::new(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Quotas
+// ---------------------------------------------------------------------------
+
+/// Calls made per service today and when each may be called next.
+#[derive(Debug, Default)]
+pub struct QuotaLedger {
+    used: HashMap<String, (NaiveDate, u32)>,
+    next_slot: HashMap<String, Instant>,
+}
+
+impl QuotaLedger {
+    /// Reserve one call to `service` on `today` at `now`: how long to wait
+    /// before sending it and the calls used today counting it, or the calls
+    /// already used when none are left.
+    pub fn reserve(
+        &mut self,
+        service: &Service,
+        today: NaiveDate,
+        now: Instant,
+    ) -> Result<(Duration, u32), u32> {
+        let used = self.used.entry(service.id.clone()).or_insert((today, 0));
+        if used.0 != today {
+            *used = (today, 0);
+        }
+        if service.daily_limit.is_some_and(|limit| used.1 >= limit) {
+            return Err(used.1);
+        }
+        used.1 += 1;
+
+        let slot = self.next_slot.entry(service.id.clone()).or_insert(now);
+        let start = (*slot).max(now);
+        *slot = start + service.min_interval;
+        Ok((start - now, used.1))
+    }
+
+    /// Calls made to `service_id` on `today`.
+    pub fn used(&self, service_id: &str, today: NaiveDate) -> u32 {
+        match self.used.get(service_id) {
+            Some((day, used)) if *day == today => *used,
+            _ => 0,
+        }
+    }
+}
+
+/// Shared by every node in the process, so parallel branches calling the
+/// same service are paced together. Counts start over when the app restarts.
+static LEDGER: OnceLock<Mutex<QuotaLedger>> = OnceLock::new();
+
+/// Today on the portal's clock; daily limits reset at midnight KST.
+pub fn portal_today() -> NaiveDate {
+    let kst = FixedOffset::east_opt(9 * 3600).expect("valid offset");
+    Utc::now().with_timezone(&kst).date_naive()
+}
+
+/// [`QuotaLedger::reserve`] on the shared ledger.
+pub fn reserve_call(service: &Service) -> Result<(Duration, u32), u32> {
+    let ledger = LEDGER.get_or_init(Default::default);
+    let mut ledger = ledger.lock().unwrap_or_else(|e| e.into_inner());
+    ledger.reserve(service, portal_today(), Instant::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xml_and_json_replies_read_the_same() {
+        let service = Service::preset("apt-trade").unwrap();
+        let xml = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+            <response>
+              <header><resultCode>000</resultCode><resultMsg>OK</resultMsg></header>
+              <body>
+                <items>
+                  <item><aptNm>래미안</aptNm><dealAmount>   82,500</dealAmount><umdNm>반포동</umdNm></item>
+                </items>
+                <numOfRows>100</numOfRows><pageNo>1</pageNo><totalCount>1</totalCount>
+              </body>
+            </response>"#;
+        let page = read_page(&service, &parse_body(xml).unwrap()).unwrap();
+        assert_eq!(page.total, Some(1));
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0]["dealAmount"], "82,500");
+
+        let json = r#"{"response":{"header":{"resultCode":"00","resultMsg":"NORMAL_SERVICE"},
+            "body":{"items":[{"stationName":"종로구","pm10Value":"31"},{"stationName":"종로구","pm10Value":"28"}],
+            "totalCount":2}}}"#;
+        let air = Service::preset("air-quality").unwrap();
+        let page = read_page(&air, &parse_body(json).unwrap()).unwrap();
+        assert_eq!((page.items.len(), page.total), (2, Some(2)));
+
+        let empty = "<response><header><resultCode>03</resultCode><resultMsg>NODATA_ERROR</resultMsg></header></response>";
+        assert_eq!(
+            read_page(&service, &parse_body(empty).unwrap()).unwrap(),
+            Page::default()
+        );
+
+        let gateway = "<OpenAPI_ServiceResponse><cmmMsgHeader><errMsg>SERVICE ERROR</errMsg>\
+            <returnAuthMsg>SERVICE_KEY_IS_NOT_REGISTERED_ERROR</returnAuthMsg>\
+            <returnReasonCode>30</returnReasonCode></cmmMsgHeader></OpenAPI_ServiceResponse>";
+        let fault = read_page(&service, &parse_body(gateway).unwrap()).unwrap_err();
+        assert_eq!(fault.code, "30");
+        assert!(fault.to_string().contains("not registered"));
+    }
+
+    #[test]
+    fn config_overrides_presets_and_checks_parameters() {
+        let service = Service::from_config(&serde_json::json!({
+            "service": "kma-forecast",
+            "page_size": 1000,
+            "daily_limit": 0,
+        }))
+        .unwrap();
+        assert_eq!(service.daily_limit, None);
+        let params = BTreeMap::from([
+            ("base_date".to_string(), "20261017".to_string()),
+            ("nx".to_string(), "60".to_string()),
+            ("numOfRows".to_string(), "50".to_string()),
+        ]);
+        assert_eq!(service.missing_params(&params), ["base_time", "ny"]);
+        let query = service.page_query(&params, 2);
+        assert!(query.contains(&("dataType".into(), "JSON".into())));
+        assert!(query.contains(&("pageNo".into(), "2".into())));
+        assert!(query.contains(&("numOfRows".into(), "50".into())));
+
+        let custom = Service::from_config(&serde_json::json!({
+            "url": "https://apis.data.go.kr/1230000/BidPublicInfoService/getBidPblancListInfoServc",
+            "format_param": "type=json",
+            "key_header": "Authorization",
+            "key_prefix": "Infuser ",
+        }))
+        .unwrap();
+        assert_eq!(custom.format, Some(("type".into(), "json".into())));
+        assert!(
+            matches!(custom.key, KeyPlacement::Header { ref prefix, .. } if prefix == "Infuser ")
+        );
+        assert!(Service::from_config(&serde_json::json!({})).is_err());
+        assert_eq!(normalize_key("abc%2Bdef%3D%3D"), "abc+def==");
+    }
+
+    #[test]
+    fn ledger_paces_calls_and_resets_daily() {
+        let service = Service {
+            daily_limit: Some(2),
+            ..Service::preset("apt-trade").unwrap()
+        };
+        let mut ledger = QuotaLedger::default();
+        let day = NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+        let now = Instant::now();
+        assert_eq!(ledger.reserve(&service, day, now), Ok((Duration::ZERO, 1)));
+        assert_eq!(
+            ledger.reserve(&service, day, now),
+            Ok((service.min_interval, 2))
+        );
+        assert_eq!(ledger.reserve(&service, day, now), Err(2));
+        assert_eq!(ledger.used(&service.id, day), 2);
+
+        let later = now + Duration::from_secs(1);
+        let next_day = day.succ_opt().unwrap();
+        assert_eq!(
+            ledger.reserve(&service, next_day, later),
+            Ok((Duration::ZERO, 1))
+        );
+    }
+}
@@ -1,11 +1,65 @@
-//! Python venv executor.
+//! Python script executor.
+//!
+//! Runs `script` with the system interpreter (`python3`, or `python` on
+//! Windows) under the same stdin/stdout contract as a process tool (see
+//! [`crate::local::execute_process`]), with the workspace's environment
+//! variables set. A relative script path is taken from the workspace root,
+//! which is also the working directory.
 
-use crate::{ExecutorError, ToolInput, ToolOutput};
+use crate::{local, ExecutorError, ToolInput, ToolOutput};
+use hb_core::path;
 
-/// Execute a Python script in a virtual environment.
-pub async fn execute(_script: &str, _input: &ToolInput) -> Result<ToolOutput, ExecutorError> {
-    // Phase 2 will create/reuse a Python venv and execute the script.
-    Err(ExecutorError::UnsupportedRuntime(
-        "Python executor not yet implemented".into(),
-    ))
+/// Execute a Python script.
+pub async fn execute(script: &str, input: &ToolInput) -> Result<ToolOutput, ExecutorError> {
+    let program = if cfg!(windows) { "python" } else { "python3" };
+    let script = input.resolve_path(script);
+    if !script.is_file() {
+        return Err(ExecutorError::ExecutionFailed(format!(
+            "Python script '{}' was not found",
+            path::display(&script)
+        )));
+    }
+    let mut command = tokio::process::Command::new(program);
+    command.arg(&script).envs(&input.env);
+    if let Some(root) = input.root_dir().filter(|root| root.is_dir()) {
+        command.current_dir(root);
+    }
+    local::run_child(command, program, input).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn scripts_see_workspace_variables() {
+        if std::process::Command::new("python3").arg("--version").output().is_err() {
+            return;
+        }
+        let script = std::env::temp_dir().join(format!("hb-python-{}.py", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &script,
+            "import json, os, sys\n\
+             data = json.load(sys.stdin)\n\
+             print(json.dumps({'region': os.environ['REGION'], 'progress': os.environ['HB_PROGRESS'], 'x': data['inputs']['x']}))\n",
+        )
+        .unwrap();
+        let input = ToolInput {
+            tool_ref: "script".into(),
+            inputs: serde_json::json!({ "x": 2 }),
+            config: serde_json::json!({}),
+            llm_provider: None,
+            env: [("REGION".to_string(), "ap-northeast-2".to_string())].into(),
+            progress: Default::default(),
+            stream: Default::default(),
+            commands: Default::default(),
+            secrets: Default::default(),
+            scratch_dir: None,
+        };
+
+        let output = execute(&script.to_string_lossy(), &input).await.unwrap().outputs;
+        std::fs::remove_file(&script).unwrap();
+        assert_eq!(output, serde_json::json!({ "region": "ap-northeast-2", "progress": "1", "x": 2 }));
+    }
 }
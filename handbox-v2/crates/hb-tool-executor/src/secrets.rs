@@ -0,0 +1,48 @@
+//! Secrets for process runtimes.
+//!
+//! A process runtime's `{ "secret": "NAME" }` variables are looked up here
+//! when the process starts — in the app, among the credential store's
+//! service keys — so the value never appears in the pack or the workflow.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Named secret values, carried by [`ToolInput`](crate::ToolInput). The
+/// default set is empty, so every secret reference fails.
+#[derive(Clone, Default)]
+pub struct Secrets(Arc<BTreeMap<String, String>>);
+
+impl fmt::Debug for Secrets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.0.keys()).finish()
+    }
+}
+
+impl Secrets {
+    pub fn new(values: BTreeMap<String, String>) -> Self {
+        Self(Arc::new(values))
+    }
+
+    /// The value of secret `name`, if it is set and not empty.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(String::as_str).filter(|value| !value.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_output_shows_names_only() {
+        let secrets = Secrets::new(BTreeMap::from([
+            ("VWORLD_KEY".to_string(), "s3cret".to_string()),
+            ("EMPTY".to_string(), String::new()),
+        ]));
+        assert_eq!(secrets.get("VWORLD_KEY"), Some("s3cret"));
+        assert_eq!(secrets.get("EMPTY"), None);
+        let shown = format!("{secrets:?}");
+        assert!(shown.contains("VWORLD_KEY") && !shown.contains("s3cret"), "{shown}");
+    }
+}
@@ -0,0 +1,890 @@
+//! Streaming text splitter for `text-split`, `file-split` and RAG ingestion.
+//!
+//! Text is read line by line from a reader, so memory use depends on the
+//! chunk size rather than on the size of the document. Strategies:
+//!
+//! - `structure` follows Markdown structure: a heading starts a new
+//!   chunk, chunks break at blank lines before they break inside a paragraph,
+//!   and a code block or table split across chunks gets its fence or header
+//!   row repeated so every chunk reads on its own.
+//! - `separator` packs the pieces between a separator string.
+//! - `characters` cuts every `chunk_size` characters.
+//!
+//! `overlap` repeats the end of a chunk at the start of the next one when a
+//! paragraph is cut mid-way (with `characters`, at every cut).
+
+use crate::ExecutorError;
+use std::collections::VecDeque;
+use std::io::{self, BufRead};
+use std::sync::Arc;
+
+pub const DEFAULT_CHUNK_SIZE: usize = 1000;
+pub const DEFAULT_OVERLAP: usize = 100;
+
+/// Lines longer than this many bytes (or four bytes per chunk character,
+/// whichever is more) are read in pieces.
+const MIN_LINE_BYTES: usize = 8192;
+
+/// How text is divided into chunks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Strategy {
+    Structure,
+    Separator(String),
+    Characters,
+}
+
+/// Chunk size and overlap are counted in characters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitConfig {
+    pub chunk_size: usize,
+    pub overlap: usize,
+    pub strategy: Strategy,
+}
+
+impl SplitConfig {
+    pub fn new(chunk_size: usize, overlap: usize, strategy: Strategy) -> Self {
+        let chunk_size = chunk_size.max(1);
+        Self {
+            chunk_size,
+            overlap: overlap.min(chunk_size - 1),
+            strategy,
+        }
+    }
+
+    /// Read `chunk_size`, `overlap`, `strategy` and `separator` from a node's
+    /// config, using `default` when no strategy is set.
+    pub fn from_config(config: &serde_json::Value, default: Strategy) -> Result<Self, ExecutorError> {
+        let number = |key: &str, default: usize| {
+            config
+                .get(key)
+                .and_then(|v| v.as_u64())
+                .map_or(default, |n| n as usize)
+        };
+        let separator = config
+            .get("separator")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(unescape);
+        let strategy = match config.get("strategy").and_then(|v| v.as_str()) {
+            None | Some("") => default,
+            Some("structure") => Strategy::Structure,
+            Some("characters") => Strategy::Characters,
+            Some("separator") => Strategy::Separator(separator.ok_or_else(|| {
+                ExecutorError::ExecutionFailed("The separator strategy needs a 'separator'".into())
+            })?),
+            Some(other) => {
+                return Err(ExecutorError::ExecutionFailed(format!(
+                    "Unknown split strategy '{other}' (expected structure, separator or characters)"
+                )))
+            }
+        };
+        Ok(Self::new(
+            number("chunk_size", DEFAULT_CHUNK_SIZE),
+            number("overlap", DEFAULT_OVERLAP),
+            strategy,
+        ))
+    }
+}
+
+/// Separators typed in the editor arrive as `\n` rather than a newline.
+fn unescape(text: &str) -> String {
+    text.replace("\\r", "\r")
+        .replace("\\n", "\n")
+        .replace("\\t", "\t")
+}
+
+/// A piece of the split text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    pub text: String,
+    /// Headings the chunk falls under, outermost first, joined with " > ".
+    pub heading: Option<String>,
+}
+
+/// Split text already in memory.
+pub fn split_text(text: &str, config: &SplitConfig) -> Vec<Chunk> {
+    // Reading from a byte slice can't fail
+    Splitter::new(text.as_bytes(), config.clone())
+        .filter_map(Result::ok)
+        .collect()
+}
+
+/// Splits UTF-8 text from a reader, yielding chunks as they fill up.
+pub struct Splitter<R> {
+    lines: Lines<R>,
+    mode: Mode,
+    out: VecDeque<Chunk>,
+    finished: bool,
+}
+
+enum Mode {
+    Structure(Packer, Markdown),
+    Separator {
+        packer: Packer,
+        separator: String,
+        pending: String,
+        glued: bool,
+    },
+    Characters(Window),
+}
+
+impl<R: BufRead> Splitter<R> {
+    pub fn new(reader: R, config: SplitConfig) -> Self {
+        let SplitConfig {
+            chunk_size,
+            overlap,
+            strategy,
+        } = config;
+        let mode = match strategy {
+            Strategy::Structure => {
+                Mode::Structure(Packer::new(chunk_size, overlap, "\n"), Markdown::default())
+            }
+            Strategy::Separator(separator) => Mode::Separator {
+                packer: Packer::new(chunk_size, overlap, &separator),
+                separator,
+                pending: String::new(),
+                glued: false,
+            },
+            Strategy::Characters => Mode::Characters(Window {
+                size: chunk_size,
+                overlap,
+                text: String::new(),
+                chars: 0,
+                emitted: 0,
+            }),
+        };
+        Self {
+            lines: Lines {
+                reader,
+                max_bytes: chunk_size.saturating_mul(4).max(MIN_LINE_BYTES),
+                pending: Vec::new(),
+                bytes_read: 0,
+            },
+            mode,
+            out: VecDeque::new(),
+            finished: false,
+        }
+    }
+
+    /// Bytes consumed from the reader so far.
+    pub fn bytes_read(&self) -> u64 {
+        self.lines.bytes_read
+    }
+
+    fn feed(&mut self, mut piece: String, end: End) {
+        let out = &mut self.out;
+        match &mut self.mode {
+            Mode::Structure(packer, markdown) => {
+                if end == End::Newline && piece.ends_with('\r') {
+                    piece.pop();
+                }
+                packer.push(markdown.unit(piece, end), out);
+            }
+            Mode::Separator {
+                packer,
+                separator,
+                pending,
+                glued,
+            } => {
+                if end == End::Newline && piece.ends_with('\r') {
+                    piece.pop();
+                }
+                pending.push_str(&piece);
+                if end == End::Newline {
+                    pending.push('\n');
+                }
+                while let Some(at) = pending.find(separator.as_str()) {
+                    let rest = pending.split_off(at + separator.len());
+                    pending.truncate(at);
+                    let text = std::mem::replace(pending, rest);
+                    if !text.is_empty() {
+                        packer.push(Unit::text(text, std::mem::take(glued)), out);
+                    }
+                }
+                if pending.len() > MIN_LINE_BYTES.max(packer.size * 4) {
+                    packer.push(Unit::text(std::mem::take(pending), *glued), out);
+                    *glued = true;
+                }
+            }
+            Mode::Characters(window) => {
+                window.text.push_str(&piece);
+                window.chars += piece.chars().count();
+                if end == End::Newline {
+                    window.text.push('\n');
+                    window.chars += 1;
+                }
+                window.drain(out);
+            }
+        }
+    }
+
+    fn finish(&mut self) {
+        let out = &mut self.out;
+        match &mut self.mode {
+            Mode::Structure(packer, _) => packer.flush(out),
+            Mode::Separator {
+                packer,
+                pending,
+                glued,
+                ..
+            } => {
+                // A trailing newline isn't a piece of its own
+                let text = std::mem::take(pending);
+                let text = text.strip_suffix('\n').unwrap_or(&text);
+                if !text.is_empty() {
+                    packer.push(Unit::text(text.to_string(), *glued), out);
+                }
+                packer.flush(out);
+            }
+            Mode::Characters(window) => window.finish(out),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Splitter<R> {
+    type Item = io::Result<Chunk>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(chunk) = self.out.pop_front() {
+                return Some(Ok(chunk));
+            }
+            if self.finished {
+                return None;
+            }
+            match self.lines.next_piece() {
+                Ok(Some((piece, end))) => self.feed(piece, end),
+                Ok(None) => {
+                    self.finish();
+                    self.finished = true;
+                }
+                Err(e) => {
+                    self.finished = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+// ---- Reading ----
+
+/// How a piece returned by [`Lines`] ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum End {
+    Newline,
+    Eof,
+    /// The line was longer than `max_bytes`; the rest follows.
+    Cut,
+}
+
+/// Lines of a reader, with long lines returned in pieces.
+struct Lines<R> {
+    reader: R,
+    max_bytes: usize,
+    /// Start of a UTF-8 sequence split by a cut.
+    pending: Vec<u8>,
+    bytes_read: u64,
+}
+
+impl<R: BufRead> Lines<R> {
+    fn next_piece(&mut self) -> io::Result<Option<(String, End)>> {
+        let mut buf = std::mem::take(&mut self.pending);
+        let end = loop {
+            if buf.len() >= self.max_bytes {
+                break End::Cut;
+            }
+            let (used, newline) = {
+                let available = self.reader.fill_buf()?;
+                if available.is_empty() {
+                    if buf.is_empty() {
+                        return Ok(None);
+                    }
+                    break End::Eof;
+                }
+                let window = &available[..available.len().min(self.max_bytes - buf.len())];
+                match window.iter().position(|&b| b == b'\n') {
+                    Some(at) => {
+                        buf.extend_from_slice(&window[..at]);
+                        (at + 1, true)
+                    }
+                    None => {
+                        buf.extend_from_slice(window);
+                        (window.len(), false)
+                    }
+                }
+            };
+            self.reader.consume(used);
+            self.bytes_read += used as u64;
+            if newline {
+                break End::Newline;
+            }
+        };
+        if end == End::Cut {
+            if let Err(e) = std::str::from_utf8(&buf) {
+                if e.error_len().is_none() {
+                    self.pending = buf.split_off(e.valid_up_to());
+                }
+            }
+        }
+        Ok(Some((String::from_utf8_lossy(&buf).into_owned(), end)))
+    }
+}
+
+// ---- Characters ----
+
+/// Text not yet emitted, plus the overlap kept from the last chunk.
+struct Window {
+    size: usize,
+    overlap: usize,
+    text: String,
+    chars: usize,
+    /// Leading characters already part of an emitted chunk.
+    emitted: usize,
+}
+
+impl Window {
+    fn drain(&mut self, out: &mut VecDeque<Chunk>) {
+        while self.chars > self.size {
+            let end = byte_offset(&self.text, self.size);
+            out.push_back(Chunk {
+                text: self.text[..end].to_string(),
+                heading: None,
+            });
+            let keep_from = byte_offset(&self.text, self.size - self.overlap);
+            self.text.drain(..keep_from);
+            self.chars -= self.size - self.overlap;
+            self.emitted = self.overlap;
+        }
+    }
+
+    fn finish(&mut self, out: &mut VecDeque<Chunk>) {
+        if self.chars > self.emitted {
+            out.push_back(Chunk {
+                text: std::mem::take(&mut self.text),
+                heading: None,
+            });
+        }
+    }
+}
+
+/// Byte offset of the character at `index`, or the end of `text`.
+fn byte_offset(text: &str, index: usize) -> usize {
+    text.char_indices()
+        .nth(index)
+        .map_or(text.len(), |(i, _)| i)
+}
+
+// ---- Structure ----
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Blank,
+    Heading,
+    Text,
+    Code,
+    Table,
+    /// Overlap repeated from the previous chunk.
+    Carried,
+}
+
+/// A line (or piece of one) waiting to be packed into a chunk.
+#[derive(Debug, Clone)]
+struct Unit {
+    text: String,
+    chars: usize,
+    kind: Kind,
+    /// Continues the previous unit without a separator (a cut line).
+    glued: bool,
+    heading: Option<Arc<str>>,
+    /// Repeated before this unit when it starts a chunk: the opening fence
+    /// or header rows of the block it belongs to.
+    reopen: Option<Arc<str>>,
+    /// Appended after this unit when it ends a chunk: the closing fence.
+    close: Option<Arc<str>>,
+}
+
+impl Unit {
+    fn new(text: String, kind: Kind, glued: bool) -> Self {
+        Self {
+            chars: text.chars().count(),
+            text,
+            kind,
+            glued,
+            heading: None,
+            reopen: None,
+            close: None,
+        }
+    }
+
+    fn text(text: String, glued: bool) -> Self {
+        Self::new(text, Kind::Text, glued)
+    }
+
+    /// Take the block context of `other`.
+    fn with(mut self, other: &Unit) -> Self {
+        self.reopen = other.reopen.clone();
+        self.close = other.close.clone();
+        self
+    }
+
+    fn is_content(&self) -> bool {
+        !matches!(self.kind, Kind::Blank | Kind::Carried)
+    }
+
+    fn prose(&self) -> bool {
+        matches!(self.kind, Kind::Text | Kind::Carried)
+    }
+
+    fn reopen_chars(&self) -> usize {
+        self.reopen.as_ref().map_or(0, |r| r.chars().count() + 1)
+    }
+
+    fn close_chars(&self) -> usize {
+        self.close.as_ref().map_or(0, |c| c.chars().count() + 1)
+    }
+}
+
+/// Tracks headings, code blocks and tables while classifying lines.
+#[derive(Default)]
+struct Markdown {
+    headings: Vec<(usize, String)>,
+    heading: Option<Arc<str>>,
+    /// Opening line and marker of the code block being read.
+    code: Option<(Arc<str>, Arc<str>)>,
+    /// Header rows of the table being read, and whether they're complete.
+    table: Option<(Arc<str>, bool)>,
+    /// Classification of the last unit, reused when a line was cut.
+    last: Option<Unit>,
+    cut: bool,
+}
+
+impl Markdown {
+    fn unit(&mut self, line: String, end: End) -> Unit {
+        let glued = std::mem::replace(&mut self.cut, end == End::Cut);
+        let mut unit = match self.last.take() {
+            Some(last) if glued => Unit::new(line, last.kind, true).with(&last),
+            _ => self.classify(line),
+        };
+        unit.heading = self.heading.clone();
+        self.last = Some(Unit {
+            text: String::new(),
+            ..unit.clone()
+        });
+        unit
+    }
+
+    fn classify(&mut self, line: String) -> Unit {
+        let trimmed = line.trim();
+        if let Some((open, marker)) = &self.code {
+            let (open, marker) = (open.clone(), marker.clone());
+            let closing = trimmed.starts_with(&*marker)
+                && trimmed
+                    .trim_start_matches(marker.chars().next().unwrap_or('`'))
+                    .is_empty();
+            let mut unit = Unit::new(line, Kind::Code, false);
+            unit.reopen = Some(open);
+            if closing {
+                self.code = None;
+            } else {
+                unit.close = Some(marker);
+            }
+            return unit;
+        }
+        if let Some(marker) = fence(trimmed) {
+            let marker: Arc<str> = marker.into();
+            self.code = Some((line.trim_end().into(), marker.clone()));
+            self.table = None;
+            let mut unit = Unit::new(line, Kind::Code, false);
+            unit.close = Some(marker);
+            return unit;
+        }
+        if trimmed.is_empty() {
+            self.table = None;
+            return Unit::new(line, Kind::Blank, false);
+        }
+        if let Some((level, title)) = heading(trimmed) {
+            self.table = None;
+            while self.headings.last().is_some_and(|(l, _)| *l >= level) {
+                self.headings.pop();
+            }
+            self.headings.push((level, title.to_string()));
+            let path: Vec<_> = self.headings.iter().map(|(_, t)| t.as_str()).collect();
+            self.heading = Some(path.join(" > ").into());
+            return Unit::new(line, Kind::Heading, false);
+        }
+        if trimmed.starts_with('|') {
+            let delimiter = is_delimiter_row(trimmed);
+            let mut unit = Unit::new(line, Kind::Table, false);
+            match &mut self.table {
+                None => self.table = Some((unit.text.trim_end().into(), false)),
+                Some((header, complete)) => {
+                    unit.reopen = Some(header.clone());
+                    if !*complete {
+                        *complete = true;
+                        if delimiter {
+                            *header = format!("{header}\n{}", unit.text.trim_end()).into();
+                        }
+                    }
+                }
+            }
+            return unit;
+        }
+        self.table = None;
+        Unit::text(line, false)
+    }
+}
+
+/// The fence marker if `line` opens a code block.
+fn fence(line: &str) -> Option<&str> {
+    let marker = line.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let len = line.len() - line.trim_start_matches(marker).len();
+    (len >= 3).then(|| &line[..len])
+}
+
+/// Level and title of an ATX heading (`## Title`).
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.len() - line.trim_start_matches('#').len();
+    let rest = &line[level..];
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with(' ')) {
+        return None;
+    }
+    Some((level, rest.trim().trim_end_matches('#').trim_end()))
+}
+
+/// `|---|:--:|` under a table header.
+fn is_delimiter_row(line: &str) -> bool {
+    line.contains('-') && line.chars().all(|c| matches!(c, '|' | '-' | ':' | ' '))
+}
+
+/// Packs units into chunks of at most `size` characters.
+struct Packer {
+    size: usize,
+    overlap: usize,
+    join: String,
+    join_chars: usize,
+    units: VecDeque<Unit>,
+    /// Characters of `units` joined, without reopen or close lines.
+    chars: usize,
+}
+
+impl Packer {
+    fn new(size: usize, overlap: usize, join: &str) -> Self {
+        Self {
+            size,
+            overlap,
+            join: join.to_string(),
+            join_chars: join.chars().count(),
+            units: VecDeque::new(),
+            chars: 0,
+        }
+    }
+
+    fn join_before(&self, unit: &Unit) -> usize {
+        if unit.glued {
+            0
+        } else {
+            self.join_chars
+        }
+    }
+
+    fn fits(&self, next: &Unit) -> bool {
+        let Some(front) = self.units.front() else {
+            return next.reopen_chars() + next.chars + next.close_chars() <= self.size;
+        };
+        front.reopen_chars() + self.chars + self.join_before(next) + next.chars + next.close_chars()
+            <= self.size
+    }
+
+    fn append(&mut self, unit: Unit) {
+        if !self.units.is_empty() {
+            self.chars += self.join_before(&unit);
+        }
+        self.chars += unit.chars;
+        self.units.push_back(unit);
+    }
+
+    fn recount(&mut self) {
+        self.chars = 0;
+        for (i, unit) in self.units.iter().enumerate() {
+            if i > 0 {
+                self.chars += self.join_before(unit);
+            }
+            self.chars += unit.chars;
+        }
+    }
+
+    fn push(&mut self, unit: Unit, out: &mut VecDeque<Chunk>) {
+        if unit.kind == Kind::Heading {
+            self.flush(out);
+        }
+        loop {
+            if self.fits(&unit) {
+                self.append(unit);
+                return;
+            }
+            if !self.units.iter().any(Unit::is_content) {
+                self.units.clear();
+                self.chars = 0;
+                if self.fits(&unit) {
+                    self.append(unit);
+                } else {
+                    self.cut(unit, out);
+                }
+                return;
+            }
+            self.emit_best(&unit, out);
+        }
+    }
+
+    /// Emit a prefix of the buffer, ending at the best break point in its
+    /// second half.
+    fn emit_best(&mut self, next: &Unit, out: &mut VecDeque<Chunk>) {
+        let reopen = self.units.front().map_or(0, Unit::reopen_chars);
+        let mut best = (0, self.units.len());
+        let mut chars = 0;
+        for k in 1..=self.units.len() {
+            let before = &self.units[k - 1];
+            if k > 1 {
+                chars += self.join_before(before);
+            }
+            chars += before.chars;
+            let len = reopen + chars + before.close_chars();
+            if len < self.size / 2 || len > self.size {
+                continue;
+            }
+            let after = self.units.get(k).unwrap_or(next);
+            let score = match (before.kind, after.kind) {
+                _ if after.glued => 0,
+                (Kind::Blank, _) | (_, Kind::Blank | Kind::Heading) => 3,
+                _ if before.prose() != after.prose() || before.kind != after.kind => 2,
+                _ => 1,
+            };
+            if score >= best.0 {
+                best = (score, k);
+            }
+        }
+        self.emit(best.1, next, out);
+    }
+
+    fn emit(&mut self, k: usize, next: &Unit, out: &mut VecDeque<Chunk>) {
+        let taken: Vec<Unit> = self.units.drain(..k).collect();
+        let Some(chunk) = self.render(&taken) else {
+            self.recount();
+            return;
+        };
+        // Carry the end of a paragraph cut mid-way into the next chunk
+        let continues = self.units.front().unwrap_or(next);
+        let cut_paragraph = taken.last().is_some_and(Unit::prose) && continues.kind == Kind::Text;
+        if self.overlap > 0 && cut_paragraph {
+            let paragraph = chunk.text.rsplit("\n\n").next().unwrap_or(&chunk.text);
+            let tail = tail(paragraph, self.overlap);
+            if !tail.is_empty() {
+                self.units
+                    .push_front(Unit::new(tail.to_string(), Kind::Carried, false));
+            }
+        }
+        self.recount();
+        out.push_back(chunk);
+    }
+
+    /// Split a unit too long for any chunk.
+    fn cut(&mut self, mut unit: Unit, out: &mut VecDeque<Chunk>) {
+        if unit.reopen_chars() + unit.close_chars() > self.size / 2 {
+            unit.reopen = None;
+            unit.close = None;
+        }
+        let budget = self.size - unit.reopen_chars() - unit.close_chars();
+        let overlap = if unit.prose() {
+            self.overlap.min(budget / 2)
+        } else {
+            0
+        };
+        let offsets: Vec<usize> = unit.text.char_indices().map(|(i, _)| i).collect();
+        let at = |index: usize| offsets.get(index).copied().unwrap_or(unit.text.len());
+        let is_space = |index: usize| unit.text[at(index)..].starts_with(char::is_whitespace);
+        let mut start = 0;
+        while unit.chars - start > budget {
+            let mut end = start + budget;
+            if let Some(space) = (start + budget / 2..end).rev().find(|&i| is_space(i)) {
+                end = space + 1;
+            }
+            let piece = Unit {
+                text: unit.text[at(start)..at(end)].to_string(),
+                chars: end - start,
+                ..unit.clone()
+            };
+            out.extend(self.render(&[piece]));
+            let mut next = end.saturating_sub(overlap).max(start + 1);
+            if overlap > 0 {
+                if let Some(space) = (next..end).find(|&i| is_space(i)) {
+                    next = space + 1;
+                }
+            }
+            start = next.min(end);
+        }
+        self.append(Unit {
+            text: unit.text[at(start)..].to_string(),
+            chars: unit.chars - start,
+            ..unit
+        });
+    }
+
+    fn flush(&mut self, out: &mut VecDeque<Chunk>) {
+        let units: Vec<Unit> = self.units.drain(..).collect();
+        self.chars = 0;
+        out.extend(self.render(&units));
+    }
+
+    fn render(&self, units: &[Unit]) -> Option<Chunk> {
+        let first = units.iter().position(|u| u.kind != Kind::Blank)?;
+        let last = units.iter().rposition(|u| u.kind != Kind::Blank)?;
+        let units = &units[first..=last];
+        let content = units.iter().find(|u| u.is_content())?;
+        let mut text = String::new();
+        if let Some(reopen) = &units[0].reopen {
+            text.push_str(reopen);
+            text.push('\n');
+        }
+        for (i, unit) in units.iter().enumerate() {
+            if i > 0 && !unit.glued {
+                text.push_str(&self.join);
+            }
+            text.push_str(&unit.text);
+        }
+        if let Some(close) = &units[units.len() - 1].close {
+            text.push('\n');
+            text.push_str(close);
+        }
+        Some(Chunk {
+            text,
+            heading: content.heading.as_deref().map(str::to_string),
+        })
+    }
+}
+
+/// The last `chars` characters of `text`, starting at a word if possible.
+fn tail(text: &str, chars: usize) -> &str {
+    let total = text.chars().count();
+    let tail = &text[byte_offset(text, total.saturating_sub(chars))..];
+    match tail.find(char::is_whitespace) {
+        Some(space) if space + 1 < tail.len() && total > chars => tail[space..].trim_start(),
+        _ => tail,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(text: &str, size: usize, overlap: usize, strategy: Strategy) -> Vec<Chunk> {
+        split_text(text, &SplitConfig::new(size, overlap, strategy))
+    }
+
+    fn texts(chunks: &[Chunk]) -> Vec<&str> {
+        chunks.iter().map(|c| c.text.as_str()).collect()
+    }
+
+    #[test]
+    fn characters_match_the_fixed_window_split() {
+        let text = "가나다라마바사아자차카타파하 0123456789";
+        let chars: Vec<char> = text.chars().collect();
+        let chunks = split(text, 10, 3, Strategy::Characters);
+        let mut expected = Vec::new();
+        let mut start = 0;
+        loop {
+            let end = (start + 10).min(chars.len());
+            expected.push(chars[start..end].iter().collect::<String>());
+            if end == chars.len() {
+                break;
+            }
+            start = end - 3;
+        }
+        assert_eq!(texts(&chunks), expected);
+        assert!(split("", 10, 3, Strategy::Characters).is_empty());
+        assert_eq!(
+            split("a\nb\n", 10, 0, Strategy::Characters)[0].text,
+            "a\nb\n"
+        );
+    }
+
+    #[test]
+    fn structure_keeps_sections_code_and_tables_together() {
+        let text = "# 개요\n\
+                    공사 개요입니다.\n\
+                    \n\
+                    ## 자재\n\
+                    | 품명 | 수량 |\n\
+                    |---|---|\n\
+                    | 철근 | 120 |\n\
+                    | 레미콘 | 40 |\n\
+                    | 거푸집 | 300 |\n\
+                    \n\
+                    ```python\n\
+                    total = 0\n\
+                    for row in rows:\n\
+                    \x20   total += row.qty\n\
+                    ```\n";
+        let chunks = split(text, 60, 0, Strategy::Structure);
+        assert_eq!(chunks[0].text, "# 개요\n공사 개요입니다.");
+        assert_eq!(chunks[0].heading.as_deref(), Some("개요"));
+        for chunk in &chunks[1..] {
+            assert_eq!(chunk.heading.as_deref(), Some("개요 > 자재"));
+            assert!(chunk.text.chars().count() <= 60, "{:?}", chunk.text);
+        }
+        // The table and code block are split with their header and fence repeated
+        let table_parts: Vec<_> = chunks
+            .iter()
+            .filter(|c| c.text.contains("| 품명"))
+            .collect();
+        assert!(table_parts.len() > 1);
+        assert!(table_parts.iter().all(|c| c.text.contains("|---|---|")));
+        let code_parts: Vec<_> = chunks.iter().filter(|c| c.text.contains("total")).collect();
+        assert!(code_parts.len() > 1);
+        assert!(code_parts
+            .iter()
+            .all(|c| c.text.starts_with("```python") && c.text.ends_with("```")));
+    }
+
+    #[test]
+    fn long_paragraphs_are_cut_at_words_with_overlap() {
+        let paragraph = "word ".repeat(60);
+        let chunks = split(&paragraph, 50, 10, Strategy::Structure);
+        assert!(chunks.len() > 5);
+        for pair in chunks.windows(2) {
+            assert!(pair[0].text.chars().count() <= 50);
+            assert!(!pair[0].text.ends_with("wor"));
+            // The next chunk starts with the end of this one
+            let start: String = pair[1].text.chars().take(4).collect();
+            assert!(
+                pair[0].text.trim_end().ends_with(start.trim_end()),
+                "{pair:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn separator_packs_pieces() {
+        let chunks = split("a;b;c;dd;e", 5, 0, Strategy::Separator(";".into()));
+        assert_eq!(texts(&chunks), ["a;b;c", "dd;e"]);
+        let config = |value| SplitConfig::from_config(&value, Strategy::Characters);
+        let separator = config(serde_json::json!({ "strategy": "separator", "separator": "\\n\\n" }));
+        assert_eq!(separator.unwrap().strategy, Strategy::Separator("\n\n".into()));
+        // A separator alone leaves the default strategy in place
+        let unset = config(serde_json::json!({ "separator": ";" })).unwrap();
+        assert_eq!(unset.strategy, Strategy::Characters);
+        assert!(config(serde_json::json!({ "strategy": "words" })).is_err());
+    }
+
+    #[test]
+    fn reads_long_lines_in_pieces() {
+        // A single line much longer than the read limit, split across a multi-byte character
+        let line = format!("x{}", "가".repeat(MIN_LINE_BYTES));
+        let chunks = split(&line, 100, 0, Strategy::Structure);
+        assert_eq!(
+            chunks.iter().map(|c| c.text.as_str()).collect::<String>(),
+            line
+        );
+        assert!(chunks.iter().all(|c| c.text.chars().count() <= 100));
+    }
+}
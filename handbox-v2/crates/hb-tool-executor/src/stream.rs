@@ -0,0 +1,166 @@
+//! Streaming output — partial results a tool produces before it finishes.
+//!
+//! Native tools send chunks through the [`OutputStream`] carried by their
+//! [`ToolInput`](crate::ToolInput): an LLM tool its tokens as they arrive, a
+//! process tool its stdout line by line. Chunks are for display only; the
+//! node's result is still the final [`ToolOutput`](crate::ToolOutput), so a
+//! tool may stream freely or not at all.
+
+use crate::progress::Heartbeat;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// A piece of a node's output on one port.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputChunk {
+    /// Output port the text belongs to, e.g. "response" or "stdout".
+    pub port: String,
+    pub text: String,
+    /// Position among the node's chunks, from 0.
+    pub index: u64,
+}
+
+/// Receives output chunks, e.g. to forward them to the UI.
+pub type ChunkSink = Arc<dyn Fn(OutputChunk) + Send + Sync>;
+
+/// Handle tools stream partial output through. The default stream drops
+/// every chunk; check [`is_enabled`](Self::is_enabled) before doing extra
+/// work to produce them.
+#[derive(Clone, Default)]
+pub struct OutputStream {
+    sink: Option<ChunkSink>,
+    next_index: Arc<AtomicU64>,
+    heartbeat: Option<Heartbeat>,
+}
+
+impl fmt::Debug for OutputStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OutputStream")
+            .field("enabled", &self.sink.is_some())
+            .field("sent", &self.next_index.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+impl OutputStream {
+    pub fn new<F>(sink: F) -> Self
+    where
+        F: Fn(OutputChunk) + Send + Sync + 'static,
+    {
+        Self {
+            sink: Some(Arc::new(sink)),
+            next_index: Default::default(),
+            heartbeat: None,
+        }
+    }
+
+    /// Also beat `heartbeat` on every chunk.
+    pub fn with_heartbeat(mut self, heartbeat: Heartbeat) -> Self {
+        self.heartbeat = Some(heartbeat);
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.sink.is_some()
+    }
+
+    /// Chunks of `port` made of the lines written to the returned writer.
+    pub fn lines(&self, port: &str) -> LineChunks {
+        LineChunks {
+            stream: self.clone(),
+            port: port.to_string(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Send `text` as the next chunk of `port`. Empty text is dropped.
+    pub fn send(&self, port: &str, text: impl Into<String>) {
+        if let Some(heartbeat) = &self.heartbeat {
+            heartbeat.beat();
+        }
+        let text = text.into();
+        let Some(sink) = &self.sink else {
+            return;
+        };
+        if text.is_empty() {
+            return;
+        }
+        sink(OutputChunk {
+            port: port.to_string(),
+            text,
+            index: self.next_index.fetch_add(1, Ordering::Relaxed),
+        });
+    }
+}
+
+/// Sends byte output, such as a process's stdout, a line per chunk. Bytes
+/// are buffered until their line ends, so multi-byte characters are never
+/// split between chunks.
+#[derive(Debug)]
+pub struct LineChunks {
+    stream: OutputStream,
+    port: String,
+    pending: Vec<u8>,
+}
+
+impl LineChunks {
+    pub fn write(&mut self, bytes: &[u8]) {
+        if !self.stream.is_enabled() {
+            return;
+        }
+        self.pending.extend_from_slice(bytes);
+        if let Some(end) = self.pending.iter().rposition(|b| *b == b'\n') {
+            let rest = self.pending.split_off(end + 1);
+            let lines = std::mem::replace(&mut self.pending, rest);
+            for line in lines.split_inclusive(|b| *b == b'\n') {
+                self.stream.send(&self.port, String::from_utf8_lossy(line));
+            }
+        }
+    }
+
+    /// Send what is left of an unfinished last line.
+    pub fn finish(mut self) {
+        let rest = std::mem::take(&mut self.pending);
+        self.stream.send(&self.port, String::from_utf8_lossy(&rest));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn chunks_are_numbered_across_clones() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let stream = OutputStream::new(move |chunk| sink.lock().unwrap().push(chunk));
+        let clone = stream.clone();
+
+        stream.send("response", "Foot");
+        stream.send("response", "");
+        clone.send("response", "ing");
+        OutputStream::default().send("response", "dropped");
+
+        let seen = seen.lock().unwrap();
+        let chunks: Vec<_> = seen.iter().map(|c| (c.index, c.text.as_str())).collect();
+        assert_eq!(chunks, [(0, "Foot"), (1, "ing")]);
+    }
+
+    #[test]
+    fn byte_output_is_sent_a_line_at_a_time() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let stream = OutputStream::new(move |chunk: OutputChunk| sink.lock().unwrap().push(chunk.text));
+        let mut lines = stream.lines("stdout");
+        let text = "기초 타설\n철근 배근\n양생";
+        // Split mid-character
+        let (a, b) = text.as_bytes().split_at(4);
+        lines.write(a);
+        lines.write(b);
+        lines.finish();
+        assert_eq!(*seen.lock().unwrap(), ["기초 타설\n", "철근 배근\n", "양생"]);
+    }
+}
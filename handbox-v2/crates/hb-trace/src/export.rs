@@ -1,9 +1,106 @@
 //! Trace export — export traces to JSON or other formats.
 
+use crate::query::TraceQuery;
+use crate::store::TraceStore;
 use crate::TraceError;
 use hb_core::trace::NodeSpan;
+use std::io::Write;
 
 /// Export spans to a JSON string.
 pub fn export_json(spans: &[NodeSpan]) -> Result<String, TraceError> {
     serde_json::to_string_pretty(spans).map_err(|e| TraceError::Export(e.to_string()))
 }
+
+/// Write the spans matching `query` to `out` as JSON Lines, one span per
+/// line, in the order they were recorded. Spans are read from the store a
+/// page at a time, so memory use doesn't grow with the size of the export.
+/// Returns the number of spans written.
+pub fn export_spans_jsonl<W: Write>(
+    store: &dyn TraceStore,
+    query: &TraceQuery,
+    out: W,
+) -> Result<u64, TraceError> {
+    let mut out = std::io::BufWriter::new(out);
+    let export_err = |e: std::io::Error| TraceError::Export(e.to_string());
+    let count = store.scan_spans(query, &mut |span| {
+        serde_json::to_writer(&mut out, &span).map_err(|e| TraceError::Export(e.to_string()))?;
+        out.write_all(b"\n").map_err(export_err)
+    })?;
+    out.flush().map_err(export_err)?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::SqliteTraceStore;
+    use chrono::Utc;
+    use hb_core::trace::{ExecutionEnvironment, ExecutionStatus};
+    use uuid::Uuid;
+
+    fn span(execution_id: Uuid, node_id: &str, status: ExecutionStatus) -> NodeSpan {
+        NodeSpan {
+            span_id: Uuid::new_v4(),
+            execution_id,
+            node_id: node_id.into(),
+            tool_ref: "core-tools/llm-chat@1.0.0".into(),
+            input_json: serde_json::json!({ "prompt": "요약\n해줘" }),
+            output_json: Some(serde_json::json!({ "response": "ok" })),
+            config_json: serde_json::json!({}),
+            started_at: Utc::now(),
+            completed_at: Some(Utc::now()),
+            duration_ms: Some(5),
+            status,
+            error: None,
+            cache_hit: false,
+            environment: ExecutionEnvironment {
+                platform_version: "0.1.0".into(),
+                os: "test".into(),
+                tool_version: "1.0.0".into(),
+                extra: Default::default(),
+            },
+        }
+    }
+
+    #[test]
+    fn streams_every_span_as_one_line_across_pages() {
+        let store = SqliteTraceStore::in_memory().unwrap();
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        // More than one page of spans, interleaved between two executions
+        for i in 0..600 {
+            let execution = if i % 3 == 0 { second } else { first };
+            let status = if i % 100 == 0 {
+                ExecutionStatus::Failed
+            } else {
+                ExecutionStatus::Completed
+            };
+            store.insert_span(&span(execution, &format!("n{i}"), status)).unwrap();
+        }
+
+        let mut out = Vec::new();
+        let count = export_spans_jsonl(&store, &TraceQuery::default(), &mut out).unwrap();
+        assert_eq!(count, 600);
+        let lines: Vec<_> = String::from_utf8(out).unwrap().lines().map(str::to_string).collect();
+        assert_eq!(lines.len(), 600);
+        let last: NodeSpan = serde_json::from_str(&lines[599]).unwrap();
+        assert_eq!(last.node_id, "n599");
+
+        let mut out = Vec::new();
+        let query = TraceQuery {
+            execution_id: Some(second),
+            ..Default::default()
+        };
+        assert_eq!(export_spans_jsonl(&store, &query, &mut out).unwrap(), 200);
+
+        let query = TraceQuery {
+            status: Some("failed".into()),
+            limit: Some(4),
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        assert_eq!(export_spans_jsonl(&store, &query, &mut out).unwrap(), 4);
+        let failed: NodeSpan =
+            serde_json::from_str(String::from_utf8(out).unwrap().lines().nth(3).unwrap()).unwrap();
+        assert_eq!(failed.node_id, "n300");
+    }
+}
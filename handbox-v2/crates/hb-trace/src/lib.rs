@@ -1,4 +1,4 @@
-//! hb-trace: Evidence/Trace storage backed by SQLite.
+//! hb-trace: Evidence/Trace storage behind a pluggable backend (SQLite by default).
 
 pub mod export;
 pub mod query;
@@ -12,6 +12,22 @@ pub enum TraceError {
     Database(String),
     #[error("span not found: {0}")]
     SpanNotFound(String),
+    #[error("execution not found: {0}")]
+    ExecutionNotFound(String),
+    #[error("executions {0} and {1} belong to different workflows")]
+    DifferentWorkflows(uuid::Uuid, uuid::Uuid),
     #[error("export error: {0}")]
     Export(String),
 }
+
+impl From<TraceError> for hb_core::error::AppError {
+    fn from(e: TraceError) -> Self {
+        use hb_core::error::{AppError, ErrorCode};
+        let code = match e {
+            TraceError::Database(_) | TraceError::Export(_) => ErrorCode::Database,
+            TraceError::SpanNotFound(_) | TraceError::ExecutionNotFound(_) => ErrorCode::NotFound,
+            TraceError::DifferentWorkflows(..) => ErrorCode::InvalidInput,
+        };
+        AppError::new(code, e.to_string())
+    }
+}
@@ -2,6 +2,9 @@
 
 use crate::store::TraceStore;
 use crate::TraceError;
+use hb_core::trace::{ExecutionRecord, ExecutionStatus, NodeSpan};
+use serde::Serialize;
+use std::collections::HashMap;
 use uuid::Uuid;
 
 /// Query parameters for trace lookup.
@@ -15,7 +18,7 @@ pub struct TraceQuery {
 
 /// Execute a trace query against a TraceStore.
 pub fn query_spans(
-    store: &TraceStore,
+    store: &dyn TraceStore,
     query: &TraceQuery,
 ) -> Result<Vec<hb_core::trace::NodeSpan>, TraceError> {
     // If execution_id is provided, use the optimized store method
@@ -47,9 +50,274 @@ pub fn query_spans(
     Ok(vec![])
 }
 
+/// Most differences listed per compared value of a node; the count of the
+/// rest is kept.
+const MAX_VALUE_CHANGES: usize = 50;
+
+/// Smallest change in a node's duration reported on its own.
+pub const MIN_DURATION_DELTA_MS: i64 = 100;
+
+/// A value that differs between execution A and execution B.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Changed<T> {
+    pub a: T,
+    pub b: T,
+}
+
+/// One place where two JSON values differ. `None` means the path doesn't
+/// exist on that side.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValueChange {
+    /// JSON Pointer to the differing value ("" for the whole value).
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub a: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub b: Option<serde_json::Value>,
+}
+
+/// Differences in one of a node's recorded values.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ValueDiff {
+    /// The first [`MAX_VALUE_CHANGES`] differences, in document order.
+    pub changes: Vec<ValueChange>,
+    /// Differences not listed.
+    pub omitted: usize,
+}
+
+impl ValueDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty() && self.omitted == 0
+    }
+}
+
+/// How a node ran differently in two executions. A node that ran several
+/// times (e.g. in a loop) is compared run by run; `run` counts from 0.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NodeDiff {
+    pub node_id: String,
+    pub run: usize,
+    /// Whether the node ran in execution A and in execution B.
+    pub ran: Changed<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<Changed<ExecutionStatus>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_ref: Option<Changed<String>>,
+    #[serde(skip_serializing_if = "ValueDiff::is_empty")]
+    pub config: ValueDiff,
+    #[serde(skip_serializing_if = "ValueDiff::is_empty")]
+    pub inputs: ValueDiff,
+    #[serde(skip_serializing_if = "ValueDiff::is_empty")]
+    pub outputs: ValueDiff,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<Changed<Option<String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_hit: Option<Changed<bool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<Changed<Option<i64>>>,
+    /// Duration in B minus duration in A, when both are known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_delta_ms: Option<i64>,
+}
+
+/// What differs between two executions of a workflow.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExecutionDiff {
+    pub workflow_id: Uuid,
+    pub execution_a: Uuid,
+    pub execution_b: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<Changed<ExecutionStatus>>,
+    /// Wall time of B minus wall time of A, when both finished.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_delta_ms: Option<i64>,
+    /// Nodes that ran differently, in the order they first ran in A, then
+    /// those that only ran in B.
+    pub nodes: Vec<NodeDiff>,
+    /// Node runs identical in both apart from timing.
+    pub unchanged: usize,
+}
+
+/// Compare two executions of the same workflow node by node: status,
+/// tool version, config, inputs, outputs, errors, cache hits and how much
+/// slower or faster each node ran. Timing always varies a little, so a
+/// duration change alone only counts once it is at least
+/// [`MIN_DURATION_DELTA_MS`] and half the node's time in A.
+pub fn diff_executions(
+    store: &dyn TraceStore,
+    execution_a: Uuid,
+    execution_b: Uuid,
+) -> Result<ExecutionDiff, TraceError> {
+    let record = |id: Uuid| {
+        store
+            .query_execution(id)?
+            .ok_or_else(|| TraceError::ExecutionNotFound(id.to_string()))
+    };
+    let (a, b) = (record(execution_a)?, record(execution_b)?);
+    if a.workflow_id != b.workflow_id {
+        return Err(TraceError::DifferentWorkflows(execution_a, execution_b));
+    }
+    let wall_time = |r: &ExecutionRecord| {
+        r.completed_at
+            .map(|end| (end - r.started_at).num_milliseconds())
+    };
+
+    // Pair the spans of each node by the order the node ran in
+    let runs = |id: Uuid| -> Result<Vec<(NodeRun, NodeSpan)>, TraceError> {
+        let mut spans = store.query_spans_by_execution(id)?;
+        spans.sort_by_key(|s| s.started_at);
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        Ok(spans
+            .into_iter()
+            .map(|span| {
+                let run = seen.entry(span.node_id.clone()).or_default();
+                *run += 1;
+                ((span.node_id.clone(), *run - 1), span)
+            })
+            .collect())
+    };
+    let runs_a = runs(execution_a)?;
+    let mut runs_b: HashMap<NodeRun, NodeSpan> = HashMap::new();
+    let mut order_b = Vec::new();
+    for (key, span) in runs(execution_b)? {
+        order_b.push(key.clone());
+        runs_b.insert(key, span);
+    }
+
+    let mut diff = ExecutionDiff {
+        workflow_id: a.workflow_id,
+        execution_a,
+        execution_b,
+        status: (a.status != b.status).then(|| Changed {
+            a: a.status.clone(),
+            b: b.status.clone(),
+        }),
+        duration_delta_ms: wall_time(&a).zip(wall_time(&b)).map(|(a, b)| b - a),
+        nodes: Vec::new(),
+        unchanged: 0,
+    };
+    for (key, span_a) in &runs_a {
+        let node = diff_node(key, Some(span_a), runs_b.remove(key).as_ref());
+        match node {
+            Some(node) => diff.nodes.push(node),
+            None => diff.unchanged += 1,
+        }
+    }
+    for key in order_b {
+        if let Some(span_b) = runs_b.remove(&key) {
+            diff.nodes.extend(diff_node(&key, None, Some(&span_b)));
+        }
+    }
+    Ok(diff)
+}
+
+/// A node and which of its runs in an execution, from 0.
+type NodeRun = (String, usize);
+
+/// How a node's run differs, or `None` if it doesn't.
+fn diff_node(
+    (node_id, run): &NodeRun,
+    a: Option<&NodeSpan>,
+    b: Option<&NodeSpan>,
+) -> Option<NodeDiff> {
+    fn changed<T: PartialEq + Clone>(a: &T, b: &T) -> Option<Changed<T>> {
+        (a != b).then(|| Changed {
+            a: a.clone(),
+            b: b.clone(),
+        })
+    }
+    let mut node = NodeDiff {
+        node_id: node_id.clone(),
+        run: *run,
+        ran: Changed {
+            a: a.is_some(),
+            b: b.is_some(),
+        },
+        status: None,
+        tool_ref: None,
+        config: ValueDiff::default(),
+        inputs: ValueDiff::default(),
+        outputs: ValueDiff::default(),
+        error: None,
+        cache_hit: None,
+        duration_ms: None,
+        duration_delta_ms: None,
+    };
+    let (Some(a), Some(b)) = (a, b) else {
+        return Some(node);
+    };
+    node.status = changed(&a.status, &b.status);
+    node.tool_ref = changed(&a.tool_ref, &b.tool_ref);
+    node.config = diff_values(&a.config_json, &b.config_json);
+    node.inputs = diff_values(&a.input_json, &b.input_json);
+    let null = serde_json::Value::Null;
+    node.outputs = diff_values(
+        a.output_json.as_ref().unwrap_or(&null),
+        b.output_json.as_ref().unwrap_or(&null),
+    );
+    node.error = changed(&a.error, &b.error);
+    node.cache_hit = changed(&a.cache_hit, &b.cache_hit);
+    node.duration_ms = changed(&a.duration_ms, &b.duration_ms);
+    node.duration_delta_ms = a.duration_ms.zip(b.duration_ms).map(|(a, b)| b - a);
+    let slowed = match (a.duration_ms, node.duration_delta_ms) {
+        (Some(before), Some(delta)) => delta.abs() >= MIN_DURATION_DELTA_MS.max(before / 2),
+        _ => false,
+    };
+
+    let differs = slowed
+        || node.status.is_some()
+        || node.tool_ref.is_some()
+        || !node.config.is_empty()
+        || !node.inputs.is_empty()
+        || !node.outputs.is_empty()
+        || node.error.is_some()
+        || node.cache_hit.is_some();
+    differs.then_some(node)
+}
+
+/// Where two JSON values differ, by JSON Pointer. Objects are compared key
+/// by key and arrays element by element; other values as a whole.
+pub fn diff_values(a: &serde_json::Value, b: &serde_json::Value) -> ValueDiff {
+    let mut diff = ValueDiff::default();
+    collect_changes(String::new(), Some(a), Some(b), &mut diff);
+    diff
+}
+
+fn collect_changes(
+    path: String,
+    a: Option<&serde_json::Value>,
+    b: Option<&serde_json::Value>,
+    diff: &mut ValueDiff,
+) {
+    use serde_json::Value;
+    if a == b {
+        return;
+    }
+    let child = |key: &str| format!("{path}/{}", key.replace('~', "~0").replace('/', "~1"));
+    match (a, b) {
+        (Some(Value::Object(a)), Some(Value::Object(b))) => {
+            for key in a.keys().chain(b.keys().filter(|k| !a.contains_key(*k))) {
+                collect_changes(child(key), a.get(key), b.get(key), diff);
+            }
+        }
+        (Some(Value::Array(a)), Some(Value::Array(b))) => {
+            for i in 0..a.len().max(b.len()) {
+                collect_changes(child(&i.to_string()), a.get(i), b.get(i), diff);
+            }
+        }
+        _ if diff.changes.len() < MAX_VALUE_CHANGES => diff.changes.push(ValueChange {
+            path,
+            a: a.cloned(),
+            b: b.cloned(),
+        }),
+        _ => diff.omitted += 1,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::store::SqliteTraceStore;
     use chrono::Utc;
     use hb_core::trace::{ExecutionEnvironment, ExecutionStatus, NodeSpan};
 
@@ -79,7 +347,7 @@ mod tests {
 
     #[test]
     fn query_by_execution_id() {
-        let store = TraceStore::in_memory().unwrap();
+        let store = SqliteTraceStore::in_memory().unwrap();
         let exec_id = Uuid::new_v4();
 
         store.insert_span(&sample_span(exec_id, "n1", ExecutionStatus::Completed)).unwrap();
@@ -95,7 +363,7 @@ mod tests {
 
     #[test]
     fn query_filter_by_node_id() {
-        let store = TraceStore::in_memory().unwrap();
+        let store = SqliteTraceStore::in_memory().unwrap();
         let exec_id = Uuid::new_v4();
 
         store.insert_span(&sample_span(exec_id, "n1", ExecutionStatus::Completed)).unwrap();
@@ -112,7 +380,7 @@ mod tests {
 
     #[test]
     fn query_with_limit() {
-        let store = TraceStore::in_memory().unwrap();
+        let store = SqliteTraceStore::in_memory().unwrap();
         let exec_id = Uuid::new_v4();
 
         for i in 0..5 {
@@ -126,4 +394,89 @@ mod tests {
         }).unwrap();
         assert_eq!(results.len(), 2);
     }
+
+    #[test]
+    fn diff_executions_reports_node_differences() {
+        use hb_core::trace::ExecutionRecord;
+
+        let store = SqliteTraceStore::in_memory().unwrap();
+        let workflow_id = Uuid::new_v4();
+        let start = Utc::now();
+        let record = |status: ExecutionStatus, millis: i64| ExecutionRecord {
+            execution_id: Uuid::new_v4(),
+            workflow_id,
+            started_at: start,
+            completed_at: Some(start + chrono::Duration::milliseconds(millis)),
+            status,
+            total_nodes: 3,
+            completed_nodes: 3,
+            failed_nodes: 0,
+            cache_hits: 0,
+            workspace_id: None,
+            manifest: Default::default(),
+            contract_violations: Vec::new(),
+        };
+        let (a, b) = (record(ExecutionStatus::Completed, 900), record(ExecutionStatus::Failed, 1500));
+        store.upsert_execution(&a).unwrap();
+        store.upsert_execution(&b).unwrap();
+
+        let mut offset = 0;
+        let mut insert = |execution_id: Uuid, node: &str, edit: &dyn Fn(&mut NodeSpan)| {
+            let mut span = sample_span(execution_id, node, ExecutionStatus::Completed);
+            span.started_at = start + chrono::Duration::milliseconds(offset);
+            offset += 1;
+            edit(&mut span);
+            store.insert_span(&span).unwrap();
+        };
+        let keep = |_: &mut NodeSpan| {};
+        insert(a.execution_id, "read", &keep);
+        insert(b.execution_id, "read", &|s| s.duration_ms = Some(40));
+        insert(a.execution_id, "llm", &|s| {
+            s.input_json = serde_json::json!({ "prompt": "summarize", "temperature": 0.7 });
+            s.output_json = Some(serde_json::json!({ "response": "short" }));
+        });
+        insert(b.execution_id, "llm", &|s| {
+            s.input_json = serde_json::json!({ "prompt": "summarize", "temperature": 0.7 });
+            s.output_json = Some(serde_json::json!({ "response": "longer", "tokens": 9 }));
+            s.cache_hit = true;
+        });
+        insert(a.execution_id, "slow", &keep);
+        insert(b.execution_id, "slow", &|s| s.duration_ms = Some(400));
+        insert(b.execution_id, "write", &|s| {
+            s.status = ExecutionStatus::Failed;
+            s.error = Some("disk full".into());
+        });
+
+        let diff = diff_executions(&store, a.execution_id, b.execution_id).unwrap();
+        assert_eq!(diff.status.as_ref().map(|s| &s.b), Some(&ExecutionStatus::Failed));
+        assert_eq!(diff.duration_delta_ms, Some(600));
+        assert_eq!(diff.unchanged, 1);
+        let nodes: Vec<&str> = diff.nodes.iter().map(|n| n.node_id.as_str()).collect();
+        assert_eq!(nodes, ["llm", "slow", "write"]);
+
+        let llm = &diff.nodes[0];
+        assert!(llm.inputs.is_empty());
+        let paths: Vec<&str> = llm.outputs.changes.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, ["/response", "/tokens"]);
+        assert_eq!(llm.outputs.changes[1].a, None);
+        assert_eq!(llm.cache_hit, Some(Changed { a: false, b: true }));
+        assert_eq!(diff.nodes[1].duration_delta_ms, Some(390));
+        assert_eq!(diff.nodes[2].ran, Changed { a: false, b: true });
+
+        let other = record(ExecutionStatus::Completed, 10);
+        store
+            .upsert_execution(&ExecutionRecord {
+                workflow_id: Uuid::new_v4(),
+                ..other.clone()
+            })
+            .unwrap();
+        assert!(matches!(
+            diff_executions(&store, a.execution_id, other.execution_id),
+            Err(TraceError::DifferentWorkflows(..))
+        ));
+        assert!(matches!(
+            diff_executions(&store, a.execution_id, Uuid::new_v4()),
+            Err(TraceError::ExecutionNotFound(_))
+        ));
+    }
 }
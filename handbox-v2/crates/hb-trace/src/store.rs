@@ -1,25 +1,237 @@
-//! SQLite-backed trace storage.
+//! Trace storage: the [`TraceStore`] backend trait and its SQLite
+//! implementation.
 
+use crate::query::TraceQuery;
 use crate::TraceError;
-use hb_core::trace::{ExecutionEnvironment, ExecutionStatus, NodeSpan};
-use rusqlite::Connection;
-use std::path::Path;
-use std::sync::Mutex;
+use hb_core::trace::{
+    ArtifactEntry, ArtifactKind, ContractViolation, EdgeValue, ExecutionEnvironment, ExecutionManifest, ExecutionRecord,
+    ExecutionStatus, NodeSpan, SchedulerMetrics, TokenUsage, ToolAverages, ToolOutcomes,
+};
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
 use uuid::Uuid;
 
+/// Persistence backend for spans, execution records and edge values.
+///
+/// [`SqliteTraceStore`] is the default; other backends (e.g. a shared
+/// database for team servers) implement this trait so the runner and the app
+/// can hold an `Arc<dyn TraceStore>` without depending on SQLite.
+pub trait TraceStore: Send + Sync {
+    /// Insert a node span into the trace store.
+    fn insert_span(&self, span: &NodeSpan) -> Result<(), TraceError>;
+
+    /// Query all spans for a given execution ID.
+    fn query_spans_by_execution(&self, execution_id: Uuid) -> Result<Vec<NodeSpan>, TraceError>;
+
+    /// Query a single span by its ID.
+    fn query_span(&self, span_id: Uuid) -> Result<Option<NodeSpan>, TraceError>;
+
+    /// Visit the spans matching `query` in the order they were recorded,
+    /// without loading them all at once, so stores of any size can be
+    /// streamed. Returns how many spans were visited; an error from `visit`
+    /// stops the scan and is returned.
+    fn scan_spans(
+        &self,
+        query: &TraceQuery,
+        visit: &mut dyn FnMut(NodeSpan) -> Result<(), TraceError>,
+    ) -> Result<u64, TraceError>;
+
+    /// Insert or update the workflow-level record of an execution. An empty
+    /// manifest leaves any previously stored one in place.
+    fn upsert_execution(&self, record: &ExecutionRecord) -> Result<(), TraceError>;
+
+    /// Mark a running execution as interrupted by an app shutdown.
+    /// Returns false if there is no record for it or it already finished.
+    fn mark_execution_interrupted(&self, execution_id: Uuid) -> Result<bool, TraceError>;
+
+    /// Query a single execution record by its ID.
+    fn query_execution(&self, execution_id: Uuid) -> Result<Option<ExecutionRecord>, TraceError>;
+
+    /// Query all execution records with the given status, newest first.
+    fn query_executions_by_status(
+        &self,
+        status: &ExecutionStatus,
+    ) -> Result<Vec<ExecutionRecord>, TraceError>;
+
+    /// Query the most recent execution records of a workflow, newest first.
+    fn query_executions_by_workflow(
+        &self,
+        workflow_id: Uuid,
+        limit: usize,
+    ) -> Result<Vec<ExecutionRecord>, TraceError>;
+
+    /// Query what an execution produced or touched.
+    fn query_manifest(&self, execution_id: Uuid) -> Result<ExecutionManifest, TraceError>;
+
+    /// Query the executions that touched `target` (a file path, index name or
+    /// endpoint), optionally only as the given kind of artifact, newest first.
+    fn query_executions_by_artifact(
+        &self,
+        target: &str,
+        kind: Option<ArtifactKind>,
+        limit: usize,
+    ) -> Result<Vec<ExecutionRecord>, TraceError>;
+
+    /// Record how the scheduler spent an execution, replacing earlier
+    /// metrics of the same execution (e.g. of a resumed run).
+    fn insert_scheduler_metrics(&self, metrics: &SchedulerMetrics) -> Result<(), TraceError>;
+
+    /// Query the scheduler metrics of an execution, if it recorded any.
+    fn query_scheduler_metrics(&self, execution_id: Uuid) -> Result<Option<SchedulerMetrics>, TraceError>;
+
+    /// Delete finished executions that completed before `cutoff`, with their
+    /// spans, edge values and manifests. Spans of executions that never had
+    /// a record are removed once they started before `cutoff`.
+    /// Like [`TraceStore::prune_executions`], the freed space is returned
+    /// to the filesystem.
+    fn prune_executions_before(
+        &self,
+        cutoff: chrono::DateTime<chrono::Utc>,
+    ) -> Result<PruneStats, TraceError>;
+
+    /// Finished executions, the longest finished first, for retention to
+    /// choose from. Spans and edge values recorded under an execution that
+    /// never had a record are listed too, dated by the latest of them.
+    fn query_finished_executions(&self) -> Result<Vec<FinishedExecution>, TraceError>;
+
+    /// Delete the given executions with their spans, edge values and
+    /// manifests, and return the freed space to the filesystem.
+    fn prune_executions(&self, execution_ids: &[Uuid]) -> Result<PruneStats, TraceError>;
+
+    /// LLM tokens recorded by spans that started at or after `since`,
+    /// summed per execution, model and day. Cache hits spent nothing and
+    /// are left out.
+    fn query_token_usage(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<TokenUsage>, TraceError>;
+
+    /// Completed and failed runs per tool for spans that started at or
+    /// after `since`. Cache hits and skipped nodes are left out.
+    fn query_tool_outcomes(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<ToolOutcomes>, TraceError>;
+
+    /// Duration and token use per tool over completed spans that started
+    /// at or after `since`. Cache hits are left out.
+    fn query_tool_averages(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<ToolAverages>, TraceError>;
+
+    /// Storage used by the store in bytes.
+    fn database_size(&self) -> Result<u64, TraceError>;
+
+    /// Flush pending writes to durable storage.
+    fn flush(&self) -> Result<(), TraceError>;
+
+    /// Flush and close the store. A store still shared elsewhere, such as
+    /// with executions that outlived shutdown, is only flushed.
+    fn close(self: Arc<Self>) -> Result<(), TraceError>;
+
+    /// Record the value that crossed an edge. Re-recording the same edge of
+    /// an execution (e.g. on resume) replaces the earlier value.
+    fn insert_edge_value(&self, value: &EdgeValue) -> Result<(), TraceError>;
+
+    /// Query the value that crossed one edge during an execution.
+    fn query_edge_value(
+        &self,
+        execution_id: Uuid,
+        edge_id: &str,
+    ) -> Result<Option<EdgeValue>, TraceError>;
+
+    /// Query every captured value that entered a node during an execution,
+    /// i.e. what exactly the node received.
+    fn query_node_inputs(
+        &self,
+        execution_id: Uuid,
+        node_id: &str,
+    ) -> Result<Vec<EdgeValue>, TraceError>;
+}
+
+/// Spans read per lock of the connection by [`TraceStore::scan_spans`].
+const SCAN_PAGE_SIZE: usize = 256;
+
+const SPAN_COLUMNS: &str = "span_id, execution_id, node_id, tool_ref,
+    input_json, output_json, config_json,
+    started_at, completed_at, duration_ms,
+    status, error, cache_hit, environment_json";
+
+/// Read-only connections a file-backed store queries through.
+const READERS: usize = 4;
+
+/// How long a statement waits for a lock held by another connection, e.g.
+/// a checkpoint waiting for readers to move on.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// Trace store backed by a single SQLite database.
 /// Uses Mutex<Connection> for thread safety (rusqlite::Connection is !Sync).
-pub struct TraceStore {
+///
+/// File-backed stores run in WAL mode: writes go through `conn` while
+/// queries use the read-only `readers`, each query reading one snapshot of
+/// the database. A burst of span writes during a large run then neither
+/// waits for nor holds up the UI's trace queries.
+pub struct SqliteTraceStore {
     conn: Mutex<Connection>,
+    /// Empty for in-memory stores, which query through `conn`.
+    readers: Vec<Mutex<Connection>>,
+    next_reader: AtomicUsize,
+}
+
+/// A connection inside a read transaction: every query through it sees the
+/// database as of the first one, whatever is committed meanwhile.
+struct Snapshot<'a> {
+    conn: MutexGuard<'a, Connection>,
+}
+
+impl Deref for Snapshot<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        &self.conn
+    }
+}
+
+impl Drop for Snapshot<'_> {
+    fn drop(&mut self) {
+        // Nothing was written; ending the transaction releases the snapshot
+        if let Err(e) = self.conn.execute_batch("COMMIT") {
+            tracing::warn!("Failed to end trace store read transaction: {e}");
+        }
+    }
 }
 
-impl TraceStore {
+impl SqliteTraceStore {
     /// Open (or create) the trace database at the given path.
     pub fn open(path: &Path) -> Result<Self, TraceError> {
         let conn =
             Connection::open(path).map_err(|e| TraceError::Database(e.to_string()))?;
-        let store = Self { conn: Mutex::new(conn) };
+        // Auto-vacuum only applies to databases created with it; older ones
+        // are converted by their first prune
+        conn.busy_timeout(BUSY_TIMEOUT)
+            .and_then(|_| conn.pragma_update(None, "auto_vacuum", "INCREMENTAL"))
+            .and_then(|_| conn.pragma_update(None, "journal_mode", "WAL"))
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+        let mut store = Self::with_connection(conn);
         store.initialize_schema()?;
+        // Readers open after the schema exists, on the database in WAL mode
+        for _ in 0..READERS {
+            let reader = Connection::open_with_flags(
+                path,
+                OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+            )
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+            reader
+                .busy_timeout(BUSY_TIMEOUT)
+                .map_err(|e| TraceError::Database(e.to_string()))?;
+            store.readers.push(Mutex::new(reader));
+        }
         Ok(store)
     }
 
@@ -27,11 +239,37 @@ impl TraceStore {
     pub fn in_memory() -> Result<Self, TraceError> {
         let conn =
             Connection::open_in_memory().map_err(|e| TraceError::Database(e.to_string()))?;
-        let store = Self { conn: Mutex::new(conn) };
+        conn.pragma_update(None, "auto_vacuum", "INCREMENTAL")
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+        let store = Self::with_connection(conn);
         store.initialize_schema()?;
         Ok(store)
     }
 
+    fn with_connection(conn: Connection) -> Self {
+        Self {
+            conn: Mutex::new(conn),
+            readers: Vec::new(),
+            next_reader: AtomicUsize::new(0),
+        }
+    }
+
+    /// A snapshot to query: an idle reader if there is one, else the next
+    /// reader in turn once it is free.
+    fn snapshot(&self) -> Result<Snapshot<'_>, TraceError> {
+        let conn = match self.readers.iter().find_map(|r| r.try_lock().ok()) {
+            Some(conn) => conn,
+            None if self.readers.is_empty() => self.conn.lock().map_err(|e| TraceError::Database(e.to_string()))?,
+            None => {
+                let next = self.next_reader.fetch_add(1, Ordering::Relaxed) % self.readers.len();
+                self.readers[next].lock().map_err(|e| TraceError::Database(e.to_string()))?
+            }
+        };
+        conn.execute_batch("BEGIN")
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+        Ok(Snapshot { conn })
+    }
+
     fn initialize_schema(&self) -> Result<(), TraceError> {
         let conn = self.conn.lock().map_err(|e| TraceError::Database(e.to_string()))?;
         conn.execute_batch(
@@ -55,14 +293,135 @@ impl TraceStore {
                 CREATE INDEX IF NOT EXISTS idx_traces_execution
                     ON traces(execution_id);
                 CREATE INDEX IF NOT EXISTS idx_traces_node
-                    ON traces(node_id);",
+                    ON traces(node_id);
+
+                CREATE TABLE IF NOT EXISTS executions (
+                    execution_id TEXT PRIMARY KEY,
+                    workflow_id TEXT NOT NULL,
+                    started_at TEXT NOT NULL,
+                    completed_at TEXT,
+                    status TEXT NOT NULL,
+                    total_nodes INTEGER NOT NULL DEFAULT 0,
+                    completed_nodes INTEGER NOT NULL DEFAULT 0,
+                    failed_nodes INTEGER NOT NULL DEFAULT 0,
+                    cache_hits INTEGER NOT NULL DEFAULT 0
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_executions_status
+                    ON executions(status);
+                CREATE INDEX IF NOT EXISTS idx_executions_workflow
+                    ON executions(workflow_id, started_at);
+
+                CREATE TABLE IF NOT EXISTS edge_values (
+                    execution_id TEXT NOT NULL,
+                    edge_id TEXT NOT NULL,
+                    source_node TEXT NOT NULL,
+                    source_port TEXT NOT NULL,
+                    target_node TEXT NOT NULL,
+                    target_port TEXT NOT NULL,
+                    value_json TEXT NOT NULL,
+                    truncated BOOLEAN NOT NULL DEFAULT 0,
+                    size_bytes INTEGER NOT NULL,
+                    recorded_at TEXT NOT NULL,
+                    PRIMARY KEY (execution_id, edge_id)
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_edge_values_target
+                    ON edge_values(execution_id, target_node);
+
+                CREATE TABLE IF NOT EXISTS execution_artifacts (
+                    execution_id TEXT NOT NULL,
+                    kind TEXT NOT NULL,
+                    target TEXT NOT NULL,
+                    node_ids_json TEXT NOT NULL,
+                    count INTEGER NOT NULL DEFAULT 1,
+                    seq INTEGER NOT NULL,
+                    PRIMARY KEY (execution_id, kind, target)
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_execution_artifacts_target
+                    ON execution_artifacts(target);
+
+                CREATE TABLE IF NOT EXISTS execution_specs (
+                    execution_id TEXT PRIMARY KEY,
+                    spec_json TEXT NOT NULL
+                );
+
+                CREATE TABLE IF NOT EXISTS execution_workspaces (
+                    execution_id TEXT PRIMARY KEY,
+                    workspace_id TEXT NOT NULL
+                );
+
+                CREATE TABLE IF NOT EXISTS contract_violations (
+                    execution_id TEXT PRIMARY KEY,
+                    violations_json TEXT NOT NULL
+                );
+
+                CREATE TABLE IF NOT EXISTS scheduler_metrics (
+                    execution_id TEXT PRIMARY KEY,
+                    metrics_json TEXT NOT NULL
+                );",
             )
             .map_err(|e| TraceError::Database(e.to_string()))?;
         Ok(())
     }
 
-    /// Insert a node span into the trace store.
-    pub fn insert_span(&self, span: &NodeSpan) -> Result<(), TraceError> {
+    /// Flush and close the underlying connection.
+    pub fn close(self) -> Result<(), TraceError> {
+        self.flush()?;
+        drop(self.readers);
+        let conn = self
+            .conn
+            .into_inner()
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+        conn.close().map_err(|(_, e)| TraceError::Database(e.to_string()))
+    }
+
+    fn query_edge_values_where(
+        &self,
+        filter: &str,
+        execution_id: Uuid,
+        key: &str,
+    ) -> Result<Vec<EdgeValue>, TraceError> {
+        let conn = self.snapshot()?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT execution_id, edge_id, source_node, source_port,
+                        target_node, target_port, value_json, truncated,
+                        size_bytes, recorded_at
+                 FROM edge_values WHERE {filter}
+                 ORDER BY recorded_at ASC, rowid ASC"
+            ))
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+
+        let rows = stmt
+            .query_map(rusqlite::params![execution_id.to_string(), key], |row| {
+                Ok(RawEdgeValueRow {
+                    execution_id: row.get(0)?,
+                    edge_id: row.get(1)?,
+                    source_node: row.get(2)?,
+                    source_port: row.get(3)?,
+                    target_node: row.get(4)?,
+                    target_port: row.get(5)?,
+                    value_json: row.get(6)?,
+                    truncated: row.get(7)?,
+                    size_bytes: row.get(8)?,
+                    recorded_at: row.get(9)?,
+                })
+            })
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+
+        let mut values = Vec::new();
+        for row in rows {
+            let raw = row.map_err(|e| TraceError::Database(e.to_string()))?;
+            values.push(raw_to_edge_value(raw)?);
+        }
+        Ok(values)
+    }
+}
+
+impl TraceStore for SqliteTraceStore {
+    fn insert_span(&self, span: &NodeSpan) -> Result<(), TraceError> {
         let conn = self.conn.lock().map_err(|e| TraceError::Database(e.to_string()))?;
         conn.execute(
                 "INSERT INTO traces (
@@ -92,9 +451,8 @@ impl TraceStore {
         Ok(())
     }
 
-    /// Query all spans for a given execution ID.
-    pub fn query_spans_by_execution(&self, execution_id: Uuid) -> Result<Vec<NodeSpan>, TraceError> {
-        let conn = self.conn.lock().map_err(|e| TraceError::Database(e.to_string()))?;
+    fn query_spans_by_execution(&self, execution_id: Uuid) -> Result<Vec<NodeSpan>, TraceError> {
+        let conn = self.snapshot()?;
         let mut stmt = conn.prepare(
                 "SELECT span_id, execution_id, node_id, tool_ref,
                         input_json, output_json, config_json,
@@ -106,75 +464,1139 @@ impl TraceStore {
             .map_err(|e| TraceError::Database(e.to_string()))?;
 
         let rows = stmt
-            .query_map(rusqlite::params![execution_id.to_string()], |row| {
-                Ok(RawSpanRow {
-                    span_id: row.get(0)?,
-                    execution_id: row.get(1)?,
-                    node_id: row.get(2)?,
-                    tool_ref: row.get(3)?,
-                    input_json: row.get(4)?,
-                    output_json: row.get(5)?,
-                    config_json: row.get(6)?,
-                    started_at: row.get(7)?,
-                    completed_at: row.get(8)?,
-                    duration_ms: row.get(9)?,
-                    status: row.get(10)?,
-                    error: row.get(11)?,
-                    cache_hit: row.get(12)?,
-                    environment_json: row.get(13)?,
-                })
+            .query_map(rusqlite::params![execution_id.to_string()], |row| raw_span_row(row, 0))
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+
+        let mut spans = Vec::new();
+        for row in rows {
+            let raw = row.map_err(|e| TraceError::Database(e.to_string()))?;
+            spans.push(raw_to_span(raw)?);
+        }
+        Ok(spans)
+    }
+
+    fn query_span(&self, span_id: Uuid) -> Result<Option<NodeSpan>, TraceError> {
+        let conn = self.snapshot()?;
+        let mut stmt = conn.prepare(
+                "SELECT span_id, execution_id, node_id, tool_ref,
+                        input_json, output_json, config_json,
+                        started_at, completed_at, duration_ms,
+                        status, error, cache_hit, environment_json
+                 FROM traces WHERE span_id = ?1",
+            )
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+
+        let mut rows = stmt
+            .query_map(rusqlite::params![span_id.to_string()], |row| raw_span_row(row, 0))
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+
+        match rows.next() {
+            Some(row) => {
+                let raw = row.map_err(|e| TraceError::Database(e.to_string()))?;
+                Ok(Some(raw_to_span(raw)?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn scan_spans(
+        &self,
+        query: &TraceQuery,
+        visit: &mut dyn FnMut(NodeSpan) -> Result<(), TraceError>,
+    ) -> Result<u64, TraceError> {
+        let mut filters = String::new();
+        let mut params: Vec<String> = Vec::new();
+        if let Some(execution_id) = query.execution_id {
+            params.push(execution_id.to_string());
+            filters.push_str(&format!(" AND execution_id = ?{}", params.len() + 1));
+        }
+        if let Some(node_id) = &query.node_id {
+            params.push(node_id.clone());
+            filters.push_str(&format!(" AND node_id = ?{}", params.len() + 1));
+        }
+        if let Some(status) = &query.status {
+            // Statuses are stored as their JSON strings
+            params.push(format!("\"{status}\""));
+            filters.push_str(&format!(" AND status = ?{}", params.len() + 1));
+        }
+        let sql = format!(
+            "SELECT rowid, {SPAN_COLUMNS} FROM traces
+             WHERE rowid > ?1{filters}
+             ORDER BY rowid LIMIT {SCAN_PAGE_SIZE}"
+        );
+        let limit = query.limit.map_or(u64::MAX, u64::from);
+
+        // Read a page at a time, keyed on the last rowid, and release the
+        // connection between pages so recording isn't held up by an export
+        let mut after = 0i64;
+        let mut visited = 0u64;
+        while visited < limit {
+            let page = {
+                let conn = self.snapshot()?;
+                let mut stmt = conn
+                    .prepare_cached(&sql)
+                    .map_err(|e| TraceError::Database(e.to_string()))?;
+                let values = std::iter::once(rusqlite::types::Value::from(after))
+                    .chain(params.iter().cloned().map(rusqlite::types::Value::from));
+                let rows = stmt
+                    .query_map(rusqlite::params_from_iter(values), |row| {
+                        Ok((row.get::<_, i64>(0)?, raw_span_row(row, 1)?))
+                    })
+                    .map_err(|e| TraceError::Database(e.to_string()))?;
+                rows.collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| TraceError::Database(e.to_string()))?
+            };
+            let Some(&(last, _)) = page.last() else {
+                break;
+            };
+            let full = page.len() == SCAN_PAGE_SIZE;
+            for (_, raw) in page {
+                if visited == limit {
+                    break;
+                }
+                visit(raw_to_span(raw)?)?;
+                visited += 1;
+            }
+            if !full {
+                break;
+            }
+            after = last;
+        }
+        Ok(visited)
+    }
+
+    fn upsert_execution(&self, record: &ExecutionRecord) -> Result<(), TraceError> {
+        let conn = self.conn.lock().map_err(|e| TraceError::Database(e.to_string()))?;
+        conn.execute(
+                "INSERT OR REPLACE INTO executions (
+                    execution_id, workflow_id, started_at, completed_at, status,
+                    total_nodes, completed_nodes, failed_nodes, cache_hits
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                rusqlite::params![
+                    record.execution_id.to_string(),
+                    record.workflow_id.to_string(),
+                    record.started_at.to_rfc3339(),
+                    record.completed_at.map(|t| t.to_rfc3339()),
+                    serde_json::to_string(&record.status).unwrap_or_default(),
+                    record.total_nodes,
+                    record.completed_nodes,
+                    record.failed_nodes,
+                    record.cache_hits,
+                ],
+            )
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+        if let Some(workspace_id) = record.workspace_id {
+            conn.execute(
+                    "INSERT OR REPLACE INTO execution_workspaces (execution_id, workspace_id)
+                     VALUES (?1, ?2)",
+                    rusqlite::params![record.execution_id.to_string(), workspace_id.to_string()],
+                )
+                .map_err(|e| TraceError::Database(e.to_string()))?;
+        }
+        // An empty manifest leaves any previously stored one in place, so
+        // interim and interrupted records don't erase what a run produced.
+        if !record.manifest.is_empty() {
+            store_manifest(&conn, record.execution_id, &record.manifest)?;
+        }
+        if !record.contract_violations.is_empty() {
+            conn.execute(
+                    "INSERT OR REPLACE INTO contract_violations (execution_id, violations_json)
+                     VALUES (?1, ?2)",
+                    rusqlite::params![
+                        record.execution_id.to_string(),
+                        serde_json::to_string(&record.contract_violations).unwrap_or_default(),
+                    ],
+                )
+                .map_err(|e| TraceError::Database(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    fn mark_execution_interrupted(&self, execution_id: Uuid) -> Result<bool, TraceError> {
+        let conn = self.conn.lock().map_err(|e| TraceError::Database(e.to_string()))?;
+        let updated = conn
+            .execute(
+                "UPDATE executions SET status = ?1, completed_at = ?2
+                 WHERE execution_id = ?3 AND status = ?4",
+                rusqlite::params![
+                    serde_json::to_string(&ExecutionStatus::Interrupted).unwrap_or_default(),
+                    chrono::Utc::now().to_rfc3339(),
+                    execution_id.to_string(),
+                    serde_json::to_string(&ExecutionStatus::Running).unwrap_or_default(),
+                ],
+            )
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+        Ok(updated > 0)
+    }
+
+    fn query_execution(&self, execution_id: Uuid) -> Result<Option<ExecutionRecord>, TraceError> {
+        let conn = self.snapshot()?;
+        let mut stmt = conn.prepare(
+                "SELECT execution_id, workflow_id, started_at, completed_at, status,
+                        total_nodes, completed_nodes, failed_nodes, cache_hits
+                 FROM executions WHERE execution_id = ?1",
+            )
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+
+        let mut rows = stmt
+            .query_map(rusqlite::params![execution_id.to_string()], raw_execution_row)
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+
+        match rows.next() {
+            Some(row) => {
+                let raw = row.map_err(|e| TraceError::Database(e.to_string()))?;
+                let mut record = raw_to_execution(raw)?;
+                record.workspace_id = load_workspace(&conn, record.execution_id)?;
+                record.manifest = load_manifest(&conn, record.execution_id)?;
+                record.contract_violations = load_violations(&conn, record.execution_id)?;
+                Ok(Some(record))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn query_executions_by_status(
+        &self,
+        status: &ExecutionStatus,
+    ) -> Result<Vec<ExecutionRecord>, TraceError> {
+        let conn = self.snapshot()?;
+        let mut stmt = conn.prepare(
+                "SELECT execution_id, workflow_id, started_at, completed_at, status,
+                        total_nodes, completed_nodes, failed_nodes, cache_hits
+                 FROM executions WHERE status = ?1
+                 ORDER BY started_at DESC",
+            )
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+
+        let rows = stmt
+            .query_map(
+                rusqlite::params![serde_json::to_string(status).unwrap_or_default()],
+                raw_execution_row,
+            )
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+
+        let mut records = Vec::new();
+        for row in rows {
+            let raw = row.map_err(|e| TraceError::Database(e.to_string()))?;
+            let mut record = raw_to_execution(raw)?;
+            record.workspace_id = load_workspace(&conn, record.execution_id)?;
+            record.contract_violations = load_violations(&conn, record.execution_id)?;
+            records.push(record);
+        }
+        attach_manifests(&conn, &mut records)?;
+        Ok(records)
+    }
+
+    fn query_executions_by_workflow(
+        &self,
+        workflow_id: Uuid,
+        limit: usize,
+    ) -> Result<Vec<ExecutionRecord>, TraceError> {
+        let conn = self.snapshot()?;
+        let mut stmt = conn.prepare(
+                "SELECT execution_id, workflow_id, started_at, completed_at, status,
+                        total_nodes, completed_nodes, failed_nodes, cache_hits
+                 FROM executions WHERE workflow_id = ?1
+                 ORDER BY started_at DESC LIMIT ?2",
+            )
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+
+        let rows = stmt
+            .query_map(
+                rusqlite::params![workflow_id.to_string(), limit as i64],
+                raw_execution_row,
+            )
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+
+        let mut records = Vec::new();
+        for row in rows {
+            let raw = row.map_err(|e| TraceError::Database(e.to_string()))?;
+            let mut record = raw_to_execution(raw)?;
+            record.workspace_id = load_workspace(&conn, record.execution_id)?;
+            record.contract_violations = load_violations(&conn, record.execution_id)?;
+            records.push(record);
+        }
+        attach_manifests(&conn, &mut records)?;
+        Ok(records)
+    }
+
+    fn query_manifest(&self, execution_id: Uuid) -> Result<ExecutionManifest, TraceError> {
+        let conn = self.snapshot()?;
+        load_manifest(&conn, execution_id)
+    }
+
+    fn insert_scheduler_metrics(&self, metrics: &SchedulerMetrics) -> Result<(), TraceError> {
+        let conn = self.conn.lock().map_err(|e| TraceError::Database(e.to_string()))?;
+        conn.execute(
+            "INSERT OR REPLACE INTO scheduler_metrics (execution_id, metrics_json) VALUES (?1, ?2)",
+            rusqlite::params![
+                metrics.execution_id.to_string(),
+                serde_json::to_string(metrics).unwrap_or_default(),
+            ],
+        )
+        .map_err(|e| TraceError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn query_scheduler_metrics(&self, execution_id: Uuid) -> Result<Option<SchedulerMetrics>, TraceError> {
+        let conn = self.snapshot()?;
+        let json: Option<String> = conn
+            .query_row(
+                "SELECT metrics_json FROM scheduler_metrics WHERE execution_id = ?1",
+                rusqlite::params![execution_id.to_string()],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+        json.map(|json| {
+            serde_json::from_str(&json).map_err(|e| TraceError::Database(format!("scheduler_metrics: {e}")))
+        })
+        .transpose()
+    }
+
+    fn query_executions_by_artifact(
+        &self,
+        target: &str,
+        kind: Option<ArtifactKind>,
+        limit: usize,
+    ) -> Result<Vec<ExecutionRecord>, TraceError> {
+        let conn = self.snapshot()?;
+        let mut stmt = conn.prepare(
+                "SELECT DISTINCT e.execution_id, e.workflow_id, e.started_at, e.completed_at, e.status,
+                        e.total_nodes, e.completed_nodes, e.failed_nodes, e.cache_hits
+                 FROM executions e
+                 JOIN execution_artifacts a ON a.execution_id = e.execution_id
+                 WHERE a.target = ?1 AND (?2 IS NULL OR a.kind = ?2)
+                 ORDER BY e.started_at DESC LIMIT ?3",
+            )
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+
+        let rows = stmt
+            .query_map(
+                rusqlite::params![target, kind.map(kind_to_str), limit as i64],
+                raw_execution_row,
+            )
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+
+        let mut records = Vec::new();
+        for row in rows {
+            let raw = row.map_err(|e| TraceError::Database(e.to_string()))?;
+            let mut record = raw_to_execution(raw)?;
+            record.workspace_id = load_workspace(&conn, record.execution_id)?;
+            record.contract_violations = load_violations(&conn, record.execution_id)?;
+            records.push(record);
+        }
+        attach_manifests(&conn, &mut records)?;
+        Ok(records)
+    }
+
+    fn prune_executions_before(
+        &self,
+        cutoff: chrono::DateTime<chrono::Utc>,
+    ) -> Result<PruneStats, TraceError> {
+        let mut conn = self.conn.lock().map_err(|e| TraceError::Database(e.to_string()))?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+        let status = |s: ExecutionStatus| serde_json::to_string(&s).unwrap_or_default();
+        let (cutoff, pending, running) = (
+            cutoff.to_rfc3339(),
+            status(ExecutionStatus::Pending),
+            status(ExecutionStatus::Running),
+        );
+        let params = rusqlite::params![cutoff, pending, running];
+        // Executions to prune; ?1 is the cutoff, ?2/?3 the in-flight statuses.
+        let expired = "SELECT execution_id FROM executions
+                       WHERE completed_at IS NOT NULL AND completed_at < ?1
+                         AND status NOT IN (?2, ?3)";
+        let delete = |sql: String| -> Result<usize, TraceError> {
+            tx.execute(&sql, params)
+                .map_err(|e| TraceError::Database(e.to_string()))
+        };
+
+        let spans = delete(format!(
+            "DELETE FROM traces WHERE execution_id IN ({expired})
+                OR (started_at < ?1 AND execution_id NOT IN (SELECT execution_id FROM executions))"
+        ))?;
+        let edge_values = delete(format!(
+            "DELETE FROM edge_values WHERE execution_id IN ({expired})
+                OR (recorded_at < ?1 AND execution_id NOT IN (SELECT execution_id FROM executions))"
+        ))?;
+        delete(format!(
+            "DELETE FROM execution_artifacts WHERE execution_id IN ({expired})"
+        ))?;
+        delete(format!(
+            "DELETE FROM execution_specs WHERE execution_id IN ({expired})"
+        ))?;
+        delete(format!(
+            "DELETE FROM execution_workspaces WHERE execution_id IN ({expired})"
+        ))?;
+        delete(format!(
+            "DELETE FROM contract_violations WHERE execution_id IN ({expired})"
+        ))?;
+        delete(format!(
+            "DELETE FROM scheduler_metrics WHERE execution_id IN ({expired})"
+        ))?;
+        let executions = delete(format!(
+            "DELETE FROM executions WHERE execution_id IN ({expired})"
+        ))?;
+        tx.commit().map_err(|e| TraceError::Database(e.to_string()))?;
+        if executions + spans + edge_values > 0 {
+            reclaim_space(&conn)?;
+        }
+
+        Ok(PruneStats {
+            executions,
+            spans,
+            edge_values,
+        })
+    }
+
+    fn query_finished_executions(&self) -> Result<Vec<FinishedExecution>, TraceError> {
+        let conn = self.snapshot()?;
+        let status = |s: ExecutionStatus| serde_json::to_string(&s).unwrap_or_default();
+        let mut stmt = conn
+            .prepare(
+                "SELECT execution_id, completed_at AS finished_at FROM executions
+                 WHERE completed_at IS NOT NULL AND status NOT IN (?1, ?2)
+                 UNION ALL
+                 SELECT execution_id, MAX(at) FROM (
+                     SELECT execution_id, started_at AS at FROM traces
+                     UNION ALL
+                     SELECT execution_id, recorded_at FROM edge_values
+                 )
+                 WHERE execution_id NOT IN (SELECT execution_id FROM executions)
+                 GROUP BY execution_id
+                 ORDER BY finished_at",
+            )
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map(
+                rusqlite::params![
+                    status(ExecutionStatus::Pending),
+                    status(ExecutionStatus::Running)
+                ],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+            )
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+
+        let mut finished = Vec::new();
+        for row in rows {
+            let (execution_id, finished_at) =
+                row.map_err(|e| TraceError::Database(e.to_string()))?;
+            finished.push(FinishedExecution {
+                execution_id: execution_id
+                    .parse()
+                    .map_err(|e: uuid::Error| TraceError::Database(format!("execution_id: {e}")))?,
+                finished_at: chrono::DateTime::parse_from_rfc3339(&finished_at)
+                    .map(|dt| dt.with_timezone(&chrono::Utc))
+                    .map_err(|e| TraceError::Database(format!("finished_at: {e}")))?,
+            });
+        }
+        Ok(finished)
+    }
+
+    fn prune_executions(&self, execution_ids: &[Uuid]) -> Result<PruneStats, TraceError> {
+        let mut conn = self.conn.lock().map_err(|e| TraceError::Database(e.to_string()))?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+        let mut stats = PruneStats::default();
+        {
+            let delete = |table: &str, id: &str| -> Result<usize, TraceError> {
+                tx.prepare_cached(&format!("DELETE FROM {table} WHERE execution_id = ?1"))
+                    .and_then(|mut stmt| stmt.execute([id]))
+                    .map_err(|e| TraceError::Database(e.to_string()))
+            };
+            for id in execution_ids.iter().map(Uuid::to_string) {
+                stats.spans += delete("traces", &id)?;
+                stats.edge_values += delete("edge_values", &id)?;
+                delete("execution_artifacts", &id)?;
+                delete("execution_specs", &id)?;
+                delete("execution_workspaces", &id)?;
+                delete("contract_violations", &id)?;
+                delete("scheduler_metrics", &id)?;
+                stats.executions += delete("executions", &id)?;
+            }
+        }
+        tx.commit().map_err(|e| TraceError::Database(e.to_string()))?;
+        if stats != PruneStats::default() {
+            reclaim_space(&conn)?;
+        }
+        Ok(stats)
+    }
+
+    fn query_token_usage(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<TokenUsage>, TraceError> {
+        let conn = self.snapshot()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT execution_id,
+                        COALESCE(json_extract(output_json, '$.model'), ''),
+                        substr(started_at, 1, 10),
+                        SUM(COALESCE(json_extract(output_json, '$.input_tokens'), 0)),
+                        SUM(COALESCE(json_extract(output_json, '$.output_tokens'), 0)),
+                        COUNT(*)
+                 FROM traces
+                 WHERE started_at >= ?1 AND cache_hit = 0 AND json_valid(output_json)
+                   AND (json_type(output_json, '$.input_tokens') = 'integer'
+                        OR json_type(output_json, '$.output_tokens') = 'integer')
+                 GROUP BY 1, 2, 3
+                 ORDER BY 3, 1, 2",
+            )
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map([since.to_rfc3339()], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, i64>(3)?,
+                    row.get::<_, i64>(4)?,
+                    row.get::<_, i64>(5)?,
+                ))
+            })
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+
+        let mut usage = Vec::new();
+        for row in rows {
+            let (execution_id, model, day, input, output, calls) =
+                row.map_err(|e| TraceError::Database(e.to_string()))?;
+            usage.push(TokenUsage {
+                execution_id: execution_id
+                    .parse()
+                    .map_err(|e: uuid::Error| TraceError::Database(format!("execution_id: {e}")))?,
+                model,
+                day,
+                input_tokens: input.max(0) as u64,
+                output_tokens: output.max(0) as u64,
+                calls: calls.max(0) as u32,
+            });
+        }
+        Ok(usage)
+    }
+
+    fn query_tool_outcomes(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<ToolOutcomes>, TraceError> {
+        let conn = self.snapshot()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT tool_ref, COUNT(*), SUM(status = '\"failed\"')
+                 FROM traces
+                 WHERE started_at >= ?1 AND cache_hit = 0
+                   AND status IN ('\"completed\"', '\"failed\"')
+                 GROUP BY tool_ref",
+            )
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map([since.to_rfc3339()], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, i64>(2)?,
+                ))
+            })
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+
+        // Versions of the same tool are merged
+        let mut outcomes: BTreeMap<String, ToolOutcomes> = BTreeMap::new();
+        for row in rows {
+            let (tool_ref, runs, failures) = row.map_err(|e| TraceError::Database(e.to_string()))?;
+            let tool_id = tool_ref.split('@').next().unwrap_or(&tool_ref).to_string();
+            let entry = outcomes.entry(tool_id.clone()).or_insert_with(|| ToolOutcomes {
+                tool_id,
+                ..Default::default()
+            });
+            entry.runs += runs.max(0) as u32;
+            entry.failures += failures.max(0) as u32;
+        }
+        Ok(outcomes.into_values().collect())
+    }
+
+    fn query_tool_averages(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<ToolAverages>, TraceError> {
+        let conn = self.snapshot()?;
+        let tokens = |field: &str| {
+            format!(
+                "SUM(CASE WHEN json_valid(output_json)
+                          THEN COALESCE(json_extract(output_json, '$.{field}'), 0) ELSE 0 END)"
+            )
+        };
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT tool_ref, COUNT(*), SUM(COALESCE(duration_ms, 0)),
+                        MIN(COALESCE(duration_ms, 0)), MAX(COALESCE(duration_ms, 0)),
+                        {}, {}
+                 FROM traces
+                 WHERE started_at >= ?1 AND cache_hit = 0 AND status = '\"completed\"'
+                 GROUP BY tool_ref",
+                tokens("input_tokens"),
+                tokens("output_tokens")
+            ))
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map([since.to_rfc3339()], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, i64>(3)?,
+                    row.get::<_, i64>(4)?,
+                    row.get::<_, i64>(5)?,
+                    row.get::<_, i64>(6)?,
+                ))
+            })
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+
+        // Versions of the same tool are merged: sums first, means at the end
+        let mut sums: BTreeMap<String, (ToolAverages, u64, u64, u64)> = BTreeMap::new();
+        for row in rows {
+            let (tool_ref, runs, total_ms, min_ms, max_ms, input, output) =
+                row.map_err(|e| TraceError::Database(e.to_string()))?;
+            let tool_id = tool_ref.split('@').next().unwrap_or(&tool_ref).to_string();
+            let (min_ms, max_ms) = (min_ms.max(0) as u64, max_ms.max(0) as u64);
+            let (entry, ms, input_sum, output_sum) = sums.entry(tool_id.clone()).or_insert_with(|| {
+                let first = ToolAverages {
+                    tool_id,
+                    min_ms,
+                    max_ms,
+                    ..Default::default()
+                };
+                (first, 0, 0, 0)
+            });
+            entry.runs += runs.max(0) as u32;
+            entry.min_ms = entry.min_ms.min(min_ms);
+            entry.max_ms = entry.max_ms.max(max_ms);
+            *ms += total_ms.max(0) as u64;
+            *input_sum += input.max(0) as u64;
+            *output_sum += output.max(0) as u64;
+        }
+        Ok(sums
+            .into_values()
+            .map(|(mut averages, ms, input, output)| {
+                let runs = u64::from(averages.runs.max(1));
+                averages.mean_ms = ms / runs;
+                averages.mean_input_tokens = input / runs;
+                averages.mean_output_tokens = output / runs;
+                averages
+            })
+            .collect())
+    }
+
+    fn database_size(&self) -> Result<u64, TraceError> {
+        let conn = self.snapshot()?;
+        conn.query_row(
+                "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+                [],
+                |row| row.get::<_, i64>(0),
+            )
+            .map(|size| size.max(0) as u64)
+            .map_err(|e| TraceError::Database(e.to_string()))
+    }
+
+    fn flush(&self) -> Result<(), TraceError> {
+        let conn = self.conn.lock().map_err(|e| TraceError::Database(e.to_string()))?;
+        conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE); PRAGMA optimize;")
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn close(self: Arc<Self>) -> Result<(), TraceError> {
+        match Arc::try_unwrap(self) {
+            Ok(store) => SqliteTraceStore::close(store),
+            Err(shared) => shared.flush(),
+        }
+    }
+
+    fn insert_edge_value(&self, value: &EdgeValue) -> Result<(), TraceError> {
+        let conn = self.conn.lock().map_err(|e| TraceError::Database(e.to_string()))?;
+        conn.execute(
+                "INSERT OR REPLACE INTO edge_values (
+                    execution_id, edge_id, source_node, source_port,
+                    target_node, target_port, value_json, truncated,
+                    size_bytes, recorded_at
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                rusqlite::params![
+                    value.execution_id.to_string(),
+                    value.edge_id,
+                    value.source_node,
+                    value.source_port,
+                    value.target_node,
+                    value.target_port,
+                    value.value.to_string(),
+                    value.truncated,
+                    value.size_bytes as i64,
+                    value.recorded_at.to_rfc3339(),
+                ],
+            )
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn query_edge_value(
+        &self,
+        execution_id: Uuid,
+        edge_id: &str,
+    ) -> Result<Option<EdgeValue>, TraceError> {
+        let mut values = self.query_edge_values_where(
+            "execution_id = ?1 AND edge_id = ?2",
+            execution_id,
+            edge_id,
+        )?;
+        Ok(values.pop())
+    }
+
+    fn query_node_inputs(
+        &self,
+        execution_id: Uuid,
+        node_id: &str,
+    ) -> Result<Vec<EdgeValue>, TraceError> {
+        self.query_edge_values_where("execution_id = ?1 AND target_node = ?2", execution_id, node_id)
+    }
+}
+
+/// Internal row struct for edge value queries.
+struct RawEdgeValueRow {
+    execution_id: String,
+    edge_id: String,
+    source_node: String,
+    source_port: String,
+    target_node: String,
+    target_port: String,
+    value_json: String,
+    truncated: bool,
+    size_bytes: i64,
+    recorded_at: String,
+}
+
+fn raw_to_edge_value(raw: RawEdgeValueRow) -> Result<EdgeValue, TraceError> {
+    let parse_err = |field: &str, e: String| TraceError::Database(format!("{field}: {e}"));
+
+    Ok(EdgeValue {
+        execution_id: raw
+            .execution_id
+            .parse()
+            .map_err(|e: uuid::Error| parse_err("execution_id", e.to_string()))?,
+        edge_id: raw.edge_id,
+        source_node: raw.source_node,
+        source_port: raw.source_port,
+        target_node: raw.target_node,
+        target_port: raw.target_port,
+        value: serde_json::from_str(&raw.value_json)
+            .map_err(|e| parse_err("value_json", e.to_string()))?,
+        truncated: raw.truncated,
+        size_bytes: raw.size_bytes.max(0) as u64,
+        recorded_at: chrono::DateTime::parse_from_rfc3339(&raw.recorded_at)
+            .map(|dt| dt.with_timezone(&chrono::Utc))
+            .map_err(|e| parse_err("recorded_at", e.to_string()))?,
+    })
+}
+
+/// Internal row struct for execution queries.
+struct RawExecutionRow {
+    execution_id: String,
+    workflow_id: String,
+    started_at: String,
+    completed_at: Option<String>,
+    status: String,
+    total_nodes: u32,
+    completed_nodes: u32,
+    failed_nodes: u32,
+    cache_hits: u32,
+}
+
+/// Rows removed by [`TraceStore::prune_executions_before`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct PruneStats {
+    pub executions: usize,
+    pub spans: usize,
+    pub edge_values: usize,
+}
+
+/// An execution retention may prune.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct FinishedExecution {
+    pub execution_id: Uuid,
+    pub finished_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// What [`vacuum`] keeps. Every limit is optional; finished executions are
+/// pruned, the longest finished first, until all the limits set are met.
+/// Pending and running executions are never pruned.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RetentionPolicy {
+    /// Prune executions that finished more than this many days ago.
+    #[serde(default)]
+    pub max_age_days: Option<u32>,
+    /// Keep at most this many finished executions.
+    #[serde(default)]
+    pub max_executions: Option<usize>,
+    /// Prune until the database takes at most this many bytes.
+    #[serde(default)]
+    pub max_bytes: Option<u64>,
+    /// Before deleting them, write the pruned spans as gzip-compressed JSON
+    /// Lines to a new `traces-<time>.jsonl.gz` file in this directory. Each
+    /// pruned batch is its own gzip member; `gunzip` or a multi-member
+    /// decoder reads them back as one stream.
+    #[serde(default)]
+    pub archive_dir: Option<PathBuf>,
+}
+
+/// Outcome of [`vacuum`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct VacuumReport {
+    pub pruned: PruneStats,
+    /// The archive written, when spans were archived.
+    pub archive: Option<PathBuf>,
+    pub archived_spans: u64,
+    /// Database size in bytes before and after.
+    pub size_before: u64,
+    pub size_after: u64,
+}
+
+/// Apply `policy` to `store`: prune the executions it no longer keeps,
+/// archiving their spans first if asked to, and return the freed space to
+/// the filesystem. Blocking.
+///
+/// Age and count limits are applied first. A size limit then prunes in
+/// batches sized by how far over the limit the store still is.
+pub fn vacuum(
+    store: &dyn TraceStore,
+    policy: &RetentionPolicy,
+) -> Result<VacuumReport, TraceError> {
+    let size_before = store.database_size()?;
+    let mut report = VacuumReport {
+        size_before,
+        size_after: size_before,
+        ..Default::default()
+    };
+    let finished = store.query_finished_executions()?;
+    let mut expired = 0;
+    if let Some(days) = policy.max_age_days {
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(i64::from(days));
+        expired = finished.partition_point(|e| e.finished_at < cutoff);
+    }
+    if let Some(max) = policy.max_executions {
+        expired = expired.max(finished.len().saturating_sub(max));
+    }
+
+    let mut archive = None;
+    let mut pruner = Pruner {
+        store,
+        archive_dir: policy.archive_dir.as_deref(),
+        archive: &mut archive,
+        report: &mut report,
+    };
+    pruner.prune(&finished[..expired])?;
+    let mut size = size_before;
+    if expired > 0 {
+        size = store.database_size()?;
+    }
+    if let Some(max_bytes) = policy.max_bytes {
+        let mut next = expired;
+        while size > max_bytes && next < finished.len() {
+            let remaining = finished.len() - next;
+            let over = (size - max_bytes) as f64 / size as f64;
+            let batch = ((remaining as f64 * over).ceil() as usize).clamp(1, remaining);
+            pruner.prune(&finished[next..next + batch])?;
+            next += batch;
+            size = store.database_size()?;
+        }
+    }
+
+    report.archive = archive.map(|(path, _)| path);
+    report.size_after = size;
+    Ok(report)
+}
+
+/// Prunes batches of executions for [`vacuum`], archiving them first.
+struct Pruner<'a> {
+    store: &'a dyn TraceStore,
+    archive_dir: Option<&'a Path>,
+    archive: &'a mut Option<(PathBuf, std::fs::File)>,
+    report: &'a mut VacuumReport,
+}
+
+impl Pruner<'_> {
+    fn prune(&mut self, batch: &[FinishedExecution]) -> Result<(), TraceError> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+        if let Some(dir) = self.archive_dir {
+            if self.archive.is_none() {
+                *self.archive = Some(create_archive(dir)?);
+            }
+            if let Some((_, file)) = self.archive.as_mut() {
+                let export_err = |e: std::io::Error| TraceError::Export(e.to_string());
+                let mut out = flate2::write::GzEncoder::new(&*file, flate2::Compression::default());
+                for execution in batch {
+                    let query = TraceQuery {
+                        execution_id: Some(execution.execution_id),
+                        ..Default::default()
+                    };
+                    self.report.archived_spans +=
+                        crate::export::export_spans_jsonl(self.store, &query, &mut out)?;
+                }
+                // A complete member on disk before any row is deleted, so a
+                // crash mid-vacuum leaves a readable archive
+                out.finish().map_err(export_err)?;
+                file.sync_all().map_err(export_err)?;
+            }
+        }
+        let ids: Vec<Uuid> = batch.iter().map(|e| e.execution_id).collect();
+        let stats = self.store.prune_executions(&ids)?;
+        self.report.pruned.executions += stats.executions;
+        self.report.pruned.spans += stats.spans;
+        self.report.pruned.edge_values += stats.edge_values;
+        Ok(())
+    }
+}
+
+fn create_archive(dir: &Path) -> Result<(PathBuf, std::fs::File), TraceError> {
+    let export_err = |e: std::io::Error| TraceError::Export(e.to_string());
+    std::fs::create_dir_all(dir).map_err(export_err)?;
+    let path = dir.join(format!(
+        "traces-{}.jsonl.gz",
+        chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ")
+    ));
+    let file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .map_err(export_err)?;
+    Ok((path, file))
+}
+
+fn kind_to_str(kind: ArtifactKind) -> String {
+    serde_json::to_value(kind)
+        .ok()
+        .and_then(|v| v.as_str().map(String::from))
+        .unwrap_or_default()
+}
+
+fn store_manifest(
+    conn: &Connection,
+    execution_id: Uuid,
+    manifest: &ExecutionManifest,
+) -> Result<(), TraceError> {
+    conn.execute(
+            "DELETE FROM execution_artifacts WHERE execution_id = ?1",
+            rusqlite::params![execution_id.to_string()],
+        )
+        .map_err(|e| TraceError::Database(e.to_string()))?;
+    // The spec is stored once; manifests without it keep the stored one
+    if let Some(spec) = &manifest.spec {
+        conn.execute(
+                "INSERT OR REPLACE INTO execution_specs (execution_id, spec_json) VALUES (?1, ?2)",
+                rusqlite::params![
+                    execution_id.to_string(),
+                    serde_json::to_string(spec).unwrap_or_default(),
+                ],
+            )
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+    }
+    for (seq, artifact) in manifest.artifacts.iter().enumerate() {
+        conn.execute(
+                "INSERT OR REPLACE INTO execution_artifacts (
+                    execution_id, kind, target, node_ids_json, count, seq
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                rusqlite::params![
+                    execution_id.to_string(),
+                    kind_to_str(artifact.kind),
+                    artifact.target,
+                    serde_json::to_string(&artifact.node_ids).unwrap_or_default(),
+                    artifact.count,
+                    seq as i64,
+                ],
+            )
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+    }
+    Ok(())
+}
+
+fn load_violations(conn: &Connection, execution_id: Uuid) -> Result<Vec<ContractViolation>, TraceError> {
+    let json: Option<String> = conn
+        .query_row(
+            "SELECT violations_json FROM contract_violations WHERE execution_id = ?1",
+            rusqlite::params![execution_id.to_string()],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| TraceError::Database(e.to_string()))?;
+    match json {
+        Some(json) => serde_json::from_str(&json).map_err(|e| TraceError::Database(format!("contract_violations: {e}"))),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Fill in the manifests of `records`.
+fn attach_manifests(conn: &Connection, records: &mut [ExecutionRecord]) -> Result<(), TraceError> {
+    let ids: Vec<Uuid> = records.iter().map(|record| record.execution_id).collect();
+    let mut manifests = load_manifests(conn, &ids)?;
+    for record in records {
+        record.manifest = manifests.remove(&record.execution_id).unwrap_or_default();
+    }
+    Ok(())
+}
+
+/// `PRAGMA auto_vacuum` value of a database that frees pages on request.
+const INCREMENTAL_VACUUM: i64 = 2;
+
+/// Return the pages freed by a prune to the filesystem. An incremental
+/// vacuum only moves the free pages, so a size limit pruning batch after
+/// batch stays cheap; a database without it gets one full `VACUUM` that
+/// converts it.
+fn reclaim_space(conn: &Connection) -> Result<(), TraceError> {
+    let mode: i64 = conn
+        .query_row("PRAGMA auto_vacuum", [], |row| row.get(0))
+        .map_err(|e| TraceError::Database(e.to_string()))?;
+    let sql = if mode == INCREMENTAL_VACUUM {
+        "PRAGMA incremental_vacuum;"
+    } else {
+        "PRAGMA auto_vacuum = INCREMENTAL; VACUUM;"
+    };
+    conn.execute_batch(sql)
+        .map_err(|e| TraceError::Database(e.to_string()))
+}
+
+/// Executions whose manifests are read with one query.
+const MANIFEST_BATCH: usize = 500;
+
+fn load_manifest(conn: &Connection, execution_id: Uuid) -> Result<ExecutionManifest, TraceError> {
+    Ok(load_manifests(conn, &[execution_id])?.remove(&execution_id).unwrap_or_default())
+}
+
+/// Manifests of `execution_ids`, read in batches rather than one query per
+/// execution. Executions without artifacts or a snapshot get none.
+fn load_manifests(
+    conn: &Connection,
+    execution_ids: &[Uuid],
+) -> Result<HashMap<Uuid, ExecutionManifest>, TraceError> {
+    let mut manifests: HashMap<Uuid, ExecutionManifest> = HashMap::new();
+    for batch in execution_ids.chunks(MANIFEST_BATCH) {
+        let placeholders = vec!["?"; batch.len()].join(", ");
+        let keys: Vec<String> = batch.iter().map(Uuid::to_string).collect();
+
+        let mut stmt = conn.prepare(&format!(
+                "SELECT execution_id, kind, target, node_ids_json, count FROM execution_artifacts
+                 WHERE execution_id IN ({placeholders}) ORDER BY seq"
+            ))
+            .map_err(|e| TraceError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(&keys), |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, u32>(4)?,
+                ))
             })
             .map_err(|e| TraceError::Database(e.to_string()))?;
-
-        let mut spans = Vec::new();
         for row in rows {
-            let raw = row.map_err(|e| TraceError::Database(e.to_string()))?;
-            spans.push(raw_to_span(raw)?);
+            let (execution_id, kind, target, node_ids, count) =
+                row.map_err(|e| TraceError::Database(e.to_string()))?;
+            let execution_id = Uuid::parse_str(&execution_id).map_err(|e| TraceError::Database(e.to_string()))?;
+            let kind = serde_json::from_value(serde_json::Value::String(kind))
+                .map_err(|e| TraceError::Database(format!("kind: {e}")))?;
+            manifests.entry(execution_id).or_default().artifacts.push(ArtifactEntry {
+                kind,
+                target,
+                node_ids: serde_json::from_str(&node_ids).unwrap_or_default(),
+                count,
+            });
         }
-        Ok(spans)
-    }
 
-    /// Query a single span by its ID.
-    pub fn query_span(&self, span_id: Uuid) -> Result<Option<NodeSpan>, TraceError> {
-        let conn = self.conn.lock().map_err(|e| TraceError::Database(e.to_string()))?;
-        let mut stmt = conn.prepare(
-                "SELECT span_id, execution_id, node_id, tool_ref,
-                        input_json, output_json, config_json,
-                        started_at, completed_at, duration_ms,
-                        status, error, cache_hit, environment_json
-                 FROM traces WHERE span_id = ?1",
-            )
+        let mut stmt = conn.prepare(&format!(
+                "SELECT execution_id, spec_json FROM execution_specs WHERE execution_id IN ({placeholders})"
+            ))
             .map_err(|e| TraceError::Database(e.to_string()))?;
-
-        let mut rows = stmt
-            .query_map(rusqlite::params![span_id.to_string()], |row| {
-                Ok(RawSpanRow {
-                    span_id: row.get(0)?,
-                    execution_id: row.get(1)?,
-                    node_id: row.get(2)?,
-                    tool_ref: row.get(3)?,
-                    input_json: row.get(4)?,
-                    output_json: row.get(5)?,
-                    config_json: row.get(6)?,
-                    started_at: row.get(7)?,
-                    completed_at: row.get(8)?,
-                    duration_ms: row.get(9)?,
-                    status: row.get(10)?,
-                    error: row.get(11)?,
-                    cache_hit: row.get(12)?,
-                    environment_json: row.get(13)?,
-                })
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(&keys), |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
             })
             .map_err(|e| TraceError::Database(e.to_string()))?;
-
-        match rows.next() {
-            Some(row) => {
-                let raw = row.map_err(|e| TraceError::Database(e.to_string()))?;
-                Ok(Some(raw_to_span(raw)?))
+        for row in rows {
+            let (execution_id, json) = row.map_err(|e| TraceError::Database(e.to_string()))?;
+            let execution_id = Uuid::parse_str(&execution_id).map_err(|e| TraceError::Database(e.to_string()))?;
+            // A snapshot this build can't read leaves the manifest without one
+            // rather than failing the record
+            match serde_json::from_str(&json) {
+                Ok(spec) => manifests.entry(execution_id).or_default().spec = Some(spec),
+                Err(e) => tracing::warn!("Unreadable workflow snapshot for execution {execution_id}: {e}"),
             }
-            None => Ok(None),
         }
     }
+    Ok(manifests)
+}
+
+fn load_workspace(conn: &Connection, execution_id: Uuid) -> Result<Option<Uuid>, TraceError> {
+    let workspace_id: Option<String> = conn
+        .query_row(
+            "SELECT workspace_id FROM execution_workspaces WHERE execution_id = ?1",
+            rusqlite::params![execution_id.to_string()],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| TraceError::Database(e.to_string()))?;
+    workspace_id
+        .map(|id| id.parse())
+        .transpose()
+        .map_err(|e: uuid::Error| TraceError::Database(format!("workspace_id: {e}")))
+}
+
+fn raw_execution_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<RawExecutionRow> {
+    Ok(RawExecutionRow {
+        execution_id: row.get(0)?,
+        workflow_id: row.get(1)?,
+        started_at: row.get(2)?,
+        completed_at: row.get(3)?,
+        status: row.get(4)?,
+        total_nodes: row.get(5)?,
+        completed_nodes: row.get(6)?,
+        failed_nodes: row.get(7)?,
+        cache_hits: row.get(8)?,
+    })
+}
+
+fn raw_to_execution(raw: RawExecutionRow) -> Result<ExecutionRecord, TraceError> {
+    let parse_err = |field: &str, e: String| TraceError::Database(format!("{field}: {e}"));
+
+    Ok(ExecutionRecord {
+        execution_id: raw
+            .execution_id
+            .parse()
+            .map_err(|e: uuid::Error| parse_err("execution_id", e.to_string()))?,
+        workflow_id: raw
+            .workflow_id
+            .parse()
+            .map_err(|e: uuid::Error| parse_err("workflow_id", e.to_string()))?,
+        started_at: chrono::DateTime::parse_from_rfc3339(&raw.started_at)
+            .map(|dt| dt.with_timezone(&chrono::Utc))
+            .map_err(|e| parse_err("started_at", e.to_string()))?,
+        completed_at: raw
+            .completed_at
+            .map(|s| {
+                chrono::DateTime::parse_from_rfc3339(&s)
+                    .map(|dt| dt.with_timezone(&chrono::Utc))
+            })
+            .transpose()
+            .map_err(|e| parse_err("completed_at", e.to_string()))?,
+        status: serde_json::from_str(&raw.status).unwrap_or(ExecutionStatus::Pending),
+        total_nodes: raw.total_nodes,
+        completed_nodes: raw.completed_nodes,
+        failed_nodes: raw.failed_nodes,
+        cache_hits: raw.cache_hits,
+        workspace_id: None,
+        manifest: Default::default(),
+        contract_violations: Vec::new(),
+    })
 }
 
 /// Internal row struct for SQLite queries.
@@ -195,6 +1617,26 @@ struct RawSpanRow {
     environment_json: String,
 }
 
+/// Read a [`RawSpanRow`] from [`SPAN_COLUMNS`] starting at column `first`.
+fn raw_span_row(row: &rusqlite::Row<'_>, first: usize) -> rusqlite::Result<RawSpanRow> {
+    Ok(RawSpanRow {
+        span_id: row.get(first)?,
+        execution_id: row.get(first + 1)?,
+        node_id: row.get(first + 2)?,
+        tool_ref: row.get(first + 3)?,
+        input_json: row.get(first + 4)?,
+        output_json: row.get(first + 5)?,
+        config_json: row.get(first + 6)?,
+        started_at: row.get(first + 7)?,
+        completed_at: row.get(first + 8)?,
+        duration_ms: row.get(first + 9)?,
+        status: row.get(first + 10)?,
+        error: row.get(first + 11)?,
+        cache_hit: row.get(first + 12)?,
+        environment_json: row.get(first + 13)?,
+    })
+}
+
 fn raw_to_span(raw: RawSpanRow) -> Result<NodeSpan, TraceError> {
     let parse_err = |field: &str, e: String| TraceError::Database(format!("{field}: {e}"));
 
@@ -252,7 +1694,7 @@ mod tests {
 
     #[test]
     fn insert_and_query() {
-        let store = TraceStore::in_memory().unwrap();
+        let store = SqliteTraceStore::in_memory().unwrap();
         let exec_id = Uuid::new_v4();
 
         let span = NodeSpan {
@@ -293,4 +1735,793 @@ mod tests {
         let missing = store.query_span(Uuid::new_v4()).unwrap();
         assert!(missing.is_none());
     }
+
+    #[test]
+    fn token_usage_sums_llm_spans_per_model() {
+        let store = SqliteTraceStore::in_memory().unwrap();
+        let exec_id = Uuid::new_v4();
+        let span = |output: serde_json::Value, cache_hit: bool| NodeSpan {
+            span_id: Uuid::new_v4(),
+            execution_id: exec_id,
+            node_id: "chat".into(),
+            tool_ref: "core-tools/llm-chat@1.0.0".into(),
+            input_json: serde_json::json!({}),
+            output_json: Some(output),
+            config_json: serde_json::json!({}),
+            started_at: Utc::now(),
+            completed_at: Some(Utc::now()),
+            duration_ms: Some(5),
+            status: ExecutionStatus::Completed,
+            error: None,
+            cache_hit,
+            environment: ExecutionEnvironment {
+                platform_version: "0.1.0".into(),
+                os: "test".into(),
+                tool_version: "1.0.0".into(),
+                extra: Default::default(),
+            },
+        };
+        let chat = |model: &str, input: u64, output: u64| {
+            serde_json::json!({ "response": "ok", "model": model, "input_tokens": input, "output_tokens": output })
+        };
+        store.insert_span(&span(chat("gpt-4o", 100, 20), false)).unwrap();
+        store.insert_span(&span(chat("gpt-4o", 50, 10), false)).unwrap();
+        store.insert_span(&span(chat("claude-3-haiku", 7, 3), false)).unwrap();
+        store.insert_span(&span(chat("gpt-4o", 1000, 1000), true)).unwrap();
+        store.insert_span(&span(serde_json::json!({ "result": "hi" }), false)).unwrap();
+
+        let usage = store
+            .query_token_usage(Utc::now() - chrono::Duration::hours(1))
+            .unwrap();
+        assert_eq!(usage.len(), 2);
+        let gpt = usage.iter().find(|u| u.model == "gpt-4o").unwrap();
+        assert_eq!((gpt.input_tokens, gpt.output_tokens, gpt.calls), (150, 30, 2));
+        assert_eq!(gpt.day, Utc::now().format("%Y-%m-%d").to_string());
+
+        assert!(store.query_token_usage(Utc::now() + chrono::Duration::hours(1)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn tool_outcomes_merge_versions_and_skip_cache_hits() {
+        let store = SqliteTraceStore::in_memory().unwrap();
+        let span = |tool_ref: &str, status: ExecutionStatus, cache_hit: bool| NodeSpan {
+            span_id: Uuid::new_v4(),
+            execution_id: Uuid::new_v4(),
+            node_id: "n".into(),
+            tool_ref: tool_ref.into(),
+            input_json: serde_json::json!({}),
+            output_json: None,
+            config_json: serde_json::json!({}),
+            started_at: Utc::now(),
+            completed_at: Some(Utc::now()),
+            duration_ms: Some(5),
+            status,
+            error: None,
+            cache_hit,
+            environment: ExecutionEnvironment {
+                platform_version: "0.1.0".into(),
+                os: "test".into(),
+                tool_version: "1.0.0".into(),
+                extra: Default::default(),
+            },
+        };
+        store.insert_span(&span("t/llm@1.0.0", ExecutionStatus::Completed, false)).unwrap();
+        store.insert_span(&span("t/llm@1.1.0", ExecutionStatus::Failed, false)).unwrap();
+        store.insert_span(&span("t/llm@1.1.0", ExecutionStatus::CacheHit, true)).unwrap();
+        store.insert_span(&span("t/read@1.0.0", ExecutionStatus::Skipped, false)).unwrap();
+
+        let outcomes = store
+            .query_tool_outcomes(Utc::now() - chrono::Duration::hours(1))
+            .unwrap();
+        assert_eq!(
+            outcomes,
+            [ToolOutcomes {
+                tool_id: "t/llm".into(),
+                runs: 2,
+                failures: 1,
+            }]
+        );
+        assert_eq!(outcomes[0].success_rate(), 0.5);
+    }
+
+    #[test]
+    fn tool_averages_merge_versions_and_count_tokens() {
+        let store = SqliteTraceStore::in_memory().unwrap();
+        let span = |tool_ref: &str, ms: i64, output: serde_json::Value, cache_hit: bool| NodeSpan {
+            span_id: Uuid::new_v4(),
+            execution_id: Uuid::new_v4(),
+            node_id: "n".into(),
+            tool_ref: tool_ref.into(),
+            input_json: serde_json::json!({}),
+            output_json: Some(output),
+            config_json: serde_json::json!({}),
+            started_at: Utc::now(),
+            completed_at: Some(Utc::now()),
+            duration_ms: Some(ms),
+            status: if cache_hit { ExecutionStatus::CacheHit } else { ExecutionStatus::Completed },
+            error: None,
+            cache_hit,
+            environment: ExecutionEnvironment {
+                platform_version: "0.1.0".into(),
+                os: "test".into(),
+                tool_version: "1.0.0".into(),
+                extra: Default::default(),
+            },
+        };
+        let chat = |input: u64, output: u64| {
+            serde_json::json!({ "response": "ok", "input_tokens": input, "output_tokens": output })
+        };
+        store.insert_span(&span("t/llm@1.0.0", 1_000, chat(300, 100), false)).unwrap();
+        store.insert_span(&span("t/llm@1.1.0", 3_000, chat(500, 300), false)).unwrap();
+        store.insert_span(&span("t/llm@1.1.0", 1, chat(9_000, 9_000), true)).unwrap();
+        store.insert_span(&span("t/read@1.0.0", 20, serde_json::json!({ "content": "x" }), false)).unwrap();
+
+        let averages = store
+            .query_tool_averages(Utc::now() - chrono::Duration::hours(1))
+            .unwrap();
+        assert_eq!(
+            averages,
+            [
+                ToolAverages {
+                    tool_id: "t/llm".into(),
+                    runs: 2,
+                    mean_ms: 2_000,
+                    min_ms: 1_000,
+                    max_ms: 3_000,
+                    mean_input_tokens: 400,
+                    mean_output_tokens: 200,
+                },
+                ToolAverages {
+                    tool_id: "t/read".into(),
+                    runs: 1,
+                    mean_ms: 20,
+                    min_ms: 20,
+                    max_ms: 20,
+                    mean_input_tokens: 0,
+                    mean_output_tokens: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn edge_values_by_edge_and_target() {
+        let store = SqliteTraceStore::in_memory().unwrap();
+        let exec_id = Uuid::new_v4();
+        let edge = |id: &str, source: &str, value: serde_json::Value| EdgeValue {
+            execution_id: exec_id,
+            edge_id: id.into(),
+            source_node: source.into(),
+            source_port: "out".into(),
+            target_node: "join".into(),
+            target_port: "in".into(),
+            value,
+            truncated: false,
+            size_bytes: 1,
+            recorded_at: Utc::now(),
+        };
+        store.insert_edge_value(&edge("e1", "a", serde_json::json!(1))).unwrap();
+        store.insert_edge_value(&edge("e2", "b", serde_json::json!({"k": "v"}))).unwrap();
+
+        let found = store.query_edge_value(exec_id, "e2").unwrap().unwrap();
+        assert_eq!(found.source_node, "b");
+        assert_eq!(found.value, serde_json::json!({"k": "v"}));
+        assert!(store.query_edge_value(Uuid::new_v4(), "e2").unwrap().is_none());
+
+        let inputs = store.query_node_inputs(exec_id, "join").unwrap();
+        assert_eq!(inputs.len(), 2);
+        assert!(store.query_node_inputs(exec_id, "a").unwrap().is_empty());
+    }
+
+    #[test]
+    fn manifest_stored_with_execution() {
+        let store = SqliteTraceStore::in_memory().unwrap();
+        let mut record = ExecutionRecord {
+            execution_id: Uuid::new_v4(),
+            workflow_id: Uuid::new_v4(),
+            started_at: Utc::now(),
+            completed_at: Some(Utc::now()),
+            status: ExecutionStatus::Completed,
+            total_nodes: 2,
+            completed_nodes: 2,
+            failed_nodes: 0,
+            cache_hits: 0,
+            workspace_id: Some(Uuid::new_v4()),
+            manifest: Default::default(),
+            contract_violations: Vec::new(),
+        };
+        let mut spec = hb_core::graph::WorkflowSpec::default();
+        spec.meta.name = "Pour log summary".into();
+        record.manifest.spec = Some(spec);
+        store.upsert_execution(&record).unwrap();
+
+        // Artifacts come later, without the spec
+        record.manifest = Default::default();
+        record.manifest.record(ArtifactKind::FileWritten, "/out/report.pdf", "pdf");
+        record.manifest.record(ArtifactKind::IndexUpdated, "docs", "store");
+        store.upsert_execution(&record).unwrap();
+
+        let found = store.query_execution(record.execution_id).unwrap().unwrap();
+        assert_eq!(found.manifest.artifacts, record.manifest.artifacts);
+        assert_eq!(found.manifest.spec.unwrap().meta.name, "Pour log summary");
+        assert_eq!(found.workspace_id, record.workspace_id);
+
+        // A later record without a manifest or workspace keeps the stored ones.
+        record.manifest = Default::default();
+        record.workspace_id = None;
+        store.upsert_execution(&record).unwrap();
+        assert_eq!(store.query_manifest(record.execution_id).unwrap().artifacts.len(), 2);
+        let found = store.query_execution(record.execution_id).unwrap().unwrap();
+        assert!(found.workspace_id.is_some());
+
+        let touched = store
+            .query_executions_by_artifact("docs", Some(ArtifactKind::IndexUpdated), 10)
+            .unwrap();
+        assert_eq!(touched.len(), 1);
+        assert!(store
+            .query_executions_by_artifact("docs", Some(ArtifactKind::FileWritten), 10)
+            .unwrap()
+            .is_empty());
+
+        // Listed records each get their own manifest
+        let other = ExecutionRecord {
+            execution_id: Uuid::new_v4(),
+            started_at: Utc::now(),
+            manifest: Default::default(),
+            ..record.clone()
+        };
+        store.upsert_execution(&other).unwrap();
+        let listed = store.query_executions_by_workflow(record.workflow_id, 10).unwrap();
+        assert_eq!(listed.len(), 2);
+        for found in listed {
+            let expected = if found.execution_id == record.execution_id { 2 } else { 0 };
+            assert_eq!(found.manifest.artifacts.len(), expected);
+        }
+    }
+
+    #[test]
+    fn prune_removes_old_finished_executions() {
+        let store = SqliteTraceStore::in_memory().unwrap();
+        let now = Utc::now();
+        let record = |completed_days_ago: Option<i64>, status: ExecutionStatus| ExecutionRecord {
+            execution_id: Uuid::new_v4(),
+            workflow_id: Uuid::new_v4(),
+            started_at: now - chrono::Duration::days(60),
+            completed_at: completed_days_ago.map(|d| now - chrono::Duration::days(d)),
+            status,
+            total_nodes: 1,
+            completed_nodes: 1,
+            failed_nodes: 0,
+            cache_hits: 0,
+            workspace_id: None,
+            manifest: Default::default(),
+            contract_violations: Vec::new(),
+        };
+        let old = record(Some(40), ExecutionStatus::Completed);
+        let recent = record(Some(1), ExecutionStatus::Completed);
+        let running = record(None, ExecutionStatus::Running);
+        for r in [&old, &recent, &running] {
+            store.upsert_execution(r).unwrap();
+        }
+        let mut span = store_span(old.execution_id);
+        store.insert_span(&span).unwrap();
+        span.span_id = Uuid::new_v4();
+        span.execution_id = recent.execution_id;
+        store.insert_span(&span).unwrap();
+
+        let stats = store.prune_executions_before(now - chrono::Duration::days(30)).unwrap();
+        assert_eq!((stats.executions, stats.spans), (1, 1));
+        assert!(store.query_execution(old.execution_id).unwrap().is_none());
+        assert!(store.query_execution(recent.execution_id).unwrap().is_some());
+        assert!(store.query_execution(running.execution_id).unwrap().is_some());
+        assert!(store.database_size().unwrap() > 0);
+    }
+
+    #[test]
+    fn vacuum_applies_each_limit_and_archives_pruned_spans() {
+        use std::io::Read;
+
+        let store = SqliteTraceStore::in_memory().unwrap();
+        let now = Utc::now();
+        let mut ids = Vec::new();
+        for days_ago in [50, 40, 20, 10, 5, 1] {
+            let record = ExecutionRecord {
+                execution_id: Uuid::new_v4(),
+                workflow_id: Uuid::new_v4(),
+                started_at: now - chrono::Duration::days(days_ago),
+                completed_at: Some(now - chrono::Duration::days(days_ago)),
+                status: ExecutionStatus::Completed,
+                total_nodes: 1,
+                completed_nodes: 1,
+                failed_nodes: 0,
+                cache_hits: 0,
+                workspace_id: None,
+                manifest: Default::default(),
+                contract_violations: Vec::new(),
+            };
+            store.upsert_execution(&record).unwrap();
+            let mut span = store_span(record.execution_id);
+            span.output_json = Some(serde_json::json!({ "text": "x".repeat(20_000) }));
+            store.insert_span(&span).unwrap();
+            ids.push(record.execution_id);
+        }
+        let running = Uuid::new_v4();
+        store.insert_span(&store_span(running)).unwrap();
+        store
+            .upsert_execution(&ExecutionRecord {
+                execution_id: running,
+                workflow_id: Uuid::new_v4(),
+                started_at: now,
+                completed_at: None,
+                status: ExecutionStatus::Running,
+                total_nodes: 1,
+                completed_nodes: 0,
+                failed_nodes: 0,
+                cache_hits: 0,
+                workspace_id: None,
+                manifest: Default::default(),
+                contract_violations: Vec::new(),
+            })
+            .unwrap();
+        let finished = store.query_finished_executions().unwrap();
+        assert_eq!(finished.iter().map(|e| e.execution_id).collect::<Vec<_>>(), ids);
+
+        // Count: the two longest finished go, into an archive
+        let dir = std::env::temp_dir().join(format!("hb-trace-archive-{}", Uuid::new_v4()));
+        let report = vacuum(
+            &store,
+            &RetentionPolicy {
+                max_executions: Some(4),
+                archive_dir: Some(dir.clone()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!((report.pruned.executions, report.archived_spans), (2, 2));
+        let mut archived = String::new();
+        flate2::read::MultiGzDecoder::new(std::fs::File::open(report.archive.unwrap()).unwrap())
+            .read_to_string(&mut archived)
+            .unwrap();
+        let archived: Vec<NodeSpan> = archived
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(archived[0].execution_id, ids[0]);
+        assert_eq!(archived[1].execution_id, ids[1]);
+
+        // Age, without archiving
+        let report = vacuum(
+            &store,
+            &RetentionPolicy {
+                max_age_days: Some(7),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!((report.pruned.executions, report.archive), (2, None));
+        assert!(store.query_execution(ids[4]).unwrap().is_some());
+
+        // Size: prunes until under the limit, never the running execution
+        let report = vacuum(
+            &store,
+            &RetentionPolicy {
+                max_bytes: Some(1),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(report.pruned.executions, 2);
+        assert!(report.size_after < report.size_before);
+        assert!(store.query_finished_executions().unwrap().is_empty());
+        assert_eq!(store.query_spans_by_execution(running).unwrap().len(), 1);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn archive_is_readable_before_pruned_rows_are_gone() {
+        use std::io::Read;
+
+        let store = SqliteTraceStore::in_memory().unwrap();
+        for _ in 0..2 {
+            let execution_id = Uuid::new_v4();
+            store
+                .upsert_execution(&ExecutionRecord {
+                    execution_id,
+                    workflow_id: Uuid::new_v4(),
+                    started_at: Utc::now(),
+                    completed_at: Some(Utc::now()),
+                    status: ExecutionStatus::Completed,
+                    total_nodes: 1,
+                    completed_nodes: 1,
+                    failed_nodes: 0,
+                    cache_hits: 0,
+                    workspace_id: None,
+                    manifest: Default::default(),
+                    contract_violations: Vec::new(),
+                })
+                .unwrap();
+            store.insert_span(&store_span(execution_id)).unwrap();
+        }
+        let finished = store.query_finished_executions().unwrap();
+        let dir = std::env::temp_dir().join(format!("hb-trace-archive-{}", Uuid::new_v4()));
+        let read_back = || {
+            let path = std::fs::read_dir(&dir).unwrap().next().unwrap().unwrap().path();
+            let mut text = String::new();
+            flate2::read::MultiGzDecoder::new(std::fs::File::open(path).unwrap())
+                .read_to_string(&mut text)
+                .unwrap();
+            text.lines()
+                .map(|line| serde_json::from_str::<NodeSpan>(line).unwrap().execution_id)
+                .collect::<Vec<_>>()
+        };
+
+        let mut archive = None;
+        let mut report = VacuumReport::default();
+        let mut pruner = Pruner {
+            store: &store,
+            archive_dir: Some(&dir),
+            archive: &mut archive,
+            report: &mut report,
+        };
+        // Each batch is a finished gzip member while the file is still open
+        pruner.prune(&finished[..1]).unwrap();
+        assert_eq!(read_back(), vec![finished[0].execution_id]);
+        assert!(store.query_execution(finished[0].execution_id).unwrap().is_none());
+        pruner.prune(&finished[1..]).unwrap();
+        assert_eq!(
+            read_back(),
+            vec![finished[0].execution_id, finished[1].execution_id]
+        );
+        assert!(archive.is_some());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    /// Minimal map-backed store, to exercise the trait apart from SQLite.
+    /// Only what the round trip below uses is kept.
+    #[derive(Default)]
+    struct MemoryTraceStore {
+        spans: Mutex<Vec<NodeSpan>>,
+        executions: Mutex<BTreeMap<Uuid, ExecutionRecord>>,
+        edge_values: Mutex<Vec<EdgeValue>>,
+    }
+
+    impl TraceStore for MemoryTraceStore {
+        fn insert_span(&self, span: &NodeSpan) -> Result<(), TraceError> {
+            self.spans.lock().unwrap().push(span.clone());
+            Ok(())
+        }
+        fn query_spans_by_execution(&self, execution_id: Uuid) -> Result<Vec<NodeSpan>, TraceError> {
+            let spans = self.spans.lock().unwrap();
+            Ok(spans.iter().filter(|s| s.execution_id == execution_id).cloned().collect())
+        }
+        fn query_span(&self, span_id: Uuid) -> Result<Option<NodeSpan>, TraceError> {
+            Ok(self.spans.lock().unwrap().iter().find(|s| s.span_id == span_id).cloned())
+        }
+        fn scan_spans(
+            &self,
+            query: &TraceQuery,
+            visit: &mut dyn FnMut(NodeSpan) -> Result<(), TraceError>,
+        ) -> Result<u64, TraceError> {
+            let spans = self.spans.lock().unwrap().clone();
+            let mut count = 0;
+            for span in spans {
+                if query.execution_id.is_none_or(|id| id == span.execution_id) {
+                    visit(span)?;
+                    count += 1;
+                }
+            }
+            Ok(count)
+        }
+        fn upsert_execution(&self, record: &ExecutionRecord) -> Result<(), TraceError> {
+            self.executions.lock().unwrap().insert(record.execution_id, record.clone());
+            Ok(())
+        }
+        fn mark_execution_interrupted(&self, execution_id: Uuid) -> Result<bool, TraceError> {
+            let mut executions = self.executions.lock().unwrap();
+            match executions.get_mut(&execution_id) {
+                Some(record) if record.status == ExecutionStatus::Running => {
+                    record.status = ExecutionStatus::Interrupted;
+                    record.completed_at = Some(chrono::Utc::now());
+                    Ok(true)
+                }
+                _ => Ok(false),
+            }
+        }
+        fn query_execution(&self, execution_id: Uuid) -> Result<Option<ExecutionRecord>, TraceError> {
+            Ok(self.executions.lock().unwrap().get(&execution_id).cloned())
+        }
+        fn query_executions_by_status(&self, _: &ExecutionStatus) -> Result<Vec<ExecutionRecord>, TraceError> {
+            Ok(Vec::new())
+        }
+        fn query_executions_by_workflow(&self, _: Uuid, _: usize) -> Result<Vec<ExecutionRecord>, TraceError> {
+            Ok(Vec::new())
+        }
+        fn query_manifest(&self, _: Uuid) -> Result<ExecutionManifest, TraceError> {
+            Ok(ExecutionManifest::default())
+        }
+        fn query_executions_by_artifact(
+            &self,
+            _: &str,
+            _: Option<ArtifactKind>,
+            _: usize,
+        ) -> Result<Vec<ExecutionRecord>, TraceError> {
+            Ok(Vec::new())
+        }
+        fn insert_scheduler_metrics(&self, _: &SchedulerMetrics) -> Result<(), TraceError> {
+            Ok(())
+        }
+        fn query_scheduler_metrics(&self, _: Uuid) -> Result<Option<SchedulerMetrics>, TraceError> {
+            Ok(None)
+        }
+        fn prune_executions_before(&self, _: chrono::DateTime<chrono::Utc>) -> Result<PruneStats, TraceError> {
+            Ok(PruneStats::default())
+        }
+        fn query_finished_executions(&self) -> Result<Vec<FinishedExecution>, TraceError> {
+            Ok(Vec::new())
+        }
+        fn prune_executions(&self, execution_ids: &[Uuid]) -> Result<PruneStats, TraceError> {
+            let mut stats = PruneStats::default();
+            let mut executions = self.executions.lock().unwrap();
+            let mut spans = self.spans.lock().unwrap();
+            let mut edge_values = self.edge_values.lock().unwrap();
+            for id in execution_ids {
+                stats.executions += usize::from(executions.remove(id).is_some());
+            }
+            let before = (spans.len(), edge_values.len());
+            spans.retain(|s| !execution_ids.contains(&s.execution_id));
+            edge_values.retain(|v| !execution_ids.contains(&v.execution_id));
+            stats.spans = before.0 - spans.len();
+            stats.edge_values = before.1 - edge_values.len();
+            Ok(stats)
+        }
+        fn query_token_usage(&self, _: chrono::DateTime<chrono::Utc>) -> Result<Vec<TokenUsage>, TraceError> {
+            Ok(Vec::new())
+        }
+        fn query_tool_outcomes(&self, _: chrono::DateTime<chrono::Utc>) -> Result<Vec<ToolOutcomes>, TraceError> {
+            Ok(Vec::new())
+        }
+        fn query_tool_averages(&self, _: chrono::DateTime<chrono::Utc>) -> Result<Vec<ToolAverages>, TraceError> {
+            Ok(Vec::new())
+        }
+        fn database_size(&self) -> Result<u64, TraceError> {
+            Ok(0)
+        }
+        fn flush(&self) -> Result<(), TraceError> {
+            Ok(())
+        }
+        fn close(self: Arc<Self>) -> Result<(), TraceError> {
+            Ok(())
+        }
+        fn insert_edge_value(&self, value: &EdgeValue) -> Result<(), TraceError> {
+            let mut values = self.edge_values.lock().unwrap();
+            values.retain(|v| (v.execution_id, &v.edge_id) != (value.execution_id, &value.edge_id));
+            values.push(value.clone());
+            Ok(())
+        }
+        fn query_edge_value(&self, execution_id: Uuid, edge_id: &str) -> Result<Option<EdgeValue>, TraceError> {
+            let values = self.edge_values.lock().unwrap();
+            Ok(values
+                .iter()
+                .find(|v| v.execution_id == execution_id && v.edge_id == edge_id)
+                .cloned())
+        }
+        fn query_node_inputs(&self, execution_id: Uuid, node_id: &str) -> Result<Vec<EdgeValue>, TraceError> {
+            let values = self.edge_values.lock().unwrap();
+            Ok(values
+                .iter()
+                .filter(|v| v.execution_id == execution_id && v.target_node == node_id)
+                .cloned()
+                .collect())
+        }
+    }
+
+    /// Record a run and read it back, only through the trait.
+    fn round_trip(store: &dyn TraceStore) {
+        let (run, other) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut spans = vec![store_span(run), store_span(run), store_span(other)];
+        spans[1].node_id = "n2".into();
+        spans[1].output_json = Some(serde_json::json!({ "text": "기초 타설" }));
+        for span in &spans {
+            store.insert_span(span).unwrap();
+        }
+        let record = ExecutionRecord {
+            execution_id: run,
+            workflow_id: Uuid::new_v4(),
+            started_at: Utc::now(),
+            completed_at: None,
+            status: ExecutionStatus::Running,
+            total_nodes: 2,
+            completed_nodes: 0,
+            failed_nodes: 0,
+            cache_hits: 0,
+            workspace_id: None,
+            manifest: Default::default(),
+            contract_violations: Vec::new(),
+        };
+        store.upsert_execution(&record).unwrap();
+        store
+            .insert_edge_value(&EdgeValue {
+                execution_id: run,
+                edge_id: "n1-n2".into(),
+                source_node: "n1".into(),
+                source_port: "out".into(),
+                target_node: "n2".into(),
+                target_port: "in".into(),
+                value: serde_json::json!("기초"),
+                truncated: false,
+                size_bytes: 8,
+                recorded_at: Utc::now(),
+            })
+            .unwrap();
+        store.flush().unwrap();
+
+        let read = store.query_spans_by_execution(run).unwrap();
+        assert_eq!(read.iter().map(|s| s.span_id).collect::<Vec<_>>(), [spans[0].span_id, spans[1].span_id]);
+        let span = store.query_span(spans[1].span_id).unwrap().unwrap();
+        assert_eq!(span.output_json, spans[1].output_json);
+        let mut jsonl = Vec::new();
+        let query = TraceQuery {
+            execution_id: Some(run),
+            ..Default::default()
+        };
+        assert_eq!(crate::export::export_spans_jsonl(store, &query, &mut jsonl).unwrap(), 2);
+        assert_eq!(String::from_utf8(jsonl).unwrap().lines().count(), 2);
+
+        assert!(store.mark_execution_interrupted(run).unwrap());
+        assert!(!store.mark_execution_interrupted(run).unwrap());
+        let stored = store.query_execution(run).unwrap().unwrap();
+        assert_eq!((stored.workflow_id, stored.status), (record.workflow_id, ExecutionStatus::Interrupted));
+        assert_eq!(store.query_edge_value(run, "n1-n2").unwrap().unwrap().value, "기초");
+        assert_eq!(store.query_node_inputs(run, "n2").unwrap().len(), 1);
+
+        let stats = store.prune_executions(&[run]).unwrap();
+        assert_eq!((stats.executions, stats.spans, stats.edge_values), (1, 2, 1));
+        assert!(store.query_execution(run).unwrap().is_none());
+        assert_eq!(store.query_spans_by_execution(other).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn pruning_converts_older_databases_to_incremental_vacuum() {
+        let dir = std::env::temp_dir().join(format!("hb-trace-vacuum-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("traces.db");
+        // A database from before auto-vacuum, with a table already in it
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch("CREATE TABLE legacy (id INTEGER);")
+            .unwrap();
+        let auto_vacuum = |store: &SqliteTraceStore| -> i64 {
+            let conn = store.conn.lock().unwrap();
+            conn.query_row("PRAGMA auto_vacuum", [], |row| row.get(0)).unwrap()
+        };
+
+        let store = SqliteTraceStore::open(&path).unwrap();
+        assert_eq!(auto_vacuum(&store), 0);
+        let span = store_span(Uuid::new_v4());
+        store.insert_span(&span).unwrap();
+        store.prune_executions(&[span.execution_id]).unwrap();
+        assert_eq!(auto_vacuum(&store), INCREMENTAL_VACUUM);
+        drop(store);
+
+        let fresh = SqliteTraceStore::open(&dir.join("fresh.db")).unwrap();
+        assert_eq!(auto_vacuum(&fresh), INCREMENTAL_VACUUM);
+        drop(fresh);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn backends_round_trip_through_the_trait() {
+        round_trip(&MemoryTraceStore::default());
+
+        let dir = std::env::temp_dir().join(format!("hb-trace-backend-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let store: Box<dyn TraceStore> = Box::new(SqliteTraceStore::open(&dir.join("traces.db")).unwrap());
+        round_trip(store.as_ref());
+        drop(store);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    fn store_span(execution_id: Uuid) -> NodeSpan {
+        NodeSpan {
+            span_id: Uuid::new_v4(),
+            execution_id,
+            node_id: "n1".into(),
+            tool_ref: "core/echo@1.0".into(),
+            input_json: serde_json::json!({}),
+            output_json: None,
+            config_json: serde_json::json!({}),
+            started_at: Utc::now(),
+            completed_at: None,
+            duration_ms: None,
+            status: ExecutionStatus::Completed,
+            error: None,
+            cache_hit: false,
+            environment: ExecutionEnvironment {
+                platform_version: "0.1.0".into(),
+                os: "test".into(),
+                tool_version: "1.0.0".into(),
+                extra: Default::default(),
+            },
+        }
+    }
+
+    #[test]
+    fn execution_record_marked_interrupted() {
+        let store = SqliteTraceStore::in_memory().unwrap();
+        let record = ExecutionRecord {
+            execution_id: Uuid::new_v4(),
+            workflow_id: Uuid::new_v4(),
+            started_at: Utc::now(),
+            completed_at: None,
+            status: ExecutionStatus::Running,
+            total_nodes: 3,
+            completed_nodes: 1,
+            failed_nodes: 0,
+            cache_hits: 0,
+            workspace_id: None,
+            manifest: Default::default(),
+            contract_violations: Vec::new(),
+        };
+        store.upsert_execution(&record).unwrap();
+
+        assert!(store.mark_execution_interrupted(record.execution_id).unwrap());
+        assert!(!store.mark_execution_interrupted(Uuid::new_v4()).unwrap());
+
+        let interrupted = store
+            .query_executions_by_status(&ExecutionStatus::Interrupted)
+            .unwrap();
+        assert_eq!(interrupted.len(), 1);
+        assert_eq!(interrupted[0].execution_id, record.execution_id);
+        assert_eq!(interrupted[0].completed_nodes, 1);
+        assert!(interrupted[0].completed_at.is_some());
+
+        // Runs that already finished keep their status
+        let finished = ExecutionRecord {
+            execution_id: Uuid::new_v4(),
+            status: ExecutionStatus::Completed,
+            ..record
+        };
+        store.upsert_execution(&finished).unwrap();
+        assert!(!store.mark_execution_interrupted(finished.execution_id).unwrap());
+        assert_eq!(
+            store
+                .query_execution(finished.execution_id)
+                .unwrap()
+                .unwrap()
+                .status,
+            ExecutionStatus::Completed
+        );
+
+        store.close().unwrap();
+    }
+
+    #[test]
+    fn reads_see_a_snapshot_and_do_not_block_writes() {
+        let dir = std::env::temp_dir().join(format!("hb-trace-snapshot-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let store = SqliteTraceStore::open(&dir.join("traces.db")).unwrap();
+        let execution_id = Uuid::new_v4();
+        store.insert_span(&store_span(execution_id)).unwrap();
+
+        let snapshot = store.snapshot().unwrap();
+        let count = |conn: &Connection| -> i64 {
+            conn.query_row(
+                "SELECT COUNT(*) FROM traces WHERE execution_id = ?1",
+                [execution_id.to_string()],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+        assert_eq!(count(&snapshot), 1);
+        // The writer commits while the snapshot is open
+        store.insert_span(&store_span(execution_id)).unwrap();
+        assert_eq!(count(&snapshot), 1);
+        // Other queries use another reader and see the new span
+        assert_eq!(store.query_spans_by_execution(execution_id).unwrap().len(), 2);
+        drop(snapshot);
+        assert_eq!(count(&store.snapshot().unwrap()), 2);
+
+        store.close().unwrap();
+        let _ = std::fs::remove_dir_all(dir);
+    }
 }
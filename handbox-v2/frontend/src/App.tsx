@@ -45,6 +45,7 @@ import { WorkflowMarketplace } from './components/marketplace'
 // Types for GIS/IFC data
 import type { GeoJsonFeatureCollection } from './types/gis'
 import type { IfcModel } from './types/ifc'
+import { errorMessage } from '@/types/error'
 
 export function App() {
   const [showExecPanel, setShowExecPanel] = useState(false)
@@ -140,7 +141,7 @@ export function App() {
       setGisLoading(false)
     } catch (error) {
       console.error('Failed to load GIS file:', error)
-      setGisError(error instanceof Error ? error.message : String(error))
+      setGisError(errorMessage(error))
       setGisLoading(false)
     }
   }
@@ -206,7 +207,7 @@ export function App() {
       setIfcLoading(false)
     } catch (error) {
       console.error('Failed to load IFC file:', error)
-      setIfcError(error instanceof Error ? error.message : String(error))
+      setIfcError(errorMessage(error))
       setIfcLoading(false)
     }
   }
@@ -199,6 +199,7 @@ import 'prismjs/components/prism-toml'
 import 'prismjs/components/prism-markdown'
 import 'prismjs/components/prism-jsx'
 import 'prismjs/components/prism-tsx'
+import { errorMessage } from '@/types/error'
 
 const langAliases: Record<string, string> = {
   ts: 'typescript', js: 'javascript', py: 'python', rs: 'rust',
@@ -1170,7 +1171,7 @@ export function AgentChatPanel({ onClose }: AgentChatPanelProps) {
         if (last && last.role === 'assistant') {
           return [...prev.slice(0, -1), {
             ...last,
-            content: `${e instanceof Error ? e.message : String(e)}`,
+            content: errorMessage(e),
             status: 'error' as const,
             streamingText: undefined,
           }]
@@ -294,7 +294,7 @@ function SetupView({
               {addingModel && (
                 <div className="absolute top-full left-0 right-0 mt-1 bg-zinc-800 border border-zinc-700 rounded shadow-lg z-10">
                   <div className="flex border-b border-zinc-700">
-                    {(['openai', 'anthropic', 'bedrock', 'local'] as LLMProvider[]).map(
+                    {(['openai', 'anthropic', 'bedrock', 'local', 'llamacpp'] as LLMProvider[]).map(
                       (provider) => (
                         <button
                           key={provider}
@@ -504,6 +504,7 @@ function ProviderBadge({ provider }: { provider: LLMProvider }) {
     anthropic: 'bg-orange-500/20 text-orange-400',
     bedrock: 'bg-amber-500/20 text-amber-400',
     local: 'bg-purple-500/20 text-purple-400',
+    llamacpp: 'bg-sky-500/20 text-sky-400',
   }
 
   return (
@@ -6,6 +6,7 @@
 import { useState } from 'react'
 import { Sparkles, Loader2, Wand2 } from 'lucide-react'
 import { safeInvoke } from '@/utils/tauri'
+import { errorMessage } from '@/types/error'
 
 interface CompilerPanelProps {
   onClose: () => void
@@ -44,7 +45,7 @@ export function CompilerPanel({ onClose, onGenerated }: CompilerPanelProps) {
       onClose()
     } catch (err) {
       // Show actual error message
-      const errorMsg = err instanceof Error ? err.message : String(err)
+      const errorMsg = errorMessage(err)
       console.error('[CompilerPanel] Compilation failed:', errorMsg)
       setError(`컴파일 실패: ${errorMsg}`)
     } finally {
@@ -13,7 +13,8 @@ import {
   Scissors, Merge, FileCode, Regex,
   Braces, GitBranch, Table, Filter,
   GitFork, Repeat, GitMerge, Timer,
-  HardDrive, SearchCode, ArrowUpDown,
+  HardDrive, SearchCode, ArrowUpDown, Network, Share2, ScanText, ListOrdered, GitCompare, Layers,
+  Mic, Users, ClipboardList, Landmark,
   FileDown, Sheet, CircleDot,
   Brain, Search, Type, Database, Download, FileInput,
   Sparkles, Terminal, Globe,
@@ -28,7 +29,8 @@ const iconMap: Record<string, React.ComponentType<{ size?: number; className?: s
   Scissors, Merge, FileCode, Regex,
   Braces, GitBranch, Table, Filter,
   GitFork, Repeat, GitMerge, Timer,
-  HardDrive, SearchCode, ArrowUpDown,
+  HardDrive, SearchCode, ArrowUpDown, Network, Share2, ScanText, ListOrdered, GitCompare, Layers,
+  Mic, Users, ClipboardList, Landmark,
   FileDown, Sheet, CircleDot,
   Brain, Search, Type, Database, Download, FileInput,
   Sparkles, Terminal, Globe,
@@ -16,11 +16,12 @@ import {
   Scissors, Merge, FileCode, Regex,
   Braces, GitBranch, Table, Filter,
   GitFork, Repeat, GitMerge, Timer,
-  HardDrive, SearchCode, ArrowUpDown,
+  HardDrive, SearchCode, ArrowUpDown, Network, Share2, ScanText, ListOrdered, GitCompare, Layers,
+  Mic, Users, ClipboardList, Landmark,
   FileDown, Sheet, CircleDot,
   Brain, Search, Type, Database, Download, FileInput,
   Sparkles, Terminal, Globe,
-  CheckCircle2, XCircle, Loader2, Clock,
+  CheckCircle2, XCircle, Loader2, Clock, PauseCircle,
   Archive, Code, Clipboard, PlayCircle,
 } from 'lucide-react'
 
@@ -30,7 +31,8 @@ const iconMap: Record<string, React.ComponentType<{ size?: number; className?: s
   Scissors, Merge, FileCode, Regex,
   Braces, GitBranch, Table, Filter,
   GitFork, Repeat, GitMerge, Timer,
-  HardDrive, SearchCode, ArrowUpDown,
+  HardDrive, SearchCode, ArrowUpDown, Network, Share2, ScanText, ListOrdered, GitCompare, Layers,
+  Mic, Users, ClipboardList, Landmark,
   FileDown, Sheet, CircleDot,
   Brain, Search, Type, Database, Download, FileInput,
   Sparkles, Terminal, Globe,
@@ -69,6 +71,12 @@ function getStatusStyles(status: ExecutionStatus | undefined): {
         glowColor: 'none',
         statusIcon: <Clock size={14} className="text-purple-400" />,
       }
+    case 'paused':
+      return {
+        borderColor: '#f59e0b',
+        glowColor: 'none',
+        statusIcon: <PauseCircle size={14} className="text-amber-500" />,
+      }
     case 'skipped':
     case 'cancelled':
       return {
@@ -131,6 +139,8 @@ function PrimitiveNodeInner({ id, data, selected }: NodeProps) {
     return HEADER_HEIGHT + PORTS_PADDING_TOP + inputsHeight + separatorOffset + (index * PORT_ROW_HEIGHT) + (PORT_ROW_HEIGHT / 2)
   }
 
+  const progress = nodeDetail?.progress
+
   // Preview data
   const hasResult = nodeDetail && (nodeStatus === 'completed' || nodeStatus === 'cache_hit' || nodeStatus === 'failed')
 
@@ -217,6 +227,40 @@ function PrimitiveNodeInner({ id, data, selected }: NodeProps) {
           ))}
         </div>
 
+        {/* Progress reported by long-running tools */}
+        {nodeStatus === 'running' && progress && (
+          <div className="px-3 pb-2" title={progress.step ?? undefined}>
+            <div className="h-1 rounded bg-neutral-800 overflow-hidden">
+              {progress.fraction != null ? (
+                <div
+                  className="h-full bg-blue-500 transition-all duration-200"
+                  style={{ width: `${Math.round(progress.fraction * 100)}%` }}
+                />
+              ) : (
+                <div className="h-full w-1/3 bg-blue-500 animate-pulse" />
+              )}
+            </div>
+            <div className="flex justify-between text-[9px] text-neutral-500 mt-0.5">
+              <span className="truncate">{progress.step}</span>
+              {progress.fraction != null && <span>{Math.round(progress.fraction * 100)}%</span>}
+            </div>
+          </div>
+        )}
+
+        {/* Tail of the output streamed so far */}
+        {nodeStatus === 'running' && nodeDetail?.streamed && (
+          <div className="px-3 pb-2 text-[9px] text-neutral-400 font-mono whitespace-pre-wrap break-words max-h-16 overflow-hidden">
+            {Object.values(nodeDetail.streamed).join('').slice(-240)}
+          </div>
+        )}
+
+        {/* Silent for longer than the liveness policy allows */}
+        {nodeStatus === 'running' && nodeDetail?.stalled && (
+          <div className="px-3 pb-2 text-[9px] text-amber-400 truncate" title={nodeDetail.stalled}>
+            {nodeDetail.stalled}
+          </div>
+        )}
+
         {/* Rich inline result preview */}
         {hasResult && (
           <NodeInlinePreview
@@ -284,7 +284,7 @@ function NodeModelOverrideSection({ nodeId }: { nodeId: string }) {
   const currentModel = getModelForNode(nodeId)
   const hasOverride = !!currentOverride
 
-  const providers: LLMProvider[] = ['openai', 'anthropic', 'bedrock', 'local']
+  const providers: LLMProvider[] = ['openai', 'anthropic', 'bedrock', 'local', 'llamacpp']
 
   const handleProviderChange = (provider: LLMProvider) => {
     const modelId = selectedModel[provider] || models[provider][0]?.id || ''
@@ -301,6 +301,7 @@ function NodeModelOverrideSection({ nodeId }: { nodeId: string }) {
     anthropic: 'bg-orange-500/20 text-orange-400 border-orange-500/50',
     bedrock: 'bg-amber-500/20 text-amber-400 border-amber-500/50',
     local: 'bg-purple-500/20 text-purple-400 border-purple-500/50',
+    llamacpp: 'bg-sky-500/20 text-sky-400 border-sky-500/50',
   }
 
   return (
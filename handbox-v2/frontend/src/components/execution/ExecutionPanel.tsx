@@ -279,6 +279,9 @@ function OutputPreview({
 
   const outputText = formatValue(detail.output)
   const inputText = formatValue(detail.input)
+  // File reads flag bytes that weren't valid in the encoding they were read with
+  const decoding = detail.output as { had_errors?: boolean; encoding?: string } | null | undefined
+  const hadDecodeErrors = typeof decoding === 'object' && decoding?.had_errors === true
 
   return (
     <div className="flex-1 flex flex-col overflow-hidden">
@@ -320,6 +323,17 @@ function OutputPreview({
           </div>
         )}
 
+        {/* Decoding */}
+        {hadDecodeErrors && (
+          <div className="p-2.5 bg-amber-950/40 border border-amber-900/50 rounded-md flex items-center gap-2">
+            <AlertTriangle size={12} className="text-amber-400" />
+            <span className="text-[11px] text-amber-300">
+              Some bytes weren't valid {decoding?.encoding ?? 'text'} and were replaced with �. Try setting the
+              encoding in the node's config.
+            </span>
+          </div>
+        )}
+
         {/* Output */}
         {detail.output !== undefined && (
           <div className="space-y-1.5">
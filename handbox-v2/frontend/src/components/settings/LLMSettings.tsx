@@ -1,13 +1,15 @@
 /**
- * LLM Settings Dialog — configure LLM providers (Bedrock, OpenAI, Anthropic, Local).
+ * LLM Settings Dialog — configure LLM providers (Bedrock, OpenAI, Anthropic,
+ * Local, llama.cpp).
  */
 
 import { useState, useEffect } from 'react'
 import { useLLMStore } from '@/stores/llmStore'
 import type { LLMProvider } from '@/types'
+import { LocalModelManager } from './LocalModelManager'
 import {
   Cloud, Key, Server, Check, X, Loader2,
-  ChevronDown, RefreshCw, Settings, Sparkles,
+  ChevronDown, RefreshCw, Settings, Sparkles, HardDrive,
 } from 'lucide-react'
 
 interface LLMSettingsProps {
@@ -31,6 +33,8 @@ export function LLMSettings({ isOpen, onClose }: LLMSettingsProps) {
     setOpenAIApiKey,
     setAnthropicApiKey,
     setLocalEndpoint,
+    setServiceKey,
+    removeServiceKey,
     testConnection,
     loadModels,
     loadCredentialStatus,
@@ -43,6 +47,8 @@ export function LLMSettings({ isOpen, onClose }: LLMSettingsProps) {
   const [openaiApiKey, setOpenaiApiKeyInput] = useState('')
   const [anthropicApiKey, setAnthropicApiKeyInput] = useState('')
   const [localEndpoint, setLocalEndpointInput] = useState(config.local.endpoint)
+  const [serviceKeyName, setServiceKeyName] = useState('DATA_GO_KR_SERVICE_KEY')
+  const [serviceKey, setServiceKeyInput] = useState('')
 
   // Load models and credential status when dialog opens
   useEffect(() => {
@@ -101,11 +107,24 @@ export function LLMSettings({ isOpen, onClose }: LLMSettingsProps) {
     await loadCredentialStatus()
   }
 
+  const handleSaveServiceKey = async () => {
+    if (await setServiceKey(serviceKeyName.trim(), serviceKey.trim())) {
+      setServiceKeyInput('') // Clear input after save
+    }
+    await loadCredentialStatus()
+  }
+
+  const handleRemoveServiceKey = async (name: string) => {
+    await removeServiceKey(name)
+    await loadCredentialStatus()
+  }
+
   const providers: { id: LLMProvider; label: string; icon: React.ReactNode }[] = [
     { id: 'bedrock', label: 'AWS Bedrock', icon: <Cloud size={14} /> },
     { id: 'openai', label: 'OpenAI', icon: <Key size={14} /> },
     { id: 'anthropic', label: 'Anthropic', icon: <Sparkles size={14} /> },
     { id: 'local', label: 'Local (Ollama)', icon: <Server size={14} /> },
+    { id: 'llamacpp', label: 'llama.cpp', icon: <HardDrive size={14} /> },
   ]
 
   const hasSavedCredential = (provider: LLMProvider) => {
@@ -357,6 +376,16 @@ export function LLMSettings({ isOpen, onClose }: LLMSettingsProps) {
             </div>
           )}
 
+          {/* llama.cpp Settings */}
+          {activeProvider === 'llamacpp' && (
+            <LocalModelManager
+              onModelsChanged={() => {
+                loadModels('llamacpp')
+                testConnection('llamacpp')
+              }}
+            />
+          )}
+
           {/* Model Selection */}
           <div className="pt-4 border-t border-neutral-800">
             <div className="flex items-center justify-between mb-2">
@@ -415,6 +444,55 @@ export function LLMSettings({ isOpen, onClose }: LLMSettingsProps) {
                 : `Connection failed: ${connectionStatus[activeProvider]?.error}`}
             </div>
           )}
+
+          {/* API Keys for tools such as public-api-fetch */}
+          <div className="pt-4 border-t border-neutral-800 space-y-2">
+            <label className="block text-xs font-medium text-neutral-400">API Keys</label>
+            <p className="text-xs text-neutral-500">
+              Service keys for data APIs, e.g. the data.go.kr key used by Public API Fetch.
+            </p>
+            {credentialStatus?.service_keys.map((name) => (
+              <div key={name} className="flex items-center justify-between text-xs text-neutral-300">
+                <span className="font-mono">{name}</span>
+                <button
+                  onClick={() => handleRemoveServiceKey(name)}
+                  className="text-neutral-500 hover:text-red-400"
+                  title="Remove key"
+                >
+                  <X size={12} />
+                </button>
+              </div>
+            ))}
+            <div className="flex gap-2">
+              <input
+                type="text"
+                value={serviceKeyName}
+                onChange={(e) => setServiceKeyName(e.target.value)}
+                placeholder="DATA_GO_KR_SERVICE_KEY"
+                className="w-1/2 px-3 py-2 text-xs font-mono bg-neutral-800 border border-neutral-700
+                         rounded-md text-neutral-200 focus:outline-none focus:ring-1
+                         focus:ring-violet-500 placeholder-neutral-600"
+              />
+              <input
+                type="password"
+                value={serviceKey}
+                onChange={(e) => setServiceKeyInput(e.target.value)}
+                placeholder="Service key"
+                className="flex-1 px-3 py-2 text-xs bg-neutral-800 border border-neutral-700
+                         rounded-md text-neutral-200 focus:outline-none focus:ring-1
+                         focus:ring-violet-500 placeholder-neutral-600"
+              />
+              <button
+                onClick={handleSaveServiceKey}
+                disabled={!serviceKeyName.trim() || !serviceKey.trim()}
+                className="flex items-center gap-1 px-3 py-2 text-xs font-medium bg-violet-600
+                         hover:bg-violet-500 text-white rounded-md transition-colors disabled:opacity-50"
+              >
+                <Key size={12} />
+                Save
+              </button>
+            </div>
+          </div>
         </div>
 
         {/* Footer */}
@@ -0,0 +1,233 @@
+/**
+ * LocalModelManager — list, download, verify and delete GGUF models for the
+ * llama.cpp provider, with a memory fit check per model.
+ */
+
+import { useState, useEffect, useCallback } from 'react'
+import { safeInvoke, safeListen } from '@/utils/tauri'
+import { errorMessage } from '@/types/error'
+import type { LocalModel, SystemMemory, VerifyReport, NodeProgress } from '@/types'
+import { Download, Trash2, ShieldCheck, Loader2, Cpu, AlertTriangle } from 'lucide-react'
+
+interface LocalModelManagerProps {
+  /** Called after the installed model list changes */
+  onModelsChanged?: () => void
+}
+
+const formatBytes = (bytes: number | null | undefined) => {
+  if (bytes == null) return '?'
+  const gib = bytes / 1024 ** 3
+  return gib >= 1 ? `${gib.toFixed(1)} GiB` : `${(bytes / 1024 ** 2).toFixed(0)} MiB`
+}
+
+const fitStyles: Record<LocalModel['memory']['fit'], string> = {
+  gpu: 'text-emerald-400',
+  cpu: 'text-amber-400',
+  insufficient: 'text-red-400',
+  unknown: 'text-neutral-500',
+}
+
+export function LocalModelManager({ onModelsChanged }: LocalModelManagerProps) {
+  const [models, setModels] = useState<LocalModel[]>([])
+  const [memory, setMemory] = useState<SystemMemory | null>(null)
+  const [url, setUrl] = useState('')
+  const [sha256, setSha256] = useState('')
+  const [downloading, setDownloading] = useState(false)
+  const [progress, setProgress] = useState<NodeProgress | null>(null)
+  const [busyId, setBusyId] = useState<string | null>(null)
+  const [verified, setVerified] = useState<Record<string, VerifyReport>>({})
+  const [error, setError] = useState<string | null>(null)
+
+  const refresh = useCallback(async () => {
+    try {
+      const [list, mem] = await Promise.all([
+        safeInvoke<LocalModel[]>('list_local_models'),
+        safeInvoke<SystemMemory>('get_system_memory'),
+      ])
+      setModels(list ?? [])
+      setMemory(mem)
+    } catch (e) {
+      setError(errorMessage(e))
+    }
+  }, [])
+
+  useEffect(() => {
+    refresh()
+    let unlisten: (() => void) | undefined
+    safeListen<{ url: string; progress: NodeProgress }>('local-model-download', (event) => {
+      setProgress(event.payload.progress)
+    }).then((fn) => { unlisten = fn })
+    return () => unlisten?.()
+  }, [refresh])
+
+  const handleDownload = async () => {
+    setDownloading(true)
+    setProgress(null)
+    setError(null)
+    try {
+      await safeInvoke<LocalModel>('download_local_model', {
+        url: url.trim(),
+        sha256: sha256.trim() || null,
+      })
+      setUrl('')
+      setSha256('')
+      await refresh()
+      onModelsChanged?.()
+    } catch (e) {
+      setError(errorMessage(e))
+    } finally {
+      setDownloading(false)
+      setProgress(null)
+    }
+  }
+
+  const handleVerify = async (id: string) => {
+    setBusyId(id)
+    setError(null)
+    try {
+      const report = await safeInvoke<VerifyReport>('verify_local_model', { id })
+      if (report) setVerified((prev) => ({ ...prev, [id]: report }))
+    } catch (e) {
+      setError(errorMessage(e))
+    } finally {
+      setBusyId(null)
+    }
+  }
+
+  const handleDelete = async (id: string) => {
+    setBusyId(id)
+    setError(null)
+    try {
+      await safeInvoke<boolean>('delete_local_model', { id })
+      await refresh()
+      onModelsChanged?.()
+    } catch (e) {
+      setError(errorMessage(e))
+    } finally {
+      setBusyId(null)
+    }
+  }
+
+  return (
+    <div className="space-y-4">
+      <p className="text-xs text-neutral-500">
+        Run GGUF models with llama.cpp — no Ollama needed. Requires the <code>llama-server</code>{' '}
+        binary on PATH (or <code>LLAMA_SERVER_PATH</code>).
+      </p>
+
+      {memory && (
+        <div className="flex items-center gap-2 text-[11px] text-neutral-500">
+          <Cpu size={12} />
+          RAM {formatBytes(memory.available_ram_bytes)} free of {formatBytes(memory.total_ram_bytes)}
+          {memory.gpus.map((gpu) => (
+            <span key={gpu.name}>· {gpu.name} {formatBytes(gpu.free_bytes)} free</span>
+          ))}
+          {memory.unified_memory && <span>· unified memory</span>}
+        </div>
+      )}
+
+      {/* Installed models */}
+      <div className="space-y-2">
+        {models.length === 0 && (
+          <p className="text-xs text-neutral-600 italic">No models installed.</p>
+        )}
+        {models.map((m) => {
+          const report = verified[m.id]
+          return (
+            <div key={m.id} className="p-2 rounded-md bg-neutral-800/60 border border-neutral-800">
+              <div className="flex items-center justify-between gap-2">
+                <div className="min-w-0">
+                  <div className="text-xs text-neutral-200 truncate">{m.info?.name ?? m.id}</div>
+                  <div className="text-[10px] text-neutral-500 truncate">
+                    {m.id} · {formatBytes(m.size_bytes)}
+                    {m.info?.file_type && ` · ${m.info.file_type}`}
+                    {m.info?.architecture && ` · ${m.info.architecture}`}
+                  </div>
+                </div>
+                <div className="flex items-center gap-1 shrink-0">
+                  <button
+                    onClick={() => handleVerify(m.id)}
+                    disabled={busyId === m.id}
+                    title="Verify checksum and header"
+                    className="p-1 rounded text-neutral-500 hover:text-violet-400 hover:bg-neutral-700 disabled:opacity-50"
+                  >
+                    {busyId === m.id ? <Loader2 size={12} className="animate-spin" /> : <ShieldCheck size={12} />}
+                  </button>
+                  <button
+                    onClick={() => handleDelete(m.id)}
+                    disabled={busyId === m.id}
+                    title="Delete model"
+                    className="p-1 rounded text-neutral-500 hover:text-red-400 hover:bg-neutral-700 disabled:opacity-50"
+                  >
+                    <Trash2 size={12} />
+                  </button>
+                </div>
+              </div>
+              <div className={`mt-1 text-[10px] ${fitStyles[m.memory.fit]}`}>
+                {m.error ? (
+                  <span className="text-red-400 flex items-center gap-1">
+                    <AlertTriangle size={10} /> {m.error}
+                  </span>
+                ) : (
+                  m.memory.message
+                )}
+              </div>
+              {report && (
+                <div className={`mt-1 text-[10px] ${report.valid ? 'text-emerald-400' : 'text-red-400'}`}>
+                  {report.valid
+                    ? report.checksum_ok ? 'Checksum verified' : 'Header OK (no checksum recorded)'
+                    : report.error ?? `Checksum mismatch (expected ${report.expected_sha256})`}
+                </div>
+              )}
+            </div>
+          )
+        })}
+      </div>
+
+      {/* Download */}
+      <div className="space-y-2 pt-2 border-t border-neutral-800">
+        <label className="block text-xs font-medium text-neutral-400">Download GGUF model</label>
+        <input
+          type="text"
+          value={url}
+          onChange={(e) => setUrl(e.target.value)}
+          placeholder="https://huggingface.co/.../resolve/main/model.Q4_K_M.gguf"
+          className="w-full px-3 py-2 text-xs bg-neutral-800 border border-neutral-700
+                   rounded-md text-neutral-200 focus:outline-none focus:ring-1
+                   focus:ring-violet-500 placeholder-neutral-600"
+        />
+        <input
+          type="text"
+          value={sha256}
+          onChange={(e) => setSha256(e.target.value)}
+          placeholder="SHA-256 (optional)"
+          className="w-full px-3 py-2 text-xs bg-neutral-800 border border-neutral-700
+                   rounded-md text-neutral-200 focus:outline-none focus:ring-1
+                   focus:ring-violet-500 placeholder-neutral-600"
+        />
+        {downloading && progress && (
+          <div className="h-1 rounded bg-neutral-800 overflow-hidden">
+            <div
+              className="h-full bg-violet-500 transition-all"
+              style={{ width: `${Math.round((progress.fraction ?? 0) * 100)}%` }}
+            />
+          </div>
+        )}
+        <button
+          onClick={handleDownload}
+          disabled={downloading || !url.trim()}
+          className="flex items-center justify-center gap-2 w-full px-4 py-2 text-xs font-medium
+                   bg-violet-600 hover:bg-violet-500 text-white rounded-md transition-colors
+                   disabled:opacity-50"
+        >
+          {downloading ? <Loader2 size={12} className="animate-spin" /> : <Download size={12} />}
+          {downloading && progress?.current != null
+            ? `Downloading ${formatBytes(progress.current)}${progress.total ? ` / ${formatBytes(progress.total)}` : ''}`
+            : 'Download'}
+        </button>
+      </div>
+
+      {error && <p className="text-xs text-red-400">{error}</p>}
+    </div>
+  )
+}
@@ -7,6 +7,7 @@ import {
   Server, Plus, X, Loader2, RefreshCw,
   Trash2, Wifi, WifiOff,
 } from 'lucide-react'
+import { errorMessage } from '@/types/error'
 
 interface MCPServer {
   id: string
@@ -101,7 +102,7 @@ export function MCPConnections({ isOpen, onClose }: MCPConnectionsProps) {
       ))
     } catch (error) {
       setServers(servers.map(s =>
-        s.id === serverId ? { ...s, status: 'error' as const, error: String(error) } : s
+        s.id === serverId ? { ...s, status: 'error' as const, error: errorMessage(error) } : s
       ))
     }
   }
@@ -6,7 +6,7 @@ import { useState, useEffect } from 'react'
 import { invoke } from '@tauri-apps/api/core'
 import {
   Package, Download, Check, X, Loader2,
-  RefreshCw, ExternalLink, ChevronRight,
+  RefreshCw, ExternalLink, ChevronRight, AlertTriangle,
 } from 'lucide-react'
 
 interface PackManifest {
@@ -20,6 +20,19 @@ interface PackManifest {
   installed?: boolean
 }
 
+/** Report from `plan_pack_migration` / `apply_pack_migration`. */
+interface MigrationReport {
+  pack_id: string
+  from_version: string
+  to_version: string
+  workflows: {
+    workflow_id: string
+    workflow_name: string
+    changes: { node_id: string | null; description: string }[]
+    issues: { node_id: string; code: string; message: string }[]
+  }[]
+}
+
 interface PackManagerProps {
   isOpen: boolean
   onClose: () => void
@@ -30,6 +43,9 @@ export function PackManager({ isOpen, onClose }: PackManagerProps) {
   const [isLoading, setIsLoading] = useState(false)
   const [selectedPack, setSelectedPack] = useState<PackManifest | null>(null)
   const [installStatus, setInstallStatus] = useState<Record<string, 'installing' | 'installed' | 'error'>>({})
+  const [migration, setMigration] = useState<MigrationReport | null>(null)
+  const [migrationState, setMigrationState] = useState<'idle' | 'working' | 'applied'>('idle')
+  const [migrationError, setMigrationError] = useState<string | null>(null)
 
   useEffect(() => {
     if (isOpen) {
@@ -141,6 +157,30 @@ export function PackManager({ isOpen, onClose }: PackManagerProps) {
     }
   }
 
+  const selectPack = (pack: PackManifest) => {
+    setSelectedPack(pack)
+    setMigration(null)
+    setMigrationState('idle')
+    setMigrationError(null)
+  }
+
+  const runMigration = async (packId: string, apply: boolean) => {
+    setMigrationState('working')
+    setMigrationError(null)
+    try {
+      const report = await invoke<MigrationReport>(
+        apply ? 'apply_pack_migration' : 'plan_pack_migration',
+        apply ? { packId, workflowIds: null } : { packId },
+      )
+      setMigration(report)
+      setMigrationState(apply ? 'applied' : 'idle')
+    } catch (error) {
+      setMigration(null)
+      setMigrationError(String(error))
+      setMigrationState('idle')
+    }
+  }
+
   if (!isOpen) return null
 
   return (
@@ -186,7 +226,7 @@ export function PackManager({ isOpen, onClose }: PackManagerProps) {
                 {packs.map((pack) => (
                   <button
                     key={pack.id}
-                    onClick={() => setSelectedPack(pack)}
+                    onClick={() => selectPack(pack)}
                     className={`w-full flex items-center gap-2 px-4 py-2.5 text-left transition-colors ${
                       selectedPack?.id === pack.id
                         ? 'bg-neutral-800'
@@ -266,6 +306,64 @@ export function PackManager({ isOpen, onClose }: PackManagerProps) {
                   </a>
                 )}
 
+                {selectedPack.installed && (
+                  <div className="pt-3 border-t border-neutral-800 space-y-2">
+                    <div className="flex items-center justify-between">
+                      <h4 className="text-xs font-medium text-neutral-400">Workflow migration</h4>
+                      <div className="flex items-center gap-1.5">
+                        <button
+                          onClick={() => runMigration(selectedPack.id, false)}
+                          disabled={migrationState === 'working'}
+                          className="px-2 py-0.5 rounded text-[10px] bg-neutral-800 hover:bg-neutral-700 text-neutral-300 disabled:opacity-50"
+                        >
+                          Check workflows
+                        </button>
+                        {migration && migrationState !== 'applied' && migration.workflows.length > 0 && (
+                          <button
+                            onClick={() => runMigration(selectedPack.id, true)}
+                            disabled={migrationState === 'working'}
+                            className="px-2 py-0.5 rounded text-[10px] bg-violet-600 hover:bg-violet-500 text-white disabled:opacity-50"
+                          >
+                            Apply migration
+                          </button>
+                        )}
+                      </div>
+                    </div>
+                    {migrationError && (
+                      <p className="text-[10px] text-neutral-500">{migrationError}</p>
+                    )}
+                    {migration && (
+                      <div className="space-y-2">
+                        <p className="text-[10px] text-neutral-500">
+                          {migration.from_version} → {migration.to_version}:{' '}
+                          {migration.workflows.length === 0
+                            ? 'no saved workflows use this pack'
+                            : `${migration.workflows.length} workflow(s) ${migrationState === 'applied' ? 'migrated' : 'affected'}`}
+                        </p>
+                        {migration.workflows.map((w) => (
+                          <div key={w.workflow_id} className="p-2 rounded bg-neutral-800/50 space-y-1">
+                            <div className="text-xs text-neutral-300">{w.workflow_name}</div>
+                            {w.changes.map((c, i) => (
+                              <div key={i} className="flex items-center gap-1 text-[10px] text-neutral-400">
+                                <Check size={10} className="text-emerald-500 shrink-0" />
+                                {c.node_id && <span className="text-neutral-500">{c.node_id}:</span>}
+                                {c.description}
+                              </div>
+                            ))}
+                            {w.issues.map((issue, i) => (
+                              <div key={i} className="flex items-center gap-1 text-[10px] text-amber-400">
+                                <AlertTriangle size={10} className="shrink-0" />
+                                <span className="text-amber-600">{issue.node_id}:</span>
+                                {issue.message}
+                              </div>
+                            ))}
+                          </div>
+                        ))}
+                      </div>
+                    )}
+                  </div>
+                )}
+
                 {selectedPack.tools && selectedPack.tools.length > 0 && (
                   <div className="pt-3 border-t border-neutral-800">
                     <h4 className="text-xs font-medium text-neutral-400 mb-2">
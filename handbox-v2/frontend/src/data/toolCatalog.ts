@@ -34,8 +34,8 @@ export interface ToolDef {
   category: string
   description: string
   icon: string // lucide icon name
-  inputs: { name: string; type: string; required?: boolean; description?: string }[]
-  outputs: { name: string; type: string; description?: string }[]
+  inputs: { name: string; type: string; required?: boolean; description?: string; variadic?: boolean }[]
+  outputs: { name: string; type: string; description?: string; variadic?: boolean }[]
   configFields: ConfigField[]
 }
 
@@ -47,6 +47,21 @@ export interface ToolCategory {
   tools: ToolDef[]
 }
 
+/** Text encoding override shared by the file and CSV readers. */
+const encodingField: ConfigField = {
+  name: 'encoding',
+  type: 'select',
+  label: 'Encoding',
+  default: 'auto',
+  options: [
+    { value: 'auto', label: 'Auto-detect' },
+    { value: 'utf-8', label: 'UTF-8' },
+    { value: 'cp949', label: 'EUC-KR / CP949' },
+    { value: 'utf-16le', label: 'UTF-16 LE' },
+    { value: 'utf-16be', label: 'UTF-16 BE' },
+  ],
+}
+
 export const toolCategories: ToolCategory[] = [
   {
     id: 'io',
@@ -61,7 +76,37 @@ export const toolCategories: ToolCategory[] = [
         description: 'Read text from a file',
         icon: 'FileText',
         inputs: [{ name: 'path', type: 'string' }],
-        outputs: [{ name: 'content', type: 'string' }, { name: 'size', type: 'number' }],
+        outputs: [
+          { name: 'content', type: 'string' },
+          { name: 'size', type: 'number' },
+          { name: 'encoding', type: 'string' },
+          { name: 'had_errors', type: 'boolean' },
+        ],
+        configFields: [
+          {
+            name: 'file_path',
+            type: 'file',
+            label: 'File',
+            default: '',
+            fileFilters: [
+              { name: 'Text Files', extensions: ['txt', 'md', 'json', 'csv', 'xml', 'yaml', 'yml'] },
+              { name: 'All Files', extensions: ['*'] },
+            ],
+          },
+          encodingField,
+        ],
+      },
+      {
+        id: 'file-split',
+        label: 'File Split',
+        category: 'io',
+        description: 'Split a file into chunks as it is read',
+        icon: 'Scissors',
+        inputs: [{ name: 'path', type: 'string' }],
+        outputs: [
+          { name: 'chunks', type: 'array' },
+          { name: 'headings', type: 'array' },
+        ],
         configFields: [
           {
             name: 'file_path',
@@ -73,18 +118,22 @@ export const toolCategories: ToolCategory[] = [
               { name: 'All Files', extensions: ['*'] },
             ],
           },
+          { name: 'chunk_size', type: 'number', label: 'Chunk Size', default: 1000 },
+          { name: 'overlap', type: 'number', label: 'Overlap', default: 100 },
           {
-            name: 'encoding',
+            name: 'strategy',
             type: 'select',
-            label: 'Encoding',
-            default: 'utf-8',
+            label: 'Strategy',
+            default: 'structure',
             options: [
-              { value: 'utf-8', label: 'UTF-8' },
-              { value: 'utf-16', label: 'UTF-16' },
-              { value: 'ascii', label: 'ASCII' },
-              { value: 'euc-kr', label: 'EUC-KR' },
+              { value: 'structure', label: 'Document structure' },
+              { value: 'separator', label: 'Separator' },
+              { value: 'characters', label: 'Characters' },
             ],
           },
+          { name: 'separator', type: 'string', label: 'Separator', default: '' },
+          { name: 'max_chunks', type: 'number', label: 'Max Chunks', default: 100000 },
+          encodingField,
         ],
       },
       {
@@ -113,6 +162,48 @@ export const toolCategories: ToolCategory[] = [
           },
         ],
       },
+      {
+        id: 'pdf-table-extract',
+        label: 'PDF Table Extract',
+        category: 'io',
+        description: 'Extract tables from a PDF with their headers and rows',
+        icon: 'Table',
+        inputs: [{ name: 'path', type: 'string' }],
+        outputs: [
+          { name: 'tables', type: 'array' },
+          { name: 'table', type: 'json' },
+          { name: 'count', type: 'number' },
+        ],
+        configFields: [
+          {
+            name: 'file_path',
+            type: 'file',
+            label: 'PDF File',
+            default: '',
+            fileFilters: [
+              { name: 'PDF Documents', extensions: ['pdf'] },
+            ],
+          },
+          {
+            name: 'pages',
+            type: 'string',
+            label: 'Pages (e.g., "1-3, 5"; empty for all)',
+            default: '',
+          },
+          {
+            name: 'header',
+            type: 'boolean',
+            label: 'First Row Is Header',
+            default: true,
+          },
+          {
+            name: 'llm_repair',
+            type: 'boolean',
+            label: 'Repair Uncertain Tables with LLM',
+            default: false,
+          },
+        ],
+      },
       {
         id: 'files-read',
         label: 'Multi-File Read',
@@ -311,14 +402,61 @@ export const toolCategories: ToolCategory[] = [
           },
         ],
       },
+      {
+        id: 'summarize-long',
+        label: 'Summarize Long Document',
+        category: 'ai',
+        description: 'Summarize a document of any length chunk by chunk',
+        icon: 'Layers',
+        inputs: [
+          { name: 'text', type: 'string' },
+          { name: 'path', type: 'string' },
+        ],
+        outputs: [
+          { name: 'summary', type: 'string' },
+          { name: 'chunk_summaries', type: 'array' },
+          { name: 'chunks', type: 'number' },
+          { name: 'levels', type: 'number' },
+        ],
+        configFields: [
+          {
+            name: 'file_path',
+            type: 'file',
+            label: 'Document',
+            default: '',
+            fileFilters: [
+              { name: 'Documents', extensions: ['pdf', 'txt', 'md'] },
+            ],
+          },
+          { name: 'chunk_size', type: 'number', label: 'Chunk Size (chars)', default: 8000 },
+          { name: 'overlap', type: 'number', label: 'Overlap (chars)', default: 200 },
+          { name: 'chunk_prompt', type: 'string', label: 'Chunk Instructions', default: '' },
+          { name: 'final_prompt', type: 'string', label: 'Final Instructions', default: '' },
+          { name: 'style', type: 'string', label: 'Style', default: 'concise' },
+          {
+            name: 'language',
+            type: 'select',
+            label: 'Language',
+            default: 'auto',
+            options: [
+              { value: 'auto', label: 'Same as input' },
+              { value: 'korean', label: 'Korean' },
+              { value: 'english', label: 'English' },
+              { value: 'japanese', label: 'Japanese' },
+              { value: 'chinese', label: 'Chinese' },
+            ],
+          },
+          { name: 'group_chars', type: 'number', label: 'Merge Group Size (chars)', default: 12000 },
+        ],
+      },
       {
         id: 'embedding',
         label: 'Embedding',
         category: 'ai',
         description: 'Generate vector embeddings from text',
         icon: 'Waypoints',
-        inputs: [{ name: 'text', type: 'string' }],
-        outputs: [{ name: 'vector', type: 'array' }],
+        inputs: [{ name: 'text', type: 'string' }, { name: 'texts', type: 'array' }],
+        outputs: [{ name: 'vector', type: 'array' }, { name: 'vectors', type: 'array' }],
         configFields: [
           {
             name: 'model',
@@ -349,10 +487,26 @@ export const toolCategories: ToolCategory[] = [
         description: 'Split text into chunks',
         icon: 'Scissors',
         inputs: [{ name: 'text', type: 'string' }],
-        outputs: [{ name: 'chunks', type: 'array' }],
+        outputs: [
+          { name: 'chunks', type: 'array' },
+          { name: 'headings', type: 'array' },
+        ],
         configFields: [
           { name: 'chunk_size', type: 'number', label: 'Chunk Size', default: 1000 },
           { name: 'overlap', type: 'number', label: 'Overlap', default: 200 },
+          {
+            name: 'strategy',
+            type: 'select',
+            label: 'Strategy',
+            default: 'characters',
+            options: [
+              { value: 'structure', label: 'Document structure' },
+              { value: 'separator', label: 'Separator' },
+              { value: 'characters', label: 'Characters' },
+            ],
+          },
+          { name: 'separator', type: 'string', label: 'Separator', default: '' },
+          { name: 'max_chunks', type: 'number', label: 'Max Chunks', default: 100000 },
         ],
       },
       {
@@ -382,8 +536,83 @@ export const toolCategories: ToolCategory[] = [
         description: 'Extract text using regular expressions',
         icon: 'Regex',
         inputs: [{ name: 'text', type: 'string' }],
-        outputs: [{ name: 'matches', type: 'array' }],
-        configFields: [{ name: 'pattern', type: 'string', label: 'Pattern', default: '' }],
+        outputs: [
+          { name: 'matches', type: 'array' },
+          { name: 'captures', type: 'array' },
+        ],
+        configFields: [
+          { name: 'pattern', type: 'string', label: 'Pattern', default: '', required: true },
+          { name: 'global', type: 'boolean', label: 'All Matches', default: true },
+          { name: 'multiline', type: 'boolean', label: 'Multiline (^ $ per line)', default: false },
+          { name: 'case_insensitive', type: 'boolean', label: 'Ignore Case', default: false },
+          { name: 'dot_all', type: 'boolean', label: 'Dot Matches Newline', default: false },
+        ],
+      },
+      {
+        id: 'clause-split',
+        label: 'Clause Split',
+        category: 'text',
+        description: 'Split a contract or regulation into numbered clauses',
+        icon: 'ListOrdered',
+        inputs: [
+          { name: 'text', type: 'string' },
+          { name: 'path', type: 'string' },
+        ],
+        outputs: [
+          { name: 'clauses', type: 'array' },
+          { name: 'count', type: 'number' },
+        ],
+        configFields: [
+          {
+            name: 'file_path',
+            type: 'file',
+            label: 'Document',
+            default: '',
+            fileFilters: [
+              { name: 'Documents', extensions: ['pdf', 'txt', 'md'] },
+            ],
+          },
+        ],
+      },
+      {
+        id: 'doc-compare',
+        label: 'Document Compare',
+        category: 'text',
+        description: 'Compare two document versions clause by clause',
+        icon: 'GitCompare',
+        inputs: [
+          { name: 'old', type: 'string' },
+          { name: 'new', type: 'string' },
+        ],
+        outputs: [
+          { name: 'changes', type: 'array' },
+          { name: 'summary', type: 'json' },
+          { name: 'report', type: 'string' },
+          { name: 'count', type: 'number' },
+        ],
+        configFields: [
+          {
+            name: 'old_path',
+            type: 'file',
+            label: 'Earlier Version',
+            default: '',
+            fileFilters: [
+              { name: 'Documents', extensions: ['pdf', 'txt', 'md'] },
+            ],
+          },
+          {
+            name: 'new_path',
+            type: 'file',
+            label: 'Later Version',
+            default: '',
+            fileFilters: [
+              { name: 'Documents', extensions: ['pdf', 'txt', 'md'] },
+            ],
+          },
+          { name: 'threshold', type: 'number', label: 'Match Threshold (0-1)', default: 0.5 },
+          { name: 'include_unchanged', type: 'boolean', label: 'Include Unchanged', default: false },
+          { name: 'llm_review', type: 'boolean', label: 'LLM Review of Major Changes', default: false },
+        ],
       },
     ],
   },
@@ -421,17 +650,155 @@ export const toolCategories: ToolCategory[] = [
         icon: 'Table',
         inputs: [{ name: 'path', type: 'string' }],
         outputs: [{ name: 'rows', type: 'array' }],
-        configFields: [{ name: 'delimiter', type: 'string', label: 'Delimiter', default: ',' }],
+        configFields: [
+          { name: 'delimiter', type: 'string', label: 'Delimiter', default: ',' },
+          encodingField,
+        ],
       },
       {
         id: 'data-filter',
         label: 'Filter',
         category: 'data',
-        description: 'Filter array items by condition',
+        description: 'Filter, sort and page array items or table rows',
         icon: 'Filter',
-        inputs: [{ name: 'items', type: 'array' }],
-        outputs: [{ name: 'filtered', type: 'array' }],
-        configFields: [{ name: 'condition', type: 'string', label: 'Condition', default: '' }],
+        inputs: [{ name: 'items', type: 'any', description: 'Array, or a table with rows' }],
+        outputs: [
+          { name: 'filtered', type: 'array' },
+          { name: 'count', type: 'number' },
+          { name: 'total', type: 'number', description: 'Matches before offset and limit' },
+          { name: 'headers', type: 'array' },
+        ],
+        configFields: [
+          {
+            name: 'condition',
+            type: 'string',
+            label: 'Condition',
+            default: '',
+            placeholder: "amount > 1000 && status == 'open'",
+          },
+          {
+            name: 'fields',
+            type: 'string',
+            label: 'Fields',
+            default: '',
+            placeholder: 'name, qty * price as total',
+          },
+          { name: 'sort_by', type: 'string', label: 'Sort By', default: '' },
+          {
+            name: 'order',
+            type: 'select',
+            label: 'Order',
+            default: 'asc',
+            options: [
+              { value: 'asc', label: 'Ascending' },
+              { value: 'desc', label: 'Descending' },
+            ],
+          },
+          { name: 'offset', type: 'number', label: 'Offset', default: 0 },
+          { name: 'limit', type: 'number', label: 'Limit', default: 0 },
+        ],
+      },
+      {
+        id: 'doc-extract-fields',
+        label: 'Document Fields',
+        category: 'data',
+        description: 'Extract labelled fields from invoices, permits and forms as validated JSON',
+        icon: 'ScanText',
+        inputs: [
+          { name: 'text', type: 'string' },
+          { name: 'path', type: 'string' },
+        ],
+        outputs: [
+          { name: 'fields', type: 'json' },
+          { name: 'valid', type: 'boolean' },
+          { name: 'errors', type: 'array' },
+          { name: 'missing', type: 'array' },
+          { name: 'sources', type: 'json' },
+        ],
+        configFields: [
+          {
+            name: 'fields',
+            type: 'string',
+            label: 'Fields (JSON)',
+            default: '[]',
+            placeholder: '[{"name": "invoice_no", "labels": ["Invoice No"], "required": true}, {"name": "total", "type": "number"}]',
+          },
+          {
+            name: 'file_path',
+            type: 'file',
+            label: 'Document',
+            default: '',
+            fileFilters: [
+              { name: 'Documents', extensions: ['pdf', 'txt', 'md'] },
+            ],
+          },
+          {
+            name: 'llm_fallback',
+            type: 'boolean',
+            label: 'LLM Fallback for Missing Fields',
+            default: true,
+          },
+          { name: 'model', type: 'string', label: 'Model', default: 'claude-3-haiku-20240307' },
+        ],
+      },
+      {
+        id: 'public-api-fetch',
+        label: 'Public Data API',
+        category: 'data',
+        description: 'Fetch records from data.go.kr and other Korean public APIs, page by page and within the daily quota',
+        icon: 'Landmark',
+        inputs: [{ name: 'params', type: 'json' }],
+        outputs: [
+          { name: 'items', type: 'array' },
+          { name: 'count', type: 'number' },
+          { name: 'total_count', type: 'number' },
+          { name: 'pages', type: 'number' },
+          { name: 'calls_today', type: 'number' },
+          { name: 'daily_limit', type: 'number' },
+        ],
+        configFields: [
+          {
+            name: 'service',
+            type: 'select',
+            label: 'Service',
+            default: 'data-go-kr',
+            options: [
+              { value: 'kma-forecast', label: 'KMA Short-term Forecast' },
+              { value: 'apt-trade', label: 'Apartment Trade Prices' },
+              { value: 'building-register', label: 'Building Register' },
+              { value: 'air-quality', label: 'AirKorea Air Quality' },
+              { value: 'data-go-kr', label: 'Other data.go.kr Service' },
+            ],
+          },
+          {
+            name: 'url',
+            type: 'string',
+            label: 'URL',
+            default: '',
+            placeholder: 'https://apis.data.go.kr/...',
+            description: 'Overrides the preset endpoint',
+          },
+          {
+            name: 'params',
+            type: 'string',
+            label: 'Parameters (JSON)',
+            default: '{}',
+            placeholder: '{"LAWD_CD": "11110", "DEAL_YMD": "202401"}',
+          },
+          {
+            name: 'key_env',
+            type: 'string',
+            label: 'Service Key Name',
+            default: 'DATA_GO_KR_SERVICE_KEY',
+            description: 'API key saved under Settings',
+          },
+          { name: 'max_pages', type: 'number', label: 'Max Pages', default: 10 },
+          { name: 'max_items', type: 'number', label: 'Max Records', default: 0, description: '0 for no limit' },
+          { name: 'page_size', type: 'number', label: 'Page Size', default: 100 },
+          { name: 'format_param', type: 'string', label: 'JSON Parameter', default: '', placeholder: '_type=json' },
+          { name: 'items_path', type: 'string', label: 'Records Path', default: '', placeholder: 'response.body.items.item' },
+          { name: 'timeout', type: 'number', label: 'Timeout (s)', default: 30 },
+        ],
       },
     ],
   },
@@ -445,14 +812,46 @@ export const toolCategories: ToolCategory[] = [
         id: 'condition',
         label: 'Condition',
         category: 'control',
-        description: 'Branch based on a condition',
+        description: 'Branch on an expression, or switch between several outputs',
         icon: 'GitFork',
         inputs: [{ name: 'value', type: 'any' }],
         outputs: [
-          { name: 'true', type: 'any' },
-          { name: 'false', type: 'any' },
+          { name: 'true_out', type: 'any' },
+          { name: 'false_out', type: 'any' },
+          { name: 'cases', type: 'any', variadic: true, description: 'One output per switch case' },
+          { name: 'default', type: 'any', description: 'Value when no switch case matched' },
+          { name: 'matched', type: 'string' },
+          { name: 'result', type: 'any' },
+          { name: 'untaken', type: 'array', description: 'Outputs whose branch was not taken' },
+        ],
+        configFields: [
+          {
+            name: 'expression',
+            type: 'string',
+            label: 'Expression',
+            default: '',
+            placeholder: "score >= 0.8 && status == 'approved'",
+            description: 'Evaluated against the value; empty tests the value itself',
+          },
+          {
+            name: 'mode',
+            type: 'select',
+            label: 'Mode',
+            default: 'if',
+            options: [
+              { value: 'if', label: 'If / else' },
+              { value: 'switch', label: 'Switch' },
+            ],
+          },
+          {
+            name: 'cases',
+            type: 'string',
+            label: 'Switch Cases',
+            default: '',
+            placeholder: 'approved, rejected, pending',
+            description: 'Comma-separated values, or a JSON list of { label, value }',
+          },
         ],
-        configFields: [{ name: 'expression', type: 'string', label: 'Expression', default: '' }],
       },
       {
         id: 'loop',
@@ -470,10 +869,7 @@ export const toolCategories: ToolCategory[] = [
         category: 'control',
         description: 'Merge multiple inputs into one',
         icon: 'GitMerge',
-        inputs: [
-          { name: 'input_a', type: 'any' },
-          { name: 'input_b', type: 'any' },
-        ],
+        inputs: [{ name: 'inputs', type: 'any', variadic: true }],
         outputs: [{ name: 'merged', type: 'json' }],
         configFields: [],
       },
@@ -534,6 +930,157 @@ export const toolCategories: ToolCategory[] = [
         outputs: [{ name: 'ranked', type: 'array' }],
         configFields: [{ name: 'top_n', type: 'number', label: 'Top N', default: 3 }],
       },
+      {
+        id: 'graph-extract',
+        label: 'Graph Extract',
+        category: 'rag',
+        description: 'Extract entities and relations into a knowledge graph',
+        icon: 'Network',
+        inputs: [
+          { name: 'text', type: 'any' },
+          { name: 'source', type: 'string' },
+        ],
+        outputs: [
+          { name: 'triples', type: 'array' },
+          { name: 'added', type: 'number' },
+        ],
+        configFields: [
+          { name: 'graph', type: 'string', label: 'Graph', default: 'default' },
+          { name: 'entity_types', type: 'string', label: 'Entity Types', default: '' },
+          { name: 'relations', type: 'string', label: 'Relations', default: '' },
+        ],
+      },
+      {
+        id: 'graph-query',
+        label: 'Graph Query',
+        category: 'rag',
+        description: 'Find facts by pattern or around an entity',
+        icon: 'Share2',
+        inputs: [
+          { name: 'entity', type: 'string' },
+          { name: 'subject', type: 'string' },
+          { name: 'predicate', type: 'string' },
+          { name: 'object', type: 'string' },
+        ],
+        outputs: [
+          { name: 'context', type: 'string' },
+          { name: 'triples', type: 'array' },
+        ],
+        configFields: [
+          { name: 'graph', type: 'string', label: 'Graph', default: 'default' },
+          { name: 'depth', type: 'number', label: 'Depth', default: 1 },
+          { name: 'limit', type: 'number', label: 'Limit', default: 50 },
+        ],
+      },
+    ],
+  },
+  {
+    id: 'meeting',
+    label: 'Meeting',
+    icon: 'Mic',
+    color: '#0ea5e9',
+    tools: [
+      {
+        id: 'audio-transcribe',
+        label: 'Audio Transcribe',
+        category: 'meeting',
+        description: 'Transcribe a meeting recording or read its subtitles',
+        icon: 'Mic',
+        inputs: [{ name: 'path', type: 'string' }],
+        outputs: [
+          { name: 'text', type: 'string' },
+          { name: 'transcript', type: 'string' },
+          { name: 'segments', type: 'array' },
+          { name: 'duration', type: 'number' },
+          { name: 'language', type: 'string' },
+        ],
+        configFields: [
+          {
+            name: 'file_path',
+            type: 'file',
+            label: 'Recording',
+            default: '',
+            fileFilters: [
+              { name: 'Audio', extensions: ['mp3', 'm4a', 'wav', 'webm', 'mp4', 'ogg', 'flac'] },
+              { name: 'Subtitles / Transcripts', extensions: ['srt', 'vtt', 'txt', 'md'] },
+            ],
+          },
+          { name: 'language', type: 'string', label: 'Language', default: 'auto' },
+          { name: 'vocabulary', type: 'string', label: 'Names & Terms', default: '' },
+          { name: 'model', type: 'string', label: 'Model', default: 'whisper-1' },
+          { name: 'endpoint', type: 'string', label: 'Endpoint', default: '' },
+        ],
+      },
+      {
+        id: 'speaker-diarize',
+        label: 'Speaker Diarize',
+        category: 'meeting',
+        description: 'Tell the speakers of a transcript apart',
+        icon: 'Users',
+        inputs: [
+          { name: 'segments', type: 'array' },
+          { name: 'text', type: 'string' },
+        ],
+        outputs: [
+          { name: 'transcript', type: 'string' },
+          { name: 'segments', type: 'array' },
+          { name: 'speakers', type: 'array' },
+          { name: 'method', type: 'string' },
+        ],
+        configFields: [
+          {
+            name: 'method',
+            type: 'select',
+            label: 'Method',
+            default: 'auto',
+            options: [
+              { value: 'auto', label: 'Labels or pauses' },
+              { value: 'llm', label: 'LLM' },
+            ],
+          },
+          { name: 'speakers', type: 'number', label: 'Speakers', default: 2 },
+          { name: 'turn_gap', type: 'number', label: 'Turn Gap (s)', default: 1.5 },
+        ],
+      },
+      {
+        id: 'meeting-minutes',
+        label: 'Meeting Minutes',
+        category: 'meeting',
+        description: 'Write minutes with decisions and action items',
+        icon: 'ClipboardList',
+        inputs: [
+          { name: 'transcript', type: 'string' },
+          { name: 'segments', type: 'array' },
+          { name: 'path', type: 'string' },
+        ],
+        outputs: [
+          { name: 'minutes', type: 'string' },
+          { name: 'action_items', type: 'array' },
+          { name: 'decisions', type: 'array' },
+          { name: 'summary', type: 'string' },
+          { name: 'attendees', type: 'array' },
+          { name: 'topics', type: 'array' },
+          { name: 'title', type: 'string' },
+        ],
+        configFields: [
+          {
+            name: 'language',
+            type: 'select',
+            label: 'Language',
+            default: 'auto',
+            options: [
+              { value: 'auto', label: 'Same as transcript' },
+              { value: 'korean', label: 'Korean' },
+              { value: 'english', label: 'English' },
+              { value: 'japanese', label: 'Japanese' },
+              { value: 'chinese', label: 'Chinese' },
+            ],
+          },
+          { name: 'context', type: 'string', label: 'Meeting Context', default: '' },
+          { name: 'max_chars', type: 'number', label: 'Chars per Pass', default: 30000 },
+          { name: 'max_tokens', type: 'number', label: 'Max Tokens', default: 4096 },
+        ],
+      },
     ],
   },
   {
@@ -833,6 +1380,34 @@ export const toolCategories: ToolCategory[] = [
           { name: 'capture_files', type: 'string', label: 'Capture Files Pattern', default: '', placeholder: '*.png,*.csv' },
         ],
       },
+      {
+        id: 'shell-exec',
+        label: 'Shell Exec',
+        category: 'system',
+        description: 'Run a program without a shell; programs not on the command allowlist ask for approval',
+        icon: 'Terminal',
+        inputs: [{ name: 'stdin', type: 'string' }],
+        outputs: [
+          { name: 'stdout', type: 'string' },
+          { name: 'stderr', type: 'string' },
+          { name: 'exit_code', type: 'number' },
+          { name: 'truncated', type: 'boolean', description: 'Output exceeded the size cap' },
+        ],
+        configFields: [
+          { name: 'command', type: 'string', label: 'Program', default: '', placeholder: 'ogr2ogr' },
+          {
+            name: 'args',
+            type: 'string',
+            label: 'Arguments (JSON list)',
+            default: '',
+            placeholder: '["-f", "GeoJSON", "{input.path}"]',
+          },
+          { name: 'working_dir', type: 'folder', label: 'Working Directory', default: '' },
+          { name: 'timeout_ms', type: 'number', label: 'Timeout (ms)', default: 30000 },
+          { name: 'max_output_bytes', type: 'number', label: 'Max Output (bytes)', default: 1048576 },
+          { name: 'fail_on_error', type: 'boolean', label: 'Fail on Non-Zero Exit', default: true },
+        ],
+      },
       {
         id: 'clipboard-read',
         label: 'Clipboard Read',
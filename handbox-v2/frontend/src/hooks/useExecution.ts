@@ -7,7 +7,8 @@
 import { useEffect, useRef } from 'react'
 import { useExecutionStore } from '@/stores/executionStore'
 import { useWorkflowStore } from '@/stores/workflowStore'
-import type { ExecutionStatus } from '@/types/trace'
+import type { ExecutionStatus, NodeProgress, OutputChunk } from '@/types/trace'
+import type { WorkflowCostEstimate } from '@/types/graph'
 
 // Helper to check if we're running in Tauri environment
 const isTauri = (): boolean => {
@@ -48,16 +49,23 @@ export interface ExecutionResult {
 interface NodeStatusEvent {
   execution_id: string
   node_id: string
-  status: string // "pending", "running", "completed", "failed", "cache_hit", "skipped"
+  status: string // "pending", "paused", "running", "progress", "output", "stalled", "completed", "failed", "cache_hit", "skipped", "cancelled"
+  /** Position in the execution's event order, from 1 */
+  seq: number
   output?: unknown
   error?: string
   duration_ms?: number
+  progress?: NodeProgress | null
+  /** Set on "output" events: output streamed before the node finishes. */
+  chunk?: OutputChunk
 }
 
 export function useExecution() {
-  const { startExecution, completeExecution, updateNodeStatus, updateNodeDetail, updateEdgeFlowStatus } = useExecutionStore()
+  const { startExecution, completeExecution, setPaused, updateNodeStatus, updateNodeDetail, updateEdgeFlowStatus } = useExecutionStore()
   const { nodes, getWorkflowJson } = useWorkflowStore()
   const unlistenRef = useRef<UnlistenFn | null>(null)
+  // Latest event applied per execution, to drop duplicates from replays
+  const lastSeqRef = useRef<Map<string, number>>(new Map())
 
   // Set up event listener for node status updates
   useEffect(() => {
@@ -79,15 +87,38 @@ export function useExecution() {
         return
       }
 
-      // Listen for node-status events from backend
-      unlistenRef.current = await tauriApis.listen<NodeStatusEvent>('node-status', (event) => {
-        const { node_id, status, error, output, duration_ms } = event.payload
+      const applyEvent = (event: NodeStatusEvent) => {
+        const { execution_id, node_id, status, seq, error, output, duration_ms, progress, chunk } = event
+        if (seq <= (lastSeqRef.current.get(execution_id) ?? 0)) return
+        lastSeqRef.current.set(execution_id, seq)
+
+        // Progress updates and stall warnings leave the status alone
+        if (status === 'progress') {
+          if (progress) updateNodeDetail(node_id, { progress, stalled: undefined })
+          return
+        }
+        if (status === 'output') {
+          if (chunk) {
+            const streamed = useExecutionStore.getState().nodeDetails[node_id]?.streamed ?? {}
+            updateNodeDetail(node_id, {
+              streamed: { ...streamed, [chunk.port]: (streamed[chunk.port] ?? '') + chunk.text },
+              stalled: undefined,
+            })
+          }
+          return
+        }
+        if (status === 'stalled') {
+          updateNodeDetail(node_id, { stalled: error })
+          return
+        }
 
         // Map backend status to frontend ExecutionStatus
         const mappedStatus: ExecutionStatus = (() => {
           switch (status) {
             case 'pending':
               return 'pending'
+            case 'paused':
+              return 'paused'
             case 'running':
               return 'running'
             case 'completed':
@@ -98,6 +129,8 @@ export function useExecution() {
               return 'cache_hit'
             case 'skipped':
               return 'skipped'
+            case 'cancelled':
+              return 'cancelled'
             default:
               return 'pending'
           }
@@ -111,6 +144,9 @@ export function useExecution() {
           error,
           output,
           duration_ms,
+          stalled: undefined,
+          // A node starting over streams from scratch
+          ...(mappedStatus === 'running' ? { streamed: undefined } : {}),
         })
 
         // Update edge flow statuses based on node transitions
@@ -141,7 +177,32 @@ export function useExecution() {
         } else if (duration_ms !== undefined && status === 'completed') {
           console.log(`Node ${node_id} completed in ${duration_ms}ms`)
         }
+      }
+
+      // Listen for node-status events from backend. Live events wait while
+      // missed ones are replayed, so transitions are applied in order.
+      let replaying = true
+      const queued: NodeStatusEvent[] = []
+      unlistenRef.current = await tauriApis.listen<NodeStatusEvent>('node-status', (event) => {
+        if (replaying) queued.push(event.payload)
+        else applyEvent(event.payload)
       })
+
+      // Catch up on executions that were already running, e.g. after a reload
+      try {
+        const running = await tauriApis.invoke<string[]>('list_running_executions')
+        for (const executionId of running) {
+          const missed = await tauriApis.invoke<NodeStatusEvent[]>('get_execution_events', {
+            executionId,
+            afterSeq: lastSeqRef.current.get(executionId) ?? 0,
+          })
+          missed.forEach(applyEvent)
+        }
+      } catch (error) {
+        console.warn('[useExecution] Failed to replay status events:', error)
+      }
+      replaying = false
+      queued.forEach(applyEvent)
     }
 
     setupListener()
@@ -289,7 +350,7 @@ export function useExecution() {
       // Mark all running nodes as cancelled
       for (const node of nodes) {
         const currentStatus = state.nodeStatuses[node.id]
-        if (currentStatus === 'running' || currentStatus === 'pending') {
+        if (currentStatus === 'running' || currentStatus === 'pending' || currentStatus === 'paused') {
           updateNodeStatus(node.id, 'cancelled')
         }
       }
@@ -307,6 +368,97 @@ export function useExecution() {
     }
   }
 
+  /** Cancel one node and its downstream dependents; other branches keep
+   *  running. Their statuses arrive as node events. */
+  const cancelNode = async (executionId: string, nodeId: string) => {
+    try {
+      const tauriApis = await getTauriApis()
+      if (!tauriApis) return false
+      await tauriApis.invoke('cancel_node', { executionId, nodeId })
+      return true
+    } catch (error) {
+      console.error('Failed to cancel node:', error)
+      return false
+    }
+  }
+
+  /** Pause a running execution. Nodes already running finish; the rest
+   *  wait (shown as paused) until it is resumed. */
+  const pause = async (executionId: string) => {
+    try {
+      const tauriApis = await getTauriApis()
+      if (!tauriApis) return false
+      await tauriApis.invoke('pause_execution', { executionId })
+      setPaused(true)
+      return true
+    } catch (error) {
+      console.error('Failed to pause execution:', error)
+      return false
+    }
+  }
+
+  /** Resume an execution. A paused one carries on where it stopped. A
+   *  failed, cancelled or interrupted one runs again under the same
+   *  execution ID, in the workspace it first ran in; nodes that completed
+   *  keep their outputs (reported as cache hits). */
+  const resume = async (executionId: string, workflowId: string) => {
+    const tauriApis = await getTauriApis()
+    if (!tauriApis) {
+      return null
+    }
+    const current = useExecutionStore.getState().currentExecution
+    if (current?.execution_id === executionId && current.status === 'paused') {
+      try {
+        await tauriApis.invoke('resume_execution', { executionId })
+        setPaused(false)
+      } catch (error) {
+        console.error('Failed to resume execution:', error)
+      }
+      return null
+    }
+    // The resumed run numbers its events from 1 again
+    lastSeqRef.current.delete(executionId)
+    startExecution({
+      execution_id: executionId,
+      workflow_id: workflowId,
+      started_at: new Date().toISOString(),
+      completed_at: null,
+      status: 'running',
+      total_nodes: nodes.length,
+      completed_nodes: 0,
+      failed_nodes: 0,
+      cache_hits: 0,
+    })
+    try {
+      const result = await tauriApis.invoke<ExecutionResult>('resume_from_checkpoint', {
+        executionId,
+      })
+      completeExecution({
+        execution_id: executionId,
+        workflow_id: workflowId,
+        started_at: result.started_at,
+        completed_at: result.completed_at || new Date().toISOString(),
+        status: result.status,
+        total_nodes: result.total_nodes,
+        completed_nodes: result.completed_nodes,
+        failed_nodes: result.failed_nodes,
+        cache_hits: 0,
+      })
+      return result
+    } catch (error) {
+      console.error('Failed to resume execution:', error)
+      const state = useExecutionStore.getState()
+      if (state.currentExecution) {
+        completeExecution({
+          ...state.currentExecution,
+          completed_at: new Date().toISOString(),
+          status: 'failed',
+        })
+      }
+      return null
+    }
+  }
+
   const getStatus = async (executionId: string) => {
     try {
       const tauriApis = await getTauriApis()
@@ -320,9 +472,36 @@ export function useExecution() {
     }
   }
 
+  /** Cost and duration range of a stored workflow, checked against the
+   *  workspace's cost limits. Null outside Tauri or on error. */
+  const estimateCost = async (
+    workflowId: string,
+    variables?: Record<string, unknown>,
+    workspaceId?: string,
+  ) => {
+    try {
+      const tauriApis = await getTauriApis()
+      if (!tauriApis) {
+        return null
+      }
+      return await tauriApis.invoke<WorkflowCostEstimate>('estimate_workflow_cost', {
+        workflowId,
+        variables,
+        workspaceId,
+      })
+    } catch (error) {
+      console.error('Failed to estimate workflow cost:', error)
+      return null
+    }
+  }
+
   return {
     execute,
+    pause,
+    resume,
     cancel,
+    cancelNode,
     getStatus,
+    estimateCost,
   }
 }
@@ -3,6 +3,17 @@
  */
 
 import { invoke } from '@tauri-apps/api/core'
+import type {
+  ArtifactKind,
+  EdgeValue,
+  ExecutionDiff,
+  ExecutionManifest,
+  ExecutionRecord,
+  RunViewerExport,
+  SchedulerMetrics,
+  TraceExport,
+  ViewerBundle,
+} from '../types/trace'
 
 export interface TraceSpan {
   span_id: string
@@ -37,13 +48,97 @@ export function useTrace() {
     }
   }
 
-  const exportTraces = async (executionId: string, format: string = 'json'): Promise<string | null> => {
+  const getEdgeValue = async (executionId: string, edgeId: string): Promise<EdgeValue | null> => {
     try {
-      return await invoke<string>('export_traces', { executionId, format })
+      return await invoke<EdgeValue | null>('get_edge_value', { executionId, edgeId })
     } catch {
       return null
     }
   }
 
-  return { getTraces, getSpan, exportTraces }
+  const getNodeInputs = async (executionId: string, nodeId: string): Promise<EdgeValue[]> => {
+    try {
+      return await invoke<EdgeValue[]>('get_node_inputs', { executionId, nodeId })
+    } catch {
+      return []
+    }
+  }
+
+  const getExecutionManifest = async (executionId: string): Promise<ExecutionManifest | null> => {
+    try {
+      return await invoke<ExecutionManifest>('get_execution_manifest', { executionId })
+    } catch {
+      return null
+    }
+  }
+
+  const getSchedulerMetrics = async (executionId: string): Promise<SchedulerMetrics | null> => {
+    try {
+      return await invoke<SchedulerMetrics | null>('get_scheduler_metrics', { executionId })
+    } catch {
+      return null
+    }
+  }
+
+  const diffExecutions = async (executionA: string, executionB: string): Promise<ExecutionDiff | null> => {
+    try {
+      return await invoke<ExecutionDiff>('diff_executions', { executionA, executionB })
+    } catch {
+      return null
+    }
+  }
+
+  const findExecutionsByArtifact = async (
+    target: string,
+    kind?: ArtifactKind,
+    limit?: number,
+  ): Promise<ExecutionRecord[]> => {
+    try {
+      return await invoke<ExecutionRecord[]>('find_executions_by_artifact', { target, kind, limit })
+    } catch {
+      return []
+    }
+  }
+
+  const exportTraces = async (
+    executionId: string,
+    outputPath: string,
+    format: string = 'json',
+  ): Promise<TraceExport | null> => {
+    try {
+      return await invoke<TraceExport>('export_traces', { executionId, format, outputPath })
+    } catch {
+      return null
+    }
+  }
+
+  const exportRunViewer = async (executionId: string): Promise<RunViewerExport | null> => {
+    try {
+      return await invoke<RunViewerExport>('export_run_viewer', { executionId })
+    } catch {
+      return null
+    }
+  }
+
+  const loadRunViewer = async (path: string): Promise<ViewerBundle | null> => {
+    try {
+      return await invoke<ViewerBundle>('load_run_viewer', { path })
+    } catch {
+      return null
+    }
+  }
+
+  return {
+    getTraces,
+    getSpan,
+    getEdgeValue,
+    getNodeInputs,
+    getExecutionManifest,
+    getSchedulerMetrics,
+    diffExecutions,
+    findExecutionsByArtifact,
+    exportTraces,
+    exportRunViewer,
+    loadRunViewer,
+  }
 }
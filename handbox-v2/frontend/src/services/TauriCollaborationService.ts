@@ -12,7 +12,10 @@ import type {
   ChatMessage,
   SessionSettings,
   CollaborationInvite,
+  SessionRecordingSummary,
+  PlaybackFrame,
 } from '@/types/marketplace'
+import type { WorkflowSpec } from '@/types/graph'
 
 // Convert frontend types to backend snake_case format
 function toSnakeCase<T>(obj: T): T {
@@ -421,6 +424,88 @@ class TauriCollaborationService {
     }
   }
 
+  /**
+   * Run the session's workflow with its status broadcast to all participants
+   */
+  async executeWorkflow(workspaceId?: string): Promise<unknown> {
+    if (!this.currentSessionId) return null
+
+    return invoke('collab_execute_workflow', {
+      sessionId: this.currentSessionId,
+      workspaceId,
+    })
+  }
+
+  /**
+   * Cancel a shared execution of the current session
+   */
+  async cancelExecution(executionId: string): Promise<boolean> {
+    if (!this.currentSessionId) return false
+
+    try {
+      await invoke('collab_cancel_execution', {
+        sessionId: this.currentSessionId,
+        executionId,
+      })
+      return true
+    } catch (e) {
+      console.error('Failed to cancel shared execution:', e)
+      return false
+    }
+  }
+
+  /**
+   * List recorded sessions, most recent first
+   */
+  async listRecordings(): Promise<SessionRecordingSummary[]> {
+    try {
+      const result = await invoke<SessionRecordingSummary[]>('collab_list_recordings')
+      return result.map(toCamelCase)
+    } catch (e) {
+      console.error('Failed to list recordings:', e)
+      return []
+    }
+  }
+
+  /**
+   * Reconstruct the workflow at a point in a recorded session — an event
+   * count or an ISO time; defaults to the end
+   */
+  async playback(sessionId: string, position?: number, at?: string): Promise<PlaybackFrame | null> {
+    try {
+      const { workflow, ...frame } = await invoke<PlaybackFrame>('collab_playback', {
+        sessionId,
+        position,
+        at,
+      })
+      // The workflow keeps its snake_case keys like every other WorkflowSpec
+      return { ...toCamelCase(frame), workflow }
+    } catch (e) {
+      console.error('Failed to play back session:', e)
+      return null
+    }
+  }
+
+  /**
+   * Restore the workflow to its state at a point in a recorded session
+   */
+  async restoreFromRecording(
+    sessionId: string,
+    position?: number,
+    at?: string
+  ): Promise<WorkflowSpec | null> {
+    try {
+      return await invoke<WorkflowSpec>('collab_restore_from_recording', {
+        sessionId,
+        position,
+        at,
+      })
+    } catch (e) {
+      console.error('Failed to restore from recording:', e)
+      return null
+    }
+  }
+
   /**
    * Subscribe to events
    */
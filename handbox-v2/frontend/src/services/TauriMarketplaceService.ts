@@ -10,6 +10,7 @@ import type {
   WorkflowReview,
   WorkflowCollection,
   WorkflowCategory,
+  PublishReport,
 } from '@/types/marketplace'
 
 // Convert frontend types to backend snake_case format
@@ -335,20 +336,40 @@ class TauriMarketplaceService {
     }
   }
 
+  /**
+   * Run the publish checks without publishing
+   */
+  async checkPublish(request: PublishRequest, workspaceId?: string): Promise<PublishReport | null> {
+    try {
+      const report = await invoke<PublishReport>('marketplace_check_publish', {
+        request: toSnakeCase(request),
+        workspaceId,
+      })
+      return toCamelCase(report)
+    } catch (e) {
+      console.error('Failed to check workflow for publishing:', e)
+      return null
+    }
+  }
+
   /**
    * Publish workflow
    */
   async publish(
     request: PublishRequest,
     userId: string,
-    userName: string
+    userName: string,
+    workspaceId?: string
   ): Promise<MarketplaceWorkflow | null> {
     try {
+      // The backend finds the stored workflow from the data's ID and
+      // publishes the approved revision when a review policy applies
       const backendRequest = toSnakeCase(request)
       const result = await invoke<MarketplaceWorkflow>('marketplace_publish', {
         request: backendRequest,
         userId,
         userName,
+        workspaceId,
       })
       return toCamelCase(result)
     } catch (e) {
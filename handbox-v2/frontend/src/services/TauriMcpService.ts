@@ -9,6 +9,7 @@ import type {
   McpRemoteTool,
   McpHealthCheck,
 } from '@/types/mcp'
+import { errorMessage } from '@/types/error'
 
 // Convert frontend types to backend snake_case format
 function toSnakeCase<T>(obj: T): T {
@@ -137,7 +138,7 @@ class TauriMcpService {
       return toCamelCase(result)
     } catch (e) {
       console.error('Failed to call MCP tool:', e)
-      return { success: false, error: String(e) }
+      return { success: false, error: errorMessage(e) }
     }
   }
 
@@ -355,6 +355,7 @@ function calculateEstimatedCost(traces: LLMTrace[]): number {
     anthropic: { input: 0.003, output: 0.015 },
     bedrock: { input: 0.003, output: 0.015 },
     local: { input: 0, output: 0 },
+    llamacpp: { input: 0, output: 0 },
   }
 
   let totalCost = 0
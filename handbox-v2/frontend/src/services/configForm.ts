@@ -0,0 +1,17 @@
+/**
+ * configForm — the property panel form for a node, generated by the backend
+ * from the tool's config schema: localized fields, options from live sources
+ * such as model lists, and visibility and validation for the node's config.
+ */
+
+import { invoke } from '@tauri-apps/api/core'
+import type { ConfigForm } from '@/types/tool'
+
+/** The form for a node of `toolRef` whose config is `config`. */
+export function getNodeConfigForm(
+  toolRef: string,
+  config: Record<string, unknown>,
+  locale?: string,
+): Promise<ConfigForm> {
+  return invoke<ConfigForm>('get_node_config_form', { toolRef, config, locale })
+}
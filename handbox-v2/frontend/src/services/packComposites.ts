@@ -0,0 +1,23 @@
+/**
+ * packComposites — ready-made composites installed from packs, such as the
+ * bundled standard library (retry with fallback, cache-through, validate
+ * then branch, batch API call), and copies of them to add to a workflow.
+ */
+
+import { invoke } from '@tauri-apps/api/core'
+import type { CompositeNodeEntry } from '@/types/graph'
+import type { InstalledPackContent } from '@/types/pack'
+
+/** Composites installed from every pack. */
+export function listPackComposites(): Promise<InstalledPackContent[]> {
+  return invoke<InstalledPackContent[]>('list_pack_contents', { kind: 'composite' })
+}
+
+/** A copy of an installed composite, ready to add to a workflow as `nodeId`. */
+export function instantiatePackComposite(
+  packId: string,
+  compositeId: string,
+  nodeId: string,
+): Promise<CompositeNodeEntry> {
+  return invoke<CompositeNodeEntry>('instantiate_pack_composite', { packId, compositeId, nodeId })
+}
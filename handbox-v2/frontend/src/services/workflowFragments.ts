@@ -0,0 +1,42 @@
+/**
+ * workflowFragments — copy a selection of nodes out of a workflow as
+ * fragment text and paste fragment text (from the clipboard or a chat
+ * message) into another workflow. The backend validates the fragment and
+ * renames colliding IDs.
+ */
+
+import { invoke } from '@tauri-apps/api/core'
+import type { FragmentPasteReport, Position } from '@/types/graph'
+
+/** Fragment text for the selected nodes. */
+export function copyFragment(workflowId: string, nodeIds: string[]): Promise<string> {
+  return invoke<string>('copy_workflow_fragment', { id: workflowId, nodeIds })
+}
+
+/** Copy the selected nodes to the system clipboard. */
+export async function copyFragmentToClipboard(workflowId: string, nodeIds: string[]): Promise<void> {
+  await navigator.clipboard.writeText(await copyFragment(workflowId, nodeIds))
+}
+
+export function pasteFragment(
+  workflowId: string,
+  text: string,
+  offset?: Position,
+  workspaceId?: string,
+): Promise<FragmentPasteReport> {
+  return invoke<FragmentPasteReport>('paste_workflow_fragment', {
+    id: workflowId,
+    text,
+    offset,
+    workspaceId,
+  })
+}
+
+/** Fragment text from a drop event (dragged text or a dropped .json file). */
+export async function fragmentTextFromDrop(event: DragEvent): Promise<string | null> {
+  const data = event.dataTransfer
+  if (!data) return null
+  const file = Array.from(data.files).find((f) => f.name.endsWith('.json') || f.type === 'application/json')
+  if (file) return file.text()
+  return data.getData('text/plain') || null
+}
@@ -0,0 +1,82 @@
+/**
+ * workflowPages — loads and saves large workflows page by page instead of
+ * one WorkflowSpec per round trip. Composite subgraphs stay on the backend
+ * until opened; `deferred` tracks the ones not loaded yet.
+ */
+
+import { invoke } from '@tauri-apps/api/core'
+import type { EdgeSpec, NodeEntry, SpecOutline, SpecPage, SubgraphSpec, WorkflowSpec } from '@/types/graph'
+
+const PAGE_SIZE = 500
+
+export interface PagedWorkflow {
+  spec: WorkflowSpec
+  /** Composite paths whose subgraphs are still on the backend. */
+  deferred: Set<string>
+}
+
+export async function loadWorkflow(id: string, pageSize = PAGE_SIZE): Promise<PagedWorkflow> {
+  const outline = await invoke<SpecOutline>('get_workflow_outline', { id })
+  const nodes: NodeEntry[] = []
+  const edges: EdgeSpec[] = []
+  const deferred = new Set<string>()
+  const total = Math.max(outline.node_count, outline.edge_count)
+  for (let offset = 0; offset < total; offset += pageSize) {
+    const page = await invoke<SpecPage>('get_workflow_page', { id, offset, limit: pageSize })
+    nodes.push(...page.nodes)
+    edges.push(...page.edges)
+    page.deferred.forEach((path) => deferred.add(path))
+  }
+  const spec: WorkflowSpec = {
+    version: outline.version,
+    id: outline.id,
+    meta: outline.meta,
+    variables: outline.variables,
+    nodes,
+    edges,
+    required_packs: outline.required_packs,
+    tests: outline.tests,
+    groups: outline.groups,
+  }
+  return { spec, deferred }
+}
+
+/** Load the subgraph of the composite at `path` and mark it loaded. */
+export async function loadSubgraph(
+  workflow: PagedWorkflow,
+  path: string,
+): Promise<SubgraphSpec> {
+  const page = await invoke<SpecPage>('get_workflow_subgraph', { id: workflow.spec.id, path })
+  workflow.deferred.delete(path)
+  page.deferred.forEach((nested) => workflow.deferred.add(nested))
+  return { nodes: page.nodes, edges: page.edges }
+}
+
+/** Save `workflow`; subgraphs still deferred are kept as stored. */
+export async function saveWorkflow(
+  workflow: PagedWorkflow,
+  workspaceId?: string,
+  pageSize = PAGE_SIZE,
+): Promise<SpecOutline> {
+  const { nodes, edges, ...rest } = workflow.spec
+  const outline: SpecOutline = {
+    ...rest,
+    tests: rest.tests ?? [],
+    node_count: nodes.length,
+    edge_count: edges.length,
+  }
+  const uploadId = await invoke<string>('begin_workflow_upload', { outline, workspaceId })
+  const deferred = [...workflow.deferred]
+  const total = Math.max(nodes.length, edges.length)
+  for (let offset = 0; offset < total; offset += pageSize) {
+    const page: SpecPage = {
+      offset,
+      nodes: nodes.slice(offset, offset + pageSize),
+      edges: edges.slice(offset, offset + pageSize),
+      // Sent once; the backend restores them when the upload finishes
+      deferred: offset === 0 ? deferred : [],
+    }
+    await invoke('append_workflow_page', { uploadId, page })
+  }
+  return invoke<SpecOutline>('finish_workflow_upload', { uploadId, workspaceId })
+}
@@ -16,6 +16,7 @@ import type {
   ComparisonModelStats,
 } from '@/types/comparison'
 import { useTraceStore } from './traceStore'
+import { errorMessage } from '@/types/error'
 
 interface ComparisonState {
   /** Saved comparison results */
@@ -167,12 +168,12 @@ export const useComparisonStore = create<ComparisonState>()(
                 const latencyMs = Date.now() - startTime
 
                 // End trace with error
-                traceStore.endLLMTrace(traceId, undefined, String(error))
+                traceStore.endLLMTrace(traceId, undefined, errorMessage(error))
 
                 results.push({
                   model,
                   status: 'failed',
-                  error: String(error),
+                  error: errorMessage(error),
                   latencyMs,
                   timestamp: new Date().toISOString(),
                 })
@@ -3,7 +3,7 @@
  */
 
 import { create } from 'zustand'
-import type { ExecutionRecord, ExecutionStatus, NodeSpan } from '@/types/trace'
+import type { ExecutionRecord, ExecutionStatus, NodeProgress, NodeSpan } from '@/types/trace'
 
 /** Details tracked per node during execution */
 export interface NodeExecutionDetail {
@@ -12,6 +12,12 @@ export interface NodeExecutionDetail {
   input?: unknown
   output?: unknown
   duration_ms?: number
+  /** Latest progress reported while running. */
+  progress?: NodeProgress
+  /** Why a running node looks stuck; cleared when it shows progress again. */
+  stalled?: string
+  /** Output streamed so far while running, per output port. */
+  streamed?: Record<string, string>
 }
 
 /** Edge execution state for data flow visualization */
@@ -50,6 +56,8 @@ interface ExecutionState {
   setAgentHighlightNode: (nodeId: string | null) => void
   addSpan: (span: NodeSpan) => void
   completeExecution: (record: ExecutionRecord) => void
+  /** Mark the current execution paused, or running again. */
+  setPaused: (paused: boolean) => void
   selectNode: (nodeId: string | null) => void
   reset: () => void
 }
@@ -114,6 +122,13 @@ export const useExecutionStore = create<ExecutionState>()((set) => ({
       isRunning: false,
     }),
 
+  setPaused: (paused) =>
+    set((state) => ({
+      currentExecution: state.currentExecution
+        ? { ...state.currentExecution, status: paused ? 'paused' : 'running' }
+        : null,
+    })),
+
   selectNode: (nodeId) =>
     set({ selectedNodeId: nodeId }),
 
@@ -17,12 +17,14 @@ import type {
   LLMResponse,
   ModelInfo,
   ConnectionResult,
+  CredentialHealth,
   EmbeddingRequest,
   EmbeddingResponse,
   NodeModelOverride,
   WorkflowModelConfig,
 } from '@/types'
 import { useTraceStore } from './traceStore'
+import { errorMessage } from '@/types/error'
 
 /** Credential status from backend */
 interface CredentialStatus {
@@ -31,6 +33,8 @@ interface CredentialStatus {
   has_openai: boolean
   has_anthropic: boolean
   local_endpoint: string | null
+  /** Names of the API service keys set (e.g. DATA_GO_KR_SERVICE_KEY) */
+  service_keys: string[]
 }
 
 /** Extended LLM request with node context */
@@ -59,12 +63,16 @@ interface LLMState {
   /** Saved credential status (from backend) */
   credentialStatus: CredentialStatus | null
 
+  /** Latest background credential check results (from backend) */
+  credentialHealth: CredentialHealth[]
+
   /** Provider configurations (persisted) */
   config: {
     bedrock: { region: string }
     openai: {}
     anthropic: {}
     local: { endpoint: string }
+    llamacpp: {}
   }
 
   /** Workflow-level model configuration */
@@ -97,12 +105,16 @@ interface LLMState {
 
   /** API calls */
   loadCredentialStatus: () => Promise<CredentialStatus>
+  loadCredentialHealth: () => Promise<CredentialHealth[]>
+  checkCredentialHealth: () => Promise<CredentialHealth[]>
   setBedrockCredentials: (accessKeyId: string, secretAccessKey: string, region?: string) => Promise<boolean>
   setBedrockRegion: (region: string) => Promise<boolean>
   setAnthropicApiKey: (apiKey: string) => Promise<boolean>
   clearLLMCredentials: () => Promise<boolean>
   setOpenAIApiKey: (apiKey: string) => Promise<boolean>
   setLocalEndpoint: (endpoint: string) => Promise<boolean>
+  setServiceKey: (name: string, key: string) => Promise<boolean>
+  removeServiceKey: (name: string) => Promise<boolean>
   testConnection: (provider: LLMProvider) => Promise<ConnectionResult>
   loadModels: (provider: LLMProvider) => Promise<ModelInfo[]>
   invokeLLM: (request: LLMRequestWithContext) => Promise<LLMResponse>
@@ -119,25 +131,30 @@ export const useLLMStore = create<LLMState>()(
         openai: null,
         anthropic: null,
         local: null,
+        llamacpp: null,
       },
       models: {
         bedrock: [],
         openai: [],
         anthropic: [],
         local: [],
+        llamacpp: [],
       },
       selectedModel: {
         bedrock: 'anthropic.anthropic-3-5-sonnet-20240620-v1:0',
         openai: 'gpt-4o',
         anthropic: 'anthropic-sonnet-4-20250514',
         local: 'llama3.2',
+        llamacpp: '',
       },
       credentialStatus: null,
+      credentialHealth: [],
       config: {
         bedrock: { region: 'us-east-1' },
         openai: {},
         anthropic: {},
         local: { endpoint: 'http://localhost:11434' },
+        llamacpp: {},
       },
       workflowModelConfig: null,
       nodeOverrides: [],
@@ -217,12 +234,12 @@ export const useLLMStore = create<LLMState>()(
       loadCredentialStatus: async () => {
         if (!isTauri()) {
           console.warn('[llmStore] Not in Tauri environment')
-          return { has_bedrock: false, bedrock_region: null, has_openai: false, has_anthropic: false, local_endpoint: null }
+          return { has_bedrock: false, bedrock_region: null, has_openai: false, has_anthropic: false, local_endpoint: null, service_keys: [] }
         }
         try {
           const status = await safeInvoke<CredentialStatus>('get_credential_status')
           if (!status) {
-            return { has_bedrock: false, bedrock_region: null, has_openai: false, has_anthropic: false, local_endpoint: null }
+            return { has_bedrock: false, bedrock_region: null, has_openai: false, has_anthropic: false, local_endpoint: null, service_keys: [] }
           }
           set({ credentialStatus: status })
           // Update local config from saved credentials
@@ -242,7 +259,31 @@ export const useLLMStore = create<LLMState>()(
           return status
         } catch (error) {
           console.error('Failed to load credential status:', error)
-          return { has_bedrock: false, bedrock_region: null, has_openai: false, has_anthropic: false, local_endpoint: null }
+          return { has_bedrock: false, bedrock_region: null, has_openai: false, has_anthropic: false, local_endpoint: null, service_keys: [] }
+        }
+      },
+
+      loadCredentialHealth: async () => {
+        if (!isTauri()) return []
+        try {
+          const health = (await safeInvoke<CredentialHealth[]>('get_credential_health')) ?? []
+          set({ credentialHealth: health })
+          return health
+        } catch (error) {
+          console.error('Failed to load credential health:', errorMessage(error))
+          return get().credentialHealth
+        }
+      },
+
+      checkCredentialHealth: async () => {
+        if (!isTauri()) return []
+        try {
+          const health = (await safeInvoke<CredentialHealth[]>('check_credential_health')) ?? []
+          set({ credentialHealth: health })
+          return health
+        } catch (error) {
+          console.error('Failed to check credential health:', errorMessage(error))
+          return get().credentialHealth
         }
       },
 
@@ -301,6 +342,26 @@ export const useLLMStore = create<LLMState>()(
         }
       },
 
+      setServiceKey: async (name, key) => {
+        if (!isTauri()) return false
+        try {
+          return (await safeInvoke<boolean>('set_service_key', { name, key })) ?? false
+        } catch (error) {
+          console.error(`Failed to set service key ${name}:`, error)
+          return false
+        }
+      },
+
+      removeServiceKey: async (name) => {
+        if (!isTauri()) return false
+        try {
+          return (await safeInvoke<boolean>('remove_service_key', { name })) ?? false
+        } catch (error) {
+          console.error(`Failed to remove service key ${name}:`, error)
+          return false
+        }
+      },
+
       setAnthropicApiKey: async (apiKey) => {
         if (!isTauri()) return false
         try {
@@ -355,7 +416,7 @@ export const useLLMStore = create<LLMState>()(
           const errorResult: ConnectionResult = {
             connected: false,
             provider,
-            error: String(error),
+            error: errorMessage(error),
           }
           set((state) => ({
             connectionStatus: { ...state.connectionStatus, [provider]: errorResult },
@@ -443,7 +504,7 @@ export const useLLMStore = create<LLMState>()(
         } catch (error) {
           // End trace with error
           if (enableTrace && traceId) {
-            traceStore.endLLMTrace(traceId, undefined, String(error))
+            traceStore.endLLMTrace(traceId, undefined, errorMessage(error))
           }
 
           set({ isInvoking: false })
@@ -521,6 +582,16 @@ export const useLLMStore = create<LLMState>()(
         fallbackEnabled: state.fallbackEnabled,
         // Note: nodeOverrides are workflow-specific, not persisted globally
       }),
+      // Keep defaults for providers added after the state was persisted
+      merge: (persisted, current) => {
+        const saved = (persisted ?? {}) as Partial<LLMState>
+        return {
+          ...current,
+          ...saved,
+          selectedModel: { ...current.selectedModel, ...saved.selectedModel },
+          config: { ...current.config, ...saved.config },
+        }
+      },
     }
   )
 )
@@ -18,6 +18,7 @@ import type {
 } from '@/types/mcp'
 import { CURATED_MCP_SERVERS } from '@/types/mcp'
 import { tauriMcpService } from '@/services/TauriMcpService'
+import { errorMessage } from '@/types/error'
 
 interface McpState {
   // Servers
@@ -232,7 +233,7 @@ export const useMcpStore = create<McpState>()(
       } catch (error) {
         set({
           isInstalling: false,
-          installError: error instanceof Error ? error.message : String(error),
+          installError: errorMessage(error),
         })
         return false
       }
@@ -280,7 +281,7 @@ export const useMcpStore = create<McpState>()(
       } catch (error) {
         set({
           isInstalling: false,
-          installError: error instanceof Error ? error.message : String(error),
+          installError: errorMessage(error),
         })
         return false
       }
@@ -326,7 +327,7 @@ export const useMcpStore = create<McpState>()(
         return result
       } catch (e) {
         console.error('Failed to call tool:', e)
-        return { success: false, error: String(e) }
+        return { success: false, error: errorMessage(e) }
       }
     },
 
@@ -4,6 +4,7 @@
 
 import { create } from 'zustand'
 import { safeInvoke } from '@/utils/tauri'
+import type { CredentialHealth } from '@/types/llm'
 
 export interface ProjectSummary {
   id: string
@@ -13,6 +14,46 @@ export interface ProjectSummary {
   updated_at?: string
 }
 
+export type OnboardingDomain =
+  | 'research'
+  | 'data_analysis'
+  | 'software_development'
+  | 'customer_support'
+  | 'translation'
+  | 'general'
+
+export type LlmProviderType =
+  | 'open_ai'
+  | 'anthropic'
+  | 'aws_bedrock'
+  | 'google_vertex'
+  | 'ollama'
+  | 'custom'
+
+/** Answers to the setup wizard's questions. */
+export interface OnboardingAnswers {
+  name: string
+  domain: OnboardingDomain
+  /** Directory, file, URL or connection string of the user's data */
+  data_location?: string
+  llm_provider: LlmProviderType
+  model?: string
+  root_path?: string
+}
+
+/** Workspace, starter workflows and credential check planned (or created) by onboarding. */
+export interface OnboardingResult {
+  workspace: ProjectSummary & {
+    root_path: string
+    data_sources: unknown[]
+    default_policy: unknown
+    llm_providers: unknown[]
+  }
+  workflows: { id: string; meta: { name: string; description: string } }[]
+  warnings: string[]
+  credential: CredentialHealth | null
+}
+
 interface ProjectState {
   /** Currently active project (null = global/no project). */
   activeProject: ProjectSummary | null
@@ -26,6 +67,8 @@ interface ProjectState {
   /** Actions */
   fetchProjects: () => Promise<void>
   createProject: (name: string, description?: string) => Promise<ProjectSummary | null>
+  previewOnboarding: (answers: OnboardingAnswers) => Promise<OnboardingResult | null>
+  completeOnboarding: (answers: OnboardingAnswers) => Promise<OnboardingResult | null>
   deleteProject: (id: string) => Promise<void>
   setActiveProject: (project: ProjectSummary | null) => void
   clearActiveProject: () => void
@@ -77,6 +120,31 @@ export const useProjectStore = create<ProjectState>()((set, get) => ({
     return null
   },
 
+  previewOnboarding: async (answers) => {
+    try {
+      return await safeInvoke<OnboardingResult>('preview_onboarding', { answers })
+    } catch (e) {
+      console.error('[projectStore] Failed to preview onboarding:', e)
+    }
+    return null
+  },
+
+  completeOnboarding: async (answers) => {
+    try {
+      const result = await safeInvoke<OnboardingResult>('complete_onboarding', { answers })
+      if (result) {
+        const { id, name, description, created_at, updated_at } = result.workspace
+        const project = { id, name, description, created_at, updated_at }
+        set(s => ({ projects: [project, ...s.projects] }))
+        get().setActiveProject(project)
+        return result
+      }
+    } catch (e) {
+      console.error('[projectStore] Failed to complete onboarding:', e)
+    }
+    return null
+  },
+
   deleteProject: async (id) => {
     try {
       await safeInvoke('delete_project', { id })
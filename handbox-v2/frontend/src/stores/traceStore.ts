@@ -368,6 +368,7 @@ export const useTraceStore = create<TraceState>()((set, get) => ({
       openai: { invocations: 0, tokens: { inputTokens: 0, outputTokens: 0 }, avgLatencyMs: 0 },
       anthropic: { invocations: 0, tokens: { inputTokens: 0, outputTokens: 0 }, avgLatencyMs: 0 },
       local: { invocations: 0, tokens: { inputTokens: 0, outputTokens: 0 }, avgLatencyMs: 0 },
+      llamacpp: { invocations: 0, tokens: { inputTokens: 0, outputTokens: 0 }, avgLatencyMs: 0 },
     }
 
     let totalTokens: TokenUsage = { inputTokens: 0, outputTokens: 0 }
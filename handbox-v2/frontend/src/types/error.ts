@@ -0,0 +1,42 @@
+/**
+ * Error types — TypeScript mirror of Rust hb-core::error types.
+ * Source of truth: crates/hb-core/src/error.rs
+ */
+
+export type ErrorCode =
+  | 'not_found'
+  | 'invalid_input'
+  | 'missing_credential'
+  | 'permission_denied'
+  | 'conflict'
+  | 'database'
+  | 'execution'
+  | 'policy_violation'
+  | 'timeout'
+  | 'cancelled'
+  | 'network'
+  | 'internal'
+
+/** Payload every Tauri command rejects with. */
+export interface AppError {
+  code: ErrorCode
+  message: string
+  details?: unknown
+  node_id?: string
+  hint?: string
+}
+
+export const isAppError = (value: unknown): value is AppError =>
+  typeof value === 'object' &&
+  value !== null &&
+  typeof (value as AppError).code === 'string' &&
+  typeof (value as AppError).message === 'string'
+
+/** Human-readable message for anything a command or promise rejected with. */
+export const errorMessage = (error: unknown): string => {
+  if (isAppError(error)) {
+    return error.hint ? `${error.message} — ${error.hint}` : error.message
+  }
+  if (error instanceof Error) return error.message
+  return String(error)
+}
@@ -15,6 +15,10 @@ export interface WorkflowSpec {
   nodes: NodeEntry[]
   edges: EdgeSpec[]
   required_packs: PackDependency[]
+  /** Test cases run before publishing */
+  tests?: WorkflowTest[]
+  /** Editor boxes grouping nodes */
+  groups?: NodeGroup[]
 }
 
 export interface WorkflowMeta {
@@ -24,6 +28,21 @@ export interface WorkflowMeta {
   tags: string[]
   created_at: string
   updated_at: string
+  /** Outputs the workflow must produce, checked when a run completes. */
+  output_contract?: OutputAssertion[]
+  /** Reuse outputs of unchanged chains of pure tools from earlier runs. */
+  memoize?: boolean
+}
+
+/** An expected output of a node port. */
+export interface OutputAssertion {
+  node_id: string
+  port: string
+  port_type?: PortType
+  /** Reject null, empty strings, arrays and objects. */
+  non_empty?: boolean
+  /** JSON Schema the value must match. */
+  schema?: Record<string, unknown>
 }
 
 export interface VariableSpec {
@@ -32,6 +51,29 @@ export interface VariableSpec {
   var_type: PortType
   default_value?: unknown
   required: boolean
+  /** Display label in parameter forms; defaults to `name`. */
+  label?: string
+  /** Form section this variable is shown in. */
+  group?: string
+  /** Allowed values; when non-empty the form renders a select. */
+  options?: { label: string; value: unknown }[]
+  constraints?: VariableConstraints
+}
+
+export interface VariableConstraints {
+  minimum?: number
+  maximum?: number
+  /** Characters for strings, items for arrays. */
+  min_length?: number
+  max_length?: number
+}
+
+/** Result of `validate_workflow_parameters`. */
+export interface ParameterValidation {
+  valid: boolean
+  /** Submitted values with defaults filled in (empty when invalid). */
+  values: Record<string, unknown>
+  errors: { name: string; message: string }[]
 }
 
 // ---------------------------------------------------------------------------
@@ -43,8 +85,10 @@ export type NodeEntry =
   | CompositeNodeEntry
   | ConditionalNodeEntry
   | LoopNodeEntry
+  | MapReduceNodeEntry
+  | WorkflowRefNodeEntry
 
-export interface PrimitiveNodeEntry {
+export interface PrimitiveNodeEntry extends Presentation {
   kind: 'primitive'
   id: string
   tool_ref: string
@@ -54,9 +98,15 @@ export interface PrimitiveNodeEntry {
   disabled: boolean
   retry?: RetryPolicy
   cache?: CachePolicy
+  /** How several edges into one input port combine (default: merge_as_array). */
+  fan_in?: FanIn
+  /** Resource class whose concurrency limit the node counts against (default: the tool's). */
+  resource_class?: string | null
 }
 
-export interface CompositeNodeEntry {
+export type FanIn = 'merge_as_array' | 'wait_for_all' | 'first_wins'
+
+export interface CompositeNodeEntry extends Presentation {
   kind: 'composite'
   id: string
   subgraph: SubgraphSpec
@@ -64,10 +114,21 @@ export interface CompositeNodeEntry {
   output_ports: PortSpec[]
   input_mapping: PortMapping[]
   output_mapping: PortMapping[]
+  exposed_config?: ExposedConfigField[]
+  config?: Record<string, unknown>
   position?: Position
   label?: string
 }
 
+/** Composite-level config field backed by internal node config keys. */
+export interface ExposedConfigField {
+  name: string
+  label?: string
+  description?: string
+  default_value?: unknown
+  targets: { node: string; key: string }[]
+}
+
 export interface ConditionalNodeEntry {
   kind: 'conditional'
   id: string
@@ -85,6 +146,59 @@ export interface LoopNodeEntry {
   max_iterations: number
   condition_expr?: string
   items_expr?: string
+  /** Path selecting the value collected from each iteration's output. */
+  collect_expr?: string
+  aggregation?: LoopAggregation
+}
+
+/** How per-iteration values combine into a loop's `aggregate` output. */
+export type LoopAggregation =
+  | { mode: 'array' }
+  | { mode: 'concat'; separator?: string }
+  | { mode: 'reduce'; expr: string; initial?: unknown }
+
+/**
+ * Shards `items` across parallel `map` executions (at most
+ * `max_concurrency` at once), then runs `reduce` over `{ results }`.
+ * Outputs `{ results, shards, reduced }`.
+ */
+export interface MapReduceNodeEntry {
+  kind: 'map_reduce'
+  id: string
+  items_expr?: string
+  shard_size?: number
+  max_concurrency?: number
+  map: SubgraphSpec
+  collect_expr?: string
+  reduce?: SubgraphSpec
+}
+
+/**
+ * Runs another saved workflow by ID. Without `output_mapping` the output
+ * is keyed by inner node ID. Inner nodes report status as `{id}/{inner id}`.
+ */
+export interface WorkflowRefNodeEntry {
+  kind: 'workflow_ref'
+  id: string
+  workflow_id: string
+  input_ports?: PortSpec[]
+  output_ports?: PortSpec[]
+  input_mapping?: PortMapping[]
+  output_mapping?: PortMapping[]
+  position?: Position
+  label?: string
+}
+
+/**
+ * Reserved input ports of every node inside a loop body.
+ * `total` is null for `while` loops.
+ */
+export interface IterationContext {
+  item: unknown
+  index: number
+  total: number | null
+  is_first: boolean
+  is_last: boolean
 }
 
 // ---------------------------------------------------------------------------
@@ -122,6 +236,10 @@ export interface PortSpec {
   description?: string
   required: boolean
   default_value?: unknown
+  /** Accepts any number of edges addressed as `${name}.${key}`. */
+  variadic?: boolean
+  /** Input whose value, and so type, this output passes on. */
+  same_type_as?: string
 }
 
 export type PortType =
@@ -148,6 +266,28 @@ export interface Position {
   y: number
 }
 
+// ---------------------------------------------------------------------------
+// Presentation (editor-only; ignored by the runner)
+// ---------------------------------------------------------------------------
+
+export type ColorTag = 'gray' | 'red' | 'orange' | 'yellow' | 'green' | 'teal' | 'blue' | 'purple' | 'pink'
+
+/** Note, color tag and group of a node or group. */
+export interface Presentation {
+  note?: string
+  color?: ColorTag
+  /** ID of the enclosing NodeGroup. */
+  group?: string
+}
+
+export interface NodeGroup extends Presentation {
+  id: string
+  label?: string
+  /** Top-left corner. */
+  position?: Position
+  size?: { width: number; height: number }
+}
+
 export interface RetryPolicy {
   max_retries: number
   backoff_ms: number
@@ -170,3 +310,245 @@ export interface PackDependency {
   pack_id: string
   version_range: string
 }
+
+/** Runs the workflow as is; outputs must contain the expected values */
+export interface WorkflowTest {
+  name: string
+  /** Expected output per node ID */
+  expected_outputs?: Record<string, unknown>
+}
+
+// ---------------------------------------------------------------------------
+// Paging (large workflows)
+// ---------------------------------------------------------------------------
+
+/** A workflow without its nodes and edges. */
+export interface SpecOutline {
+  version: '0.1.0'
+  id: string
+  meta: WorkflowMeta
+  variables: VariableSpec[]
+  required_packs: PackDependency[]
+  tests: WorkflowTest[]
+  groups: NodeGroup[]
+  node_count: number
+  edge_count: number
+}
+
+/** Nodes and edges starting at `offset`. */
+export interface SpecPage {
+  offset: number
+  nodes: NodeEntry[]
+  edges: EdgeSpec[]
+  /** Paths (composite IDs joined with '/') of composites sent without their subgraph. */
+  deferred: string[]
+}
+
+// ---------------------------------------------------------------------------
+// Fragments (copy/paste between workflows)
+// ---------------------------------------------------------------------------
+
+/** What a fragment's nodes depend on. */
+export interface FragmentRequirements {
+  tools: string[]
+  workflows: string[]
+  /** `{{env:NAME}}` variables. */
+  env: string[]
+}
+
+/** Result of pasting a fragment into a workflow. */
+export interface PastedFragment {
+  /** Fragment node ID → new ID, for renamed nodes only. */
+  id_map: Record<string, string>
+  nodes: string[]
+  /** Edges that crossed the copied selection, with pasted ends renamed. */
+  detached: EdgeSpec[]
+  requires: FragmentRequirements
+}
+
+export interface FragmentPasteReport {
+  spec: WorkflowSpec
+  pasted: PastedFragment
+  missing_tools: string[]
+  missing_workflows: string[]
+  missing_env: string[]
+}
+
+// ---------------------------------------------------------------------------
+// Edit journal (granular edits with undo/redo)
+// ---------------------------------------------------------------------------
+
+export type EditOp =
+  | { op: 'add_node'; node: NodeEntry; index?: number }
+  | { op: 'remove_node'; node_id: string }
+  | { op: 'move_node'; node_id: string; position: Position | null }
+  | { op: 'connect'; edge: EdgeSpec }
+  | { op: 'disconnect'; edge_id: string }
+  | { op: 'set_config'; node_id: string; key: string; value: unknown | null }
+  | { op: 'set_label'; node_id: string; label: string | null }
+  | { op: 'batch'; ops: EditOp[] }
+
+export interface JournalEntry {
+  workflow_id: string
+  seq: number
+  op: EditOp
+  inverse: EditOp
+  actor: string | null
+  created_at: string
+  undone: boolean
+}
+
+export interface EditResult {
+  spec: WorkflowSpec
+  entry: JournalEntry | null
+  undo: { can_undo: boolean; can_redo: boolean }
+}
+
+// ---------------------------------------------------------------------------
+// Live diagnostics ("workflow-diagnostics" event)
+// ---------------------------------------------------------------------------
+
+export interface Diagnostic {
+  severity: 'warning' | 'error'
+  code: string
+  message: string
+  node_id: string
+  edge_id?: string
+}
+
+export interface DiagnosticsEvent {
+  workflow_id: string
+  /** True when all diagnostics are replaced; otherwise only `revalidated` nodes changed. */
+  full: boolean
+  revalidated: string[]
+  diagnostics: Diagnostic[]
+  error_count: number
+  warning_count: number
+}
+
+/** Structural problem found by `lint_workflow`; sub-graph nodes by path (`outer/inner`). */
+export interface SpecIssue {
+  severity: 'warning' | 'error'
+  code: string
+  message: string
+  node_id?: string
+  edge_id?: string
+  variable?: string
+}
+
+// ---------------------------------------------------------------------------
+// Connection suggestions (`suggest_connections`)
+// ---------------------------------------------------------------------------
+
+export interface PortSuggestion {
+  node_id: string
+  /** For variadic inputs, the next free key (e.g. `inputs.2`). */
+  port: string
+  port_type: PortType
+  score: number
+  reasons: string[]
+}
+
+export interface ToolSuggestion {
+  tool_id: string
+  display_name: string
+  port: string
+  port_type: PortType
+  score: number
+  reasons: string[]
+}
+
+export interface ConnectionSuggestions {
+  source_node: string
+  source_port: string
+  source_type: PortType
+  ports: PortSuggestion[]
+  tools: ToolSuggestion[]
+}
+
+// ---------------------------------------------------------------------------
+// Optimization hints (`analyze_workflow_performance`)
+// ---------------------------------------------------------------------------
+
+export interface NodeTiming {
+  runs: number
+  cache_hits: number
+  mean_ms: number | null
+}
+
+export type OptimizationHintKind =
+  | { kind: 'parallel_chains'; chains: string[][]; estimated_saving_ms: number }
+  | { kind: 'critical_path'; mean_ms: number; share: number }
+  | { kind: 'cache_never_hits'; runs: number }
+  | {
+      kind: 'rate_limited_fan_out'
+      tool_id: string
+      concurrent: number
+      requests_per_minute: number | null
+      limit: number
+      suggested_concurrency: number
+    }
+
+export type OptimizationHint = OptimizationHintKind & {
+  node_ids: string[]
+  message: string
+}
+
+export interface OptimizationReport {
+  executions: number
+  timings: Record<string, NodeTiming>
+  estimated_ms: number
+  critical_path: string[]
+  critical_path_ms: number
+  nodes_without_history: string[]
+  hints: OptimizationHint[]
+}
+
+// ---------------------------------------------------------------------------
+// Node output cache (`get_cache_stats`)
+// ---------------------------------------------------------------------------
+
+export interface CacheStats {
+  memory_hits: number
+  persistent_hits: number
+  misses: number
+  stores: number
+  rejected_large: number
+  rejected_cheap: number
+  evictions: number
+  memory_entries: number
+  memory_bytes: number
+  memory_budget_bytes: number
+  hit_rate: number
+}
+
+// ---------------------------------------------------------------------------
+// Cost estimate (`estimate_workflow_cost`)
+// ---------------------------------------------------------------------------
+
+export interface EstimateRange {
+  min: number
+  max: number
+}
+
+export interface NodeCostEstimate {
+  node_id: string
+  tool_id: string
+  runs: EstimateRange
+  /** One run. */
+  duration_ms: EstimateRange
+  /** All runs. */
+  tokens: EstimateRange
+  cost_usd: EstimateRange
+  from_history: boolean
+}
+
+export interface WorkflowCostEstimate {
+  cost_usd: EstimateRange
+  duration_ms: EstimateRange
+  tokens: EstimateRange
+  nodes: NodeCostEstimate[]
+  notes: string[]
+  budget_warnings: string[]
+  budget_error: string | null
+}
@@ -12,6 +12,7 @@ export type * from './comparison'
 export type * from './workflow'
 export type * from './agent'
 export type * from './marketplace'
+export type * from './error'
 
 // Domain-specific types
 export type * from './gis'
@@ -20,3 +21,4 @@ export type * from './mcp'
 
 // Re-export utility functions
 export { calculateCost, MODEL_COSTS } from './comparison'
+export { isAppError, errorMessage } from './error'
@@ -2,7 +2,7 @@
  * LLM Provider types for Handbox v2
  */
 
-export type LLMProvider = 'bedrock' | 'openai' | 'anthropic' | 'local'
+export type LLMProvider = 'bedrock' | 'openai' | 'anthropic' | 'local' | 'llamacpp'
 
 export interface LLMConfig {
   provider: LLMProvider
@@ -62,3 +62,72 @@ export interface ConnectionResult {
   region?: string
   error?: string
 }
+
+export type CredentialHealthState = 'valid' | 'rejected' | 'expired' | 'unreachable'
+
+/** Result of the background credential check for one provider */
+export interface CredentialHealth {
+  provider: LLMProvider
+  state: CredentialHealthState
+  message: string | null
+  checked_at: string
+  /** When the provider entered its current state */
+  since: string
+  latency_ms: number
+}
+
+// ========== Local GGUF models (llama.cpp provider) ==========
+
+export interface GgufInfo {
+  version: number
+  tensor_count: number
+  architecture: string | null
+  name: string | null
+  /** Quantization, e.g. "Q4_K_M" */
+  file_type: string | null
+  context_length: number | null
+  block_count: number | null
+  embedding_length: number | null
+  head_count: number | null
+  head_count_kv: number | null
+}
+
+export type MemoryFit = 'gpu' | 'cpu' | 'insufficient' | 'unknown'
+
+export interface MemoryCheck {
+  weights_bytes: number
+  kv_cache_bytes: number
+  required_bytes: number
+  fit: MemoryFit
+  message: string
+}
+
+export interface LocalModel {
+  /** File name, used as the model id */
+  id: string
+  path: string
+  size_bytes: number
+  source_url: string | null
+  sha256: string | null
+  info: GgufInfo | null
+  /** Why the header could not be read */
+  error: string | null
+  memory: MemoryCheck
+}
+
+export interface SystemMemory {
+  total_ram_bytes: number | null
+  available_ram_bytes: number | null
+  gpus: { name: string; total_bytes: number; free_bytes: number }[]
+  unified_memory: boolean
+}
+
+export interface VerifyReport {
+  id: string
+  sha256: string
+  expected_sha256: string | null
+  checksum_ok: boolean | null
+  header_ok: boolean
+  valid: boolean
+  error: string | null
+}
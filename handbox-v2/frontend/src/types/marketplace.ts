@@ -2,6 +2,8 @@
  * Marketplace types — Workflow sharing and collaboration types.
  */
 
+import type { WorkflowSpec } from './graph'
+
 /** Published workflow in marketplace */
 export interface MarketplaceWorkflow {
   id: string
@@ -132,6 +134,33 @@ export interface WorkflowCollection {
   updatedAt: string
 }
 
+/** Step of the publish pipeline */
+export type PublishCheckKind = 'lint' | 'validation' | 'secrets' | 'licenses' | 'tests'
+
+/** Finding of a publish check; errors block publishing */
+export interface PublishFinding {
+  severity: 'warning' | 'error'
+  code: string
+  message: string
+  nodeId?: string
+}
+
+/** Outcome of one publish check */
+export interface PublishCheckResult {
+  check: PublishCheckKind
+  status: 'passed' | 'warning' | 'failed' | 'skipped'
+  findings: PublishFinding[]
+  skippedReason?: string
+}
+
+/** Report of the publish pipeline */
+export interface PublishReport {
+  passed: boolean
+  checks: PublishCheckResult[]
+  errorCount: number
+  warningCount: number
+}
+
 // ========== Collaboration Types ==========
 
 /** Collaborator in a session */
@@ -166,6 +195,18 @@ export interface CollaborationSession {
   createdAt: string
   isActive: boolean
   settings: SessionSettings
+  /** Shared executions currently running */
+  executions?: SharedExecution[]
+}
+
+/** An execution started from a session and broadcast to its participants */
+export interface SharedExecution {
+  executionId: string
+  workflowId: string
+  startedBy: string
+  startedAt: string
+  /** Participants online when the execution started */
+  watchers: string[]
 }
 
 /** Session settings */
@@ -176,6 +217,8 @@ export interface SessionSettings {
   maxCollaborators: number
   autoSave: boolean
   autoSaveInterval: number  // ms
+  /** Include node outputs in shared execution events */
+  shareOutputs?: boolean
 }
 
 /** Collaboration event types */
@@ -190,8 +233,11 @@ export type CollaborationEventType =
   | 'edge_added'
   | 'edge_removed'
   | 'execution_started'
+  | 'execution_status'
   | 'execution_completed'
   | 'chat_message'
+  | 'edit_op'
+  | 'workflow_snapshot'
 
 /** Collaboration event */
 export interface CollaborationEvent {
@@ -203,6 +249,28 @@ export interface CollaborationEvent {
   payload: unknown
 }
 
+/** A recorded collaboration session */
+export interface SessionRecordingSummary {
+  sessionId: string
+  workflowId: string
+  name: string
+  startedAt: string
+  lastEventAt: string | null
+  eventCount: number
+}
+
+/** Workflow state reconstructed at a point in a recorded session */
+export interface PlaybackFrame {
+  sessionId: string
+  /** Number of events replayed */
+  position: number
+  totalEvents: number
+  event: CollaborationEvent | null
+  workflow: WorkflowSpec | null
+  applied: number
+  errors: { eventId: string; message: string }[]
+}
+
 /** Chat message in collaboration */
 export interface ChatMessage {
   id: string
@@ -3,6 +3,8 @@
  * Source of truth: crates/hb-core/src/pack/mod.rs
  */
 
+import type { CompositeNodeEntry } from './graph'
+
 export interface PackManifest {
   pack_version: '0.1.0'
   id: string
@@ -17,6 +19,9 @@ export interface PackManifest {
   tools: string[]
   templates: string[]
   composites: string[]
+  compile_templates: string[]
+  prompt_libraries: string[]
+  eval_suites: string[]
   runtime_requirements?: RuntimeRequirements
 }
 
@@ -43,3 +48,33 @@ export interface RuntimeRequirements {
   native_deps: string[]
   os: string[]
 }
+
+export type PackContentKind =
+  | 'tool'
+  | 'workflow_template'
+  | 'composite'
+  | 'compile_template'
+  | 'prompt_library'
+  | 'eval_suite'
+
+/** A composite, compile template, prompt library or eval suite installed from a pack. */
+export interface InstalledPackContent {
+  kind: PackContentKind
+  pack_id: string
+  version: string
+  id: string
+  name: string
+  description: string
+  path: string
+}
+
+/** A ready-made composite node shipped by a pack. */
+export interface CompositeDefinition {
+  id: string
+  name: string
+  description: string
+  /** The composite's own version; changes when its behavior does. */
+  version: string
+  tags: string[]
+  composite: Omit<CompositeNodeEntry, 'kind'>
+}
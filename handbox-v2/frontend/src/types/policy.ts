@@ -20,8 +20,50 @@ export interface ToolWhitelist {
   blocked_tools: string[]
 }
 
+export interface EdgeCapturePolicy {
+  enabled: boolean
+  /** Values larger than this are stored as a truncated preview. */
+  max_value_bytes?: number
+  /** If non-empty, only these edge IDs are captured. */
+  edges: string[]
+}
+
+export interface CommandPolicy {
+  /** Programs shell-exec may start without asking: bare names or full paths. */
+  allowed_programs: string[]
+}
+
+export interface LivenessPolicy {
+  /** Seconds without a heartbeat before a running node is reported as stalled; 0 never warns. */
+  warn_after_secs: number
+  /** Seconds without a heartbeat before the node is killed and retried per its retry policy. */
+  kill_after_secs?: number | null
+}
+
+export interface ConcurrencyPolicy {
+  /** Tool nodes of one execution running at once; unset or 0 for no limit. */
+  max_parallel?: number | null
+  /** Limit per resource class, e.g. { llm: 4, docker: 2, cpu: 4 }; 0 for no limit. */
+  classes: Record<string, number>
+}
+
+/** What a shared run (viewer bundle) keeps; known secrets are always redacted. */
+export interface SharePolicy {
+  /** Keep node input and output values; otherwise only statuses, timings and errors. */
+  include_values: boolean
+  /** Keep node and workflow config. */
+  include_config: boolean
+  /** Object keys whose values are redacted, on top of the built-in secret keys. */
+  redact_keys: string[]
+}
+
 export interface Policy {
   permissions: PermissionSet
   cost_limit: CostLimit
   tool_whitelist: ToolWhitelist
+  edge_capture?: EdgeCapturePolicy
+  commands?: CommandPolicy
+  liveness?: LivenessPolicy
+  concurrency?: ConcurrencyPolicy
+  sharing?: SharePolicy
 }
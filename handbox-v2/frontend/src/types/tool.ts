@@ -10,6 +10,10 @@ export interface ToolInterface {
   version: string
   display_name: string
   description: string
+  /** Palette category (derived from the first capability tag when absent). */
+  category?: string | null
+  /** Lucide icon name shown in the palette. */
+  icon?: string | null
   capability_tags: string[]
   input_schema: PortSchema
   output_schema: PortSchema
@@ -19,6 +23,8 @@ export interface ToolInterface {
   error_model: ErrorModel
   runtime: RuntimeSpec
   config_schema: ConfigField[]
+  /** Shared resources the tool reads or writes; concurrent executions are serialized on them. */
+  resources?: ResourceDecl[]
 }
 
 export interface PortSchema {
@@ -27,6 +33,20 @@ export interface PortSchema {
 
 export type SideEffect = 'none' | 'read' | 'write' | 'network' | 'process'
 
+export type ResourceAccess = 'read' | 'write'
+
+/** URI template such as `index://{config.index_name}` or `file://{input.path}`. */
+export interface ResourceDecl {
+  uri: string
+  access?: ResourceAccess
+}
+
+export interface HeldLock {
+  uri: string
+  access: ResourceAccess
+  executions: string[]
+}
+
 export interface CostHint {
   time: TimeHint
   monetary: MonetaryHint
@@ -56,7 +76,13 @@ export interface ErrorType {
 
 export type RuntimeSpec =
   | { kind: 'native' }
-  | { kind: 'process'; command: string }
+  | {
+      kind: 'process'
+      command: string
+      args?: string[]
+      env?: Record<string, string | { secret: string }>
+      working_dir?: string
+    }
   | { kind: 'python'; script: string }
   | { kind: 'docker'; image: string }
   | { kind: 'wasm'; module: string }
@@ -70,6 +96,14 @@ export interface ConfigField {
   default_value?: unknown
   required: boolean
   options: ConfigOption[]
+  /** Live list the options are filled from when the config form is built. */
+  options_source?: OptionsSource
+  /** Expression over the node's config that must hold for the field to apply. */
+  visible_when?: string
+  min?: number
+  max?: number
+  /** Regular expression a string value must match. */
+  pattern?: string
 }
 
 export type ConfigFieldType =
@@ -80,12 +114,32 @@ export type ConfigFieldType =
   | 'multi_select'
   | 'file_path'
   | 'json'
+  | 'regex'
+  | 'expression'
 
 export interface ConfigOption {
   label: string
   value: unknown
 }
 
+export type OptionsSource = 'llm_models' | 'local_models' | 'service_keys'
+
+/** A config field as the property panel shows it for one node. */
+export interface FormField extends ConfigField {
+  /** Whether `visible_when` holds for the node's config. */
+  visible: boolean
+  /** What is wrong with the node's value, if anything. */
+  error?: string | null
+}
+
+/** The property panel form for a node, generated from its tool's config schema. */
+export interface ConfigForm {
+  tool_id: string
+  version: string
+  label: string
+  fields: FormField[]
+}
+
 // ============================================================================
 // Simplified ToolDefinition for UI catalogs
 // ============================================================================
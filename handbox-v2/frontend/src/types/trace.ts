@@ -20,14 +20,35 @@ export interface NodeSpan {
   environment: ExecutionEnvironment
 }
 
+/** Progress reported by a long-running tool (mirror of hb-tool-executor::Progress). */
+export interface NodeProgress {
+  /** Completed share, 0–1; null while indeterminate. */
+  fraction: number | null
+  current: number | null
+  total: number | null
+  step: string | null
+}
+
+/** A piece of a node's output streamed before it finishes. */
+export interface OutputChunk {
+  /** Output port, e.g. "response" or "stdout". */
+  port: string
+  text: string
+  /** Position among the node's chunks, from 0. */
+  index: number
+}
+
 export type ExecutionStatus =
   | 'pending'
+  | 'paused'
   | 'running'
   | 'completed'
   | 'failed'
   | 'skipped'
   | 'cache_hit'
   | 'cancelled'
+  | 'interrupted'
+  | 'completed_with_violations'
 
 export interface ExecutionEnvironment {
   platform_version: string
@@ -46,4 +67,176 @@ export interface ExecutionRecord {
   completed_nodes: number
   failed_nodes: number
   cache_hits: number
+  /** What the execution produced or touched outside the workflow. */
+  manifest: ExecutionManifest
+  /** Output contract assertions the completed run failed. */
+  contract_violations?: ContractViolation[]
+}
+
+/** A broken output contract assertion. */
+export interface ContractViolation {
+  node_id: string
+  port: string
+  message: string
+}
+
+/** How the scheduler spent an execution. */
+export interface SchedulerMetrics {
+  execution_id: string
+  wall_ms: number
+  samples: QueueSample[]
+  nodes: NodeTiming[]
+  peak_running: number
+  average_parallelism: number
+  capacity: number
+  /** average_parallelism as a share of capacity, 0–1. */
+  utilization: number
+  /** Loop nodes at any depth, in the order they finished. */
+  loops?: LoopTiming[]
+}
+
+/** Ready-queue depth and running nodes at_ms after the run started. */
+export interface QueueSample {
+  at_ms: number
+  ready: number
+  running: number
+}
+
+export interface NodeTiming {
+  node_id: string
+  level: number
+  queued_ms: number
+  run_ms: number
+}
+
+/** Iterations one run of a loop node made. */
+export interface LoopTiming {
+  node_id: string
+  iterations: number
+  max_iterations: number
+  run_ms: number
+}
+
+export type ArtifactKind = 'file_written' | 'index_updated' | 'external_call'
+
+/** A file, index or endpoint, with the nodes that touched it. */
+export interface ArtifactEntry {
+  kind: ArtifactKind
+  /** File path, index name or endpoint URI. */
+  target: string
+  node_ids: string[]
+  count: number
+}
+
+export interface ExecutionManifest {
+  artifacts: ArtifactEntry[]
+}
+
+/** A value that crossed an edge during an execution. */
+export interface EdgeValue {
+  execution_id: string
+  edge_id: string
+  source_node: string
+  source_port: string
+  target_node: string
+  target_port: string
+  /** Delivered value, or a string preview when `truncated`. */
+  value: unknown
+  truncated: boolean
+  size_bytes: number
+  recorded_at: string
+}
+
+/** A value that differs between execution A and execution B. */
+export interface Changed<T> {
+  a: T
+  b: T
+}
+
+/** Where two JSON values differ; a missing side means the path doesn't exist there. */
+export interface ValueChange {
+  /** JSON Pointer to the differing value ("" for the whole value). */
+  path: string
+  a?: unknown
+  b?: unknown
+}
+
+export interface ValueDiff {
+  changes: ValueChange[]
+  /** Differences not listed. */
+  omitted: number
+}
+
+/** How a node ran differently in two executions; `run` counts repeated runs from 0. */
+export interface NodeDiff {
+  node_id: string
+  run: number
+  ran: Changed<boolean>
+  status?: Changed<ExecutionStatus>
+  tool_ref?: Changed<string>
+  config?: ValueDiff
+  inputs?: ValueDiff
+  outputs?: ValueDiff
+  error?: Changed<string | null>
+  cache_hit?: Changed<boolean>
+  duration_ms?: Changed<number | null>
+  duration_delta_ms?: number
+}
+
+/** What differs between two executions of a workflow. */
+export interface ExecutionDiff {
+  workflow_id: string
+  execution_a: string
+  execution_b: string
+  status?: Changed<ExecutionStatus>
+  duration_delta_ms?: number
+  nodes: NodeDiff[]
+  /** Node runs identical in both apart from timing. */
+  unchanged: number
+}
+
+/** Summary at the top of a shared run viewer. */
+export interface RunReport {
+  workflow_name: string
+  status: ExecutionStatus
+  started_at: string
+  completed_at: string | null
+  duration_ms: number | null
+  total_nodes: number
+  completed_nodes: number
+  failed_nodes: number
+  cache_hits: number
+  failures: { node_id: string; error: string }[]
+  slowest: { node_id: string; duration_ms: number }[]
+  contract_violations: ContractViolation[]
+}
+
+/** A finished execution packaged for read-only viewing, already redacted. */
+export interface ViewerBundle {
+  viewer_version: number
+  app_version: string
+  created_at: string
+  execution_id: string
+  report: RunReport
+  workflow: unknown | null
+  record: ExecutionRecord
+  spans: unknown[]
+  /** The workflow graph as SVG. */
+  graph_svg: string | null
+  /** Whether node inputs and outputs were kept. */
+  values_included: boolean
+  redactions: number
+}
+
+export interface TraceExport {
+  path: string
+  /** Spans written to the file. */
+  spans: number
+}
+
+export interface RunViewerExport {
+  /** The self-contained HTML page. */
+  path: string
+  redactions: number
+  values_included: boolean
 }